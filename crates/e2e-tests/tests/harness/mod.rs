@@ -16,6 +16,7 @@ use sqlx::PgPool;
 use tokio::task::JoinHandle;
 
 use tokf::auth::credentials::LoadedAuth;
+use tokf::remote::async_http::AsyncClient;
 use tokf::remote::client::{MachineInfo, RegisteredMachine};
 use tokf::remote::filter_client::{self, DownloadedFilter, FilterDetails, FilterSummary};
 use tokf::remote::gain_client::{GainResponse, GlobalGainResponse};
@@ -285,7 +286,7 @@ impl TestHarness {
         machine_id: &str,
     ) -> SyncRequest {
         let last_id = tracking::get_last_synced_id(conn).unwrap();
-        let events = tracking::get_events_since(conn, last_id).unwrap();
+        let events = tracking::get_events_since(conn, last_id, 500).unwrap();
         let sync_events: Vec<SyncEvent> = events
             .iter()
             .map(|e| SyncEvent {
@@ -306,14 +307,25 @@ impl TestHarness {
         }
     }
 
-    // ── Blocking helpers (wrap spawn_blocking boilerplate) ───────
+    // ── Clients ─────────────────────────────────────────────────
     //
-    // IMPORTANT: `reqwest::blocking::Client` creates an internal Tokio runtime.
-    // It must NOT be constructed or dropped in an async context — otherwise the
-    // runtime's `Drop` panics ("Cannot drop a runtime in a context where
-    // blocking is not allowed"). The three `run_blocking*` helpers below
-    // ensure `Client::new()` is always called inside a `spawn_blocking`
-    // closure.
+    // Sync, publish and download go through the async client and are awaited
+    // in place. The remaining helpers still call blocking wrappers, and
+    // `tokf::remote::http::Client` owns a Tokio runtime: it must NOT be
+    // constructed or dropped in an async context — otherwise the runtime's
+    // `Drop` panics ("Cannot drop a runtime in a context where blocking is
+    // not allowed"). The three `run_blocking*` helpers below ensure
+    // `Client::new()` is always called inside a `spawn_blocking` closure.
+
+    /// An async client authenticated as the harness user.
+    pub fn async_client(&self) -> AsyncClient {
+        self.async_client_with_token(&self.token)
+    }
+
+    /// An async client authenticated with `token`.
+    pub fn async_client_with_token(&self, token: &str) -> AsyncClient {
+        AsyncClient::new(&self.rt, &self.base_url, Some(token)).unwrap()
+    }
 
     /// Run `f` on a blocking thread with an authenticated `Client`.
     async fn run_blocking<T, F>(&self, f: F) -> T
@@ -367,9 +379,9 @@ impl TestHarness {
 
     /// Sync a pre-built request to the remote server.
     pub async fn blocking_sync_request(&self, req: &SyncRequest) -> SyncResponse {
-        let req = req.clone();
-        self.run_blocking(move |c| sync_client::sync_events(c, &req).unwrap())
+        sync_client::sync_events_async(&self.async_client(), req)
             .await
+            .unwrap()
     }
 
     /// Attempt sync and return the `Result` (for error-path tests).
@@ -378,9 +390,7 @@ impl TestHarness {
         req: &SyncRequest,
         token: &str,
     ) -> anyhow::Result<SyncResponse> {
-        let req = req.clone();
-        self.run_blocking_with_token(token, move |c| sync_client::sync_events(c, &req))
-            .await
+        sync_client::sync_events_async(&self.async_client_with_token(token), req).await
     }
 
     /// Fetch the authenticated user's gain summary.
@@ -429,8 +439,14 @@ impl TestHarness {
         filter_bytes: Vec<u8>,
         test_files: Vec<(String, Vec<u8>)>,
     ) -> anyhow::Result<(bool, PublishResponse)> {
-        self.run_blocking(move |c| publish_client::publish_filter(c, &filter_bytes, &test_files))
-            .await
+        publish_client::publish_filter_async(
+            &self.async_client(),
+            &filter_bytes,
+            &test_files,
+            None,
+            publish_client::Namespace::default(),
+        )
+        .await
     }
 
     /// Publish a filter with optional test files. Returns `(is_new, response)`.
@@ -439,10 +455,15 @@ impl TestHarness {
         filter_bytes: Vec<u8>,
         test_files: Vec<(String, Vec<u8>)>,
     ) -> (bool, PublishResponse) {
-        self.run_blocking(move |c| {
-            publish_client::publish_filter(c, &filter_bytes, &test_files).unwrap()
-        })
+        publish_client::publish_filter_async(
+            &self.async_client(),
+            &filter_bytes,
+            &test_files,
+            None,
+            publish_client::Namespace::default(),
+        )
         .await
+        .unwrap()
     }
 
     /// Update the test suite for a published filter.
@@ -451,9 +472,9 @@ impl TestHarness {
         hash: &str,
         test_files: Vec<(String, Vec<u8>)>,
     ) -> UpdateTestsResponse {
-        let hash = hash.to_string();
-        self.run_blocking(move |c| publish_client::update_tests(c, &hash, &test_files).unwrap())
+        publish_client::update_tests_async(&self.async_client(), hash, &test_files)
             .await
+            .unwrap()
     }
 
     /// Try to update tests (returns Result for error-path tests).
@@ -462,9 +483,7 @@ impl TestHarness {
         hash: &str,
         test_files: Vec<(String, Vec<u8>)>,
     ) -> anyhow::Result<UpdateTestsResponse> {
-        let hash = hash.to_string();
-        self.run_blocking(move |c| publish_client::update_tests(c, &hash, &test_files))
-            .await
+        publish_client::update_tests_async(&self.async_client(), hash, &test_files).await
     }
 
     /// Try to update tests with a custom token (for auth tests).
@@ -474,11 +493,8 @@ impl TestHarness {
         test_files: Vec<(String, Vec<u8>)>,
         token: &str,
     ) -> anyhow::Result<UpdateTestsResponse> {
-        let hash = hash.to_string();
-        self.run_blocking_with_token(token, move |c| {
-            publish_client::update_tests(c, &hash, &test_files)
-        })
-        .await
+        publish_client::update_tests_async(&self.async_client_with_token(token), hash, &test_files)
+            .await
     }
 
     /// Search the filter registry.
@@ -508,8 +524,8 @@ impl TestHarness {
 
     /// Download filter TOML + test files by hash.
     pub async fn blocking_download_filter(&self, hash: &str) -> DownloadedFilter {
-        let hash = hash.to_string();
-        self.run_blocking(move |c| filter_client::download_filter(c, &hash).unwrap())
+        filter_client::download_filter_async(&self.async_client(), hash)
             .await
+            .unwrap()
     }
 }
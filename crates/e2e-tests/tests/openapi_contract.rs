@@ -0,0 +1,162 @@
+//! Contract tests between the server's `OpenAPI` document and the CLI's
+//! client types in `tokf::remote`.
+//!
+//! Every response example in the document must deserialize into the client
+//! type that reads it, both as written and trimmed to the schema's required
+//! fields, so the client never depends on a field the server may omit. The
+//! request bodies the client sends must only use documented fields and
+//! include every required one.
+
+#![allow(clippy::unwrap_used, clippy::expect_used, clippy::panic)]
+
+use std::collections::BTreeSet;
+
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use tokf::remote::{
+    client, filter_client, gain_client, keys_client, publish_client, sync_client, tokens_client,
+    tos_client,
+};
+
+fn spec() -> Value {
+    serde_json::from_str(tokf_server::openapi::OPENAPI_JSON).unwrap()
+}
+
+fn schema(spec: &Value, name: &str) -> Value {
+    let schema = &spec["components"]["schemas"][name];
+    assert!(schema.is_object(), "no schema named {name}");
+    schema.clone()
+}
+
+/// The properties and required fields of `schema`, following `allOf`.
+fn fields(spec: &Value, schema: &Value) -> (BTreeSet<String>, BTreeSet<String>) {
+    if let Some(target) = schema["$ref"].as_str() {
+        let name = target.rsplit('/').next().unwrap();
+        return fields(spec, &self::schema(spec, name));
+    }
+    let mut properties: BTreeSet<String> = schema["properties"]
+        .as_object()
+        .map(|p| p.keys().cloned().collect())
+        .unwrap_or_default();
+    let mut required: BTreeSet<String> = schema["required"]
+        .as_array()
+        .map(|r| r.iter().map(|f| f.as_str().unwrap().to_string()).collect())
+        .unwrap_or_default();
+    for part in schema["allOf"].as_array().into_iter().flatten() {
+        let (p, r) = fields(spec, part);
+        properties.extend(p);
+        required.extend(r);
+    }
+    (properties, required)
+}
+
+/// Check `name`'s example against its schema and parse it, in full and
+/// trimmed to the required fields, as `T`.
+fn accepts_example<T: DeserializeOwned>(name: &str) {
+    let spec = spec();
+    let schema = schema(&spec, name);
+    let (properties, required) = fields(&spec, &schema);
+    let example = schema["example"]
+        .as_object()
+        .unwrap_or_else(|| panic!("{name} has no example"));
+
+    for key in example.keys() {
+        assert!(
+            properties.contains(key),
+            "{name} example has undocumented {key}"
+        );
+    }
+    for key in &required {
+        assert!(
+            example.contains_key(key),
+            "{name} example lacks required {key}"
+        );
+    }
+
+    let full = Value::Object(example.clone());
+    if let Err(e) = serde_json::from_value::<T>(full) {
+        panic!("{name} example is rejected by the client: {e}");
+    }
+    let minimal: serde_json::Map<_, _> = example
+        .iter()
+        .filter(|(key, _)| required.contains(*key))
+        .map(|(key, value)| (key.clone(), value.clone()))
+        .collect();
+    if let Err(e) = serde_json::from_value::<T>(Value::Object(minimal)) {
+        panic!("client needs a field {name} does not require: {e}");
+    }
+}
+
+/// Check a request body the client sends against the `name` schema.
+fn sends_documented_body(name: &str, body: &impl serde::Serialize) {
+    let spec = spec();
+    let (properties, required) = fields(&spec, &schema(&spec, name));
+    let body = serde_json::to_value(body).unwrap();
+    let sent: BTreeSet<String> = body.as_object().unwrap().keys().cloned().collect();
+    assert!(
+        sent.is_subset(&properties),
+        "client sends undocumented {name} fields: {:?}",
+        sent.difference(&properties).collect::<Vec<_>>()
+    );
+    assert!(
+        required.is_subset(&sent),
+        "client omits required {name} fields: {:?}",
+        required.difference(&sent).collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn filter_responses_parse() {
+    accepts_example::<filter_client::FilterSummary>("FilterSummary");
+    accepts_example::<filter_client::FilterDetails>("FilterDetails");
+    accepts_example::<filter_client::DownloadedFilter>("DownloadedFilter");
+    accepts_example::<publish_client::PublishResponse>("PublishFilterResponse");
+    accepts_example::<publish_client::UpdateTestsResponse>("UpdateTestsResponse");
+}
+
+#[test]
+fn account_responses_parse() {
+    accepts_example::<client::RegisteredMachine>("Machine");
+    accepts_example::<client::MachineInfo>("Machine");
+    accepts_example::<keys_client::SigningKeyInfo>("SigningKey");
+    accepts_example::<tokens_client::TokenInfo>("Token");
+    accepts_example::<tokens_client::CreatedToken>("CreatedToken");
+    accepts_example::<tos_client::TosInfoResponse>("TosInfo");
+    accepts_example::<tos_client::AcceptTosResponse>("AcceptTosResponse");
+}
+
+#[test]
+fn sync_and_gain_responses_parse() {
+    accepts_example::<sync_client::SyncResponse>("SyncResponse");
+    accepts_example::<sync_client::ReconcileResponse>("ReconcileResponse");
+    accepts_example::<gain_client::GainResponse>("Gain");
+    accepts_example::<gain_client::GlobalGainResponse>("GlobalGain");
+}
+
+#[test]
+fn sync_requests_match_the_spec() {
+    let event = sync_client::SyncEvent {
+        id: 1,
+        filter_name: Some("git/push".to_string()),
+        filter_hash: None,
+        input_tokens: 100,
+        output_tokens: 10,
+        raw_tokens: Some(120),
+        command_count: 1,
+        recorded_at: "2026-10-16T08:00:00Z".to_string(),
+    };
+    sends_documented_body("SyncEvent", &event);
+    let request = sync_client::SyncRequest {
+        machine_id: "0b3c8f4e-4d1a-4a8e-9d55-7f7d0e2a1c11".to_string(),
+        last_event_id: 0,
+        events: vec![event],
+    };
+    sends_documented_body("SyncRequest", &request);
+    let reconcile = sync_client::ReconcileRequest {
+        machine_id: request.machine_id,
+        last_synced_id: 1,
+        max_event_id: 1,
+        reset: false,
+    };
+    sends_documented_body("ReconcileRequest", &reconcile);
+}
@@ -10,7 +10,6 @@
 mod harness;
 
 use serde::{Deserialize, Serialize};
-use tokf::remote::http::Client;
 
 // Mirror the CLI-side types for the publish-stdlib request/response.
 #[derive(Debug, Serialize)]
@@ -75,6 +74,17 @@ async fn insert_service_token(pool: &sqlx::PgPool) -> String {
     token
 }
 
+/// POST `req` to the publish-stdlib endpoint as the holder of `token`.
+async fn publish_stdlib(
+    h: &harness::TestHarness,
+    token: &str,
+    req: &StdlibPublishRequest,
+) -> anyhow::Result<StdlibPublishResponse> {
+    h.async_client_with_token(token)
+        .post("/api/filters/publish-stdlib", req)
+        .await
+}
+
 /// Publish a single stdlib filter → success.
 #[crdb_test_macro::crdb_test(migrations = "../tokf-server/migrations")]
 async fn stdlib_publish_single_filter(pool: PgPool) {
@@ -86,18 +96,7 @@ async fn stdlib_publish_single_filter(pool: PgPool) {
         version: None,
     };
 
-    let resp: StdlibPublishResponse = tokio::task::spawn_blocking({
-        let base_url = h.base_url.clone();
-        let rt = h.rt.clone();
-        move || {
-            let client = Client::new(&rt, &base_url, Some(&service_token)).unwrap();
-            client
-                .post::<_, StdlibPublishResponse>("/api/filters/publish-stdlib", &req)
-                .unwrap()
-        }
-    })
-    .await
-    .unwrap();
+    let resp = publish_stdlib(&h, &service_token, &req).await.unwrap();
 
     assert_eq!(resp.published, 1);
     assert_eq!(resp.skipped, 0);
@@ -128,35 +127,11 @@ async fn stdlib_publish_idempotent(pool: PgPool) {
     };
 
     // First publish
-    let resp1: StdlibPublishResponse = tokio::task::spawn_blocking({
-        let base_url = h.base_url.clone();
-        let rt = h.rt.clone();
-        let token = service_token.clone();
-        let req = StdlibPublishRequest {
-            filters: vec![make_passthrough_entry("idempotent-tool")],
-            version: None,
-        };
-        move || {
-            let client = Client::new(&rt, &base_url, Some(&token)).unwrap();
-            client.post("/api/filters/publish-stdlib", &req).unwrap()
-        }
-    })
-    .await
-    .unwrap();
+    let resp1 = publish_stdlib(&h, &service_token, &req).await.unwrap();
     assert_eq!(resp1.published, 1);
 
     // Second publish (same content)
-    let resp2: StdlibPublishResponse = tokio::task::spawn_blocking({
-        let base_url = h.base_url.clone();
-        let rt = h.rt.clone();
-        let token = service_token;
-        move || {
-            let client = Client::new(&rt, &base_url, Some(&token)).unwrap();
-            client.post("/api/filters/publish-stdlib", &req).unwrap()
-        }
-    })
-    .await
-    .unwrap();
+    let resp2 = publish_stdlib(&h, &service_token, &req).await.unwrap();
     assert_eq!(resp2.published, 0);
     assert_eq!(resp2.skipped, 1);
     assert!(resp2.failed.is_empty());
@@ -196,16 +171,7 @@ not_contains = "noise"
         version: None,
     };
 
-    let resp: StdlibPublishResponse = tokio::task::spawn_blocking({
-        let base_url = h.base_url.clone();
-        let rt = h.rt.clone();
-        move || {
-            let client = Client::new(&rt, &base_url, Some(&service_token)).unwrap();
-            client.post("/api/filters/publish-stdlib", &req).unwrap()
-        }
-    })
-    .await
-    .unwrap();
+    let resp = publish_stdlib(&h, &service_token, &req).await.unwrap();
 
     assert_eq!(resp.published, 1, "filter should be published");
     assert!(
@@ -238,16 +204,7 @@ async fn stdlib_publish_with_backslash_content(pool: PgPool) {
         version: None,
     };
 
-    let resp: StdlibPublishResponse = tokio::task::spawn_blocking({
-        let base_url = h.base_url.clone();
-        let rt = h.rt.clone();
-        move || {
-            let client = Client::new(&rt, &base_url, Some(&service_token)).unwrap();
-            client.post("/api/filters/publish-stdlib", &req).unwrap()
-        }
-    })
-    .await
-    .unwrap();
+    let resp = publish_stdlib(&h, &service_token, &req).await.unwrap();
 
     assert_eq!(
         resp.published, 1,
@@ -275,17 +232,7 @@ async fn stdlib_publish_one_by_one(pool: PgPool) {
             filters: vec![make_passthrough_entry(cmd)],
             version: None,
         };
-        let resp: StdlibPublishResponse = tokio::task::spawn_blocking({
-            let base_url = h.base_url.clone();
-            let rt = h.rt.clone();
-            let token = service_token.clone();
-            move || {
-                let client = Client::new(&rt, &base_url, Some(&token)).unwrap();
-                client.post("/api/filters/publish-stdlib", &req).unwrap()
-            }
-        })
-        .await
-        .unwrap();
+        let resp = publish_stdlib(&h, &service_token, &req).await.unwrap();
 
         published += resp.published;
         failed += resp.failed.len();
@@ -312,16 +259,7 @@ async fn stdlib_publish_rejects_invalid_token(pool: PgPool) {
         version: None,
     };
 
-    let result = tokio::task::spawn_blocking({
-        let base_url = h.base_url.clone();
-        let rt = h.rt.clone();
-        move || {
-            let client = Client::new(&rt, &base_url, Some("bad-token")).unwrap();
-            client.post::<_, StdlibPublishResponse>("/api/filters/publish-stdlib", &req)
-        }
-    })
-    .await
-    .unwrap();
+    let result = publish_stdlib(&h, "bad-token", &req).await;
 
     assert!(result.is_err(), "invalid token should produce an error");
     let err = result.unwrap_err().to_string();
@@ -343,17 +281,7 @@ async fn stdlib_filter_appears_in_search(pool: PgPool) {
         version: None,
     };
 
-    let resp: StdlibPublishResponse = tokio::task::spawn_blocking({
-        let base_url = h.base_url.clone();
-        let rt = h.rt.clone();
-        let token = service_token;
-        move || {
-            let client = Client::new(&rt, &base_url, Some(&token)).unwrap();
-            client.post("/api/filters/publish-stdlib", &req).unwrap()
-        }
-    })
-    .await
-    .unwrap();
+    let resp = publish_stdlib(&h, &service_token, &req).await.unwrap();
     assert_eq!(resp.published, 1);
 
     // Search for it using the regular user token
@@ -6,10 +6,12 @@ use std::time::{SystemTime, UNIX_EPOCH};
 
 use serde::{Deserialize, Serialize};
 
+use super::token_store;
+#[cfg(any(test, feature = "test-support"))]
+pub use super::token_store::use_mock_keyring;
+pub use super::token_store::{token_file_path, uses_token_file};
 use crate::runtime::Runtime;
 
-const KEYRING_USER: &str = "default";
-
 #[derive(Debug, Serialize, Deserialize)]
 pub struct StoredAuth {
     pub username: String,
@@ -66,13 +68,17 @@ fn require_auth_config_path(rt: &Runtime) -> anyhow::Result<PathBuf> {
 
 /// Store authentication credentials (token in keyring, metadata in TOML).
 ///
+/// When the OS keyring is unavailable the token goes to a `0600` file in the
+/// config directory instead, with a notice on stderr.
+///
 /// `token_expires_in` is the number of seconds until the token expires
 /// (from the server's `expires_in` field). Pass 0 if unknown.
 ///
 /// # Errors
 ///
-/// Returns an error if the keyring is inaccessible, the config directory
-/// cannot be determined, or the TOML file cannot be written.
+/// Returns an error if neither the keyring nor the fallback file can be
+/// written, the config directory cannot be determined, or the TOML file
+/// cannot be written.
 pub fn save(
     rt: &Runtime,
     token: &str,
@@ -80,11 +86,7 @@ pub fn save(
     server_url: &str,
     token_expires_in: i64,
 ) -> anyhow::Result<()> {
-    // Store token in OS keyring
-    let entry = keyring_entry(rt)?;
-    entry
-        .set_password(token)
-        .map_err(|e| anyhow::anyhow!("could not access system keyring: {e}"))?;
+    token_store::save_token(rt, token)?;
 
     let expires_at = if token_expires_in > 0 {
         SystemTime::now()
@@ -202,15 +204,34 @@ fn update_stored_auth(
 
 /// Load stored authentication credentials.
 ///
-/// Returns `None` if no credentials are stored, the TOML file is missing
-/// or malformed, or the keyring entry is absent.
+/// A `TOKF_TOKEN` personal access token takes precedence over the stored
+/// login and is sent to `TOKF_SERVER_URL`; any stored metadata (username,
+/// license acceptance) still applies.
+///
+/// Otherwise returns `None` if no credentials are stored, the TOML file is
+/// missing or malformed, or there is no token in either the keyring or the
+/// fallback file.
 pub fn load(rt: &Runtime) -> Option<LoadedAuth> {
-    let path = auth_config_path(rt)?;
-    let content = fs::read_to_string(&path).ok()?;
-    let meta: StoredAuth = toml::from_str(&content).ok()?;
-
-    let entry = keyring_entry(rt).ok()?;
-    let token = entry.get_password().ok()?;
+    let meta = auth_config_path(rt)
+        .and_then(|path| fs::read_to_string(path).ok())
+        .and_then(|content| toml::from_str::<StoredAuth>(&content).ok());
+
+    if let Some(token) = rt.token() {
+        return Some(LoadedAuth {
+            token: token.to_string(),
+            username: meta
+                .as_ref()
+                .map(|m| m.username.clone())
+                .unwrap_or_default(),
+            server_url: rt.server_url().to_string(),
+            // The server enforces the token's expiry.
+            expires_at: 0,
+            mit_license_accepted: meta.as_ref().and_then(|m| m.mit_license_accepted),
+            tos_accepted_version: meta.as_ref().and_then(|m| m.tos_accepted_version),
+        });
+    }
+    let meta = meta?;
+    let token = token_store::load_token(rt)?;
 
     Some(LoadedAuth {
         token,
@@ -222,86 +243,21 @@ pub fn load(rt: &Runtime) -> Option<LoadedAuth> {
     })
 }
 
-/// Guards mock-store installation so it happens exactly once per process.
-///
-/// `keyring_core`'s default store is process-global and first-write-wins, so
-/// installing it more than once would either be ignored or swap in a fresh,
-/// empty store underneath a test that had already saved a credential.
-#[cfg(any(test, feature = "test-support"))]
-static MOCK_STORE_INIT: std::sync::Once = std::sync::Once::new();
-
-/// Switch the keyring to an in-memory backend that persists across entries.
-///
-/// Idempotent: the store is installed on the first call and every later call
-/// is a no-op, so concurrent callers all observe the same store.
-///
-/// Uses `keyring_core`'s mock store, which reuses one credential per
-/// `(service, user)` pair, so `save()` + `load()` round-trips work in tests
-/// (its persistence is `ProcessOnly`).
-#[cfg(any(test, feature = "test-support"))]
-pub fn use_mock_keyring() {
-    MOCK_STORE_INIT.call_once(|| {
-        if let Ok(store) = keyring_core::mock::Store::new() {
-            keyring_core::set_default_store(store);
-        }
-    });
-}
-
-/// The entry type used to reach the credential store.
-///
-/// Test builds deliberately use `keyring_core::Entry` rather than
-/// `keyring::Entry`; see [`keyring_entry`] for why that distinction matters.
-#[cfg(any(test, feature = "test-support"))]
-type KeyringEntry = keyring_core::Entry;
-#[cfg(not(any(test, feature = "test-support")))]
-type KeyringEntry = keyring::Entry;
-
-/// Construct the keyring entry holding the auth token.
-///
-/// Every keyring access in this module goes through here.
-///
-/// **Why test builds bypass `keyring::Entry`.** `keyring` 4's `v1` wrapper opens
-/// `Entry::new` with `SET_CREDENTIAL_STORE.call_once(set_credential_store)`,
-/// which calls `keyring_core::set_default_store(platform_store)` — and that
-/// setter overwrites unconditionally. So the *first* `keyring::Entry::new`
-/// anywhere in the process replaces whatever store was installed, including a
-/// mock. Installing the mock earlier cannot win that race; the wrapper always
-/// clobbers it, which is why tests reached the real OS keychain (prompting for
-/// access, and failing with "item already exists" against leftover state).
-///
-/// Going through `keyring_core::Entry` in test builds skips that `call_once`
-/// entirely, so the mock store installed by [`use_mock_keyring`] is the one
-/// actually used. Production builds keep the `keyring::Entry` wrapper and its
-/// platform-store selection, unchanged.
-fn keyring_entry(rt: &Runtime) -> keyring::Result<KeyringEntry> {
-    #[cfg(any(test, feature = "test-support"))]
-    {
-        use_mock_keyring();
-        keyring_core::Entry::new(rt.keyring_service(), KEYRING_USER)
-    }
-
-    #[cfg(not(any(test, feature = "test-support")))]
-    {
-        keyring::Entry::new(rt.keyring_service(), KEYRING_USER)
-    }
-}
-
-/// Remove stored credentials (keyring entry and TOML file).
+/// Remove stored credentials (keyring entry, fallback token file and TOML
+/// file).
 ///
 /// Silently ignores errors — the credentials may already be absent.
 /// Returns `true` if credentials were present before removal.
 pub fn remove(rt: &Runtime) -> bool {
     let had_credentials = load(rt).is_some();
 
-    // Remove keyring entry (ignore errors — may already be absent).
+    // Remove the token (ignore errors — may already be absent).
     //
     // Deliberately unconditional, not gated on `had_credentials`: the TOML file
-    // and the keyring entry can go out of sync (a hand-deleted auth.toml leaves
+    // and the stored token can go out of sync (a hand-deleted auth.toml leaves
     // the token orphaned), and `load()` reports None whenever the TOML is gone.
     // Gating here would silently strand that token forever.
-    if let Ok(entry) = keyring_entry(rt) {
-        let _ = entry.delete_credential();
-    }
+    token_store::delete_token(rt);
 
     // Remove TOML file
     if let Some(path) = auth_config_path(rt) {
@@ -420,6 +376,32 @@ mod tests {
         assert!(!loaded.is_expired());
     }
 
+    #[test]
+    fn env_token_overrides_the_stored_login() {
+        use_mock_keyring();
+        let rt = Runtime::builder()
+            .server_url("https://ci.example.com")
+            .token("tokf_pat_abc")
+            .build();
+        save(&rt, "stored-token", "alice", "https://api.tokf.net", 3600).unwrap();
+        save_license_accepted(&rt, true).unwrap();
+
+        let loaded = load(&rt).expect("the env token should load");
+        assert_eq!(loaded.token, "tokf_pat_abc");
+        assert_eq!(loaded.server_url, "https://ci.example.com");
+        assert_eq!(loaded.username, "alice");
+        assert_eq!(loaded.mit_license_accepted, Some(true));
+        assert_eq!(loaded.expires_at, 0);
+    }
+
+    #[test]
+    fn env_token_needs_no_stored_login() {
+        let rt = Runtime::builder().token("tokf_pat_abc").build();
+        let loaded = load(&rt).expect("the env token should load");
+        assert_eq!(loaded.token, "tokf_pat_abc");
+        assert_eq!(loaded.username, "");
+    }
+
     #[test]
     fn remove_clears_credentials() {
         use_mock_keyring();
@@ -438,52 +420,6 @@ mod tests {
         assert!(after.is_none(), "credentials should be gone after remove");
     }
 
-    /// Asserts that a keyring access lands on the mock store.
-    ///
-    /// Before `keyring_entry()` existed, `load()` here could construct an entry
-    /// against the real OS keychain, and because `keyring_core`'s default store
-    /// is process-global and first-write-wins, that pinned the real store for
-    /// every test that ran afterwards — the cause of the intermittent
-    /// "item already exists in the keychain" failures.
-    ///
-    /// The store is still process-global, but each isolated runtime carries its
-    /// own service name, so concurrent tests address disjoint credentials and
-    /// no longer need to be serialised.
-    #[test]
-    fn keyring_entry_installs_the_mock_store_without_an_explicit_call() {
-        // No use_mock_keyring() call: keyring_entry() must install it.
-        let rt = Runtime::isolated();
-        let entry = keyring_entry(&rt).expect("keyring entry should be constructible");
-
-        // Against the mock this round-trips; against a real keychain it would
-        // prompt for access or fail outright.
-        entry
-            .set_password("mock-store-probe")
-            .expect("mock store should accept a write");
-        assert_eq!(
-            entry.get_password().expect("mock store should read back"),
-            "mock-store-probe"
-        );
-        let _ = entry.delete_credential();
-    }
-
-    #[test]
-    fn use_mock_keyring_is_idempotent_and_preserves_state() {
-        use_mock_keyring();
-        let rt = Runtime::isolated();
-        let entry = keyring_entry(&rt).unwrap();
-        entry.set_password("first-write").unwrap();
-
-        // A second call must NOT swap in a fresh, empty store underneath us.
-        use_mock_keyring();
-        assert_eq!(
-            keyring_entry(&rt).unwrap().get_password().unwrap(),
-            "first-write",
-            "re-installing the mock store must not discard existing credentials"
-        );
-        let _ = entry.delete_credential();
-    }
-
     #[test]
     fn stored_auth_tos_version_roundtrip() {
         let meta = StoredAuth {
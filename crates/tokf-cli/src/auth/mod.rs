@@ -1,2 +1,4 @@
 pub mod client;
 pub mod credentials;
+pub mod signing_key;
+mod token_store;
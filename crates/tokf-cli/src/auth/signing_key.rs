@@ -0,0 +1,80 @@
+//! The local filter-signing key, stored as hex PKCS#8 in
+//! `<config dir>/signing_key` (mode 0600 on Unix).
+
+use std::path::PathBuf;
+
+use anyhow::Context as _;
+use tokf_common::signing::SigningKey;
+
+use crate::fs::write_config_file;
+use crate::runtime::Runtime;
+
+/// Where the signing key lives; `None` without a user config dir.
+pub fn key_path(rt: &Runtime) -> Option<PathBuf> {
+    rt.user_dir().map(|d| d.join("signing_key"))
+}
+
+/// Load the signing key, if one has been generated.
+///
+/// # Errors
+///
+/// Returns an error if the key file exists but cannot be read or parsed.
+pub fn load(rt: &Runtime) -> anyhow::Result<Option<SigningKey>> {
+    let Some(path) = key_path(rt) else {
+        return Ok(None);
+    };
+    let content = match std::fs::read_to_string(&path) {
+        Ok(c) => c,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(e).with_context(|| format!("read {}", path.display())),
+    };
+    SigningKey::from_pkcs8_hex(&content)
+        .map(Some)
+        .with_context(|| format!("parse {}", path.display()))
+}
+
+/// Generate a new signing key and store it, replacing any existing one.
+///
+/// # Errors
+///
+/// Returns an error if there is no config dir, key generation fails, or the
+/// file cannot be written.
+pub fn generate(rt: &Runtime) -> anyhow::Result<SigningKey> {
+    let path = rt.require_user_dir()?.join("signing_key");
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let (key, pkcs8_hex) =
+        SigningKey::generate().map_err(|_| anyhow::anyhow!("could not generate signing key"))?;
+    write_config_file(&path, &format!("{pkcs8_hex}\n"))?;
+    Ok(key)
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn missing_key_is_none() {
+        let rt = Runtime::isolated();
+        assert!(load(&rt).unwrap().is_none());
+    }
+
+    #[test]
+    fn generated_key_reloads() {
+        let rt = Runtime::isolated();
+        let key = generate(&rt).unwrap();
+        let loaded = load(&rt).unwrap().unwrap();
+        assert_eq!(key.public_key_hex(), loaded.public_key_hex());
+    }
+
+    #[test]
+    fn corrupt_key_is_an_error() {
+        let rt = Runtime::isolated();
+        let path = key_path(&rt).unwrap();
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, "not a key").unwrap();
+        assert!(load(&rt).is_err());
+    }
+}
@@ -0,0 +1,244 @@
+//! Where the auth token itself is kept.
+//!
+//! The OS keyring (Keychain on macOS, Credential Manager on Windows, Secret
+//! Service on Linux) is preferred. Hosts without one — headless Linux,
+//! containers, some CI runners — get a `0600` file in the config directory
+//! instead, so `tokf auth login` still works there. Everything else about a
+//! login lives in `auth.toml`; see [`super::credentials`].
+
+use std::fs;
+use std::path::PathBuf;
+
+use crate::fs::write_config_file;
+use crate::runtime::Runtime;
+
+const KEYRING_USER: &str = "default";
+
+/// Filename of the token when no OS keyring is available.
+const TOKEN_FILE: &str = "auth_token";
+
+/// Where the token is kept when the OS keyring cannot be used (headless
+/// Linux without a Secret Service, containers, some CI runners).
+pub fn token_file_path(rt: &Runtime) -> Option<PathBuf> {
+    rt.user_dir().map(|d| d.join(TOKEN_FILE))
+}
+
+/// Whether the stored token lives in [`token_file_path`] rather than the OS
+/// keyring.
+pub fn uses_token_file(rt: &Runtime) -> bool {
+    token_file_path(rt).is_some_and(|p| p.is_file())
+}
+
+/// Store the token in the OS keyring, falling back to a `0600` file beside
+/// `auth.toml` when the keyring is unavailable.
+pub fn save_token(rt: &Runtime, token: &str) -> anyhow::Result<()> {
+    let keyring = keyring_entry(rt).and_then(|entry| entry.set_password(token));
+    let path = token_file_path(rt);
+    match keyring {
+        Ok(()) => {
+            // A token left over from an earlier fallback is now stale.
+            if let Some(path) = path {
+                let _ = fs::remove_file(path);
+            }
+            Ok(())
+        }
+        Err(e) => {
+            let path = path.ok_or_else(|| {
+                anyhow::anyhow!("could not access system keyring: {e} (and no config directory)")
+            })?;
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            write_config_file(&path, token)?;
+            eprintln!(
+                "[tokf] system keyring unavailable ({e}); token stored in {} instead",
+                path.display()
+            );
+            Ok(())
+        }
+    }
+}
+
+/// The stored token: from the OS keyring, else from the fallback file.
+pub fn load_token(rt: &Runtime) -> Option<String> {
+    if let Ok(token) = keyring_entry(rt).and_then(|entry| entry.get_password()) {
+        return Some(token);
+    }
+    let content = fs::read_to_string(token_file_path(rt)?).ok()?;
+    let token = content.trim();
+    (!token.is_empty()).then(|| token.to_string())
+}
+
+/// Delete the token from both the keyring and the fallback file, ignoring
+/// errors — either may already be absent.
+pub fn delete_token(rt: &Runtime) {
+    if let Ok(entry) = keyring_entry(rt) {
+        let _ = entry.delete_credential();
+    }
+    if let Some(path) = token_file_path(rt) {
+        let _ = fs::remove_file(path);
+    }
+}
+
+/// Guards mock-store installation so it happens exactly once per process.
+///
+/// `keyring_core`'s default store is process-global and first-write-wins, so
+/// installing it more than once would either be ignored or swap in a fresh,
+/// empty store underneath a test that had already saved a credential.
+#[cfg(any(test, feature = "test-support"))]
+static MOCK_STORE_INIT: std::sync::Once = std::sync::Once::new();
+
+/// Switch the keyring to an in-memory backend that persists across entries.
+///
+/// Idempotent: the store is installed on the first call and every later call
+/// is a no-op, so concurrent callers all observe the same store.
+///
+/// Uses `keyring_core`'s mock store, which reuses one credential per
+/// `(service, user)` pair, so `save()` + `load()` round-trips work in tests
+/// (its persistence is `ProcessOnly`).
+#[cfg(any(test, feature = "test-support"))]
+pub fn use_mock_keyring() {
+    MOCK_STORE_INIT.call_once(|| {
+        if let Ok(store) = keyring_core::mock::Store::new() {
+            keyring_core::set_default_store(store);
+        }
+    });
+}
+
+/// The entry type used to reach the credential store.
+///
+/// Test builds deliberately use `keyring_core::Entry` rather than
+/// `keyring::Entry`; see [`keyring_entry`] for why that distinction matters.
+#[cfg(any(test, feature = "test-support"))]
+type KeyringEntry = keyring_core::Entry;
+#[cfg(not(any(test, feature = "test-support")))]
+type KeyringEntry = keyring::Entry;
+
+/// Construct the keyring entry holding the auth token.
+///
+/// Every keyring access goes through here.
+///
+/// **Why test builds bypass `keyring::Entry`.** `keyring` 4's `v1` wrapper opens
+/// `Entry::new` with `SET_CREDENTIAL_STORE.call_once(set_credential_store)`,
+/// which calls `keyring_core::set_default_store(platform_store)` — and that
+/// setter overwrites unconditionally. So the *first* `keyring::Entry::new`
+/// anywhere in the process replaces whatever store was installed, including a
+/// mock. Installing the mock earlier cannot win that race; the wrapper always
+/// clobbers it, which is why tests reached the real OS keychain (prompting for
+/// access, and failing with "item already exists" against leftover state).
+///
+/// Going through `keyring_core::Entry` in test builds skips that `call_once`
+/// entirely, so the mock store installed by [`use_mock_keyring`] is the one
+/// actually used. Production builds keep the `keyring::Entry` wrapper and its
+/// platform-store selection, unchanged.
+fn keyring_entry(rt: &Runtime) -> keyring::Result<KeyringEntry> {
+    #[cfg(any(test, feature = "test-support"))]
+    {
+        use_mock_keyring();
+        keyring_core::Entry::new(rt.keyring_service(), KEYRING_USER)
+    }
+
+    #[cfg(not(any(test, feature = "test-support")))]
+    {
+        keyring::Entry::new(rt.keyring_service(), KEYRING_USER)
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+
+    /// Asserts that a keyring access lands on the mock store.
+    ///
+    /// Before `keyring_entry()` existed, `load()` here could construct an entry
+    /// against the real OS keychain, and because `keyring_core`'s default store
+    /// is process-global and first-write-wins, that pinned the real store for
+    /// every test that ran afterwards — the cause of the intermittent
+    /// "item already exists in the keychain" failures.
+    ///
+    /// The store is still process-global, but each isolated runtime carries its
+    /// own service name, so concurrent tests address disjoint credentials and
+    /// no longer need to be serialised.
+    #[test]
+    fn keyring_entry_installs_the_mock_store_without_an_explicit_call() {
+        // No use_mock_keyring() call: keyring_entry() must install it.
+        let rt = Runtime::isolated();
+        let entry = keyring_entry(&rt).expect("keyring entry should be constructible");
+
+        // Against the mock this round-trips; against a real keychain it would
+        // prompt for access or fail outright.
+        entry
+            .set_password("mock-store-probe")
+            .expect("mock store should accept a write");
+        assert_eq!(
+            entry.get_password().expect("mock store should read back"),
+            "mock-store-probe"
+        );
+        let _ = entry.delete_credential();
+    }
+
+    #[test]
+    fn use_mock_keyring_is_idempotent_and_preserves_state() {
+        use_mock_keyring();
+        let rt = Runtime::isolated();
+        let entry = keyring_entry(&rt).unwrap();
+        entry.set_password("first-write").unwrap();
+
+        // A second call must NOT swap in a fresh, empty store underneath us.
+        use_mock_keyring();
+        assert_eq!(
+            keyring_entry(&rt).unwrap().get_password().unwrap(),
+            "first-write",
+            "re-installing the mock store must not discard existing credentials"
+        );
+        let _ = entry.delete_credential();
+    }
+
+    /// Make the next keyring operation for `rt` fail, as it does on a
+    /// headless host with no Secret Service.
+    fn break_keyring_once(rt: &Runtime) {
+        let entry = keyring_entry(rt).unwrap();
+        entry
+            .as_any()
+            .downcast_ref::<keyring_core::mock::Cred>()
+            .unwrap()
+            .set_error(keyring_core::Error::NoStorageAccess(
+                "no secret service".into(),
+            ));
+    }
+
+    #[test]
+    fn save_falls_back_to_a_file_without_a_keyring() {
+        let rt = Runtime::isolated();
+        break_keyring_once(&rt);
+
+        save_token(&rt, "file-token").unwrap();
+        assert!(uses_token_file(&rt));
+        let path = token_file_path(&rt).unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "file-token");
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = fs::metadata(&path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+        assert_eq!(load_token(&rt).as_deref(), Some("file-token"));
+
+        delete_token(&rt);
+        assert!(!path.exists());
+        assert_eq!(load_token(&rt), None);
+    }
+
+    #[test]
+    fn saving_to_the_keyring_drops_a_stale_token_file() {
+        let rt = Runtime::isolated();
+        break_keyring_once(&rt);
+        save_token(&rt, "file-token").unwrap();
+        assert!(uses_token_file(&rt));
+
+        save_token(&rt, "keyring-token").unwrap();
+        assert!(!uses_token_file(&rt));
+        assert_eq!(load_token(&rt).as_deref(), Some("keyring-token"));
+    }
+}
@@ -354,9 +354,17 @@ pub fn cmd_auth_logout(rt: &Runtime) -> anyhow::Result<i32> {
 #[allow(clippy::unnecessary_wraps)] // Returns Result for or_exit() consistency
 pub fn cmd_auth_status(rt: &Runtime) -> anyhow::Result<i32> {
     match credentials::load(rt) {
+        Some(auth) if rt.token().is_some() => {
+            println!("Using the personal access token in TOKF_TOKEN");
+            println!("Server: {}", auth.server_url);
+        }
         Some(auth) => {
             println!("Logged in as {}", auth.username);
             println!("Server: {}", auth.server_url);
+            if credentials::uses_token_file(rt) {
+                let path = credentials::token_file_path(rt).unwrap_or_default();
+                println!("Token store: {} (no OS keyring available)", path.display());
+            }
             if auth.is_expired() {
                 println!("Token: expired — run `tokf auth login` to re-authenticate");
             }
@@ -375,6 +383,7 @@ pub fn cmd_auth_delete_account(rt: &Runtime) -> anyhow::Result<i32> {
     eprintln!("[tokf] WARNING: This will permanently delete your account.");
     eprintln!("[tokf] The following data will be removed:");
     eprintln!("[tokf]   - Auth tokens and sessions");
+    eprintln!("[tokf]   - Registered filter-signing keys");
     eprintln!("[tokf]   - Machine registrations and sync state");
     eprintln!("[tokf]   - Usage statistics and event history");
     eprintln!("[tokf]   - Terms of Service acceptance records");
@@ -0,0 +1,99 @@
+//! `tokf auth keys`: the ed25519 key that signs published filters.
+//!
+//! The private key stays in the local config dir; only the public half is
+//! registered with the server, which then accepts publishes signed with it.
+
+use tokf::auth::signing_key;
+use tokf::remote::{http::Client, keys_client};
+use tokf::runtime::Runtime;
+use tokf_common::signing::fingerprint;
+
+pub fn cmd_keys_generate(rt: &Runtime, force: bool) -> anyhow::Result<i32> {
+    if !force && let Some(existing) = signing_key::load(rt)? {
+        eprintln!(
+            "[tokf] a signing key already exists ({}) — pass --force to replace it",
+            fingerprint(&existing.public_key_hex())
+        );
+        return Ok(1);
+    }
+    // Fail before touching the key file when not logged in.
+    let client = Client::authed(rt)?;
+    let key = signing_key::generate(rt)?;
+    let public_key = key.public_key_hex();
+    eprintln!(
+        "[tokf] generated signing key {} ({})",
+        fingerprint(&public_key),
+        signing_key::key_path(rt).map_or_else(String::new, |p| p.display().to_string())
+    );
+    Ok(register(&client, &public_key))
+}
+
+pub fn cmd_keys_register(rt: &Runtime) -> anyhow::Result<i32> {
+    let key = require_key(rt)?;
+    let client = Client::authed(rt)?;
+    Ok(register(&client, &key.public_key_hex()))
+}
+
+#[allow(clippy::unnecessary_wraps)] // Returns Result for or_exit() consistency
+pub fn cmd_keys_show(rt: &Runtime) -> anyhow::Result<i32> {
+    let key = require_key(rt)?;
+    let public_key = key.public_key_hex();
+    println!("Public key:  {public_key}");
+    println!("Fingerprint: {}", fingerprint(&public_key));
+    Ok(0)
+}
+
+pub fn cmd_keys_list(rt: &Runtime) -> anyhow::Result<i32> {
+    let client = Client::authed(rt)?;
+    let keys = keys_client::list_keys(&client)?;
+    if keys.is_empty() {
+        eprintln!("[tokf] no signing keys registered — run `tokf auth keys generate`");
+        return Ok(0);
+    }
+    let local = signing_key::load(rt)?.map(|k| k.public_key_hex());
+    for key in keys {
+        let marker = if local.as_deref() == Some(key.public_key.as_str()) {
+            "  (this machine)"
+        } else {
+            ""
+        };
+        println!("{}  {}{marker}", key.fingerprint, key.created_at);
+    }
+    Ok(0)
+}
+
+pub fn cmd_keys_revoke(rt: &Runtime, key: &str) -> anyhow::Result<i32> {
+    let client = Client::authed(rt)?;
+    let wanted = key.trim().to_ascii_lowercase();
+    let keys = keys_client::list_keys(&client)?;
+    let Some(found) = keys
+        .iter()
+        .find(|k| k.public_key == wanted || k.fingerprint == wanted)
+    else {
+        eprintln!("[tokf] no registered signing key matches {key}");
+        return Ok(1);
+    };
+    keys_client::revoke_key(&client, &found.public_key)?;
+    eprintln!("[tokf] revoked signing key {}", found.fingerprint);
+    Ok(0)
+}
+
+fn register(client: &Client, public_key: &str) -> i32 {
+    match keys_client::register_key(client, public_key) {
+        Ok(info) => {
+            eprintln!("[tokf] registered signing key {}", info.fingerprint);
+            eprintln!("[tokf] `tokf publish` will now sign your filters");
+            0
+        }
+        Err(e) => {
+            eprintln!("[tokf] could not register the signing key: {e:#}");
+            eprintln!("[tokf] retry with `tokf auth keys register`");
+            1
+        }
+    }
+}
+
+fn require_key(rt: &Runtime) -> anyhow::Result<tokf_common::signing::SigningKey> {
+    signing_key::load(rt)?
+        .ok_or_else(|| anyhow::anyhow!("no signing key — run `tokf auth keys generate` first"))
+}
@@ -0,0 +1,55 @@
+//! `tokf auth token`: personal access tokens for CI.
+//!
+//! A token is minted from an interactive login and handed to a pipeline as
+//! `TOKF_TOKEN`, where it stands in for `tokf auth login`. Each token carries
+//! scopes that limit what it can do and always expires.
+
+use tokf::remote::{http::Client, tokens_client};
+use tokf::runtime::Runtime;
+
+pub fn cmd_token_create(
+    rt: &Runtime,
+    name: &str,
+    scopes: &[String],
+    expires_in_days: Option<u32>,
+) -> anyhow::Result<i32> {
+    let client = Client::authed(rt)?;
+    let created = tokens_client::create_token(&client, name, scopes, expires_in_days)?;
+    eprintln!(
+        "[tokf] created token '{}' (id {}, scopes: {}, expires {})",
+        created.info.name,
+        created.info.id,
+        created.info.scopes.join(", "),
+        created.info.expires_at
+    );
+    eprintln!("[tokf] store it as the TOKF_TOKEN secret — it will not be shown again");
+    println!("{}", created.token);
+    Ok(0)
+}
+
+pub fn cmd_token_list(rt: &Runtime) -> anyhow::Result<i32> {
+    let client = Client::authed(rt)?;
+    let tokens = tokens_client::list_tokens(&client)?;
+    if tokens.is_empty() {
+        eprintln!("[tokf] no personal access tokens — run `tokf auth token create`");
+        return Ok(0);
+    }
+    for token in tokens {
+        println!(
+            "{:>6}  {:<24}  {:<20}  expires {}  last used {}",
+            token.id,
+            token.name,
+            token.scopes.join(","),
+            token.expires_at,
+            token.last_used_at.as_deref().unwrap_or("never")
+        );
+    }
+    Ok(0)
+}
+
+pub fn cmd_token_revoke(rt: &Runtime, id: i64) -> anyhow::Result<i32> {
+    let client = Client::authed(rt)?;
+    tokens_client::revoke_token(&client, id)?;
+    eprintln!("[tokf] revoked token {id}");
+    Ok(0)
+}
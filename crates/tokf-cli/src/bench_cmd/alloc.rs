@@ -0,0 +1,84 @@
+//! Heap accounting for `tokf bench`: the system allocator, plus a count of
+//! live and peak bytes while a measurement is running.
+//!
+//! Counting is off outside [`peak`], so every other tokf command pays only
+//! one relaxed load per allocation.
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+#[global_allocator]
+static ALLOCATOR: Counting = Counting;
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+/// Bytes allocated and not yet freed since counting was enabled. Frees of
+/// memory allocated before that can take it below zero, hence the wrap.
+static LIVE: AtomicUsize = AtomicUsize::new(0);
+static PEAK: AtomicUsize = AtomicUsize::new(0);
+
+struct Counting;
+
+fn grow(bytes: usize) {
+    if ENABLED.load(Ordering::Relaxed) {
+        let live = LIVE.fetch_add(bytes, Ordering::Relaxed).wrapping_add(bytes);
+        if live < usize::MAX / 2 {
+            PEAK.fetch_max(live, Ordering::Relaxed);
+        }
+    }
+}
+
+fn shrink(bytes: usize) {
+    if ENABLED.load(Ordering::Relaxed) {
+        LIVE.fetch_sub(bytes, Ordering::Relaxed);
+    }
+}
+
+// SAFETY: every call is forwarded to `System` unchanged; the counters are
+// plain atomics and never allocate.
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        // SAFETY: same contract as the caller's.
+        let ptr = unsafe { System.alloc(layout) };
+        if !ptr.is_null() {
+            grow(layout.size());
+        }
+        ptr
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        // SAFETY: same contract as the caller's.
+        let ptr = unsafe { System.alloc_zeroed(layout) };
+        if !ptr.is_null() {
+            grow(layout.size());
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        // SAFETY: same contract as the caller's.
+        unsafe { System.dealloc(ptr, layout) };
+        shrink(layout.size());
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        // SAFETY: same contract as the caller's.
+        let new = unsafe { System.realloc(ptr, layout, new_size) };
+        if !new.is_null() {
+            shrink(layout.size());
+            grow(new_size);
+        }
+        new
+    }
+}
+
+/// Run `f`, returning its result and the most heap held at once while it
+/// ran, beyond what was allocated before. Counts every thread, so measure
+/// with nothing else running.
+pub fn peak<T>(f: impl FnOnce() -> T) -> (T, usize) {
+    LIVE.store(0, Ordering::Relaxed);
+    PEAK.store(0, Ordering::Relaxed);
+    ENABLED.store(true, Ordering::Relaxed);
+    let value = f();
+    ENABLED.store(false, Ordering::Relaxed);
+    (value, PEAK.load(Ordering::Relaxed))
+}
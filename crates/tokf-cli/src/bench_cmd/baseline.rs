@@ -0,0 +1,104 @@
+//! Stored bench results, and how a new run compares to them.
+
+use std::path::Path;
+
+use anyhow::Context as _;
+use serde::{Deserialize, Serialize};
+
+use super::measure::FilterBench;
+
+/// Latency changes smaller than this are timer and scheduler noise, however
+/// large they are in percent.
+const LATENCY_NOISE_NS: u64 = 200_000;
+
+/// Allocation changes smaller than this are ignored the same way.
+const ALLOC_NOISE_BYTES: usize = 64 * 1024;
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Baseline {
+    pub filters: Vec<FilterBench>,
+}
+
+impl Baseline {
+    pub fn get(&self, filter_name: &str) -> Option<&FilterBench> {
+        self.filters.iter().find(|f| f.filter_name == filter_name)
+    }
+}
+
+/// The baseline at `path`, or `None` if there is none yet.
+pub fn load(path: &Path) -> anyhow::Result<Option<Baseline>> {
+    let content = match std::fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(e).with_context(|| format!("reading {}", path.display())),
+    };
+    serde_json::from_str(&content)
+        .map(Some)
+        .with_context(|| format!("parsing {}", path.display()))
+}
+
+/// Save `results` as the baseline, keeping entries for filters that were not
+/// benchmarked this time.
+pub fn save(
+    path: &Path,
+    previous: Option<Baseline>,
+    results: &[FilterBench],
+) -> anyhow::Result<()> {
+    let mut baseline = previous.unwrap_or_default();
+    baseline
+        .filters
+        .retain(|old| !results.iter().any(|r| r.filter_name == old.filter_name));
+    baseline.filters.extend(results.iter().cloned());
+    baseline
+        .filters
+        .sort_by(|a, b| a.filter_name.cmp(&b.filter_name));
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(path, serde_json::to_string_pretty(&baseline)? + "\n")
+        .with_context(|| format!("writing {}", path.display()))
+}
+
+/// How a filter's numbers moved against its baseline entry.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct Change {
+    /// Change in p50 latency, in percent.
+    pub latency_pct: f64,
+    /// Change in peak allocation, in percent.
+    pub alloc_pct: f64,
+    /// Either grew by more than the `--fail-on-regress` threshold.
+    pub regressed: bool,
+}
+
+pub fn compare(current: &FilterBench, base: &FilterBench, threshold_pct: Option<f64>) -> Change {
+    #[allow(clippy::cast_precision_loss)] // bytes and nanoseconds: far below 2^52
+    let pct = |new: f64, old: f64| {
+        if old > 0.0 {
+            (new - old) / old * 100.0
+        } else {
+            0.0
+        }
+    };
+    #[allow(clippy::cast_precision_loss)]
+    let latency_pct = pct(current.p50_ns as f64, base.p50_ns as f64);
+    #[allow(clippy::cast_precision_loss)]
+    let alloc_pct = pct(
+        current.peak_alloc_bytes as f64,
+        base.peak_alloc_bytes as f64,
+    );
+    let regressed = threshold_pct.is_some_and(|limit| {
+        let slower =
+            latency_pct > limit && current.p50_ns.saturating_sub(base.p50_ns) >= LATENCY_NOISE_NS;
+        let bigger = alloc_pct > limit
+            && current
+                .peak_alloc_bytes
+                .saturating_sub(base.peak_alloc_bytes)
+                >= ALLOC_NOISE_BYTES;
+        slower || bigger
+    });
+    Change {
+        latency_pct,
+        alloc_pct,
+        regressed,
+    }
+}
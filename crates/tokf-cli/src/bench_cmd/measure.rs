@@ -0,0 +1,105 @@
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+
+use tokf::config;
+use tokf::filter;
+use tokf::runner::CommandResult;
+use tokf::suite_discovery::DiscoveredSuite;
+
+use super::alloc;
+use crate::verify_cmd::{load_case, load_fixture};
+
+/// Timings and heap use of one filter over its test fixtures.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FilterBench {
+    pub filter_name: String,
+    /// Test cases the filter was run on, each `iterations` times.
+    pub cases: usize,
+    /// Median time for one run of the filter on one fixture, in nanoseconds.
+    pub p50_ns: u64,
+    pub p95_ns: u64,
+    /// Most heap any single run held at once, in bytes.
+    pub peak_alloc_bytes: usize,
+}
+
+struct Input {
+    args: Vec<String>,
+    result: CommandResult,
+}
+
+/// Run the suite's filter over each of its fixtures `iterations` times, after
+/// one unmeasured warm-up run each.
+pub fn bench_suite(suite: &DiscoveredSuite, iterations: usize) -> anyhow::Result<FilterBench> {
+    let cfg = config::try_load_filter(&suite.filter_path)?
+        .ok_or_else(|| anyhow::anyhow!("filter not found: {}", suite.filter_path.display()))?;
+    let inputs = load_inputs(suite)?;
+    anyhow::ensure!(
+        !inputs.is_empty(),
+        "suite directory is empty: {}",
+        suite.suite_dir.display()
+    );
+
+    let options = filter::FilterOptions::default();
+    let run = |input: &Input| filter::apply(&cfg, &input.result, &input.args, &options);
+    for input in &inputs {
+        black_box(run(input));
+    }
+
+    let mut durations = Vec::with_capacity(inputs.len() * iterations);
+    let mut peak_alloc_bytes = 0;
+    for _ in 0..iterations {
+        for input in &inputs {
+            let started = Instant::now();
+            let (output, peak) = alloc::peak(|| black_box(run(input)));
+            durations.push(started.elapsed());
+            peak_alloc_bytes = peak_alloc_bytes.max(peak);
+            drop(output);
+        }
+    }
+    durations.sort_unstable();
+
+    Ok(FilterBench {
+        filter_name: suite.filter_name.clone(),
+        cases: inputs.len(),
+        p50_ns: nanos(percentile(&durations, 50)),
+        p95_ns: nanos(percentile(&durations, 95)),
+        peak_alloc_bytes,
+    })
+}
+
+fn load_inputs(suite: &DiscoveredSuite) -> anyhow::Result<Vec<Input>> {
+    let mut case_files: Vec<_> = std::fs::read_dir(&suite.suite_dir)?
+        .filter_map(Result::ok)
+        .map(|e| e.path())
+        .filter(|p| p.extension().is_some_and(|e| e == "toml"))
+        .collect();
+    case_files.sort();
+    case_files
+        .iter()
+        .map(|path| {
+            let case = load_case(path)?;
+            let combined = load_fixture(&case, path)?;
+            Ok(Input {
+                args: case.args,
+                result: CommandResult {
+                    stdout: String::new(),
+                    stderr: String::new(),
+                    exit_code: case.exit_code,
+                    combined,
+                },
+            })
+        })
+        .collect()
+}
+
+/// Nearest-rank percentile of sorted, non-empty `samples`.
+pub fn percentile(samples: &[Duration], pct: usize) -> Duration {
+    let rank = (samples.len() * pct).div_ceil(100).max(1);
+    samples[rank.min(samples.len()) - 1]
+}
+
+fn nanos(d: Duration) -> u64 {
+    u64::try_from(d.as_nanos()).unwrap_or(u64::MAX)
+}
@@ -0,0 +1,216 @@
+//! `tokf bench` — time filters over their test fixtures and catch
+//! regressions against a stored baseline.
+
+mod alloc;
+mod baseline;
+mod measure;
+
+use std::path::PathBuf;
+
+use serde::Serialize;
+
+use tokf::runtime::Runtime;
+use tokf::suite_discovery::{DiscoveredSuite, discover_suites};
+
+use crate::verify_cmd::{VerifyScope, verify_search_dirs};
+
+use baseline::{Baseline, Change};
+use measure::FilterBench;
+
+/// Baseline file, relative to the working directory.
+const DEFAULT_BASELINE: &str = ".tokf/bench.json";
+
+/// Args for `tokf bench`.
+#[derive(clap::Args, Debug, Clone)]
+pub struct BenchArgs {
+    /// Filter to benchmark (e.g. "cargo/build"). Omit to run all with a test suite.
+    pub filter: Option<String>,
+    /// Runs of each filter on each fixture
+    #[arg(long, short = 'n', default_value_t = 20, value_parser = clap::value_parser!(u16).range(1..))]
+    pub iterations: u16,
+    /// Restrict to a single filter scope (project, global, or stdlib)
+    #[arg(long, value_enum)]
+    pub scope: Option<VerifyScope>,
+    /// Baseline to compare against [default: .tokf/bench.json]
+    #[arg(long, value_name = "FILE")]
+    pub baseline: Option<PathBuf>,
+    /// Store this run's results in the baseline
+    #[arg(long)]
+    pub save_baseline: bool,
+    /// Exit 1 if a filter's p50 latency or peak allocation grew by more than PCT percent
+    #[arg(long, value_name = "PCT")]
+    pub fail_on_regress: Option<f64>,
+    /// Output results as JSON
+    #[arg(long)]
+    pub json: bool,
+}
+
+#[derive(Serialize)]
+struct BenchReport {
+    #[serde(flatten)]
+    bench: FilterBench,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    change: Option<Change>,
+}
+
+/// Entry point for the `tokf bench` subcommand.
+///
+/// Exit codes: 0 = done, 1 = regression, 2 = config/IO error.
+pub fn cmd_bench(rt: &Runtime, args: &BenchArgs) -> i32 {
+    let search_dirs = verify_search_dirs(rt, args.scope.as_ref());
+    let suites = discover_suites(&search_dirs, args.filter.as_deref());
+    if suites.is_empty() {
+        if let Some(name) = &args.filter {
+            eprintln!("[tokf] no test suite found for filter: {name}");
+            return 2;
+        }
+        eprintln!("[tokf] no test suites discovered");
+        return 0;
+    }
+
+    let baseline_path = baseline_path(rt, args);
+    let previous = match baseline::load(&baseline_path) {
+        Ok(previous) => previous,
+        Err(e) => {
+            eprintln!("[tokf] error: {e:#}");
+            return 2;
+        }
+    };
+    if args.fail_on_regress.is_some() && previous.is_none() {
+        eprintln!(
+            "[tokf] error: no baseline at {} — record one with --save-baseline",
+            baseline_path.display()
+        );
+        return 2;
+    }
+
+    let (results, has_error) = bench_all(&suites, usize::from(args.iterations));
+    let reports = reports(results, previous.as_ref(), args.fail_on_regress);
+    if args.json {
+        crate::output::print_json(&reports);
+    } else {
+        print_table(&reports);
+    }
+
+    if args.save_baseline {
+        let benches: Vec<FilterBench> = reports.iter().map(|r| r.bench.clone()).collect();
+        if let Err(e) = baseline::save(&baseline_path, previous, &benches) {
+            eprintln!("[tokf] error: {e:#}");
+            return 2;
+        }
+        eprintln!("[tokf] baseline saved to {}", baseline_path.display());
+    }
+
+    if has_error {
+        2
+    } else {
+        i32::from(
+            reports
+                .iter()
+                .any(|r| r.change.is_some_and(|c| c.regressed)),
+        )
+    }
+}
+
+fn baseline_path(rt: &Runtime, args: &BenchArgs) -> PathBuf {
+    args.baseline.clone().unwrap_or_else(|| {
+        rt.cwd().map_or_else(
+            || PathBuf::from(DEFAULT_BASELINE),
+            |cwd| cwd.join(DEFAULT_BASELINE),
+        )
+    })
+}
+
+/// Benchmark every suite; the flag is set if any of them failed to load.
+fn bench_all(suites: &[DiscoveredSuite], iterations: usize) -> (Vec<FilterBench>, bool) {
+    let mut results = Vec::new();
+    let mut has_error = false;
+    for suite in suites {
+        match measure::bench_suite(suite, iterations) {
+            Ok(bench) => results.push(bench),
+            Err(e) => {
+                eprintln!("[tokf] {} — error: {e:#}", suite.filter_name);
+                has_error = true;
+            }
+        }
+    }
+    (results, has_error)
+}
+
+fn reports(
+    results: Vec<FilterBench>,
+    previous: Option<&Baseline>,
+    threshold_pct: Option<f64>,
+) -> Vec<BenchReport> {
+    results
+        .into_iter()
+        .map(|bench| {
+            let change = previous
+                .and_then(|b| b.get(&bench.filter_name))
+                .map(|base| baseline::compare(&bench, base, threshold_pct));
+            BenchReport { bench, change }
+        })
+        .collect()
+}
+
+fn print_table(reports: &[BenchReport]) {
+    let width = reports
+        .iter()
+        .map(|r| r.bench.filter_name.len())
+        .max()
+        .unwrap_or(0)
+        .max("filter".len());
+    println!(
+        "  {:<width$}  {:>5}  {:>9}  {:>9}  {:>10}  vs baseline",
+        "filter", "cases", "p50", "p95", "peak alloc"
+    );
+    for report in reports {
+        let b = &report.bench;
+        let (icon, versus) = report.change.map_or_else(
+            || (" ", "-".to_string()),
+            |c| {
+                (
+                    if c.regressed { "\u{2717}" } else { " " },
+                    format!("{:+.1}% time, {:+.1}% alloc", c.latency_pct, c.alloc_pct),
+                )
+            },
+        );
+        println!(
+            "{icon} {:<width$}  {:>5}  {:>9}  {:>9}  {:>10}  {versus}",
+            b.filter_name,
+            b.cases,
+            format_nanos(b.p50_ns),
+            format_nanos(b.p95_ns),
+            format_bytes(b.peak_alloc_bytes),
+        );
+    }
+    let regressed = reports
+        .iter()
+        .filter(|r| r.change.is_some_and(|c| c.regressed))
+        .count();
+    if regressed > 0 {
+        println!("\n{regressed} filter(s) regressed against the baseline.");
+    }
+}
+
+#[allow(clippy::cast_precision_loss)] // display only
+fn format_nanos(ns: u64) -> String {
+    if ns < 1_000_000 {
+        format!("{:.0}\u{b5}s", ns as f64 / 1_000.0)
+    } else {
+        format!("{:.2}ms", ns as f64 / 1_000_000.0)
+    }
+}
+
+#[allow(clippy::cast_precision_loss)] // display only
+pub fn format_bytes(bytes: usize) -> String {
+    if bytes < 1024 * 1024 {
+        format!("{:.1} KiB", bytes as f64 / 1024.0)
+    } else {
+        format!("{:.1} MiB", bytes as f64 / (1024.0 * 1024.0))
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests;
@@ -0,0 +1,117 @@
+use std::time::Duration;
+
+use super::baseline::{self, Baseline};
+use super::measure::{FilterBench, percentile};
+use super::*;
+
+fn bench(name: &str, p50_ms: u64, peak_kib: usize) -> FilterBench {
+    FilterBench {
+        filter_name: name.to_string(),
+        cases: 2,
+        p50_ns: p50_ms * 1_000_000,
+        p95_ns: p50_ms * 1_000_000,
+        peak_alloc_bytes: peak_kib * 1024,
+    }
+}
+
+// --- percentiles ---
+
+#[test]
+fn percentile_is_nearest_rank() {
+    let samples: Vec<Duration> = (1..=20).map(Duration::from_millis).collect();
+    assert_eq!(percentile(&samples, 50), Duration::from_millis(10));
+    assert_eq!(percentile(&samples, 95), Duration::from_millis(19));
+    assert_eq!(
+        percentile(&[Duration::from_millis(7)], 95),
+        Duration::from_millis(7)
+    );
+}
+
+// --- comparison ---
+
+#[test]
+fn compare_flags_growth_past_the_threshold() {
+    let base = bench("cargo/build", 10, 512);
+    let change = baseline::compare(&bench("cargo/build", 12, 512), &base, Some(10.0));
+    assert!((change.latency_pct - 20.0).abs() < 1e-9);
+    assert!(change.regressed);
+
+    let change = baseline::compare(&bench("cargo/build", 10, 1024), &base, Some(10.0));
+    assert!((change.alloc_pct - 100.0).abs() < 1e-9);
+    assert!(change.regressed);
+
+    let change = baseline::compare(&bench("cargo/build", 10, 512), &base, Some(10.0));
+    assert!(!change.regressed);
+}
+
+#[test]
+fn compare_ignores_noise_sized_changes() {
+    let base = FilterBench {
+        p50_ns: 20_000,
+        ..bench("git/status", 0, 8)
+    };
+    let current = FilterBench {
+        p50_ns: 60_000,
+        ..bench("git/status", 0, 16)
+    };
+    let change = baseline::compare(&current, &base, Some(10.0));
+    assert!(change.latency_pct > 100.0);
+    assert!(!change.regressed);
+}
+
+#[test]
+fn compare_without_threshold_never_regresses() {
+    let change = baseline::compare(&bench("a", 50, 512), &bench("a", 1, 512), None);
+    assert!(!change.regressed);
+}
+
+#[test]
+fn reports_match_baseline_entries_by_name() {
+    let previous = Baseline {
+        filters: vec![bench("a", 1, 8)],
+    };
+    let reports = reports(
+        vec![bench("a", 1, 8), bench("b", 1, 8)],
+        Some(&previous),
+        None,
+    );
+    assert!(reports[0].change.is_some());
+    assert!(reports[1].change.is_none());
+}
+
+// --- baseline file ---
+
+#[test]
+fn save_keeps_filters_not_benchmarked_this_time() {
+    let dir = tempfile::TempDir::new().unwrap();
+    let path = dir.path().join(".tokf/bench.json");
+    assert!(baseline::load(&path).unwrap().is_none());
+
+    baseline::save(&path, None, &[bench("a", 1, 8), bench("b", 2, 8)]).unwrap();
+    let previous = baseline::load(&path).unwrap();
+    baseline::save(&path, previous, &[bench("b", 3, 8)]).unwrap();
+
+    let saved = baseline::load(&path).unwrap().unwrap();
+    assert_eq!(saved.filters, [bench("a", 1, 8), bench("b", 3, 8)]);
+}
+
+// --- formatting ---
+
+#[test]
+fn durations_and_sizes_are_human_readable() {
+    assert_eq!(format_nanos(42_000), "42\u{b5}s");
+    assert_eq!(format_nanos(51_250_000), "51.25ms");
+    assert_eq!(format_bytes(1536), "1.5 KiB");
+    assert_eq!(format_bytes(3 * 1024 * 1024), "3.0 MiB");
+}
+
+// --- allocation accounting ---
+
+#[test]
+fn peak_counts_memory_held_during_the_call() {
+    const SIZE: usize = 64 * 1024 * 1024;
+    let (len, peak) = super::alloc::peak(|| vec![1_u8; SIZE].len());
+    assert_eq!(len, SIZE);
+    // Other test threads allocate and free concurrently; allow for them.
+    assert!(peak >= SIZE / 2, "peak {peak}");
+}
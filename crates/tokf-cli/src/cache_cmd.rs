@@ -88,6 +88,7 @@ fn cmd_cache_info(rt: &Runtime, search_dirs: &[PathBuf]) -> i32 {
         Ok(manifest) => {
             println!("version: {}", manifest.version);
             println!("filters: {}", manifest.filters.len());
+            println!("files: {}", manifest.files.len());
             let valid = cache::is_cache_valid(&manifest, search_dirs);
             println!("valid: {valid}");
         }
@@ -31,6 +31,11 @@ pub struct Cli {
     #[arg(long, global = true)]
     pub no_cache: bool,
 
+    /// Kill the command after this many seconds and filter the output captured
+    /// so far. Overrides a filter's `timeout_secs`.
+    #[arg(long, global = true, value_name = "SECS")]
+    pub timeout: Option<u64>,
+
     /// Disable exit-code masking. By default tokf exits 0 and prepends
     /// "Error: Exit code N" to output when the underlying command fails.
     /// This flag restores real exit-code propagation.
@@ -74,19 +79,45 @@ pub enum Commands {
         /// Use whichever output is smaller: filtered or piped (no-op without --baseline-pipe)
         #[arg(long)]
         prefer_less: bool,
+        /// Run the command attached to a pseudo-terminal (same as `pty = true` in a filter)
+        #[arg(long)]
+        pty: bool,
+        /// Stdin for the command (overrides the filter's `stdin`)
+        #[arg(long, value_enum)]
+        stdin: Option<crate::run_cmd::StdinCli>,
+        /// Print the filtered output as is, or as a JSON object with run metadata
+        #[arg(long, value_enum, default_value_t)]
+        format: crate::run_envelope::RunFormat,
+        /// Also save the unfiltered output to this file (overrides the filter's `tee_raw_dir`)
+        #[arg(long, value_name = "PATH")]
+        tee_raw: Option<std::path::PathBuf>,
         #[arg(trailing_var_arg = true, required = true)]
         command_args: Vec<String>,
     },
+    /// Run a command filtered, then re-run it whenever a watched file changes
+    Watch(crate::watch_cmd::WatchArgs),
     /// Generate shell completion scripts
     Completions {
         /// Target shell (bash, zsh, fish, powershell, elvish, nushell)
         shell: crate::completions_cmd::ShellChoice,
     },
+    /// Print filter names, one per line (used by the completion scripts)
+    #[command(name = "__complete-filters", hide = true)]
+    CompleteFilters,
     /// Validate a filter TOML file
     Check {
         /// Path to the filter file
         filter_path: String,
     },
+    /// Check a filter for invalid regexes, unknown pipes, undefined template
+    /// variables, inconsistent sections and Lua syntax errors
+    Lint {
+        /// Path to the filter file
+        filter_path: String,
+        /// Output diagnostics as JSON
+        #[arg(long)]
+        json: bool,
+    },
     /// Apply a filter to a fixture file (formerly `test`)
     #[command(alias = "test-filter")]
     Apply {
@@ -97,9 +128,16 @@ pub enum Commands {
         /// Simulated exit code for branch selection
         #[arg(long, default_value_t = 0)]
         exit_code: i32,
+        /// Print a per-stage report (dropped lines, branch, sections, timings) to stderr
+        #[arg(long)]
+        trace: bool,
     },
     /// List available filters
-    Ls,
+    Ls {
+        /// Output as JSON, with each filter's source, priority and patterns
+        #[arg(long)]
+        json: bool,
+    },
     /// Rewrite a command string (apply filter-derived rules)
     Rewrite {
         /// The command string to rewrite
@@ -109,22 +147,57 @@ pub enum Commands {
     Which {
         /// The command string to look up (e.g. "git push origin main")
         command: String,
+        /// Output as JSON, with the matched pattern, consumed words and variants
+        #[arg(long)]
+        json: bool,
     },
     /// Show the TOML source of an active filter
     Show {
         /// Filter relative path without extension (e.g. "git/push")
         filter: String,
         /// Print the SHA-256 content hash of the filter (for identity verification or change detection)
-        #[arg(long)]
+        #[arg(long, conflicts_with_all = ["resolved", "json"])]
         hash: bool,
+        /// Print the filter as tokf loaded it, with `extends` applied, instead of its source
+        #[arg(long)]
+        resolved: bool,
+        /// Output the resolved filter as JSON, with its source, priority and hash
+        #[arg(long)]
+        json: bool,
+    },
+    /// Create a project `.tokf/` with a commented config.toml, an example
+    /// filter and rewrites.toml
+    Init {
+        /// Also install the Claude Code hook for this project
+        #[arg(long)]
+        hook: bool,
+        /// Overwrite files that already exist
+        #[arg(long)]
+        force: bool,
+    },
+    /// Draft a filter for a command from its output, with a test suite
+    New(crate::new_cmd::NewArgs),
+    /// Record a command's output as a test fixture
+    Fixture {
+        #[command(subcommand)]
+        action: crate::fixture_cmd::FixtureAction,
     },
     /// Copy a filter to your local or global config for customization
     Eject {
         /// Filter relative path without extension (e.g. "cargo/build")
         filter: String,
         /// Eject to global config dir instead of project-local .tokf/
-        #[arg(long)]
+        #[arg(long, conflicts_with_all = ["diff", "update"])]
         global: bool,
+        /// Show how the ejected copy differs from the built-in this tokf ships
+        #[arg(long, conflicts_with = "update")]
+        diff: bool,
+        /// Merge the built-in's changes since the eject into the ejected copy
+        #[arg(long)]
+        update: bool,
+        /// Disable colored `--diff` output (also respects the `NO_COLOR` environment variable)
+        #[arg(long, requires = "diff")]
+        no_color: bool,
     },
     /// Claude Code hook management
     Hook {
@@ -141,19 +214,43 @@ pub enum Commands {
         #[command(subcommand)]
         action: crate::cache_cmd::CacheAction,
     },
+    /// Maintain the tracking database
+    Db {
+        #[command(subcommand)]
+        action: crate::db_cmd::DbAction,
+    },
     /// View and modify tokf configuration
     Config {
         #[command(subcommand)]
         action: crate::config_cmd::ConfigAction,
     },
     /// Show token savings statistics
+    #[command(args_conflicts_with_subcommands = true)]
     Gain {
+        #[command(subcommand)]
+        action: Option<crate::commands::GainAction>,
         /// Show daily breakdown
         #[arg(long)]
         daily: bool,
         /// Show breakdown by filter
         #[arg(long, name = "by-filter")]
         by_filter: bool,
+        /// Show breakdown by project root
+        #[arg(long, name = "by-project")]
+        by_project: bool,
+        /// Show breakdown by command (first two words), to spot commands worth a filter
+        #[arg(long, name = "by-command")]
+        by_command: bool,
+        /// Show per filter how often the agent re-ran a command or asked for raw output
+        /// (recorded by the `PostToolUse` hook)
+        #[arg(long, conflicts_with_all = ["daily", "by-filter", "by-project", "by-command"])]
+        confusion: bool,
+        /// Show tokens saved as estimated dollars, using the `[pricing]` config
+        #[arg(long, conflicts_with_all = ["daily", "by-filter", "by-project", "by-command", "confusion"])]
+        cost: bool,
+        /// Price `--cost` for this model only (a key in `[pricing]`)
+        #[arg(long, requires = "cost", value_name = "MODEL")]
+        model: Option<String>,
         /// Output as JSON
         #[arg(long)]
         json: bool,
@@ -166,6 +263,15 @@ pub enum Commands {
         /// Disable colored output (also respects the `NO_COLOR` environment variable)
         #[arg(long)]
         no_color: bool,
+        /// Only count runs on or after this day (YYYY-MM-DD, UTC)
+        #[arg(long, global = true, value_name = "DATE", value_parser = tokf::tracking::parse_day)]
+        since: Option<String>,
+        /// Only count runs on or before this day (YYYY-MM-DD, UTC)
+        #[arg(long, global = true, value_name = "DATE", value_parser = tokf::tracking::parse_day)]
+        until: Option<String>,
+        /// Only count runs in the current day, week or month
+        #[arg(long, global = true, value_enum, conflicts_with_all = ["since", "until"])]
+        period: Option<crate::commands::PeriodCli>,
     },
     /// Manage filtered output history
     History {
@@ -177,6 +283,11 @@ pub enum Commands {
         /// "last" for most recent, or a numeric entry ID
         target: String,
     },
+    /// Explain how a filter produced a history entry — `tokf explain last` or `tokf explain <id>`
+    Explain {
+        /// "last" for most recent, or a numeric entry ID
+        target: String,
+    },
     /// Run declarative test suites for filters
     Verify {
         /// Filter name to test (e.g. "cargo/build"). Omit to run all.
@@ -197,6 +308,8 @@ pub enum Commands {
         #[arg(long)]
         safety: bool,
     },
+    /// Time filters over their test fixtures and compare against a baseline
+    Bench(crate::bench_cmd::BenchArgs),
     /// Show system paths, database locations, and filter counts
     Info {
         /// Output as JSON
@@ -225,23 +338,30 @@ pub enum Commands {
         /// Replace the test suite for an already-published filter (author-only)
         #[arg(long)]
         update_tests: bool,
+        /// Registry to publish to, by name or URL (default: the highest-priority one)
+        #[arg(long)]
+        registry: Option<String>,
+        /// Accept the MIT license without prompting (for CI)
+        #[arg(long)]
+        accept_license: bool,
+        /// Publish under a GitHub org you belong to
+        #[arg(long)]
+        org: Option<String>,
+        /// Make the filter visible only to you (and members of --org)
+        #[arg(long)]
+        private: bool,
     },
     /// Search the community filter registry
     Search {
-        /// Maximum number of results to return
-        #[arg(long, short = 'n', default_value_t = 20)]
-        limit: usize,
-        /// Output results as JSON
-        #[arg(long)]
-        json: bool,
-        /// Search query (matches command pattern)
-        #[arg(trailing_var_arg = true, required = true)]
-        query: Vec<String>,
+        #[command(flatten)]
+        args: crate::commands::SearchArgs,
     },
     /// Sync local usage data to the remote server
     Sync {
-        /// Show last sync time and count of pending events
-        #[arg(long)]
+        #[command(subcommand)]
+        action: Option<SyncAction>,
+        /// Same as `tokf sync status`
+        #[arg(long, hide = true)]
         status: bool,
     },
     /// Publish all stdlib filters to the registry (CI only)
@@ -278,6 +398,16 @@ pub enum Commands {
         #[command(subcommand)]
         action: TelemetryAction,
     },
+    /// Serve tokf to agents as Model Context Protocol tools
+    Mcp {
+        #[command(subcommand)]
+        action: McpAction,
+    },
+    /// Keep filter patterns compiled in memory for `tokf hook handle`
+    Daemon {
+        #[command(subcommand)]
+        action: DaemonAction,
+    },
     /// Detect filters that may be causing agent confusion (post-hoc analysis of tracking.db)
     Doctor(crate::commands::DoctorArgs),
     /// Find missed token savings in Claude Code sessions
@@ -346,9 +476,13 @@ pub enum Commands {
     /// Install a filter from the community registry
     Install {
         /// Filter hash (64 hex chars) or command pattern to search for
-        filter: String,
-        /// Install to project-local .tokf/filters/ instead of global config
+        #[arg(required = true, num_args = 1..)]
+        filter: Vec<String>,
+        /// Install to the global config dir instead of the project's .tokf/filters/
         #[arg(long)]
+        global: bool,
+        /// Install to the project's .tokf/filters/ (the default)
+        #[arg(long, hide = true, conflicts_with = "global")]
         local: bool,
         /// Overwrite an existing filter at the same path
         #[arg(long)]
@@ -359,6 +493,20 @@ pub enum Commands {
         /// Skip confirmation prompts (Lua filters still emit an audit warning)
         #[arg(long, short = 'y')]
         yes: bool,
+        /// Install from this registry, by name or URL (default: the first one that has it)
+        #[arg(long)]
+        registry: Option<String>,
+    },
+    /// Update filters installed from the community registry
+    Update {
+        /// Installed filters to update (e.g. "git/push"); all when omitted
+        filters: Vec<String>,
+        /// List outdated filters without updating; exits 1 if any are found
+        #[arg(long)]
+        check: bool,
+        /// Update without asking (Lua filters still emit an audit warning)
+        #[arg(long, short = 'y', conflicts_with = "check")]
+        yes: bool,
     },
 }
 
@@ -372,6 +520,22 @@ pub enum TelemetryAction {
     },
 }
 
+#[derive(Subcommand)]
+pub enum McpAction {
+    /// Speak MCP over stdio, with the `run_filtered`, `history_show` and `which_filter` tools
+    Serve,
+}
+
+#[derive(Subcommand)]
+pub enum DaemonAction {
+    /// Serve hook rewrites on a unix socket in the foreground until stopped
+    Run,
+    /// Stop the running daemon
+    Stop,
+    /// Show whether a daemon is running and what it holds
+    Status,
+}
+
 #[derive(Subcommand)]
 pub enum SkillAction {
     /// Install skill files to .claude/skills/tokf-filter/ (project-local or global)
@@ -392,6 +556,68 @@ pub enum AuthAction {
     Status,
     /// Permanently delete your account (requires confirmation)
     DeleteAccount,
+    /// Manage the ed25519 key used to sign published filters
+    Keys {
+        #[command(subcommand)]
+        action: KeysAction,
+    },
+    /// Manage personal access tokens for CI
+    Token {
+        #[command(subcommand)]
+        action: TokenAction,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum KeysAction {
+    /// Generate a signing key and register it with the server
+    Generate {
+        /// Replace an existing local key
+        #[arg(long)]
+        force: bool,
+    },
+    /// Register the local signing key with the server (e.g. after a failed generate)
+    Register,
+    /// Show the local signing key's public key and fingerprint
+    Show,
+    /// List the signing keys registered to your account
+    List,
+    /// Revoke a registered key so it can no longer sign new publishes
+    Revoke {
+        /// Public key or fingerprint of the key to revoke
+        key: String,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum TokenAction {
+    /// Mint a scoped, expiring token and print it to stdout
+    Create {
+        /// What the token is for (e.g. "github-actions")
+        name: String,
+        /// Grant a scope: read, publish, sync or stdlib (repeatable)
+        #[arg(long = "scope", required = true)]
+        scopes: Vec<String>,
+        /// Days until the token expires (default 90, at most 365)
+        #[arg(long)]
+        expires_in_days: Option<u32>,
+    },
+    /// List your unexpired tokens
+    List,
+    /// Revoke a token by id
+    Revoke {
+        /// Token id, as shown by `tokf auth token list`
+        id: i64,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum SyncAction {
+    /// Show pending events, sync cursor, machine, server and token expiry
+    Status,
+    /// Tell the server this machine's tracking database started over (e.g.
+    /// after a reimage), so new events are not skipped as already synced
+    ResetMachine,
 }
 
 #[derive(Subcommand)]
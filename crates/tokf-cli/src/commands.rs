@@ -1,18 +1,14 @@
 use std::path::{Path, PathBuf};
 
-use clap::Subcommand;
-use tokf::baseline;
+use clap::{Subcommand, ValueEnum as _};
 use tokf::config;
 use tokf::filter;
-use tokf::history;
 use tokf::hook;
 use tokf::rewrite;
 use tokf::runner;
 use tokf::skill;
-use tokf::telemetry;
 
 use crate::Cli;
-use crate::marker;
 use crate::resolve;
 
 use tokf::runtime::Runtime;
@@ -51,6 +47,8 @@ pub enum HookFormat {
     Cursor,
     #[value(name = "codex")]
     Codex,
+    #[value(name = "windsurf")]
+    Windsurf,
 }
 
 /// CLI surface for `tokf doctor --sort`. Mirrors `tokf::doctor::SortBy`
@@ -67,6 +65,27 @@ pub enum SortByCli {
     Tokens,
 }
 
+/// CLI surface for `tokf gain --period`, mirroring `tokf::tracking::Period`.
+#[derive(clap::ValueEnum, Clone, Copy, Debug)]
+pub enum PeriodCli {
+    /// Today (UTC)
+    Day,
+    /// Since Monday
+    Week,
+    /// Since the first of the month
+    Month,
+}
+
+impl From<PeriodCli> for tokf::tracking::Period {
+    fn from(v: PeriodCli) -> Self {
+        match v {
+            PeriodCli::Day => Self::Day,
+            PeriodCli::Week => Self::Week,
+            PeriodCli::Month => Self::Month,
+        }
+    }
+}
+
 impl From<SortByCli> for tokf::doctor::SortBy {
     fn from(v: SortByCli) -> Self {
         match v {
@@ -77,6 +96,74 @@ impl From<SortByCli> for tokf::doctor::SortBy {
     }
 }
 
+/// CLI surface for `tokf search --sort`, mirroring
+/// `tokf::remote::filter_client::SearchSort`.
+#[derive(clap::ValueEnum, Clone, Copy, Default, Debug)]
+pub enum SearchSortCli {
+    /// Savings weighted by usage volume
+    #[default]
+    Relevance,
+    /// Most downloaded first
+    Downloads,
+    /// Most recently published first
+    Recent,
+    /// Most starred first
+    Stars,
+}
+
+impl From<SearchSortCli> for tokf::remote::filter_client::SearchSort {
+    fn from(v: SearchSortCli) -> Self {
+        match v {
+            SearchSortCli::Relevance => Self::Relevance,
+            SearchSortCli::Downloads => Self::Downloads,
+            SearchSortCli::Recent => Self::Recent,
+            SearchSortCli::Stars => Self::Stars,
+        }
+    }
+}
+
+/// Args for `tokf search`, flattened into `Commands::Search`.
+#[derive(clap::Args, Debug, Clone)]
+pub struct SearchArgs {
+    /// Maximum number of results to return
+    #[arg(long, short = 'n', default_value_t = 20)]
+    pub limit: usize,
+    /// Output results as JSON
+    #[arg(long)]
+    pub json: bool,
+    /// Search only this registry, by name or URL (default: all of them)
+    #[arg(long)]
+    pub registry: Option<String>,
+    /// Result order
+    #[arg(long, value_enum, default_value_t)]
+    pub sort: SearchSortCli,
+    /// Only filters by this author
+    #[arg(long)]
+    pub author: Option<String>,
+    /// Only standard-library filters
+    #[arg(long)]
+    pub stdlib: bool,
+    /// Only filters for this command and its subcommands (e.g. `git`)
+    #[arg(long)]
+    pub command: Option<String>,
+    /// Continue a previous search from the cursor it printed
+    #[arg(long)]
+    pub cursor: Option<String>,
+    /// Also list filters their authors have yanked
+    #[arg(long)]
+    pub include_yanked: bool,
+    /// Only filters published under this GitHub org
+    #[arg(long)]
+    pub org: Option<String>,
+    /// Search query (matches command pattern); optional with --author,
+    /// --stdlib, --command or --org
+    #[arg(
+        trailing_var_arg = true,
+        required_unless_present_any = ["author", "stdlib", "command", "org"]
+    )]
+    pub query: Vec<String>,
+}
+
 /// Args for `tokf doctor`. Defined here (not inline in `main.rs`) so the
 /// per-flag doc comments don't push `main.rs` over the 700-line hard
 /// limit. `#[command(flatten)]` in the `Commands::Doctor` variant inlines
@@ -111,6 +198,12 @@ pub struct DoctorArgs {
     /// Sort the per-filter table by `health` (default), `bursts`, or `tokens`
     #[arg(long, value_enum, default_value_t = SortByCli::Health)]
     pub sort: SortByCli,
+    /// Check the installation instead: database, cache, hooks, login, registry and filter files
+    #[arg(long, conflicts_with_all = ["filter", "project", "all", "include_noise"])]
+    pub setup: bool,
+    /// With --setup, skip the registry request
+    #[arg(long, requires = "setup")]
+    pub offline: bool,
 }
 
 #[derive(Subcommand)]
@@ -120,7 +213,16 @@ pub enum HookAction {
         /// Hook protocol format (default: claude-code)
         #[arg(long, value_enum, default_value_t = HookFormat::ClaudeCode)]
         format: HookFormat,
+        /// Use the adapter in `.tokf/hooks/<TOOL>.toml` instead of a built-in format
+        #[arg(long, value_name = "TOOL", conflicts_with = "format")]
+        adapter: Option<String>,
     },
+    /// Record agent feedback on filters from a Claude Code `PostToolUse` hook
+    /// (reads JSON from stdin)
+    Feedback,
+    /// Print this repo's token savings as session context from a Claude Code
+    /// `SessionStart` hook
+    SessionStart,
     /// Install the integration for the target tool
     Install {
         /// Install globally instead of project-local
@@ -137,6 +239,28 @@ pub enum HookAction {
         #[arg(long)]
         no_context: bool,
     },
+    /// Remove the integration for the target tool: its scripts, files and
+    /// tokf entries in the tool's hook config
+    Uninstall {
+        /// Uninstall the global integration instead of the project-local one
+        #[arg(long)]
+        global: bool,
+        /// Target tool to uninstall (default: claude-code)
+        #[arg(long, value_enum, default_value_t = HookTool::ClaudeCode)]
+        tool: HookTool,
+    },
+    /// Show which integrations are installed, tool by tool
+    Status {
+        /// Show the global integrations instead of the project-local ones
+        #[arg(long)]
+        global: bool,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum GainAction {
+    /// Export the gain tables as CSV or Markdown
+    Export(crate::gain_export::ExportArgs),
 }
 
 #[derive(Subcommand)]
@@ -158,6 +282,30 @@ pub enum HistoryAction {
         #[arg(long)]
         raw: bool,
     },
+    /// Show what a filter removed from a history entry, as a unified diff of
+    /// the raw and filtered output
+    Diff {
+        /// Entry ID to diff
+        id: i64,
+        /// Print only a one-line summary of removed, kept and added lines
+        #[arg(long)]
+        stat: bool,
+        /// Disable colored output (also respects the `NO_COLOR` environment variable)
+        #[arg(long)]
+        no_color: bool,
+    },
+    /// Run an entry's raw output through the filter its command resolves to
+    /// now and show the recorded and new filtered output side by side
+    Refilter {
+        /// Entry ID to re-filter
+        id: i64,
+        /// Use this filter instead of the one the command resolves to
+        #[arg(long)]
+        filter: Option<String>,
+    },
+    /// Save an entry's raw output as a fixture in a filter's test suite, with
+    /// a case for its exit code
+    ExportFixture(crate::fixture_cmd::ExportArgs),
     /// Show the most recent history entry (current project by default)
     Last {
         /// Print only the raw captured output (no metadata, no filtered output)
@@ -169,7 +317,8 @@ pub enum HistoryAction {
     },
     /// Search history by command or output content (current project by default)
     Search {
-        /// Search query (searches command, raw output, and filtered output)
+        /// Full-text query over command, raw output, and filtered output;
+        /// supports `"phrases"`, `OR`, `NOT` and `prefix*`
         query: String,
         /// Maximum number of results to show (default: 10)
         #[arg(short, long, default_value_t = 10)]
@@ -178,6 +327,14 @@ pub enum HistoryAction {
         #[arg(short, long)]
         all: bool,
     },
+    /// Show how many entries history holds and the disk space they take
+    Stats,
+    /// Delete the entries the `[history]` retention limits no longer allow
+    Prune {
+        /// Report what would be deleted without deleting it
+        #[arg(long)]
+        dry_run: bool,
+    },
     /// Clear history entries (current project by default)
     Clear {
         /// Clear history for all projects — this is destructive and cannot be undone
@@ -193,248 +350,6 @@ pub fn or_exit(r: anyhow::Result<i32>) -> i32 {
     })
 }
 
-/// What `tokf run` was asked to execute.
-#[derive(Clone, Copy)]
-pub struct RunRequest<'a> {
-    pub command_args: &'a [String],
-    pub baseline_pipe: Option<&'a str>,
-    pub prefer_less: bool,
-}
-
-// NOTE: cmd_run integrates command resolution, execution, output rendering, tracking,
-// history recording, and telemetry. Splitting would require threading 6+ values through helpers.
-// Approved to exceed the 60-line limit.
-#[allow(clippy::too_many_lines)]
-pub fn cmd_run(
-    rt: &Runtime,
-    request: RunRequest<'_>,
-    cli: &Cli,
-    reporter: &dyn telemetry::TelemetryReporter,
-) -> anyhow::Result<i32> {
-    let RunRequest {
-        command_args,
-        baseline_pipe,
-        prefer_less,
-    } = request;
-    let filter_match = if cli.no_filter {
-        None
-    } else {
-        resolve::find_filter(rt, command_args, cli.verbose, cli.no_cache)?
-    };
-
-    let words_consumed = filter_match.as_ref().map_or(0, |m| m.words_consumed);
-    let remaining_args: Vec<String> = if words_consumed > 0 {
-        command_args[words_consumed..].to_vec()
-    } else if command_args.len() > 1 {
-        command_args[1..].to_vec()
-    } else {
-        vec![]
-    };
-
-    // Phase A.5: resolve args-pattern variants before passthrough check.
-    // If an args variant matches, its config replaces the parent (including
-    // its passthrough_args), so the passthrough check below uses the correct set.
-    let filter_match =
-        filter_match.map(|m| resolve::resolve_args_variants(m, &remaining_args, cli.verbose));
-
-    let passthrough = filter_match
-        .as_ref()
-        .is_some_and(|m| m.config.should_passthrough(&remaining_args));
-    if passthrough && cli.verbose {
-        eprintln!("[tokf] passthrough: user args match passthrough_args, skipping filter");
-    }
-    let filter_cfg = if passthrough {
-        None
-    } else {
-        filter_match.as_ref().map(|m| &m.config)
-    };
-    let matched_command = filter_match.as_ref().map(|m| m.matched_command.as_str());
-    let (cmd_result, executed_command) = resolve::run_command(
-        rt,
-        resolve::ResolvedCommand {
-            filter_cfg,
-            words_consumed,
-            matched_command,
-            command_args,
-            remaining_args: &remaining_args,
-            verbose: cli.verbose,
-        },
-    )?;
-
-    let filter_match = if passthrough { None } else { filter_match };
-    let Some(filter_match) = filter_match else {
-        if prefer_less && cli.verbose {
-            eprintln!("[tokf] --prefer-less has no effect: no matching filter found");
-        }
-        let raw_len = cmd_result.combined.len();
-        let input_bytes = match baseline_pipe {
-            Some(pipe_cmd) => baseline::compute(&cmd_result.combined, pipe_cmd),
-            None => raw_len,
-        };
-        let mask = !cli.no_mask_exit_code && cmd_result.exit_code != 0;
-        if mask {
-            println!("Error: Exit code {}", cmd_result.exit_code);
-        }
-        if !cmd_result.combined.is_empty() {
-            println!("{}", cmd_result.combined);
-        }
-        // filter_time_ms = 0: no filter was applied, not 0ms of filtering.
-        // Passthrough commands are not recorded to history: raw == filtered would
-        // waste storage and add noise with nothing useful to compare.
-        // output_bytes = raw_len: what tokf actually printed (full raw output).
-        resolve::record_run(
-            rt,
-            command_args,
-            None,
-            None,
-            input_bytes,
-            raw_len,
-            raw_len,
-            0,
-            cmd_result.exit_code,
-            false,
-        );
-        resolve::try_auto_sync(rt);
-        reporter.report(&telemetry::TelemetryEvent::new(
-            rt,
-            None,
-            command_args.join(" "),
-            input_bytes,
-            raw_len,
-            raw_len,
-            &cmd_result.combined,
-            &cmd_result.combined,
-            std::time::Duration::ZERO,
-            cmd_result.exit_code,
-        ));
-        if cli.no_mask_exit_code {
-            return Ok(cmd_result.exit_code);
-        }
-        return Ok(0);
-    };
-
-    // Phase B: resolve deferred output-pattern variants using the already-discovered
-    // filter list (no second discovery call needed).
-    let (cfg, filter_hash) =
-        resolve::resolve_phase_b(filter_match, &cmd_result.combined, cli.verbose);
-
-    // Compute piped output once: when prefer_less is active we need the full text
-    // for comparison, otherwise just the byte count for tracking.
-    let (input_bytes, piped_text) = match baseline_pipe {
-        Some(pipe_cmd) if prefer_less => {
-            let text = baseline::compute_output(&cmd_result.combined, pipe_cmd);
-            let bytes = text.as_ref().map_or(cmd_result.combined.len(), String::len);
-            (bytes, text)
-        }
-        Some(pipe_cmd) => (baseline::compute(&cmd_result.combined, pipe_cmd), None),
-        None => (cmd_result.combined.len(), None),
-    };
-
-    let start = std::time::Instant::now();
-    let filter_opts = filter::FilterOptions {
-        preserve_color: cli.preserve_color,
-    };
-    let filtered = filter::apply(&cfg, &cmd_result, &remaining_args, &filter_opts);
-    let elapsed = start.elapsed();
-
-    if cli.timing {
-        eprintln!("[tokf] filter took {:.1}ms", elapsed.as_secs_f64() * 1000.0);
-    }
-
-    // --prefer-less: compare filtered output with cached piped output, use whichever is smaller.
-    let (final_output, output_bytes, pipe_override) =
-        if let Some(piped) = piped_text.filter(|t| t.len() < filtered.output.len()) {
-            if cli.verbose {
-                eprintln!(
-                    "[tokf] prefer-less: pipe output ({} bytes) < filtered ({} bytes), using pipe",
-                    piped.len(),
-                    filtered.output.len()
-                );
-            }
-            let len = piped.len();
-            (piped, len, true)
-        } else {
-            let len = filtered.output.len();
-            (filtered.output, len, false)
-        };
-
-    let filter_name = cfg.command.first();
-    let command_str = command_args.join(" ");
-    let raw_bytes = cmd_result.combined.len();
-
-    if cli.verbose {
-        eprintln!(
-            "[tokf] accounting: raw={raw_bytes}B baseline={input_bytes}B filtered={output_bytes}B"
-        );
-    }
-
-    resolve::record_run(
-        rt,
-        command_args,
-        Some(filter_name),
-        Some(&filter_hash),
-        input_bytes,
-        output_bytes,
-        raw_bytes,
-        elapsed.as_millis(),
-        cmd_result.exit_code,
-        pipe_override,
-    );
-    resolve::try_auto_sync(rt);
-
-    // Detect whether to show the history hint:
-    //   - filter author opted in via `show_history_hint = true`, or
-    //   - the same command was re-run (LLM confusion signal: it didn't act on
-    //     the previous filtered output and is asking again).
-    // Check the DB before recording so we compare against the *previous* run.
-    let show_hint = cfg.show_history_hint || history::try_was_recently_run(rt, &command_str);
-
-    let history_id = history::try_record(
-        rt,
-        &history::RecordedRun {
-            command: &command_str,
-            executed_command: executed_command.as_deref(),
-            filter_name,
-            raw_output: &cmd_result.combined,
-            filtered_output: &final_output,
-            exit_code: cmd_result.exit_code,
-        },
-    );
-
-    let render_cfg = marker::load_render_config(rt);
-
-    let mask = !cli.no_mask_exit_code && cmd_result.exit_code != 0;
-    if mask {
-        println!("Error: Exit code {}", cmd_result.exit_code);
-    }
-    if !final_output.is_empty() {
-        marker::print_with_indicator(&final_output, &render_cfg, history_id);
-    }
-
-    if show_hint && let Some(id) = history_id {
-        println!("🗜️ compressed — run `tokf raw {id}` for full output");
-    }
-
-    reporter.report(&telemetry::TelemetryEvent::new(
-        rt,
-        Some(filter_name.to_string()),
-        command_str,
-        input_bytes,
-        output_bytes,
-        raw_bytes,
-        &cmd_result.combined,
-        &final_output,
-        elapsed,
-        cmd_result.exit_code,
-    ));
-
-    if cli.no_mask_exit_code {
-        Ok(cmd_result.exit_code)
-    } else {
-        Ok(0)
-    }
-}
-
 pub fn cmd_check(filter_path: &Path) -> i32 {
     match config::try_load_filter(filter_path) {
         Ok(Some(cfg)) => {
@@ -460,6 +375,7 @@ pub fn cmd_apply(
     filter_path: &Path,
     fixture_path: &Path,
     exit_code: i32,
+    trace: bool,
     cli: &Cli,
 ) -> anyhow::Result<i32> {
     let cfg = config::try_load_filter(filter_path)?
@@ -477,10 +393,14 @@ pub fn cmd_apply(
     };
 
     let start = std::time::Instant::now();
-    let filter_opts = filter::FilterOptions {
-        preserve_color: cli.preserve_color,
+    let cwd = std::env::current_dir().ok();
+    let filter_opts = resolve::filter_options(&cfg, cwd.as_deref(), cli.preserve_color);
+    let (filtered, report) = if trace {
+        let (filtered, report) = filter::trace::apply_traced(&cfg, &cmd_result, &[], &filter_opts);
+        (filtered, Some(report))
+    } else {
+        (filter::apply(&cfg, &cmd_result, &[], &filter_opts), None)
     };
-    let filtered = filter::apply(&cfg, &cmd_result, &[], &filter_opts);
     let elapsed = start.elapsed();
 
     if cli.timing {
@@ -492,51 +412,11 @@ pub fn cmd_apply(
     if !filtered.output.is_empty() {
         println!("{}", filtered.output);
     }
-
-    Ok(0)
-}
-
-// Note: cmd_ls and cmd_which always use the cache. The --no-cache flag
-// only affects `tokf run`. Pass --no-cache to `tokf run` if you need uncached resolution.
-pub fn cmd_ls(rt: &Runtime, verbose: bool) -> i32 {
-    let Ok(filters) = resolve::discover_filters(rt, false) else {
-        eprintln!("[tokf] error: failed to discover filters");
-        return 1;
-    };
-
-    for filter in &filters {
-        // Display: relative path without .toml extension  →  command  (description)
-        let display_name = filter
-            .relative_path
-            .with_extension("")
-            .display()
-            .to_string();
-        let desc_suffix = filter
-            .config
-            .description
-            .as_deref()
-            .map_or(String::new(), |d| format!("  ({d})"));
-        println!(
-            "{display_name}  \u{2192}  {}{desc_suffix}",
-            filter.config.command.first()
-        );
-
-        if verbose {
-            eprintln!(
-                "[tokf]   source: {}  [{}]",
-                filter.source_path.display(),
-                filter.priority_label()
-            );
-            let patterns = filter.config.command.patterns();
-            if patterns.len() > 1 {
-                for p in patterns {
-                    eprintln!("[tokf]     pattern: \"{p}\"");
-                }
-            }
-        }
+    if let Some(report) = report {
+        eprint!("{}", crate::explain_cmd::render_report(&report));
     }
 
-    0
+    Ok(0)
 }
 
 pub fn cmd_rewrite(rt: &Runtime, command: &str, verbose: bool) -> i32 {
@@ -558,15 +438,20 @@ pub fn cmd_skill_install(rt: &Runtime, global: bool) -> i32 {
 pub fn cmd_hook_handle(
     rt: &Runtime,
     format: &HookFormat,
+    adapter: Option<&str>,
     no_cache: bool,
     no_mask_exit_code: bool,
 ) -> i32 {
-    let outcome = match format {
-        HookFormat::ClaudeCode => hook::handle(rt, no_cache, no_mask_exit_code),
-        HookFormat::Gemini => hook::handle_gemini(rt, no_cache, no_mask_exit_code),
-        HookFormat::Cursor => hook::handle_cursor(rt, no_cache, no_mask_exit_code),
-        HookFormat::Codex => hook::handle_codex(rt, no_cache, no_mask_exit_code),
-    };
+    let outcome = adapter.map_or_else(
+        || match format {
+            HookFormat::ClaudeCode => hook::handle(rt, no_cache, no_mask_exit_code),
+            HookFormat::Gemini => hook::handle_gemini(rt, no_cache, no_mask_exit_code),
+            HookFormat::Cursor => hook::handle_cursor(rt, no_cache, no_mask_exit_code),
+            HookFormat::Codex => hook::handle_codex(rt, no_cache, no_mask_exit_code),
+            HookFormat::Windsurf => hook::windsurf::handle(rt, no_cache, no_mask_exit_code),
+        },
+        |tool| hook::adapter::handle(rt, tool, no_cache, no_mask_exit_code),
+    );
     hook_outcome_exit_code(format, outcome)
 }
 
@@ -601,7 +486,7 @@ pub fn cmd_hook_install(
         HookTool::GeminiCli => hook::gemini::install(rt, global, &tokf_bin, install_context),
         HookTool::Cursor => hook::cursor::install(rt, global, &tokf_bin, install_context),
         HookTool::Cline => hook::cline::install(global),
-        HookTool::Windsurf => hook::windsurf::install(global),
+        HookTool::Windsurf => hook::windsurf::install(rt, global, &tokf_bin),
         HookTool::Copilot => hook::copilot::install(global),
         HookTool::Aider => hook::aider::install(rt, global),
     };
@@ -614,6 +499,75 @@ pub fn cmd_hook_install(
     }
 }
 
+fn hook_footprint(
+    rt: &Runtime,
+    global: bool,
+    tool: &HookTool,
+) -> anyhow::Result<hook::uninstall::Footprint> {
+    match tool {
+        HookTool::ClaudeCode => hook::uninstall::claude_code_footprint(rt, global),
+        HookTool::OpenCode => hook::opencode::footprint(global),
+        HookTool::Codex => hook::codex::footprint(rt, global),
+        HookTool::GeminiCli => hook::gemini::footprint(rt, global),
+        HookTool::Cursor => hook::cursor::footprint(rt, global),
+        HookTool::Cline => hook::cline::footprint(global),
+        HookTool::Windsurf => hook::windsurf::footprint(rt, global),
+        HookTool::Copilot => Ok(hook::copilot::footprint(global)),
+        HookTool::Aider => hook::aider::footprint(rt, global),
+    }
+}
+
+fn tool_name(tool: &HookTool) -> String {
+    tool.to_possible_value()
+        .map_or_else(String::new, |v| v.get_name().to_string())
+}
+
+pub fn cmd_hook_uninstall(rt: &Runtime, global: bool, tool: &HookTool) -> i32 {
+    let result = hook_footprint(rt, global, tool).and_then(|f| f.uninstall());
+    match result {
+        Ok(changed) if changed.is_empty() => {
+            eprintln!("[tokf] {} integration not installed", tool_name(tool));
+            0
+        }
+        Ok(changed) => {
+            eprintln!("[tokf] {} integration removed", tool_name(tool));
+            for path in changed {
+                eprintln!("[tokf]   {}", path.display());
+            }
+            0
+        }
+        Err(e) => {
+            eprintln!("[tokf] hook uninstall failed: {e:#}");
+            1
+        }
+    }
+}
+
+pub fn cmd_hook_status(rt: &Runtime, global: bool) -> i32 {
+    for tool in HookTool::value_variants() {
+        let name = tool_name(tool);
+        let footprint = match hook_footprint(rt, global, tool) {
+            Ok(f) => f,
+            Err(e) => {
+                println!("{name}: unknown ({e:#})");
+                continue;
+            }
+        };
+        let rows = footprint.status();
+        if rows.is_empty() {
+            println!("{name}: not supported");
+        } else if !footprint.is_installed() {
+            println!("{name}: not installed");
+        } else {
+            println!("{name}: installed");
+            for (kind, path, state) in rows {
+                println!("  {kind:<8} {} ({})", path.display(), state.label());
+            }
+        }
+    }
+    0
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -652,6 +606,14 @@ mod tests {
         );
     }
 
+    #[test]
+    fn windsurf_deny_outcome_exits_two_so_cascade_blocks() {
+        assert_eq!(
+            hook_outcome_exit_code(&HookFormat::Windsurf, hook::HookOutcome::Deny),
+            2
+        );
+    }
+
     #[test]
     fn codex_deny_outcome_exits_zero_so_codex_reads_json_block() {
         assert_eq!(
@@ -4,6 +4,21 @@ use clap::CommandFactory;
 use clap_complete::Generator;
 
 use crate::Cli;
+use crate::resolve;
+use tokf::runtime::Runtime;
+
+// Filter-name completion for `show`, `eject` and `verify`. clap's static
+// scripts only know fixed values, so each snippet asks the binary through
+// the hidden `tokf __complete-filters` command.
+const BASH_FILTERS: &str = include_str!("../templates/completions/filters.bash");
+const ZSH_FILTERS: &str = include_str!("../templates/completions/filters.zsh");
+const FISH_FILTERS: &str = include_str!("../templates/completions/filters.fish");
+const POWERSHELL_FILTERS: &str = include_str!("../templates/completions/filters.ps1");
+
+/// The line of clap's PowerShell script the filter snippet goes before: by
+/// then `$command` names the subcommand and `$completions` holds clap's
+/// candidates.
+const POWERSHELL_ANCHOR: &str = "    $completions.Where{";
 
 #[derive(clap::ValueEnum, Clone, Copy, Debug)]
 pub enum ShellChoice {
@@ -24,11 +39,51 @@ pub fn cmd_completions(shell: ShellChoice) -> i32 {
     0
 }
 
+/// Print every filter name, one per line, for the completion scripts.
+pub fn cmd_complete_filters(rt: &Runtime) -> i32 {
+    let Ok(filters) = resolve::discover_installed_filters(rt, false) else {
+        return 1;
+    };
+    let mut names: Vec<String> = filters
+        .iter()
+        .map(|f| f.relative_path.with_extension("").display().to_string())
+        .collect();
+    names.sort();
+    names.dedup();
+    for name in names {
+        println!("{name}");
+    }
+    0
+}
+
 fn generate_to_writer(
     shell: ShellChoice,
     cmd: &mut clap::Command,
     name: &str,
     out: &mut dyn io::Write,
+) {
+    let mut buf = Vec::new();
+    generate_static(shell, cmd, name, &mut buf);
+    let script = String::from_utf8_lossy(&buf);
+    let script = match shell {
+        ShellChoice::Bash => format!("{script}{BASH_FILTERS}"),
+        ShellChoice::Zsh => format!("{script}{ZSH_FILTERS}"),
+        ShellChoice::Fish => format!("{script}{FISH_FILTERS}"),
+        ShellChoice::PowerShell => script.replacen(
+            POWERSHELL_ANCHOR,
+            &format!("{POWERSHELL_FILTERS}{POWERSHELL_ANCHOR}"),
+            1,
+        ),
+        ShellChoice::Elvish | ShellChoice::Nushell => script.into_owned(),
+    };
+    let _ = out.write_all(script.as_bytes());
+}
+
+fn generate_static(
+    shell: ShellChoice,
+    cmd: &mut clap::Command,
+    name: &str,
+    out: &mut dyn io::Write,
 ) {
     match shell {
         ShellChoice::Bash => clap_complete::generate(clap_complete::Shell::Bash, cmd, name, out),
@@ -69,6 +124,7 @@ fn print_install_hint(shell: ShellChoice) {
 }
 
 #[cfg(test)]
+#[allow(clippy::unwrap_used)]
 mod tests {
     use super::*;
 
@@ -99,4 +155,29 @@ mod tests {
             );
         }
     }
+
+    #[test]
+    fn filter_names_are_completed_dynamically() {
+        let shells = [
+            ShellChoice::Bash,
+            ShellChoice::Zsh,
+            ShellChoice::Fish,
+            ShellChoice::PowerShell,
+        ];
+        for shell in shells {
+            let output = String::from_utf8(generate_completions(shell)).unwrap();
+            assert!(
+                output.contains("tokf __complete-filters"),
+                "no filter completion for {shell:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn powershell_snippet_runs_before_candidates_are_filtered() {
+        let output = String::from_utf8(generate_completions(ShellChoice::PowerShell)).unwrap();
+        let snippet = output.find("__complete-filters").unwrap();
+        let anchor = output.find(POWERSHELL_ANCHOR).unwrap();
+        assert!(snippet < anchor);
+    }
 }
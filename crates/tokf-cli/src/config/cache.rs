@@ -1,3 +1,4 @@
+use std::collections::HashMap;
 use std::io::Write;
 use std::path::{Path, PathBuf};
 use std::sync::OnceLock;
@@ -5,13 +6,17 @@ use std::time::SystemTime;
 
 use anyhow::Context;
 use rkyv::{Archive, Deserialize, Serialize, rancor};
+use sha2::{Digest, Sha256};
 
 use super::types::FilterConfig;
-use super::{ResolvedFilter, discover_all_filters};
+use super::{
+    ResolvedFilter, discover_all_filters, discover_filter_files, embedded_filters, extends,
+    rank_filters,
+};
 use crate::runner::shell_escape;
 use crate::runtime::Runtime;
 
-const CACHE_VERSION: u32 = 11;
+const CACHE_VERSION: u32 = 13;
 
 /// A single filter serialized for the binary cache.
 ///
@@ -31,12 +36,49 @@ pub struct CachedFilter {
     pub priority: u8,
 }
 
-/// The on-disk binary manifest: version guard, mtime fingerprints, and the filter list.
+/// A base filter file an `extends` chain read, fingerprinted by its contents.
+#[derive(Debug, Clone, Archive, Serialize, Deserialize)]
+pub struct CachedBase {
+    /// Path stored as a UTF-8 string, like [`CachedFilter::source_path`].
+    pub path: String,
+    /// First 8 bytes of the SHA-256 of the file's contents.
+    pub content_hash: u64,
+}
+
+/// A `.toml` file under a search dir, fingerprinted by its contents.
+#[derive(Debug, Clone, Archive, Serialize, Deserialize)]
+pub struct CachedFile {
+    /// Path stored as a UTF-8 string, like [`CachedFilter::source_path`].
+    pub path: String,
+    /// First 8 bytes of the SHA-256 of the file's contents.
+    pub content_hash: u64,
+    /// The file uses `extends`, so its resolved config also depends on its
+    /// bases; it is re-parsed whenever any file changes.
+    pub extends: bool,
+    /// The base files its `extends` chain read. They may live outside the
+    /// search dirs (e.g. `.tokf/common.toml`), so they are checked separately.
+    pub bases: Vec<CachedBase>,
+    /// The parsed filter, or `None` for partials and files that fail to parse.
+    pub filter: Option<CachedFilter>,
+}
+
+/// The on-disk binary manifest: version guard, per-file content hashes, and
+/// the resolved filter list.
+///
+/// Content hashes rather than mtimes decide validity, so git checkouts and
+/// network filesystems that do not preserve mtimes neither serve stale
+/// filters nor force needless rebuilds.
 #[derive(Debug, Archive, Serialize, Deserialize)]
 pub struct ResolvedManifest {
     pub version: u32,
-    /// `(dir_path_string, mtime_nanos_since_epoch)` for each search dir plus `"<binary>"`.
-    pub dir_mtimes: Vec<(String, u64)>,
+    /// Search dirs the manifest was built for, in priority order.
+    pub search_dirs: Vec<String>,
+    /// mtime of the tokf binary in nanoseconds: a new build may change the
+    /// embedded stdlib or the config schema.
+    pub binary_mtime: u64,
+    /// Every `.toml` file under the search dirs, in discovery order.
+    pub files: Vec<CachedFile>,
+    /// The resolved filters (embedded stdlib included), ranked and deduplicated.
     pub filters: Vec<CachedFilter>,
 }
 
@@ -77,10 +119,7 @@ pub fn cache_path(rt: &Runtime, search_dirs: &[PathBuf]) -> Option<PathBuf> {
 }
 
 /// Return the mtime of `path` as nanoseconds since the Unix epoch, or 0 on error.
-///
-/// Nanosecond precision ensures that sub-second file writes are detected on
-/// high-resolution filesystems (APFS, ext4 with `noatime`).
-fn dir_mtime(path: &Path) -> u64 {
+fn file_mtime(path: &Path) -> u64 {
     std::fs::metadata(path)
         .and_then(|m| m.modified())
         .ok()
@@ -94,24 +133,98 @@ fn dir_mtime(path: &Path) -> u64 {
 
 fn binary_mtime() -> u64 {
     static CACHE: OnceLock<u64> = OnceLock::new();
-    *CACHE.get_or_init(|| std::env::current_exe().ok().as_deref().map_or(0, dir_mtime))
+    *CACHE.get_or_init(|| {
+        std::env::current_exe()
+            .ok()
+            .as_deref()
+            .map_or(0, file_mtime)
+    })
+}
+
+fn content_hash(data: &[u8]) -> u64 {
+    let digest = Sha256::digest(data);
+    let mut prefix = [0u8; 8];
+    prefix.copy_from_slice(&digest[..8]);
+    u64::from_le_bytes(prefix)
 }
 
-fn compute_mtimes(search_dirs: &[PathBuf]) -> Vec<(String, u64)> {
-    let mut mtimes: Vec<(String, u64)> = search_dirs
+fn dir_strings(search_dirs: &[PathBuf]) -> Vec<String> {
+    search_dirs
         .iter()
-        .map(|d| (d.to_string_lossy().into_owned(), dir_mtime(d)))
-        .collect();
-    mtimes.push(("<binary>".to_string(), binary_mtime()));
-    mtimes
+        .map(|d| d.to_string_lossy().into_owned())
+        .collect()
+}
+
+/// A filter file read from disk.
+struct ScannedFile {
+    /// Index of its search dir.
+    priority: usize,
+    path: PathBuf,
+    content: Vec<u8>,
+    content_hash: u64,
+}
+
+/// Read and hash every `.toml` file under `search_dirs`. Unreadable files
+/// are skipped, as discovery skips them.
+fn scan_files(search_dirs: &[PathBuf]) -> Vec<ScannedFile> {
+    let mut files = Vec::new();
+    for (priority, dir) in search_dirs.iter().enumerate() {
+        for path in discover_filter_files(dir) {
+            let Ok(content) = std::fs::read(&path) else {
+                continue;
+            };
+            files.push(ScannedFile {
+                priority,
+                path,
+                content_hash: content_hash(&content),
+                content,
+            });
+        }
+    }
+    files
+}
+
+/// Whether `manifest` was written by this binary for these search dirs.
+fn header_matches(manifest: &ResolvedManifest, search_dirs: &[PathBuf]) -> bool {
+    manifest.version == CACHE_VERSION
+        && manifest.binary_mtime == binary_mtime()
+        && manifest.search_dirs == dir_strings(search_dirs)
+}
+
+/// The content hash of the file at `path`, or `None` if it cannot be read.
+fn file_hash(path: &Path) -> Option<u64> {
+    std::fs::read(path).ok().map(|data| content_hash(&data))
+}
+
+/// Whether `manifest` lists exactly the scanned files, with the same contents,
+/// and every base they extend is unchanged.
+fn files_match(manifest: &ResolvedManifest, scanned: &[ScannedFile]) -> bool {
+    manifest.files.len() == scanned.len()
+        && manifest.files.iter().zip(scanned).all(|(cached, file)| {
+            cached.content_hash == file.content_hash
+                && Path::new(&cached.path) == file.path
+                && cached
+                    .bases
+                    .iter()
+                    .all(|base| file_hash(Path::new(&base.path)) == Some(base.content_hash))
+        })
+}
+
+/// A fingerprint of the filter files under `search_dirs` and of this binary:
+/// it changes whenever discovery could give a different result.
+pub fn fingerprint(search_dirs: &[PathBuf]) -> u64 {
+    let mut hasher = Sha256::new();
+    hasher.update(binary_mtime().to_le_bytes());
+    for file in scan_files(search_dirs) {
+        hasher.update(file.path.as_os_str().as_encoded_bytes());
+        hasher.update(file.content_hash.to_le_bytes());
+    }
+    content_hash(&hasher.finalize())
 }
 
 /// Returns true iff the cached manifest is still valid for the given search dirs.
 pub fn is_cache_valid(manifest: &ResolvedManifest, search_dirs: &[PathBuf]) -> bool {
-    if manifest.version != CACHE_VERSION {
-        return false;
-    }
-    manifest.dir_mtimes == compute_mtimes(search_dirs)
+    header_matches(manifest, search_dirs) && files_match(manifest, &scan_files(search_dirs))
 }
 
 /// Load a previously written manifest from disk.
@@ -125,15 +238,90 @@ pub fn load_manifest(path: &Path) -> anyhow::Result<ResolvedManifest> {
         .map_err(|e| anyhow::anyhow!("deserialize cache: {e}"))
 }
 
+/// Parse one scanned file into its manifest entry and filter.
+fn parse_file(
+    search_dirs: &[PathBuf],
+    file: ScannedFile,
+) -> anyhow::Result<(CachedFile, Option<ResolvedFilter>)> {
+    let content = std::str::from_utf8(&file.content).ok();
+    // Same test `extends::parse_filter` uses to skip composition.
+    let uses_extends = content.is_some_and(|c| c.contains("extends"));
+    let origin = extends::Origin::File(file.path.clone());
+    let mut base_paths = Vec::new();
+    let config =
+        content.and_then(|c| extends::parse_filter_with_bases(c, &origin, &mut base_paths).ok());
+    let bases = base_paths
+        .iter()
+        .map(|p| CachedBase {
+            path: p.to_string_lossy().into_owned(),
+            content_hash: file_hash(p).unwrap_or_default(),
+        })
+        .collect();
+    let path = file.path.to_string_lossy().into_owned();
+    let filter = config.map(|config| {
+        ResolvedFilter::from_file(
+            config,
+            &search_dirs[file.priority],
+            file.path,
+            file.priority,
+        )
+    });
+    let entry = CachedFile {
+        path,
+        content_hash: file.content_hash,
+        extends: uses_extends,
+        bases,
+        filter: filter.as_ref().map(filter_to_cached).transpose()?,
+    };
+    Ok((entry, filter))
+}
+
+/// Parse the scanned files, reusing the entry of `previous` for every file
+/// whose contents are unchanged and that does not use `extends`.
+fn resolve_files(
+    search_dirs: &[PathBuf],
+    scanned: Vec<ScannedFile>,
+    previous: Option<ResolvedManifest>,
+) -> anyhow::Result<(Vec<CachedFile>, Vec<ResolvedFilter>)> {
+    let mut reusable: HashMap<String, CachedFile> = previous
+        .map(|m| m.files)
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|f| !f.extends)
+        .map(|f| (f.path.clone(), f))
+        .collect();
+    let mut entries = Vec::with_capacity(scanned.len());
+    let mut filters = Vec::new();
+    for file in scanned {
+        let key = file.path.to_string_lossy();
+        if let Some(entry) = reusable
+            .remove(key.as_ref())
+            .filter(|e| e.content_hash == file.content_hash)
+            && let Ok(filter) = entry.filter.clone().map(cached_to_filter).transpose()
+        {
+            entries.push(entry);
+            filters.extend(filter);
+            continue;
+        }
+        let (entry, filter) = parse_file(search_dirs, file)?;
+        entries.push(entry);
+        filters.extend(filter);
+    }
+    Ok((entries, filters))
+}
+
 fn write_manifest(
     path: &Path,
-    filters: &[ResolvedFilter],
     search_dirs: &[PathBuf],
+    files: Vec<CachedFile>,
+    filters: &[ResolvedFilter],
 ) -> anyhow::Result<()> {
     let cached: anyhow::Result<Vec<CachedFilter>> = filters.iter().map(filter_to_cached).collect();
     let manifest = ResolvedManifest {
         version: CACHE_VERSION,
-        dir_mtimes: compute_mtimes(search_dirs),
+        search_dirs: dir_strings(search_dirs),
+        binary_mtime: binary_mtime(),
+        files,
         filters: cached?,
     };
     let data = rkyv::to_bytes::<rancor::Error>(&manifest)
@@ -239,14 +427,17 @@ pub fn generate_shims(rt: &Runtime, filters: &[ResolvedFilter]) {
 ///
 /// Flow:
 /// 1. Determine cache path; if none, fall through to `discover_all_filters`.
-/// 2. Try to load and validate the cached manifest; on hit, return immediately.
-/// 3. On miss: call `discover_all_filters`, attempt to persist the result, then return.
+/// 2. Read and hash every filter file. If the cached manifest lists the same
+///    files with the same hashes, return its filters immediately.
+/// 3. Otherwise re-parse only the changed files (plus any that use
+///    `extends`), re-rank with the stdlib, attempt to persist the result,
+///    then return.
 ///
 /// Cache write failures are logged to stderr but never propagated.
 ///
 /// # Errors
 ///
-/// Returns `Err` only if `discover_all_filters` itself fails (unexpected I/O error).
+/// Returns `Err` only if discovery itself fails (unexpected I/O error).
 pub fn discover_with_cache(
     rt: &Runtime,
     search_dirs: &[PathBuf],
@@ -255,9 +446,12 @@ pub fn discover_with_cache(
         return discover_all_filters(search_dirs);
     };
 
-    if let Ok(manifest) = load_manifest(&path)
-        && is_cache_valid(&manifest, search_dirs)
-    {
+    let scanned = scan_files(search_dirs);
+    let mut previous = load_manifest(&path)
+        .ok()
+        .filter(|m| header_matches(m, search_dirs));
+
+    if let Some(manifest) = previous.take_if(|m| files_match(m, &scanned)) {
         let result: anyhow::Result<Vec<ResolvedFilter>> =
             manifest.filters.into_iter().map(cached_to_filter).collect();
         if let Ok(filters) = result {
@@ -270,9 +464,11 @@ pub fn discover_with_cache(
         // JSON deserialization failed — fall through to a full rebuild
     }
 
-    let filters = discover_all_filters(search_dirs)?;
+    let (files, mut filters) = resolve_files(search_dirs, scanned, previous)?;
+    filters.extend(embedded_filters());
+    let filters = rank_filters(filters);
     generate_shims(rt, &filters);
-    if let Err(e) = write_manifest(&path, &filters, search_dirs) {
+    if let Err(e) = write_manifest(&path, search_dirs, files, &filters) {
         eprintln!("[tokf] cache write failed ({}): {e:#}", path.display());
         eprintln!(
             "[tokf] hint: check permissions on {}; use --no-cache to skip, \
@@ -285,377 +481,5 @@ pub fn discover_with_cache(
 
 #[cfg(test)]
 #[allow(clippy::unwrap_used, clippy::expect_used)]
-mod tests {
-    use std::fs;
-
-    use tempfile::TempDir;
-
-    use super::*;
-
-    fn make_resolved_filter(command: &str, priority: u8) -> ResolvedFilter {
-        let config: FilterConfig = toml::from_str(&format!("command = \"{command}\"")).unwrap();
-        let hash = tokf_common::hash::canonical_hash(&config).unwrap_or_default();
-        ResolvedFilter {
-            config,
-            hash,
-            source_path: PathBuf::from(format!("/fake/{command}.toml")),
-            relative_path: PathBuf::from(format!("{command}.toml")),
-            priority,
-        }
-    }
-
-    #[test]
-    fn roundtrip_serialize_deserialize() {
-        let rf = make_resolved_filter("echo test", 0);
-        let cached = filter_to_cached(&rf).unwrap();
-        let manifest = ResolvedManifest {
-            version: CACHE_VERSION,
-            dir_mtimes: vec![("<binary>".to_string(), 42)],
-            filters: vec![cached],
-        };
-        let data = rkyv::to_bytes::<rancor::Error>(&manifest).unwrap();
-        let manifest2 = rkyv::from_bytes::<ResolvedManifest, rancor::Error>(&data).unwrap();
-
-        assert_eq!(manifest2.version, CACHE_VERSION);
-        assert_eq!(manifest2.filters.len(), 1);
-        assert_eq!(manifest2.dir_mtimes, vec![("<binary>".to_string(), 42u64)]);
-
-        let rf2 = cached_to_filter(manifest2.filters.into_iter().next().unwrap()).unwrap();
-        assert_eq!(rf2.config.command.first(), "echo test");
-    }
-
-    #[test]
-    fn stale_on_version_mismatch() {
-        let manifest = ResolvedManifest {
-            version: 0, // wrong version
-            dir_mtimes: compute_mtimes(&[]),
-            filters: vec![],
-        };
-        assert!(!is_cache_valid(&manifest, &[]));
-    }
-
-    #[test]
-    fn stale_on_dir_mtime_change() {
-        let tmp = TempDir::new().unwrap();
-        let filters_dir = tmp.path().join("filters");
-        fs::create_dir_all(&filters_dir).unwrap();
-        let search_dirs = vec![filters_dir.clone()];
-
-        let manifest = ResolvedManifest {
-            version: CACHE_VERSION,
-            dir_mtimes: compute_mtimes(&search_dirs),
-            filters: vec![],
-        };
-        assert!(is_cache_valid(&manifest, &search_dirs));
-
-        // Brief pause then write a file to update the directory mtime
-        std::thread::sleep(std::time::Duration::from_millis(10));
-        fs::write(filters_dir.join("new.toml"), "command = \"new\"").unwrap();
-
-        assert!(!is_cache_valid(&manifest, &search_dirs));
-    }
-
-    #[test]
-    fn cache_path_project_local() {
-        let tmp = TempDir::new().unwrap();
-        let tokf_dir = tmp.path().join(".tokf");
-        fs::create_dir_all(&tokf_dir).unwrap();
-        let search_dirs = vec![tokf_dir.join("filters")];
-
-        let rt = Runtime::isolated();
-        let path = cache_path(&rt, &search_dirs).unwrap();
-        assert!(path.starts_with(&tokf_dir));
-        assert!(path.ends_with("cache/manifest.bin"));
-    }
-
-    #[test]
-    fn cache_path_user_fallback() {
-        // A parent path that definitely doesn't exist on disk.
-        let rt = Runtime::isolated();
-        let search_dirs = vec![PathBuf::from("/tokf_test_nonexistent_dir/.tokf/filters")];
-        let path = cache_path(&rt, &search_dirs);
-
-        assert_eq!(
-            path,
-            Some(rt.user_cache_dir().unwrap().join("manifest.bin"))
-        );
-    }
-
-    #[test]
-    fn cache_path_respects_tokf_home() {
-        let rt = Runtime::builder().home("/custom/tokf_home").build();
-        let search_dirs = vec![PathBuf::from("/tokf_test_nonexistent_dir/.tokf/filters")];
-        let path = cache_path(&rt, &search_dirs);
-
-        assert_eq!(
-            path,
-            Some(PathBuf::from("/custom/tokf_home/manifest.bin")),
-            "cache path should be under TOKF_HOME when set"
-        );
-    }
-
-    #[test]
-    fn write_failure_does_not_propagate() {
-        let tmp = TempDir::new().unwrap();
-        let tokf_dir = tmp.path().join(".tokf");
-        fs::create_dir_all(&tokf_dir).unwrap();
-        // Block cache dir creation by placing a regular file at that path
-        fs::write(tokf_dir.join("cache"), b"not a directory").unwrap();
-
-        let search_dirs = vec![tokf_dir.join("filters")];
-        let rt = Runtime::isolated();
-        let result = discover_with_cache(&rt, &search_dirs);
-        assert!(result.is_ok());
-    }
-
-    fn write_payloads_in_parallel(path: &Path, payloads: &[Vec<u8>]) {
-        std::thread::scope(|scope| {
-            let handles = payloads
-                .iter()
-                .map(|payload| {
-                    let path = &path;
-                    scope.spawn(move || write_manifest_bytes(path, payload))
-                })
-                .collect::<Vec<_>>();
-
-            for handle in handles {
-                handle.join().unwrap().unwrap();
-            }
-        });
-    }
-
-    fn leftover_paths(dir: &Path, final_path: &Path) -> Vec<PathBuf> {
-        fs::read_dir(dir)
-            .unwrap()
-            .map(|entry| entry.unwrap().path())
-            .filter(|entry_path| entry_path != final_path)
-            .collect()
-    }
-
-    #[test]
-    fn manifest_write_allows_parallel_writers() {
-        let tmp = TempDir::new().unwrap();
-        let path = tmp.path().join("manifest.bin");
-        let payloads = (0..32).map(|i| vec![i; 128 * 1024]).collect::<Vec<_>>();
-
-        write_payloads_in_parallel(&path, &payloads);
-
-        let data = fs::read(&path).unwrap();
-        assert!(
-            payloads.iter().any(|payload| payload.as_slice() == data),
-            "final manifest should be one complete writer payload"
-        );
-
-        let leftovers = leftover_paths(tmp.path(), &path);
-        assert!(
-            leftovers.is_empty(),
-            "temporary files should be cleaned up: {leftovers:?}"
-        );
-    }
-
-    #[test]
-    fn cached_filter_roundtrip() {
-        let config: FilterConfig = toml::from_str("command = \"git push\"").unwrap();
-        let hash = tokf_common::hash::canonical_hash(&config).unwrap_or_default();
-        let rf = ResolvedFilter {
-            config,
-            hash: hash.clone(),
-            source_path: PathBuf::from("/some/path/push.toml"),
-            relative_path: PathBuf::from("git/push.toml"),
-            priority: 1,
-        };
-        let cached = filter_to_cached(&rf).unwrap();
-        let rf2 = cached_to_filter(cached).unwrap();
-
-        assert_eq!(rf2.config.command.first(), "git push");
-        assert_eq!(rf2.source_path, PathBuf::from("/some/path/push.toml"));
-        assert_eq!(rf2.relative_path, PathBuf::from("git/push.toml"));
-        assert_eq!(rf2.priority, 1);
-        assert_eq!(rf2.hash, hash);
-    }
-
-    #[test]
-    fn hash_survives_cache_roundtrip() {
-        let config: FilterConfig = toml::from_str("command = \"cargo test\"").unwrap();
-        let expected_hash = tokf_common::hash::canonical_hash(&config).unwrap();
-        let rf = ResolvedFilter {
-            config,
-            hash: expected_hash.clone(),
-            source_path: PathBuf::from("/fake/cargo/test.toml"),
-            relative_path: PathBuf::from("cargo/test.toml"),
-            priority: 0,
-        };
-
-        let cached = filter_to_cached(&rf).unwrap();
-        assert_eq!(
-            cached.hash, expected_hash,
-            "hash must survive filter_to_cached"
-        );
-
-        let rf2 = cached_to_filter(cached).unwrap();
-        assert_eq!(
-            rf2.hash, expected_hash,
-            "hash must survive cached_to_filter"
-        );
-    }
-
-    #[test]
-    fn binary_sentinel_in_mtimes() {
-        let mtimes = compute_mtimes(&[]);
-        assert!(mtimes.iter().any(|(k, _)| k == "<binary>"));
-    }
-
-    #[test]
-    fn generate_shims_creates_scripts() {
-        let tmp = TempDir::new().unwrap();
-        let rt = Runtime::builder().home(tmp.path()).build();
-
-        let filters = vec![
-            make_resolved_filter("git push", 0),
-            make_resolved_filter("cargo test", 0),
-            make_resolved_filter("git commit", 0), // git should be deduped
-        ];
-        generate_shims(&rt, &filters);
-
-        let shims = rt.shims_dir().unwrap();
-        assert!(shims.exists());
-
-        // git and cargo should have shims
-        let git_shim = shims.join("git");
-        let cargo_shim = shims.join("cargo");
-        assert!(git_shim.exists(), "git shim should exist");
-        assert!(cargo_shim.exists(), "cargo shim should exist");
-
-        // tokf should NOT have a shim
-        assert!(!shims.join("tokf").exists(), "tokf shim must not exist");
-
-        // Check content
-        let content = fs::read_to_string(&git_shim).unwrap();
-        assert!(content.starts_with("#!/bin/sh\n"));
-        assert!(
-            content.contains("-c 'git'"),
-            "shim should use shell mode with escaped command: {content}"
-        );
-        assert!(content.contains("\"$@\""));
-
-        // Check permissions on unix
-        #[cfg(unix)]
-        {
-            use std::os::unix::fs::PermissionsExt;
-            let mode = fs::metadata(&git_shim).unwrap().permissions().mode();
-            assert_eq!(mode & 0o755, 0o755, "shim should be executable");
-        }
-    }
-
-    #[test]
-    fn generate_shims_clean_slate() {
-        let tmp = TempDir::new().unwrap();
-        let rt = Runtime::builder().home(tmp.path()).build();
-
-        // Create a stale shim
-        let shims = rt.shims_dir().unwrap();
-        fs::create_dir_all(&shims).unwrap();
-        fs::write(shims.join("stale_cmd"), "old").unwrap();
-
-        let filters = vec![make_resolved_filter("git push", 0)];
-        generate_shims(&rt, &filters);
-
-        // Stale shim should be gone
-        assert!(!shims.join("stale_cmd").exists());
-        assert!(shims.join("git").exists());
-    }
-
-    #[test]
-    fn generate_shims_includes_make_and_just() {
-        let tmp = TempDir::new().unwrap();
-        let rt = Runtime::builder().home(tmp.path()).build();
-
-        let filters = vec![
-            make_resolved_filter("make build", 0),
-            make_resolved_filter("just test", 0),
-        ];
-        generate_shims(&rt, &filters);
-
-        let shims = rt.shims_dir().unwrap();
-        assert!(shims.join("make").exists(), "make shim should exist");
-        assert!(shims.join("just").exists(), "just shim should exist");
-    }
-
-    #[test]
-    fn generate_shims_extracts_basename() {
-        let tmp = TempDir::new().unwrap();
-        let rt = Runtime::builder().home(tmp.path()).build();
-
-        let filters = vec![make_resolved_filter("/usr/bin/git push", 0)];
-        generate_shims(&rt, &filters);
-
-        let shims = rt.shims_dir().unwrap();
-        assert!(shims.join("git").exists(), "basename should be extracted");
-        // Only the basename "git" should exist, not "usr" or any other directory
-        let entries: Vec<_> = fs::read_dir(&shims)
-            .unwrap()
-            .filter_map(Result::ok)
-            .map(|e| e.file_name().to_string_lossy().into_owned())
-            .collect();
-        assert_eq!(entries, vec!["git"], "only basename shim should exist");
-    }
-
-    #[test]
-    fn generate_shims_cleans_up_when_disabled() {
-        let tmp = TempDir::new().unwrap();
-        let rt = Runtime::builder().home(tmp.path()).build();
-
-        // Create shims directory with existing shim scripts
-        let shims = rt.shims_dir().unwrap();
-        fs::create_dir_all(&shims).unwrap();
-        fs::write(shims.join("git"), "#!/bin/sh\nold shim").unwrap();
-        fs::write(shims.join("cargo"), "#!/bin/sh\nold shim").unwrap();
-        assert!(shims.exists());
-
-        // Write global config disabling shims
-        let config_path = rt.user_dir().unwrap().join("config.toml");
-        fs::create_dir_all(config_path.parent().unwrap()).unwrap();
-        fs::write(&config_path, "[shims]\nenabled = false\n").unwrap();
-
-        let filters = vec![make_resolved_filter("git push", 0)];
-        generate_shims(&rt, &filters);
-
-        // Shims directory should be removed
-        assert!(
-            !shims.exists(),
-            "shims directory should be removed when disabled"
-        );
-    }
-
-    #[test]
-    fn stale_cache_triggers_rebuild() {
-        let tmp = TempDir::new().unwrap();
-        let tokf_dir = tmp.path().join(".tokf");
-        let filters_dir = tokf_dir.join("filters");
-        fs::create_dir_all(&filters_dir).unwrap();
-
-        fs::write(filters_dir.join("first.toml"), "command = \"first cmd\"").unwrap();
-        let search_dirs = vec![filters_dir.clone()];
-        let rt = Runtime::isolated();
-
-        // First run: populates cache
-        let filters1 = discover_with_cache(&rt, &search_dirs).unwrap();
-        let count1 = filters1
-            .iter()
-            .filter(|f| f.priority < crate::config::STDLIB_PRIORITY)
-            .count();
-        assert_eq!(count1, 1);
-
-        // Brief pause then add a new filter (updates dir mtime)
-        std::thread::sleep(std::time::Duration::from_millis(10));
-        fs::write(filters_dir.join("second.toml"), "command = \"second cmd\"").unwrap();
-
-        // Second run: cache is stale, rebuilds with both filters
-        let filters2 = discover_with_cache(&rt, &search_dirs).unwrap();
-        let count2 = filters2
-            .iter()
-            .filter(|f| f.priority < crate::config::STDLIB_PRIORITY)
-            .count();
-        assert_eq!(count2, 2);
-    }
-}
+#[path = "cache_tests.rs"]
+mod tests;
@@ -0,0 +1,541 @@
+use std::fs;
+
+use tempfile::TempDir;
+
+use super::*;
+
+fn make_resolved_filter(command: &str, priority: u8) -> ResolvedFilter {
+    let config: FilterConfig = toml::from_str(&format!("command = \"{command}\"")).unwrap();
+    let hash = tokf_common::hash::canonical_hash(&config).unwrap_or_default();
+    ResolvedFilter {
+        config,
+        hash,
+        source_path: PathBuf::from(format!("/fake/{command}.toml")),
+        relative_path: PathBuf::from(format!("{command}.toml")),
+        priority,
+    }
+}
+
+#[test]
+fn roundtrip_serialize_deserialize() {
+    let rf = make_resolved_filter("echo test", 0);
+    let cached = filter_to_cached(&rf).unwrap();
+    let manifest = ResolvedManifest {
+        version: CACHE_VERSION,
+        search_dirs: vec!["/fake".to_string()],
+        binary_mtime: 42,
+        files: vec![CachedFile {
+            path: "/fake/echo test.toml".to_string(),
+            content_hash: 7,
+            extends: false,
+            bases: vec![],
+            filter: Some(cached.clone()),
+        }],
+        filters: vec![cached],
+    };
+    let data = rkyv::to_bytes::<rancor::Error>(&manifest).unwrap();
+    let manifest2 = rkyv::from_bytes::<ResolvedManifest, rancor::Error>(&data).unwrap();
+
+    assert_eq!(manifest2.version, CACHE_VERSION);
+    assert_eq!(manifest2.filters.len(), 1);
+    assert_eq!(manifest2.binary_mtime, 42);
+    assert_eq!(manifest2.files[0].content_hash, 7);
+
+    let rf2 = cached_to_filter(manifest2.filters.into_iter().next().unwrap()).unwrap();
+    assert_eq!(rf2.config.command.first(), "echo test");
+}
+
+fn manifest_for(search_dirs: &[PathBuf]) -> ResolvedManifest {
+    let scanned = scan_files(search_dirs);
+    let (files, _) = resolve_files(search_dirs, scanned, None).unwrap();
+    ResolvedManifest {
+        version: CACHE_VERSION,
+        search_dirs: dir_strings(search_dirs),
+        binary_mtime: binary_mtime(),
+        files,
+        filters: vec![],
+    }
+}
+
+#[test]
+fn stale_on_version_mismatch() {
+    let manifest = ResolvedManifest {
+        version: 0, // wrong version
+        ..manifest_for(&[])
+    };
+    assert!(!is_cache_valid(&manifest, &[]));
+}
+
+#[test]
+fn stale_on_binary_change() {
+    let manifest = ResolvedManifest {
+        binary_mtime: binary_mtime().wrapping_add(1),
+        ..manifest_for(&[])
+    };
+    assert!(!is_cache_valid(&manifest, &[]));
+}
+
+#[test]
+fn stale_on_search_dirs_change() {
+    let manifest = manifest_for(&[]);
+    assert!(!is_cache_valid(&manifest, &[PathBuf::from("/elsewhere")]));
+}
+
+#[test]
+fn stale_on_added_file() {
+    let tmp = TempDir::new().unwrap();
+    let filters_dir = tmp.path().join("filters");
+    fs::create_dir_all(&filters_dir).unwrap();
+    let search_dirs = vec![filters_dir.clone()];
+
+    let manifest = manifest_for(&search_dirs);
+    assert!(is_cache_valid(&manifest, &search_dirs));
+
+    fs::write(filters_dir.join("new.toml"), "command = \"new\"").unwrap();
+    assert!(!is_cache_valid(&manifest, &search_dirs));
+}
+
+#[test]
+fn stale_on_content_change_with_preserved_mtime() {
+    let tmp = TempDir::new().unwrap();
+    let filters_dir = tmp.path().join("filters");
+    fs::create_dir_all(&filters_dir).unwrap();
+    let file = filters_dir.join("a.toml");
+    fs::write(&file, "command = \"old\"").unwrap();
+    let search_dirs = vec![filters_dir.clone()];
+    let manifest = manifest_for(&search_dirs);
+
+    // Rewrite the file and put the old mtimes back, as a checkout or
+    // network filesystem may.
+    let file_mtime = fs::metadata(&file).unwrap().modified().unwrap();
+    let dir_mtime = fs::metadata(&filters_dir).unwrap().modified().unwrap();
+    fs::write(&file, "command = \"new\"").unwrap();
+    fs::File::options()
+        .write(true)
+        .open(&file)
+        .unwrap()
+        .set_modified(file_mtime)
+        .unwrap();
+    fs::File::open(&filters_dir)
+        .unwrap()
+        .set_modified(dir_mtime)
+        .unwrap();
+
+    assert!(!is_cache_valid(&manifest, &search_dirs));
+}
+
+#[test]
+fn valid_after_touch_without_content_change() {
+    let tmp = TempDir::new().unwrap();
+    let filters_dir = tmp.path().join("filters");
+    fs::create_dir_all(&filters_dir).unwrap();
+    let file = filters_dir.join("a.toml");
+    fs::write(&file, "command = \"same\"").unwrap();
+    let search_dirs = vec![filters_dir];
+    let manifest = manifest_for(&search_dirs);
+
+    std::thread::sleep(std::time::Duration::from_millis(10));
+    fs::write(&file, "command = \"same\"").unwrap();
+
+    assert!(is_cache_valid(&manifest, &search_dirs));
+}
+
+#[test]
+fn cache_path_project_local() {
+    let tmp = TempDir::new().unwrap();
+    let tokf_dir = tmp.path().join(".tokf");
+    fs::create_dir_all(&tokf_dir).unwrap();
+    let search_dirs = vec![tokf_dir.join("filters")];
+
+    let rt = Runtime::isolated();
+    let path = cache_path(&rt, &search_dirs).unwrap();
+    assert!(path.starts_with(&tokf_dir));
+    assert!(path.ends_with("cache/manifest.bin"));
+}
+
+#[test]
+fn cache_path_user_fallback() {
+    // A parent path that definitely doesn't exist on disk.
+    let rt = Runtime::isolated();
+    let search_dirs = vec![PathBuf::from("/tokf_test_nonexistent_dir/.tokf/filters")];
+    let path = cache_path(&rt, &search_dirs);
+
+    assert_eq!(
+        path,
+        Some(rt.user_cache_dir().unwrap().join("manifest.bin"))
+    );
+}
+
+#[test]
+fn cache_path_respects_tokf_home() {
+    let rt = Runtime::builder().home("/custom/tokf_home").build();
+    let search_dirs = vec![PathBuf::from("/tokf_test_nonexistent_dir/.tokf/filters")];
+    let path = cache_path(&rt, &search_dirs);
+
+    assert_eq!(
+        path,
+        Some(PathBuf::from("/custom/tokf_home/manifest.bin")),
+        "cache path should be under TOKF_HOME when set"
+    );
+}
+
+#[test]
+fn write_failure_does_not_propagate() {
+    let tmp = TempDir::new().unwrap();
+    let tokf_dir = tmp.path().join(".tokf");
+    fs::create_dir_all(&tokf_dir).unwrap();
+    // Block cache dir creation by placing a regular file at that path
+    fs::write(tokf_dir.join("cache"), b"not a directory").unwrap();
+
+    let search_dirs = vec![tokf_dir.join("filters")];
+    let rt = Runtime::isolated();
+    let result = discover_with_cache(&rt, &search_dirs);
+    assert!(result.is_ok());
+}
+
+fn write_payloads_in_parallel(path: &Path, payloads: &[Vec<u8>]) {
+    std::thread::scope(|scope| {
+        let handles = payloads
+            .iter()
+            .map(|payload| {
+                let path = &path;
+                scope.spawn(move || write_manifest_bytes(path, payload))
+            })
+            .collect::<Vec<_>>();
+
+        for handle in handles {
+            handle.join().unwrap().unwrap();
+        }
+    });
+}
+
+fn leftover_paths(dir: &Path, final_path: &Path) -> Vec<PathBuf> {
+    fs::read_dir(dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|entry_path| entry_path != final_path)
+        .collect()
+}
+
+#[test]
+fn manifest_write_allows_parallel_writers() {
+    let tmp = TempDir::new().unwrap();
+    let path = tmp.path().join("manifest.bin");
+    let payloads = (0..32).map(|i| vec![i; 128 * 1024]).collect::<Vec<_>>();
+
+    write_payloads_in_parallel(&path, &payloads);
+
+    let data = fs::read(&path).unwrap();
+    assert!(
+        payloads.iter().any(|payload| payload.as_slice() == data),
+        "final manifest should be one complete writer payload"
+    );
+
+    let leftovers = leftover_paths(tmp.path(), &path);
+    assert!(
+        leftovers.is_empty(),
+        "temporary files should be cleaned up: {leftovers:?}"
+    );
+}
+
+#[test]
+fn cached_filter_roundtrip() {
+    let config: FilterConfig = toml::from_str("command = \"git push\"").unwrap();
+    let hash = tokf_common::hash::canonical_hash(&config).unwrap_or_default();
+    let rf = ResolvedFilter {
+        config,
+        hash: hash.clone(),
+        source_path: PathBuf::from("/some/path/push.toml"),
+        relative_path: PathBuf::from("git/push.toml"),
+        priority: 1,
+    };
+    let cached = filter_to_cached(&rf).unwrap();
+    let rf2 = cached_to_filter(cached).unwrap();
+
+    assert_eq!(rf2.config.command.first(), "git push");
+    assert_eq!(rf2.source_path, PathBuf::from("/some/path/push.toml"));
+    assert_eq!(rf2.relative_path, PathBuf::from("git/push.toml"));
+    assert_eq!(rf2.priority, 1);
+    assert_eq!(rf2.hash, hash);
+}
+
+#[test]
+fn hash_survives_cache_roundtrip() {
+    let config: FilterConfig = toml::from_str("command = \"cargo test\"").unwrap();
+    let expected_hash = tokf_common::hash::canonical_hash(&config).unwrap();
+    let rf = ResolvedFilter {
+        config,
+        hash: expected_hash.clone(),
+        source_path: PathBuf::from("/fake/cargo/test.toml"),
+        relative_path: PathBuf::from("cargo/test.toml"),
+        priority: 0,
+    };
+
+    let cached = filter_to_cached(&rf).unwrap();
+    assert_eq!(
+        cached.hash, expected_hash,
+        "hash must survive filter_to_cached"
+    );
+
+    let rf2 = cached_to_filter(cached).unwrap();
+    assert_eq!(
+        rf2.hash, expected_hash,
+        "hash must survive cached_to_filter"
+    );
+}
+
+#[test]
+fn content_hash_depends_on_content() {
+    assert_eq!(
+        content_hash(b"command = \"a\""),
+        content_hash(b"command = \"a\"")
+    );
+    assert_ne!(
+        content_hash(b"command = \"a\""),
+        content_hash(b"command = \"b\"")
+    );
+}
+
+#[test]
+fn generate_shims_creates_scripts() {
+    let tmp = TempDir::new().unwrap();
+    let rt = Runtime::builder().home(tmp.path()).build();
+
+    let filters = vec![
+        make_resolved_filter("git push", 0),
+        make_resolved_filter("cargo test", 0),
+        make_resolved_filter("git commit", 0), // git should be deduped
+    ];
+    generate_shims(&rt, &filters);
+
+    let shims = rt.shims_dir().unwrap();
+    assert!(shims.exists());
+
+    // git and cargo should have shims
+    let git_shim = shims.join("git");
+    let cargo_shim = shims.join("cargo");
+    assert!(git_shim.exists(), "git shim should exist");
+    assert!(cargo_shim.exists(), "cargo shim should exist");
+
+    // tokf should NOT have a shim
+    assert!(!shims.join("tokf").exists(), "tokf shim must not exist");
+
+    // Check content
+    let content = fs::read_to_string(&git_shim).unwrap();
+    assert!(content.starts_with("#!/bin/sh\n"));
+    assert!(
+        content.contains("-c 'git'"),
+        "shim should use shell mode with escaped command: {content}"
+    );
+    assert!(content.contains("\"$@\""));
+
+    // Check permissions on unix
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mode = fs::metadata(&git_shim).unwrap().permissions().mode();
+        assert_eq!(mode & 0o755, 0o755, "shim should be executable");
+    }
+}
+
+#[test]
+fn generate_shims_clean_slate() {
+    let tmp = TempDir::new().unwrap();
+    let rt = Runtime::builder().home(tmp.path()).build();
+
+    // Create a stale shim
+    let shims = rt.shims_dir().unwrap();
+    fs::create_dir_all(&shims).unwrap();
+    fs::write(shims.join("stale_cmd"), "old").unwrap();
+
+    let filters = vec![make_resolved_filter("git push", 0)];
+    generate_shims(&rt, &filters);
+
+    // Stale shim should be gone
+    assert!(!shims.join("stale_cmd").exists());
+    assert!(shims.join("git").exists());
+}
+
+#[test]
+fn generate_shims_includes_make_and_just() {
+    let tmp = TempDir::new().unwrap();
+    let rt = Runtime::builder().home(tmp.path()).build();
+
+    let filters = vec![
+        make_resolved_filter("make build", 0),
+        make_resolved_filter("just test", 0),
+    ];
+    generate_shims(&rt, &filters);
+
+    let shims = rt.shims_dir().unwrap();
+    assert!(shims.join("make").exists(), "make shim should exist");
+    assert!(shims.join("just").exists(), "just shim should exist");
+}
+
+#[test]
+fn generate_shims_extracts_basename() {
+    let tmp = TempDir::new().unwrap();
+    let rt = Runtime::builder().home(tmp.path()).build();
+
+    let filters = vec![make_resolved_filter("/usr/bin/git push", 0)];
+    generate_shims(&rt, &filters);
+
+    let shims = rt.shims_dir().unwrap();
+    assert!(shims.join("git").exists(), "basename should be extracted");
+    // Only the basename "git" should exist, not "usr" or any other directory
+    let entries: Vec<_> = fs::read_dir(&shims)
+        .unwrap()
+        .filter_map(Result::ok)
+        .map(|e| e.file_name().to_string_lossy().into_owned())
+        .collect();
+    assert_eq!(entries, vec!["git"], "only basename shim should exist");
+}
+
+#[test]
+fn generate_shims_cleans_up_when_disabled() {
+    let tmp = TempDir::new().unwrap();
+    let rt = Runtime::builder().home(tmp.path()).build();
+
+    // Create shims directory with existing shim scripts
+    let shims = rt.shims_dir().unwrap();
+    fs::create_dir_all(&shims).unwrap();
+    fs::write(shims.join("git"), "#!/bin/sh\nold shim").unwrap();
+    fs::write(shims.join("cargo"), "#!/bin/sh\nold shim").unwrap();
+    assert!(shims.exists());
+
+    // Write global config disabling shims
+    let config_path = rt.user_dir().unwrap().join("config.toml");
+    fs::create_dir_all(config_path.parent().unwrap()).unwrap();
+    fs::write(&config_path, "[shims]\nenabled = false\n").unwrap();
+
+    let filters = vec![make_resolved_filter("git push", 0)];
+    generate_shims(&rt, &filters);
+
+    // Shims directory should be removed
+    assert!(
+        !shims.exists(),
+        "shims directory should be removed when disabled"
+    );
+}
+
+#[test]
+fn stale_cache_triggers_rebuild() {
+    let tmp = TempDir::new().unwrap();
+    let tokf_dir = tmp.path().join(".tokf");
+    let filters_dir = tokf_dir.join("filters");
+    fs::create_dir_all(&filters_dir).unwrap();
+
+    fs::write(filters_dir.join("first.toml"), "command = \"first cmd\"").unwrap();
+    let search_dirs = vec![filters_dir.clone()];
+    let rt = Runtime::isolated();
+
+    // First run: populates cache
+    let filters1 = discover_with_cache(&rt, &search_dirs).unwrap();
+    let count1 = filters1
+        .iter()
+        .filter(|f| f.priority < crate::config::STDLIB_PRIORITY)
+        .count();
+    assert_eq!(count1, 1);
+
+    fs::write(filters_dir.join("second.toml"), "command = \"second cmd\"").unwrap();
+
+    // Second run: cache is stale, rebuilds with both filters
+    let filters2 = discover_with_cache(&rt, &search_dirs).unwrap();
+    let count2 = filters2
+        .iter()
+        .filter(|f| f.priority < crate::config::STDLIB_PRIORITY)
+        .count();
+    assert_eq!(count2, 2);
+}
+
+#[test]
+fn rebuild_reuses_unchanged_entries() {
+    let tmp = TempDir::new().unwrap();
+    let tokf_dir = tmp.path().join(".tokf");
+    let filters_dir = tokf_dir.join("filters");
+    fs::create_dir_all(&filters_dir).unwrap();
+    fs::write(filters_dir.join("kept.toml"), "command = \"kept cmd\"").unwrap();
+    fs::write(filters_dir.join("edited.toml"), "command = \"old cmd\"").unwrap();
+    let search_dirs = vec![filters_dir.clone()];
+    let rt = Runtime::isolated();
+    discover_with_cache(&rt, &search_dirs).unwrap();
+
+    // Tamper with the cached entry of the unchanged file: a rebuild that
+    // reuses it keeps the tampered command instead of re-parsing.
+    let path = cache_path(&rt, &search_dirs).unwrap();
+    let mut manifest = load_manifest(&path).unwrap();
+    let kept = manifest
+        .files
+        .iter_mut()
+        .find(|f| f.path.ends_with("kept.toml"))
+        .unwrap();
+    let mut filter = cached_to_filter(kept.filter.take().unwrap()).unwrap();
+    filter.config = toml::from_str("command = \"reused cmd\"").unwrap();
+    kept.filter = Some(filter_to_cached(&filter).unwrap());
+    let data = rkyv::to_bytes::<rancor::Error>(&manifest).unwrap();
+    write_manifest_bytes(&path, &data).unwrap();
+
+    fs::write(filters_dir.join("edited.toml"), "command = \"new cmd\"").unwrap();
+    let filters = discover_with_cache(&rt, &search_dirs).unwrap();
+    let local: Vec<&str> = filters
+        .iter()
+        .filter(|f| f.priority < crate::config::STDLIB_PRIORITY)
+        .map(|f| f.config.command.first())
+        .collect();
+    assert_eq!(local, vec!["new cmd", "reused cmd"]);
+}
+
+#[test]
+fn rebuild_reparses_extending_filters() {
+    let tmp = TempDir::new().unwrap();
+    let tokf_dir = tmp.path().join(".tokf");
+    let filters_dir = tokf_dir.join("filters");
+    fs::create_dir_all(&filters_dir).unwrap();
+    fs::write(filters_dir.join("base.toml"), "skip = [\"^old\"]").unwrap();
+    fs::write(
+        filters_dir.join("child.toml"),
+        "command = \"child cmd\"\nextends = \"base\"",
+    )
+    .unwrap();
+    let search_dirs = vec![filters_dir.clone()];
+    let rt = Runtime::isolated();
+    discover_with_cache(&rt, &search_dirs).unwrap();
+
+    // Only the base changes; the child must pick up the new rule.
+    fs::write(filters_dir.join("base.toml"), "skip = [\"^new\"]").unwrap();
+    let filters = discover_with_cache(&rt, &search_dirs).unwrap();
+    let child = filters
+        .iter()
+        .find(|f| f.config.command.first() == "child cmd")
+        .unwrap();
+    assert_eq!(child.config.skip, vec!["^new"]);
+}
+
+#[test]
+fn stale_when_a_base_outside_the_search_dirs_changes() {
+    let tmp = TempDir::new().unwrap();
+    let tokf_dir = tmp.path().join(".tokf");
+    let filters_dir = tokf_dir.join("filters");
+    fs::create_dir_all(&filters_dir).unwrap();
+    // Found by walking up from the filter, but not under any search dir.
+    fs::write(tokf_dir.join("common.toml"), "skip = [\"^aaa\"]").unwrap();
+    fs::write(
+        filters_dir.join("child.toml"),
+        "command = \"child cmd\"\nextends = \"common\"",
+    )
+    .unwrap();
+    let search_dirs = vec![filters_dir];
+    let rt = Runtime::isolated();
+    discover_with_cache(&rt, &search_dirs).unwrap();
+    let manifest = load_manifest(&cache_path(&rt, &search_dirs).unwrap()).unwrap();
+    assert!(is_cache_valid(&manifest, &search_dirs));
+
+    fs::write(tokf_dir.join("common.toml"), "skip = [\"^bbb\"]").unwrap();
+    assert!(!is_cache_valid(&manifest, &search_dirs));
+    let filters = discover_with_cache(&rt, &search_dirs).unwrap();
+    let child = filters
+        .iter()
+        .find(|f| f.config.command.first() == "child cmd")
+        .unwrap();
+    assert_eq!(child.config.skip, vec!["^bbb"]);
+}
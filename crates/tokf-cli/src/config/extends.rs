@@ -0,0 +1,188 @@
+//! Filter composition: `extends = "base/common-rust"`.
+//!
+//! The name is a filter path without `.toml`. It is looked up next to the
+//! extending file, then in each parent directory, then in the embedded stdlib,
+//! so `.tokf/filters/cargo/test.toml` finds `.tokf/filters/base/common-rust.toml`.
+//! A base may be a full filter or a partial without `command` (partials are
+//! skipped by discovery, so they never match a command on their own).
+//!
+//! Merging happens on the raw TOML before deserialization:
+//!
+//! - rule lists (`skip`, `keep`, `[[replace]]`, `[[section]]`, …) are
+//!   concatenated, base rules first
+//! - tables (`[on_success]`, …) are merged key by key
+//! - every other key in the extending filter replaces the base value
+
+use std::path::{Component, Path, PathBuf};
+
+use anyhow::Context;
+use toml::{Table, Value};
+
+use super::get_embedded_filter;
+use super::types::FilterConfig;
+
+/// Maximum length of an `extends` chain.
+const MAX_DEPTH: usize = 8;
+
+/// Array keys whose base and local entries are concatenated.
+const APPENDED_KEYS: &[&str] = &[
+    "skip",
+    "keep",
+    "replace",
+    "collapse",
+    "section",
+    "chunk",
+    "match_output",
+    "redact",
+];
+
+/// Where a filter's TOML came from — decides how `extends` names resolve.
+#[derive(Debug, Clone)]
+pub enum Origin {
+    /// A filter file on disk.
+    File(PathBuf),
+    /// A filter embedded in the binary (stdlib); resolves against the stdlib only.
+    Embedded,
+}
+
+/// Parse a filter, resolving its `extends` chain.
+///
+/// Filters without `extends` are deserialized directly, so their parse errors
+/// are unchanged.
+///
+/// # Errors
+///
+/// Returns an error if the TOML is invalid, a base cannot be found or parsed,
+/// the chain has a cycle or exceeds [`MAX_DEPTH`], or the merged result is
+/// not a valid filter.
+pub fn parse_filter(content: &str, origin: &Origin) -> anyhow::Result<FilterConfig> {
+    parse_filter_with_bases(content, origin, &mut Vec::new())
+}
+
+/// [`parse_filter`], also pushing every base file read from disk onto
+/// `bases`, nearest first. Bases found before an error are still pushed.
+///
+/// # Errors
+///
+/// See [`parse_filter`].
+pub fn parse_filter_with_bases(
+    content: &str,
+    origin: &Origin,
+    bases: &mut Vec<PathBuf>,
+) -> anyhow::Result<FilterConfig> {
+    if !content.contains("extends") {
+        return Ok(toml::from_str(content)?);
+    }
+    let table: Table = toml::from_str(content)?;
+    if !table.contains_key("extends") {
+        return Ok(toml::from_str(content)?);
+    }
+    let mut chain = Chain {
+        names: Vec::new(),
+        bases,
+    };
+    let merged = resolve_table(table, origin, &mut chain)?;
+    Ok(merged.try_into()?)
+}
+
+/// Serialize `content` with its `extends` chain flattened into one
+/// self-contained filter, or `None` when it does not use `extends`.
+///
+/// # Errors
+///
+/// See [`parse_filter`].
+pub fn flatten(content: &str, origin: &Origin) -> anyhow::Result<Option<String>> {
+    let table: Table = toml::from_str(content)?;
+    if !table.contains_key("extends") {
+        return Ok(None);
+    }
+    let mut chain = Chain {
+        names: Vec::new(),
+        bases: &mut Vec::new(),
+    };
+    let merged = resolve_table(table, origin, &mut chain)?;
+    let cfg: FilterConfig = merged.try_into()?;
+    Ok(Some(toml::to_string_pretty(&cfg)?))
+}
+
+/// The `extends` chain walked so far.
+struct Chain<'a> {
+    /// Base names, outermost first, for cycle and depth checks.
+    names: Vec<String>,
+    /// Base files read from disk.
+    bases: &'a mut Vec<PathBuf>,
+}
+
+fn resolve_table(
+    mut table: Table,
+    origin: &Origin,
+    chain: &mut Chain<'_>,
+) -> anyhow::Result<Table> {
+    let Some(extends) = table.remove("extends") else {
+        return Ok(table);
+    };
+    let Value::String(name) = extends else {
+        anyhow::bail!("extends must be a string, got {}", extends.type_str());
+    };
+    if chain.names.contains(&name) {
+        anyhow::bail!("extends cycle: {} -> {name}", chain.names.join(" -> "));
+    }
+    if chain.names.len() >= MAX_DEPTH {
+        anyhow::bail!(
+            "extends chain deeper than {MAX_DEPTH}: {}",
+            chain.names.join(" -> ")
+        );
+    }
+    chain.names.push(name.clone());
+
+    let (content, base_origin) = locate(&name, origin)?;
+    if let Origin::File(path) = &base_origin {
+        chain.bases.push(path.clone());
+    }
+    let base: Table = toml::from_str(&content)
+        .with_context(|| format!("failed to parse base filter '{name}'"))?;
+    let base = resolve_table(base, &base_origin, chain)?;
+    Ok(merge(base, table))
+}
+
+/// Find the base filter `name`, returning its content and origin.
+fn locate(name: &str, origin: &Origin) -> anyhow::Result<(String, Origin)> {
+    let rel = PathBuf::from(format!("{name}.toml"));
+    if !rel.components().all(|c| matches!(c, Component::Normal(_))) {
+        anyhow::bail!("extends: '{name}' must be a relative filter name without '..'");
+    }
+    if let Origin::File(path) = origin {
+        let start = path.parent().unwrap_or_else(|| Path::new("."));
+        for dir in start.ancestors() {
+            let candidate = dir.join(&rel);
+            if candidate != *path && candidate.is_file() {
+                let content = std::fs::read_to_string(&candidate).with_context(|| {
+                    format!("failed to read base filter: {}", candidate.display())
+                })?;
+                return Ok((content, Origin::File(candidate)));
+            }
+        }
+    }
+    get_embedded_filter(&rel)
+        .map(|content| (content.to_string(), Origin::Embedded))
+        .with_context(|| format!("extends: cannot find base filter '{name}'"))
+}
+
+fn merge(mut base: Table, local: Table) -> Table {
+    for (key, value) in local {
+        let merged = match (base.remove(&key), value) {
+            (Some(Value::Array(mut inherited)), Value::Array(own))
+                if APPENDED_KEYS.contains(&key.as_str()) =>
+            {
+                inherited.extend(own);
+                Value::Array(inherited)
+            }
+            (Some(Value::Table(inherited)), Value::Table(own)) => {
+                Value::Table(merge(inherited, own))
+            }
+            (_, own) => own,
+        };
+        base.insert(key, merged);
+    }
+    base
+}
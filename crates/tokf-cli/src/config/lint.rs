@@ -0,0 +1,382 @@
+//! `tokf lint`: static checks that deserialization alone does not catch.
+//!
+//! - every regex compiles
+//! - `[[section]]`s are consistent (`exit` needs `enter`, names are unique, …)
+//! - branch `output` templates only use variables the filter defines
+//! - template pipes are known
+//! - `lua_script` compiles
+//!
+//! Each [`Diagnostic`] points at the offending value with a 1-based line and
+//! column, so editors can underline it.
+
+use std::collections::BTreeSet;
+use std::ops::Range;
+use std::path::Path;
+
+use regex::Regex;
+use serde::Serialize;
+use tokf_filter::filter::inspect_template;
+use toml::Spanned;
+use toml::de::{DeTable, DeValue};
+
+use super::extends::{self, Origin};
+use super::types::{FilterConfig, Section};
+
+/// Keys whose string values (or arrays of strings) are regexes.
+const REGEX_KEYS: &[&str] = &[
+    "skip",
+    "keep",
+    "pattern",
+    "matches",
+    "unless",
+    "enter",
+    "exit",
+    "match",
+    "split_on",
+    "pattern_regex",
+    "redact",
+    "output_pattern",
+    "args_pattern",
+];
+
+/// Variables every branch `output` template can use.
+const BUILTIN_VARS: &[&str] = &["output", "stdout", "stderr"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    /// The filter will not behave as written.
+    Error,
+    /// Probably a mistake, but the filter still runs.
+    Warning,
+}
+
+impl Severity {
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Error => "error",
+            Self::Warning => "warning",
+        }
+    }
+}
+
+/// A problem found in a filter file.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    /// 1-based line of the offending value.
+    pub line: usize,
+    /// 1-based column, in characters.
+    pub column: usize,
+    /// Key path of the value, e.g. `section[0].enter` (empty for file-level errors).
+    pub key: String,
+    pub message: String,
+}
+
+/// Lint the filter TOML in `content`, sorted by position.
+///
+/// A TOML or deserialization error is reported on its own, since the other
+/// checks need a valid filter.
+pub fn lint_filter(content: &str, origin: &Origin) -> Vec<Diagnostic> {
+    let table = match DeTable::parse(content) {
+        Ok(table) => table,
+        Err(e) => {
+            return vec![diagnostic(
+                content,
+                Severity::Error,
+                e.span(),
+                "",
+                e.message(),
+            )];
+        }
+    };
+    let config = match extends::parse_filter(content, origin) {
+        Ok(config) => config,
+        Err(e) => {
+            let toml_err = e.downcast_ref::<toml::de::Error>();
+            let message = toml_err.map_or_else(|| format!("{e:#}"), |t| t.message().to_string());
+            let span = toml_err.and_then(toml::de::Error::span);
+            return vec![diagnostic(content, Severity::Error, span, "", &message)];
+        }
+    };
+
+    let mut linter = Linter {
+        content,
+        known_vars: known_variables(&config),
+        diagnostics: Vec::new(),
+    };
+    linter.table(table.get_ref(), "");
+    for (key, value) in table.get_ref() {
+        match key.get_ref().as_ref() {
+            "section" => linter.sections(value, "section"),
+            "lua_script" => linter.lua_script(value, origin),
+            _ => {}
+        }
+    }
+    let mut diagnostics = linter.diagnostics;
+    diagnostics.sort_by_key(|d| (d.line, d.column));
+    diagnostics
+}
+
+struct Linter<'a> {
+    content: &'a str,
+    /// Names branch templates may use, or `None` when a `parse`, Lua or WASM
+    /// stage can add variables the linter cannot see.
+    known_vars: Option<BTreeSet<String>>,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl Linter<'_> {
+    fn push(&mut self, severity: Severity, span: Range<usize>, key: &str, message: &str) {
+        self.diagnostics
+            .push(diagnostic(self.content, severity, Some(span), key, message));
+    }
+
+    fn table(&mut self, table: &DeTable<'_>, path: &str) {
+        for (key, value) in table {
+            let key = key.get_ref().as_ref();
+            let path = if path.is_empty() {
+                key.to_string()
+            } else {
+                format!("{path}.{key}")
+            };
+            self.value(key, &path, value);
+        }
+    }
+
+    fn value(&mut self, key: &str, path: &str, value: &Spanned<DeValue<'_>>) {
+        match value.get_ref() {
+            DeValue::String(s) => self.string(key, path, s, value.span()),
+            DeValue::Array(items) => {
+                for (i, item) in items.iter().enumerate() {
+                    self.value(key, &format!("{path}[{i}]"), item);
+                }
+            }
+            DeValue::Table(table) => self.table(table, path),
+            _ => {}
+        }
+    }
+
+    fn string(&mut self, key: &str, path: &str, s: &str, span: Range<usize>) {
+        if REGEX_KEYS.contains(&key) {
+            if let Err(e) = Regex::new(s) {
+                // The last line of a regex syntax error is the summary.
+                let msg = e.to_string();
+                let summary = msg.lines().last().unwrap_or_default().trim();
+                let summary = summary.strip_prefix("error: ").unwrap_or(summary);
+                self.push(
+                    Severity::Error,
+                    span,
+                    path,
+                    &format!("invalid regex: {summary}"),
+                );
+            }
+        } else if key == "output" {
+            self.template(path, s, span);
+        }
+    }
+
+    fn template(&mut self, path: &str, template: &str, span: Range<usize>) {
+        let found = inspect_template(template);
+        for pipe in &found.unknown_pipes {
+            self.push(
+                Severity::Error,
+                span.clone(),
+                path,
+                &format!("unknown pipe `{pipe}`"),
+            );
+        }
+        if !is_branch_output(path) {
+            return;
+        }
+        let Some(known) = &self.known_vars else {
+            return;
+        };
+        let undefined: Vec<String> = found
+            .variables
+            .into_iter()
+            .filter(|v| !known.contains(v))
+            .collect();
+        for var in undefined {
+            self.push(
+                Severity::Warning,
+                span.clone(),
+                path,
+                &format!("`{var}` is not defined by this filter and renders as an empty string"),
+            );
+        }
+    }
+
+    fn sections(&mut self, value: &Spanned<DeValue<'_>>, path: &str) {
+        let DeValue::Array(items) = value.get_ref() else {
+            return;
+        };
+        let mut seen = BTreeSet::new();
+        for (i, item) in items.iter().enumerate() {
+            let DeValue::Table(section) = item.get_ref() else {
+                continue;
+            };
+            let path = format!("{path}[{i}]");
+            let field = |name: &str| {
+                section
+                    .iter()
+                    .find(|(k, _)| k.get_ref() == name)
+                    .map(|(_, v)| v)
+            };
+            match field("collect_as") {
+                None => self.push(
+                    Severity::Warning,
+                    item.span(),
+                    &path,
+                    "section has no `collect_as`, so it is skipped",
+                ),
+                Some(value) => {
+                    if let DeValue::String(name) = value.get_ref()
+                        && !seen.insert(name.to_string())
+                    {
+                        self.push(
+                            Severity::Warning,
+                            value.span(),
+                            &format!("{path}.collect_as"),
+                            &format!(
+                                "another section already collects as `{name}`; the last one wins"
+                            ),
+                        );
+                    }
+                }
+            }
+            if field("enter").is_none()
+                && let Some(exit) = field("exit")
+            {
+                self.push(
+                    Severity::Warning,
+                    exit.span(),
+                    &format!("{path}.exit"),
+                    "`exit` has no effect without `enter`",
+                );
+            }
+            if let Some(children) = field("children") {
+                self.sections(children, &format!("{path}.children"));
+            }
+        }
+    }
+
+    fn lua_script(&mut self, value: &Spanned<DeValue<'_>>, origin: &Origin) {
+        let DeValue::Table(script) = value.get_ref() else {
+            return;
+        };
+        for (key, value) in script {
+            let DeValue::String(s) = value.get_ref() else {
+                continue;
+            };
+            let source = match key.get_ref().as_ref() {
+                "source" => s.to_string(),
+                "file" => {
+                    let path = match origin {
+                        Origin::File(filter) => filter.with_file_name(&**s),
+                        Origin::Embedded => Path::new(&**s).to_path_buf(),
+                    };
+                    match std::fs::read_to_string(&path) {
+                        Ok(source) => source,
+                        Err(e) => {
+                            self.push(
+                                Severity::Error,
+                                value.span(),
+                                "lua_script.file",
+                                &format!("cannot read {}: {e}", path.display()),
+                            );
+                            continue;
+                        }
+                    }
+                }
+                _ => continue,
+            };
+            if let Err(e) = tokf_filter::filter::lua::check_syntax(&source) {
+                let key = format!("lua_script.{}", key.get_ref());
+                self.push(
+                    Severity::Error,
+                    value.span(),
+                    &key,
+                    &format!("lua_script does not compile: {e}"),
+                );
+            }
+        }
+    }
+}
+
+/// Variables a branch template can reference, or `None` when a stage the
+/// linter cannot inspect may define more.
+fn known_variables(config: &FilterConfig) -> Option<BTreeSet<String>> {
+    if config.parse.is_some() || config.lua_script.is_some() || config.wasm_module.is_some() {
+        return None;
+    }
+    let mut known: BTreeSet<String> = BUILTIN_VARS.iter().map(ToString::to_string).collect();
+    collect_section_names(&config.section, &mut known);
+    for chunk in &config.chunk {
+        known.insert(chunk.collect_as.clone());
+        known.extend(chunk.children_as.clone());
+    }
+    let branches = [&config.on_success, &config.on_failure]
+        .into_iter()
+        .flatten()
+        .chain(config.on_exit.iter().map(|e| &e.branch));
+    for branch in branches {
+        for rule in branch.aggregate.iter().chain(&branch.aggregates) {
+            known.extend(rule.sum.clone());
+            known.extend(rule.count_as.clone());
+        }
+    }
+    for rule in config.json.iter().flat_map(|j| &j.extract) {
+        known.insert(format!("{}_count", rule.as_name));
+        known.insert(rule.as_name.clone());
+    }
+    known.extend(config.step.iter().filter_map(|s| s.as_name.clone()));
+    Some(known)
+}
+
+fn collect_section_names(sections: &[Section], known: &mut BTreeSet<String>) {
+    for section in sections {
+        known.extend(section.collect_as.clone());
+        collect_section_names(&section.children, known);
+    }
+}
+
+/// Whether `path` is the `output` template of an `on_success`, `on_failure`
+/// or `on_exit` branch.
+fn is_branch_output(path: &str) -> bool {
+    let Some(branch) = path.strip_suffix(".output") else {
+        return false;
+    };
+    matches!(branch, "on_success" | "on_failure")
+        || branch
+            .strip_prefix("on_exit[")
+            .and_then(|rest| rest.strip_suffix(']'))
+            .is_some_and(|i| i.parse::<usize>().is_ok())
+}
+
+fn diagnostic(
+    content: &str,
+    severity: Severity,
+    span: Option<Range<usize>>,
+    key: &str,
+    message: &str,
+) -> Diagnostic {
+    let before = span
+        .and_then(|s| content.get(..s.start))
+        .unwrap_or_default();
+    let line = before.matches('\n').count() + 1;
+    let column = before
+        .rsplit('\n')
+        .next()
+        .unwrap_or_default()
+        .chars()
+        .count()
+        + 1;
+    Diagnostic {
+        severity,
+        line,
+        column,
+        key: key.to_string(),
+        message: message.to_string(),
+    }
+}
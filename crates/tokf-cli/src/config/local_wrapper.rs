@@ -10,6 +10,8 @@
 
 use tokf_hook_types::{LocalWrapperConfig, LocalWrapperRule};
 
+use regex::Regex;
+
 use super::{ResolvedFilter, extract_basename};
 
 /// A built-in local wrapper specification.
@@ -115,6 +117,10 @@ fn match_spec(
 /// consumed count spans the wrapper prefix **and** the matched inner pattern,
 /// so `command_args[..consumed]` still forms the full command prefix.
 ///
+/// The first matching filter is authoritative: when `words` matches one of
+/// its `exclude` patterns, the result is `None` and the command runs
+/// unfiltered, even if a later filter would match.
+///
 /// Recursion terminates: [`strip_local_wrapper`] always consumes at least two
 /// words (command + marker), so `words.len()` strictly decreases each call.
 pub fn match_filters_with_wrapper<'a>(
@@ -124,6 +130,9 @@ pub fn match_filters_with_wrapper<'a>(
 ) -> Option<(&'a ResolvedFilter, &'a str, usize)> {
     for filter in filters {
         if let Some((pattern, consumed)) = filter.matching_pattern(words) {
+            if filter.is_excluded(words) {
+                return None;
+            }
             return Some((filter, pattern, consumed));
         }
     }
@@ -133,25 +142,30 @@ pub fn match_filters_with_wrapper<'a>(
     Some((filter, pattern, wrapper_len + inner_consumed))
 }
 
-/// Returns `true` if `words` matches any of `patterns` directly, or after
-/// stripping one or more local-wrapper layers.
+/// Returns `true` if `words` matches any of `patterns` or `regexes` (filters'
+/// `pattern_regex`) directly, or after stripping one or more local-wrapper
+/// layers.
 ///
 /// Used by the rewrite path, which works with raw pattern strings rather than
 /// [`ResolvedFilter`]s. Termination is guaranteed for the same reason as
 /// [`match_filters_with_wrapper`].
 pub fn patterns_match_with_wrapper(
     patterns: &[String],
+    regexes: &[Regex],
     words: &[&str],
     config: &LocalWrapperConfig,
 ) -> bool {
     if patterns
         .iter()
         .any(|p| super::pattern_matches_prefix(p, words).is_some())
+        || regexes
+            .iter()
+            .any(|re| super::pattern::regex_matches_prefix(re, words).is_some())
     {
         return true;
     }
     let Some(wrapper_len) = strip_local_wrapper(words, config) else {
         return false;
     };
-    patterns_match_with_wrapper(patterns, &words[wrapper_len..], config)
+    patterns_match_with_wrapper(patterns, regexes, &words[wrapper_len..], config)
 }
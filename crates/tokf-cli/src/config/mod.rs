@@ -1,5 +1,8 @@
 pub mod cache;
+pub mod extends;
+pub mod lint;
 pub mod local_wrapper;
+pub mod pattern;
 pub mod types;
 pub mod variant;
 
@@ -36,12 +39,32 @@ pub fn get_embedded_dir_files(dir_path: &Path) -> Vec<(PathBuf, &'static str)> {
 
 /// Build default search dirs in priority order:
 /// 1. `.tokf/filters/` (repo-local, resolved from the runtime's working directory)
-/// 2. `{config_dir}/tokf/filters/` (user-level, platform-native)
+/// 2. `.tokf/filters/` in each ancestor directory that has one, nearest first,
+///    so a package in a monorepo layers its filters over the repo root's
+/// 3. `{config_dir}/tokf/filters/` (user-level, platform-native)
 ///
 /// The embedded stdlib is always appended at the end by `discover_all_filters`,
 /// so no binary-adjacent path is needed.
 pub fn default_search_dirs(rt: &Runtime) -> Vec<PathBuf> {
-    rt.layered_paths("filters")
+    let user = rt.user_dir().map(|d| d.join("filters"));
+    let mut dirs = Vec::new();
+    if let Some(cwd) = rt.cwd() {
+        dirs.push(cwd.join(".tokf/filters"));
+        dirs.extend(
+            cwd.ancestors()
+                .skip(1)
+                .map(|d| d.join(".tokf/filters"))
+                .filter(|d| d.is_dir() && user.as_ref() != Some(d)),
+        );
+    }
+    dirs.extend(user);
+    dirs
+}
+
+/// Whether `dir` is a project-level `.tokf/filters` search dir (in the
+/// working directory or one of its ancestors) rather than the user dir.
+pub fn is_project_dir(dir: &Path) -> bool {
+    dir.ends_with(".tokf/filters")
 }
 
 /// Try to load a filter from `path`. Returns `Ok(Some(config))` on success,
@@ -59,7 +82,8 @@ pub fn try_load_filter(path: &Path) -> anyhow::Result<Option<FilterConfig>> {
                 .context(format!("failed to read filter file: {}", path.display())));
         }
     };
-    let config: FilterConfig = toml::from_str(&content)
+    let origin = extends::Origin::File(path.to_path_buf());
+    let config = extends::parse_filter(&content, &origin)
         .with_context(|| format!("failed to parse filter file: {}", path.display()))?;
     Ok(Some(config))
 }
@@ -127,7 +151,8 @@ fn skip_flags_to_match(words: &[&str], target: &str) -> Option<usize> {
 /// Returns `words_consumed` if pattern matches a prefix of `words`, else `None`.
 ///
 /// Pattern word `*` matches any single non-empty token but does **not** count
-/// toward `words_consumed`.  This means wildcard-matched words stay in the
+/// toward `words_consumed`; glob words such as `*:test` (see [`pattern`])
+/// behave the same way but constrain the token.  This means wildcard-matched words stay in the
 /// caller's `remaining_args`, making them available for `{args}` substitution
 /// in `run` templates.
 ///
@@ -173,6 +198,17 @@ pub fn pattern_matches_prefix(pattern: &str, words: &[&str]) -> Option<usize> {
             }
             word_idx += 1;
             wildcard_consumed += 1;
+        } else if pattern::is_glob(pword) {
+            let word = if pat_idx == 0 {
+                extract_basename(words[word_idx])
+            } else {
+                words[word_idx]
+            };
+            if !pattern::glob_matches(pword, word) {
+                return None;
+            }
+            word_idx += 1;
+            wildcard_consumed += 1;
         } else {
             // For the first word compare basenames, supporting path variants
             // on both the input word and the pattern word.
@@ -251,19 +287,33 @@ pub struct ResolvedFilter {
     pub source_path: PathBuf,
     /// Path relative to its source search dir (for display).
     pub relative_path: PathBuf,
-    /// 0 = repo-local, 1 = user-level, [`STDLIB_PRIORITY`] = built-in.
+    /// Index of the search dir the filter came from (0 = nearest repo-local),
+    /// or [`STDLIB_PRIORITY`] for built-in filters.
     pub priority: u8,
 }
 
 impl ResolvedFilter {
     /// Returns the matching command pattern and `words_consumed`.
+    ///
+    /// `command` patterns are tried first, then `pattern_regex` (an invalid
+    /// regex never matches).
     pub fn matching_pattern<'a>(&'a self, words: &[&str]) -> Option<(&'a str, usize)> {
         for pattern in self.config.command.patterns() {
             if let Some(consumed) = pattern_matches_prefix(pattern, words) {
                 return Some((pattern, consumed));
             }
         }
-        None
+        let source = self.config.pattern_regex.as_deref()?;
+        let re = regex::Regex::new(source).ok()?;
+        pattern::regex_matches_prefix(&re, words).map(|consumed| (source, consumed))
+    }
+
+    /// Whether `words` matches one of this filter's `exclude` patterns.
+    pub fn is_excluded(&self, words: &[&str]) -> bool {
+        self.config
+            .exclude
+            .iter()
+            .any(|p| pattern::exclude_matches(p, words))
     }
 
     /// Returns `words_consumed` if any of this filter's patterns match `words`.
@@ -288,12 +338,29 @@ impl ResolvedFilter {
         self.relative_path.with_extension("").to_string_lossy() == name
     }
 
+    /// Whether this filter came from a repo-local search dir: the first one,
+    /// or any ancestor `.tokf/filters/` layer.
+    pub fn is_local(&self) -> bool {
+        if self.priority == 0 {
+            return true;
+        }
+        let depth = self.relative_path.components().count();
+        self.priority != STDLIB_PRIORITY
+            && self
+                .source_path
+                .ancestors()
+                .nth(depth)
+                .is_some_and(is_project_dir)
+    }
+
     /// Human-readable priority label.
-    pub const fn priority_label(&self) -> &'static str {
-        match self.priority {
-            0 => "local",
-            1 => "user",
-            _ => "built-in",
+    pub fn priority_label(&self) -> &'static str {
+        if self.priority == STDLIB_PRIORITY {
+            "built-in"
+        } else if self.is_local() {
+            "local"
+        } else {
+            "user"
         }
     }
 }
@@ -320,55 +387,90 @@ pub fn discover_all_filters(search_dirs: &[PathBuf]) -> anyhow::Result<Vec<Resol
             let Ok(Some(config)) = try_load_filter(&path) else {
                 continue;
             };
+            all_filters.push(ResolvedFilter::from_file(config, dir, path, priority));
+        }
+    }
 
-            let relative_path = path.strip_prefix(dir).unwrap_or(&path).to_path_buf();
-            let hash = tokf_common::hash::canonical_hash(&config).unwrap_or_default();
+    all_filters.extend(embedded_filters());
+    Ok(rank_filters(all_filters))
+}
+
+impl ResolvedFilter {
+    /// Wrap a filter loaded from `path` inside search dir `dir`, the
+    /// `priority`-th search dir.
+    pub(crate) fn from_file(
+        config: FilterConfig,
+        dir: &Path,
+        path: PathBuf,
+        priority: usize,
+    ) -> Self {
+        let relative_path = path.strip_prefix(dir).unwrap_or(&path).to_path_buf();
+        let hash = tokf_common::hash::canonical_hash(&config).unwrap_or_default();
+        Self {
+            config,
+            hash,
+            source_path: path,
+            relative_path,
+            priority: u8::try_from(priority).unwrap_or(u8::MAX),
+        }
+    }
+}
 
-            all_filters.push(ResolvedFilter {
+/// The embedded stdlib at [`STDLIB_PRIORITY`], so it always sorts after
+/// local/user dirs regardless of how many dirs are searched.
+pub(crate) fn embedded_filters() -> Vec<ResolvedFilter> {
+    let mut filters = Vec::new();
+    let Ok(entries) = STDLIB.find("**/*.toml") else {
+        return filters;
+    };
+    for entry in entries {
+        if let DirEntry::File(file) = entry {
+            let content = file.contents_utf8().unwrap_or("");
+            let Ok(config) = extends::parse_filter(content, &extends::Origin::Embedded) else {
+                continue; // silently skip invalid embedded TOML
+            };
+            let rel = file.path().to_path_buf();
+            let hash = tokf_common::hash::canonical_hash(&config).unwrap_or_default();
+            filters.push(ResolvedFilter {
                 config,
                 hash,
-                source_path: path,
-                relative_path,
-                priority: u8::try_from(priority).unwrap_or(u8::MAX),
+                source_path: PathBuf::from("<built-in>").join(&rel),
+                relative_path: rel,
+                priority: STDLIB_PRIORITY,
             });
         }
     }
+    filters
+}
 
-    // Append embedded stdlib at the lowest priority (STDLIB_PRIORITY ensures it
-    // always sorts after local/user dirs regardless of how many dirs are in the slice).
-    if let Ok(entries) = STDLIB.find("**/*.toml") {
-        for entry in entries {
-            if let DirEntry::File(file) = entry {
-                let content = file.contents_utf8().unwrap_or("");
-                let Ok(config) = toml::from_str::<FilterConfig>(content) else {
-                    continue; // silently skip invalid embedded TOML
-                };
-                let rel = file.path().to_path_buf();
-                let hash = tokf_common::hash::canonical_hash(&config).unwrap_or_default();
-                all_filters.push(ResolvedFilter {
-                    config,
-                    hash,
-                    source_path: PathBuf::from("<built-in>").join(&rel),
-                    relative_path: rel,
-                    priority: STDLIB_PRIORITY,
-                });
-            }
-        }
-    }
+/// `(priority ASC, specificity DESC)`: the order in which filters claim a
+/// command pattern.
+pub(crate) fn rank_order(a: &ResolvedFilter, b: &ResolvedFilter) -> std::cmp::Ordering {
+    a.priority
+        .cmp(&b.priority)
+        .then_with(|| b.specificity().cmp(&a.specificity()))
+}
 
-    // Sort by (priority ASC, specificity DESC): lower priority number and higher
-    // specificity win.
-    all_filters.sort_by(|a, b| {
-        a.priority
-            .cmp(&b.priority)
-            .then_with(|| b.specificity().cmp(&a.specificity()))
-    });
+/// Sort by `(priority ASC, specificity DESC)` and keep the first filter for
+/// each command pattern (by `first()` string).
+pub(crate) fn rank_filters(mut filters: Vec<ResolvedFilter>) -> Vec<ResolvedFilter> {
+    filters.sort_by(rank_order);
 
-    // Dedup: keep first occurrence of each canonical command pattern.
     let mut seen: std::collections::HashSet<String> = std::collections::HashSet::new();
-    all_filters.retain(|f| seen.insert(f.config.command.first().to_string()));
+    filters.retain(|f| seen.insert(f.config.command.first().to_string()));
+    filters
+}
 
-    Ok(all_filters)
+/// Drop the filters named in `[filters] disabled` of the project or global
+/// `config.toml`, so their commands run unfiltered. A filter shadowed by a
+/// disabled one does not take its place.
+pub fn remove_disabled(rt: &Runtime, filters: &mut Vec<ResolvedFilter>) {
+    let project_root = rt.cwd().map(crate::history::project_root_for);
+    let config = crate::history::FiltersConfig::load(rt, project_root.as_deref());
+    if !config.disabled.is_empty() {
+        filters
+            .retain(|f| !config.is_disabled(&f.relative_path.with_extension("").to_string_lossy()));
+    }
 }
 
 /// Build a rewrite regex pattern for a command pattern string.
@@ -406,6 +508,9 @@ pub fn command_pattern_to_regex(pattern: &str) -> String {
         if i == 0 {
             if word == "*" {
                 regex.push_str(r"\S+");
+            } else if pattern::is_glob(word) {
+                regex.push_str(r"(?:[^\s]*[\\/])?");
+                regex.push_str(&pattern::glob_to_regex(extract_basename(word)));
             } else {
                 // Strip any path prefix from the pattern word itself (e.g.
                 // `./mvnw` → `mvnw`) so that `command = "./mvnw test"` and
@@ -420,6 +525,10 @@ pub fn command_pattern_to_regex(pattern: &str) -> String {
         } else if word == "*" {
             // Wildcard: require exactly one whitespace-separated token.
             regex.push_str(r"\s+\S+");
+        } else if pattern::is_glob(word) {
+            // Glob: one token, constrained; no flag skipping (like `*`).
+            regex.push_str(r"\s+");
+            regex.push_str(&pattern::glob_to_regex(word));
         } else {
             // Between consecutive literal words, allow any number of flag-like
             // tokens to be skipped transparently.
@@ -459,6 +568,12 @@ mod tests_basename;
 #[cfg(test)]
 mod tests_discovery;
 #[cfg(test)]
+mod tests_extends;
+#[cfg(test)]
+mod tests_lint;
+#[cfg(test)]
 mod tests_local_wrapper;
 #[cfg(test)]
 mod tests_matching;
+#[cfg(test)]
+mod tests_pattern;
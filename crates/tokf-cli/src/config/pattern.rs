@@ -0,0 +1,103 @@
+//! Glob words, `pattern_regex` and `exclude` matching for command patterns.
+//!
+//! A pattern word containing `*` or `?` (other than a lone `*`) is a glob
+//! matched against a single word: `*` matches any run of characters, `?`
+//! exactly one. `npm run *:test` matches `npm run unit:test`. Like a lone
+//! `*`, a glob word does not count toward `words_consumed`.
+//!
+//! `exclude` patterns reuse the same matching, with flag words allowed
+//! anywhere after the program (see [`exclude_matches`]).
+
+use regex::Regex;
+
+use super::{extract_basename, pattern_matches_prefix};
+
+/// Whether `word` is a glob (contains `*` or `?`, and is not a lone `*`).
+pub fn is_glob(word: &str) -> bool {
+    word != "*" && word.contains(['*', '?'])
+}
+
+/// Match `word` against a glob of `*` and `?` wildcards.
+pub fn glob_matches(glob: &str, word: &str) -> bool {
+    let glob: Vec<char> = glob.chars().collect();
+    let word: Vec<char> = word.chars().collect();
+    let (mut g, mut w) = (0, 0);
+    // Position of the last `*` in the glob and the word index it resumes at.
+    let mut star: Option<(usize, usize)> = None;
+    while w < word.len() {
+        match glob.get(g) {
+            Some('*') => {
+                star = Some((g, w));
+                g += 1;
+            }
+            Some(&c) if c == '?' || c == word[w] => {
+                g += 1;
+                w += 1;
+            }
+            _ => {
+                let Some((sg, sw)) = star else {
+                    return false;
+                };
+                g = sg + 1;
+                w = sw + 1;
+                star = Some((sg, sw + 1));
+            }
+        }
+    }
+    glob[g..].iter().all(|c| *c == '*')
+}
+
+/// Regex source for a glob word, matching within one whitespace-free token.
+pub fn glob_to_regex(glob: &str) -> String {
+    let mut out = String::new();
+    for c in glob.chars() {
+        match c {
+            '*' => out.push_str(r"\S*"),
+            '?' => out.push_str(r"\S"),
+            c => out.push_str(&regex::escape(c.encode_utf8(&mut [0; 4]))),
+        }
+    }
+    out
+}
+
+/// Returns `words_consumed` if `re` matches the start of `words`, else `None`.
+///
+/// The words are joined by single spaces, with the first reduced to its
+/// basename, so `/usr/bin/npm run test` is tested as `npm run test`. The
+/// match must start at the beginning and end on a word boundary; every word
+/// it covers is consumed.
+pub fn regex_matches_prefix(re: &Regex, words: &[&str]) -> Option<usize> {
+    let (first, rest) = words.split_first()?;
+    let mut line = extract_basename(first).to_string();
+    let mut ends = vec![line.len()];
+    for word in rest {
+        line.push(' ');
+        line.push_str(word);
+        ends.push(line.len());
+    }
+    let m = re.find(&line).filter(|m| m.start() == 0 && m.end() > 0)?;
+    ends.iter().position(|end| *end == m.end()).map(|i| i + 1)
+}
+
+/// Whether `words` matches an `exclude` pattern.
+///
+/// The pattern's non-flag words must match a prefix of `words`, as for
+/// `command`. Each flag word (starting with `-`) must then appear somewhere
+/// after the program: as the exact token, as `--flag=value`, or via a glob.
+pub fn exclude_matches(pattern: &str, words: &[&str]) -> bool {
+    let (flags, positional): (Vec<&str>, Vec<&str>) =
+        pattern.split_whitespace().partition(|w| w.starts_with('-'));
+    if positional.is_empty() || pattern_matches_prefix(&positional.join(" "), words).is_none() {
+        return false;
+    }
+    let args = words.get(1..).unwrap_or_default();
+    flags.iter().all(|flag| {
+        args.iter().any(|arg| {
+            *arg == *flag
+                || arg
+                    .strip_prefix(flag)
+                    .is_some_and(|rest| rest.starts_with('='))
+                || (is_glob(flag) && glob_matches(flag, arg))
+        })
+    })
+}
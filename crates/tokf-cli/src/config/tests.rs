@@ -81,6 +81,55 @@ fn test_default_search_dirs_only_local_and_user() {
     );
 }
 
+#[test]
+fn test_default_search_dirs_layers_ancestors_nearest_first() {
+    let root = TempDir::new().unwrap();
+    let pkg = root.path().join("packages/web");
+    fs::create_dir_all(root.path().join(".tokf/filters")).unwrap();
+    fs::create_dir_all(root.path().join("packages/.tokf/filters")).unwrap();
+    fs::create_dir_all(&pkg).unwrap();
+    let rt = Runtime::builder().cwd(&pkg).build();
+
+    let dirs = default_search_dirs(&rt);
+    assert_eq!(dirs[0], pkg.join(".tokf/filters"));
+    assert_eq!(dirs[1], root.path().join("packages/.tokf/filters"));
+    assert_eq!(dirs[2], root.path().join(".tokf/filters"));
+    assert_eq!(
+        dirs.last(),
+        rt.user_dir().map(|d| d.join("filters")).as_ref()
+    );
+}
+
+#[test]
+fn test_nearest_layer_shadows_ancestor_filter() {
+    let root = TempDir::new().unwrap();
+    let pkg = root.path().join("app");
+    let root_filters = root.path().join(".tokf/filters");
+    let pkg_filters = pkg.join(".tokf/filters");
+    fs::create_dir_all(&root_filters).unwrap();
+    fs::create_dir_all(&pkg_filters).unwrap();
+    fs::write(
+        root_filters.join("test.toml"),
+        "command = \"npm test\"\nskip = [\"root\"]",
+    )
+    .unwrap();
+    fs::write(root_filters.join("lint.toml"), "command = \"npm run lint\"").unwrap();
+    fs::write(
+        pkg_filters.join("test.toml"),
+        "command = \"npm test\"\nskip = [\"pkg\"]",
+    )
+    .unwrap();
+    let rt = Runtime::builder().cwd(&pkg).build();
+
+    let filters = discover_all_filters(&default_search_dirs(&rt)).unwrap();
+    let test = filters.iter().find(|f| f.matches_name("test")).unwrap();
+    assert_eq!(test.config.skip, vec!["pkg"]);
+    assert_eq!(test.priority_label(), "local");
+    let lint = filters.iter().find(|f| f.matches_name("lint")).unwrap();
+    assert_eq!(lint.priority, 1);
+    assert_eq!(lint.priority_label(), "local");
+}
+
 // --- embedded stdlib tests ---
 
 #[test]
@@ -0,0 +1,131 @@
+#![allow(clippy::unwrap_used, clippy::expect_used)]
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use tempfile::TempDir;
+
+use super::extends::{Origin, flatten, parse_filter};
+use super::*;
+
+fn write(dir: &Path, rel: &str, content: &str) -> PathBuf {
+    let path = dir.join(rel);
+    fs::create_dir_all(path.parent().unwrap()).unwrap();
+    fs::write(&path, content).unwrap();
+    path
+}
+
+const COMMON: &str = r#"
+strip_ansi = true
+skip = ["^\\s*Compiling "]
+max_lines = 40
+
+[[replace]]
+pattern = "/home/ci/"
+output = ""
+
+[on_success]
+output = "ok"
+tail = 5
+"#;
+
+#[test]
+fn inherits_rules_with_local_overrides() {
+    let dir = TempDir::new().unwrap();
+    write(dir.path(), "base/common-rust.toml", COMMON);
+    let path = write(
+        dir.path(),
+        "cargo/test.toml",
+        r#"
+command = "cargo test"
+extends = "base/common-rust"
+skip = ["^running "]
+max_lines = 10
+
+[on_success]
+output = "tests passed"
+"#,
+    );
+
+    let cfg = try_load_filter(&path).unwrap().unwrap();
+    assert!(cfg.strip_ansi);
+    assert_eq!(cfg.skip, vec![r"^\s*Compiling ", "^running "]);
+    assert_eq!(cfg.replace.len(), 1);
+    assert_eq!(cfg.max_lines, Some(10));
+    let on_success = cfg.on_success.unwrap();
+    assert_eq!(on_success.output.as_deref(), Some("tests passed"));
+    assert_eq!(on_success.tail, Some(5));
+}
+
+#[test]
+fn partial_base_is_not_discovered_on_its_own() {
+    let dir = TempDir::new().unwrap();
+    write(dir.path(), "base/common-rust.toml", COMMON);
+    write(
+        dir.path(),
+        "mytool.toml",
+        "command = \"mytool-extends-test\"\nextends = \"base/common-rust\"\n",
+    );
+
+    let filters = discover_all_filters(&[dir.path().to_path_buf()]).unwrap();
+    let local: Vec<_> = filters.iter().filter(|f| f.priority == 0).collect();
+    assert_eq!(local.len(), 1);
+    assert_eq!(local[0].config.skip, vec![r"^\s*Compiling "]);
+}
+
+#[test]
+fn chains_resolve_and_fall_back_to_stdlib() {
+    let dir = TempDir::new().unwrap();
+    write(
+        dir.path(),
+        "base/push.toml",
+        "extends = \"git/push\"\nskip = [\"^extra\"]\n",
+    );
+    let content = "command = \"git push --force\"\nextends = \"base/push\"\n";
+    let origin = Origin::File(dir.path().join("push-force.toml"));
+
+    let cfg = parse_filter(content, &origin).unwrap();
+    let stdlib: FilterConfig =
+        toml::from_str(get_embedded_filter(Path::new("git/push.toml")).unwrap()).unwrap();
+    assert_eq!(cfg.command.first(), "git push --force");
+    assert_eq!(cfg.skip.last().map(String::as_str), Some("^extra"));
+    assert_eq!(cfg.skip.len(), stdlib.skip.len() + 1);
+}
+
+#[test]
+fn cycles_are_rejected() {
+    let dir = TempDir::new().unwrap();
+    write(dir.path(), "a.toml", "extends = \"b\"\n");
+    write(dir.path(), "b.toml", "extends = \"a\"\n");
+    let origin = Origin::File(dir.path().join("c.toml"));
+
+    let err = parse_filter("command = \"c\"\nextends = \"a\"\n", &origin).unwrap_err();
+    assert!(err.to_string().contains("cycle"), "{err:#}");
+}
+
+#[test]
+fn missing_and_escaping_bases_are_errors() {
+    let dir = TempDir::new().unwrap();
+    let origin = Origin::File(dir.path().join("x.toml"));
+
+    let err = parse_filter("command = \"x\"\nextends = \"nope/missing\"\n", &origin).unwrap_err();
+    assert!(err.to_string().contains("cannot find"), "{err:#}");
+
+    let err = parse_filter("command = \"x\"\nextends = \"../outside\"\n", &origin).unwrap_err();
+    assert!(err.to_string().contains(".."), "{err:#}");
+}
+
+#[test]
+fn flatten_produces_self_contained_filter() {
+    let dir = TempDir::new().unwrap();
+    write(dir.path(), "base/common-rust.toml", COMMON);
+    let content = "command = \"cargo build\"\nextends = \"base/common-rust\"\n";
+    let origin = Origin::File(dir.path().join("cargo/build.toml"));
+
+    let flat = flatten(content, &origin).unwrap().unwrap();
+    assert!(!flat.contains("extends"));
+    let reparsed: FilterConfig = toml::from_str(&flat).unwrap();
+    assert_eq!(reparsed, parse_filter(content, &origin).unwrap());
+
+    assert!(flatten("command = \"x\"", &origin).unwrap().is_none());
+}
@@ -0,0 +1,146 @@
+#![allow(clippy::unwrap_used, clippy::expect_used)]
+
+use super::lint::{Diagnostic, Severity, lint_filter};
+use super::*;
+
+fn lint(toml: &str) -> Vec<Diagnostic> {
+    lint_filter(toml, &extends::Origin::Embedded)
+}
+
+fn messages(diagnostics: &[Diagnostic]) -> Vec<String> {
+    diagnostics
+        .iter()
+        .map(|d| format!("{}:{} {} {}", d.line, d.column, d.key, d.message))
+        .collect()
+}
+
+#[test]
+fn clean_filter_has_no_diagnostics() {
+    let diagnostics = lint(
+        r#"
+command = "cargo test"
+skip = ["^\\s+Compiling"]
+
+[[section]]
+enter = "^failures:$"
+exit = "^test result:"
+collect_as = "failures"
+
+[on_failure]
+output = "{failures.count} failed\n{failures | each: \"  {value}\" | join: \"\\n\"}"
+"#,
+    );
+    assert!(diagnostics.is_empty(), "{:?}", messages(&diagnostics));
+}
+
+#[test]
+fn invalid_regex_points_at_the_value() {
+    let diagnostics = lint("command = \"t\"\nskip = [\"ok\", \"(unclosed\"]\n");
+    assert_eq!(diagnostics.len(), 1);
+    let d = &diagnostics[0];
+    assert_eq!(d.severity, Severity::Error);
+    assert_eq!((d.line, d.column), (2, 15));
+    assert_eq!(d.key, "skip[1]");
+    assert!(d.message.starts_with("invalid regex:"), "{}", d.message);
+}
+
+#[test]
+fn unknown_pipes_and_undefined_variables() {
+    let diagnostics = lint(
+        r#"command = "t"
+[on_success]
+output = "{output | upcase} {summary}"
+"#,
+    );
+    assert_eq!(
+        messages(&diagnostics),
+        vec![
+            "3:10 on_success.output unknown pipe `upcase`",
+            "3:10 on_success.output `summary` is not defined by this filter and renders as an \
+             empty string",
+        ]
+    );
+    assert_eq!(diagnostics[0].severity, Severity::Error);
+    assert_eq!(diagnostics[1].severity, Severity::Warning);
+}
+
+#[test]
+fn variables_from_lua_are_not_checked() {
+    let diagnostics = lint(
+        r#"command = "t"
+lua_script = { lang = "luau", source = "return { summary = 'x' }" }
+[on_success]
+output = "{summary}"
+"#,
+    );
+    assert!(diagnostics.is_empty(), "{:?}", messages(&diagnostics));
+}
+
+#[test]
+fn section_consistency() {
+    let diagnostics = lint(
+        r#"command = "t"
+[[section]]
+exit = "^done"
+collect_as = "a"
+
+[[section]]
+match = "x"
+collect_as = "a"
+
+[[section]]
+match = "y"
+"#,
+    );
+    assert_eq!(
+        messages(&diagnostics),
+        vec![
+            "3:8 section[0].exit `exit` has no effect without `enter`",
+            "8:14 section[1].collect_as another section already collects as `a`; the last one wins",
+            "10:1 section[2] section has no `collect_as`, so it is skipped",
+        ]
+    );
+}
+
+#[test]
+fn lua_syntax_errors_are_reported() {
+    let diagnostics = lint(
+        r#"command = "t"
+[lua_script]
+lang = "luau"
+source = "if output then"
+"#,
+    );
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].key, "lua_script.source");
+    assert_eq!(diagnostics[0].line, 4);
+    assert!(diagnostics[0].message.contains("does not compile"));
+}
+
+#[test]
+fn toml_errors_carry_a_position() {
+    let diagnostics = lint("command = \"t\"\nskip = [\n");
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].severity, Severity::Error);
+    assert_eq!(diagnostics[0].line, 2);
+
+    let diagnostics = lint("command = \"t\"\ndedup = \"yes\"\n");
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].line, 2);
+}
+
+#[test]
+fn stdlib_filters_lint_clean() {
+    let filters = discover_all_filters(&[]).unwrap();
+    assert!(!filters.is_empty());
+    for filter in &filters {
+        let content = get_embedded_filter(&filter.relative_path).unwrap();
+        let diagnostics = lint(content);
+        assert!(
+            diagnostics.is_empty(),
+            "{}: {:?}",
+            filter.relative_path.display(),
+            messages(&diagnostics)
+        );
+    }
+}
@@ -205,6 +205,7 @@ fn patterns_match_direct() {
     let patterns = vec!["cargo test".to_string()];
     assert!(patterns_match_with_wrapper(
         &patterns,
+        &[],
         &words("cargo test"),
         &default_cfg()
     ));
@@ -215,6 +216,7 @@ fn patterns_match_through_wrapper() {
     let patterns = vec!["cargo test".to_string()];
     assert!(patterns_match_with_wrapper(
         &patterns,
+        &[],
         &words("nix develop -c cargo test"),
         &default_cfg()
     ));
@@ -225,6 +227,7 @@ fn patterns_no_match_through_wrapper_when_inner_unknown() {
     let patterns = vec!["cargo test".to_string()];
     assert!(!patterns_match_with_wrapper(
         &patterns,
+        &[],
         &words("nix develop -c echo hi"),
         &default_cfg()
     ));
@@ -240,17 +243,36 @@ fn patterns_no_match_when_disabled() {
     };
     assert!(!patterns_match_with_wrapper(
         &patterns,
+        &[],
         &words("nix develop -c cargo test"),
         &cfg
     ));
 }
 
+#[test]
+fn regex_patterns_match_through_wrapper() {
+    let regexes = vec![regex::Regex::new("^npm run (test|lint)").unwrap()];
+    assert!(patterns_match_with_wrapper(
+        &[],
+        &regexes,
+        &words("nix develop -c npm run lint"),
+        &default_cfg()
+    ));
+    assert!(!patterns_match_with_wrapper(
+        &[],
+        &regexes,
+        &words("npm run build"),
+        &default_cfg()
+    ));
+}
+
 #[test]
 fn patterns_degenerate_nesting_terminates() {
     // Double-wrapped: nix develop -c nix develop -c cargo test.
     let patterns = vec!["cargo test".to_string()];
     assert!(patterns_match_with_wrapper(
         &patterns,
+        &[],
         &words("nix develop -c nix develop -c cargo test"),
         &default_cfg()
     ));
@@ -0,0 +1,188 @@
+#![allow(clippy::unwrap_used, clippy::expect_used)]
+
+use regex::Regex;
+
+use super::pattern::{exclude_matches, glob_matches, is_glob, regex_matches_prefix};
+use super::*;
+
+fn words(s: &str) -> Vec<&str> {
+    s.split_whitespace().collect()
+}
+
+fn regex_filter(command: &str, pattern_regex: &str) -> ResolvedFilter {
+    let config: FilterConfig = toml::from_str(&format!(
+        "command = \"{command}\"\npattern_regex = '{pattern_regex}'"
+    ))
+    .unwrap();
+    ResolvedFilter {
+        config,
+        hash: String::new(),
+        source_path: PathBuf::from("<built-in>/npm/scripts.toml"),
+        relative_path: PathBuf::from("npm/scripts.toml"),
+        priority: STDLIB_PRIORITY,
+    }
+}
+
+// --- glob words ---
+
+#[test]
+fn glob_matching() {
+    assert!(glob_matches("*:test", "unit:test"));
+    assert!(glob_matches("*:test", ":test"));
+    assert!(!glob_matches("*:test", "unit:tests"));
+    assert!(glob_matches("test:*", "test:e2e"));
+    assert!(glob_matches("lint-?", "lint-a"));
+    assert!(!glob_matches("lint-?", "lint-ab"));
+    assert!(glob_matches("*-*-x", "a-b-c-x"));
+    assert!(glob_matches("日*", "日本"));
+}
+
+#[test]
+fn lone_star_is_not_a_glob() {
+    assert!(!is_glob("*"));
+    assert!(!is_glob("test"));
+    assert!(is_glob("*:test"));
+    assert!(is_glob("v?"));
+}
+
+#[test]
+fn glob_word_matches_prefix_without_consuming() {
+    assert_eq!(
+        pattern_matches_prefix("npm run *:test", &words("npm run unit:test --watch")),
+        Some(2)
+    );
+    assert_eq!(
+        pattern_matches_prefix("npm run *:test", &words("npm run build")),
+        None
+    );
+}
+
+#[test]
+fn glob_first_word_matches_basename() {
+    assert_eq!(
+        pattern_matches_prefix("*-lint check", &words("/usr/bin/eslint-lint check")),
+        Some(1)
+    );
+}
+
+#[test]
+fn glob_pattern_regex_agrees_with_matcher() {
+    let re = Regex::new(&command_pattern_to_regex("npm run *:test")).unwrap();
+    assert!(re.is_match("npm run unit:test"));
+    assert!(re.is_match("npm --silent run e2e:test --ci"));
+    assert!(!re.is_match("npm run unit:tests"));
+    assert!(!re.is_match("npm run build"));
+}
+
+// --- pattern_regex ---
+
+#[test]
+fn regex_matches_whole_words_only() {
+    let re = Regex::new("^npm run (test|lint)").unwrap();
+    assert_eq!(
+        regex_matches_prefix(&re, &words("npm run lint --fix")),
+        Some(3)
+    );
+    assert_eq!(
+        regex_matches_prefix(&re, &words("/usr/local/bin/npm run test")),
+        Some(3)
+    );
+    assert_eq!(regex_matches_prefix(&re, &words("npm run linter")), None);
+    assert_eq!(regex_matches_prefix(&re, &words("pnpm run lint")), None);
+}
+
+#[test]
+fn regex_must_match_at_start() {
+    let re = Regex::new("run (test|lint)").unwrap();
+    assert_eq!(regex_matches_prefix(&re, &words("npm run test")), None);
+}
+
+#[test]
+fn resolved_filter_falls_back_to_pattern_regex() {
+    let filter = regex_filter("npm run test", r"^npm run (test|lint)(:\S+)?");
+    assert_eq!(
+        filter.matching_pattern(&words("npm run test")),
+        Some(("npm run test", 3))
+    );
+    assert_eq!(
+        filter.matching_pattern(&words("npm run lint:css")),
+        Some((r"^npm run (test|lint)(:\S+)?", 3))
+    );
+    assert_eq!(filter.matching_pattern(&words("npm run build")), None);
+}
+
+#[test]
+fn invalid_pattern_regex_never_matches() {
+    let filter = regex_filter("npm run test", "^npm (");
+    assert_eq!(filter.matching_pattern(&words("npm (")), None);
+}
+
+// --- exclude ---
+
+fn filter_with(toml_src: &str, name: &str, priority: u8) -> ResolvedFilter {
+    ResolvedFilter {
+        config: toml::from_str(toml_src).unwrap(),
+        hash: String::new(),
+        source_path: PathBuf::from(format!("{name}.toml")),
+        relative_path: PathBuf::from(format!("{name}.toml")),
+        priority,
+    }
+}
+
+#[test]
+fn exclude_flag_words_match_anywhere() {
+    assert!(exclude_matches(
+        "git push --dry-run",
+        &words("git push --dry-run")
+    ));
+    assert!(exclude_matches(
+        "git push --dry-run",
+        &words("git push origin main --dry-run")
+    ));
+    assert!(exclude_matches(
+        "cargo test --doc",
+        &words("cargo test --doc -p tokf")
+    ));
+    assert!(exclude_matches(
+        "cargo test --features",
+        &words("cargo test --features=wasm")
+    ));
+    assert!(!exclude_matches(
+        "git push --dry-run",
+        &words("git push origin main")
+    ));
+    assert!(!exclude_matches(
+        "cargo test --doc",
+        &words("cargo build --doc")
+    ));
+}
+
+#[test]
+fn exclude_positional_words_use_command_matching() {
+    assert!(exclude_matches(
+        "npm run *:watch",
+        &words("npm run test:watch")
+    ));
+    assert!(!exclude_matches("npm run *:watch", &words("npm run test")));
+    assert!(!exclude_matches("--doc", &words("cargo test --doc")));
+}
+
+#[test]
+fn excluded_command_runs_unfiltered_even_with_later_match() {
+    let filters = vec![
+        filter_with(
+            "command = \"cargo test\"\nexclude = [\"cargo test --doc\"]",
+            "local/cargo-test",
+            0,
+        ),
+        filter_with("command = \"cargo *\"", "cargo-any", STDLIB_PRIORITY),
+    ];
+    let cfg = tokf_hook_types::LocalWrapperConfig::default();
+
+    let matched = local_wrapper::match_filters_with_wrapper(&filters, &words("cargo test"), &cfg);
+    assert!(matched.is_some_and(|(f, _, _)| f.matches_name("local/cargo-test")));
+    assert!(
+        local_wrapper::match_filters_with_wrapper(&filters, &words("cargo test --doc"), &cfg)
+            .is_none()
+    );
+}
@@ -361,13 +361,20 @@ empty = "nothing to show"
         assert_eq!(cfg.output, None);
         assert_eq!(cfg.fallback, None);
         assert!(cfg.replace.is_empty());
+        assert!(cfg.collapse.is_empty());
         assert!(!cfg.dedup);
         assert_eq!(cfg.dedup_window, None);
+        assert!(!cfg.dedup_annotate);
+        assert!(!cfg.collapse_cr);
+        assert!(!cfg.shorten_paths);
+        assert!(cfg.redact_secrets);
+        assert!(cfg.redact.is_empty());
         assert!(!cfg.strip_ansi);
         assert!(!cfg.trim_lines);
         assert!(!cfg.strip_empty_lines);
         assert!(!cfg.collapse_empty_lines);
         assert_eq!(cfg.lua_script, None);
+        assert_eq!(cfg.wasm_module, None);
         assert!(cfg.variant.is_empty());
     }
 
@@ -1,9 +1,10 @@
+use std::collections::BTreeMap;
 use std::path::Path;
 
 use regex::Regex;
 
 use super::ResolvedFilter;
-use super::types::FilterConfig;
+use super::types::{FilterConfig, Variant};
 
 /// A variant whose detection is deferred to after command execution
 /// (output-pattern matching).
@@ -23,11 +24,13 @@ pub struct VariantResolution {
     pub output_variants: Vec<DeferredVariant>,
 }
 
-/// Resolve variants using file detection (Phase A).
+/// Resolve variants using file and environment detection (Phase A).
 ///
-/// Iterates `parent.variant` in declaration order. For each variant with
-/// `detect.files`, checks if any listed file exists in `cwd`. On first file
-/// match, looks up the variant's filter in `all_filters` and returns it.
+/// Iterates `parent.variant` in declaration order, skipping variants whose
+/// `detect.env` does not match the process environment. A variant matches
+/// when any file in `detect.files` exists in `cwd`, or when `detect.env` is
+/// its only criterion. On first match, looks up the variant's filter in
+/// `all_filters` and returns it.
 ///
 /// Variants with only `detect.output_pattern` are collected as deferred for
 /// Phase B (post-execution output matching).
@@ -43,44 +46,35 @@ pub fn resolve_variants(
         let has_files = !variant.detect.files.is_empty();
         let has_output = variant.detect.output_pattern.is_some();
         let has_args = variant.detect.args_pattern.is_some();
+        let has_env = !variant.detect.env.is_empty();
 
-        if !has_files && !has_output && !has_args {
+        if !has_files && !has_output && !has_args && !has_env {
             eprintln!(
-                "[tokf] warning: variant '{}' has no detection criteria (no files, args_pattern, or output_pattern), skipping",
+                "[tokf] warning: variant '{}' has no detection criteria (no files, args_pattern, output_pattern, or env), skipping",
                 variant.name
             );
             continue;
         }
+        if !env_matches(&variant.detect.env, |k| std::env::var(k).ok()) {
+            continue;
+        }
 
-        if has_files {
-            let file_match = variant.detect.files.iter().any(|f| cwd.join(f).exists());
-            if file_match {
-                if let Some(cfg) = lookup_filter_by_name(&variant.filter, all_filters) {
-                    if verbose {
-                        eprintln!(
-                            "[tokf] variant '{}' matched by file detection, delegating to {}",
-                            variant.name, variant.filter
-                        );
-                    }
-                    return VariantResolution {
-                        config: cfg,
-                        output_variants: vec![],
-                    };
-                }
-                eprintln!(
-                    "[tokf] warning: variant '{}' references filter '{}' which was not found, skipping",
-                    variant.name, variant.filter
-                );
-            }
-            // File variant didn't match; if it also has an output pattern, defer it
-            if has_output {
-                deferred.push(DeferredVariant {
-                    name: variant.name.clone(),
-                    output_pattern: variant.detect.output_pattern.clone().unwrap_or_default(),
-                    filter_name: variant.filter.clone(),
-                });
+        let matched = if has_files {
+            variant.detect.files.iter().any(|f| cwd.join(f).exists())
+        } else {
+            has_env && !has_output && !has_args
+        };
+        if matched {
+            let how = if has_files { "file" } else { "environment" };
+            if let Some(cfg) = delegate(variant, all_filters, how, verbose) {
+                return VariantResolution {
+                    config: cfg,
+                    output_variants: vec![],
+                };
             }
-        } else if has_output {
+        }
+        // No pre-execution match; defer output-pattern variants to Phase B
+        if has_output {
             deferred.push(DeferredVariant {
                 name: variant.name.clone(),
                 output_pattern: variant.detect.output_pattern.clone().unwrap_or_default(),
@@ -95,6 +89,37 @@ pub fn resolve_variants(
     }
 }
 
+/// Whether every `detect.env` entry equals the value returned by `lookup`.
+/// An empty map always matches.
+fn env_matches(env: &BTreeMap<String, String>, lookup: impl Fn(&str) -> Option<String>) -> bool {
+    env.iter()
+        .all(|(key, want)| lookup(key).is_some_and(|got| got == *want))
+}
+
+/// Look up a matched variant's filter, logging the delegation (or warning
+/// when the filter is missing).
+fn delegate(
+    variant: &Variant,
+    all_filters: &[ResolvedFilter],
+    how: &str,
+    verbose: bool,
+) -> Option<FilterConfig> {
+    let Some(cfg) = lookup_filter_by_name(&variant.filter, all_filters) else {
+        eprintln!(
+            "[tokf] warning: variant '{}' references filter '{}' which was not found, skipping",
+            variant.name, variant.filter
+        );
+        return None;
+    };
+    if verbose {
+        eprintln!(
+            "[tokf] variant '{}' matched by {how} detection, delegating to {}",
+            variant.name, variant.filter
+        );
+    }
+    Some(cfg)
+}
+
 /// Resolve deferred variants by matching output patterns (Phase B).
 ///
 /// Returns the config of the first variant whose `output_pattern` regex
@@ -155,6 +180,9 @@ pub fn resolve_args_variants(
         let Some(pattern) = &variant.detect.args_pattern else {
             continue;
         };
+        if !env_matches(&variant.detect.env, |k| std::env::var(k).ok()) {
+            continue;
+        }
         let Ok(re) = Regex::new(pattern) else {
             eprintln!(
                 "[tokf] warning: variant '{}' has invalid args_pattern '{}', skipping",
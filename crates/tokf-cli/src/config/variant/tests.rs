@@ -1,3 +1,4 @@
+use std::collections::BTreeMap;
 use std::path::PathBuf;
 
 use tempfile::TempDir;
@@ -40,6 +41,7 @@ fn make_variant(
             files: files.into_iter().map(String::from).collect(),
             output_pattern: output_pattern.map(String::from),
             args_pattern: args_pattern.map(String::from),
+            env: BTreeMap::new(),
         },
         filter: filter.to_string(),
     }
@@ -278,6 +280,7 @@ fn empty_variant_detect_is_skipped() {
             files: vec![],
             output_pattern: None,
             args_pattern: None,
+            env: BTreeMap::new(),
         },
         filter: "npm/test-whatever".to_string(),
     }]);
@@ -463,3 +466,83 @@ fn args_only_variant_not_deferred_in_file_resolution() {
     assert!(result.output_variants.is_empty());
     assert_eq!(result.config.command.first(), "npm test");
 }
+
+fn make_env_variant(name: &str, env: &[(&str, &str)], args_pattern: Option<&str>) -> Variant {
+    let mut variant = make_variant(name, vec![], None, args_pattern, &format!("npm/{name}"));
+    variant.detect.env = env
+        .iter()
+        .map(|(k, v)| ((*k).to_string(), (*v).to_string()))
+        .collect();
+    variant
+}
+
+#[test]
+fn env_matches_requires_every_entry() {
+    let lookup = |k: &str| (k == "CI").then(|| "true".to_string());
+    let env = |pairs: &[(&str, &str)]| -> BTreeMap<String, String> {
+        pairs
+            .iter()
+            .map(|(k, v)| ((*k).to_string(), (*v).to_string()))
+            .collect()
+    };
+    assert!(env_matches(&env(&[]), lookup));
+    assert!(env_matches(&env(&[("CI", "true")]), lookup));
+    assert!(!env_matches(&env(&[("CI", "1")]), lookup));
+    assert!(!env_matches(
+        &env(&[("CI", "true"), ("GITHUB_ACTIONS", "true")]),
+        lookup
+    ));
+}
+
+#[test]
+fn env_only_variant_resolves_in_phase_a() {
+    let tmp = TempDir::new().unwrap();
+    let path = std::env::var("PATH").unwrap_or_default();
+    let parent = make_parent_with_variants(vec![
+        make_env_variant("unset", &[("TOKF_VARIANT_TEST_UNSET", "1")], None),
+        make_env_variant("ci", &[("PATH", &path)], None),
+    ]);
+    let all_filters = vec![
+        make_resolved("npm/unset", "unset"),
+        make_resolved("npm/ci", "ci-runner"),
+    ];
+
+    let result = resolve_variants(&parent, &all_filters, tmp.path(), false);
+
+    assert_eq!(result.config.command.first(), "ci-runner");
+}
+
+#[test]
+fn env_gates_args_variant() {
+    let parent = make_parent_with_variants(vec![make_env_variant(
+        "verbose-ci",
+        &[("TOKF_VARIANT_TEST_UNSET", "1")],
+        Some("--verbose"),
+    )]);
+    let all_filters = vec![make_resolved("npm/verbose-ci", "verbose")];
+    let args = vec!["--verbose".to_string()];
+
+    assert!(resolve_args_variants(&parent, &all_filters, &args, false).is_none());
+
+    let tmp = TempDir::new().unwrap();
+    let result = resolve_variants(&parent, &all_filters, tmp.path(), false);
+    assert_eq!(result.config.command.first(), "npm test");
+}
+
+#[test]
+fn when_env_and_when_args_aliases_parse() {
+    let cfg: FilterConfig = toml::from_str(
+        r#"
+command = "pytest"
+
+[[variant]]
+name = "ci-verbose"
+detect = { when_env = { CI = "true" }, when_args = "-v" }
+filter = "pytest/ci"
+"#,
+    )
+    .unwrap();
+    let detect = &cfg.variant[0].detect;
+    assert_eq!(detect.env.get("CI").map(String::as_str), Some("true"));
+    assert_eq!(detect.args_pattern.as_deref(), Some("-v"));
+}
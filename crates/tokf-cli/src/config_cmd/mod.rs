@@ -0,0 +1,421 @@
+mod pipe;
+mod set;
+
+use clap::Subcommand;
+use serde::Serialize;
+
+use tokf::history::{
+    FiltersConfig, HistoryConfig, OutputConfig, RunConfig, ShimsConfig, SyncConfig,
+    TokfProjectConfig, global_config_path, load_project_config, local_config_path,
+    project_root_for,
+};
+use tokf::runtime::Runtime;
+
+#[derive(Subcommand)]
+pub enum ConfigAction {
+    /// Show all effective configuration with source paths
+    #[command(visible_alias = "list")]
+    Show {
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+        /// Ignore the project-local .tokf/config.toml
+        #[arg(long)]
+        global: bool,
+    },
+    /// Print a single configuration value (for scripting)
+    Get {
+        /// Dotted key, e.g. `history.retention` (`tokf config list` shows them all)
+        key: String,
+        /// Ignore the project-local .tokf/config.toml
+        #[arg(long)]
+        global: bool,
+    },
+    /// Set a configuration value
+    Set {
+        /// Dotted key, e.g. `run.mask_exit_code` (`tokf config list` shows them all)
+        key: String,
+        /// Value to set
+        value: String,
+        /// Write to project-local .tokf/config.toml instead of global config
+        #[arg(long, conflicts_with = "global")]
+        local: bool,
+        /// Write to the global config (the default)
+        #[arg(long, conflicts_with = "local")]
+        global: bool,
+    },
+    /// Print raw config file contents
+    Print {
+        /// Print global config file
+        #[arg(long, conflicts_with = "local")]
+        global: bool,
+        /// Print local (project) config file
+        #[arg(long, conflicts_with = "global")]
+        local: bool,
+    },
+    /// Show config file paths with existence status
+    Path,
+}
+
+pub fn run_config_action(rt: &Runtime, action: &ConfigAction) -> i32 {
+    match action {
+        ConfigAction::Show { json, global } => cmd_config_show(rt, *json, *global),
+        ConfigAction::Get { key, global } => cmd_config_get(rt, key, *global),
+        ConfigAction::Set {
+            key, value, local, ..
+        } => set::cmd_config_set(rt, key, value, *local),
+        ConfigAction::Print { global, local } => cmd_config_print(rt, *global, *local),
+        ConfigAction::Path => cmd_config_path(rt),
+    }
+}
+
+// ── Supported keys ──────────────────────────────────────────────
+
+const KNOWN_KEYS: &[&str] = &[
+    "history.retention",
+    "output.show_indicator",
+    "shims.enabled",
+    "sync.auto",
+    "sync.auto_sync_threshold",
+    "sync.auto_sync_interval_minutes",
+    "sync.upload_stats",
+    "history.max_age_days",
+    "history.max_total_mb",
+    "filters.disabled",
+    "run.mask_exit_code",
+    "run.spill_threshold_mb",
+    "pipe.strip",
+    "pipe.prefer_less",
+];
+
+fn print_known_keys() {
+    eprintln!("[tokf] known config keys:");
+    for key in KNOWN_KEYS {
+        eprintln!("  {key}");
+    }
+}
+
+// ── config show ─────────────────────────────────────────────────
+
+#[derive(Serialize)]
+struct ConfigEntry {
+    key: String,
+    /// `None` when the value is unset (serialises as JSON `null`).
+    value: Option<String>,
+    source: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    file: Option<String>,
+}
+
+fn cmd_config_show(rt: &Runtime, json: bool, global: bool) -> i32 {
+    let project_root = (!global).then(|| project_root_for(rt.cwd_or_empty()));
+    let global_path = global_config_path(rt);
+
+    let entries = collect_config_entries(rt, global_path.as_deref(), project_root.as_deref());
+
+    if json {
+        crate::output::print_json(&entries);
+    } else {
+        println!("tokf configuration:");
+        for entry in &entries {
+            let source_display = match entry.source.as_str() {
+                "default" => "(default)".to_string(),
+                "local" => format!(
+                    "(local: {})",
+                    entry.file.as_deref().unwrap_or(".tokf/config.toml")
+                ),
+                "global" => format!(
+                    "(global: {})",
+                    entry.file.as_deref().unwrap_or("config.toml")
+                ),
+                other => format!("({other})"),
+            };
+            let display_value = entry.value.as_deref().unwrap_or("(not set)");
+            println!("  {} = {display_value}  {source_display}", entry.key);
+        }
+    }
+    0
+}
+
+#[allow(clippy::too_many_lines)]
+fn collect_config_entries(
+    rt: &Runtime,
+    global_path: Option<&std::path::Path>,
+    project_root: Option<&std::path::Path>,
+) -> Vec<ConfigEntry> {
+    let history = HistoryConfig::load_from(project_root, global_path);
+    let shims = ShimsConfig::load_from(global_path);
+    let sync = SyncConfig::load_from(project_root, global_path);
+
+    let local_path = project_root.map(local_config_path);
+    let local_path = local_path.as_deref();
+    let local_cfg = local_path.filter(|p| p.is_file()).map(load_project_config);
+    let global_cfg = global_path.filter(|p| p.is_file()).map(load_project_config);
+
+    let mut entries = Vec::new();
+
+    let src = |has_field: fn(&TokfProjectConfig) -> bool| {
+        find_source(
+            local_cfg.as_ref(),
+            local_path,
+            global_cfg.as_ref(),
+            global_path,
+            has_field,
+        )
+    };
+
+    // history.retention
+    let (ret_source, ret_file) = src(|c| c.history.as_ref().and_then(|h| h.retention).is_some());
+    entries.push(ConfigEntry {
+        key: "history.retention".to_string(),
+        value: Some(history.retention_count.to_string()),
+        source: ret_source,
+        file: ret_file,
+    });
+
+    // shims.enabled (global-only — skip local config check)
+    let (shims_source, shims_file) = if global_cfg
+        .as_ref()
+        .is_some_and(|c| c.shims.as_ref().and_then(|s| s.enabled).is_some())
+    {
+        (
+            "global".to_string(),
+            global_path.map(|p| p.display().to_string()),
+        )
+    } else {
+        ("default".to_string(), None)
+    };
+    entries.push(ConfigEntry {
+        key: "shims.enabled".to_string(),
+        value: Some(shims.enabled.to_string()),
+        source: shims_source,
+        file: shims_file,
+    });
+
+    // output.show_indicator (env var takes priority)
+    let env_indicator = rt.show_indicator();
+    let (output_val, output_source, output_file) = env_indicator.map_or_else(
+        || {
+            let output = OutputConfig::load_from(project_root, global_path);
+            let (s, f) = src(|c| c.output.as_ref().and_then(|o| o.show_indicator).is_some());
+            (output.show_indicator, s, f)
+        },
+        |b| {
+            (
+                b,
+                "env".to_string(),
+                Some("TOKF_SHOW_INDICATOR".to_string()),
+            )
+        },
+    );
+    entries.push(ConfigEntry {
+        key: "output.show_indicator".to_string(),
+        value: Some(output_val.to_string()),
+        source: output_source,
+        file: output_file,
+    });
+
+    // sync.auto_sync_threshold
+    let (thresh_source, thresh_file) = src(|c| {
+        c.sync
+            .as_ref()
+            .and_then(|s| s.auto_sync_threshold)
+            .is_some()
+    });
+    entries.push(ConfigEntry {
+        key: "sync.auto_sync_threshold".to_string(),
+        value: Some(sync.auto_sync_threshold.to_string()),
+        source: thresh_source,
+        file: thresh_file,
+    });
+
+    // sync.upload_stats
+    let (stats_source, stats_file) =
+        src(|c| c.sync.as_ref().and_then(|s| s.upload_usage_stats).is_some());
+    entries.push(ConfigEntry {
+        key: "sync.upload_stats".to_string(),
+        value: sync.upload_usage_stats.map(|b| b.to_string()),
+        source: stats_source,
+        file: stats_file,
+    });
+
+    let run = RunConfig::load_from(project_root, global_path);
+    let disabled = FiltersConfig::load_from(project_root, global_path).disabled;
+    let mut push = |key: &str, value: Option<String>, has_field| {
+        let (source, file) = src(has_field);
+        entries.push(ConfigEntry {
+            key: key.to_string(),
+            value,
+            source,
+            file,
+        });
+    };
+    push("sync.auto", sync.auto.map(|b| b.to_string()), |c| {
+        c.sync.as_ref().and_then(|s| s.auto).is_some()
+    });
+    push(
+        "sync.auto_sync_interval_minutes",
+        Some(sync.auto_sync_interval_minutes.to_string()),
+        |c| {
+            c.sync
+                .as_ref()
+                .and_then(|s| s.auto_sync_interval_minutes)
+                .is_some()
+        },
+    );
+    push(
+        "history.max_age_days",
+        history.max_age_days.map(|d| d.to_string()),
+        |c| c.history.as_ref().and_then(|h| h.max_age_days).is_some(),
+    );
+    push(
+        "history.max_total_mb",
+        history.max_total_mb.map(|mb| mb.to_string()),
+        |c| c.history.as_ref().and_then(|h| h.max_total_mb).is_some(),
+    );
+    // The lists from both files are combined, so the source is the first that has one.
+    push(
+        "filters.disabled",
+        (!disabled.is_empty()).then(|| disabled.join(",")),
+        |c| c.filters.as_ref().is_some_and(|f| !f.disabled.is_empty()),
+    );
+    push(
+        "run.mask_exit_code",
+        Some(run.mask_exit_code.to_string()),
+        |c| c.run.as_ref().and_then(|r| r.mask_exit_code).is_some(),
+    );
+    push(
+        "run.spill_threshold_mb",
+        Some(
+            run.spill_threshold
+                .map_or(0, |b| b / (1024 * 1024))
+                .to_string(),
+        ),
+        |c| c.run.as_ref().and_then(|r| r.spill_threshold_mb).is_some(),
+    );
+    // `[pipe]` lives in rewrites.toml, not config.toml.
+    entries.extend(pipe::entries(rt, global_path, project_root.is_some()));
+
+    entries
+}
+
+/// Determine which config source (local, global, or default) provides a given field.
+///
+/// `has_field` extracts the field from a `TokfProjectConfig`, returning `true` when present.
+/// Checks local first, then global, falling back to `"default"`.
+fn find_source(
+    local_cfg: Option<&TokfProjectConfig>,
+    local_path: Option<&std::path::Path>,
+    global_cfg: Option<&TokfProjectConfig>,
+    global_path: Option<&std::path::Path>,
+    has_field: fn(&TokfProjectConfig) -> bool,
+) -> (String, Option<String>) {
+    if local_cfg.is_some_and(has_field) {
+        return (
+            "local".to_string(),
+            local_path.map(|p| p.display().to_string()),
+        );
+    }
+    if global_cfg.is_some_and(has_field) {
+        return (
+            "global".to_string(),
+            global_path.map(|p| p.display().to_string()),
+        );
+    }
+    ("default".to_string(), None)
+}
+
+// ── config get ──────────────────────────────────────────────────
+
+/// Print the effective value of `key`; exits 1 when it is unset.
+fn cmd_config_get(rt: &Runtime, key: &str, global: bool) -> i32 {
+    if !KNOWN_KEYS.contains(&key) {
+        eprintln!("[tokf] unknown config key: {key}");
+        print_known_keys();
+        return 1;
+    }
+    let project_root = (!global).then(|| project_root_for(rt.cwd_or_empty()));
+    let global_path = global_config_path(rt);
+    let entries = collect_config_entries(rt, global_path.as_deref(), project_root.as_deref());
+    let value = entries
+        .into_iter()
+        .find(|e| e.key == key)
+        .and_then(|e| e.value);
+    value.map_or(1, |value| {
+        println!("{value}");
+        0
+    })
+}
+
+// ── config print ────────────────────────────────────────────────
+
+fn cmd_config_print(rt: &Runtime, global: bool, local: bool) -> i32 {
+    let path = if local {
+        let cwd = rt.cwd_or_empty();
+        let project_root = project_root_for(cwd);
+        local_config_path(&project_root)
+    } else if global {
+        let Some(p) = global_config_path(rt) else {
+            eprintln!("[tokf] cannot determine global config directory");
+            return 1;
+        };
+        p
+    } else {
+        // Default: try local first, fall back to global
+        let cwd = rt.cwd_or_empty();
+        let project_root = project_root_for(cwd);
+        let local_path = local_config_path(&project_root);
+        if local_path.is_file() {
+            local_path
+        } else {
+            let Some(p) = global_config_path(rt) else {
+                eprintln!("[tokf] no config file found");
+                return 1;
+            };
+            p
+        }
+    };
+
+    match std::fs::read_to_string(&path) {
+        Ok(content) => {
+            print!("{content}");
+            0
+        }
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            eprintln!("[tokf] config file not found: {}", path.display());
+            1
+        }
+        Err(e) => {
+            eprintln!("[tokf] error reading {}: {e}", path.display());
+            1
+        }
+    }
+}
+
+// ── config path ─────────────────────────────────────────────────
+
+fn cmd_config_path(rt: &Runtime) -> i32 {
+    let cwd = rt.cwd_or_empty();
+    let project_root = project_root_for(cwd);
+
+    let global = global_config_path(rt);
+    let local = local_config_path(&project_root);
+
+    print_path_line("global", global.as_deref());
+    print_path_line("local", Some(&local));
+
+    0
+}
+
+fn print_path_line(label: &str, path: Option<&std::path::Path>) {
+    if let Some(p) = path {
+        let status = if p.exists() { "exists" } else { "not found" };
+        println!("{label:7} {} ({status})", p.display());
+    } else {
+        println!("{label:7} (unavailable)");
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests;
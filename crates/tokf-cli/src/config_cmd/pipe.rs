@@ -0,0 +1,140 @@
+//! `pipe.*` keys: the `[pipe]` section of `rewrites.toml`, which decides how
+//! rewriting treats piped commands.
+//!
+//! Unlike the other keys these live in `rewrites.toml`, read first found
+//! wins: `.tokf/rewrites.toml` in the current directory, else the user-level
+//! file. A local file without `[pipe]` therefore hides the user-level values.
+
+use std::path::{Path, PathBuf};
+
+use tokf::runtime::Runtime;
+
+use super::ConfigEntry;
+
+/// The `pipe.*` keys, each with its default.
+pub(super) const PIPE_KEYS: &[(&str, bool)] = &[("pipe.strip", true), ("pipe.prefer_less", false)];
+
+/// The `rewrites.toml` next to the global `config.toml`.
+fn global_rewrites(global_path: &Path) -> PathBuf {
+    global_path.with_file_name("rewrites.toml")
+}
+
+/// The project-local `rewrites.toml` tokf reads from the current directory.
+fn local_rewrites(rt: &Runtime) -> Option<PathBuf> {
+    rt.cwd().map(|cwd| cwd.join(".tokf").join("rewrites.toml"))
+}
+
+fn read_table(path: &Path) -> anyhow::Result<toml::Table> {
+    let content = std::fs::read_to_string(path)?;
+    Ok(toml::from_str(&content)?)
+}
+
+/// The effective `pipe.*` values, with the file each comes from.
+///
+/// `local` includes `.tokf/rewrites.toml`; without it only the user-level
+/// file counts, as with `--global`.
+pub(super) fn entries(rt: &Runtime, global_path: Option<&Path>, local: bool) -> Vec<ConfigEntry> {
+    let candidates = [
+        ("local", local.then(|| local_rewrites(rt)).flatten()),
+        ("global", global_path.map(global_rewrites)),
+    ];
+    // First existing file wins, as in `rewrite::load_user_config`; one that
+    // fails to parse leaves every value at its default.
+    let found = candidates
+        .into_iter()
+        .find_map(|(source, path)| path.filter(|p| p.is_file()).map(|p| (source, p)));
+    let pipe = found.as_ref().and_then(|(source, path)| {
+        let table = read_table(path).ok()?;
+        let pipe = table.get("pipe")?.as_table()?.clone();
+        Some((*source, path.display().to_string(), pipe))
+    });
+
+    PIPE_KEYS
+        .iter()
+        .map(|&(key, default)| {
+            let field = key.trim_start_matches("pipe.");
+            let set = pipe
+                .as_ref()
+                .and_then(|(source, file, t)| Some((source, file, t.get(field)?.as_bool()?)));
+            let (value, source, file) = set.map_or_else(
+                || (default, "default".to_string(), None),
+                |(source, file, value)| (value, (*source).to_string(), Some(file.clone())),
+            );
+            ConfigEntry {
+                key: key.to_string(),
+                value: Some(value.to_string()),
+                source,
+                file,
+            }
+        })
+        .collect()
+}
+
+/// `tokf config set pipe.*`: write the value to the local or user-level
+/// `rewrites.toml`, keeping the rest of the file.
+pub(super) fn cmd_set(rt: &Runtime, key: &str, value: &str, local: bool) -> i32 {
+    let path = if local {
+        local_rewrites(rt)
+    } else {
+        rt.global_config_path().as_deref().map(global_rewrites)
+    };
+    let Some(path) = path else {
+        eprintln!("[tokf] cannot determine where rewrites.toml goes");
+        return 1;
+    };
+    let rc = set_in(&path, key, value);
+    if rc == 0
+        && !local
+        && let Some(local_path) = local_rewrites(rt).filter(|p| p.is_file())
+    {
+        eprintln!(
+            "[tokf] note: {} takes precedence here; use --local to change it",
+            local_path.display()
+        );
+    }
+    rc
+}
+
+/// Set `key` to `value` in the `rewrites.toml` at `path`.
+pub(super) fn set_in(path: &Path, key: &str, value: &str) -> i32 {
+    let Ok(b) = value.parse::<bool>() else {
+        eprintln!("[tokf] invalid value for {key}: expected true or false");
+        return 1;
+    };
+    let mut table = if path.exists() {
+        match read_table(path) {
+            Ok(table) => table,
+            Err(e) => {
+                eprintln!(
+                    "[tokf] not overwriting {}, which does not parse: {e:#}",
+                    path.display()
+                );
+                return 1;
+            }
+        }
+    } else {
+        toml::Table::new()
+    };
+    let pipe = table
+        .entry("pipe")
+        .or_insert_with(|| toml::Value::Table(toml::Table::new()));
+    let Some(pipe) = pipe.as_table_mut() else {
+        eprintln!("[tokf] {}: `pipe` is not a table", path.display());
+        return 1;
+    };
+    pipe.insert(
+        key.trim_start_matches("pipe.").to_string(),
+        toml::Value::Boolean(b),
+    );
+    let written = path
+        .parent()
+        .map_or(Ok(()), std::fs::create_dir_all)
+        .map_err(anyhow::Error::from)
+        .and_then(|()| Ok(toml::to_string_pretty(&table)?))
+        .and_then(|content| tokf::fs::write_config_file(path, &content));
+    if let Err(e) = written {
+        eprintln!("[tokf] failed to write {}: {e:#}", path.display());
+        return 1;
+    }
+    0
+}
@@ -0,0 +1,181 @@
+//! `tokf config set`: validate a key and value, then write them to one config file.
+
+use tokf::history::{
+    self, TokfFiltersSection, TokfOutputSection, TokfProjectConfig, TokfRunSection,
+    TokfShimsSection, TokfSyncSection, global_config_path, load_project_config, local_config_path,
+    project_root_for, save_project_config,
+};
+use tokf::runtime::Runtime;
+
+use super::print_known_keys;
+
+pub(super) fn cmd_config_set(rt: &Runtime, key: &str, value: &str, local: bool) -> i32 {
+    if super::pipe::PIPE_KEYS.iter().any(|&(k, _)| k == key) {
+        return super::pipe::cmd_set(rt, key, value, local);
+    }
+    let target_path = if local {
+        let cwd = rt.cwd_or_empty();
+        let project_root = project_root_for(cwd);
+        local_config_path(&project_root)
+    } else {
+        let Some(p) = global_config_path(rt) else {
+            eprintln!("[tokf] cannot determine config directory");
+            return 1;
+        };
+        p
+    };
+
+    match key {
+        "shims.enabled" => {
+            if local {
+                eprintln!(
+                    "[tokf] shims.enabled is a global-only setting — \
+                     use without --local"
+                );
+                return 1;
+            }
+            let rc = set_parsed_field(&target_path, key, value, "true or false", |cfg, b| {
+                cfg.shims
+                    .get_or_insert(TokfShimsSection { enabled: None })
+                    .enabled = Some(b);
+            });
+            // Immediately remove stale shims when disabling
+            if rc == 0
+                && value == "false"
+                && let Some(dir) = rt.shims_dir()
+            {
+                let _ = std::fs::remove_dir_all(dir);
+            }
+            rc
+        }
+        "sync.upload_stats" => set_upload_stats(&target_path, value),
+        _ => set_field(&target_path, key, value),
+    }
+}
+
+/// Set any key that needs no special handling beyond parsing its value.
+fn set_field(path: &std::path::Path, key: &str, value: &str) -> i32 {
+    const COUNT: &str = "a non-negative integer";
+    const BOOL: &str = "true or false";
+    match key {
+        "history.retention" => set_parsed_field(path, key, value, COUNT, |cfg, n| {
+            history_section(cfg).retention = Some(n);
+        }),
+        "history.max_age_days" => set_parsed_field(path, key, value, COUNT, |cfg, n| {
+            history_section(cfg).max_age_days = Some(n);
+        }),
+        "history.max_total_mb" => set_parsed_field(path, key, value, COUNT, |cfg, n| {
+            history_section(cfg).max_total_mb = Some(n);
+        }),
+        "output.show_indicator" => set_parsed_field(path, key, value, BOOL, |cfg, b| {
+            cfg.output
+                .get_or_insert(TokfOutputSection {
+                    show_indicator: None,
+                })
+                .show_indicator = Some(b);
+        }),
+        "sync.auto_sync_threshold" => set_parsed_field(path, key, value, COUNT, |cfg, n| {
+            sync_section(cfg).auto_sync_threshold = Some(n);
+        }),
+        "sync.auto" => set_parsed_field(path, key, value, BOOL, |cfg, b| {
+            sync_section(cfg).auto = Some(b);
+        }),
+        "sync.auto_sync_interval_minutes" => set_parsed_field(path, key, value, COUNT, |cfg, n| {
+            sync_section(cfg).auto_sync_interval_minutes = Some(n);
+        }),
+        "run.mask_exit_code" => set_parsed_field(path, key, value, BOOL, |cfg, b| {
+            run_section(cfg).mask_exit_code = Some(b);
+        }),
+        "run.spill_threshold_mb" => set_parsed_field(path, key, value, COUNT, |cfg, n| {
+            run_section(cfg).spill_threshold_mb = Some(n);
+        }),
+        "filters.disabled" => set_parsed_field(
+            path,
+            key,
+            value,
+            "comma-separated filter names, e.g. git/log,cargo/build",
+            |cfg, names: FilterNames| {
+                cfg.filters
+                    .get_or_insert_with(TokfFiltersSection::default)
+                    .disabled = names.0;
+            },
+        ),
+        _ => {
+            eprintln!("[tokf] unknown config key: {key}");
+            print_known_keys();
+            1
+        }
+    }
+}
+
+fn history_section(cfg: &mut TokfProjectConfig) -> &mut history::TokfHistorySection {
+    cfg.history
+        .get_or_insert_with(history::TokfHistorySection::default)
+}
+
+fn sync_section(cfg: &mut TokfProjectConfig) -> &mut TokfSyncSection {
+    cfg.sync.get_or_insert_with(TokfSyncSection::default)
+}
+
+fn run_section(cfg: &mut TokfProjectConfig) -> &mut TokfRunSection {
+    cfg.run.get_or_insert_with(TokfRunSection::default)
+}
+
+/// A `filters.disabled` value: comma-separated filter names such as `git/log`.
+/// An empty value clears the list.
+#[derive(Debug, PartialEq, Eq)]
+pub(super) struct FilterNames(pub Vec<String>);
+
+impl std::str::FromStr for FilterNames {
+    type Err = ();
+
+    fn from_str(value: &str) -> Result<Self, ()> {
+        let mut names: Vec<String> = Vec::new();
+        for name in value.split(',').map(str::trim).filter(|n| !n.is_empty()) {
+            let name = name.strip_suffix(".toml").unwrap_or(name);
+            let valid = !name.starts_with('/')
+                && !name.contains(char::is_whitespace)
+                && name.split('/').all(|part| !part.is_empty() && part != "..");
+            if !valid {
+                return Err(());
+            }
+            if !names.iter().any(|n| n == name) {
+                names.push(name.to_string());
+            }
+        }
+        Ok(Self(names))
+    }
+}
+
+/// Parse a value of type `T` and apply it to the config via the given setter.
+pub(super) fn set_parsed_field<T: std::str::FromStr>(
+    path: &std::path::Path,
+    key: &str,
+    value: &str,
+    type_hint: &str,
+    apply: fn(&mut TokfProjectConfig, T),
+) -> i32 {
+    let Ok(parsed) = value.parse::<T>() else {
+        eprintln!("[tokf] invalid value for {key}: expected {type_hint}");
+        return 1;
+    };
+    let mut config = load_project_config(path);
+    apply(&mut config, parsed);
+    if let Err(e) = save_project_config(path, &config) {
+        eprintln!("[tokf] failed to write config: {e:#}");
+        return 1;
+    }
+    0
+}
+
+pub(super) fn set_upload_stats(path: &std::path::Path, value: &str) -> i32 {
+    let Ok(b) = value.parse::<bool>() else {
+        eprintln!("[tokf] invalid value for sync.upload_stats: expected true or false");
+        return 1;
+    };
+    if let Err(e) = history::save_upload_stats_to_path(path, b) {
+        eprintln!("[tokf] failed to write config: {e:#}");
+        return 1;
+    }
+    0
+}
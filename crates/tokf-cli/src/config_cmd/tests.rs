@@ -0,0 +1,301 @@
+use tempfile::TempDir;
+
+use tokf::history::{self, TokfShimsSection, TokfSyncSection};
+
+use super::set::{FilterNames, set_parsed_field, set_upload_stats};
+use super::*;
+
+#[test]
+fn collect_config_entries_defaults() {
+    let dir = TempDir::new().unwrap();
+    let rt = Runtime::isolated();
+    let entries = collect_config_entries(&rt, None, Some(dir.path()));
+    assert_eq!(entries.len(), KNOWN_KEYS.len());
+    assert_eq!(entries[0].key, "history.retention");
+    assert_eq!(entries[0].value.as_deref(), Some("10"));
+    assert_eq!(entries[0].source, "default");
+}
+
+#[test]
+fn collect_config_entries_from_local() {
+    let dir = TempDir::new().unwrap();
+    let tokf_dir = dir.path().join(".tokf");
+    std::fs::create_dir_all(&tokf_dir).unwrap();
+    let local = tokf_dir.join("config.toml");
+    std::fs::write(&local, "[history]\nretention = 42\n").unwrap();
+
+    let rt = Runtime::isolated();
+    let entries = collect_config_entries(&rt, None, Some(dir.path()));
+    assert_eq!(entries[0].value.as_deref(), Some("42"));
+    assert_eq!(entries[0].source, "local");
+}
+
+#[test]
+fn collect_config_entries_from_global() {
+    let dir = TempDir::new().unwrap();
+    let global = dir.path().join("global_config.toml");
+    std::fs::write(&global, "[sync]\nauto_sync_threshold = 200\n").unwrap();
+
+    let rt = Runtime::isolated();
+    let entries = collect_config_entries(&rt, Some(&global), Some(dir.path()));
+    assert_eq!(entries[3].value.as_deref(), Some("200"));
+    assert_eq!(entries[3].source, "global");
+}
+
+#[test]
+fn known_keys_are_valid() {
+    assert!(KNOWN_KEYS.contains(&"history.retention"));
+    assert!(KNOWN_KEYS.contains(&"shims.enabled"));
+    assert!(KNOWN_KEYS.contains(&"sync.auto_sync_threshold"));
+    assert!(KNOWN_KEYS.contains(&"sync.upload_stats"));
+}
+
+/// Helper: call `set_parsed_field` for `history.retention`.
+fn set_retention(path: &std::path::Path, value: &str) -> i32 {
+    set_parsed_field(
+        path,
+        "history.retention",
+        value,
+        "a non-negative integer",
+        |cfg, n| {
+            cfg.history
+                .get_or_insert_with(history::TokfHistorySection::default)
+                .retention = Some(n);
+        },
+    )
+}
+
+/// Helper: call `set_parsed_field` for `sync.auto_sync_threshold`.
+fn set_sync_threshold(path: &std::path::Path, value: &str) -> i32 {
+    set_parsed_field(
+        path,
+        "sync.auto_sync_threshold",
+        value,
+        "a non-negative integer",
+        |cfg, n| {
+            cfg.sync
+                .get_or_insert_with(TokfSyncSection::default)
+                .auto_sync_threshold = Some(n);
+        },
+    )
+}
+
+#[test]
+fn set_retention_valid() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("config.toml");
+    assert_eq!(set_retention(&path, "25"), 0);
+    let cfg = load_project_config(&path);
+    assert_eq!(cfg.history.unwrap().retention, Some(25));
+}
+
+#[test]
+fn set_retention_invalid() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("config.toml");
+    assert_eq!(set_retention(&path, "abc"), 1);
+}
+
+#[test]
+fn set_sync_threshold_valid() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("config.toml");
+    assert_eq!(set_sync_threshold(&path, "50"), 0);
+    let cfg = load_project_config(&path);
+    assert_eq!(cfg.sync.unwrap().auto_sync_threshold, Some(50));
+}
+
+#[test]
+fn set_upload_stats_valid() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("config.toml");
+    assert_eq!(set_upload_stats(&path, "true"), 0);
+    let cfg = load_project_config(&path);
+    assert_eq!(cfg.sync.unwrap().upload_usage_stats, Some(true));
+}
+
+#[test]
+fn set_upload_stats_invalid() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("config.toml");
+    assert_eq!(set_upload_stats(&path, "yes"), 1);
+}
+
+#[test]
+fn set_shims_enabled_valid() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("config.toml");
+    assert_eq!(
+        set_parsed_field(
+            &path,
+            "shims.enabled",
+            "false",
+            "true or false",
+            |cfg, b| {
+                cfg.shims
+                    .get_or_insert(TokfShimsSection { enabled: None })
+                    .enabled = Some(b);
+            }
+        ),
+        0
+    );
+    let cfg = load_project_config(&path);
+    assert_eq!(cfg.shims.unwrap().enabled, Some(false));
+}
+
+#[test]
+fn set_shims_enabled_invalid() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("config.toml");
+    assert_eq!(
+        set_parsed_field(&path, "shims.enabled", "yes", "true or false", |cfg, b| {
+            cfg.shims
+                .get_or_insert(TokfShimsSection { enabled: None })
+                .enabled = Some(b);
+        }),
+        1
+    );
+}
+
+#[test]
+fn collect_config_entries_shims_default() {
+    let dir = TempDir::new().unwrap();
+    let rt = Runtime::isolated();
+    let entries = collect_config_entries(&rt, None, Some(dir.path()));
+    let shims_entry = entries.iter().find(|e| e.key == "shims.enabled").unwrap();
+    assert_eq!(shims_entry.value.as_deref(), Some("true"));
+    assert_eq!(shims_entry.source, "default");
+}
+
+#[test]
+fn set_preserves_existing_fields() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("config.toml");
+    std::fs::write(&path, "[history]\nretention = 30\n").unwrap();
+
+    set_sync_threshold(&path, "200");
+
+    let cfg = load_project_config(&path);
+    assert_eq!(cfg.history.unwrap().retention, Some(30));
+    assert_eq!(cfg.sync.unwrap().auto_sync_threshold, Some(200));
+}
+
+#[test]
+fn collect_config_entries_without_project_ignores_local() {
+    let dir = TempDir::new().unwrap();
+    let tokf_dir = dir.path().join(".tokf");
+    std::fs::create_dir_all(&tokf_dir).unwrap();
+    std::fs::write(
+        tokf_dir.join("config.toml"),
+        "[run]\nmask_exit_code = false\n",
+    )
+    .unwrap();
+
+    let rt = Runtime::isolated();
+    let find = |entries: Vec<ConfigEntry>| {
+        entries
+            .into_iter()
+            .find(|e| e.key == "run.mask_exit_code")
+            .unwrap()
+    };
+    let local = find(collect_config_entries(&rt, None, Some(dir.path())));
+    assert_eq!(local.value.as_deref(), Some("false"));
+    assert_eq!(local.source, "local");
+    let global = find(collect_config_entries(&rt, None, None));
+    assert_eq!(global.value.as_deref(), Some("true"));
+    assert_eq!(global.source, "default");
+}
+
+#[test]
+fn filter_names_parse() {
+    assert_eq!(
+        " git/log, cargo/build.toml,git/log".parse(),
+        Ok(FilterNames(vec![
+            "git/log".to_string(),
+            "cargo/build".to_string()
+        ]))
+    );
+    assert_eq!("".parse(), Ok(FilterNames(vec![])));
+    assert!("git log".parse::<FilterNames>().is_err());
+    assert!("../git/log".parse::<FilterNames>().is_err());
+    assert!("/git/log".parse::<FilterNames>().is_err());
+}
+
+fn pipe_values(entries: &[ConfigEntry]) -> Vec<(&str, &str, &str)> {
+    entries
+        .iter()
+        .filter(|e| e.key.starts_with("pipe."))
+        .map(|e| {
+            (
+                e.key.as_str(),
+                e.value.as_deref().unwrap(),
+                e.source.as_str(),
+            )
+        })
+        .collect()
+}
+
+#[test]
+fn pipe_keys_come_from_the_first_rewrites_file() {
+    let dir = TempDir::new().unwrap();
+    let global = dir.path().join("user/config.toml");
+    std::fs::create_dir_all(global.parent().unwrap()).unwrap();
+    std::fs::write(
+        global.with_file_name("rewrites.toml"),
+        "[pipe]\nprefer_less = true\n",
+    )
+    .unwrap();
+    let rt = Runtime::builder().cwd(dir.path().to_path_buf()).build();
+
+    let entries = collect_config_entries(&rt, Some(&global), Some(dir.path()));
+    assert_eq!(
+        pipe_values(&entries),
+        [
+            ("pipe.strip", "true", "default"),
+            ("pipe.prefer_less", "true", "global")
+        ]
+    );
+
+    // A local rewrites.toml wins even without [pipe], as at run time.
+    std::fs::create_dir_all(dir.path().join(".tokf")).unwrap();
+    std::fs::write(
+        dir.path().join(".tokf/rewrites.toml"),
+        "[skip]\npatterns = []\n",
+    )
+    .unwrap();
+    let entries = collect_config_entries(&rt, Some(&global), Some(dir.path()));
+    assert_eq!(
+        pipe_values(&entries)[1],
+        ("pipe.prefer_less", "false", "default")
+    );
+    let entries = collect_config_entries(&rt, Some(&global), None);
+    assert_eq!(
+        pipe_values(&entries)[1],
+        ("pipe.prefer_less", "true", "global")
+    );
+}
+
+#[test]
+fn set_pipe_key_keeps_the_rest_of_rewrites_toml() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join(".tokf/rewrites.toml");
+    assert_eq!(pipe::set_in(&path, "pipe.strip", "false"), 0);
+    std::fs::write(
+        &path,
+        "[skip]\npatterns = [\"^make\"]\n\n[pipe]\nstrip = false\n",
+    )
+    .unwrap();
+    assert_eq!(pipe::set_in(&path, "pipe.prefer_less", "true"), 0);
+    assert_eq!(pipe::set_in(&path, "pipe.strip", "maybe"), 1);
+
+    let config: tokf::rewrite::types::RewriteConfig =
+        toml::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+    let pipe = config.pipe.unwrap();
+    assert!(!pipe.strip);
+    assert!(pipe.prefer_less);
+    assert_eq!(config.skip.unwrap().patterns, ["^make"]);
+
+    std::fs::write(&path, "not = [toml").unwrap();
+    assert_eq!(pipe::set_in(&path, "pipe.strip", "true"), 1);
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), "not = [toml");
+}
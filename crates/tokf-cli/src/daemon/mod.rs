@@ -0,0 +1,124 @@
+//! `tokf daemon`: answers hook rewrites over a unix socket, with the filter
+//! patterns kept compiled in memory.
+//!
+//! Without it every hook call discovers filters and compiles their patterns
+//! afresh. With it, `tokf hook handle` sends the command to the daemon and
+//! only rewrites in-process when no daemon answers. The protocol is one JSON
+//! [`Request`] line and one JSON [`Response`] line per connection.
+
+#[cfg(unix)]
+mod server;
+
+use std::path::{Path, PathBuf};
+#[cfg(unix)]
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::rewrite::types::{RewriteConfig, RewriteOptions};
+use crate::rewrite::{self, RewriteCtx};
+use crate::runtime::Runtime;
+
+#[cfg(unix)]
+pub use server::{run, serve};
+
+/// How long a client waits for the daemon before rewriting in-process.
+#[cfg(unix)]
+const CLIENT_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// A request to the daemon.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum Request {
+    Rewrite(Box<RewriteRequest>),
+    Status,
+    Stop,
+}
+
+/// Everything a rewrite depends on, so the daemon's answer matches what the
+/// client would have computed itself.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RewriteRequest {
+    /// The client's tokf version; a daemon of another version declines.
+    pub version: String,
+    pub cwd: PathBuf,
+    pub command: String,
+    pub user_config: RewriteConfig,
+    pub search_dirs: Vec<PathBuf>,
+    pub no_cache: bool,
+    pub no_mask_exit_code: bool,
+}
+
+/// The daemon's answer.
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(tag = "result", rename_all = "snake_case")]
+pub enum Response {
+    Rewritten {
+        command: String,
+    },
+    Status {
+        pid: u32,
+        version: String,
+        /// Requests answered since start.
+        served: u64,
+        /// Pattern sets held in memory.
+        pattern_sets: usize,
+    },
+    Stopping,
+    Error {
+        message: String,
+    },
+}
+
+/// Where the daemon listens.
+pub fn socket_path(rt: &Runtime) -> Option<PathBuf> {
+    rt.user_cache_dir().map(|d| d.join("daemon.sock"))
+}
+
+/// Rewrite `command` through the daemon when one is running, and in this
+/// process otherwise.
+pub(crate) fn rewrite(ctx: RewriteCtx<'_>, command: &str, options: &RewriteOptions) -> String {
+    let request = Request::Rewrite(Box::new(RewriteRequest {
+        version: env!("CARGO_PKG_VERSION").to_string(),
+        cwd: ctx.rt.cwd_or_empty().to_path_buf(),
+        command: command.to_string(),
+        user_config: ctx.user_config.clone(),
+        search_dirs: ctx.search_dirs.to_vec(),
+        no_cache: ctx.no_cache,
+        no_mask_exit_code: options.no_mask_exit_code,
+    }));
+    if let Some(path) = socket_path(ctx.rt)
+        && let Some(Response::Rewritten { command }) = send(&path, &request)
+    {
+        return command;
+    }
+    rewrite::rewrite_with_config_and_options(ctx, command, false, options)
+}
+
+/// Send one request to the daemon at `path`. `None` when no daemon answers.
+#[cfg(unix)]
+pub fn send(path: &Path, request: &Request) -> Option<Response> {
+    use std::io::{BufRead, BufReader, Write};
+    use std::os::unix::net::UnixStream;
+
+    let mut stream = UnixStream::connect(path).ok()?;
+    stream.set_read_timeout(Some(CLIENT_TIMEOUT)).ok()?;
+    stream.set_write_timeout(Some(CLIENT_TIMEOUT)).ok()?;
+    let mut line = serde_json::to_string(request).ok()?;
+    line.push('\n');
+    stream.write_all(line.as_bytes()).ok()?;
+    let mut reply = String::new();
+    BufReader::new(stream).read_line(&mut reply).ok()?;
+    serde_json::from_str(&reply).ok()
+}
+
+/// Send one request to the daemon at `path`. There is no daemon on this
+/// platform, so the answer is always `None`.
+#[cfg(not(unix))]
+pub const fn send(_path: &Path, _request: &Request) -> Option<Response> {
+    None
+}
+
+#[cfg(all(test, unix))]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests;
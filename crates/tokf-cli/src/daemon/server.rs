@@ -0,0 +1,114 @@
+use std::io::{BufRead, BufReader, Write};
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::time::Duration;
+
+use anyhow::Context as _;
+
+use super::{Request, Response, RewriteRequest, send, socket_path};
+use crate::rewrite::types::RewriteOptions;
+use crate::rewrite::{self, PatternCache, RewriteCtx};
+use crate::runtime::Runtime;
+
+/// How long the daemon waits for a connected client to send its request.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Listen on the socket from [`socket_path`] until a `stop` request.
+///
+/// # Errors
+///
+/// Returns an error if another daemon is running or the socket cannot be bound.
+pub fn run(rt: &Runtime) -> anyhow::Result<()> {
+    let path = socket_path(rt).context("cannot determine the tokf cache directory")?;
+    if path.exists() {
+        if send(&path, &Request::Status).is_some() {
+            anyhow::bail!("a daemon is already listening on {}", path.display());
+        }
+        // Left behind by a daemon that did not shut down cleanly.
+        std::fs::remove_file(&path)
+            .with_context(|| format!("remove stale socket {}", path.display()))?;
+    }
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let listener = UnixListener::bind(&path).with_context(|| format!("bind {}", path.display()))?;
+    eprintln!("[tokf] daemon listening on {}", path.display());
+    let result = serve(rt, &listener);
+    let _ = std::fs::remove_file(&path);
+    result
+}
+
+/// Answer connections on `listener`, one at a time, until a `stop` request.
+///
+/// # Errors
+///
+/// Returns an error if accepting a connection fails.
+pub fn serve(rt: &Runtime, listener: &UnixListener) -> anyhow::Result<()> {
+    let cache = PatternCache::default();
+    let mut served: u64 = 0;
+    for stream in listener.incoming() {
+        let stream = stream.context("accept connection")?;
+        let Some(request) = read_request(&stream) else {
+            continue;
+        };
+        served += 1;
+        let stop = matches!(request, Request::Stop);
+        let response = match request {
+            Request::Rewrite(req) => rewrite(rt, &cache, *req),
+            Request::Status => Response::Status {
+                pid: std::process::id(),
+                version: env!("CARGO_PKG_VERSION").to_string(),
+                served,
+                pattern_sets: cache.len(),
+            },
+            Request::Stop => Response::Stopping,
+        };
+        write_response(stream, &response);
+        if stop {
+            break;
+        }
+    }
+    Ok(())
+}
+
+fn read_request(stream: &UnixStream) -> Option<Request> {
+    stream.set_read_timeout(Some(REQUEST_TIMEOUT)).ok()?;
+    let mut line = String::new();
+    BufReader::new(stream).read_line(&mut line).ok()?;
+    serde_json::from_str(&line).ok()
+}
+
+fn write_response(mut stream: UnixStream, response: &Response) {
+    if let Ok(mut json) = serde_json::to_string(response) {
+        json.push('\n');
+        // The client falls back to rewriting in-process if this fails.
+        let _ = stream.write_all(json.as_bytes());
+    }
+}
+
+fn rewrite(rt: &Runtime, cache: &PatternCache, req: RewriteRequest) -> Response {
+    if req.version != env!("CARGO_PKG_VERSION") {
+        return Response::Error {
+            message: format!(
+                "daemon is tokf {}, client is {}",
+                env!("CARGO_PKG_VERSION"),
+                req.version
+            ),
+        };
+    }
+    let rt = rt.with_cwd(req.cwd);
+    let command = rewrite::rewrite_with_config_and_options(
+        RewriteCtx {
+            rt: &rt,
+            user_config: &req.user_config,
+            search_dirs: &req.search_dirs,
+            no_cache: req.no_cache,
+            pattern_cache: Some(cache),
+        },
+        &req.command,
+        false,
+        &RewriteOptions {
+            no_mask_exit_code: req.no_mask_exit_code,
+        },
+    );
+    Response::Rewritten { command }
+}
@@ -0,0 +1,102 @@
+use std::os::unix::net::UnixListener;
+use std::path::Path;
+
+use super::*;
+use crate::runtime::Runtime;
+
+fn rewrite_request(cwd: &Path, filters: &Path, command: &str) -> Request {
+    Request::Rewrite(Box::new(RewriteRequest {
+        version: env!("CARGO_PKG_VERSION").to_string(),
+        cwd: cwd.to_path_buf(),
+        command: command.to_string(),
+        user_config: RewriteConfig::default(),
+        search_dirs: vec![filters.to_path_buf()],
+        no_cache: false,
+        no_mask_exit_code: false,
+    }))
+}
+
+/// Run a daemon on a socket in `dir`, hand its path to `client`, then stop it.
+fn with_daemon(dir: &Path, client: impl FnOnce(&Path)) {
+    let socket = dir.join("daemon.sock");
+    let listener = UnixListener::bind(&socket).unwrap();
+    let rt = Runtime::isolated();
+    let server = std::thread::spawn(move || serve(&rt, &listener));
+    client(&socket);
+    assert_eq!(send(&socket, &Request::Stop), Some(Response::Stopping));
+    server.join().unwrap().unwrap();
+}
+
+#[test]
+fn rewrites_match_the_in_process_path() {
+    let dir = tempfile::TempDir::new().unwrap();
+    std::fs::write(
+        dir.path().join("git-status.toml"),
+        "command = \"git status\"",
+    )
+    .unwrap();
+    with_daemon(dir.path(), |socket| {
+        let request = rewrite_request(dir.path(), dir.path(), "git status");
+        assert_eq!(
+            send(socket, &request),
+            Some(Response::Rewritten {
+                command: "tokf run git status".to_string()
+            })
+        );
+        let request = rewrite_request(dir.path(), dir.path(), "frobnicate --all");
+        assert_eq!(
+            send(socket, &request),
+            Some(Response::Rewritten {
+                command: "frobnicate --all".to_string()
+            })
+        );
+    });
+}
+
+#[test]
+fn status_reports_cached_pattern_sets() {
+    let dir = tempfile::TempDir::new().unwrap();
+    with_daemon(dir.path(), |socket| {
+        send(
+            socket,
+            &rewrite_request(dir.path(), dir.path(), "git status"),
+        )
+        .unwrap();
+        let status = send(socket, &Request::Status);
+        assert!(
+            matches!(
+                status,
+                Some(Response::Status {
+                    served: 2,
+                    pattern_sets: 1,
+                    ..
+                })
+            ),
+            "{status:?}"
+        );
+    });
+}
+
+#[test]
+fn other_versions_are_declined() {
+    let dir = tempfile::TempDir::new().unwrap();
+    with_daemon(dir.path(), |socket| {
+        let mut request = rewrite_request(dir.path(), dir.path(), "git status");
+        if let Request::Rewrite(req) = &mut request {
+            req.version = "0.0.0-other".to_string();
+        }
+        assert!(matches!(
+            send(socket, &request),
+            Some(Response::Error { .. })
+        ));
+    });
+}
+
+#[test]
+fn no_daemon_means_no_answer() {
+    let dir = tempfile::TempDir::new().unwrap();
+    assert_eq!(
+        send(&dir.path().join("daemon.sock"), &Request::Status),
+        None
+    );
+}
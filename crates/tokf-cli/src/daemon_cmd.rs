@@ -0,0 +1,48 @@
+use tokf::daemon::{self, Request, Response};
+use tokf::runtime::Runtime;
+
+/// `tokf daemon run`: serve hook rewrites until stopped.
+#[cfg(unix)]
+pub fn cmd_daemon_run(rt: &Runtime) -> anyhow::Result<i32> {
+    daemon::run(rt)?;
+    Ok(0)
+}
+
+/// `tokf daemon run`: serve hook rewrites until stopped.
+#[cfg(not(unix))]
+pub fn cmd_daemon_run(_rt: &Runtime) -> anyhow::Result<i32> {
+    anyhow::bail!("tokf daemon needs unix sockets, which this platform lacks")
+}
+
+/// `tokf daemon stop`: ask a running daemon to exit.
+pub fn cmd_daemon_stop(rt: &Runtime) -> i32 {
+    if request(rt, &Request::Stop) == Some(Response::Stopping) {
+        println!("daemon stopped");
+        0
+    } else {
+        println!("daemon not running");
+        1
+    }
+}
+
+/// `tokf daemon status`: report whether a daemon is answering.
+pub fn cmd_daemon_status(rt: &Runtime) -> i32 {
+    let Some(Response::Status {
+        pid,
+        version,
+        served,
+        pattern_sets,
+    }) = request(rt, &Request::Status)
+    else {
+        println!("daemon: not running");
+        return 1;
+    };
+    println!("daemon: running (pid {pid}, tokf {version})");
+    println!("  requests served: {served}");
+    println!("  pattern sets:    {pattern_sets}");
+    0
+}
+
+fn request(rt: &Runtime, request: &Request) -> Option<Response> {
+    daemon::send(&daemon::socket_path(rt)?, request)
+}
@@ -0,0 +1,81 @@
+use clap::Subcommand;
+
+use tokf::history::TrackingConfig;
+use tokf::remote::machine;
+use tokf::runtime::Runtime;
+use tokf::tracking::{self, Archive, MaintainOptions, MaintainReport};
+
+#[derive(Subcommand)]
+pub enum DbAction {
+    /// Archive old events, vacuum the tracking DB and check its integrity
+    Maintain {
+        /// Archive events older than this many days (default: `[tracking]
+        /// archive_after_days`, else none)
+        #[arg(long, value_name = "DAYS")]
+        archive_after: Option<u32>,
+        /// Skip VACUUM, which rewrites the whole DB file
+        #[arg(long)]
+        no_vacuum: bool,
+    },
+}
+
+pub fn run_db_action(rt: &Runtime, action: &DbAction) -> anyhow::Result<i32> {
+    match action {
+        DbAction::Maintain {
+            archive_after,
+            no_vacuum,
+        } => cmd_db_maintain(rt, *archive_after, !no_vacuum),
+    }
+}
+
+fn cmd_db_maintain(rt: &Runtime, archive_after: Option<u32>, vacuum: bool) -> anyhow::Result<i32> {
+    let path = rt
+        .tracking_db_path()
+        .ok_or_else(|| anyhow::anyhow!("cannot determine tracking DB path"))?;
+    if !path.exists() {
+        println!("no tracking DB at {}", path.display());
+        return Ok(0);
+    }
+    let conn = tracking::open_db(&path)?;
+    let after_days = archive_after.or_else(|| TrackingConfig::load(rt).archive_after_days);
+    let options = MaintainOptions {
+        archive: after_days.map(|after_days| Archive {
+            after_days,
+            keep_unsynced: machine::load(rt).is_some(),
+        }),
+        vacuum,
+    };
+    let report = tracking::maintain(&conn, &path, options)?;
+    print_report(&report, options);
+    Ok(i32::from(!report.problems.is_empty()))
+}
+
+fn print_report(report: &MaintainReport, options: MaintainOptions) {
+    match (&report.archived, options.archive.map(|a| a.after_days)) {
+        (Some((count, file)), Some(days)) => println!(
+            "archive:   {count} events older than {days} days -> {}",
+            file.display()
+        ),
+        (None, Some(days)) => println!("archive:   no events older than {days} days"),
+        _ => println!("archive:   off (set --archive-after or [tracking] archive_after_days)"),
+    }
+    let size = format!("{} -> {}", mib(report.size_before), mib(report.size_after));
+    if options.vacuum {
+        println!("vacuum:    {size}");
+    } else {
+        println!("vacuum:    skipped ({size})");
+    }
+    if report.problems.is_empty() {
+        println!("integrity: ok");
+    } else {
+        println!("integrity: {} problem(s)", report.problems.len());
+        for problem in &report.problems {
+            println!("  {problem}");
+        }
+    }
+}
+
+#[allow(clippy::cast_precision_loss)]
+fn mib(bytes: u64) -> String {
+    format!("{:.1} MiB", bytes as f64 / (1024.0 * 1024.0))
+}
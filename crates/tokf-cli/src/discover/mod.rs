@@ -241,6 +241,9 @@ pub fn classify_command(
 
     for (filter, name) in filters.iter().zip(filter_names) {
         if filter.matches(&words).is_some() {
+            if filter.is_excluded(&words) {
+                return CommandAnalysis::NoFilter;
+            }
             return CommandAnalysis::Filterable {
                 filter_name: name.clone(),
                 normalized_command: normalized,
@@ -379,7 +382,7 @@ fn load_historical_ratios(rt: &Runtime) -> HashMap<String, f64> {
     let Ok(conn) = tracking::open_db(&db_path) else {
         return ratios;
     };
-    let Ok(gains) = tracking::query_by_filter(&conn) else {
+    let Ok(gains) = tracking::query_by_filter(&conn, &tracking::TimeRange::ALL) else {
         return ratios;
     };
     for gain in gains {
@@ -8,6 +8,7 @@
 pub mod noise;
 pub mod queries;
 pub mod render;
+pub mod setup;
 
 use std::collections::{BTreeMap, BTreeSet, HashMap};
 
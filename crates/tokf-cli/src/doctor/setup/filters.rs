@@ -0,0 +1,101 @@
+//! Checks over the filter files in the search directories.
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use crate::config::{self, ResolvedFilter};
+
+use super::{Check, Status};
+
+/// Every filter file in the search directories, parsed, plus the ones that
+/// failed to parse (which filter discovery skips without a word).
+pub struct FilterScan {
+    pub filters: Vec<ResolvedFilter>,
+    /// `(path, error)` for each file that is not a valid filter.
+    pub invalid: Vec<(PathBuf, String)>,
+}
+
+pub fn scan_filter_files(search_dirs: &[PathBuf]) -> FilterScan {
+    let mut filters = Vec::new();
+    let mut invalid = Vec::new();
+    for (priority, dir) in search_dirs.iter().enumerate() {
+        for path in config::discover_filter_files(dir) {
+            match config::try_load_filter(&path) {
+                Ok(Some(cfg)) => {
+                    filters.push(ResolvedFilter::from_file(cfg, dir, path, priority));
+                }
+                Ok(None) => {}
+                Err(e) => invalid.push((path, format!("{e:#}"))),
+            }
+        }
+    }
+    FilterScan { filters, invalid }
+}
+
+pub fn check_invalid_filters(scan: &FilterScan) -> Check {
+    const NAME: &str = "filter files";
+    if scan.invalid.is_empty() {
+        return Check::new(
+            NAME,
+            Status::Ok,
+            format!("{} filter file(s) parse", scan.filters.len()),
+        );
+    }
+    let details = scan
+        .invalid
+        .iter()
+        .map(|(path, e)| format!("{}: {}", path.display(), first_line(e)))
+        .collect();
+    Check::new(
+        NAME,
+        Status::Fail,
+        format!(
+            "{} file(s) are not valid filters and are ignored",
+            scan.invalid.len()
+        ),
+    )
+    .with_details(details)
+    .with_fix("fix or remove them; `tokf lint <file>` shows the full error")
+}
+
+/// Filters on disk that never run because a filter earlier in the search
+/// order claims the same command. Overriding a built-in filter is what
+/// ejecting is for, so built-ins losing out are not reported.
+pub fn check_shadowed_filters(scan: &FilterScan) -> Check {
+    const NAME: &str = "shadowed filters";
+    let mut ranked: Vec<&ResolvedFilter> = scan.filters.iter().collect();
+    ranked.sort_by(|a, b| config::rank_order(a, b));
+
+    let mut winners: HashMap<&str, &ResolvedFilter> = HashMap::new();
+    let mut details = Vec::new();
+    for filter in ranked {
+        let command = filter.config.command.first();
+        match winners.get(command) {
+            Some(winner) if winner.source_path != filter.source_path => {
+                details.push(format!(
+                    "{} is shadowed by {} (both match \"{command}\")",
+                    filter.source_path.display(),
+                    winner.source_path.display(),
+                ));
+            }
+            Some(_) => {}
+            None => {
+                winners.insert(command, filter);
+            }
+        }
+    }
+    if details.is_empty() {
+        return Check::new(NAME, Status::Ok, "none");
+    }
+    Check::new(
+        NAME,
+        Status::Warn,
+        format!("{} filter(s) never run", details.len()),
+    )
+    .with_details(details)
+    .with_fix("remove or rename the unused copies; `tokf which \"<command>\"` shows which one runs")
+}
+
+fn first_line(s: &str) -> &str {
+    s.lines().next().unwrap_or(s)
+}
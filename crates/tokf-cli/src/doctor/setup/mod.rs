@@ -0,0 +1,298 @@
+//! `tokf doctor --setup` — checks of the local installation rather than of
+//! filter behaviour.
+//!
+//! Can tokf write its database and cache, is a hook installed, is the login
+//! still valid, can the registry be reached, and do the filter directories
+//! hold anything broken or unreachable? Every failed check carries the
+//! command or setting that fixes it.
+
+mod filters;
+
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::Serialize;
+
+use crate::auth::credentials::{self, StoredAuth};
+use crate::config::{self, cache};
+use crate::remote::http::Client;
+use crate::runtime::Runtime;
+use crate::tracking;
+
+use super::render::Colors;
+
+pub use filters::{check_invalid_filters, check_shadowed_filters, scan_filter_files};
+
+/// A login this close to expiry is reported as a warning.
+const EXPIRY_WARNING_DAYS: u64 = 7;
+
+/// Hook configuration files written by `tokf hook install`, relative to the
+/// project or home directory.
+const HOOK_FILES: &[(&str, &str)] = &[
+    ("Claude Code", ".claude/settings.json"),
+    ("Gemini CLI", ".gemini/settings.json"),
+    ("Cursor", ".cursor/hooks.json"),
+    ("Codex", ".codex/hooks.json"),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Status {
+    Ok,
+    Warn,
+    Fail,
+    Skipped,
+}
+
+/// The outcome of one check.
+#[derive(Debug, Clone, Serialize)]
+pub struct Check {
+    pub name: &'static str,
+    pub status: Status,
+    pub summary: String,
+    /// One line per affected file or tool.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub details: Vec<String>,
+    /// What to do about a warning or failure.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fix: Option<String>,
+}
+
+impl Check {
+    fn new(name: &'static str, status: Status, summary: impl Into<String>) -> Self {
+        Self {
+            name,
+            status,
+            summary: summary.into(),
+            details: Vec::new(),
+            fix: None,
+        }
+    }
+
+    fn with_fix(mut self, fix: impl Into<String>) -> Self {
+        self.fix = Some(fix.into());
+        self
+    }
+
+    fn with_details(mut self, details: Vec<String>) -> Self {
+        self.details = details;
+        self
+    }
+}
+
+/// Options for [`run_checks`].
+#[derive(Debug, Clone, Copy)]
+pub struct SetupOpts<'a> {
+    /// Home directory holding global hook configuration.
+    pub home: Option<&'a Path>,
+    /// Skip the registry request.
+    pub offline: bool,
+}
+
+/// Run every check, in a fixed order.
+pub fn run_checks(rt: &Runtime, opts: &SetupOpts<'_>) -> Vec<Check> {
+    let search_dirs = config::default_search_dirs(rt);
+    let scan = scan_filter_files(&search_dirs);
+    vec![
+        check_tracking_db(rt),
+        check_cache(rt, &search_dirs),
+        check_hooks(rt.cwd(), opts.home),
+        check_auth(rt),
+        check_registry(rt, opts.offline),
+        check_invalid_filters(&scan),
+        check_shadowed_filters(&scan),
+    ]
+}
+
+/// Whether any check failed outright.
+pub fn any_failed(checks: &[Check]) -> bool {
+    checks.iter().any(|c| c.status == Status::Fail)
+}
+
+pub fn check_tracking_db(rt: &Runtime) -> Check {
+    const NAME: &str = "tracking database";
+    let Some(path) = rt.tracking_db_path() else {
+        return Check::new(NAME, Status::Fail, "no location for tracking.db")
+            .with_fix("set TOKF_HOME or TOKF_DB_PATH");
+    };
+    match tracking::open_db(&path) {
+        Ok(_) => Check::new(NAME, Status::Ok, format!("{} is writable", path.display())),
+        Err(e) => Check::new(NAME, Status::Fail, format!("{e:#}")).with_fix(format!(
+            "make {} writable, or point TOKF_DB_PATH at a writable file",
+            path.parent().unwrap_or(&path).display()
+        )),
+    }
+}
+
+pub fn check_cache(rt: &Runtime, search_dirs: &[PathBuf]) -> Check {
+    const NAME: &str = "filter cache";
+    let Some(path) = cache::cache_path(rt, search_dirs) else {
+        return Check::new(NAME, Status::Skipped, "no cache location");
+    };
+    if !path.exists() {
+        return Check::new(NAME, Status::Ok, "not built yet; built on the next run");
+    }
+    match cache::load_manifest(&path) {
+        Ok(manifest) if cache::is_cache_valid(&manifest, search_dirs) => Check::new(
+            NAME,
+            Status::Ok,
+            format!("{} is up to date", path.display()),
+        ),
+        Ok(_) => Check::new(NAME, Status::Ok, "stale; rebuilt on the next run"),
+        Err(e) => Check::new(NAME, Status::Fail, format!("{}: {e:#}", path.display()))
+            .with_fix("run `tokf cache clear`"),
+    }
+}
+
+/// Look for tokf in each tool's hook configuration, in the project and in
+/// the home directory.
+pub fn check_hooks(cwd: Option<&Path>, home: Option<&Path>) -> Check {
+    const NAME: &str = "hooks";
+    let mut installed = Vec::new();
+    for (scope, base) in [("project", cwd), ("global", home)] {
+        let Some(base) = base else { continue };
+        for (tool, rel) in HOOK_FILES {
+            let path = base.join(rel);
+            let has_tokf = std::fs::read_to_string(&path).is_ok_and(|c| c.contains("tokf"));
+            if has_tokf {
+                installed.push(format!("{tool} ({scope}): {}", path.display()));
+            }
+        }
+    }
+    if installed.is_empty() {
+        let tools: Vec<&str> = HOOK_FILES.iter().map(|(tool, _)| *tool).collect();
+        return Check::new(
+            NAME,
+            Status::Warn,
+            format!("no tokf hook found for {}", tools.join(", ")),
+        )
+        .with_fix("run `tokf setup`, or `tokf hook install --tool <tool>`");
+    }
+    Check::new(
+        NAME,
+        Status::Ok,
+        format!("installed in {} place(s)", installed.len()),
+    )
+    .with_details(installed)
+}
+
+pub fn check_auth(rt: &Runtime) -> Check {
+    const NAME: &str = "login";
+    let Some(path) = credentials::auth_config_path(rt) else {
+        return Check::new(NAME, Status::Skipped, "no tokf config directory");
+    };
+    let Ok(content) = std::fs::read_to_string(&path) else {
+        return Check::new(
+            NAME,
+            Status::Ok,
+            "not logged in (only needed to publish, install and sync)",
+        );
+    };
+    let Ok(stored) = toml::from_str::<StoredAuth>(&content) else {
+        return Check::new(
+            NAME,
+            Status::Fail,
+            format!("{} is malformed", path.display()),
+        )
+        .with_fix("run `tokf auth login`");
+    };
+    if credentials::load(rt).is_none() {
+        return Check::new(
+            NAME,
+            Status::Fail,
+            format!("{} has no stored token", stored.username),
+        )
+        .with_fix("run `tokf auth login`");
+    }
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    auth_expiry(&stored, now)
+}
+
+/// Classify a stored login by how long its token has left at `now`.
+pub fn auth_expiry(stored: &StoredAuth, now: u64) -> Check {
+    const NAME: &str = "login";
+    const DAY: u64 = 24 * 60 * 60;
+    let user = &stored.username;
+    if stored.expires_at == 0 {
+        return Check::new(
+            NAME,
+            Status::Ok,
+            format!("logged in as {user} (expiry unknown)"),
+        );
+    }
+    if now >= stored.expires_at {
+        let days = (now - stored.expires_at) / DAY;
+        return Check::new(
+            NAME,
+            Status::Fail,
+            format!("token for {user} expired {days} day(s) ago"),
+        )
+        .with_fix("run `tokf auth login`");
+    }
+    let days = (stored.expires_at - now) / DAY;
+    if days < EXPIRY_WARNING_DAYS {
+        return Check::new(
+            NAME,
+            Status::Warn,
+            format!("token for {user} expires in {days} day(s)"),
+        )
+        .with_fix("run `tokf auth login` to renew it");
+    }
+    Check::new(
+        NAME,
+        Status::Ok,
+        format!("logged in as {user}, token valid for {days} more day(s)"),
+    )
+}
+
+pub fn check_registry(rt: &Runtime, offline: bool) -> Check {
+    const NAME: &str = "registry";
+    if offline {
+        return Check::new(NAME, Status::Skipped, "skipped (--offline)");
+    }
+    let url = rt.server_url();
+    let reached = Client::unauthenticated(rt, url).and_then(|client| client.get_raw("/health"));
+    match reached {
+        Ok(_) => Check::new(NAME, Status::Ok, format!("{url} is reachable")),
+        Err(e) => Check::new(NAME, Status::Fail, format!("{url}: {e:#}")).with_fix(
+            "check the network connection and proxy settings; TOKF_SERVER_URL sets the registry address",
+        ),
+    }
+}
+
+/// Render the checks as a human-readable block.
+pub fn render_checks(checks: &[Check], colors: &Colors) -> String {
+    let width = checks.iter().map(|c| c.name.len()).max().unwrap_or(0);
+    let mut out = String::new();
+    for check in checks {
+        let (icon, color) = match check.status {
+            Status::Ok => ("\u{2713}", colors.green),
+            Status::Warn => ("!", colors.yellow),
+            Status::Fail => ("\u{2717}", colors.red),
+            Status::Skipped => ("-", colors.dim),
+        };
+        let _ = writeln!(
+            out,
+            "{color}{icon}{reset} {bold}{name:<width$}{reset}  {summary}",
+            reset = colors.reset,
+            bold = colors.bold,
+            name = check.name,
+            summary = check.summary,
+        );
+        for detail in &check.details {
+            let _ = writeln!(out, "    {}{detail}{}", colors.dim, colors.reset);
+        }
+        if let Some(fix) = &check.fix {
+            let _ = writeln!(out, "    {}fix:{} {fix}", colors.cyan, colors.reset);
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests;
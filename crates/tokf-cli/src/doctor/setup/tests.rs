@@ -0,0 +1,171 @@
+use std::path::Path;
+
+use super::*;
+
+fn stored(expires_at: u64) -> StoredAuth {
+    StoredAuth {
+        username: "octocat".to_string(),
+        server_url: "https://api.tokf.net".to_string(),
+        expires_at,
+        mit_license_accepted: None,
+        tos_accepted_version: None,
+    }
+}
+
+fn write(path: &Path, content: &str) {
+    std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+    std::fs::write(path, content).unwrap();
+}
+
+// --- login ---
+
+#[test]
+fn auth_expiry_classifies_remaining_time() {
+    const DAY: u64 = 24 * 60 * 60;
+    let now = 1_000 * DAY;
+    assert_eq!(auth_expiry(&stored(0), now).status, Status::Ok);
+    assert_eq!(auth_expiry(&stored(now + 30 * DAY), now).status, Status::Ok);
+
+    let soon = auth_expiry(&stored(now + 2 * DAY), now);
+    assert_eq!(soon.status, Status::Warn);
+    assert!(soon.summary.contains("2 day(s)"), "{}", soon.summary);
+
+    let expired = auth_expiry(&stored(now - 3 * DAY), now);
+    assert_eq!(expired.status, Status::Fail);
+    assert_eq!(expired.fix.as_deref(), Some("run `tokf auth login`"));
+}
+
+#[test]
+fn no_login_is_fine() {
+    let rt = Runtime::isolated();
+    let check = check_auth(&rt);
+    assert_eq!(check.status, Status::Ok);
+    assert!(check.summary.contains("not logged in"));
+}
+
+// --- hooks ---
+
+#[test]
+fn hooks_are_found_in_project_and_home() {
+    let project = tempfile::TempDir::new().unwrap();
+    let home = tempfile::TempDir::new().unwrap();
+    assert_eq!(
+        check_hooks(Some(project.path()), Some(home.path())).status,
+        Status::Warn
+    );
+
+    write(
+        &project.path().join(".claude/settings.json"),
+        r#"{"hooks":{"PreToolUse":[{"command":"/p/.tokf/hooks/pre-tool-use.sh"}]}}"#,
+    );
+    write(&home.path().join(".cursor/hooks.json"), r#"{"hooks":{}}"#);
+    write(
+        &home.path().join(".gemini/settings.json"),
+        r#"{"hooks":{"BeforeTool":[{"command":"tokf hook handle --format gemini"}]}}"#,
+    );
+
+    let check = check_hooks(Some(project.path()), Some(home.path()));
+    assert_eq!(check.status, Status::Ok);
+    assert_eq!(check.details.len(), 2, "{:?}", check.details);
+    assert!(check.details[0].starts_with("Claude Code (project)"));
+    assert!(check.details[1].starts_with("Gemini CLI (global)"));
+}
+
+// --- filter files ---
+
+#[test]
+fn invalid_filter_files_are_reported() {
+    let dir = tempfile::TempDir::new().unwrap();
+    write(&dir.path().join("ok.toml"), "command = \"ok\"\n");
+    write(&dir.path().join("broken.toml"), "command = [\n");
+
+    let scan = scan_filter_files(&[dir.path().to_path_buf()]);
+    let check = check_invalid_filters(&scan);
+    assert_eq!(check.status, Status::Fail);
+    assert_eq!(check.details.len(), 1);
+    assert!(
+        check.details[0].contains("broken.toml"),
+        "{:?}",
+        check.details
+    );
+}
+
+#[test]
+fn filters_claiming_the_same_command_are_shadowed() {
+    let project = tempfile::TempDir::new().unwrap();
+    let user = tempfile::TempDir::new().unwrap();
+    write(
+        &project.path().join("cargo/build.toml"),
+        "command = \"cargo build\"\n",
+    );
+    write(
+        &user.path().join("mine/build.toml"),
+        "command = \"cargo build\"\n",
+    );
+    write(
+        &user.path().join("git/push.toml"),
+        "command = \"git push\"\n",
+    );
+
+    let dirs = [project.path().to_path_buf(), user.path().to_path_buf()];
+    let check = check_shadowed_filters(&scan_filter_files(&dirs));
+    assert_eq!(check.status, Status::Warn);
+    assert_eq!(check.details.len(), 1);
+    assert!(
+        check.details[0].contains("mine/build.toml is shadowed by"),
+        "{:?}",
+        check.details
+    );
+}
+
+#[test]
+fn a_search_dir_listed_twice_shadows_nothing() {
+    let dir = tempfile::TempDir::new().unwrap();
+    write(&dir.path().join("a.toml"), "command = \"a\"\n");
+    let dirs = [dir.path().to_path_buf(), dir.path().to_path_buf()];
+    let check = check_shadowed_filters(&scan_filter_files(&dirs));
+    assert_eq!(check.status, Status::Ok);
+}
+
+// --- database and cache ---
+
+#[test]
+fn tracking_db_in_a_writable_dir_passes() {
+    let dir = tempfile::TempDir::new().unwrap();
+    let rt = Runtime::builder()
+        .db_path(dir.path().join("tracking.db"))
+        .build();
+    assert_eq!(check_tracking_db(&rt).status, Status::Ok);
+}
+
+#[test]
+fn corrupt_cache_fails_with_a_fix() {
+    let rt = Runtime::isolated();
+    let search_dirs = config::default_search_dirs(&rt);
+    assert_eq!(check_cache(&rt, &search_dirs).status, Status::Ok);
+
+    let path = cache::cache_path(&rt, &search_dirs).unwrap();
+    write(&path, "not a manifest");
+    let check = check_cache(&rt, &search_dirs);
+    assert_eq!(check.status, Status::Fail);
+    assert_eq!(check.fix.as_deref(), Some("run `tokf cache clear`"));
+}
+
+// --- rendering ---
+
+#[test]
+fn render_lists_details_and_fixes() {
+    let checks = [
+        Check::new("registry", Status::Skipped, "skipped (--offline)"),
+        Check::new("login", Status::Fail, "token expired")
+            .with_details(vec!["octocat".to_string()])
+            .with_fix("run `tokf auth login`"),
+    ];
+    let out = render_checks(&checks, &Colors::disabled());
+    assert_eq!(
+        out,
+        "- registry  skipped (--offline)\n\
+         \u{2717} login     token expired\n    octocat\n    fix: run `tokf auth login`\n"
+    );
+    assert!(any_failed(&checks));
+}
@@ -4,7 +4,7 @@
 use std::io::IsTerminal as _;
 
 use tokf::doctor::render::{Colors, render_human, should_disable_color};
-use tokf::doctor::{DoctorOpts, SortBy, run};
+use tokf::doctor::{DoctorOpts, SortBy, run, setup};
 use tokf::tracking;
 
 use crate::resolve;
@@ -24,14 +24,20 @@ pub struct DoctorCliOpts<'a> {
     pub json: bool,
     pub no_color: bool,
     pub no_cache: bool,
+    pub setup: bool,
+    pub offline: bool,
 }
 
 /// Run the doctor command. Returns the process exit code.
 ///
 /// Exit codes:
 ///   - `0`: report rendered successfully (even if it surfaced problems)
-///   - `1`: failed to open the tracking DB or fetch events
+///   - `1`: failed to open the tracking DB or fetch events; with `--setup`,
+///     a check failed
 pub fn cmd_doctor(rt: &Runtime, opts: &DoctorCliOpts<'_>) -> i32 {
+    if opts.setup {
+        return cmd_setup_checks(rt, opts);
+    }
     let Some(path) = rt.tracking_db_path() else {
         eprintln!("[tokf] error: cannot determine tracking DB path");
         return 1;
@@ -97,11 +103,31 @@ pub fn cmd_doctor(rt: &Runtime, opts: &DoctorCliOpts<'_>) -> i32 {
         return 0;
     }
 
-    let colors = if should_disable_color(opts.no_color) || !std::io::stdout().is_terminal() {
+    print!("{}", render_human(&report, &colors(opts)));
+    0
+}
+
+fn cmd_setup_checks(rt: &Runtime, opts: &DoctorCliOpts<'_>) -> i32 {
+    let home = dirs::home_dir();
+    let checks = setup::run_checks(
+        rt,
+        &setup::SetupOpts {
+            home: home.as_deref(),
+            offline: opts.offline,
+        },
+    );
+    if opts.json {
+        crate::output::print_json(&checks);
+    } else {
+        print!("{}", setup::render_checks(&checks, &colors(opts)));
+    }
+    i32::from(setup::any_failed(&checks))
+}
+
+fn colors(opts: &DoctorCliOpts<'_>) -> Colors {
+    if should_disable_color(opts.no_color) || !std::io::stdout().is_terminal() {
         Colors::disabled()
     } else {
         Colors::enabled()
-    };
-    print!("{}", render_human(&report, &colors));
-    0
+    }
 }
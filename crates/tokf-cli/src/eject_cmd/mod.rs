@@ -1,15 +1,33 @@
+mod update;
+
 use anyhow::Context as _;
 
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 use tokf::config;
 
 use tokf::runtime::Runtime;
 
+/// What `tokf eject` does with the filter.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EjectMode {
+    /// Copy the filter, into the user config dir when `global`.
+    Copy { global: bool },
+    /// `--diff`: compare the ejected copy with today's built-in.
+    Diff { no_color: bool },
+    /// `--update`: merge the built-in's changes into the ejected copy.
+    Update,
+}
+
 /// Entry point for the `tokf eject` subcommand.
-pub fn cmd_eject(rt: &Runtime, filter: &str, global: bool, no_cache: bool) -> i32 {
-    match eject(rt, filter, global, no_cache) {
-        Ok(()) => 0,
+pub fn cmd_eject(rt: &Runtime, filter: &str, mode: EjectMode, no_cache: bool) -> i32 {
+    let result = match mode {
+        EjectMode::Copy { global } => eject(rt, filter, global, no_cache).map(|()| 0),
+        EjectMode::Diff { no_color } => update::diff(rt, filter, no_color, no_cache),
+        EjectMode::Update => update::update(rt, filter, no_cache),
+    };
+    match result {
+        Ok(code) => code,
         Err(e) => {
             eprintln!("[tokf] error: {e:#}");
             1
@@ -32,13 +50,7 @@ fn eject(rt: &Runtime, filter: &str, global: bool, no_cache: bool) -> anyhow::Re
 fn eject_to(rt: &Runtime, filter: &str, target_base: &Path, no_cache: bool) -> anyhow::Result<()> {
     let filter_name = filter.strip_suffix(".toml").unwrap_or(filter);
 
-    let search_dirs = config::default_search_dirs(rt);
-    let resolved = if no_cache {
-        config::discover_all_filters(&search_dirs)?
-    } else {
-        config::cache::discover_with_cache(rt, &search_dirs)?
-    };
-
+    let resolved = discover(rt, no_cache)?;
     let found = resolved.iter().find(|f| f.matches_name(filter_name));
 
     let resolved_filter =
@@ -67,6 +79,11 @@ fn eject_to(rt: &Runtime, filter: &str, target_base: &Path, no_cache: bool) -> a
     }
     std::fs::write(&target_toml, &toml_content)?;
     eprintln!("[tokf] wrote {}", target_toml.display());
+    if resolved_filter.priority == tokf::config::STDLIB_PRIORITY {
+        // The version ejected, so `tokf eject --update` can merge later
+        // built-in changes with the edits made to the copy.
+        write_base(target_base, &resolved_filter.relative_path, &toml_content)?;
+    }
 
     // Copy the _test/ directory if present
     copy_test_suite(resolved_filter, target_base)?;
@@ -75,6 +92,33 @@ fn eject_to(rt: &Runtime, filter: &str, target_base: &Path, no_cache: bool) -> a
     Ok(())
 }
 
+fn discover(rt: &Runtime, no_cache: bool) -> anyhow::Result<Vec<config::ResolvedFilter>> {
+    let search_dirs = config::default_search_dirs(rt);
+    if no_cache {
+        config::discover_all_filters(&search_dirs)
+    } else {
+        config::cache::discover_with_cache(rt, &search_dirs)
+    }
+}
+
+/// Where the built-in a filter was ejected from is kept: `ejected/` next to
+/// the `filters/` directory, under the filter's relative path.
+fn base_path(filters_dir: &Path, relative_path: &Path) -> PathBuf {
+    filters_dir
+        .parent()
+        .unwrap_or(filters_dir)
+        .join("ejected")
+        .join(relative_path)
+}
+
+fn write_base(filters_dir: &Path, relative_path: &Path, content: &str) -> anyhow::Result<()> {
+    let path = base_path(filters_dir, relative_path);
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(&path, content).with_context(|| format!("write {}", path.display()))
+}
+
 /// Copy the `_test/` directory for a filter (if it exists) to the target base.
 fn copy_test_suite(
     resolved_filter: &config::ResolvedFilter,
@@ -171,6 +215,19 @@ mod tests {
         );
     }
 
+    #[test]
+    fn eject_builtin_records_the_ejected_version() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let target = dir.path().join("filters");
+
+        let rt = Runtime::isolated();
+        eject_to(&rt, "cargo/build", &target, true).unwrap();
+
+        let base = std::fs::read_to_string(dir.path().join("ejected/cargo/build.toml")).unwrap();
+        let copy = std::fs::read_to_string(target.join("cargo/build.toml")).unwrap();
+        assert_eq!(base, copy);
+    }
+
     #[test]
     fn eject_builtin_copies_test_dir() {
         let dir = tempfile::TempDir::new().unwrap();
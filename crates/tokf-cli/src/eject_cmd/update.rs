@@ -0,0 +1,256 @@
+//! `tokf eject --diff` and `tokf eject --update`: keep an ejected built-in in
+//! step with the version this tokf ships.
+
+use std::io::IsTerminal as _;
+use std::path::{Path, PathBuf};
+
+use anyhow::Context as _;
+use tokf::config;
+use tokf::doctor::render::{Colors, should_disable_color};
+use tokf::runtime::Runtime;
+
+use super::{base_path, write_base};
+use crate::history_diff::render_unified_labeled;
+
+/// An ejected copy of a built-in filter.
+struct Ejected {
+    name: String,
+    relative_path: PathBuf,
+    /// The copy's file.
+    path: PathBuf,
+    /// The `filters/` directory the copy sits in.
+    filters_dir: PathBuf,
+    content: String,
+    /// The built-in as this tokf ships it.
+    upstream: &'static str,
+}
+
+impl Ejected {
+    /// The built-in as it was when ejected, if tokf recorded it.
+    fn base(&self) -> Option<String> {
+        std::fs::read_to_string(base_path(&self.filters_dir, &self.relative_path)).ok()
+    }
+}
+
+fn find_ejected(rt: &Runtime, filter: &str, no_cache: bool) -> anyhow::Result<Ejected> {
+    let name = filter.strip_suffix(".toml").unwrap_or(filter);
+    let filters = super::discover(rt, no_cache)?;
+    let found = filters
+        .iter()
+        .find(|f| f.matches_name(name))
+        .with_context(|| format!("filter not found: {name}"))?;
+    anyhow::ensure!(
+        found.priority != config::STDLIB_PRIORITY,
+        "{name} has not been ejected — `tokf eject {name}` copies it first"
+    );
+    let upstream = config::get_embedded_filter(&found.relative_path).with_context(|| {
+        format!("{name} is not a built-in filter, so there is no upstream version to compare with")
+    })?;
+    let content = std::fs::read_to_string(&found.source_path)
+        .with_context(|| format!("read {}", found.source_path.display()))?;
+    let depth = found.relative_path.components().count();
+    let filters_dir = found
+        .source_path
+        .ancestors()
+        .nth(depth)
+        .unwrap_or_else(|| Path::new("."))
+        .to_path_buf();
+    Ok(Ejected {
+        name: name.to_string(),
+        relative_path: found.relative_path.clone(),
+        path: found.source_path.clone(),
+        filters_dir,
+        content,
+        upstream,
+    })
+}
+
+/// `tokf eject --diff <filter>`: a unified diff from the ejected copy to the
+/// built-in this tokf ships.
+pub fn diff(rt: &Runtime, filter: &str, no_color: bool, no_cache: bool) -> anyhow::Result<i32> {
+    let ejected = find_ejected(rt, filter, no_cache)?;
+    let colors = if std::io::stdout().is_terminal() && !should_disable_color(no_color) {
+        Colors::enabled()
+    } else {
+        Colors::disabled()
+    };
+    let copy_label = ejected.path.display().to_string();
+    let builtin_label = format!("built-in {}", ejected.name);
+    let diff = render_unified_labeled(
+        &ejected.content,
+        ejected.upstream,
+        (&copy_label, &builtin_label),
+        &colors,
+    );
+    if diff.is_empty() {
+        eprintln!("[tokf] {} matches the built-in", ejected.path.display());
+        return Ok(0);
+    }
+    print!("{diff}");
+    match ejected.base() {
+        Some(base) if base == ejected.upstream => eprintln!(
+            "[tokf] the built-in has not changed since it was ejected: the diff is your edits"
+        ),
+        Some(_) => eprintln!(
+            "[tokf] the built-in changed since it was ejected — `tokf eject --update {}` merges it in",
+            ejected.name
+        ),
+        None => {}
+    }
+    Ok(0)
+}
+
+/// `tokf eject --update <filter>`: a 3-way merge of the built-in's changes
+/// since the eject into the copy. Conflicts are written into the copy with
+/// `<<<<<<<` markers, as git does, and make the command exit 1.
+pub fn update(rt: &Runtime, filter: &str, no_cache: bool) -> anyhow::Result<i32> {
+    let ejected = find_ejected(rt, filter, no_cache)?;
+    let record_upstream = || {
+        write_base(
+            &ejected.filters_dir,
+            &ejected.relative_path,
+            ejected.upstream,
+        )
+    };
+
+    let Some(base) = ejected.base() else {
+        anyhow::ensure!(
+            ejected.content == ejected.upstream,
+            "there is no record of the version {name} was ejected from, so there is nothing to \
+             merge against — compare with `tokf eject --diff {name}` and copy over what you want",
+            name = ejected.name
+        );
+        record_upstream()?;
+        eprintln!("[tokf] {} is up to date", ejected.name);
+        return Ok(0);
+    };
+    if base == ejected.upstream {
+        eprintln!(
+            "[tokf] {}: the built-in has not changed since it was ejected",
+            ejected.name
+        );
+        return Ok(0);
+    }
+
+    let merged = diffy::merge(&base, &ejected.content, ejected.upstream);
+    let (content, conflicted) = match merged {
+        Ok(content) => (content, false),
+        Err(content) => (content, true),
+    };
+    std::fs::write(&ejected.path, &content)
+        .with_context(|| format!("write {}", ejected.path.display()))?;
+    record_upstream()?;
+
+    if conflicted {
+        eprintln!(
+            "[tokf] conflicts in {}: resolve the <<<<<<< markers, then run `tokf check {}`",
+            ejected.path.display(),
+            ejected.path.display()
+        );
+        return Ok(1);
+    }
+    eprintln!(
+        "[tokf] merged the built-in's changes into {}",
+        ejected.path.display()
+    );
+    if let Err(e) = config::try_load_filter(&ejected.path) {
+        eprintln!("[tokf] warning: the merged filter does not load: {e:#}");
+    }
+    Ok(0)
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    /// Eject `cargo/build` into an isolated project and return its runtime,
+    /// the copy's path and the shipped built-in.
+    fn ejected_project() -> (Runtime, PathBuf, &'static str) {
+        let rt = Runtime::isolated();
+        let filters_dir = rt.temp_root().unwrap().join(".tokf/filters");
+        super::super::eject_to(&rt, "cargo/build", &filters_dir, true).unwrap();
+        let upstream = config::get_embedded_filter(Path::new("cargo/build.toml")).unwrap();
+        (rt, filters_dir.join("cargo/build.toml"), upstream)
+    }
+
+    /// Pretend the copy was ejected from an older built-in: `base` becomes
+    /// the built-in with its first `skip` pattern line changed.
+    fn age_base(rt: &Runtime, upstream: &str) -> (String, String) {
+        let line = upstream
+            .lines()
+            .find(|l| l.trim_start().starts_with('"'))
+            .unwrap()
+            .to_string();
+        let base = upstream.replacen(&line, "  \"^old pattern\",", 1);
+        let base_file = rt
+            .temp_root()
+            .unwrap()
+            .join(".tokf/ejected/cargo/build.toml");
+        std::fs::write(&base_file, &base).unwrap();
+        (base, line)
+    }
+
+    #[test]
+    fn update_merges_upstream_changes_with_local_edits() {
+        let (rt, copy, upstream) = ejected_project();
+        let (base, line) = age_base(&rt, upstream);
+        std::fs::write(&copy, format!("# my tweak\n{base}")).unwrap();
+
+        assert_eq!(update(&rt, "cargo/build", true).unwrap(), 0);
+
+        let merged = std::fs::read_to_string(&copy).unwrap();
+        assert!(merged.starts_with("# my tweak\n"), "{merged}");
+        assert!(merged.contains(&line), "upstream change missing: {merged}");
+        assert!(!merged.contains("^old pattern"), "{merged}");
+        let base_file = rt
+            .temp_root()
+            .unwrap()
+            .join(".tokf/ejected/cargo/build.toml");
+        assert_eq!(std::fs::read_to_string(base_file).unwrap(), upstream);
+    }
+
+    #[test]
+    fn update_writes_conflict_markers_and_exits_one() {
+        let (rt, copy, upstream) = ejected_project();
+        let (base, _) = age_base(&rt, upstream);
+        std::fs::write(&copy, base.replace("^old pattern", "^my pattern")).unwrap();
+
+        assert_eq!(update(&rt, "cargo/build", true).unwrap(), 1);
+
+        let merged = std::fs::read_to_string(&copy).unwrap();
+        assert!(merged.contains("<<<<<<<"), "{merged}");
+        assert!(merged.contains("^my pattern"), "{merged}");
+    }
+
+    #[test]
+    fn update_without_upstream_changes_keeps_the_copy() {
+        let (rt, copy, _) = ejected_project();
+        std::fs::write(&copy, "command = \"cargo build\"\n").unwrap();
+
+        assert_eq!(update(&rt, "cargo/build", true).unwrap(), 0);
+        assert_eq!(
+            std::fs::read_to_string(&copy).unwrap(),
+            "command = \"cargo build\"\n"
+        );
+    }
+
+    #[test]
+    fn update_without_a_recorded_base_refuses_to_guess() {
+        let (rt, copy, _) = ejected_project();
+        std::fs::remove_dir_all(rt.temp_root().unwrap().join(".tokf/ejected")).unwrap();
+        std::fs::write(&copy, "command = \"cargo build\"\n").unwrap();
+
+        let err = update(&rt, "cargo/build", true).unwrap_err().to_string();
+        assert!(err.contains("eject --diff cargo/build"), "{err}");
+    }
+
+    #[test]
+    fn diff_requires_an_ejected_copy() {
+        let rt = Runtime::isolated();
+        let err = diff(&rt, "cargo/build", true, true)
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("has not been ejected"), "{err}");
+    }
+}
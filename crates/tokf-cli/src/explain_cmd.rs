@@ -0,0 +1,107 @@
+use std::fmt::Write as _;
+
+use tokf::filter::trace::{self, FilterReport};
+use tokf::history;
+use tokf::runner;
+use tokf::runtime::Runtime;
+
+use crate::resolve;
+
+/// Dropped lines listed before the report summarizes the rest.
+const MAX_DROPPED_SHOWN: usize = 100;
+
+/// `tokf explain <target>`: re-run the filter of a history entry (`last` or a
+/// numeric ID) on its recorded raw output and print a per-stage report.
+///
+/// History stores the combined output only, so filters with a `source`
+/// channel are explained against combined output.
+///
+/// # Errors
+/// Returns an error if the history DB or filters cannot be loaded.
+pub fn cmd_explain(rt: &Runtime, target: &str) -> anyhow::Result<i32> {
+    let path = rt
+        .tracking_db_path()
+        .ok_or_else(|| anyhow::anyhow!("cannot determine history DB path"))?;
+    let conn = history::open_db(&path)?;
+    let entry = if target == "last" {
+        history::get_latest_entry(&conn, Some(&history::current_project(rt)))?
+    } else if let Ok(id) = target.parse::<i64>() {
+        history::get_history_entry(&conn, id)?
+    } else {
+        eprintln!("[tokf] expected `last` or a numeric ID, got: {target}");
+        return Ok(1);
+    };
+    let Some(entry) = entry else {
+        eprintln!("[tokf] history entry {target} not found");
+        return Ok(1);
+    };
+    let Some(filter_name) = entry.filter_name.as_deref() else {
+        eprintln!(
+            "[tokf] history entry {} ran without a filter: {}",
+            entry.id, entry.command
+        );
+        return Ok(1);
+    };
+
+    let filters = resolve::discover_filters(rt, false)?;
+    let Some(filter) = filters.iter().find(|f| f.matches_name(filter_name)) else {
+        eprintln!("[tokf] filter '{filter_name}' is no longer installed");
+        return Ok(1);
+    };
+
+    let cmd_result = runner::CommandResult {
+        stdout: String::new(),
+        stderr: String::new(),
+        exit_code: entry.exit_code,
+        combined: entry.raw_output,
+    };
+    let cwd = std::env::current_dir().ok();
+    let opts = resolve::filter_options(&filter.config, cwd.as_deref(), false);
+    let (_, report) = trace::apply_traced(&filter.config, &cmd_result, &[], &opts);
+
+    println!(
+        "[tokf] explain #{}: {} (filter: {filter_name}, exit {})",
+        entry.id, entry.command, entry.exit_code
+    );
+    print!("{}", render_report(&report));
+    Ok(0)
+}
+
+/// Render a [`FilterReport`] as plain text, one section per block.
+pub fn render_report(report: &FilterReport) -> String {
+    let mut out = String::new();
+    let total_ms = report.total.as_secs_f64() * 1000.0;
+    let _ = writeln!(out, "branch: {}", report.branch);
+    let _ = writeln!(out, "total: {total_ms:.2}ms");
+
+    if !report.stages.is_empty() {
+        let _ = writeln!(out, "stages:");
+        for stage in &report.stages {
+            let _ = writeln!(
+                out,
+                "  {:<22} {:>6} -> {:<6} {:.2}ms",
+                stage.name,
+                stage.lines_in,
+                stage.lines_out,
+                stage.elapsed.as_secs_f64() * 1000.0
+            );
+        }
+    }
+    if !report.sections.is_empty() {
+        let _ = writeln!(out, "sections:");
+        for (name, count) in &report.sections {
+            let _ = writeln!(out, "  {name}: {count} item(s)");
+        }
+    }
+    if !report.dropped.is_empty() {
+        let _ = writeln!(out, "dropped:");
+        for dropped in report.dropped.iter().take(MAX_DROPPED_SHOWN) {
+            let _ = writeln!(out, "  [{}] {}", dropped.stage, dropped.line);
+        }
+        let hidden = report.dropped.len().saturating_sub(MAX_DROPPED_SHOWN);
+        if hidden > 0 {
+            let _ = writeln!(out, "  … {hidden} more");
+        }
+    }
+    out
+}
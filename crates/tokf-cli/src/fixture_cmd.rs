@@ -0,0 +1,393 @@
+//! `tokf fixture record` — save a command's real output as a test fixture —
+//! and `tokf history export-fixture`, which does the same for a recorded run.
+//!
+//! The output is written byte for byte (ANSI codes included) into the
+//! filter's `<stem>_test/` suite, optionally with a case that pins the
+//! filter's current output for it.
+
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+
+use anyhow::Context as _;
+use clap::Subcommand;
+
+use tokf::config::{self, ResolvedFilter};
+use tokf::filter;
+use tokf::history;
+use tokf::rewrite;
+use tokf::runner::{self, CommandResult, ExecOptions};
+use tokf::runtime::Runtime;
+
+use crate::resolve;
+
+#[derive(Subcommand)]
+pub enum FixtureAction {
+    /// Run a command and save its output in a filter's test suite
+    Record(RecordArgs),
+}
+
+/// Args for `tokf fixture record`.
+#[derive(clap::Args, Debug, Clone)]
+pub struct RecordArgs {
+    /// Filter whose suite gets the fixture (default: the filter matching the command)
+    #[arg(long)]
+    pub filter: Option<String>,
+    /// Case name, saved as <case>.txt (default: "success" or "failure" by exit code)
+    #[arg(long)]
+    pub case: Option<String>,
+    /// Also write <case>.toml, a test case pinning the filter's current output
+    #[arg(long)]
+    pub scaffold: bool,
+    /// Run under a pseudo-terminal, for tools that only colour their output on a terminal
+    #[arg(long)]
+    pub pty: bool,
+    /// Overwrite an existing fixture and case
+    #[arg(long)]
+    pub force: bool,
+    /// The command to record
+    #[arg(trailing_var_arg = true, required = true)]
+    pub command_args: Vec<String>,
+}
+
+/// Args for `tokf history export-fixture`.
+#[derive(clap::Args, Debug, Clone)]
+pub struct ExportArgs {
+    /// History entry ID
+    pub id: i64,
+    /// Filter whose suite gets the fixture (default: the filter that ran)
+    #[arg(long)]
+    pub filter: Option<String>,
+    /// Case name, saved as <case>.txt (default: "success" or "failure" by exit code)
+    #[arg(long)]
+    pub case: Option<String>,
+    /// Overwrite an existing fixture and case
+    #[arg(long)]
+    pub force: bool,
+}
+
+/// One run of a command, saved as a case of a filter's test suite.
+pub struct Recording<'a> {
+    pub case: &'a str,
+    /// The command line the output came from.
+    pub source: &'a str,
+    /// Output with trailing whitespace trimmed, as `tokf verify` reads it.
+    pub output: &'a str,
+    pub exit_code: i32,
+    /// Arguments the filter sees (`args` in the case).
+    pub args: &'a [String],
+}
+
+pub fn run_fixture_action(rt: &Runtime, action: &FixtureAction) -> i32 {
+    let result = match action {
+        FixtureAction::Record(args) => record(rt, args),
+    };
+    match result {
+        Ok(()) => 0,
+        Err(e) => {
+            eprintln!("[tokf] error: {e:#}");
+            1
+        }
+    }
+}
+
+fn record(rt: &Runtime, args: &RecordArgs) -> anyhow::Result<()> {
+    let filters = resolve::discover_installed_filters(rt, false)?;
+    let (filter, filter_args) =
+        find_filter(rt, &filters, &args.command_args, args.filter.as_deref())?;
+    let suite_dir = target_suite(filter)?;
+
+    eprintln!("[tokf] running: {}", args.command_args.join(" "));
+    let opts = ExecOptions {
+        pty: args.pty,
+        ..ExecOptions::default()
+    };
+    let result =
+        runner::execute_with_env(&args.command_args[0], &args.command_args[1..], &[], opts)?.result;
+    let case = args
+        .case
+        .clone()
+        .unwrap_or_else(|| default_case(result.exit_code).to_string());
+    validate_case(&case)?;
+    let output = result.combined.trim_end();
+    anyhow::ensure!(
+        !output.is_empty() || args.scaffold,
+        "the command printed nothing — pass --scaffold for a case with `inline = \"\"`"
+    );
+    check_free(&suite_dir, &case, args.force)?;
+
+    let recording = Recording {
+        case: &case,
+        source: &args.command_args.join(" "),
+        output,
+        exit_code: result.exit_code,
+        args: &filter_args,
+    };
+    let expected = args
+        .scaffold
+        .then(|| pinned_output(&filter.config, &recording));
+    write_case(&suite_dir, &recording, expected.as_deref())?;
+    eprintln!("[tokf] next: run `tokf verify {}`", filter_name(filter));
+    Ok(())
+}
+
+/// `tokf history export-fixture`: save a recorded run's raw output, with a
+/// case pinning what the filter makes of it today.
+pub fn export_history_entry(rt: &Runtime, args: &ExportArgs) -> anyhow::Result<()> {
+    let path = rt
+        .tracking_db_path()
+        .context("cannot determine history DB path")?;
+    let conn = history::open_db(&path)?;
+    let entry = history::get_history_entry(&conn, args.id)?
+        .with_context(|| format!("history entry {} not found", args.id))?;
+
+    let words: Vec<String> = entry
+        .command
+        .split_whitespace()
+        .map(str::to_string)
+        .collect();
+    // Default to the filter that produced the entry, not whatever matches now.
+    let name = args.filter.as_deref().or(entry.filter_name.as_deref());
+    let filters = resolve::discover_installed_filters(rt, false)?;
+    let (filter, filter_args) = find_filter(rt, &filters, &words, name)?;
+    let suite_dir = target_suite(filter)?;
+
+    let case = args
+        .case
+        .clone()
+        .unwrap_or_else(|| default_case(entry.exit_code).to_string());
+    validate_case(&case)?;
+    check_free(&suite_dir, &case, args.force)?;
+
+    let recording = Recording {
+        case: &case,
+        source: &entry.command,
+        output: entry.raw_output.trim_end(),
+        exit_code: entry.exit_code,
+        args: &filter_args,
+    };
+    let expected = pinned_output(&filter.config, &recording);
+    write_case(&suite_dir, &recording, Some(&expected))?;
+    eprintln!("[tokf] next: run `tokf verify {}`", filter_name(filter));
+    Ok(())
+}
+
+/// The filter's name as `tokf verify` takes it, e.g. `cargo/test`.
+fn filter_name(filter: &ResolvedFilter) -> String {
+    filter
+        .relative_path
+        .with_extension("")
+        .display()
+        .to_string()
+}
+
+/// The suite a fixture for `filter` goes into. Built-in filters have no
+/// suite on disk to write to.
+fn target_suite(filter: &ResolvedFilter) -> anyhow::Result<PathBuf> {
+    let name = filter_name(filter);
+    anyhow::ensure!(
+        filter.priority != config::STDLIB_PRIORITY,
+        "{name} is a built-in filter — run `tokf eject {name}` first, then record into the copy"
+    );
+    suite_dir(&filter.source_path)
+}
+
+/// The filter named by `--filter`, or the one the command resolves to, with
+/// the arguments it would see for the command.
+fn find_filter<'a>(
+    rt: &Runtime,
+    filters: &'a [ResolvedFilter],
+    command_args: &[String],
+    filter: Option<&str>,
+) -> anyhow::Result<(&'a ResolvedFilter, Vec<String>)> {
+    let words: Vec<&str> = command_args.iter().map(String::as_str).collect();
+    let wrapper_cfg = rewrite::load_local_wrapper_config(rt);
+    let matched = config::local_wrapper::match_filters_with_wrapper(filters, &words, &wrapper_cfg);
+    if let Some(name) = filter {
+        let name = name.strip_suffix(".toml").unwrap_or(name);
+        let filter = filters
+            .iter()
+            .find(|f| f.matches_name(name))
+            .with_context(|| format!("filter not found: {name}"))?;
+        let consumed = matched
+            .filter(|(f, _, _)| std::ptr::eq(*f, filter))
+            .map_or(0, |(_, _, consumed)| consumed);
+        return Ok((filter, command_args[consumed..].to_vec()));
+    }
+    let (filter, _, consumed) = matched.with_context(|| {
+        format!(
+            "no filter matches \"{}\" — pass --filter, or draft one with `tokf new`",
+            command_args.join(" ")
+        )
+    })?;
+    Ok((filter, command_args[consumed..].to_vec()))
+}
+
+/// `<stem>_test/` next to the filter file.
+fn suite_dir(filter_path: &Path) -> anyhow::Result<PathBuf> {
+    let stem = filter_path
+        .file_stem()
+        .context("filter path has no file name")?
+        .to_string_lossy();
+    Ok(filter_path.with_file_name(format!("{stem}_test")))
+}
+
+const fn default_case(exit_code: i32) -> &'static str {
+    if exit_code == 0 { "success" } else { "failure" }
+}
+
+fn validate_case(case: &str) -> anyhow::Result<()> {
+    let valid = !case.is_empty()
+        && !case.starts_with('.')
+        && case
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'));
+    anyhow::ensure!(
+        valid,
+        "invalid case name {case:?}: use letters, digits, '-', '_' and '.'"
+    );
+    Ok(())
+}
+
+fn check_free(suite_dir: &Path, case: &str, force: bool) -> anyhow::Result<()> {
+    if force {
+        return Ok(());
+    }
+    for ext in ["txt", "toml"] {
+        let path = suite_dir.join(format!("{case}.{ext}"));
+        if path.exists() {
+            anyhow::bail!(
+                "{} already exists — pass --force to replace it, or --case to pick another name",
+                path.display()
+            );
+        }
+    }
+    Ok(())
+}
+
+/// What the filter makes of the recording today.
+fn pinned_output(cfg: &tokf::config::types::FilterConfig, recording: &Recording<'_>) -> String {
+    let result = CommandResult {
+        stdout: String::new(),
+        stderr: String::new(),
+        exit_code: recording.exit_code,
+        combined: recording.output.to_string(),
+    };
+    filter::apply(
+        cfg,
+        &result,
+        recording.args,
+        &filter::FilterOptions::default(),
+    )
+    .output
+}
+
+/// Write `<case>.txt` into `suite_dir` and, when `expected` is given, a
+/// `<case>.toml` asserting the filter output `equals` it. Empty output has no
+/// fixture file (`tokf verify` rejects those); its case uses `inline = ""`.
+pub fn write_case(
+    suite_dir: &Path,
+    recording: &Recording<'_>,
+    expected: Option<&str>,
+) -> anyhow::Result<()> {
+    std::fs::create_dir_all(suite_dir)?;
+    let case = recording.case;
+    let input_line = if recording.output.is_empty() {
+        "inline = \"\"".to_string()
+    } else {
+        let fixture_path = suite_dir.join(format!("{case}.txt"));
+        std::fs::write(&fixture_path, format!("{}\n", recording.output))?;
+        eprintln!("[tokf] wrote {}", fixture_path.display());
+        format!("fixture = {}", toml_string(&format!("{case}.txt")))
+    };
+    let Some(expected) = expected else {
+        return Ok(());
+    };
+
+    let mut case_toml = format!(
+        "name = {}\n{input_line}\nexit_code = {}\n",
+        toml_string(&format!("{case} output of `{}`", recording.source)),
+        recording.exit_code,
+    );
+    if !recording.args.is_empty() {
+        let args = toml::Value::Array(
+            recording
+                .args
+                .iter()
+                .map(|a| toml::Value::String(a.clone()))
+                .collect(),
+        );
+        let _ = writeln!(case_toml, "args = {args}");
+    }
+    let _ = write!(
+        case_toml,
+        "\n[[expect]]\nequals = {}\n",
+        toml_string(expected)
+    );
+    let case_path = suite_dir.join(format!("{case}.toml"));
+    std::fs::write(&case_path, case_toml)?;
+    eprintln!("[tokf] wrote {}", case_path.display());
+    Ok(())
+}
+
+fn toml_string(s: &str) -> String {
+    toml::Value::String(s.to_string()).to_string()
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    fn recording<'a>(output: &'a str, args: &'a [String]) -> Recording<'a> {
+        Recording {
+            case: "success",
+            source: "my-tool build --release",
+            output,
+            exit_code: 0,
+            args,
+        }
+    }
+
+    #[test]
+    fn write_case_keeps_ansi_codes_in_the_fixture() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let output = "\x1b[32mok\x1b[0m 3 targets";
+        write_case(dir.path(), &recording(output, &[]), None).unwrap();
+
+        let fixture = std::fs::read_to_string(dir.path().join("success.txt")).unwrap();
+        assert_eq!(fixture, "\x1b[32mok\x1b[0m 3 targets\n");
+        assert!(!dir.path().join("success.toml").exists());
+    }
+
+    #[test]
+    fn write_case_scaffolds_a_pinned_case_with_args() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let args = vec!["--release".to_string()];
+        write_case(dir.path(), &recording("built", &args), Some("ok")).unwrap();
+
+        let case: tokf_common::test_case::TestCase =
+            toml::from_str(&std::fs::read_to_string(dir.path().join("success.toml")).unwrap())
+                .unwrap();
+        assert_eq!(case.fixture.as_deref(), Some("success.txt"));
+        assert_eq!(case.args, args);
+        assert_eq!(case.expects[0].equals.as_deref(), Some("ok"));
+    }
+
+    #[test]
+    fn write_case_uses_inline_for_empty_output() {
+        let dir = tempfile::TempDir::new().unwrap();
+        write_case(dir.path(), &recording("", &[]), Some("")).unwrap();
+
+        assert!(!dir.path().join("success.txt").exists());
+        let case = std::fs::read_to_string(dir.path().join("success.toml")).unwrap();
+        assert!(case.contains("inline = \"\""), "{case}");
+    }
+
+    #[test]
+    fn case_names_stay_inside_the_suite() {
+        assert!(validate_case("colour-on").is_ok());
+        assert!(validate_case("../escape").is_err());
+        assert!(validate_case(".hidden").is_err());
+        assert!(validate_case("").is_err());
+    }
+}
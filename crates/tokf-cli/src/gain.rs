@@ -10,23 +10,61 @@ use crate::gain_render;
 use tokf::runtime::Runtime;
 
 /// Presentation options shared by the local and remote gain commands.
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone)]
 #[allow(clippy::struct_excessive_bools)] // CLI flags are naturally booleans
 pub struct GainOpts {
     pub daily: bool,
     pub by_filter: bool,
+    pub by_project: bool,
+    pub by_command: bool,
+    /// `--confusion`: agent feedback signals per filter.
+    pub confusion: bool,
+    /// `--cost`, priced for `model` or every model in `[pricing]`.
+    pub cost: bool,
+    pub model: Option<String>,
     pub json: bool,
     pub top: usize,
     pub no_color: bool,
+    pub range: RangeOpts,
+}
+
+/// `--since` / `--until` / `--period`; local stats only.
+#[derive(Debug, Clone, Default)]
+pub struct RangeOpts {
+    pub since: Option<String>,
+    pub until: Option<String>,
+    pub period: Option<tracking::Period>,
+}
+
+impl RangeOpts {
+    const fn is_set(&self) -> bool {
+        self.since.is_some() || self.until.is_some() || self.period.is_some()
+    }
+
+    pub fn resolve(self, conn: &rusqlite::Connection) -> anyhow::Result<tracking::TimeRange> {
+        match self.period {
+            Some(period) => tracking::TimeRange::for_period(conn, period),
+            None => Ok(tracking::TimeRange {
+                since: self.since,
+                until: self.until,
+            }),
+        }
+    }
 }
 
 pub fn cmd_gain(rt: &Runtime, opts: GainOpts) -> i32 {
     let GainOpts {
         daily,
         by_filter,
+        by_project,
+        by_command,
+        confusion,
+        cost,
+        model,
         json,
         top,
         no_color,
+        range,
     } = opts;
     prompt_upload_stats_if_needed(rt);
     tokf::setup::hint_setup_if_needed(rt);
@@ -43,17 +81,39 @@ pub fn cmd_gain(rt: &Runtime, opts: GainOpts) -> i32 {
         }
     };
 
+    let range = match range.resolve(&conn) {
+        Ok(r) => r,
+        Err(e) => {
+            eprintln!("[tokf] error: {e:#}");
+            return 1;
+        }
+    };
+
     if daily {
-        cmd_gain_daily(&conn, json)
+        cmd_gain_daily(&conn, &range, json)
     } else if by_filter {
-        cmd_gain_by_filter(&conn, json)
+        cmd_gain_by_filter(&conn, &range, json)
+    } else if by_project {
+        cmd_gain_by_project(&conn, &range, json)
+    } else if by_command {
+        cmd_gain_by_command(&conn, &range, json)
+    } else if confusion {
+        cmd_gain_confusion(&conn, &range, json)
+    } else if cost {
+        crate::gain_cost::cmd_gain_cost(rt, &conn, &range, json, model.as_deref())
     } else {
-        cmd_gain_summary(&conn, json, top, no_color)
+        cmd_gain_summary(&conn, &range, json, top, no_color)
     }
 }
 
-fn cmd_gain_summary(conn: &rusqlite::Connection, json: bool, top: usize, no_color: bool) -> i32 {
-    let summary = match tracking::query_summary(conn) {
+fn cmd_gain_summary(
+    conn: &rusqlite::Connection,
+    range: &tracking::TimeRange,
+    json: bool,
+    top: usize,
+    no_color: bool,
+) -> i32 {
+    let summary = match tracking::query_summary(conn, range) {
         Ok(s) => s,
         Err(e) => {
             eprintln!("[tokf] error: {e:#}");
@@ -66,7 +126,7 @@ fn cmd_gain_summary(conn: &rusqlite::Connection, json: bool, top: usize, no_colo
         return 0;
     }
 
-    let filters = match tracking::query_by_filter(conn) {
+    let filters = match tracking::query_by_filter(conn, range) {
         Ok(f) => f,
         Err(e) => {
             eprintln!("[tokf] error: {e:#}");
@@ -74,6 +134,10 @@ fn cmd_gain_summary(conn: &rusqlite::Connection, json: bool, top: usize, no_colo
         }
     };
 
+    if !range.is_all() {
+        println!("range: {}", range.describe());
+    }
+
     if std::io::stdout().is_terminal() {
         let colors = if gain_render::should_disable_color(no_color) {
             gain_render::ColorMode::new(false)
@@ -124,6 +188,15 @@ where
     }
 }
 
+/// `title`, followed by the range when one is set.
+pub fn header(title: &str, range: &tracking::TimeRange) -> String {
+    if range.is_all() {
+        title.to_string()
+    } else {
+        format!("{title} ({})", range.describe())
+    }
+}
+
 fn fmt_gain_row(
     label: &str,
     commands: i64,
@@ -142,12 +215,12 @@ fn fmt_gain_row(
     )
 }
 
-fn cmd_gain_by_filter(conn: &rusqlite::Connection, json: bool) -> i32 {
+fn cmd_gain_by_filter(conn: &rusqlite::Connection, range: &tracking::TimeRange, json: bool) -> i32 {
     query_and_print(
         conn,
         json,
-        "tokf gain by filter",
-        tracking::query_by_filter,
+        &header("tokf gain by filter", range),
+        |c| tracking::query_by_filter(c, range),
         |r| {
             fmt_gain_row(
                 &format!("{:30}", r.filter_name),
@@ -160,16 +233,89 @@ fn cmd_gain_by_filter(conn: &rusqlite::Connection, json: bool) -> i32 {
     )
 }
 
-fn cmd_gain_daily(conn: &rusqlite::Connection, json: bool) -> i32 {
-    query_and_print(conn, json, "tokf gain daily", tracking::query_daily, |r| {
-        fmt_gain_row(
-            &r.date,
-            r.commands,
-            r.tokens_saved,
-            r.savings_pct,
-            r.pipe_override_count,
-        )
-    })
+fn cmd_gain_by_project(
+    conn: &rusqlite::Connection,
+    range: &tracking::TimeRange,
+    json: bool,
+) -> i32 {
+    query_and_print(
+        conn,
+        json,
+        &header("tokf gain by project", range),
+        |c| tracking::query_by_project(c, range),
+        |r| {
+            fmt_gain_row(
+                &r.project,
+                r.commands,
+                r.tokens_saved,
+                r.savings_pct,
+                r.pipe_override_count,
+            )
+        },
+    )
+}
+
+fn cmd_gain_by_command(
+    conn: &rusqlite::Connection,
+    range: &tracking::TimeRange,
+    json: bool,
+) -> i32 {
+    query_and_print(
+        conn,
+        json,
+        &header("tokf gain by command", range),
+        |c| tracking::query_by_command(c, range),
+        |r| {
+            let row = fmt_gain_row(
+                &format!("{:30}", r.command),
+                r.commands,
+                r.tokens_saved,
+                r.savings_pct,
+                r.pipe_override_count,
+            );
+            if r.passthrough_runs > 0 {
+                format!("{row}  unfiltered: {}", r.passthrough_runs)
+            } else {
+                row
+            }
+        },
+    )
+}
+
+fn cmd_gain_confusion(conn: &rusqlite::Connection, range: &tracking::TimeRange, json: bool) -> i32 {
+    query_and_print(
+        conn,
+        json,
+        &header(
+            "tokf gain confusion (reruns and raw-output requests per filtered run)",
+            range,
+        ),
+        |c| tracking::query_confusion(c, range),
+        |r| {
+            format!(
+                "  {:30}  runs: {:4}  reruns: {:3}  raw: {:3}  confusion: {:.1}%",
+                r.filter_name, r.runs, r.reruns, r.raw_requests, r.confusion_pct
+            )
+        },
+    )
+}
+
+fn cmd_gain_daily(conn: &rusqlite::Connection, range: &tracking::TimeRange, json: bool) -> i32 {
+    query_and_print(
+        conn,
+        json,
+        &header("tokf gain daily", range),
+        |c| tracking::query_daily(c, range),
+        |r| {
+            fmt_gain_row(
+                &r.date,
+                r.commands,
+                r.tokens_saved,
+                r.savings_pct,
+                r.pipe_override_count,
+            )
+        },
+    )
 }
 
 // Remote gain fetches, converts and renders three report shapes inline.
@@ -179,14 +325,31 @@ pub fn cmd_gain_remote(rt: &Runtime, opts: GainOpts) -> i32 {
     let GainOpts {
         daily,
         by_filter,
+        by_project,
+        by_command,
+        confusion,
+        cost,
+        model: _,
         json,
         top,
         no_color,
+        range,
     } = opts;
     if daily {
         eprintln!("[tokf] --daily is not available for remote stats");
         return 1;
     }
+    if by_project || by_command || confusion || cost {
+        eprintln!(
+            "[tokf] --by-project, --by-command, --confusion and --cost are not available \
+             for remote stats"
+        );
+        return 1;
+    }
+    if range.is_set() {
+        eprintln!("[tokf] --since, --until and --period are not available for remote stats");
+        return 1;
+    }
 
     let client = match Client::authed(rt) {
         Ok(c) => c,
@@ -314,9 +477,40 @@ mod tests {
             GainOpts {
                 daily: true,
                 by_filter: false,
+                by_project: false,
+                by_command: false,
+                confusion: false,
+                cost: false,
+                model: None,
+                json: false,
+                top: 10,
+                no_color: false,
+                range: RangeOpts::default(),
+            },
+        );
+        assert_eq!(code, 1);
+    }
+
+    #[test]
+    fn cmd_gain_remote_range_returns_error() {
+        let rt = Runtime::isolated();
+        let code = cmd_gain_remote(
+            &rt,
+            GainOpts {
+                daily: false,
+                by_filter: false,
+                by_project: false,
+                by_command: false,
+                confusion: false,
+                cost: false,
+                model: None,
                 json: false,
                 top: 10,
                 no_color: false,
+                range: RangeOpts {
+                    period: Some(tracking::Period::Week),
+                    ..RangeOpts::default()
+                },
             },
         );
         assert_eq!(code, 1);
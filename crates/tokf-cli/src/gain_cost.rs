@@ -0,0 +1,152 @@
+//! `tokf gain --cost` — tokens saved, converted to dollars with the prices
+//! in `[pricing]`.
+
+use serde::Serialize;
+
+use tokf::history::{self, PricingConfig};
+use tokf::runtime::Runtime;
+use tokf::tracking;
+
+use crate::gain_render::format_num;
+
+/// Estimated savings at one model's input price.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CostRow {
+    pub model: String,
+    pub usd_per_1k_input_tokens: f64,
+    pub tokens_saved: i64,
+    pub usd_saved: f64,
+}
+
+/// One row for `model`, or one per configured model when it is `None`.
+///
+/// # Errors
+/// Returns an error naming the fix when nothing is priced or `model` is
+/// not in `[pricing]`.
+pub fn cost_rows(
+    pricing: &PricingConfig,
+    model: Option<&str>,
+    tokens_saved: i64,
+) -> anyhow::Result<Vec<CostRow>> {
+    if pricing.models.is_empty() {
+        anyhow::bail!(
+            "no model prices configured; add a [pricing] section to config.toml, e.g.\n\n  \
+             [pricing]\n  claude-sonnet = 0.003  # dollars per 1K input tokens"
+        );
+    }
+    let row = |model: &str, per_1k: f64| CostRow {
+        model: model.to_string(),
+        usd_per_1k_input_tokens: per_1k,
+        tokens_saved,
+        usd_saved: history::tokens_to_usd(tokens_saved, per_1k),
+    };
+    match model {
+        Some(model) => {
+            let Some(per_1k) = pricing.price(model) else {
+                let known: Vec<&str> = pricing.models.keys().map(String::as_str).collect();
+                anyhow::bail!(
+                    "no price for model \"{model}\" in [pricing] (known: {})",
+                    known.join(", ")
+                );
+            };
+            Ok(vec![row(model, per_1k)])
+        }
+        None => Ok(pricing
+            .models
+            .iter()
+            .map(|(model, per_1k)| row(model, *per_1k))
+            .collect()),
+    }
+}
+
+/// `$1,234.56`; negative amounts as `-$0.12`.
+#[allow(clippy::cast_possible_truncation)]
+pub fn format_usd(usd: f64) -> String {
+    let cents = (usd * 100.0).round() as i64;
+    let sign = if cents < 0 { "-" } else { "" };
+    let cents = cents.abs();
+    format!("{sign}${}.{:02}", format_num(cents / 100), cents % 100)
+}
+
+pub fn cmd_gain_cost(
+    rt: &Runtime,
+    conn: &rusqlite::Connection,
+    range: &tracking::TimeRange,
+    json: bool,
+    model: Option<&str>,
+) -> i32 {
+    let project_root = history::project_root_for(rt.cwd_or_empty());
+    let pricing = PricingConfig::load(rt, Some(&project_root));
+    let rows = tracking::query_summary(conn, range)
+        .and_then(|summary| cost_rows(&pricing, model, summary.tokens_saved));
+    let rows = match rows {
+        Ok(rows) => rows,
+        Err(e) => {
+            eprintln!("[tokf] error: {e:#}");
+            return 1;
+        }
+    };
+
+    if json {
+        crate::output::print_json(&rows);
+        return 0;
+    }
+    println!("{}", crate::gain::header("tokf gain cost", range));
+    for r in &rows {
+        println!(
+            "  {:30}  saved: {} est. ({} tokens at ${}/1K)",
+            r.model,
+            format_usd(r.usd_saved),
+            format_num(r.tokens_saved),
+            r.usd_per_1k_input_tokens,
+        );
+    }
+    0
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    fn pricing(models: &[(&str, f64)]) -> PricingConfig {
+        PricingConfig {
+            models: models.iter().map(|(m, p)| ((*m).to_string(), *p)).collect(),
+        }
+    }
+
+    #[test]
+    fn format_usd_rounds_to_cents() {
+        assert_eq!(format_usd(0.0), "$0.00");
+        assert_eq!(format_usd(0.252), "$0.25");
+        assert_eq!(format_usd(1234.567), "$1,234.57");
+        assert_eq!(format_usd(-0.125), "-$0.13");
+    }
+
+    #[test]
+    fn cost_rows_cover_every_model_unless_one_is_named() {
+        let pricing = pricing(&[("claude-sonnet", 0.003), ("claude-haiku", 0.0008)]);
+        let all = cost_rows(&pricing, None, 100_000).unwrap();
+        let models: Vec<&str> = all.iter().map(|r| r.model.as_str()).collect();
+        assert_eq!(models, ["claude-haiku", "claude-sonnet"]);
+
+        let one = cost_rows(&pricing, Some("claude-sonnet"), 100_000).unwrap();
+        assert_eq!(one.len(), 1);
+        assert!((one[0].usd_saved - 0.3).abs() < 1e-9);
+    }
+
+    #[test]
+    fn cost_rows_explain_missing_prices() {
+        let err = cost_rows(&pricing(&[]), None, 1).unwrap_err().to_string();
+        assert!(err.contains("[pricing]"), "{err}");
+
+        let pricing = pricing(&[("claude-sonnet", 0.003)]);
+        let err = cost_rows(&pricing, Some("gpt-4o"), 1)
+            .unwrap_err()
+            .to_string();
+        assert!(
+            err.contains("gpt-4o") && err.contains("claude-sonnet"),
+            "{err}"
+        );
+    }
+}
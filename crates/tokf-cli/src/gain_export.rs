@@ -0,0 +1,293 @@
+//! `tokf gain export` — the gain tables as CSV or Markdown, for pasting into
+//! spreadsheets and team docs.
+
+use std::fmt::Write as _;
+use std::path::PathBuf;
+
+use tokf::runtime::Runtime;
+use tokf::tracking::{self, DailyGain, FilterGain, GainSummary, ProjectGain};
+
+use crate::gain::RangeOpts;
+use crate::gain_render::format_num;
+
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ExportFormat {
+    Csv,
+    #[value(name = "md")]
+    Markdown,
+}
+
+#[derive(clap::Args, Debug)]
+pub struct ExportArgs {
+    /// Output format
+    #[arg(long, value_enum)]
+    pub format: ExportFormat,
+    /// One row per day
+    #[arg(long, conflicts_with_all = ["by_filter", "by_project"])]
+    pub daily: bool,
+    /// One row per filter
+    #[arg(long, conflicts_with = "by_project")]
+    pub by_filter: bool,
+    /// One row per project root
+    #[arg(long)]
+    pub by_project: bool,
+    /// Write to this file instead of stdout
+    #[arg(long, value_name = "FILE")]
+    pub out: Option<PathBuf>,
+}
+
+/// One table cell; numbers are formatted per output format.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Cell {
+    Text(String),
+    Int(i64),
+    Pct(f64),
+}
+
+impl Cell {
+    fn csv(&self) -> String {
+        match self {
+            Self::Text(s) => csv_escape(s),
+            Self::Int(n) => n.to_string(),
+            Self::Pct(p) => format!("{p:.1}"),
+        }
+    }
+
+    fn markdown(&self) -> String {
+        match self {
+            Self::Text(s) => s.replace('|', "\\|"),
+            Self::Int(n) => format_num(*n),
+            Self::Pct(p) => format!("{p:.1}%"),
+        }
+    }
+
+    const fn is_numeric(&self) -> bool {
+        !matches!(self, Self::Text(_))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Table {
+    pub headers: Vec<&'static str>,
+    pub rows: Vec<Vec<Cell>>,
+}
+
+const TOTALS: [&str; 6] = [
+    "runs",
+    "input_tokens",
+    "output_tokens",
+    "tokens_saved",
+    "savings_pct",
+    "pipe_overrides",
+];
+
+/// `[runs, input, output, saved]`, then the percentage and pipe overrides.
+fn totals(counts: [i64; 4], pct: f64, pipe_overrides: i64) -> Vec<Cell> {
+    let mut cells: Vec<Cell> = counts.into_iter().map(Cell::Int).collect();
+    cells.push(Cell::Pct(pct));
+    cells.push(Cell::Int(pipe_overrides));
+    cells
+}
+
+fn headers(first: Option<&'static str>) -> Vec<&'static str> {
+    first.into_iter().chain(TOTALS).collect()
+}
+
+pub fn summary_table(s: &GainSummary) -> Table {
+    Table {
+        headers: headers(None),
+        rows: vec![totals(
+            [
+                s.total_commands,
+                s.total_input_tokens,
+                s.total_output_tokens,
+                s.tokens_saved,
+            ],
+            s.savings_pct,
+            s.pipe_override_count,
+        )],
+    }
+}
+
+pub fn daily_table(days: &[DailyGain]) -> Table {
+    let rows = days
+        .iter()
+        .map(|d| {
+            let mut row = vec![Cell::Text(d.date.clone())];
+            row.extend(totals(
+                [d.commands, d.input_tokens, d.output_tokens, d.tokens_saved],
+                d.savings_pct,
+                d.pipe_override_count,
+            ));
+            row
+        })
+        .collect();
+    Table {
+        headers: headers(Some("date")),
+        rows,
+    }
+}
+
+pub fn filter_table(filters: &[FilterGain]) -> Table {
+    let rows = filters
+        .iter()
+        .map(|f| {
+            let mut row = vec![Cell::Text(f.filter_name.clone())];
+            row.extend(totals(
+                [f.commands, f.input_tokens, f.output_tokens, f.tokens_saved],
+                f.savings_pct,
+                f.pipe_override_count,
+            ));
+            row
+        })
+        .collect();
+    Table {
+        headers: headers(Some("filter")),
+        rows,
+    }
+}
+
+pub fn project_table(projects: &[ProjectGain]) -> Table {
+    let rows = projects
+        .iter()
+        .map(|p| {
+            let mut row = vec![Cell::Text(p.project.clone())];
+            row.extend(totals(
+                [p.commands, p.input_tokens, p.output_tokens, p.tokens_saved],
+                p.savings_pct,
+                p.pipe_override_count,
+            ));
+            row
+        })
+        .collect();
+    Table {
+        headers: headers(Some("project")),
+        rows,
+    }
+}
+
+pub fn render(table: &Table, format: ExportFormat) -> String {
+    let mut out = String::new();
+    match format {
+        ExportFormat::Csv => {
+            let _ = writeln!(out, "{}", table.headers.join(","));
+            for row in &table.rows {
+                let cells: Vec<String> = row.iter().map(Cell::csv).collect();
+                let _ = writeln!(out, "{}", cells.join(","));
+            }
+        }
+        ExportFormat::Markdown => {
+            let _ = writeln!(out, "| {} |", table.headers.join(" | "));
+            let align: Vec<&str> = table
+                .headers
+                .iter()
+                .enumerate()
+                .map(|(i, _)| {
+                    let numeric = table.rows.first().is_some_and(|r| r[i].is_numeric());
+                    if numeric { "---:" } else { "---" }
+                })
+                .collect();
+            let _ = writeln!(out, "| {} |", align.join(" | "));
+            for row in &table.rows {
+                let cells: Vec<String> = row.iter().map(Cell::markdown).collect();
+                let _ = writeln!(out, "| {} |", cells.join(" | "));
+            }
+        }
+    }
+    out
+}
+
+fn csv_escape(s: &str) -> String {
+    if s.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_string()
+    }
+}
+
+pub fn cmd_gain_export(rt: &Runtime, args: &ExportArgs, range: RangeOpts) -> i32 {
+    match export(rt, args, range) {
+        Ok(()) => 0,
+        Err(e) => {
+            eprintln!("[tokf] error: {e:#}");
+            1
+        }
+    }
+}
+
+fn export(rt: &Runtime, args: &ExportArgs, range: RangeOpts) -> anyhow::Result<()> {
+    let path = rt
+        .tracking_db_path()
+        .ok_or_else(|| anyhow::anyhow!("cannot determine DB path"))?;
+    let conn = tracking::open_db(&path)?;
+    let range = range.resolve(&conn)?;
+
+    let table = if args.daily {
+        daily_table(&tracking::query_daily(&conn, &range)?)
+    } else if args.by_filter {
+        filter_table(&tracking::query_by_filter(&conn, &range)?)
+    } else if args.by_project {
+        project_table(&tracking::query_by_project(&conn, &range)?)
+    } else {
+        summary_table(&tracking::query_summary(&conn, &range)?)
+    };
+    let rendered = render(&table, args.format);
+
+    if let Some(out) = &args.out {
+        std::fs::write(out, rendered)
+            .map_err(|e| anyhow::anyhow!("cannot write {}: {e}", out.display()))?;
+        eprintln!("[tokf] wrote {}", out.display());
+    } else {
+        print!("{rendered}");
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn filter(name: &str, commands: i64, saved: i64) -> FilterGain {
+        FilterGain {
+            filter_name: name.to_string(),
+            commands,
+            input_tokens: saved * 2,
+            output_tokens: saved,
+            tokens_saved: saved,
+            savings_pct: 50.0,
+            pipe_override_count: 0,
+            total_filter_time_ms: 0,
+            avg_filter_time_ms: 0.0,
+            raw_tokens: saved * 2,
+        }
+    }
+
+    #[test]
+    fn csv_has_raw_numbers_and_quotes_when_needed() {
+        let table = filter_table(&[filter("git/status", 3, 1200), filter("a,\"b\"", 1, 5)]);
+        assert_eq!(
+            render(&table, ExportFormat::Csv),
+            "filter,runs,input_tokens,output_tokens,tokens_saved,savings_pct,pipe_overrides\n\
+             git/status,3,2400,1200,1200,50.0,0\n\
+             \"a,\"\"b\"\"\",1,10,5,5,50.0,0\n"
+        );
+    }
+
+    #[test]
+    fn markdown_right_aligns_numbers() {
+        let table = filter_table(&[filter("git|status", 3, 1200)]);
+        assert_eq!(
+            render(&table, ExportFormat::Markdown),
+            "| filter | runs | input_tokens | output_tokens | tokens_saved | savings_pct | pipe_overrides |\n\
+             | --- | ---: | ---: | ---: | ---: | ---: | ---: |\n\
+             | git\\|status | 3 | 2,400 | 1,200 | 1,200 | 50.0% | 0 |\n"
+        );
+    }
+
+    #[test]
+    fn empty_tables_still_have_headers() {
+        let out = render(&daily_table(&[]), ExportFormat::Markdown);
+        assert!(out.starts_with("| date | runs |"), "{out}");
+        assert_eq!(out.lines().count(), 2);
+    }
+}
@@ -4,6 +4,7 @@ pub mod summary;
 pub mod test_run;
 
 use tokf::baseline;
+use tokf::filter;
 use tokf::history;
 use tokf::runner;
 
@@ -21,13 +22,26 @@ pub struct GenericRun<'a> {
     pub filter_name: &'a str,
 }
 
+/// Run `command_args` verbatim, measuring what it cost.
+fn execute(
+    command_args: &[String],
+) -> anyhow::Result<(runner::CommandResult, runner::ResourceUsage)> {
+    let execution = runner::execute_with_env(
+        &command_args[0],
+        &command_args[1..],
+        &[],
+        runner::ExecOptions::default(),
+    )?;
+    Ok((execution.result, execution.usage))
+}
+
 /// Shared execution + tracking for generic fallback commands.
 ///
 /// 1. Execute command
 /// 2. Compute baseline (if `--baseline-pipe`)
-/// 3. Apply `filter_fn` to combined output
+/// 3. Apply `filter_fn` to combined output, masking secrets
 /// 4. Handle exit-code masking
-/// 5. Record tracking + history
+/// 5. Record tracking + history (raw output with secrets masked)
 /// 6. Print output with compression indicator
 pub fn cmd_generic_run(
     rt: &Runtime,
@@ -40,7 +54,7 @@ pub fn cmd_generic_run(
         baseline_pipe,
         filter_name,
     } = run;
-    let cmd_result = runner::execute(&command_args[0], &command_args[1..])?;
+    let (cmd_result, usage) = execute(command_args)?;
 
     let raw_bytes = cmd_result.combined.len();
     let input_bytes = match baseline_pipe {
@@ -50,6 +64,7 @@ pub fn cmd_generic_run(
 
     let start = std::time::Instant::now();
     let filtered = filter_fn(&cmd_result.combined, cmd_result.exit_code);
+    let filtered = filter::redact::redact_builtin(&filtered).into_owned();
     let elapsed = start.elapsed();
 
     if cli.timing {
@@ -61,8 +76,8 @@ pub fn cmd_generic_run(
 
     let output_bytes = filtered.len();
 
-    let mask = !cli.no_mask_exit_code && cmd_result.exit_code != 0;
-    if mask {
+    let mask = crate::run_cmd::masks_exit_code(rt, cli, None);
+    if mask && cmd_result.exit_code != 0 {
         println!("Error: Exit code {}", cmd_result.exit_code);
     }
 
@@ -77,7 +92,7 @@ pub fn cmd_generic_run(
             // override exists, so there is no substitution to record.
             executed_command: None,
             filter_name,
-            raw_output: &cmd_result.combined,
+            raw_output: &filter::redact::redact_builtin(&cmd_result.combined),
             filtered_output: &filtered,
             exit_code: cmd_result.exit_code,
         },
@@ -100,12 +115,12 @@ pub fn cmd_generic_run(
         elapsed.as_millis(),
         cmd_result.exit_code,
         false,
+        Some(&usage),
+        baseline_pipe
+            .is_none()
+            .then_some((cmd_result.combined.as_str(), filtered.as_str())),
     );
     resolve::try_auto_sync(rt);
 
-    if cli.no_mask_exit_code {
-        Ok(cmd_result.exit_code)
-    } else {
-        Ok(0)
-    }
+    Ok(if mask { 0 } else { cmd_result.exit_code })
 }
@@ -0,0 +1,47 @@
+//! zstd compression of stored outputs.
+//!
+//! Outputs are written as a zstd-compressed BLOB when that is smaller, and
+//! as plain TEXT otherwise, so short outputs and rows from before
+//! compression read back unchanged. The storage class tells the two apart.
+
+use rusqlite::types::{FromSql, FromSqlError, FromSqlResult, ToSql, ToSqlOutput, Value, ValueRef};
+
+/// zstd's own default: most of the ratio of higher levels at a fraction of
+/// the time, which matters because compression runs on every recorded run.
+const LEVEL: i32 = 3;
+
+/// Output text written compressed when that saves space.
+pub struct Compressed<'a>(pub &'a str);
+
+impl ToSql for Compressed<'_> {
+    fn to_sql(&self) -> rusqlite::Result<ToSqlOutput<'_>> {
+        Ok(compress(self.0).map_or_else(
+            || ToSqlOutput::from(self.0),
+            |bytes| ToSqlOutput::Owned(Value::Blob(bytes)),
+        ))
+    }
+}
+
+/// Output text read back from either storage form.
+pub struct Stored(pub String);
+
+impl FromSql for Stored {
+    fn column_result(value: ValueRef<'_>) -> FromSqlResult<Self> {
+        match value {
+            ValueRef::Text(text) => Ok(Self(String::from_utf8_lossy(text).into_owned())),
+            ValueRef::Blob(bytes) => zstd::decode_all(bytes)
+                .map_err(|e| FromSqlError::Other(Box::new(e)))
+                .and_then(|raw| {
+                    String::from_utf8(raw).map_err(|e| FromSqlError::Other(Box::new(e)))
+                })
+                .map(Self),
+            _ => Err(FromSqlError::InvalidType),
+        }
+    }
+}
+
+/// `text` compressed, or `None` when compressing would not make it smaller.
+pub fn compress(text: &str) -> Option<Vec<u8>> {
+    let bytes = zstd::encode_all(text.as_bytes(), LEVEL).ok()?;
+    (bytes.len() < text.len()).then_some(bytes)
+}
@@ -1,17 +1,28 @@
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
 use serde::{Deserialize, Serialize};
 
+use crate::runner::Shell;
 use crate::runtime::Runtime;
 
 /// Configuration for history retention
 #[derive(Debug, Clone)]
 pub struct HistoryConfig {
     pub retention_count: u32,
+    /// Entries older than this are deleted; `None` keeps them regardless of age.
+    pub max_age_days: Option<u32>,
+    /// Oldest entries are deleted while stored outputs exceed this; `None`
+    /// sets no cap.
+    pub max_total_mb: Option<u64>,
 }
 
 impl Default for HistoryConfig {
     fn default() -> Self {
         Self {
             retention_count: 10,
+            max_age_days: None,
+            max_total_mb: None,
         }
     }
 }
@@ -24,6 +35,17 @@ pub struct TokfProjectConfig {
     pub shims: Option<TokfShimsSection>,
     pub output: Option<TokfOutputSection>,
     pub setup: Option<crate::setup::TokfSetupSection>,
+    pub filters: Option<TokfFiltersSection>,
+    pub run: Option<TokfRunSection>,
+    pub shell: Option<TokfShellSection>,
+    /// Dollars per 1,000 input tokens, by model name.
+    pub pricing: Option<BTreeMap<String, f64>>,
+    pub tokenizer: Option<TokfTokenizerSection>,
+    pub tracking: Option<TokfTracking>,
+    /// `[[registry]]` entries; read from the global config only.
+    pub registry: Option<Vec<TokfRegistryEntry>>,
+    /// `[remote]` network settings; read from the global config only.
+    pub remote: Option<TokfRemoteSection>,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -31,14 +53,20 @@ pub struct TokfOutputSection {
     pub show_indicator: Option<bool>,
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, Default)]
 pub struct TokfHistorySection {
     pub retention: Option<u32>,
+    pub max_age_days: Option<u32>,
+    pub max_total_mb: Option<u64>,
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, Default)]
 pub struct TokfSyncSection {
+    /// Sync in the background after runs; falls back to `upload_usage_stats`.
+    pub auto: Option<bool>,
     pub auto_sync_threshold: Option<u32>,
+    /// Also sync in the background once the last sync is this many minutes old.
+    pub auto_sync_interval_minutes: Option<u32>,
     pub upload_usage_stats: Option<bool>,
 }
 
@@ -47,6 +75,74 @@ pub struct TokfShimsSection {
     pub enabled: Option<bool>,
 }
 
+#[derive(Serialize, Deserialize, Default)]
+pub struct TokfFiltersSection {
+    /// Filter names (relative path without `.toml`, e.g. `git/log`) to turn off.
+    #[serde(default)]
+    pub disabled: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Default)]
+pub struct TokfRunSection {
+    /// Output size in MiB past which `tokf run` spills to a temporary file.
+    pub spill_threshold_mb: Option<u64>,
+    /// `false` makes `tokf run` exit with the command's own exit code.
+    pub mask_exit_code: Option<bool>,
+}
+
+#[derive(Serialize, Deserialize, Default)]
+pub struct TokfTokenizerSection {
+    /// Model whose tokenizer counts tokens (`gpt-4o`, `cl100k`, …).
+    pub model: Option<String>,
+}
+
+/// `tracking = false` to record nothing for a project, or a `[tracking]` table.
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum TokfTracking {
+    Enabled(bool),
+    Section(TokfTrackingSection),
+}
+
+#[derive(Serialize, Deserialize, Default)]
+pub struct TokfTrackingSection {
+    /// Events older than this many days are moved to an archive file.
+    pub archive_after_days: Option<u32>,
+    /// Runs between automatic maintenance passes; `0` turns them off.
+    pub maintain_every: Option<u32>,
+}
+
+/// A filter registry besides (or replacing) the one `tokf auth login` uses.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct TokfRegistryEntry {
+    /// Name for `--registry`; `default` overrides the built-in registry.
+    pub name: String,
+    pub url: String,
+    /// Bearer token; without one, the `tokf auth login` token is used when
+    /// `url` is the server it was issued by.
+    pub token: Option<String>,
+    /// Lower is consulted first; unset means 0. Ties keep file order.
+    pub priority: Option<i64>,
+}
+
+/// How tokf reaches registries and the sync server.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct TokfRemoteSection {
+    /// Proxy URL (`http://` or `https://`) for every request;
+    /// takes precedence over `HTTPS_PROXY` / `HTTP_PROXY`.
+    pub proxy: Option<String>,
+    /// PEM file of extra CA certificates to trust, e.g. a corporate root.
+    pub ca_bundle: Option<std::path::PathBuf>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct TokfShellSection {
+    /// `sh`, `bash`, `zsh`, `fish`, `pwsh`, `powershell` or `cmd`.
+    pub name: Option<String>,
+    /// Program to spawn; defaults to `name`, looked up on `PATH`.
+    pub path: Option<String>,
+}
+
 /// Read `[output] show_indicator` from a TOML config file path. Returns `None` on any error.
 fn read_indicator(path: &std::path::Path) -> Option<bool> {
     let content = std::fs::read_to_string(path).ok()?;
@@ -54,48 +150,54 @@ fn read_indicator(path: &std::path::Path) -> Option<bool> {
     cfg.output?.show_indicator
 }
 
-/// Read `[history] retention` from a TOML config file path. Returns `None` on any error.
-fn read_retention_from_config(path: &std::path::Path) -> Option<u32> {
-    let content = std::fs::read_to_string(path).ok()?;
-    let cfg: TokfProjectConfig = toml::from_str(&content).ok()?;
-    cfg.history?.retention
-}
-
 impl HistoryConfig {
-    /// Load retention config using auto-detected paths. Priority:
-    /// 1. `{project_root}/.tokf/config.toml` `[history] retention`
-    /// 2. `{config_dir}/tokf/config.toml` `[history] retention`  (e.g. `~/.config/tokf/config.toml`)
-    /// 3. Default: 10
+    /// Load retention config using auto-detected paths. Priority, per key:
+    /// 1. `{project_root}/.tokf/config.toml` `[history]`
+    /// 2. `{config_dir}/tokf/config.toml` `[history]`  (e.g. `~/.config/tokf/config.toml`)
+    /// 3. Default: `retention` 10, no age or size limit
     pub fn load(rt: &Runtime, project_root: Option<&std::path::Path>) -> Self {
         let global = rt.global_config_path();
         Self::load_from(project_root, global.as_deref())
     }
 
     /// Load retention config from explicit paths. Useful for testing.
-    /// Priority: project config → global config → default 10.
+    /// Priority: project config → global config → default.
     pub fn load_from(
         project_root: Option<&std::path::Path>,
         global_config: Option<&std::path::Path>,
     ) -> Self {
-        let from_project = project_root
-            .and_then(|root| read_retention_from_config(&root.join(".tokf").join("config.toml")));
-        let from_global = global_config.and_then(read_retention_from_config);
-        let retention_count = from_project.or(from_global).unwrap_or(10);
-        Self { retention_count }
+        let read = |path: &std::path::Path| load_project_config(path).history;
+        let project = project_root
+            .and_then(|root| read(&local_config_path(root)))
+            .unwrap_or_default();
+        let global = global_config.and_then(read).unwrap_or_default();
+        Self {
+            retention_count: project.retention.or(global.retention).unwrap_or(10),
+            max_age_days: project.max_age_days.or(global.max_age_days),
+            max_total_mb: project.max_total_mb.or(global.max_total_mb),
+        }
     }
 }
 
 /// Configuration for auto-sync behavior
 #[derive(Debug, Clone)]
 pub struct SyncConfig {
+    /// `[sync] auto`, or `None` when unset; see [`SyncConfig::auto_enabled`].
+    pub auto: Option<bool>,
+    /// Pending events that trigger a background sync; `0` turns this trigger off.
     pub auto_sync_threshold: u32,
+    /// Minutes after the last sync that trigger a background sync; `0` turns
+    /// this trigger off.
+    pub auto_sync_interval_minutes: u32,
     pub upload_usage_stats: Option<bool>,
 }
 
 impl Default for SyncConfig {
     fn default() -> Self {
         Self {
+            auto: None,
             auto_sync_threshold: 100,
+            auto_sync_interval_minutes: 60,
             upload_usage_stats: None,
         }
     }
@@ -105,7 +207,8 @@ impl SyncConfig {
     /// Load sync config using auto-detected paths. Priority (per field):
     /// 1. `{project_root}/.tokf/config.toml` `[sync]`
     /// 2. `{config_dir}/tokf/config.toml` `[sync]`
-    /// 3. Defaults: `auto_sync_threshold = 100`, `upload_usage_stats = None`
+    /// 3. Defaults: `auto_sync_threshold = 100`, `auto_sync_interval_minutes = 60`,
+    ///    everything else unset
     pub fn load(rt: &Runtime, project_root: Option<&std::path::Path>) -> Self {
         let global = rt.global_config_path();
         Self::load_from(project_root, global.as_deref())
@@ -114,45 +217,37 @@ impl SyncConfig {
     /// Load sync config from explicit paths. Useful for testing.
     /// Priority (per field): project config → global config → default.
     ///
-    /// Reads each config file at most once and extracts both
-    /// `auto_sync_threshold` and `upload_usage_stats` from the parsed result.
+    /// Reads each config file at most once.
     pub fn load_from(
         project_root: Option<&std::path::Path>,
         global_config: Option<&std::path::Path>,
     ) -> Self {
-        let project_cfg = project_root.and_then(|root| {
-            let path = root.join(".tokf").join("config.toml");
-            let content = std::fs::read_to_string(path).ok()?;
-            toml::from_str::<TokfProjectConfig>(&content).ok()
-        });
-        let global_cfg = global_config.and_then(|p| {
-            let content = std::fs::read_to_string(p).ok()?;
-            toml::from_str::<TokfProjectConfig>(&content).ok()
-        });
-
-        let threshold_from_project = project_cfg
-            .as_ref()
-            .and_then(|c| c.sync.as_ref()?.auto_sync_threshold);
-        let threshold_from_global = global_cfg
-            .as_ref()
-            .and_then(|c| c.sync.as_ref()?.auto_sync_threshold);
-        let auto_sync_threshold = threshold_from_project
-            .or(threshold_from_global)
-            .unwrap_or(100);
-
-        let upload_from_project = project_cfg
-            .as_ref()
-            .and_then(|c| c.sync.as_ref()?.upload_usage_stats);
-        let upload_from_global = global_cfg
-            .as_ref()
-            .and_then(|c| c.sync.as_ref()?.upload_usage_stats);
-        let upload_usage_stats = upload_from_project.or(upload_from_global);
-
+        let read = |path: &std::path::Path| load_project_config(path).sync;
+        let project = project_root
+            .and_then(|root| read(&local_config_path(root)))
+            .unwrap_or_default();
+        let global = global_config.and_then(read).unwrap_or_default();
+        let defaults = Self::default();
         Self {
-            auto_sync_threshold,
-            upload_usage_stats,
+            auto: project.auto.or(global.auto),
+            auto_sync_threshold: project
+                .auto_sync_threshold
+                .or(global.auto_sync_threshold)
+                .unwrap_or(defaults.auto_sync_threshold),
+            auto_sync_interval_minutes: project
+                .auto_sync_interval_minutes
+                .or(global.auto_sync_interval_minutes)
+                .unwrap_or(defaults.auto_sync_interval_minutes),
+            upload_usage_stats: project.upload_usage_stats.or(global.upload_usage_stats),
         }
     }
+
+    /// Whether `tokf run` may start a background sync. `auto` decides when
+    /// set; otherwise the `upload_usage_stats` consent from `tokf auth login`
+    /// does, and no answer means no.
+    pub fn auto_enabled(&self) -> bool {
+        self.auto.or(self.upload_usage_stats).unwrap_or(false)
+    }
 }
 
 /// Configuration for shim generation and PATH injection
@@ -193,6 +288,48 @@ impl ShimsConfig {
     }
 }
 
+/// Filters turned off with `[filters] disabled`.
+#[derive(Debug, Clone, Default)]
+pub struct FiltersConfig {
+    pub disabled: Vec<String>,
+}
+
+impl FiltersConfig {
+    /// Load the disabled filters using auto-detected paths: the union of
+    /// 1. `{project_root}/.tokf/config.toml` `[filters] disabled`
+    /// 2. `{config_dir}/tokf/config.toml` `[filters] disabled`
+    pub fn load(rt: &Runtime, project_root: Option<&std::path::Path>) -> Self {
+        let global = rt.global_config_path();
+        Self::load_from(project_root, global.as_deref())
+    }
+
+    /// Load the disabled filters from explicit paths. Useful for testing.
+    pub fn load_from(
+        project_root: Option<&std::path::Path>,
+        global_config: Option<&std::path::Path>,
+    ) -> Self {
+        let project = project_root.map(local_config_path);
+        let mut disabled: Vec<String> = Vec::new();
+        for path in project.iter().map(PathBuf::as_path).chain(global_config) {
+            let Some(section) = load_project_config(path).filters else {
+                continue;
+            };
+            for name in section.disabled {
+                let name = name.strip_suffix(".toml").unwrap_or(&name).to_string();
+                if !disabled.contains(&name) {
+                    disabled.push(name);
+                }
+            }
+        }
+        Self { disabled }
+    }
+
+    /// Whether the filter named `name` (e.g. `git/log`) is disabled.
+    pub fn is_disabled(&self, name: &str) -> bool {
+        self.disabled.iter().any(|d| d == name)
+    }
+}
+
 /// Load a `TokfProjectConfig` from a TOML file path. Returns `Default` on any error.
 pub fn load_project_config(path: &std::path::Path) -> TokfProjectConfig {
     std::fs::read_to_string(path)
@@ -242,10 +379,7 @@ pub fn save_upload_stats(rt: &Runtime, enabled: bool) -> anyhow::Result<()> {
 /// Returns an error if the config file cannot be written.
 pub fn save_upload_stats_to_path(path: &std::path::Path, enabled: bool) -> anyhow::Result<()> {
     let mut config = load_project_config(path);
-    let sync = config.sync.get_or_insert(TokfSyncSection {
-        auto_sync_threshold: None,
-        upload_usage_stats: None,
-    });
+    let sync = config.sync.get_or_insert_with(TokfSyncSection::default);
     sync.upload_usage_stats = Some(enabled);
     save_project_config(path, &config)
 }
@@ -302,6 +436,118 @@ impl OutputConfig {
     }
 }
 
+/// How `tokf run` captures output and reports its exit code.
+#[derive(Debug, Clone)]
+pub struct RunConfig {
+    /// Bytes of output kept in memory before the rest is spilled to a
+    /// temporary file; `None` when spilling is turned off.
+    pub spill_threshold: Option<usize>,
+    /// Exit 0 and print `Error: Exit code N` on failure, for filters that
+    /// leave `mask_exit_code` unset.
+    pub mask_exit_code: bool,
+}
+
+/// Default `[run] spill_threshold_mb`.
+const DEFAULT_SPILL_THRESHOLD_MB: u64 = 64;
+
+impl Default for RunConfig {
+    fn default() -> Self {
+        Self::from_mb(DEFAULT_SPILL_THRESHOLD_MB)
+    }
+}
+
+impl RunConfig {
+    /// Load run config using auto-detected paths. Priority:
+    /// 1. `{project_root}/.tokf/config.toml` `[run] spill_threshold_mb`
+    /// 2. `{config_dir}/tokf/config.toml` `[run] spill_threshold_mb`
+    /// 3. Default: 64 (`0` turns spilling off)
+    ///
+    /// `[run] mask_exit_code` follows the same order and defaults to `true`.
+    pub fn load(rt: &Runtime, project_root: Option<&std::path::Path>) -> Self {
+        let global = rt.global_config_path();
+        Self::load_from(project_root, global.as_deref())
+    }
+
+    /// Load run config from explicit paths. Useful for testing.
+    pub fn load_from(
+        project_root: Option<&std::path::Path>,
+        global_config: Option<&std::path::Path>,
+    ) -> Self {
+        let project =
+            project_root.and_then(|root| load_project_config(&local_config_path(root)).run);
+        let global = global_config.and_then(|path| load_project_config(path).run);
+        // Project settings first, then global.
+        let sections: Vec<TokfRunSection> = project.into_iter().chain(global).collect();
+        let mb = sections
+            .iter()
+            .find_map(|s| s.spill_threshold_mb)
+            .unwrap_or(DEFAULT_SPILL_THRESHOLD_MB);
+        let mask_exit_code = sections
+            .iter()
+            .find_map(|s| s.mask_exit_code)
+            .unwrap_or(true);
+        Self {
+            mask_exit_code,
+            ..Self::from_mb(mb)
+        }
+    }
+
+    fn from_mb(mb: u64) -> Self {
+        let bytes = mb.saturating_mul(1024 * 1024);
+        Self {
+            spill_threshold: (mb > 0).then(|| usize::try_from(bytes).unwrap_or(usize::MAX)),
+            mask_exit_code: true,
+        }
+    }
+}
+
+/// Which shell runs filter `run` commands and shell-mode (`tokf -c`) lines.
+#[derive(Debug, Clone, Default)]
+pub struct ShellConfig {
+    pub shell: Shell,
+}
+
+impl ShellConfig {
+    /// Load shell config using auto-detected paths. Priority:
+    /// 1. `{project_root}/.tokf/config.toml` `[shell]`
+    /// 2. `{config_dir}/tokf/config.toml` `[shell]`
+    /// 3. Default: `sh` (`powershell.exe` on Windows)
+    pub fn load(rt: &Runtime, project_root: Option<&std::path::Path>) -> Self {
+        let global = rt.global_config_path();
+        Self::load_from(project_root, global.as_deref())
+    }
+
+    /// Load shell config from explicit paths. Useful for testing.
+    ///
+    /// `name` and `path` are taken from the same file, so a project `[shell]`
+    /// never runs a global `path` under its own `name`. An unknown `name` falls
+    /// back to the default with a warning.
+    pub fn load_from(
+        project_root: Option<&std::path::Path>,
+        global_config: Option<&std::path::Path>,
+    ) -> Self {
+        let read = |path: &std::path::Path| {
+            load_project_config(path)
+                .shell
+                .filter(|section| section.name.is_some())
+        };
+        let section = project_root
+            .and_then(|root| read(&local_config_path(root)))
+            .or_else(|| global_config.and_then(read));
+        let shell = section.and_then(|section| {
+            let name = section.name?;
+            let shell = Shell::named(&name, section.path);
+            if shell.is_none() {
+                eprintln!("[tokf] unknown [shell] name {name:?}, using the default shell");
+            }
+            shell
+        });
+        Self {
+            shell: shell.unwrap_or_default(),
+        }
+    }
+}
+
 /// Walk up from `dir` to find the nearest ancestor containing `.git` or `.tokf/`.
 /// Falls back to `dir` itself if neither is found.
 pub fn project_root_for(dir: &std::path::Path) -> std::path::PathBuf {
@@ -1,6 +1,7 @@
 #![allow(clippy::unwrap_used, clippy::expect_used)]
 
 use super::*;
+use crate::runner::{Shell, ShellKind};
 use tempfile::TempDir;
 
 // --- HistoryConfig ---
@@ -100,206 +101,129 @@ fn history_config_load_malformed_global_config_falls_back_to_default() {
     assert_eq!(config.retention_count, 10);
 }
 
-// --- SyncConfig ---
+// --- FiltersConfig ---
 
 #[test]
-fn sync_config_default() {
-    let config = SyncConfig::default();
-    assert_eq!(config.auto_sync_threshold, 100);
+fn filters_config_defaults_to_none_disabled() {
+    let config = FiltersConfig::load_from(None, None);
+    assert!(config.disabled.is_empty());
 }
 
 #[test]
-fn sync_config_from_toml() {
-    let dir = TempDir::new().expect("tempdir");
-    let tokf_dir = dir.path().join(".tokf");
-    std::fs::create_dir(&tokf_dir).expect("create .tokf");
-    std::fs::write(
-        tokf_dir.join("config.toml"),
-        "[sync]\nauto_sync_threshold = 100\n",
-    )
-    .expect("write config");
-
-    let config = SyncConfig::load_from(Some(dir.path()), None);
-    assert_eq!(config.auto_sync_threshold, 100);
-}
-
-#[test]
-fn sync_config_from_global() {
-    let global_dir = TempDir::new().expect("tempdir");
-    let global_config = global_dir.path().join("config.toml");
-    std::fs::write(&global_config, "[sync]\nauto_sync_threshold = 200\n")
-        .expect("write global config");
-
-    let config = SyncConfig::load_from(None, Some(&global_config));
-    assert_eq!(config.auto_sync_threshold, 200);
-}
-
-#[test]
-fn sync_config_project_overrides_global() {
+fn filters_config_unions_project_and_global() {
     let project_dir = TempDir::new().expect("tempdir");
     let tokf_dir = project_dir.path().join(".tokf");
     std::fs::create_dir(&tokf_dir).expect("create .tokf");
     std::fs::write(
         tokf_dir.join("config.toml"),
-        "[sync]\nauto_sync_threshold = 25\n",
+        "[filters]\ndisabled = [\"git/log\", \"cargo/bench.toml\"]\n",
     )
     .expect("write project config");
 
     let global_dir = TempDir::new().expect("tempdir");
     let global_config = global_dir.path().join("config.toml");
-    std::fs::write(&global_config, "[sync]\nauto_sync_threshold = 300\n")
-        .expect("write global config");
-
-    let config = SyncConfig::load_from(Some(project_dir.path()), Some(&global_config));
-    assert_eq!(
-        config.auto_sync_threshold, 25,
-        "project config should take priority over global"
-    );
-}
-
-#[test]
-fn sync_config_falls_back_to_default() {
-    let dir = TempDir::new().expect("tempdir");
-    let config = SyncConfig::load_from(Some(dir.path()), None);
-    assert_eq!(config.auto_sync_threshold, 100);
-}
-
-#[test]
-fn sync_config_zero_disables_auto_sync() {
-    let dir = TempDir::new().expect("tempdir");
-    let tokf_dir = dir.path().join(".tokf");
-    std::fs::create_dir(&tokf_dir).expect("create .tokf");
     std::fs::write(
-        tokf_dir.join("config.toml"),
-        "[sync]\nauto_sync_threshold = 0\n",
+        &global_config,
+        "[filters]\ndisabled = [\"git/log\", \"npm/test\"]\n",
     )
-    .expect("write config");
+    .expect("write global config");
 
-    let config = SyncConfig::load_from(Some(dir.path()), None);
-    assert_eq!(config.auto_sync_threshold, 0);
+    let config = FiltersConfig::load_from(Some(project_dir.path()), Some(&global_config));
+    assert_eq!(config.disabled, vec!["git/log", "cargo/bench", "npm/test"]);
+    assert!(config.is_disabled("cargo/bench"));
+    assert!(!config.is_disabled("git/push"));
 }
 
-#[test]
-fn mixed_history_and_sync_config() {
-    let dir = TempDir::new().expect("tempdir");
-    let tokf_dir = dir.path().join(".tokf");
-    std::fs::create_dir(&tokf_dir).expect("create .tokf");
-    std::fs::write(
-        tokf_dir.join("config.toml"),
-        "[history]\nretention = 20\n\n[sync]\nauto_sync_threshold = 75\n",
-    )
-    .expect("write config");
-
-    let history = HistoryConfig::load_from(Some(dir.path()), None);
-    let sync = SyncConfig::load_from(Some(dir.path()), None);
-    assert_eq!(history.retention_count, 20);
-    assert_eq!(sync.auto_sync_threshold, 75);
-}
-
-// --- SyncConfig: upload_usage_stats ---
+// --- RunConfig ---
 
 #[test]
-fn sync_config_upload_usage_stats_from_toml() {
-    let dir = TempDir::new().expect("tempdir");
-    let tokf_dir = dir.path().join(".tokf");
-    std::fs::create_dir(&tokf_dir).expect("create .tokf");
-    std::fs::write(
-        tokf_dir.join("config.toml"),
-        "[sync]\nupload_usage_stats = true\n",
-    )
-    .expect("write config");
-
-    let config = SyncConfig::load_from(Some(dir.path()), None);
-    assert_eq!(config.upload_usage_stats, Some(true));
+fn run_config_default_spills_at_64_mib() {
+    let config = RunConfig::load_from(None, None);
+    assert_eq!(config.spill_threshold, Some(64 * 1024 * 1024));
 }
 
 #[test]
-fn sync_config_upload_usage_stats_false() {
-    let dir = TempDir::new().expect("tempdir");
-    let tokf_dir = dir.path().join(".tokf");
+fn run_config_project_overrides_global_and_zero_disables() {
+    let project_dir = TempDir::new().expect("tempdir");
+    let tokf_dir = project_dir.path().join(".tokf");
     std::fs::create_dir(&tokf_dir).expect("create .tokf");
     std::fs::write(
         tokf_dir.join("config.toml"),
-        "[sync]\nupload_usage_stats = false\n",
+        "[run]\nspill_threshold_mb = 0\n",
     )
-    .expect("write config");
-
-    let config = SyncConfig::load_from(Some(dir.path()), None);
-    assert_eq!(config.upload_usage_stats, Some(false));
-}
-
-#[test]
-fn sync_config_upload_usage_stats_default_is_none() {
-    let dir = TempDir::new().expect("tempdir");
-    let config = SyncConfig::load_from(Some(dir.path()), None);
-    assert_eq!(config.upload_usage_stats, None);
-}
+    .expect("write project config");
 
-#[test]
-fn sync_config_upload_usage_stats_from_global() {
     let global_dir = TempDir::new().expect("tempdir");
     let global_config = global_dir.path().join("config.toml");
-    std::fs::write(&global_config, "[sync]\nupload_usage_stats = true\n")
-        .expect("write global config");
+    std::fs::write(&global_config, "[run]\nspill_threshold_mb = 2\n").expect("write global config");
 
-    let config = SyncConfig::load_from(None, Some(&global_config));
-    assert_eq!(config.upload_usage_stats, Some(true));
+    let global = RunConfig::load_from(None, Some(&global_config));
+    assert_eq!(global.spill_threshold, Some(2 * 1024 * 1024));
+    let project = RunConfig::load_from(Some(project_dir.path()), Some(&global_config));
+    assert_eq!(project.spill_threshold, None);
 }
 
 #[test]
-fn sync_config_upload_usage_stats_project_overrides_global() {
+fn run_config_masks_exit_code_unless_turned_off() {
+    assert!(RunConfig::load_from(None, None).mask_exit_code);
+
     let project_dir = TempDir::new().expect("tempdir");
     let tokf_dir = project_dir.path().join(".tokf");
     std::fs::create_dir(&tokf_dir).expect("create .tokf");
     std::fs::write(
         tokf_dir.join("config.toml"),
-        "[sync]\nupload_usage_stats = false\n",
+        "[run]\nmask_exit_code = false\n",
     )
     .expect("write project config");
-
     let global_dir = TempDir::new().expect("tempdir");
     let global_config = global_dir.path().join("config.toml");
-    std::fs::write(&global_config, "[sync]\nupload_usage_stats = true\n")
-        .expect("write global config");
+    std::fs::write(&global_config, "[run]\nspill_threshold_mb = 2\n").expect("write global config");
 
-    let config = SyncConfig::load_from(Some(project_dir.path()), Some(&global_config));
-    assert_eq!(
-        config.upload_usage_stats,
-        Some(false),
-        "project config should take priority over global"
-    );
+    let config = RunConfig::load_from(Some(project_dir.path()), Some(&global_config));
+    assert!(!config.mask_exit_code);
+    assert_eq!(config.spill_threshold, Some(2 * 1024 * 1024));
 }
 
-// --- SyncConfig: partial [sync] section fallthrough ---
+// --- ShellConfig ---
+
+#[test]
+fn shell_config_default_is_system_shell() {
+    let config = ShellConfig::load_from(None, None);
+    assert_eq!(config.shell, Shell::default());
+}
 
 #[test]
-fn sync_config_partial_section_falls_through_to_global() {
-    // Project has [sync] with only auto_sync_threshold, no upload_usage_stats.
-    // Global has [sync] with only upload_usage_stats.
-    // Result: threshold from project, upload_usage_stats from global.
+fn shell_config_project_section_wins_as_a_whole() {
     let project_dir = TempDir::new().expect("tempdir");
     let tokf_dir = project_dir.path().join(".tokf");
     std::fs::create_dir(&tokf_dir).expect("create .tokf");
-    std::fs::write(
-        tokf_dir.join("config.toml"),
-        "[sync]\nauto_sync_threshold = 42\n",
-    )
-    .expect("write project config");
+    std::fs::write(tokf_dir.join("config.toml"), "[shell]\nname = \"bash\"\n")
+        .expect("write project config");
 
     let global_dir = TempDir::new().expect("tempdir");
     let global_config = global_dir.path().join("config.toml");
-    std::fs::write(&global_config, "[sync]\nupload_usage_stats = true\n")
-        .expect("write global config");
+    std::fs::write(
+        &global_config,
+        "[shell]\nname = \"fish\"\npath = \"/opt/fish/bin/fish\"\n",
+    )
+    .expect("write global config");
 
-    let config = SyncConfig::load_from(Some(project_dir.path()), Some(&global_config));
-    assert_eq!(
-        config.auto_sync_threshold, 42,
-        "threshold should come from project"
-    );
+    let global = ShellConfig::load_from(None, Some(&global_config)).shell;
+    assert_eq!(global.kind, ShellKind::Fish);
+    assert_eq!(global.program, "/opt/fish/bin/fish");
+    let project = ShellConfig::load_from(Some(project_dir.path()), Some(&global_config)).shell;
+    assert_eq!(project.kind, ShellKind::Posix);
+    assert_eq!(project.program, "bash");
+}
+
+#[test]
+fn shell_config_unknown_name_falls_back_to_default() {
+    let dir = TempDir::new().expect("tempdir");
+    let config = dir.path().join("config.toml");
+    std::fs::write(&config, "[shell]\nname = \"tcsh\"\n").expect("write config");
     assert_eq!(
-        config.upload_usage_stats,
-        Some(true),
-        "upload_usage_stats should fall through to global"
+        ShellConfig::load_from(None, Some(&config)).shell,
+        Shell::default()
     );
 }
 
@@ -0,0 +1,247 @@
+#![allow(clippy::unwrap_used, clippy::expect_used)]
+
+use super::*;
+use tempfile::TempDir;
+
+// --- SyncConfig ---
+
+#[test]
+fn sync_config_default() {
+    let config = SyncConfig::default();
+    assert_eq!(config.auto_sync_threshold, 100);
+}
+
+#[test]
+fn sync_config_from_toml() {
+    let dir = TempDir::new().expect("tempdir");
+    let tokf_dir = dir.path().join(".tokf");
+    std::fs::create_dir(&tokf_dir).expect("create .tokf");
+    std::fs::write(
+        tokf_dir.join("config.toml"),
+        "[sync]\nauto_sync_threshold = 100\n",
+    )
+    .expect("write config");
+
+    let config = SyncConfig::load_from(Some(dir.path()), None);
+    assert_eq!(config.auto_sync_threshold, 100);
+}
+
+#[test]
+fn sync_config_from_global() {
+    let global_dir = TempDir::new().expect("tempdir");
+    let global_config = global_dir.path().join("config.toml");
+    std::fs::write(&global_config, "[sync]\nauto_sync_threshold = 200\n")
+        .expect("write global config");
+
+    let config = SyncConfig::load_from(None, Some(&global_config));
+    assert_eq!(config.auto_sync_threshold, 200);
+}
+
+#[test]
+fn sync_config_project_overrides_global() {
+    let project_dir = TempDir::new().expect("tempdir");
+    let tokf_dir = project_dir.path().join(".tokf");
+    std::fs::create_dir(&tokf_dir).expect("create .tokf");
+    std::fs::write(
+        tokf_dir.join("config.toml"),
+        "[sync]\nauto_sync_threshold = 25\n",
+    )
+    .expect("write project config");
+
+    let global_dir = TempDir::new().expect("tempdir");
+    let global_config = global_dir.path().join("config.toml");
+    std::fs::write(&global_config, "[sync]\nauto_sync_threshold = 300\n")
+        .expect("write global config");
+
+    let config = SyncConfig::load_from(Some(project_dir.path()), Some(&global_config));
+    assert_eq!(
+        config.auto_sync_threshold, 25,
+        "project config should take priority over global"
+    );
+}
+
+#[test]
+fn sync_config_falls_back_to_default() {
+    let dir = TempDir::new().expect("tempdir");
+    let config = SyncConfig::load_from(Some(dir.path()), None);
+    assert_eq!(config.auto_sync_threshold, 100);
+}
+
+#[test]
+fn sync_config_zero_disables_auto_sync() {
+    let dir = TempDir::new().expect("tempdir");
+    let tokf_dir = dir.path().join(".tokf");
+    std::fs::create_dir(&tokf_dir).expect("create .tokf");
+    std::fs::write(
+        tokf_dir.join("config.toml"),
+        "[sync]\nauto_sync_threshold = 0\n",
+    )
+    .expect("write config");
+
+    let config = SyncConfig::load_from(Some(dir.path()), None);
+    assert_eq!(config.auto_sync_threshold, 0);
+}
+
+#[test]
+fn mixed_history_and_sync_config() {
+    let dir = TempDir::new().expect("tempdir");
+    let tokf_dir = dir.path().join(".tokf");
+    std::fs::create_dir(&tokf_dir).expect("create .tokf");
+    std::fs::write(
+        tokf_dir.join("config.toml"),
+        "[history]\nretention = 20\n\n[sync]\nauto_sync_threshold = 75\n",
+    )
+    .expect("write config");
+
+    let history = HistoryConfig::load_from(Some(dir.path()), None);
+    let sync = SyncConfig::load_from(Some(dir.path()), None);
+    assert_eq!(history.retention_count, 20);
+    assert_eq!(sync.auto_sync_threshold, 75);
+}
+
+// --- SyncConfig: upload_usage_stats ---
+
+#[test]
+fn sync_config_upload_usage_stats_from_toml() {
+    let dir = TempDir::new().expect("tempdir");
+    let tokf_dir = dir.path().join(".tokf");
+    std::fs::create_dir(&tokf_dir).expect("create .tokf");
+    std::fs::write(
+        tokf_dir.join("config.toml"),
+        "[sync]\nupload_usage_stats = true\n",
+    )
+    .expect("write config");
+
+    let config = SyncConfig::load_from(Some(dir.path()), None);
+    assert_eq!(config.upload_usage_stats, Some(true));
+}
+
+#[test]
+fn sync_config_upload_usage_stats_false() {
+    let dir = TempDir::new().expect("tempdir");
+    let tokf_dir = dir.path().join(".tokf");
+    std::fs::create_dir(&tokf_dir).expect("create .tokf");
+    std::fs::write(
+        tokf_dir.join("config.toml"),
+        "[sync]\nupload_usage_stats = false\n",
+    )
+    .expect("write config");
+
+    let config = SyncConfig::load_from(Some(dir.path()), None);
+    assert_eq!(config.upload_usage_stats, Some(false));
+}
+
+#[test]
+fn sync_config_upload_usage_stats_default_is_none() {
+    let dir = TempDir::new().expect("tempdir");
+    let config = SyncConfig::load_from(Some(dir.path()), None);
+    assert_eq!(config.upload_usage_stats, None);
+}
+
+#[test]
+fn sync_config_upload_usage_stats_from_global() {
+    let global_dir = TempDir::new().expect("tempdir");
+    let global_config = global_dir.path().join("config.toml");
+    std::fs::write(&global_config, "[sync]\nupload_usage_stats = true\n")
+        .expect("write global config");
+
+    let config = SyncConfig::load_from(None, Some(&global_config));
+    assert_eq!(config.upload_usage_stats, Some(true));
+}
+
+#[test]
+fn sync_config_upload_usage_stats_project_overrides_global() {
+    let project_dir = TempDir::new().expect("tempdir");
+    let tokf_dir = project_dir.path().join(".tokf");
+    std::fs::create_dir(&tokf_dir).expect("create .tokf");
+    std::fs::write(
+        tokf_dir.join("config.toml"),
+        "[sync]\nupload_usage_stats = false\n",
+    )
+    .expect("write project config");
+
+    let global_dir = TempDir::new().expect("tempdir");
+    let global_config = global_dir.path().join("config.toml");
+    std::fs::write(&global_config, "[sync]\nupload_usage_stats = true\n")
+        .expect("write global config");
+
+    let config = SyncConfig::load_from(Some(project_dir.path()), Some(&global_config));
+    assert_eq!(
+        config.upload_usage_stats,
+        Some(false),
+        "project config should take priority over global"
+    );
+}
+
+// --- SyncConfig: partial [sync] section fallthrough ---
+
+#[test]
+fn sync_config_partial_section_falls_through_to_global() {
+    // Project has [sync] with only auto_sync_threshold, no upload_usage_stats.
+    // Global has [sync] with only upload_usage_stats.
+    // Result: threshold from project, upload_usage_stats from global.
+    let project_dir = TempDir::new().expect("tempdir");
+    let tokf_dir = project_dir.path().join(".tokf");
+    std::fs::create_dir(&tokf_dir).expect("create .tokf");
+    std::fs::write(
+        tokf_dir.join("config.toml"),
+        "[sync]\nauto_sync_threshold = 42\n",
+    )
+    .expect("write project config");
+
+    let global_dir = TempDir::new().expect("tempdir");
+    let global_config = global_dir.path().join("config.toml");
+    std::fs::write(&global_config, "[sync]\nupload_usage_stats = true\n")
+        .expect("write global config");
+
+    let config = SyncConfig::load_from(Some(project_dir.path()), Some(&global_config));
+    assert_eq!(
+        config.auto_sync_threshold, 42,
+        "threshold should come from project"
+    );
+    assert_eq!(
+        config.upload_usage_stats,
+        Some(true),
+        "upload_usage_stats should fall through to global"
+    );
+}
+
+// --- SyncConfig: auto ---
+
+#[test]
+fn sync_config_auto_defaults_off_with_hourly_interval() {
+    let config = SyncConfig::default();
+    assert_eq!(config.auto, None);
+    assert_eq!(config.auto_sync_interval_minutes, 60);
+    assert!(!config.auto_enabled());
+}
+
+#[test]
+fn sync_config_auto_reads_auto_and_interval() {
+    let global_dir = TempDir::new().expect("tempdir");
+    let global_config = global_dir.path().join("config.toml");
+    std::fs::write(
+        &global_config,
+        "[sync]\nauto = true\nauto_sync_interval_minutes = 15\n",
+    )
+    .expect("write global config");
+
+    let config = SyncConfig::load_from(None, Some(&global_config));
+    assert_eq!(config.auto, Some(true));
+    assert_eq!(config.auto_sync_interval_minutes, 15);
+    assert!(config.auto_enabled());
+}
+
+#[test]
+fn sync_config_auto_falls_back_to_upload_consent_and_wins_when_set() {
+    let consent = SyncConfig {
+        upload_usage_stats: Some(true),
+        ..SyncConfig::default()
+    };
+    assert!(consent.auto_enabled());
+    let opted_out = SyncConfig {
+        auto: Some(false),
+        ..consent
+    };
+    assert!(!opted_out.auto_enabled());
+}
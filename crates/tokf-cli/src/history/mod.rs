@@ -1,23 +1,34 @@
 use anyhow::Context as _;
-use rusqlite::Connection;
+use rusqlite::{Connection, OptionalExtension as _};
 
 use crate::runtime::Runtime;
 
+mod compress;
 mod config;
+mod pricing;
 mod queries;
+mod retention;
+mod tokenizer;
+mod tracking_config;
 mod types;
 
 pub use config::{
-    HistoryConfig, OutputConfig, ShimsConfig, SyncConfig, TokfHistorySection, TokfOutputSection,
-    TokfProjectConfig, TokfShimsSection, TokfSyncSection, current_project, global_config_path,
+    FiltersConfig, HistoryConfig, OutputConfig, RunConfig, ShellConfig, ShimsConfig, SyncConfig,
+    TokfFiltersSection, TokfHistorySection, TokfOutputSection, TokfProjectConfig,
+    TokfRegistryEntry, TokfRunSection, TokfShellSection, TokfShimsSection, TokfSyncSection,
+    TokfTokenizerSection, TokfTracking, TokfTrackingSection, current_project, global_config_path,
     load_project_config, local_config_path, project_root_for, save_project_config,
     save_upload_stats, save_upload_stats_to_path,
 };
+pub use pricing::{PricingConfig, tokens_to_usd};
 pub use queries::{
-    clear_history, get_history_entry, get_latest_entry, list_history, record_history,
-    search_history,
+    clear_history, get_history_entry, get_latest_entry, get_latest_for_command, history_stats,
+    list_history, record_history, search_history,
 };
-pub use types::{HistoryEntry, HistoryRecord};
+pub use retention::{PruneReport, prune_history};
+pub use tokenizer::TokenizerConfig;
+pub use tracking_config::{TrackingConfig, tracking_enabled};
+pub use types::{HistoryEntry, HistoryRecord, HistoryStats};
 
 /// Return `true` when `command` matches the most recent history entry for the
 /// current project.  Errors are silently ignored (returns `false`).
@@ -40,6 +51,20 @@ pub fn try_was_recently_run(rt: &Runtime, command: &str) -> bool {
     )
 }
 
+/// Return the previous history entry for `command` in the current project.
+/// Errors are silently ignored (returns `None`).
+///
+/// Call this before recording the current run, otherwise the "previous" entry
+/// is the run itself.
+pub fn try_previous_run(rt: &Runtime, command: &str) -> Option<HistoryEntry> {
+    let project = current_project(rt);
+    let path = rt.tracking_db_path()?;
+    let conn = open_db(&path).ok()?;
+    get_latest_for_command(&conn, &project, command)
+        .ok()
+        .flatten()
+}
+
 /// Open the shared tracking database and ensure the history schema is initialized.
 ///
 /// # Errors
@@ -106,6 +131,52 @@ pub fn init_history_table(conn: &Connection) -> anyhow::Result<()> {
     conn.execute_batch("CREATE INDEX IF NOT EXISTS idx_history_project ON history(project);")
         .context("create project index")?;
 
+    init_search_index(conn)
+}
+
+/// Create the `history_fts` full-text index, and bring older databases up
+/// to the current storage format.
+///
+/// The index is contentless: it keeps only tokens, and [`record_history`]
+/// feeds it the plain text of each run, because `history` itself holds
+/// compressed outputs. A trigger drops a row's tokens when the row goes.
+///
+/// A database without the index, or with the earlier external-content index
+/// that read its text straight from `history`, is upgraded once: existing
+/// outputs are compressed and every row is indexed.
+fn init_search_index(conn: &Connection) -> anyhow::Result<()> {
+    let index_sql: Option<String> = conn
+        .query_row(
+            "SELECT sql FROM sqlite_master WHERE name = 'history_fts'",
+            [],
+            |r| r.get(0),
+        )
+        .optional()
+        .context("check history search index")?;
+    if index_sql
+        .as_deref()
+        .is_some_and(|sql| sql.contains("contentless_delete"))
+    {
+        return Ok(());
+    }
+
+    let tx = conn.unchecked_transaction()?;
+    tx.execute_batch(
+        "DROP TRIGGER IF EXISTS history_fts_insert;
+        DROP TRIGGER IF EXISTS history_fts_update;
+        DROP TRIGGER IF EXISTS history_fts_delete;
+        DROP TABLE IF EXISTS history_fts;
+        CREATE VIRTUAL TABLE history_fts USING fts5(
+            command, executed_command, raw_output, filtered_output,
+            content = '', contentless_delete = 1
+        );
+        CREATE TRIGGER history_fts_delete AFTER DELETE ON history BEGIN
+            DELETE FROM history_fts WHERE rowid = old.id;
+        END;",
+    )
+    .context("create history search index")?;
+    queries::compress_and_index_all(&tx).context("upgrade history storage")?;
+    tx.commit()?;
     Ok(())
 }
 
@@ -126,7 +197,8 @@ pub struct RecordedRun<'a> {
 /// Only records commands where a filter was applied. Passthrough runs (no filter)
 /// are excluded because raw and filtered output would be identical.
 ///
-/// Returns `Some(id)` with the new history entry ID on success, `None` on error.
+/// Returns `Some(id)` with the new history entry ID on success, `None` on error
+/// or when the project has `tracking = false`.
 pub fn try_record(rt: &Runtime, run: &RecordedRun<'_>) -> Option<i64> {
     let RecordedRun {
         command,
@@ -137,6 +209,9 @@ pub fn try_record(rt: &Runtime, run: &RecordedRun<'_>) -> Option<i64> {
         exit_code,
     } = *run;
     let project_root = project_root_for(rt.cwd().unwrap_or_else(|| std::path::Path::new("")));
+    if !tracking_enabled(&project_root) {
+        return None;
+    }
     let project = project_root.to_string_lossy().into_owned();
     let config = HistoryConfig::load(rt, Some(&project_root));
 
@@ -173,8 +248,22 @@ pub fn try_record(rt: &Runtime, run: &RecordedRun<'_>) -> Option<i64> {
 #[cfg(test)]
 mod config_tests;
 #[cfg(test)]
+mod config_tests_sync;
+#[cfg(test)]
 mod tests;
 #[cfg(test)]
 mod tests_clear;
+#[cfg(test)]
+mod tests_compress;
+#[cfg(test)]
+mod tests_pricing;
+#[cfg(test)]
+mod tests_retention;
+
 #[cfg(test)]
 mod tests_search;
+#[cfg(test)]
+mod tests_tokenizer;
+
+#[cfg(test)]
+mod tests_tracking_config;
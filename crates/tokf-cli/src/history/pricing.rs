@@ -0,0 +1,52 @@
+//! `[pricing]` — what a model charges for input tokens, for `tokf gain --cost`.
+
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use super::config::{load_project_config, local_config_path};
+use crate::runtime::Runtime;
+
+/// Dollars per 1,000 input tokens, by model name.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PricingConfig {
+    pub models: BTreeMap<String, f64>,
+}
+
+impl PricingConfig {
+    /// Load pricing using auto-detected paths: the entries of
+    /// 1. `{config_dir}/tokf/config.toml` `[pricing]`
+    /// 2. `{project_root}/.tokf/config.toml` `[pricing]`, which win per model
+    pub fn load(rt: &Runtime, project_root: Option<&Path>) -> Self {
+        let global = rt.global_config_path();
+        Self::load_from(project_root, global.as_deref())
+    }
+
+    /// Load pricing from explicit paths. Useful for testing.
+    ///
+    /// Negative or non-finite prices are skipped with a warning.
+    pub fn load_from(project_root: Option<&Path>, global_config: Option<&Path>) -> Self {
+        let project = project_root.map(local_config_path);
+        let mut models = BTreeMap::new();
+        for path in global_config.into_iter().chain(project.as_deref()) {
+            for (model, price) in load_project_config(path).pricing.unwrap_or_default() {
+                if price.is_finite() && price >= 0.0 {
+                    models.insert(model, price);
+                } else {
+                    eprintln!("[tokf] ignoring [pricing] {model} = {price}: not a valid price");
+                }
+            }
+        }
+        Self { models }
+    }
+
+    /// Price of `model`, per 1,000 input tokens.
+    pub fn price(&self, model: &str) -> Option<f64> {
+        self.models.get(model).copied()
+    }
+}
+
+/// Dollars that `tokens` input tokens cost at `per_1k` dollars per 1,000.
+#[allow(clippy::cast_precision_loss)]
+pub fn tokens_to_usd(tokens: i64, per_1k: f64) -> f64 {
+    tokens as f64 / 1000.0 * per_1k
+}
@@ -1,8 +1,10 @@
 use anyhow::Context as _;
 use rusqlite::Connection;
 
+use super::compress::{Compressed, Stored};
 use super::config::HistoryConfig;
-use super::types::{HistoryEntry, HistoryRecord};
+use super::retention::{STORED_SIZE, prune_history};
+use super::types::{HistoryEntry, HistoryRecord, HistoryStats};
 
 /// Column list shared by every entry-returning query. Kept in one place so the
 /// positional indices in [`map_row`] can't drift apart from the SELECTs.
@@ -17,8 +19,8 @@ pub(super) fn map_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<HistoryEntry>
         command: row.get(3)?,
         executed_command: row.get(4)?,
         filter_name: row.get(5)?,
-        raw_output: row.get(6)?,
-        filtered_output: row.get(7)?,
+        raw_output: row.get::<_, Stored>(6)?.0,
+        filtered_output: row.get::<_, Stored>(7)?.0,
         exit_code: row.get(8)?,
     })
 }
@@ -45,37 +47,72 @@ pub fn record_history(
             record.command,
             record.executed_command,
             record.filter_name,
-            record.raw_output,
-            record.filtered_output,
+            Compressed(&record.raw_output),
+            Compressed(&record.filtered_output),
             record.exit_code
         ],
     )
     .context("insert history entry")?;
 
     let id = conn.last_insert_rowid();
+    index_entry(
+        conn,
+        id,
+        [
+            Some(record.command.as_str()),
+            record.executed_command.as_deref(),
+            Some(record.raw_output.as_str()),
+            Some(record.filtered_output.as_str()),
+        ],
+    )?;
+
+    // Retention is scoped per project so each project keeps its own N
+    // entries; the age and size limits cover the whole history.
+    prune_history(conn, &record.project, config, Some(id))?;
+
+    Ok(id)
+}
 
-    // Retention is scoped per project so each project keeps its own N entries.
-    //
-    // The row we just inserted is explicitly excluded from the prune. Without
-    // that guard a retention of 0 would delete the entry in the same call that
-    // returns its ID, and callers would print a `🗜️#<id>` recovery marker
-    // pointing at a row that no longer exists.
-    let retention_i64 = i64::from(config.retention_count);
+/// Add one entry's searchable text to `history_fts`: command, executed
+/// command, raw and filtered output.
+fn index_entry(conn: &Connection, id: i64, text: [Option<&str>; 4]) -> anyhow::Result<()> {
     conn.execute(
-        "DELETE FROM history
-         WHERE project = ?1
-           AND id <> ?3
-           AND id NOT IN (
-               SELECT id FROM history
-               WHERE project = ?1
-               ORDER BY id DESC
-               LIMIT ?2
-           )",
-        rusqlite::params![record.project, retention_i64, id],
+        "INSERT INTO history_fts (rowid, command, executed_command, raw_output, filtered_output)
+         VALUES (?1, ?2, ?3, ?4, ?5)",
+        rusqlite::params![id, text[0], text[1], text[2], text[3]],
     )
-    .context("enforce history retention")?;
+    .context("index history entry")?;
+    Ok(())
+}
 
-    Ok(id)
+/// Compress every output still stored as plain text, and index every entry.
+/// Used once, when the database is upgraded.
+pub(super) fn compress_and_index_all(conn: &Connection) -> anyhow::Result<()> {
+    let mut stmt = conn.prepare(&format!("SELECT {ENTRY_COLUMNS} FROM history"))?;
+    let entries = stmt
+        .query_map([], map_row)?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    for entry in entries {
+        conn.execute(
+            "UPDATE history SET raw_output = ?2, filtered_output = ?3 WHERE id = ?1",
+            rusqlite::params![
+                entry.id,
+                Compressed(&entry.raw_output),
+                Compressed(&entry.filtered_output)
+            ],
+        )?;
+        index_entry(
+            conn,
+            entry.id,
+            [
+                Some(entry.command.as_str()),
+                entry.executed_command.as_deref(),
+                Some(entry.raw_output.as_str()),
+                Some(entry.filtered_output.as_str()),
+            ],
+        )?;
+    }
+    Ok(())
 }
 
 /// List recent history entries.
@@ -127,41 +164,63 @@ pub fn get_history_entry(conn: &Connection, id: i64) -> anyhow::Result<Option<Hi
     }
 }
 
-/// Search history entries by command or output content.
+/// Full-text search over command, executed command, raw and filtered
+/// output, best matches first.
+///
+/// `query` uses FTS5 syntax: words must all appear, `"a phrase"` matches
+/// words in order, `OR`, `NOT` and `prefix*` work as usual. A query that is
+/// not valid FTS5 — say `error: E0308` — is retried with every word quoted,
+/// so punctuation is matched literally rather than rejected.
 ///
 /// Pass `project = Some("path")` to scope to one project, or `None` for all.
 ///
 /// # Errors
-/// Returns an error if the query fails.
+/// Returns an error if the search query fails.
 pub fn search_history(
     conn: &Connection,
     query: &str,
     limit: usize,
     project: Option<&str>,
 ) -> anyhow::Result<Vec<HistoryEntry>> {
+    if query.trim().is_empty() {
+        return Ok(Vec::new());
+    }
+    run_search(conn, query, limit, project)
+        .or_else(|_| run_search(conn, &literal_query(query), limit, project))
+        .context("search history")
+}
+
+/// `query` with each word quoted, so FTS5 reads none of it as syntax.
+fn literal_query(query: &str) -> String {
+    query
+        .split_whitespace()
+        .map(|word| format!("\"{}\"", word.replace('"', "\"\"")))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn run_search(
+    conn: &Connection,
+    fts_query: &str,
+    limit: usize,
+    project: Option<&str>,
+) -> rusqlite::Result<Vec<HistoryEntry>> {
     #[allow(clippy::cast_possible_wrap)]
     let limit_i64 = limit as i64;
-    let search_pattern = format!("%{query}%");
-
+    // bm25 scores are lower for better matches; a hit in the command counts
+    // for more than one in pages of output. Ties go to the newest entry.
     let mut stmt = conn.prepare(&format!(
         "SELECT {ENTRY_COLUMNS}
          FROM history
+         JOIN (SELECT rowid AS hit, bm25(history_fts, 4.0, 4.0, 1.0, 1.0) AS score
+               FROM history_fts
+               WHERE history_fts MATCH ?2) ON id = hit
          WHERE (?1 IS NULL OR project = ?1)
-           AND (command LIKE ?2 OR executed_command LIKE ?2
-                OR raw_output LIKE ?2 OR filtered_output LIKE ?2)
-         ORDER BY id DESC
+         ORDER BY score, id DESC
          LIMIT ?3"
     ))?;
-
-    let rows = stmt.query_map(
-        rusqlite::params![project, search_pattern, limit_i64],
-        map_row,
-    )?;
-    let mut result = Vec::new();
-    for row in rows {
-        result.push(row.context("read history row")?);
-    }
-    Ok(result)
+    let rows = stmt.query_map(rusqlite::params![project, fts_query, limit_i64], map_row)?;
+    rows.collect()
 }
 
 /// Return the full most recent history entry.
@@ -189,6 +248,30 @@ pub fn get_latest_entry(
     }
 }
 
+/// Return the most recent history entry for `command` in `project`.
+///
+/// # Errors
+/// Returns an error if the query fails.
+pub fn get_latest_for_command(
+    conn: &Connection,
+    project: &str,
+    command: &str,
+) -> anyhow::Result<Option<HistoryEntry>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {ENTRY_COLUMNS}
+         FROM history
+         WHERE project = ?1 AND command = ?2
+         ORDER BY id DESC
+         LIMIT 1"
+    ))?;
+    let mut rows = stmt.query([project, command])?;
+    if let Some(row) = rows.next()? {
+        Ok(Some(map_row(row)?))
+    } else {
+        Ok(None)
+    }
+}
+
 /// Return the command string of the most recent history entry for a project.
 ///
 /// # Errors
@@ -226,3 +309,38 @@ pub fn clear_history(conn: &Connection, project: Option<&str>) -> anyhow::Result
 
     Ok(())
 }
+
+/// Count entries and measure the space their outputs take, stored and
+/// decompressed.
+///
+/// # Errors
+/// Returns an error if the query fails or a stored output cannot be read.
+pub fn history_stats(conn: &Connection) -> anyhow::Result<HistoryStats> {
+    let (entries, projects, compressed_outputs, stored_bytes) = conn
+        .query_row(
+            &format!(
+                "SELECT COUNT(*), COUNT(DISTINCT project),
+                        COALESCE(SUM((typeof(raw_output) = 'blob') + (typeof(filtered_output) = 'blob')), 0),
+                        COALESCE(SUM({STORED_SIZE}), 0)
+                 FROM history"
+            ),
+            [],
+            |r| Ok((r.get(0)?, r.get(1)?, r.get(2)?, r.get(3)?)),
+        )
+        .context("query history stats")?;
+
+    let mut stmt = conn.prepare("SELECT raw_output, filtered_output FROM history")?;
+    let mut rows = stmt.query([])?;
+    let mut output_bytes = 0usize;
+    while let Some(row) = rows.next()? {
+        output_bytes += row.get::<_, Stored>(0)?.0.len() + row.get::<_, Stored>(1)?.0.len();
+    }
+
+    Ok(HistoryStats {
+        entries,
+        projects,
+        compressed_outputs,
+        stored_bytes,
+        output_bytes: i64::try_from(output_bytes).unwrap_or(i64::MAX),
+    })
+}
@@ -0,0 +1,103 @@
+//! Pruning history to the limits in `[history]`.
+
+use anyhow::Context as _;
+use rusqlite::Connection;
+
+use super::config::HistoryConfig;
+
+/// Bytes one entry's outputs take in the database, compressed or not.
+pub(super) const STORED_SIZE: &str =
+    "length(CAST(raw_output AS BLOB)) + length(CAST(filtered_output AS BLOB))";
+
+/// What a prune removed.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct PruneReport {
+    pub entries: usize,
+    /// Stored output bytes the removed entries held.
+    pub bytes: i64,
+}
+
+/// Delete the entries `config` no longer allows, oldest first:
+///
+/// 1. all but the newest `retention` entries of `project`,
+/// 2. entries older than `max_age_days`, in any project,
+/// 3. then the oldest entries, in any project, until the stored outputs fit
+///    in `max_total_mb`.
+///
+/// `keep` is never deleted. [`super::record_history`] passes the entry it
+/// just inserted, so the ID it returns always points at an existing row
+/// even with a retention of 0.
+///
+/// # Errors
+/// Returns an error if a DELETE fails.
+pub fn prune_history(
+    conn: &Connection,
+    project: &str,
+    config: &HistoryConfig,
+    keep: Option<i64>,
+) -> anyhow::Result<PruneReport> {
+    let before = stored_bytes(conn)?;
+    let keep = keep.unwrap_or(-1);
+
+    let mut entries = conn
+        .execute(
+            "DELETE FROM history
+             WHERE project = ?1
+               AND id <> ?3
+               AND id NOT IN (
+                   SELECT id FROM history
+                   WHERE project = ?1
+                   ORDER BY id DESC
+                   LIMIT ?2
+               )",
+            rusqlite::params![project, i64::from(config.retention_count), keep],
+        )
+        .context("enforce history retention")?;
+
+    if let Some(days) = config.max_age_days {
+        entries += conn
+            .execute(
+                "DELETE FROM history
+                 WHERE id <> ?2
+                   AND timestamp < strftime('%Y-%m-%dT%H:%M:%SZ', 'now', '-' || ?1 || ' days')",
+                rusqlite::params![days, keep],
+            )
+            .context("enforce history max_age_days")?;
+    }
+
+    if let Some(mb) = config.max_total_mb {
+        let limit = i64::try_from(mb.saturating_mul(1024 * 1024)).unwrap_or(i64::MAX);
+        // Running total from the newest entry back: everything past the
+        // point where it crosses the limit goes.
+        entries += conn
+            .execute(
+                &format!(
+                    "DELETE FROM history
+                     WHERE id <> ?2
+                       AND id IN (
+                           SELECT id FROM (
+                               SELECT id, SUM({STORED_SIZE}) OVER (ORDER BY id DESC) AS running
+                               FROM history
+                           )
+                           WHERE running > ?1
+                       )"
+                ),
+                rusqlite::params![limit, keep],
+            )
+            .context("enforce history max_total_mb")?;
+    }
+
+    Ok(PruneReport {
+        entries,
+        bytes: before - stored_bytes(conn)?,
+    })
+}
+
+fn stored_bytes(conn: &Connection) -> anyhow::Result<i64> {
+    conn.query_row(
+        &format!("SELECT COALESCE(SUM({STORED_SIZE}), 0) FROM history"),
+        [],
+        |r| r.get(0),
+    )
+    .context("measure history size")
+}
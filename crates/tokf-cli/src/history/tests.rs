@@ -355,7 +355,10 @@ fn record_history_timestamp_iso8601() {
 #[test]
 fn record_history_enforces_retention_per_project() {
     let (_dir, conn) = temp_db();
-    let config = HistoryConfig { retention_count: 2 };
+    let config = HistoryConfig {
+        retention_count: 2,
+        ..HistoryConfig::default()
+    };
 
     // Insert 3 entries for proj-a — only 2 should remain
     for i in 1..=3 {
@@ -396,7 +399,10 @@ fn record_history_never_prunes_the_entry_it_just_inserted() {
     // exist when `record_history` returns — otherwise the marker is dead on
     // arrival rather than merely going stale after N more commands.
     let (_dir, conn) = temp_db();
-    let config = HistoryConfig { retention_count: 0 };
+    let config = HistoryConfig {
+        retention_count: 0,
+        ..HistoryConfig::default()
+    };
 
     let first = record_history(
         &conn,
@@ -547,3 +553,38 @@ fn get_history_entry_found() {
     assert_eq!(entry.filtered_output, "filtered data");
     assert_eq!(entry.exit_code, 5);
 }
+
+// --- get_latest_for_command ---
+
+#[test]
+fn latest_for_command_matches_command_and_project() {
+    let (_dir, conn) = temp_db();
+    let cfg = HistoryConfig::default();
+    for rec in [
+        make_record("/proj", "cargo clippy", Some("cargo/clippy"), "old", "o", 0),
+        make_record("/proj", "cargo clippy", Some("cargo/clippy"), "new", "n", 1),
+        make_record("/proj", "cargo test", Some("cargo/test"), "test", "t", 0),
+        make_record(
+            "/other",
+            "cargo clippy",
+            Some("cargo/clippy"),
+            "other",
+            "x",
+            0,
+        ),
+    ] {
+        record_history(&conn, &rec, &cfg).expect("record");
+    }
+
+    let entry = get_latest_for_command(&conn, "/proj", "cargo clippy")
+        .expect("query")
+        .expect("entry exists");
+    assert_eq!(entry.raw_output, "new");
+    assert_eq!(entry.exit_code, 1);
+
+    assert!(
+        get_latest_for_command(&conn, "/proj", "cargo build")
+            .expect("query")
+            .is_none()
+    );
+}
@@ -0,0 +1,111 @@
+#![allow(clippy::unwrap_used, clippy::expect_used)]
+
+use super::tests::{make_record, temp_db};
+use super::*;
+
+fn storage_class(conn: &Connection, id: i64, column: &str) -> String {
+    conn.query_row(
+        &format!("SELECT typeof({column}) FROM history WHERE id = ?1"),
+        [id],
+        |r| r.get(0),
+    )
+    .unwrap()
+}
+
+fn big_output() -> String {
+    (0..500).fold(String::new(), |mut out, i| {
+        out.push_str("   Compiling crate-");
+        out.push_str(&(i % 7).to_string());
+        out.push_str(" v0.1.0\n");
+        out
+    })
+}
+
+#[test]
+fn large_outputs_are_compressed_and_read_back_unchanged() {
+    let (_dir, conn) = temp_db();
+    let raw = big_output();
+    let id = record_history(
+        &conn,
+        &make_record("p", "cargo build", None, &raw, "ok", 0),
+        &HistoryConfig::default(),
+    )
+    .unwrap();
+
+    assert_eq!(storage_class(&conn, id, "raw_output"), "blob");
+    assert_eq!(storage_class(&conn, id, "filtered_output"), "text");
+    let entry = get_history_entry(&conn, id).unwrap().unwrap();
+    assert_eq!(entry.raw_output, raw);
+    assert_eq!(entry.filtered_output, "ok");
+
+    let found = search_history(&conn, "crate", 10, None).unwrap();
+    assert_eq!(found.len(), 1, "compressed outputs stay searchable");
+}
+
+#[test]
+fn history_stats_measures_stored_and_decompressed_size() {
+    let (_dir, conn) = temp_db();
+    let config = HistoryConfig::default();
+    let raw = big_output();
+    record_history(
+        &conn,
+        &make_record("a", "build", None, &raw, "ok", 0),
+        &config,
+    )
+    .unwrap();
+    record_history(&conn, &make_record("b", "ls", None, "x", "x", 0), &config).unwrap();
+
+    let stats = history_stats(&conn).unwrap();
+    assert_eq!(stats.entries, 2);
+    assert_eq!(stats.projects, 2);
+    assert_eq!(stats.compressed_outputs, 1);
+    assert_eq!(stats.output_bytes, i64::try_from(raw.len() + 4).unwrap());
+    assert!(stats.stored_bytes < stats.output_bytes / 4, "{stats:?}");
+}
+
+#[test]
+fn upgrade_compresses_rows_from_the_external_content_index() {
+    let dir = tempfile::TempDir::new().expect("tempdir");
+    let conn = Connection::open(dir.path().join("history.db")).expect("open");
+    let raw = big_output();
+    conn.execute_batch(
+        "CREATE TABLE history (
+            id INTEGER PRIMARY KEY AUTOINCREMENT, timestamp TEXT NOT NULL,
+            project TEXT NOT NULL DEFAULT '', command TEXT NOT NULL,
+            executed_command TEXT, filter_name TEXT,
+            raw_output TEXT NOT NULL, filtered_output TEXT NOT NULL,
+            exit_code INTEGER NOT NULL
+        );
+        CREATE VIRTUAL TABLE history_fts USING fts5(
+            command, executed_command, raw_output, filtered_output,
+            content = 'history', content_rowid = 'id'
+        );
+        CREATE TRIGGER history_fts_insert AFTER INSERT ON history BEGIN
+            INSERT INTO history_fts(rowid, command, executed_command, raw_output, filtered_output)
+            VALUES (new.id, new.command, new.executed_command, new.raw_output, new.filtered_output);
+        END;",
+    )
+    .expect("previous schema");
+    conn.execute(
+        "INSERT INTO history (timestamp, command, raw_output, filtered_output, exit_code)
+         VALUES ('2024-01-01T00:00:00Z', 'cargo build', ?1, 'ok', 0)",
+        [&raw],
+    )
+    .unwrap();
+
+    init_history_table(&conn).expect("upgrade");
+    assert_eq!(storage_class(&conn, 1, "raw_output"), "blob");
+    assert_eq!(
+        get_history_entry(&conn, 1).unwrap().unwrap().raw_output,
+        raw
+    );
+    let found = search_history(&conn, "compiling", 10, None).unwrap();
+    assert_eq!(found.len(), 1);
+
+    // A second open finds nothing left to do.
+    init_history_table(&conn).expect("reopen");
+    assert_eq!(
+        search_history(&conn, "compiling", 10, None).unwrap().len(),
+        1
+    );
+}
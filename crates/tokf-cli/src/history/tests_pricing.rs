@@ -0,0 +1,50 @@
+#![allow(clippy::unwrap_used, clippy::expect_used)]
+
+use super::*;
+use tempfile::TempDir;
+
+fn write_config(path: &std::path::Path, content: &str) {
+    std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+    std::fs::write(path, content).unwrap();
+}
+
+#[test]
+fn pricing_is_empty_without_config() {
+    assert!(PricingConfig::load_from(None, None).models.is_empty());
+}
+
+#[test]
+fn project_pricing_overrides_global_per_model() {
+    let global_dir = TempDir::new().expect("tempdir");
+    let global = global_dir.path().join("config.toml");
+    write_config(
+        &global,
+        "[pricing]\nclaude-sonnet = 0.003\nclaude-haiku = 0.0008\n",
+    );
+    let project = TempDir::new().expect("tempdir");
+    write_config(
+        &local_config_path(project.path()),
+        "[pricing]\nclaude-sonnet = 0.0025\n",
+    );
+
+    let pricing = PricingConfig::load_from(Some(project.path()), Some(&global));
+    assert_eq!(pricing.price("claude-sonnet"), Some(0.0025));
+    assert_eq!(pricing.price("claude-haiku"), Some(0.0008));
+    assert_eq!(pricing.price("gpt-4o"), None);
+}
+
+#[test]
+fn negative_prices_are_skipped_and_integers_accepted() {
+    let dir = TempDir::new().expect("tempdir");
+    let global = dir.path().join("config.toml");
+    write_config(&global, "[pricing]\ncheap = -1.0\nfine = 3\n");
+    let pricing = PricingConfig::load_from(None, Some(&global));
+    assert_eq!(pricing.models.keys().collect::<Vec<_>>(), ["fine"]);
+    assert_eq!(pricing.price("fine"), Some(3.0));
+}
+
+#[test]
+fn tokens_to_usd_scales_per_thousand() {
+    assert!((tokens_to_usd(84_000, 0.003) - 0.252).abs() < 1e-9);
+    assert!(tokens_to_usd(0, 0.003).abs() < f64::EPSILON);
+}
@@ -0,0 +1,120 @@
+#![allow(clippy::unwrap_used, clippy::expect_used)]
+
+use super::tests::{make_record, temp_db};
+use super::*;
+use tempfile::TempDir;
+
+fn insert(conn: &Connection, project: &str, raw: &str, age_days: u32) -> i64 {
+    let unlimited = HistoryConfig {
+        retention_count: u32::MAX,
+        ..HistoryConfig::default()
+    };
+    let id = record_history(
+        conn,
+        &make_record(project, "cmd", None, raw, "f", 0),
+        &unlimited,
+    )
+    .unwrap();
+    conn.execute(
+        "UPDATE history SET timestamp = strftime('%Y-%m-%dT%H:%M:%SZ', 'now', ?1) WHERE id = ?2",
+        rusqlite::params![format!("-{age_days} days"), id],
+    )
+    .unwrap();
+    id
+}
+
+fn ids(conn: &Connection) -> Vec<i64> {
+    let mut stmt = conn.prepare("SELECT id FROM history ORDER BY id").unwrap();
+    stmt.query_map([], |r| r.get(0))
+        .unwrap()
+        .collect::<rusqlite::Result<_>>()
+        .unwrap()
+}
+
+/// High-entropy text: it compresses to roughly 80% of its length.
+fn noise(len: usize) -> String {
+    let mut state = 0x2545_f491_u32;
+    (0..len)
+        .map(|_| {
+            state ^= state << 13;
+            state ^= state >> 17;
+            state ^= state << 5;
+            char::from(b'!' + u8::try_from(state % 90).unwrap())
+        })
+        .collect()
+}
+
+#[test]
+fn max_age_days_removes_old_entries_in_every_project() {
+    let (_dir, conn) = temp_db();
+    let old_a = insert(&conn, "a", "r", 40);
+    let _old_b = insert(&conn, "b", "r", 31);
+    let recent = insert(&conn, "b", "r", 2);
+    let config = HistoryConfig {
+        max_age_days: Some(30),
+        ..HistoryConfig::default()
+    };
+
+    let report = prune_history(&conn, "a", &config, Some(old_a)).unwrap();
+    assert_eq!(report.entries, 1);
+    assert_eq!(ids(&conn), [old_a, recent], "`keep` survives even when old");
+}
+
+#[test]
+fn max_total_mb_drops_oldest_until_outputs_fit() {
+    let (_dir, conn) = temp_db();
+    let big = noise(700 * 1024);
+    let first = insert(&conn, "p", &big, 0);
+    let second = insert(&conn, "p", &big, 0);
+    let third = insert(&conn, "p", &big, 0);
+    let config = HistoryConfig {
+        max_total_mb: Some(1),
+        ..HistoryConfig::default()
+    };
+
+    let report = prune_history(&conn, "p", &config, None).unwrap();
+    assert_eq!(report.entries, 2, "any two entries exceed 1 MiB");
+    assert!(report.bytes > 1024 * 1024 / 2, "{report:?}");
+    assert_eq!(ids(&conn), [third]);
+    assert!(!ids(&conn).contains(&first) && !ids(&conn).contains(&second));
+}
+
+#[test]
+fn record_history_enforces_every_limit() {
+    let (_dir, conn) = temp_db();
+    let old = insert(&conn, "other", "r", 100);
+    let config = HistoryConfig {
+        max_age_days: Some(7),
+        ..HistoryConfig::default()
+    };
+    let id = record_history(&conn, &make_record("p", "ls", None, "r", "f", 0), &config).unwrap();
+    assert_eq!(ids(&conn), [id]);
+    assert_ne!(old, id);
+}
+
+#[test]
+fn limits_load_per_key_from_project_then_global() {
+    let project = TempDir::new().unwrap();
+    std::fs::create_dir(project.path().join(".tokf")).unwrap();
+    std::fs::write(
+        local_config_path(project.path()),
+        "[history]\nmax_age_days = 14\n",
+    )
+    .unwrap();
+    let global_dir = TempDir::new().unwrap();
+    let global = global_dir.path().join("config.toml");
+    std::fs::write(
+        &global,
+        "[history]\nretention = 25\nmax_age_days = 90\nmax_total_mb = 200\n",
+    )
+    .unwrap();
+
+    let config = HistoryConfig::load_from(Some(project.path()), Some(&global));
+    assert_eq!(config.retention_count, 25);
+    assert_eq!(config.max_age_days, Some(14));
+    assert_eq!(config.max_total_mb, Some(200));
+
+    let defaults = HistoryConfig::load_from(None, None);
+    assert_eq!(defaults.max_age_days, None);
+    assert_eq!(defaults.max_total_mb, None);
+}
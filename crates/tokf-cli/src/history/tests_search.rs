@@ -128,3 +128,94 @@ fn search_history_filters_by_project() {
     assert_eq!(proj_a.len(), 1);
     assert_eq!(proj_a[0].command, "git status");
 }
+
+// --- full-text search ---
+
+fn record_all(conn: &Connection, records: &[(&str, &str)]) {
+    let config = HistoryConfig::default();
+    for (cmd, raw) in records {
+        record_history(conn, &make_record("p", cmd, None, raw, "f", 0), &config).expect("record");
+    }
+}
+
+fn commands(entries: &[HistoryEntry]) -> Vec<&str> {
+    entries.iter().map(|e| e.command.as_str()).collect()
+}
+
+#[test]
+fn search_history_supports_phrases_and_boolean_operators() {
+    let (_dir, conn) = temp_db();
+    record_all(
+        &conn,
+        &[
+            ("df", "error: disk full on /var"),
+            ("du", "full scan, disk ok, one error"),
+            ("ls", "warning: disk nearly full"),
+        ],
+    );
+
+    let phrase = search_history(&conn, "\"disk full\"", 10, None).expect("search");
+    assert_eq!(commands(&phrase), ["df"]);
+
+    let boolean = search_history(&conn, "disk NOT error", 10, None).expect("search");
+    assert_eq!(commands(&boolean), ["ls"]);
+
+    let prefix = search_history(&conn, "warn*", 10, None).expect("search");
+    assert_eq!(commands(&prefix), ["ls"]);
+}
+
+#[test]
+fn search_history_ranks_command_matches_first() {
+    let (_dir, conn) = temp_db();
+    record_all(
+        &conn,
+        &[("cargo test", "running 3 tests"), ("ls", "Cargo.toml src")],
+    );
+    let entries = search_history(&conn, "cargo", 10, None).expect("search");
+    assert_eq!(commands(&entries), ["cargo test", "ls"]);
+}
+
+#[test]
+fn search_history_matches_punctuation_literally() {
+    let (_dir, conn) = temp_db();
+    record_all(
+        &conn,
+        &[
+            ("build", "error: E0308 mismatched types"),
+            ("lint", "error"),
+        ],
+    );
+    let entries = search_history(&conn, "error: E0308", 10, None).expect("search");
+    assert_eq!(commands(&entries), ["build"]);
+    let unbalanced = search_history(&conn, "\"mismatched", 10, None).expect("search");
+    assert_eq!(commands(&unbalanced), ["build"]);
+    assert!(search_history(&conn, "  ", 10, None).unwrap().is_empty());
+}
+
+#[test]
+fn search_index_follows_deletes_and_indexes_existing_rows() {
+    let dir = tempfile::TempDir::new().expect("tempdir");
+    let conn = Connection::open(dir.path().join("history.db")).expect("open");
+    conn.execute_batch(
+        "CREATE TABLE history (
+            id INTEGER PRIMARY KEY AUTOINCREMENT, timestamp TEXT NOT NULL,
+            command TEXT NOT NULL, filter_name TEXT,
+            raw_output TEXT NOT NULL, filtered_output TEXT NOT NULL,
+            exit_code INTEGER NOT NULL
+        );
+        INSERT INTO history (timestamp, command, raw_output, filtered_output, exit_code)
+        VALUES ('2024-01-01T00:00:00Z', 'old run', 'needle', 'f', 0);",
+    )
+    .expect("legacy schema");
+
+    init_history_table(&conn).expect("migrate");
+    let found = search_history(&conn, "needle", 10, None).expect("search");
+    assert_eq!(commands(&found), ["old run"]);
+
+    clear_history(&conn, None).expect("clear");
+    assert!(
+        search_history(&conn, "needle", 10, None)
+            .unwrap()
+            .is_empty()
+    );
+}
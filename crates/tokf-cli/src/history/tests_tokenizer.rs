@@ -0,0 +1,40 @@
+#![allow(clippy::unwrap_used, clippy::expect_used)]
+
+use super::*;
+use tempfile::TempDir;
+
+fn write_config(path: &std::path::Path, content: &str) {
+    std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+    std::fs::write(path, content).unwrap();
+}
+
+#[test]
+fn no_model_without_config() {
+    assert_eq!(TokenizerConfig::load_from(None, None).model, None);
+}
+
+#[test]
+fn project_model_overrides_global() {
+    let global_dir = TempDir::new().expect("tempdir");
+    let global = global_dir.path().join("config.toml");
+    write_config(&global, "[tokenizer]\nmodel = \"gpt-4\"\n");
+    let project = TempDir::new().expect("tempdir");
+
+    let cfg = TokenizerConfig::load_from(Some(project.path()), Some(&global));
+    assert_eq!(cfg.model.as_deref(), Some("gpt-4"));
+
+    write_config(
+        &local_config_path(project.path()),
+        "[tokenizer]\nmodel = \"gpt-4o\"\n",
+    );
+    let cfg = TokenizerConfig::load_from(Some(project.path()), Some(&global));
+    assert_eq!(cfg.model.as_deref(), Some("gpt-4o"));
+}
+
+#[test]
+fn blank_model_counts_as_unset() {
+    let dir = TempDir::new().expect("tempdir");
+    let global = dir.path().join("config.toml");
+    write_config(&global, "[tokenizer]\nmodel = \" \"\n");
+    assert_eq!(TokenizerConfig::load_from(None, Some(&global)).model, None);
+}
@@ -0,0 +1,56 @@
+#![allow(clippy::unwrap_used, clippy::expect_used)]
+
+use super::*;
+use tempfile::TempDir;
+
+#[test]
+fn defaults_keep_everything_and_maintain_every_thousand_runs() {
+    let cfg = TrackingConfig::load_from(None);
+    assert_eq!(cfg.archive_after_days, None);
+    assert_eq!(cfg.maintain_every, 1000);
+}
+
+#[test]
+fn reads_tracking_section() {
+    let dir = TempDir::new().expect("tempdir");
+    let path = dir.path().join("config.toml");
+    std::fs::write(
+        &path,
+        "[tracking]\narchive_after_days = 90\nmaintain_every = 0\n",
+    )
+    .unwrap();
+    assert_eq!(
+        TrackingConfig::load_from(Some(&path)),
+        TrackingConfig {
+            archive_after_days: Some(90),
+            maintain_every: 0,
+        }
+    );
+}
+
+#[test]
+fn tracking_false_opts_the_project_out() {
+    let dir = TempDir::new().expect("tempdir");
+    assert!(tracking_enabled(dir.path()));
+
+    std::fs::create_dir(dir.path().join(".tokf")).unwrap();
+    let config = local_config_path(dir.path());
+    std::fs::write(&config, "tracking = false\n").unwrap();
+    assert!(!tracking_enabled(dir.path()));
+
+    std::fs::write(&config, "tracking = true\n").unwrap();
+    assert!(tracking_enabled(dir.path()));
+    std::fs::write(&config, "[tracking]\nmaintain_every = 5\n").unwrap();
+    assert!(tracking_enabled(dir.path()));
+}
+
+#[test]
+fn tracking_false_in_the_global_config_leaves_defaults() {
+    let dir = TempDir::new().expect("tempdir");
+    let path = dir.path().join("config.toml");
+    std::fs::write(&path, "tracking = false\n").unwrap();
+    assert_eq!(
+        TrackingConfig::load_from(Some(&path)),
+        TrackingConfig::default()
+    );
+}
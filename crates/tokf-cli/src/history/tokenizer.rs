@@ -0,0 +1,35 @@
+//! `[tokenizer]` — which model's tokenizer counts tokens for tracking.
+
+use std::path::Path;
+
+use super::config::{load_project_config, local_config_path};
+use crate::runtime::Runtime;
+
+/// The `[tokenizer]` settings in effect.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TokenizerConfig {
+    /// Model whose tokenizer counts tokens; `None` records estimates only.
+    pub model: Option<String>,
+}
+
+impl TokenizerConfig {
+    /// Load using auto-detected paths. Priority:
+    /// 1. `{project_root}/.tokf/config.toml` `[tokenizer]`
+    /// 2. `{config_dir}/tokf/config.toml` `[tokenizer]`
+    pub fn load(rt: &Runtime, project_root: Option<&Path>) -> Self {
+        let global = rt.global_config_path();
+        Self::load_from(project_root, global.as_deref())
+    }
+
+    /// Load from explicit paths. Useful for testing.
+    pub fn load_from(project_root: Option<&Path>, global_config: Option<&Path>) -> Self {
+        let project = project_root.map(local_config_path);
+        let model = project
+            .as_deref()
+            .into_iter()
+            .chain(global_config)
+            .find_map(|path| load_project_config(path).tokenizer?.model)
+            .filter(|model| !model.trim().is_empty());
+        Self { model }
+    }
+}
@@ -0,0 +1,61 @@
+//! `[tracking]` — upkeep of the tracking DB (`tokf db maintain`), and the
+//! per-project `tracking = false` opt-out.
+
+use std::path::Path;
+
+use super::config::{TokfTracking, load_project_config, local_config_path};
+use crate::runtime::Runtime;
+
+/// Runs between automatic maintenance passes when `maintain_every` is unset.
+const DEFAULT_MAINTAIN_EVERY: u32 = 1000;
+
+/// Tracking DB upkeep settings.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TrackingConfig {
+    /// Events older than this many days are archived; `None` keeps them all.
+    pub archive_after_days: Option<u32>,
+    /// Runs between automatic maintenance passes; `0` turns them off.
+    pub maintain_every: u32,
+}
+
+impl Default for TrackingConfig {
+    fn default() -> Self {
+        Self {
+            archive_after_days: None,
+            maintain_every: DEFAULT_MAINTAIN_EVERY,
+        }
+    }
+}
+
+impl TrackingConfig {
+    /// Load from the global config only: there is one tracking DB for all
+    /// projects, so a project could not sensibly decide how it is kept.
+    pub fn load(rt: &Runtime) -> Self {
+        let global = rt.global_config_path();
+        Self::load_from(global.as_deref())
+    }
+
+    /// Load from an explicit global config path. Useful for testing.
+    pub fn load_from(global_config: Option<&Path>) -> Self {
+        let section = global_config
+            .and_then(|path| match load_project_config(path).tracking {
+                Some(TokfTracking::Section(section)) => Some(section),
+                Some(TokfTracking::Enabled(_)) | None => None,
+            })
+            .unwrap_or_default();
+        Self {
+            archive_after_days: section.archive_after_days,
+            maintain_every: section.maintain_every.unwrap_or(DEFAULT_MAINTAIN_EVERY),
+        }
+    }
+}
+
+/// Whether runs in the project at `project_root` are recorded, in tracking
+/// and history alike. `tracking = false` in its `.tokf/config.toml` turns
+/// both off; filtering is unaffected.
+pub fn tracking_enabled(project_root: &Path) -> bool {
+    !matches!(
+        load_project_config(&local_config_path(project_root)).tracking,
+        Some(TokfTracking::Enabled(false))
+    )
+}
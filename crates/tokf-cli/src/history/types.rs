@@ -25,3 +25,16 @@ pub struct HistoryRecord {
     pub filtered_output: String,
     pub exit_code: i32,
 }
+
+/// Storage used by history, for `tokf history stats`.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct HistoryStats {
+    pub entries: i64,
+    pub projects: i64,
+    /// Raw and filtered outputs stored compressed, out of `2 * entries`.
+    pub compressed_outputs: i64,
+    /// Bytes the outputs take in the database.
+    pub stored_bytes: i64,
+    /// Bytes the outputs take once decompressed.
+    pub output_bytes: i64,
+}
@@ -2,6 +2,7 @@ use std::path::Path;
 
 use tokf::history;
 
+use crate::bench_cmd::format_bytes;
 use crate::commands::HistoryAction;
 
 use tokf::runtime::Runtime;
@@ -14,8 +15,19 @@ pub fn dispatch_history(rt: &Runtime, action: &HistoryAction) -> anyhow::Result<
     match action {
         HistoryAction::List { limit, all } => cmd_history_list(rt, *limit, *all),
         HistoryAction::Show { id, raw } => cmd_history_show(rt, *id, *raw),
+        HistoryAction::Diff { id, stat, no_color } => {
+            crate::history_diff::cmd_history_diff(rt, *id, *stat, *no_color)
+        }
+        HistoryAction::ExportFixture(args) => {
+            crate::fixture_cmd::export_history_entry(rt, args).map(|()| 0)
+        }
+        HistoryAction::Refilter { id, filter } => {
+            crate::history_refilter::cmd_history_refilter(rt, *id, filter.as_deref())
+        }
         HistoryAction::Last { raw, all } => cmd_history_last(rt, *raw, *all),
         HistoryAction::Search { query, limit, all } => cmd_history_search(rt, query, *limit, *all),
+        HistoryAction::Stats => cmd_history_stats(rt),
+        HistoryAction::Prune { dry_run } => cmd_history_prune(rt, *dry_run),
         HistoryAction::Clear { all } => cmd_history_clear(rt, *all),
     }
 }
@@ -35,7 +47,7 @@ pub fn dispatch_raw(rt: &Runtime, target: &str) -> anyhow::Result<i32> {
     }
 }
 
-fn open_history_conn(rt: &Runtime) -> anyhow::Result<rusqlite::Connection> {
+pub fn open_history_conn(rt: &Runtime) -> anyhow::Result<rusqlite::Connection> {
     let path = rt
         .tracking_db_path()
         .ok_or_else(|| anyhow::anyhow!("cannot determine history DB path"))?;
@@ -126,26 +138,36 @@ fn print_substitution_note(entry: &history::HistoryEntry) {
 }
 
 fn print_entry_detail(entry: &history::HistoryEntry) {
-    println!("ID: {}", entry.id);
-    println!("Timestamp: {}", entry.timestamp);
-    println!("Project: {}", entry.project);
-    println!("Command: {}", entry.command);
+    print!("{}", entry_detail(entry));
+}
+
+/// An entry's metadata followed by its raw and filtered output.
+pub fn entry_detail(entry: &history::HistoryEntry) -> String {
+    use std::fmt::Write;
+
+    let mut out = String::new();
+    let _ = writeln!(out, "ID: {}", entry.id);
+    let _ = writeln!(out, "Timestamp: {}", entry.timestamp);
+    let _ = writeln!(out, "Project: {}", entry.project);
+    let _ = writeln!(out, "Command: {}", entry.command);
     // Only shown when a `run` override was in play; entries where the user's
     // command ran verbatim look exactly as they did before.
     if let Some(executed) = entry.executed_command.as_deref() {
-        println!("Executed: {executed}");
+        let _ = writeln!(out, "Executed: {executed}");
     }
     // filter_name is always Some for recorded entries; the Option is defensive for
     // manually-inserted rows or future code paths.
-    println!(
+    let _ = writeln!(
+        out,
         "Filter: {}",
         entry.filter_name.as_deref().unwrap_or("(unknown)")
     );
-    println!("Exit Code: {}", entry.exit_code);
-    println!("\n--- Raw Output ---");
-    println!("{}", entry.raw_output);
-    println!("\n--- Filtered Output ---");
-    println!("{}", entry.filtered_output);
+    let _ = writeln!(out, "Exit Code: {}", entry.exit_code);
+    let _ = writeln!(out, "\n--- Raw Output ---");
+    let _ = writeln!(out, "{}", entry.raw_output);
+    let _ = writeln!(out, "\n--- Filtered Output ---");
+    let _ = writeln!(out, "{}", entry.filtered_output);
+    out
 }
 
 pub fn cmd_history_search(
@@ -175,6 +197,74 @@ pub fn cmd_history_search(
     Ok(0)
 }
 
+pub fn cmd_history_stats(rt: &Runtime) -> anyhow::Result<i32> {
+    let conn = open_history_conn(rt)?;
+    let stats = history::history_stats(&conn)?;
+    let bytes = |n: i64| format_bytes(usize::try_from(n).unwrap_or(0));
+
+    println!(
+        "entries:         {} ({} projects)",
+        stats.entries, stats.projects
+    );
+    println!(
+        "outputs stored:  {} ({} uncompressed, {} of {} compressed)",
+        bytes(stats.stored_bytes),
+        bytes(stats.output_bytes),
+        stats.compressed_outputs,
+        stats.entries * 2
+    );
+    // The file also holds the tracking events and the search index.
+    if let Some(path) = rt.tracking_db_path() {
+        let on_disk: u64 = ["", "-wal"]
+            .iter()
+            .filter_map(|suffix| {
+                let mut file = path.clone().into_os_string();
+                file.push(suffix);
+                std::fs::metadata(file).ok()
+            })
+            .map(|m| m.len())
+            .sum();
+        println!(
+            "database file:   {} ({})",
+            format_bytes(usize::try_from(on_disk).unwrap_or(usize::MAX)),
+            path.display()
+        );
+    }
+    Ok(0)
+}
+
+/// Apply the current project's `[history]` limits now rather than on the
+/// next recorded run, e.g. after lowering them.
+pub fn cmd_history_prune(rt: &Runtime, dry_run: bool) -> anyhow::Result<i32> {
+    let conn = open_history_conn(rt)?;
+    let project_root = history::project_root_for(rt.cwd_or_empty());
+    let config = history::HistoryConfig::load(rt, Some(&project_root));
+    let project = project_root.to_string_lossy();
+
+    let tx = conn.unchecked_transaction()?;
+    let report = history::prune_history(&tx, &project, &config, None)?;
+    let freed = format_bytes(usize::try_from(report.bytes).unwrap_or(0));
+    if dry_run {
+        // Dropping the transaction rolls the deletes back.
+        drop(tx);
+        eprintln!(
+            "[tokf] would prune {} entries ({freed} of output)",
+            report.entries
+        );
+        return Ok(0);
+    }
+    tx.commit()?;
+    if report.entries > 0 {
+        // Deleted pages are only returned to the filesystem by a VACUUM.
+        conn.execute_batch("VACUUM")?;
+    }
+    eprintln!(
+        "[tokf] pruned {} entries ({freed} of output)",
+        report.entries
+    );
+    Ok(0)
+}
+
 pub fn cmd_history_clear(rt: &Runtime, all: bool) -> anyhow::Result<i32> {
     let conn = open_history_conn(rt)?;
     let project = if all {
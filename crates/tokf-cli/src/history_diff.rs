@@ -0,0 +1,153 @@
+//! `tokf history diff <id>` — what a filter removed from one run.
+
+use std::fmt::Write as _;
+use std::io::IsTerminal as _;
+
+use similar::{ChangeTag, TextDiff};
+use tokf::doctor::render::{Colors, should_disable_color};
+use tokf::history;
+use tokf::runtime::Runtime;
+
+/// Lines of context around each hunk.
+const CONTEXT_LINES: usize = 3;
+
+/// Line counts for `--stat`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DiffStat {
+    /// Raw lines the filter dropped or rewrote.
+    pub removed: usize,
+    /// Raw lines that reached the filtered output unchanged.
+    pub kept: usize,
+    /// Filtered lines not in the raw output: summaries and rewrites.
+    pub added: usize,
+}
+
+pub fn diff_stat(raw: &str, filtered: &str) -> DiffStat {
+    let mut stat = DiffStat::default();
+    for change in TextDiff::from_lines(raw, filtered).iter_all_changes() {
+        match change.tag() {
+            ChangeTag::Delete => stat.removed += 1,
+            ChangeTag::Equal => stat.kept += 1,
+            ChangeTag::Insert => stat.added += 1,
+        }
+    }
+    stat
+}
+
+/// Unified diff from the raw to the filtered output, headed `raw` and
+/// `filtered`; empty when the filter changed nothing.
+pub fn render_unified(raw: &str, filtered: &str, colors: &Colors) -> String {
+    render_unified_labeled(raw, filtered, ("raw", "filtered"), colors)
+}
+
+/// Unified diff from `old` to `new` under the given `---`/`+++` labels;
+/// empty when they are the same.
+pub fn render_unified_labeled(
+    old: &str,
+    new: &str,
+    (old_label, new_label): (&str, &str),
+    colors: &Colors,
+) -> String {
+    let diff = TextDiff::from_lines(old, new);
+    let plain = diff
+        .unified_diff()
+        .context_radius(CONTEXT_LINES)
+        .header(old_label, new_label)
+        .to_string();
+    let mut out = String::new();
+    for line in plain.lines() {
+        let color = if line.starts_with("---") || line.starts_with("+++") {
+            colors.bold
+        } else if line.starts_with("@@") {
+            colors.cyan
+        } else if line.starts_with('-') {
+            colors.red
+        } else if line.starts_with('+') {
+            colors.green
+        } else {
+            ""
+        };
+        if color.is_empty() {
+            let _ = writeln!(out, "{line}");
+        } else {
+            let _ = writeln!(out, "{color}{line}{}", colors.reset);
+        }
+    }
+    out
+}
+
+pub fn cmd_history_diff(rt: &Runtime, id: i64, stat: bool, no_color: bool) -> anyhow::Result<i32> {
+    let path = rt
+        .tracking_db_path()
+        .ok_or_else(|| anyhow::anyhow!("cannot determine history DB path"))?;
+    let conn = history::open_db(&path)?;
+    let Some(entry) = history::get_history_entry(&conn, id)? else {
+        eprintln!("[tokf] history entry {id} not found");
+        return Ok(1);
+    };
+
+    if stat {
+        let s = diff_stat(&entry.raw_output, &entry.filtered_output);
+        println!(
+            "{id} {}: {} removed, {} kept, {} added",
+            entry.command, s.removed, s.kept, s.added
+        );
+        return Ok(0);
+    }
+
+    let colors = if std::io::stdout().is_terminal() && !should_disable_color(no_color) {
+        Colors::enabled()
+    } else {
+        Colors::disabled()
+    };
+    let diff = render_unified(&entry.raw_output, &entry.filtered_output, &colors);
+    if diff.is_empty() {
+        eprintln!("[tokf] history entry {id}: filtered output is identical to the raw output");
+    } else {
+        print!("{diff}");
+    }
+    Ok(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RAW: &str = "Compiling a\nCompiling b\nwarning: unused\nFinished\n";
+    const FILTERED: &str = "warning: unused\nFinished\n2 crates compiled\n";
+
+    #[test]
+    fn stat_counts_removed_kept_and_added_lines() {
+        assert_eq!(
+            diff_stat(RAW, FILTERED),
+            DiffStat {
+                removed: 2,
+                kept: 2,
+                added: 1,
+            }
+        );
+    }
+
+    #[test]
+    fn unified_diff_marks_dropped_lines() {
+        let out = render_unified(RAW, FILTERED, &Colors::disabled());
+        assert!(out.starts_with("--- raw\n+++ filtered\n@@ "), "{out}");
+        assert!(
+            out.contains("\n-Compiling a\n-Compiling b\n warning: unused\n"),
+            "{out}"
+        );
+        assert!(out.contains("\n+2 crates compiled\n"), "{out}");
+    }
+
+    #[test]
+    fn colors_wrap_changed_lines_only() {
+        let out = render_unified(RAW, FILTERED, &Colors::enabled());
+        assert!(out.contains("\x1b[31m-Compiling a\x1b[0m"), "{out:?}");
+        assert!(out.contains("\n warning: unused\n"), "{out:?}");
+    }
+
+    #[test]
+    fn identical_outputs_have_no_diff() {
+        assert_eq!(render_unified(RAW, RAW, &Colors::disabled()), "");
+    }
+}
@@ -0,0 +1,195 @@
+//! `tokf history refilter <id>` — re-run a recorded raw output through the
+//! filter that would handle its command today.
+
+use std::fmt::Write as _;
+
+use similar::{DiffOp, TextDiff};
+use tokf::config::types::FilterConfig;
+use tokf::filter;
+use tokf::history;
+use tokf::runner;
+use tokf::runtime::Runtime;
+
+use crate::resolve;
+
+/// Widest the left column gets before its lines are cut short.
+const MAX_COLUMN_WIDTH: usize = 80;
+
+/// Lay out the recorded and the new filtered output in two columns, aligned
+/// on the lines they share. The gutter follows `sdiff`: blank for identical
+/// lines, `|` for changed ones, `<` and `>` for lines only one side has.
+pub fn render_side_by_side(recorded: &str, current: &str) -> String {
+    let diff = TextDiff::from_lines(recorded, current);
+    let old: Vec<&str> = recorded.lines().collect();
+    let new: Vec<&str> = current.lines().collect();
+    let width = old
+        .iter()
+        .map(|l| l.chars().count())
+        .chain(["recorded".len()])
+        .max()
+        .unwrap_or(0)
+        .min(MAX_COLUMN_WIDTH);
+
+    let mut out = String::new();
+    let _ = writeln!(out, "{:width$}   current", "recorded");
+    for op in diff.ops() {
+        let (left, right) = (op.old_range(), op.new_range());
+        let gutter = match op {
+            DiffOp::Equal { .. } => ' ',
+            DiffOp::Delete { .. } => '<',
+            DiffOp::Insert { .. } => '>',
+            DiffOp::Replace { .. } => '|',
+        };
+        for i in 0..left.len().max(right.len()) {
+            let l = old.get(left.start + i).filter(|_| i < left.len());
+            let r = new.get(right.start + i).filter(|_| i < right.len());
+            // A replace of unequal length pads the shorter side.
+            let mark = match (l, r, gutter) {
+                (Some(_), None, '|') => '<',
+                (None, Some(_), '|') => '>',
+                _ => gutter,
+            };
+            let l = fit(l.copied().unwrap_or(""), width);
+            let line = format!("{l:width$} {mark} {}", r.copied().unwrap_or(""));
+            let _ = writeln!(out, "{}", line.trim_end());
+        }
+    }
+    out
+}
+
+/// Cut `line` to `width` characters, marking the cut with `…`.
+fn fit(line: &str, width: usize) -> String {
+    if line.chars().count() <= width {
+        return line.to_string();
+    }
+    let mut cut: String = line.chars().take(width.saturating_sub(1)).collect();
+    cut.push('…');
+    cut
+}
+
+/// The filter a recorded command is re-filtered with.
+struct CurrentFilter {
+    config: FilterConfig,
+    /// Shown in the header: the `--filter` name or the matched command.
+    name: String,
+    /// The command's words after the matched pattern.
+    args: Vec<String>,
+}
+
+/// The filter `filter_name` names, or else the one `tokf run` would pick for
+/// `words` now, with its variants resolved.
+fn current_filter(
+    rt: &Runtime,
+    words: &[String],
+    filter_name: Option<&str>,
+    raw_output: &str,
+) -> anyhow::Result<Option<CurrentFilter>> {
+    if let Some(name) = filter_name {
+        let filters = resolve::discover_installed_filters(rt, false)?;
+        let Some(filter) = filters.iter().find(|f| f.matches_name(name)) else {
+            anyhow::bail!("filter '{name}' not found");
+        };
+        let refs: Vec<&str> = words.iter().map(String::as_str).collect();
+        let args = filter
+            .matches(&refs)
+            .map_or_else(Vec::new, |consumed| words[consumed..].to_vec());
+        return Ok(Some(CurrentFilter {
+            config: filter.config.clone(),
+            name: name.to_string(),
+            args,
+        }));
+    }
+
+    let Some(filter_match) = resolve::find_filter(rt, words, false, false)? else {
+        return Ok(None);
+    };
+    let args = words[filter_match.words_consumed..].to_vec();
+    let name = filter_match.matched_command.clone();
+    let filter_match = resolve::resolve_args_variants(filter_match, &args, false);
+    let (config, _) = resolve::resolve_phase_b(filter_match, raw_output, false);
+    Ok(Some(CurrentFilter { config, name, args }))
+}
+
+pub fn cmd_history_refilter(
+    rt: &Runtime,
+    id: i64,
+    filter_name: Option<&str>,
+) -> anyhow::Result<i32> {
+    let path = rt
+        .tracking_db_path()
+        .ok_or_else(|| anyhow::anyhow!("cannot determine history DB path"))?;
+    let conn = history::open_db(&path)?;
+    let Some(entry) = history::get_history_entry(&conn, id)? else {
+        eprintln!("[tokf] history entry {id} not found");
+        return Ok(1);
+    };
+
+    let words: Vec<String> = entry
+        .command
+        .split_whitespace()
+        .map(str::to_string)
+        .collect();
+    let Some(CurrentFilter { config, name, args }) =
+        current_filter(rt, &words, filter_name, &entry.raw_output)?
+    else {
+        eprintln!(
+            "[tokf] no filter matches \"{}\"; pass --filter to pick one",
+            entry.command
+        );
+        return Ok(1);
+    };
+
+    let cmd_result = runner::CommandResult {
+        stdout: String::new(),
+        stderr: String::new(),
+        exit_code: entry.exit_code,
+        combined: entry.raw_output,
+    };
+    let opts = resolve::filter_options(&config, Some(rt.cwd_or_empty()), false);
+    let refiltered = filter::apply(&config, &cmd_result, &args, &opts);
+
+    let recorded_with = entry.filter_name.as_deref().unwrap_or("no filter");
+    println!(
+        "[tokf] refilter #{id}: {} (recorded: {recorded_with}, current: {name})",
+        entry.command
+    );
+    if refiltered.output.trim_end() == entry.filtered_output.trim_end() {
+        eprintln!("[tokf] the current filter gives the recorded output");
+    }
+    print!(
+        "{}",
+        render_side_by_side(&entry.filtered_output, &refiltered.output)
+    );
+    Ok(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn equal_lines_share_a_row() {
+        let out = render_side_by_side("a\nb\n", "a\nb\n");
+        assert_eq!(out, "recorded   current\na          a\nb          b\n");
+    }
+
+    #[test]
+    fn gutter_marks_changed_and_one_sided_lines() {
+        let out = render_side_by_side("keep\nold\ngone\n", "keep\nnew\n");
+        let rows: Vec<&str> = out.lines().collect();
+        assert_eq!(rows[1], "keep       keep");
+        assert_eq!(rows[2], "old      | new");
+        assert_eq!(rows[3], "gone     <");
+        let out = render_side_by_side("keep\n", "keep\nadded\n");
+        assert_eq!(out.lines().last(), Some("         > added"));
+    }
+
+    #[test]
+    fn long_lines_are_cut_to_the_column() {
+        let long = "x".repeat(MAX_COLUMN_WIDTH + 10);
+        let out = render_side_by_side(&long, "y");
+        let row = out.lines().nth(1).unwrap_or_default();
+        assert!(row.starts_with(&format!("{}…", "x".repeat(MAX_COLUMN_WIDTH - 1))));
+        assert!(row.ends_with(" | y"), "{row}");
+    }
+}
@@ -0,0 +1,382 @@
+//! Hook adapters declared in TOML, for AI tools without a built-in format.
+//!
+//! `tokf hook handle --adapter <tool>` reads `.tokf/hooks/<tool>.toml` (or
+//! `hooks/<tool>.toml` in the user config dir), which names the JSON paths of
+//! the tool name and command in the hook input and where the rewritten
+//! command, decision and reason go in the response:
+//!
+//! ```toml
+//! tool_name = "tool_name"
+//! shell_tools = ["Bash"]
+//! command = "tool_input.command"
+//!
+//! [response]
+//! command = "hookSpecificOutput.updatedInput.command"
+//! decision = "hookSpecificOutput.permissionDecision"
+//! reason = "hookSpecificOutput.permissionDecisionReason"
+//! fields = { "hookSpecificOutput.hookEventName" = "PreToolUse" }
+//! ```
+
+use std::collections::BTreeMap;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+use anyhow::Context;
+use serde::Deserialize;
+use serde_json::Value;
+
+use super::HookOutcome;
+use super::types::HookFormat;
+use crate::rewrite;
+use crate::rewrite::types::RewriteConfig;
+use crate::runtime::Runtime;
+
+/// A hook adapter, parsed from `<tool>.toml`. Paths are dot-separated keys,
+/// with numbers indexing arrays (`tool_calls.0.command`).
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AdapterConfig {
+    /// Path to the tool name in the hook input. Unset, every call is a shell call.
+    pub tool_name: Option<String>,
+    /// Tool names that run a shell command, e.g. `["Bash"]`.
+    #[serde(default)]
+    pub shell_tools: Vec<String>,
+    /// Path to the shell command in the hook input.
+    pub command: String,
+    pub response: ResponseShape,
+}
+
+/// Where the parts of the hook response go.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ResponseShape {
+    /// Path the rewritten command is written to.
+    pub command: String,
+    /// Path for `allow`, `ask` or `deny`; unset for tools that take no decision.
+    pub decision: Option<String>,
+    /// Path for the permission engine's reason, when there is one.
+    pub reason: Option<String>,
+    /// Decision values the tool spells differently, e.g. `{ ask = "deny" }`.
+    #[serde(default)]
+    pub decisions: BTreeMap<String, String>,
+    /// Fixed values added to every response, by path.
+    #[serde(default)]
+    pub fields: BTreeMap<String, Value>,
+}
+
+impl AdapterConfig {
+    /// Parse and validate an adapter definition.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error for invalid TOML, an empty path, or a `tool_name`
+    /// without `shell_tools`.
+    pub fn parse(content: &str) -> anyhow::Result<Self> {
+        let config: Self = toml::from_str(content)?;
+        if config.tool_name.is_some() && config.shell_tools.is_empty() {
+            anyhow::bail!("`tool_name` is set but `shell_tools` is empty");
+        }
+        let response = &config.response;
+        let paths = [config.tool_name.as_deref(), Some(config.command.as_str())]
+            .into_iter()
+            .chain([
+                Some(response.command.as_str()),
+                response.decision.as_deref(),
+                response.reason.as_deref(),
+            ])
+            .flatten()
+            .chain(response.fields.keys().map(String::as_str));
+        for path in paths {
+            if path.split('.').any(str::is_empty) {
+                anyhow::bail!("invalid JSON path `{path}`");
+            }
+        }
+        for key in response.decisions.keys() {
+            if !matches!(key.as_str(), "allow" | "ask" | "deny") {
+                anyhow::bail!("unknown decision `{key}` (expected allow, ask or deny)");
+            }
+        }
+        Ok(config)
+    }
+
+    /// The shell command in `input`, or `None` when the call is not a shell
+    /// call or has no command.
+    pub fn extract_command(&self, input: &Value) -> Option<String> {
+        if let Some(path) = &self.tool_name {
+            let name = get_path(input, path)?.as_str()?;
+            if !self.shell_tools.iter().any(|t| t == name) {
+                return None;
+            }
+        }
+        get_path(input, &self.command)?.as_str().map(str::to_string)
+    }
+
+    /// Build the response for `decision` (`allow`, `ask` or `deny`).
+    pub fn response(&self, decision: &str, command: String, reason: Option<String>) -> Value {
+        let shape = &self.response;
+        let mut out = Value::Object(serde_json::Map::new());
+        for (path, value) in &shape.fields {
+            set_path(&mut out, path, value.clone());
+        }
+        set_path(&mut out, &shape.command, Value::String(command));
+        if let Some(path) = &shape.decision {
+            let value = shape
+                .decisions
+                .get(decision)
+                .map_or(decision, String::as_str);
+            set_path(&mut out, path, Value::String(value.to_string()));
+        }
+        if let (Some(path), Some(reason)) = (&shape.reason, reason) {
+            set_path(&mut out, path, Value::String(reason));
+        }
+        out
+    }
+}
+
+/// Read the value at a dotted `path`.
+fn get_path<'a>(value: &'a Value, path: &str) -> Option<&'a Value> {
+    path.split('.').try_fold(value, |v, key| match v {
+        Value::Array(items) => items.get(key.parse::<usize>().ok()?),
+        _ => v.get(key),
+    })
+}
+
+/// Write `new` at a dotted `path`, creating objects along the way.
+fn set_path(value: &mut Value, path: &str, new: Value) {
+    let mut cur = value;
+    for key in path.split('.') {
+        if !cur.is_object() {
+            *cur = Value::Object(serde_json::Map::new());
+        }
+        let Value::Object(map) = cur else {
+            unreachable!("replaced with an object above")
+        };
+        cur = map.entry(key).or_insert(Value::Null);
+    }
+    *cur = new;
+}
+
+/// Find `<tool>.toml`: the project's `.tokf/hooks/` first, then the user
+/// config dir's `hooks/`.
+fn adapter_path(rt: &Runtime, tool: &str) -> anyhow::Result<PathBuf> {
+    if tool.is_empty() || tool.contains(['/', '\\']) || tool.starts_with('.') {
+        anyhow::bail!("invalid adapter name `{tool}`");
+    }
+    let file = format!("{tool}.toml");
+    let project = crate::history::project_root_for(rt.cwd_or_empty());
+    let candidates = [
+        Some(project.join(".tokf/hooks")),
+        rt.user_dir().map(|d| d.join("hooks")),
+    ];
+    candidates
+        .into_iter()
+        .flatten()
+        .map(|dir| dir.join(&file))
+        .find(|p| p.is_file())
+        .with_context(|| format!("no adapter found for `{tool}` (looked for .tokf/hooks/{file})"))
+}
+
+/// Load the adapter for `tool`.
+///
+/// # Errors
+///
+/// Returns an error if no adapter file exists or it is invalid.
+pub fn load(rt: &Runtime, tool: &str) -> anyhow::Result<AdapterConfig> {
+    let path = adapter_path(rt, tool)?;
+    load_from(&path)
+}
+
+fn load_from(path: &Path) -> anyhow::Result<AdapterConfig> {
+    let content = std::fs::read_to_string(path)
+        .with_context(|| format!("failed to read {}", path.display()))?;
+    AdapterConfig::parse(&content).with_context(|| format!("invalid adapter {}", path.display()))
+}
+
+/// Process a hook invocation for the tool described by `.tokf/hooks/<tool>.toml`.
+///
+/// A missing or invalid adapter is reported on stderr and passes the command
+/// through, so a broken adapter never blocks the tool.
+pub fn handle(rt: &Runtime, tool: &str, no_cache: bool, no_mask_exit_code: bool) -> HookOutcome {
+    let adapter = match load(rt, tool) {
+        Ok(adapter) => adapter,
+        Err(e) => {
+            eprintln!("[tokf] hook adapter: {e:#}");
+            return HookOutcome::PassThrough;
+        }
+    };
+    let mut input = String::new();
+    if std::io::stdin().read_to_string(&mut input).is_err() {
+        return HookOutcome::PassThrough;
+    }
+    let user_config = rewrite::load_user_config(rt).unwrap_or_default();
+    let search_dirs = crate::config::default_search_dirs(rt);
+    let ctx = Invocation {
+        tool,
+        user_config: &user_config,
+        search_dirs: &search_dirs,
+        no_cache,
+        no_mask_exit_code,
+    };
+    handle_json_inner(rt, &adapter, &input, &ctx)
+}
+
+/// Everything about one hook call besides the adapter and its input.
+struct Invocation<'a> {
+    tool: &'a str,
+    user_config: &'a RewriteConfig,
+    search_dirs: &'a [PathBuf],
+    no_cache: bool,
+    no_mask_exit_code: bool,
+}
+
+fn handle_json_inner(
+    rt: &Runtime,
+    adapter: &AdapterConfig,
+    json: &str,
+    ctx: &Invocation<'_>,
+) -> HookOutcome {
+    let Ok(input) = serde_json::from_str::<Value>(json) else {
+        return HookOutcome::PassThrough;
+    };
+    let Some(command) = adapter.extract_command(&input) else {
+        return HookOutcome::PassThrough;
+    };
+
+    super::process_command(
+        rt,
+        &command,
+        ctx.tool,
+        json,
+        HookFormat::Adapter,
+        ctx.user_config,
+        ctx.search_dirs,
+        ctx.no_cache,
+        ctx.no_mask_exit_code,
+        |cmd, reason| adapter.response("allow", cmd, reason),
+        |cmd, reason| adapter.response("ask", cmd, reason),
+        |cmd, reason| adapter.response("deny", cmd, reason),
+        HookOutcome::Allow,
+        HookOutcome::Ask,
+    )
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    const CLAUDE_LIKE: &str = r#"
+tool_name = "tool_name"
+shell_tools = ["Bash"]
+command = "tool_input.command"
+
+[response]
+command = "hookSpecificOutput.updatedInput.command"
+decision = "hookSpecificOutput.permissionDecision"
+reason = "hookSpecificOutput.permissionDecisionReason"
+decisions = { ask = "deny" }
+fields = { "hookSpecificOutput.hookEventName" = "PreToolUse" }
+"#;
+
+    #[test]
+    fn extract_command_checks_tool_name() {
+        let adapter = AdapterConfig::parse(CLAUDE_LIKE).unwrap();
+        let bash =
+            serde_json::json!({"tool_name": "Bash", "tool_input": {"command": "git status"}});
+        assert_eq!(
+            adapter.extract_command(&bash).as_deref(),
+            Some("git status")
+        );
+        let read = serde_json::json!({"tool_name": "Read", "tool_input": {"command": "x"}});
+        assert_eq!(adapter.extract_command(&read), None);
+    }
+
+    #[test]
+    fn extract_command_follows_array_indices() {
+        let adapter =
+            AdapterConfig::parse("command = \"calls.0.cmd\"\n[response]\ncommand = \"cmd\"")
+                .unwrap();
+        let input = serde_json::json!({"calls": [{"cmd": "cargo test"}]});
+        assert_eq!(
+            adapter.extract_command(&input).as_deref(),
+            Some("cargo test")
+        );
+    }
+
+    #[test]
+    fn response_fills_paths_and_renames_decisions() {
+        let adapter = AdapterConfig::parse(CLAUDE_LIKE).unwrap();
+        let allow = adapter.response("allow", "tokf run git status".to_string(), None);
+        assert_eq!(
+            allow,
+            serde_json::json!({"hookSpecificOutput": {
+                "hookEventName": "PreToolUse",
+                "updatedInput": {"command": "tokf run git status"},
+                "permissionDecision": "allow",
+            }})
+        );
+        let ask = adapter.response("ask", "x".to_string(), Some("why".to_string()));
+        assert_eq!(ask["hookSpecificOutput"]["permissionDecision"], "deny");
+        assert_eq!(ask["hookSpecificOutput"]["permissionDecisionReason"], "why");
+    }
+
+    #[test]
+    fn parse_rejects_invalid_definitions() {
+        let no_shell_tools = "tool_name = \"t\"\ncommand = \"c\"\n[response]\ncommand = \"c\"";
+        assert!(AdapterConfig::parse(no_shell_tools).is_err());
+        let empty_segment = "command = \"a..b\"\n[response]\ncommand = \"c\"";
+        assert!(AdapterConfig::parse(empty_segment).is_err());
+        let bad_decision =
+            "command = \"c\"\n[response]\ncommand = \"c\"\ndecisions = { maybe = \"x\" }";
+        assert!(AdapterConfig::parse(bad_decision).is_err());
+        let unknown_key = "command = \"c\"\nextra = 1\n[response]\ncommand = \"c\"";
+        assert!(AdapterConfig::parse(unknown_key).is_err());
+    }
+
+    #[test]
+    fn load_prefers_project_adapter() {
+        let dir = TempDir::new().unwrap();
+        let hooks = dir.path().join(".tokf/hooks");
+        std::fs::create_dir_all(&hooks).unwrap();
+        std::fs::write(hooks.join("mytool.toml"), CLAUDE_LIKE).unwrap();
+        let rt = Runtime::builder().cwd(dir.path()).build();
+
+        let adapter = load(&rt, "mytool").unwrap();
+        assert_eq!(adapter.command, "tool_input.command");
+        assert!(load(&rt, "other").is_err());
+        assert!(load(&rt, "../mytool").is_err());
+    }
+
+    #[test]
+    fn handle_rewrites_matching_command() {
+        let filters = TempDir::new().unwrap();
+        std::fs::write(
+            filters.path().join("git-status.toml"),
+            "command = \"git status\"",
+        )
+        .unwrap();
+        let adapter = AdapterConfig::parse(CLAUDE_LIKE).unwrap();
+        let rt = Runtime::isolated();
+        let config = RewriteConfig::default();
+        let search_dirs = [filters.path().to_path_buf()];
+        let ctx = Invocation {
+            tool: "mytool",
+            user_config: &config,
+            search_dirs: &search_dirs,
+            no_cache: false,
+            no_mask_exit_code: false,
+        };
+
+        let json = r#"{"tool_name":"Bash","tool_input":{"command":"git status"}}"#;
+        assert_eq!(
+            handle_json_inner(&rt, &adapter, json, &ctx),
+            HookOutcome::Allow
+        );
+        let json = r#"{"tool_name":"Bash","tool_input":{"command":"unknown-cmd"}}"#;
+        assert_eq!(
+            handle_json_inner(&rt, &adapter, json, &ctx),
+            HookOutcome::PassThrough
+        );
+    }
+}
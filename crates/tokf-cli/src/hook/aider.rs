@@ -1,6 +1,7 @@
 use std::path::{Path, PathBuf};
 
 use super::instructions;
+use super::uninstall::Footprint;
 
 use crate::runtime::Runtime;
 
@@ -27,6 +28,26 @@ pub fn install(rt: &Runtime, global: bool) -> anyhow::Result<()> {
     Ok(())
 }
 
+/// The files [`install`] writes. The `read:` entry a global install adds to
+/// `~/.aider.conf.yml` is not part of it.
+///
+/// # Errors
+///
+/// Returns an error if the tokf user directory cannot be determined.
+pub fn footprint(rt: &Runtime, global: bool) -> anyhow::Result<Footprint> {
+    Ok(if global {
+        Footprint {
+            files: vec![global_conventions_path(rt)?],
+            ..Footprint::default()
+        }
+    } else {
+        Footprint {
+            sections: vec![PathBuf::from("CONVENTIONS.md")],
+            ..Footprint::default()
+        }
+    })
+}
+
 /// Core install logic for project-local (testable).
 pub(crate) fn install_to(conventions_path: &Path) -> anyhow::Result<()> {
     append_to_conventions(conventions_path)?;
@@ -3,6 +3,7 @@ use std::path::{Path, PathBuf};
 use anyhow::Context;
 
 use super::instructions;
+use super::uninstall::Footprint;
 
 /// Install the Cline rules file.
 ///
@@ -10,12 +11,27 @@ use super::instructions;
 ///
 /// Returns an error if file I/O fails.
 pub fn install(global: bool) -> anyhow::Result<()> {
-    let rules_path = if global {
-        global_rules_path()?
+    install_to(&rules_path(global)?)
+}
+
+/// The rules file [`install`] writes.
+///
+/// # Errors
+///
+/// Returns an error if the home directory cannot be determined.
+pub fn footprint(global: bool) -> anyhow::Result<Footprint> {
+    Ok(Footprint {
+        files: vec![rules_path(global)?],
+        ..Footprint::default()
+    })
+}
+
+fn rules_path(global: bool) -> anyhow::Result<PathBuf> {
+    if global {
+        global_rules_path()
     } else {
-        PathBuf::from(".clinerules/tokf.md")
-    };
-    install_to(&rules_path)
+        Ok(PathBuf::from(".clinerules/tokf.md"))
+    }
 }
 
 /// Core install logic with explicit path (testable).
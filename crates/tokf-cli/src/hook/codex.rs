@@ -3,6 +3,7 @@ use std::process::Command;
 
 use anyhow::Context;
 
+use super::uninstall::Footprint;
 use super::{
     CodexRewriteMode, patch_json_hook_config_with_command, patch_md_with_reference, resolve_paths,
     write_context_doc,
@@ -45,12 +46,7 @@ pub fn install(
     let mode = detect_codex_rewrite_mode();
     install_hook_to(&hook_dir, &codex_dir, tokf_bin, install_context, mode)?;
 
-    let parent = if global {
-        let home = dirs::home_dir().context("could not determine home directory")?;
-        home.join(".agents/skills")
-    } else {
-        PathBuf::from(".agents/skills")
-    };
+    let parent = skills_dir(global)?;
     for skill in CODEX_SKILLS {
         write_skill_file(&parent.join(skill.dir_name), skill.content)?;
     }
@@ -59,6 +55,34 @@ pub fn install(
     Ok(())
 }
 
+/// The files [`install`] writes: the hook shim, `hooks.json` and the skills.
+///
+/// # Errors
+///
+/// Returns an error if the home or working directory cannot be determined.
+pub fn footprint(rt: &Runtime, global: bool) -> anyhow::Result<Footprint> {
+    let (hook_dir, codex_dir) = resolve_paths(rt, global, ".codex")?;
+    let skills = skills_dir(global)?;
+    Ok(Footprint {
+        scripts: vec![hook_dir.join(codex_hook_script_name(current_hook_script_platform()))],
+        files: CODEX_SKILLS
+            .iter()
+            .map(|skill| skills.join(skill.dir_name).join("SKILL.md"))
+            .collect(),
+        config: Some(codex_dir.join("hooks.json")),
+        sections: vec![],
+    })
+}
+
+fn skills_dir(global: bool) -> anyhow::Result<PathBuf> {
+    if global {
+        let home = dirs::home_dir().context("could not determine home directory")?;
+        Ok(home.join(".agents/skills"))
+    } else {
+        Ok(PathBuf::from(".agents/skills"))
+    }
+}
+
 fn install_hook_to(
     hook_dir: &Path,
     codex_dir: &Path,
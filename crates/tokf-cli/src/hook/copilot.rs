@@ -1,6 +1,7 @@
 use std::path::{Path, PathBuf};
 
 use super::instructions;
+use super::uninstall::Footprint;
 
 /// Install the GitHub Copilot instructions.
 ///
@@ -22,6 +23,19 @@ pub fn install(global: bool) -> anyhow::Result<()> {
     install_to(&instructions_dir, &copilot_instructions)
 }
 
+/// The files [`install`] writes; none globally, as Copilot has no global
+/// instructions.
+pub fn footprint(global: bool) -> Footprint {
+    if global {
+        return Footprint::default();
+    }
+    Footprint {
+        files: vec![PathBuf::from(".github/instructions/tokf.instructions.md")],
+        sections: vec![PathBuf::from(".github/copilot-instructions.md")],
+        ..Footprint::default()
+    }
+}
+
 /// Core install logic with explicit paths (testable).
 pub(crate) fn install_to(
     instructions_dir: &Path,
@@ -1,9 +1,10 @@
 use std::path::Path;
 
-use crate::runner;
-
+use super::uninstall::Footprint;
 use crate::runtime::Runtime;
 
+const HOOK_SCRIPT: &str = "cursor-pre-tool-use.sh";
+
 /// Install the Cursor `preToolUse` hook.
 ///
 /// # Errors
@@ -27,6 +28,20 @@ pub fn install(
     )
 }
 
+/// The files [`install`] writes.
+///
+/// # Errors
+///
+/// Returns an error if the home or working directory cannot be determined.
+pub fn footprint(rt: &Runtime, global: bool) -> anyhow::Result<Footprint> {
+    let (hook_dir, cursor_dir) = super::resolve_paths(rt, global, ".cursor")?;
+    Ok(Footprint {
+        scripts: vec![hook_dir.join(HOOK_SCRIPT)],
+        config: Some(cursor_dir.join("hooks.json")),
+        ..Footprint::default()
+    })
+}
+
 /// Core install logic with explicit paths (testable).
 pub(crate) fn install_to(
     hook_dir: &Path,
@@ -35,7 +50,7 @@ pub(crate) fn install_to(
     tokf_bin: &str,
     install_context: bool,
 ) -> anyhow::Result<()> {
-    let hook_script = hook_dir.join("cursor-pre-tool-use.sh");
+    let hook_script = hook_dir.join(HOOK_SCRIPT);
     super::write_hook_shim(hook_dir, &hook_script, tokf_bin, "--format cursor")?;
     patch_hooks_json(hooks_json_path, &hook_script)?;
 
@@ -58,62 +73,13 @@ pub(crate) fn install_to(
 /// in a `hooks` array), and the event name is `beforeShellExecution` (not
 /// `preToolUse`).
 fn patch_hooks_json(hooks_json_path: &Path, hook_script: &Path) -> anyhow::Result<()> {
-    let mut config: serde_json::Value = if hooks_json_path.exists() {
-        let content = std::fs::read_to_string(hooks_json_path)?;
-        serde_json::from_str(&content).map_err(|e| {
-            anyhow::anyhow!("corrupt hooks.json at {}: {e}", hooks_json_path.display())
-        })?
-    } else {
-        serde_json::json!({ "version": 1 })
-    };
-
-    let hook_command = runner::shell_escape(
-        hook_script
-            .to_str()
-            .ok_or_else(|| anyhow::anyhow!("hook script path is not valid UTF-8"))?,
-    );
-
-    let tokf_hook_entry = serde_json::json!({
-        "type": "command",
-        "command": hook_command
-    });
-
-    let hooks = config
-        .as_object_mut()
-        .ok_or_else(|| anyhow::anyhow!("hooks.json is not an object"))?
-        .entry("hooks")
-        .or_insert_with(|| serde_json::json!({}));
-
-    let before_shell = hooks
-        .as_object_mut()
-        .ok_or_else(|| anyhow::anyhow!("hooks.json hooks is not an object"))?
-        .entry("beforeShellExecution")
-        .or_insert_with(|| serde_json::json!([]));
-
-    let arr = before_shell
-        .as_array_mut()
-        .ok_or_else(|| anyhow::anyhow!("hooks.beforeShellExecution is not an array"))?;
-
-    // Remove existing tokf entries (idempotent install)
-    arr.retain(|entry| {
-        let is_tokf = entry
-            .get("command")
-            .and_then(serde_json::Value::as_str)
-            .is_some_and(|cmd| cmd.contains("tokf") && cmd.contains("hook"));
-        !is_tokf
-    });
-
-    arr.push(tokf_hook_entry);
-
-    if let Some(parent) = hooks_json_path.parent() {
-        std::fs::create_dir_all(parent)?;
-    }
-    let json = serde_json::to_string_pretty(&config)?;
-    let tmp_path = hooks_json_path.with_extension("json.tmp");
-    std::fs::write(&tmp_path, &json)?;
-    std::fs::rename(&tmp_path, hooks_json_path)?;
-
-    Ok(())
+    super::patch_flat_hooks_json(
+        hooks_json_path,
+        hook_script,
+        "beforeShellExecution",
+        serde_json::json!({ "version": 1 }),
+        |command| serde_json::json!({ "type": "command", "command": command }),
+    )
 }
 
 #[cfg(test)]
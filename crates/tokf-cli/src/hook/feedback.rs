@@ -0,0 +1,234 @@
+//! `tokf hook feedback`: the Claude Code `PostToolUse` hook.
+//!
+//! Runs after each Bash call and records, per filter, when the agent ran the
+//! same command again straight away or asked for a run's raw output. It never
+//! prints to stdout and always lets the agent carry on. Projects with
+//! `tracking = false` record nothing.
+
+use std::io::Read;
+use std::path::Path;
+
+use rusqlite::Connection;
+
+use super::types::PostToolUseInput;
+use crate::history;
+use crate::runtime::Runtime;
+use crate::tracking::{self, FeedbackSignal, HookSession};
+
+/// The history entry a raw-output request names.
+#[derive(Debug, PartialEq, Eq)]
+enum RawTarget {
+    Last,
+    Id(i64),
+}
+
+/// Read a `PostToolUse` payload from stdin and record any feedback signals.
+/// Failures are reported on stderr and otherwise ignored.
+pub fn handle(rt: &Runtime) {
+    let mut input = String::new();
+    if std::io::stdin().read_to_string(&mut input).is_err() {
+        return;
+    }
+    if let Err(e) = handle_json(rt, &input) {
+        eprintln!("[tokf] hook feedback: {e:#}");
+    }
+}
+
+fn handle_json(rt: &Runtime, json: &str) -> anyhow::Result<()> {
+    let Ok(input) = serde_json::from_str::<PostToolUseInput>(json) else {
+        return Ok(());
+    };
+    let Some(command) = input
+        .tool_input
+        .command
+        .filter(|_| input.tool_name == "Bash")
+    else {
+        return Ok(());
+    };
+    if !history::tracking_enabled(Path::new(&history::current_project(rt))) {
+        return Ok(());
+    }
+    let path = rt
+        .tracking_db_path()
+        .ok_or_else(|| anyhow::anyhow!("cannot determine tracking DB path"))?;
+    record(rt, &history::open_db(&path)?, &input.session_id, &command)
+}
+
+/// Record the signals `command` gives and remember it as the session's last.
+fn record(rt: &Runtime, conn: &Connection, session_id: &str, command: &str) -> anyhow::Result<()> {
+    let project = history::current_project(rt);
+
+    if let Some(target) = raw_target(command) {
+        let entry = match target {
+            RawTarget::Last => history::get_latest_entry(conn, Some(&project))?,
+            RawTarget::Id(id) => history::get_history_entry(conn, id)?,
+        };
+        if let Some(filter) = entry.and_then(|e| e.filter_name) {
+            tracking::record_feedback(conn, &filter, FeedbackSignal::Raw, &project)?;
+        }
+    }
+
+    let previous = tracking::get_session(conn, session_id)?;
+    if let Some(HookSession {
+        command: prev_command,
+        filter_name: Some(filter),
+    }) = previous
+        && prev_command == command
+    {
+        tracking::record_feedback(conn, &filter, FeedbackSignal::Rerun, &project)?;
+    }
+
+    let session = HookSession {
+        command: command.to_string(),
+        filter_name: tracking::recent_filter_for(conn, command, &project)?,
+    };
+    tracking::save_session(conn, session_id, &session)
+}
+
+/// The entry `command` asks for raw output of: `tokf raw <id|last>`,
+/// `tokf history show <id>` or `tokf history last`, flags anywhere.
+fn raw_target(command: &str) -> Option<RawTarget> {
+    let words: Vec<&str> = command.split_whitespace().collect();
+    let start = words
+        .iter()
+        .position(|w| Path::new(w).file_name().is_some_and(|name| name == "tokf"))?;
+    let args: Vec<&str> = words[start + 1..]
+        .iter()
+        .copied()
+        .filter(|w| !w.starts_with('-'))
+        .collect();
+    match args.as_slice() {
+        ["raw" | "history", "last", ..] => Some(RawTarget::Last),
+        ["raw", id, ..] | ["history", "show", id, ..] => id.parse().ok().map(RawTarget::Id),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+
+    fn runtime(dir: &Path) -> Runtime {
+        Runtime::builder()
+            .cwd(dir.to_path_buf())
+            .db_path(dir.join("tracking.db"))
+            .build()
+    }
+
+    fn feedback(conn: &Connection) -> Vec<(String, String)> {
+        let mut stmt = conn
+            .prepare("SELECT filter_name, signal FROM filter_feedback ORDER BY id")
+            .unwrap();
+        stmt.query_map([], |r| Ok((r.get(0)?, r.get(1)?)))
+            .unwrap()
+            .map(Result::unwrap)
+            .collect()
+    }
+
+    fn filtered_run(rt: &Runtime, conn: &Connection, command: &str, filter: &str) {
+        let mut event = tracking::build_event(command, Some(filter), None, 10, 1, 10, 0, 0, false);
+        event.project = history::current_project(rt);
+        tracking::record_event(conn, &event).unwrap();
+    }
+
+    #[test]
+    fn raw_target_finds_raw_requests() {
+        assert_eq!(raw_target("tokf raw last"), Some(RawTarget::Last));
+        assert_eq!(
+            raw_target("tokf raw 42 | grep error"),
+            Some(RawTarget::Id(42))
+        );
+        assert_eq!(
+            raw_target("/usr/bin/tokf history show --raw 7"),
+            Some(RawTarget::Id(7))
+        );
+        assert_eq!(raw_target("tokf history last --raw"), Some(RawTarget::Last));
+        assert_eq!(raw_target("tokf history list"), None);
+        assert_eq!(raw_target("tokf run git status"), None);
+        assert_eq!(raw_target("echo raw last"), None);
+    }
+
+    #[test]
+    fn rerun_of_a_filtered_command_is_recorded() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let rt = runtime(dir.path());
+        let conn = history::open_db(&dir.path().join("tracking.db")).unwrap();
+
+        filtered_run(&rt, &conn, "git status", "git/status");
+        record(&rt, &conn, "s", "tokf run git status").unwrap();
+        assert!(feedback(&conn).is_empty());
+
+        filtered_run(&rt, &conn, "git status", "git/status");
+        record(&rt, &conn, "s", "tokf run git status").unwrap();
+        assert_eq!(
+            feedback(&conn),
+            [("git/status".to_string(), "rerun".to_string())]
+        );
+
+        // A different session's repeat is not a rerun of this one's command.
+        record(&rt, &conn, "other", "ls").unwrap();
+        record(&rt, &conn, "s", "ls").unwrap();
+        assert_eq!(feedback(&conn).len(), 1);
+    }
+
+    #[test]
+    fn raw_request_is_recorded_against_the_entry_filter() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let rt = runtime(dir.path());
+        let conn = history::open_db(&dir.path().join("tracking.db")).unwrap();
+        let entry = history::HistoryRecord {
+            project: history::current_project(&rt),
+            command: "cargo test".to_string(),
+            executed_command: None,
+            filter_name: Some("cargo/test".to_string()),
+            raw_output: "raw".to_string(),
+            filtered_output: "ok".to_string(),
+            exit_code: 0,
+        };
+        let id =
+            history::record_history(&conn, &entry, &history::HistoryConfig::default()).unwrap();
+
+        record(&rt, &conn, "s", "tokf raw last").unwrap();
+        record(&rt, &conn, "s", &format!("tokf history show {id}")).unwrap();
+        record(&rt, &conn, "s", "tokf raw 999").unwrap();
+        let raw = ("cargo/test".to_string(), "raw".to_string());
+        assert_eq!(feedback(&conn), [raw.clone(), raw]);
+    }
+
+    #[test]
+    fn unfiltered_rerun_is_not_recorded() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let rt = runtime(dir.path());
+        let conn = history::open_db(&dir.path().join("tracking.db")).unwrap();
+
+        record(&rt, &conn, "s", "ls -la").unwrap();
+        record(&rt, &conn, "s", "ls -la").unwrap();
+        assert!(feedback(&conn).is_empty());
+    }
+
+    #[test]
+    fn non_bash_and_malformed_input_are_ignored() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let rt = runtime(dir.path());
+        handle_json(&rt, "not json").unwrap();
+        handle_json(
+            &rt,
+            r#"{"tool_name":"Read","tool_input":{"file_path":"a.rs"}}"#,
+        )
+        .unwrap();
+        assert!(!dir.path().join("tracking.db").exists());
+    }
+
+    #[test]
+    fn opted_out_projects_record_nothing() {
+        let dir = tempfile::TempDir::new().unwrap();
+        std::fs::create_dir(dir.path().join(".tokf")).unwrap();
+        std::fs::write(dir.path().join(".tokf/config.toml"), "tracking = false\n").unwrap();
+        let rt = runtime(dir.path());
+        let json = r#"{"session_id":"s","tool_name":"Bash","tool_input":{"command":"deploy --token abc"}}"#;
+        handle_json(&rt, json).unwrap();
+        handle_json(&rt, json).unwrap();
+        assert!(!dir.path().join("tracking.db").exists());
+    }
+}
@@ -1,7 +1,10 @@
 use std::path::Path;
 
+use super::uninstall::Footprint;
 use crate::runtime::Runtime;
 
+const HOOK_SCRIPT: &str = "gemini-before-tool.sh";
+
 /// Install the Gemini CLI `BeforeTool` hook.
 ///
 /// # Errors
@@ -24,6 +27,20 @@ pub fn install(
     )
 }
 
+/// The files [`install`] writes.
+///
+/// # Errors
+///
+/// Returns an error if the home or working directory cannot be determined.
+pub fn footprint(rt: &Runtime, global: bool) -> anyhow::Result<Footprint> {
+    let (hook_dir, gemini_dir) = super::resolve_paths(rt, global, ".gemini")?;
+    Ok(Footprint {
+        scripts: vec![hook_dir.join(HOOK_SCRIPT)],
+        config: Some(gemini_dir.join("settings.json")),
+        ..Footprint::default()
+    })
+}
+
 /// Core install logic with explicit paths (testable).
 pub(crate) fn install_to(
     hook_dir: &Path,
@@ -32,7 +49,7 @@ pub(crate) fn install_to(
     tokf_bin: &str,
     install_context: bool,
 ) -> anyhow::Result<()> {
-    let hook_script = hook_dir.join("gemini-before-tool.sh");
+    let hook_script = hook_dir.join(HOOK_SCRIPT);
     super::write_hook_shim(hook_dir, &hook_script, tokf_bin, "--format gemini")?;
     super::patch_json_hook_config(
         settings_path,
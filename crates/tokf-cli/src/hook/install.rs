@@ -6,6 +6,10 @@ use crate::runner;
 
 use crate::runtime::Runtime;
 
+const PRE_TOOL_USE_SCRIPT: &str = "pre-tool-use.sh";
+const POST_TOOL_USE_SCRIPT: &str = "post-tool-use.sh";
+const SESSION_START_SCRIPT: &str = "session-start.sh";
+
 /// Install the hook shim and register it in Claude Code settings.
 ///
 /// # Errors
@@ -17,21 +21,30 @@ pub(super) fn install(
     tokf_bin: &str,
     install_context: bool,
 ) -> anyhow::Result<()> {
-    let (hook_dir, settings_path) = if global {
+    let (hook_dir, settings_path) = claude_code_paths(rt, global)?;
+    install_to(&hook_dir, &settings_path, tokf_bin, install_context)
+}
+
+/// The Claude Code hook dir and `settings.json` path.
+pub(super) fn claude_code_paths(rt: &Runtime, global: bool) -> anyhow::Result<(PathBuf, PathBuf)> {
+    if global {
         let user = rt.require_user_dir()?;
-        let hook_dir = user.join("hooks");
         let home = dirs::home_dir()
             .ok_or_else(|| anyhow::anyhow!("could not determine home directory"))?;
-        let settings_path = home.join(".claude/settings.json");
-        (hook_dir, settings_path)
+        Ok((user.join("hooks"), home.join(".claude/settings.json")))
     } else {
         let cwd = rt.cwd().context("could not determine working directory")?;
-        let hook_dir = cwd.join(".tokf/hooks");
-        let settings_path = cwd.join(".claude/settings.json");
-        (hook_dir, settings_path)
-    };
+        Ok((cwd.join(".tokf/hooks"), cwd.join(".claude/settings.json")))
+    }
+}
 
-    install_to(&hook_dir, &settings_path, tokf_bin, install_context)
+/// The hook scripts [`install_to`] writes into `hook_dir`.
+pub(super) fn claude_code_scripts(hook_dir: &Path) -> Vec<PathBuf> {
+    vec![
+        hook_dir.join(PRE_TOOL_USE_SCRIPT),
+        hook_dir.join(POST_TOOL_USE_SCRIPT),
+        hook_dir.join(SESSION_START_SCRIPT),
+    ]
 }
 
 /// Core install logic with explicit paths (testable).
@@ -41,12 +54,20 @@ pub(super) fn install_to(
     tokf_bin: &str,
     install_context: bool,
 ) -> anyhow::Result<()> {
-    let hook_script = hook_dir.join("pre-tool-use.sh");
+    let hook_script = hook_dir.join(PRE_TOOL_USE_SCRIPT);
     write_hook_shim(hook_dir, &hook_script, tokf_bin, "")?;
     patch_json_hook_config(settings_path, &hook_script, "PreToolUse", "Bash", None)?;
+    let feedback_script = hook_dir.join(POST_TOOL_USE_SCRIPT);
+    write_exec_shim(hook_dir, &feedback_script, tokf_bin, "hook feedback")?;
+    patch_json_hook_config(settings_path, &feedback_script, "PostToolUse", "Bash", None)?;
+    let session_script = hook_dir.join(SESSION_START_SCRIPT);
+    write_exec_shim(hook_dir, &session_script, tokf_bin, "hook session-start")?;
+    patch_json_hook_config(settings_path, &session_script, "SessionStart", "", None)?;
 
     eprintln!("[tokf] hook installed");
     eprintln!("[tokf]   script: {}", hook_script.display());
+    eprintln!("[tokf]   feedback: {}", feedback_script.display());
+    eprintln!("[tokf]   session start: {}", session_script.display());
     eprintln!("[tokf]   settings: {}", settings_path.display());
 
     if install_context && let Some(claude_dir) = settings_path.parent() {
@@ -156,24 +177,39 @@ pub(super) fn write_hook_shim_with_global_args(
     global_args: &str,
     extra_args: &str,
 ) -> anyhow::Result<()> {
-    std::fs::create_dir_all(hook_dir)?;
-
-    let escaped_bin = if tokf_bin == "tokf" {
-        tokf_bin.to_string()
-    } else {
-        runner::shell_escape(tokf_bin)
-    };
-    let global_suffix = if global_args.is_empty() {
+    let global_prefix = if global_args.is_empty() {
         String::new()
     } else {
-        format!(" {}", global_args.trim())
+        format!("{} ", global_args.trim())
     };
     let suffix = if extra_args.is_empty() {
         String::new()
     } else {
         format!(" {}", extra_args.trim())
     };
-    let content = format!("#!/bin/sh\nexec {escaped_bin}{global_suffix} hook handle{suffix}\n");
+    write_exec_shim(
+        hook_dir,
+        hook_script,
+        tokf_bin,
+        &format!("{global_prefix}hook handle{suffix}"),
+    )
+}
+
+/// Write an executable shim script that runs `tokf <args>`.
+pub(super) fn write_exec_shim(
+    hook_dir: &Path,
+    hook_script: &Path,
+    tokf_bin: &str,
+    args: &str,
+) -> anyhow::Result<()> {
+    std::fs::create_dir_all(hook_dir)?;
+
+    let escaped_bin = if tokf_bin == "tokf" {
+        tokf_bin.to_string()
+    } else {
+        runner::shell_escape(tokf_bin)
+    };
+    let content = format!("#!/bin/sh\nexec {escaped_bin} {args}\n");
     std::fs::write(hook_script, content)?;
 
     #[cfg(unix)]
@@ -189,7 +225,8 @@ pub(super) fn write_hook_shim_with_global_args(
 /// Patch a JSON settings/config file to register a tokf hook entry.
 ///
 /// Works for both Claude Code `settings.json` and Gemini `settings.json`.
-/// For Cursor, which uses a different structure, see `cursor::patch_hooks_json`.
+/// For Cursor and Windsurf, which use a different structure, see
+/// [`patch_flat_hooks_json`].
 ///
 /// - `hook_event_key`: e.g. `"PreToolUse"` or `"BeforeTool"`
 /// - `matcher`: e.g. `"Bash"` or `"run_shell_command"`
@@ -252,19 +289,7 @@ pub(super) fn patch_json_hook_config_with_command(
         .as_array_mut()
         .ok_or_else(|| anyhow::anyhow!("hooks.{hook_event_key} is not an array"))?;
 
-    arr.retain(|entry| {
-        let is_tokf = entry
-            .get("hooks")
-            .and_then(|h| h.as_array())
-            .is_some_and(|hooks| {
-                hooks.iter().any(|h| {
-                    h.get("command")
-                        .and_then(serde_json::Value::as_str)
-                        .is_some_and(|cmd| cmd.contains("tokf") && cmd.contains("hook"))
-                })
-            });
-        !is_tokf
-    });
+    arr.retain(|entry| !is_tokf_hook_entry(entry));
 
     arr.push(tokf_hook_entry);
 
@@ -279,6 +304,80 @@ pub(super) fn patch_json_hook_config_with_command(
     Ok(())
 }
 
+/// Whether a hook config entry runs a tokf hook, either directly (`command`,
+/// as in Cursor and Windsurf) or through a nested `hooks` array (Claude Code,
+/// Gemini, Codex).
+pub(super) fn is_tokf_hook_entry(entry: &serde_json::Value) -> bool {
+    let is_tokf_command = |v: &serde_json::Value| {
+        v.get("command")
+            .and_then(serde_json::Value::as_str)
+            .is_some_and(|cmd| cmd.contains("tokf") && cmd.contains("hook"))
+    };
+    is_tokf_command(entry)
+        || entry
+            .get("hooks")
+            .and_then(serde_json::Value::as_array)
+            .is_some_and(|hooks| hooks.iter().any(is_tokf_command))
+}
+
+/// Patch a `hooks.json` whose `hooks.<event>` arrays hold flat entries with a
+/// top-level `command` (Cursor, Windsurf), unlike the nested `matcher`/`hooks`
+/// entries of [`patch_json_hook_config`]. `make_entry` builds the tokf entry
+/// from the shell-escaped hook script path.
+pub(super) fn patch_flat_hooks_json(
+    hooks_json_path: &Path,
+    hook_script: &Path,
+    hook_event_key: &str,
+    initial_value: serde_json::Value,
+    make_entry: impl FnOnce(String) -> serde_json::Value,
+) -> anyhow::Result<()> {
+    let mut config: serde_json::Value = if hooks_json_path.exists() {
+        let content = std::fs::read_to_string(hooks_json_path)?;
+        serde_json::from_str(&content).map_err(|e| {
+            anyhow::anyhow!("corrupt hooks.json at {}: {e}", hooks_json_path.display())
+        })?
+    } else {
+        initial_value
+    };
+
+    let hook_command = runner::shell_escape(
+        hook_script
+            .to_str()
+            .ok_or_else(|| anyhow::anyhow!("hook script path is not valid UTF-8"))?,
+    );
+
+    let hooks = config
+        .as_object_mut()
+        .ok_or_else(|| anyhow::anyhow!("hooks.json is not an object"))?
+        .entry("hooks")
+        .or_insert_with(|| serde_json::json!({}));
+
+    let event_hooks = hooks
+        .as_object_mut()
+        .ok_or_else(|| anyhow::anyhow!("hooks.json hooks is not an object"))?
+        .entry(hook_event_key)
+        .or_insert_with(|| serde_json::json!([]));
+
+    let arr = event_hooks
+        .as_array_mut()
+        .ok_or_else(|| anyhow::anyhow!("hooks.{hook_event_key} is not an array"))?;
+
+    // Remove existing tokf entries (idempotent install)
+    arr.retain(|entry| !is_tokf_hook_entry(entry));
+
+    arr.push(make_entry(hook_command));
+
+    if let Some(parent) = hooks_json_path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let json = serde_json::to_string_pretty(&config)?;
+    let tmp_path = hooks_json_path.with_extension("json.tmp");
+    std::fs::write(&tmp_path, &json)?;
+    std::fs::rename(&tmp_path, hooks_json_path)?;
+
+    Ok(())
+}
+
 /// Append or replace a tokf section in a markdown file, idempotent via markers.
 pub(super) fn append_or_replace_section(
     path: &Path,
@@ -319,6 +418,39 @@ pub(super) fn append_or_replace_section(
     Ok(())
 }
 
+/// Remove the tokf section [`append_or_replace_section`] added, deleting the
+/// file when nothing else is left. Returns whether a section was removed.
+pub(super) fn remove_section(path: &Path) -> anyhow::Result<bool> {
+    let start_marker = "<!-- tokf:start -->";
+    let end_marker = "<!-- tokf:end -->";
+
+    let existing = match std::fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(false),
+        Err(e) => return Err(e.into()),
+    };
+    let (Some(s), Some(e)) = (existing.find(start_marker), existing.find(end_marker)) else {
+        return Ok(false);
+    };
+    if s > e {
+        return Ok(false);
+    }
+
+    let before = existing[..s].trim_end();
+    let after = existing[e + end_marker.len()..].trim_start_matches('\n');
+    if before.is_empty() && after.trim().is_empty() {
+        std::fs::remove_file(path)?;
+        return Ok(true);
+    }
+    let separator = match (before.is_empty(), after.is_empty()) {
+        (true, _) => "",
+        (false, true) => "\n",
+        (false, false) => "\n\n",
+    };
+    std::fs::write(path, format!("{before}{separator}{after}"))?;
+    Ok(true)
+}
+
 /// Write an instruction/convention file (creates parent dirs, overwrites).
 pub(super) fn write_instruction_file(path: &Path, content: &str) -> anyhow::Result<()> {
     if let Some(parent) = path.parent() {
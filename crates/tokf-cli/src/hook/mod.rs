@@ -1,16 +1,21 @@
+pub mod adapter;
 pub mod aider;
 pub mod cline;
 pub mod codex;
 pub mod copilot;
 pub mod cursor;
 mod debug_log;
+pub mod feedback;
 pub mod gemini;
 mod install;
 pub mod instructions;
 pub mod opencode;
 pub mod permission_engine;
 pub mod permissions;
+pub mod session_start;
+mod tool_policy;
 pub mod types;
+pub mod uninstall;
 pub mod windsurf;
 
 use std::io::Read;
@@ -19,9 +24,9 @@ use std::path::PathBuf;
 #[cfg(test)]
 use install::install_to;
 use install::{
-    append_or_replace_section, patch_json_hook_config, patch_json_hook_config_with_command,
-    patch_md_with_reference, resolve_paths, write_context_doc, write_hook_shim,
-    write_instruction_file,
+    append_or_replace_section, patch_flat_hooks_json, patch_json_hook_config,
+    patch_json_hook_config_with_command, patch_md_with_reference, resolve_paths, write_context_doc,
+    write_hook_shim, write_instruction_file,
 };
 use permission_engine::ErrorFallback;
 use permissions::PermissionVerdict;
@@ -524,6 +529,12 @@ fn process_command<R: serde::Serialize>(
     allow_outcome: HookOutcome,
     ask_outcome: HookOutcome,
 ) -> HookOutcome {
+    // Adapters share one format, so their policy is keyed by the adapter name.
+    let policy_key = if format == HookFormat::Adapter {
+        tool_name
+    } else {
+        format.as_str()
+    };
     let (outcome, after) = decide(
         rt,
         command,
@@ -533,6 +544,7 @@ fn process_command<R: serde::Serialize>(
         search_dirs,
         no_cache,
         no_mask_exit_code,
+        tool_policy::rewrite_allowed(user_config, policy_key, command),
         build_allow,
         build_ask,
         build_deny,
@@ -565,6 +577,7 @@ fn decide<R: serde::Serialize>(
     search_dirs: &[PathBuf],
     no_cache: bool,
     no_mask_exit_code: bool,
+    rewrite_allowed: bool,
     build_allow: impl FnOnce(String, Option<String>) -> R,
     build_ask: impl FnOnce(String, Option<String>) -> R,
     build_deny: impl FnOnce(String, Option<String>) -> R,
@@ -575,19 +588,24 @@ fn decide<R: serde::Serialize>(
     // `tokf run` invocation (including each member of a compound command) —
     // otherwise the flag is silently dropped and exit codes stay masked (#414).
     let options = rewrite::types::RewriteOptions { no_mask_exit_code };
-    // `verbose` is `false`: hook rewrites must not emit diagnostics to the
-    // agent's stderr, and `no_cache` rides in `RewriteCtx`, not the old
-    // positional bool that used to be mistaken for it (#431).
+    // Hook rewrites never emit diagnostics to the agent's stderr, and
+    // `no_cache` rides in `RewriteCtx`, not the old positional bool that used
+    // to be mistaken for it (#431).
+    // A command the tool's `[hook.<tool>]` policy excludes is never rewritten.
     let rewrite_cmd = |cmd: &str| {
-        rewrite::rewrite_with_config_and_options(
+        if !rewrite_allowed {
+            return cmd.to_string();
+        }
+        // Served by `tokf daemon` when one is running.
+        crate::daemon::rewrite(
             rewrite::RewriteCtx {
                 rt,
                 user_config,
                 search_dirs,
                 no_cache,
+                pattern_cache: None,
             },
             cmd,
-            false,
             &options,
         )
     };
@@ -596,7 +614,7 @@ fn decide<R: serde::Serialize>(
     if let Some(verdict) = query_external_engine(rt, command, json, format, user_config) {
         // Deny doesn't need a rewrite — the command won't execute.
         if verdict.decision == PermissionDecision::Deny {
-            if emit_response(&build_deny(command.to_string(), verdict.reason)) {
+            if emit_response(format, &build_deny(command.to_string(), verdict.reason)) {
                 return (HookOutcome::Deny, None);
             }
             return (HookOutcome::PassThrough, None);
@@ -609,18 +627,21 @@ fn decide<R: serde::Serialize>(
             command.to_string()
         };
         let logged_after = rewrite_changed.then(|| output_cmd.clone());
+        // Codex and Windsurf answer allow/ask with a block: with nothing to
+        // rewrite, an allow passes through and an ask is a plain deny.
+        let blocks = matches!(format, HookFormat::Codex | HookFormat::Windsurf);
         let (response, outcome) = match verdict.decision {
-            PermissionDecision::Ask if format == HookFormat::Codex && !rewrite_changed => (
+            PermissionDecision::Ask if blocks && !rewrite_changed => (
                 build_deny(command.to_string(), verdict.reason),
                 HookOutcome::Deny,
             ),
             PermissionDecision::Ask => (build_ask(output_cmd, verdict.reason), ask_outcome),
-            _ if format == HookFormat::Codex && !rewrite_changed => {
+            _ if blocks && !rewrite_changed => {
                 return (HookOutcome::PassThrough, None);
             }
             _ => (build_allow(output_cmd, verdict.reason), allow_outcome),
         };
-        if emit_response(&response) {
+        if emit_response(format, &response) {
             return (outcome, logged_after);
         }
         return (HookOutcome::PassThrough, logged_after);
@@ -633,7 +654,7 @@ fn decide<R: serde::Serialize>(
     }
 
     let logged_after = Some(rewritten.clone());
-    if emit_response(&build_allow(rewritten, None)) {
+    if emit_response(format, &build_allow(rewritten, None)) {
         (allow_outcome, logged_after)
     } else {
         (HookOutcome::PassThrough, logged_after)
@@ -641,9 +662,16 @@ fn decide<R: serde::Serialize>(
 }
 
 /// Serialize and print a hook response. Returns true on success.
-fn emit_response<R: serde::Serialize>(response: &R) -> bool {
+///
+/// Windsurf ignores a hook's stdout and shows Cascade the stderr of a
+/// blocked command, so its responses go to stderr.
+fn emit_response<R: serde::Serialize>(format: HookFormat, response: &R) -> bool {
     if let Ok(json) = serde_json::to_string(response) {
-        println!("{json}");
+        if format == HookFormat::Windsurf {
+            eprintln!("{json}");
+        } else {
+            println!("{json}");
+        }
         return true;
     }
     false
@@ -2,6 +2,8 @@ use std::path::{Path, PathBuf};
 
 use anyhow::Context;
 
+use super::uninstall::Footprint;
+
 // R1: Default export is a factory function returning the Hooks object, matching
 //     the @opencode-ai/plugin Plugin type: (input) => Promise<Hooks>. The old
 //     object-literal default export is rejected by current opencode with
@@ -60,6 +62,23 @@ pub fn install(global: bool, tokf_bin: &str) -> anyhow::Result<()> {
     install_to(&plugin_dir, tokf_bin)
 }
 
+/// The plugin file [`install`] writes.
+///
+/// # Errors
+///
+/// Returns an error if the home directory cannot be determined.
+pub fn footprint(global: bool) -> anyhow::Result<Footprint> {
+    let plugin_dir = if global {
+        global_plugin_dir()?
+    } else {
+        PathBuf::from(".opencode/plugins")
+    };
+    Ok(Footprint {
+        files: vec![plugin_dir.join("tokf.ts")],
+        ..Footprint::default()
+    })
+}
+
 pub(crate) fn install_to(plugin_dir: &Path, tokf_bin: &str) -> anyhow::Result<()> {
     write_plugin_file(plugin_dir, tokf_bin)?;
     // R5: Standardize eprintln prefix to [tokf]
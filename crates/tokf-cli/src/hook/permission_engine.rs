@@ -138,19 +138,18 @@ fn parse_engine_output(
 ///
 /// Returns `None` if the response doesn't contain a recognisable verdict.
 /// Also extracts the reason string (per-format field names):
-/// - Claude: `hookSpecificOutput.permissionDecisionReason`
-/// - Codex: `hookSpecificOutput.permissionDecisionReason`
-/// - Gemini: `reason`
+/// - Claude, Codex, adapters: `hookSpecificOutput.permissionDecisionReason`
+/// - Gemini, Windsurf: `reason`
 /// - Cursor: `userMessage`
 pub fn extract_verdict(json: &Value, format: HookFormat) -> Option<PermissionVerdict> {
     let (decision_str, reason_str) = match format {
-        HookFormat::ClaudeCode | HookFormat::Codex => {
+        HookFormat::ClaudeCode | HookFormat::Codex | HookFormat::Adapter => {
             let hso = json.get("hookSpecificOutput")?;
             let decision = hso.get("permissionDecision").and_then(Value::as_str);
             let reason = hso.get("permissionDecisionReason").and_then(Value::as_str);
             (decision, reason)
         }
-        HookFormat::Gemini => {
+        HookFormat::Gemini | HookFormat::Windsurf => {
             let decision = json.get("decision").and_then(Value::as_str);
             let reason = json.get("reason").and_then(Value::as_str);
             (decision, reason)
@@ -0,0 +1,124 @@
+//! `tokf hook session-start`: the Claude Code `SessionStart` hook.
+//!
+//! Tells the agent, as session context, how many tokens tokf has saved in
+//! this repo so far. Prints nothing when there is nothing to report.
+
+use std::io::Read;
+
+use crate::history;
+use crate::runtime::Runtime;
+use crate::tracking;
+
+/// Drain the `SessionStart` payload from stdin and print the savings summary
+/// as hook output. Failures are reported on stderr and otherwise ignored.
+pub fn handle(rt: &Runtime) {
+    let mut input = String::new();
+    let _ = std::io::stdin().read_to_string(&mut input);
+    match response(rt) {
+        Ok(Some(json)) => println!("{json}"),
+        Ok(None) => {}
+        Err(e) => eprintln!("[tokf] hook session-start: {e:#}"),
+    }
+}
+
+/// The hook output for the current project, or `None` when tokf has saved
+/// nothing here yet.
+fn response(rt: &Runtime) -> anyhow::Result<Option<serde_json::Value>> {
+    let Some(path) = rt.tracking_db_path().filter(|p| p.exists()) else {
+        return Ok(None);
+    };
+    let conn = tracking::open_db(&path)?;
+    let (commands, saved) = tracking::query_project_savings(&conn, &history::current_project(rt))?;
+    if commands == 0 || saved <= 0 {
+        return Ok(None);
+    }
+    Ok(Some(serde_json::json!({
+        "hookSpecificOutput": {
+            "hookEventName": "SessionStart",
+            "additionalContext": summary(commands, saved),
+        }
+    })))
+}
+
+fn summary(commands: i64, saved: i64) -> String {
+    let noun = if commands == 1 { "command" } else { "commands" };
+    format!(
+        "tokf saved {} tokens across {} {noun} in this repo.",
+        with_separators(saved),
+        with_separators(commands)
+    )
+}
+
+/// `1234567` as `1,234,567`.
+fn with_separators(n: i64) -> String {
+    let digits = n.unsigned_abs().to_string();
+    let mut out = String::with_capacity(digits.len() + digits.len() / 3);
+    for (i, c) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            out.push(',');
+        }
+        out.push(c);
+    }
+    if n < 0 { format!("-{out}") } else { out }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+
+    fn runtime(dir: &std::path::Path) -> Runtime {
+        Runtime::builder()
+            .cwd(dir.to_path_buf())
+            .db_path(dir.join("tracking.db"))
+            .build()
+    }
+
+    fn record(rt: &Runtime, project: &str) {
+        let conn = tracking::open_db(&rt.tracking_db_path().unwrap()).unwrap();
+        let mut event =
+            tracking::build_event("git status", None, None, 4000, 400, 4000, 1, 0, false);
+        event.project = project.to_string();
+        tracking::record_event(&conn, &event).unwrap();
+    }
+
+    #[test]
+    fn summary_formats_counts() {
+        assert_eq!(
+            summary(1, 1_234_567),
+            "tokf saved 1,234,567 tokens across 1 command in this repo."
+        );
+        assert_eq!(
+            summary(12, 900),
+            "tokf saved 900 tokens across 12 commands in this repo."
+        );
+    }
+
+    #[test]
+    fn no_database_means_no_output() {
+        let dir = tempfile::TempDir::new().unwrap();
+        assert!(response(&runtime(dir.path())).unwrap().is_none());
+        assert!(!dir.path().join("tracking.db").exists());
+    }
+
+    #[test]
+    fn reports_only_this_projects_savings() {
+        let dir = tempfile::TempDir::new().unwrap();
+        std::fs::create_dir(dir.path().join(".git")).unwrap();
+        let rt = runtime(dir.path());
+        record(&rt, "/elsewhere");
+        assert!(response(&rt).unwrap().is_none());
+
+        record(&rt, &history::current_project(&rt));
+        record(&rt, &history::current_project(&rt));
+        let value = response(&rt).unwrap().unwrap();
+        assert_eq!(value["hookSpecificOutput"]["hookEventName"], "SessionStart");
+        let context = value["hookSpecificOutput"]["additionalContext"]
+            .as_str()
+            .unwrap();
+        assert!(
+            context.ends_with("across 2 commands in this repo."),
+            "{context}"
+        );
+    }
+}
@@ -354,9 +354,19 @@ fn install_to_creates_files() {
     assert!(hook_script.exists(), "hook script should exist");
     assert!(settings_path.exists(), "settings.json should exist");
 
+    let feedback = std::fs::read_to_string(hook_dir.join("post-tool-use.sh")).unwrap();
+    assert!(feedback.contains("exec tokf hook feedback"), "{feedback}");
+    let session = std::fs::read_to_string(hook_dir.join("session-start.sh")).unwrap();
+    assert!(
+        session.contains("exec tokf hook session-start"),
+        "{session}"
+    );
+
     let settings_content = std::fs::read_to_string(&settings_path).unwrap();
     let value: serde_json::Value = serde_json::from_str(&settings_content).unwrap();
     assert!(value["hooks"]["PreToolUse"].is_array());
+    assert_eq!(value["hooks"]["PostToolUse"][0]["matcher"], "Bash");
+    assert!(value["hooks"]["SessionStart"].is_array());
 }
 
 #[test]
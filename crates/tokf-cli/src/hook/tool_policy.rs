@@ -0,0 +1,107 @@
+//! Per-tool rewrite allowlist/denylist: `[hook.<tool>]` in `rewrites.toml`.
+
+use crate::config::pattern::glob_matches;
+use crate::rewrite::bash_ast::{split_compound, strip_env_prefix};
+use crate::rewrite::types::{HookToolConfig, RewriteConfig};
+
+/// Whether the hook for `tool` may rewrite `command`.
+///
+/// Without a `[hook.<tool>]` table every command may be rewritten. See
+/// [`HookToolConfig`] for how `only` and `never` apply.
+pub(super) fn rewrite_allowed(user_config: &RewriteConfig, tool: &str, command: &str) -> bool {
+    let Some(policy) = user_config.hook.get(tool) else {
+        return true;
+    };
+    split_compound(command)
+        .iter()
+        .map(|(segment, _)| segment.trim())
+        .filter(|segment| !segment.is_empty())
+        .all(|segment| segment_allowed(policy, segment))
+}
+
+fn segment_allowed(policy: &HookToolConfig, segment: &str) -> bool {
+    let stripped = strip_env_prefix(segment).map(|(_, cmd)| cmd);
+    let forms: Vec<&str> = std::iter::once(segment)
+        .chain(stripped.as_deref())
+        .collect();
+    let matches_any = |globs: &[String]| {
+        globs
+            .iter()
+            .any(|glob| forms.iter().any(|form| glob_matches(glob, form)))
+    };
+    !matches_any(&policy.never) && (policy.only.is_empty() || matches_any(&policy.only))
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+    use crate::hook::{HookOutcome, handle_json_with_rules};
+
+    fn config(toml: &str) -> RewriteConfig {
+        toml::from_str(toml).unwrap()
+    }
+
+    #[test]
+    fn no_table_allows_everything() {
+        let cfg = config("[hook.cursor]\nnever = [\"*\"]\n");
+        assert!(rewrite_allowed(&cfg, "claude-code", "rm -rf build"));
+    }
+
+    #[test]
+    fn never_blocks_matching_commands_and_compounds() {
+        let cfg = config("[hook.claude-code]\nnever = [\"rm *\", \"git push*\"]\n");
+        assert!(!rewrite_allowed(&cfg, "claude-code", "rm -rf build"));
+        assert!(!rewrite_allowed(
+            &cfg,
+            "claude-code",
+            "cargo build && rm -rf x"
+        ));
+        assert!(!rewrite_allowed(
+            &cfg,
+            "claude-code",
+            "FORCE=1 git push --force"
+        ));
+        assert!(rewrite_allowed(&cfg, "claude-code", "git status"));
+    }
+
+    #[test]
+    fn only_requires_every_part_to_match() {
+        let cfg = config("[hook.claude-code]\nonly = [\"git *\", \"cargo *\"]\n");
+        assert!(rewrite_allowed(&cfg, "claude-code", "git status"));
+        assert!(rewrite_allowed(&cfg, "claude-code", "cargo test; git diff"));
+        assert!(!rewrite_allowed(
+            &cfg,
+            "claude-code",
+            "cargo test && npm test"
+        ));
+        assert!(!rewrite_allowed(&cfg, "claude-code", "ls -la"));
+    }
+
+    #[test]
+    fn never_wins_over_only() {
+        let cfg = config("[hook.gemini]\nonly = [\"git *\"]\nnever = [\"git clean *\"]\n");
+        assert!(rewrite_allowed(&cfg, "gemini", "git log"));
+        assert!(!rewrite_allowed(&cfg, "gemini", "git clean -fdx"));
+    }
+
+    #[test]
+    fn hook_leaves_excluded_commands_alone() {
+        let dir = tempfile::TempDir::new().unwrap();
+        std::fs::write(dir.path().join("git-clean.toml"), "command = \"git clean\"").unwrap();
+        std::fs::write(dir.path().join("git-log.toml"), "command = \"git log\"").unwrap();
+        let cfg = config("[hook.claude-code]\nnever = [\"git clean*\"]\n");
+        let dirs = [dir.path().to_path_buf()];
+        let input =
+            |cmd: &str| format!(r#"{{"tool_name":"Bash","tool_input":{{"command":"{cmd}"}}}}"#);
+
+        assert_eq!(
+            handle_json_with_rules(&input("git clean -fdx"), &cfg, &dirs),
+            HookOutcome::PassThrough
+        );
+        assert_eq!(
+            handle_json_with_rules(&input("git log"), &cfg, &dirs),
+            HookOutcome::Allow
+        );
+    }
+}
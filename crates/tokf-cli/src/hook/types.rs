@@ -9,6 +9,15 @@ pub struct HookInput {
     pub tool_input: ToolInput,
 }
 
+/// Claude Code `PostToolUse` hook input (read from stdin).
+#[derive(Debug, Clone, Deserialize)]
+pub struct PostToolUseInput {
+    #[serde(default)]
+    pub session_id: String,
+    pub tool_name: String,
+    pub tool_input: ToolInput,
+}
+
 /// The `tool_input` payload from the hook.
 #[derive(Debug, Clone, Deserialize)]
 pub struct ToolInput {
@@ -191,6 +200,57 @@ impl CursorHookResponse {
     }
 }
 
+// --- Windsurf types ---
+// Windsurf's `pre_run_command` hook nests the command under `tool_info`. The
+// hook cannot rewrite the command, only block it (exit 2, message on stderr).
+
+/// Windsurf Cascade `pre_run_command` hook input (read from stdin).
+#[derive(Debug, Clone, Deserialize)]
+pub struct WindsurfInput {
+    pub tool_info: Option<WindsurfToolInfo>,
+}
+
+/// The command Cascade is about to run.
+#[derive(Debug, Clone, Deserialize)]
+pub struct WindsurfToolInfo {
+    pub command_line: Option<String>,
+}
+
+/// Response shown to Cascade when tokf blocks a command.
+#[derive(Debug, Clone, Serialize)]
+pub struct WindsurfHookResponse {
+    pub decision: &'static str,
+    pub reason: String,
+}
+
+#[allow(clippy::needless_pass_by_value)]
+impl WindsurfHookResponse {
+    /// Windsurf can't rewrite commands: block and ask Cascade to rerun the
+    /// rewritten one, which tokf then lets through.
+    pub fn rewrite(command: String, _reason: Option<String>) -> Self {
+        Self::deny_with_reason(format!("Run with tokf: {command}"))
+    }
+
+    /// Windsurf has no ask decision, so ask maps to deny.
+    pub fn rewrite_ask(command: String, reason: Option<String>) -> Self {
+        Self::deny_with_reason(
+            reason.unwrap_or_else(|| format!("Requires confirmation before running: {command}")),
+        )
+    }
+
+    /// Create a Windsurf deny response with an optional reason.
+    pub fn deny(command: String, reason: Option<String>) -> Self {
+        Self::deny_with_reason(reason.unwrap_or_else(|| format!("Blocked by tokf: {command}")))
+    }
+
+    const fn deny_with_reason(reason: String) -> Self {
+        Self {
+            decision: "deny",
+            reason,
+        }
+    }
+}
+
 /// Response to send back to Codex `PreToolUse`.
 #[derive(Debug, Clone, Serialize)]
 pub struct CodexHookResponse {
@@ -533,4 +593,33 @@ mod tests {
         assert_eq!(value["userMessage"], "dangerous command");
         assert_eq!(value["agentMessage"], "dangerous command");
     }
+
+    // --- Windsurf types ---
+
+    #[test]
+    fn deserialize_windsurf_pre_run_command() {
+        let json = r#"{"agent_action_name":"pre_run_command","trajectory_id":"t1","execution_id":"e1","tool_info":{"command_line":"git status","cwd":"/tmp"}}"#;
+        let input: WindsurfInput = serde_json::from_str(json).unwrap();
+        assert_eq!(
+            input.tool_info.unwrap().command_line.as_deref(),
+            Some("git status")
+        );
+    }
+
+    #[test]
+    fn serialize_windsurf_rewrite_blocks_with_rerun_hint() {
+        let response = WindsurfHookResponse::rewrite("tokf run git status".to_string(), None);
+        let value = serde_json::to_value(&response).unwrap();
+        assert_eq!(value["decision"], "deny");
+        assert_eq!(value["reason"], "Run with tokf: tokf run git status");
+    }
+
+    #[test]
+    fn serialize_windsurf_deny_keeps_reason() {
+        let response =
+            WindsurfHookResponse::deny("rm -rf /".to_string(), Some("dangerous".to_string()));
+        let value = serde_json::to_value(&response).unwrap();
+        assert_eq!(value["decision"], "deny");
+        assert_eq!(value["reason"], "dangerous");
+    }
 }
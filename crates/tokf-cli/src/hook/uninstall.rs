@@ -0,0 +1,346 @@
+//! `tokf hook uninstall` and `tokf hook status`: the files an install leaves
+//! behind, and undoing them.
+
+use std::path::{Path, PathBuf};
+
+use super::install::{claude_code_paths, claude_code_scripts, is_tokf_hook_entry, remove_section};
+use crate::runtime::Runtime;
+
+/// The files `tokf hook install` writes for one tool.
+#[derive(Debug, Default)]
+pub struct Footprint {
+    /// Executable hook shims.
+    pub scripts: Vec<PathBuf>,
+    /// Other files tokf owns outright: plugins, rules files, skills.
+    pub files: Vec<PathBuf>,
+    /// JSON hook config (`settings.json`, `hooks.json`) registering the shims.
+    pub config: Option<PathBuf>,
+    /// Shared markdown files holding a `<!-- tokf:start -->` section.
+    pub sections: Vec<PathBuf>,
+}
+
+/// State of one file of a [`Footprint`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileState {
+    Missing,
+    /// A script without the executable bit, which the tool cannot run.
+    NotExecutable,
+    /// The file is there; for the config and section files, with tokf's part in it.
+    Present,
+    /// The config or section file exists but holds nothing of tokf's.
+    NoTokfEntry,
+}
+
+impl FileState {
+    pub const fn label(self) -> &'static str {
+        match self {
+            Self::Missing => "missing",
+            Self::NotExecutable => "not executable",
+            Self::Present => "ok",
+            Self::NoTokfEntry => "no tokf entry",
+        }
+    }
+}
+
+impl Footprint {
+    /// Every file of the footprint with its current state, labelled by kind.
+    pub fn status(&self) -> Vec<(&'static str, &Path, FileState)> {
+        let mut rows = Vec::new();
+        for script in &self.scripts {
+            rows.push(("script", script.as_path(), script_state(script)));
+        }
+        for file in &self.files {
+            let state = if file.exists() {
+                FileState::Present
+            } else {
+                FileState::Missing
+            };
+            rows.push(("file", file.as_path(), state));
+        }
+        if let Some(config) = &self.config {
+            let state = marked_state(config, has_tokf_hook(config));
+            rows.push(("settings", config.as_path(), state));
+        }
+        for section in &self.sections {
+            let has_section =
+                std::fs::read_to_string(section).is_ok_and(|c| c.contains("<!-- tokf:start -->"));
+            rows.push((
+                "section",
+                section.as_path(),
+                marked_state(section, has_section),
+            ));
+        }
+        rows
+    }
+
+    /// Whether anything of the footprint is on disk.
+    pub fn is_installed(&self) -> bool {
+        self.status()
+            .iter()
+            .any(|(_, _, state)| matches!(state, FileState::Present | FileState::NotExecutable))
+    }
+
+    /// Delete the scripts and files, strip tokf entries from the config and
+    /// tokf sections from shared files. Returns the paths that changed.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a file cannot be removed or rewritten, or the
+    /// config is not valid JSON.
+    pub fn uninstall(&self) -> anyhow::Result<Vec<PathBuf>> {
+        let mut changed = Vec::new();
+        for path in self.scripts.iter().chain(&self.files) {
+            if remove_file_and_empty_parent(path)? {
+                changed.push(path.clone());
+            }
+        }
+        if let Some(config) = &self.config
+            && strip_tokf_hooks(config)?
+        {
+            changed.push(config.clone());
+        }
+        for section in &self.sections {
+            if remove_section(section)? {
+                changed.push(section.clone());
+            }
+        }
+        Ok(changed)
+    }
+}
+
+/// The Claude Code footprint: both hook shims and `settings.json`.
+///
+/// # Errors
+///
+/// Returns an error if the home or working directory cannot be determined.
+pub fn claude_code_footprint(rt: &Runtime, global: bool) -> anyhow::Result<Footprint> {
+    let (hook_dir, settings_path) = claude_code_paths(rt, global)?;
+    Ok(Footprint {
+        scripts: claude_code_scripts(&hook_dir),
+        config: Some(settings_path),
+        ..Footprint::default()
+    })
+}
+
+fn script_state(path: &Path) -> FileState {
+    let Ok(meta) = std::fs::metadata(path) else {
+        return FileState::Missing;
+    };
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        if meta.permissions().mode() & 0o111 == 0 {
+            return FileState::NotExecutable;
+        }
+    }
+    #[cfg(not(unix))]
+    let _ = meta;
+    FileState::Present
+}
+
+fn marked_state(path: &Path, has_tokf: bool) -> FileState {
+    if !path.exists() {
+        FileState::Missing
+    } else if has_tokf {
+        FileState::Present
+    } else {
+        FileState::NoTokfEntry
+    }
+}
+
+fn read_config(path: &Path) -> anyhow::Result<Option<serde_json::Value>> {
+    let content = match std::fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(e.into()),
+    };
+    let value = serde_json::from_str(&content)
+        .map_err(|e| anyhow::anyhow!("corrupt hook config at {}: {e}", path.display()))?;
+    Ok(Some(value))
+}
+
+/// Whether the hook config at `path` registers a tokf hook for any event.
+pub fn has_tokf_hook(path: &Path) -> bool {
+    let Ok(Some(config)) = read_config(path) else {
+        return false;
+    };
+    config
+        .get("hooks")
+        .and_then(serde_json::Value::as_object)
+        .is_some_and(|events| {
+            events
+                .values()
+                .filter_map(serde_json::Value::as_array)
+                .flatten()
+                .any(is_tokf_hook_entry)
+        })
+}
+
+/// Drop tokf entries from every event of the hook config at `path`, along
+/// with the event arrays that end up empty. Returns whether anything changed.
+fn strip_tokf_hooks(path: &Path) -> anyhow::Result<bool> {
+    let Some(mut config) = read_config(path)? else {
+        return Ok(false);
+    };
+    let Some(events) = config
+        .get_mut("hooks")
+        .and_then(serde_json::Value::as_object_mut)
+    else {
+        return Ok(false);
+    };
+
+    let mut changed = false;
+    events.retain(|_, entries| {
+        let Some(arr) = entries.as_array_mut() else {
+            return true;
+        };
+        let before = arr.len();
+        arr.retain(|entry| !is_tokf_hook_entry(entry));
+        changed |= arr.len() != before;
+        !(arr.is_empty() && before > 0)
+    });
+    if !changed {
+        return Ok(false);
+    }
+
+    let json = serde_json::to_string_pretty(&config)?;
+    let tmp_path = path.with_extension("json.tmp");
+    std::fs::write(&tmp_path, &json)?;
+    std::fs::rename(&tmp_path, path)?;
+    Ok(true)
+}
+
+/// Remove `path`, then its parent directory if that left it empty.
+fn remove_file_and_empty_parent(path: &Path) -> anyhow::Result<bool> {
+    match std::fs::remove_file(path) {
+        Ok(()) => {}
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(false),
+        Err(e) => return Err(e.into()),
+    }
+    if let Some(parent) = path.parent() {
+        // Fails, as intended, when the directory holds anything else.
+        let _ = std::fs::remove_dir(parent);
+    }
+    Ok(true)
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn installed_claude_code(dir: &Path) -> Footprint {
+        let hook_dir = dir.join(".tokf/hooks");
+        let settings = dir.join(".claude/settings.json");
+        super::super::install::install_to(&hook_dir, &settings, "tokf", false).unwrap();
+        Footprint {
+            scripts: claude_code_scripts(&hook_dir),
+            config: Some(settings),
+            ..Footprint::default()
+        }
+    }
+
+    #[test]
+    fn status_reports_installed_scripts_and_settings() {
+        let dir = TempDir::new().unwrap();
+        let footprint = installed_claude_code(dir.path());
+
+        assert!(footprint.is_installed());
+        let states: Vec<_> = footprint.status().iter().map(|r| (r.0, r.2)).collect();
+        assert_eq!(
+            states,
+            [
+                ("script", FileState::Present),
+                ("script", FileState::Present),
+                ("script", FileState::Present),
+                ("settings", FileState::Present),
+            ]
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn status_flags_a_script_without_the_executable_bit() {
+        use std::os::unix::fs::PermissionsExt;
+        let dir = TempDir::new().unwrap();
+        let footprint = installed_claude_code(dir.path());
+        let script = &footprint.scripts[0];
+        std::fs::set_permissions(script, std::fs::Permissions::from_mode(0o644)).unwrap();
+
+        assert_eq!(footprint.status()[0].2, FileState::NotExecutable);
+    }
+
+    #[test]
+    fn uninstall_keeps_other_settings_and_hooks() {
+        let dir = TempDir::new().unwrap();
+        let settings = dir.path().join(".claude/settings.json");
+        std::fs::create_dir_all(settings.parent().unwrap()).unwrap();
+        std::fs::write(
+            &settings,
+            r#"{"permissions":{"allow":["Read"]},"hooks":{"PreToolUse":[
+                {"matcher":"Bash","hooks":[{"type":"command","command":"my-lint"}]}]}}"#,
+        )
+        .unwrap();
+        let footprint = installed_claude_code(dir.path());
+
+        let changed = footprint.uninstall().unwrap();
+        assert_eq!(changed.len(), 4);
+        assert!(!footprint.is_installed());
+        assert!(!dir.path().join(".tokf/hooks").exists());
+
+        let value: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(&settings).unwrap()).unwrap();
+        assert_eq!(value["permissions"]["allow"][0], "Read");
+        assert_eq!(value["hooks"]["PreToolUse"].as_array().unwrap().len(), 1);
+        assert!(value["hooks"].get("PostToolUse").is_none());
+        assert!(value["hooks"].get("SessionStart").is_none());
+
+        // A second run has nothing left to do.
+        assert!(footprint.uninstall().unwrap().is_empty());
+    }
+
+    #[test]
+    fn uninstall_strips_flat_entries() {
+        let dir = TempDir::new().unwrap();
+        let hooks_json = dir.path().join("hooks.json");
+        std::fs::write(
+            &hooks_json,
+            r#"{"version":1,"hooks":{"beforeShellExecution":[
+                {"command":"/p/.tokf/hooks/cursor-pre-tool-use.sh"},{"command":"audit"}]}}"#,
+        )
+        .unwrap();
+        assert!(has_tokf_hook(&hooks_json));
+
+        assert!(strip_tokf_hooks(&hooks_json).unwrap());
+        assert!(!has_tokf_hook(&hooks_json));
+        let value: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(&hooks_json).unwrap()).unwrap();
+        assert_eq!(
+            value["hooks"]["beforeShellExecution"][0]["command"],
+            "audit"
+        );
+    }
+
+    #[test]
+    fn uninstall_removes_sections_and_owned_files() {
+        let dir = TempDir::new().unwrap();
+        let shared = dir.path().join("CONVENTIONS.md");
+        std::fs::write(&shared, "# Ours\n").unwrap();
+        super::super::append_or_replace_section(&shared, || {
+            "<!-- tokf:start -->\ntokf\n<!-- tokf:end -->\n".to_string()
+        })
+        .unwrap();
+        let owned = dir.path().join("rules/tokf.md");
+        std::fs::create_dir_all(owned.parent().unwrap()).unwrap();
+        std::fs::write(&owned, "tokf").unwrap();
+        let footprint = Footprint {
+            files: vec![owned.clone()],
+            sections: vec![shared.clone()],
+            ..Footprint::default()
+        };
+
+        assert_eq!(footprint.uninstall().unwrap(), [owned, shared.clone()]);
+        assert_eq!(std::fs::read_to_string(&shared).unwrap(), "# Ours\n");
+    }
+}
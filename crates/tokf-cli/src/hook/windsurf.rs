@@ -1,21 +1,66 @@
+use std::io::Read;
 use std::path::{Path, PathBuf};
 
 use anyhow::Context;
 
+use super::HookOutcome;
 use super::instructions;
+use super::types::{HookFormat, WindsurfHookResponse, WindsurfInput};
+use super::uninstall::Footprint;
+use crate::rewrite;
+use crate::rewrite::types::RewriteConfig;
+use crate::runtime::Runtime;
 
-/// Install the Windsurf rules file.
+const HOOK_SCRIPT: &str = "windsurf-pre-run-command.sh";
+
+/// Install the Windsurf rules file and the Cascade `pre_run_command` hook.
 ///
 /// # Errors
 ///
 /// Returns an error if file I/O fails.
-pub fn install(global: bool) -> anyhow::Result<()> {
-    let rules_path = if global {
-        global_rules_path()?
+pub fn install(rt: &Runtime, global: bool, tokf_bin: &str) -> anyhow::Result<()> {
+    install_to(&rules_path(global)?, global)?;
+    let (hook_dir, windsurf_dir) = hook_paths(rt, global)?;
+    install_hook_to(&hook_dir, &windsurf_dir.join("hooks.json"), tokf_bin)
+}
+
+/// The files [`install`] writes. The global rules file is shared, so only
+/// its tokf section counts.
+///
+/// # Errors
+///
+/// Returns an error if the home or working directory cannot be determined.
+pub fn footprint(rt: &Runtime, global: bool) -> anyhow::Result<Footprint> {
+    let rules_path = rules_path(global)?;
+    let (hook_dir, windsurf_dir) = hook_paths(rt, global)?;
+    let (files, sections) = if global {
+        (vec![], vec![rules_path])
     } else {
-        PathBuf::from(".windsurf/rules/tokf.md")
+        (vec![rules_path], vec![])
     };
-    install_to(&rules_path, global)
+    Ok(Footprint {
+        scripts: vec![hook_dir.join(HOOK_SCRIPT)],
+        files,
+        config: Some(windsurf_dir.join("hooks.json")),
+        sections,
+    })
+}
+
+fn rules_path(global: bool) -> anyhow::Result<PathBuf> {
+    if global {
+        global_rules_path()
+    } else {
+        Ok(PathBuf::from(".windsurf/rules/tokf.md"))
+    }
+}
+
+fn hook_paths(rt: &Runtime, global: bool) -> anyhow::Result<(PathBuf, PathBuf)> {
+    let windsurf_dir_name = if global {
+        ".codeium/windsurf"
+    } else {
+        ".windsurf"
+    };
+    super::resolve_paths(rt, global, windsurf_dir_name)
 }
 
 /// Core install logic with explicit path (testable).
@@ -31,6 +76,85 @@ pub(crate) fn install_to(rules_path: &Path, is_global: bool) -> anyhow::Result<(
     Ok(())
 }
 
+/// Write the hook shim and register it under `pre_run_command` in `hooks.json`.
+pub(crate) fn install_hook_to(
+    hook_dir: &Path,
+    hooks_json_path: &Path,
+    tokf_bin: &str,
+) -> anyhow::Result<()> {
+    let hook_script = hook_dir.join(HOOK_SCRIPT);
+    super::write_hook_shim(hook_dir, &hook_script, tokf_bin, "--format windsurf")?;
+    super::patch_flat_hooks_json(
+        hooks_json_path,
+        &hook_script,
+        "pre_run_command",
+        serde_json::json!({}),
+        |command| serde_json::json!({ "command": command }),
+    )?;
+
+    eprintln!("[tokf] Windsurf hook installed");
+    eprintln!("[tokf]   script: {}", hook_script.display());
+    eprintln!("[tokf]   hooks: {}", hooks_json_path.display());
+    Ok(())
+}
+
+/// Process a Windsurf Cascade `pre_run_command` hook invocation.
+///
+/// Windsurf can't rewrite the command, so a command tokf would rewrite is
+/// blocked with a hint to rerun it as `tokf run …` (see
+/// [`WindsurfHookResponse::rewrite`]).
+pub fn handle(rt: &Runtime, no_cache: bool, no_mask_exit_code: bool) -> HookOutcome {
+    let mut input = String::new();
+    if std::io::stdin().read_to_string(&mut input).is_err() {
+        return HookOutcome::PassThrough;
+    }
+    let user_config = rewrite::load_user_config(rt).unwrap_or_default();
+    let search_dirs = crate::config::default_search_dirs(rt);
+    handle_json_inner(
+        rt,
+        &input,
+        &user_config,
+        &search_dirs,
+        no_cache,
+        no_mask_exit_code,
+    )
+}
+
+#[allow(clippy::too_many_arguments)]
+fn handle_json_inner(
+    rt: &Runtime,
+    json: &str,
+    user_config: &RewriteConfig,
+    search_dirs: &[PathBuf],
+    no_cache: bool,
+    no_mask_exit_code: bool,
+) -> HookOutcome {
+    let Ok(input) = serde_json::from_str::<WindsurfInput>(json) else {
+        return HookOutcome::PassThrough;
+    };
+
+    let Some(command) = input.tool_info.and_then(|info| info.command_line) else {
+        return HookOutcome::PassThrough;
+    };
+
+    super::process_command(
+        rt,
+        &command,
+        "run_command",
+        json,
+        HookFormat::Windsurf,
+        user_config,
+        search_dirs,
+        no_cache,
+        no_mask_exit_code,
+        WindsurfHookResponse::rewrite,
+        WindsurfHookResponse::rewrite_ask,
+        WindsurfHookResponse::deny,
+        HookOutcome::Deny,
+        HookOutcome::Deny,
+    )
+}
+
 fn global_rules_path() -> anyhow::Result<PathBuf> {
     let home = dirs::home_dir().context("could not determine home directory")?;
     Ok(home.join(".codeium/windsurf/memories/global_rules.md"))
@@ -108,6 +232,76 @@ mod tests {
         assert_eq!(count, 1, "should have exactly one tokf section");
     }
 
+    #[test]
+    fn install_hook_to_registers_pre_run_command() {
+        let dir = TempDir::new().unwrap();
+        let hook_dir = dir.path().join(".tokf/hooks");
+        let hooks_json = dir.path().join(".windsurf/hooks.json");
+        std::fs::create_dir_all(hooks_json.parent().unwrap()).unwrap();
+        std::fs::write(
+            &hooks_json,
+            r#"{"hooks":{"pre_run_command":[{"command":"other-hook"}]}}"#,
+        )
+        .unwrap();
+
+        install_hook_to(&hook_dir, &hooks_json, "tokf").unwrap();
+        install_hook_to(&hook_dir, &hooks_json, "tokf").unwrap();
+
+        let script = std::fs::read_to_string(hook_dir.join("windsurf-pre-run-command.sh")).unwrap();
+        assert!(script.contains("tokf hook handle --format windsurf"));
+        let content = std::fs::read_to_string(&hooks_json).unwrap();
+        let value: serde_json::Value = serde_json::from_str(&content).unwrap();
+        let entries = value["hooks"]["pre_run_command"].as_array().unwrap();
+        assert_eq!(entries.len(), 2, "other hooks kept, tokf registered once");
+        assert_eq!(entries[0]["command"], "other-hook");
+        assert!(
+            entries[1]["command"]
+                .as_str()
+                .unwrap()
+                .contains("windsurf-pre-run-command.sh")
+        );
+    }
+
+    fn handle_with_filter_dir(json: &str) -> HookOutcome {
+        let dir = TempDir::new().unwrap();
+        std::fs::write(
+            dir.path().join("git-status.toml"),
+            "command = \"git status\"",
+        )
+        .unwrap();
+        let rt = Runtime::isolated();
+        let config = RewriteConfig::default();
+        handle_json_inner(
+            &rt,
+            json,
+            &config,
+            &[dir.path().to_path_buf()],
+            false,
+            false,
+        )
+    }
+
+    #[test]
+    fn handle_blocks_filtered_command_with_rerun_hint() {
+        let json = r#"{"agent_action_name":"pre_run_command","tool_info":{"command_line":"git status","cwd":"/tmp"}}"#;
+        assert_eq!(handle_with_filter_dir(json), HookOutcome::Deny);
+    }
+
+    #[test]
+    fn handle_lets_rerun_through() {
+        let json = r#"{"agent_action_name":"pre_run_command","tool_info":{"command_line":"tokf run git status"}}"#;
+        assert_eq!(handle_with_filter_dir(json), HookOutcome::PassThrough);
+    }
+
+    #[test]
+    fn handle_without_command_passes_through() {
+        assert_eq!(
+            handle_with_filter_dir(r#"{"agent_action_name":"pre_run_command"}"#),
+            HookOutcome::PassThrough
+        );
+        assert_eq!(handle_with_filter_dir("not json"), HookOutcome::PassThrough);
+    }
+
     #[test]
     fn global_creates_new_file() {
         let dir = TempDir::new().unwrap();
@@ -158,7 +158,11 @@ fn collect_search_dirs(search_dirs: &[PathBuf]) -> Vec<SearchDir> {
         .iter()
         .enumerate()
         .map(|(i, dir)| SearchDir {
-            scope: if i == 0 { "local" } else { "user" },
+            scope: if i == 0 || config::is_project_dir(dir) {
+                "local"
+            } else {
+                "user"
+            },
             path: dir.display().to_string(),
             exists: dir.exists(),
             access: dir.exists().then(|| {
@@ -181,10 +185,10 @@ fn collect_search_dirs(search_dirs: &[PathBuf]) -> Vec<SearchDir> {
 
 /// Bucket a discovered filter list into the counts shown in `tokf info`.
 pub fn count_filters_by_priority(filters: &[ResolvedFilter]) -> FilterCounts {
-    let local = filters.iter().filter(|fi| fi.priority == 0).count();
+    let local = filters.iter().filter(|fi| fi.is_local()).count();
     let user = filters
         .iter()
-        .filter(|fi| fi.priority > 0 && fi.priority < u8::MAX)
+        .filter(|fi| !fi.is_local() && fi.priority < u8::MAX)
         .count();
     let builtin = filters.iter().filter(|fi| fi.priority == u8::MAX).count();
     FilterCounts {
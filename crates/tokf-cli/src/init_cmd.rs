@@ -0,0 +1,160 @@
+use anyhow::Context as _;
+
+use std::path::Path;
+
+use tokf::hook;
+use tokf::runtime::Runtime;
+
+const CONFIG_TOML: &str = include_str!("../templates/init/config.toml");
+const EXAMPLE_FILTER_TOML: &str = include_str!("../templates/init/example.toml");
+const REWRITES_TOML: &str = include_str!("../templates/init/rewrites.toml");
+
+/// `.gitignore` entry for the filter resolution cache (`.tokf/cache/`).
+const CACHE_IGNORE: &str = "cache/";
+
+struct ScaffoldFile {
+    /// Relative path within `.tokf/`.
+    rel_path: &'static str,
+    content: &'static str,
+}
+
+const FILES: &[ScaffoldFile] = &[
+    ScaffoldFile {
+        rel_path: "config.toml",
+        content: CONFIG_TOML,
+    },
+    ScaffoldFile {
+        rel_path: "filters/example.toml",
+        content: EXAMPLE_FILTER_TOML,
+    },
+    ScaffoldFile {
+        rel_path: "rewrites.toml",
+        content: REWRITES_TOML,
+    },
+];
+
+/// Entry point for the `tokf init` subcommand.
+pub fn cmd_init(rt: &Runtime, hook: bool, force: bool) -> i32 {
+    match init(rt, hook, force) {
+        Ok(()) => 0,
+        Err(e) => {
+            eprintln!("[tokf] error: {e:#}");
+            1
+        }
+    }
+}
+
+fn init(rt: &Runtime, install_hook: bool, force: bool) -> anyhow::Result<()> {
+    let base = rt
+        .cwd()
+        .context("could not determine working directory")?
+        .join(".tokf");
+    scaffold_to(&base, force)?;
+    if install_hook {
+        hook::install(rt, false, "tokf", true)?;
+    }
+    eprintln!("[tokf] initialized {}", base.display());
+    Ok(())
+}
+
+/// Core scaffolding logic with an explicit `.tokf/` path (testable).
+///
+/// Existing files are left alone unless `force` is set; the `.gitignore` is
+/// only ever appended to.
+fn scaffold_to(base: &Path, force: bool) -> anyhow::Result<()> {
+    for file in FILES {
+        let dest = base.join(file.rel_path);
+        if dest.exists() && !force {
+            eprintln!(
+                "[tokf] {} already exists, skipped (--force overwrites it)",
+                dest.display()
+            );
+            continue;
+        }
+        if let Some(parent) = dest.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(&dest, file.content)?;
+        eprintln!("[tokf] wrote {}", dest.display());
+    }
+    ignore_cache(&base.join(".gitignore"))
+}
+
+/// Add the cache entry to `.tokf/.gitignore`, unless it is already there.
+fn ignore_cache(path: &Path) -> anyhow::Result<()> {
+    let existing = match std::fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+        Err(e) => return Err(e).with_context(|| format!("reading {}", path.display())),
+    };
+    if existing.lines().any(|line| line.trim() == CACHE_IGNORE) {
+        return Ok(());
+    }
+    let mut content = existing;
+    if !content.is_empty() && !content.ends_with('\n') {
+        content.push('\n');
+    }
+    content.push_str(CACHE_IGNORE);
+    content.push('\n');
+    std::fs::write(path, content)?;
+    eprintln!("[tokf] wrote {}", path.display());
+    Ok(())
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scaffold_writes_every_file() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let base = dir.path().join(".tokf");
+        scaffold_to(&base, false).unwrap();
+
+        for file in FILES {
+            let content = std::fs::read_to_string(base.join(file.rel_path)).unwrap();
+            assert_eq!(content, file.content);
+        }
+        let ignore = std::fs::read_to_string(base.join(".gitignore")).unwrap();
+        assert_eq!(ignore, "cache/\n");
+    }
+
+    #[test]
+    fn scaffold_keeps_existing_files_unless_forced() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let base = dir.path().join(".tokf");
+        std::fs::create_dir_all(&base).unwrap();
+        std::fs::write(base.join("config.toml"), "[history]\nretention = 3\n").unwrap();
+
+        scaffold_to(&base, false).unwrap();
+        let config = std::fs::read_to_string(base.join("config.toml")).unwrap();
+        assert_eq!(config, "[history]\nretention = 3\n");
+
+        scaffold_to(&base, true).unwrap();
+        let config = std::fs::read_to_string(base.join("config.toml")).unwrap();
+        assert_eq!(config, CONFIG_TOML);
+    }
+
+    #[test]
+    fn gitignore_entry_is_appended_once() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join(".gitignore");
+        std::fs::write(&path, "hooks/").unwrap();
+
+        ignore_cache(&path).unwrap();
+        ignore_cache(&path).unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "hooks/\ncache/\n");
+    }
+
+    #[test]
+    fn templates_parse() {
+        let config: tokf::history::TokfProjectConfig = toml::from_str(CONFIG_TOML).unwrap();
+        assert!(config.history.is_none());
+        let filter: tokf::config::types::FilterConfig =
+            toml::from_str(EXAMPLE_FILTER_TOML).unwrap();
+        assert_eq!(filter.command.first(), "my-tool build");
+        let rewrites: tokf::rewrite::types::RewriteConfig = toml::from_str(REWRITES_TOML).unwrap();
+        assert!(rewrites.rewrite.is_empty());
+    }
+}
@@ -1,9 +1,16 @@
+mod provenance;
+mod signature;
+mod update;
+
+pub use update::{UpdateOpts, cmd_update};
+
 use anyhow::Context as _;
 
 use std::path::{Component, Path, PathBuf};
 
 use tokf::remote::filter_client;
 use tokf::remote::http::Client;
+use tokf::remote::registry;
 use tokf_common::config::types::FilterConfig;
 
 use tokf::runtime::Runtime;
@@ -12,8 +19,12 @@ use tokf::runtime::Runtime;
 #[derive(Debug, Clone, Copy)]
 #[allow(clippy::struct_excessive_bools)] // CLI flags are naturally booleans
 pub struct InstallOpts<'a> {
+    /// Content hash, or a command pattern to search the registry for.
     pub filter: &'a str,
-    pub local: bool,
+    /// Registry name or URL; every registry in priority order when `None`.
+    pub registry: Option<&'a str>,
+    /// Install into the user config dir rather than the project's `.tokf/`.
+    pub global: bool,
     pub force: bool,
     pub dry_run: bool,
     pub yes: bool,
@@ -30,32 +41,89 @@ pub fn cmd_install(rt: &Runtime, opts: InstallOpts<'_>) -> i32 {
     }
 }
 
-fn resolve_hash(client: &Client, filter: &str) -> anyhow::Result<(String, String)> {
+/// A filter found in the registry.
+struct Found {
+    hash: String,
+    author: String,
+    /// Why the filter should no longer be used, if it shouldn't — see
+    /// [`filter_client::Retirement::notice`].
+    notice: Option<String>,
+}
+
+fn resolve_hash(client: &Client, filter: &str) -> anyhow::Result<Found> {
     if is_hash(filter) {
         let details = filter_client::get_filter(client, filter)?;
-        Ok((details.content_hash, details.author))
+        Ok(Found {
+            notice: details.retirement().notice(),
+            hash: details.content_hash,
+            author: details.author,
+        })
     } else {
         let results = filter_client::search_filters(client, filter, 1)?;
         let first = results
             .into_iter()
             .next()
             .ok_or_else(|| anyhow::anyhow!("no filter found matching: {filter}"))?;
-        Ok((first.content_hash, first.author))
+        Ok(Found {
+            notice: first.retirement().notice(),
+            hash: first.content_hash,
+            author: first.author,
+        })
+    }
+}
+
+/// Find `opts.filter` in the registry named by `opts.registry`, or in the
+/// first registry, by priority, that has it.
+fn locate(rt: &Runtime, opts: InstallOpts<'_>) -> anyhow::Result<(Client, Found)> {
+    let registries = registry::select(rt, opts.registry)?;
+    let mut last_error = None;
+    for r in &registries {
+        let found = r.client(rt).and_then(|client| {
+            let found = resolve_hash(&client, opts.filter)?;
+            Ok((client, found))
+        });
+        match found {
+            Ok(found) => return Ok(found),
+            Err(e) if registries.len() > 1 => {
+                eprintln!("[tokf] not found in registry '{}': {e:#}", r.name);
+                last_error = Some(e);
+            }
+            Err(e) => return Err(e),
+        }
     }
+    Err(last_error.unwrap_or_else(|| anyhow::anyhow!("no registry configured")))
 }
 
 fn install(rt: &Runtime, opts: InstallOpts<'_>) -> anyhow::Result<i32> {
+    let (client, found) = locate(rt, opts)?;
+    if let Some(notice) = &found.notice {
+        eprintln!("[tokf] warning: filter {} is {notice}", found.hash);
+    }
+    let downloaded = filter_client::download_filter(rt, &client, &found.hash)?;
+    let opts = InstallOpts {
+        filter: &found.hash,
+        ..opts
+    };
+    install_downloaded(rt, &client, found.author, &downloaded, opts)
+}
+
+/// Verify, write, test and record a filter already downloaded from `client`.
+/// `opts.filter` is the hash it was downloaded under.
+fn install_downloaded(
+    rt: &Runtime,
+    client: &Client,
+    author: String,
+    downloaded: &filter_client::DownloadedFilter,
+    opts: InstallOpts<'_>,
+) -> anyhow::Result<i32> {
     let InstallOpts {
-        filter,
-        local,
+        filter: url_hash,
+        global,
         force,
         dry_run,
         yes,
+        registry: _,
     } = opts;
-    let client = Client::authed(rt)?;
-
-    let (url_hash, author) = resolve_hash(&client, filter)?;
-    let downloaded = filter_client::download_filter(&client, &url_hash)?;
 
     // Parse TOML once; derive command pattern and detect Lua in a single pass.
     let (command_pattern, config) = parse_filter_toml(&downloaded.filter_toml)?;
@@ -65,14 +133,15 @@ fn install(rt: &Runtime, opts: InstallOpts<'_>) -> anyhow::Result<i32> {
     //  2. Server-provided `content_hash` (schema-tied recompute; #351).
     //  3. URL hash (legacy fallback for old servers; #350 scaffolding).
     let hash = verify_and_resolve_hash(
-        &url_hash,
+        url_hash,
         downloaded.v1_hash.as_deref(),
         downloaded.content_hash.as_deref(),
         &config,
         &downloaded.filter_toml,
     )?;
+    let signing_key = signature::check(downloaded);
 
-    let install_base = resolve_install_base(rt, local)?;
+    let install_base = resolve_install_base(rt, global)?;
     let rel_path = command_pattern_to_path(&command_pattern);
 
     // Ensure command_pattern doesn't produce a path that escapes install_base.
@@ -99,19 +168,23 @@ fn install(rt: &Runtime, opts: InstallOpts<'_>) -> anyhow::Result<i32> {
         .join(format!("{stem}_test"));
 
     if dry_run {
-        print_dry_run_summary(&command_pattern, &hash, &author, &install_path, &downloaded);
+        print_dry_run_summary(&command_pattern, &hash, &author, &install_path, downloaded);
+        eprintln!("  Registry: {}", client.base_url());
         return Ok(0);
     }
 
     // Show the filter and ask for confirmation before writing anything.
     prompt_install_confirm(&downloaded.filter_toml, &config, &author, &hash, yes)?;
 
-    write_filter(&downloaded, &install_path, &hash, &author, &test_dir)?;
+    write_filter(downloaded, &install_path, &hash, &author, &test_dir)?;
 
     if !downloaded.test_files.is_empty() {
         run_verify(rt, &rel_path, &install_path, &test_dir)?;
     }
 
+    let record = provenance::Provenance::now(hash, author, client.base_url(), signing_key);
+    record_provenance(&install_base, &rel_path, record);
+
     eprintln!(
         "[tokf] installed {} → {}",
         command_pattern,
@@ -120,6 +193,18 @@ fn install(rt: &Runtime, opts: InstallOpts<'_>) -> anyhow::Result<i32> {
     Ok(0)
 }
 
+/// Note in `installed.toml` where the filter at `rel_path` came from. A
+/// failure only warns: the filter itself is already installed and verified.
+fn record_provenance(install_base: &Path, rel_path: &Path, record: provenance::Provenance) {
+    let name = rel_path
+        .with_extension("")
+        .to_string_lossy()
+        .replace('\\', "/");
+    if let Err(e) = provenance::record(install_base, &name, record) {
+        eprintln!("[tokf] warning: could not record where {name} came from: {e:#}");
+    }
+}
+
 /// Parse the filter TOML and return the first command pattern together with
 /// the parsed config.
 ///
@@ -336,12 +421,12 @@ fn command_pattern_to_path(pattern: &str) -> PathBuf {
     }
 }
 
-fn resolve_install_base(rt: &Runtime, local: bool) -> anyhow::Result<PathBuf> {
-    if local {
+fn resolve_install_base(rt: &Runtime, global: bool) -> anyhow::Result<PathBuf> {
+    if global {
+        rt.require_user_dir()
+    } else {
         let cwd = rt.cwd().context("could not determine working directory")?;
         Ok(cwd.join(".tokf"))
-    } else {
-        rt.require_user_dir()
     }
 }
 
@@ -437,198 +522,4 @@ fn write_filter(
 
 #[cfg(test)]
 #[allow(clippy::unwrap_used)]
-mod tests {
-    use super::*;
-
-    /// Sample TOML, parsed config, and the three relevant hashes for
-    /// driving `verify_and_resolve_hash` deterministically.
-    fn sample_filter() -> (String, FilterConfig, String, String) {
-        let toml = r#"command = "git push""#.to_string();
-        let cfg: FilterConfig = toml::from_str(&toml).unwrap();
-        let canonical = tokf_common::hash::canonical_hash(&cfg).unwrap();
-        let v1 = tokf_common::canonical_v1::hash(&toml).unwrap();
-        (toml, cfg, canonical, v1)
-    }
-
-    #[test]
-    fn verify_and_resolve_prefers_v1_when_provided() {
-        let (toml, cfg, _canonical, v1) = sample_filter();
-        // URL hash is stale; server provides v1. Client picks v1.
-        let url = "0".repeat(64);
-        let resolved = verify_and_resolve_hash(&url, Some(&v1), None, &cfg, &toml).unwrap();
-        assert_eq!(resolved, v1);
-    }
-
-    #[test]
-    fn verify_and_resolve_v1_takes_precedence_over_content_hash() {
-        let (toml, cfg, canonical, v1) = sample_filter();
-        // Both v1 and content_hash present: v1 wins.
-        let url = "0".repeat(64);
-        let resolved =
-            verify_and_resolve_hash(&url, Some(&v1), Some(&canonical), &cfg, &toml).unwrap();
-        assert_eq!(resolved, v1);
-        assert_ne!(resolved, canonical);
-    }
-
-    #[test]
-    fn verify_and_resolve_errors_when_v1_disagrees() {
-        let (toml, cfg, _canonical, _v1) = sample_filter();
-        let url = "0".repeat(64);
-        let bogus_v1 = "v1:".to_string() + &"1".repeat(64);
-        let err = verify_and_resolve_hash(&url, Some(&bogus_v1), None, &cfg, &toml).unwrap_err();
-        let msg = format!("{err:#}");
-        assert!(
-            msg.contains("v1 hash mismatch with server-provided value"),
-            "wrong error: {msg}"
-        );
-    }
-
-    #[test]
-    fn verify_and_resolve_falls_back_to_content_hash_when_v1_absent() {
-        let (toml, cfg, canonical, _v1) = sample_filter();
-        // Pre-v1 server: provides content_hash but no v1.
-        let url = "0".repeat(64);
-        let resolved = verify_and_resolve_hash(&url, None, Some(&canonical), &cfg, &toml).unwrap();
-        assert_eq!(resolved, canonical);
-    }
-
-    #[test]
-    fn verify_and_resolve_errors_when_content_hash_disagrees() {
-        let (toml, cfg, _canonical, _v1) = sample_filter();
-        let url = "0".repeat(64);
-        let bogus = "1".repeat(64);
-        let err = verify_and_resolve_hash(&url, None, Some(&bogus), &cfg, &toml).unwrap_err();
-        let msg = format!("{err:#}");
-        assert!(
-            msg.contains("filter content hash mismatch with server-provided value"),
-            "wrong error: {msg}"
-        );
-    }
-
-    #[test]
-    fn verify_and_resolve_falls_back_to_url_hash_on_pre_351_server() {
-        let (toml, cfg, canonical, _v1) = sample_filter();
-        // Pre-#351 server: no v1_hash, no content_hash; URL hash equals
-        // the client's canonical_hash.
-        let resolved = verify_and_resolve_hash(&canonical, None, None, &cfg, &toml).unwrap();
-        assert_eq!(resolved, canonical);
-    }
-
-    #[test]
-    fn verify_and_resolve_errors_on_pre_351_server_with_url_mismatch() {
-        let (toml, cfg, _canonical, _v1) = sample_filter();
-        let url = "0".repeat(64);
-        let err = verify_and_resolve_hash(&url, None, None, &cfg, &toml).unwrap_err();
-        let msg = format!("{err:#}");
-        assert!(msg.contains("hash mismatch"), "wrong error: {msg}");
-        assert!(msg.contains("issue #350"), "should reference issue: {msg}");
-    }
-
-    #[test]
-    fn command_pattern_to_install_path_single_word() {
-        let path = command_pattern_to_path("git");
-        assert_eq!(path, PathBuf::from("git.toml"));
-    }
-
-    #[test]
-    fn command_pattern_to_install_path_two_words() {
-        let path = command_pattern_to_path("git push");
-        assert_eq!(path, PathBuf::from("git/push.toml"));
-    }
-
-    #[test]
-    fn command_pattern_to_install_path_three_words_uses_first_two() {
-        let path = command_pattern_to_path("cargo test --workspace");
-        assert_eq!(path, PathBuf::from("cargo/test.toml"));
-    }
-
-    #[test]
-    fn attribution_header_format() {
-        let header = attribution_header("alice", "deadbeef");
-        assert!(header.starts_with('#'), "header should start with #");
-        assert!(header.contains("@alice"), "header should mention author");
-        assert!(header.contains("deadbeef"), "header should contain hash");
-        assert!(
-            header.contains("https://tokf.net/filters/deadbeef"),
-            "header should have filter URL"
-        );
-    }
-
-    #[test]
-    fn conflict_detected_without_force() {
-        let dir = tempfile::TempDir::new().unwrap();
-        let filter_path = dir.path().join("git").join("push.toml");
-        std::fs::create_dir_all(filter_path.parent().unwrap()).unwrap();
-        std::fs::write(&filter_path, b"command = \"git push\"\n").unwrap();
-
-        let force = false;
-        assert!(
-            filter_path.exists() && !force,
-            "conflict should be detected"
-        );
-    }
-
-    #[test]
-    fn parse_filter_toml_extracts_command_pattern() {
-        let toml = r#"command = "git push""#;
-        let (pattern, _) = parse_filter_toml(toml).unwrap();
-        assert_eq!(pattern, "git push");
-    }
-
-    #[test]
-    fn parse_filter_toml_strips_attribution_comments() {
-        let toml = "# Published by @alice · hash: abc123\ncommand = \"cargo build\"\n";
-        let (pattern, _) = parse_filter_toml(toml).unwrap();
-        assert_eq!(pattern, "cargo build");
-    }
-
-    #[test]
-    fn parse_filter_toml_errors_on_invalid_toml() {
-        let result = parse_filter_toml("this is [[[not valid toml");
-        assert!(result.is_err(), "should error on invalid TOML");
-    }
-
-    #[test]
-    fn parse_filter_toml_detects_lua() {
-        let toml =
-            "command = \"my-tool\"\n[lua_script]\nlang = \"luau\"\nsource = \"return input\"\n";
-        let (_, config) = parse_filter_toml(toml).unwrap();
-        assert!(config.lua_script.is_some(), "should detect lua_script");
-    }
-
-    #[test]
-    fn parse_filter_toml_no_lua_for_plain_filter() {
-        let toml = r#"command = "git push""#;
-        let (_, config) = parse_filter_toml(toml).unwrap();
-        assert!(config.lua_script.is_none(), "should not detect lua_script");
-    }
-
-    #[test]
-    fn safe_test_filenames_accepted() {
-        assert!(is_safe_test_filename("basic.toml"));
-        assert!(is_safe_test_filename("my-test_case.toml"));
-        assert!(is_safe_test_filename("file123.toml"));
-    }
-
-    #[test]
-    fn unsafe_test_filenames_rejected() {
-        assert!(!is_safe_test_filename(""), "empty name");
-        assert!(!is_safe_test_filename("."), "dot");
-        assert!(!is_safe_test_filename(".."), "double dot");
-        assert!(!is_safe_test_filename("../escape.toml"), "path traversal");
-        assert!(!is_safe_test_filename("sub/dir.toml"), "subdirectory");
-        assert!(!is_safe_test_filename("file name.toml"), "space");
-    }
-
-    #[test]
-    fn unsafe_command_pattern_path_rejected() {
-        // A safe path should have all Normal components.
-        let safe_path = command_pattern_to_path("git push");
-        assert!(
-            safe_path
-                .components()
-                .all(|c| matches!(c, Component::Normal(_))),
-            "safe path should have all Normal components"
-        );
-    }
-}
+mod tests;
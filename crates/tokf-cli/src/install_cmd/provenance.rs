@@ -0,0 +1,139 @@
+//! Where installed registry filters came from.
+//!
+//! `tokf install` records each filter it writes in `installed.toml` next to
+//! the `filters/` directory it installed into (`.tokf/installed.toml` or
+//! `~/.config/tokf/installed.toml`), keyed by the filter's name:
+//!
+//! ```toml
+//! [filters."git/push"]
+//! hash = "…"
+//! author = "alice"
+//! registry = "https://api.tokf.net"
+//! installed_at = "2026-03-01T12:00:00Z"
+//! signing_key = "…"  # only for filters whose signature verified
+//! ```
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use anyhow::Context as _;
+use serde::{Deserialize, Serialize};
+
+/// Where one installed filter came from.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Provenance {
+    /// Content hash the filter was verified against.
+    pub hash: String,
+    pub author: String,
+    /// Base URL of the registry it was downloaded from.
+    pub registry: String,
+    pub installed_at: String,
+    /// Public key (hex) whose signature verified at install time.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signing_key: Option<String>,
+}
+
+impl Provenance {
+    /// A record for a filter installed from `registry` just now.
+    pub fn now(hash: String, author: String, registry: &str, signing_key: Option<String>) -> Self {
+        Self {
+            hash,
+            author,
+            registry: registry.to_string(),
+            installed_at: tokf::sync_core::utc_now_iso8601(),
+            signing_key,
+        }
+    }
+}
+
+/// Every filter installed under one install base.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct InstalledFilters {
+    #[serde(default)]
+    pub filters: BTreeMap<String, Provenance>,
+}
+
+/// The provenance file for an install base (`.tokf` or the user config dir).
+pub fn provenance_path(install_base: &Path) -> PathBuf {
+    install_base.join("installed.toml")
+}
+
+/// Load the provenance records under `install_base`; none if the file is
+/// missing.
+///
+/// # Errors
+///
+/// Returns an error if the file exists but cannot be read or parsed.
+pub fn load(install_base: &Path) -> anyhow::Result<InstalledFilters> {
+    let path = provenance_path(install_base);
+    let content = match std::fs::read_to_string(&path) {
+        Ok(c) => c,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            return Ok(InstalledFilters::default());
+        }
+        Err(e) => return Err(e).with_context(|| format!("read {}", path.display())),
+    };
+    toml::from_str(&content).with_context(|| format!("parse {}", path.display()))
+}
+
+/// Record (or replace) the provenance of the filter `name` under
+/// `install_base`, keeping every other record.
+///
+/// # Errors
+///
+/// Returns an error if the existing file is unreadable or the new one cannot
+/// be written.
+pub fn record(install_base: &Path, name: &str, provenance: Provenance) -> anyhow::Result<()> {
+    let mut installed = load(install_base)?;
+    installed.filters.insert(name.to_string(), provenance);
+    let content = toml::to_string_pretty(&installed).context("serialize installed filters")?;
+    std::fs::create_dir_all(install_base)?;
+    tokf::fs::write_config_file(&provenance_path(install_base), &content)
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    fn provenance(hash: &str) -> Provenance {
+        Provenance {
+            hash: hash.to_string(),
+            author: "alice".to_string(),
+            registry: "https://api.tokf.net".to_string(),
+            installed_at: "2026-03-01T12:00:00Z".to_string(),
+            signing_key: None,
+        }
+    }
+
+    #[test]
+    fn missing_file_is_empty() {
+        let dir = tempfile::TempDir::new().unwrap();
+        assert!(load(dir.path()).unwrap().filters.is_empty());
+    }
+
+    #[test]
+    fn record_keeps_other_filters_and_replaces_same_name() {
+        let dir = tempfile::TempDir::new().unwrap();
+        record(dir.path(), "git/push", provenance("aaa")).unwrap();
+        record(dir.path(), "cargo/build", provenance("bbb")).unwrap();
+        record(dir.path(), "git/push", provenance("ccc")).unwrap();
+
+        let installed = load(dir.path()).unwrap();
+        assert_eq!(installed.filters.len(), 2);
+        assert_eq!(installed.filters["git/push"], provenance("ccc"));
+        assert_eq!(installed.filters["cargo/build"], provenance("bbb"));
+    }
+
+    #[test]
+    fn file_is_keyed_by_filter_name() {
+        let dir = tempfile::TempDir::new().unwrap();
+        record(dir.path(), "git/push", provenance("aaa")).unwrap();
+        let content = std::fs::read_to_string(provenance_path(dir.path())).unwrap();
+        assert!(content.contains("[filters.\"git/push\"]"), "{content}");
+        assert!(
+            content.contains("registry = \"https://api.tokf.net\""),
+            "{content}"
+        );
+    }
+}
@@ -0,0 +1,100 @@
+//! Checking the author's signature on a downloaded filter.
+//!
+//! The registry serves the signature and public key the author published
+//! with. A missing or non-verifying signature is reported but does not block
+//! the install: the content hash has already been checked, and most filters
+//! predate signing.
+
+use tokf::remote::filter_client::DownloadedFilter;
+use tokf_common::signing::{self, SignatureError};
+
+/// What the downloaded signature says about the filter.
+#[derive(Debug, PartialEq, Eq)]
+pub enum SignatureStatus {
+    /// Signed with this public key (hex) over exactly the downloaded TOML.
+    Verified(String),
+    /// Published without a signature.
+    Unsigned,
+    /// A signature was served but does not verify.
+    Invalid(SignatureError),
+}
+
+/// Verify the signature served with `downloaded`.
+pub fn status(downloaded: &DownloadedFilter) -> SignatureStatus {
+    let (Some(signature), Some(key)) = (&downloaded.signature, &downloaded.signing_key) else {
+        return SignatureStatus::Unsigned;
+    };
+    match signing::verify(key, signature, downloaded.filter_toml.as_bytes()) {
+        Ok(()) => SignatureStatus::Verified(key.clone()),
+        Err(e) => SignatureStatus::Invalid(e),
+    }
+}
+
+/// Verify and report the signature on stderr, returning the signing key
+/// when it checks out.
+pub fn check(downloaded: &DownloadedFilter) -> Option<String> {
+    match status(downloaded) {
+        SignatureStatus::Verified(key) => {
+            eprintln!(
+                "[tokf] signature verified (key {})",
+                signing::fingerprint(&key)
+            );
+            Some(key)
+        }
+        SignatureStatus::Unsigned => {
+            eprintln!("[tokf] warning: filter is unsigned — its author cannot be verified");
+            None
+        }
+        SignatureStatus::Invalid(e) => {
+            eprintln!(
+                "[tokf] WARNING: {e} — the filter may have been altered since its author signed it"
+            );
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+    use tokf_common::signing::SigningKey;
+
+    const TOML: &str = "command = \"git push\"\n";
+
+    fn downloaded(filter_toml: &str, signed_with: Option<(&SigningKey, &str)>) -> DownloadedFilter {
+        DownloadedFilter {
+            filter_toml: filter_toml.to_string(),
+            test_files: Vec::new(),
+            content_hash: None,
+            v1_hash: None,
+            signature: signed_with.map(|(k, content)| k.sign(content.as_bytes())),
+            signing_key: signed_with.map(|(k, _)| k.public_key_hex()),
+        }
+    }
+
+    #[test]
+    fn matching_signature_is_verified() {
+        let (key, _) = SigningKey::generate().unwrap();
+        assert_eq!(
+            status(&downloaded(TOML, Some((&key, TOML)))),
+            SignatureStatus::Verified(key.public_key_hex())
+        );
+    }
+
+    #[test]
+    fn missing_signature_is_unsigned() {
+        assert_eq!(status(&downloaded(TOML, None)), SignatureStatus::Unsigned);
+    }
+
+    #[test]
+    fn altered_content_is_invalid() {
+        let (key, _) = SigningKey::generate().unwrap();
+        let tampered = downloaded("command = \"rm -rf /\"\n", Some((&key, TOML)));
+        assert_eq!(
+            status(&tampered),
+            SignatureStatus::Invalid(SignatureError::Mismatch)
+        );
+        assert_eq!(check(&tampered), None);
+    }
+}
@@ -0,0 +1,191 @@
+use super::*;
+
+/// Sample TOML, parsed config, and the three relevant hashes for
+/// driving `verify_and_resolve_hash` deterministically.
+fn sample_filter() -> (String, FilterConfig, String, String) {
+    let toml = r#"command = "git push""#.to_string();
+    let cfg: FilterConfig = toml::from_str(&toml).unwrap();
+    let canonical = tokf_common::hash::canonical_hash(&cfg).unwrap();
+    let v1 = tokf_common::canonical_v1::hash(&toml).unwrap();
+    (toml, cfg, canonical, v1)
+}
+
+#[test]
+fn verify_and_resolve_prefers_v1_when_provided() {
+    let (toml, cfg, _canonical, v1) = sample_filter();
+    // URL hash is stale; server provides v1. Client picks v1.
+    let url = "0".repeat(64);
+    let resolved = verify_and_resolve_hash(&url, Some(&v1), None, &cfg, &toml).unwrap();
+    assert_eq!(resolved, v1);
+}
+
+#[test]
+fn verify_and_resolve_v1_takes_precedence_over_content_hash() {
+    let (toml, cfg, canonical, v1) = sample_filter();
+    // Both v1 and content_hash present: v1 wins.
+    let url = "0".repeat(64);
+    let resolved = verify_and_resolve_hash(&url, Some(&v1), Some(&canonical), &cfg, &toml).unwrap();
+    assert_eq!(resolved, v1);
+    assert_ne!(resolved, canonical);
+}
+
+#[test]
+fn verify_and_resolve_errors_when_v1_disagrees() {
+    let (toml, cfg, _canonical, _v1) = sample_filter();
+    let url = "0".repeat(64);
+    let bogus_v1 = "v1:".to_string() + &"1".repeat(64);
+    let err = verify_and_resolve_hash(&url, Some(&bogus_v1), None, &cfg, &toml).unwrap_err();
+    let msg = format!("{err:#}");
+    assert!(
+        msg.contains("v1 hash mismatch with server-provided value"),
+        "wrong error: {msg}"
+    );
+}
+
+#[test]
+fn verify_and_resolve_falls_back_to_content_hash_when_v1_absent() {
+    let (toml, cfg, canonical, _v1) = sample_filter();
+    // Pre-v1 server: provides content_hash but no v1.
+    let url = "0".repeat(64);
+    let resolved = verify_and_resolve_hash(&url, None, Some(&canonical), &cfg, &toml).unwrap();
+    assert_eq!(resolved, canonical);
+}
+
+#[test]
+fn verify_and_resolve_errors_when_content_hash_disagrees() {
+    let (toml, cfg, _canonical, _v1) = sample_filter();
+    let url = "0".repeat(64);
+    let bogus = "1".repeat(64);
+    let err = verify_and_resolve_hash(&url, None, Some(&bogus), &cfg, &toml).unwrap_err();
+    let msg = format!("{err:#}");
+    assert!(
+        msg.contains("filter content hash mismatch with server-provided value"),
+        "wrong error: {msg}"
+    );
+}
+
+#[test]
+fn verify_and_resolve_falls_back_to_url_hash_on_pre_351_server() {
+    let (toml, cfg, canonical, _v1) = sample_filter();
+    // Pre-#351 server: no v1_hash, no content_hash; URL hash equals
+    // the client's canonical_hash.
+    let resolved = verify_and_resolve_hash(&canonical, None, None, &cfg, &toml).unwrap();
+    assert_eq!(resolved, canonical);
+}
+
+#[test]
+fn verify_and_resolve_errors_on_pre_351_server_with_url_mismatch() {
+    let (toml, cfg, _canonical, _v1) = sample_filter();
+    let url = "0".repeat(64);
+    let err = verify_and_resolve_hash(&url, None, None, &cfg, &toml).unwrap_err();
+    let msg = format!("{err:#}");
+    assert!(msg.contains("hash mismatch"), "wrong error: {msg}");
+    assert!(msg.contains("issue #350"), "should reference issue: {msg}");
+}
+
+#[test]
+fn command_pattern_to_install_path_single_word() {
+    let path = command_pattern_to_path("git");
+    assert_eq!(path, PathBuf::from("git.toml"));
+}
+
+#[test]
+fn command_pattern_to_install_path_two_words() {
+    let path = command_pattern_to_path("git push");
+    assert_eq!(path, PathBuf::from("git/push.toml"));
+}
+
+#[test]
+fn command_pattern_to_install_path_three_words_uses_first_two() {
+    let path = command_pattern_to_path("cargo test --workspace");
+    assert_eq!(path, PathBuf::from("cargo/test.toml"));
+}
+
+#[test]
+fn attribution_header_format() {
+    let header = attribution_header("alice", "deadbeef");
+    assert!(header.starts_with('#'), "header should start with #");
+    assert!(header.contains("@alice"), "header should mention author");
+    assert!(header.contains("deadbeef"), "header should contain hash");
+    assert!(
+        header.contains("https://tokf.net/filters/deadbeef"),
+        "header should have filter URL"
+    );
+}
+
+#[test]
+fn conflict_detected_without_force() {
+    let dir = tempfile::TempDir::new().unwrap();
+    let filter_path = dir.path().join("git").join("push.toml");
+    std::fs::create_dir_all(filter_path.parent().unwrap()).unwrap();
+    std::fs::write(&filter_path, b"command = \"git push\"\n").unwrap();
+
+    let force = false;
+    assert!(
+        filter_path.exists() && !force,
+        "conflict should be detected"
+    );
+}
+
+#[test]
+fn parse_filter_toml_extracts_command_pattern() {
+    let toml = r#"command = "git push""#;
+    let (pattern, _) = parse_filter_toml(toml).unwrap();
+    assert_eq!(pattern, "git push");
+}
+
+#[test]
+fn parse_filter_toml_strips_attribution_comments() {
+    let toml = "# Published by @alice · hash: abc123\ncommand = \"cargo build\"\n";
+    let (pattern, _) = parse_filter_toml(toml).unwrap();
+    assert_eq!(pattern, "cargo build");
+}
+
+#[test]
+fn parse_filter_toml_errors_on_invalid_toml() {
+    let result = parse_filter_toml("this is [[[not valid toml");
+    assert!(result.is_err(), "should error on invalid TOML");
+}
+
+#[test]
+fn parse_filter_toml_detects_lua() {
+    let toml = "command = \"my-tool\"\n[lua_script]\nlang = \"luau\"\nsource = \"return input\"\n";
+    let (_, config) = parse_filter_toml(toml).unwrap();
+    assert!(config.lua_script.is_some(), "should detect lua_script");
+}
+
+#[test]
+fn parse_filter_toml_no_lua_for_plain_filter() {
+    let toml = r#"command = "git push""#;
+    let (_, config) = parse_filter_toml(toml).unwrap();
+    assert!(config.lua_script.is_none(), "should not detect lua_script");
+}
+
+#[test]
+fn safe_test_filenames_accepted() {
+    assert!(is_safe_test_filename("basic.toml"));
+    assert!(is_safe_test_filename("my-test_case.toml"));
+    assert!(is_safe_test_filename("file123.toml"));
+}
+
+#[test]
+fn unsafe_test_filenames_rejected() {
+    assert!(!is_safe_test_filename(""), "empty name");
+    assert!(!is_safe_test_filename("."), "dot");
+    assert!(!is_safe_test_filename(".."), "double dot");
+    assert!(!is_safe_test_filename("../escape.toml"), "path traversal");
+    assert!(!is_safe_test_filename("sub/dir.toml"), "subdirectory");
+    assert!(!is_safe_test_filename("file name.toml"), "space");
+}
+
+#[test]
+fn unsafe_command_pattern_path_rejected() {
+    // A safe path should have all Normal components.
+    let safe_path = command_pattern_to_path("git push");
+    assert!(
+        safe_path
+            .components()
+            .all(|c| matches!(c, Component::Normal(_))),
+        "safe path should have all Normal components"
+    );
+}
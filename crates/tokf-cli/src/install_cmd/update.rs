@@ -0,0 +1,465 @@
+//! `tokf update`: bring filters installed with `tokf install` up to date with
+//! the registry.
+//!
+//! A filter is outdated when the registry has a newer, non-deprecated,
+//! non-yanked filter for exactly the same command pattern. "Newer" is judged
+//! by publish time against the installed version's (or, when the registry no
+//! longer lists that version, against when it was installed).
+//!
+//! An installed filter whose signature verified is pinned to that key: an
+//! upgrade that is unsigned, fails to verify or is signed with another key is
+//! refused, even with `--yes`. Reinstalling it with `tokf install --force`
+//! accepts the new key.
+
+use std::io::IsTerminal as _;
+use std::path::{Path, PathBuf};
+
+use tokf::doctor::render::{Colors, should_disable_color};
+use tokf::remote::filter_client::{self, FilterSummary, SearchQuery};
+use tokf::remote::http::Client;
+use tokf::remote::registry;
+use tokf::runtime::Runtime;
+use tokf_common::signing;
+
+use super::provenance::{self, Provenance};
+use super::signature::{self, SignatureStatus};
+use super::{InstallOpts, install_downloaded, parse_filter_toml, read_line};
+use crate::history_diff::render_unified_labeled;
+
+/// How many search results to scan for newer versions of one pattern.
+const SEARCH_LIMIT: usize = 50;
+
+/// Options for `tokf update`.
+#[derive(Debug, Clone, Copy)]
+pub struct UpdateOpts<'a> {
+    /// Names of installed filters to consider (e.g. `git/push`); all if empty.
+    pub filters: &'a [String],
+    /// Only report what is outdated.
+    pub check: bool,
+    /// Upgrade without asking (Lua filters still emit an audit warning).
+    pub yes: bool,
+}
+
+/// A filter installed from the registry.
+struct Installed {
+    name: String,
+    /// The filter file.
+    path: PathBuf,
+    global: bool,
+    provenance: Provenance,
+}
+
+/// Entry point for the `tokf update` subcommand.
+pub fn cmd_update(rt: &Runtime, opts: UpdateOpts<'_>) -> i32 {
+    match update(rt, opts) {
+        Ok(code) => code,
+        Err(e) => {
+            eprintln!("[tokf] error: {e:#}");
+            1
+        }
+    }
+}
+
+fn update(rt: &Runtime, opts: UpdateOpts<'_>) -> anyhow::Result<i32> {
+    let installed = installed_filters(rt, opts.filters)?;
+    if installed.is_empty() {
+        eprintln!("[tokf] no filters installed from the registry — see `tokf install`");
+        return Ok(0);
+    }
+    let registries = registry::all(rt);
+
+    let mut outdated = Vec::new();
+    for filter in installed {
+        let Some(source) = registries
+            .iter()
+            .find(|r| r.url == filter.provenance.registry)
+        else {
+            eprintln!(
+                "[tokf] skipping {}: installed from {}, which is not a configured registry",
+                filter.name, filter.provenance.registry
+            );
+            continue;
+        };
+        let client = source.client(rt)?;
+        if let Some(latest) = find_newer(&client, &filter)? {
+            outdated.push((filter, latest, client));
+        }
+    }
+
+    if outdated.is_empty() {
+        eprintln!("[tokf] all installed registry filters are up to date");
+        return Ok(0);
+    }
+    for (filter, latest, _) in &outdated {
+        println!(
+            "{}  {} → {}  @{}  ({})",
+            filter.name,
+            short_hash(&filter.provenance.hash),
+            short_hash(&latest.content_hash),
+            latest.author,
+            latest.created_at
+        );
+    }
+    if opts.check {
+        return Ok(1);
+    }
+
+    let mut refused = false;
+    for (filter, latest, client) in &outdated {
+        refused |= !upgrade(rt, client, filter, latest, opts.yes)?;
+    }
+    Ok(i32::from(refused))
+}
+
+/// Every recorded install under the project's `.tokf/` and the user config
+/// dir whose filter file is still there, limited to `names` when given.
+fn installed_filters(rt: &Runtime, names: &[String]) -> anyhow::Result<Vec<Installed>> {
+    let mut bases = Vec::new();
+    if let Some(cwd) = rt.cwd() {
+        bases.push((cwd.join(".tokf"), false));
+    }
+    if let Some(user) = rt.user_dir() {
+        bases.push((user, true));
+    }
+
+    let mut installed = Vec::new();
+    for (base, global) in bases {
+        for (name, provenance) in provenance::load(&base)?.filters {
+            let wanted = names.is_empty()
+                || names
+                    .iter()
+                    .any(|n| n.strip_suffix(".toml").unwrap_or(n) == name);
+            let path = filter_path(&base, &name);
+            if wanted && path.is_file() {
+                installed.push(Installed {
+                    name,
+                    path,
+                    global,
+                    provenance,
+                });
+            }
+        }
+    }
+    Ok(installed)
+}
+
+/// The newest registry filter that would replace `filter`, if any. Warns
+/// when the installed version itself has been yanked or deprecated.
+fn find_newer(client: &Client, filter: &Installed) -> anyhow::Result<Option<FilterSummary>> {
+    let content = std::fs::read_to_string(&filter.path)?;
+    let (pattern, _) = parse_filter_toml(&content)?;
+    // Yanked filters are included so the installed version is found even
+    // when it has been yanked.
+    let search = SearchQuery {
+        query: &pattern,
+        limit: SEARCH_LIMIT,
+        include_yanked: true,
+        ..SearchQuery::default()
+    };
+    let results = filter_client::search_filters_page(client, &search)?.filters;
+    if let Some(notice) = results
+        .iter()
+        .find(|r| r.content_hash == filter.provenance.hash)
+        .and_then(|r| r.retirement().notice())
+    {
+        eprintln!(
+            "[tokf] warning: installed filter {} ({}) is {notice}",
+            filter.name,
+            short_hash(&filter.provenance.hash)
+        );
+    }
+    Ok(pick_newer(results, &pattern, &filter.provenance))
+}
+
+/// Of `results`, the newest non-deprecated, non-yanked filter for exactly
+/// `pattern` that was published after the installed one.
+fn pick_newer(
+    results: Vec<FilterSummary>,
+    pattern: &str,
+    installed: &Provenance,
+) -> Option<FilterSummary> {
+    let baseline = results
+        .iter()
+        .find(|r| r.content_hash == installed.hash)
+        .map_or_else(|| installed.installed_at.clone(), |r| r.created_at.clone());
+    results
+        .into_iter()
+        .filter(|r| {
+            r.command_pattern == pattern
+                && r.deprecated_at.is_none()
+                && r.yanked_at.is_none()
+                && r.content_hash != installed.hash
+                && r.created_at > baseline
+        })
+        .max_by(|a, b| a.created_at.cmp(&b.created_at))
+}
+
+/// Show the diff from the installed filter to `latest` and install it once
+/// the user agrees. Returns `false` when the upgrade was refused because it
+/// is not signed by the installed filter's key.
+fn upgrade(
+    rt: &Runtime,
+    client: &Client,
+    filter: &Installed,
+    latest: &FilterSummary,
+    yes: bool,
+) -> anyhow::Result<bool> {
+    let downloaded = filter_client::download_filter(rt, client, &latest.content_hash)?;
+    if let Some(problem) = key_problem(
+        filter.provenance.signing_key.as_deref(),
+        &signature::status(&downloaded),
+    ) {
+        eprintln!(
+            "[tokf] WARNING: refusing to update {}: {problem}. If you trust it, \
+             reinstall with `tokf install {} --force{}`.",
+            filter.name,
+            latest.content_hash,
+            if filter.global { " --global" } else { "" }
+        );
+        return Ok(false);
+    }
+    let current = std::fs::read_to_string(&filter.path)?;
+
+    let colors = if std::io::stdout().is_terminal() && !should_disable_color(false) {
+        Colors::enabled()
+    } else {
+        Colors::disabled()
+    };
+    let new_label = format!("{} @{}", short_hash(&latest.content_hash), latest.author);
+    print!(
+        "{}",
+        render_unified_labeled(
+            strip_attribution(&current),
+            strip_attribution(&downloaded.filter_toml),
+            (&filter.path.display().to_string(), &new_label),
+            &colors,
+        )
+    );
+
+    let (_, config) = parse_filter_toml(&downloaded.filter_toml)?;
+    if !yes && !confirm(&filter.name, config.lua_script.is_some())? {
+        eprintln!("[tokf] kept {}", filter.name);
+        return Ok(true);
+    }
+
+    let opts = InstallOpts {
+        filter: &latest.content_hash,
+        registry: None,
+        global: filter.global,
+        force: true,
+        dry_run: false,
+        // The diff above was the review.
+        yes: true,
+    };
+    install_downloaded(rt, client, latest.author.clone(), &downloaded, opts)?;
+    Ok(true)
+}
+
+/// Why an upgrade with signature `status` can't replace a filter installed
+/// with a signature from `installed_key`, if it can't.
+fn key_problem(installed_key: Option<&str>, status: &SignatureStatus) -> Option<String> {
+    let installed_key = installed_key?;
+    let pinned = signing::fingerprint(installed_key);
+    match status {
+        SignatureStatus::Verified(key) if key.eq_ignore_ascii_case(installed_key) => None,
+        SignatureStatus::Verified(key) => Some(format!(
+            "the new version is signed with a different key ({} instead of {pinned})",
+            signing::fingerprint(key)
+        )),
+        SignatureStatus::Unsigned => Some(format!(
+            "the new version is unsigned, but the installed one was signed with key {pinned}"
+        )),
+        SignatureStatus::Invalid(e) => Some(format!(
+            "the new version's signature is invalid ({e}); the installed one was signed with key {pinned}"
+        )),
+    }
+}
+
+/// Ask whether to upgrade `name`; Lua filters need `yes` typed in full.
+fn confirm(name: &str, has_lua: bool) -> anyhow::Result<bool> {
+    if has_lua {
+        eprintln!("[tokf] WARNING: the new version contains embedded Lua code.");
+        eprint!("[tokf] Type 'yes' to update {name}: ");
+    } else {
+        eprint!("[tokf] Update {name}? [y/N] ");
+    }
+    let _ = std::io::Write::flush(&mut std::io::stderr());
+    let answer = read_line()?.trim().to_lowercase();
+    Ok(if has_lua {
+        answer == "yes"
+    } else {
+        matches!(answer.as_str(), "y" | "yes")
+    })
+}
+
+/// The filter TOML without the attribution header `tokf install` adds.
+fn strip_attribution(toml: &str) -> &str {
+    match toml.split_once('\n') {
+        Some((first, rest)) if first.starts_with("# Published by @") => rest,
+        _ => toml,
+    }
+}
+
+fn short_hash(hash: &str) -> &str {
+    let hex = hash.strip_prefix("v1:").unwrap_or(hash);
+    hex.get(..12).unwrap_or(hex)
+}
+
+/// Where the filter named `name` lives under the install base `base`.
+fn filter_path(base: &Path, name: &str) -> PathBuf {
+    base.join("filters").join(format!("{name}.toml"))
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    fn summary(hash: &str, pattern: &str, created_at: &str) -> FilterSummary {
+        serde_json::from_value(serde_json::json!({
+            "content_hash": hash,
+            "command_pattern": pattern,
+            "author": "alice",
+            "savings_pct": 50.0,
+            "total_commands": 10,
+            "created_at": created_at,
+        }))
+        .unwrap()
+    }
+
+    fn installed(hash: &str, installed_at: &str) -> Provenance {
+        Provenance {
+            hash: hash.to_string(),
+            author: "alice".to_string(),
+            registry: "https://api.tokf.net".to_string(),
+            installed_at: installed_at.to_string(),
+            signing_key: None,
+        }
+    }
+
+    #[test]
+    fn picks_the_newest_filter_for_the_same_pattern() {
+        let results = vec![
+            summary("aaa", "git push", "2026-01-01T00:00:00Z"),
+            summary("bbb", "git push", "2026-02-01T00:00:00Z"),
+            summary("ccc", "git push", "2026-03-01T00:00:00Z"),
+            summary("ddd", "git push --force", "2026-04-01T00:00:00Z"),
+        ];
+        let newer = pick_newer(
+            results,
+            "git push",
+            &installed("aaa", "2026-05-01T00:00:00Z"),
+        );
+        assert_eq!(newer.unwrap().content_hash, "ccc");
+    }
+
+    #[test]
+    fn nothing_newer_than_the_installed_version() {
+        let results = vec![
+            summary("aaa", "git push", "2026-01-01T00:00:00Z"),
+            summary("bbb", "git push", "2026-03-01T00:00:00Z"),
+        ];
+        let newer = pick_newer(
+            results,
+            "git push",
+            &installed("bbb", "2026-03-02T00:00:00Z"),
+        );
+        assert!(newer.is_none());
+    }
+
+    #[test]
+    fn unlisted_install_is_compared_by_install_time() {
+        let results = vec![
+            summary("aaa", "git push", "2026-01-01T00:00:00Z"),
+            summary("bbb", "git push", "2026-03-01T00:00:00Z"),
+        ];
+        let newer = pick_newer(
+            results,
+            "git push",
+            &installed("v1:zzz", "2026-02-01T00:00:00Z"),
+        );
+        assert_eq!(newer.unwrap().content_hash, "bbb");
+    }
+
+    #[test]
+    fn deprecated_filters_are_not_offered() {
+        let mut deprecated = summary("bbb", "git push", "2026-03-01T00:00:00Z");
+        deprecated.deprecated_at = Some("2026-03-02T00:00:00Z".to_string());
+        let newer = pick_newer(
+            vec![deprecated],
+            "git push",
+            &installed("aaa", "2026-01-01T00:00:00Z"),
+        );
+        assert!(newer.is_none());
+    }
+
+    #[test]
+    fn yanked_filters_are_not_offered() {
+        let mut yanked = summary("bbb", "git push", "2026-03-01T00:00:00Z");
+        yanked.yanked_at = Some("2026-03-02 00:00:00+00".to_string());
+        let newer = pick_newer(
+            vec![yanked],
+            "git push",
+            &installed("aaa", "2026-01-01T00:00:00Z"),
+        );
+        assert!(newer.is_none());
+    }
+
+    #[test]
+    fn upgrades_must_keep_the_installed_signing_key() {
+        use tokf_common::signing::{SignatureError, SigningKey};
+
+        let (key, _) = SigningKey::generate().unwrap();
+        let (other, _) = SigningKey::generate().unwrap();
+        let pinned = key.public_key_hex();
+        let same = SignatureStatus::Verified(pinned.to_uppercase());
+        assert_eq!(key_problem(Some(&pinned), &same), None);
+
+        let changed = SignatureStatus::Verified(other.public_key_hex());
+        let problem = key_problem(Some(&pinned), &changed).unwrap();
+        assert!(problem.contains("different key"), "{problem}");
+        assert!(
+            problem.contains(&signing::fingerprint(&pinned)),
+            "{problem}"
+        );
+        assert!(key_problem(Some(&pinned), &SignatureStatus::Unsigned).is_some());
+        let invalid = SignatureStatus::Invalid(SignatureError::Mismatch);
+        assert!(key_problem(Some(&pinned), &invalid).is_some());
+
+        // Filters installed unsigned have no key to keep.
+        assert_eq!(key_problem(None, &changed), None);
+        assert_eq!(key_problem(None, &SignatureStatus::Unsigned), None);
+    }
+
+    #[test]
+    fn strip_attribution_drops_only_the_header() {
+        let toml = "# Published by @alice · hash: abc\ncommand = \"git push\"\n";
+        assert_eq!(strip_attribution(toml), "command = \"git push\"\n");
+        let plain = "# a comment\ncommand = \"git push\"\n";
+        assert_eq!(strip_attribution(plain), plain);
+    }
+
+    #[test]
+    fn installed_filters_skips_removed_files_and_unwanted_names() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let rt = Runtime::builder().cwd(dir.path()).build();
+        let base = dir.path().join(".tokf");
+        for name in ["git/push", "cargo/build", "gone/away"] {
+            provenance::record(&base, name, installed("aaa", "2026-01-01T00:00:00Z")).unwrap();
+        }
+        for name in ["git/push", "cargo/build"] {
+            let path = filter_path(&base, name);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, "command = \"x\"\n").unwrap();
+        }
+
+        let all = installed_filters(&rt, &[]).unwrap();
+        let names: Vec<&str> = all.iter().map(|f| f.name.as_str()).collect();
+        assert_eq!(names, ["cargo/build", "git/push"]);
+        assert!(all.iter().all(|f| !f.global));
+
+        let one = installed_filters(&rt, &["git/push.toml".to_string()]).unwrap();
+        assert_eq!(one.len(), 1);
+        assert_eq!(one[0].name, "git/push");
+    }
+}
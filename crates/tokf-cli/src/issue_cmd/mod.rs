@@ -305,7 +305,7 @@ fn collect_filter_names(filters: &[tokf::config::ResolvedFilter]) -> FilterNames
     };
     for f in filters {
         let name = f.relative_path.with_extension("").display().to_string();
-        if f.priority == 0 {
+        if f.is_local() {
             names.local.push(name);
         } else if f.priority == u8::MAX {
             names.builtin.push(name);
@@ -1,6 +1,7 @@
 pub mod auth;
 pub mod baseline;
 pub mod config;
+pub mod daemon;
 pub mod discover;
 pub mod doctor;
 pub mod fs;
@@ -15,6 +16,8 @@ pub mod setup;
 pub mod skill;
 pub mod suite_discovery;
 pub mod sync_core;
+pub mod sync_reconcile;
+pub mod sync_retry;
 pub mod telemetry;
 pub mod tracking;
 
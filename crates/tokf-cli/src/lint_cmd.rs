@@ -0,0 +1,42 @@
+use std::path::Path;
+
+use tokf::config::extends::Origin;
+use tokf::config::lint::{self, Severity};
+
+/// `tokf lint <filter>`: print diagnostics as `path:line:column: severity:
+/// message [key]`, or as a JSON array with `--json`.
+///
+/// Exits 1 when any diagnostic is an error; warnings alone exit 0.
+pub fn cmd_lint(filter_path: &Path, json: bool) -> i32 {
+    let content = match std::fs::read_to_string(filter_path) {
+        Ok(c) => c,
+        Err(e) => {
+            eprintln!("[tokf] cannot read {}: {e}", filter_path.display());
+            return 1;
+        }
+    };
+    let diagnostics = lint::lint_filter(&content, &Origin::File(filter_path.to_path_buf()));
+
+    if json {
+        crate::output::print_json(&diagnostics);
+    } else if diagnostics.is_empty() {
+        eprintln!("[tokf] {}: no problems found", filter_path.display());
+    } else {
+        for d in &diagnostics {
+            let key = if d.key.is_empty() {
+                String::new()
+            } else {
+                format!(" [{}]", d.key)
+            };
+            println!(
+                "{}:{}:{}: {}: {}{key}",
+                filter_path.display(),
+                d.line,
+                d.column,
+                d.severity.as_str(),
+                d.message
+            );
+        }
+    }
+    i32::from(diagnostics.iter().any(|d| d.severity == Severity::Error))
+}
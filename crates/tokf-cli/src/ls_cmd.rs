@@ -0,0 +1,96 @@
+use serde::Serialize;
+use tokf::config::ResolvedFilter;
+use tokf::runtime::Runtime;
+
+use crate::resolve;
+
+/// A filter as `ls`, `which` and `show` report it with `--json`.
+#[derive(Serialize)]
+pub struct FilterInfo<'a> {
+    /// Relative path without `.toml`, as `tokf show` takes it.
+    pub name: String,
+    pub command: &'a str,
+    pub patterns: &'a [String],
+    pub description: Option<&'a str>,
+    pub source: String,
+    /// `local`, `user` or `built-in`.
+    pub priority: &'static str,
+    pub version: Option<&'a str>,
+    pub tags: &'a [String],
+    pub hash: &'a str,
+}
+
+impl<'a> From<&'a ResolvedFilter> for FilterInfo<'a> {
+    fn from(filter: &'a ResolvedFilter) -> Self {
+        Self {
+            name: filter
+                .relative_path
+                .with_extension("")
+                .display()
+                .to_string(),
+            command: filter.config.command.first(),
+            patterns: filter.config.command.patterns(),
+            description: filter.config.description.as_deref(),
+            source: filter.source_path.display().to_string(),
+            priority: filter.priority_label(),
+            version: filter.config.version.as_deref(),
+            tags: &filter.config.tags,
+            hash: &filter.hash,
+        }
+    }
+}
+
+// Note: cmd_ls and cmd_which always use the cache. The --no-cache flag
+// only affects `tokf run`. Pass --no-cache to `tokf run` if you need uncached resolution.
+pub fn cmd_ls(rt: &Runtime, verbose: bool, json: bool) -> i32 {
+    let Ok(filters) = resolve::discover_filters(rt, false) else {
+        eprintln!("[tokf] error: failed to discover filters");
+        return 1;
+    };
+
+    if json {
+        let infos: Vec<FilterInfo<'_>> = filters.iter().map(FilterInfo::from).collect();
+        crate::output::print_json(&infos);
+        return 0;
+    }
+
+    for filter in &filters {
+        // Display: relative path without .toml extension  →  command  (description)
+        let display_name = filter
+            .relative_path
+            .with_extension("")
+            .display()
+            .to_string();
+        let desc_suffix = filter
+            .config
+            .description
+            .as_deref()
+            .map_or(String::new(), |d| format!("  ({d})"));
+        println!(
+            "{display_name}  \u{2192}  {}{desc_suffix}",
+            filter.config.command.first()
+        );
+
+        if verbose {
+            eprintln!(
+                "[tokf]   source: {}  [{}]",
+                filter.source_path.display(),
+                filter.priority_label()
+            );
+            if let Some(version) = &filter.config.version {
+                eprintln!("[tokf]   version: {version}");
+            }
+            if !filter.config.tags.is_empty() {
+                eprintln!("[tokf]   tags: {}", filter.config.tags.join(", "));
+            }
+            let patterns = filter.config.command.patterns();
+            if patterns.len() > 1 {
+                for p in patterns {
+                    eprintln!("[tokf]     pattern: \"{p}\"");
+                }
+            }
+        }
+    }
+
+    0
+}
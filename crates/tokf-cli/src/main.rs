@@ -1,28 +1,47 @@
 mod auth_cmd;
+mod auth_keys_cmd;
+mod auth_token_cmd;
 #[cfg(feature = "stdlib-publish")]
 mod backfill_cmd;
+mod bench_cmd;
 mod cache_cmd;
 mod cli_args;
 mod commands;
 mod completions_cmd;
 mod config_cmd;
+mod daemon_cmd;
+mod db_cmd;
 mod discover_cmd;
 mod doctor_cmd;
 mod eject_cmd;
+mod explain_cmd;
+mod fixture_cmd;
 mod gain;
+mod gain_cost;
+mod gain_export;
 mod gain_render;
 mod generic;
 mod history_cmd;
+mod history_diff;
+mod history_refilter;
 mod info_cmd;
+mod init_cmd;
 mod install_cmd;
 mod issue_cmd;
+mod lint_cmd;
+mod ls_cmd;
 mod marker;
+mod mcp_cmd;
+mod new_cmd;
 mod output;
 mod publish_cmd;
 #[cfg(feature = "stdlib-publish")]
 mod publish_stdlib_cmd;
 mod remote_cmd;
 mod resolve;
+mod run_cmd;
+mod run_envelope;
+mod run_tee;
 mod search_cmd;
 mod setup_cmd;
 mod shell;
@@ -31,13 +50,17 @@ mod sync_cmd;
 mod telemetry_cmd;
 // pub(crate): accessed by install_cmd::run_verify
 pub(crate) mod verify_cmd;
+mod watch_cmd;
 mod which_cmd;
 
 use std::path::Path;
 
 use clap::Parser;
 
-use cli_args::{AuthAction, Cli, Commands, RemoteAction, SkillAction, TelemetryAction};
+use cli_args::{
+    AuthAction, Cli, Commands, DaemonAction, KeysAction, McpAction, RemoteAction, SkillAction,
+    TelemetryAction, TokenAction,
+};
 use commands::HookAction;
 
 use tokf::telemetry;
@@ -47,8 +70,8 @@ use tokf::runtime::Runtime;
 #[allow(clippy::too_many_lines)]
 fn main() {
     use commands::{
-        cmd_apply, cmd_check, cmd_hook_handle, cmd_hook_install, cmd_ls, cmd_rewrite, cmd_run,
-        cmd_skill_install, or_exit,
+        cmd_apply, cmd_check, cmd_hook_handle, cmd_hook_install, cmd_hook_status,
+        cmd_hook_uninstall, cmd_rewrite, cmd_skill_install, or_exit,
     };
     use which_cmd::cmd_which;
 
@@ -107,38 +130,90 @@ fn main() {
             command_args,
             baseline_pipe,
             prefer_less,
-        } => or_exit(cmd_run(
+            pty,
+            stdin,
+            format,
+            tee_raw,
+        } => or_exit(run_cmd::cmd_run(
             &rt,
-            commands::RunRequest {
+            run_cmd::RunRequest {
                 command_args,
                 baseline_pipe: baseline_pipe.as_deref(),
                 prefer_less: *prefer_less,
+                pty: *pty,
+                stdin: stdin.map(Into::into),
+                format: *format,
+                tee_raw: tee_raw.as_deref(),
             },
             &cli,
             reporter.as_ref(),
         )),
+        Commands::Watch(args) => or_exit(watch_cmd::cmd_watch(&rt, args, &cli, reporter.as_ref())),
         Commands::Completions { shell } => completions_cmd::cmd_completions(*shell),
+        Commands::CompleteFilters => completions_cmd::cmd_complete_filters(&rt),
         Commands::Check { filter_path } => cmd_check(Path::new(filter_path)),
+        Commands::Lint { filter_path, json } => lint_cmd::cmd_lint(Path::new(filter_path), *json),
         Commands::Apply {
             filter_path,
             fixture_path,
             exit_code,
+            trace,
         } => or_exit(cmd_apply(
             Path::new(filter_path),
             Path::new(fixture_path),
             *exit_code,
+            *trace,
             &cli,
         )),
-        Commands::Ls => cmd_ls(&rt, cli.verbose),
+        Commands::Ls { json } => ls_cmd::cmd_ls(&rt, cli.verbose, *json),
         Commands::Rewrite { command } => cmd_rewrite(&rt, command, cli.verbose),
-        Commands::Which { command } => cmd_which(&rt, command, cli.verbose),
-        Commands::Show { filter, hash } => show_cmd::cmd_show(&rt, filter, *hash),
-        Commands::Eject { filter, global } => {
-            eject_cmd::cmd_eject(&rt, filter, *global, cli.no_cache)
+        Commands::Which { command, json } => cmd_which(&rt, command, cli.verbose, *json),
+        Commands::Show {
+            filter,
+            hash,
+            resolved,
+            json,
+        } => show_cmd::cmd_show(
+            &rt,
+            filter,
+            show_cmd::ShowMode::from_flags(*hash, *resolved, *json),
+        ),
+        Commands::Init { hook, force } => init_cmd::cmd_init(&rt, *hook, *force),
+        Commands::New(args) => new_cmd::cmd_new(&rt, args),
+        Commands::Fixture { action } => fixture_cmd::run_fixture_action(&rt, action),
+        Commands::Eject {
+            filter,
+            global,
+            diff,
+            update,
+            no_color,
+        } => {
+            let mode = if *diff {
+                eject_cmd::EjectMode::Diff {
+                    no_color: *no_color,
+                }
+            } else if *update {
+                eject_cmd::EjectMode::Update
+            } else {
+                eject_cmd::EjectMode::Copy { global: *global }
+            };
+            eject_cmd::cmd_eject(&rt, filter, mode, cli.no_cache)
         }
         Commands::Hook { action } => match action {
-            HookAction::Handle { format } => {
-                cmd_hook_handle(&rt, format, cli.no_cache, cli.no_mask_exit_code)
+            HookAction::Handle { format, adapter } => cmd_hook_handle(
+                &rt,
+                format,
+                adapter.as_deref(),
+                cli.no_cache,
+                cli.no_mask_exit_code,
+            ),
+            HookAction::Feedback => {
+                tokf::hook::feedback::handle(&rt);
+                0
+            }
+            HookAction::SessionStart => {
+                tokf::hook::session_start::handle(&rt);
+                0
             }
             HookAction::Install {
                 global,
@@ -146,31 +221,58 @@ fn main() {
                 path,
                 no_context,
             } => cmd_hook_install(&rt, *global, tool, path.as_deref(), !no_context),
+            HookAction::Uninstall { global, tool } => cmd_hook_uninstall(&rt, *global, tool),
+            HookAction::Status { global } => cmd_hook_status(&rt, *global),
         },
         Commands::Skill { action } => match action {
             SkillAction::Install { global } => cmd_skill_install(&rt, *global),
         },
         Commands::Cache { action } => cache_cmd::run_cache_action(&rt, action),
         Commands::Config { action } => config_cmd::run_config_action(&rt, action),
+        Commands::Db { action } => or_exit(db_cmd::run_db_action(&rt, action)),
         Commands::Gain {
+            action,
             daily,
             by_filter,
+            by_project,
+            by_command,
+            confusion,
+            cost,
+            model,
             json,
             remote,
             top,
             no_color,
+            since,
+            until,
+            period,
         } => {
-            let opts = gain::GainOpts {
-                daily: *daily,
-                by_filter: *by_filter,
-                json: *json,
-                top: *top,
-                no_color: *no_color,
+            let range = gain::RangeOpts {
+                since: since.clone(),
+                until: until.clone(),
+                period: period.map(Into::into),
             };
-            if *remote {
-                gain::cmd_gain_remote(&rt, opts)
+            if let Some(commands::GainAction::Export(args)) = action {
+                gain_export::cmd_gain_export(&rt, args, range)
             } else {
-                gain::cmd_gain(&rt, opts)
+                let opts = gain::GainOpts {
+                    daily: *daily,
+                    by_filter: *by_filter,
+                    by_project: *by_project,
+                    by_command: *by_command,
+                    confusion: *confusion,
+                    cost: *cost,
+                    model: model.clone(),
+                    json: *json,
+                    top: *top,
+                    no_color: *no_color,
+                    range,
+                };
+                if *remote {
+                    gain::cmd_gain_remote(&rt, opts)
+                } else {
+                    gain::cmd_gain(&rt, opts)
+                }
             }
         }
         Commands::Verify {
@@ -189,6 +291,7 @@ fn main() {
             scope.as_ref(),
             *safety,
         ),
+        Commands::Bench(args) => bench_cmd::cmd_bench(&rt, args),
         Commands::Info { json } => info_cmd::cmd_info(&rt, *json),
         Commands::Issue(args) => issue_cmd::cmd_issue(&rt, args),
         Commands::Auth { action } => or_exit(match action {
@@ -196,6 +299,22 @@ fn main() {
             AuthAction::Logout => auth_cmd::cmd_auth_logout(&rt),
             AuthAction::Status => auth_cmd::cmd_auth_status(&rt),
             AuthAction::DeleteAccount => auth_cmd::cmd_auth_delete_account(&rt),
+            AuthAction::Keys { action } => match action {
+                KeysAction::Generate { force } => auth_keys_cmd::cmd_keys_generate(&rt, *force),
+                KeysAction::Register => auth_keys_cmd::cmd_keys_register(&rt),
+                KeysAction::Show => auth_keys_cmd::cmd_keys_show(&rt),
+                KeysAction::List => auth_keys_cmd::cmd_keys_list(&rt),
+                KeysAction::Revoke { key } => auth_keys_cmd::cmd_keys_revoke(&rt, key),
+            },
+            AuthAction::Token { action } => match action {
+                TokenAction::Create {
+                    name,
+                    scopes,
+                    expires_in_days,
+                } => auth_token_cmd::cmd_token_create(&rt, name, scopes, *expires_in_days),
+                TokenAction::List => auth_token_cmd::cmd_token_list(&rt),
+                TokenAction::Revoke { id } => auth_token_cmd::cmd_token_revoke(&rt, *id),
+            },
         }),
         Commands::Remote { action } => or_exit(match action {
             RemoteAction::Setup => remote_cmd::cmd_remote_setup(&rt),
@@ -205,16 +324,38 @@ fn main() {
         }),
         Commands::History { action } => or_exit(history_cmd::dispatch_history(&rt, action)),
         Commands::Raw { target } => or_exit(history_cmd::dispatch_raw(&rt, target)),
-        Commands::Sync { status } => or_exit(sync_cmd::cmd_sync(&rt, *status)),
+        Commands::Explain { target } => or_exit(explain_cmd::cmd_explain(&rt, target)),
+        Commands::Sync {
+            action: Some(cli_args::SyncAction::ResetMachine),
+            ..
+        } => or_exit(sync_cmd::cmd_sync_reset_machine(&rt)),
+        Commands::Sync { action, status } => or_exit(sync_cmd::cmd_sync(
+            &rt,
+            *status || matches!(action, Some(cli_args::SyncAction::Status)),
+        )),
         Commands::Publish {
             filter,
             dry_run,
             update_tests,
-        } => publish_cmd::cmd_publish(&rt, filter, *dry_run, *update_tests),
-        Commands::Search { query, limit, json } => {
-            let joined = query.join(" ");
-            search_cmd::cmd_search(&rt, &joined, *limit, *json)
-        }
+            registry,
+            accept_license,
+            org,
+            private,
+        } => publish_cmd::cmd_publish(
+            &rt,
+            filter,
+            publish_cmd::PublishOpts {
+                dry_run: *dry_run,
+                update_tests: *update_tests,
+                registry: registry.as_deref(),
+                accept_license: *accept_license,
+                namespace: tokf::remote::publish_client::Namespace {
+                    org: org.as_deref(),
+                    private: *private,
+                },
+            },
+        ),
+        Commands::Search { args } => search_cmd::cmd_search(&rt, args),
         #[cfg(feature = "stdlib-publish")]
         Commands::PublishStdlib { auth, dry_run } => {
             publish_stdlib_cmd::cmd_publish_stdlib(&rt, &auth.registry_url, &auth.token, *dry_run)
@@ -232,6 +373,14 @@ fn main() {
                 telemetry_cmd::cmd_telemetry_status(&rt, *check, cli.verbose)
             }
         }),
+        Commands::Mcp { action } => match action {
+            McpAction::Serve => mcp_cmd::cmd_mcp_serve(&rt),
+        },
+        Commands::Daemon { action } => match action {
+            DaemonAction::Run => or_exit(daemon_cmd::cmd_daemon_run(&rt)),
+            DaemonAction::Stop => daemon_cmd::cmd_daemon_stop(&rt),
+            DaemonAction::Status => daemon_cmd::cmd_daemon_status(&rt),
+        },
         Commands::Discover {
             project,
             all,
@@ -266,6 +415,8 @@ fn main() {
                 json: args.json,
                 no_color: args.no_color,
                 no_cache: cli.no_cache,
+                setup: args.setup,
+                offline: args.offline,
             },
         ),
         Commands::Err {
@@ -313,20 +464,35 @@ fn main() {
         Commands::Setup { refresh } => setup_cmd::cmd_setup(&rt, *refresh),
         Commands::Install {
             filter,
-            local,
+            global,
+            local: _,
             force,
             dry_run,
             yes,
+            registry,
         } => install_cmd::cmd_install(
             &rt,
             install_cmd::InstallOpts {
-                filter,
-                local: *local,
+                filter: &filter.join(" "),
+                registry: registry.as_deref(),
+                global: *global,
                 force: *force,
                 dry_run: *dry_run,
                 yes: *yes,
             },
         ),
+        Commands::Update {
+            filters,
+            check,
+            yes,
+        } => install_cmd::cmd_update(
+            &rt,
+            install_cmd::UpdateOpts {
+                filters,
+                check: *check,
+                yes: *yes,
+            },
+        ),
     };
     let flushed = reporter.shutdown();
     if cli.verbose && reporter.endpoint_description().is_some() {
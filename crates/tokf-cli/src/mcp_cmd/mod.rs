@@ -0,0 +1,118 @@
+//! `tokf mcp serve`: tokf as a Model Context Protocol server over stdio.
+//!
+//! Messages are JSON-RPC 2.0, one per line. The server answers `initialize`,
+//! `ping`, `tools/list` and `tools/call`; notifications get no reply.
+
+mod tools;
+
+use std::io::{BufRead, Write};
+
+use serde_json::{Value, json};
+
+use tokf::runtime::Runtime;
+
+/// Protocol revisions this server speaks, newest first.
+const PROTOCOL_VERSIONS: &[&str] = &["2025-06-18", "2025-03-26", "2024-11-05"];
+
+const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+
+/// A JSON-RPC error: code and message.
+#[derive(Debug, PartialEq, Eq)]
+struct RpcError(i64, String);
+
+/// `tokf mcp serve`: answer requests on stdin until it closes.
+pub fn cmd_mcp_serve(rt: &Runtime) -> i32 {
+    let stdin = std::io::stdin();
+    let stdout = std::io::stdout();
+    match serve(rt, stdin.lock(), stdout.lock()) {
+        Ok(()) => 0,
+        Err(e) => {
+            eprintln!("[tokf] mcp: {e:#}");
+            1
+        }
+    }
+}
+
+/// Read one message per line from `input` and write replies to `output`.
+fn serve(rt: &Runtime, input: impl BufRead, mut output: impl Write) -> anyhow::Result<()> {
+    for line in input.lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        if let Some(reply) = handle_line(rt, &line) {
+            writeln!(output, "{reply}")?;
+            output.flush()?;
+        }
+    }
+    Ok(())
+}
+
+/// The reply to one line, or `None` for a notification.
+fn handle_line(rt: &Runtime, line: &str) -> Option<Value> {
+    let Ok(message) = serde_json::from_str::<Value>(line) else {
+        return Some(error_reply(
+            &Value::Null,
+            RpcError(PARSE_ERROR, "parse error".to_string()),
+        ));
+    };
+    let id = message.get("id")?.clone();
+    let Some(method) = message.get("method").and_then(Value::as_str) else {
+        return Some(error_reply(
+            &id,
+            RpcError(INVALID_REQUEST, "missing method".to_string()),
+        ));
+    };
+    let params = message.get("params").cloned().unwrap_or(Value::Null);
+    Some(match dispatch(rt, method, &params) {
+        Ok(result) => json!({"jsonrpc": "2.0", "id": id, "result": result}),
+        Err(e) => error_reply(&id, e),
+    })
+}
+
+fn dispatch(rt: &Runtime, method: &str, params: &Value) -> Result<Value, RpcError> {
+    match method {
+        "initialize" => Ok(initialize(params)),
+        "ping" => Ok(json!({})),
+        "tools/list" => Ok(json!({"tools": tools::list()})),
+        "tools/call" => {
+            let name = params
+                .get("name")
+                .and_then(Value::as_str)
+                .ok_or_else(|| RpcError(INVALID_PARAMS, "missing tool name".to_string()))?;
+            let args = params
+                .get("arguments")
+                .cloned()
+                .unwrap_or_else(|| json!({}));
+            tools::call(rt, name, &args)
+        }
+        _ => Err(RpcError(
+            METHOD_NOT_FOUND,
+            format!("method not found: {method}"),
+        )),
+    }
+}
+
+/// Agree on the client's protocol revision when we speak it, else offer ours.
+fn initialize(params: &Value) -> Value {
+    let requested = params.get("protocolVersion").and_then(Value::as_str);
+    let version = requested
+        .filter(|v| PROTOCOL_VERSIONS.contains(v))
+        .unwrap_or(PROTOCOL_VERSIONS[0]);
+    json!({
+        "protocolVersion": version,
+        "capabilities": {"tools": {}},
+        "serverInfo": {"name": "tokf", "version": env!("CARGO_PKG_VERSION")},
+    })
+}
+
+fn error_reply(id: &Value, RpcError(code, message): RpcError) -> Value {
+    json!({"jsonrpc": "2.0", "id": id, "error": {"code": code, "message": message}})
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests;
@@ -0,0 +1,140 @@
+use super::*;
+
+fn request(method: &str, params: &Value) -> String {
+    json!({"jsonrpc": "2.0", "id": 1, "method": method, "params": params}).to_string()
+}
+
+fn call_tool(name: &str, arguments: &Value) -> Value {
+    let rt = Runtime::isolated();
+    let line = request("tools/call", &json!({"name": name, "arguments": arguments}));
+    handle_line(&rt, &line).unwrap()
+}
+
+#[test]
+fn initialize_agrees_on_a_known_protocol_version() {
+    let rt = Runtime::isolated();
+    let reply = handle_line(
+        &rt,
+        &request("initialize", &json!({"protocolVersion": "2024-11-05"})),
+    )
+    .unwrap();
+    assert_eq!(reply["id"], 1);
+    assert_eq!(reply["result"]["protocolVersion"], "2024-11-05");
+    assert_eq!(reply["result"]["serverInfo"]["name"], "tokf");
+    assert!(reply["result"]["capabilities"]["tools"].is_object());
+
+    let reply = handle_line(
+        &rt,
+        &request("initialize", &json!({"protocolVersion": "1999-01-01"})),
+    )
+    .unwrap();
+    assert_eq!(reply["result"]["protocolVersion"], PROTOCOL_VERSIONS[0]);
+}
+
+#[test]
+fn notifications_get_no_reply() {
+    let rt = Runtime::isolated();
+    let line = json!({"jsonrpc": "2.0", "method": "notifications/initialized"}).to_string();
+    assert!(handle_line(&rt, &line).is_none());
+}
+
+#[test]
+fn malformed_and_unknown_requests_are_errors() {
+    let rt = Runtime::isolated();
+    let reply = handle_line(&rt, "{not json").unwrap();
+    assert_eq!(reply["error"]["code"], PARSE_ERROR);
+    assert!(reply["id"].is_null());
+
+    let reply = handle_line(&rt, &request("resources/list", &json!({}))).unwrap();
+    assert_eq!(reply["error"]["code"], METHOD_NOT_FOUND);
+
+    let reply = call_tool("format_disk", &json!({}));
+    assert_eq!(reply["error"]["code"], INVALID_PARAMS);
+
+    let reply = call_tool("which_filter", &json!({"command": 42}));
+    assert_eq!(reply["error"]["code"], INVALID_PARAMS);
+}
+
+#[test]
+fn tools_list_names_every_tool() {
+    let rt = Runtime::isolated();
+    let reply = handle_line(&rt, &request("tools/list", &json!({}))).unwrap();
+    let names: Vec<&str> = reply["result"]["tools"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|t| t["name"].as_str().unwrap())
+        .collect();
+    assert_eq!(names, ["run_filtered", "history_show", "which_filter"]);
+}
+
+#[test]
+fn which_filter_reports_an_unmatched_command() {
+    let reply = call_tool("which_filter", &json!({"command": "no-such-tool --flag"}));
+    let result = &reply["result"];
+    assert_eq!(result["isError"], false);
+    let which: Value =
+        serde_json::from_str(result["content"][0]["text"].as_str().unwrap()).unwrap();
+    assert_eq!(which["matched"], false);
+    assert_eq!(which["command"], "no-such-tool --flag");
+}
+
+#[cfg(unix)]
+#[test]
+fn run_filtered_keeps_the_exit_code() {
+    let reply = call_tool("run_filtered", &json!({"command": "echo hi"}));
+    assert_eq!(reply["result"]["isError"], false);
+    assert_eq!(reply["result"]["content"][0]["text"], "hi");
+
+    let reply = call_tool("run_filtered", &json!({"command": "echo oops >&2; exit 3"}));
+    assert_eq!(reply["result"]["isError"], true);
+    assert_eq!(
+        reply["result"]["content"][0]["text"],
+        "oops\n[exit code: 3]"
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn run_filtered_kills_a_command_at_its_timeout() {
+    let started = std::time::Instant::now();
+    let reply = call_tool(
+        "run_filtered",
+        &json!({"command": "echo started; sleep 30", "timeout_secs": 1}),
+    );
+    assert!(started.elapsed() < std::time::Duration::from_secs(20));
+    assert_eq!(reply["result"]["isError"], true);
+    assert_eq!(
+        reply["result"]["content"][0]["text"],
+        "started\n[timed out after 1s]\n[exit code: 124]"
+    );
+
+    let reply = call_tool(
+        "run_filtered",
+        &json!({"command": "true", "timeout_secs": 0}),
+    );
+    assert_eq!(reply["error"]["code"], INVALID_PARAMS);
+}
+
+#[test]
+fn serve_answers_requests_line_by_line() {
+    let rt = Runtime::isolated();
+    let input = format!(
+        "{}\n\n{}\n{}\n",
+        request("initialize", &json!({})),
+        json!({"jsonrpc": "2.0", "method": "notifications/initialized"}),
+        json!({"jsonrpc": "2.0", "id": "b", "method": "ping"}),
+    );
+    let mut output = Vec::new();
+    serve(&rt, input.as_bytes(), &mut output).unwrap();
+    let replies: Vec<Value> = String::from_utf8(output)
+        .unwrap()
+        .lines()
+        .map(|l| serde_json::from_str(l).unwrap())
+        .collect();
+    assert_eq!(replies.len(), 2);
+    assert_eq!(
+        replies[1],
+        json!({"jsonrpc": "2.0", "id": "b", "result": {}})
+    );
+}
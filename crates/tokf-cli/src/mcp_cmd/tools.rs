@@ -0,0 +1,172 @@
+//! The tools `tokf mcp serve` offers: `run_filtered`, `history_show` and
+//! `which_filter`.
+
+use std::fmt::Write;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use serde_json::{Value, json};
+
+use tokf::config::types::StdinMode;
+use tokf::history;
+use tokf::rewrite::{self, types::RewriteOptions};
+use tokf::runner::{self, ExecOptions};
+use tokf::runtime::Runtime;
+
+use super::{INVALID_PARAMS, RpcError};
+use crate::{history_cmd, resolve, which_cmd};
+
+/// How long `run_filtered` lets a command run when the call sets no
+/// `timeout_secs`.
+const DEFAULT_RUN_TIMEOUT: Duration = Duration::from_mins(10);
+
+/// Tool descriptions for `tools/list`.
+pub(super) fn list() -> Value {
+    json!([
+        {
+            "name": "run_filtered",
+            "description": "Run a shell command through tokf and return its filtered output. \
+                            Commands without a filter run unchanged.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "command": {"type": "string", "description": "The shell command to run"},
+                    "timeout_secs": {
+                        "type": "integer",
+                        "minimum": 1,
+                        "description": "Kill the command after this many seconds (default 600)"
+                    }
+                },
+                "required": ["command"]
+            }
+        },
+        {
+            "name": "history_show",
+            "description": "Show a recorded tokf run: the command, its exit code and its \
+                            raw and filtered output. Defaults to the latest run in this project.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "id": {"type": "integer", "description": "History entry ID"},
+                    "raw": {"type": "boolean", "description": "Only the unfiltered output"}
+                }
+            }
+        },
+        {
+            "name": "which_filter",
+            "description": "Report which tokf filter, if any, matches a command.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "command": {"type": "string", "description": "The command to look up"}
+                },
+                "required": ["command"]
+            }
+        }
+    ])
+}
+
+/// Run tool `name`. A failure of the tool itself is a result with `isError`;
+/// unknown tools and bad arguments are protocol errors.
+pub(super) fn call(rt: &Runtime, name: &str, args: &Value) -> Result<Value, RpcError> {
+    let outcome = match name {
+        "run_filtered" => run_filtered(rt, str_arg(args, "command")?, timeout_arg(args)?),
+        "history_show" => history_show(rt, args),
+        "which_filter" => which_filter(rt, str_arg(args, "command")?),
+        _ => return Err(RpcError(INVALID_PARAMS, format!("unknown tool: {name}"))),
+    };
+    let (text, is_error) = outcome.unwrap_or_else(|e| (format!("{e:#}"), true));
+    Ok(json!({
+        "content": [{"type": "text", "text": text}],
+        "isError": is_error,
+    }))
+}
+
+fn str_arg<'a>(args: &'a Value, key: &str) -> Result<&'a str, RpcError> {
+    args.get(key)
+        .and_then(Value::as_str)
+        .ok_or_else(|| RpcError(INVALID_PARAMS, format!("`{key}` must be a string")))
+}
+
+fn timeout_arg(args: &Value) -> Result<Duration, RpcError> {
+    args.get("timeout_secs")
+        .map_or(Ok(DEFAULT_RUN_TIMEOUT), |secs| {
+            secs.as_u64()
+                .filter(|&s| s > 0)
+                .map(Duration::from_secs)
+                .ok_or_else(|| {
+                    let message = "`timeout_secs` must be a positive integer".to_string();
+                    RpcError(INVALID_PARAMS, message)
+                })
+        })
+}
+
+/// Rewrite `command` as the hooks do and run it in the system shell, killing
+/// it and everything it started after `timeout`. The real exit code is kept;
+/// a non-zero one marks the result as an error.
+fn run_filtered(rt: &Runtime, command: &str, timeout: Duration) -> anyhow::Result<(String, bool)> {
+    let options = RewriteOptions {
+        no_mask_exit_code: true,
+    };
+    let rewritten = rewrite::rewrite_with_options(rt, command, false, &options);
+    let path = path_with_tokf(rt)?;
+    let opts = ExecOptions {
+        timeout: Some(timeout),
+        cwd: rt.cwd(),
+        // stdin carries the protocol, so the command must not read from it.
+        stdin: StdinMode::Null,
+        ..ExecOptions::default()
+    };
+    let run = runner::execute_shell_with_env(&rewritten, &[], &[("PATH", &path)], opts)?;
+    let mut text = run.result.combined;
+    if let Some(limit) = run.timed_out {
+        let _ = write!(text, "\n[timed out after {}s]", limit.as_secs());
+    }
+    let code = run.result.exit_code;
+    if code != 0 {
+        let _ = write!(text, "\n[exit code: {code}]");
+    }
+    Ok((text, code != 0))
+}
+
+/// `PATH` with this binary's directory first, so the `tokf run` in a
+/// rewritten command is this tokf.
+fn path_with_tokf(rt: &Runtime) -> anyhow::Result<String> {
+    let original = rt
+        .original_path()
+        .map(std::borrow::ToOwned::to_owned)
+        .or_else(|| std::env::var("PATH").ok())
+        .unwrap_or_default();
+    let exe_dir = std::env::current_exe()?
+        .parent()
+        .map(PathBuf::from)
+        .unwrap_or_default();
+    let dirs = std::iter::once(exe_dir).chain(std::env::split_paths(&original));
+    std::env::join_paths(dirs)?
+        .into_string()
+        .map_err(|_| anyhow::anyhow!("PATH is not valid UTF-8"))
+}
+
+fn history_show(rt: &Runtime, args: &Value) -> anyhow::Result<(String, bool)> {
+    let conn = history_cmd::open_history_conn(rt)?;
+    let entry = match args.get("id").and_then(Value::as_i64) {
+        Some(id) => history::get_history_entry(&conn, id)?,
+        None => history::get_latest_entry(&conn, Some(&history::current_project(rt)))?,
+    };
+    let Some(entry) = entry else {
+        return Ok(("no history entry found".to_string(), true));
+    };
+    let raw = args.get("raw").and_then(Value::as_bool).unwrap_or(false);
+    let text = if raw {
+        entry.raw_output
+    } else {
+        history_cmd::entry_detail(&entry)
+    };
+    Ok((text, false))
+}
+
+fn which_filter(rt: &Runtime, command: &str) -> anyhow::Result<(String, bool)> {
+    let filters = resolve::discover_filters(rt, false)?;
+    let which = which_cmd::resolve_which(rt, &filters, command, false);
+    Ok((serde_json::to_string_pretty(&which)?, false))
+}
@@ -0,0 +1,129 @@
+//! The filter `tokf new` proposes: line groups to skip, rendered as TOML.
+
+use std::fmt::Write as _;
+
+/// Lines of the sample output that start with the same word.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LineGroup {
+    /// Regex matching every line of the group, for `skip`.
+    pub pattern: String,
+    pub count: usize,
+    /// The first line of the group.
+    pub example: String,
+    /// Some line mentions an error, failure or warning.
+    pub noteworthy: bool,
+}
+
+impl LineGroup {
+    /// Groups worth dropping by default: blank lines, and lines that repeat
+    /// (progress, per-file notices) without reporting a problem.
+    pub fn suggest_skip(&self) -> bool {
+        !self.noteworthy && (self.pattern == BLANK || self.count >= 3)
+    }
+}
+
+const BLANK: &str = r"^\s*$";
+
+/// Group the lines of `output` by their leading word, in order of first
+/// appearance. Digits in that word are generalized, so `[1/20]` and `[2/20]`
+/// fall in the same group.
+pub fn group_lines(output: &str) -> Vec<LineGroup> {
+    let mut groups: Vec<LineGroup> = Vec::new();
+    for line in output.lines() {
+        let pattern = line_pattern(line);
+        let noteworthy = is_noteworthy(line);
+        if let Some(group) = groups.iter_mut().find(|g| g.pattern == pattern) {
+            group.count += 1;
+            group.noteworthy |= noteworthy;
+        } else {
+            groups.push(LineGroup {
+                pattern,
+                count: 1,
+                example: line.to_string(),
+                noteworthy,
+            });
+        }
+    }
+    groups
+}
+
+fn line_pattern(line: &str) -> String {
+    let Some(word) = line.split_whitespace().next() else {
+        return BLANK.to_string();
+    };
+    let mut pattern = String::from(r"^\s*");
+    let mut in_digits = false;
+    for c in regex::escape(word).chars() {
+        if c.is_ascii_digit() {
+            if !in_digits {
+                pattern.push_str(r"\d+");
+            }
+            in_digits = true;
+        } else {
+            pattern.push(c);
+            in_digits = false;
+        }
+    }
+    if word.ends_with(|c: char| c.is_alphanumeric() || c == '_') {
+        pattern.push_str(r"\b");
+    }
+    pattern
+}
+
+fn is_noteworthy(line: &str) -> bool {
+    let lower = line.to_lowercase();
+    ["error", "fail", "warn", "panic"]
+        .iter()
+        .any(|word| lower.contains(word))
+}
+
+/// A filter drafted from the user's answers.
+#[derive(Debug, Clone, Default)]
+pub struct Draft {
+    /// `command` pattern.
+    pub command: String,
+    /// The command line the sample output came from.
+    pub source: String,
+    pub skip: Vec<String>,
+    /// One-line `[on_success] output`, replacing the filtered lines.
+    pub success_output: Option<String>,
+    /// `[on_failure] tail`.
+    pub failure_tail: Option<usize>,
+}
+
+impl Draft {
+    pub fn to_toml(&self) -> String {
+        let mut out = String::new();
+        let _ = writeln!(
+            out,
+            "# Drafted by `tokf new` from `{}`.\n\
+             # Adjust the patterns, then check the result with `tokf verify`.\n",
+            self.source
+        );
+        let _ = writeln!(out, "command = {}", toml_string(&self.command));
+        let _ = writeln!(
+            out,
+            "description = {}",
+            toml_string(&format!("Compact {} output", self.command))
+        );
+        if !self.skip.is_empty() {
+            out.push_str("\nskip = [\n");
+            for pattern in &self.skip {
+                let _ = writeln!(out, "    {},", toml_string(pattern));
+            }
+            out.push_str("]\n");
+        }
+        if let Some(output) = &self.success_output {
+            let _ = write!(out, "\n[on_success]\noutput = {}\n", toml_string(output));
+        }
+        if let Some(tail) = self.failure_tail {
+            let _ = write!(out, "\n[on_failure]\ntail = {tail}\n");
+        }
+        out
+    }
+}
+
+/// `s` as a TOML string value, quoted and escaped.
+pub fn toml_string(s: &str) -> String {
+    toml::Value::String(s.to_string()).to_string()
+}
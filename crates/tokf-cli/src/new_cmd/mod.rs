@@ -0,0 +1,280 @@
+//! `tokf new` — draft a filter from a command's real output.
+//!
+//! The command is run (or its output read from `--fixture`), the output is
+//! shown, and the user picks which kinds of lines to drop and how the success
+//! and failure branches should look. The result is a filter TOML plus a
+//! `<stem>_test/` suite whose single case pins the drafted filter's output for
+//! that sample, so `tokf verify` passes from the start and flags later edits.
+
+mod draft;
+
+use std::io::IsTerminal as _;
+use std::path::{Path, PathBuf};
+
+use anyhow::Context as _;
+
+use tokf::config::types::FilterConfig;
+use tokf::filter;
+use tokf::runner::{self, CommandResult};
+use tokf::runtime::Runtime;
+
+use draft::{Draft, LineGroup, group_lines};
+
+use crate::fixture_cmd::{Recording, write_case};
+
+/// Lines of the sample shown before the questions.
+const PREVIEW_LINES: usize = 40;
+
+/// `[on_failure] tail` suggested for a new filter.
+const DEFAULT_FAILURE_TAIL: usize = 20;
+
+/// Args for `tokf new`.
+#[derive(clap::Args, Debug, Clone)]
+pub struct NewArgs {
+    /// Read the sample output from this file instead of running the command
+    #[arg(long, value_name = "FILE")]
+    pub fixture: Option<PathBuf>,
+    /// Exit code the fixture was produced with
+    #[arg(long, default_value_t = 0, requires = "fixture")]
+    pub exit_code: i32,
+    /// Filter name, e.g. "my-tool/build" (default: from the command words)
+    #[arg(long)]
+    pub name: Option<String>,
+    /// Write to the global filters directory instead of .tokf/filters/
+    #[arg(long)]
+    pub global: bool,
+    /// Overwrite an existing filter and test suite
+    #[arg(long)]
+    pub force: bool,
+    /// Accept the suggested answers without prompting
+    #[arg(long, short = 'y')]
+    pub yes: bool,
+    /// The command to write a filter for
+    #[arg(trailing_var_arg = true, required = true)]
+    pub command_args: Vec<String>,
+}
+
+/// The output a filter is drafted from.
+struct Sample {
+    output: String,
+    exit_code: i32,
+}
+
+/// Entry point for the `tokf new` subcommand.
+pub fn cmd_new(rt: &Runtime, args: &NewArgs) -> i32 {
+    match new_filter(rt, args) {
+        Ok(()) => 0,
+        Err(e) => {
+            eprintln!("[tokf] error: {e:#}");
+            1
+        }
+    }
+}
+
+fn new_filter(rt: &Runtime, args: &NewArgs) -> anyhow::Result<()> {
+    let command = command_pattern(&args.command_args);
+    let name = args.name.clone().unwrap_or_else(|| default_name(&command));
+    validate_name(&name)?;
+    let base = if args.global {
+        rt.require_user_dir()?.join("filters")
+    } else {
+        rt.cwd()
+            .context("could not determine working directory")?
+            .join(".tokf/filters")
+    };
+    let filter_path = base.join(format!("{name}.toml"));
+    if filter_path.exists() && !args.force {
+        anyhow::bail!(
+            "filter already exists at {} — pass --force to replace it",
+            filter_path.display()
+        );
+    }
+
+    let sample = capture(args)?;
+    preview(&sample);
+    let groups = group_lines(&sample.output);
+    let mut draft = if args.yes || !std::io::stdin().is_terminal() {
+        suggested(&groups)
+    } else {
+        ask(&groups)?
+    };
+    draft.command = command;
+    draft.source = args.command_args.join(" ");
+
+    write_filter(&filter_path, &draft, &sample)?;
+    eprintln!(
+        "[tokf] next: edit {}, then run `tokf verify {name}`",
+        filter_path.display()
+    );
+    Ok(())
+}
+
+/// The leading words of the command, up to the first flag or path-like
+/// argument: `cargo test -p foo` becomes `cargo test`.
+fn command_pattern(args: &[String]) -> String {
+    let words: Vec<&str> = args
+        .iter()
+        .enumerate()
+        .take_while(|(i, w)| *i == 0 || !(w.starts_with('-') || w.contains(['/', '=', '.'])))
+        .map(|(_, w)| w.as_str())
+        .collect();
+    words.join(" ")
+}
+
+/// `my-tool build` becomes `my-tool/build`; path prefixes and characters that
+/// do not belong in a file name are dropped.
+fn default_name(command: &str) -> String {
+    command
+        .split_whitespace()
+        .map(|word| {
+            let word = word.rsplit('/').next().unwrap_or(word);
+            word.chars()
+                .map(|c| if is_name_char(c) { c } else { '-' })
+                .collect::<String>()
+        })
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+const fn is_name_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.')
+}
+
+fn validate_name(name: &str) -> anyhow::Result<()> {
+    let valid = name.split('/').all(|segment| {
+        !segment.is_empty()
+            && segment != "."
+            && segment != ".."
+            && segment.chars().all(is_name_char)
+    });
+    anyhow::ensure!(
+        valid,
+        "invalid filter name {name:?}: use letters, digits, '-', '_' and '.', with '/' between directories"
+    );
+    Ok(())
+}
+
+fn capture(args: &NewArgs) -> anyhow::Result<Sample> {
+    let (output, exit_code) = if let Some(fixture) = &args.fixture {
+        let output = std::fs::read_to_string(fixture)
+            .with_context(|| format!("cannot read fixture {}", fixture.display()))?;
+        (output, args.exit_code)
+    } else {
+        eprintln!("[tokf] running: {}", args.command_args.join(" "));
+        let result = runner::execute(&args.command_args[0], &args.command_args[1..])?;
+        (result.combined, result.exit_code)
+    };
+    Ok(Sample {
+        output: output.trim_end().to_string(),
+        exit_code,
+    })
+}
+
+fn preview(sample: &Sample) {
+    let lines: Vec<&str> = sample.output.lines().collect();
+    eprintln!(
+        "[tokf] {} lines of output, exit code {}:",
+        lines.len(),
+        sample.exit_code
+    );
+    for (i, line) in lines.iter().take(PREVIEW_LINES).enumerate() {
+        eprintln!("{:>4} | {line}", i + 1);
+    }
+    if lines.len() > PREVIEW_LINES {
+        eprintln!("     … {} more lines", lines.len() - PREVIEW_LINES);
+    }
+    eprintln!();
+}
+
+fn suggested(groups: &[LineGroup]) -> Draft {
+    Draft {
+        skip: groups
+            .iter()
+            .filter(|g| g.suggest_skip())
+            .map(|g| g.pattern.clone())
+            .collect(),
+        failure_tail: Some(DEFAULT_FAILURE_TAIL),
+        ..Draft::default()
+    }
+}
+
+fn ask(groups: &[LineGroup]) -> anyhow::Result<Draft> {
+    let term = dialoguer::console::Term::stderr();
+    let items: Vec<String> = groups
+        .iter()
+        .map(|g| format!("{:>4}×  {}", g.count, truncate(&g.example, 70)))
+        .collect();
+    let defaults: Vec<bool> = groups.iter().map(LineGroup::suggest_skip).collect();
+    let dropped = dialoguer::MultiSelect::new()
+        .with_prompt("Lines to drop (space toggles, enter confirms)")
+        .items(&items)
+        .defaults(&defaults)
+        .interact_on_opt(&term)?
+        .context("cancelled")?;
+    let summary: String = dialoguer::Input::new()
+        .with_prompt("One-line summary on success (empty keeps the remaining lines)")
+        .allow_empty(true)
+        .interact_text_on(&term)?;
+    let tail: usize = dialoguer::Input::new()
+        .with_prompt("Lines to keep from the end on failure (0 keeps all)")
+        .default(DEFAULT_FAILURE_TAIL)
+        .interact_text_on(&term)?;
+    Ok(Draft {
+        skip: dropped.iter().map(|&i| groups[i].pattern.clone()).collect(),
+        success_output: Some(summary).filter(|s| !s.trim().is_empty()),
+        failure_tail: Some(tail).filter(|&t| t > 0),
+        ..Draft::default()
+    })
+}
+
+fn truncate(line: &str, max: usize) -> String {
+    if line.chars().count() <= max {
+        return line.to_string();
+    }
+    let mut cut: String = line.chars().take(max - 1).collect();
+    cut.push('…');
+    cut
+}
+
+/// Write the filter and a `<stem>_test/` suite with one case for `sample`.
+fn write_filter(filter_path: &Path, draft: &Draft, sample: &Sample) -> anyhow::Result<()> {
+    let filter_toml = draft.to_toml();
+    let cfg: FilterConfig =
+        toml::from_str(&filter_toml).context("drafted filter does not parse")?;
+    let result = CommandResult {
+        stdout: String::new(),
+        stderr: String::new(),
+        exit_code: sample.exit_code,
+        combined: sample.output.clone(),
+    };
+    let filtered = filter::apply(&cfg, &result, &[], &filter::FilterOptions::default());
+
+    if let Some(parent) = filter_path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(filter_path, &filter_toml)?;
+    eprintln!("[tokf] wrote {}", filter_path.display());
+
+    let stem = filter_path
+        .file_stem()
+        .context("filter path has no file name")?
+        .to_string_lossy();
+    let suite_dir = filter_path.with_file_name(format!("{stem}_test"));
+    let recording = Recording {
+        case: if sample.exit_code == 0 {
+            "success"
+        } else {
+            "failure"
+        },
+        source: &draft.source,
+        output: &sample.output,
+        exit_code: sample.exit_code,
+        args: &[],
+    };
+    write_case(&suite_dir, &recording, Some(&filtered.output))?;
+    Ok(())
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests;
@@ -0,0 +1,144 @@
+use super::*;
+
+const CARGO_BUILD: &str = concat!(
+    "   Compiling libc v0.2.1\n",
+    "   Compiling serde v1.0.0\n",
+    "   Compiling tokf v0.2.52\n",
+    "\n",
+    "warning: unused variable `x`\n",
+    "    Finished `dev` profile in 3.2s",
+);
+
+fn strings(words: &[&str]) -> Vec<String> {
+    words.iter().map(ToString::to_string).collect()
+}
+
+// --- command pattern and name ---
+
+#[test]
+fn command_pattern_stops_at_flags_and_paths() {
+    assert_eq!(
+        command_pattern(&strings(&["cargo", "test", "-p", "foo"])),
+        "cargo test"
+    );
+    assert_eq!(
+        command_pattern(&strings(&["npm", "run", "build", "src/"])),
+        "npm run build"
+    );
+    assert_eq!(
+        command_pattern(&strings(&["./build.sh", "release"])),
+        "./build.sh release"
+    );
+}
+
+#[test]
+fn default_name_is_a_relative_filter_path() {
+    assert_eq!(default_name("my-tool build"), "my-tool/build");
+    assert_eq!(default_name("./scripts/build.sh"), "build.sh");
+    assert_eq!(default_name("tool run:all"), "tool/run-all");
+}
+
+#[test]
+fn validate_name_rejects_escapes() {
+    assert!(validate_name("my-tool/build").is_ok());
+    assert!(validate_name("../evil").is_err());
+    assert!(validate_name("a//b").is_err());
+    assert!(validate_name("with space").is_err());
+}
+
+// --- grouping ---
+
+#[test]
+fn group_lines_by_leading_word() {
+    let groups = group_lines(CARGO_BUILD);
+    let patterns: Vec<&str> = groups.iter().map(|g| g.pattern.as_str()).collect();
+    assert_eq!(
+        patterns,
+        [
+            r"^\s*Compiling\b",
+            r"^\s*$",
+            r"^\s*warning:",
+            r"^\s*Finished\b"
+        ]
+    );
+    assert_eq!(groups[0].count, 3);
+    assert_eq!(groups[0].example, "   Compiling libc v0.2.1");
+    assert!(groups[2].noteworthy);
+}
+
+#[test]
+fn group_lines_generalizes_digits() {
+    let groups = group_lines("[1/20] a\n[2/20] b\n[13/20] c");
+    assert_eq!(groups.len(), 1);
+    assert_eq!(groups[0].pattern, r"^\s*\[\d+/\d+\]");
+}
+
+#[test]
+fn repeated_lines_are_suggested_unless_noteworthy() {
+    let suggested: Vec<bool> = group_lines(CARGO_BUILD)
+        .iter()
+        .map(LineGroup::suggest_skip)
+        .collect();
+    assert_eq!(suggested, [true, true, false, false]);
+
+    let failing = group_lines("test a ... ok\ntest b ... ok\ntest c ... FAILED");
+    assert!(!failing[0].suggest_skip());
+}
+
+// --- writing ---
+
+fn sample(output: &str, exit_code: i32) -> Sample {
+    Sample {
+        output: output.to_string(),
+        exit_code,
+    }
+}
+
+#[test]
+fn write_filter_pins_drafted_output_in_a_test_case() {
+    let dir = tempfile::TempDir::new().unwrap();
+    let filter_path = dir.path().join("cargo/build.toml");
+    let mut draft = suggested(&group_lines(CARGO_BUILD));
+    draft.command = "cargo build".to_string();
+    draft.source = "cargo build".to_string();
+
+    write_filter(&filter_path, &draft, &sample(CARGO_BUILD, 0)).unwrap();
+
+    let cfg: FilterConfig =
+        toml::from_str(&std::fs::read_to_string(&filter_path).unwrap()).unwrap();
+    assert_eq!(cfg.command.first(), "cargo build");
+    assert_eq!(cfg.skip, [r"^\s*Compiling\b", r"^\s*$"]);
+    assert_eq!(cfg.on_failure.unwrap().tail, Some(20));
+
+    let suite = dir.path().join("cargo/build_test");
+    let fixture = std::fs::read_to_string(suite.join("success.txt")).unwrap();
+    assert_eq!(fixture.trim_end(), CARGO_BUILD);
+    let case: toml::Value =
+        toml::from_str(&std::fs::read_to_string(suite.join("success.toml")).unwrap()).unwrap();
+    assert_eq!(case["fixture"].as_str(), Some("success.txt"));
+    assert_eq!(
+        case["expect"][0]["equals"].as_str(),
+        Some("warning: unused variable `x`\n    Finished `dev` profile in 3.2s")
+    );
+}
+
+#[test]
+fn write_filter_uses_inline_fixture_for_empty_output() {
+    let dir = tempfile::TempDir::new().unwrap();
+    let filter_path = dir.path().join("quiet.toml");
+    let draft = Draft {
+        command: "quiet".to_string(),
+        source: "quiet".to_string(),
+        success_output: Some("quiet ok".to_string()),
+        ..Draft::default()
+    };
+
+    write_filter(&filter_path, &draft, &sample("", 0)).unwrap();
+
+    let case: toml::Value = toml::from_str(
+        &std::fs::read_to_string(dir.path().join("quiet_test/success.toml")).unwrap(),
+    )
+    .unwrap();
+    assert_eq!(case["inline"].as_str(), Some(""));
+    assert_eq!(case["expect"][0]["equals"].as_str(), Some("quiet ok"));
+}
@@ -1,19 +1,35 @@
 use std::io::BufRead as _;
 
-use tokf::auth::credentials;
+use tokf::auth::{credentials, signing_key};
 use tokf::config;
-use tokf::publish_shared::{collect_test_files_resolved, hash_filter, inline_lua_script};
-use tokf::remote::http::Client;
-use tokf::remote::publish_client;
+use tokf::publish_shared::{
+    collect_test_files_resolved, flatten_extends, hash_filter, inline_lua_script,
+};
+use tokf::remote::publish_client::{self, Namespace};
+use tokf::remote::registry;
 
 use tokf::runtime::Runtime;
 
+/// Options for `tokf publish`.
+#[derive(Debug, Clone, Copy)]
+pub struct PublishOpts<'a> {
+    pub dry_run: bool,
+    /// Replace the test suite of an already-published filter instead.
+    pub update_tests: bool,
+    /// Registry name or URL; the highest-priority registry when `None`.
+    pub registry: Option<&'a str>,
+    /// Accept the MIT license without prompting, for CI.
+    pub accept_license: bool,
+    /// Where to publish and who can see the filter.
+    pub namespace: Namespace<'a>,
+}
+
 /// Entry point for the `tokf publish` subcommand.
-pub fn cmd_publish(rt: &Runtime, filter_name: &str, dry_run: bool, update_tests: bool) -> i32 {
-    let result = if update_tests {
-        publish_update_tests(rt, filter_name, dry_run)
+pub fn cmd_publish(rt: &Runtime, filter_name: &str, opts: PublishOpts<'_>) -> i32 {
+    let result = if opts.update_tests {
+        publish_update_tests(rt, filter_name, opts)
     } else {
-        publish(rt, filter_name, dry_run)
+        publish(rt, filter_name, opts)
     };
     match result {
         Ok(code) => code,
@@ -47,11 +63,12 @@ fn resolve_local_filter(rt: &Runtime, filter_name: &str) -> anyhow::Result<confi
 
 // ── Publish flow ────────────────────────────────────────────────────────────
 
-fn publish(rt: &Runtime, filter_name: &str, dry_run: bool) -> anyhow::Result<i32> {
+fn publish(rt: &Runtime, filter_name: &str, opts: PublishOpts<'_>) -> anyhow::Result<i32> {
     let filter_name = filter_name.strip_suffix(".toml").unwrap_or(filter_name);
     let resolved_filter = resolve_local_filter(rt, filter_name)?;
 
     let filter_bytes = std::fs::read(&resolved_filter.source_path)?;
+    let filter_bytes = flatten_extends(filter_bytes, &resolved_filter.source_path)?;
     let filter_bytes = inline_lua_script(filter_bytes, &resolved_filter.source_path)?;
     let (content_hash, command_pattern) = hash_filter(&filter_bytes)?;
     let test_files = collect_test_files_resolved(&resolved_filter.source_path)?;
@@ -60,17 +77,38 @@ fn publish(rt: &Runtime, filter_name: &str, dry_run: bool) -> anyhow::Result<i32
     eprintln!("  Command: {command_pattern}");
     eprintln!("  Hash:    {content_hash}");
     eprintln!("  Tests:   {} file(s)", test_files.len());
+    if let Some(org) = opts.namespace.org {
+        eprintln!("  Org:     {org}");
+    }
+    if opts.namespace.private {
+        let who = opts.namespace.org.map_or_else(
+            || "you".to_string(),
+            |org| format!("you and members of {org}"),
+        );
+        eprintln!("  Visible: only to {who}");
+    }
+    let registry = registry::primary(rt, opts.registry)?;
+    eprintln!("  Registry: {} ({})", registry.name, registry.url);
 
-    if dry_run {
+    if opts.dry_run {
         eprintln!("[tokf] dry-run: no files uploaded");
         return Ok(0);
     }
 
-    ensure_license_accepted(rt)?;
-    let client = Client::authed(rt)?;
+    if !opts.accept_license {
+        ensure_license_accepted(rt)?;
+    }
+    let client = registry.client(rt)?;
+    let signature = sign(rt, &filter_bytes)?;
 
     let (is_new, resp) = tokf::remote::retry::with_retry("publish", || {
-        publish_client::publish_filter(&client, &filter_bytes, &test_files)
+        publish_client::publish_filter(
+            &client,
+            &filter_bytes,
+            &test_files,
+            signature.as_ref(),
+            opts.namespace,
+        )
     })?;
 
     if is_new {
@@ -84,9 +122,35 @@ fn publish(rt: &Runtime, filter_name: &str, dry_run: bool) -> anyhow::Result<i32
     Ok(0)
 }
 
+/// Sign `filter_bytes` with the local signing key, if there is one.
+fn sign(
+    rt: &Runtime,
+    filter_bytes: &[u8],
+) -> anyhow::Result<Option<publish_client::FilterSignature>> {
+    let Some(key) = signing_key::load(rt)? else {
+        eprintln!(
+            "[tokf] note: publishing unsigned — run `tokf auth keys generate` to sign your filters"
+        );
+        return Ok(None);
+    };
+    let public_key = key.public_key_hex();
+    eprintln!(
+        "  Signed:  {}",
+        tokf_common::signing::fingerprint(&public_key)
+    );
+    Ok(Some(publish_client::FilterSignature {
+        signature: key.sign(filter_bytes),
+        public_key,
+    }))
+}
+
 // ── Update-tests flow ───────────────────────────────────────────────────────
 
-fn publish_update_tests(rt: &Runtime, filter_name: &str, dry_run: bool) -> anyhow::Result<i32> {
+fn publish_update_tests(
+    rt: &Runtime,
+    filter_name: &str,
+    opts: PublishOpts<'_>,
+) -> anyhow::Result<i32> {
     let filter_name = filter_name.strip_suffix(".toml").unwrap_or(filter_name);
     let resolved_filter = resolve_local_filter(rt, filter_name)?;
 
@@ -106,8 +170,10 @@ fn publish_update_tests(rt: &Runtime, filter_name: &str, dry_run: bool) -> anyho
     eprintln!("[tokf] updating test suite for: {filter_name}");
     eprintln!("  Hash:  {content_hash}");
     eprintln!("  Tests: {} file(s)", test_files.len());
+    let registry = registry::primary(rt, opts.registry)?;
+    eprintln!("  Registry: {} ({})", registry.name, registry.url);
 
-    if dry_run {
+    if opts.dry_run {
         for (name, _) in &test_files {
             eprintln!("  - {name}");
         }
@@ -115,7 +181,7 @@ fn publish_update_tests(rt: &Runtime, filter_name: &str, dry_run: bool) -> anyho
         return Ok(0);
     }
 
-    let client = Client::authed(rt)?;
+    let client = registry.client(rt)?;
 
     let resp = tokf::remote::retry::with_retry("update-tests", || {
         publish_client::update_tests(&client, &content_hash, &test_files)
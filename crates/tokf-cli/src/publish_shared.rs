@@ -16,6 +16,24 @@ pub fn hash_filter(filter_bytes: &[u8]) -> anyhow::Result<(String, String)> {
     Ok((hash, cfg.command.first().to_string()))
 }
 
+/// If the filter uses `extends`, merge its base filters in so the published
+/// TOML is self-contained. Returns the original bytes when it does not.
+///
+/// # Errors
+///
+/// Returns an error if the TOML is invalid or a base filter cannot be resolved.
+pub fn flatten_extends(filter_bytes: Vec<u8>, filter_path: &Path) -> anyhow::Result<Vec<u8>> {
+    use crate::config::extends::{Origin, flatten};
+
+    let toml_str = std::str::from_utf8(&filter_bytes)
+        .map_err(|_| anyhow::anyhow!("filter TOML is not valid UTF-8"))?;
+    let flat = flatten(toml_str, &Origin::File(filter_path.to_path_buf()))?;
+    Ok(flat.map_or(filter_bytes, |flat| {
+        eprintln!("[tokf] flattened extends into filter source");
+        flat.into_bytes()
+    }))
+}
+
 /// If `lua_script.file` is set, read the external file and embed its content
 /// as `lua_script.source` so the filter TOML is self-contained for publishing.
 ///
@@ -0,0 +1,383 @@
+use reqwest::multipart::Form;
+
+use crate::runtime::Runtime;
+
+use super::http::load_auth;
+use super::network::Network;
+use super::{ProxyFailure, RemoteError, classify_reqwest_error, require_success};
+
+/// Async HTTP client for all tokf remote operations.
+///
+/// Handles timeouts (`TOKF_HTTP_TIMEOUT`, default 5s), auth header injection,
+/// error classification into [`RemoteError`], and one automatic retry on
+/// transient failures for idempotent methods (GET/PUT). Proxy and CA
+/// settings come from [`Network`].
+///
+/// Async callers (the e2e harness, anything already inside Tokio) use this
+/// directly; the CLI goes through the blocking [`super::http::Client`].
+pub struct AsyncClient {
+    inner: reqwest::Client,
+    base_url: String,
+    token: Option<String>,
+    /// `TOKF_DEBUG`, captured so errors built here can render their verbose
+    /// form without reaching back into the environment.
+    debug: bool,
+    /// Kept so failures can be attributed to the proxy they went through.
+    network: Network,
+}
+
+impl AsyncClient {
+    /// Build a client with the given base URL and optional auth token.
+    ///
+    /// Reads `TOKF_HTTP_TIMEOUT` for the request timeout (default 5s), and
+    /// the proxy and CA bundle settings described in [`super::network`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a proxy URL or the CA bundle is invalid, or the
+    /// underlying HTTP client cannot be constructed.
+    pub fn new(rt: &Runtime, base_url: &str, token: Option<&str>) -> anyhow::Result<Self> {
+        let timeout = rt.http_timeout();
+        let network = Network::load(rt);
+        let builder = reqwest::Client::builder()
+            .timeout(timeout)
+            .connect_timeout(timeout)
+            .redirect(reqwest::redirect::Policy::limited(10));
+        let inner = network
+            .apply(builder)?
+            .build()
+            .map_err(|e| anyhow::anyhow!("could not build HTTP client: {e}"))?;
+        Ok(Self {
+            inner,
+            base_url: base_url.trim_end_matches('/').to_string(),
+            token: token.map(String::from),
+            debug: rt.debug(),
+            network,
+        })
+    }
+
+    /// Build an authenticated client from stored credentials.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if credentials are missing/expired or the client
+    /// cannot be constructed.
+    pub fn authed(rt: &Runtime) -> anyhow::Result<Self> {
+        let auth = load_auth(rt)?;
+        Self::new(rt, &auth.server_url, Some(&auth.token))
+    }
+
+    /// The base URL this client is configured for.
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    /// Build the full URL for a request path.
+    ///
+    /// Panics in debug builds if `path` doesn't start with `/`.
+    fn url(&self, path: &str) -> String {
+        debug_assert!(
+            path.starts_with('/'),
+            "Client path must start with '/': {path}"
+        );
+        format!("{}{path}", self.base_url)
+    }
+
+    /// GET `{base_url}{path}` and deserialize JSON.
+    ///
+    /// Retries once on transient errors (connection/timeout/5xx).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error on network failure, non-2xx status, or JSON parse error.
+    pub async fn get<T: serde::de::DeserializeOwned>(&self, path: &str) -> anyhow::Result<T> {
+        let url = self.url(path);
+        let resp = self.execute_idempotent(|c| c.inner.get(&url), &url).await?;
+        parse_json(resp).await
+    }
+
+    /// GET `{base_url}{path}` with query parameters and deserialize JSON.
+    ///
+    /// Retries once on transient errors.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error on network failure, non-2xx status, or JSON parse error.
+    pub async fn get_with_query<T: serde::de::DeserializeOwned>(
+        &self,
+        path: &str,
+        params: &[(&str, &str)],
+    ) -> anyhow::Result<T> {
+        parse_json(self.get_raw_with_query(path, params).await?).await
+    }
+
+    /// GET `{base_url}{path}?{params}` and return the raw response, for
+    /// callers that need its headers.
+    ///
+    /// Retries once on transient errors.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error on network failure or non-2xx status.
+    pub async fn get_raw_with_query(
+        &self,
+        path: &str,
+        params: &[(&str, &str)],
+    ) -> anyhow::Result<reqwest::Response> {
+        let url = self.url(path);
+        self.execute_idempotent(|c| c.inner.get(&url).query(params), &url)
+            .await
+    }
+
+    /// GET `{base_url}{path}` and return the raw response.
+    ///
+    /// Retries once on transient errors. Useful for non-JSON endpoints.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error on network failure or non-2xx status.
+    pub async fn get_raw(&self, path: &str) -> anyhow::Result<reqwest::Response> {
+        let url = self.url(path);
+        self.execute_idempotent(|c| c.inner.get(&url), &url).await
+    }
+
+    /// GET `{base_url}{path}` with `If-None-Match: {etag}` when an `etag` is
+    /// given, returning `None` if the server answers 304 Not Modified.
+    ///
+    /// Retries once on transient errors.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error on network failure or a non-2xx, non-304 status.
+    pub async fn get_if_none_match(
+        &self,
+        path: &str,
+        etag: Option<&str>,
+    ) -> anyhow::Result<Option<reqwest::Response>> {
+        let url = self.url(path);
+        let resp = self
+            .execute_idempotent(
+                |c| {
+                    let request = c.inner.get(&url);
+                    if let Some(etag) = etag {
+                        request.header(reqwest::header::IF_NONE_MATCH, etag)
+                    } else {
+                        request
+                    }
+                },
+                &url,
+            )
+            .await?;
+        Ok((resp.status() != reqwest::StatusCode::NOT_MODIFIED).then_some(resp))
+    }
+
+    /// POST `{base_url}{path}` with a JSON body and deserialize the response.
+    ///
+    /// Does **not** retry — POST is non-idempotent.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error on network failure, non-2xx status, or JSON parse error.
+    pub async fn post<B: serde::Serialize + Sync, T: serde::de::DeserializeOwned>(
+        &self,
+        path: &str,
+        body: &B,
+    ) -> anyhow::Result<T> {
+        let url = self.url(path);
+        let request = self.build_request(self.inner.post(&url)).json(body);
+        let resp = self.send_and_classify(request, &url).await?;
+        parse_json(resp).await
+    }
+
+    /// DELETE `{base_url}{path}`, returning the raw response.
+    ///
+    /// Does **not** retry — DELETE is non-idempotent in our usage
+    /// (account deletion is a one-shot operation).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error on network failure or non-2xx status.
+    pub async fn delete(&self, path: &str) -> anyhow::Result<reqwest::Response> {
+        let url = self.url(path);
+        let request = self.build_request(self.inner.delete(&url));
+        Ok(self.send_and_classify(request, &url).await?)
+    }
+
+    /// POST `{base_url}{path}` with a multipart form.
+    ///
+    /// Returns the raw response — callers handle per-status-code logic (e.g.
+    /// 400 test failures in publish). Does not retry (POST is non-idempotent).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error on network failure (does not check status).
+    pub async fn post_multipart(
+        &self,
+        path: &str,
+        form: Form,
+    ) -> anyhow::Result<reqwest::Response> {
+        let url = self.url(path);
+        let request = self.build_request(self.inner.post(&url)).multipart(form);
+        Ok(self.send(request, &url).await?)
+    }
+
+    /// PUT `{base_url}{path}` with a multipart form.
+    ///
+    /// Returns the raw response — callers handle per-status-code logic.
+    /// Retries once on transport-level errors (PUT is idempotent).
+    /// `form_builder` must be callable multiple times (forms are consumed on send).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error on network failure (does not check status).
+    pub async fn put_multipart<F>(
+        &self,
+        path: &str,
+        form_builder: F,
+    ) -> anyhow::Result<reqwest::Response>
+    where
+        F: Fn() -> Form + Sync,
+    {
+        let url = self.url(path);
+        let request = || {
+            self.build_request(self.inner.put(&url))
+                .multipart(form_builder())
+        };
+        match self.send(request(), &url).await {
+            Ok(resp) => Ok(resp),
+            // Retry once on transport errors.
+            Err(err) if err.is_transient() => Ok(self.send(request(), &url).await?),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    // ── Internal helpers ────────────────────────────────────────────────
+
+    /// Add auth header if a token is present.
+    fn build_request(&self, builder: reqwest::RequestBuilder) -> reqwest::RequestBuilder {
+        match &self.token {
+            Some(token) => builder.header("Authorization", format!("Bearer {token}")),
+            None => builder,
+        }
+    }
+
+    /// Execute an idempotent request (GET/PUT), retrying once on transient errors.
+    async fn execute_idempotent<F>(
+        &self,
+        make_request: F,
+        url: &str,
+    ) -> anyhow::Result<reqwest::Response>
+    where
+        F: Fn(&Self) -> reqwest::RequestBuilder,
+    {
+        let req = self.build_request(make_request(self));
+        match self.send_and_classify(req, url).await {
+            Ok(resp) => Ok(resp),
+            Err(err) if err.is_transient() => {
+                let req = self.build_request(make_request(self));
+                Ok(self.send_and_classify(req, url).await?)
+            }
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    /// Send a request, classify errors into [`RemoteError`], and check the response status.
+    async fn send_and_classify(
+        &self,
+        request: reqwest::RequestBuilder,
+        url: &str,
+    ) -> Result<reqwest::Response, RemoteError> {
+        let resp = self.send(request, url).await?;
+        require_success(resp, url, self.debug).await
+    }
+
+    /// Send a request without checking its status, except that a proxy's
+    /// 407 and failures to get through a proxy become
+    /// [`RemoteError::ProxyFailed`].
+    async fn send(
+        &self,
+        request: reqwest::RequestBuilder,
+        url: &str,
+    ) -> Result<reqwest::Response, RemoteError> {
+        let Some(proxy) = self.network.proxy_for(url) else {
+            return request
+                .send()
+                .await
+                .map_err(|e| classify_reqwest_error(url, e, self.debug));
+        };
+        let reason = match request.send().await {
+            Ok(resp) if resp.status() == reqwest::StatusCode::PROXY_AUTHENTICATION_REQUIRED => {
+                ProxyFailure::AuthRequired
+            }
+            Ok(resp) => return Ok(resp),
+            Err(err) if is_tunnel_auth_error(&err) => ProxyFailure::AuthRequired,
+            Err(err) => match classify_reqwest_error(url, err, self.debug) {
+                RemoteError::ConnectionFailed { source, .. } => ProxyFailure::Connect(source),
+                other => return Err(other),
+            },
+        };
+        Err(RemoteError::ProxyFailed {
+            url: url.to_string(),
+            proxy,
+            reason,
+            debug: self.debug,
+        })
+    }
+}
+
+/// Whether `err` is an HTTPS tunnel the proxy refused with 407. The tunnel
+/// error type is private to the connector, so it is recognised by message.
+fn is_tunnel_auth_error(err: &reqwest::Error) -> bool {
+    let mut source: Option<&dyn std::error::Error> = Some(err);
+    while let Some(e) = source {
+        if e.to_string().contains("proxy authorization required") {
+            return true;
+        }
+        source = e.source();
+    }
+    false
+}
+
+async fn parse_json<T: serde::de::DeserializeOwned>(resp: reqwest::Response) -> anyhow::Result<T> {
+    resp.json::<T>()
+        .await
+        .map_err(|e| anyhow::anyhow!("invalid response from server: {e}"))
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn client_base_url_trims_trailing_slash() {
+        let rt = Runtime::isolated();
+        let c = AsyncClient::new(&rt, "https://api.tokf.net/", None).unwrap();
+        assert_eq!(c.base_url(), "https://api.tokf.net");
+    }
+
+    #[test]
+    fn client_new_with_token() {
+        let rt = Runtime::isolated();
+        let c = AsyncClient::new(&rt, "https://api.tokf.net", Some("tok123")).unwrap();
+        assert_eq!(c.token.as_deref(), Some("tok123"));
+        let c = AsyncClient::new(&rt, "https://api.tokf.net", None).unwrap();
+        assert!(c.token.is_none());
+    }
+
+    #[test]
+    fn url_helper_formats_correctly() {
+        let rt = Runtime::isolated();
+        let c = AsyncClient::new(&rt, "https://api.tokf.net/", None).unwrap();
+        assert_eq!(c.url("/api/test"), "https://api.tokf.net/api/test");
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "must start with '/'")]
+    fn url_helper_panics_on_missing_slash_in_debug() {
+        let rt = Runtime::isolated();
+        let c = AsyncClient::new(&rt, "https://api.tokf.net", None).unwrap();
+        let _ = c.url("api/test");
+    }
+}
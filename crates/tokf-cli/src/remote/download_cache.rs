@@ -0,0 +1,133 @@
+//! Filter downloads kept on disk and revalidated with their `ETag`.
+//!
+//! Each download is stored as `downloads/<hash>.json` under the user data
+//! directory, with the `ETag` it was served with. The next download of the
+//! same hash sends that tag in `If-None-Match`; a `304 Not Modified` answer
+//! reuses the stored copy instead of transferring the TOML and tests again.
+//! The cache is an optimisation only — a missing or unreadable entry just
+//! means a full download.
+
+use std::io::Write as _;
+use std::path::PathBuf;
+
+use anyhow::Context as _;
+use serde::{Deserialize, Serialize};
+
+use super::filter_client::DownloadedFilter;
+use crate::runtime::Runtime;
+
+/// A cached download and the `ETag` it was served with.
+#[derive(Debug, Deserialize)]
+pub struct CachedDownload {
+    pub etag: String,
+    pub filter: DownloadedFilter,
+}
+
+/// The serialized form of [`CachedDownload`], borrowing its parts.
+#[derive(Serialize)]
+struct Entry<'a> {
+    etag: &'a str,
+    filter: &'a DownloadedFilter,
+}
+
+/// The download cache directory.
+pub struct DownloadCache {
+    dir: PathBuf,
+}
+
+impl DownloadCache {
+    /// The cache under `rt`'s user data directory, or `None` when there is
+    /// no such directory.
+    pub fn open(rt: &Runtime) -> Option<Self> {
+        rt.user_data_dir().map(|d| Self {
+            dir: d.join("downloads"),
+        })
+    }
+
+    /// The entry for `hash`, if one is cached and readable.
+    pub fn load(&self, hash: &str) -> Option<CachedDownload> {
+        let content = std::fs::read_to_string(self.path(hash)?).ok()?;
+        serde_json::from_str(&content).ok()
+    }
+
+    /// Store `filter` under `hash` with its `etag`, replacing any entry.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the entry cannot be written.
+    pub fn store(&self, hash: &str, etag: &str, filter: &DownloadedFilter) -> anyhow::Result<()> {
+        let path = self
+            .path(hash)
+            .ok_or_else(|| anyhow::anyhow!("not a cacheable hash: {hash}"))?;
+        let entry = Entry { etag, filter };
+        std::fs::create_dir_all(&self.dir).context("create download cache dir")?;
+        let mut tmp = tempfile::Builder::new()
+            .prefix(".download-")
+            .suffix(".tmp")
+            .tempfile_in(&self.dir)
+            .context("create download cache tmp")?;
+        serde_json::to_writer(&mut tmp, &entry).context("write download cache tmp")?;
+        tmp.flush().context("flush download cache tmp")?;
+        tmp.persist(&path)
+            .map(|_| ())
+            .map_err(|err| anyhow::Error::new(err.error).context("rename download cache tmp"))
+    }
+
+    /// The file for `hash`. Only content hashes are cached, which also keeps
+    /// the name from escaping the directory.
+    fn path(&self, hash: &str) -> Option<PathBuf> {
+        (hash.len() == 64 && hash.chars().all(|c| c.is_ascii_hexdigit()))
+            .then(|| self.dir.join(format!("{hash}.json")))
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    const HASH: &str = "abc123def456abc123def456abc123def456abc123def456abc123def456abc1";
+
+    fn filter(toml: &str) -> DownloadedFilter {
+        serde_json::from_value(serde_json::json!({
+            "filter_toml": toml,
+            "test_files": [{"filename": "basic.toml", "content": "name = \"basic\""}],
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn stored_entries_load_back() {
+        let rt = Runtime::isolated();
+        let cache = DownloadCache::open(&rt).unwrap();
+        assert!(cache.load(HASH).is_none());
+
+        cache
+            .store(HASH, "\"v1\"", &filter("command = \"a\""))
+            .unwrap();
+        cache
+            .store(HASH, "\"v2\"", &filter("command = \"b\""))
+            .unwrap();
+        let entry = cache.load(HASH).unwrap();
+        assert_eq!(entry.etag, "\"v2\"");
+        assert_eq!(entry.filter.filter_toml, "command = \"b\"");
+        assert_eq!(entry.filter.test_files[0].filename, "basic.toml");
+    }
+
+    #[test]
+    fn only_content_hashes_are_cached() {
+        let rt = Runtime::isolated();
+        let cache = DownloadCache::open(&rt).unwrap();
+        assert!(cache.store("../escape", "\"v1\"", &filter("x")).is_err());
+        assert!(cache.load("../escape").is_none());
+    }
+
+    #[test]
+    fn a_corrupt_entry_is_a_miss() {
+        let rt = Runtime::isolated();
+        let cache = DownloadCache::open(&rt).unwrap();
+        std::fs::create_dir_all(&cache.dir).unwrap();
+        std::fs::write(cache.path(HASH).unwrap(), "not json").unwrap();
+        assert!(cache.load(HASH).is_none());
+    }
+}
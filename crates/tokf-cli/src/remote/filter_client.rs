@@ -1,6 +1,9 @@
 use serde::{Deserialize, Serialize};
 
+use super::async_http::AsyncClient;
+use super::download_cache::DownloadCache;
 use super::http::Client;
+use crate::runtime::Runtime;
 
 #[derive(Debug, Deserialize, Serialize)]
 pub struct FilterSummary {
@@ -14,11 +17,38 @@ pub struct FilterSummary {
     #[serde(default)]
     pub test_count: i64,
     #[serde(default)]
+    pub downloads: i64,
+    #[serde(default)]
+    pub stars: i64,
+    #[serde(default)]
     pub is_stdlib: bool,
     #[serde(default)]
     pub introduced_at: Option<String>,
     #[serde(default)]
     pub deprecated_at: Option<String>,
+    #[serde(default)]
+    pub successor_hash: Option<String>,
+    #[serde(default)]
+    pub yanked_at: Option<String>,
+    #[serde(default)]
+    pub yank_reason: Option<String>,
+    /// GitHub org the filter is published under.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub org: Option<String>,
+    /// `public`, or `private` for a filter only its author and org can see;
+    /// absent from older servers.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub visibility: Option<String>,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub version: Option<String>,
+    /// Name of the registry this result came from; set by the client when
+    /// searching more than one registry.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub registry: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -31,6 +61,10 @@ pub struct FilterDetails {
     pub created_at: String,
     #[serde(default)]
     pub test_count: i64,
+    #[serde(default)]
+    pub downloads: i64,
+    #[serde(default)]
+    pub stars: i64,
     pub registry_url: String,
     #[serde(default)]
     pub is_stdlib: bool,
@@ -38,15 +72,100 @@ pub struct FilterDetails {
     pub introduced_at: Option<String>,
     #[serde(default)]
     pub deprecated_at: Option<String>,
+    #[serde(default)]
+    pub successor_hash: Option<String>,
+    #[serde(default)]
+    pub yanked_at: Option<String>,
+    #[serde(default)]
+    pub yank_reason: Option<String>,
+    /// GitHub org the filter is published under.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub org: Option<String>,
+    /// `public`, or `private` for a filter only its author and org can see;
+    /// absent from older servers.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub visibility: Option<String>,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub version: Option<String>,
 }
 
-#[derive(Debug, Deserialize)]
+/// Whether a published filter has been yanked or deprecated, borrowed from
+/// a [`FilterSummary`] or [`FilterDetails`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Retirement<'a> {
+    pub yanked_at: Option<&'a str>,
+    pub yank_reason: Option<&'a str>,
+    pub deprecated_at: Option<&'a str>,
+    pub successor_hash: Option<&'a str>,
+}
+
+impl Retirement<'_> {
+    /// A short explanation such as `yanked (leaks env)` or
+    /// `deprecated; use abc123… instead`, or `None` for a filter in good
+    /// standing.
+    pub fn notice(&self) -> Option<String> {
+        let yanked = self.yanked_at.map(|_| {
+            self.yank_reason
+                .map_or_else(|| "yanked".to_string(), |r| format!("yanked ({r})"))
+        });
+        let deprecated = self.deprecated_at.map(|_| {
+            self.successor_hash.map_or_else(
+                || "deprecated".to_string(),
+                |s| format!("deprecated; use {s} instead"),
+            )
+        });
+        match (yanked, deprecated) {
+            (Some(y), Some(d)) => Some(format!("{y} and {d}")),
+            (y, d) => y.or(d),
+        }
+    }
+}
+
+impl FilterSummary {
+    /// Whether only the author and members of [`Self::org`] can see it.
+    pub fn is_private(&self) -> bool {
+        self.visibility.as_deref() == Some("private")
+    }
+
+    /// `org/<name>` for an org filter, otherwise the author.
+    pub fn namespace(&self) -> String {
+        self.org
+            .as_ref()
+            .map_or_else(|| self.author.clone(), |org| format!("org/{org}"))
+    }
+
+    pub fn retirement(&self) -> Retirement<'_> {
+        Retirement {
+            yanked_at: self.yanked_at.as_deref(),
+            yank_reason: self.yank_reason.as_deref(),
+            deprecated_at: self.deprecated_at.as_deref(),
+            successor_hash: self.successor_hash.as_deref(),
+        }
+    }
+}
+
+impl FilterDetails {
+    pub fn retirement(&self) -> Retirement<'_> {
+        Retirement {
+            yanked_at: self.yanked_at.as_deref(),
+            yank_reason: self.yank_reason.as_deref(),
+            deprecated_at: self.deprecated_at.as_deref(),
+            successor_hash: self.successor_hash.as_deref(),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize)]
 pub struct TestFilePayload {
     pub filename: String,
     pub content: String,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Serialize)]
 pub struct DownloadedFilter {
     pub filter_toml: String,
     pub test_files: Vec<TestFilePayload>,
@@ -62,6 +181,100 @@ pub struct DownloadedFilter {
     /// then to the URL hash.
     #[serde(default)]
     pub v1_hash: Option<String>,
+    /// Author's Ed25519 signature over `filter_toml` (hex). `None` for
+    /// unsigned filters and against servers without signing support.
+    #[serde(default)]
+    pub signature: Option<String>,
+    /// Public key `signature` was made with (hex).
+    #[serde(default)]
+    pub signing_key: Option<String>,
+}
+
+/// Response header carrying the cursor for the next page of search results.
+pub const NEXT_CURSOR_HEADER: &str = "x-next-cursor";
+
+/// How the registry orders search results.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SearchSort {
+    /// Savings percentage weighted by usage volume.
+    #[default]
+    Relevance,
+    /// Most downloaded first.
+    Downloads,
+    /// Most recently published first.
+    Recent,
+    /// Most starred first.
+    Stars,
+}
+
+impl SearchSort {
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Relevance => "relevance",
+            Self::Downloads => "downloads",
+            Self::Recent => "recent",
+            Self::Stars => "stars",
+        }
+    }
+}
+
+/// A registry search: the query plus optional filters and a page cursor.
+#[derive(Debug, Clone, Default)]
+pub struct SearchQuery<'a> {
+    /// Substring of the command pattern, or a tag; empty matches everything.
+    pub query: &'a str,
+    pub limit: usize,
+    pub sort: SearchSort,
+    /// Only filters by this author.
+    pub author: Option<&'a str>,
+    /// Only standard-library (`true`) or only community (`false`) filters.
+    pub stdlib: Option<bool>,
+    /// Only filters for this command and its subcommands.
+    pub command: Option<&'a str>,
+    /// [`SearchPage::next_cursor`] of the previous page.
+    pub cursor: Option<&'a str>,
+    /// Also return yanked filters, which the registry hides by default.
+    pub include_yanked: bool,
+    /// Only filters published under this GitHub org.
+    pub org: Option<&'a str>,
+}
+
+impl SearchQuery<'_> {
+    fn params(&self) -> Vec<(&'static str, String)> {
+        let mut params = vec![
+            ("q", self.query.to_string()),
+            ("limit", self.limit.to_string()),
+        ];
+        if self.sort != SearchSort::Relevance {
+            params.push(("sort", self.sort.as_str().to_string()));
+        }
+        let optional = [
+            ("author", self.author.map(str::to_string)),
+            ("stdlib", self.stdlib.map(|b| b.to_string())),
+            ("command", self.command.map(str::to_string)),
+            ("cursor", self.cursor.map(str::to_string)),
+            ("org", self.org.map(str::to_string)),
+            (
+                "include_yanked",
+                self.include_yanked.then(|| "true".to_string()),
+            ),
+        ];
+        params.extend(
+            optional
+                .into_iter()
+                .filter_map(|(key, value)| value.map(|v| (key, v))),
+        );
+        params
+    }
+}
+
+/// One page of search results.
+#[derive(Debug)]
+pub struct SearchPage {
+    pub filters: Vec<FilterSummary>,
+    /// Pass back as [`SearchQuery::cursor`] for the next page; `None` on the
+    /// last page and from servers without pagination.
+    pub next_cursor: Option<String>,
 }
 
 /// Search the community filter registry.
@@ -78,8 +291,45 @@ pub fn search_filters(
     query: &str,
     limit: usize,
 ) -> anyhow::Result<Vec<FilterSummary>> {
-    let limit_str = limit.to_string();
-    client.get_with_query("/api/filters", &[("q", query), ("limit", &limit_str)])
+    let search = SearchQuery {
+        query,
+        limit,
+        ..SearchQuery::default()
+    };
+    search_filters_page(client, &search).map(|page| page.filters)
+}
+
+/// Run `search` against the registry, returning one page of results.
+///
+/// # Errors
+///
+/// Returns an error if the server is unreachable, returns a non-success
+/// status, or the response body cannot be deserialized.
+pub fn search_filters_page(
+    client: &Client,
+    search: &SearchQuery<'_>,
+) -> anyhow::Result<SearchPage> {
+    let params = search.params();
+    let params: Vec<(&str, &str)> = params.iter().map(|(k, v)| (*k, v.as_str())).collect();
+    client.block_on(async {
+        let resp = client
+            .as_async()
+            .get_raw_with_query("/api/filters", &params)
+            .await?;
+        let next_cursor = resp
+            .headers()
+            .get(NEXT_CURSOR_HEADER)
+            .and_then(|v| v.to_str().ok())
+            .map(str::to_string);
+        let filters = resp
+            .json()
+            .await
+            .map_err(|e| anyhow::anyhow!("invalid response from server: {e}"))?;
+        Ok(SearchPage {
+            filters,
+            next_cursor,
+        })
+    })
 }
 
 /// Get details for a specific filter by content hash.
@@ -98,8 +348,51 @@ pub fn get_filter(client: &Client, hash: &str) -> anyhow::Result<FilterDetails>
 ///
 /// Returns an error if the server is unreachable, returns a non-success
 /// status, or the response body cannot be deserialized.
-pub fn download_filter(client: &Client, hash: &str) -> anyhow::Result<DownloadedFilter> {
-    client.get(&format!("/api/filters/{hash}/download"))
+pub async fn download_filter_async(
+    client: &AsyncClient,
+    hash: &str,
+) -> anyhow::Result<DownloadedFilter> {
+    client.get(&format!("/api/filters/{hash}/download")).await
+}
+
+/// Blocking [`download_filter_async`], through the download cache.
+///
+/// A cached copy is revalidated with `If-None-Match` and reused when the
+/// server answers 304; a fresh download replaces it. See
+/// [`super::download_cache`].
+///
+/// # Errors
+///
+/// See [`download_filter_async`].
+pub fn download_filter(
+    rt: &Runtime,
+    client: &Client,
+    hash: &str,
+) -> anyhow::Result<DownloadedFilter> {
+    let cache = DownloadCache::open(rt);
+    let cached = cache.as_ref().and_then(|c| c.load(hash));
+    let path = format!("/api/filters/{hash}/download");
+    let Some(resp) = client.get_if_none_match(&path, cached.as_ref().map(|c| c.etag.as_str()))?
+    else {
+        return cached
+            .map(|c| c.filter)
+            .ok_or_else(|| anyhow::anyhow!("server answered 304 to an unconditional download"));
+    };
+    let etag = resp
+        .headers()
+        .get(reqwest::header::ETAG)
+        .and_then(|v| v.to_str().ok())
+        .map(String::from);
+    let filter: DownloadedFilter = client
+        .block_on(resp.json())
+        .map_err(|e| anyhow::anyhow!("invalid response from server: {e}"))?;
+    if let (Some(cache), Some(etag)) = (cache, etag)
+        && let Err(e) = cache.store(hash, &etag, &filter)
+        && rt.debug()
+    {
+        eprintln!("[tokf] download cache error: {e:#}");
+    }
+    Ok(filter)
 }
 
 #[cfg(test)]
@@ -272,6 +565,42 @@ mod tests {
         assert!(summary.deprecated_at.is_none());
     }
 
+    #[test]
+    fn deserialize_filter_summary_with_metadata() {
+        let json = r#"{
+            "content_hash": "abc123",
+            "command_pattern": "cargo build",
+            "author": "alice",
+            "savings_pct": 0.0,
+            "total_commands": 0,
+            "description": "Compact cargo build output",
+            "tags": ["rust", "build"],
+            "version": "1.2.0"
+        }"#;
+        let summary: FilterSummary = serde_json::from_str(json).unwrap();
+        assert_eq!(
+            summary.description.as_deref(),
+            Some("Compact cargo build output")
+        );
+        assert_eq!(summary.tags, vec!["rust", "build"]);
+        assert_eq!(summary.version.as_deref(), Some("1.2.0"));
+    }
+
+    #[test]
+    fn deserialize_filter_summary_metadata_defaults_empty() {
+        let json = r#"{
+            "content_hash": "abc123",
+            "command_pattern": "git push",
+            "author": "alice",
+            "savings_pct": 0.0,
+            "total_commands": 0
+        }"#;
+        let summary: FilterSummary = serde_json::from_str(json).unwrap();
+        assert!(summary.description.is_none());
+        assert!(summary.tags.is_empty());
+        assert!(summary.version.is_none());
+    }
+
     #[test]
     fn deserialize_filter_details_with_version_fields() {
         let json = r#"{
@@ -288,6 +617,7 @@ mod tests {
         let details: FilterDetails = serde_json::from_str(json).unwrap();
         assert_eq!(details.introduced_at.as_deref(), Some("0.2.3"));
         assert_eq!(details.deprecated_at.as_deref(), Some("0.3.0"));
+        assert_eq!((details.downloads, details.stars), (0, 0));
     }
 
     #[test]
@@ -302,4 +632,30 @@ mod tests {
         let summary: FilterSummary = serde_json::from_str(json).unwrap();
         assert!(!summary.is_stdlib);
     }
+
+    #[test]
+    fn retirement_notice_describes_yanks_and_deprecations() {
+        assert_eq!(Retirement::default().notice(), None);
+        let yanked = Retirement {
+            yanked_at: Some("2026-10-01"),
+            yank_reason: Some("leaks env"),
+            ..Retirement::default()
+        };
+        assert_eq!(yanked.notice().as_deref(), Some("yanked (leaks env)"));
+        let deprecated = Retirement {
+            deprecated_at: Some("2026-10-01T00:00:00Z"),
+            successor_hash: Some("abc"),
+            ..Retirement::default()
+        };
+        assert_eq!(
+            deprecated.notice().as_deref(),
+            Some("deprecated; use abc instead")
+        );
+        let both = Retirement {
+            yanked_at: Some("2026-10-01"),
+            deprecated_at: Some("0.2.0"),
+            ..Retirement::default()
+        };
+        assert_eq!(both.notice().as_deref(), Some("yanked and deprecated"));
+    }
 }
@@ -1,9 +1,9 @@
-use reqwest::blocking::multipart::Form;
+use reqwest::multipart::Form;
 
 use crate::auth::credentials;
 use crate::runtime::Runtime;
 
-use super::{RemoteError, classify_reqwest_error, require_success};
+use super::async_http::AsyncClient;
 
 /// Load stored auth credentials and validate they are not expired.
 ///
@@ -19,18 +19,15 @@ pub fn load_auth(rt: &Runtime) -> anyhow::Result<credentials::LoadedAuth> {
     Ok(auth)
 }
 
-/// Centralized HTTP client for all tokf remote operations.
+/// Blocking HTTP client for the CLI's remote operations.
 ///
-/// Handles timeouts (`TOKF_HTTP_TIMEOUT`, default 5s), auth header injection,
-/// error classification into [`RemoteError`], and one automatic retry on
-/// transient failures for idempotent methods (GET/PUT).
+/// A thin wrapper that drives an [`AsyncClient`] on its own current-thread
+/// Tokio runtime, so timeouts, auth, error classification and retries are
+/// the async client's. Like `reqwest::blocking`, it must not be created,
+/// used or dropped inside an async context; use [`AsyncClient`] there.
 pub struct Client {
-    inner: reqwest::blocking::Client,
-    base_url: String,
-    token: Option<String>,
-    /// `TOKF_DEBUG`, captured so errors built here can render their verbose
-    /// form without reaching back into the environment.
-    debug: bool,
+    inner: AsyncClient,
+    runtime: tokio::runtime::Runtime,
 }
 
 impl Client {
@@ -42,18 +39,13 @@ impl Client {
     ///
     /// Returns an error if the underlying HTTP client cannot be constructed.
     pub fn new(rt: &Runtime, base_url: &str, token: Option<&str>) -> anyhow::Result<Self> {
-        let timeout = rt.http_timeout();
-        let inner = reqwest::blocking::Client::builder()
-            .timeout(timeout)
-            .connect_timeout(timeout)
-            .redirect(reqwest::redirect::Policy::limited(10))
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
             .build()
-            .map_err(|e| anyhow::anyhow!("could not build HTTP client: {e}"))?;
+            .map_err(|e| anyhow::anyhow!("could not start HTTP runtime: {e}"))?;
         Ok(Self {
-            inner,
-            base_url: base_url.trim_end_matches('/').to_string(),
-            token: token.map(String::from),
-            debug: rt.debug(),
+            inner: AsyncClient::new(rt, base_url, token)?,
+            runtime,
         })
     }
 
@@ -81,37 +73,29 @@ impl Client {
 
     /// The base URL this client is configured for.
     pub fn base_url(&self) -> &str {
-        &self.base_url
+        self.inner.base_url()
     }
 
-    /// Build the full URL for a request path.
-    ///
-    /// Panics in debug builds if `path` doesn't start with `/`.
-    fn url(&self, path: &str) -> String {
-        debug_assert!(
-            path.starts_with('/'),
-            "Client path must start with '/': {path}"
-        );
-        format!("{}{path}", self.base_url)
+    /// The async client this one drives.
+    pub const fn as_async(&self) -> &AsyncClient {
+        &self.inner
     }
 
-    /// GET `{base_url}{path}` and deserialize JSON.
-    ///
-    /// Retries once on transient errors (connection/timeout/5xx).
+    /// Run `future` to completion on this client's runtime.
+    pub fn block_on<F: Future>(&self, future: F) -> F::Output {
+        self.runtime.block_on(future)
+    }
+
+    /// Blocking [`AsyncClient::get`].
     ///
     /// # Errors
     ///
     /// Returns an error on network failure, non-2xx status, or JSON parse error.
     pub fn get<T: serde::de::DeserializeOwned>(&self, path: &str) -> anyhow::Result<T> {
-        let url = self.url(path);
-        let resp = self.execute_idempotent(|c| c.inner.get(&url), &url)?;
-        resp.json::<T>()
-            .map_err(|e| anyhow::anyhow!("invalid response from server: {e}"))
+        self.block_on(self.inner.get(path))
     }
 
-    /// GET `{base_url}{path}` with query parameters and deserialize JSON.
-    ///
-    /// Retries once on transient errors.
+    /// Blocking [`AsyncClient::get_with_query`].
     ///
     /// # Errors
     ///
@@ -121,67 +105,54 @@ impl Client {
         path: &str,
         params: &[(&str, &str)],
     ) -> anyhow::Result<T> {
-        let url = self.url(path);
-        let resp = self.execute_idempotent(|c| c.inner.get(&url).query(params), &url)?;
-        resp.json::<T>()
-            .map_err(|e| anyhow::anyhow!("invalid response from server: {e}"))
+        self.block_on(self.inner.get_with_query(path, params))
     }
 
-    /// GET `{base_url}{path}` and return the raw response.
-    ///
-    /// Retries once on transient errors. Useful for non-JSON endpoints.
+    /// Blocking [`AsyncClient::get_raw`].
     ///
     /// # Errors
     ///
     /// Returns an error on network failure or non-2xx status.
-    pub fn get_raw(&self, path: &str) -> anyhow::Result<reqwest::blocking::Response> {
-        let url = self.url(path);
-        self.execute_idempotent(|c| c.inner.get(&url), &url)
+    pub fn get_raw(&self, path: &str) -> anyhow::Result<reqwest::Response> {
+        self.block_on(self.inner.get_raw(path))
     }
 
-    /// POST `{base_url}{path}` with a JSON body and deserialize the response.
+    /// Blocking [`AsyncClient::get_if_none_match`].
     ///
-    /// Does **not** retry — POST is non-idempotent.
+    /// # Errors
+    ///
+    /// Returns an error on network failure or a non-2xx, non-304 status.
+    pub fn get_if_none_match(
+        &self,
+        path: &str,
+        etag: Option<&str>,
+    ) -> anyhow::Result<Option<reqwest::Response>> {
+        self.block_on(self.inner.get_if_none_match(path, etag))
+    }
+
+    /// Blocking [`AsyncClient::post`].
     ///
     /// # Errors
     ///
     /// Returns an error on network failure, non-2xx status, or JSON parse error.
-    pub fn post<B: serde::Serialize, T: serde::de::DeserializeOwned>(
+    pub fn post<B: serde::Serialize + Sync, T: serde::de::DeserializeOwned>(
         &self,
         path: &str,
         body: &B,
     ) -> anyhow::Result<T> {
-        let url = self.url(path);
-        let resp = Self::send_once(
-            self.build_request(self.inner.post(&url)).json(body),
-            &url,
-            self.debug,
-        )?;
-        resp.json::<T>()
-            .map_err(|e| anyhow::anyhow!("invalid response from server: {e}"))
+        self.block_on(self.inner.post(path, body))
     }
 
-    /// DELETE `{base_url}{path}`, returning the raw response.
-    ///
-    /// Does **not** retry — DELETE is non-idempotent in our usage
-    /// (account deletion is a one-shot operation).
+    /// Blocking [`AsyncClient::delete`].
     ///
     /// # Errors
     ///
     /// Returns an error on network failure or non-2xx status.
-    pub fn delete(&self, path: &str) -> anyhow::Result<reqwest::blocking::Response> {
-        let url = self.url(path);
-        Self::send_once(
-            self.build_request(self.inner.delete(&url)),
-            &url,
-            self.debug,
-        )
+    pub fn delete(&self, path: &str) -> anyhow::Result<reqwest::Response> {
+        self.block_on(self.inner.delete(path))
     }
 
-    /// POST `{base_url}{path}` with a multipart form.
-    ///
-    /// Returns the raw response — callers handle per-status-code logic (e.g.
-    /// 400 test failures in publish). Does not retry (POST is non-idempotent).
+    /// Blocking [`AsyncClient::post_multipart`].
     ///
     /// # Errors
     ///
@@ -190,115 +161,26 @@ impl Client {
         &self,
         path: &str,
         form_builder: F,
-    ) -> anyhow::Result<reqwest::blocking::Response>
+    ) -> anyhow::Result<reqwest::Response>
     where
         F: FnOnce() -> Form,
     {
-        let url = self.url(path);
-        let form = form_builder();
-        self.build_request(self.inner.post(&url))
-            .multipart(form)
-            .send()
-            .map_err(|e| anyhow::Error::from(classify_reqwest_error(&url, e, self.debug)))
+        self.block_on(self.inner.post_multipart(path, form_builder()))
     }
 
-    /// PUT `{base_url}{path}` with a multipart form.
-    ///
-    /// Returns the raw response — callers handle per-status-code logic.
-    /// Retries once on transport-level errors (PUT is idempotent).
-    /// `form_builder` must be callable multiple times (forms are consumed on send).
+    /// Blocking [`AsyncClient::put_multipart`].
     ///
     /// # Errors
     ///
     /// Returns an error on network failure (does not check status).
-    pub fn put_multipart<F>(
-        &self,
-        path: &str,
-        form_builder: F,
-    ) -> anyhow::Result<reqwest::blocking::Response>
-    where
-        F: Fn() -> Form,
-    {
-        let url = self.url(path);
-        let result = self
-            .build_request(self.inner.put(&url))
-            .multipart(form_builder())
-            .send();
-
-        match result {
-            Ok(resp) => Ok(resp),
-            Err(first_err) => {
-                let classified = classify_reqwest_error(&url, first_err, self.debug);
-                if classified.is_transient() {
-                    // Retry once on transport errors.
-                    self.build_request(self.inner.put(&url))
-                        .multipart(form_builder())
-                        .send()
-                        .map_err(|e| {
-                            anyhow::Error::from(classify_reqwest_error(&url, e, self.debug))
-                        })
-                } else {
-                    Err(classified.into())
-                }
-            }
-        }
-    }
-
-    // ── Internal helpers ────────────────────────────────────────────────
-
-    /// Add auth header if a token is present.
-    fn build_request(
-        &self,
-        builder: reqwest::blocking::RequestBuilder,
-    ) -> reqwest::blocking::RequestBuilder {
-        match &self.token {
-            Some(token) => builder.header("Authorization", format!("Bearer {token}")),
-            None => builder,
-        }
-    }
-
-    /// Execute an idempotent request (GET/PUT), retrying once on transient errors.
-    fn execute_idempotent<F>(
-        &self,
-        make_request: F,
-        url: &str,
-    ) -> anyhow::Result<reqwest::blocking::Response>
+    pub fn put_multipart<F>(&self, path: &str, form_builder: F) -> anyhow::Result<reqwest::Response>
     where
-        F: Fn(&Self) -> reqwest::blocking::RequestBuilder,
+        F: Fn() -> Form + Sync,
     {
-        let req = self.build_request(make_request(self));
-        match send_and_classify(req, url, self.debug) {
-            Ok(resp) => Ok(resp),
-            Err(err) if err.is_transient() => {
-                let req = self.build_request(make_request(self));
-                Self::send_once(req, url, self.debug)
-            }
-            Err(err) => Err(err.into()),
-        }
-    }
-
-    /// Send a request once (no retry). Converts to `anyhow::Error`.
-    fn send_once(
-        request: reqwest::blocking::RequestBuilder,
-        url: &str,
-        debug: bool,
-    ) -> anyhow::Result<reqwest::blocking::Response> {
-        send_and_classify(request, url, debug).map_err(anyhow::Error::from)
+        self.block_on(self.inner.put_multipart(path, form_builder))
     }
 }
 
-/// Send a request, classify errors into [`RemoteError`], and check the response status.
-fn send_and_classify(
-    request: reqwest::blocking::RequestBuilder,
-    url: &str,
-    debug: bool,
-) -> Result<reqwest::blocking::Response, RemoteError> {
-    let resp = request
-        .send()
-        .map_err(|e| classify_reqwest_error(url, e, debug))?;
-    require_success(resp, url, debug)
-}
-
 #[cfg(test)]
 #[allow(clippy::unwrap_used)]
 mod tests {
@@ -321,46 +203,10 @@ mod tests {
     }
 
     #[test]
-    fn client_base_url_trims_trailing_slash() {
-        let rt = Runtime::isolated();
-        let c = Client::new(&rt, "https://api.tokf.net/", None).unwrap();
-        assert_eq!(c.base_url(), "https://api.tokf.net");
-    }
-
-    #[test]
-    fn client_base_url_no_trailing_slash() {
+    fn client_wraps_an_async_client_for_the_same_url() {
         let rt = Runtime::isolated();
-        let c = Client::new(&rt, "https://api.tokf.net", None).unwrap();
+        let c = Client::unauthenticated(&rt, "https://api.tokf.net/").unwrap();
         assert_eq!(c.base_url(), "https://api.tokf.net");
-    }
-
-    #[test]
-    fn client_unauthenticated_has_no_token() {
-        let rt = Runtime::isolated();
-        let c = Client::unauthenticated(&rt, "https://api.tokf.net").unwrap();
-        assert!(c.token.is_none());
-    }
-
-    #[test]
-    fn client_new_with_token() {
-        let rt = Runtime::isolated();
-        let c = Client::new(&rt, "https://api.tokf.net", Some("tok123")).unwrap();
-        assert_eq!(c.token.as_deref(), Some("tok123"));
-    }
-
-    #[test]
-    fn url_helper_formats_correctly() {
-        let rt = Runtime::isolated();
-        let c = Client::new(&rt, "https://api.tokf.net/", None).unwrap();
-        assert_eq!(c.url("/api/test"), "https://api.tokf.net/api/test");
-    }
-
-    #[test]
-    #[cfg(debug_assertions)]
-    #[should_panic(expected = "must start with '/'")]
-    fn url_helper_panics_on_missing_slash_in_debug() {
-        let rt = Runtime::isolated();
-        let c = Client::new(&rt, "https://api.tokf.net", None).unwrap();
-        let _ = c.url("api/test");
+        assert_eq!(c.as_async().base_url(), c.base_url());
     }
 }
@@ -0,0 +1,62 @@
+use serde::{Deserialize, Serialize};
+
+use super::http::Client;
+
+#[derive(Debug, Serialize)]
+struct RegisterKeyRequest<'a> {
+    public_key: &'a str,
+}
+
+/// A signing key registered to the authenticated user.
+#[derive(Debug, Deserialize)]
+pub struct SigningKeyInfo {
+    pub public_key: String,
+    pub fingerprint: String,
+    pub created_at: String,
+}
+
+/// Register a filter-signing public key (hex) with the server.
+///
+/// Registering a key the user already has is a no-op.
+///
+/// # Errors
+///
+/// Returns an error on network failure, non-2xx status (e.g. 409 when the
+/// key belongs to another user), or an invalid response.
+pub fn register_key(client: &Client, public_key: &str) -> anyhow::Result<SigningKeyInfo> {
+    client.post("/api/keys", &RegisterKeyRequest { public_key })
+}
+
+/// List the authenticated user's signing keys, newest first.
+///
+/// # Errors
+///
+/// Returns an error on network failure, non-2xx status, or an invalid response.
+pub fn list_keys(client: &Client) -> anyhow::Result<Vec<SigningKeyInfo>> {
+    client.get("/api/keys")
+}
+
+/// Revoke one of the authenticated user's signing keys.
+///
+/// # Errors
+///
+/// Returns an error on network failure or non-2xx status (404 if the key is
+/// not registered to the user).
+pub fn revoke_key(client: &Client, public_key: &str) -> anyhow::Result<()> {
+    client.delete(&format!("/api/keys/{public_key}"))?;
+    Ok(())
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn deserialize_signing_key_info() {
+        let json = r#"{"public_key": "ab12", "fingerprint": "0011223344556677", "created_at": "2026-10-15T00:00:00Z"}"#;
+        let info: SigningKeyInfo = serde_json::from_str(json).unwrap();
+        assert_eq!(info.public_key, "ab12");
+        assert_eq!(info.fingerprint, "0011223344556677");
+    }
+}
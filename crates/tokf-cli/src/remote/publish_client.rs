@@ -1,6 +1,7 @@
-use reqwest::blocking::multipart::{Form, Part};
+use reqwest::multipart::{Form, Part};
 use serde::Deserialize;
 
+use super::async_http::AsyncClient;
 use super::check_auth_and_rate_limit;
 use super::http::Client;
 
@@ -13,6 +14,30 @@ fn extract_error_message(body: &str) -> String {
         .unwrap_or_else(|| body.to_string())
 }
 
+/// Turn a 400 response into an error, calling out server-side test failures.
+async fn bad_request_error(resp: reqwest::Response) -> anyhow::Error {
+    let status = resp.status();
+    let body = match resp.text().await {
+        Ok(body) => body,
+        Err(e) => return anyhow::anyhow!("could not read response body: {e}"),
+    };
+    let msg = extract_error_message(&body);
+    if msg.contains("tests failed") {
+        return anyhow::anyhow!(
+            "server-side test verification failed:\n\n{msg}\n\n\
+             Hint: run `tokf verify` locally to debug test failures"
+        );
+    }
+    anyhow::anyhow!("server returned HTTP {status}: {msg}")
+}
+
+/// Turn any other non-2xx response into an error carrying its body.
+async fn status_error(resp: reqwest::Response) -> anyhow::Error {
+    let status = resp.status();
+    let body = resp.text().await.unwrap_or_default();
+    anyhow::anyhow!("server returned HTTP {status}: {body}")
+}
+
 #[derive(Debug, Deserialize)]
 pub struct PublishResponse {
     pub content_hash: String,
@@ -21,7 +46,26 @@ pub struct PublishResponse {
     pub registry_url: String,
 }
 
-/// Publish a filter and optional test files to the community registry.
+/// An author's signature over the filter bytes being published.
+#[derive(Debug, Clone)]
+pub struct FilterSignature {
+    /// Hex Ed25519 public key, registered with `tokf auth keys generate`.
+    pub public_key: String,
+    /// Hex signature from [`tokf_common::signing::SigningKey::sign`].
+    pub signature: String,
+}
+
+/// Where a filter is published and who can see it.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Namespace<'a> {
+    /// GitHub org to publish under; the author must be a member.
+    pub org: Option<&'a str>,
+    /// Visible only to the author and, with `org`, the org's members.
+    pub private: bool,
+}
+
+/// Publish a filter and optional test files to the community registry,
+/// optionally signed, under the author's name or an org.
 ///
 /// Returns `(is_new, response)`:
 /// - `is_new = true` when the server returns `201 Created` (first upload).
@@ -30,53 +74,75 @@ pub struct PublishResponse {
 /// # Errors
 ///
 /// Returns an error if the server is unreachable, returns a non-success
-/// status, or the response body cannot be deserialized.
-pub fn publish_filter(
-    client: &Client,
+/// status (403 = not a member of the org, 409 = an identical filter is
+/// published privately), or the response body cannot be deserialized.
+pub async fn publish_filter_async(
+    client: &AsyncClient,
     filter_bytes: &[u8],
     test_files: &[(String, Vec<u8>)],
+    signature: Option<&FilterSignature>,
+    namespace: Namespace<'_>,
 ) -> anyhow::Result<(bool, PublishResponse)> {
-    let filter_bytes = filter_bytes.to_vec();
-    let test_files = test_files.to_vec();
-    let resp = client.post_multipart("/api/filters", move || {
-        let mut form = Form::new()
-            .part("filter", Part::bytes(filter_bytes))
-            .part("mit_license_accepted", Part::text("true"));
-        for (name, bytes) in test_files {
-            form = form.part(format!("test:{name}"), Part::bytes(bytes));
-        }
-        form
-    })?;
+    let mut form = Form::new()
+        .part("filter", Part::bytes(filter_bytes.to_vec()))
+        .part("mit_license_accepted", Part::text("true"));
+    if let Some(org) = namespace.org {
+        form = form.part("org", Part::text(org.to_string()));
+    }
+    if namespace.private {
+        form = form.part("visibility", Part::text("private"));
+    }
+    if let Some(sig) = signature {
+        form = form
+            .part("public_key", Part::text(sig.public_key.clone()))
+            .part("signature", Part::text(sig.signature.clone()));
+    }
+    for (name, bytes) in test_files {
+        form = form.part(format!("test:{name}"), Part::bytes(bytes.clone()));
+    }
+    let resp = client.post_multipart("/api/filters", form).await?;
 
     check_auth_and_rate_limit(&resp)?;
 
-    let is_new = resp.status() == reqwest::StatusCode::CREATED;
     let status = resp.status();
-
     // Extract test failure details from 400 responses before the generic handler
     if status == reqwest::StatusCode::BAD_REQUEST {
-        let body = resp
-            .text()
-            .map_err(|e| anyhow::anyhow!("could not read response body: {e}"))?;
-        let msg = extract_error_message(&body);
-        if msg.contains("tests failed") {
-            anyhow::bail!(
-                "server-side test verification failed:\n\n{msg}\n\n\
-                 Hint: run `tokf verify` locally to debug test failures"
-            );
-        }
-        anyhow::bail!("server returned HTTP {status}: {msg}");
+        return Err(bad_request_error(resp).await);
+    }
+    if status == reqwest::StatusCode::FORBIDDEN || status == reqwest::StatusCode::CONFLICT {
+        let body = resp.text().await.unwrap_or_default();
+        anyhow::bail!("{}", extract_error_message(&body));
     }
-
     if !status.is_success() {
-        let body = resp.text().unwrap_or_default();
-        anyhow::bail!("server returned HTTP {status}: {body}");
+        return Err(status_error(resp).await);
     }
 
     let response = resp
         .json::<PublishResponse>()
+        .await
         .map_err(|e| anyhow::anyhow!("invalid response from server: {e}"))?;
-    Ok((is_new, response))
+    Ok((status == reqwest::StatusCode::CREATED, response))
+}
+
+/// Blocking [`publish_filter_async`].
+///
+/// # Errors
+///
+/// See [`publish_filter_async`].
+pub fn publish_filter(
+    client: &Client,
+    filter_bytes: &[u8],
+    test_files: &[(String, Vec<u8>)],
+    signature: Option<&FilterSignature>,
+    namespace: Namespace<'_>,
+) -> anyhow::Result<(bool, PublishResponse)> {
+    client.block_on(publish_filter_async(
+        client.as_async(),
+        filter_bytes,
+        test_files,
+        signature,
+        namespace,
+    ))
 }
 
 #[derive(Debug, Deserialize)]
@@ -98,20 +164,21 @@ pub struct UpdateTestsResponse {
 /// Returns an error if the server is unreachable, returns a non-success
 /// status (403 = not author, 404 = filter not found), or the response
 /// body cannot be deserialized.
-pub fn update_tests(
-    client: &Client,
+pub async fn update_tests_async(
+    client: &AsyncClient,
     content_hash: &str,
     test_files: &[(String, Vec<u8>)],
 ) -> anyhow::Result<UpdateTestsResponse> {
     let path = format!("/api/filters/{content_hash}/tests");
-    let test_files = test_files.to_vec();
-    let resp = client.put_multipart(&path, || {
-        let mut form = Form::new();
-        for (name, bytes) in &test_files {
-            form = form.part(format!("test:{name}"), Part::bytes(bytes.clone()));
-        }
-        form
-    })?;
+    let resp = client
+        .put_multipart(&path, || {
+            let mut form = Form::new();
+            for (name, bytes) in test_files {
+                form = form.part(format!("test:{name}"), Part::bytes(bytes.clone()));
+            }
+            form
+        })
+        .await?;
 
     check_auth_and_rate_limit(&resp)?;
 
@@ -123,26 +190,33 @@ pub fn update_tests(
         anyhow::bail!("filter not found in registry (hash: {content_hash})");
     }
     if status == reqwest::StatusCode::BAD_REQUEST {
-        let body = resp
-            .text()
-            .map_err(|e| anyhow::anyhow!("could not read response body: {e}"))?;
-        let msg = extract_error_message(&body);
-        if msg.contains("tests failed") {
-            anyhow::bail!(
-                "server-side test verification failed:\n\n{msg}\n\n\
-                 Hint: run `tokf verify` locally to debug test failures"
-            );
-        }
-        anyhow::bail!("server returned HTTP {status}: {msg}");
+        return Err(bad_request_error(resp).await);
     }
     if !status.is_success() {
-        let body = resp.text().unwrap_or_default();
-        anyhow::bail!("server returned HTTP {status}: {body}");
+        return Err(status_error(resp).await);
     }
     resp.json::<UpdateTestsResponse>()
+        .await
         .map_err(|e| anyhow::anyhow!("invalid response from server: {e}"))
 }
 
+/// Blocking [`update_tests_async`].
+///
+/// # Errors
+///
+/// See [`update_tests_async`].
+pub fn update_tests(
+    client: &Client,
+    content_hash: &str,
+    test_files: &[(String, Vec<u8>)],
+) -> anyhow::Result<UpdateTestsResponse> {
+    client.block_on(update_tests_async(
+        client.as_async(),
+        content_hash,
+        test_files,
+    ))
+}
+
 #[cfg(test)]
 #[allow(clippy::unwrap_used, clippy::expect_used)]
 mod tests {
@@ -0,0 +1,284 @@
+//! Filter registries: the one `tokf auth login` signs into, plus any
+//! `[[registry]]` entries in the global `config.toml`:
+//!
+//! ```toml
+//! [[registry]]
+//! name = "acme"
+//! url = "https://tokf.acme.internal"
+//! token = "…"
+//! priority = -10
+//! ```
+//!
+//! Registries are consulted in ascending `priority` (unset means 0, ties keep
+//! file order). The built-in registry is named `default` and has priority
+//! 100, so configured registries come first unless an entry named `default`
+//! says otherwise. Entries are only read from the global config so that a
+//! checked-out project cannot point tokf at a registry of its choosing.
+
+use crate::auth::credentials;
+use crate::history::{TokfRegistryEntry, load_project_config};
+use crate::runtime::Runtime;
+
+use super::http::{Client, load_auth};
+
+/// Name of the registry `tokf auth login` signs into.
+pub const DEFAULT_NAME: &str = "default";
+
+/// Priority of the built-in registry when no entry overrides it.
+const DEFAULT_PRIORITY: i64 = 100;
+
+/// One registry tokf can search, install from and publish to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Registry {
+    pub name: String,
+    pub url: String,
+    token: Option<String>,
+}
+
+impl Registry {
+    /// An authenticated client for this registry.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the registry has no usable token or the client
+    /// cannot be constructed.
+    pub fn client(&self, rt: &Runtime) -> anyhow::Result<Client> {
+        match &self.token {
+            Some(token) => Client::new(rt, &self.url, Some(token)),
+            None if self.url == login_server(rt) => {
+                // Not logged in or expired: surface load_auth's message.
+                let auth = load_auth(rt)?;
+                Client::new(rt, &self.url, Some(&auth.token))
+            }
+            None => anyhow::bail!(
+                "registry '{}' has no token — set `token` in its [[registry]] entry \
+                 or log in to {} with `tokf auth login`",
+                self.name,
+                self.url
+            ),
+        }
+    }
+}
+
+/// Every registry, highest priority first.
+pub fn all(rt: &Runtime) -> Vec<Registry> {
+    let entries = rt
+        .global_config_path()
+        .and_then(|p| load_project_config(&p).registry)
+        .unwrap_or_default();
+    let auth = credentials::load(rt);
+    let login_url = auth
+        .as_ref()
+        .map_or_else(|| rt.server_url().to_string(), |a| a.server_url.clone());
+    resolve(entries, &login_url, auth)
+}
+
+/// The registry called `name` (or with base URL `name`).
+///
+/// # Errors
+///
+/// Returns an error naming the known registries when none matches.
+pub fn find(rt: &Runtime, name: &str) -> anyhow::Result<Registry> {
+    let registries = all(rt);
+    let url = name.trim_end_matches('/');
+    if let Some(found) = registries.iter().find(|r| r.name == name || r.url == url) {
+        return Ok(found.clone());
+    }
+    let known: Vec<&str> = registries.iter().map(|r| r.name.as_str()).collect();
+    anyhow::bail!(
+        "unknown registry '{name}' (configured: {})",
+        known.join(", ")
+    )
+}
+
+/// The registry named by `--registry`, or every registry in priority order.
+///
+/// # Errors
+///
+/// Returns an error if `name` matches no registry.
+pub fn select(rt: &Runtime, name: Option<&str>) -> anyhow::Result<Vec<Registry>> {
+    match name {
+        Some(name) => Ok(vec![find(rt, name)?]),
+        None => Ok(all(rt)),
+    }
+}
+
+/// The registry named by `--registry`, or the highest-priority one.
+///
+/// # Errors
+///
+/// Returns an error if `name` matches no registry.
+pub fn primary(rt: &Runtime, name: Option<&str>) -> anyhow::Result<Registry> {
+    select(rt, name)?
+        .into_iter()
+        .next()
+        .ok_or_else(|| anyhow::anyhow!("no registry configured"))
+}
+
+/// The server `tokf auth login` signed into, or would sign into.
+fn login_server(rt: &Runtime) -> String {
+    credentials::load(rt).map_or_else(|| rt.server_url().to_string(), |a| a.server_url)
+}
+
+/// Order `entries` plus the built-in registry at `login_url`; entries without
+/// a token borrow the login token when they point at its server.
+fn resolve(
+    entries: Vec<TokfRegistryEntry>,
+    login_url: &str,
+    auth: Option<credentials::LoadedAuth>,
+) -> Vec<Registry> {
+    let login_token = auth
+        .filter(|a| !a.is_expired() && a.server_url == login_url)
+        .map(|a| a.token);
+    let mut ranked: Vec<(i64, Registry)> = Vec::new();
+    if !entries.iter().any(|e| e.name == DEFAULT_NAME) {
+        let default = TokfRegistryEntry {
+            name: DEFAULT_NAME.to_string(),
+            url: login_url.to_string(),
+            token: None,
+            priority: Some(DEFAULT_PRIORITY),
+        };
+        ranked.push((
+            DEFAULT_PRIORITY,
+            registry(default, login_url, login_token.as_ref()),
+        ));
+    }
+    for entry in entries {
+        if ranked.iter().any(|(_, r)| r.name == entry.name) {
+            eprintln!(
+                "[tokf] warning: duplicate [[registry]] name '{}' ignored",
+                entry.name
+            );
+            continue;
+        }
+        let priority = entry.priority.unwrap_or(0);
+        ranked.push((priority, registry(entry, login_url, login_token.as_ref())));
+    }
+    // Stable: equal priorities keep their order (the built-in one last).
+    ranked.sort_by_key(|(priority, _)| *priority);
+    ranked.into_iter().map(|(_, r)| r).collect()
+}
+
+fn registry(entry: TokfRegistryEntry, login_url: &str, login_token: Option<&String>) -> Registry {
+    let url = entry.url.trim_end_matches('/').to_string();
+    let token = entry
+        .token
+        .or_else(|| (url == login_url).then(|| login_token.cloned()).flatten());
+    Registry {
+        name: entry.name,
+        url,
+        token,
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    const LOGIN: &str = "https://api.tokf.net";
+
+    fn entry(name: &str, url: &str, priority: Option<i64>) -> TokfRegistryEntry {
+        TokfRegistryEntry {
+            name: name.to_string(),
+            url: url.to_string(),
+            token: Some(format!("{name}-token")),
+            priority,
+        }
+    }
+
+    fn auth(server_url: &str) -> credentials::LoadedAuth {
+        credentials::LoadedAuth {
+            token: "login-token".to_string(),
+            username: "alice".to_string(),
+            server_url: server_url.to_string(),
+            expires_at: 0,
+            mit_license_accepted: None,
+            tos_accepted_version: None,
+        }
+    }
+
+    fn names(registries: &[Registry]) -> Vec<&str> {
+        registries.iter().map(|r| r.name.as_str()).collect()
+    }
+
+    #[test]
+    fn without_entries_only_the_default_registry() {
+        let registries = resolve(Vec::new(), LOGIN, Some(auth(LOGIN)));
+        assert_eq!(
+            registries,
+            [Registry {
+                name: DEFAULT_NAME.to_string(),
+                url: LOGIN.to_string(),
+                token: Some("login-token".to_string()),
+            }]
+        );
+    }
+
+    #[test]
+    fn entries_precede_the_default_in_priority_then_file_order() {
+        let entries = vec![
+            entry("b", "https://b.example", None),
+            entry("late", "https://late.example", Some(200)),
+            entry("a", "https://a.example", Some(-5)),
+            entry("c", "https://c.example", None),
+        ];
+        let registries = resolve(entries, LOGIN, None);
+        assert_eq!(names(&registries), ["a", "b", "c", "default", "late"]);
+    }
+
+    #[test]
+    fn an_entry_named_default_replaces_the_builtin() {
+        let mut default = entry(DEFAULT_NAME, LOGIN, Some(-1));
+        default.token = None;
+        let entries = vec![entry("acme", "https://acme.example", None), default];
+        let registries = resolve(entries, LOGIN, Some(auth(LOGIN)));
+        assert_eq!(names(&registries), ["default", "acme"]);
+        assert_eq!(registries[0].token.as_deref(), Some("login-token"));
+    }
+
+    #[test]
+    fn login_token_is_only_lent_to_its_own_server() {
+        let mut same = entry("mirror", "https://api.tokf.net/", None);
+        same.token = None;
+        let mut other = entry("acme", "https://acme.example", None);
+        other.token = None;
+        let registries = resolve(vec![same, other], LOGIN, Some(auth(LOGIN)));
+        assert_eq!(registries[0].url, LOGIN, "trailing slash is trimmed");
+        assert_eq!(registries[0].token.as_deref(), Some("login-token"));
+        assert_eq!(registries[1].token, None);
+    }
+
+    #[test]
+    fn duplicate_names_keep_the_first() {
+        let entries = vec![
+            entry("acme", "https://one.example", None),
+            entry("acme", "https://two.example", None),
+        ];
+        let registries = resolve(entries, LOGIN, None);
+        assert_eq!(names(&registries), ["acme", "default"]);
+        assert_eq!(registries[0].url, "https://one.example");
+    }
+
+    #[test]
+    fn registries_are_read_from_the_global_config() {
+        let rt = Runtime::isolated();
+        let path = rt.global_config_path().unwrap();
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(
+            &path,
+            "[[registry]]\nname = \"acme\"\nurl = \"https://acme.example\"\ntoken = \"t\"\n",
+        )
+        .unwrap();
+        assert_eq!(names(&all(&rt)), ["acme", "default"]);
+        assert_eq!(find(&rt, "https://acme.example/").unwrap().name, "acme");
+        assert!(
+            find(&rt, "nope")
+                .unwrap_err()
+                .to_string()
+                .contains("acme, default")
+        );
+        assert_eq!(primary(&rt, None).unwrap().name, "acme");
+        assert_eq!(primary(&rt, Some("default")).unwrap().name, "default");
+    }
+}
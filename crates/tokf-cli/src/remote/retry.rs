@@ -28,23 +28,49 @@ where
     loop {
         match f() {
             Ok(val) => return Ok(val),
-            Err(e) => {
-                if !is_rate_limited(&e) || attempt >= MAX_RETRIES {
-                    return Err(e);
-                }
-                let computed = BASE_BACKOFF_SECS << attempt;
-                let backoff = parse_retry_after(&e).map_or(computed, |ra| ra.max(computed));
-                let jitter_ms = jitter();
-                attempt += 1;
-                eprintln!(
-                    "[tokf] {operation}: rate limited, retrying in {backoff}s ({attempt}/{MAX_RETRIES})"
-                );
-                thread::sleep(Duration::from_secs(backoff) + Duration::from_millis(jitter_ms));
-            }
+            Err(e) => match next_delay(operation, &e, &mut attempt) {
+                Some(delay) => thread::sleep(delay),
+                None => return Err(e),
+            },
         }
     }
 }
 
+/// Async [`with_retry`]: the same backoff, awaited instead of slept.
+///
+/// # Errors
+///
+/// Propagates the final error if all attempts fail or the first non-429 error.
+pub async fn with_retry_async<T, F, Fut>(operation: &str, mut f: F) -> anyhow::Result<T>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = anyhow::Result<T>>,
+{
+    let mut attempt = 0u32;
+    loop {
+        match f().await {
+            Ok(val) => return Ok(val),
+            Err(e) => match next_delay(operation, &e, &mut attempt) {
+                Some(delay) => tokio::time::sleep(delay).await,
+                None => return Err(e),
+            },
+        }
+    }
+}
+
+/// How long to wait before retrying after `err`, or `None` to give up.
+/// Advances `attempt` and announces the retry on stderr.
+fn next_delay(operation: &str, err: &anyhow::Error, attempt: &mut u32) -> Option<Duration> {
+    if !is_rate_limited(err) || *attempt >= MAX_RETRIES {
+        return None;
+    }
+    let computed = BASE_BACKOFF_SECS << *attempt;
+    let backoff = parse_retry_after(err).map_or(computed, |ra| ra.max(computed));
+    *attempt += 1;
+    eprintln!("[tokf] {operation}: rate limited, retrying in {backoff}s ({attempt}/{MAX_RETRIES})");
+    Some(Duration::from_secs(backoff) + Duration::from_millis(jitter()))
+}
+
 /// Return a pseudo-random jitter in the range 0–499 ms to prevent thundering
 /// herd effects when multiple clients retry at the same time.
 fn jitter() -> u64 {
@@ -125,6 +151,27 @@ mod tests {
         assert_eq!(calls.get(), 2);
     }
 
+    #[test]
+    fn async_retry_succeeds_after_retry() {
+        let calls = Cell::new(0u32);
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_time()
+            .build()
+            .unwrap();
+        let result = runtime.block_on(with_retry_async("test", || {
+            calls.set(calls.get() + 1);
+            let call = calls.get();
+            async move {
+                if call < 2 {
+                    return Err(rate_limited_err(0));
+                }
+                Ok("success")
+            }
+        }));
+        assert_eq!(result.unwrap(), "success");
+        assert_eq!(calls.get(), 2);
+    }
+
     #[test]
     fn parses_retry_after_from_structured_error() {
         let err = rate_limited_err(120);
@@ -1,5 +1,6 @@
 use serde::{Deserialize, Serialize};
 
+use super::async_http::AsyncClient;
 use super::http::Client;
 
 #[derive(Debug, Clone, Serialize)]
@@ -28,14 +29,54 @@ pub struct SyncResponse {
     pub cursor: i64,
 }
 
+#[derive(Debug, Clone, Serialize)]
+pub struct ReconcileRequest {
+    pub machine_id: String,
+    pub last_synced_id: i64,
+    pub max_event_id: i64,
+    pub reset: bool,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ReconcileResponse {
+    pub cursor: i64,
+    pub previous_cursor: i64,
+}
+
 /// Send a batch of usage events to the remote server.
 ///
 /// # Errors
 ///
 /// Returns an error if the server is unreachable, returns a non-success status,
 /// or the response body cannot be deserialized.
+pub async fn sync_events_async(
+    client: &AsyncClient,
+    req: &SyncRequest,
+) -> anyhow::Result<SyncResponse> {
+    client.post("/api/sync", req).await
+}
+
+/// Blocking [`sync_events_async`].
+///
+/// # Errors
+///
+/// See [`sync_events_async`].
 pub fn sync_events(client: &Client, req: &SyncRequest) -> anyhow::Result<SyncResponse> {
-    client.post("/api/sync", req)
+    client.block_on(sync_events_async(client.as_async(), req))
+}
+
+/// Negotiate this machine's sync cursor with the server, which answers with
+/// the event id to resume after.
+///
+/// # Errors
+///
+/// Returns an error if the server is unreachable, returns a non-success status,
+/// or the response body cannot be deserialized.
+pub async fn reconcile_async(
+    client: &AsyncClient,
+    req: &ReconcileRequest,
+) -> anyhow::Result<ReconcileResponse> {
+    client.post("/api/sync/reconcile", req).await
 }
 
 #[cfg(test)]
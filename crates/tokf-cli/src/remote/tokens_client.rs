@@ -0,0 +1,93 @@
+use serde::{Deserialize, Serialize};
+
+use super::http::Client;
+
+#[derive(Debug, Serialize)]
+struct CreateTokenRequest<'a> {
+    name: &'a str,
+    scopes: &'a [String],
+    #[serde(skip_serializing_if = "Option::is_none")]
+    expires_in_days: Option<u32>,
+}
+
+/// A personal access token, as listed by the server.
+#[derive(Debug, Deserialize)]
+pub struct TokenInfo {
+    pub id: i64,
+    pub name: String,
+    pub scopes: Vec<String>,
+    pub created_at: String,
+    pub expires_at: String,
+    #[serde(default)]
+    pub last_used_at: Option<String>,
+}
+
+/// A freshly minted personal access token. `token` is only ever returned here.
+#[derive(Debug, Deserialize)]
+pub struct CreatedToken {
+    pub token: String,
+    #[serde(flatten)]
+    pub info: TokenInfo,
+}
+
+/// Mint a scoped personal access token for the authenticated user.
+///
+/// `expires_in_days` defaults to the server's 90 days when `None`.
+///
+/// # Errors
+///
+/// Returns an error on network failure, non-2xx status (400 for an unknown
+/// scope or lifetime, 403 when called with a personal access token), or an
+/// invalid response.
+pub fn create_token(
+    client: &Client,
+    name: &str,
+    scopes: &[String],
+    expires_in_days: Option<u32>,
+) -> anyhow::Result<CreatedToken> {
+    client.post(
+        "/api/tokens",
+        &CreateTokenRequest {
+            name,
+            scopes,
+            expires_in_days,
+        },
+    )
+}
+
+/// List the authenticated user's unexpired personal access tokens.
+///
+/// # Errors
+///
+/// Returns an error on network failure, non-2xx status, or an invalid response.
+pub fn list_tokens(client: &Client) -> anyhow::Result<Vec<TokenInfo>> {
+    client.get("/api/tokens")
+}
+
+/// Revoke one of the authenticated user's personal access tokens.
+///
+/// # Errors
+///
+/// Returns an error on network failure or non-2xx status (404 if the user has
+/// no such token).
+pub fn revoke_token(client: &Client, id: i64) -> anyhow::Result<()> {
+    client.delete(&format!("/api/tokens/{id}"))?;
+    Ok(())
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn deserialize_created_token() {
+        let json = r#"{"token": "tokf_pat_abc", "id": 7, "name": "ci", "scopes": ["publish"],
+            "created_at": "2026-10-16T00:00:00Z", "expires_at": "2027-01-14T00:00:00Z"}"#;
+        let created: CreatedToken = serde_json::from_str(json).unwrap();
+        assert_eq!(created.token, "tokf_pat_abc");
+        assert_eq!(created.info.id, 7);
+        assert_eq!(created.info.scopes, ["publish"]);
+        assert!(created.info.last_used_at.is_none());
+    }
+}
@@ -128,6 +128,12 @@ pub fn cmd_remote_sync(rt: &Runtime) -> anyhow::Result<i32> {
     }
 
     let result = tokf::sync_core::perform_sync(rt, &auth, &machine, &conn)?;
+    if let Some(reconciled) = result
+        .reconciled
+        .filter(tokf::sync_reconcile::Reconciled::changed)
+    {
+        eprintln!("[tokf] {}", reconciled.summary());
+    }
     eprintln!(
         "[tokf] Synced {} event(s). Cursor: {}.",
         result.synced_count, result.cursor
@@ -147,7 +153,7 @@ pub fn cmd_remote_sync(rt: &Runtime) -> anyhow::Result<i32> {
 pub fn cmd_remote_backfill(rt: &Runtime, no_cache: bool) -> anyhow::Result<i32> {
     use tokf::tracking;
 
-    let filters = crate::resolve::discover_filters(rt, no_cache)?;
+    let filters = crate::resolve::discover_installed_filters(rt, no_cache)?;
 
     let db_path = rt
         .tracking_db_path()
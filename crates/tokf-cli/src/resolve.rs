@@ -1,6 +1,11 @@
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
 use tokf::config;
-use tokf::config::types::FilterConfig;
-use tokf::history::current_project;
+use tokf::config::types::{FilterConfig, StdinMode};
+use tokf::filter;
+use tokf::history::{self, current_project};
+use tokf::remote;
 use tokf::runner;
 use tokf::tracking;
 
@@ -18,10 +23,22 @@ pub struct FilterMatch {
     pub resolved_filters: Vec<config::ResolvedFilter>,
 }
 
-/// Discover all filters using the standard search dirs + cache.
+/// Discover the filters that take part in resolution: every installed filter
+/// except those disabled in `config.toml`.
 pub fn discover_filters(
     rt: &Runtime,
     no_cache: bool,
+) -> anyhow::Result<Vec<config::ResolvedFilter>> {
+    let mut filters = discover_installed_filters(rt, no_cache)?;
+    config::remove_disabled(rt, &mut filters);
+    Ok(filters)
+}
+
+/// Discover all filters using the standard search dirs + cache, disabled
+/// ones included (for commands that address a filter by name).
+pub fn discover_installed_filters(
+    rt: &Runtime,
+    no_cache: bool,
 ) -> anyhow::Result<Vec<config::ResolvedFilter>> {
     let search_dirs = config::default_search_dirs(rt);
     if no_cache {
@@ -204,6 +221,7 @@ fn build_inject_env(rt: &Runtime, filter_cfg: Option<&FilterConfig>) -> Vec<(Str
 }
 
 fn run_command_with_consumed_prefix(
+    shell: &runner::Shell,
     run_cmd: &str,
     matched_command: &str,
     command_args: &[String],
@@ -225,7 +243,7 @@ fn run_command_with_consumed_prefix(
     }
 
     let mut prefix = command_args[0].clone();
-    let quoted_args = crate::shell::quote_argv(&command_args[1..words_consumed]);
+    let quoted_args = shell.quote_all(&command_args[1..words_consumed]);
     if !quoted_args.is_empty() {
         prefix.push(' ');
         prefix.push_str(&quoted_args);
@@ -242,6 +260,66 @@ pub struct ResolvedCommand<'a> {
     pub command_args: &'a [String],
     pub remaining_args: &'a [String],
     pub verbose: bool,
+    /// `--pty` was passed; a filter's `pty = true` also enables it.
+    pub pty: bool,
+    /// `--timeout`, which takes precedence over a filter's `timeout_secs`.
+    pub timeout: Option<Duration>,
+    /// `--stdin`, which takes precedence over a filter's `stdin`.
+    pub stdin: Option<StdinMode>,
+    /// `[run] spill_threshold_mb` from config.toml, in bytes.
+    pub spill_threshold: Option<usize>,
+    /// `[shell]` from config.toml: the shell that runs a filter's `run`.
+    pub shell: &'a runner::Shell,
+}
+
+/// Environment for the command: the filter's `env`, then the shim variables of
+/// `inject_path`, applied last so a filter cannot undo them.
+fn build_env(rt: &Runtime, filter_cfg: Option<&FilterConfig>) -> Vec<(String, String)> {
+    let mut env: Vec<(String, String)> = filter_cfg
+        .map(|cfg| cfg.env.clone().into_iter().collect())
+        .unwrap_or_default();
+    env.extend(build_inject_env(rt, filter_cfg));
+    env
+}
+
+/// The filter's `cwd`, resolved against the current directory.
+fn filter_cwd(
+    rt: &Runtime,
+    filter_cfg: Option<&FilterConfig>,
+    verbose: bool,
+) -> anyhow::Result<Option<PathBuf>> {
+    let Some(dir) = filter_cfg.and_then(|cfg| cfg.cwd.as_deref()) else {
+        return Ok(None);
+    };
+    let dir = rt.cwd_or_empty().join(dir);
+    anyhow::ensure!(
+        dir.is_dir(),
+        "filter `cwd` is not a directory: {}",
+        dir.display()
+    );
+    if verbose {
+        eprintln!("[tokf] running in {}", dir.display());
+    }
+    Ok(Some(dir))
+}
+
+/// How to run the command: command-line flags win over the filter's settings.
+fn exec_options<'a>(cmd: &ResolvedCommand<'a>, cwd: Option<&'a Path>) -> runner::ExecOptions<'a> {
+    let cfg = cmd.filter_cfg;
+    runner::ExecOptions {
+        pty: cmd.pty || cfg.is_some_and(|cfg| cfg.pty),
+        timeout: cmd.timeout.or_else(|| {
+            cfg.and_then(|cfg| cfg.timeout_secs)
+                .map(Duration::from_secs)
+        }),
+        cwd,
+        stdin: cmd
+            .stdin
+            .or_else(|| cfg.map(|cfg| cfg.stdin))
+            .unwrap_or_default(),
+        spill_threshold: cmd.spill_threshold,
+        shell: Some(cmd.shell),
+    }
 }
 
 /// Execute the resolved command.
@@ -254,7 +332,7 @@ pub struct ResolvedCommand<'a> {
 pub fn run_command(
     rt: &Runtime,
     cmd: ResolvedCommand<'_>,
-) -> anyhow::Result<(runner::CommandResult, Option<String>)> {
+) -> anyhow::Result<(runner::Execution, Option<String>)> {
     let ResolvedCommand {
         filter_cfg,
         words_consumed,
@@ -262,13 +340,18 @@ pub fn run_command(
         command_args,
         remaining_args,
         verbose,
+        shell,
+        ..
     } = cmd;
-    let env_overrides = build_inject_env(rt, filter_cfg);
+    let cwd = filter_cwd(rt, filter_cfg, verbose)?;
+    let opts = exec_options(&cmd, cwd.as_deref());
+    let env_overrides = build_env(rt, filter_cfg);
     let env_refs: Vec<(&str, &str)> = env_overrides
         .iter()
         .map(|(k, v)| (k.as_str(), v.as_str()))
         .collect();
 
+    let retry = filter_cfg.and_then(|cfg| cfg.retry.as_ref());
     if let Some(cfg) = filter_cfg
         && let Some(run_cmd) = &cfg.run
     {
@@ -276,6 +359,7 @@ pub fn run_command(
             || String::clone(run_cmd),
             |matched_command| {
                 run_command_with_consumed_prefix(
+                    shell,
                     run_cmd,
                     matched_command,
                     command_args,
@@ -283,53 +367,47 @@ pub fn run_command(
                 )
             },
         );
-        let executed = runner::expand_run_command(&run_cmd, remaining_args);
+        let executed = runner::expand_run_command(shell, &run_cmd, remaining_args);
         if verbose {
             eprintln!(
                 "[tokf] executing: {executed}\n[tokf]   (substituted by `run` for: {})",
                 command_args.join(" ")
             );
         }
-        let result = runner::execute_shell_with_env(&run_cmd, remaining_args, &env_refs)?;
+        let result = runner::with_retry(retry, verbose, || {
+            runner::execute_shell_with_env(&run_cmd, remaining_args, &env_refs, opts)
+        })?;
         Ok((result, Some(executed)))
-    } else if words_consumed > 0 {
-        let cmd_str = command_args[..words_consumed].join(" ");
-        Ok((
-            runner::execute_with_env(&cmd_str, remaining_args, &env_refs)?,
-            None,
-        ))
     } else {
-        Ok((
-            runner::execute_with_env(&command_args[0], remaining_args, &env_refs)?,
-            None,
-        ))
+        let cmd_str = command_args[..words_consumed.max(1)].join(" ");
+        let result = runner::with_retry(retry, verbose, || {
+            runner::execute_with_env(&cmd_str, remaining_args, &env_refs, opts)
+        })?;
+        Ok((result, None))
     }
 }
 
-/// Attempt a background auto-sync if the pending event count exceeds the configured threshold.
+/// Attempt a background auto-sync when `[sync] auto` is on and one is due
+/// (see [`tokf::sync_core::auto_sync_due`]).
 ///
 /// All checks are cheap (no network I/O) — only spawns a detached `tokf sync` process
-/// when all preconditions are met.
+/// when all preconditions are met and no other sync holds the lock.
 ///
-/// **Note:** `upload_usage_stats` is read from the global config only (project root = `None`).
+/// **Note:** the `[sync]` settings are read from the global config only (project root = `None`).
 /// This is intentional — `try_auto_sync` runs in the hot path after every filtered command,
 /// so we skip the filesystem walk to locate `.tokf/config.toml` for performance. Users who
-/// need per-project overrides can set `upload_usage_stats` in their global config instead.
+/// need per-project overrides can set them in their global config instead.
 pub fn try_auto_sync(rt: &Runtime) {
-    use std::process::{Command, Stdio};
     use tokf::auth::credentials;
     use tokf::history::SyncConfig;
     use tokf::remote::machine;
+    use tokf::sync_core;
 
     // Pass None for project dir: auto-sync runs in the hot path after every command,
     // so we only check the global config to avoid a filesystem scan for .tokf/config.toml.
     let config = SyncConfig::load(rt, None);
-    if config.auto_sync_threshold == 0 {
-        return;
-    }
-
-    if !config.upload_usage_stats.unwrap_or(false) {
-        return; // None → never asked, Some(false) → opted out
+    if !config.auto_enabled() {
+        return; // unset → never asked, false → opted out
     }
 
     if credentials::load(rt).is_none() {
@@ -338,6 +416,9 @@ pub fn try_auto_sync(rt: &Runtime) {
     if machine::load(rt).is_none() {
         return;
     }
+    if sync_core::sync_running(rt) {
+        return;
+    }
 
     let Some(db_path) = rt.tracking_db_path() else {
         return;
@@ -345,31 +426,77 @@ pub fn try_auto_sync(rt: &Runtime) {
     let Ok(conn) = tracking::open_db(&db_path) else {
         return;
     };
-    let Ok(pending) = tracking::get_pending_count(&conn) else {
+    if !sync_core::auto_sync_due(&conn, &config).unwrap_or(false) {
         return;
-    };
+    }
 
-    if pending < i64::from(config.auto_sync_threshold) {
-        return;
+    if let Err(e) = spawn_detached_sync()
+        && rt.debug()
+    {
+        eprintln!("[tokf] auto-sync spawn failed: {e}");
     }
+}
+
+/// Start `tokf sync` with no stdio, in its own process group on Unix so the
+/// agent interrupting or killing the run's group does not take it down too.
+fn spawn_detached_sync() -> std::io::Result<()> {
+    use std::process::{Command, Stdio};
 
     let exe = std::env::current_exe().unwrap_or_else(|_| "tokf".into());
-    match Command::new(exe)
-        .args(["sync"])
+    let mut cmd = Command::new(exe);
+    cmd.args(["sync"])
         .stdin(Stdio::null())
         .stdout(Stdio::null())
-        .stderr(Stdio::null())
-        .spawn()
+        .stderr(Stdio::null());
+    #[cfg(unix)]
     {
-        Ok(_) => {}
-        Err(e) => {
-            if rt.debug() {
-                eprintln!("[tokf] auto-sync spawn failed: {e}");
-            }
-        }
+        use std::os::unix::process::CommandExt as _;
+        cmd.process_group(0);
+    }
+    cmd.spawn().map(drop)
+}
+
+/// Filtered output of the previous run of `command`, for `diff_previous`.
+///
+/// The previous run's raw output is filtered again with `cfg` rather than
+/// reusing its stored filtered output, which was itself diffed. Must be called
+/// before the current run is recorded. Returns `None` when there is no
+/// previous run.
+pub fn previous_filtered_output(
+    rt: &Runtime,
+    cfg: &FilterConfig,
+    command: &str,
+    args: &[String],
+    opts: &filter::FilterOptions,
+) -> Option<String> {
+    let previous = history::try_previous_run(rt, command)?;
+    let previous = runner::CommandResult {
+        stdout: String::new(),
+        stderr: String::new(),
+        exit_code: previous.exit_code,
+        combined: previous.raw_output,
+    };
+    Some(filter::apply(cfg, &previous, args, opts).output)
+}
+
+/// Engine options for a run from `cwd`. The project root is only looked up
+/// when the filter sets `shorten_paths`.
+pub fn filter_options(
+    cfg: &FilterConfig,
+    cwd: Option<&std::path::Path>,
+    preserve_color: bool,
+) -> filter::FilterOptions {
+    filter::FilterOptions {
+        preserve_color,
+        project_root: cwd
+            .filter(|_| cfg.shorten_paths)
+            .map(history::project_root_for),
+        ..filter::FilterOptions::default()
     }
 }
 
+/// Record one run in the tracking DB. `texts` is the input and output text,
+/// when it is at hand, for real tokenizer counts (see [`count_tokens`]).
 #[allow(clippy::too_many_arguments)]
 pub fn record_run(
     rt: &Runtime,
@@ -382,7 +509,13 @@ pub fn record_run(
     filter_time_ms: u128,
     exit_code: i32,
     pipe_override: bool,
+    usage: Option<&runner::ResourceUsage>,
+    texts: Option<(&str, &str)>,
 ) {
+    let project = current_project(rt);
+    if !history::tracking_enabled(Path::new(&project)) {
+        return;
+    }
     let Some(path) = rt.tracking_db_path() else {
         eprintln!("[tokf] tracking: cannot determine DB path");
         return;
@@ -410,233 +543,53 @@ pub fn record_run(
         exit_code,
         pipe_override,
     );
-    event.project = current_project(rt);
+    event.project = project;
+    if let Some(usage) = usage {
+        tracking::set_usage(&mut event, usage);
+    }
+    if let Some((input, output)) = texts {
+        count_tokens(rt, &mut event, input, output);
+    }
     if let Err(e) = tracking::record_event(&conn, &event) {
         eprintln!(
             "[tokf] tracking error (record) at {}: {e:#}",
             path.display()
         );
+        return;
     }
-}
-
-#[cfg(test)]
-#[allow(clippy::unwrap_used)]
-mod tests {
-    use tokf::config::types::FilterConfig;
-
-    use super::*;
-
-    fn config_with_inject(inject: bool) -> FilterConfig {
-        let toml = format!("command = \"git commit\"\ninject_path = {inject}");
-        toml::from_str(&toml).unwrap()
-    }
-
-    fn config_with_run() -> FilterConfig {
-        toml::from_str(
-            r#"
-command = "git status"
-run = "git status --porcelain=v1 -b -uall --find-renames"
-"#,
-        )
-        .unwrap()
-    }
-
-    #[test]
-    fn run_command_with_consumed_prefix_preserves_global_args_for_run_override() {
-        let cfg = config_with_run();
-        let command_args = vec![
-            "git".to_string(),
-            "-C".to_string(),
-            "/tmp/repo with spaces".to_string(),
-            "status".to_string(),
-        ];
-
-        let run_cmd = run_command_with_consumed_prefix(
-            cfg.run.as_ref().unwrap(),
-            "git status",
-            &command_args,
-            4,
-        );
-
-        assert_eq!(
-            run_cmd,
-            "git '-C' '/tmp/repo with spaces' 'status' --porcelain=v1 -b -uall --find-renames"
-        );
-    }
-
-    #[test]
-    fn run_command_with_consumed_prefix_keeps_plain_run_override_unchanged() {
-        let cfg = config_with_run();
-        let command_args = vec!["git".to_string(), "status".to_string()];
-
-        let run_cmd = run_command_with_consumed_prefix(
-            cfg.run.as_ref().unwrap(),
-            "git status",
-            &command_args,
-            2,
-        );
-
-        assert_eq!(run_cmd, cfg.run.as_ref().unwrap().as_str());
-    }
-
-    #[test]
-    fn run_command_with_consumed_prefix_ignores_nonmatching_run_override() {
-        let command_args = vec![
-            "git".to_string(),
-            "-C".to_string(),
-            "/tmp/repo".to_string(),
-            "status".to_string(),
-        ];
-
-        let run_cmd =
-            run_command_with_consumed_prefix("echo git status", "git status", &command_args, 4);
-
-        assert_eq!(run_cmd, "echo git status");
-    }
-
-    #[test]
-    fn run_command_with_consumed_prefix_uses_matched_array_pattern() {
-        let cfg: FilterConfig = toml::from_str(
-            r#"
-command = ["npm test", "pnpm test"]
-run = "pnpm test --reporter=dot {args}"
-"#,
-        )
-        .unwrap();
-        let command_args = vec![
-            "pnpm".to_string(),
-            "--dir".to_string(),
-            "webapp".to_string(),
-            "test".to_string(),
-        ];
-
-        let run_cmd = run_command_with_consumed_prefix(
-            cfg.run.as_ref().unwrap(),
-            "pnpm test",
-            &command_args,
-            4,
-        );
-
-        assert_eq!(
-            run_cmd,
-            "pnpm '--dir' 'webapp' 'test' --reporter=dot {args}"
-        );
-    }
-
-    #[test]
-    fn build_inject_env_empty_when_no_config() {
-        let rt = Runtime::isolated();
-        assert!(build_inject_env(&rt, None).is_empty());
-    }
-
-    #[test]
-    fn build_inject_env_empty_when_disabled() {
-        let rt = Runtime::isolated();
-        let cfg = config_with_inject(false);
-        assert!(build_inject_env(&rt, Some(&cfg)).is_empty());
-    }
-
-    #[test]
-    fn build_inject_env_empty_when_shims_dir_missing() {
-        let rt = Runtime::builder()
-            .home("/nonexistent/path/tokf_test")
-            .build();
-        let cfg = config_with_inject(true);
-        // shims_dir exists in theory but the directory doesn't exist on disk
-        assert!(build_inject_env(&rt, Some(&cfg)).is_empty());
-    }
-
-    #[test]
-    fn build_inject_env_returns_three_vars_when_enabled() {
-        let rt = Runtime::isolated();
-        let shims = rt.shims_dir().unwrap();
-        std::fs::create_dir_all(&shims).unwrap();
-
-        let cfg = config_with_inject(true);
-        let env = build_inject_env(&rt, Some(&cfg));
-
-        assert_eq!(env.len(), 3);
-        assert_eq!(env[0].0, "PATH");
-        assert!(env[0].1.starts_with(&shims.to_string_lossy().to_string()));
-        assert_eq!(env[1].0, "TOKF_ORIGINAL_PATH");
-        assert_eq!(env[2].0, "SHELL");
-    }
-
-    #[test]
-    fn build_inject_env_uses_original_path_when_nested() {
-        // Simulate a nested invocation: TOKF_ORIGINAL_PATH is already set.
-        let rt = Runtime::builder().original_path("/usr/bin:/bin").build();
-        let shims = rt.shims_dir().unwrap();
-        std::fs::create_dir_all(&shims).unwrap();
-
-        let cfg = config_with_inject(true);
-        let env = build_inject_env(&rt, Some(&cfg));
-
-        // PATH should be shims:/usr/bin:/bin (not shims:shims:/usr/bin:/bin)
-        assert_eq!(env[1].0, "TOKF_ORIGINAL_PATH");
-        assert_eq!(env[1].1, "/usr/bin:/bin");
-        assert!(
-            env[0]
-                .1
-                .starts_with(&format!("{}:/usr/bin:/bin", shims.display()))
-        );
-    }
-
-    // --- run_command reports what it actually executed (issue #430) ---
-
-    #[test]
-    fn run_command_reports_the_substituted_command() {
-        let rt = Runtime::builder().build();
-        let cfg: FilterConfig =
-            toml::from_str("command = \"fake-cmd\"\nrun = \"echo substituted {args}\"").unwrap();
-        let command_args = vec!["fake-cmd".to_string()];
-        let remaining = vec!["extra".to_string()];
-
-        let (result, executed) = run_command(
-            &rt,
-            ResolvedCommand {
-                filter_cfg: Some(&cfg),
-                words_consumed: 1,
-                matched_command: Some("fake-cmd"),
-                command_args: &command_args,
-                remaining_args: &remaining,
-                verbose: false,
-            },
-        )
-        .unwrap();
-
-        assert_eq!(result.stdout.trim(), "substituted extra");
-        assert_eq!(
-            executed.as_deref(),
-            // Args are shell-quoted: this is the literal line handed to `sh`.
-            Some("echo substituted 'extra'"),
-            "the fully expanded substituted command must be reported to the caller"
-        );
+    let cfg = history::TrackingConfig::load(rt);
+    let event_id = conn.last_insert_rowid();
+    let archive = cfg.archive_after_days.map(|after_days| tracking::Archive {
+        after_days,
+        keep_unsynced: remote::machine::load(rt).is_some(),
+    });
+    if let Err(e) = tracking::maintain_if_due(&conn, &path, event_id, archive, cfg.maintain_every) {
+        eprintln!("[tokf] tracking error (maintain): {e:#}");
     }
+}
 
-    #[test]
-    fn run_command_reports_no_substitution_without_run_override() {
-        let rt = Runtime::builder().build();
-        let command_args = vec!["echo".to_string(), "plain".to_string()];
-        let remaining = vec!["plain".to_string()];
-
-        let (result, executed) = run_command(
-            &rt,
-            ResolvedCommand {
-                filter_cfg: None,
-                words_consumed: 0,
-                matched_command: None,
-                command_args: &command_args,
-                remaining_args: &remaining,
-                verbose: false,
-            },
-        )
-        .unwrap();
+/// Add real tokenizer counts to `event` for the `[tokenizer] model`, if set.
+#[cfg(feature = "tokenizer")]
+fn count_tokens(rt: &Runtime, event: &mut tracking::TrackingEvent, input: &str, output: &str) {
+    let project_root = rt.cwd().map(history::project_root_for);
+    let Some(model) = history::TokenizerConfig::load(rt, project_root.as_deref()).model else {
+        return;
+    };
+    let (encoding, counter) = tokf_common::tokens::tokenizer_for_model(&model);
+    tracking::set_token_counts(event, encoding, counter, input, output);
+}
 
-        assert_eq!(result.stdout.trim(), "plain");
-        assert_eq!(
-            executed, None,
-            "a verbatim run must not claim a substitution occurred"
-        );
-    }
+/// Without the `tokenizer` feature only the byte-based estimates are kept.
+#[cfg(not(feature = "tokenizer"))]
+const fn count_tokens(
+    _rt: &Runtime,
+    _event: &mut tracking::TrackingEvent,
+    _input: &str,
+    _output: &str,
+) {
 }
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+#[path = "resolve_tests.rs"]
+mod tests;
@@ -0,0 +1,307 @@
+use tokf::config::types::FilterConfig;
+
+use super::*;
+
+fn config_with_inject(inject: bool) -> FilterConfig {
+    let toml = format!("command = \"git commit\"\ninject_path = {inject}");
+    toml::from_str(&toml).unwrap()
+}
+
+fn config_with_run() -> FilterConfig {
+    toml::from_str(
+        r#"
+command = "git status"
+run = "git status --porcelain=v1 -b -uall --find-renames"
+"#,
+    )
+    .unwrap()
+}
+
+#[test]
+fn run_command_with_consumed_prefix_preserves_global_args_for_run_override() {
+    let cfg = config_with_run();
+    let command_args = vec![
+        "git".to_string(),
+        "-C".to_string(),
+        "/tmp/repo with spaces".to_string(),
+        "status".to_string(),
+    ];
+
+    let run_cmd = run_command_with_consumed_prefix(
+        &runner::Shell::default(),
+        cfg.run.as_ref().unwrap(),
+        "git status",
+        &command_args,
+        4,
+    );
+
+    assert_eq!(
+        run_cmd,
+        "git '-C' '/tmp/repo with spaces' 'status' --porcelain=v1 -b -uall --find-renames"
+    );
+}
+
+#[test]
+fn run_command_with_consumed_prefix_keeps_plain_run_override_unchanged() {
+    let cfg = config_with_run();
+    let command_args = vec!["git".to_string(), "status".to_string()];
+
+    let run_cmd = run_command_with_consumed_prefix(
+        &runner::Shell::default(),
+        cfg.run.as_ref().unwrap(),
+        "git status",
+        &command_args,
+        2,
+    );
+
+    assert_eq!(run_cmd, cfg.run.as_ref().unwrap().as_str());
+}
+
+#[test]
+fn run_command_with_consumed_prefix_ignores_nonmatching_run_override() {
+    let command_args = vec![
+        "git".to_string(),
+        "-C".to_string(),
+        "/tmp/repo".to_string(),
+        "status".to_string(),
+    ];
+
+    let run_cmd = run_command_with_consumed_prefix(
+        &runner::Shell::default(),
+        "echo git status",
+        "git status",
+        &command_args,
+        4,
+    );
+
+    assert_eq!(run_cmd, "echo git status");
+}
+
+#[test]
+fn run_command_with_consumed_prefix_uses_matched_array_pattern() {
+    let cfg: FilterConfig = toml::from_str(
+        r#"
+command = ["npm test", "pnpm test"]
+run = "pnpm test --reporter=dot {args}"
+"#,
+    )
+    .unwrap();
+    let command_args = vec![
+        "pnpm".to_string(),
+        "--dir".to_string(),
+        "webapp".to_string(),
+        "test".to_string(),
+    ];
+
+    let run_cmd = run_command_with_consumed_prefix(
+        &runner::Shell::default(),
+        cfg.run.as_ref().unwrap(),
+        "pnpm test",
+        &command_args,
+        4,
+    );
+
+    assert_eq!(
+        run_cmd,
+        "pnpm '--dir' 'webapp' 'test' --reporter=dot {args}"
+    );
+}
+
+#[test]
+fn build_inject_env_empty_when_no_config() {
+    let rt = Runtime::isolated();
+    assert!(build_inject_env(&rt, None).is_empty());
+}
+
+#[test]
+fn build_inject_env_empty_when_disabled() {
+    let rt = Runtime::isolated();
+    let cfg = config_with_inject(false);
+    assert!(build_inject_env(&rt, Some(&cfg)).is_empty());
+}
+
+#[test]
+fn build_inject_env_empty_when_shims_dir_missing() {
+    let rt = Runtime::builder()
+        .home("/nonexistent/path/tokf_test")
+        .build();
+    let cfg = config_with_inject(true);
+    // shims_dir exists in theory but the directory doesn't exist on disk
+    assert!(build_inject_env(&rt, Some(&cfg)).is_empty());
+}
+
+#[test]
+fn build_inject_env_returns_three_vars_when_enabled() {
+    let rt = Runtime::isolated();
+    let shims = rt.shims_dir().unwrap();
+    std::fs::create_dir_all(&shims).unwrap();
+
+    let cfg = config_with_inject(true);
+    let env = build_inject_env(&rt, Some(&cfg));
+
+    assert_eq!(env.len(), 3);
+    assert_eq!(env[0].0, "PATH");
+    assert!(env[0].1.starts_with(&shims.to_string_lossy().to_string()));
+    assert_eq!(env[1].0, "TOKF_ORIGINAL_PATH");
+    assert_eq!(env[2].0, "SHELL");
+}
+
+#[test]
+fn build_inject_env_uses_original_path_when_nested() {
+    // Simulate a nested invocation: TOKF_ORIGINAL_PATH is already set.
+    let rt = Runtime::builder().original_path("/usr/bin:/bin").build();
+    let shims = rt.shims_dir().unwrap();
+    std::fs::create_dir_all(&shims).unwrap();
+
+    let cfg = config_with_inject(true);
+    let env = build_inject_env(&rt, Some(&cfg));
+
+    // PATH should be shims:/usr/bin:/bin (not shims:shims:/usr/bin:/bin)
+    assert_eq!(env[1].0, "TOKF_ORIGINAL_PATH");
+    assert_eq!(env[1].1, "/usr/bin:/bin");
+    assert!(
+        env[0]
+            .1
+            .starts_with(&format!("{}:/usr/bin:/bin", shims.display()))
+    );
+}
+
+// --- run_command reports what it actually executed (issue #430) ---
+
+#[test]
+fn run_command_reports_the_substituted_command() {
+    let rt = Runtime::builder().build();
+    let cfg: FilterConfig =
+        toml::from_str("command = \"fake-cmd\"\nrun = \"echo substituted {args}\"").unwrap();
+    let command_args = vec!["fake-cmd".to_string()];
+    let remaining = vec!["extra".to_string()];
+
+    let (result, executed) = run_command(
+        &rt,
+        ResolvedCommand {
+            filter_cfg: Some(&cfg),
+            words_consumed: 1,
+            matched_command: Some("fake-cmd"),
+            command_args: &command_args,
+            remaining_args: &remaining,
+            verbose: false,
+            pty: false,
+            timeout: None,
+            stdin: None,
+            spill_threshold: None,
+            shell: &runner::Shell::default(),
+        },
+    )
+    .unwrap();
+
+    assert_eq!(result.result.stdout.trim(), "substituted extra");
+    assert_eq!(
+        executed.as_deref(),
+        // Args are shell-quoted: this is the literal line handed to `sh`.
+        Some("echo substituted 'extra'"),
+        "the fully expanded substituted command must be reported to the caller"
+    );
+}
+
+#[test]
+fn run_command_reports_no_substitution_without_run_override() {
+    let rt = Runtime::builder().build();
+    let command_args = vec!["echo".to_string(), "plain".to_string()];
+    let remaining = vec!["plain".to_string()];
+
+    let (result, executed) = run_command(
+        &rt,
+        ResolvedCommand {
+            filter_cfg: None,
+            words_consumed: 0,
+            matched_command: None,
+            command_args: &command_args,
+            remaining_args: &remaining,
+            verbose: false,
+            pty: false,
+            timeout: None,
+            stdin: None,
+            spill_threshold: None,
+            shell: &runner::Shell::default(),
+        },
+    )
+    .unwrap();
+
+    assert_eq!(result.result.stdout.trim(), "plain");
+    assert_eq!(
+        executed, None,
+        "a verbatim run must not claim a substitution occurred"
+    );
+}
+
+// --- filter env and cwd ---
+
+#[test]
+fn build_env_puts_filter_env_before_shim_vars() {
+    let rt = Runtime::isolated();
+    let cfg: FilterConfig =
+        toml::from_str("command = \"cargo build\"\nenv = { CARGO_TERM_COLOR = \"never\" }")
+            .unwrap();
+    assert_eq!(
+        build_env(&rt, Some(&cfg)),
+        vec![("CARGO_TERM_COLOR".to_string(), "never".to_string())]
+    );
+    assert!(build_env(&rt, None).is_empty());
+}
+
+#[test]
+fn filter_cwd_is_relative_to_current_dir() {
+    let dir = tempfile::TempDir::new().unwrap();
+    std::fs::create_dir(dir.path().join("sub")).unwrap();
+    let rt = Runtime::builder().cwd(dir.path()).build();
+    let cfg: FilterConfig = toml::from_str("command = \"make\"\ncwd = \"sub\"").unwrap();
+    assert_eq!(
+        filter_cwd(&rt, Some(&cfg), false).unwrap(),
+        Some(dir.path().join("sub"))
+    );
+    assert_eq!(filter_cwd(&rt, None, false).unwrap(), None);
+}
+
+#[test]
+fn filter_cwd_rejects_missing_directory() {
+    let dir = tempfile::TempDir::new().unwrap();
+    let rt = Runtime::builder().cwd(dir.path()).build();
+    let cfg: FilterConfig = toml::from_str("command = \"make\"\ncwd = \"missing\"").unwrap();
+    let err = filter_cwd(&rt, Some(&cfg), false).unwrap_err();
+    assert!(err.to_string().contains("not a directory"), "{err}");
+}
+
+#[cfg(unix)]
+#[test]
+fn run_command_applies_filter_env_and_cwd() {
+    let dir = tempfile::TempDir::new().unwrap();
+    std::fs::create_dir(dir.path().join("sub")).unwrap();
+    std::fs::write(dir.path().join("sub/marker.txt"), "").unwrap();
+    let rt = Runtime::builder().cwd(dir.path()).build();
+    let cfg: FilterConfig = toml::from_str(
+        "command = \"probe\"\nrun = \"echo $PROBE_VAR; ls\"\ncwd = \"sub\"\n\
+         env = { PROBE_VAR = \"from-filter\" }",
+    )
+    .unwrap();
+    let command_args = vec!["probe".to_string()];
+
+    let (run, _) = run_command(
+        &rt,
+        ResolvedCommand {
+            filter_cfg: Some(&cfg),
+            words_consumed: 1,
+            matched_command: Some("probe"),
+            command_args: &command_args,
+            remaining_args: &[],
+            verbose: false,
+            pty: false,
+            timeout: None,
+            stdin: None,
+            spill_threshold: None,
+            shell: &runner::Shell::default(),
+        },
+    )
+    .unwrap();
+
+    assert_eq!(run.result.stdout, "from-filter\nmarker.txt");
+}
@@ -1,17 +1,22 @@
 pub mod types;
 
 pub(crate) mod bash_ast;
+mod pattern_cache;
 pub(crate) mod rules;
 pub(crate) mod transparent;
 pub(crate) mod user_config;
 
 use std::path::PathBuf;
+use std::sync::Arc;
+
+use regex::Regex;
 
 use crate::config;
 use bash_ast::{StrippedPipe, split_compound, strip_env_prefix};
 use rules::{apply_rules, should_skip};
 use types::{RewriteConfig, RewriteOptions, RewriteRule};
 
+pub use pattern_cache::PatternCache;
 pub use user_config::{load_local_wrapper_config, load_user_config};
 
 use crate::runtime::Runtime;
@@ -50,29 +55,51 @@ fn build_wrapper_rules() -> Vec<RewriteRule> {
         .collect()
 }
 
+/// Raw filter patterns collected from all discovered filters.
+#[derive(Default)]
+struct FilterPatterns {
+    /// `command` pattern strings.
+    words: Vec<String>,
+    /// Compiled `pattern_regex` values (invalid ones are dropped).
+    regexes: Vec<Regex>,
+}
+
 /// Collect raw filter pattern strings from all discovered filters.
 ///
 /// These patterns are matched using [`config::pattern_matches_prefix`] — the
 /// same authoritative matching logic used by `tokf run` and `tokf which` — so
 /// that `tokf -c` (shell mode) and `tokf rewrite` produce identical results.
-fn collect_filter_patterns(rt: &Runtime, search_dirs: &[PathBuf], no_cache: bool) -> Vec<String> {
-    let mut patterns = Vec::new();
+fn collect_filter_patterns(
+    rt: &Runtime,
+    search_dirs: &[PathBuf],
+    no_cache: bool,
+) -> FilterPatterns {
+    let mut patterns = FilterPatterns::default();
     let mut seen: std::collections::HashSet<String> = std::collections::HashSet::new();
     let discovered = if no_cache {
         config::discover_all_filters(search_dirs)
     } else {
         config::cache::discover_with_cache(rt, search_dirs)
     };
-    let Ok(filters) = discovered else {
+    let Ok(mut filters) = discovered else {
         return patterns;
     };
+    config::remove_disabled(rt, &mut filters);
     for filter in filters {
         for pattern in filter.config.command.patterns() {
             let owned = pattern.clone();
             if seen.insert(owned.clone()) {
-                patterns.push(owned);
+                patterns.words.push(owned);
             }
         }
+        if let Some(re) = filter
+            .config
+            .pattern_regex
+            .as_deref()
+            .and_then(|p| Regex::new(p).ok())
+        {
+            patterns.regexes.push(re);
+        }
     }
     patterns
 }
@@ -89,7 +116,7 @@ fn collect_filter_patterns(rt: &Runtime, search_dirs: &[PathBuf], no_cache: bool
 /// environment. See issue #403.
 fn try_filter_match(
     cmd: &str,
-    patterns: &[String],
+    patterns: &FilterPatterns,
     options: &RewriteOptions,
     local_wrapper: &types::LocalWrapperConfig,
 ) -> Option<String> {
@@ -97,7 +124,12 @@ fn try_filter_match(
     if words.is_empty() {
         return None;
     }
-    if !config::local_wrapper::patterns_match_with_wrapper(patterns, &words, local_wrapper) {
+    if !config::local_wrapper::patterns_match_with_wrapper(
+        &patterns.words,
+        &patterns.regexes,
+        &words,
+        local_wrapper,
+    ) {
         return None;
     }
     let prefix = if options.no_mask_exit_code {
@@ -141,6 +173,7 @@ pub fn rewrite_with_options(
             // or the `tokf -c` shell path yet (see #431 follow-up); those callers
             // always use the cache. The hook path sets this explicitly.
             no_cache: false,
+            pattern_cache: None,
         },
         command,
         verbose,
@@ -152,8 +185,8 @@ pub fn rewrite_with_options(
 struct SegmentRules<'a> {
     /// Wrapper rules for task runners (tried first, before pipe handling).
     wrapper: &'a [RewriteRule],
-    /// Raw filter pattern strings matched via `pattern_matches_prefix`.
-    filter_patterns: &'a [String],
+    /// Raw filter patterns matched via `pattern_matches_prefix`.
+    filter_patterns: &'a FilterPatterns,
     /// Local environment wrappers (e.g. `nix develop -c`) to unwrap when
     /// matching filter patterns.
     local_wrapper: &'a types::LocalWrapperConfig,
@@ -312,6 +345,23 @@ pub(crate) struct RewriteCtx<'a> {
     /// `verbose` — so call sites name the flag and can't silently swap the two
     /// (the exact footgun behind #431).
     pub no_cache: bool,
+    /// Filter patterns kept across rewrites (`tokf daemon`); `None` collects
+    /// them afresh.
+    pub pattern_cache: Option<&'a PatternCache>,
+}
+
+/// Filter patterns for `ctx`, from its pattern cache when it has one.
+fn filter_patterns_for(ctx: &RewriteCtx<'_>) -> Arc<FilterPatterns> {
+    match ctx.pattern_cache {
+        Some(cache) if !ctx.no_cache => cache.get_or_build(ctx.rt, ctx.search_dirs, || {
+            collect_filter_patterns(ctx.rt, ctx.search_dirs, false)
+        }),
+        _ => Arc::new(collect_filter_patterns(
+            ctx.rt,
+            ctx.search_dirs,
+            ctx.no_cache,
+        )),
+    }
 }
 
 /// Testable version with explicit config, search dirs, and rewrite options.
@@ -352,7 +402,7 @@ pub(crate) fn rewrite_with_config_and_options(
     }
 
     let wrapper_rules = build_wrapper_rules();
-    let filter_patterns = collect_filter_patterns(ctx.rt, ctx.search_dirs, ctx.no_cache);
+    let filter_patterns = filter_patterns_for(&ctx);
     let local_wrapper = user_config.local_wrapper.clone().unwrap_or_default();
     let log_parse_failures = user_config
         .debug
@@ -433,6 +483,7 @@ pub(crate) fn rewrite_isolated_with_options(
             user_config,
             search_dirs,
             no_cache: false,
+            pattern_cache: None,
         },
         command,
         verbose,
@@ -443,7 +494,7 @@ pub(crate) fn rewrite_isolated_with_options(
 #[cfg(test)]
 pub(crate) fn collect_filter_patterns_isolated(search_dirs: &[PathBuf]) -> Vec<String> {
     let rt = Runtime::isolated();
-    collect_filter_patterns(&rt, search_dirs, false)
+    collect_filter_patterns(&rt, search_dirs, false).words
 }
 
 #[cfg(test)]
@@ -0,0 +1,101 @@
+//! Filter patterns kept in memory across rewrites, for `tokf daemon`.
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex, PoisonError};
+
+use super::FilterPatterns;
+use crate::config;
+use crate::history::{FiltersConfig, project_root_for};
+use crate::runtime::Runtime;
+
+/// Compiled filter patterns per set of search dirs. An entry is reused while
+/// the filter files and the disabled filters it was built from are unchanged.
+#[derive(Default)]
+pub struct PatternCache {
+    entries: Mutex<HashMap<Vec<PathBuf>, Entry>>,
+}
+
+struct Entry {
+    fingerprint: u64,
+    disabled: Vec<String>,
+    patterns: Arc<FilterPatterns>,
+}
+
+impl PatternCache {
+    /// Number of pattern sets held.
+    pub fn len(&self) -> usize {
+        self.entries
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .len()
+    }
+
+    /// Whether no pattern set is held yet.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The patterns for `search_dirs`, from memory when still current or
+    /// else from `build`.
+    pub(super) fn get_or_build(
+        &self,
+        rt: &Runtime,
+        search_dirs: &[PathBuf],
+        build: impl FnOnce() -> FilterPatterns,
+    ) -> Arc<FilterPatterns> {
+        let fingerprint = config::cache::fingerprint(search_dirs);
+        let project_root = rt.cwd().map(project_root_for);
+        let disabled = FiltersConfig::load(rt, project_root.as_deref()).disabled;
+
+        let mut entries = self.entries.lock().unwrap_or_else(PoisonError::into_inner);
+        if let Some(entry) = entries.get(search_dirs)
+            && entry.fingerprint == fingerprint
+            && entry.disabled == disabled
+        {
+            return Arc::clone(&entry.patterns);
+        }
+        let patterns = Arc::new(build());
+        entries.insert(
+            search_dirs.to_vec(),
+            Entry {
+                fingerprint,
+                disabled,
+                patterns: Arc::clone(&patterns),
+            },
+        );
+        patterns
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+
+    fn build_count(cache: &PatternCache, rt: &Runtime, dirs: &[PathBuf], count: &mut u32) {
+        cache.get_or_build(rt, dirs, || {
+            *count += 1;
+            FilterPatterns::default()
+        });
+    }
+
+    #[test]
+    fn reuses_patterns_until_a_filter_changes() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let rt = Runtime::isolated();
+        let dirs = [dir.path().to_path_buf()];
+        std::fs::write(dir.path().join("a.toml"), "command = \"a\"").unwrap();
+        let cache = PatternCache::default();
+        let mut builds = 0;
+
+        build_count(&cache, &rt, &dirs, &mut builds);
+        build_count(&cache, &rt, &dirs, &mut builds);
+        assert_eq!(builds, 1);
+
+        std::fs::write(dir.path().join("a.toml"), "command = \"b\"").unwrap();
+        build_count(&cache, &rt, &dirs, &mut builds);
+        assert_eq!(builds, 2);
+        assert_eq!(cache.len(), 1);
+    }
+}
@@ -70,6 +70,7 @@ fn rw(cmd: &str) -> String {
             user_config: &user_config,
             search_dirs: filter_dirs(),
             no_cache: true,
+            pattern_cache: None,
         },
         cmd,
         false,
@@ -1,5 +1,6 @@
 #![allow(clippy::unwrap_used, clippy::expect_used)]
 
+use std::collections::BTreeMap;
 use std::fs;
 
 use tempfile::TempDir;
@@ -161,6 +162,36 @@ fn rewrite_with_filter_match_with_args() {
     assert_eq!(result, "tokf run git status --short");
 }
 
+#[test]
+fn rewrite_with_glob_and_regex_patterns() {
+    let dir = TempDir::new().unwrap();
+    fs::write(
+        dir.path().join("npm-test-scripts.toml"),
+        "command = \"npmx run *:test\"",
+    )
+    .unwrap();
+    fs::write(
+        dir.path().join("npm-lint.toml"),
+        "command = \"npmx run lint\"\npattern_regex = '^npmx run lint(:\\S+)?'",
+    )
+    .unwrap();
+
+    let config = RewriteConfig::default();
+    let dirs = [dir.path().to_path_buf()];
+    assert_eq!(
+        rewrite_isolated("npmx run unit:test", &config, &dirs, false),
+        "tokf run npmx run unit:test"
+    );
+    assert_eq!(
+        rewrite_isolated("npmx run lint:css", &config, &dirs, false),
+        "tokf run npmx run lint:css"
+    );
+    assert_eq!(
+        rewrite_isolated("npmx run build", &config, &dirs, false),
+        "npmx run build"
+    );
+}
+
 #[test]
 fn rewrite_builtin_skip_tokf() {
     let dir = TempDir::new().unwrap();
@@ -207,6 +238,7 @@ fn rewrite_user_rule_takes_priority() {
         debug: None,
         transparent: None,
         local_wrapper: None,
+        hook: BTreeMap::new(),
     };
     let result = rewrite_isolated("git status", &config, &[dir.path().to_path_buf()], false);
     assert_eq!(result, "custom-wrapper git status");
@@ -231,6 +263,7 @@ fn rewrite_user_skip_prevents_rewrite() {
         debug: None,
         transparent: None,
         local_wrapper: None,
+        hook: BTreeMap::new(),
     };
     let result = rewrite_isolated("git status", &config, &[dir.path().to_path_buf()], false);
     assert_eq!(result, "git status");
@@ -404,6 +437,7 @@ fn wrapper_user_rule_overrides_builtin_wrapper() {
         debug: None,
         transparent: None,
         local_wrapper: None,
+        hook: BTreeMap::new(),
     };
     let r = rewrite_isolated("make check", &config, &[dir.path().to_path_buf()], false);
     assert_eq!(r, "custom-make check");
@@ -422,6 +456,7 @@ fn wrapper_skip_pattern_prevents_wrapper() {
         debug: None,
         transparent: None,
         local_wrapper: None,
+        hook: BTreeMap::new(),
     };
     let r = rewrite_isolated("make check", &config, &[dir.path().to_path_buf()], false);
     assert_eq!(r, "make check");
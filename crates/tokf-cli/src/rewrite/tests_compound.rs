@@ -1,5 +1,6 @@
 #![allow(clippy::unwrap_used, clippy::expect_used)]
 
+use std::collections::BTreeMap;
 use std::fs;
 
 use tempfile::TempDir;
@@ -246,6 +247,7 @@ fn rewrite_user_rule_wraps_piped_command() {
         debug: None,
         transparent: None,
         local_wrapper: None,
+        hook: BTreeMap::new(),
     };
     let r = rewrite_isolated(
         "cargo test | grep FAILED",
@@ -271,6 +273,7 @@ fn rewrite_skip_pattern_wins_over_pipe_guard() {
         debug: None,
         transparent: None,
         local_wrapper: None,
+        hook: BTreeMap::new(),
     };
     let r = rewrite_isolated(
         "git status | grep M",
@@ -2,6 +2,7 @@
 
 #![allow(clippy::unwrap_used, clippy::expect_used)]
 
+use std::collections::BTreeMap;
 use std::fs;
 
 use tempfile::TempDir;
@@ -232,6 +233,7 @@ fn rewrite_user_skip_pattern_matches_env_stripped_command() {
         debug: None,
         transparent: None,
         local_wrapper: None,
+        hook: BTreeMap::new(),
     };
     // "FOO=bar git status" does NOT start with "git", so skip does not fire
     // and the command IS rewritten.
@@ -2,6 +2,7 @@
 
 #![allow(clippy::unwrap_used, clippy::expect_used)]
 
+use std::collections::BTreeMap;
 use std::fs;
 
 use tempfile::TempDir;
@@ -30,6 +31,7 @@ fn rewrite_pipe_strip_disabled_preserves_pipe() {
         debug: None,
         transparent: None,
         local_wrapper: None,
+        hook: BTreeMap::new(),
     };
     let r = rewrite_isolated(
         "cargo test | tail -5",
@@ -61,6 +63,7 @@ fn rewrite_pipe_strip_disabled_non_piped_still_rewritten() {
         debug: None,
         transparent: None,
         local_wrapper: None,
+        hook: BTreeMap::new(),
     };
     let r = rewrite_isolated(
         "cargo test --lib",
@@ -94,6 +97,7 @@ fn rewrite_prefer_less_injects_flag() {
         debug: None,
         transparent: None,
         local_wrapper: None,
+        hook: BTreeMap::new(),
     };
     let r = rewrite_isolated(
         "cargo test | tail -5",
@@ -127,6 +131,7 @@ fn rewrite_prefer_less_without_pipe_no_effect() {
         debug: None,
         transparent: None,
         local_wrapper: None,
+        hook: BTreeMap::new(),
     };
     let r = rewrite_isolated(
         "cargo test --lib",
@@ -158,6 +163,7 @@ fn rewrite_strip_false_overrides_prefer_less() {
         debug: None,
         transparent: None,
         local_wrapper: None,
+        hook: BTreeMap::new(),
     };
     let r = rewrite_isolated(
         "cargo test | tail -5",
@@ -186,6 +192,7 @@ fn rewrite_compound_prefer_less_per_segment() {
         debug: None,
         transparent: None,
         local_wrapper: None,
+        hook: BTreeMap::new(),
     };
     let r = rewrite_isolated(
         "git add . && git diff | head -5",
@@ -8,6 +8,7 @@
 //! built-in list (`ssh`, `mosh`, `slogin`) is always active; users can
 //! extend via `[transparent] commands = […]`.
 
+use std::collections::BTreeMap;
 use std::fs;
 
 use tempfile::TempDir;
@@ -32,6 +33,7 @@ fn config_with_mangling_rule() -> RewriteConfig {
         debug: None,
         transparent: None,
         local_wrapper: None,
+        hook: BTreeMap::new(),
     }
 }
 
@@ -132,6 +134,7 @@ fn user_can_extend_transparent_list() {
             commands: vec!["kubectl".to_string()],
         }),
         local_wrapper: None,
+        hook: BTreeMap::new(),
     };
     let result = rewrite_isolated(
         "kubectl exec POD -- cmd",
@@ -159,6 +162,7 @@ fn user_extra_does_not_disable_built_ins() {
             commands: vec!["kubectl".to_string()],
         }),
         local_wrapper: None,
+        hook: BTreeMap::new(),
     };
     let result = rewrite_isolated("ssh HOST cmd", &config, &[dir.path().to_path_buf()], false);
     assert_eq!(result, "ssh HOST cmd");
@@ -263,6 +267,7 @@ fn user_skip_pattern_takes_precedence_over_transparent_gate() {
         debug: None,
         transparent: None,
         local_wrapper: None,
+        hook: BTreeMap::new(),
     };
     let result = rewrite_isolated(
         "ssh HOST 'cmd'",
@@ -0,0 +1,494 @@
+//! `tokf run`: resolve a filter, execute the command, filter and record it.
+
+use std::time::Duration;
+
+use tokf::baseline;
+use tokf::config::types::{FilterConfig, StdinMode};
+use tokf::filter;
+use tokf::history;
+use tokf::runner;
+use tokf::telemetry;
+
+use crate::Cli;
+use crate::marker;
+use crate::resolve;
+use crate::run_envelope::{RunEnvelope, RunFormat};
+use crate::run_tee;
+
+use tokf::runtime::Runtime;
+
+/// What `tokf run` was asked to execute.
+#[derive(Clone, Copy)]
+pub struct RunRequest<'a> {
+    pub command_args: &'a [String],
+    pub baseline_pipe: Option<&'a str>,
+    pub prefer_less: bool,
+    pub pty: bool,
+    pub stdin: Option<StdinMode>,
+    pub format: RunFormat,
+    /// `--tee-raw`: save the unfiltered output to this file.
+    pub tee_raw: Option<&'a std::path::Path>,
+}
+
+/// CLI surface for `tokf run --stdin`. Mirrors [`StdinMode`] but lives here so
+/// the `clap::ValueEnum` derive doesn't pollute the library crate.
+#[derive(clap::ValueEnum, Clone, Copy, Debug)]
+pub enum StdinCli {
+    /// tokf's own stdin, so editors and prompts reach the user
+    Inherit,
+    /// /dev/null: reads hit end-of-file at once
+    Null,
+    /// No stdin at all; reads fail
+    Closed,
+}
+
+impl From<StdinCli> for StdinMode {
+    fn from(v: StdinCli) -> Self {
+        match v {
+            StdinCli::Inherit => Self::Inherit,
+            StdinCli::Null => Self::Null,
+            StdinCli::Closed => Self::Closed,
+        }
+    }
+}
+
+// NOTE: cmd_run integrates command resolution, execution, output rendering, tracking,
+// history recording, and telemetry. Splitting would require threading 6+ values through helpers.
+// Approved to exceed the 60-line limit.
+#[allow(clippy::too_many_lines)]
+pub fn cmd_run(
+    rt: &Runtime,
+    request: RunRequest<'_>,
+    cli: &Cli,
+    reporter: &dyn telemetry::TelemetryReporter,
+) -> anyhow::Result<i32> {
+    let RunRequest {
+        command_args,
+        baseline_pipe,
+        prefer_less,
+        pty,
+        stdin,
+        format,
+        tee_raw,
+    } = request;
+    let json = format == RunFormat::Json;
+    let filter_match = if cli.no_filter {
+        None
+    } else {
+        resolve::find_filter(rt, command_args, cli.verbose, cli.no_cache)?
+    };
+
+    let words_consumed = filter_match.as_ref().map_or(0, |m| m.words_consumed);
+    let remaining_args: Vec<String> = if words_consumed > 0 {
+        command_args[words_consumed..].to_vec()
+    } else if command_args.len() > 1 {
+        command_args[1..].to_vec()
+    } else {
+        vec![]
+    };
+
+    // Phase A.5: resolve args-pattern variants before passthrough check.
+    // If an args variant matches, its config replaces the parent (including
+    // its passthrough_args), so the passthrough check below uses the correct set.
+    let filter_match =
+        filter_match.map(|m| resolve::resolve_args_variants(m, &remaining_args, cli.verbose));
+
+    let passthrough = filter_match
+        .as_ref()
+        .is_some_and(|m| m.config.should_passthrough(&remaining_args));
+    if passthrough && cli.verbose {
+        eprintln!("[tokf] passthrough: user args match passthrough_args, skipping filter");
+    }
+    let filter_cfg = if passthrough {
+        None
+    } else {
+        filter_match.as_ref().map(|m| &m.config)
+    };
+    let matched_command = filter_match.as_ref().map(|m| m.matched_command.as_str());
+    let (execution, executed_command) = resolve::run_command(
+        rt,
+        resolve::ResolvedCommand {
+            filter_cfg,
+            words_consumed,
+            matched_command,
+            command_args,
+            remaining_args: &remaining_args,
+            verbose: cli.verbose,
+            pty,
+            timeout: cli.timeout.map(Duration::from_secs),
+            stdin,
+            spill_threshold: spill_threshold(rt),
+            shell: &shell(rt),
+        },
+    )?;
+    let banner = banner(&execution);
+    let runner::Execution {
+        result: cmd_result,
+        usage,
+        spilled,
+        attempts,
+        ..
+    } = execution;
+    let raw_len = spilled
+        .as_ref()
+        .map_or(cmd_result.combined.len(), |s| s.bytes);
+    if cli.verbose
+        && let Some(spilled) = &spilled
+    {
+        eprintln!(
+            "[tokf] output spilled to disk: {} bytes, first {} kept in memory",
+            spilled.bytes,
+            cmd_result.combined.len()
+        );
+    }
+
+    let filter_match = if passthrough { None } else { filter_match };
+    let Some(filter_match) = filter_match else {
+        if prefer_less && cli.verbose {
+            eprintln!("[tokf] --prefer-less has no effect: no matching filter found");
+        }
+        run_tee::tee_raw(rt, tee_raw, None, &cmd_result, spilled.as_ref());
+        let input_bytes = match baseline_pipe.filter(|_| spilled.is_none()) {
+            Some(pipe_cmd) => baseline::compute(&cmd_result.combined, pipe_cmd),
+            None => raw_len,
+        };
+        let mask = masks_exit_code(rt, cli, None);
+        if json {
+            let output = passthrough_output(&cmd_result, banner.as_deref(), spilled.as_ref())?;
+            let command = command_args.join(" ");
+            RunEnvelope::new(
+                &command,
+                None,
+                cmd_result.exit_code,
+                &output,
+                (input_bytes, raw_len),
+            )
+            .duration(usage.duration)
+            .print();
+        } else {
+            print_passthrough(&cmd_result, banner.as_deref(), spilled.as_ref(), mask)?;
+        }
+        // filter_time_ms = 0: no filter was applied, not 0ms of filtering.
+        // Passthrough commands are not recorded to history: raw == filtered would
+        // waste storage and add noise with nothing useful to compare.
+        // output_bytes = raw_len: what tokf actually printed (full raw output).
+        resolve::record_run(
+            rt,
+            command_args,
+            None,
+            None,
+            input_bytes,
+            raw_len,
+            raw_len,
+            0,
+            cmd_result.exit_code,
+            false,
+            Some(&usage),
+            counted_texts(baseline_pipe, spilled.as_ref(), &cmd_result.combined)
+                .map(|raw| (raw, raw)),
+        );
+        resolve::try_auto_sync(rt);
+        reporter.report(&telemetry::TelemetryEvent::new(
+            rt,
+            None,
+            command_args.join(" "),
+            input_bytes,
+            raw_len,
+            raw_len,
+            &cmd_result.combined,
+            &cmd_result.combined,
+            std::time::Duration::ZERO,
+            cmd_result.exit_code,
+        ));
+        return Ok(if mask { 0 } else { cmd_result.exit_code });
+    };
+
+    // Phase B: resolve deferred output-pattern variants using the already-discovered
+    // filter list (no second discovery call needed).
+    let (cfg, filter_hash) =
+        resolve::resolve_phase_b(filter_match, &cmd_result.combined, cli.verbose);
+    run_tee::tee_raw(rt, tee_raw, Some(&cfg), &cmd_result, spilled.as_ref());
+    let mask = masks_exit_code(rt, cli, Some(&cfg));
+
+    // Compute piped output once: when prefer_less is active we need the full text
+    // for comparison, otherwise just the byte count for tracking. Spilled
+    // output is too large to pipe through the baseline command.
+    let (input_bytes, piped_text) = match baseline_pipe.filter(|_| spilled.is_none()) {
+        Some(pipe_cmd) if prefer_less => {
+            let text = baseline::compute_output(&cmd_result.combined, pipe_cmd);
+            let bytes = text.as_ref().map_or(cmd_result.combined.len(), String::len);
+            (bytes, text)
+        }
+        Some(pipe_cmd) => (baseline::compute(&cmd_result.combined, pipe_cmd), None),
+        None => (raw_len, None),
+    };
+
+    let start = std::time::Instant::now();
+    let mut filter_opts = resolve::filter_options(&cfg, rt.cwd(), cli.preserve_color);
+    filter_opts.vars = usage.template_vars();
+    filter_opts
+        .vars
+        .insert("attempts".to_string(), attempts.to_string());
+    let mut filtered = match &spilled {
+        Some(spilled) => filter_spilled(&cfg, spilled, &cmd_result, &remaining_args, &filter_opts)?,
+        None => filter::apply(&cfg, &cmd_result, &remaining_args, &filter_opts),
+    };
+    if cfg.diff_previous
+        && let Some(previous) = resolve::previous_filtered_output(
+            rt,
+            &cfg,
+            &command_args.join(" "),
+            &remaining_args,
+            &filter_opts,
+        )
+    {
+        filtered.output = filter::diff::diff_against_previous(&filtered.output, &previous);
+    }
+    let elapsed = start.elapsed();
+
+    if cli.timing {
+        eprintln!("[tokf] filter took {:.1}ms", elapsed.as_secs_f64() * 1000.0);
+    }
+
+    // --prefer-less: compare filtered output with cached piped output, use whichever is smaller.
+    let (final_output, output_bytes, pipe_override) =
+        if let Some(piped) = piped_text.filter(|t| t.len() < filtered.output.len()) {
+            if cli.verbose {
+                eprintln!(
+                    "[tokf] prefer-less: pipe output ({} bytes) < filtered ({} bytes), using pipe",
+                    piped.len(),
+                    filtered.output.len()
+                );
+            }
+            let len = piped.len();
+            (piped, len, true)
+        } else {
+            let len = filtered.output.len();
+            (filtered.output, len, false)
+        };
+
+    // The banner is tokf's own note, so it is left out of `output_bytes`.
+    let final_output = match banner {
+        Some(banner) if final_output.is_empty() => banner,
+        Some(banner) => format!("{banner}\n{final_output}"),
+        None => final_output,
+    };
+
+    let filter_name = cfg.command.first();
+    let command_str = command_args.join(" ");
+    let raw_bytes = raw_len;
+
+    if cli.verbose {
+        eprintln!(
+            "[tokf] accounting: raw={raw_bytes}B baseline={input_bytes}B filtered={output_bytes}B"
+        );
+    }
+
+    resolve::record_run(
+        rt,
+        command_args,
+        Some(filter_name),
+        Some(&filter_hash),
+        input_bytes,
+        output_bytes,
+        raw_bytes,
+        elapsed.as_millis(),
+        cmd_result.exit_code,
+        pipe_override,
+        Some(&usage),
+        // The banner is prepended, so the printed output is the tail.
+        counted_texts(baseline_pipe, spilled.as_ref(), &cmd_result.combined)
+            .map(|raw| (raw, &final_output[final_output.len() - output_bytes..])),
+    );
+    resolve::try_auto_sync(rt);
+
+    // Detect whether to show the history hint:
+    //   - filter author opted in via `show_history_hint = true`, or
+    //   - the same command was re-run (LLM confusion signal: it didn't act on
+    //     the previous filtered output and is asking again).
+    // Check the DB before recording so we compare against the *previous* run.
+    let show_hint = cfg.show_history_hint || history::try_was_recently_run(rt, &command_str);
+
+    let history_id = history::try_record(
+        rt,
+        &history::RecordedRun {
+            command: &command_str,
+            executed_command: executed_command.as_deref(),
+            filter_name,
+            raw_output: &filter::redact::redact(
+                &cfg,
+                &history_raw(&cmd_result.combined, spilled.as_ref()),
+            ),
+            filtered_output: &final_output,
+            exit_code: cmd_result.exit_code,
+        },
+    );
+
+    if json {
+        RunEnvelope::new(
+            &command_str,
+            Some(filter_name),
+            cmd_result.exit_code,
+            &final_output,
+            (input_bytes, output_bytes),
+        )
+        .history_id(history_id)
+        .duration(usage.duration)
+        .print();
+    } else {
+        let exit_line = (mask && cmd_result.exit_code != 0).then_some(cmd_result.exit_code);
+        let hint = history_id.filter(|_| show_hint);
+        print_filtered(rt, &final_output, exit_line, history_id, hint);
+    }
+
+    reporter.report(&telemetry::TelemetryEvent::new(
+        rt,
+        Some(filter_name.to_string()),
+        command_str,
+        input_bytes,
+        output_bytes,
+        raw_bytes,
+        &cmd_result.combined,
+        &final_output,
+        elapsed,
+        cmd_result.exit_code,
+    ));
+
+    Ok(if mask { 0 } else { cmd_result.exit_code })
+}
+
+/// The raw output when it is also the run's input for tracking: not
+/// replaced by a baseline and not cut short by spilling to disk.
+fn counted_texts<'a>(
+    baseline_pipe: Option<&str>,
+    spilled: Option<&runner::SpilledOutput>,
+    raw: &'a str,
+) -> Option<&'a str> {
+    (baseline_pipe.is_none() && spilled.is_none()).then_some(raw)
+}
+
+/// Print the unfiltered output of a passthrough run.
+fn print_passthrough(
+    cmd_result: &runner::CommandResult,
+    banner: Option<&str>,
+    spilled: Option<&runner::SpilledOutput>,
+    mask: bool,
+) -> anyhow::Result<()> {
+    if mask && cmd_result.exit_code != 0 {
+        println!("Error: Exit code {}", cmd_result.exit_code);
+    }
+    if let Some(banner) = banner {
+        println!("{banner}");
+    }
+    if let Some(spilled) = spilled {
+        spilled.copy_to(&mut std::io::stdout().lock())?;
+    } else if !cmd_result.combined.is_empty() {
+        println!("{}", cmd_result.combined);
+    }
+    Ok(())
+}
+
+/// The full output of a passthrough run, spilled or not, for `--format json`.
+fn passthrough_output(
+    cmd_result: &runner::CommandResult,
+    banner: Option<&str>,
+    spilled: Option<&runner::SpilledOutput>,
+) -> anyhow::Result<String> {
+    let mut output = banner.map(|b| format!("{b}\n")).unwrap_or_default();
+    if let Some(spilled) = spilled {
+        let mut buf = Vec::with_capacity(spilled.bytes);
+        spilled.copy_to(&mut buf)?;
+        output.push_str(String::from_utf8_lossy(&buf).trim_end());
+    } else {
+        output.push_str(&cmd_result.combined);
+    }
+    Ok(output)
+}
+
+/// Print the filtered output, after the masked exit code and before the
+/// history hint when there is one.
+fn print_filtered(
+    rt: &Runtime,
+    final_output: &str,
+    exit_line: Option<i32>,
+    history_id: Option<i64>,
+    hint: Option<i64>,
+) {
+    let render_cfg = marker::load_render_config(rt);
+    if let Some(code) = exit_line {
+        println!("Error: Exit code {code}");
+    }
+    if !final_output.is_empty() {
+        marker::print_with_indicator(final_output, &render_cfg, history_id);
+    }
+    if let Some(id) = hint {
+        println!("🗜️ compressed — run `tokf raw {id}` for full output");
+    }
+}
+
+/// First line of the output of a command that was interrupted or killed on
+/// timeout: what follows is partial.
+fn banner(execution: &runner::Execution) -> Option<String> {
+    if execution.interrupted {
+        return Some("(interrupted)".to_string());
+    }
+    execution
+        .timed_out
+        .map(|timeout| format!("(timed out after {}s)", timeout.as_secs()))
+}
+
+/// Whether tokf exits 0 and prints `Error: Exit code N` rather than exiting
+/// with the command's code: off with `--no-mask-exit-code`, else as the
+/// filter's `mask_exit_code`, else as the project's `[run] mask_exit_code`.
+pub fn masks_exit_code(rt: &Runtime, cli: &Cli, cfg: Option<&FilterConfig>) -> bool {
+    if cli.no_mask_exit_code {
+        return false;
+    }
+    cfg.and_then(|cfg| cfg.mask_exit_code).unwrap_or_else(|| {
+        let project_root = history::project_root_for(rt.cwd_or_empty());
+        history::RunConfig::load(rt, Some(&project_root)).mask_exit_code
+    })
+}
+
+/// `[run] spill_threshold_mb` for the current project, in bytes.
+fn spill_threshold(rt: &Runtime) -> Option<usize> {
+    let project_root = history::project_root_for(rt.cwd_or_empty());
+    history::RunConfig::load(rt, Some(&project_root)).spill_threshold
+}
+
+fn shell(rt: &Runtime) -> runner::Shell {
+    let project_root = history::project_root_for(rt.cwd_or_empty());
+    history::ShellConfig::load(rt, Some(&project_root)).shell
+}
+
+/// Filter spilled output line by line from disk, so only what the filter
+/// keeps is held in memory. Each line carries its channel, for `source`.
+fn filter_spilled(
+    cfg: &FilterConfig,
+    spilled: &runner::SpilledOutput,
+    cmd_result: &runner::CommandResult,
+    args: &[String],
+    opts: &filter::FilterOptions,
+) -> anyhow::Result<filter::FilterResult> {
+    let mut streaming = filter::streaming::StreamingFilter::new(cfg, args, opts);
+    for line in spilled.lines_by_stream()? {
+        let (stream, line) = line?;
+        streaming.push_line_from(stream, &line);
+    }
+    Ok(streaming.finish(cmd_result.exit_code).1)
+}
+
+/// Raw output to store in history. Spilled output is too large to store, so
+/// only its first lines are kept, followed by a note.
+fn history_raw<'a>(
+    combined: &'a str,
+    spilled: Option<&runner::SpilledOutput>,
+) -> std::borrow::Cow<'a, str> {
+    spilled.map_or(std::borrow::Cow::Borrowed(combined), |spilled| {
+        std::borrow::Cow::Owned(format!(
+            "{combined}\n[tokf] output truncated: {} of {} bytes kept",
+            combined.len(),
+            spilled.bytes
+        ))
+    })
+}
@@ -0,0 +1,109 @@
+//! `tokf run --format json`: the run's output and metadata as one JSON object,
+//! for wrappers that would otherwise parse tokf's stderr hints.
+
+use serde::Serialize;
+use tokf_common::tokens::estimate_tokens_from_bytes;
+
+/// How `tokf run` prints its result.
+#[derive(clap::ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum RunFormat {
+    /// The filtered output, as the command would print it
+    #[default]
+    Text,
+    /// A single JSON object with the filtered output and run metadata
+    Json,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RunEnvelope<'a> {
+    pub command: &'a str,
+    /// The filter that ran; `null` for passthrough.
+    pub filter: Option<&'a str>,
+    pub exit_code: i32,
+    pub filtered_output: &'a str,
+    /// Bytes the output is measured against (the baseline pipe's, if any).
+    pub input_bytes: usize,
+    pub output_bytes: usize,
+    pub tokens_saved_est: i64,
+    /// `tokf raw <id>` prints the full output; `null` when nothing was recorded.
+    pub history_id: Option<i64>,
+    /// Wall-clock time of the command itself.
+    pub duration_ms: u64,
+}
+
+impl<'a> RunEnvelope<'a> {
+    pub fn new(
+        command: &'a str,
+        filter: Option<&'a str>,
+        exit_code: i32,
+        filtered_output: &'a str,
+        bytes: (usize, usize),
+    ) -> Self {
+        let (input_bytes, output_bytes) = bytes;
+        #[allow(clippy::cast_possible_wrap)]
+        let tokens_saved_est = estimate_tokens_from_bytes(input_bytes) as i64
+            - estimate_tokens_from_bytes(output_bytes) as i64;
+        Self {
+            command,
+            filter,
+            exit_code,
+            filtered_output,
+            input_bytes,
+            output_bytes,
+            tokens_saved_est,
+            history_id: None,
+            duration_ms: 0,
+        }
+    }
+
+    #[must_use]
+    pub const fn history_id(mut self, id: Option<i64>) -> Self {
+        self.history_id = id;
+        self
+    }
+
+    #[must_use]
+    pub fn duration(mut self, duration: std::time::Duration) -> Self {
+        self.duration_ms = u64::try_from(duration.as_millis()).unwrap_or(u64::MAX);
+        self
+    }
+
+    /// Print as one line of JSON, so the output can be read as JSON Lines.
+    pub fn print(&self) {
+        match serde_json::to_string(self) {
+            Ok(json) => println!("{json}"),
+            Err(e) => eprintln!("[tokf] JSON serialization error: {e}"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[allow(clippy::cast_possible_wrap)]
+    fn envelope_estimates_tokens_saved() {
+        let envelope = RunEnvelope::new("cargo test", Some("cargo/test"), 0, "ok", (4000, 400))
+            .history_id(Some(7))
+            .duration(std::time::Duration::from_millis(1250));
+        assert_eq!(
+            envelope.tokens_saved_est,
+            (estimate_tokens_from_bytes(4000) - estimate_tokens_from_bytes(400)) as i64
+        );
+        let json = serde_json::to_value(&envelope).unwrap_or_default();
+        assert_eq!(json["history_id"], 7);
+        assert_eq!(json["duration_ms"], 1250);
+        assert_eq!(json["filter"], "cargo/test");
+    }
+
+    #[test]
+    fn passthrough_envelope_has_null_filter_and_history() {
+        let envelope = RunEnvelope::new("ls", None, 2, "boom", (4, 4));
+        let json = serde_json::to_value(&envelope).unwrap_or_default();
+        assert!(json["filter"].is_null());
+        assert!(json["history_id"].is_null());
+        assert_eq!(json["exit_code"], 2);
+        assert_eq!(json["tokens_saved_est"], 0);
+    }
+}
@@ -0,0 +1,130 @@
+//! `tokf run --tee-raw` and a filter's `tee_raw_dir`: save the unfiltered
+//! output to a file while the filtered version is printed, e.g. to keep the
+//! full log as a CI artifact.
+
+use std::io::Write as _;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use anyhow::Context as _;
+use tokf::config::types::FilterConfig;
+use tokf::runner;
+
+use tokf::runtime::Runtime;
+
+/// Where to save this run's raw output: `--tee-raw`, else a new file in the
+/// filter's `tee_raw_dir`.
+fn target(rt: &Runtime, flag: Option<&Path>, cfg: Option<&FilterConfig>) -> Option<PathBuf> {
+    if let Some(path) = flag {
+        return Some(path.to_path_buf());
+    }
+    let cfg = cfg?;
+    let dir = rt.cwd_or_empty().join(cfg.tee_raw_dir.as_deref()?);
+    let millis = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_millis());
+    Some(dir.join(format!("{}-{millis}.log", slug(cfg.command.first()))))
+}
+
+/// `cargo test` → `cargo-test`.
+fn slug(command: &str) -> String {
+    command
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+fn write(
+    path: &Path,
+    result: &runner::CommandResult,
+    spilled: Option<&runner::SpilledOutput>,
+) -> anyhow::Result<()> {
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        std::fs::create_dir_all(parent)?;
+    }
+    let mut file = std::io::BufWriter::new(std::fs::File::create(path)?);
+    if let Some(spilled) = spilled {
+        spilled.copy_to(&mut file)?;
+    } else if !result.combined.is_empty() {
+        writeln!(file, "{}", result.combined)?;
+    }
+    file.flush()?;
+    Ok(())
+}
+
+/// Save the combined output as the command printed it, unmasked. A file that
+/// cannot be written is reported on stderr and does not fail the run.
+pub fn tee_raw(
+    rt: &Runtime,
+    flag: Option<&Path>,
+    cfg: Option<&FilterConfig>,
+    result: &runner::CommandResult,
+    spilled: Option<&runner::SpilledOutput>,
+) {
+    let Some(path) = target(rt, flag, cfg) else {
+        return;
+    };
+    if let Err(e) =
+        write(&path, result, spilled).with_context(|| format!("write {}", path.display()))
+    {
+        eprintln!("[tokf] warning: raw output not saved: {e:#}");
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    fn result(combined: &str) -> runner::CommandResult {
+        runner::CommandResult {
+            stdout: combined.to_string(),
+            stderr: String::new(),
+            exit_code: 0,
+            combined: combined.to_string(),
+        }
+    }
+
+    #[test]
+    fn flag_wins_over_filter_dir() {
+        let rt = Runtime::isolated();
+        let cfg: FilterConfig =
+            toml::from_str("command = \"cargo test\"\ntee_raw_dir = \"logs\"").unwrap();
+        let flag = Path::new("/tmp/raw.log");
+        assert_eq!(
+            target(&rt, Some(flag), Some(&cfg)),
+            Some(flag.to_path_buf())
+        );
+        let in_dir = target(&rt, None, Some(&cfg)).unwrap();
+        assert_eq!(
+            in_dir.parent(),
+            Some(rt.cwd_or_empty().join("logs").as_path())
+        );
+        assert_eq!(in_dir.extension().unwrap(), "log");
+        let name = in_dir.file_name().unwrap().to_string_lossy().to_string();
+        assert!(name.starts_with("cargo-test-"), "{name}");
+    }
+
+    #[test]
+    fn no_flag_and_no_dir_saves_nothing() {
+        let rt = Runtime::isolated();
+        let cfg: FilterConfig = toml::from_str("command = \"cargo test\"").unwrap();
+        assert_eq!(target(&rt, None, Some(&cfg)), None);
+        assert_eq!(target(&rt, None, None), None);
+    }
+
+    #[test]
+    fn tee_raw_creates_parent_dirs() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("ci/logs/raw.log");
+        tee_raw(
+            &Runtime::isolated(),
+            Some(&path),
+            None,
+            &result("line 1\nline 2"),
+            None,
+        );
+        assert_eq!(std::fs::read_to_string(path).unwrap(), "line 1\nline 2\n");
+    }
+}
@@ -1,14 +1,78 @@
 use std::io::ErrorKind;
 use std::io::{BufRead, BufReader};
+use std::path::Path;
 use std::process::{Command, Stdio};
 use std::sync::mpsc;
 use std::thread;
+use std::time::Duration;
+
+use crate::config::types::StdinMode;
+
+#[cfg(unix)]
+mod pty;
+mod retry;
+mod shell;
+mod signals;
+mod spill;
+mod usage;
+mod wait;
+
+pub use retry::with_retry;
+pub use shell::{Shell, ShellKind};
+pub use signals::received as signals_received;
+pub use spill::SpilledOutput;
+pub use usage::ResourceUsage;
 
 /// Re-export `CommandResult` from tokf-filter so existing code that
 /// references `crate::runner::CommandResult` continues to work.
 pub type CommandResult = tokf_filter::CommandResult;
 
+/// Exit code reported for a command killed on timeout, as GNU `timeout` does.
+pub const TIMEOUT_EXIT_CODE: i32 = 124;
+
+/// Exit code reported when tokf is interrupted between retries, as shells
+/// report a Ctrl-C (128 + SIGINT).
+pub const INTERRUPTED_EXIT_CODE: i32 = 130;
+
+/// How to run a command.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ExecOptions<'a> {
+    /// Attach the command to a pseudo-terminal instead of pipes.
+    pub pty: bool,
+    /// Kill the command and everything it spawned after this long. On Unix
+    /// the command then gets `/dev/null` instead of an inherited terminal
+    /// stdin, which it could not read from its own process group.
+    pub timeout: Option<Duration>,
+    /// Working directory for the command (tokf's own when `None`).
+    pub cwd: Option<&'a Path>,
+    pub stdin: StdinMode,
+    /// Bytes of output to keep in memory before spilling the rest to a
+    /// temporary file (see [`SpilledOutput`]). Ignored in pty mode.
+    pub spill_threshold: Option<usize>,
+    /// Shell for `run` commands; the system shell when `None`.
+    pub shell: Option<&'a Shell>,
+}
+
+/// A finished command.
+#[derive(Debug)]
+pub struct Execution {
+    /// On timeout, the output captured until then, with [`TIMEOUT_EXIT_CODE`].
+    pub result: CommandResult,
+    /// The timeout that killed the command, if it fired.
+    pub timed_out: Option<Duration>,
+    /// tokf got SIGINT or SIGTERM while the command ran; `result` holds the
+    /// output captured until the command exited or was killed.
+    pub interrupted: bool,
+    pub usage: ResourceUsage,
+    /// The full output, when it passed the spill threshold; `result` then
+    /// holds only its first lines, in `combined`.
+    pub spilled: Option<SpilledOutput>,
+    /// Runs it took, counting retries (see [`with_retry`]).
+    pub attempts: u32,
+}
+
 /// Which stream a line came from.
+#[derive(Debug, Clone, Copy)]
 enum Source {
     Stdout,
     Stderr,
@@ -29,6 +93,16 @@ fn exit_code_from_status(status: std::process::ExitStatus) -> i32 {
     }
 }
 
+/// Exit code of a finished command, or [`TIMEOUT_EXIT_CODE`] if it was killed
+/// on timeout.
+fn exit_code(waited: &wait::Waited) -> i32 {
+    if waited.timed_out {
+        TIMEOUT_EXIT_CODE
+    } else {
+        exit_code_from_status(waited.status)
+    }
+}
+
 /// Join collected lines into a single string without forcing a trailing newline.
 fn join_lines(lines: &[String]) -> String {
     lines.join("\n")
@@ -40,7 +114,15 @@ fn join_lines(lines: &[String]) -> String {
 /// This is critical for filters that use chunk processing — e.g. the
 /// cargo-test filter splits on `Running` headers (stderr) and expects
 /// `test result:` lines (stdout) to appear within each chunk.
-fn run_interleaved(mut child: std::process::Child) -> anyhow::Result<CommandResult> {
+///
+/// Lines are collected while the command runs, so output past
+/// `opts.spill_threshold` goes straight to disk.
+fn run_interleaved(
+    mut child: std::process::Child,
+    opts: ExecOptions<'_>,
+    started: &usage::Snapshot,
+) -> anyhow::Result<Execution> {
+    let timeout = opts.timeout;
     let stdout_pipe = child
         .stdout
         .take()
@@ -67,6 +149,18 @@ fn run_interleaved(mut child: std::process::Child) -> anyhow::Result<CommandResu
         }
     });
 
+    // Ends once both readers are done and every sender is dropped.
+    let threshold = opts.spill_threshold;
+    let collector_thread = thread::spawn(move || {
+        let mut collector = spill::Collector::new(threshold);
+        for (source, line) in rx {
+            collector.push(source, line);
+        }
+        collector
+    });
+
+    let waited = wait::wait(&mut child, timeout)?;
+
     stdout_thread
         .join()
         .map_err(|_| anyhow::anyhow!("stdout reader thread panicked"))?;
@@ -74,26 +168,18 @@ fn run_interleaved(mut child: std::process::Child) -> anyhow::Result<CommandResu
         .join()
         .map_err(|_| anyhow::anyhow!("stderr reader thread panicked"))?;
 
-    // All senders dropped → rx iteration will terminate
-    let mut stdout_lines = Vec::new();
-    let mut stderr_lines = Vec::new();
-    let mut combined_lines = Vec::new();
-
-    for (source, line) in rx {
-        combined_lines.push(line.clone());
-        match source {
-            Source::Stdout => stdout_lines.push(line),
-            Source::Stderr => stderr_lines.push(line),
-        }
-    }
-
-    let status = child.wait()?;
-
-    Ok(CommandResult {
-        stdout: join_lines(&stdout_lines),
-        stderr: join_lines(&stderr_lines),
-        exit_code: exit_code_from_status(status),
-        combined: combined_lines.join("\n"),
+    let (result, spilled) = collector_thread
+        .join()
+        .map_err(|_| anyhow::anyhow!("output collector thread panicked"))?
+        .finish(exit_code(&waited))?;
+
+    Ok(Execution {
+        result,
+        timed_out: timeout.filter(|_| waited.timed_out),
+        interrupted: waited.interrupted,
+        usage: started.finish(),
+        spilled,
+        attempts: 1,
     })
 }
 
@@ -112,24 +198,6 @@ pub fn resolve_program(program: &str) -> Option<std::path::PathBuf> {
     None
 }
 
-/// Build the system shell command for a shell snippet.
-fn build_shell_command(command: &str) -> Command {
-    #[cfg(windows)]
-    {
-        let mut cmd = Command::new("powershell.exe");
-        cmd.args(["-NoLogo", "-NoProfile", "-NonInteractive", "-Command"])
-            .arg(command);
-        cmd
-    }
-
-    #[cfg(not(windows))]
-    {
-        let mut cmd = Command::new("sh");
-        cmd.arg("-c").arg(command);
-        cmd
-    }
-}
-
 fn spawn_command(mut cmd: Command, program: &str) -> anyhow::Result<std::process::Child> {
     match cmd.spawn() {
         Ok(child) => Ok(child),
@@ -140,6 +208,53 @@ fn spawn_command(mut cmd: Command, program: &str) -> anyhow::Result<std::process
     }
 }
 
+/// Spawn `cmd` and capture its output as `opts` asks.
+fn capture(mut cmd: Command, program: &str, opts: ExecOptions<'_>) -> anyhow::Result<Execution> {
+    if let Some(dir) = opts.cwd {
+        cmd.current_dir(dir);
+    }
+    set_stdin(&mut cmd, opts.stdin);
+    if opts.timeout.is_some() {
+        wait::isolate(&mut cmd, opts.stdin);
+    }
+    let started = usage::Snapshot::now();
+    if opts.pty {
+        #[cfg(unix)]
+        return pty::run(cmd, program, opts.timeout, &started);
+        #[cfg(not(unix))]
+        eprintln!("[tokf] pty mode is not supported on this platform, using pipes");
+    }
+    cmd.stdout(Stdio::piped()).stderr(Stdio::piped());
+    run_interleaved(spawn_command(cmd, program)?, opts, &started)
+}
+
+/// Give `cmd` the stdin `mode` asks for.
+fn set_stdin(cmd: &mut Command, mode: StdinMode) {
+    match mode {
+        StdinMode::Inherit => {}
+        StdinMode::Null => {
+            cmd.stdin(Stdio::null());
+        }
+        #[cfg(unix)]
+        StdinMode::Closed => {
+            use std::os::unix::process::CommandExt;
+            // SAFETY: the hook runs between fork and exec and only calls
+            // close(2), which is async-signal-safe. fd 0 is not used by the
+            // child afterwards except through the program being exec'd.
+            unsafe {
+                cmd.pre_exec(|| {
+                    rustix::io::close(0);
+                    Ok(())
+                });
+            }
+        }
+        #[cfg(not(unix))]
+        StdinMode::Closed => {
+            cmd.stdin(Stdio::null());
+        }
+    }
+}
+
 /// Escape a string for safe inclusion in a shell command.
 pub(crate) fn shell_escape(arg: &str) -> String {
     #[cfg(windows)]
@@ -162,7 +277,7 @@ pub(crate) fn shell_escape(arg: &str) -> String {
 ///
 /// Returns an error if the command string is empty or the process fails to spawn.
 pub fn execute(command: &str, args: &[String]) -> anyhow::Result<CommandResult> {
-    execute_with_env(command, args, &[])
+    execute_with_env(command, args, &[], ExecOptions::default()).map(|e| e.result)
 }
 
 /// Execute a command with extra environment variables.
@@ -171,6 +286,8 @@ pub fn execute(command: &str, args: &[String]) -> anyhow::Result<CommandResult>
 /// absolute path via the *current* `PATH` before the override is applied.
 /// This prevents the spawned process from finding our own shim.
 ///
+/// `opts` selects pseudo-terminal capture and a timeout.
+///
 /// # Errors
 ///
 /// Returns an error if the command string is empty or the process fails to spawn.
@@ -178,7 +295,8 @@ pub fn execute_with_env(
     command: &str,
     args: &[String],
     extra_env: &[(&str, &str)],
-) -> anyhow::Result<CommandResult> {
+    opts: ExecOptions<'_>,
+) -> anyhow::Result<Execution> {
     let mut parts = command.split_whitespace();
     let program = parts
         .next()
@@ -196,15 +314,12 @@ pub fn execute_with_env(
         .map_or(program, |p| p.to_str().unwrap_or(program));
 
     let mut cmd = Command::new(actual_program);
-    cmd.args(&base_args)
-        .args(args)
-        .stdout(Stdio::piped())
-        .stderr(Stdio::piped());
+    cmd.args(&base_args).args(args);
     for (k, v) in extra_env {
         cmd.env(k, v);
     }
 
-    run_interleaved(spawn_command(cmd, actual_program)?)
+    capture(cmd, actual_program, opts)
 }
 
 /// Execute a shell command with `{args}` interpolation.
@@ -216,27 +331,23 @@ pub fn execute_with_env(
 ///
 /// Returns an error if the shell process fails to spawn.
 pub fn execute_shell(run: &str, args: &[String]) -> anyhow::Result<CommandResult> {
-    execute_shell_with_env(run, args, &[])
+    execute_shell_with_env(run, args, &[], ExecOptions::default()).map(|e| e.result)
 }
 
 /// Expand a filter's `run` template into the exact shell command line that will
-/// be executed: `{args}` is replaced with the shell-escaped user arguments.
+/// be executed: `{args}` is replaced with the user arguments, quoted for `shell`.
 ///
 /// A template without `{args}` drops the user's arguments — that is the
 /// documented behaviour, and this function reproduces it faithfully so callers
 /// can record what actually ran (issue #430).
 #[must_use]
-pub fn expand_run_command(run: &str, args: &[String]) -> String {
-    let joined_args = args
-        .iter()
-        .map(|a| shell_escape(a))
-        .collect::<Vec<_>>()
-        .join(" ");
+pub fn expand_run_command(shell: &Shell, run: &str, args: &[String]) -> String {
     #[allow(clippy::literal_string_with_formatting_args)]
-    run.replace("{args}", &joined_args)
+    run.replace("{args}", &shell.quote_all(args))
 }
 
-/// Execute a shell command with extra environment variables.
+/// Execute a shell command with extra environment variables, run as `opts`
+/// asks (in `opts.shell`, or the system shell).
 ///
 /// # Errors
 ///
@@ -245,21 +356,16 @@ pub fn execute_shell_with_env(
     run: &str,
     args: &[String],
     extra_env: &[(&str, &str)],
-) -> anyhow::Result<CommandResult> {
-    let shell_cmd = expand_run_command(run, args);
-
-    let shell_program = if cfg!(windows) {
-        "powershell.exe"
-    } else {
-        "sh"
-    };
-    let mut cmd = build_shell_command(&shell_cmd);
-    cmd.stdout(Stdio::piped()).stderr(Stdio::piped());
+    opts: ExecOptions<'_>,
+) -> anyhow::Result<Execution> {
+    let system_shell = Shell::default();
+    let shell = opts.shell.unwrap_or(&system_shell);
+    let mut cmd = shell.command(&expand_run_command(shell, run, args));
     for (k, v) in extra_env {
         cmd.env(k, v);
     }
 
-    run_interleaved(spawn_command(cmd, shell_program)?)
+    capture(cmd, &shell.program, opts)
 }
 
 #[cfg(test)]
@@ -268,268 +374,16 @@ pub fn execute_shell_with_env(
     clippy::expect_used,
     clippy::literal_string_with_formatting_args
 )]
-mod tests {
-    use super::*;
-
-    // --- execute tests ---
-
-    #[test]
-    fn test_execute_echo() {
-        let result = execute("echo hello", &[]).unwrap();
-        assert_eq!(result.stdout.trim(), "hello");
-        assert_eq!(result.exit_code, 0);
-        assert!(result.stderr.is_empty());
-    }
-
-    #[test]
-    fn test_execute_with_args() {
-        let args = vec!["hello".to_string(), "world".to_string()];
-        let result = execute("echo", &args).unwrap();
-        assert_eq!(result.stdout.trim(), "hello world");
-    }
-
-    #[test]
-    fn test_execute_embedded_and_extra_args() {
-        let args = vec!["world".to_string()];
-        let result = execute("echo hello", &args).unwrap();
-        assert_eq!(result.stdout.trim(), "hello world");
-    }
+mod tests;
 
-    #[test]
-    fn test_execute_failure() {
-        let result = execute("false", &[]).unwrap();
-        assert_ne!(result.exit_code, 0);
-    }
-
-    #[test]
-    fn test_execute_specific_exit_code() {
-        let result = execute_shell("exit 42", &[]).unwrap();
-        assert_eq!(result.exit_code, 42);
-    }
-
-    #[test]
-    fn test_execute_empty_command() {
-        let result = execute("", &[]);
-        assert!(result.is_err());
-    }
-
-    #[test]
-    fn test_execute_whitespace_only_command() {
-        let result = execute("   ", &[]);
-        assert!(result.is_err());
-    }
+#[cfg(all(test, unix))]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests_retry;
 
-    #[test]
-    fn test_execute_nonexistent_command() {
-        let result = execute("nonexistent_cmd_xyz", &[]);
-        let err = result.unwrap_err().to_string();
-        assert_eq!(err, "program not found: nonexistent_cmd_xyz");
-    }
-
-    #[test]
-    fn test_execute_args_with_special_characters() {
-        // execute() uses Command::new (no shell), so special chars are passed literally
-        let args = vec!["hello world".to_string()];
-        let result = execute("echo", &args).unwrap();
-        assert_eq!(result.stdout.trim(), "hello world");
-        assert_eq!(result.exit_code, 0);
-    }
-
-    // --- execute_shell tests ---
-
-    // --- expand_run_command: what gets recorded as the executed command ---
-
-    #[test]
-    fn expand_run_command_interpolates_args() {
-        // Args are shell-quoted, exactly as execute_shell hands them to `sh`.
-        // The recorded command is the literal shell input, quotes included.
-        let args = vec!["--all".to_string(), "HEAD".to_string()];
-        assert_eq!(
-            expand_run_command("git log --oneline {args}", &args),
-            "git log --oneline '--all' 'HEAD'"
-        );
-    }
-
-    #[test]
-    fn expand_run_command_escapes_args_like_the_shell_sees_them() {
-        let args = vec!["a b".to_string()];
-        assert_eq!(
-            expand_run_command("git log {args}", &args),
-            "git log 'a b'",
-            "the recorded command must be the one actually handed to the shell"
-        );
-    }
-
-    #[test]
-    fn expand_run_command_drops_args_when_template_has_no_placeholder() {
-        // Mirrors execute_shell: without {args} the user's arguments never reach
-        // the command. Recording them would misrepresent what ran.
-        let args = vec!["--json".to_string()];
-        assert_eq!(
-            expand_run_command("docker ps --format json", &args),
-            "docker ps --format json"
-        );
-    }
-
-    #[test]
-    fn expand_run_command_matches_what_execute_shell_runs() {
-        let args = vec!["hi there".to_string()];
-        let expanded = expand_run_command("echo {args}", &args);
-        let result = execute_shell("echo {args}", &args).unwrap();
-        // `expanded` is `echo 'hi there'`; running it must produce the same output.
-        assert_eq!(result.stdout.trim(), "hi there");
-        assert_eq!(
-            execute_shell(&expanded, &[]).unwrap().stdout.trim(),
-            "hi there"
-        );
-    }
-
-    #[test]
-    fn test_execute_shell_basic() {
-        let result = execute_shell("echo hello", &[]).unwrap();
-        assert_eq!(result.stdout.trim(), "hello");
-        assert_eq!(result.exit_code, 0);
-    }
-
-    #[test]
-    fn test_execute_shell_args_interpolation() {
-        let args = vec!["a".to_string(), "b".to_string()];
-        let result = execute_shell("echo {args}", &args).unwrap();
-        assert_eq!(result.stdout.trim(), "a b");
-    }
-
-    #[test]
-    fn test_execute_shell_args_empty() {
-        let result = execute_shell("echo {args} done", &[]).unwrap();
-        assert_eq!(result.stdout.trim(), "done");
-    }
-
-    #[test]
-    fn test_execute_shell_args_escaped() {
-        let args = vec!["hello world".to_string()];
-        let result = execute_shell("echo {args}", &args).unwrap();
-        assert_eq!(result.stdout.trim(), "hello world");
-    }
-
-    #[test]
-    fn test_execute_shell_args_with_semicolon() {
-        let args = vec!["; echo injected".to_string()];
-        let result = execute_shell("echo {args}", &args).unwrap();
-        let stdout = result.stdout.trim();
-        // The semicolon should be escaped and printed literally, not executed
-        assert!(stdout.contains("; echo injected"));
-        // "injected" should not appear as a separate execution
-        assert!(!stdout.contains("\ninjected"));
-    }
-
-    #[test]
-    fn test_execute_shell_args_with_single_quote() {
-        let args = vec!["it's quoted".to_string()];
-        let result = execute_shell("echo {args}", &args).unwrap();
-        assert_eq!(result.stdout.trim(), "it's quoted");
-    }
-
-    // --- build_result / combined field tests ---
-
-    #[test]
-    fn test_execute_stderr() {
-        let result = execute_shell("echo err >&2", &[]).unwrap();
-        assert!(result.stderr.contains("err"));
-        assert!(result.stdout.is_empty());
-        assert_eq!(result.combined, "err");
-    }
-
-    #[test]
-    fn test_combined_both_empty() {
-        let result = execute("true", &[]).unwrap();
-        assert!(result.stdout.is_empty());
-        assert!(result.stderr.is_empty());
-        assert_eq!(result.combined, "");
-    }
-
-    #[test]
-    fn test_combined_stdout_only() {
-        let result = execute("echo hello", &[]).unwrap();
-        assert_eq!(result.combined, "hello");
-    }
-
-    #[test]
-    fn test_combined_stderr_only() {
-        let result = execute_shell("echo err >&2", &[]).unwrap();
-        assert_eq!(result.combined, "err");
-    }
-
-    #[test]
-    fn test_combined_both_streams() {
-        let result = execute_shell("echo out && echo err >&2", &[]).unwrap();
-        // Both streams present in combined; exact order depends on scheduling
-        assert!(result.combined.contains("out"));
-        assert!(result.combined.contains("err"));
-    }
-
-    #[test]
-    fn test_combined_interleaving() {
-        // Verify that stderr lines appear interleaved with stdout, not appended
-        let result = execute_shell(
-            "echo out1 && echo err1 >&2 && echo out2 && echo err2 >&2",
-            &[],
-        )
-        .unwrap();
-        assert!(result.combined.contains("out1"));
-        assert!(result.combined.contains("out2"));
-        assert!(result.combined.contains("err1"));
-        assert!(result.combined.contains("err2"));
-        assert!(result.stdout.contains("out1"));
-        assert!(result.stdout.contains("out2"));
-        assert!(result.stderr.contains("err1"));
-        assert!(result.stderr.contains("err2"));
-    }
-
-    // --- resolve_program tests ---
-
-    #[test]
-    fn resolve_program_finds_sh() {
-        let result = resolve_program("sh");
-        assert!(result.is_some(), "sh should be on PATH");
-        assert!(result.unwrap().is_absolute());
-    }
-
-    #[test]
-    fn resolve_program_returns_none_for_missing() {
-        let result = resolve_program("nonexistent_program_xyz_abc_123");
-        assert!(result.is_none());
-    }
-
-    // --- execute_with_env tests ---
-
-    #[test]
-    fn test_execute_with_env_propagates_vars() {
-        let env = vec![("TOKF_TEST_VAR", "hello_from_env")];
-        let result =
-            execute_with_env("sh", &["-c".into(), "echo $TOKF_TEST_VAR".into()], &env).unwrap();
-        assert_eq!(result.stdout.trim(), "hello_from_env");
-    }
-
-    #[test]
-    fn test_execute_with_env_empty_env() {
-        let result = execute_with_env("echo", &["hi".into()], &[]).unwrap();
-        assert_eq!(result.stdout.trim(), "hi");
-    }
-
-    #[test]
-    fn test_execute_shell_with_env_propagates_vars() {
-        let env = vec![("TOKF_TEST_VAR2", "shell_env_val")];
-        let result = execute_shell_with_env("echo $TOKF_TEST_VAR2", &[], &env).unwrap();
-        assert_eq!(result.stdout.trim(), "shell_env_val");
-    }
-
-    // --- signal handling (unix only) ---
-
-    #[cfg(unix)]
-    #[test]
-    fn test_execute_signal_exit_code() {
-        // SIGTERM = 15, expected exit code = 128 + 15 = 143
-        let result = execute_shell("kill -TERM $$", &[]).unwrap();
-        assert_eq!(result.exit_code, 143);
-    }
-}
+#[cfg(test)]
+#[allow(
+    clippy::unwrap_used,
+    clippy::expect_used,
+    clippy::literal_string_with_formatting_args
+)]
+mod tests_shell;
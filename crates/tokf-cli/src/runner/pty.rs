@@ -0,0 +1,100 @@
+//! Pseudo-terminal capture for filters with `pty = true`.
+//!
+//! The child's stdout and stderr are attached to the slave side of a fresh
+//! PTY, so `isatty` is true and tools keep their interactive output format.
+//! Stdin is left untouched. A terminal has a single output stream, so the
+//! whole transcript is returned as `stdout`/`combined` and `stderr` is empty.
+
+use std::fs::File;
+use std::io::{ErrorKind, Read};
+use std::process::{Command, Stdio};
+use std::thread;
+use std::time::Duration;
+
+use rustix::fs::{Mode, OFlags};
+use rustix::io::Errno;
+use rustix::pty::{OpenptFlags, grantpt, openpt, ptsname, unlockpt};
+use rustix::termios::{self, OptionalActions, OutputModes, Winsize};
+
+use super::{CommandResult, Execution, exit_code, spawn_command, usage, wait};
+
+/// Window size used when tokf itself is not attached to a terminal.
+const DEFAULT_SIZE: Winsize = Winsize {
+    ws_row: 24,
+    ws_col: 80,
+    ws_xpixel: 0,
+    ws_ypixel: 0,
+};
+
+/// Spawn `cmd` attached to a new pseudo-terminal and capture its output.
+pub(super) fn run(
+    mut cmd: Command,
+    program: &str,
+    timeout: Option<Duration>,
+    started: &usage::Snapshot,
+) -> anyhow::Result<Execution> {
+    let master = openpt(OpenptFlags::RDWR | OpenptFlags::NOCTTY | OpenptFlags::CLOEXEC)?;
+    grantpt(&master)?;
+    unlockpt(&master)?;
+    let slave_path = ptsname(&master, Vec::new())?;
+    let slave = rustix::fs::open(
+        slave_path.as_c_str(),
+        OFlags::RDWR | OFlags::NOCTTY | OFlags::CLOEXEC,
+        Mode::empty(),
+    )?;
+
+    // Report the caller's terminal size so width-aware tools wrap the same way
+    // they would interactively.
+    let size = termios::tcgetwinsize(std::io::stderr())
+        .ok()
+        .filter(|s| s.ws_col > 0 && s.ws_row > 0)
+        .unwrap_or(DEFAULT_SIZE);
+    termios::tcsetwinsize(&master, size)?;
+    // Keep `\n` as-is instead of translating it to `\r\n`.
+    let mut attrs = termios::tcgetattr(&slave)?;
+    attrs.output_modes.remove(OutputModes::ONLCR);
+    termios::tcsetattr(&slave, OptionalActions::Now, &attrs)?;
+
+    cmd.stdout(Stdio::from(slave.try_clone()?))
+        .stderr(Stdio::from(slave));
+    // `spawn_command` consumes `cmd`, closing our copies of the slave so the
+    // reader ends once the child (and anything it spawned) exits.
+    let mut child = spawn_command(cmd, program)?;
+    let reader = thread::spawn(move || read_to_end(File::from(master)));
+    let waited = wait::wait(&mut child, timeout)?;
+    let output = reader
+        .join()
+        .map_err(|_| anyhow::anyhow!("pty reader thread panicked"))??;
+
+    let text = String::from_utf8_lossy(&output);
+    let combined = text.lines().collect::<Vec<_>>().join("\n");
+    Ok(Execution {
+        result: CommandResult {
+            stdout: combined.clone(),
+            stderr: String::new(),
+            exit_code: exit_code(&waited),
+            combined,
+        },
+        timed_out: timeout.filter(|_| waited.timed_out),
+        interrupted: waited.interrupted,
+        usage: started.finish(),
+        spilled: None,
+        attempts: 1,
+    })
+}
+
+/// Read the master side until every slave fd is closed.
+fn read_to_end(mut master: File) -> std::io::Result<Vec<u8>> {
+    let mut output = Vec::new();
+    let mut buf = [0u8; 8192];
+    loop {
+        match master.read(&mut buf) {
+            Ok(0) => return Ok(output),
+            Ok(n) => output.extend_from_slice(&buf[..n]),
+            Err(e) if e.kind() == ErrorKind::Interrupted => {}
+            // Linux reports EIO on the master once every slave fd is closed.
+            Err(e) if e.raw_os_error() == Some(Errno::IO.raw_os_error()) => return Ok(output),
+            Err(e) => return Err(e),
+        }
+    }
+}
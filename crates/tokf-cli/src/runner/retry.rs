@@ -0,0 +1,73 @@
+//! Re-running a flaky command under a filter's `retry` policy.
+
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::config::types::RetryConfig;
+
+use super::{Execution, INTERRUPTED_EXIT_CODE, signals};
+
+/// Longest single sleep while backing off, so a signal ends the wait promptly.
+const BACKOFF_SLICE: Duration = Duration::from_millis(50);
+
+/// Run `execute` until it succeeds, exits with a code `policy` does not retry,
+/// is interrupted or timed out, or runs out of attempts. Returns the last attempt.
+///
+/// tokf's SIGINT/SIGTERM handlers keep it alive once a command has run, so a
+/// signal between attempts would otherwise go unnoticed: one that arrives
+/// before or during a backoff stops the retries, and the last attempt is
+/// returned as interrupted with [`INTERRUPTED_EXIT_CODE`].
+///
+/// # Errors
+///
+/// Returns the first error from `execute` (e.g. the program was not found);
+/// spawn failures are not retried.
+pub fn with_retry(
+    policy: Option<&RetryConfig>,
+    verbose: bool,
+    mut execute: impl FnMut() -> anyhow::Result<Execution>,
+) -> anyhow::Result<Execution> {
+    let attempts = policy.map_or(1, |p| p.attempts.max(1));
+    let mut backoff = Duration::from_millis(policy.map_or(0, |p| p.backoff_ms));
+    let mut attempt = 1;
+    let signals = signals::received();
+    loop {
+        let mut execution = execute()?;
+        execution.attempts = attempt;
+        let exit_code = execution.result.exit_code;
+        let retry = policy.is_some_and(|p| p.retries(exit_code));
+        let stopped = execution.interrupted || execution.timed_out.is_some();
+        if !retry || stopped || attempt >= attempts {
+            return Ok(execution);
+        }
+        if verbose {
+            eprintln!(
+                "[tokf] attempt {attempt}/{attempts} exited with {exit_code}, retrying in {}ms",
+                backoff.as_millis()
+            );
+        }
+        if !sleep_unless_signalled(backoff, signals) {
+            execution.interrupted = true;
+            execution.result.exit_code = INTERRUPTED_EXIT_CODE;
+            return Ok(execution);
+        }
+        backoff *= 2;
+        attempt += 1;
+    }
+}
+
+/// Sleep for `duration`, returning `false` as soon as the count of signals
+/// received is no longer `signals`.
+fn sleep_unless_signalled(duration: Duration, signals: usize) -> bool {
+    let deadline = Instant::now() + duration;
+    loop {
+        if signals::received() != signals {
+            return false;
+        }
+        let left = deadline.saturating_duration_since(Instant::now());
+        if left.is_zero() {
+            return true;
+        }
+        thread::sleep(left.min(BACKOFF_SLICE));
+    }
+}
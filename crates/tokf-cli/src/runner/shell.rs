@@ -0,0 +1,164 @@
+//! The shell that runs a filter's `run` command and shell-mode (`tokf -c`)
+//! lines.
+//!
+//! Each [`ShellKind`] has its own invocation flags and quoting rules, so the
+//! user's arguments interpolated into `{args}` stay single words whichever
+//! shell parses the line.
+
+use std::process::Command;
+
+/// Syntax family of a shell: how it is invoked and how arguments are quoted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ShellKind {
+    /// `sh`, `bash`, `zsh`: `-c`, single quotes with `'\''` for a quote.
+    Posix,
+    /// `fish`: `-c`, single quotes with `\'` and `\\` escapes.
+    Fish,
+    /// `pwsh`, `powershell`: `-Command`, single quotes with `''` for a quote.
+    PowerShell,
+    /// `cmd`: `/d /s /c`, double quotes with `""` for a quote.
+    Cmd,
+}
+
+/// A shell to run command lines with.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Shell {
+    pub kind: ShellKind,
+    /// Program to spawn: a name looked up on `PATH`, or a path.
+    pub program: String,
+}
+
+impl Default for Shell {
+    /// The system shell: `sh`, or `powershell.exe` on Windows.
+    fn default() -> Self {
+        if cfg!(windows) {
+            Self {
+                kind: ShellKind::PowerShell,
+                program: "powershell.exe".to_string(),
+            }
+        } else {
+            Self {
+                kind: ShellKind::Posix,
+                program: "sh".to_string(),
+            }
+        }
+    }
+}
+
+impl Shell {
+    /// The shell called `name` (`sh`, `bash`, `zsh`, `fish`, `pwsh`,
+    /// `powershell` or `cmd`), spawned from `path` when given and looked up
+    /// on `PATH` by name otherwise. `None` for an unknown name.
+    #[must_use]
+    pub fn named(name: &str, path: Option<String>) -> Option<Self> {
+        let kind = match name {
+            "sh" | "bash" | "zsh" => ShellKind::Posix,
+            "fish" => ShellKind::Fish,
+            "pwsh" | "powershell" => ShellKind::PowerShell,
+            "cmd" => ShellKind::Cmd,
+            _ => return None,
+        };
+        Some(Self {
+            kind,
+            program: path.unwrap_or_else(|| name.to_string()),
+        })
+    }
+
+    /// Quote `arg` so this shell passes it on as one word, unexpanded.
+    #[must_use]
+    pub fn quote(&self, arg: &str) -> String {
+        match self.kind {
+            ShellKind::Posix => format!("'{}'", arg.replace('\'', "'\\''")),
+            ShellKind::Fish => format!("'{}'", arg.replace('\\', "\\\\").replace('\'', "\\'")),
+            ShellKind::PowerShell => format!("'{}'", arg.replace('\'', "''")),
+            ShellKind::Cmd => quote_cmd(arg),
+        }
+    }
+
+    /// Quote each argument and join them with spaces.
+    #[must_use]
+    pub fn quote_all(&self, args: &[String]) -> String {
+        args.iter()
+            .map(|a| self.quote(a))
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    /// A command that runs `script` in this shell.
+    #[must_use]
+    pub fn command(&self, script: &str) -> Command {
+        let mut cmd = Command::new(&self.program);
+        match self.kind {
+            ShellKind::Posix | ShellKind::Fish => {
+                cmd.arg("-c").arg(script);
+            }
+            ShellKind::PowerShell => {
+                cmd.args(["-NoLogo", "-NoProfile", "-NonInteractive", "-Command"])
+                    .arg(script);
+            }
+            ShellKind::Cmd => {
+                cmd.args(["/d", "/s", "/c"]);
+                cmd_script_arg(&mut cmd, script);
+            }
+        }
+        cmd
+    }
+
+    /// Like [`Self::command`], for a line that came with POSIX shell flags
+    /// (`-c`, `-ec`, `-cu`, …). POSIX shells get the flags as they are; the
+    /// others only understand `-c`, so the rest is dropped.
+    #[must_use]
+    pub fn command_with_flags(&self, flags: &str, script: &str) -> Command {
+        if self.kind == ShellKind::Posix {
+            let mut cmd = Command::new(&self.program);
+            cmd.arg(flags).arg(script);
+            cmd
+        } else {
+            self.command(script)
+        }
+    }
+}
+
+/// Quote for `cmd`, in a form that both `cmd` and the program's own argument
+/// parser (the Microsoft C runtime rules) read back as one word: `""` inside
+/// double quotes is a literal quote, backslashes before a quote are doubled,
+/// and `%` is caret-escaped outside the quotes so it cannot expand a variable.
+fn quote_cmd(arg: &str) -> String {
+    let mut out = String::from("\"");
+    let mut backslashes = 0;
+    for c in arg.chars() {
+        match c {
+            '\\' => {
+                backslashes += 1;
+                out.push('\\');
+                continue;
+            }
+            '"' => {
+                out.push_str(&"\\".repeat(backslashes));
+                out.push_str("\"\"");
+            }
+            '%' => {
+                out.push_str(&"\\".repeat(backslashes));
+                out.push_str("\"^%\"");
+            }
+            _ => out.push(c),
+        }
+        backslashes = 0;
+    }
+    out.push_str(&"\\".repeat(backslashes));
+    out.push('"');
+    out
+}
+
+/// `cmd /s /c` strips the outer quotes of its command line itself, so the
+/// script goes in verbatim rather than with the standard library's quoting.
+#[cfg(windows)]
+fn cmd_script_arg(cmd: &mut Command, script: &str) {
+    use std::os::windows::process::CommandExt;
+    cmd.raw_arg(format!("\"{script}\""));
+}
+
+#[cfg(not(windows))]
+fn cmd_script_arg(cmd: &mut Command, script: &str) {
+    cmd.arg(script);
+}
@@ -0,0 +1,108 @@
+//! Forwarding SIGINT/SIGTERM to the running command.
+//!
+//! While a command runs, tokf's handlers do not exit: they record the signal
+//! and pass it on, so [`super::wait`] can still collect and filter the output
+//! captured so far. A command isolated in its own process group (see
+//! [`super::wait::isolate`]) gets every signal sent to its group. Otherwise it
+//! shares tokf's group: a Ctrl-C from the terminal already reached it, so only
+//! SIGTERM is forwarded, to the command itself.
+
+#[cfg(unix)]
+mod imp {
+    use std::process::Child;
+    use std::sync::Once;
+    use std::sync::atomic::{AtomicI32, AtomicUsize, Ordering};
+
+    use rustix::process::{Pid, Signal, kill_process, kill_process_group};
+
+    /// The running command's pid, negated when it leads its own process
+    /// group; 0 when no command is running.
+    static TARGET: AtomicI32 = AtomicI32::new(0);
+    /// Signals received since tokf started.
+    static RECEIVED: AtomicUsize = AtomicUsize::new(0);
+
+    fn install() {
+        static ONCE: Once = Once::new();
+        ONCE.call_once(|| {
+            for signal in [Signal::INT, Signal::TERM] {
+                // SAFETY: `forward` only touches atomics and calls kill(2),
+                // which are async-signal-safe.
+                let _ = unsafe {
+                    signal_hook_registry::register(signal.as_raw(), move || forward(signal))
+                };
+            }
+        });
+    }
+
+    fn forward(signal: Signal) {
+        RECEIVED.fetch_add(1, Ordering::SeqCst);
+        let target = TARGET.load(Ordering::SeqCst);
+        if target < 0 {
+            if let Some(group) = Pid::from_raw(-target) {
+                let _ = kill_process_group(group, signal);
+            }
+        } else if signal != Signal::INT
+            && let Some(pid) = Pid::from_raw(target)
+        {
+            let _ = kill_process(pid, signal);
+        }
+    }
+
+    /// Forwards signals to a command until dropped.
+    pub struct Forwarding {
+        received_before: usize,
+    }
+
+    impl Forwarding {
+        pub fn start(child: &Child, isolated: bool) -> Self {
+            install();
+            let pid = i32::try_from(child.id()).unwrap_or(0);
+            TARGET.store(if isolated { -pid } else { pid }, Ordering::SeqCst);
+            Self {
+                received_before: RECEIVED.load(Ordering::SeqCst),
+            }
+        }
+
+        /// Signals received since [`Self::start`].
+        pub fn received(&self) -> usize {
+            RECEIVED.load(Ordering::SeqCst) - self.received_before
+        }
+    }
+
+    /// Signals received since tokf started. Counts only once a command has
+    /// run: until then SIGINT and SIGTERM keep their default action.
+    pub fn received() -> usize {
+        RECEIVED.load(Ordering::SeqCst)
+    }
+
+    impl Drop for Forwarding {
+        fn drop(&mut self) {
+            TARGET.store(0, Ordering::SeqCst);
+        }
+    }
+}
+
+#[cfg(not(unix))]
+mod imp {
+    use std::process::Child;
+
+    /// Signals are not forwarded on this platform.
+    pub struct Forwarding;
+
+    impl Forwarding {
+        pub const fn start(_child: &Child, _isolated: bool) -> Self {
+            Self
+        }
+
+        pub const fn received(&self) -> usize {
+            0
+        }
+    }
+
+    pub const fn received() -> usize {
+        0
+    }
+}
+
+pub(super) use imp::Forwarding;
+pub use imp::received;
@@ -0,0 +1,163 @@
+//! Bounded capture: spilling very large output to disk.
+//!
+//! Output is collected in memory until it passes
+//! [`ExecOptions::spill_threshold`](super::ExecOptions) bytes. From then on the
+//! combined output goes to an anonymous temporary file instead, and memory
+//! holds only the first lines (up to the threshold) as
+//! [`CommandResult::combined`](super::CommandResult). The per-channel
+//! `stdout`/`stderr` copies are dropped, as keeping them would double the
+//! footprint; only the channel each line came from is kept, one byte a line.
+//! Callers read the full output back with [`SpilledOutput::lines`] or, to
+//! know each line's channel, [`SpilledOutput::lines_by_stream`].
+
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, BufWriter, Seek, SeekFrom, Write};
+
+use crate::config::types::Stream;
+
+use super::{CommandResult, Source, join_lines};
+
+/// The full combined output of a command whose output was spilled to disk.
+/// The file is deleted when this value is dropped.
+#[derive(Debug)]
+pub struct SpilledOutput {
+    file: File,
+    /// The channel each line arrived on, in order.
+    sources: Vec<Source>,
+    /// Size of the full output, counting one `\n` per line.
+    pub bytes: usize,
+}
+
+impl SpilledOutput {
+    /// The full combined output, line by line.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the temporary file cannot be reopened.
+    pub fn lines(&self) -> io::Result<impl Iterator<Item = io::Result<String>> + use<>> {
+        let mut file = self.file.try_clone()?;
+        file.seek(SeekFrom::Start(0))?;
+        Ok(BufReader::new(file).lines())
+    }
+
+    /// The full combined output, line by line, with the channel of each.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the temporary file cannot be reopened.
+    pub fn lines_by_stream(
+        &self,
+    ) -> io::Result<impl Iterator<Item = io::Result<(Stream, String)>> + use<'_>> {
+        let streams = self.sources.iter().map(|source| match source {
+            Source::Stdout => Stream::Stdout,
+            Source::Stderr => Stream::Stderr,
+        });
+        Ok(streams
+            .zip(self.lines()?)
+            .map(|(stream, line)| line.map(|line| (stream, line))))
+    }
+
+    /// Copy the full combined output to `out`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if reading the temporary file or writing fails.
+    pub fn copy_to(&self, out: &mut impl Write) -> io::Result<u64> {
+        let mut file = self.file.try_clone()?;
+        file.seek(SeekFrom::Start(0))?;
+        io::copy(&mut file, out)
+    }
+}
+
+/// Collects a command's lines, spilling past the threshold.
+pub(super) struct Collector {
+    threshold: Option<usize>,
+    stdout: Vec<String>,
+    stderr: Vec<String>,
+    combined: Vec<String>,
+    /// The channel of each line in `combined` and the spill file.
+    sources: Vec<Source>,
+    bytes: usize,
+    spill: Option<BufWriter<File>>,
+    /// Writing to the spill file failed; later lines are dropped.
+    spill_failed: bool,
+}
+
+impl Collector {
+    pub const fn new(threshold: Option<usize>) -> Self {
+        Self {
+            threshold,
+            stdout: Vec::new(),
+            stderr: Vec::new(),
+            combined: Vec::new(),
+            sources: Vec::new(),
+            bytes: 0,
+            spill: None,
+            spill_failed: false,
+        }
+    }
+
+    pub fn push(&mut self, source: Source, line: String) {
+        self.bytes += line.len() + 1;
+        if self.spill.is_none() && self.threshold.is_some_and(|t| self.bytes > t) {
+            self.start_spill();
+        }
+        if let Some(spill) = &mut self.spill {
+            if !self.spill_failed && writeln!(spill, "{line}").is_err() {
+                eprintln!("[tokf] could not write spilled output; the rest is dropped");
+                self.spill_failed = true;
+            }
+            if !self.spill_failed {
+                self.sources.push(source);
+            }
+            return;
+        }
+        self.sources.push(source);
+        self.combined.push(line.clone());
+        match source {
+            Source::Stdout => self.stdout.push(line),
+            Source::Stderr => self.stderr.push(line),
+        }
+    }
+
+    /// Move what was collected so far into a new temporary file. On failure,
+    /// keep collecting in memory.
+    fn start_spill(&mut self) {
+        let spill = tempfile::tempfile().and_then(|file| {
+            let mut spill = BufWriter::new(file);
+            for line in &self.combined {
+                writeln!(spill, "{line}")?;
+            }
+            Ok(spill)
+        });
+        match spill {
+            Ok(spill) => {
+                self.spill = Some(spill);
+                self.stdout = Vec::new();
+                self.stderr = Vec::new();
+            }
+            Err(e) => {
+                eprintln!("[tokf] could not spill output to disk, keeping it in memory: {e}");
+                self.threshold = None;
+            }
+        }
+    }
+
+    pub fn finish(self, exit_code: i32) -> io::Result<(CommandResult, Option<SpilledOutput>)> {
+        let spilled = match self.spill {
+            Some(spill) => Some(SpilledOutput {
+                file: spill.into_inner().map_err(io::IntoInnerError::into_error)?,
+                sources: self.sources,
+                bytes: self.bytes,
+            }),
+            None => None,
+        };
+        let result = CommandResult {
+            stdout: join_lines(&self.stdout),
+            stderr: join_lines(&self.stderr),
+            exit_code,
+            combined: self.combined.join("\n"),
+        };
+        Ok((result, spilled))
+    }
+}
@@ -0,0 +1,535 @@
+use super::*;
+
+// --- execute tests ---
+
+#[test]
+fn test_execute_echo() {
+    let result = execute("echo hello", &[]).unwrap();
+    assert_eq!(result.stdout.trim(), "hello");
+    assert_eq!(result.exit_code, 0);
+    assert!(result.stderr.is_empty());
+}
+
+#[test]
+fn test_execute_with_args() {
+    let args = vec!["hello".to_string(), "world".to_string()];
+    let result = execute("echo", &args).unwrap();
+    assert_eq!(result.stdout.trim(), "hello world");
+}
+
+#[test]
+fn test_execute_embedded_and_extra_args() {
+    let args = vec!["world".to_string()];
+    let result = execute("echo hello", &args).unwrap();
+    assert_eq!(result.stdout.trim(), "hello world");
+}
+
+#[test]
+fn test_execute_failure() {
+    let result = execute("false", &[]).unwrap();
+    assert_ne!(result.exit_code, 0);
+}
+
+#[test]
+fn test_execute_specific_exit_code() {
+    let result = execute_shell("exit 42", &[]).unwrap();
+    assert_eq!(result.exit_code, 42);
+}
+
+#[test]
+fn test_execute_empty_command() {
+    let result = execute("", &[]);
+    assert!(result.is_err());
+}
+
+#[test]
+fn test_execute_whitespace_only_command() {
+    let result = execute("   ", &[]);
+    assert!(result.is_err());
+}
+
+#[test]
+fn test_execute_nonexistent_command() {
+    let result = execute("nonexistent_cmd_xyz", &[]);
+    let err = result.unwrap_err().to_string();
+    assert_eq!(err, "program not found: nonexistent_cmd_xyz");
+}
+
+#[test]
+fn test_execute_args_with_special_characters() {
+    // execute() uses Command::new (no shell), so special chars are passed literally
+    let args = vec!["hello world".to_string()];
+    let result = execute("echo", &args).unwrap();
+    assert_eq!(result.stdout.trim(), "hello world");
+    assert_eq!(result.exit_code, 0);
+}
+
+// --- execute_shell tests ---
+
+// --- expand_run_command: what gets recorded as the executed command ---
+
+#[test]
+fn expand_run_command_interpolates_args() {
+    // Args are shell-quoted, exactly as execute_shell hands them to `sh`.
+    // The recorded command is the literal shell input, quotes included.
+    let args = vec!["--all".to_string(), "HEAD".to_string()];
+    assert_eq!(
+        expand_run_command(&Shell::default(), "git log --oneline {args}", &args),
+        "git log --oneline '--all' 'HEAD'"
+    );
+}
+
+#[test]
+fn expand_run_command_escapes_args_like_the_shell_sees_them() {
+    let args = vec!["a b".to_string()];
+    assert_eq!(
+        expand_run_command(&Shell::default(), "git log {args}", &args),
+        "git log 'a b'",
+        "the recorded command must be the one actually handed to the shell"
+    );
+}
+
+#[test]
+fn expand_run_command_drops_args_when_template_has_no_placeholder() {
+    // Mirrors execute_shell: without {args} the user's arguments never reach
+    // the command. Recording them would misrepresent what ran.
+    let args = vec!["--json".to_string()];
+    assert_eq!(
+        expand_run_command(&Shell::default(), "docker ps --format json", &args),
+        "docker ps --format json"
+    );
+}
+
+#[test]
+fn expand_run_command_matches_what_execute_shell_runs() {
+    let args = vec!["hi there".to_string()];
+    let expanded = expand_run_command(&Shell::default(), "echo {args}", &args);
+    let result = execute_shell("echo {args}", &args).unwrap();
+    // `expanded` is `echo 'hi there'`; running it must produce the same output.
+    assert_eq!(result.stdout.trim(), "hi there");
+    assert_eq!(
+        execute_shell(&expanded, &[]).unwrap().stdout.trim(),
+        "hi there"
+    );
+}
+
+#[test]
+fn test_execute_shell_basic() {
+    let result = execute_shell("echo hello", &[]).unwrap();
+    assert_eq!(result.stdout.trim(), "hello");
+    assert_eq!(result.exit_code, 0);
+}
+
+#[test]
+fn test_execute_shell_args_interpolation() {
+    let args = vec!["a".to_string(), "b".to_string()];
+    let result = execute_shell("echo {args}", &args).unwrap();
+    assert_eq!(result.stdout.trim(), "a b");
+}
+
+#[test]
+fn test_execute_shell_args_empty() {
+    let result = execute_shell("echo {args} done", &[]).unwrap();
+    assert_eq!(result.stdout.trim(), "done");
+}
+
+#[test]
+fn test_execute_shell_args_escaped() {
+    let args = vec!["hello world".to_string()];
+    let result = execute_shell("echo {args}", &args).unwrap();
+    assert_eq!(result.stdout.trim(), "hello world");
+}
+
+#[test]
+fn test_execute_shell_args_with_semicolon() {
+    let args = vec!["; echo injected".to_string()];
+    let result = execute_shell("echo {args}", &args).unwrap();
+    let stdout = result.stdout.trim();
+    // The semicolon should be escaped and printed literally, not executed
+    assert!(stdout.contains("; echo injected"));
+    // "injected" should not appear as a separate execution
+    assert!(!stdout.contains("\ninjected"));
+}
+
+#[test]
+fn test_execute_shell_args_with_single_quote() {
+    let args = vec!["it's quoted".to_string()];
+    let result = execute_shell("echo {args}", &args).unwrap();
+    assert_eq!(result.stdout.trim(), "it's quoted");
+}
+
+// --- build_result / combined field tests ---
+
+#[test]
+fn test_execute_stderr() {
+    let result = execute_shell("echo err >&2", &[]).unwrap();
+    assert!(result.stderr.contains("err"));
+    assert!(result.stdout.is_empty());
+    assert_eq!(result.combined, "err");
+}
+
+#[test]
+fn test_combined_both_empty() {
+    let result = execute("true", &[]).unwrap();
+    assert!(result.stdout.is_empty());
+    assert!(result.stderr.is_empty());
+    assert_eq!(result.combined, "");
+}
+
+#[test]
+fn test_combined_stdout_only() {
+    let result = execute("echo hello", &[]).unwrap();
+    assert_eq!(result.combined, "hello");
+}
+
+#[test]
+fn test_combined_stderr_only() {
+    let result = execute_shell("echo err >&2", &[]).unwrap();
+    assert_eq!(result.combined, "err");
+}
+
+#[test]
+fn test_combined_both_streams() {
+    let result = execute_shell("echo out && echo err >&2", &[]).unwrap();
+    // Both streams present in combined; exact order depends on scheduling
+    assert!(result.combined.contains("out"));
+    assert!(result.combined.contains("err"));
+}
+
+#[test]
+fn test_combined_interleaving() {
+    // Verify that stderr lines appear interleaved with stdout, not appended
+    let result = execute_shell(
+        "echo out1 && echo err1 >&2 && echo out2 && echo err2 >&2",
+        &[],
+    )
+    .unwrap();
+    assert!(result.combined.contains("out1"));
+    assert!(result.combined.contains("out2"));
+    assert!(result.combined.contains("err1"));
+    assert!(result.combined.contains("err2"));
+    assert!(result.stdout.contains("out1"));
+    assert!(result.stdout.contains("out2"));
+    assert!(result.stderr.contains("err1"));
+    assert!(result.stderr.contains("err2"));
+}
+
+// --- resolve_program tests ---
+
+#[test]
+fn resolve_program_finds_sh() {
+    let result = resolve_program("sh");
+    assert!(result.is_some(), "sh should be on PATH");
+    assert!(result.unwrap().is_absolute());
+}
+
+#[test]
+fn resolve_program_returns_none_for_missing() {
+    let result = resolve_program("nonexistent_program_xyz_abc_123");
+    assert!(result.is_none());
+}
+
+// --- execute_with_env tests ---
+
+#[test]
+fn test_execute_with_env_propagates_vars() {
+    let env = vec![("TOKF_TEST_VAR", "hello_from_env")];
+    let args = ["-c".into(), "echo $TOKF_TEST_VAR".into()];
+    let run = execute_with_env("sh", &args, &env, ExecOptions::default()).unwrap();
+    assert_eq!(run.result.stdout.trim(), "hello_from_env");
+    assert_eq!(run.timed_out, None);
+}
+
+#[test]
+fn test_execute_with_env_empty_env() {
+    let run = execute_with_env("echo", &["hi".into()], &[], ExecOptions::default()).unwrap();
+    assert_eq!(run.result.stdout.trim(), "hi");
+}
+
+#[test]
+fn test_execute_shell_with_env_propagates_vars() {
+    let env = vec![("TOKF_TEST_VAR2", "shell_env_val")];
+    let run =
+        execute_shell_with_env("echo $TOKF_TEST_VAR2", &[], &env, ExecOptions::default()).unwrap();
+    assert_eq!(run.result.stdout.trim(), "shell_env_val");
+}
+
+// --- signal handling (unix only) ---
+
+#[cfg(unix)]
+#[test]
+fn test_execute_signal_exit_code() {
+    // SIGTERM = 15, expected exit code = 128 + 15 = 143
+    let result = execute_shell("kill -TERM $$", &[]).unwrap();
+    assert_eq!(result.exit_code, 143);
+}
+
+// --- pty mode (unix only) ---
+
+#[cfg(unix)]
+const PTY: ExecOptions = ExecOptions {
+    pty: true,
+    timeout: None,
+    cwd: None,
+    stdin: StdinMode::Inherit,
+    spill_threshold: None,
+    shell: None,
+};
+
+#[cfg(unix)]
+#[test]
+fn test_pty_stdout_and_stderr_are_terminals() {
+    let probe = "test -t 1 && echo out-tty; test -t 2 && echo err-tty >&2";
+    let result = execute_shell_with_env(probe, &[], &[], PTY).unwrap().result;
+    assert_eq!(result.stdout, "out-tty\nerr-tty");
+    assert_eq!(result.combined, result.stdout);
+    assert!(result.stderr.is_empty());
+}
+
+#[cfg(unix)]
+#[test]
+fn test_pipes_are_not_terminals() {
+    let result = execute_shell("test -t 1 && echo out-tty || echo piped", &[]).unwrap();
+    assert_eq!(result.stdout, "piped");
+}
+
+#[cfg(unix)]
+#[test]
+fn test_pty_preserves_exit_code_and_newlines() {
+    let args = ["-c".into(), "printf 'a\\nb\\n'; exit 3".into()];
+    let result = execute_with_env("sh", &args, &[], PTY).unwrap().result;
+    assert_eq!(result.stdout, "a\nb");
+    assert_eq!(result.exit_code, 3);
+}
+
+// --- timeouts ---
+
+#[test]
+fn test_isolated_commands_never_inherit_a_terminal() {
+    use super::wait::detaches_stdin;
+
+    assert!(detaches_stdin(StdinMode::Inherit, true));
+    assert!(
+        !detaches_stdin(StdinMode::Inherit, false),
+        "pipes still flow"
+    );
+    assert!(!detaches_stdin(StdinMode::Null, true));
+    assert!(!detaches_stdin(StdinMode::Closed, true));
+}
+
+fn with_timeout(pty: bool, millis: u64) -> ExecOptions<'static> {
+    ExecOptions {
+        pty,
+        timeout: Some(Duration::from_millis(millis)),
+        cwd: None,
+        stdin: StdinMode::Inherit,
+        spill_threshold: None,
+        shell: None,
+    }
+}
+
+#[cfg(unix)]
+#[test]
+fn test_timeout_kills_and_keeps_partial_output() {
+    let start = std::time::Instant::now();
+    let run = execute_shell_with_env(
+        "echo before; sleep 30; echo after",
+        &[],
+        &[],
+        with_timeout(false, 300),
+    )
+    .unwrap();
+    assert!(
+        start.elapsed() < Duration::from_secs(10),
+        "command was not killed"
+    );
+    assert_eq!(run.timed_out, Some(Duration::from_millis(300)));
+    assert_eq!(run.result.exit_code, TIMEOUT_EXIT_CODE);
+    assert_eq!(run.result.stdout, "before");
+}
+
+#[cfg(unix)]
+#[test]
+fn test_timeout_kills_the_process_tree() {
+    // The backgrounded `sleep` inherits stdout: unless it is killed too, the
+    // pipe stays open and reading blocks until it exits.
+    let start = std::time::Instant::now();
+    let run = execute_shell_with_env(
+        "sleep 30 & echo started; wait",
+        &[],
+        &[],
+        with_timeout(false, 300),
+    )
+    .unwrap();
+    assert!(
+        start.elapsed() < Duration::from_secs(10),
+        "sleep outlived the timeout"
+    );
+    assert_eq!(run.result.stdout, "started");
+}
+
+#[cfg(unix)]
+#[test]
+fn test_timeout_in_pty_mode() {
+    let run =
+        execute_shell_with_env("echo before; sleep 30", &[], &[], with_timeout(true, 300)).unwrap();
+    assert!(run.timed_out.is_some());
+    assert_eq!(run.result.stdout, "before");
+}
+
+#[test]
+fn test_fast_command_is_not_timed_out() {
+    let run = execute_with_env("echo", &["hi".into()], &[], with_timeout(false, 30_000)).unwrap();
+    assert_eq!(run.timed_out, None);
+    assert_eq!(run.result.exit_code, 0);
+    assert_eq!(run.result.stdout.trim(), "hi");
+}
+
+// --- working directory ---
+
+#[cfg(unix)]
+#[test]
+fn test_cwd_sets_working_directory() {
+    let dir = tempfile::TempDir::new().unwrap();
+    let opts = ExecOptions {
+        cwd: Some(dir.path()),
+        ..ExecOptions::default()
+    };
+    let run = execute_shell_with_env("pwd", &[], &[], opts).unwrap();
+    let reported = std::fs::canonicalize(run.result.stdout.trim()).unwrap();
+    assert_eq!(reported, std::fs::canonicalize(dir.path()).unwrap());
+}
+
+// --- stdin ---
+
+#[cfg(unix)]
+fn run_with_stdin(stdin: StdinMode) -> String {
+    let opts = ExecOptions {
+        stdin,
+        ..ExecOptions::default()
+    };
+    execute_shell_with_env("cat >/dev/null 2>&1; echo rc=$?", &[], &[], opts)
+        .unwrap()
+        .result
+        .stdout
+}
+
+#[cfg(unix)]
+#[test]
+fn test_stdin_null_reads_eof() {
+    assert_eq!(run_with_stdin(StdinMode::Null), "rc=0");
+}
+
+#[cfg(unix)]
+#[test]
+fn test_stdin_closed_fails_reads() {
+    assert_eq!(run_with_stdin(StdinMode::Closed), "rc=1");
+}
+
+// --- resource usage ---
+
+#[test]
+fn test_usage_measures_wall_clock_time() {
+    #[cfg(unix)]
+    let cmd = "sleep 0.2";
+    #[cfg(windows)]
+    let cmd = "Start-Sleep -Milliseconds 200";
+    let run = execute_shell_with_env(cmd, &[], &[], ExecOptions::default()).unwrap();
+    assert!(run.usage.duration >= Duration::from_millis(200));
+}
+
+#[cfg(unix)]
+#[test]
+fn test_usage_reports_cpu_and_memory() {
+    let run = execute_shell_with_env("true", &[], &[], ExecOptions::default()).unwrap();
+    assert!(run.usage.user_cpu.is_some());
+    assert!(run.usage.sys_cpu.is_some());
+    assert!(run.usage.max_rss_kb.is_some_and(|kb| kb > 0));
+}
+
+#[test]
+fn test_usage_template_vars() {
+    let usage = ResourceUsage {
+        duration: Duration::from_millis(12_345),
+        user_cpu: Some(Duration::from_millis(800)),
+        sys_cpu: None,
+        max_rss_kb: Some(524_288),
+    };
+    let vars = usage.template_vars();
+    assert_eq!(vars["duration"], "12.3s");
+    assert_eq!(vars["user_time"], "0.8s");
+    assert!(!vars.contains_key("sys_time"));
+    assert_eq!(vars["max_rss"], "512.0 MB");
+}
+
+// --- spilling ---
+
+#[cfg(unix)]
+fn run_with_spill(cmd: &str, spill_threshold: Option<usize>) -> Execution {
+    let opts = ExecOptions {
+        spill_threshold,
+        ..ExecOptions::default()
+    };
+    execute_shell_with_env(cmd, &[], &[], opts).unwrap()
+}
+
+#[cfg(unix)]
+#[test]
+fn test_spill_keeps_full_output_on_disk() {
+    let run = run_with_spill("seq 1 1000", Some(100));
+    let spilled = run.spilled.expect("output should spill");
+    let lines: Vec<String> = spilled.lines().unwrap().map(Result::unwrap).collect();
+    assert_eq!(lines.len(), 1000);
+    assert_eq!(lines[999], "1000");
+    assert_eq!(
+        spilled.bytes,
+        lines.iter().map(|l| l.len() + 1).sum::<usize>()
+    );
+    // Memory holds only the head, without per-channel copies.
+    assert!(run.result.combined.len() <= 100);
+    assert!(run.result.combined.starts_with("1\n2\n"));
+    assert!(run.result.stdout.is_empty());
+}
+
+#[cfg(unix)]
+#[test]
+fn test_spill_copy_to_writes_every_line() {
+    let run = run_with_spill("seq 1 50; seq 1 50 >&2", Some(10));
+    let mut out = Vec::new();
+    run.spilled.unwrap().copy_to(&mut out).unwrap();
+    assert_eq!(String::from_utf8(out).unwrap().lines().count(), 100);
+}
+
+#[cfg(unix)]
+#[test]
+fn test_spill_keeps_each_lines_channel() {
+    use crate::config::types::Stream;
+
+    let run = run_with_spill("seq 1 50; echo oops >&2; echo done", Some(10));
+    let lines: Vec<(Stream, String)> = run
+        .spilled
+        .unwrap()
+        .lines_by_stream()
+        .unwrap()
+        .map(Result::unwrap)
+        .collect();
+    assert_eq!(lines.len(), 52);
+    // The two channels are read concurrently, so only check each line's tag.
+    for (stream, line) in &lines {
+        let expected = if line == "oops" {
+            Stream::Stderr
+        } else {
+            Stream::Stdout
+        };
+        assert_eq!(*stream, expected, "{line}");
+    }
+}
+
+#[cfg(unix)]
+#[test]
+fn test_output_under_threshold_is_not_spilled() {
+    let run = run_with_spill("seq 1 10", Some(1024));
+    assert!(run.spilled.is_none());
+    assert_eq!(run.result.stdout.lines().count(), 10);
+    assert!(run_with_spill("seq 1 1000", None).spilled.is_none());
+}
@@ -0,0 +1,74 @@
+use super::*;
+use crate::config::types::RetryConfig;
+
+/// Fails with exit 1 until it has run `fails` times, then prints `ok`.
+fn flaky(dir: &tempfile::TempDir, fails: u32) -> String {
+    let count = dir.path().join("count");
+    let count = count.display();
+    format!(
+        "n=$(cat {count} 2>/dev/null || echo 0); echo $((n + 1)) > {count}; \
+         [ \"$n\" -ge {fails} ] && echo ok || {{ echo fail $n; exit 1; }}"
+    )
+}
+
+const fn policy(attempts: u32, on_exit: Vec<i32>) -> RetryConfig {
+    RetryConfig {
+        attempts,
+        on_exit,
+        backoff_ms: 1,
+    }
+}
+
+fn run(cmd: &str, policy: Option<&RetryConfig>) -> Execution {
+    with_retry(policy, false, || {
+        execute_shell_with_env(cmd, &[], &[], ExecOptions::default())
+    })
+    .unwrap()
+}
+
+#[test]
+fn retries_until_success_and_keeps_last_output() {
+    let dir = tempfile::TempDir::new().unwrap();
+    let run = run(&flaky(&dir, 2), Some(&policy(3, vec![1])));
+    assert_eq!(run.attempts, 3);
+    assert_eq!(run.result.exit_code, 0);
+    assert_eq!(run.result.stdout, "ok");
+}
+
+#[test]
+fn stops_after_the_last_attempt() {
+    let dir = tempfile::TempDir::new().unwrap();
+    let run = run(&flaky(&dir, 5), Some(&policy(2, vec![])));
+    assert_eq!(run.attempts, 2);
+    assert_eq!(run.result.exit_code, 1);
+    assert_eq!(run.result.stdout, "fail 1");
+}
+
+#[test]
+fn other_exit_codes_are_not_retried() {
+    let run = run("exit 2", Some(&policy(3, vec![1])));
+    assert_eq!(run.attempts, 1);
+    assert_eq!(run.result.exit_code, 2);
+}
+
+#[test]
+fn no_policy_runs_once() {
+    let dir = tempfile::TempDir::new().unwrap();
+    let run = run(&flaky(&dir, 1), None);
+    assert_eq!(run.attempts, 1);
+    assert_eq!(run.result.exit_code, 1);
+}
+
+#[test]
+fn timed_out_run_is_not_retried() {
+    let opts = ExecOptions {
+        timeout: Some(std::time::Duration::from_millis(100)),
+        ..ExecOptions::default()
+    };
+    let run = with_retry(Some(&policy(3, vec![])), false, || {
+        execute_shell_with_env("sleep 5", &[], &[], opts)
+    })
+    .unwrap();
+    assert_eq!(run.attempts, 1);
+    assert!(run.timed_out.is_some());
+}
@@ -0,0 +1,89 @@
+use super::*;
+
+fn shell(name: &str) -> Shell {
+    Shell::named(name, None).unwrap()
+}
+
+#[test]
+fn named_maps_shells_to_their_syntax() {
+    assert_eq!(shell("bash").kind, ShellKind::Posix);
+    assert_eq!(shell("zsh").kind, ShellKind::Posix);
+    assert_eq!(shell("fish").kind, ShellKind::Fish);
+    assert_eq!(shell("powershell").kind, ShellKind::PowerShell);
+    assert_eq!(shell("cmd").kind, ShellKind::Cmd);
+    assert!(Shell::named("tcsh", None).is_none());
+    let custom = Shell::named("pwsh", Some("/opt/pwsh".to_string())).unwrap();
+    assert_eq!(custom.program, "/opt/pwsh");
+}
+
+#[test]
+fn quote_posix() {
+    assert_eq!(shell("bash").quote("it's $HOME"), "'it'\\''s $HOME'");
+}
+
+#[test]
+fn quote_fish() {
+    assert_eq!(shell("fish").quote("it's a\\b"), "'it\\'s a\\\\b'");
+}
+
+#[test]
+fn quote_powershell() {
+    assert_eq!(shell("pwsh").quote("it's $env:HOME"), "'it''s $env:HOME'");
+}
+
+#[test]
+fn quote_cmd_doubles_quotes_and_escapes_percent() {
+    let cmd = shell("cmd");
+    assert_eq!(cmd.quote("a b"), "\"a b\"");
+    assert_eq!(cmd.quote("say \"hi\" & exit"), "\"say \"\"hi\"\" & exit\"");
+    assert_eq!(cmd.quote("100%PATH%"), "\"100\"^%\"PATH\"^%\"\"");
+    // A trailing backslash must not escape the closing quote.
+    assert_eq!(cmd.quote("C:\\dir\\"), "\"C:\\dir\\\\\"");
+}
+
+#[test]
+fn expand_run_command_quotes_for_the_shell() {
+    let args = vec!["it's".to_string()];
+    assert_eq!(
+        expand_run_command(&shell("pwsh"), "echo {args}", &args),
+        "echo 'it''s'"
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn execute_shell_uses_the_configured_shell() {
+    let bash = shell("bash");
+    let opts = ExecOptions {
+        shell: Some(&bash),
+        ..ExecOptions::default()
+    };
+    let run = execute_shell_with_env(
+        "[[ -n {args} ]] && echo yes",
+        &["x y".to_string()],
+        &[],
+        opts,
+    )
+    .unwrap();
+    assert_eq!(run.result.stdout, "yes");
+}
+
+#[cfg(unix)]
+#[test]
+fn posix_shell_keeps_flags_others_get_their_own() {
+    let cmd = shell("bash").command_with_flags("-ec", "true");
+    let args: Vec<_> = cmd.get_args().collect();
+    assert_eq!(args, ["-ec", "true"]);
+    let cmd = shell("pwsh").command_with_flags("-ec", "true");
+    let args: Vec<_> = cmd.get_args().collect();
+    assert_eq!(
+        args,
+        [
+            "-NoLogo",
+            "-NoProfile",
+            "-NonInteractive",
+            "-Command",
+            "true"
+        ]
+    );
+}
@@ -0,0 +1,111 @@
+//! Resource usage of a finished command.
+//!
+//! Wall-clock time is measured around the run. On Unix, CPU time and peak
+//! memory come from `getrusage(RUSAGE_CHILDREN)` snapshots taken before and
+//! after, so they cover the command and every descendant it waited for. The
+//! peak RSS is the largest of any child tokf has reaped, which for `tokf run`
+//! is the command itself.
+
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+/// What a command cost to run. CPU and memory figures are `None` where the
+/// platform does not report them.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ResourceUsage {
+    /// Wall-clock time from spawn until the output was collected.
+    pub duration: Duration,
+    pub user_cpu: Option<Duration>,
+    pub sys_cpu: Option<Duration>,
+    /// Peak resident set size, in KiB.
+    pub max_rss_kb: Option<u64>,
+}
+
+impl ResourceUsage {
+    /// Template variables: `{duration}`, `{user_time}` and `{sys_time}` in
+    /// seconds (`12.3s`), `{max_rss}` in MiB (`512.0 MB`). Unreported
+    /// figures are left out, so templates render them as empty.
+    #[must_use]
+    pub fn template_vars(&self) -> HashMap<String, String> {
+        let mut vars = HashMap::new();
+        vars.insert("duration".to_string(), seconds(self.duration));
+        if let Some(user) = self.user_cpu {
+            vars.insert("user_time".to_string(), seconds(user));
+        }
+        if let Some(sys) = self.sys_cpu {
+            vars.insert("sys_time".to_string(), seconds(sys));
+        }
+        if let Some(kb) = self.max_rss_kb {
+            #[allow(clippy::cast_precision_loss)]
+            let mb = kb as f64 / 1024.0;
+            vars.insert("max_rss".to_string(), format!("{mb:.1} MB"));
+        }
+        vars
+    }
+}
+
+fn seconds(d: Duration) -> String {
+    format!("{:.1}s", d.as_secs_f64())
+}
+
+/// Usage counters taken just before a command is spawned.
+pub(super) struct Snapshot {
+    start: Instant,
+    #[cfg(unix)]
+    children: Option<libc::rusage>,
+}
+
+impl Snapshot {
+    pub fn now() -> Self {
+        Self {
+            start: Instant::now(),
+            #[cfg(unix)]
+            children: children_rusage(),
+        }
+    }
+
+    /// Usage accumulated since [`Self::now`]; call once the command is reaped.
+    pub fn finish(&self) -> ResourceUsage {
+        let duration = self.start.elapsed();
+        #[cfg(unix)]
+        if let (Some(before), Some(after)) = (self.children, children_rusage()) {
+            return ResourceUsage {
+                duration,
+                user_cpu: Some(timeval(after.ru_utime).saturating_sub(timeval(before.ru_utime))),
+                sys_cpu: Some(timeval(after.ru_stime).saturating_sub(timeval(before.ru_stime))),
+                max_rss_kb: Some(max_rss_kb(&after)),
+            };
+        }
+        ResourceUsage {
+            duration,
+            ..ResourceUsage::default()
+        }
+    }
+}
+
+#[cfg(unix)]
+fn children_rusage() -> Option<libc::rusage> {
+    // SAFETY: `rusage` is plain old data, so all-zero is a valid value, and
+    // getrusage only writes through the pointer it is given.
+    let mut usage: libc::rusage = unsafe { std::mem::zeroed() };
+    let rc = unsafe { libc::getrusage(libc::RUSAGE_CHILDREN, &raw mut usage) };
+    (rc == 0).then_some(usage)
+}
+
+#[cfg(unix)]
+fn timeval(tv: libc::timeval) -> Duration {
+    let secs = u64::try_from(tv.tv_sec).unwrap_or(0);
+    let micros = u32::try_from(tv.tv_usec).unwrap_or(0);
+    Duration::from_secs(secs) + Duration::from_micros(u64::from(micros))
+}
+
+/// `ru_maxrss` is in bytes on macOS and in KiB everywhere else.
+#[cfg(unix)]
+fn max_rss_kb(usage: &libc::rusage) -> u64 {
+    let raw = u64::try_from(usage.ru_maxrss).unwrap_or(0);
+    if cfg!(target_os = "macos") {
+        raw / 1024
+    } else {
+        raw
+    }
+}
@@ -0,0 +1,114 @@
+//! Waiting for a command, with an optional deadline and interrupt handling.
+//!
+//! A command with a timeout is spawned as the leader of its own process group
+//! so that, on expiry, the whole tree it started can be killed at once —
+//! killing only the direct child would leave e.g. `sh -c`'s grandchildren
+//! holding the output pipes open. Being outside the terminal's foreground
+//! group, such a command would be stopped by SIGTTIN on its first read from
+//! the terminal, so an inherited terminal stdin is replaced by `/dev/null`.
+//!
+//! When tokf receives SIGINT or SIGTERM, the command gets
+//! [`INTERRUPT_GRACE`] to exit (see [`super::signals`]) before it is killed;
+//! a second signal kills it at once.
+
+use std::process::{Child, Command, ExitStatus, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::config::types::StdinMode;
+
+use super::signals::Forwarding;
+
+/// First and longest pause between checks for the command's exit.
+const MIN_POLL: Duration = Duration::from_millis(1);
+const MAX_POLL: Duration = Duration::from_millis(50);
+
+/// How long an interrupted command may take to exit before it is killed.
+const INTERRUPT_GRACE: Duration = Duration::from_secs(5);
+
+/// How a command ended.
+pub(super) struct Waited {
+    pub status: ExitStatus,
+    /// Killed because the timeout elapsed.
+    pub timed_out: bool,
+    /// tokf was sent SIGINT or SIGTERM while the command ran.
+    pub interrupted: bool,
+}
+
+/// Prepare `cmd` so [`wait`] can kill its whole process tree. Applied to
+/// every command with a timeout.
+pub(super) fn isolate(cmd: &mut Command, stdin: StdinMode) {
+    #[cfg(unix)]
+    {
+        use std::io::IsTerminal as _;
+        use std::os::unix::process::CommandExt;
+        cmd.process_group(0);
+        if detaches_stdin(stdin, std::io::stdin().is_terminal()) {
+            cmd.stdin(Stdio::null());
+        }
+    }
+    #[cfg(not(unix))]
+    let _ = (cmd, stdin);
+}
+
+/// Whether an isolated command must not inherit tokf's stdin: a background
+/// process group reading its terminal would be stopped, never to resume.
+pub(super) const fn detaches_stdin(stdin: StdinMode, stdin_is_terminal: bool) -> bool {
+    matches!(stdin, StdinMode::Inherit) && stdin_is_terminal
+}
+
+/// Wait for `child` to exit, killing its process tree once `timeout` elapses
+/// or an interrupt's grace period runs out.
+pub(super) fn wait(child: &mut Child, timeout: Option<Duration>) -> std::io::Result<Waited> {
+    let signals = Forwarding::start(child, timeout.is_some());
+    let deadline = timeout.map(|t| Instant::now() + t);
+    let mut grace_deadline = None;
+    let mut interval = MIN_POLL;
+    loop {
+        let received = signals.received();
+        if let Some(status) = child.try_wait()? {
+            return Ok(Waited {
+                status,
+                timed_out: false,
+                interrupted: received > 0,
+            });
+        }
+        let now = Instant::now();
+        if received > 0 && grace_deadline.is_none() {
+            grace_deadline = Some(now + INTERRUPT_GRACE);
+        }
+        let give_up = received > 1 || grace_deadline.is_some_and(|d| now >= d);
+        let timed_out = received == 0 && deadline.is_some_and(|d| now >= d);
+        if give_up || timed_out {
+            kill_tree(child);
+            return Ok(Waited {
+                status: child.wait()?,
+                timed_out,
+                interrupted: received > 0,
+            });
+        }
+        thread::sleep(interval);
+        interval = (interval * 2).min(MAX_POLL);
+    }
+}
+
+/// Kill `child` and everything it spawned, falling back to the child alone.
+fn kill_tree(child: &mut Child) {
+    #[cfg(unix)]
+    let killed = {
+        use rustix::process::{Pid, Signal, kill_process_group};
+        kill_process_group(Pid::from_child(child), Signal::KILL).is_ok()
+    };
+    #[cfg(windows)]
+    let killed = Command::new("taskkill")
+        .args(["/T", "/F", "/PID", &child.id().to_string()])
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .status()
+        .is_ok_and(|s| s.success());
+    #[cfg(not(any(unix, windows)))]
+    let killed = false;
+    if !killed {
+        let _ = child.kill();
+    }
+}
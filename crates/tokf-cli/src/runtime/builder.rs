@@ -19,7 +19,7 @@ use std::sync::Arc;
 use std::sync::atomic::{AtomicU64, Ordering};
 use std::time::Duration;
 
-use super::{Dirs, Flags, Net, OtelEnv, Runtime};
+use super::{Dirs, Flags, Net, OtelEnv, ProxyEnv, Runtime};
 
 /// Distinguishes the keyring service name of every isolated runtime.
 static KEYRING_SEQ: AtomicU64 = AtomicU64::new(0);
@@ -156,6 +156,20 @@ impl RuntimeBuilder {
         self
     }
 
+    /// Set the `TOKF_TOKEN` personal access token.
+    #[must_use]
+    pub fn token(mut self, token: impl Into<String>) -> Self {
+        self.inner.net.token = Some(token.into());
+        self
+    }
+
+    /// Replace the proxy environment.
+    #[must_use]
+    pub fn proxy_env(mut self, proxy: ProxyEnv) -> Self {
+        self.inner.net.proxy = proxy;
+        self
+    }
+
     /// Set the HTTP request timeout.
     #[must_use]
     pub const fn http_timeout(mut self, timeout: Duration) -> Self {
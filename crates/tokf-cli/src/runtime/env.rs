@@ -49,6 +49,34 @@ pub(super) struct Net {
     pub(super) server_url: String,
     /// `TOKF_HTTP_TIMEOUT` seconds, or [`DEFAULT_TIMEOUT_SECS`].
     pub(super) http_timeout: Duration,
+    /// `TOKF_TOKEN` — a personal access token used instead of the stored
+    /// login, for CI.
+    pub(super) token: Option<String>,
+    /// The conventional proxy variables.
+    pub(super) proxy: ProxyEnv,
+}
+
+/// `HTTPS_PROXY`, `HTTP_PROXY` and `NO_PROXY`.
+///
+/// Each falls back to its lowercase spelling. Captured here rather than left for `reqwest` to read,
+/// so a test's proxy settings are as local as the rest of its runtime.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ProxyEnv {
+    pub https_proxy: Option<String>,
+    pub http_proxy: Option<String>,
+    pub no_proxy: Option<String>,
+}
+
+impl ProxyEnv {
+    fn from_lookup(get: Lookup<'_>) -> Self {
+        let either =
+            |upper: &str, lower: &str| non_empty(get, upper).or_else(|| non_empty(get, lower));
+        Self {
+            https_proxy: either("HTTPS_PROXY", "https_proxy"),
+            http_proxy: either("HTTP_PROXY", "http_proxy"),
+            no_proxy: either("NO_PROXY", "no_proxy"),
+        }
+    }
 }
 
 impl Default for Net {
@@ -56,6 +84,8 @@ impl Default for Net {
         Self {
             server_url: DEFAULT_SERVER_URL.to_string(),
             http_timeout: Duration::from_secs(DEFAULT_TIMEOUT_SECS),
+            token: None,
+            proxy: ProxyEnv::default(),
         }
     }
 }
@@ -158,6 +188,8 @@ impl Net {
                     .and_then(|v| v.parse::<u64>().ok())
                     .unwrap_or(DEFAULT_TIMEOUT_SECS),
             ),
+            token: non_empty(get, "TOKF_TOKEN"),
+            proxy: ProxyEnv::from_lookup(get),
         }
     }
 }
@@ -383,6 +415,7 @@ mod tests {
         let net = net_from(&[]);
         assert_eq!(net.server_url, DEFAULT_SERVER_URL);
         assert_eq!(net.http_timeout, Duration::from_secs(DEFAULT_TIMEOUT_SECS));
+        assert_eq!(net.token, None);
     }
 
     #[test]
@@ -390,9 +423,35 @@ mod tests {
         let net = net_from(&[
             ("TOKF_SERVER_URL", "https://staging.example.com"),
             ("TOKF_HTTP_TIMEOUT", "42"),
+            ("TOKF_TOKEN", "tokf_pat_abc"),
         ]);
         assert_eq!(net.server_url, "https://staging.example.com");
         assert_eq!(net.http_timeout, Duration::from_secs(42));
+        assert_eq!(net.token.as_deref(), Some("tokf_pat_abc"));
+    }
+
+    #[test]
+    fn proxy_variables_prefer_the_uppercase_spelling() {
+        let net = net_from(&[
+            ("HTTPS_PROXY", "http://upper:3128"),
+            ("https_proxy", "http://lower:3128"),
+            ("http_proxy", "http://plain:3128"),
+            ("no_proxy", "localhost,.internal"),
+        ]);
+        assert_eq!(
+            net.proxy,
+            ProxyEnv {
+                https_proxy: Some("http://upper:3128".to_string()),
+                http_proxy: Some("http://plain:3128".to_string()),
+                no_proxy: Some("localhost,.internal".to_string()),
+            }
+        );
+        assert_eq!(net_from(&[]).proxy, ProxyEnv::default());
+    }
+
+    #[test]
+    fn an_empty_token_is_absent() {
+        assert_eq!(net_from(&[("TOKF_TOKEN", "")]).token, None);
     }
 
     #[test]
@@ -29,7 +29,7 @@ use std::sync::Arc;
 use std::time::Duration;
 
 use dirs::Dirs;
-pub use env::{DEFAULT_SERVER_URL, DEFAULT_TIMEOUT_SECS, OtelEnv};
+pub use env::{DEFAULT_SERVER_URL, DEFAULT_TIMEOUT_SECS, OtelEnv, ProxyEnv};
 use env::{Flags, Net};
 
 #[cfg(any(test, feature = "test-support"))]
@@ -191,6 +191,16 @@ impl Runtime {
         self.net.http_timeout
     }
 
+    /// The `TOKF_TOKEN` personal access token, if set.
+    pub fn token(&self) -> Option<&str> {
+        self.net.token.as_deref()
+    }
+
+    /// The `HTTPS_PROXY` / `HTTP_PROXY` / `NO_PROXY` environment.
+    pub const fn proxy_env(&self) -> &ProxyEnv {
+        &self.net.proxy
+    }
+
     // -- telemetry --------------------------------------------------------
 
     /// The raw OpenTelemetry environment.
@@ -205,6 +215,16 @@ impl Runtime {
         self.cwd.as_deref()
     }
 
+    /// This runtime with another working directory, for `tokf daemon`, which
+    /// answers hooks from many projects.
+    #[must_use]
+    pub fn with_cwd(&self, cwd: PathBuf) -> Self {
+        Self {
+            cwd: Some(cwd),
+            ..self.clone()
+        }
+    }
+
     /// The working directory, or an empty path when it could not be resolved.
     ///
     /// Mirrors the old `std::env::current_dir().unwrap_or_default()` that call
@@ -1,14 +1,17 @@
 use std::fmt;
 use std::io::IsTerminal as _;
 
-use tokf::remote::filter_client::{self, FilterSummary};
-use tokf::remote::http::Client;
+use tokf::remote::filter_client::{self, FilterSummary, SearchQuery};
+use tokf::remote::registry::{self, Registry};
 
 use tokf::runtime::Runtime;
 
-/// Entry point for the `tokf search` subcommand.
-pub fn cmd_search(rt: &Runtime, query: &str, limit: usize, json: bool) -> i32 {
-    match search(rt, query, limit, json) {
+use crate::commands::SearchArgs;
+
+/// Entry point for the `tokf search` subcommand. Searches `--registry`, or
+/// every configured registry when it is not given.
+pub fn cmd_search(rt: &Runtime, args: &SearchArgs) -> i32 {
+    match search(rt, args) {
         Ok(code) => code,
         Err(e) => {
             eprintln!("[tokf] error: {e:#}");
@@ -17,13 +20,34 @@ pub fn cmd_search(rt: &Runtime, query: &str, limit: usize, json: bool) -> i32 {
     }
 }
 
-fn search(rt: &Runtime, query: &str, limit: usize, json: bool) -> anyhow::Result<i32> {
-    let client = Client::authed(rt)?;
-
-    let results = filter_client::search_filters(&client, query, limit)?;
+fn search(rt: &Runtime, args: &SearchArgs) -> anyhow::Result<i32> {
+    let registry = args.registry.as_deref();
+    let registries = registry::select(rt, registry)?;
+    if args.cursor.is_some() && registries.len() > 1 {
+        anyhow::bail!("--cursor continues a single registry's results; pass --registry");
+    }
+    let joined = args.query.join(" ");
+    let query = SearchQuery {
+        query: &joined,
+        limit: args.limit,
+        sort: args.sort.into(),
+        author: args.author.as_deref(),
+        stdlib: args.stdlib.then_some(true),
+        command: args.command.as_deref(),
+        cursor: args.cursor.as_deref(),
+        include_yanked: args.include_yanked,
+        org: args.org.as_deref(),
+    };
+    let (results, next_cursor) = search_registries(rt, &registries, &query)?;
+    let more = || {
+        if let Some(ref cursor) = next_cursor {
+            eprintln!("[tokf] more results: rerun with --cursor {cursor}");
+        }
+    };
 
-    if json {
+    if args.json {
         println!("{}", serde_json::to_string_pretty(&results)?);
+        more();
         return Ok(0);
     }
 
@@ -33,14 +57,60 @@ fn search(rt: &Runtime, query: &str, limit: usize, json: bool) -> anyhow::Result
     }
 
     if std::io::stderr().is_terminal() {
-        interactive_select(rt, &results)
+        more();
+        interactive_select(rt, &results, registry)
     } else {
         print_table(&results);
+        more();
         Ok(0)
     }
 }
 
-fn interactive_select(rt: &Runtime, results: &[FilterSummary]) -> anyhow::Result<i32> {
+/// Search each registry in priority order, up to `limit` results from each,
+/// returning the results and the cursor for the next page.
+///
+/// With several registries, results are tagged with the registry they came
+/// from and a registry that fails is skipped with a warning; the search only
+/// fails if all of them do. Paging is only offered for a single registry.
+fn search_registries(
+    rt: &Runtime,
+    registries: &[Registry],
+    query: &SearchQuery<'_>,
+) -> anyhow::Result<(Vec<FilterSummary>, Option<String>)> {
+    let search_one = |r: &Registry| filter_client::search_filters_page(&r.client(rt)?, query);
+    if let [only] = registries {
+        return search_one(only).map(|page| (page.filters, page.next_cursor));
+    }
+
+    let mut results = Vec::new();
+    let mut first_error = None;
+    let mut any_ok = false;
+    for r in registries {
+        match search_one(r) {
+            Ok(page) => {
+                any_ok = true;
+                results.extend(page.filters.into_iter().map(|mut f| {
+                    f.registry = Some(r.name.clone());
+                    f
+                }));
+            }
+            Err(e) => {
+                eprintln!("[tokf] warning: registry '{}': {e:#}", r.name);
+                first_error.get_or_insert(e);
+            }
+        }
+    }
+    match first_error {
+        Some(e) if !any_ok => Err(e),
+        _ => Ok((results, None)),
+    }
+}
+
+fn interactive_select(
+    rt: &Runtime,
+    results: &[FilterSummary],
+    registry: Option<&str>,
+) -> anyhow::Result<i32> {
     let items: Vec<SelectableFilter<'_>> = results.iter().map(SelectableFilter).collect();
 
     eprintln!();
@@ -62,7 +132,8 @@ fn interactive_select(rt: &Runtime, results: &[FilterSummary]) -> anyhow::Result
                 rt,
                 crate::install_cmd::InstallOpts {
                     filter: &selected.content_hash,
-                    local: false,
+                    registry: selected.registry.as_deref().or(registry),
+                    global: true,
                     force: false,
                     dry_run: false,
                     // Interactive selection is itself the confirmation.
@@ -79,6 +150,9 @@ impl fmt::Display for SelectableFilter<'_> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let r = self.0;
         write!(f, "{}", r.command_pattern)?;
+        if let Some(ref registry) = r.registry {
+            write!(f, " [{registry}]")?;
+        }
         if let Some(ref ver) = r.introduced_at {
             write!(f, " v{ver}")?;
         }
@@ -88,48 +162,61 @@ impl fmt::Display for SelectableFilter<'_> {
         if r.deprecated_at.is_some() {
             write!(f, " [deprecated]")?;
         }
-        write!(f, "  @{}", r.author)?;
+        if r.yanked_at.is_some() {
+            write!(f, " [yanked]")?;
+        }
+        if r.is_private() {
+            write!(f, " [private]")?;
+        }
+        if !r.tags.is_empty() {
+            write!(f, " ({})", r.tags.join(", "))?;
+        }
+        write!(f, "  @{}", r.namespace())?;
         write!(f, "  savings:{:.0}%", r.savings_pct)?;
         write!(f, "  tests:{}", r.test_count)?;
         write!(f, "  runs:{}", format_number(r.total_commands))?;
+        if r.downloads > 0 {
+            write!(f, "  downloads:{}", format_number(r.downloads))?;
+        }
+        if r.stars > 0 {
+            write!(f, "  stars:{}", r.stars)?;
+        }
         Ok(())
     }
 }
 
+/// Width of a column: its widest cell, but at least `header` wide.
+fn column_width(
+    results: &[FilterSummary],
+    header: usize,
+    cell: impl Fn(&FilterSummary) -> usize,
+) -> usize {
+    results.iter().map(cell).max().unwrap_or(header).max(header)
+}
+
 fn print_table(results: &[FilterSummary]) {
-    let cmd_width = results
-        .iter()
-        .map(|r| display_command(r).len())
-        .max()
-        .unwrap_or(7)
-        .max(7);
-    let author_width = results
-        .iter()
-        .map(|r| r.author.len())
-        .max()
-        .unwrap_or(6)
-        .max(6);
-    let ver_width = results
-        .iter()
-        .map(|r| display_version(r).len())
-        .max()
-        .unwrap_or(7)
-        .max(7);
+    let cmd_width = column_width(results, 7, |r| display_command(r).len());
+    let author_width = column_width(results, 6, |r| r.namespace().len());
+    let ver_width = column_width(results, 7, |r| display_version(r).len());
 
     eprintln!(
-        "{:<cmd_width$}  {:<ver_width$}  {:<author_width$}  {:>8}  {:>5}  {:>8}",
+        "{:<cmd_width$}  {:<ver_width$}  {:<author_width$}  {:>8}  {:>5}  {:>8}  {:>9}  {:>5}",
         "COMMAND",
         "VERSION",
         "AUTHOR",
         "SAVINGS%",
         "TESTS",
         "RUNS",
+        "DOWNLOADS",
+        "STARS",
         cmd_width = cmd_width,
         ver_width = ver_width,
         author_width = author_width,
     );
     eprintln!(
-        "{:-<cmd_width$}  {:-<ver_width$}  {:-<author_width$}  {:->8}  {:->5}  {:->8}",
+        "{:-<cmd_width$}  {:-<ver_width$}  {:-<author_width$}  {:->8}  {:->5}  {:->8}  {:->9}  {:->5}",
+        "",
+        "",
         "",
         "",
         "",
@@ -143,13 +230,15 @@ fn print_table(results: &[FilterSummary]) {
 
     for r in results {
         eprintln!(
-            "{:<cmd_width$}  {:<ver_width$}  {:<author_width$}  {:>7.1}%  {:>5}  {:>8}",
+            "{:<cmd_width$}  {:<ver_width$}  {:<author_width$}  {:>7.1}%  {:>5}  {:>8}  {:>9}  {:>5}",
             display_command(r),
             display_version(r),
-            r.author,
+            r.namespace(),
             r.savings_pct,
             r.test_count,
             format_number(r.total_commands),
+            format_number(r.downloads),
+            r.stars,
             cmd_width = cmd_width,
             ver_width = ver_width,
             author_width = author_width,
@@ -164,11 +253,20 @@ fn display_version(r: &FilterSummary) -> String {
 }
 
 fn display_command(r: &FilterSummary) -> String {
+    let mut display = r.command_pattern.clone();
+    if let Some(ref registry) = r.registry {
+        display = format!("{display} [{registry}]");
+    }
     if r.is_stdlib {
-        format!("{} [stdlib]", r.command_pattern)
-    } else {
-        r.command_pattern.clone()
+        display.push_str(" [stdlib]");
+    }
+    if r.yanked_at.is_some() {
+        display.push_str(" [yanked]");
+    }
+    if r.is_private() {
+        display.push_str(" [private]");
     }
+    display
 }
 
 fn format_number(n: i64) -> String {
@@ -185,263 +283,5 @@ fn format_number(n: i64) -> String {
 
 #[cfg(test)]
 #[allow(clippy::unwrap_used)]
-mod tests {
-    use super::*;
-
-    #[test]
-    fn format_number_small() {
-        assert_eq!(format_number(0), "0");
-        assert_eq!(format_number(999), "999");
-    }
-
-    #[test]
-    fn format_number_thousands() {
-        assert_eq!(format_number(1000), "1,000");
-        assert_eq!(format_number(1234), "1,234");
-        assert_eq!(format_number(1_000_000), "1,000,000");
-    }
-
-    fn make_summary(command: &str, is_stdlib: bool) -> FilterSummary {
-        FilterSummary {
-            content_hash: String::new(),
-            command_pattern: command.to_string(),
-            author: String::new(),
-            savings_pct: 0.0,
-            total_commands: 0,
-            created_at: String::new(),
-            test_count: 0,
-            is_stdlib,
-            introduced_at: None,
-            deprecated_at: None,
-        }
-    }
-
-    #[test]
-    fn display_command_appends_stdlib_badge() {
-        let r = make_summary("git push", true);
-        assert_eq!(display_command(&r), "git push [stdlib]");
-    }
-
-    #[test]
-    fn display_command_no_badge_for_community() {
-        let r = make_summary("git push", false);
-        assert_eq!(display_command(&r), "git push");
-    }
-
-    #[test]
-    fn selectable_filter_display_stdlib() {
-        let summary = FilterSummary {
-            content_hash: "abc123".to_string(),
-            command_pattern: "git push".to_string(),
-            author: "mpecan".to_string(),
-            savings_pct: 45.0,
-            total_commands: 12234,
-            created_at: String::new(),
-            test_count: 3,
-            is_stdlib: true,
-            introduced_at: None,
-            deprecated_at: None,
-        };
-        let display = format!("{}", SelectableFilter(&summary));
-        assert_eq!(
-            display,
-            "git push [stdlib]  @mpecan  savings:45%  tests:3  runs:12,234"
-        );
-    }
-
-    #[test]
-    fn selectable_filter_display_community() {
-        let summary = FilterSummary {
-            content_hash: "def456".to_string(),
-            command_pattern: "cargo build".to_string(),
-            author: "alice".to_string(),
-            savings_pct: 72.8,
-            total_commands: 500,
-            created_at: String::new(),
-            test_count: 0,
-            is_stdlib: false,
-            introduced_at: None,
-            deprecated_at: None,
-        };
-        let display = format!("{}", SelectableFilter(&summary));
-        assert_eq!(
-            display,
-            "cargo build  @alice  savings:73%  tests:0  runs:500"
-        );
-    }
-
-    #[test]
-    fn selectable_filter_display_zero_savings() {
-        let summary = FilterSummary {
-            content_hash: String::new(),
-            command_pattern: "npm test".to_string(),
-            author: "bob".to_string(),
-            savings_pct: 0.0,
-            total_commands: 0,
-            created_at: String::new(),
-            test_count: 1,
-            is_stdlib: false,
-            introduced_at: None,
-            deprecated_at: None,
-        };
-        let display = format!("{}", SelectableFilter(&summary));
-        assert_eq!(display, "npm test  @bob  savings:0%  tests:1  runs:0");
-    }
-
-    #[test]
-    fn selectable_filter_display_large_runs() {
-        let summary = FilterSummary {
-            content_hash: String::new(),
-            command_pattern: "git status".to_string(),
-            author: "dev".to_string(),
-            savings_pct: 90.0,
-            total_commands: 1_234_567_890,
-            created_at: String::new(),
-            test_count: 10,
-            is_stdlib: true,
-            introduced_at: None,
-            deprecated_at: None,
-        };
-        let display = format!("{}", SelectableFilter(&summary));
-        assert_eq!(
-            display,
-            "git status [stdlib]  @dev  savings:90%  tests:10  runs:1,234,567,890"
-        );
-    }
-
-    #[test]
-    fn selectable_filter_display_with_version() {
-        let summary = FilterSummary {
-            content_hash: String::new(),
-            command_pattern: "git push".to_string(),
-            author: "mpecan".to_string(),
-            savings_pct: 45.0,
-            total_commands: 100,
-            created_at: String::new(),
-            test_count: 3,
-            is_stdlib: true,
-            introduced_at: Some("0.2.3".to_string()),
-            deprecated_at: None,
-        };
-        let display = format!("{}", SelectableFilter(&summary));
-        assert_eq!(
-            display,
-            "git push v0.2.3 [stdlib]  @mpecan  savings:45%  tests:3  runs:100"
-        );
-    }
-
-    #[test]
-    fn selectable_filter_display_deprecated() {
-        let summary = FilterSummary {
-            content_hash: String::new(),
-            command_pattern: "git push".to_string(),
-            author: "mpecan".to_string(),
-            savings_pct: 45.0,
-            total_commands: 100,
-            created_at: String::new(),
-            test_count: 3,
-            is_stdlib: true,
-            introduced_at: Some("0.1.0".to_string()),
-            deprecated_at: Some("0.2.3".to_string()),
-        };
-        let display = format!("{}", SelectableFilter(&summary));
-        assert_eq!(
-            display,
-            "git push v0.1.0 [stdlib] [deprecated]  @mpecan  savings:45%  tests:3  runs:100"
-        );
-    }
-
-    #[test]
-    #[allow(clippy::too_many_lines)]
-    fn print_table_column_alignment() {
-        // Capture output by calling format functions directly — print_table writes
-        // to stderr which we can't easily capture, so we verify the building blocks.
-        let results = [
-            FilterSummary {
-                content_hash: String::new(),
-                command_pattern: "git push".to_string(),
-                author: "alice".to_string(),
-                savings_pct: 42.3,
-                total_commands: 1234,
-                created_at: String::new(),
-                test_count: 2,
-                is_stdlib: true,
-                introduced_at: Some("0.2.3".to_string()),
-                deprecated_at: None,
-            },
-            FilterSummary {
-                content_hash: String::new(),
-                command_pattern: "cargo build".to_string(),
-                author: "bob".to_string(),
-                savings_pct: 80.0,
-                total_commands: 500,
-                created_at: String::new(),
-                test_count: 0,
-                is_stdlib: false,
-                introduced_at: None,
-                deprecated_at: None,
-            },
-        ];
-
-        // Verify cmd_width calculation (stdlib badge adds 9 chars)
-        let cmd_width = results
-            .iter()
-            .map(|r| display_command(r).len())
-            .max()
-            .unwrap()
-            .max(7);
-        // "git push [stdlib]" = 17 chars, "cargo build" = 11 chars → max is 17
-        assert_eq!(cmd_width, 17);
-
-        // Verify author_width
-        let author_width = results.iter().map(|r| r.author.len()).max().unwrap().max(6);
-        // "alice" = 5, "bob" = 3, min 6 → 6
-        assert_eq!(author_width, 6);
-
-        // Verify ver_width
-        let ver_width = results
-            .iter()
-            .map(|r| display_version(r).len())
-            .max()
-            .unwrap()
-            .max(7);
-        // "v0.2.3" = 6, "" = 0, min 7 → 7
-        assert_eq!(ver_width, 7);
-
-        // Verify row formatting produces consistent-width output
-        let row1 = format!(
-            "{:<cmd_width$}  {:<ver_width$}  {:<author_width$}  {:>7.1}%  {:>5}  {:>8}",
-            display_command(&results[0]),
-            display_version(&results[0]),
-            results[0].author,
-            results[0].savings_pct,
-            results[0].test_count,
-            format_number(results[0].total_commands),
-        );
-        let row2 = format!(
-            "{:<cmd_width$}  {:<ver_width$}  {:<author_width$}  {:>7.1}%  {:>5}  {:>8}",
-            display_command(&results[1]),
-            display_version(&results[1]),
-            results[1].author,
-            results[1].savings_pct,
-            results[1].test_count,
-            format_number(results[1].total_commands),
-        );
-        assert_eq!(row1.len(), row2.len(), "rows should have equal width");
-    }
-
-    #[test]
-    fn display_version_with_introduced_at() {
-        let r = FilterSummary {
-            introduced_at: Some("0.2.3".to_string()),
-            ..make_summary("git push", true)
-        };
-        assert_eq!(display_version(&r), "v0.2.3");
-    }
-
-    #[test]
-    fn display_version_without_introduced_at() {
-        let r = make_summary("git push", true);
-        assert_eq!(display_version(&r), "");
-    }
-}
+#[path = "search_cmd_tests.rs"]
+mod tests;
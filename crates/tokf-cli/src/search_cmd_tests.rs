@@ -0,0 +1,425 @@
+use super::*;
+
+#[test]
+fn format_number_small() {
+    assert_eq!(format_number(0), "0");
+    assert_eq!(format_number(999), "999");
+}
+
+#[test]
+fn format_number_thousands() {
+    assert_eq!(format_number(1000), "1,000");
+    assert_eq!(format_number(1234), "1,234");
+    assert_eq!(format_number(1_000_000), "1,000,000");
+}
+
+fn make_summary(command: &str, is_stdlib: bool) -> FilterSummary {
+    FilterSummary {
+        content_hash: String::new(),
+        command_pattern: command.to_string(),
+        author: String::new(),
+        savings_pct: 0.0,
+        total_commands: 0,
+        created_at: String::new(),
+        test_count: 0,
+        downloads: 0,
+        stars: 0,
+        is_stdlib,
+        introduced_at: None,
+        deprecated_at: None,
+        successor_hash: None,
+        yanked_at: None,
+        yank_reason: None,
+        org: None,
+        visibility: None,
+        description: None,
+        tags: vec![],
+        version: None,
+        registry: None,
+    }
+}
+
+#[test]
+fn display_command_appends_stdlib_badge() {
+    let r = make_summary("git push", true);
+    assert_eq!(display_command(&r), "git push [stdlib]");
+}
+
+#[test]
+fn display_command_no_badge_for_community() {
+    let r = make_summary("git push", false);
+    assert_eq!(display_command(&r), "git push");
+}
+
+#[test]
+fn selectable_filter_display_stdlib() {
+    let summary = FilterSummary {
+        content_hash: "abc123".to_string(),
+        command_pattern: "git push".to_string(),
+        author: "mpecan".to_string(),
+        savings_pct: 45.0,
+        total_commands: 12234,
+        created_at: String::new(),
+        test_count: 3,
+        downloads: 0,
+        stars: 0,
+        is_stdlib: true,
+        introduced_at: None,
+        deprecated_at: None,
+        successor_hash: None,
+        yanked_at: None,
+        yank_reason: None,
+        org: None,
+        visibility: None,
+        description: None,
+        tags: vec![],
+        version: None,
+        registry: None,
+    };
+    let display = format!("{}", SelectableFilter(&summary));
+    assert_eq!(
+        display,
+        "git push [stdlib]  @mpecan  savings:45%  tests:3  runs:12,234"
+    );
+}
+
+#[test]
+fn selectable_filter_display_community() {
+    let summary = FilterSummary {
+        content_hash: "def456".to_string(),
+        command_pattern: "cargo build".to_string(),
+        author: "alice".to_string(),
+        savings_pct: 72.8,
+        total_commands: 500,
+        created_at: String::new(),
+        test_count: 0,
+        downloads: 0,
+        stars: 0,
+        is_stdlib: false,
+        introduced_at: None,
+        deprecated_at: None,
+        successor_hash: None,
+        yanked_at: None,
+        yank_reason: None,
+        org: None,
+        visibility: None,
+        description: None,
+        tags: vec![],
+        version: None,
+        registry: None,
+    };
+    let display = format!("{}", SelectableFilter(&summary));
+    assert_eq!(
+        display,
+        "cargo build  @alice  savings:73%  tests:0  runs:500"
+    );
+}
+
+#[test]
+fn selectable_filter_display_zero_savings() {
+    let summary = FilterSummary {
+        content_hash: String::new(),
+        command_pattern: "npm test".to_string(),
+        author: "bob".to_string(),
+        savings_pct: 0.0,
+        total_commands: 0,
+        created_at: String::new(),
+        test_count: 1,
+        downloads: 0,
+        stars: 0,
+        is_stdlib: false,
+        introduced_at: None,
+        deprecated_at: None,
+        successor_hash: None,
+        yanked_at: None,
+        yank_reason: None,
+        org: None,
+        visibility: None,
+        description: None,
+        tags: vec![],
+        version: None,
+        registry: None,
+    };
+    let display = format!("{}", SelectableFilter(&summary));
+    assert_eq!(display, "npm test  @bob  savings:0%  tests:1  runs:0");
+}
+
+#[test]
+fn selectable_filter_display_large_runs() {
+    let summary = FilterSummary {
+        content_hash: String::new(),
+        command_pattern: "git status".to_string(),
+        author: "dev".to_string(),
+        savings_pct: 90.0,
+        total_commands: 1_234_567_890,
+        created_at: String::new(),
+        test_count: 10,
+        downloads: 0,
+        stars: 0,
+        is_stdlib: true,
+        introduced_at: None,
+        deprecated_at: None,
+        successor_hash: None,
+        yanked_at: None,
+        yank_reason: None,
+        org: None,
+        visibility: None,
+        description: None,
+        tags: vec![],
+        version: None,
+        registry: None,
+    };
+    let display = format!("{}", SelectableFilter(&summary));
+    assert_eq!(
+        display,
+        "git status [stdlib]  @dev  savings:90%  tests:10  runs:1,234,567,890"
+    );
+}
+
+#[test]
+fn selectable_filter_display_popularity() {
+    let summary = FilterSummary {
+        content_hash: String::new(),
+        command_pattern: "make".to_string(),
+        author: "dev".to_string(),
+        savings_pct: 50.0,
+        total_commands: 10,
+        created_at: String::new(),
+        test_count: 1,
+        downloads: 12_345,
+        stars: 7,
+        is_stdlib: false,
+        introduced_at: None,
+        deprecated_at: None,
+        successor_hash: None,
+        yanked_at: None,
+        yank_reason: None,
+        org: None,
+        visibility: None,
+        description: None,
+        tags: vec![],
+        version: None,
+        registry: None,
+    };
+    let display = format!("{}", SelectableFilter(&summary));
+    assert_eq!(
+        display,
+        "make  @dev  savings:50%  tests:1  runs:10  downloads:12,345  stars:7"
+    );
+}
+
+#[test]
+fn selectable_filter_display_with_version() {
+    let summary = FilterSummary {
+        content_hash: String::new(),
+        command_pattern: "git push".to_string(),
+        author: "mpecan".to_string(),
+        savings_pct: 45.0,
+        total_commands: 100,
+        created_at: String::new(),
+        test_count: 3,
+        downloads: 0,
+        stars: 0,
+        is_stdlib: true,
+        introduced_at: Some("0.2.3".to_string()),
+        deprecated_at: None,
+        successor_hash: None,
+        yanked_at: None,
+        yank_reason: None,
+        org: None,
+        visibility: None,
+        description: None,
+        tags: vec![],
+        version: None,
+        registry: None,
+    };
+    let display = format!("{}", SelectableFilter(&summary));
+    assert_eq!(
+        display,
+        "git push v0.2.3 [stdlib]  @mpecan  savings:45%  tests:3  runs:100"
+    );
+}
+
+#[test]
+fn selectable_filter_display_with_tags() {
+    let summary = FilterSummary {
+        tags: vec!["rust".to_string(), "build".to_string()],
+        ..make_summary("cargo build", false)
+    };
+    let display = format!("{}", SelectableFilter(&summary));
+    assert!(
+        display.starts_with("cargo build (rust, build)  @"),
+        "got: {display}"
+    );
+}
+
+#[test]
+fn org_filters_show_their_namespace() {
+    let summary = FilterSummary {
+        author: "alice".to_string(),
+        org: Some("acme".to_string()),
+        visibility: Some("private".to_string()),
+        ..make_summary("deploy", false)
+    };
+    assert_eq!(display_command(&summary), "deploy [private]");
+    let display = format!("{}", SelectableFilter(&summary));
+    assert!(
+        display.starts_with("deploy [private]  @org/acme  "),
+        "got: {display}"
+    );
+}
+
+#[test]
+fn selectable_filter_display_deprecated() {
+    let summary = FilterSummary {
+        content_hash: String::new(),
+        command_pattern: "git push".to_string(),
+        author: "mpecan".to_string(),
+        savings_pct: 45.0,
+        total_commands: 100,
+        created_at: String::new(),
+        test_count: 3,
+        downloads: 0,
+        stars: 0,
+        is_stdlib: true,
+        introduced_at: Some("0.1.0".to_string()),
+        deprecated_at: Some("0.2.3".to_string()),
+        successor_hash: None,
+        yanked_at: None,
+        yank_reason: None,
+        org: None,
+        visibility: None,
+        description: None,
+        tags: vec![],
+        version: None,
+        registry: None,
+    };
+    let display = format!("{}", SelectableFilter(&summary));
+    assert_eq!(
+        display,
+        "git push v0.1.0 [stdlib] [deprecated]  @mpecan  savings:45%  tests:3  runs:100"
+    );
+}
+
+#[test]
+#[allow(clippy::too_many_lines)]
+fn print_table_column_alignment() {
+    // Capture output by calling format functions directly — print_table writes
+    // to stderr which we can't easily capture, so we verify the building blocks.
+    let results = [
+        FilterSummary {
+            content_hash: String::new(),
+            command_pattern: "git push".to_string(),
+            author: "alice".to_string(),
+            savings_pct: 42.3,
+            total_commands: 1234,
+            created_at: String::new(),
+            test_count: 2,
+            downloads: 0,
+            stars: 0,
+            is_stdlib: true,
+            introduced_at: Some("0.2.3".to_string()),
+            deprecated_at: None,
+            successor_hash: None,
+            yanked_at: None,
+            yank_reason: None,
+            org: None,
+            visibility: None,
+            description: None,
+            tags: vec![],
+            version: None,
+            registry: None,
+        },
+        FilterSummary {
+            content_hash: String::new(),
+            command_pattern: "cargo build".to_string(),
+            author: "bob".to_string(),
+            savings_pct: 80.0,
+            total_commands: 500,
+            created_at: String::new(),
+            test_count: 0,
+            downloads: 0,
+            stars: 0,
+            is_stdlib: false,
+            introduced_at: None,
+            deprecated_at: None,
+            successor_hash: None,
+            yanked_at: None,
+            yank_reason: None,
+            org: None,
+            visibility: None,
+            description: None,
+            tags: vec![],
+            version: None,
+            registry: None,
+        },
+    ];
+
+    // Verify cmd_width calculation (stdlib badge adds 9 chars)
+    let cmd_width = results
+        .iter()
+        .map(|r| display_command(r).len())
+        .max()
+        .unwrap()
+        .max(7);
+    // "git push [stdlib]" = 17 chars, "cargo build" = 11 chars → max is 17
+    assert_eq!(cmd_width, 17);
+
+    // Verify author_width
+    let author_width = results.iter().map(|r| r.author.len()).max().unwrap().max(6);
+    // "alice" = 5, "bob" = 3, min 6 → 6
+    assert_eq!(author_width, 6);
+
+    // Verify ver_width
+    let ver_width = results
+        .iter()
+        .map(|r| display_version(r).len())
+        .max()
+        .unwrap()
+        .max(7);
+    // "v0.2.3" = 6, "" = 0, min 7 → 7
+    assert_eq!(ver_width, 7);
+
+    // Verify row formatting produces consistent-width output
+    let row1 = format!(
+        "{:<cmd_width$}  {:<ver_width$}  {:<author_width$}  {:>7.1}%  {:>5}  {:>8}",
+        display_command(&results[0]),
+        display_version(&results[0]),
+        results[0].author,
+        results[0].savings_pct,
+        results[0].test_count,
+        format_number(results[0].total_commands),
+    );
+    let row2 = format!(
+        "{:<cmd_width$}  {:<ver_width$}  {:<author_width$}  {:>7.1}%  {:>5}  {:>8}",
+        display_command(&results[1]),
+        display_version(&results[1]),
+        results[1].author,
+        results[1].savings_pct,
+        results[1].test_count,
+        format_number(results[1].total_commands),
+    );
+    assert_eq!(row1.len(), row2.len(), "rows should have equal width");
+}
+
+#[test]
+fn display_version_with_introduced_at() {
+    let r = FilterSummary {
+        introduced_at: Some("0.2.3".to_string()),
+        ..make_summary("git push", true)
+    };
+    assert_eq!(display_version(&r), "v0.2.3");
+}
+
+#[test]
+fn display_version_without_introduced_at() {
+    let r = make_summary("git push", true);
+    assert_eq!(display_version(&r), "");
+}
+
+#[test]
+fn display_command_names_the_registry() {
+    let mut summary = make_summary("git push", true);
+    summary.registry = Some("acme".to_string());
+    assert_eq!(display_command(&summary), "git push [acme] [stdlib]");
+}
@@ -5,10 +5,13 @@
 //! line is individually matched against installed filters.
 //!
 //! Both entry points (`cmd_shell` for string mode, `cmd_shell_argv` for
-//! argv mode) delegate to the rewrite system and then to `sh -c`. Matched
-//! commands become `tokf run --no-mask-exit-code ...` which goes through
-//! the normal `cmd_run` path — no duplicated filter pipeline here.
+//! argv mode) delegate to the rewrite system and then to the real shell —
+//! `sh`, or the one named by `[shell]` in config.toml. Matched commands
+//! become `tokf run --no-mask-exit-code ...` which goes through the normal
+//! `run_cmd::cmd_run` path — no duplicated filter pipeline here.
 
+use tokf::history;
+use tokf::runner::Shell;
 use tokf::runtime::Runtime;
 
 /// Returns `true` if `flag` looks like a POSIX shell flag containing `-c`.
@@ -45,12 +48,16 @@ fn restore_original_path(rt: &Runtime) {
 /// both modes are protected from shim recursion.
 fn rewrite_and_delegate(rt: &Runtime, flags: &str, command: &str, verbose: bool) -> i32 {
     restore_original_path(rt);
+    let shell = configured_shell(rt);
 
     if rt.no_filter() {
         if verbose {
-            eprintln!("[tokf] shell: TOKF_NO_FILTER set, delegating to sh");
+            eprintln!(
+                "[tokf] shell: TOKF_NO_FILTER set, delegating to {}",
+                shell.program
+            );
         }
-        return delegate_to_real_shell(flags, command);
+        return delegate_to_real_shell(&shell, flags, command);
     }
 
     let options = tokf::rewrite::types::RewriteOptions {
@@ -60,13 +67,22 @@ fn rewrite_and_delegate(rt: &Runtime, flags: &str, command: &str, verbose: bool)
 
     if verbose {
         if rewritten == command {
-            eprintln!("[tokf] shell: no filter match, delegating to sh");
+            eprintln!(
+                "[tokf] shell: no filter match, delegating to {}",
+                shell.program
+            );
         } else {
             eprintln!("[tokf] shell: rewritten to: {rewritten}");
         }
     }
 
-    delegate_to_real_shell(flags, &rewritten)
+    delegate_to_real_shell(&shell, flags, &rewritten)
+}
+
+/// The shell to delegate to: `[shell]` from config.toml, or `sh`.
+fn configured_shell(rt: &Runtime) -> Shell {
+    let project_root = history::project_root_for(rt.cwd_or_empty());
+    history::ShellConfig::load(rt, Some(&project_root)).shell
 }
 
 /// Entry point for string shell mode.
@@ -81,14 +97,6 @@ pub fn cmd_shell(rt: &Runtime, flags: &str, command: &str) -> i32 {
     rewrite_and_delegate(rt, flags, command, rt.verbose())
 }
 
-/// Build a shell-safe command string by single-quoting each argument.
-pub fn quote_argv(args: &[String]) -> String {
-    args.iter()
-        .map(|a| format!("'{}'", a.replace('\'', "'\\''")))
-        .collect::<Vec<_>>()
-        .join(" ")
-}
-
 /// Entry point for argv shell mode.
 ///
 /// Called when tokf is invoked as `tokf -c cmd arg1 arg2 ...` (more than one
@@ -97,12 +105,12 @@ pub fn quote_argv(args: &[String]) -> String {
 ///
 /// Unlike string mode, argv mode uses the **unquoted** args for rewrite
 /// pattern matching (so `cargo fmt` matches instead of `'cargo' 'fmt'`),
-/// then falls back to the **quoted** form for safe shell delegation when
-/// no filter matches.
+/// then falls back to the **quoted** form (quoted for the configured shell)
+/// for safe shell delegation when no filter matches.
 ///
 /// The `flags` parameter is the original shell flag string (e.g. `-c`, `-cu`,
-/// `-ecu`) so that combined flags are forwarded to `sh` consistently with
-/// string mode.
+/// `-ecu`) so that combined flags are forwarded to a POSIX shell consistently
+/// with string mode.
 pub fn cmd_shell_argv(rt: &Runtime, flags: &str, args: &[String]) -> i32 {
     if args.is_empty() {
         return 0;
@@ -110,12 +118,17 @@ pub fn cmd_shell_argv(rt: &Runtime, flags: &str, args: &[String]) -> i32 {
 
     let verbose = rt.verbose();
     restore_original_path(rt);
+    let shell = configured_shell(rt);
+    let quoted = shell.quote_all(args);
 
     if rt.no_filter() {
         if verbose {
-            eprintln!("[tokf] shell: TOKF_NO_FILTER set, delegating to sh");
+            eprintln!(
+                "[tokf] shell: TOKF_NO_FILTER set, delegating to {}",
+                shell.program
+            );
         }
-        return delegate_to_real_shell(flags, &quote_argv(args));
+        return delegate_to_real_shell(&shell, flags, &quoted);
     }
 
     // Use unquoted args for rewrite pattern matching so filters can match.
@@ -127,31 +140,31 @@ pub fn cmd_shell_argv(rt: &Runtime, flags: &str, args: &[String]) -> i32 {
 
     if rewritten == unquoted {
         if verbose {
-            eprintln!("[tokf] shell: no filter match, delegating to sh");
+            eprintln!(
+                "[tokf] shell: no filter match, delegating to {}",
+                shell.program
+            );
         }
-        delegate_to_real_shell(flags, &quote_argv(args))
+        delegate_to_real_shell(&shell, flags, &quoted)
     } else {
         // The rewrite matched — substitute the unquoted portion with
-        // quoted args so `sh -c` preserves argument boundaries (e.g.
+        // quoted args so the shell preserves argument boundaries (e.g.
         // `-m "hello world"` stays as one arg, not two).
-        let safe_rewritten = rewritten.replacen(&unquoted, &quote_argv(args), 1);
+        let safe_rewritten = rewritten.replacen(&unquoted, &quoted, 1);
         if verbose {
             eprintln!("[tokf] shell: rewritten to: {safe_rewritten}");
         }
-        delegate_to_real_shell(flags, &safe_rewritten)
+        delegate_to_real_shell(&shell, flags, &safe_rewritten)
     }
 }
 
-/// Delegate to the real system shell, preserving the original flags.
+/// Delegate to the real shell, preserving the original flags where it
+/// understands them.
 ///
-/// Spawns `sh` with the given flags and command, waits for completion,
+/// Spawns `shell` with the given flags and command, waits for completion,
 /// and returns the exit code.
-fn delegate_to_real_shell(flags: &str, command: &str) -> i32 {
-    match std::process::Command::new("sh")
-        .arg(flags)
-        .arg(command)
-        .status()
-    {
+fn delegate_to_real_shell(shell: &Shell, flags: &str, command: &str) -> i32 {
+    match shell.command_with_flags(flags, command).status() {
         Ok(status) => {
             #[cfg(unix)]
             {
@@ -166,7 +179,7 @@ fn delegate_to_real_shell(flags: &str, command: &str) -> i32 {
             }
         }
         Err(e) => {
-            eprintln!("[tokf] shell: failed to run sh: {e}");
+            eprintln!("[tokf] shell: failed to run {}: {e}", shell.program);
             127
         }
     }
@@ -365,6 +378,10 @@ mod tests {
 
     // --- quote_argv ---
 
+    fn quote_argv(args: &[String]) -> String {
+        Shell::named("sh", None).unwrap().quote_all(args)
+    }
+
     #[test]
     fn quote_argv_simple() {
         let args: Vec<String> = vec!["cargo".into(), "fmt".into()];
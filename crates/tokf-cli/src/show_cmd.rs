@@ -1,13 +1,55 @@
+use serde::Serialize;
+
+use crate::ls_cmd::FilterInfo;
 use crate::resolve;
-use tokf::config;
+use tokf::config::types::FilterConfig;
+use tokf::config::variant::VariantResolution;
+use tokf::config::{self, ResolvedFilter};
 
 use tokf::runtime::Runtime;
 
-pub fn cmd_show(rt: &Runtime, filter: &str, hash: bool) -> i32 {
+/// What `tokf show` prints.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ShowMode {
+    /// The filter file as written.
+    Source,
+    /// `--hash`: the canonical content hash.
+    Hash,
+    /// `--resolved`: the config `tokf run` would use here, with `extends`
+    /// applied and the variant for the current directory selected, as TOML.
+    Resolved,
+    /// `--json`: the `--resolved` config and where it came from.
+    Json,
+}
+
+impl ShowMode {
+    pub const fn from_flags(hash: bool, resolved: bool, json: bool) -> Self {
+        if hash {
+            Self::Hash
+        } else if json {
+            Self::Json
+        } else if resolved {
+            Self::Resolved
+        } else {
+            Self::Source
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct ShowJson<'a> {
+    #[serde(flatten)]
+    info: FilterInfo<'a>,
+    config: &'a FilterConfig,
+    /// Variants only the command's output can decide between.
+    deferred_variants: Vec<&'a str>,
+}
+
+pub fn cmd_show(rt: &Runtime, filter: &str, mode: ShowMode) -> i32 {
     // Normalize: strip ".toml" suffix if present
     let filter_name = filter.strip_suffix(".toml").unwrap_or(filter);
 
-    let Ok(filters) = resolve::discover_filters(rt, false) else {
+    let Ok(filters) = resolve::discover_installed_filters(rt, false) else {
         eprintln!("[tokf] error: failed to discover filters");
         return 1;
     };
@@ -19,15 +61,27 @@ pub fn cmd_show(rt: &Runtime, filter: &str, hash: bool) -> i32 {
         return 1;
     };
 
-    if hash {
-        match tokf_common::hash::canonical_hash(&resolved.config) {
-            Ok(h) => println!("{h}"),
-            Err(e) => {
-                eprintln!("[tokf] error computing hash: {e}");
-                return 1;
-            }
+    match mode {
+        ShowMode::Source => {}
+        ShowMode::Hash => return print_hash(&resolved.config),
+        ShowMode::Resolved => {
+            let effective = effective_config(rt, resolved, &filters);
+            report_variants(resolved, &effective);
+            return print_resolved(&effective.config);
+        }
+        ShowMode::Json => {
+            let effective = effective_config(rt, resolved, &filters);
+            crate::output::print_json(&ShowJson {
+                info: FilterInfo::from(resolved),
+                config: &effective.config,
+                deferred_variants: effective
+                    .output_variants
+                    .iter()
+                    .map(|v| v.name.as_str())
+                    .collect(),
+            });
+            return 0;
         }
-        return 0;
     }
 
     let content = if resolved.priority == u8::MAX {
@@ -50,3 +104,67 @@ pub fn cmd_show(rt: &Runtime, filter: &str, hash: bool) -> i32 {
     print!("{content}");
     0
 }
+
+/// Phase A variant resolution against the current directory, as `tokf run`
+/// does before executing. A filter without variants is its own result.
+fn effective_config(
+    rt: &Runtime,
+    filter: &ResolvedFilter,
+    filters: &[ResolvedFilter],
+) -> VariantResolution {
+    if filter.config.variant.is_empty() {
+        return VariantResolution {
+            config: filter.config.clone(),
+            output_variants: vec![],
+        };
+    }
+    config::variant::resolve_variants(&filter.config, filters, rt.cwd_or_empty(), false)
+}
+
+/// Say on stderr when the printed config is not the filter's own, so the
+/// TOML on stdout stays valid.
+fn report_variants(filter: &ResolvedFilter, effective: &VariantResolution) {
+    if effective.config != filter.config {
+        eprintln!(
+            "[tokf] variant selected here: \"{}\"",
+            effective.config.command.first()
+        );
+    }
+    if !effective.output_variants.is_empty() {
+        let names: Vec<&str> = effective
+            .output_variants
+            .iter()
+            .map(|v| v.name.as_str())
+            .collect();
+        eprintln!(
+            "[tokf] decided on the command's output: {}",
+            names.join(", ")
+        );
+    }
+}
+
+fn print_hash(config: &FilterConfig) -> i32 {
+    match tokf_common::hash::canonical_hash(config) {
+        Ok(h) => {
+            println!("{h}");
+            0
+        }
+        Err(e) => {
+            eprintln!("[tokf] error computing hash: {e}");
+            1
+        }
+    }
+}
+
+fn print_resolved(config: &FilterConfig) -> i32 {
+    match toml::to_string(config) {
+        Ok(content) => {
+            print!("{content}");
+            0
+        }
+        Err(e) => {
+            eprintln!("[tokf] error serializing filter: {e}");
+            1
+        }
+    }
+}
@@ -1,11 +1,17 @@
+use tokf::auth::credentials::{self, LoadedAuth};
 use tokf::remote::{http, machine};
+use tokf::sync_core;
+use tokf::sync_reconcile;
+use tokf::sync_retry;
 use tokf::tracking;
 
 use tokf::runtime::Runtime;
 
 /// Handle the `tokf sync` command.
 ///
-/// In `--status` mode, prints the last sync time and pending event count.
+/// In status mode (`tokf sync status`), prints the sync state instead: pending
+/// events, the sync cursor, the registered machine, the server, when the
+/// token expires and any backoff left by failed syncs.
 /// Otherwise, performs a full sync of pending events to the remote server.
 ///
 /// # Errors
@@ -34,7 +40,13 @@ pub fn cmd_sync(rt: &Runtime, status: bool) -> anyhow::Result<i32> {
         return Ok(0);
     }
 
-    let result = tokf::sync_core::perform_sync(rt, &auth, &machine, &conn)?;
+    let result = sync_core::perform_sync(rt, &auth, &machine, &conn)?;
+    if let Some(reconciled) = result
+        .reconciled
+        .filter(sync_reconcile::Reconciled::changed)
+    {
+        eprintln!("[tokf] {}", reconciled.summary());
+    }
     eprintln!(
         "[tokf] Synced {} event(s). Cursor: {}.",
         result.synced_count, result.cursor
@@ -43,20 +55,95 @@ pub fn cmd_sync(rt: &Runtime, status: bool) -> anyhow::Result<i32> {
     Ok(0)
 }
 
+/// Handle `tokf sync reset-machine`: make the server resume this machine's
+/// sync from the local cursor, for a tracking database that started over.
+///
+/// # Errors
+///
+/// Returns an error if the user is not logged in, no machine is registered,
+/// the DB cannot be opened, or the server is unreachable.
+pub fn cmd_sync_reset_machine(rt: &Runtime) -> anyhow::Result<i32> {
+    let auth = http::load_auth(rt)
+        .map_err(|_| anyhow::anyhow!("not logged in — run `tokf auth login` first"))?;
+    let machine = machine::load(rt)
+        .ok_or_else(|| anyhow::anyhow!("machine not registered — run `tokf remote setup` first"))?;
+    let db_path = rt
+        .tracking_db_path()
+        .ok_or_else(|| anyhow::anyhow!("cannot determine tracking DB path"))?;
+    let conn = tracking::open_db(&db_path)?;
+
+    let reconciled = sync_reconcile::reset_machine(rt, &auth, &machine, &conn)?;
+    if reconciled.previous_server == reconciled.cursor {
+        eprintln!(
+            "[tokf] Server cursor already at event {}; nothing to reset.",
+            reconciled.cursor
+        );
+    } else {
+        eprintln!(
+            "[tokf] Server cursor reset from event {} to {}. Run `tokf sync` to upload {} pending event(s).",
+            reconciled.previous_server,
+            reconciled.cursor,
+            tracking::get_pending_count(&conn)?
+        );
+    }
+    Ok(0)
+}
+
 fn cmd_sync_status(rt: &Runtime) -> anyhow::Result<i32> {
     let db_path = rt
         .tracking_db_path()
         .ok_or_else(|| anyhow::anyhow!("cannot determine tracking DB path"))?;
     let conn = tracking::open_db(&db_path)?;
 
+    match credentials::load(rt) {
+        Some(auth) => {
+            println!("Server:         {}", auth.server_url);
+            println!("Account:        {}", auth.username);
+            println!("Token expires:  {}", token_expiry(&auth));
+        }
+        None => println!("Server:         not logged in — run `tokf auth login`"),
+    }
+    match machine::load(rt) {
+        Some(m) => println!("Machine:        {} ({})", m.machine_id, m.hostname),
+        None => println!("Machine:        not registered — run `tokf remote setup`"),
+    }
+
     let last_synced_at = tracking::get_last_synced_at(&conn)?;
-    let pending = tracking::get_pending_count(&conn)?;
+    println!("Last synced ID: {}", tracking::get_last_synced_id(&conn)?);
+    println!(
+        "Last sync:      {}",
+        last_synced_at.as_deref().unwrap_or("never")
+    );
+    println!("Pending events: {}", tracking::get_pending_count(&conn)?);
 
-    match last_synced_at {
-        Some(ts) => println!("Last sync: {ts}"),
-        None => println!("Last sync: Never"),
+    let retry = sync_retry::load(&conn)?;
+    if retry.attempts > 0 {
+        println!(
+            "Retry:          {} failed attempt(s); background sync resumes after {}",
+            retry.attempts,
+            sync_core::unix_to_iso8601(retry.next_attempt_at)
+        );
+    }
+    if retry.batch_size < sync_retry::MAX_BATCH {
+        println!(
+            "Batch size:     {} (reduced after server errors)",
+            retry.batch_size
+        );
     }
-    println!("Pending events: {pending}");
 
     Ok(0)
 }
+
+/// When the token expires, and how far off that is.
+fn token_expiry(auth: &LoadedAuth) -> String {
+    if auth.expires_at == 0 {
+        return "unknown".to_string();
+    }
+    let at = sync_core::unix_to_iso8601(auth.expires_at);
+    if auth.is_expired() {
+        return format!("{at} (expired — run `tokf auth login`)");
+    }
+    let days = auth.expires_at.saturating_sub(sync_core::unix_now()) / 86_400;
+    let unit = if days == 1 { "day" } else { "days" };
+    format!("{at} (in {days} {unit})")
+}
@@ -5,9 +5,13 @@ use std::path::PathBuf;
 use rusqlite::Connection;
 
 use crate::auth::credentials::LoadedAuth;
+use crate::history::SyncConfig;
+use crate::remote::async_http::AsyncClient;
 use crate::remote::http::Client;
 use crate::remote::machine::StoredMachine;
 use crate::remote::sync_client::{SyncEvent, SyncRequest};
+use crate::sync_reconcile::{self, Reconciled};
+use crate::sync_retry;
 use crate::tracking;
 
 use crate::runtime::Runtime;
@@ -40,13 +44,7 @@ impl SyncLock {
         }
 
         // File exists — check whether it's stale (older than LOCK_STALE_SECS).
-        let stale = fs::metadata(&path)
-            .and_then(|m| m.modified())
-            .ok()
-            .and_then(|mtime| mtime.elapsed().ok())
-            .is_some_and(|age| age.as_secs() > LOCK_STALE_SECS);
-
-        if !stale {
+        if lock_is_held(&path) {
             return None; // another sync is still running
         }
 
@@ -58,21 +56,43 @@ impl SyncLock {
     }
 }
 
+/// Whether the lock file at `path` exists and is not yet stale.
+fn lock_is_held(path: &std::path::Path) -> bool {
+    fs::metadata(path)
+        .and_then(|m| m.modified())
+        .ok()
+        .and_then(|mtime| mtime.elapsed().ok())
+        .is_some_and(|age| age.as_secs() <= LOCK_STALE_SECS)
+}
+
+/// Whether another sync currently holds the sync lock.
+pub fn sync_running(rt: &Runtime) -> bool {
+    lock_path(rt).is_some_and(|path| lock_is_held(&path))
+}
+
 impl Drop for SyncLock {
     fn drop(&mut self) {
         let _ = fs::remove_file(&self.path);
     }
 }
 
-/// Generate a UTC ISO 8601 timestamp string without external dependencies.
-#[allow(clippy::cast_possible_wrap, clippy::cast_sign_loss)]
-pub fn utc_now_iso8601() -> String {
+/// Seconds since the Unix epoch.
+pub fn unix_now() -> u64 {
     use std::time::{SystemTime, UNIX_EPOCH};
-    let secs = SystemTime::now()
+    SystemTime::now()
         .duration_since(UNIX_EPOCH)
         .unwrap_or_default()
-        .as_secs();
+        .as_secs()
+}
+
+/// Generate a UTC ISO 8601 timestamp string without external dependencies.
+pub fn utc_now_iso8601() -> String {
+    unix_to_iso8601(unix_now())
+}
 
+/// Format a Unix timestamp as a UTC ISO 8601 string.
+#[allow(clippy::cast_possible_wrap, clippy::cast_sign_loss)]
+pub fn unix_to_iso8601(secs: u64) -> String {
     // Convert Unix timestamp to date/time components.
     let days = secs / 86400;
     let time_of_day = secs % 86400;
@@ -95,10 +115,50 @@ pub fn utc_now_iso8601() -> String {
     format!("{y:04}-{m:02}-{d:02}T{hours:02}:{minutes:02}:{seconds:02}Z")
 }
 
+/// Whether a background sync is due.
+///
+/// It is when pending events have reached `auto_sync_threshold`, or some are
+/// pending and the last sync (if any) is at least `auto_sync_interval_minutes`
+/// old. A `0` turns either trigger off. Neither applies while a failed sync's
+/// backoff is still running.
+///
+/// Does not consult [`SyncConfig::auto_enabled`]; callers check that first.
+///
+/// # Errors
+///
+/// Returns an error if the DB query fails.
+pub fn auto_sync_due(conn: &Connection, config: &SyncConfig) -> anyhow::Result<bool> {
+    let pending = tracking::get_pending_count(conn)?;
+    if pending == 0 || !sync_retry::load(conn)?.eligible(unix_now()) {
+        return Ok(false);
+    }
+    let threshold = config.auto_sync_threshold;
+    if threshold != 0 && pending >= i64::from(threshold) {
+        return Ok(true);
+    }
+    let interval = config.auto_sync_interval_minutes;
+    if interval == 0 {
+        return Ok(false);
+    }
+    let Some(last) = tracking::get_last_synced_at(conn)? else {
+        return Ok(true);
+    };
+    let age_minutes: Option<f64> = conn.query_row(
+        "SELECT (julianday('now') - julianday(?1)) * 1440",
+        [&last],
+        |r| r.get(0),
+    )?;
+    // An unparseable timestamp counts as old: syncing rewrites it.
+    Ok(age_minutes.is_none_or(|age| age >= f64::from(interval)))
+}
+
 /// Result of a sync operation.
 pub struct SyncResult {
     pub synced_count: usize,
     pub cursor: i64,
+    /// Set when the server skipped a whole batch and the cursors had to be
+    /// renegotiated.
+    pub reconciled: Option<Reconciled>,
 }
 
 /// Convert a `SyncableEvent` to a `SyncEvent` for the remote API.
@@ -118,8 +178,8 @@ fn to_sync_event(e: &tracking::SyncableEvent) -> SyncEvent {
 /// Perform a full sync of all pending events to the remote server.
 ///
 /// Acquires a file lock to prevent concurrent syncs. Batches events in chunks
-/// of 500 (the DB query limit), sending each batch to the server and advancing
-/// the cursor. Continues until no more events remain.
+/// of up to 500 (fewer after repeated server errors), sending each batch to
+/// the server and advancing the cursor. Continues until no more events remain.
 ///
 /// # Errors
 ///
@@ -133,50 +193,89 @@ pub fn perform_sync(
 ) -> anyhow::Result<SyncResult> {
     let _lock =
         SyncLock::acquire(rt).ok_or_else(|| anyhow::anyhow!("another sync is already running"))?;
-
     let http_client = Client::new(rt, &auth.server_url, Some(&auth.token))?;
+    http_client.block_on(sync_pending(http_client.as_async(), machine, conn))
+}
 
+/// Async [`perform_sync`], for callers already inside a Tokio runtime.
+///
+/// The future is not `Send`: it holds the `SQLite` connection, which is not
+/// `Sync`, across requests. Await it in place rather than spawning it.
+///
+/// # Errors
+///
+/// See [`perform_sync`].
+#[allow(clippy::future_not_send)]
+pub async fn perform_sync_async(
+    rt: &Runtime,
+    auth: &LoadedAuth,
+    machine: &StoredMachine,
+    conn: &Connection,
+) -> anyhow::Result<SyncResult> {
+    let _lock =
+        SyncLock::acquire(rt).ok_or_else(|| anyhow::anyhow!("another sync is already running"))?;
+    let http_client = AsyncClient::new(rt, &auth.server_url, Some(&auth.token))?;
+    sync_pending(&http_client, machine, conn).await
+}
+
+/// Send every event past the sync cursor, batch by batch.
+///
+/// Batches are sized by the persisted [`sync_retry::RetryState`]. A failure
+/// is recorded there before it is returned, so the next background sync
+/// backs off; each accepted batch clears the backoff.
+///
+/// A batch the server skips entirely means its cursor is at or past every
+/// event sent. That is either a retry of a batch it already stored, or a
+/// local database that started over; the cursors are renegotiated once (see
+/// [`sync_reconcile`]) and syncing resumes from the agreed point.
+#[allow(clippy::future_not_send)]
+async fn sync_pending(
+    http_client: &AsyncClient,
+    machine: &StoredMachine,
+    conn: &Connection,
+) -> anyhow::Result<SyncResult> {
+    let mut retry = sync_retry::load(conn)?;
     let mut total_synced = 0usize;
     let mut cursor = tracking::get_last_synced_id(conn)?;
+    let mut reconciled = None;
 
     loop {
-        let events = tracking::get_events_since(conn, cursor)?;
+        let limit = retry.batch_size;
+        let events = tracking::get_events_since(conn, cursor, limit)?;
         if events.is_empty() {
             break;
         }
 
-        let sync_events: Vec<SyncEvent> = events.iter().map(to_sync_event).collect();
-
         let req = SyncRequest {
             machine_id: machine.machine_id.clone(),
             last_event_id: cursor,
-            events: sync_events,
+            events: events.iter().map(to_sync_event).collect(),
         };
 
-        let response = crate::remote::retry::with_retry("sync", || {
-            crate::remote::sync_client::sync_events(&http_client, &req)
-        })?;
-
-        total_synced += response.accepted;
-        let new_cursor = response.cursor;
-
-        // Guard: if the server returned a cursor that didn't advance, bail out
-        // to prevent an infinite loop (e.g. server bug or desync).
-        if new_cursor <= cursor {
-            anyhow::bail!(
-                "sync stalled: server returned cursor {new_cursor} (was {cursor}). \
-                 This may indicate a server issue — try again later."
-            );
+        let (accepted, new_cursor) = match send_batch(http_client, &req).await {
+            Ok(sent) => sent,
+            Err(e) => return Err(record_failure(conn, &mut retry, e)),
+        };
+        if accepted == 0 && reconciled.is_none() {
+            let agreed = sync_reconcile::reconcile(http_client, machine, conn, false)
+                .await
+                .map_err(|e| record_failure(conn, &mut retry, e))?;
+            cursor = agreed.cursor;
+            reconciled = Some(agreed);
+            continue;
         }
+        total_synced += accepted;
         cursor = new_cursor;
+        retry.record_success();
 
         let tx = conn.unchecked_transaction()?;
         tracking::set_last_synced_id(&tx, cursor)?;
         tracking::set_last_synced_at(&tx, &utc_now_iso8601())?;
+        sync_retry::save(&tx, &retry)?;
         tx.commit()?;
 
-        // If we got fewer than 500, we've reached the end
-        if events.len() < 500 {
+        // A short batch means we've reached the end.
+        if events.len() < limit as usize {
             break;
         }
     }
@@ -184,9 +283,42 @@ pub fn perform_sync(
     Ok(SyncResult {
         synced_count: total_synced,
         cursor,
+        reconciled,
     })
 }
 
+/// Record a failed sync in `retry` and hand the error back.
+fn record_failure(
+    conn: &Connection,
+    retry: &mut sync_retry::RetryState,
+    e: anyhow::Error,
+) -> anyhow::Error {
+    retry.record_failure(unix_now(), sync_retry::is_server_error(&e));
+    // The sync error is the one worth reporting.
+    let _ = sync_retry::save(conn, retry);
+    e
+}
+
+/// Send one batch, returning how many events the server accepted and the
+/// cursor it advanced to.
+async fn send_batch(http_client: &AsyncClient, req: &SyncRequest) -> anyhow::Result<(usize, i64)> {
+    let response = crate::remote::retry::with_retry_async("sync", || {
+        crate::remote::sync_client::sync_events_async(http_client, req)
+    })
+    .await?;
+
+    // Guard: if the server returned a cursor that didn't advance, bail out
+    // to prevent an infinite loop (e.g. server bug or desync).
+    let (cursor, new_cursor) = (req.last_event_id, response.cursor);
+    if new_cursor <= cursor {
+        anyhow::bail!(
+            "sync stalled: server returned cursor {new_cursor} (was {cursor}). \
+             This may indicate a server issue — try again later."
+        );
+    }
+    Ok((response.accepted, new_cursor))
+}
+
 #[cfg(test)]
 #[allow(clippy::unwrap_used)]
 mod tests {
@@ -288,6 +420,12 @@ mod tests {
         assert!(result.filter_hash.is_none());
     }
 
+    #[test]
+    fn unix_to_iso8601_formats_known_instants() {
+        assert_eq!(unix_to_iso8601(0), "1970-01-01T00:00:00Z");
+        assert_eq!(unix_to_iso8601(1_709_210_096), "2024-02-29T12:34:56Z");
+    }
+
     #[test]
     fn utc_now_iso8601_format() {
         let ts = utc_now_iso8601();
@@ -311,4 +449,74 @@ mod tests {
         assert!((1..=12).contains(&month), "month out of range: {month}");
         assert!((1..=31).contains(&day), "day out of range: {day}");
     }
+
+    #[test]
+    fn sync_running_follows_the_lock() {
+        let rt = Runtime::isolated();
+        assert!(!sync_running(&rt));
+        let lock = SyncLock::acquire(&rt);
+        assert!(sync_running(&rt));
+        drop(lock);
+        assert!(!sync_running(&rt));
+    }
+
+    fn db_with_events(n: usize) -> (tempfile::TempDir, Connection) {
+        let dir = tempfile::TempDir::new().unwrap();
+        let conn = tracking::open_db(&dir.path().join("tracking.db")).unwrap();
+        for _ in 0..n {
+            let event = tracking::build_event("git status", None, None, 400, 40, 400, 1, 0, false);
+            tracking::record_event(&conn, &event).unwrap();
+        }
+        (dir, conn)
+    }
+
+    fn config(threshold: u32, interval: u32) -> SyncConfig {
+        SyncConfig {
+            auto_sync_threshold: threshold,
+            auto_sync_interval_minutes: interval,
+            ..SyncConfig::default()
+        }
+    }
+
+    #[test]
+    fn auto_sync_due_on_pending_threshold() {
+        let (_dir, conn) = db_with_events(3);
+        tracking::set_last_synced_at(&conn, &utc_now_iso8601()).unwrap();
+        assert!(auto_sync_due(&conn, &config(3, 60)).unwrap());
+        assert!(!auto_sync_due(&conn, &config(4, 60)).unwrap());
+        assert!(!auto_sync_due(&conn, &config(0, 0)).unwrap());
+    }
+
+    #[test]
+    fn auto_sync_due_on_last_sync_age() {
+        let (_dir, conn) = db_with_events(1);
+        // Never synced counts as overdue.
+        assert!(auto_sync_due(&conn, &config(100, 60)).unwrap());
+
+        tracking::set_last_synced_at(&conn, &utc_now_iso8601()).unwrap();
+        assert!(!auto_sync_due(&conn, &config(100, 60)).unwrap());
+
+        tracking::set_last_synced_at(&conn, "2020-01-01T00:00:00Z").unwrap();
+        assert!(auto_sync_due(&conn, &config(100, 60)).unwrap());
+        assert!(!auto_sync_due(&conn, &config(100, 0)).unwrap());
+    }
+
+    #[test]
+    fn auto_sync_waits_out_the_backoff() {
+        let (_dir, conn) = db_with_events(3);
+        let mut retry = sync_retry::RetryState::default();
+        retry.record_failure(unix_now(), true);
+        sync_retry::save(&conn, &retry).unwrap();
+        assert!(!auto_sync_due(&conn, &config(1, 1)).unwrap());
+
+        retry.next_attempt_at = unix_now() - 1;
+        sync_retry::save(&conn, &retry).unwrap();
+        assert!(auto_sync_due(&conn, &config(1, 1)).unwrap());
+    }
+
+    #[test]
+    fn auto_sync_never_due_without_pending_events() {
+        let (_dir, conn) = db_with_events(0);
+        assert!(!auto_sync_due(&conn, &config(1, 1)).unwrap());
+    }
 }
@@ -0,0 +1,126 @@
+//! Repairing a sync cursor that disagrees with the server's.
+//!
+//! The server keeps a cursor per machine and ignores events at or below it.
+//! When the local tracking database starts over — a reimaged laptop, a
+//! deleted `tracking.db` — its event ids restart from 1 while the server's
+//! cursor does not, and every new event would be silently dropped. The
+//! client and server then negotiate a resume point through
+//! `POST /api/sync/reconcile`: automatically when a sync batch is skipped
+//! entirely, or unconditionally with `tokf sync reset-machine`.
+
+use rusqlite::Connection;
+
+use crate::auth::credentials::LoadedAuth;
+use crate::remote::async_http::AsyncClient;
+use crate::remote::http::Client;
+use crate::remote::machine::StoredMachine;
+use crate::remote::sync_client::{ReconcileRequest, reconcile_async};
+use crate::runtime::Runtime;
+use crate::sync_core::SyncLock;
+use crate::tracking;
+
+/// The outcome of a cursor negotiation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Reconciled {
+    /// Where syncing resumes; both cursors now hold this value.
+    pub cursor: i64,
+    /// The server's cursor before the negotiation.
+    pub previous_server: i64,
+    /// The local cursor before the negotiation.
+    pub previous_local: i64,
+}
+
+impl Reconciled {
+    /// Whether either side's cursor moved.
+    pub const fn changed(&self) -> bool {
+        self.cursor != self.previous_server || self.cursor != self.previous_local
+    }
+
+    /// One line describing the negotiation, for a `[tokf]` notice.
+    pub fn summary(&self) -> String {
+        format!(
+            "sync cursor repaired: server was at event {}, this machine at {}; resuming after {}",
+            self.previous_server, self.previous_local, self.cursor
+        )
+    }
+}
+
+/// Negotiate the cursor with the server and store the result locally.
+///
+/// With `reset`, the server takes the local cursor as is; otherwise it
+/// decides — see the server's `resume_point`.
+///
+/// # Errors
+///
+/// Returns an error if the DB cannot be read or written or the request fails.
+#[allow(clippy::future_not_send)]
+pub async fn reconcile(
+    http_client: &AsyncClient,
+    machine: &StoredMachine,
+    conn: &Connection,
+    reset: bool,
+) -> anyhow::Result<Reconciled> {
+    let last_synced_id = tracking::get_last_synced_id(conn)?;
+    let req = ReconcileRequest {
+        machine_id: machine.machine_id.clone(),
+        last_synced_id,
+        max_event_id: tracking::get_max_event_id(conn)?.max(last_synced_id),
+        reset,
+    };
+    let resp = reconcile_async(http_client, &req).await?;
+    tracking::set_last_synced_id(conn, resp.cursor)?;
+    Ok(Reconciled {
+        cursor: resp.cursor,
+        previous_server: resp.previous_cursor,
+        previous_local: last_synced_id,
+    })
+}
+
+/// Tell the server this machine's tracking history started over, so the
+/// events it records from now on are not mistaken for ones already synced.
+///
+/// # Errors
+///
+/// Returns an error if another sync is running, the DB cannot be read or
+/// written, or the request fails.
+pub fn reset_machine(
+    rt: &Runtime,
+    auth: &LoadedAuth,
+    machine: &StoredMachine,
+    conn: &Connection,
+) -> anyhow::Result<Reconciled> {
+    let _lock =
+        SyncLock::acquire(rt).ok_or_else(|| anyhow::anyhow!("another sync is already running"))?;
+    let http_client = Client::new(rt, &auth.server_url, Some(&auth.token))?;
+    http_client.block_on(reconcile(http_client.as_async(), machine, conn, true))
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn changed_compares_against_both_sides() {
+        let same = Reconciled {
+            cursor: 5,
+            previous_server: 5,
+            previous_local: 5,
+        };
+        assert!(!same.changed());
+        assert!(
+            Reconciled {
+                previous_server: 9,
+                ..same
+            }
+            .changed()
+        );
+        assert!(
+            Reconciled {
+                previous_local: 0,
+                ..same
+            }
+            .changed()
+        );
+    }
+}
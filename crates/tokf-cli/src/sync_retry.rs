@@ -0,0 +1,217 @@
+//! Retry state for `tokf sync`, kept in the tracking DB's `sync_state` table
+//! so that backoff carries over from one invocation to the next.
+//!
+//! A failed sync pushes the next background attempt out exponentially (1 min,
+//! 2 min, 4 min, … up to 6 h). Repeated 5xx responses also halve the batch
+//! size, down to [`MIN_BATCH`]; each successful batch doubles it back towards
+//! [`MAX_BATCH`].
+
+use rusqlite::{Connection, OptionalExtension as _};
+
+use anyhow::Context as _;
+
+use crate::remote::RemoteError;
+
+/// Largest batch sent in one request (also the DB query cap).
+pub const MAX_BATCH: u32 = 500;
+
+/// Smallest batch repeated server errors can shrink to.
+pub const MIN_BATCH: u32 = 25;
+
+/// Wait after the first failure; doubles with each one after.
+const BASE_BACKOFF_SECS: u64 = 60;
+
+/// Longest wait between attempts.
+const MAX_BACKOFF_SECS: u64 = 6 * 60 * 60;
+
+/// Consecutive 5xx failures before batches start shrinking.
+const SHRINK_AFTER: u32 = 2;
+
+/// Where a failing sync stands.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryState {
+    /// Consecutive failed sync attempts.
+    pub attempts: u32,
+    /// Unix time before which a background sync should not try again.
+    pub next_attempt_at: u64,
+    /// Consecutive failures that were server errors (5xx).
+    pub server_errors: u32,
+    /// Events to send per request.
+    pub batch_size: u32,
+}
+
+impl Default for RetryState {
+    fn default() -> Self {
+        Self {
+            attempts: 0,
+            next_attempt_at: 0,
+            server_errors: 0,
+            batch_size: MAX_BATCH,
+        }
+    }
+}
+
+impl RetryState {
+    /// Whether the backoff has run out at `now` (Unix seconds).
+    pub const fn eligible(&self, now: u64) -> bool {
+        now >= self.next_attempt_at
+    }
+
+    /// Note a failed attempt at `now`, pushing the next one out.
+    pub fn record_failure(&mut self, now: u64, server_error: bool) {
+        self.attempts = self.attempts.saturating_add(1);
+        self.next_attempt_at = now + backoff_secs(self.attempts);
+        if server_error {
+            self.server_errors = self.server_errors.saturating_add(1);
+            if self.server_errors >= SHRINK_AFTER {
+                self.batch_size = (self.batch_size / 2).max(MIN_BATCH);
+            }
+        } else {
+            self.server_errors = 0;
+        }
+    }
+
+    /// Note a batch the server accepted: clear the backoff and grow the batch.
+    pub fn record_success(&mut self) {
+        *self = Self {
+            batch_size: self.batch_size.saturating_mul(2).min(MAX_BATCH),
+            ..Self::default()
+        };
+    }
+}
+
+/// The wait after `attempts` consecutive failures.
+fn backoff_secs(attempts: u32) -> u64 {
+    let doublings = attempts.saturating_sub(1).min(16);
+    (BASE_BACKOFF_SECS << doublings).min(MAX_BACKOFF_SECS)
+}
+
+/// Whether `err` came from the server answering 5xx.
+pub fn is_server_error(err: &anyhow::Error) -> bool {
+    matches!(
+        err.downcast_ref::<RemoteError>(),
+        Some(RemoteError::ServerError { .. })
+    )
+}
+
+/// Read the retry state; missing or unparseable keys take their defaults.
+///
+/// # Errors
+/// Returns an error if the SQL query fails.
+pub fn load(conn: &Connection) -> anyhow::Result<RetryState> {
+    let defaults = RetryState::default();
+    Ok(RetryState {
+        attempts: get(conn, "retry_attempts")?.unwrap_or(defaults.attempts),
+        next_attempt_at: get(conn, "retry_next_at")?.unwrap_or(defaults.next_attempt_at),
+        server_errors: get(conn, "retry_server_errors")?.unwrap_or(defaults.server_errors),
+        batch_size: get(conn, "sync_batch_size")?
+            .map_or(defaults.batch_size, |n: u32| n.clamp(MIN_BATCH, MAX_BATCH)),
+    })
+}
+
+/// Persist the retry state.
+///
+/// # Errors
+/// Returns an error if the SQL INSERT/UPDATE fails.
+pub fn save(conn: &Connection, state: &RetryState) -> anyhow::Result<()> {
+    set(conn, "retry_attempts", state.attempts)?;
+    set(conn, "retry_next_at", state.next_attempt_at)?;
+    set(conn, "retry_server_errors", state.server_errors)?;
+    set(conn, "sync_batch_size", state.batch_size)
+}
+
+fn get<T: std::str::FromStr>(conn: &Connection, key: &str) -> anyhow::Result<Option<T>> {
+    let value: Option<String> = conn
+        .query_row("SELECT value FROM sync_state WHERE key = ?1", [key], |r| {
+            r.get(0)
+        })
+        .optional()
+        .with_context(|| format!("query {key}"))?;
+    Ok(value.and_then(|v| v.parse().ok()))
+}
+
+fn set(conn: &Connection, key: &str, value: impl std::fmt::Display) -> anyhow::Result<()> {
+    conn.execute(
+        "INSERT INTO sync_state (key, value) VALUES (?1, ?2)
+         ON CONFLICT(key) DO UPDATE SET value = excluded.value",
+        rusqlite::params![key, value.to_string()],
+    )
+    .with_context(|| format!("set {key}"))?;
+    Ok(())
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn backoff_doubles_up_to_the_cap() {
+        let mut state = RetryState::default();
+        let waits: Vec<u64> = (0..12)
+            .map(|_| {
+                state.record_failure(1_000, false);
+                state.next_attempt_at - 1_000
+            })
+            .collect();
+        assert_eq!(&waits[..4], &[60, 120, 240, 480]);
+        assert_eq!(*waits.last().unwrap(), MAX_BACKOFF_SECS);
+        assert!(!state.eligible(1_000));
+        assert!(state.eligible(1_000 + MAX_BACKOFF_SECS));
+    }
+
+    #[test]
+    fn repeated_server_errors_shrink_the_batch() {
+        let mut state = RetryState::default();
+        state.record_failure(0, true);
+        assert_eq!(state.batch_size, MAX_BATCH, "one 5xx is not a pattern");
+        state.record_failure(0, true);
+        assert_eq!(state.batch_size, 250);
+        for _ in 0..10 {
+            state.record_failure(0, true);
+        }
+        assert_eq!(state.batch_size, MIN_BATCH);
+
+        // A non-5xx failure breaks the run without restoring the size.
+        state.record_failure(0, false);
+        assert_eq!(state.server_errors, 0);
+        assert_eq!(state.batch_size, MIN_BATCH);
+    }
+
+    #[test]
+    fn success_clears_backoff_and_grows_the_batch() {
+        let mut state = RetryState {
+            attempts: 4,
+            next_attempt_at: u64::MAX,
+            server_errors: 4,
+            batch_size: 100,
+        };
+        state.record_success();
+        assert_eq!(
+            state,
+            RetryState {
+                batch_size: 200,
+                ..RetryState::default()
+            }
+        );
+        state.record_success();
+        state.record_success();
+        assert_eq!(state.batch_size, MAX_BATCH);
+    }
+
+    #[test]
+    fn state_roundtrips_through_sync_state() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let conn = crate::tracking::open_db(&dir.path().join("tracking.db")).unwrap();
+        assert_eq!(load(&conn).unwrap(), RetryState::default());
+
+        let state = RetryState {
+            attempts: 3,
+            next_attempt_at: 1_700_000_000,
+            server_errors: 2,
+            batch_size: 125,
+        };
+        save(&conn, &state).unwrap();
+        assert_eq!(load(&conn).unwrap(), state);
+    }
+}
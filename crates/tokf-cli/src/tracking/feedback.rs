@@ -0,0 +1,217 @@
+//! Agent feedback on filters, recorded by the `PostToolUse` hook.
+//!
+//! Two signals suggest a filter hid too much: the agent ran the same command
+//! again straight away, or asked for the raw output of a filtered run.
+//! `tokf gain --confusion` sets them against each filter's run count.
+
+use anyhow::Context as _;
+use rusqlite::{Connection, OptionalExtension as _};
+use serde::Serialize;
+
+use super::TimeRange;
+
+/// A sign that the agent did not get what it needed from a filter.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FeedbackSignal {
+    /// The same command ran again as the agent's next shell call.
+    Rerun,
+    /// The agent asked for the unfiltered output (`tokf raw`, `tokf history show`).
+    Raw,
+}
+
+impl FeedbackSignal {
+    const fn as_str(self) -> &'static str {
+        match self {
+            Self::Rerun => "rerun",
+            Self::Raw => "raw",
+        }
+    }
+}
+
+/// The last shell command an agent session ran, and the filter it went through.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HookSession {
+    pub command: String,
+    pub filter_name: Option<String>,
+}
+
+/// Per-filter confusion, for `tokf gain --confusion`.
+#[derive(Debug, Clone, Serialize)]
+pub struct FilterConfusion {
+    pub filter_name: String,
+    /// Filtered runs in the range.
+    pub runs: i64,
+    pub reruns: i64,
+    pub raw_requests: i64,
+    /// Signals per run, as a percentage (can pass 100).
+    pub confusion_pct: f64,
+}
+
+/// Create the feedback tables. Called from [`super::open_db`].
+pub(super) fn create_tables(conn: &Connection) -> anyhow::Result<()> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS filter_feedback (
+            id          INTEGER PRIMARY KEY AUTOINCREMENT,
+            timestamp   TEXT NOT NULL,
+            filter_name TEXT NOT NULL,
+            signal      TEXT NOT NULL,
+            project     TEXT NOT NULL DEFAULT ''
+        );
+        CREATE TABLE IF NOT EXISTS hook_sessions (
+            session_id  TEXT PRIMARY KEY,
+            command     TEXT NOT NULL,
+            filter_name TEXT,
+            updated_at  TEXT NOT NULL
+        );",
+    )
+    .context("create feedback tables")
+}
+
+/// Record one signal against `filter_name`.
+///
+/// # Errors
+/// Returns an error if the INSERT fails.
+pub fn record_feedback(
+    conn: &Connection,
+    filter_name: &str,
+    signal: FeedbackSignal,
+    project: &str,
+) -> anyhow::Result<()> {
+    conn.execute(
+        "INSERT INTO filter_feedback (timestamp, filter_name, signal, project)
+         VALUES (strftime('%Y-%m-%dT%H:%M:%SZ','now'), ?1, ?2, ?3)",
+        rusqlite::params![filter_name, signal.as_str(), project],
+    )
+    .context("insert feedback")?;
+    Ok(())
+}
+
+/// The last command recorded for `session_id`.
+///
+/// # Errors
+/// Returns an error if the query fails.
+pub fn get_session(conn: &Connection, session_id: &str) -> anyhow::Result<Option<HookSession>> {
+    conn.query_row(
+        "SELECT command, filter_name FROM hook_sessions WHERE session_id = ?1",
+        [session_id],
+        |r| {
+            Ok(HookSession {
+                command: r.get(0)?,
+                filter_name: r.get(1)?,
+            })
+        },
+    )
+    .optional()
+    .context("query hook session")
+}
+
+/// Remember `session` as the last command of `session_id`, and forget
+/// sessions idle for a day.
+///
+/// # Errors
+/// Returns an error if the write fails.
+pub fn save_session(
+    conn: &Connection,
+    session_id: &str,
+    session: &HookSession,
+) -> anyhow::Result<()> {
+    conn.execute(
+        "INSERT INTO hook_sessions (session_id, command, filter_name, updated_at)
+         VALUES (?1, ?2, ?3, strftime('%Y-%m-%dT%H:%M:%SZ','now'))
+         ON CONFLICT(session_id) DO UPDATE SET
+            command = excluded.command,
+            filter_name = excluded.filter_name,
+            updated_at = excluded.updated_at",
+        rusqlite::params![session_id, session.command, session.filter_name],
+    )
+    .context("save hook session")?;
+    conn.execute(
+        "DELETE FROM hook_sessions
+         WHERE updated_at < strftime('%Y-%m-%dT%H:%M:%SZ','now','-1 day')",
+        [],
+    )
+    .context("prune hook sessions")?;
+    Ok(())
+}
+
+/// The filter of the run `shell_command` just made, `None` when it was not filtered.
+///
+/// That run is the newest event of the last minute in `project` whose command
+/// `shell_command` contains (it may carry a `tokf run` prefix).
+///
+/// # Errors
+/// Returns an error if the query fails.
+pub fn recent_filter_for(
+    conn: &Connection,
+    shell_command: &str,
+    project: &str,
+) -> anyhow::Result<Option<String>> {
+    let name: Option<Option<String>> = conn
+        .query_row(
+            "SELECT filter_name FROM events
+             WHERE project = ?2
+               AND command != ''
+               AND instr(?1, command) > 0
+               AND timestamp >= strftime('%Y-%m-%dT%H:%M:%SZ','now','-60 seconds')
+             ORDER BY id DESC LIMIT 1",
+            rusqlite::params![shell_command, project],
+            |r| r.get(0),
+        )
+        .optional()
+        .context("query recent event")?;
+    Ok(name.flatten())
+}
+
+/// Filters with at least one feedback signal in `range`, most confusing first.
+///
+/// # Errors
+/// Returns an error if the SQL query fails.
+pub fn query_confusion(
+    conn: &Connection,
+    range: &TimeRange,
+) -> anyhow::Result<Vec<FilterConfusion>> {
+    let mut stmt = conn.prepare(
+        "WITH signals AS (
+             SELECT filter_name,
+                    SUM(signal = 'rerun') AS reruns,
+                    SUM(signal = 'raw') AS raw_requests
+             FROM filter_feedback
+             WHERE (?1 IS NULL OR timestamp >= ?1) AND (?2 IS NULL OR timestamp <= ?2)
+             GROUP BY filter_name
+         )
+         SELECT s.filter_name,
+                (SELECT COUNT(*) FROM events e
+                 WHERE e.filter_name = s.filter_name
+                   AND (?1 IS NULL OR e.timestamp >= ?1) AND (?2 IS NULL OR e.timestamp <= ?2)),
+                s.reruns, s.raw_requests
+         FROM signals s",
+    )?;
+    let rows = stmt.query_map(rusqlite::params![range.lower(), range.upper()], |r| {
+        let runs: i64 = r.get(1)?;
+        let reruns: i64 = r.get(2)?;
+        let raw_requests: i64 = r.get(3)?;
+        #[allow(clippy::cast_precision_loss)]
+        let confusion_pct = if runs == 0 {
+            0.0
+        } else {
+            (reruns + raw_requests) as f64 / runs as f64 * 100.0
+        };
+        Ok(FilterConfusion {
+            filter_name: r.get(0)?,
+            runs,
+            reruns,
+            raw_requests,
+            confusion_pct,
+        })
+    })?;
+    let mut result = Vec::new();
+    for row in rows {
+        result.push(row.context("read confusion row")?);
+    }
+    result.sort_by(|a, b| {
+        b.confusion_pct
+            .total_cmp(&a.confusion_pct)
+            .then_with(|| a.filter_name.cmp(&b.filter_name))
+    });
+    Ok(result)
+}
@@ -0,0 +1,205 @@
+//! Upkeep of the tracking DB: `tokf db maintain`, and a lighter pass that
+//! runs on its own every `[tracking] maintain_every` recorded runs.
+//!
+//! Archived events are written as zstd-compressed JSON lines, one object per
+//! row with every column of `events`, to `archive/` next to the DB.
+
+use std::io::Write as _;
+use std::path::{Path, PathBuf};
+
+use anyhow::Context as _;
+use rusqlite::Connection;
+use rusqlite::types::ValueRef;
+
+/// zstd level for archives: written once and rarely read, so favour ratio.
+const ARCHIVE_LEVEL: i32 = 9;
+
+/// Which events to archive.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Archive {
+    /// Archive events older than this many days.
+    pub after_days: u32,
+    /// Keep events the sync cursor has not passed yet. Set when this machine
+    /// is registered: sync only sends events after its cursor, so archiving
+    /// them first would lose them.
+    pub keep_unsynced: bool,
+}
+
+/// What `tokf db maintain` should do.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MaintainOptions {
+    /// Which events to archive; `None` archives nothing.
+    pub archive: Option<Archive>,
+    /// Rebuild the DB file to give freed pages back to the filesystem.
+    pub vacuum: bool,
+}
+
+/// What a maintenance run did.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct MaintainReport {
+    /// Events moved out of the DB, and the file they went to.
+    pub archived: Option<(usize, PathBuf)>,
+    /// DB file size before and after, in bytes.
+    pub size_before: u64,
+    pub size_after: u64,
+    /// What `PRAGMA integrity_check` found; empty when the DB is sound.
+    pub problems: Vec<String>,
+}
+
+/// Archive, vacuum and check the DB at `db_path`.
+///
+/// # Errors
+/// Returns an error if writing the archive or any statement fails.
+pub fn maintain(
+    conn: &Connection,
+    db_path: &Path,
+    options: MaintainOptions,
+) -> anyhow::Result<MaintainReport> {
+    let size_before = file_size(db_path);
+    let archived = match options.archive {
+        Some(archive) => archive_events(conn, &archive_dir(db_path), archive)?,
+        None => None,
+    };
+    if options.vacuum {
+        conn.execute_batch("VACUUM").context("vacuum tracking DB")?;
+    }
+    Ok(MaintainReport {
+        archived,
+        size_before,
+        size_after: file_size(db_path),
+        problems: integrity_problems(conn)?,
+    })
+}
+
+/// The light pass, run after recording the event with ID `event_id`.
+///
+/// Every `every` events (never when `every` is 0) it archives old events if
+/// `archive` is set and refreshes the query planner's statistics.
+/// Returns whether it ran.
+///
+/// # Errors
+/// Returns an error if archiving or `PRAGMA optimize` fails.
+pub fn maintain_if_due(
+    conn: &Connection,
+    db_path: &Path,
+    event_id: i64,
+    archive: Option<Archive>,
+    every: u32,
+) -> anyhow::Result<bool> {
+    if every == 0 || event_id % i64::from(every) != 0 {
+        return Ok(false);
+    }
+    if let Some(archive) = archive {
+        archive_events(conn, &archive_dir(db_path), archive)?;
+    }
+    conn.execute_batch("PRAGMA optimize")
+        .context("optimize tracking DB")?;
+    Ok(true)
+}
+
+/// Where archives of the DB at `db_path` go.
+pub fn archive_dir(db_path: &Path) -> PathBuf {
+    db_path
+        .parent()
+        .unwrap_or_else(|| Path::new("."))
+        .join("archive")
+}
+
+/// Move the events `archive` selects into a new archive in `dir`.
+///
+/// Returns how many moved and the archive's path, or `None` when none were
+/// old enough. The events are deleted only once the archive is written.
+///
+/// # Errors
+/// Returns an error if the archive cannot be written or a statement fails.
+pub fn archive_events(
+    conn: &Connection,
+    dir: &Path,
+    archive: Archive,
+) -> anyhow::Result<Option<(usize, PathBuf)>> {
+    let tx = conn.unchecked_transaction()?;
+    let (cutoff, stamp): (String, String) = tx.query_row(
+        "SELECT strftime('%Y-%m-%dT%H:%M:%SZ', 'now', '-' || ?1 || ' days'),
+                strftime('%Y%m%dT%H%M%SZ', 'now')",
+        [archive.after_days],
+        |r| Ok((r.get(0)?, r.get(1)?)),
+    )?;
+    let max_id = if archive.keep_unsynced {
+        super::get_last_synced_id(&tx)?
+    } else {
+        i64::MAX
+    };
+    let mut stmt =
+        tx.prepare("SELECT * FROM events WHERE timestamp < ?1 AND id <= ?2 ORDER BY id")?;
+    let names: Vec<String> = stmt
+        .column_names()
+        .iter()
+        .map(ToString::to_string)
+        .collect();
+    let rows = stmt
+        .query_map(rusqlite::params![cutoff, max_id], |row| {
+            row_json(row, &names)
+        })?
+        .collect::<rusqlite::Result<Vec<_>>>()
+        .context("read events to archive")?;
+    drop(stmt);
+    let Some(last_id) = rows.last().and_then(|r| r["id"].as_i64()) else {
+        return Ok(None);
+    };
+
+    std::fs::create_dir_all(dir).with_context(|| format!("create {}", dir.display()))?;
+    let path = dir.join(format!("events-{stamp}-{last_id}.jsonl.zst"));
+    write_archive(&path, &rows).with_context(|| format!("write {}", path.display()))?;
+
+    let deleted = tx
+        .execute(
+            "DELETE FROM events WHERE timestamp < ?1 AND id <= ?2",
+            rusqlite::params![cutoff, last_id],
+        )
+        .context("delete archived events")?;
+    tx.commit()?;
+    Ok(Some((deleted, path)))
+}
+
+fn write_archive(path: &Path, rows: &[serde_json::Value]) -> anyhow::Result<()> {
+    let file = std::fs::File::create_new(path)?;
+    let mut encoder = zstd::Encoder::new(file, ARCHIVE_LEVEL)?;
+    for row in rows {
+        serde_json::to_writer(&mut encoder, row)?;
+        encoder.write_all(b"\n")?;
+    }
+    encoder.finish()?.sync_all()?;
+    Ok(())
+}
+
+/// One row as a JSON object keyed by column name.
+fn row_json(row: &rusqlite::Row<'_>, names: &[String]) -> rusqlite::Result<serde_json::Value> {
+    let mut object = serde_json::Map::with_capacity(names.len());
+    for (i, name) in names.iter().enumerate() {
+        let value = match row.get_ref(i)? {
+            ValueRef::Null => serde_json::Value::Null,
+            ValueRef::Integer(n) => n.into(),
+            ValueRef::Real(f) => f.into(),
+            ValueRef::Text(t) | ValueRef::Blob(t) => String::from_utf8_lossy(t).into(),
+        };
+        object.insert(name.clone(), value);
+    }
+    Ok(object.into())
+}
+
+/// What `PRAGMA integrity_check` reports, without its all-clear `ok`.
+///
+/// # Errors
+/// Returns an error if the pragma cannot run.
+pub fn integrity_problems(conn: &Connection) -> anyhow::Result<Vec<String>> {
+    let mut stmt = conn.prepare("PRAGMA integrity_check")?;
+    let lines = stmt
+        .query_map([], |r| r.get::<_, String>(0))?
+        .collect::<rusqlite::Result<Vec<_>>>()
+        .context("integrity check")?;
+    Ok(lines.into_iter().filter(|l| l != "ok").collect())
+}
+
+fn file_size(path: &Path) -> u64 {
+    std::fs::metadata(path).map_or(0, |m| m.len())
+}
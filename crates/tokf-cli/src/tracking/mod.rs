@@ -1,10 +1,32 @@
 use std::path::Path;
 
+mod feedback;
+mod maintain;
+mod queries;
+mod range;
+
 use anyhow::Context as _;
 use rusqlite::{Connection, OptionalExtension as _};
 
-use tokf_common::tokens::estimate_tokens_from_bytes;
-pub use tokf_common::tracking::types::{DailyGain, FilterGain, GainSummary, TrackingEvent};
+use tokf_common::tokens::{TokenCounter, estimate_tokens_from_bytes};
+
+use crate::runner::ResourceUsage;
+pub use feedback::{
+    FeedbackSignal, FilterConfusion, HookSession, get_session, query_confusion, recent_filter_for,
+    record_feedback, save_session,
+};
+pub use maintain::{
+    Archive, MaintainOptions, MaintainReport, archive_dir, archive_events, integrity_problems,
+    maintain, maintain_if_due,
+};
+pub use queries::{
+    query_by_command, query_by_filter, query_by_project, query_daily, query_project_savings,
+    query_summary,
+};
+pub use range::{Period, TimeRange, parse_day};
+pub use tokf_common::tracking::types::{
+    CommandGain, DailyGain, FilterGain, GainSummary, ProjectGain, TrackingEvent,
+};
 
 /// Open or create the DB at `path`, running `CREATE TABLE IF NOT EXISTS` for the
 /// events table.
@@ -43,7 +65,14 @@ pub fn open_db(path: &Path) -> anyhow::Result<Connection> {
             pipe_override     INTEGER NOT NULL DEFAULT 0,
             raw_bytes         INTEGER NOT NULL DEFAULT 0,
             raw_tokens_est    INTEGER NOT NULL DEFAULT 0,
-            project           TEXT    NOT NULL DEFAULT ''
+            project           TEXT    NOT NULL DEFAULT '',
+            duration_ms       INTEGER,
+            user_cpu_ms       INTEGER,
+            sys_cpu_ms        INTEGER,
+            max_rss_kb        INTEGER,
+            tokenizer         TEXT,
+            input_tokens_tok  INTEGER,
+            output_tokens_tok INTEGER
         );",
     )
     .context("create events table")?;
@@ -55,20 +84,25 @@ pub fn open_db(path: &Path) -> anyhow::Result<Connection> {
     )
     .context("create sync_state table")?;
 
+    feedback::create_tables(&conn)?;
+
     Ok(conn)
 }
 
+/// Whether the events table has a column called `name`.
+fn has_column(conn: &Connection, name: &str) -> bool {
+    conn.query_row(
+        "SELECT COUNT(*) FROM pragma_table_info('events') WHERE name=?1",
+        [name],
+        |r| r.get::<_, i64>(0),
+    )
+    .is_ok_and(|n| n > 0)
+}
+
 /// Run schema migrations for the events table.
 fn run_migrations(conn: &Connection) -> anyhow::Result<()> {
     // Migration: add pipe_override column when upgrading from a schema without it.
-    let has_pipe_override: i64 = conn
-        .query_row(
-            "SELECT COUNT(*) FROM pragma_table_info('events') WHERE name='pipe_override'",
-            [],
-            |r| r.get(0),
-        )
-        .unwrap_or(0);
-    if has_pipe_override == 0 {
+    if !has_column(conn, "pipe_override") {
         conn.execute_batch(
             "ALTER TABLE events ADD COLUMN pipe_override INTEGER NOT NULL DEFAULT 0;",
         )
@@ -76,27 +110,13 @@ fn run_migrations(conn: &Connection) -> anyhow::Result<()> {
     }
 
     // Migration: add filter_hash column when upgrading from a schema without it.
-    let has_filter_hash: i64 = conn
-        .query_row(
-            "SELECT COUNT(*) FROM pragma_table_info('events') WHERE name='filter_hash'",
-            [],
-            |r| r.get(0),
-        )
-        .unwrap_or(0);
-    if has_filter_hash == 0 {
+    if !has_column(conn, "filter_hash") {
         conn.execute_batch("ALTER TABLE events ADD COLUMN filter_hash TEXT;")
             .context("migrate events table: add filter_hash column")?;
     }
 
     // Migration: add raw_bytes and raw_tokens_est columns.
-    let has_raw_bytes: i64 = conn
-        .query_row(
-            "SELECT COUNT(*) FROM pragma_table_info('events') WHERE name='raw_bytes'",
-            [],
-            |r| r.get(0),
-        )
-        .unwrap_or(0);
-    if has_raw_bytes == 0 {
+    if !has_column(conn, "raw_bytes") {
         conn.execute_batch(
             "ALTER TABLE events ADD COLUMN raw_bytes INTEGER NOT NULL DEFAULT 0;
              ALTER TABLE events ADD COLUMN raw_tokens_est INTEGER NOT NULL DEFAULT 0;
@@ -108,18 +128,34 @@ fn run_migrations(conn: &Connection) -> anyhow::Result<()> {
     // Migration: add project column when upgrading from a schema without it.
     // Pre-existing rows get the empty-string default — `tokf doctor` treats
     // empty as "unknown" and shows them under all projects.
-    let has_project: i64 = conn
-        .query_row(
-            "SELECT COUNT(*) FROM pragma_table_info('events') WHERE name='project'",
-            [],
-            |r| r.get(0),
-        )
-        .unwrap_or(0);
-    if has_project == 0 {
+    if !has_column(conn, "project") {
         conn.execute_batch("ALTER TABLE events ADD COLUMN project TEXT NOT NULL DEFAULT '';")
             .context("migrate events table: add project column")?;
     }
 
+    // Migration: add resource usage columns. Pre-existing rows stay NULL
+    // ("not measured").
+    if !has_column(conn, "duration_ms") {
+        conn.execute_batch(
+            "ALTER TABLE events ADD COLUMN duration_ms INTEGER;
+             ALTER TABLE events ADD COLUMN user_cpu_ms INTEGER;
+             ALTER TABLE events ADD COLUMN sys_cpu_ms INTEGER;
+             ALTER TABLE events ADD COLUMN max_rss_kb INTEGER;",
+        )
+        .context("migrate events table: add resource usage columns")?;
+    }
+
+    // Migration: add real tokenizer count columns. Pre-existing rows stay
+    // NULL ("not counted").
+    if !has_column(conn, "tokenizer") {
+        conn.execute_batch(
+            "ALTER TABLE events ADD COLUMN tokenizer TEXT;
+             ALTER TABLE events ADD COLUMN input_tokens_tok INTEGER;
+             ALTER TABLE events ADD COLUMN output_tokens_tok INTEGER;",
+        )
+        .context("migrate events table: add tokenizer columns")?;
+    }
+
     // Indexes used by `tokf doctor` burst-detection and per-filter queries.
     // Created here (not in CREATE TABLE) so existing DBs pick them up too.
     conn.execute_batch(
@@ -172,11 +208,45 @@ pub fn build_event(
         pipe_override,
         // `project` defaults to empty here. Callers that know the project
         // (currently `resolve::record_run`) set it on the event before
-        // passing it to `record_event`.
+        // passing it to `record_event`. The same goes for resource usage
+        // (set from the runner's measurements) and real tokenizer counts.
         project: String::new(),
+        duration_ms: None,
+        user_cpu_ms: None,
+        sys_cpu_ms: None,
+        max_rss_kb: None,
+        tokenizer: None,
+        input_tokens_tok: None,
+        output_tokens_tok: None,
     }
 }
 
+/// Copy the runner's measurements onto `event`.
+pub fn set_usage(event: &mut TrackingEvent, usage: &ResourceUsage) {
+    fn millis(d: std::time::Duration) -> Option<i64> {
+        i64::try_from(d.as_millis()).ok()
+    }
+    event.duration_ms = millis(usage.duration);
+    event.user_cpu_ms = usage.user_cpu.and_then(millis);
+    event.sys_cpu_ms = usage.sys_cpu.and_then(millis);
+    event.max_rss_kb = usage.max_rss_kb.and_then(|kb| i64::try_from(kb).ok());
+}
+
+/// Count `input` and `output` with `counter` and store the counts on `event`
+/// under the encoding name `tokenizer`.
+pub fn set_token_counts(
+    event: &mut TrackingEvent,
+    tokenizer: &str,
+    counter: &dyn TokenCounter,
+    input: &str,
+    output: &str,
+) {
+    let count = |text: &str| i64::try_from(counter.count(text)).ok();
+    event.tokenizer = Some(tokenizer.to_owned());
+    event.input_tokens_tok = count(input);
+    event.output_tokens_tok = count(output);
+}
+
 /// Insert one row; timestamp set by `SQLite` `strftime` in the SQL.
 ///
 /// # Errors
@@ -188,10 +258,13 @@ pub fn record_event(conn: &Connection, event: &TrackingEvent) -> anyhow::Result<
              input_bytes, output_bytes,
              input_tokens_est, output_tokens_est,
              raw_bytes, raw_tokens_est,
-             filter_time_ms, exit_code, pipe_override, project)
+             filter_time_ms, exit_code, pipe_override, project,
+             duration_ms, user_cpu_ms, sys_cpu_ms, max_rss_kb,
+             tokenizer, input_tokens_tok, output_tokens_tok)
          VALUES
             (strftime('%Y-%m-%dT%H:%M:%SZ','now'),
-             ?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13)",
+             ?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13,
+             ?14, ?15, ?16, ?17, ?18, ?19, ?20)",
         rusqlite::params![
             event.command,
             event.filter_name,
@@ -206,198 +279,19 @@ pub fn record_event(conn: &Connection, event: &TrackingEvent) -> anyhow::Result<
             event.exit_code,
             i64::from(event.pipe_override),
             event.project,
+            event.duration_ms,
+            event.user_cpu_ms,
+            event.sys_cpu_ms,
+            event.max_rss_kb,
+            event.tokenizer,
+            event.input_tokens_tok,
+            event.output_tokens_tok,
         ],
     )
     .context("insert event")?;
     Ok(())
 }
 
-/// # Errors
-/// Returns an error if the SQL query fails.
-pub fn query_summary(conn: &Connection) -> anyhow::Result<GainSummary> {
-    let row = conn
-        .query_row(
-            "SELECT COUNT(*), COALESCE(SUM(input_tokens_est),0),
-                    COALESCE(SUM(output_tokens_est),0),
-                    COALESCE(SUM(input_tokens_est - output_tokens_est),0),
-                    COALESCE(SUM(pipe_override),0),
-                    COALESCE(SUM(filter_time_ms),0),
-                    COALESCE(SUM(CASE WHEN raw_tokens_est = 0 THEN input_tokens_est ELSE raw_tokens_est END),0)
-             FROM events",
-            [],
-            |row| {
-                Ok((
-                    row.get::<_, i64>(0)?,
-                    row.get::<_, i64>(1)?,
-                    row.get::<_, i64>(2)?,
-                    row.get::<_, i64>(3)?,
-                    row.get::<_, i64>(4)?,
-                    row.get::<_, i64>(5)?,
-                    row.get::<_, i64>(6)?,
-                ))
-            },
-        )
-        .context("query summary")?;
-
-    let (
-        total_commands,
-        total_input_tokens,
-        total_output_tokens,
-        tokens_saved,
-        pipe_override_count,
-        total_filter_time_ms,
-        total_raw_tokens,
-    ) = row;
-    let savings_pct = if total_input_tokens == 0 {
-        0.0
-    } else {
-        #[allow(clippy::cast_precision_loss)]
-        let pct = tokens_saved as f64 / total_input_tokens as f64 * 100.0;
-        pct
-    };
-    #[allow(clippy::cast_precision_loss)]
-    let avg_filter_time_ms = if total_commands == 0 {
-        0.0
-    } else {
-        total_filter_time_ms as f64 / total_commands as f64
-    };
-
-    Ok(GainSummary {
-        total_commands,
-        total_input_tokens,
-        total_output_tokens,
-        tokens_saved,
-        savings_pct,
-        pipe_override_count,
-        total_filter_time_ms,
-        avg_filter_time_ms,
-        total_raw_tokens,
-    })
-}
-
-/// Row type returned by aggregate queries.
-type AggregateRow = (String, i64, i64, i64, i64, i64, i64, i64);
-
-/// Shared row mapper for aggregate queries.
-/// Returns `(label, commands, input, output, saved, pipe_overrides, filter_time_ms, raw_tokens)`.
-fn map_aggregate_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<AggregateRow> {
-    Ok((
-        row.get::<_, String>(0)?,
-        row.get::<_, i64>(1)?,
-        row.get::<_, i64>(2)?,
-        row.get::<_, i64>(3)?,
-        row.get::<_, i64>(4)?,
-        row.get::<_, i64>(5)?,
-        row.get::<_, i64>(6)?,
-        row.get::<_, i64>(7)?,
-    ))
-}
-
-/// Compute savings percentage from input tokens and tokens saved.
-#[allow(clippy::cast_precision_loss)]
-fn savings_pct(input_tokens: i64, tokens_saved: i64) -> f64 {
-    if input_tokens == 0 {
-        0.0
-    } else {
-        tokens_saved as f64 / input_tokens as f64 * 100.0
-    }
-}
-
-/// # Errors
-/// Returns an error if the SQL query fails.
-pub fn query_by_filter(conn: &Connection) -> anyhow::Result<Vec<FilterGain>> {
-    let mut stmt = conn.prepare(
-        "SELECT COALESCE(filter_name, 'passthrough'), COUNT(*),
-                SUM(input_tokens_est), SUM(output_tokens_est),
-                SUM(input_tokens_est - output_tokens_est),
-                COALESCE(SUM(pipe_override),0),
-                COALESCE(SUM(filter_time_ms),0),
-                COALESCE(SUM(CASE WHEN raw_tokens_est = 0 THEN input_tokens_est ELSE raw_tokens_est END),0)
-         FROM events
-         GROUP BY filter_name
-         ORDER BY SUM(input_tokens_est - output_tokens_est) DESC",
-    )?;
-
-    let rows = stmt.query_map([], map_aggregate_row)?;
-
-    let mut result = Vec::new();
-    for row in rows {
-        let (
-            filter_name,
-            commands,
-            input_tokens,
-            output_tokens,
-            tokens_saved,
-            pipe_override_count,
-            total_filter_time_ms,
-            raw_tokens,
-        ) = row.context("read filter row")?;
-        #[allow(clippy::cast_precision_loss)]
-        let avg_filter_time_ms = if commands == 0 {
-            0.0
-        } else {
-            total_filter_time_ms as f64 / commands as f64
-        };
-        result.push(FilterGain {
-            filter_name,
-            commands,
-            input_tokens,
-            output_tokens,
-            tokens_saved,
-            savings_pct: savings_pct(input_tokens, tokens_saved),
-            pipe_override_count,
-            total_filter_time_ms,
-            avg_filter_time_ms,
-            raw_tokens,
-        });
-    }
-    Ok(result)
-}
-
-/// # Errors
-/// Returns an error if the SQL query fails.
-pub fn query_daily(conn: &Connection) -> anyhow::Result<Vec<DailyGain>> {
-    let mut stmt = conn.prepare(
-        "SELECT substr(timestamp, 1, 10), COUNT(*),
-                SUM(input_tokens_est), SUM(output_tokens_est),
-                SUM(input_tokens_est - output_tokens_est),
-                COALESCE(SUM(pipe_override),0),
-                COALESCE(SUM(filter_time_ms),0),
-                COALESCE(SUM(CASE WHEN raw_tokens_est = 0 THEN input_tokens_est ELSE raw_tokens_est END),0)
-         FROM events
-         GROUP BY substr(timestamp, 1, 10)
-         ORDER BY substr(timestamp, 1, 10) DESC",
-    )?;
-
-    let rows = stmt.query_map([], map_aggregate_row)?;
-
-    let mut result = Vec::new();
-    for row in rows {
-        let (
-            date,
-            commands,
-            input_tokens,
-            output_tokens,
-            tokens_saved,
-            pipe_override_count,
-            total_filter_time_ms,
-            raw_tokens,
-        ) = row.context("read daily row")?;
-        result.push(DailyGain {
-            date,
-            commands,
-            input_tokens,
-            output_tokens,
-            tokens_saved,
-            savings_pct: savings_pct(input_tokens, tokens_saved),
-            pipe_override_count,
-            total_filter_time_ms,
-            raw_tokens,
-        });
-    }
-    Ok(result)
-}
-
 /// Returns the last successfully synced event ID (from `sync_state` table, default 0).
 ///
 /// # Errors
@@ -428,6 +322,23 @@ pub fn set_last_synced_id(conn: &Connection, id: i64) -> anyhow::Result<()> {
     Ok(())
 }
 
+/// Returns the highest event ID ever assigned, including events since
+/// archived or deleted (0 for an empty database).
+///
+/// # Errors
+/// Returns an error if the SQL query fails.
+pub fn get_max_event_id(conn: &Connection) -> anyhow::Result<i64> {
+    let seq: Option<i64> = conn
+        .query_row(
+            "SELECT seq FROM sqlite_sequence WHERE name = 'events'",
+            [],
+            |r| r.get(0),
+        )
+        .optional()
+        .context("query max event id")?;
+    Ok(seq.unwrap_or(0))
+}
+
 /// Returns the timestamp of the last successful sync (from `sync_state` table).
 ///
 /// # Errors
@@ -523,17 +434,22 @@ pub fn backfill_filter_hashes(
     Ok((updated, not_found))
 }
 
-/// Returns up to 500 events with `id > last_id`, ordered ascending.
+/// Returns up to `limit` events (never more than 500) with `id > last_id`,
+/// ordered ascending.
 ///
 /// # Errors
 /// Returns an error if the SQL query fails.
-pub fn get_events_since(conn: &Connection, last_id: i64) -> anyhow::Result<Vec<SyncableEvent>> {
+pub fn get_events_since(
+    conn: &Connection,
+    last_id: i64,
+    limit: u32,
+) -> anyhow::Result<Vec<SyncableEvent>> {
     let mut stmt = conn.prepare(
         "SELECT id, filter_name, filter_hash, input_tokens_est, output_tokens_est,
                 raw_tokens_est, timestamp
-         FROM events WHERE id > ?1 ORDER BY id ASC LIMIT 500",
+         FROM events WHERE id > ?1 ORDER BY id ASC LIMIT ?2",
     )?;
-    let rows = stmt.query_map(rusqlite::params![last_id], |row| {
+    let rows = stmt.query_map(rusqlite::params![last_id, limit.min(500)], |row| {
         Ok(SyncableEvent {
             id: row.get(0)?,
             filter_name: row.get(1)?,
@@ -557,14 +473,32 @@ mod tests;
 #[cfg(test)]
 mod tests_backfill;
 
+#[cfg(test)]
+mod tests_command;
+
+#[cfg(test)]
+mod tests_feedback;
+
 #[cfg(test)]
 mod tests_pipe_override;
 
 #[cfg(test)]
 mod tests_project;
 
+#[cfg(test)]
+mod tests_range;
+
 #[cfg(test)]
 mod tests_raw_bytes;
 
 #[cfg(test)]
 mod tests_sync_state;
+
+#[cfg(test)]
+mod tests_usage;
+
+#[cfg(test)]
+mod tests_tokenizer;
+
+#[cfg(test)]
+mod tests_maintain;
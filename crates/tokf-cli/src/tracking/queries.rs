@@ -0,0 +1,329 @@
+//! Aggregate queries behind `tokf gain`.
+
+use anyhow::Context as _;
+use rusqlite::Connection;
+
+use super::{CommandGain, DailyGain, FilterGain, GainSummary, ProjectGain, TimeRange};
+
+/// Restricts the gain queries to a [`TimeRange`], bound as `?1` and `?2`.
+const RANGE_WHERE: &str =
+    "WHERE (?1 IS NULL OR timestamp >= ?1) AND (?2 IS NULL OR timestamp <= ?2)";
+
+/// # Errors
+/// Returns an error if the SQL query fails.
+pub fn query_summary(conn: &Connection, range: &TimeRange) -> anyhow::Result<GainSummary> {
+    let row = conn
+        .query_row(
+            &format!(
+                "SELECT COUNT(*), COALESCE(SUM(input_tokens_est),0),
+                        COALESCE(SUM(output_tokens_est),0),
+                        COALESCE(SUM(input_tokens_est - output_tokens_est),0),
+                        COALESCE(SUM(pipe_override),0),
+                        COALESCE(SUM(filter_time_ms),0),
+                        COALESCE(SUM(CASE WHEN raw_tokens_est = 0 THEN input_tokens_est ELSE raw_tokens_est END),0)
+                 FROM events {RANGE_WHERE}"
+            ),
+            rusqlite::params![range.lower(), range.upper()],
+            |row| {
+                Ok((
+                    row.get::<_, i64>(0)?,
+                    row.get::<_, i64>(1)?,
+                    row.get::<_, i64>(2)?,
+                    row.get::<_, i64>(3)?,
+                    row.get::<_, i64>(4)?,
+                    row.get::<_, i64>(5)?,
+                    row.get::<_, i64>(6)?,
+                ))
+            },
+        )
+        .context("query summary")?;
+
+    let (
+        total_commands,
+        total_input_tokens,
+        total_output_tokens,
+        tokens_saved,
+        pipe_override_count,
+        total_filter_time_ms,
+        total_raw_tokens,
+    ) = row;
+    let savings_pct = if total_input_tokens == 0 {
+        0.0
+    } else {
+        #[allow(clippy::cast_precision_loss)]
+        let pct = tokens_saved as f64 / total_input_tokens as f64 * 100.0;
+        pct
+    };
+    #[allow(clippy::cast_precision_loss)]
+    let avg_filter_time_ms = if total_commands == 0 {
+        0.0
+    } else {
+        total_filter_time_ms as f64 / total_commands as f64
+    };
+
+    Ok(GainSummary {
+        total_commands,
+        total_input_tokens,
+        total_output_tokens,
+        tokens_saved,
+        savings_pct,
+        pipe_override_count,
+        total_filter_time_ms,
+        avg_filter_time_ms,
+        total_raw_tokens,
+    })
+}
+
+/// Row type returned by aggregate queries.
+type AggregateRow = (String, i64, i64, i64, i64, i64, i64, i64);
+
+/// Shared row mapper for aggregate queries.
+/// Returns `(label, commands, input, output, saved, pipe_overrides, filter_time_ms, raw_tokens)`.
+fn map_aggregate_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<AggregateRow> {
+    Ok((
+        row.get::<_, String>(0)?,
+        row.get::<_, i64>(1)?,
+        row.get::<_, i64>(2)?,
+        row.get::<_, i64>(3)?,
+        row.get::<_, i64>(4)?,
+        row.get::<_, i64>(5)?,
+        row.get::<_, i64>(6)?,
+        row.get::<_, i64>(7)?,
+    ))
+}
+
+/// Compute savings percentage from input tokens and tokens saved.
+#[allow(clippy::cast_precision_loss)]
+fn savings_pct(input_tokens: i64, tokens_saved: i64) -> f64 {
+    if input_tokens == 0 {
+        0.0
+    } else {
+        tokens_saved as f64 / input_tokens as f64 * 100.0
+    }
+}
+
+/// # Errors
+/// Returns an error if the SQL query fails.
+pub fn query_by_filter(conn: &Connection, range: &TimeRange) -> anyhow::Result<Vec<FilterGain>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT COALESCE(filter_name, 'passthrough'), COUNT(*),
+                SUM(input_tokens_est), SUM(output_tokens_est),
+                SUM(input_tokens_est - output_tokens_est),
+                COALESCE(SUM(pipe_override),0),
+                COALESCE(SUM(filter_time_ms),0),
+                COALESCE(SUM(CASE WHEN raw_tokens_est = 0 THEN input_tokens_est ELSE raw_tokens_est END),0)
+         FROM events {RANGE_WHERE}
+         GROUP BY filter_name
+         ORDER BY SUM(input_tokens_est - output_tokens_est) DESC"
+    ))?;
+
+    let rows = stmt.query_map(
+        rusqlite::params![range.lower(), range.upper()],
+        map_aggregate_row,
+    )?;
+
+    let mut result = Vec::new();
+    for row in rows {
+        let (
+            filter_name,
+            commands,
+            input_tokens,
+            output_tokens,
+            tokens_saved,
+            pipe_override_count,
+            total_filter_time_ms,
+            raw_tokens,
+        ) = row.context("read filter row")?;
+        #[allow(clippy::cast_precision_loss)]
+        let avg_filter_time_ms = if commands == 0 {
+            0.0
+        } else {
+            total_filter_time_ms as f64 / commands as f64
+        };
+        result.push(FilterGain {
+            filter_name,
+            commands,
+            input_tokens,
+            output_tokens,
+            tokens_saved,
+            savings_pct: savings_pct(input_tokens, tokens_saved),
+            pipe_override_count,
+            total_filter_time_ms,
+            avg_filter_time_ms,
+            raw_tokens,
+        });
+    }
+    Ok(result)
+}
+
+/// # Errors
+/// Returns an error if the SQL query fails.
+pub fn query_daily(conn: &Connection, range: &TimeRange) -> anyhow::Result<Vec<DailyGain>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT substr(timestamp, 1, 10), COUNT(*),
+                SUM(input_tokens_est), SUM(output_tokens_est),
+                SUM(input_tokens_est - output_tokens_est),
+                COALESCE(SUM(pipe_override),0),
+                COALESCE(SUM(filter_time_ms),0),
+                COALESCE(SUM(CASE WHEN raw_tokens_est = 0 THEN input_tokens_est ELSE raw_tokens_est END),0)
+         FROM events {RANGE_WHERE}
+         GROUP BY substr(timestamp, 1, 10)
+         ORDER BY substr(timestamp, 1, 10) DESC"
+    ))?;
+
+    let rows = stmt.query_map(
+        rusqlite::params![range.lower(), range.upper()],
+        map_aggregate_row,
+    )?;
+
+    let mut result = Vec::new();
+    for row in rows {
+        let (
+            date,
+            commands,
+            input_tokens,
+            output_tokens,
+            tokens_saved,
+            pipe_override_count,
+            total_filter_time_ms,
+            raw_tokens,
+        ) = row.context("read daily row")?;
+        result.push(DailyGain {
+            date,
+            commands,
+            input_tokens,
+            output_tokens,
+            tokens_saved,
+            savings_pct: savings_pct(input_tokens, tokens_saved),
+            pipe_override_count,
+            total_filter_time_ms,
+            raw_tokens,
+        });
+    }
+    Ok(result)
+}
+
+/// # Errors
+/// Returns an error if the SQL query fails.
+pub fn query_by_project(conn: &Connection, range: &TimeRange) -> anyhow::Result<Vec<ProjectGain>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT CASE WHEN project = '' THEN '(unknown)' ELSE project END, COUNT(*),
+                SUM(input_tokens_est), SUM(output_tokens_est),
+                SUM(input_tokens_est - output_tokens_est),
+                COALESCE(SUM(pipe_override),0),
+                COALESCE(SUM(filter_time_ms),0),
+                COALESCE(SUM(CASE WHEN raw_tokens_est = 0 THEN input_tokens_est ELSE raw_tokens_est END),0)
+         FROM events {RANGE_WHERE}
+         GROUP BY project
+         ORDER BY SUM(input_tokens_est - output_tokens_est) DESC"
+    ))?;
+
+    let rows = stmt.query_map(
+        rusqlite::params![range.lower(), range.upper()],
+        map_aggregate_row,
+    )?;
+
+    let mut result = Vec::new();
+    for row in rows {
+        let (
+            project,
+            commands,
+            input_tokens,
+            output_tokens,
+            tokens_saved,
+            pipe_override_count,
+            total_filter_time_ms,
+            raw_tokens,
+        ) = row.context("read project row")?;
+        result.push(ProjectGain {
+            project,
+            commands,
+            input_tokens,
+            output_tokens,
+            tokens_saved,
+            savings_pct: savings_pct(input_tokens, tokens_saved),
+            pipe_override_count,
+            total_filter_time_ms,
+            raw_tokens,
+        });
+    }
+    Ok(result)
+}
+
+/// The first two space-separated words of `command`, or all of it when it
+/// has fewer.
+const COMMAND_PREFIX: &str = "CASE
+    WHEN instr(command, ' ') = 0 THEN command
+    WHEN instr(substr(command, instr(command, ' ') + 1), ' ') = 0 THEN command
+    ELSE substr(command, 1,
+        instr(command, ' ') + instr(substr(command, instr(command, ' ') + 1), ' ') - 1)
+END";
+
+/// Group by command prefix rather than filter, so commands that mostly pass
+/// through unfiltered stand out as candidates for a new filter. Ordered by
+/// input volume, the tokens a filter could act on.
+///
+/// # Errors
+/// Returns an error if the SQL query fails.
+pub fn query_by_command(conn: &Connection, range: &TimeRange) -> anyhow::Result<Vec<CommandGain>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {COMMAND_PREFIX} AS prefix, COUNT(*),
+                SUM(input_tokens_est), SUM(output_tokens_est),
+                SUM(input_tokens_est - output_tokens_est),
+                COALESCE(SUM(pipe_override),0),
+                COALESCE(SUM(filter_time_ms),0),
+                COALESCE(SUM(CASE WHEN raw_tokens_est = 0 THEN input_tokens_est ELSE raw_tokens_est END),0),
+                COUNT(*) - COUNT(filter_name)
+         FROM events {RANGE_WHERE}
+         GROUP BY prefix
+         ORDER BY SUM(input_tokens_est) DESC"
+    ))?;
+
+    let rows = stmt.query_map(rusqlite::params![range.lower(), range.upper()], |row| {
+        Ok((map_aggregate_row(row)?, row.get::<_, i64>(8)?))
+    })?;
+
+    let mut result = Vec::new();
+    for row in rows {
+        let (
+            (
+                command,
+                commands,
+                input_tokens,
+                output_tokens,
+                tokens_saved,
+                pipe_override_count,
+                total_filter_time_ms,
+                raw_tokens,
+            ),
+            passthrough_runs,
+        ) = row.context("read command row")?;
+        result.push(CommandGain {
+            command,
+            commands,
+            passthrough_runs,
+            input_tokens,
+            output_tokens,
+            tokens_saved,
+            savings_pct: savings_pct(input_tokens, tokens_saved),
+            pipe_override_count,
+            total_filter_time_ms,
+            raw_tokens,
+        });
+    }
+    Ok(result)
+}
+
+/// `(commands, tokens saved)` across every recorded run in `project`.
+///
+/// # Errors
+/// Returns an error if the SQL query fails.
+pub fn query_project_savings(conn: &Connection, project: &str) -> anyhow::Result<(i64, i64)> {
+    conn.query_row(
+        "SELECT COUNT(*), COALESCE(SUM(input_tokens_est - output_tokens_est),0)
+         FROM events WHERE project = ?1",
+        [project],
+        |row| Ok((row.get(0)?, row.get(1)?)),
+    )
+    .context("query project savings")
+}
@@ -0,0 +1,109 @@
+//! Date ranges for the gain queries.
+//!
+//! Event timestamps are `YYYY-MM-DDTHH:MM:SSZ` strings, which sort like the
+//! instants they name, so a range is two string bounds compared directly in
+//! SQL. Days are UTC days.
+
+use anyhow::Context as _;
+use rusqlite::Connection;
+
+/// A calendar period ending today, for `tokf gain --period`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Period {
+    /// Today.
+    Day,
+    /// Since Monday.
+    Week,
+    /// Since the first of the month.
+    Month,
+}
+
+impl Period {
+    /// `SQLite` expression for the first day of the period.
+    const fn start_sql(self) -> &'static str {
+        match self {
+            Self::Day => "SELECT date('now')",
+            Self::Week => "SELECT date('now', '-6 days', 'weekday 1')",
+            Self::Month => "SELECT date('now', 'start of month')",
+        }
+    }
+}
+
+/// Inclusive range of days to aggregate over; `None` leaves that side open.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TimeRange {
+    /// First day included, `YYYY-MM-DD`.
+    pub since: Option<String>,
+    /// Last day included, `YYYY-MM-DD`.
+    pub until: Option<String>,
+}
+
+impl TimeRange {
+    /// Every event.
+    pub const ALL: Self = Self {
+        since: None,
+        until: None,
+    };
+
+    /// From the start of `period` (by the database clock) to now.
+    ///
+    /// # Errors
+    /// Returns an error if the date query fails.
+    pub fn for_period(conn: &Connection, period: Period) -> anyhow::Result<Self> {
+        let since: String = conn
+            .query_row(period.start_sql(), [], |r| r.get(0))
+            .context("compute period start")?;
+        Ok(Self {
+            since: Some(since),
+            until: None,
+        })
+    }
+
+    pub const fn is_all(&self) -> bool {
+        self.since.is_none() && self.until.is_none()
+    }
+
+    /// Human-readable form, e.g. `2024-06-01 to 2024-06-14`.
+    pub fn describe(&self) -> String {
+        match (&self.since, &self.until) {
+            (Some(since), Some(until)) => format!("{since} to {until}"),
+            (Some(since), None) => format!("since {since}"),
+            (None, Some(until)) => format!("until {until}"),
+            (None, None) => "all time".to_string(),
+        }
+    }
+
+    /// Lower bound for `timestamp >= ?`: a bare date sorts before every
+    /// timestamp on that day.
+    pub(super) fn lower(&self) -> Option<&str> {
+        self.since.as_deref()
+    }
+
+    /// Upper bound for `timestamp <= ?`: the last second of the day.
+    pub(super) fn upper(&self) -> Option<String> {
+        self.until.as_ref().map(|day| format!("{day}T23:59:59Z"))
+    }
+}
+
+/// Check that `s` is a `YYYY-MM-DD` date.
+///
+/// # Errors
+/// Returns a message naming the expected format.
+pub fn parse_day(s: &str) -> Result<String, String> {
+    let bytes = s.as_bytes();
+    let shaped = bytes.len() == 10
+        && bytes[4] == b'-'
+        && bytes[7] == b'-'
+        && bytes
+            .iter()
+            .enumerate()
+            .all(|(i, b)| i == 4 || i == 7 || b.is_ascii_digit());
+    let in_range = shaped
+        && s[5..7].parse::<u8>().is_ok_and(|m| (1..=12).contains(&m))
+        && s[8..10].parse::<u8>().is_ok_and(|d| (1..=31).contains(&d));
+    if in_range {
+        Ok(s.to_string())
+    } else {
+        Err(format!("expected a date like 2024-06-01, got \"{s}\""))
+    }
+}
@@ -203,7 +203,7 @@ fn build_event_passthrough_filter_name_none() {
 #[test]
 fn query_summary_empty_db() {
     let (_dir, conn) = temp_db();
-    let s = query_summary(&conn).expect("summary");
+    let s = query_summary(&conn, &TimeRange::ALL).expect("summary");
     assert_eq!(s.total_commands, 0);
     assert_eq!(s.total_input_tokens, 0);
     assert_eq!(s.total_output_tokens, 0);
@@ -217,7 +217,7 @@ fn query_summary_with_events() {
     let (_dir, conn) = temp_db();
     let ev = build_event("cmd", Some("f"), None, 400, 100, 400, 5, 0, false);
     record_event(&conn, &ev).expect("record");
-    let s = query_summary(&conn).expect("summary");
+    let s = query_summary(&conn, &TimeRange::ALL).expect("summary");
     assert_eq!(s.total_commands, 1);
     assert_eq!(s.total_input_tokens, est_i64(400));
     assert_eq!(s.total_output_tokens, est_i64(100));
@@ -232,7 +232,7 @@ fn query_summary_zero_input_no_divide_by_zero() {
     let (_dir, conn) = temp_db();
     let ev = build_event("cmd", None, None, 0, 0, 0, 0, 0, false);
     record_event(&conn, &ev).expect("record");
-    let s = query_summary(&conn).expect("summary");
+    let s = query_summary(&conn, &TimeRange::ALL).expect("summary");
     assert!(s.savings_pct.abs() < f64::EPSILON); // must not panic or NaN
 }
 
@@ -252,7 +252,7 @@ fn query_summary_accumulates_multiple_events() {
     for ev in &events {
         record_event(&conn, ev).expect("record");
     }
-    let s = query_summary(&conn).expect("summary");
+    let s = query_summary(&conn, &TimeRange::ALL).expect("summary");
     assert_eq!(s.total_commands, 3);
     let inp = est_i64(400) + est_i64(800) + est_i64(1200);
     let out = est_i64(100) + est_i64(400) + est_i64(0);
@@ -271,7 +271,7 @@ fn query_by_filter_groups_correctly() {
         let ev = build_event("cmd", Some(fname), None, 400, 100, 400, 0, 0, false);
         record_event(&conn, &ev).expect("record");
     }
-    let rows = query_by_filter(&conn).expect("query");
+    let rows = query_by_filter(&conn, &TimeRange::ALL).expect("query");
     assert_eq!(rows.len(), 3);
     assert!(rows.iter().all(|r| r.commands == 1));
 }
@@ -281,7 +281,7 @@ fn query_by_filter_null_shown_as_passthrough() {
     let (_dir, conn) = temp_db();
     let ev = build_event("echo hi", None, None, 200, 200, 200, 0, 0, false);
     record_event(&conn, &ev).expect("record");
-    let rows = query_by_filter(&conn).expect("query");
+    let rows = query_by_filter(&conn, &TimeRange::ALL).expect("query");
     assert_eq!(rows.len(), 1);
     assert_eq!(rows[0].filter_name, "passthrough");
 }
@@ -310,7 +310,7 @@ fn query_by_filter_mixed_null_and_named() {
         &build_event("echo hi", None, None, 200, 200, 200, 0, 0, false),
     )
     .expect("record");
-    let rows = query_by_filter(&conn).expect("query");
+    let rows = query_by_filter(&conn, &TimeRange::ALL).expect("query");
     assert_eq!(rows.len(), 2);
     let names: Vec<&str> = rows.iter().map(|r| r.filter_name.as_str()).collect();
     assert!(names.contains(&"git status"), "rows: {names:?}");
@@ -333,7 +333,7 @@ fn query_by_filter_ordered_by_savings_desc() {
         &build_event("cmd", Some("big"), None, 400, 0, 400, 0, 0, false),
     )
     .expect("record");
-    let rows = query_by_filter(&conn).expect("query");
+    let rows = query_by_filter(&conn, &TimeRange::ALL).expect("query");
     assert_eq!(rows.len(), 2);
     assert_eq!(
         rows[0].filter_name, "big",
@@ -351,7 +351,7 @@ fn query_daily_groups_by_date() {
         let ev = build_event("cmd", None, None, 400, 100, 400, 0, 0, false);
         record_event(&conn, &ev).expect("record");
     }
-    let rows = query_daily(&conn).expect("query");
+    let rows = query_daily(&conn, &TimeRange::ALL).expect("query");
     assert_eq!(rows.len(), 1);
     assert_eq!(rows[0].commands, 2);
 }
@@ -77,7 +77,7 @@ fn backfill_updates_known_filters() {
     assert_eq!(updated, 2, "two events should be updated");
     assert!(not_found.is_empty(), "no unknown filters: {not_found:?}");
 
-    let events = get_events_since(&conn, 0).unwrap();
+    let events = get_events_since(&conn, 0, 500).unwrap();
     let git = events
         .iter()
         .find(|e| e.filter_name.as_deref() == Some("git/status"))
@@ -152,7 +152,7 @@ fn backfill_skips_already_hashed_events() {
     assert_eq!(updated, 0, "already-hashed event must not be updated");
     assert!(not_found.is_empty());
 
-    let events = get_events_since(&conn, 0).unwrap();
+    let events = get_events_since(&conn, 0, 500).unwrap();
     assert_eq!(
         events[0].filter_hash.as_deref(),
         Some(existing_hash.as_str()),
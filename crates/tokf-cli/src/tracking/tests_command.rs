@@ -0,0 +1,31 @@
+#![allow(clippy::unwrap_used, clippy::expect_used)]
+
+use super::*;
+use tempfile::TempDir;
+
+fn record(conn: &Connection, command: &str, filter: Option<&str>, input: usize) {
+    let ev = build_event(command, filter, None, input, 40, input, 1, 0, false);
+    record_event(conn, &ev).expect("record");
+}
+
+#[test]
+fn query_by_command_groups_on_the_first_two_words() {
+    let dir = TempDir::new().expect("tempdir");
+    let conn = open_db(&dir.path().join("tracking.db")).expect("open");
+    record(&conn, "git status --short", Some("git/status"), 400);
+    record(&conn, "git status", Some("git/status"), 400);
+    record(&conn, "kubectl get pods -A", None, 4000);
+    record(&conn, "kubectl get svc", None, 400);
+    record(&conn, "ls", None, 40);
+
+    let rows = query_by_command(&conn, &TimeRange::ALL).expect("query");
+    let summary: Vec<(&str, i64, i64)> = rows
+        .iter()
+        .map(|r| (r.command.as_str(), r.commands, r.passthrough_runs))
+        .collect();
+    assert_eq!(
+        summary,
+        [("kubectl get", 2, 2), ("git status", 2, 0), ("ls", 1, 1)],
+        "ordered by input volume"
+    );
+}
@@ -0,0 +1,96 @@
+#![allow(clippy::unwrap_used, clippy::expect_used)]
+
+use super::*;
+use tempfile::TempDir;
+
+fn db() -> (TempDir, Connection) {
+    let dir = TempDir::new().unwrap();
+    let conn = open_db(&dir.path().join("tracking.db")).unwrap();
+    (dir, conn)
+}
+
+fn record_run(conn: &Connection, command: &str, filter: Option<&str>, project: &str) {
+    let mut event = build_event(command, filter, None, 100, 10, 100, 1, 0, false);
+    event.project = project.to_string();
+    record_event(conn, &event).unwrap();
+}
+
+#[test]
+fn session_round_trips_and_updates() {
+    let (_dir, conn) = db();
+    assert_eq!(get_session(&conn, "s1").unwrap(), None);
+
+    let first = HookSession {
+        command: "git status".to_string(),
+        filter_name: Some("git/status".to_string()),
+    };
+    save_session(&conn, "s1", &first).unwrap();
+    assert_eq!(get_session(&conn, "s1").unwrap(), Some(first));
+
+    let second = HookSession {
+        command: "ls".to_string(),
+        filter_name: None,
+    };
+    save_session(&conn, "s1", &second).unwrap();
+    assert_eq!(get_session(&conn, "s1").unwrap(), Some(second));
+    assert_eq!(get_session(&conn, "s2").unwrap(), None);
+}
+
+#[test]
+fn recent_filter_matches_the_run_inside_the_shell_command() {
+    let (_dir, conn) = db();
+    record_run(&conn, "git status", Some("git/status"), "/p");
+    record_run(&conn, "cargo test", Some("cargo/test"), "/other");
+
+    let found = recent_filter_for(&conn, "tokf run git status", "/p").unwrap();
+    assert_eq!(found.as_deref(), Some("git/status"));
+    // Another project's run does not count.
+    assert_eq!(recent_filter_for(&conn, "cargo test", "/p").unwrap(), None);
+    assert_eq!(recent_filter_for(&conn, "ls -la", "/p").unwrap(), None);
+}
+
+#[test]
+fn recent_filter_ignores_old_runs() {
+    let (_dir, conn) = db();
+    record_run(&conn, "git status", Some("git/status"), "/p");
+    conn.execute("UPDATE events SET timestamp = '2020-01-01T00:00:00Z'", [])
+        .unwrap();
+    assert_eq!(recent_filter_for(&conn, "git status", "/p").unwrap(), None);
+}
+
+#[test]
+fn confusion_sets_signals_against_runs() {
+    let (_dir, conn) = db();
+    for _ in 0..4 {
+        record_run(&conn, "git status", Some("git/status"), "/p");
+    }
+    for _ in 0..2 {
+        record_run(&conn, "cargo test", Some("cargo/test"), "/p");
+    }
+    record_run(&conn, "ls", None, "/p");
+    record_feedback(&conn, "git/status", FeedbackSignal::Rerun, "/p").unwrap();
+    record_feedback(&conn, "cargo/test", FeedbackSignal::Raw, "/p").unwrap();
+    record_feedback(&conn, "cargo/test", FeedbackSignal::Rerun, "/p").unwrap();
+
+    let rows = query_confusion(&conn, &TimeRange::ALL).unwrap();
+    assert_eq!(rows.len(), 2);
+    assert_eq!(rows[0].filter_name, "cargo/test");
+    assert_eq!(rows[0].runs, 2);
+    assert_eq!(rows[0].reruns, 1);
+    assert_eq!(rows[0].raw_requests, 1);
+    assert!((rows[0].confusion_pct - 100.0).abs() < f64::EPSILON);
+    assert_eq!(rows[1].filter_name, "git/status");
+    assert!((rows[1].confusion_pct - 25.0).abs() < f64::EPSILON);
+}
+
+#[test]
+fn confusion_respects_the_range() {
+    let (_dir, conn) = db();
+    record_run(&conn, "git status", Some("git/status"), "/p");
+    record_feedback(&conn, "git/status", FeedbackSignal::Raw, "/p").unwrap();
+    let range = TimeRange {
+        since: None,
+        until: Some("2020-01-01".to_string()),
+    };
+    assert!(query_confusion(&conn, &range).unwrap().is_empty());
+}
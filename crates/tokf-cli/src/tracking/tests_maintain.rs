@@ -0,0 +1,147 @@
+#![allow(clippy::unwrap_used, clippy::expect_used)]
+
+use std::path::Path;
+
+use super::*;
+use tempfile::TempDir;
+
+fn db() -> (TempDir, Connection) {
+    let dir = TempDir::new().unwrap();
+    let conn = open_db(&dir.path().join("tracking.db")).unwrap();
+    (dir, conn)
+}
+
+/// Record one event, then backdate it by `days_ago` days.
+fn record_aged(conn: &Connection, command: &str, days_ago: u32) {
+    record_event(
+        conn,
+        &build_event(command, None, None, 400, 40, 400, 1, 0, false),
+    )
+    .unwrap();
+    conn.execute(
+        "UPDATE events SET timestamp = strftime('%Y-%m-%dT%H:%M:%SZ', 'now', '-' || ?1 || ' days')
+         WHERE id = last_insert_rowid()",
+        [days_ago],
+    )
+    .unwrap();
+}
+
+/// Archive everything older than `after_days`, synced or not.
+const fn after(after_days: u32) -> Archive {
+    Archive {
+        after_days,
+        keep_unsynced: false,
+    }
+}
+
+fn commands(conn: &Connection) -> Vec<String> {
+    let mut stmt = conn
+        .prepare("SELECT command FROM events ORDER BY id")
+        .unwrap();
+    stmt.query_map([], |r| r.get(0))
+        .unwrap()
+        .collect::<rusqlite::Result<_>>()
+        .unwrap()
+}
+
+fn read_archive(path: &Path) -> Vec<serde_json::Value> {
+    let bytes = zstd::decode_all(std::fs::File::open(path).unwrap()).unwrap();
+    String::from_utf8(bytes)
+        .unwrap()
+        .lines()
+        .map(|l| serde_json::from_str(l).unwrap())
+        .collect()
+}
+
+#[test]
+fn archive_moves_only_old_events() {
+    let (dir, conn) = db();
+    record_aged(&conn, "old one", 100);
+    record_aged(&conn, "new", 1);
+    record_aged(&conn, "old two", 40);
+
+    let (moved, path) = archive_events(&conn, &dir.path().join("archive"), after(30))
+        .unwrap()
+        .unwrap();
+    assert_eq!(moved, 2);
+    assert_eq!(commands(&conn), ["new"]);
+
+    let rows = read_archive(&path);
+    let archived: Vec<_> = rows.iter().map(|r| r["command"].clone()).collect();
+    assert_eq!(archived, ["old one", "old two"]);
+    assert_eq!(rows[0]["input_bytes"], 400);
+    assert!(rows[0]["tokenizer"].is_null());
+}
+
+#[test]
+fn registered_machines_keep_unsynced_events() {
+    let (dir, conn) = db();
+    record_aged(&conn, "synced", 100);
+    record_aged(&conn, "unsynced", 100);
+    set_last_synced_id(&conn, 1).unwrap();
+    let archive = dir.path().join("archive");
+    let keep = Archive {
+        after_days: 30,
+        keep_unsynced: true,
+    };
+
+    let (moved, path) = archive_events(&conn, &archive, keep).unwrap().unwrap();
+    assert_eq!(moved, 1);
+    assert_eq!(commands(&conn), ["unsynced"]);
+    assert_eq!(read_archive(&path)[0]["command"], "synced");
+    assert_eq!(get_pending_count(&conn).unwrap(), 1);
+
+    // Nothing synced yet: nothing may go.
+    set_last_synced_id(&conn, 0).unwrap();
+    assert_eq!(archive_events(&conn, &archive, keep).unwrap(), None);
+    assert_eq!(commands(&conn), ["unsynced"]);
+}
+
+#[test]
+fn archive_with_nothing_old_writes_no_file() {
+    let (dir, conn) = db();
+    record_aged(&conn, "new", 1);
+    let archive = dir.path().join("archive");
+    assert_eq!(archive_events(&conn, &archive, after(30)).unwrap(), None);
+    assert!(!archive.exists());
+}
+
+#[test]
+fn maintain_archives_vacuums_and_checks() {
+    let (dir, conn) = db();
+    let db_path = dir.path().join("tracking.db");
+    for _ in 0..200 {
+        record_aged(&conn, &"x".repeat(500), 400);
+    }
+    record_aged(&conn, "new", 0);
+
+    let report = maintain(
+        &conn,
+        &db_path,
+        MaintainOptions {
+            archive: Some(after(365)),
+            vacuum: true,
+        },
+    )
+    .unwrap();
+    let (moved, path) = report.archived.unwrap();
+    assert_eq!(moved, 200);
+    assert_eq!(path.parent().unwrap(), archive_dir(&db_path));
+    assert!(report.size_after < report.size_before);
+    assert!(report.problems.is_empty(), "{:?}", report.problems);
+}
+
+#[test]
+fn light_pass_runs_every_n_events() {
+    let (dir, conn) = db();
+    let db_path = dir.path().join("tracking.db");
+    record_aged(&conn, "old", 100);
+    record_aged(&conn, "new", 1);
+
+    assert!(!maintain_if_due(&conn, &db_path, 3, Some(after(30)), 2).unwrap());
+    assert!(!maintain_if_due(&conn, &db_path, 4, Some(after(30)), 0).unwrap());
+    assert_eq!(commands(&conn), ["old", "new"]);
+
+    assert!(maintain_if_due(&conn, &db_path, 4, Some(after(30)), 2).unwrap());
+    assert_eq!(commands(&conn), ["new"]);
+}
@@ -70,7 +70,7 @@ fn query_summary_pipe_override_count() {
         &build_event("cmd3", Some("f"), None, 400, 400, 400, 5, 0, true),
     )
     .expect("record");
-    let s = query_summary(&conn).expect("summary");
+    let s = query_summary(&conn, &TimeRange::ALL).expect("summary");
     assert_eq!(s.pipe_override_count, 2);
 }
 
@@ -92,7 +92,7 @@ fn query_by_filter_pipe_override_count() {
         &build_event("cmd", Some("f2"), None, 400, 400, 400, 0, 0, true),
     )
     .expect("record");
-    let rows = query_by_filter(&conn).expect("query");
+    let rows = query_by_filter(&conn, &TimeRange::ALL).expect("query");
     let f1 = rows.iter().find(|r| r.filter_name == "f1").expect("f1");
     let f2 = rows.iter().find(|r| r.filter_name == "f2").expect("f2");
     assert_eq!(f1.pipe_override_count, 1);
@@ -112,7 +112,7 @@ fn query_daily_pipe_override_count() {
         &build_event("cmd", Some("f"), None, 400, 200, 400, 0, 0, false),
     )
     .expect("record");
-    let rows = query_daily(&conn).expect("query");
+    let rows = query_daily(&conn, &TimeRange::ALL).expect("query");
     assert_eq!(rows.len(), 1);
     assert_eq!(rows[0].pipe_override_count, 1);
 }
@@ -127,3 +127,51 @@ fn build_event_default_project_is_empty() {
     let ev = build_event("cmd", None, None, 100, 50, 100, 1, 0, false);
     assert_eq!(ev.project, "");
 }
+
+#[test]
+fn query_by_project_groups_and_labels_unknown() {
+    let (_dir, conn) = temp_db();
+    for (project, input) in [
+        ("/work/a", 200),
+        ("/work/b", 100),
+        ("/work/a", 200),
+        ("", 40),
+    ] {
+        let mut ev = build_event("cmd", None, None, input, 40, input, 1, 0, false);
+        ev.project = project.to_string();
+        record_event(&conn, &ev).expect("record");
+    }
+    let rows = query_by_project(&conn, &TimeRange::ALL).expect("query");
+    let summary: Vec<(&str, i64)> = rows
+        .iter()
+        .map(|r| (r.project.as_str(), r.commands))
+        .collect();
+    assert_eq!(
+        summary,
+        [("/work/a", 2), ("/work/b", 1), ("(unknown)", 1)],
+        "ordered by tokens saved"
+    );
+    assert_eq!(
+        rows[0].tokens_saved,
+        rows[0].input_tokens - rows[0].output_tokens
+    );
+}
+
+#[test]
+fn query_project_savings_counts_only_that_project() {
+    let (_dir, conn) = temp_db();
+    for project in ["/work/a", "/work/b", "/work/a"] {
+        let mut ev = build_event("cmd", None, None, 400, 40, 400, 1, 0, false);
+        ev.project = project.to_string();
+        record_event(&conn, &ev).expect("record");
+    }
+    let per_project = query_by_project(&conn, &TimeRange::ALL).expect("query");
+    assert_eq!(
+        query_project_savings(&conn, "/work/a").expect("savings"),
+        (2, per_project[0].tokens_saved)
+    );
+    assert_eq!(
+        query_project_savings(&conn, "/work/c").expect("savings"),
+        (0, 0)
+    );
+}
@@ -0,0 +1,125 @@
+#![allow(clippy::unwrap_used, clippy::expect_used)]
+
+use super::*;
+use tempfile::TempDir;
+
+/// One event per timestamp, each saving 10 tokens.
+fn db_with_events(timestamps: &[&str]) -> (TempDir, Connection) {
+    let dir = TempDir::new().expect("tempdir");
+    let conn = open_db(&dir.path().join("tracking.db")).expect("open");
+    for (i, ts) in timestamps.iter().enumerate() {
+        let filter = if i % 2 == 0 {
+            "git/status"
+        } else {
+            "cargo/test"
+        };
+        conn.execute(
+            "INSERT INTO events (timestamp, command, filter_name, input_bytes, output_bytes,
+                 input_tokens_est, output_tokens_est, filter_time_ms, exit_code)
+             VALUES (?1, 'cmd', ?2, 80, 40, 20, 10, 1, 0)",
+            rusqlite::params![ts, filter],
+        )
+        .expect("insert");
+    }
+    (dir, conn)
+}
+
+fn range(since: Option<&str>, until: Option<&str>) -> TimeRange {
+    TimeRange {
+        since: since.map(str::to_string),
+        until: until.map(str::to_string),
+    }
+}
+
+const EVENTS: &[&str] = &[
+    "2024-05-31T23:59:59Z",
+    "2024-06-01T00:00:00Z",
+    "2024-06-14T23:59:59Z",
+    "2024-06-15T00:00:00Z",
+];
+
+#[test]
+fn range_bounds_include_both_whole_days() {
+    let (_dir, conn) = db_with_events(EVENTS);
+    let sprint = range(Some("2024-06-01"), Some("2024-06-14"));
+    let s = query_summary(&conn, &sprint).expect("summary");
+    assert_eq!(s.total_commands, 2);
+    assert_eq!(s.tokens_saved, 20);
+}
+
+#[test]
+fn open_ended_ranges() {
+    let (_dir, conn) = db_with_events(EVENTS);
+    let since = query_summary(&conn, &range(Some("2024-06-01"), None)).unwrap();
+    assert_eq!(since.total_commands, 3);
+    let until = query_summary(&conn, &range(None, Some("2024-05-31"))).unwrap();
+    assert_eq!(until.total_commands, 1);
+    let all = query_summary(&conn, &TimeRange::ALL).unwrap();
+    assert_eq!(all.total_commands, 4);
+}
+
+#[test]
+fn by_filter_and_daily_respect_the_range() {
+    let (_dir, conn) = db_with_events(EVENTS);
+    let sprint = range(Some("2024-06-01"), Some("2024-06-14"));
+
+    let filters = query_by_filter(&conn, &sprint).unwrap();
+    let commands: i64 = filters.iter().map(|f| f.commands).sum();
+    assert_eq!(commands, 2);
+
+    let days: Vec<String> = query_daily(&conn, &sprint)
+        .unwrap()
+        .into_iter()
+        .map(|d| d.date)
+        .collect();
+    assert_eq!(days, ["2024-06-14", "2024-06-01"]);
+}
+
+#[test]
+fn period_starts_on_or_before_today() {
+    let (_dir, conn) = db_with_events(&[]);
+    let today: String = conn
+        .query_row("SELECT date('now')", [], |r| r.get(0))
+        .unwrap();
+    let day = TimeRange::for_period(&conn, Period::Day).unwrap();
+    assert_eq!(day.since.as_deref(), Some(today.as_str()));
+    assert_eq!(day.until, None);
+
+    let week = TimeRange::for_period(&conn, Period::Week).unwrap();
+    let week_start = week.since.unwrap();
+    assert!(week_start <= today);
+    let weekday: String = conn
+        .query_row("SELECT strftime('%w', ?1)", [&week_start], |r| r.get(0))
+        .unwrap();
+    assert_eq!(weekday, "1", "{week_start} is not a Monday");
+
+    let month = TimeRange::for_period(&conn, Period::Month).unwrap();
+    assert_eq!(month.since.unwrap(), format!("{}-01", &today[..7]));
+}
+
+#[test]
+fn parse_day_accepts_only_dates() {
+    assert_eq!(parse_day("2024-06-01").unwrap(), "2024-06-01");
+    for bad in [
+        "2024-6-1",
+        "2024-13-01",
+        "2024-06-32",
+        "yesterday",
+        "2024-06-01T00:00:00Z",
+    ] {
+        assert!(parse_day(bad).is_err(), "{bad} should be rejected");
+    }
+}
+
+#[test]
+fn describe_names_the_bounds() {
+    assert_eq!(TimeRange::ALL.describe(), "all time");
+    assert_eq!(
+        range(Some("2024-06-01"), None).describe(),
+        "since 2024-06-01"
+    );
+    assert_eq!(
+        range(Some("2024-06-01"), Some("2024-06-14")).describe(),
+        "2024-06-01 to 2024-06-14"
+    );
+}
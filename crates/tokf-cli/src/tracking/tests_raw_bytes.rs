@@ -45,7 +45,7 @@ fn query_summary_includes_raw_tokens() {
     let ev2 = build_event("cmd2", Some("f"), None, 800, 100, 1200, 5, 0, false);
     record_event(&conn, &ev1).expect("record");
     record_event(&conn, &ev2).expect("record");
-    let s = query_summary(&conn).expect("summary");
+    let s = query_summary(&conn, &TimeRange::ALL).expect("summary");
     assert_eq!(
         s.total_raw_tokens,
         est_i64(400) + est_i64(1200),
@@ -19,6 +19,22 @@ fn sync_state_roundtrip() {
     assert_eq!(get_last_synced_id(&conn).unwrap(), 100);
 }
 
+#[test]
+fn max_event_id_survives_deleted_events() {
+    let dir = TempDir::new().unwrap();
+    let conn = open_db(&dir.path().join("test.db")).unwrap();
+    assert_eq!(get_max_event_id(&conn).unwrap(), 0);
+    for _ in 0..3 {
+        record_event(
+            &conn,
+            &build_event("git status", None, None, 100, 20, 0, 0, 0, false),
+        )
+        .unwrap();
+    }
+    conn.execute("DELETE FROM events", []).unwrap();
+    assert_eq!(get_max_event_id(&conn).unwrap(), 3);
+}
+
 // --- get_events_since ---
 
 #[test]
@@ -37,7 +53,7 @@ fn get_events_since_filters_correctly() {
         let _ = i;
     }
     // All 5 events have id 1-5; get events since id=2 → should return ids 3,4,5
-    let events = get_events_since(&conn, 2).unwrap();
+    let events = get_events_since(&conn, 2, 500).unwrap();
     assert_eq!(events.len(), 3);
     assert!(events[0].id > 2);
     assert!(events.iter().all(|e| e.id > 2));
@@ -62,8 +78,8 @@ fn get_events_since_respects_limit_500() {
         .unwrap();
     }
 
-    // Request all events since 0 — should be capped at 500.
-    let events = get_events_since(&conn, 0).unwrap();
+    // Ask for more than 500 — should still be capped at 500.
+    let events = get_events_since(&conn, 0, 1000).unwrap();
     assert_eq!(
         events.len(),
         500,
@@ -79,7 +95,7 @@ fn get_events_since_respects_limit_500() {
 
     // Request with offset inside the 600 — should return remaining (100 events: 501–600).
     let last_id = *ids.last().unwrap(); // 500
-    let rest = get_events_since(&conn, last_id).unwrap();
+    let rest = get_events_since(&conn, last_id, 500).unwrap();
     assert_eq!(
         rest.len(),
         100,
@@ -89,6 +105,9 @@ fn get_events_since_respects_limit_500() {
         rest.iter().all(|e| e.id > last_id),
         "second batch must only contain events with id > {last_id}"
     );
+
+    // A smaller limit is honoured.
+    assert_eq!(get_events_since(&conn, 0, 25).unwrap().len(), 25);
 }
 
 #[test]
@@ -106,7 +125,7 @@ fn get_events_since_includes_null_filter_names() {
     )
     .unwrap();
 
-    let events = get_events_since(&conn, 0).unwrap();
+    let events = get_events_since(&conn, 0, 500).unwrap();
     assert_eq!(events.len(), 1);
     assert_eq!(
         events[0].filter_name, None,
@@ -135,7 +154,7 @@ fn filter_hash_stored_and_retrieved() {
     assert_eq!(ev.filter_hash.as_deref(), Some(hash.as_str()));
     record_event(&conn, &ev).expect("record");
 
-    let events = get_events_since(&conn, 0).unwrap();
+    let events = get_events_since(&conn, 0, 500).unwrap();
     assert_eq!(events.len(), 1);
     assert_eq!(
         events[0].filter_hash.as_deref(),
@@ -152,7 +171,7 @@ fn filter_hash_none_stored_as_null() {
     assert!(ev.filter_hash.is_none());
     record_event(&conn, &ev).expect("record");
 
-    let events = get_events_since(&conn, 0).unwrap();
+    let events = get_events_since(&conn, 0, 500).unwrap();
     assert_eq!(events.len(), 1);
     assert_eq!(
         events[0].filter_hash, None,
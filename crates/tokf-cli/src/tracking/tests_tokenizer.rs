@@ -0,0 +1,78 @@
+#![allow(clippy::unwrap_used, clippy::expect_used)]
+
+use super::*;
+use tempfile::TempDir;
+
+type TokenizerRow = (i64, i64, Option<String>, Option<i64>, Option<i64>);
+
+fn tokenizer_row(conn: &Connection) -> TokenizerRow {
+    conn.query_row(
+        "SELECT input_tokens_est, output_tokens_est,
+                tokenizer, input_tokens_tok, output_tokens_tok FROM events",
+        [],
+        |r| Ok((r.get(0)?, r.get(1)?, r.get(2)?, r.get(3)?, r.get(4)?)),
+    )
+    .expect("select tokenizer counts")
+}
+
+/// One token per whitespace-separated word.
+struct WordCounter;
+
+impl TokenCounter for WordCounter {
+    fn count(&self, text: &str) -> usize {
+        text.split_whitespace().count()
+    }
+}
+
+fn event() -> TrackingEvent {
+    build_event("git log", Some("git/log"), None, 700, 70, 700, 5, 0, false)
+}
+
+#[test]
+fn record_event_keeps_estimates_and_tokenizer_counts() {
+    let dir = TempDir::new().expect("tempdir");
+    let conn = open_db(&dir.path().join("tracking.db")).expect("open_db");
+    let mut ev = event();
+    set_token_counts(&mut ev, "cl100k", &WordCounter, "a b c d", "a");
+    record_event(&conn, &ev).expect("record");
+    assert_eq!(
+        tokenizer_row(&conn),
+        (200, 20, Some("cl100k".to_string()), Some(4), Some(1))
+    );
+}
+
+#[test]
+fn uncounted_events_store_null() {
+    let dir = TempDir::new().expect("tempdir");
+    let conn = open_db(&dir.path().join("tracking.db")).expect("open_db");
+    record_event(&conn, &event()).expect("record");
+    assert_eq!(tokenizer_row(&conn), (200, 20, None, None, None));
+}
+
+#[test]
+fn open_db_adds_tokenizer_columns_to_existing_db() {
+    let dir = TempDir::new().expect("tempdir");
+    let path = dir.path().join("tracking.db");
+    {
+        let conn = open_db(&path).expect("open_db");
+        conn.execute_batch(
+            "ALTER TABLE events DROP COLUMN tokenizer;
+             ALTER TABLE events DROP COLUMN input_tokens_tok;
+             ALTER TABLE events DROP COLUMN output_tokens_tok;",
+        )
+        .expect("drop tokenizer columns");
+        record_event_without_tokenizer(&conn);
+    }
+    let conn = open_db(&path).expect("open_db with migration");
+    assert_eq!(tokenizer_row(&conn), (200, 20, None, None, None));
+}
+
+fn record_event_without_tokenizer(conn: &Connection) {
+    conn.execute(
+        "INSERT INTO events (timestamp, command, input_bytes, output_bytes,
+            input_tokens_est, output_tokens_est, filter_time_ms, exit_code)
+         VALUES ('2024-01-01T00:00:00Z', 'git log', 700, 70, 200, 20, 5, 0)",
+        [],
+    )
+    .expect("insert legacy row");
+}
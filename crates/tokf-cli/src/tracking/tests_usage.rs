@@ -0,0 +1,104 @@
+#![allow(clippy::unwrap_used, clippy::expect_used)]
+
+use std::time::Duration;
+
+use super::*;
+use tempfile::TempDir;
+
+type UsageRow = (Option<i64>, Option<i64>, Option<i64>, Option<i64>);
+
+fn usage_row(conn: &Connection) -> UsageRow {
+    conn.query_row(
+        "SELECT duration_ms, user_cpu_ms, sys_cpu_ms, max_rss_kb FROM events",
+        [],
+        |r| Ok((r.get(0)?, r.get(1)?, r.get(2)?, r.get(3)?)),
+    )
+    .expect("select usage")
+}
+
+fn event() -> TrackingEvent {
+    build_event(
+        "cargo build",
+        Some("cargo/build"),
+        None,
+        200,
+        50,
+        200,
+        5,
+        0,
+        false,
+    )
+}
+
+#[test]
+fn record_event_persists_usage() {
+    let dir = TempDir::new().expect("tempdir");
+    let conn = open_db(&dir.path().join("tracking.db")).expect("open_db");
+    let mut ev = event();
+    set_usage(
+        &mut ev,
+        &ResourceUsage {
+            duration: Duration::from_millis(1500),
+            user_cpu: Some(Duration::from_millis(1200)),
+            sys_cpu: Some(Duration::from_millis(300)),
+            max_rss_kb: Some(2048),
+        },
+    );
+    record_event(&conn, &ev).expect("record");
+    assert_eq!(
+        usage_row(&conn),
+        (Some(1500), Some(1200), Some(300), Some(2048))
+    );
+}
+
+#[test]
+fn unmeasured_usage_is_null() {
+    let dir = TempDir::new().expect("tempdir");
+    let conn = open_db(&dir.path().join("tracking.db")).expect("open_db");
+    let mut ev = event();
+    set_usage(
+        &mut ev,
+        &ResourceUsage {
+            duration: Duration::from_millis(7),
+            ..ResourceUsage::default()
+        },
+    );
+    record_event(&conn, &ev).expect("record");
+    assert_eq!(usage_row(&conn), (Some(7), None, None, None));
+}
+
+#[test]
+fn open_db_migrates_usage_columns_on_legacy_schema() {
+    // Schema before the usage columns: existing rows must survive and read
+    // back as "not measured".
+    let dir = TempDir::new().expect("tempdir");
+    let path = dir.path().join("tracking.db");
+    {
+        let conn = Connection::open(&path).expect("open");
+        conn.execute_batch(
+            "CREATE TABLE events (
+                id                INTEGER PRIMARY KEY AUTOINCREMENT,
+                timestamp         TEXT    NOT NULL,
+                command           TEXT    NOT NULL,
+                filter_name       TEXT,
+                filter_hash       TEXT,
+                input_bytes       INTEGER NOT NULL,
+                output_bytes      INTEGER NOT NULL,
+                input_tokens_est  INTEGER NOT NULL,
+                output_tokens_est INTEGER NOT NULL,
+                filter_time_ms    INTEGER NOT NULL,
+                exit_code         INTEGER NOT NULL,
+                pipe_override     INTEGER NOT NULL DEFAULT 0,
+                raw_bytes         INTEGER NOT NULL DEFAULT 0,
+                raw_tokens_est    INTEGER NOT NULL DEFAULT 0,
+                project           TEXT    NOT NULL DEFAULT ''
+            );
+            INSERT INTO events (timestamp, command, input_bytes, output_bytes,
+                input_tokens_est, output_tokens_est, filter_time_ms, exit_code)
+            VALUES ('2024-01-01T00:00:00Z', 'cargo build', 400, 200, 100, 50, 5, 0);",
+        )
+        .expect("create old schema");
+    }
+    let conn = open_db(&path).expect("open_db with migration");
+    assert_eq!(usage_row(&conn), (None, None, None, None));
+}
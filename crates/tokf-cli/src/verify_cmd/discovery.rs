@@ -16,7 +16,7 @@ pub(super) use tokf::suite_discovery::{
 // Intentionally different from `config::default_search_dirs()`: verify puts
 // `filters/` (stdlib) first so repo developers test the stdlib by default,
 // while the runtime puts `.tokf/filters/` (project overrides) first.
-pub(super) fn verify_search_dirs(rt: &Runtime, scope: Option<&super::VerifyScope>) -> Vec<PathBuf> {
+pub fn verify_search_dirs(rt: &Runtime, scope: Option<&super::VerifyScope>) -> Vec<PathBuf> {
     match scope {
         Some(super::VerifyScope::Project) => {
             let mut dirs = Vec::new();
@@ -6,6 +6,9 @@ use tokf_common::examples::ExamplesSafety;
 
 use self::discovery::DiscoveredSuite;
 
+pub use discovery::verify_search_dirs;
+pub use runner::{load_case, load_fixture};
+
 pub use tokf_common::test_case::TestCase;
 
 use tokf::runtime::Runtime;
@@ -28,7 +28,7 @@ fn read_fixture_file(path: &Path, fixture_name: &str) -> anyhow::Result<String>
     Ok(content)
 }
 
-fn load_fixture(case: &TestCase, case_path: &Path) -> anyhow::Result<String> {
+pub fn load_fixture(case: &TestCase, case_path: &Path) -> anyhow::Result<String> {
     if let Some(inline) = &case.inline {
         // Inline TOML strings already handle escape sequences (TOML spec)
         return Ok(inline.trim_end().to_string());
@@ -301,7 +301,7 @@ fn apply_and_check(
     (filtered.output, failures)
 }
 
-fn load_case(case_path: &Path) -> anyhow::Result<TestCase> {
+pub fn load_case(case_path: &Path) -> anyhow::Result<TestCase> {
     let content = std::fs::read_to_string(case_path)
         .map_err(|e| anyhow::anyhow!("cannot read {}: {e}", case_path.display()))?;
     let case: TestCase = toml::from_str(&content)
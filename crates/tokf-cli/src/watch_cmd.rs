@@ -0,0 +1,309 @@
+//! `tokf watch <command>`: run a command through its filter, then run it again
+//! each time a watched file changes, for a continuous test loop.
+//!
+//! Files are polled rather than subscribed to, so the loop behaves the same on
+//! every platform and filesystem (network mounts, containers).
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+use regex::Regex;
+use tokf::config::types::StdinMode;
+use tokf::runner::signals_received;
+use tokf::telemetry;
+
+use crate::Cli;
+use crate::resolve;
+use crate::run_cmd::{self, RunRequest};
+use crate::run_envelope::RunFormat;
+
+use tokf::runtime::Runtime;
+
+/// Directories never watched: VCS metadata, build output and dependencies
+/// change on every run and would re-trigger it.
+const SKIPPED_DIRS: &[&str] = &["target", "node_modules"];
+
+#[derive(clap::Args, Debug)]
+pub struct WatchArgs {
+    /// Re-run when a file matching this glob changes (repeatable); overrides
+    /// the filter's `watch` list. `*.rs` matches by name, `src/**/*.ts` by path
+    #[arg(long = "glob", value_name = "GLOB")]
+    pub globs: Vec<String>,
+    /// Wait until files have stopped changing for this long before re-running
+    #[arg(long, default_value_t = 200)]
+    pub debounce_ms: u64,
+    /// How often to check files for changes
+    #[arg(long, default_value_t = 500)]
+    pub poll_ms: u64,
+    #[arg(trailing_var_arg = true, required = true)]
+    pub command_args: Vec<String>,
+}
+
+/// Modification time of every watched file.
+type Snapshot = BTreeMap<PathBuf, SystemTime>;
+
+/// The files under a directory that `tokf watch` re-runs on.
+struct Watcher {
+    root: PathBuf,
+    /// Empty matches any file.
+    globs: Vec<Regex>,
+}
+
+impl Watcher {
+    fn new(root: &Path, globs: &[String]) -> anyhow::Result<Self> {
+        let globs = globs
+            .iter()
+            .map(|g| {
+                Regex::new(&path_glob_to_regex(g))
+                    .map_err(|e| anyhow::anyhow!("invalid watch glob {g:?}: {e}"))
+            })
+            .collect::<anyhow::Result<_>>()?;
+        let root = if root.as_os_str().is_empty() {
+            PathBuf::from(".")
+        } else {
+            root.to_path_buf()
+        };
+        Ok(Self { root, globs })
+    }
+
+    /// Whether `relative` (with `/` separators) is watched.
+    fn is_watched(&self, relative: &str) -> bool {
+        if self.globs.is_empty() {
+            return true;
+        }
+        let name = relative.rsplit('/').next().unwrap_or(relative);
+        self.globs
+            .iter()
+            .any(|re| re.is_match(relative) || re.is_match(name))
+    }
+
+    fn snapshot(&self) -> Snapshot {
+        let mut snapshot = Snapshot::new();
+        self.walk(&self.root, &mut snapshot);
+        snapshot
+    }
+
+    fn walk(&self, dir: &Path, snapshot: &mut Snapshot) {
+        let Ok(entries) = std::fs::read_dir(dir) else {
+            return;
+        };
+        for entry in entries.flatten() {
+            let name = entry.file_name();
+            let name = name.to_string_lossy();
+            let Ok(file_type) = entry.file_type() else {
+                continue;
+            };
+            let path = entry.path();
+            if file_type.is_dir() {
+                if !name.starts_with('.') && !SKIPPED_DIRS.contains(&name.as_ref()) {
+                    self.walk(&path, snapshot);
+                }
+                continue;
+            }
+            let Ok(relative) = path.strip_prefix(&self.root) else {
+                continue;
+            };
+            let relative = relative.to_string_lossy().replace('\\', "/");
+            if self.is_watched(&relative) {
+                let modified = entry
+                    .metadata()
+                    .and_then(|m| m.modified())
+                    .unwrap_or(SystemTime::UNIX_EPOCH);
+                snapshot.insert(relative.into(), modified);
+            }
+        }
+    }
+}
+
+/// Regex for a path glob: `*` and `?` stay within one path component, `**`
+/// spans directories and `{a,b}` is an alternation.
+fn path_glob_to_regex(glob: &str) -> String {
+    let mut out = String::from("^");
+    let mut chars = glob.chars().peekable();
+    let mut in_braces = false;
+    while let Some(c) = chars.next() {
+        match c {
+            '*' if chars.peek() == Some(&'*') => {
+                chars.next();
+                if chars.peek() == Some(&'/') {
+                    chars.next();
+                    out.push_str("(?:.*/)?");
+                } else {
+                    out.push_str(".*");
+                }
+            }
+            '*' => out.push_str("[^/]*"),
+            '?' => out.push_str("[^/]"),
+            '{' => {
+                in_braces = true;
+                out.push_str("(?:");
+            }
+            '}' if in_braces => {
+                in_braces = false;
+                out.push(')');
+            }
+            ',' if in_braces => out.push('|'),
+            c => out.push_str(&regex::escape(c.encode_utf8(&mut [0; 4]))),
+        }
+    }
+    out.push('$');
+    out
+}
+
+/// The first file added, changed or removed between two snapshots.
+fn first_change(old: &Snapshot, new: &Snapshot) -> Option<PathBuf> {
+    new.iter()
+        .find(|(path, modified)| old.get(*path) != Some(modified))
+        .map(|(path, _)| path.clone())
+        .or_else(|| old.keys().find(|path| !new.contains_key(*path)).cloned())
+}
+
+/// Block until a watched file changes and the files have then been quiet for
+/// the debounce period. `None` when tokf is asked to stop first.
+fn wait_for_change(
+    watcher: &Watcher,
+    snapshot: &mut Snapshot,
+    args: &WatchArgs,
+    signals: usize,
+) -> Option<PathBuf> {
+    let poll = Duration::from_millis(args.poll_ms.max(10));
+    let debounce = Duration::from_millis(args.debounce_ms);
+    let path = loop {
+        std::thread::sleep(poll);
+        if signals_received() != signals {
+            return None;
+        }
+        let next = watcher.snapshot();
+        if let Some(path) = first_change(snapshot, &next) {
+            *snapshot = next;
+            break path;
+        }
+    };
+    loop {
+        std::thread::sleep(debounce);
+        let next = watcher.snapshot();
+        if next == *snapshot {
+            return Some(path);
+        }
+        *snapshot = next;
+    }
+}
+
+/// The globs to watch: `--glob`, else the matched filter's `watch` list.
+fn watch_globs(rt: &Runtime, args: &WatchArgs) -> Vec<String> {
+    if !args.globs.is_empty() {
+        return args.globs.clone();
+    }
+    resolve::find_filter(rt, &args.command_args, false, false)
+        .ok()
+        .flatten()
+        .map(|m| m.config.watch)
+        .unwrap_or_default()
+}
+
+/// `tokf watch`: returns the exit code of the last run once tokf is
+/// interrupted (Ctrl-C or SIGTERM).
+pub fn cmd_watch(
+    rt: &Runtime,
+    args: &WatchArgs,
+    cli: &Cli,
+    reporter: &dyn telemetry::TelemetryReporter,
+) -> anyhow::Result<i32> {
+    let globs = watch_globs(rt, args);
+    let watcher = Watcher::new(rt.cwd_or_empty(), &globs)?;
+    let watching = if globs.is_empty() {
+        "all files".to_string()
+    } else {
+        globs.join(", ")
+    };
+    let signals = signals_received();
+    let mut snapshot = watcher.snapshot();
+    loop {
+        let code = run_cmd::cmd_run(
+            rt,
+            RunRequest {
+                command_args: &args.command_args,
+                baseline_pipe: None,
+                prefer_less: false,
+                pty: false,
+                stdin: Some(StdinMode::Null),
+                format: RunFormat::Text,
+                tee_raw: None,
+            },
+            cli,
+            reporter,
+        )?;
+        if signals_received() != signals {
+            return Ok(code);
+        }
+        eprintln!("[tokf] watch: exit {code}; watching {watching} (Ctrl-C to stop)");
+        let Some(path) = wait_for_change(&watcher, &mut snapshot, args, signals) else {
+            return Ok(code);
+        };
+        eprintln!("[tokf] watch: {} changed — re-running", path.display());
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    fn watcher(globs: &[&str]) -> Watcher {
+        let globs: Vec<String> = globs.iter().map(ToString::to_string).collect();
+        Watcher::new(Path::new("."), &globs).unwrap()
+    }
+
+    #[test]
+    fn glob_without_slash_matches_file_name_anywhere() {
+        let w = watcher(&["*.rs"]);
+        assert!(w.is_watched("main.rs"));
+        assert!(w.is_watched("src/deep/lib.rs"));
+        assert!(!w.is_watched("src/lib.rs.bak"));
+    }
+
+    #[test]
+    fn glob_with_slash_matches_path() {
+        let w = watcher(&["src/**/*.{ts,tsx}", "Cargo.toml"]);
+        assert!(w.is_watched("src/app.ts"));
+        assert!(w.is_watched("src/a/b/view.tsx"));
+        assert!(!w.is_watched("test/app.ts"));
+        assert!(w.is_watched("crates/x/Cargo.toml"));
+    }
+
+    #[test]
+    fn no_globs_watch_everything() {
+        assert!(watcher(&[]).is_watched("anything/at/all"));
+    }
+
+    #[test]
+    fn snapshot_skips_hidden_and_build_dirs() {
+        let dir = tempfile::TempDir::new().unwrap();
+        for file in ["a.txt", ".git/HEAD", "target/out.txt", "sub/b.txt"] {
+            let path = dir.path().join(file);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, "x").unwrap();
+        }
+        let w = Watcher::new(dir.path(), &[]).unwrap();
+        let files: Vec<PathBuf> = w.snapshot().into_keys().collect();
+        assert_eq!(files, [PathBuf::from("a.txt"), PathBuf::from("sub/b.txt")]);
+    }
+
+    #[test]
+    fn first_change_reports_added_modified_and_removed_files() {
+        let t0 = SystemTime::UNIX_EPOCH;
+        let t1 = t0 + Duration::from_secs(1);
+        let old = Snapshot::from([("a".into(), t0), ("b".into(), t0)]);
+        assert_eq!(first_change(&old, &old), None);
+
+        let modified = Snapshot::from([("a".into(), t0), ("b".into(), t1)]);
+        assert_eq!(first_change(&old, &modified), Some("b".into()));
+
+        let removed = Snapshot::from([("a".into(), t0)]);
+        assert_eq!(first_change(&old, &removed), Some("b".into()));
+
+        let added = Snapshot::from([("a".into(), t0), ("b".into(), t0), ("c".into(), t0)]);
+        assert_eq!(first_change(&old, &added), Some("c".into()));
+    }
+}
@@ -1,70 +1,130 @@
+use serde::Serialize;
 use tokf::config;
 use tokf::rewrite;
 
+use crate::ls_cmd::FilterInfo;
 use crate::resolve;
 
 use tokf::runtime::Runtime;
 
+/// How a command resolves, as `tokf which --json` and the MCP `which_filter`
+/// tool report it.
+#[derive(Serialize)]
+pub struct WhichJson<'a> {
+    command: &'a str,
+    matched: bool,
+    filter: Option<FilterInfo<'a>>,
+    /// The pattern that matched.
+    pattern: Option<&'a str>,
+    /// Words of the command the match covers, a local wrapper prefix included.
+    words_consumed: usize,
+    /// Command of the variant a project file selected, if any.
+    variant: Option<String>,
+    /// Variants left to decide on the command's output.
+    deferred_variants: Vec<String>,
+    #[serde(skip)]
+    variant_count: usize,
+}
+
+impl<'a> WhichJson<'a> {
+    const fn unmatched(command: &'a str) -> Self {
+        Self {
+            command,
+            matched: false,
+            filter: None,
+            pattern: None,
+            words_consumed: 0,
+            variant: None,
+            deferred_variants: Vec::new(),
+            variant_count: 0,
+        }
+    }
+}
+
+/// Resolve `command` against `filters`, directly or after stripping a local
+/// environment wrapper prefix (e.g. `nix develop -c cargo test` reports the
+/// `cargo test` filter).
+pub fn resolve_which<'a>(
+    rt: &Runtime,
+    filters: &'a [config::ResolvedFilter],
+    command: &'a str,
+    verbose: bool,
+) -> WhichJson<'a> {
+    let words: Vec<&str> = command.split_whitespace().collect();
+    let wrapper_cfg = rewrite::load_local_wrapper_config(rt);
+    let Some((filter, pattern, consumed)) =
+        config::local_wrapper::match_filters_with_wrapper(filters, &words, &wrapper_cfg)
+    else {
+        return WhichJson::unmatched(command);
+    };
+
+    let resolution = (!filter.config.variant.is_empty()).then(|| {
+        config::variant::resolve_variants(&filter.config, filters, rt.cwd_or_empty(), verbose)
+    });
+    let variant = resolution
+        .as_ref()
+        .map(|res| res.config.command.first().to_string())
+        .filter(|resolved| resolved != filter.config.command.first());
+    let deferred_variants = resolution
+        .as_ref()
+        .map(|res| res.output_variants.iter().map(|v| v.name.clone()).collect())
+        .unwrap_or_default();
+
+    WhichJson {
+        command,
+        matched: true,
+        filter: Some(FilterInfo::from(filter)),
+        pattern: Some(pattern),
+        words_consumed: consumed,
+        variant,
+        deferred_variants,
+        variant_count: filter.config.variant.len(),
+    }
+}
+
 /// `tokf which <command>` — report which filter (if any) matches a command,
 /// including through a local environment wrapper such as `nix develop -c`.
-pub fn cmd_which(rt: &Runtime, command: &str, verbose: bool) -> i32 {
+pub fn cmd_which(rt: &Runtime, command: &str, verbose: bool, json: bool) -> i32 {
     let Ok(filters) = resolve::discover_filters(rt, false) else {
         eprintln!("[tokf] error: failed to discover filters");
         return 1;
     };
 
-    let words: Vec<&str> = command.split_whitespace().collect();
-    let cwd = rt.cwd_or_empty();
-    let wrapper_cfg = rewrite::load_local_wrapper_config(rt);
-
-    // Match directly, or after stripping a local environment wrapper prefix
-    // (e.g. `nix develop -c cargo test` reports the `cargo test` filter).
-    let Some((filter, _pattern, _consumed)) =
-        config::local_wrapper::match_filters_with_wrapper(&filters, &words, &wrapper_cfg)
-    else {
+    let which = resolve_which(rt, &filters, command, verbose);
+    if json {
+        crate::output::print_json(&which);
+        return i32::from(!which.matched);
+    }
+    let Some(filter) = &which.filter else {
         eprintln!("[tokf] no filter found for \"{command}\"");
         return 1;
     };
 
-    let display_name = filter
-        .relative_path
-        .with_extension("")
-        .display()
-        .to_string();
-
-    let variant_info = if filter.config.variant.is_empty() {
-        String::new()
-    } else {
-        let res = config::variant::resolve_variants(&filter.config, &filters, cwd, verbose);
-        let resolved = res.config.command.first().to_string();
-        if resolved != filter.config.command.first() {
-            format!(" -> variant: \"{resolved}\"")
-        } else if res.output_variants.is_empty() {
-            format!(
-                " ({} variant(s), none matched by file)",
-                filter.config.variant.len()
-            )
-        } else {
-            let names: Vec<&str> = res
-                .output_variants
-                .iter()
-                .map(|v| v.name.as_str())
-                .collect();
-            format!(
-                " ({} variant(s), {} deferred to output-pattern: {})",
-                filter.config.variant.len(),
-                res.output_variants.len(),
-                names.join(", ")
-            )
-        }
-    };
+    let variant_info = variant_info(
+        which.variant_count,
+        which.variant.as_deref(),
+        &which.deferred_variants,
+    );
     println!(
-        "{display_name}  [{}]  command: \"{}\"{variant_info}",
-        filter.priority_label(),
-        filter.config.command.first()
+        "{}  [{}]  command: \"{}\"{variant_info}",
+        filter.name, filter.priority, filter.command
     );
     if verbose {
-        eprintln!("[tokf] source: {}", filter.source_path.display());
+        eprintln!("[tokf] source: {}", filter.source);
     }
     0
 }
+
+/// The text `which` appends for a filter with `count` variants.
+fn variant_info(count: usize, selected: Option<&str>, deferred: &[String]) -> String {
+    match selected {
+        _ if count == 0 => String::new(),
+        Some(resolved) => format!(" -> variant: \"{resolved}\""),
+        None if deferred.is_empty() => format!(" ({count} variant(s), none matched by file)"),
+        None => format!(
+            " ({count} variant(s), {} deferred to output-pattern: {})",
+            deferred.len(),
+            deferred.join(", ")
+        ),
+    }
+}
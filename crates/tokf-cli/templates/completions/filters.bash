@@ -0,0 +1,19 @@
+
+# Complete filter names for `tokf show`, `tokf eject` and `tokf verify`.
+_tokf_filters() {
+    local cur="${COMP_WORDS[COMP_CWORD]}" i
+    local -a positional=()
+    for ((i = 1; i < COMP_CWORD; i++)); do
+        [[ "${COMP_WORDS[i]}" == -* ]] || positional+=("${COMP_WORDS[i]}")
+    done
+    if [[ ${#positional[@]} -eq 1 && "$cur" != -* ]]; then
+        case "${positional[0]}" in
+            show | eject | verify)
+                mapfile -t COMPREPLY < <(compgen -W "$(tokf __complete-filters 2>/dev/null)" -- "$cur")
+                return 0
+                ;;
+        esac
+    fi
+    _tokf "$@"
+}
+complete -F _tokf_filters -o bashdefault -o default tokf
@@ -0,0 +1,3 @@
+
+# Complete filter names for `tokf show`, `tokf eject` and `tokf verify`.
+complete -c tokf -n "__fish_tokf_using_subcommand show eject verify; and test (count (commandline -opc)) -le 2" -f -a "(tokf __complete-filters 2>/dev/null)"
@@ -0,0 +1,7 @@
+    # Complete filter names for `tokf show`, `tokf eject` and `tokf verify`.
+    if (@('tokf;show', 'tokf;eject', 'tokf;verify') -contains $command) {
+        $completions += @(tokf __complete-filters 2>$null | ForEach-Object {
+            [CompletionResult]::new($_, $_, [CompletionResultType]::ParameterValue, $_)
+        })
+    }
+
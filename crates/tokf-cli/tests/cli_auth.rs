@@ -87,3 +87,68 @@ fn auth_help_shows_subcommands() {
         "expected 'status' in help: {stdout}"
     );
 }
+
+#[test]
+fn auth_keys_show_without_key() {
+    let (mut cmd, _dir) = tokf_isolated();
+    let output = cmd.args(["auth", "keys", "show"]).output().unwrap();
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("tokf auth keys generate"),
+        "expected a hint to generate a key, got: {stderr}"
+    );
+}
+
+#[test]
+fn auth_keys_generate_requires_login_before_writing_a_key() {
+    let (mut cmd, dir) = tokf_isolated();
+    let output = cmd.args(["auth", "keys", "generate"]).output().unwrap();
+    assert!(
+        !output.status.success(),
+        "expected non-zero exit when not logged in"
+    );
+    assert!(
+        !dir.path().join("signing_key").exists(),
+        "no key should be written when registration cannot happen"
+    );
+}
+
+#[test]
+fn auth_status_reports_an_env_token() {
+    let (mut cmd, _dir) = tokf_isolated();
+    let output = cmd
+        .args(["auth", "status"])
+        .env("TOKF_TOKEN", "tokf_pat_abc")
+        .env("TOKF_SERVER_URL", "https://ci.example.com")
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("TOKF_TOKEN"), "got: {stdout}");
+    assert!(stdout.contains("https://ci.example.com"), "got: {stdout}");
+}
+
+#[test]
+fn auth_token_create_requires_a_scope() {
+    let (mut cmd, _dir) = tokf_isolated();
+    let output = cmd
+        .args(["auth", "token", "create", "ci"])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("--scope"), "got: {stderr}");
+}
+
+#[test]
+fn auth_token_create_requires_login() {
+    let (mut cmd, _dir) = tokf_isolated();
+    let output = cmd
+        .args(["auth", "token", "create", "ci", "--scope", "publish"])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("tokf auth login"), "got: {stderr}");
+}
@@ -0,0 +1,71 @@
+#![allow(clippy::unwrap_used, clippy::expect_used)]
+
+mod common;
+use common::tokf;
+
+// --- tokf bench ---
+
+fn project_with_suite() -> tempfile::TempDir {
+    let dir = tempfile::TempDir::new().unwrap();
+    let filters = dir.path().join(".tokf/filters/demo");
+    std::fs::create_dir_all(filters.join("build_test")).unwrap();
+    std::fs::write(
+        filters.join("build.toml"),
+        "command = \"demo build\"\nskip = [\"^Compiling\"]\n",
+    )
+    .unwrap();
+    let mut fixture = "Compiling crate\n".repeat(5000);
+    fixture.push_str("done\n");
+    std::fs::write(filters.join("build_test/success.txt"), fixture).unwrap();
+    std::fs::write(
+        filters.join("build_test/success.toml"),
+        "name = \"success\"\nfixture = \"success.txt\"\n\n[[expect]]\nequals = \"done\"\n",
+    )
+    .unwrap();
+    dir
+}
+
+fn bench(dir: &tempfile::TempDir, extra: &[&str]) -> std::process::Output {
+    tokf()
+        .args(["bench", "--scope", "project", "-n", "3"])
+        .args(extra)
+        .current_dir(dir.path())
+        .output()
+        .unwrap()
+}
+
+#[test]
+fn bench_reports_and_saves_a_baseline() {
+    let dir = project_with_suite();
+    let output = bench(&dir, &["--save-baseline"]);
+    assert!(output.status.success(), "{output:?}");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("demo/build"), "{stdout}");
+
+    let saved = std::fs::read_to_string(dir.path().join(".tokf/bench.json")).unwrap();
+    let saved: serde_json::Value = serde_json::from_str(&saved).unwrap();
+    assert_eq!(saved["filters"][0]["filter_name"], "demo/build");
+    assert_eq!(saved["filters"][0]["cases"], 1);
+}
+
+#[test]
+fn fail_on_regress_needs_a_baseline() {
+    let dir = project_with_suite();
+    let output = bench(&dir, &["--fail-on-regress", "10"]);
+    assert_eq!(output.status.code(), Some(2), "{output:?}");
+    assert!(String::from_utf8_lossy(&output.stderr).contains("--save-baseline"));
+}
+
+#[test]
+fn fail_on_regress_exits_1_when_a_filter_grew() {
+    let dir = project_with_suite();
+    std::fs::write(
+        dir.path().join(".tokf/bench.json"),
+        r#"{"filters":[{"filter_name":"demo/build","cases":1,"p50_ns":1,"p95_ns":1,"peak_alloc_bytes":1}]}"#,
+    )
+    .unwrap();
+    let output = bench(&dir, &["--fail-on-regress", "10", "--json"]);
+    assert_eq!(output.status.code(), Some(1), "{output:?}");
+    let reports: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(reports[0]["change"]["regressed"], true);
+}
@@ -55,6 +55,42 @@ fn check_invalid_toml() {
     );
 }
 
+// --- tokf lint ---
+
+#[test]
+fn lint_reports_positions_and_fails_on_errors() {
+    let dir = tempfile::TempDir::new().unwrap();
+    let filter = dir.path().join("bad.toml");
+    std::fs::write(&filter, "command = \"t\"\nskip = [\"(x\"]\n").unwrap();
+
+    let output = tokf()
+        .args(["lint", filter.to_str().unwrap()])
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(1));
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.contains("bad.toml:2:9: error: invalid regex"),
+        "got: {stdout}"
+    );
+
+    let output = tokf()
+        .args(["lint", "--json", filter.to_str().unwrap()])
+        .output()
+        .unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(json[0]["line"], 2);
+    assert_eq!(json[0]["key"], "skip[0]");
+}
+
+#[test]
+fn lint_stdlib_filter_is_clean() {
+    let filter = format!("{}/filters/git/push.toml", manifest_dir());
+    let output = tokf().args(["lint", &filter]).output().unwrap();
+    assert!(output.status.success());
+    assert!(output.stdout.is_empty());
+}
+
 // --- tokf apply ---
 
 #[test]
@@ -164,3 +200,19 @@ fn test_with_timing() {
         "expected timing info on stderr, got: {stderr}"
     );
 }
+
+#[test]
+fn test_trace_reports_stages_on_stderr() {
+    let filter = format!("{}/filters/cargo/build.toml", manifest_dir());
+    let fixture = format!("{}/filters/cargo/build_test/success.txt", manifest_dir());
+    let output = tokf()
+        .args(["apply", "--trace", &filter, &fixture])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert_eq!(stdout.trim(), "ok ✓");
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("branch: on_success"), "got: {stderr}");
+    assert!(stderr.contains("[skip]    Compiling "), "got: {stderr}");
+}
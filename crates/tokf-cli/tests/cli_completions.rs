@@ -119,3 +119,54 @@ fn completions_missing_shell_arg_fails() {
         "expected failure when shell arg is missing"
     );
 }
+
+#[test]
+fn complete_filters_lists_project_and_builtin_filters() {
+    let dir = tempfile::TempDir::new().unwrap();
+    let filters = dir.path().join(".tokf/filters/acme");
+    std::fs::create_dir_all(&filters).unwrap();
+    std::fs::write(filters.join("deploy.toml"), "command = \"acme deploy\"\n").unwrap();
+
+    let output = tokf()
+        .arg("__complete-filters")
+        .current_dir(dir.path())
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let names: Vec<&str> = stdout.lines().collect();
+    assert!(names.contains(&"acme/deploy"), "{stdout}");
+    assert!(names.contains(&"git/push"), "{stdout}");
+}
+
+#[cfg(unix)]
+#[test]
+fn bash_completes_filter_names_for_show() {
+    let script = tokf()
+        .args(["completions", "bash"])
+        .output()
+        .unwrap()
+        .stdout;
+    let dir = tempfile::TempDir::new().unwrap();
+    let path = dir.path().join("tokf.bash");
+    std::fs::write(&path, script).unwrap();
+    let bin_dir = std::path::Path::new(env!("CARGO_BIN_EXE_tokf"))
+        .parent()
+        .unwrap();
+
+    let output = common::isolated_tool("bash", dir.path())
+        .arg("-c")
+        .arg(format!(
+            "source {}; COMP_WORDS=(tokf show git/pu); COMP_CWORD=2; \
+             _tokf_filters; printf '%s\\n' \"${{COMPREPLY[@]}}\"",
+            path.display()
+        ))
+        .env(
+            "PATH",
+            format!("{}:{}", bin_dir.display(), std::env::var("PATH").unwrap()),
+        )
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert_eq!(stdout.trim(), "git/push", "{output:?}");
+}
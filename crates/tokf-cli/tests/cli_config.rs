@@ -102,7 +102,7 @@ fn config_show_json_valid() {
         serde_json::from_str(&stdout).expect("config show --json should be valid JSON");
     assert!(parsed.is_array(), "expected JSON array, got: {parsed}");
     let arr = parsed.as_array().unwrap();
-    assert_eq!(arr.len(), 5, "expected 5 config entries");
+    assert_eq!(arr.len(), 14, "expected 14 config entries");
     assert!(arr[0]["key"].is_string());
     assert!(arr[0]["value"].is_string());
     assert!(arr[0]["source"].is_string());
@@ -379,3 +379,117 @@ fn config_set_shims_enabled_roundtrip() {
     let stdout = String::from_utf8_lossy(&get_output.stdout);
     assert_eq!(stdout.trim(), "false");
 }
+
+#[test]
+fn config_get_global_ignores_local_value() {
+    let tmp = TempDir::new().unwrap();
+    let home = tmp.path().join("home");
+    std::fs::create_dir_all(tmp.path().join(".tokf")).unwrap();
+
+    let output = tokf()
+        .current_dir(tmp.path())
+        .env("TOKF_HOME", home.to_str().unwrap())
+        .args(["config", "set", "--local", "run.mask_exit_code", "false"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    let get = |global: bool| {
+        let mut args = vec!["config", "get", "run.mask_exit_code"];
+        if global {
+            args.push("--global");
+        }
+        let output = tokf()
+            .current_dir(tmp.path())
+            .env("TOKF_HOME", home.to_str().unwrap())
+            .args(&args)
+            .output()
+            .unwrap();
+        String::from_utf8_lossy(&output.stdout).trim().to_string()
+    };
+    assert_eq!(get(false), "false");
+    assert_eq!(get(true), "true");
+}
+
+#[test]
+fn config_list_shows_disabled_filters() {
+    let tmp = TempDir::new().unwrap();
+    let home = tmp.path().join("home");
+
+    let output = tokf()
+        .current_dir(tmp.path())
+        .env("TOKF_HOME", home.to_str().unwrap())
+        .args([
+            "config",
+            "set",
+            "filters.disabled",
+            "git/log,cargo/build.toml",
+        ])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    let output = tokf()
+        .current_dir(tmp.path())
+        .env("TOKF_HOME", home.to_str().unwrap())
+        .args(["config", "list"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.contains("filters.disabled = git/log,cargo/build  (global:"),
+        "expected disabled filters:\n{stdout}"
+    );
+}
+
+#[test]
+fn config_set_invalid_filter_name_fails() {
+    let tmp = TempDir::new().unwrap();
+
+    let output = tokf()
+        .env("TOKF_HOME", tmp.path().to_str().unwrap())
+        .args(["config", "set", "filters.disabled", "git log"])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("invalid value for filters.disabled"),
+        "stderr: {stderr}"
+    );
+}
+
+#[test]
+fn config_set_pipe_strip_reaches_rewrites() {
+    let tmp = TempDir::new().unwrap();
+    let home = tmp.path().join("home");
+    let run = |args: &[&str]| {
+        tokf()
+            .current_dir(tmp.path())
+            .env("TOKF_HOME", home.to_str().unwrap())
+            .args(args)
+            .output()
+            .unwrap()
+    };
+    let piped = "cargo test | tail -5";
+    let stripped = String::from_utf8_lossy(&run(&["rewrite", piped]).stdout).into_owned();
+    assert_ne!(stripped.trim(), piped, "stdlib should strip the pipe");
+
+    let set = run(&["config", "set", "pipe.strip", "false"]);
+    assert!(
+        set.status.success(),
+        "stderr: {}",
+        String::from_utf8_lossy(&set.stderr)
+    );
+    let get = run(&["config", "get", "pipe.strip"]);
+    assert_eq!(String::from_utf8_lossy(&get.stdout).trim(), "false");
+    let content = std::fs::read_to_string(home.join("rewrites.toml")).unwrap();
+    assert!(content.contains("strip = false"), "got:\n{content}");
+    // The pipe is no longer stripped, so the command is left alone.
+    let rewritten = run(&["rewrite", piped]);
+    assert_eq!(String::from_utf8_lossy(&rewritten.stdout).trim(), piped);
+
+    let bad = run(&["config", "set", "pipe.prefer_less", "yes"]);
+    assert!(!bad.status.success());
+}
@@ -0,0 +1,67 @@
+#![allow(clippy::unwrap_used, clippy::expect_used)]
+#![cfg(unix)]
+
+mod common;
+use common::TestHome;
+
+use std::process::{Child, Stdio};
+use std::time::{Duration, Instant};
+
+fn daemon(home: &TestHome, action: &str) -> std::process::Output {
+    home.cmd().args(["daemon", action]).output().unwrap()
+}
+
+fn spawn_daemon(home: &TestHome) -> Child {
+    let child = home
+        .cmd()
+        .args(["daemon", "run"])
+        .stderr(Stdio::null())
+        .spawn()
+        .unwrap();
+    let deadline = Instant::now() + Duration::from_secs(10);
+    while !daemon(home, "status").status.success() {
+        assert!(Instant::now() < deadline, "daemon did not start");
+        std::thread::sleep(Duration::from_millis(20));
+    }
+    child
+}
+
+#[test]
+fn status_without_daemon_reports_not_running() {
+    let home = TestHome::new();
+    let output = daemon(&home, "status");
+    assert!(!output.status.success());
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout).trim(),
+        "daemon: not running"
+    );
+}
+
+#[test]
+fn hook_rewrites_through_running_daemon() {
+    let home = TestHome::new();
+    let mut child = spawn_daemon(&home);
+
+    let mut hook = home
+        .cmd()
+        .args(["hook", "handle"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    std::io::Write::write_all(
+        hook.stdin.as_mut().unwrap(),
+        br#"{"tool_name":"Bash","tool_input":{"command":"git status"}}"#,
+    )
+    .unwrap();
+    let output = hook.wait_with_output().unwrap();
+    assert!(String::from_utf8_lossy(&output.stdout).contains("tokf run git status"));
+
+    let status = String::from_utf8_lossy(&daemon(&home, "status").stdout).into_owned();
+    // The pattern set exists only if the rewrite went through the daemon.
+    assert!(status.contains("pattern sets:    1"), "{status}");
+
+    assert!(daemon(&home, "stop").status.success());
+    child.wait().unwrap();
+    assert!(!daemon(&home, "status").status.success());
+}
@@ -0,0 +1,74 @@
+//! `tokf db maintain`.
+
+#![allow(clippy::unwrap_used, clippy::expect_used)]
+
+mod common;
+use common::TestHome;
+
+fn maintain(home: &TestHome, args: &[&str]) -> (bool, String) {
+    let output = home
+        .cmd()
+        .args(["db", "maintain"])
+        .args(args)
+        .output()
+        .unwrap();
+    (
+        output.status.success(),
+        String::from_utf8_lossy(&output.stdout).into_owned(),
+    )
+}
+
+fn event_count(home: &TestHome) -> i64 {
+    let conn = rusqlite::Connection::open(home.db_path()).unwrap();
+    conn.query_row("SELECT COUNT(*) FROM events", [], |r| r.get(0))
+        .unwrap()
+}
+
+#[test]
+fn without_a_db_there_is_nothing_to_do() {
+    let home = TestHome::new();
+    let (ok, stdout) = maintain(&home, &[]);
+    assert!(ok);
+    assert!(stdout.starts_with("no tracking DB at "), "{stdout}");
+}
+
+#[test]
+fn archives_old_events_and_reports_integrity() {
+    let home = TestHome::new();
+    for _ in 0..3 {
+        let run = home
+            .cmd()
+            .args(["run", "--no-filter", "echo", "hi"])
+            .output()
+            .unwrap();
+        assert!(run.status.success());
+    }
+    rusqlite::Connection::open(home.db_path())
+        .unwrap()
+        .execute(
+            "UPDATE events SET timestamp = '2020-01-01T00:00:00Z' WHERE id < 3",
+            [],
+        )
+        .unwrap();
+
+    let (ok, stdout) = maintain(&home, &["--archive-after", "30", "--no-vacuum"]);
+    assert!(ok, "{stdout}");
+    assert!(
+        stdout.contains("archive:   2 events older than 30 days -> "),
+        "{stdout}"
+    );
+    assert!(stdout.contains("vacuum:    skipped"), "{stdout}");
+    assert!(stdout.contains("integrity: ok"), "{stdout}");
+    assert_eq!(event_count(&home), 1);
+    assert_eq!(
+        std::fs::read_dir(home.path().join("archive"))
+            .unwrap()
+            .count(),
+        1
+    );
+
+    let (ok, stdout) = maintain(&home, &[]);
+    assert!(ok);
+    assert!(stdout.contains("archive:   off"), "{stdout}");
+    assert!(stdout.contains("vacuum:    0."), "{stdout}");
+}
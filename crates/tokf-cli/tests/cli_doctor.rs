@@ -462,3 +462,63 @@ fn doctor_help_lists_burst_threshold() {
     assert!(stdout.contains("--burst-threshold"));
     assert!(stdout.contains("--window"));
 }
+
+// --- tokf doctor --setup ---
+
+fn setup_checks(project: &Path) -> (i32, serde_json::Value) {
+    let db = project.join("state/tracking.db");
+    let out = tokf_with_db(&db)
+        .args(["doctor", "--setup", "--offline", "--json"])
+        .env("HOME", project.join("home"))
+        .current_dir(project)
+        .output()
+        .expect("run");
+    let parsed = serde_json::from_slice(&out.stdout).unwrap_or_else(|e| {
+        panic!("{e}: {}", String::from_utf8_lossy(&out.stdout));
+    });
+    (out.status.code().unwrap(), parsed)
+}
+
+fn status_of<'a>(checks: &'a serde_json::Value, name: &str) -> &'a str {
+    checks
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|c| c["name"] == name)
+        .unwrap_or_else(|| panic!("no {name} check in {checks}"))["status"]
+        .as_str()
+        .unwrap()
+}
+
+#[test]
+fn setup_checks_pass_on_a_fresh_project() {
+    let dir = temp_db_dir();
+    let (code, checks) = setup_checks(dir.path());
+    assert_eq!(code, 0, "{checks}");
+    assert_eq!(status_of(&checks, "tracking database"), "ok");
+    assert_eq!(status_of(&checks, "hooks"), "warn");
+    assert_eq!(status_of(&checks, "registry"), "skipped");
+    assert_eq!(status_of(&checks, "filter files"), "ok");
+}
+
+#[test]
+fn setup_checks_fail_on_an_invalid_filter_file() {
+    let dir = temp_db_dir();
+    let filters = dir.path().join(".tokf/filters");
+    std::fs::create_dir_all(&filters).unwrap();
+    std::fs::write(filters.join("broken.toml"), "command = [\n").unwrap();
+
+    let (code, checks) = setup_checks(dir.path());
+    assert_eq!(code, 1, "{checks}");
+    assert_eq!(status_of(&checks, "filter files"), "fail");
+}
+
+#[test]
+fn setup_rejects_usage_report_flags() {
+    let dir = temp_db_dir();
+    let out = tokf_with_db(&dir.path().join("tracking.db"))
+        .args(["doctor", "--setup", "--all"])
+        .output()
+        .expect("run");
+    assert_eq!(out.status.code(), Some(2));
+}
@@ -0,0 +1,80 @@
+#![allow(clippy::unwrap_used, clippy::expect_used)]
+
+mod common;
+use common::tokf;
+
+// --- tokf fixture record ---
+
+#[cfg(unix)]
+fn project_with_filter() -> tempfile::TempDir {
+    let dir = tempfile::TempDir::new().unwrap();
+    let filters = dir.path().join(".tokf/filters/demo");
+    std::fs::create_dir_all(&filters).unwrap();
+    std::fs::write(
+        filters.join("printf.toml"),
+        "command = \"printf\"\nskip = [\"^noise\"]\n",
+    )
+    .unwrap();
+    dir
+}
+
+#[cfg(unix)]
+#[test]
+fn record_saves_raw_output_and_scaffolds_a_passing_case() {
+    let dir = project_with_filter();
+    let output = tokf()
+        .args(["fixture", "record", "--scaffold", "--"])
+        .args(["printf", "noise\\n\\033[31mred\\033[0m\\n"])
+        .current_dir(dir.path())
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{output:?}");
+
+    let suite = dir.path().join(".tokf/filters/demo/printf_test");
+    let fixture = std::fs::read_to_string(suite.join("success.txt")).unwrap();
+    assert_eq!(fixture, "noise\n\x1b[31mred\x1b[0m\n");
+    let case = std::fs::read_to_string(suite.join("success.toml")).unwrap();
+    assert!(case.contains("fixture = \"success.txt\""), "{case}");
+
+    let output = tokf()
+        .args(["verify", "demo/printf"])
+        .current_dir(dir.path())
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{output:?}");
+}
+
+#[cfg(unix)]
+#[test]
+fn record_refuses_to_overwrite_without_force() {
+    let dir = project_with_filter();
+    let record = |force: bool| {
+        let mut cmd = tokf();
+        cmd.args(["fixture", "record", "--case", "plain"]);
+        if force {
+            cmd.arg("--force");
+        }
+        cmd.args(["printf", "hello"])
+            .current_dir(dir.path())
+            .output()
+            .unwrap()
+    };
+
+    assert!(record(false).status.success());
+    let output = record(false);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("--force"));
+    assert!(record(true).status.success());
+}
+
+#[test]
+fn record_without_a_matching_filter_fails() {
+    let dir = tempfile::TempDir::new().unwrap();
+    let output = tokf()
+        .args(["fixture", "record", "no-such-tool-xyz", "run"])
+        .current_dir(dir.path())
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("tokf new"));
+}
@@ -96,3 +96,32 @@ fn err_nonzero_exit_no_mask() {
         .unwrap();
     assert!(!output.status.success(), "should propagate exit code");
 }
+
+#[test]
+fn secrets_are_redacted_in_output_and_history() {
+    let home = common::TestHome::new();
+    for sub in ["err", "test", "summary"] {
+        let output = home
+            .cmd()
+            .args([
+                "--no-cache",
+                sub,
+                "echo",
+                "error: FAILED DB_PASSWORD=hunter2",
+            ])
+            .output()
+            .unwrap();
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        assert!(
+            stdout.contains("DB_PASSWORD=[REDACTED]") && !stdout.contains("hunter2"),
+            "{sub} printed the secret: {stdout}"
+        );
+
+        let raw = home.cmd().args(["raw", "last"]).output().unwrap();
+        let raw = String::from_utf8_lossy(&raw.stdout);
+        assert!(
+            raw.contains("DB_PASSWORD=[REDACTED]") && !raw.contains("hunter2"),
+            "{sub} recorded the secret: {raw}"
+        );
+    }
+}
@@ -533,3 +533,299 @@ fn compression_indicator_disabled_by_env() {
         "indicator should be suppressed by env, got: {stdout}"
     );
 }
+
+// ---------------------------------------------------------------------------
+// diff_previous
+// ---------------------------------------------------------------------------
+
+#[test]
+fn diff_previous_shows_only_new_lines_on_rerun() {
+    let db_dir = temp_db_dir();
+    let db = db_dir.path().join("tracking.db");
+    let work_dir = TempDir::new().unwrap();
+    let filters_dir = work_dir.path().join(".tokf/filters");
+    std::fs::create_dir_all(&filters_dir).unwrap();
+    std::fs::write(
+        filters_dir.join("cat.toml"),
+        "command = \"cat\"\ndiff_previous = true\n",
+    )
+    .unwrap();
+    let log = work_dir.path().join("warnings.txt");
+
+    // Same command both times; only its output changes between runs.
+    let run = |contents: &str| {
+        std::fs::write(&log, contents).unwrap();
+        let out = tokf_with_db(&db)
+            .current_dir(work_dir.path())
+            .env("TOKF_SHOW_INDICATOR", "false")
+            .args(["run", "cat", "warnings.txt"])
+            .output()
+            .expect("run");
+        assert!(out.status.success());
+        String::from_utf8_lossy(&out.stdout).into_owned()
+    };
+
+    let first = run("warn a\nwarn b\n");
+    assert_eq!(
+        first.trim_end(),
+        "warn a\nwarn b",
+        "no previous run: full output"
+    );
+
+    let second = run("warn a\nwarn b\nwarn c\n");
+    // A re-run also triggers the history hint line after the output.
+    assert!(
+        second.starts_with("warn c\nunchanged: 2 lines\n"),
+        "expected only the new line plus summary, got: {second}"
+    );
+}
+
+// ---------------------------------------------------------------------------
+// secret redaction
+// ---------------------------------------------------------------------------
+
+#[test]
+fn recorded_raw_output_has_secrets_redacted() {
+    let db_dir = temp_db_dir();
+    let db = db_dir.path().join("tracking.db");
+    let work_dir = setup_local_filter(false);
+
+    tokf_with_db(&db)
+        .current_dir(work_dir.path())
+        .args(["run", "echo", "DB_PASSWORD=hunter2"])
+        .output()
+        .expect("run");
+
+    let raw_out = tokf_with_db(&db)
+        .current_dir(work_dir.path())
+        .args(["raw", "last"])
+        .output()
+        .expect("tokf raw last");
+    let raw_stdout = String::from_utf8_lossy(&raw_out.stdout);
+    assert!(raw_out.status.success());
+    assert!(
+        raw_stdout.contains("DB_PASSWORD=[REDACTED]") && !raw_stdout.contains("hunter2"),
+        "raw history must not keep the secret, got: {raw_stdout}"
+    );
+}
+
+// ---------------------------------------------------------------------------
+// history diff
+// ---------------------------------------------------------------------------
+
+#[test]
+fn history_diff_shows_what_the_filter_removed() {
+    let db_dir = temp_db_dir();
+    let db = db_dir.path().join("tracking.db");
+    let work_dir = setup_local_filter(false);
+
+    tokf_with_db(&db)
+        .current_dir(work_dir.path())
+        .args(["run", "echo", "hello world"])
+        .output()
+        .expect("run");
+    let list_out = tokf_with_db(&db)
+        .current_dir(work_dir.path())
+        .args(["history", "list"])
+        .output()
+        .expect("history list");
+    let list_stdout = String::from_utf8_lossy(&list_out.stdout);
+    let id = list_stdout.split_whitespace().next().expect("entry ID");
+
+    let out = tokf_with_db(&db)
+        .args(["history", "diff", id])
+        .output()
+        .expect("history diff");
+    assert!(out.status.success(), "{out:?}");
+    let stdout = String::from_utf8_lossy(&out.stdout);
+    assert!(stdout.starts_with("--- raw\n+++ filtered\n"), "{stdout}");
+    assert!(stdout.contains("\n-hello world\n"), "{stdout}");
+    assert!(stdout.contains("\n+filtered"), "{stdout}");
+    assert!(!stdout.contains('\x1b'), "no color when piped: {stdout:?}");
+
+    let out = tokf_with_db(&db)
+        .args(["history", "diff", "--stat", id])
+        .output()
+        .expect("history diff --stat");
+    let stdout = String::from_utf8_lossy(&out.stdout);
+    assert_eq!(
+        stdout.trim(),
+        format!("{id} echo hello world: 1 removed, 0 kept, 1 added")
+    );
+
+    let out = tokf_with_db(&db)
+        .args(["history", "diff", "999999"])
+        .output()
+        .expect("history diff missing");
+    assert_eq!(out.status.code(), Some(1));
+}
+
+#[test]
+fn history_stats_reports_entries_and_sizes() {
+    let db_dir = temp_db_dir();
+    let db = db_dir.path().join("tracking.db");
+    let work_dir = setup_local_filter(false);
+
+    tokf_with_db(&db)
+        .current_dir(work_dir.path())
+        .args(["run", "echo", "hello world"])
+        .output()
+        .expect("run");
+
+    let out = tokf_with_db(&db)
+        .args(["history", "stats"])
+        .output()
+        .expect("history stats");
+    assert!(out.status.success(), "{out:?}");
+    let stdout = String::from_utf8_lossy(&out.stdout);
+    assert!(
+        stdout.contains("entries:         1 (1 projects)"),
+        "{stdout}"
+    );
+    assert!(stdout.contains("outputs stored:"), "{stdout}");
+    assert!(stdout.contains("database file:"), "{stdout}");
+}
+
+#[test]
+fn history_prune_applies_retention_and_dry_run_keeps_entries() {
+    let db_dir = temp_db_dir();
+    let db = db_dir.path().join("tracking.db");
+    let work_dir = setup_local_filter(false);
+    for word in ["one", "two", "three"] {
+        tokf_with_db(&db)
+            .current_dir(work_dir.path())
+            .args(["run", "echo", word])
+            .output()
+            .expect("run");
+    }
+    std::fs::write(
+        work_dir.path().join(".tokf/config.toml"),
+        "[history]\nretention = 1\n",
+    )
+    .unwrap();
+
+    let entry_count = || {
+        let out = tokf_with_db(&db)
+            .current_dir(work_dir.path())
+            .args(["history", "list"])
+            .output()
+            .expect("history list");
+        String::from_utf8_lossy(&out.stdout).lines().count()
+    };
+    let prune = |args: &[&str]| {
+        let out = tokf_with_db(&db)
+            .current_dir(work_dir.path())
+            .args(["history", "prune"])
+            .args(args)
+            .output()
+            .expect("history prune");
+        assert!(out.status.success(), "{out:?}");
+        String::from_utf8_lossy(&out.stderr).into_owned()
+    };
+
+    assert_eq!(entry_count(), 3);
+    assert!(prune(&["--dry-run"]).contains("would prune 2 entries"));
+    assert_eq!(entry_count(), 3);
+    assert!(prune(&[]).contains("pruned 2 entries"));
+    assert_eq!(entry_count(), 1);
+}
+
+#[test]
+fn history_refilter_runs_raw_output_through_the_current_filter() {
+    let db_dir = temp_db_dir();
+    let db = db_dir.path().join("tracking.db");
+    let work_dir = setup_local_filter(false);
+    tokf_with_db(&db)
+        .current_dir(work_dir.path())
+        .args(["run", "echo", "hello world"])
+        .output()
+        .expect("run");
+    let list_out = tokf_with_db(&db)
+        .current_dir(work_dir.path())
+        .args(["history", "list"])
+        .output()
+        .expect("history list");
+    let list_stdout = String::from_utf8_lossy(&list_out.stdout);
+    let id = list_stdout.split_whitespace().next().expect("entry ID");
+
+    // Iterate on the filter after the run was recorded.
+    std::fs::write(
+        work_dir.path().join(".tokf/filters/echo.toml"),
+        "command = \"echo\"\n[on_success]\noutput = \"now: {output}\"",
+    )
+    .unwrap();
+
+    let out = tokf_with_db(&db)
+        .current_dir(work_dir.path())
+        .args(["history", "refilter", id])
+        .output()
+        .expect("history refilter");
+    assert!(out.status.success(), "{out:?}");
+    let stdout = String::from_utf8_lossy(&out.stdout);
+    let rows: Vec<&str> = stdout.lines().collect();
+    assert!(rows[0].contains("current: echo"), "{stdout}");
+    assert_eq!(
+        rows[1].split_whitespace().collect::<Vec<_>>(),
+        ["recorded", "current"]
+    );
+    assert_eq!(
+        rows[2].split_whitespace().collect::<Vec<_>>(),
+        ["filtered", "|", "now:", "hello", "world"],
+        "{stdout}"
+    );
+
+    let out = tokf_with_db(&db)
+        .current_dir(work_dir.path())
+        .args(["history", "refilter", id, "--filter", "nope"])
+        .output()
+        .expect("history refilter --filter");
+    assert!(!out.status.success());
+}
+
+#[test]
+fn history_export_fixture_scaffolds_a_passing_case() {
+    let db_dir = temp_db_dir();
+    let db = db_dir.path().join("tracking.db");
+    let work_dir = setup_local_filter(false);
+    tokf_with_db(&db)
+        .current_dir(work_dir.path())
+        .args(["run", "echo", "hello world"])
+        .output()
+        .expect("run");
+    let list_out = tokf_with_db(&db)
+        .current_dir(work_dir.path())
+        .args(["history", "list"])
+        .output()
+        .expect("history list");
+    let list_stdout = String::from_utf8_lossy(&list_out.stdout);
+    let id = list_stdout.split_whitespace().next().expect("entry ID");
+
+    let export = || {
+        tokf_with_db(&db)
+            .current_dir(work_dir.path())
+            .args(["history", "export-fixture", id])
+            .output()
+            .expect("history export-fixture")
+    };
+    let out = export();
+    assert!(out.status.success(), "{out:?}");
+    let suite = work_dir.path().join(".tokf/filters/echo_test");
+    let fixture = std::fs::read_to_string(suite.join("success.txt")).unwrap();
+    assert_eq!(fixture, "hello world\n");
+    let case = std::fs::read_to_string(suite.join("success.toml")).unwrap();
+    assert!(case.contains("exit_code = 0"), "{case}");
+    assert!(case.contains("equals = \"filtered\""), "{case}");
+
+    let out = tokf_with_db(&db)
+        .current_dir(work_dir.path())
+        .args(["verify", "echo"])
+        .output()
+        .expect("verify");
+    assert!(out.status.success(), "{out:?}");
+
+    let out = export();
+    assert!(
+        !out.status.success(),
+        "refuses to overwrite without --force"
+    );
+}
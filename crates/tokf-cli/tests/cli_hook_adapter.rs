@@ -0,0 +1,88 @@
+//! `tokf hook handle --adapter <tool>` with adapters in `.tokf/hooks/<tool>.toml`.
+
+#![allow(clippy::unwrap_used, clippy::expect_used)]
+
+mod common;
+use common::tokf;
+
+use std::io::Write;
+use std::process::Stdio;
+
+const ADAPTER: &str = r#"
+tool_name = "event.tool"
+shell_tools = ["terminal"]
+command = "event.args.cmd"
+
+[response]
+command = "result.cmd"
+decision = "result.verdict"
+decisions = { allow = "approve" }
+fields = { "version" = 2 }
+"#;
+
+/// Pipe `json` to `tokf hook handle --adapter mytool` in a project with `ADAPTER`.
+fn handle(json: &str) -> (String, String, bool) {
+    let dir = tempfile::TempDir::new().unwrap();
+    let hooks = dir.path().join(".tokf/hooks");
+    std::fs::create_dir_all(&hooks).unwrap();
+    std::fs::write(hooks.join("mytool.toml"), ADAPTER).unwrap();
+
+    let mut child = tokf()
+        .args(["hook", "handle", "--adapter", "mytool"])
+        .current_dir(dir.path())
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child
+        .stdin
+        .as_mut()
+        .unwrap()
+        .write_all(json.as_bytes())
+        .unwrap();
+    let output = child.wait_with_output().unwrap();
+    (
+        String::from_utf8_lossy(&output.stdout).to_string(),
+        String::from_utf8_lossy(&output.stderr).to_string(),
+        output.status.success(),
+    )
+}
+
+#[test]
+fn adapter_rewrites_in_the_tool_shape() {
+    let json = r#"{"event":{"tool":"terminal","args":{"cmd":"git status"}}}"#;
+    let (stdout, _, success) = handle(json);
+    assert!(success);
+    let response: serde_json::Value = serde_json::from_str(stdout.trim()).unwrap();
+    assert_eq!(
+        response,
+        serde_json::json!({
+            "version": 2,
+            "result": {"cmd": "tokf run git status", "verdict": "approve"}
+        })
+    );
+}
+
+#[test]
+fn adapter_ignores_other_tools() {
+    let json = r#"{"event":{"tool":"editor","args":{"cmd":"git status"}}}"#;
+    let (stdout, _, success) = handle(json);
+    assert!(success);
+    assert!(stdout.is_empty(), "{stdout}");
+}
+
+#[test]
+fn missing_adapter_passes_through_with_a_warning() {
+    let dir = tempfile::TempDir::new().unwrap();
+    let output = tokf()
+        .args(["hook", "handle", "--adapter", "nope"])
+        .current_dir(dir.path())
+        .stdin(Stdio::null())
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert!(output.stdout.is_empty());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("no adapter found for `nope`"), "{stderr}");
+}
@@ -0,0 +1,88 @@
+//! `tokf hook feedback` (Claude Code `PostToolUse`) and `tokf gain --confusion`.
+
+#![allow(clippy::unwrap_used, clippy::expect_used)]
+
+mod common;
+use common::TestHome;
+
+use std::io::Write;
+use std::path::Path;
+use std::process::Stdio;
+
+fn feedback(home: &TestHome, project: &Path, command: &str) {
+    let json = serde_json::json!({
+        "session_id": "abc",
+        "hook_event_name": "PostToolUse",
+        "tool_name": "Bash",
+        "tool_input": {"command": command},
+        "tool_response": {"stdout": "", "stderr": ""}
+    });
+    let mut child = home
+        .cmd()
+        .args(["hook", "feedback"])
+        .current_dir(project)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child
+        .stdin
+        .as_mut()
+        .unwrap()
+        .write_all(json.to_string().as_bytes())
+        .unwrap();
+    let output = child.wait_with_output().unwrap();
+    assert!(output.status.success());
+    assert!(
+        output.stdout.is_empty(),
+        "feedback must not print to stdout"
+    );
+}
+
+fn filtered_run(home: &TestHome, project: &Path) {
+    let output = home
+        .cmd()
+        .args(["run", "seq", "3"])
+        .current_dir(project)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+}
+
+#[test]
+fn rerun_and_raw_request_show_up_in_gain_confusion() {
+    let home = TestHome::new();
+    let project = tempfile::TempDir::new().unwrap();
+    let filters = project.path().join(".tokf/filters");
+    std::fs::create_dir_all(&filters).unwrap();
+    std::fs::write(
+        filters.join("seq.toml"),
+        "command = \"seq\"\n\n[on_success]\noutput = \"done\"\n",
+    )
+    .unwrap();
+
+    filtered_run(&home, project.path());
+    feedback(&home, project.path(), "tokf run seq 3");
+    filtered_run(&home, project.path());
+    feedback(&home, project.path(), "tokf run seq 3");
+    feedback(&home, project.path(), "tokf raw last");
+
+    let output = home
+        .cmd()
+        .args(["gain", "--confusion", "--json"])
+        .current_dir(project.path())
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let rows: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(
+        rows,
+        serde_json::json!([{
+            "filter_name": "seq",
+            "runs": 2,
+            "reruns": 1,
+            "raw_requests": 1,
+            "confusion_pct": 100.0
+        }])
+    );
+}
@@ -0,0 +1,59 @@
+//! `tokf hook session-start` (Claude Code `SessionStart`).
+
+#![allow(clippy::unwrap_used, clippy::expect_used)]
+
+mod common;
+use common::TestHome;
+
+use std::path::Path;
+
+fn session_start(home: &TestHome, project: &Path) -> String {
+    let output = home
+        .cmd()
+        .args(["hook", "session-start"])
+        .current_dir(project)
+        .stdin(std::process::Stdio::null())
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    String::from_utf8(output.stdout).unwrap()
+}
+
+#[test]
+fn prints_nothing_before_any_savings() {
+    let home = TestHome::new();
+    let project = tempfile::TempDir::new().unwrap();
+    assert_eq!(session_start(&home, project.path()), "");
+}
+
+#[test]
+fn reports_savings_recorded_in_this_project() {
+    let home = TestHome::new();
+    let project = tempfile::TempDir::new().unwrap();
+    let filters = project.path().join(".tokf/filters");
+    std::fs::create_dir_all(&filters).unwrap();
+    std::fs::write(
+        filters.join("seq.toml"),
+        "command = \"seq\"\n\n[on_success]\noutput = \"done\"\n",
+    )
+    .unwrap();
+    let run = home
+        .cmd()
+        .args(["run", "seq", "500"])
+        .current_dir(project.path())
+        .output()
+        .unwrap();
+    assert!(run.status.success());
+
+    let value: serde_json::Value =
+        serde_json::from_str(&session_start(&home, project.path())).unwrap();
+    assert_eq!(value["hookSpecificOutput"]["hookEventName"], "SessionStart");
+    let context = value["hookSpecificOutput"]["additionalContext"]
+        .as_str()
+        .unwrap();
+    assert!(
+        context.starts_with("tokf saved ")
+            && context.ends_with(" tokens across 1 command in this repo."),
+        "{context}"
+    );
+}
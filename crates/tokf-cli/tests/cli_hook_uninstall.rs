@@ -0,0 +1,78 @@
+#![allow(clippy::unwrap_used, clippy::expect_used)]
+
+mod common;
+use common::tokf;
+
+use std::path::Path;
+
+fn hook(dir: &Path, args: &[&str]) -> std::process::Output {
+    let output = tokf()
+        .arg("hook")
+        .args(args)
+        .current_dir(dir)
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "tokf hook {args:?} failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    output
+}
+
+fn status_line(dir: &Path, tool: &str) -> String {
+    let output = hook(dir, &["status"]);
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .find(|l| l.starts_with(&format!("{tool}:")))
+        .unwrap()
+        .to_string()
+}
+
+#[test]
+fn uninstall_reverts_install() {
+    let dir = tempfile::TempDir::new().unwrap();
+    hook(dir.path(), &["install", "--no-context"]);
+    assert_eq!(
+        status_line(dir.path(), "claude-code"),
+        "claude-code: installed"
+    );
+
+    let output = hook(dir.path(), &["uninstall"]);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("claude-code integration removed"),
+        "{stderr}"
+    );
+
+    assert!(!dir.path().join(".tokf/hooks/pre-tool-use.sh").exists());
+    let settings = std::fs::read_to_string(dir.path().join(".claude/settings.json")).unwrap();
+    assert!(!settings.contains("tokf"), "{settings}");
+    assert_eq!(
+        status_line(dir.path(), "claude-code"),
+        "claude-code: not installed"
+    );
+}
+
+#[test]
+fn uninstall_without_install_is_a_no_op() {
+    let dir = tempfile::TempDir::new().unwrap();
+    let output = hook(dir.path(), &["uninstall", "--tool", "cursor"]);
+    assert!(String::from_utf8_lossy(&output.stderr).contains("cursor integration not installed"));
+}
+
+#[test]
+fn status_lists_files_of_installed_tools() {
+    let dir = tempfile::TempDir::new().unwrap();
+    hook(
+        dir.path(),
+        &["install", "--tool", "gemini-cli", "--no-context"],
+    );
+
+    let output = hook(dir.path(), &["status"]);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("gemini-cli: installed"), "{stdout}");
+    assert!(stdout.contains("gemini-before-tool.sh (ok)"), "{stdout}");
+    assert!(stdout.contains(".gemini/settings.json (ok)"), "{stdout}");
+    assert!(stdout.contains("cursor: not installed"), "{stdout}");
+}
@@ -0,0 +1,62 @@
+#![allow(clippy::unwrap_used, clippy::expect_used)]
+
+mod common;
+use common::tokf;
+
+// --- tokf init ---
+
+#[test]
+fn init_scaffolds_a_working_project() {
+    let dir = tempfile::TempDir::new().unwrap();
+
+    let output = tokf().arg("init").current_dir(dir.path()).output().unwrap();
+    assert!(output.status.success(), "{output:?}");
+
+    let base = dir.path().join(".tokf");
+    for file in ["config.toml", "rewrites.toml", "filters/example.toml"] {
+        assert!(base.join(file).is_file(), "{file} should exist");
+    }
+    let ignore = std::fs::read_to_string(base.join(".gitignore")).unwrap();
+    assert!(ignore.lines().any(|l| l == "cache/"));
+    assert!(!dir.path().join(".claude").exists(), "hook needs --hook");
+
+    // The example filter is picked up for its command.
+    let output = tokf()
+        .args(["which", "my-tool build"])
+        .current_dir(dir.path())
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{output:?}");
+    assert!(String::from_utf8_lossy(&output.stdout).contains("example"));
+}
+
+#[test]
+fn init_keeps_existing_files() {
+    let dir = tempfile::TempDir::new().unwrap();
+    let config = dir.path().join(".tokf/config.toml");
+    std::fs::create_dir_all(config.parent().unwrap()).unwrap();
+    std::fs::write(&config, "[history]\nretention = 3\n").unwrap();
+
+    let output = tokf().arg("init").current_dir(dir.path()).output().unwrap();
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("already exists"));
+    assert_eq!(
+        std::fs::read_to_string(&config).unwrap(),
+        "[history]\nretention = 3\n"
+    );
+}
+
+#[test]
+fn init_with_hook_installs_claude_code_hook() {
+    let dir = tempfile::TempDir::new().unwrap();
+
+    let output = tokf()
+        .args(["init", "--hook"])
+        .current_dir(dir.path())
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{output:?}");
+    assert!(dir.path().join(".tokf/config.toml").is_file());
+    assert!(dir.path().join(".tokf/hooks/pre-tool-use.sh").is_file());
+    assert!(dir.path().join(".claude/settings.json").is_file());
+}
@@ -146,6 +146,30 @@ fn ls_verbose_shows_all_patterns_for_multiple() {
     );
 }
 
+#[test]
+fn ls_verbose_shows_version_and_tags() {
+    let dir = tempfile::TempDir::new().unwrap();
+    let filters_dir = dir.path().join(".tokf/filters");
+    std::fs::create_dir_all(&filters_dir).unwrap();
+    std::fs::write(
+        filters_dir.join("tagged.toml"),
+        "command = \"tagged cmd\"\ntags = [\"rust\", \"build\"]\nversion = \"1.2.0\"\n",
+    )
+    .unwrap();
+
+    let output = tokf()
+        .args(["ls", "--verbose"])
+        .current_dir(dir.path())
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("version: 1.2.0") && stderr.contains("tags: rust, build"),
+        "expected version and tags in verbose output, got: {stderr}"
+    );
+}
+
 #[test]
 fn ls_skips_invalid_toml_silently() {
     let dir = tempfile::TempDir::new().unwrap();
@@ -188,6 +212,31 @@ fn ls_verbose_shows_builtin_for_embedded_filter() {
     );
 }
 
+#[test]
+fn ls_hides_filters_disabled_in_project_config() {
+    let dir = tempfile::TempDir::new().unwrap();
+    let tokf_dir = dir.path().join(".tokf");
+    std::fs::create_dir_all(&tokf_dir).unwrap();
+    std::fs::write(
+        tokf_dir.join("config.toml"),
+        "[filters]\ndisabled = [\"git/log\"]\n",
+    )
+    .unwrap();
+
+    let output = tokf()
+        .args(["ls"])
+        .current_dir(dir.path())
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        !stdout.contains("git/log "),
+        "disabled filter should not be listed, got: {stdout}"
+    );
+    assert!(stdout.contains("git/push"), "got: {stdout}");
+}
+
 // --- tokf which ---
 
 #[test]
@@ -240,6 +289,33 @@ fn which_unknown_command_exits_one() {
     );
 }
 
+#[test]
+fn which_json_reports_the_match() {
+    let dir = tempfile::TempDir::new().unwrap();
+    let output = tokf()
+        .args(["which", "--json", "git push origin main"])
+        .current_dir(dir.path())
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(json["matched"], true);
+    assert_eq!(json["filter"]["name"], "git/push");
+    assert_eq!(json["filter"]["priority"], "built-in");
+    assert_eq!(json["pattern"], "git push");
+    assert_eq!(json["words_consumed"], 2);
+    assert!(json["variant"].is_null());
+
+    let output = tokf()
+        .args(["which", "--json", "unknown-cmd-xyz-99"])
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(1));
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(json["matched"], false);
+    assert!(json["filter"].is_null());
+}
+
 #[test]
 fn which_shows_priority_label() {
     // Embedded stdlib filter shows [built-in] when no local override
@@ -297,3 +373,61 @@ fn which_skips_invalid_toml_silently() {
         "expected valid filter to be found, got: {stdout}"
     );
 }
+
+#[test]
+fn which_skips_filter_disabled_in_project_config() {
+    let dir = tempfile::TempDir::new().unwrap();
+    let tokf_dir = dir.path().join(".tokf");
+    std::fs::create_dir_all(&tokf_dir).unwrap();
+    std::fs::write(
+        tokf_dir.join("config.toml"),
+        "[filters]\ndisabled = [\"git/log\"]\n",
+    )
+    .unwrap();
+
+    let output = tokf()
+        .args(["which", "git log"])
+        .current_dir(dir.path())
+        .output()
+        .unwrap();
+    assert!(
+        !output.status.success(),
+        "disabled filter should not match, got: {}",
+        String::from_utf8_lossy(&output.stdout)
+    );
+
+    // `tokf show` still finds it by name.
+    let output = tokf()
+        .args(["show", "git/log"])
+        .current_dir(dir.path())
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+}
+
+#[test]
+fn ls_json_lists_filters_with_source_and_patterns() {
+    let dir = tempfile::TempDir::new().unwrap();
+    let filters_dir = dir.path().join(".tokf/filters");
+    std::fs::create_dir_all(&filters_dir).unwrap();
+    std::fs::write(
+        filters_dir.join("my-tool.toml"),
+        "command = [\"my tool\", \"mytool\"]\ndescription = \"mine\"",
+    )
+    .unwrap();
+
+    let output = tokf()
+        .args(["ls", "--json"])
+        .current_dir(dir.path())
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let filters = json.as_array().unwrap();
+    let mine = filters.iter().find(|f| f["name"] == "my-tool").unwrap();
+    assert_eq!(mine["priority"], "local");
+    assert_eq!(mine["patterns"], serde_json::json!(["my tool", "mytool"]));
+    assert_eq!(mine["description"], "mine");
+    assert!(mine["source"].as_str().unwrap().ends_with("my-tool.toml"));
+    assert!(filters.iter().any(|f| f["name"] == "git/push"));
+}
@@ -0,0 +1,80 @@
+//! `tokf mcp serve`: MCP over stdio.
+
+#![allow(clippy::unwrap_used, clippy::expect_used)]
+
+mod common;
+use common::tokf;
+
+use std::io::Write;
+use std::process::Stdio;
+
+use serde_json::{Value, json};
+
+/// Send `requests` to `tokf mcp serve` in a project with a `seq` filter and
+/// return the replies.
+fn session(requests: &[Value]) -> Vec<Value> {
+    let dir = tempfile::TempDir::new().unwrap();
+    let filters = dir.path().join(".tokf/filters");
+    std::fs::create_dir_all(&filters).unwrap();
+    std::fs::write(
+        filters.join("seq.toml"),
+        "command = \"seq\"\n\n[on_success]\noutput = \"FILTERED\"\n",
+    )
+    .unwrap();
+
+    let mut child = tokf()
+        .args(["mcp", "serve"])
+        .current_dir(dir.path())
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    let mut stdin = child.stdin.take().unwrap();
+    for request in requests {
+        writeln!(stdin, "{request}").unwrap();
+    }
+    drop(stdin);
+    let output = child.wait_with_output().unwrap();
+    assert!(output.status.success());
+    String::from_utf8(output.stdout)
+        .unwrap()
+        .lines()
+        .map(|l| serde_json::from_str(l).unwrap())
+        .collect()
+}
+
+fn tool_call(id: i64, name: &str, arguments: &Value) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "method": "tools/call",
+        "params": {"name": name, "arguments": arguments}
+    })
+}
+
+#[test]
+fn mcp_session_runs_filtered_commands() {
+    let replies = session(&[
+        json!({"jsonrpc": "2.0", "id": 0, "method": "initialize", "params": {}}),
+        json!({"jsonrpc": "2.0", "method": "notifications/initialized"}),
+        tool_call(1, "which_filter", &json!({"command": "seq 3"})),
+        tool_call(2, "run_filtered", &json!({"command": "seq 3"})),
+        tool_call(3, "history_show", &json!({"raw": true})),
+    ]);
+    assert_eq!(replies.len(), 4, "{replies:?}");
+    assert_eq!(replies[0]["result"]["serverInfo"]["name"], "tokf");
+
+    let which: Value =
+        serde_json::from_str(replies[1]["result"]["content"][0]["text"].as_str().unwrap()).unwrap();
+    assert_eq!(which["matched"], true);
+    assert_eq!(which["filter"]["name"], "seq");
+
+    let run = &replies[2]["result"];
+    assert_eq!(run["isError"], false, "{run}");
+    let text = run["content"][0]["text"].as_str().unwrap();
+    assert!(text.trim_end().ends_with("FILTERED"), "{text}");
+    assert!(!text.contains("\n2\n"), "raw output leaked: {text}");
+
+    let raw = &replies[3]["result"]["content"][0]["text"];
+    assert_eq!(raw.as_str().unwrap().trim(), "1\n2\n3");
+}
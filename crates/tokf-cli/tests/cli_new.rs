@@ -0,0 +1,61 @@
+#![allow(clippy::unwrap_used, clippy::expect_used)]
+
+mod common;
+use common::tokf;
+
+// --- tokf new ---
+
+#[test]
+fn new_from_fixture_drafts_a_verified_filter() {
+    let dir = tempfile::TempDir::new().unwrap();
+    let fixture = dir.path().join("build.log");
+    std::fs::write(
+        &fixture,
+        "Compiling a\nCompiling b\nCompiling c\nerror: boom\n",
+    )
+    .unwrap();
+
+    let output = tokf()
+        .args(["new", "--yes", "--exit-code", "1", "--fixture"])
+        .arg(&fixture)
+        .args(["--", "my-tool", "build", "--release"])
+        .current_dir(dir.path())
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{output:?}");
+
+    let filters = dir.path().join(".tokf/filters");
+    let filter = std::fs::read_to_string(filters.join("my-tool/build.toml")).unwrap();
+    assert!(filter.contains("command = \"my-tool build\""), "{filter}");
+    assert!(filters.join("my-tool/build_test/failure.toml").is_file());
+    assert!(filters.join("my-tool/build_test/failure.txt").is_file());
+
+    let output = tokf()
+        .args(["verify", "my-tool/build"])
+        .current_dir(dir.path())
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{output:?}");
+}
+
+#[cfg(unix)]
+#[test]
+fn new_runs_the_command_and_refuses_to_overwrite() {
+    let dir = tempfile::TempDir::new().unwrap();
+    let new = || {
+        tokf()
+            .args(["new", "-y", "--name", "demo/seq", "seq", "3"])
+            .current_dir(dir.path())
+            .output()
+            .unwrap()
+    };
+
+    let output = new();
+    assert!(output.status.success(), "{output:?}");
+    let fixture = dir.path().join(".tokf/filters/demo/seq_test/success.txt");
+    assert_eq!(std::fs::read_to_string(fixture).unwrap(), "1\n2\n3\n");
+
+    let output = new();
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("--force"));
+}
@@ -192,3 +192,97 @@ fn publish_nonexistent_filter_fails() {
         "expected error message, got: {stderr}"
     );
 }
+
+/// `--registry` picks the publish target from `[[registry]]` entries; without
+/// it the highest-priority registry is used.
+#[test]
+fn publish_dry_run_names_the_target_registry() {
+    let home = common::TestHome::new();
+    let filter_dir = home.path().join(".tokf").join("filters").join("myns");
+    std::fs::create_dir_all(&filter_dir).unwrap();
+    std::fs::write(
+        filter_dir.join("test-filter.toml"),
+        r#"command = "my-test-command""#,
+    )
+    .unwrap();
+    std::fs::write(
+        home.path().join("config.toml"),
+        "[[registry]]\nname = \"acme\"\nurl = \"https://tokf.acme.example\"\ntoken = \"t\"\n",
+    )
+    .unwrap();
+
+    let publish = |extra: &[&str]| {
+        let output = home
+            .cmd()
+            .current_dir(home.path())
+            .args(["publish", "myns/test-filter", "--dry-run"])
+            .args(extra)
+            .output()
+            .unwrap();
+        (
+            output.status.success(),
+            String::from_utf8_lossy(&output.stderr).to_string(),
+        )
+    };
+
+    let (ok, stderr) = publish(&[]);
+    assert!(ok, "{stderr}");
+    assert!(
+        stderr.contains("Registry: acme (https://tokf.acme.example)"),
+        "{stderr}"
+    );
+
+    let (ok, stderr) = publish(&["--registry", "default"]);
+    assert!(ok, "{stderr}");
+    assert!(stderr.contains("Registry: default ("), "{stderr}");
+
+    let (ok, stderr) = publish(&["--registry", "nope"]);
+    assert!(!ok);
+    assert!(stderr.contains("unknown registry 'nope'"), "{stderr}");
+}
+
+/// `--org` and `--private` are shown in the preview.
+#[test]
+fn publish_dry_run_shows_org_and_visibility() {
+    let home = tempfile::tempdir().unwrap();
+    let filter_dir = home.path().join(".tokf").join("filters").join("myns");
+    std::fs::create_dir_all(&filter_dir).unwrap();
+    std::fs::write(
+        filter_dir.join("deploy.toml"),
+        r#"command = "internal-deploy""#,
+    )
+    .unwrap();
+
+    let output = tokf()
+        .env("HOME", home.path())
+        .current_dir(home.path())
+        .args([
+            "publish",
+            "myns/deploy",
+            "--dry-run",
+            "--org",
+            "acme",
+            "--private",
+        ])
+        .output()
+        .unwrap();
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(output.status.success(), "stderr: {stderr}");
+    assert!(stderr.contains("Org:     acme"), "got: {stderr}");
+    assert!(
+        stderr.contains("Visible: only to you and members of acme"),
+        "got: {stderr}"
+    );
+
+    // A private filter needs no org: only its author can see it.
+    let output = tokf()
+        .env("HOME", home.path())
+        .current_dir(home.path())
+        .args(["publish", "myns/deploy", "--dry-run", "--private"])
+        .output()
+        .unwrap();
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(output.status.success(), "stderr: {stderr}");
+    assert!(stderr.contains("Visible: only to you\n"), "got: {stderr}");
+    assert!(!stderr.contains("Org:"), "got: {stderr}");
+}
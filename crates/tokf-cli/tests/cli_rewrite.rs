@@ -715,3 +715,23 @@ fn rewrite_heredoc_commit_with_unmatched_parens_not_mangled() {
         "heredoc commit must not be wrapped with tokf run, got: {result}"
     );
 }
+
+#[test]
+fn rewrite_skips_filter_disabled_in_project_config() {
+    let dir = tempfile::TempDir::new().unwrap();
+    let tokf_dir = dir.path().join(".tokf");
+    std::fs::create_dir_all(&tokf_dir).unwrap();
+    std::fs::write(
+        tokf_dir.join("config.toml"),
+        "[filters]\ndisabled = [\"git/log\"]\n",
+    )
+    .unwrap();
+
+    let output = tokf()
+        .args(["rewrite", "git log"])
+        .current_dir(dir.path())
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert_eq!(String::from_utf8_lossy(&output.stdout).trim(), "git log");
+}
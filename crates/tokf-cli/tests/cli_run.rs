@@ -753,3 +753,29 @@ fn run_nix_develop_real_nix() {
         "expected the git status filter to match through real nix, got: {stderr}"
     );
 }
+
+#[test]
+fn run_shorten_paths_relativizes_to_project_root() {
+    let dir = tempfile::TempDir::new().unwrap();
+    let root = dir.path().canonicalize().unwrap();
+    let filters_dir = root.join(".tokf/filters");
+    std::fs::create_dir_all(&filters_dir).unwrap();
+    std::fs::write(
+        filters_dir.join("echo.toml"),
+        "command = \"echo\"\nshorten_paths = true\n",
+    )
+    .unwrap();
+
+    let abs = format!("{}/src/lib.rs:3:1", root.display());
+    let output = tokf()
+        .args(["run", "echo", "error at", &abs])
+        .current_dir(&root)
+        .env("TOKF_SHOW_INDICATOR", "false")
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout).trim(),
+        "error at src/lib.rs:3:1"
+    );
+}
@@ -0,0 +1,61 @@
+//! `tokf run --format json`: the run as one JSON object on stdout.
+
+#![cfg(unix)]
+#![allow(clippy::unwrap_used, clippy::expect_used)]
+
+mod common;
+
+use common::tokf;
+use tempfile::TempDir;
+
+fn project_with_filter() -> TempDir {
+    let dir = TempDir::new().unwrap();
+    let filters_dir = dir.path().join(".tokf/filters");
+    std::fs::create_dir_all(&filters_dir).unwrap();
+    std::fs::write(
+        filters_dir.join("echo.toml"),
+        "command = \"echo\"\n[on_success]\noutput = \"filtered\"",
+    )
+    .unwrap();
+    dir
+}
+
+fn run_json(dir: &TempDir, args: &[&str]) -> serde_json::Value {
+    let output = tokf()
+        .args(["run", "--format", "json"])
+        .args(args)
+        .current_dir(dir.path())
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{output:?}");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert_eq!(stdout.lines().count(), 1, "one JSON line: {stdout}");
+    serde_json::from_str(&stdout).unwrap()
+}
+
+#[test]
+fn filtered_run_prints_an_envelope() {
+    let dir = project_with_filter();
+    let long = "x".repeat(400);
+    let json = run_json(&dir, &["echo", &long]);
+    assert_eq!(json["command"], format!("echo {long}"));
+    assert_eq!(json["filter"], "echo");
+    assert_eq!(json["exit_code"], 0);
+    assert_eq!(json["filtered_output"], "filtered");
+    assert_eq!(json["input_bytes"], 400);
+    assert_eq!(json["output_bytes"], 8);
+    assert!(json["tokens_saved_est"].as_i64().unwrap() > 0, "{json}");
+    assert!(json["history_id"].as_i64().is_some(), "{json}");
+    assert!(json["duration_ms"].is_u64(), "{json}");
+}
+
+#[test]
+fn passthrough_run_reports_the_raw_output_and_exit_code() {
+    let dir = project_with_filter();
+    let json = run_json(&dir, &["sh", "-c", "echo boom; exit 3"]);
+    assert!(json["filter"].is_null());
+    assert!(json["history_id"].is_null());
+    assert_eq!(json["exit_code"], 3);
+    assert_eq!(json["filtered_output"], "boom");
+    assert_eq!(json["tokens_saved_est"], 0);
+}
@@ -0,0 +1,70 @@
+//! Exit-code masking policy: `--no-mask-exit-code`, a filter's
+//! `mask_exit_code` and the project's `[run] mask_exit_code`.
+
+#![cfg(unix)]
+#![allow(clippy::unwrap_used, clippy::expect_used)]
+
+mod common;
+
+use common::tokf;
+use tempfile::TempDir;
+
+/// A project with a filter for `false`, which exits 1.
+fn project(filter_extra: &str, project_config: Option<&str>) -> TempDir {
+    let dir = TempDir::new().unwrap();
+    let tokf_dir = dir.path().join(".tokf");
+    std::fs::create_dir_all(tokf_dir.join("filters")).unwrap();
+    std::fs::write(
+        tokf_dir.join("filters/false.toml"),
+        format!("command = \"false\"\n{filter_extra}\n[on_failure]\noutput = \"failed\""),
+    )
+    .unwrap();
+    if let Some(config) = project_config {
+        std::fs::write(tokf_dir.join("config.toml"), config).unwrap();
+    }
+    dir
+}
+
+fn run(dir: &TempDir, args: &[&str]) -> (Option<i32>, String) {
+    let output = tokf().args(args).current_dir(dir.path()).output().unwrap();
+    (
+        output.status.code(),
+        String::from_utf8_lossy(&output.stdout).to_string(),
+    )
+}
+
+#[test]
+fn filter_can_turn_masking_off() {
+    let dir = project("mask_exit_code = false", None);
+    let (code, stdout) = run(&dir, &["run", "false"]);
+    assert_eq!(code, Some(1));
+    assert!(!stdout.contains("Error: Exit code"), "{stdout}");
+    assert!(stdout.contains("failed"), "{stdout}");
+}
+
+#[test]
+fn project_default_applies_to_filtered_and_passthrough_runs() {
+    let dir = project("", Some("[run]\nmask_exit_code = false\n"));
+    assert_eq!(run(&dir, &["run", "false"]).0, Some(1));
+    assert_eq!(run(&dir, &["run", "sh", "-c", "exit 3"]).0, Some(3));
+}
+
+#[test]
+fn filter_setting_overrides_project_default() {
+    let dir = project(
+        "mask_exit_code = true",
+        Some("[run]\nmask_exit_code = false\n"),
+    );
+    let (code, stdout) = run(&dir, &["run", "false"]);
+    assert_eq!(code, Some(0));
+    assert!(stdout.starts_with("Error: Exit code 1\n"), "{stdout}");
+}
+
+#[test]
+fn flag_overrides_filter_setting() {
+    let dir = project("mask_exit_code = true", None);
+    assert_eq!(
+        run(&dir, &["run", "--no-mask-exit-code", "false"]).0,
+        Some(1)
+    );
+}
@@ -0,0 +1,63 @@
+//! `pty = true` and `tokf run --pty`: the command sees a terminal, and its
+//! output still goes through the filter.
+
+#![cfg(unix)]
+#![allow(clippy::unwrap_used, clippy::expect_used)]
+
+mod common;
+
+use common::tokf;
+use tempfile::TempDir;
+
+const PROBE: &str = "test -t 1 && echo terminal || echo pipe";
+
+fn setup_probe_filter(pty: bool) -> TempDir {
+    let dir = TempDir::new().unwrap();
+    let filters_dir = dir.path().join(".tokf/filters");
+    std::fs::create_dir_all(&filters_dir).unwrap();
+    std::fs::write(
+        filters_dir.join("tty-probe.toml"),
+        format!(
+            "command = \"tty-probe\"\nrun = \"{PROBE}\"\npty = {pty}\n\
+             [on_success]\noutput = \"probe: {{output}}\""
+        ),
+    )
+    .unwrap();
+    dir
+}
+
+/// Run tokf in `dir` and assert its output ends with `expected` (filtered
+/// output may be prefixed with a history marker).
+fn assert_output(dir: &TempDir, args: &[&str], expected: &str) {
+    let output = tokf().args(args).current_dir(dir.path()).output().unwrap();
+    assert!(output.status.success(), "{output:?}");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.trim().ends_with(expected),
+        "expected {expected:?}, got: {stdout}"
+    );
+}
+
+#[test]
+fn pty_filter_runs_command_in_a_terminal() {
+    let dir = setup_probe_filter(true);
+    assert_output(&dir, &["run", "tty-probe"], "probe: terminal");
+}
+
+#[test]
+fn filter_without_pty_uses_pipes() {
+    let dir = setup_probe_filter(false);
+    assert_output(&dir, &["run", "tty-probe"], "probe: pipe");
+}
+
+#[test]
+fn pty_flag_enables_pty_for_any_filter() {
+    let dir = setup_probe_filter(false);
+    assert_output(&dir, &["run", "--pty", "tty-probe"], "probe: terminal");
+}
+
+#[test]
+fn pty_flag_works_without_a_matching_filter() {
+    let dir = TempDir::new().unwrap();
+    assert_output(&dir, &["run", "--pty", "sh", "-c", PROBE], "terminal");
+}
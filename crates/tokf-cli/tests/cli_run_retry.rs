@@ -0,0 +1,61 @@
+//! `retry = { ... }`: a flaky command is re-run before the filter sees it.
+
+#![cfg(unix)]
+#![allow(
+    clippy::unwrap_used,
+    clippy::expect_used,
+    clippy::literal_string_with_formatting_args
+)]
+
+mod common;
+
+use common::tokf;
+use tempfile::TempDir;
+
+fn setup(filter: &str) -> TempDir {
+    let dir = TempDir::new().unwrap();
+    let filters_dir = dir.path().join(".tokf/filters");
+    std::fs::create_dir_all(&filters_dir).unwrap();
+    std::fs::write(filters_dir.join("flaky.toml"), filter).unwrap();
+    dir
+}
+
+#[test]
+fn flaky_command_succeeds_on_retry() {
+    let dir = setup(
+        "command = \"flaky\"\n\
+         run = \"test -f marker || { touch marker; echo boom; exit 1; }; echo ok\"\n\
+         retry = { attempts = 3, on_exit = [1], backoff_ms = 10 }\n\
+         [on_success]\noutput = \"{output} after {attempts} attempts\"\n\
+         [on_failure]\noutput = \"failed: {output}\"",
+    );
+    let output = tokf()
+        .args(["run", "flaky"])
+        .current_dir(dir.path())
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{output:?}");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.trim().ends_with("ok after 2 attempts"),
+        "got: {stdout}"
+    );
+}
+
+#[test]
+fn persistent_failure_renders_last_attempt() {
+    let dir = setup(
+        "command = \"flaky\"\n\
+         run = \"echo try >> log; wc -l < log; exit 1\"\n\
+         retry = { attempts = 2 }\n\
+         [on_failure]\noutput = \"failed ({attempts}): {output}\"",
+    );
+    let output = tokf()
+        .args(["run", "--no-mask-exit-code", "flaky"])
+        .current_dir(dir.path())
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(1));
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.trim().ends_with("failed (2): 2"), "got: {stdout}");
+}
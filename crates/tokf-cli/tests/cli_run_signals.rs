@@ -0,0 +1,110 @@
+//! SIGINT/SIGTERM during `tokf run`: the signal is forwarded to the command
+//! and the output captured so far is still filtered, under an `(interrupted)`
+//! banner.
+
+#![cfg(unix)]
+#![allow(clippy::unwrap_used, clippy::expect_used)]
+
+mod common;
+
+use std::process::{Child, Command, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use common::tokf;
+use tempfile::TempDir;
+
+fn setup_hanging_filter() -> TempDir {
+    let dir = TempDir::new().unwrap();
+    let filters_dir = dir.path().join(".tokf/filters");
+    std::fs::create_dir_all(&filters_dir).unwrap();
+    std::fs::write(
+        filters_dir.join("hang.toml"),
+        "command = \"hang\"\nrun = \"echo noise; echo partial; sleep 30\"\n\
+         timeout_secs = 60\nskip = [\"^noise$\"]",
+    )
+    .unwrap();
+    dir
+}
+
+fn spawn_in(dir: &TempDir, args: &[&str]) -> Child {
+    tokf()
+        .args(args)
+        .current_dir(dir.path())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap()
+}
+
+fn send(child: &Child, signal: &str) {
+    // Give tokf time to start the command and install its handlers.
+    thread::sleep(Duration::from_millis(500));
+    let status = Command::new("kill")
+        .args([&format!("-{signal}"), &child.id().to_string()])
+        .status()
+        .unwrap();
+    assert!(status.success());
+}
+
+#[test]
+fn sigterm_filters_partial_output() {
+    let dir = setup_hanging_filter();
+    let start = Instant::now();
+    let child = spawn_in(&dir, &["run", "hang"]);
+    send(&child, "TERM");
+    let output = child.wait_with_output().unwrap();
+    assert!(start.elapsed() < Duration::from_secs(20), "command hung");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("(interrupted)"), "got: {stdout}");
+    assert!(stdout.contains("partial"), "got: {stdout}");
+    assert!(
+        !stdout.contains("noise"),
+        "filter was not applied: {stdout}"
+    );
+}
+
+#[test]
+fn sigint_reaches_an_isolated_command() {
+    let dir = TempDir::new().unwrap();
+    let start = Instant::now();
+    let child = spawn_in(
+        &dir,
+        &[
+            "run",
+            "--timeout",
+            "60",
+            "--no-mask-exit-code",
+            "sh",
+            "-c",
+            "echo partial; sleep 30",
+        ],
+    );
+    send(&child, "INT");
+    let output = child.wait_with_output().unwrap();
+    assert!(start.elapsed() < Duration::from_secs(20), "command hung");
+    assert_eq!(output.status.code(), Some(130));
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert_eq!(stdout, "(interrupted)\npartial\n");
+}
+
+#[test]
+fn sigint_during_a_retry_backoff_stops_retrying() {
+    let dir = TempDir::new().unwrap();
+    let filters_dir = dir.path().join(".tokf/filters");
+    std::fs::create_dir_all(&filters_dir).unwrap();
+    std::fs::write(
+        filters_dir.join("flaky.toml"),
+        "command = \"flaky\"\nrun = \"echo failed; exit 1\"\n\
+         retry = { attempts = 5, backoff_ms = 10000 }",
+    )
+    .unwrap();
+    let start = Instant::now();
+    let child = spawn_in(&dir, &["run", "--no-mask-exit-code", "flaky"]);
+    send(&child, "INT");
+    let output = child.wait_with_output().unwrap();
+    assert!(start.elapsed() < Duration::from_secs(5), "kept retrying");
+    assert_eq!(output.status.code(), Some(130));
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("(interrupted)"), "got: {stdout}");
+    assert!(stdout.contains("failed"), "got: {stdout}");
+}
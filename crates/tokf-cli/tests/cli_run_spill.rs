@@ -0,0 +1,71 @@
+//! `[run] spill_threshold_mb`: output past the threshold is spilled to disk
+//! and still reaches the filter (or the terminal) in full.
+
+#![cfg(unix)]
+#![allow(clippy::unwrap_used, clippy::expect_used)]
+
+mod common;
+
+use common::tokf;
+use tempfile::TempDir;
+
+/// About 2 MiB of output: past a 1 MiB threshold.
+const LARGE: &[&str] = &["seq", "1", "300000"];
+
+fn setup_project(filter: Option<&str>) -> TempDir {
+    let dir = TempDir::new().unwrap();
+    let tokf_dir = dir.path().join(".tokf");
+    std::fs::create_dir_all(tokf_dir.join("filters")).unwrap();
+    std::fs::write(
+        tokf_dir.join("config.toml"),
+        "[run]\nspill_threshold_mb = 1\n",
+    )
+    .unwrap();
+    if let Some(filter) = filter {
+        std::fs::write(tokf_dir.join("filters/seq.toml"), filter).unwrap();
+    }
+    dir
+}
+
+#[test]
+fn spilled_output_is_filtered_in_full() {
+    let dir = setup_project(Some("command = \"seq\"\nkeep = [\"^(1|300000)$\"]"));
+    let output = tokf()
+        .arg("run")
+        .args(LARGE)
+        .current_dir(dir.path())
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{output:?}");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.trim().ends_with("1\n300000"), "got: {stdout}");
+}
+
+#[test]
+fn spilled_output_passes_through_in_full() {
+    let dir = setup_project(None);
+    let output = tokf()
+        .args(["run", "--no-filter"])
+        .args(LARGE)
+        .current_dir(dir.path())
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{output:?}");
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert_eq!(stdout.lines().count(), 300_000);
+    assert!(stdout.ends_with("\n300000\n"));
+}
+
+#[test]
+fn spilled_output_keeps_its_channels() {
+    let dir = setup_project(Some("command = \"sh\"\nsource = \"stderr\""));
+    let output = tokf()
+        .args(["run", "sh", "-c", "seq 1 300000; echo boom >&2"])
+        .current_dir(dir.path())
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{output:?}");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert_eq!(stdout.trim().lines().count(), 1, "got: {stdout}");
+    assert!(stdout.trim().ends_with("boom"), "got: {stdout}");
+}
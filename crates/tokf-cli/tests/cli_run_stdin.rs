@@ -0,0 +1,72 @@
+//! `stdin` in a filter and `tokf run --stdin`: what the wrapped command reads.
+
+#![cfg(unix)]
+#![allow(clippy::unwrap_used, clippy::expect_used)]
+
+mod common;
+
+use std::io::Write;
+use std::process::Stdio;
+
+use common::tokf;
+use tempfile::TempDir;
+
+fn setup_cat_filter(stdin: &str) -> TempDir {
+    let dir = TempDir::new().unwrap();
+    let filters_dir = dir.path().join(".tokf/filters");
+    std::fs::create_dir_all(&filters_dir).unwrap();
+    std::fs::write(
+        filters_dir.join("echo-input.toml"),
+        format!(
+            "command = \"echo-input\"\nrun = \"cat; echo end\"\nstdin = \"{stdin}\"\n\
+             [on_success]\noutput = \"got: {{output}}\""
+        ),
+    )
+    .unwrap();
+    dir
+}
+
+/// Run tokf in `dir` with `input` on its stdin and return stdout.
+fn run_with_input(dir: &TempDir, args: &[&str], input: &str) -> String {
+    let mut child = tokf()
+        .args(args)
+        .current_dir(dir.path())
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(input.as_bytes())
+        .unwrap();
+    let output = child.wait_with_output().unwrap();
+    assert!(output.status.success(), "{output:?}");
+    String::from_utf8_lossy(&output.stdout).trim().to_string()
+}
+
+#[test]
+fn stdin_is_inherited_by_default() {
+    let dir = TempDir::new().unwrap();
+    let stdout = run_with_input(&dir, &["run", "cat"], "typed answer\n");
+    assert_eq!(stdout, "typed answer");
+}
+
+#[test]
+fn filter_stdin_null_hides_input() {
+    let dir = setup_cat_filter("null");
+    let stdout = run_with_input(&dir, &["run", "echo-input"], "typed answer\n");
+    assert!(stdout.ends_with("got: end"), "got: {stdout}");
+}
+
+#[test]
+fn stdin_flag_overrides_filter() {
+    let dir = setup_cat_filter("null");
+    let stdout = run_with_input(
+        &dir,
+        &["run", "--stdin", "inherit", "echo-input"],
+        "typed answer\n",
+    );
+    assert!(stdout.ends_with("got: typed answer\nend"), "got: {stdout}");
+}
@@ -0,0 +1,71 @@
+//! `tokf run --tee-raw` and `tee_raw_dir`: the unfiltered output saved to a
+//! file while the filtered version is printed.
+
+#![cfg(unix)]
+#![allow(clippy::unwrap_used, clippy::expect_used)]
+
+mod common;
+
+use common::tokf;
+use tempfile::TempDir;
+
+fn project_with_filter(extra: &str) -> TempDir {
+    let dir = TempDir::new().unwrap();
+    let filters_dir = dir.path().join(".tokf/filters");
+    std::fs::create_dir_all(&filters_dir).unwrap();
+    std::fs::write(
+        filters_dir.join("echo.toml"),
+        format!("command = \"echo\"\n{extra}\n[on_success]\noutput = \"filtered\""),
+    )
+    .unwrap();
+    dir
+}
+
+#[test]
+fn tee_raw_saves_unfiltered_output_and_prints_filtered() {
+    let dir = project_with_filter("");
+    let output = tokf()
+        .args(["run", "--tee-raw", "logs/raw.log", "echo", "the full log"])
+        .current_dir(dir.path())
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{output:?}");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("filtered"), "{stdout}");
+    assert!(!stdout.contains("full log"), "{stdout}");
+    let raw = std::fs::read_to_string(dir.path().join("logs/raw.log")).unwrap();
+    assert_eq!(raw, "the full log\n");
+}
+
+#[test]
+fn tee_raw_applies_to_passthrough_runs() {
+    let dir = TempDir::new().unwrap();
+    let output = tokf()
+        .args(["run", "--tee-raw", "raw.log", "printf", "unfiltered"])
+        .current_dir(dir.path())
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{output:?}");
+    let raw = std::fs::read_to_string(dir.path().join("raw.log")).unwrap();
+    assert_eq!(raw, "unfiltered\n");
+}
+
+#[test]
+fn filter_tee_raw_dir_gets_one_file_per_run() {
+    let dir = project_with_filter("tee_raw_dir = \"ci-logs\"");
+    for word in ["first", "second"] {
+        let output = tokf()
+            .args(["run", "echo", word])
+            .current_dir(dir.path())
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "{output:?}");
+        std::thread::sleep(std::time::Duration::from_millis(5));
+    }
+    let mut logs: Vec<String> = std::fs::read_dir(dir.path().join("ci-logs"))
+        .unwrap()
+        .map(|e| std::fs::read_to_string(e.unwrap().path()).unwrap())
+        .collect();
+    logs.sort();
+    assert_eq!(logs, ["first\n", "second\n"]);
+}
@@ -0,0 +1,83 @@
+//! `timeout_secs` and `--timeout`: a hung command is killed and the output
+//! captured so far is still filtered, under a `(timed out after Ns)` banner.
+
+#![cfg(unix)]
+#![allow(clippy::unwrap_used, clippy::expect_used)]
+
+mod common;
+
+use std::time::{Duration, Instant};
+
+use common::tokf;
+use tempfile::TempDir;
+
+fn setup_hanging_filter(timeout_secs: u64) -> TempDir {
+    let dir = TempDir::new().unwrap();
+    let filters_dir = dir.path().join(".tokf/filters");
+    std::fs::create_dir_all(&filters_dir).unwrap();
+    std::fs::write(
+        filters_dir.join("hang.toml"),
+        format!(
+            "command = \"hang\"\nrun = \"echo noise; echo partial; sleep 30\"\n\
+             timeout_secs = {timeout_secs}\nskip = [\"^noise$\"]"
+        ),
+    )
+    .unwrap();
+    dir
+}
+
+#[test]
+fn filter_timeout_filters_partial_output() {
+    let dir = setup_hanging_filter(1);
+    let start = Instant::now();
+    let output = tokf()
+        .args(["run", "--no-mask-exit-code", "hang"])
+        .current_dir(dir.path())
+        .output()
+        .unwrap();
+    assert!(start.elapsed() < Duration::from_secs(20), "command hung");
+    assert_eq!(output.status.code(), Some(124));
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("(timed out after 1s)"), "got: {stdout}");
+    assert!(stdout.contains("partial"), "got: {stdout}");
+    assert!(
+        !stdout.contains("noise"),
+        "filter was not applied: {stdout}"
+    );
+}
+
+#[test]
+fn timeout_flag_overrides_filter_timeout() {
+    let dir = setup_hanging_filter(60);
+    let start = Instant::now();
+    let output = tokf()
+        .args(["--timeout", "1", "run", "hang"])
+        .current_dir(dir.path())
+        .output()
+        .unwrap();
+    assert!(start.elapsed() < Duration::from_secs(20), "command hung");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("Error: Exit code 124"), "got: {stdout}");
+    assert!(stdout.contains("(timed out after 1s)"), "got: {stdout}");
+}
+
+#[test]
+fn timeout_flag_works_without_a_matching_filter() {
+    let dir = TempDir::new().unwrap();
+    let output = tokf()
+        .args([
+            "run",
+            "--timeout",
+            "1",
+            "--no-mask-exit-code",
+            "sh",
+            "-c",
+            "echo partial; sleep 30",
+        ])
+        .current_dir(dir.path())
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(124));
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert_eq!(stdout, "(timed out after 1s)\npartial\n");
+}
@@ -0,0 +1,71 @@
+//! Resource usage of the wrapped command: `{duration}` / `{max_rss}` in
+//! templates and the usage columns of the tracking DB.
+
+#![cfg(unix)]
+#![allow(
+    clippy::unwrap_used,
+    clippy::expect_used,
+    clippy::literal_string_with_formatting_args
+)]
+
+mod common;
+
+use common::tokf;
+use tempfile::TempDir;
+
+type UsageRow = (Option<i64>, Option<i64>, Option<i64>, Option<i64>);
+
+fn last_usage(db_path: &std::path::Path) -> UsageRow {
+    let conn = rusqlite::Connection::open(db_path).unwrap();
+    conn.query_row(
+        "SELECT duration_ms, user_cpu_ms, sys_cpu_ms, max_rss_kb \
+         FROM events ORDER BY rowid DESC LIMIT 1",
+        [],
+        |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)),
+    )
+    .unwrap()
+}
+
+#[test]
+fn usage_vars_render_in_template() {
+    let dir = TempDir::new().unwrap();
+    let filters_dir = dir.path().join(".tokf/filters");
+    std::fs::create_dir_all(&filters_dir).unwrap();
+    std::fs::write(
+        filters_dir.join("slow.toml"),
+        "command = \"slow\"\nrun = \"sleep 0.2; echo done\"\n\
+         [on_success]\noutput = \"{output} in {duration}, peak {max_rss}\"",
+    )
+    .unwrap();
+    let output = tokf()
+        .args(["run", "slow"])
+        .current_dir(dir.path())
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{output:?}");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let line = stdout.trim().rsplit('\n').next().unwrap();
+    let rest = line.split_once("done in ").expect("usage line").1;
+    let (duration, peak) = rest.split_once(", peak ").unwrap();
+    let secs: f64 = duration.strip_suffix('s').unwrap().parse().unwrap();
+    assert!(secs >= 0.2, "got: {stdout}");
+    assert!(peak.ends_with(" MB"), "got: {stdout}");
+}
+
+#[test]
+fn usage_is_recorded_in_tracking_db() {
+    let dir = TempDir::new().unwrap();
+    let db_path = dir.path().join("usage.db");
+    let output = tokf()
+        .args(["run", "--no-filter", "sh", "-c", "sleep 0.2"])
+        .env("TOKF_DB_PATH", &db_path)
+        .current_dir(dir.path())
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let (duration_ms, user_cpu_ms, sys_cpu_ms, max_rss_kb) = last_usage(&db_path);
+    assert!(duration_ms.is_some_and(|ms| ms >= 200), "{duration_ms:?}");
+    assert!(user_cpu_ms.is_some());
+    assert!(sys_cpu_ms.is_some());
+    assert!(max_rss_kb.is_some_and(|kb| kb > 0));
+}
@@ -77,3 +77,33 @@ fn search_flags_before_query() {
         "flags before query should be accepted, got: {stderr}"
     );
 }
+
+/// An unknown `--registry` fails before any network call.
+#[test]
+fn search_unknown_registry_is_an_error() {
+    let home = common::TestHome::new();
+    let output = home
+        .cmd()
+        .args(["search", "--registry", "nope", "git"])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("unknown registry 'nope'"), "{stderr}");
+}
+
+/// The query is optional when a narrowing filter is given.
+#[test]
+fn search_query_is_optional_with_filters() {
+    let home = common::TestHome::new();
+    let output = home
+        .cmd()
+        .args(["search", "--stdlib", "--sort", "downloads"])
+        .output()
+        .unwrap();
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        !stderr.contains("Usage") && !stderr.contains("required"),
+        "a filter should stand in for the query, got: {stderr}"
+    );
+}
@@ -0,0 +1,67 @@
+//! `[shell]` in config.toml: the shell that runs a filter's `run` command and
+//! shell-mode (`tokf -c`) lines.
+
+#![cfg(unix)]
+#![allow(clippy::unwrap_used, clippy::expect_used)]
+
+mod common;
+
+use common::tokf;
+use tempfile::TempDir;
+
+/// `[[ ]]` is bash syntax that `sh` (dash) rejects.
+const BASH_ONLY: &str = "[[ -n x ]] && echo bash-ran";
+
+fn setup_project(shell: &str) -> TempDir {
+    let dir = TempDir::new().unwrap();
+    let tokf_dir = dir.path().join(".tokf");
+    std::fs::create_dir_all(tokf_dir.join("filters")).unwrap();
+    std::fs::write(
+        tokf_dir.join("config.toml"),
+        format!("[shell]\nname = \"{shell}\"\n"),
+    )
+    .unwrap();
+    std::fs::write(
+        tokf_dir.join("filters/check.toml"),
+        format!("command = \"check\"\nrun = \"{BASH_ONLY}; echo {{args}}\""),
+    )
+    .unwrap();
+    dir
+}
+
+#[test]
+fn run_override_uses_configured_shell() {
+    let dir = setup_project("bash");
+    let output = tokf()
+        .args(["run", "check", "it's"])
+        .current_dir(dir.path())
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{output:?}");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.trim().ends_with("bash-ran\nit's"), "got: {stdout}");
+}
+
+#[test]
+fn shell_mode_delegates_to_configured_shell() {
+    let dir = setup_project("bash");
+    let output = tokf()
+        .args(["-ec", BASH_ONLY])
+        .current_dir(dir.path())
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{output:?}");
+    assert_eq!(String::from_utf8_lossy(&output.stdout).trim(), "bash-ran");
+}
+
+#[test]
+fn shell_mode_argv_quotes_for_configured_shell() {
+    let dir = setup_project("bash");
+    let output = tokf()
+        .args(["-c", "printf", "%s|", "a b", "it's"])
+        .current_dir(dir.path())
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{output:?}");
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "a b|it's|");
+}
@@ -186,3 +186,75 @@ fn show_cargo_build_nested_embedded_path() {
         "expected TOML content with on_success or skip, got: {stdout}"
     );
 }
+
+#[test]
+fn show_resolved_applies_extends() {
+    let dir = tempfile::TempDir::new().unwrap();
+    let filters_dir = dir.path().join(".tokf/filters");
+    std::fs::create_dir_all(&filters_dir).unwrap();
+    std::fs::write(
+        filters_dir.join("base.toml"),
+        "command = \"base\"\nskip = [\"^noise\"]\n",
+    )
+    .unwrap();
+    std::fs::write(
+        filters_dir.join("my-tool.toml"),
+        "command = \"my tool\"\nextends = \"base\"\n",
+    )
+    .unwrap();
+
+    let output = tokf()
+        .args(["show", "--resolved", "my-tool"])
+        .current_dir(dir.path())
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{output:?}");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.contains("^noise"),
+        "inherited skip missing: {stdout}"
+    );
+
+    let output = tokf()
+        .args(["show", "--json", "my-tool"])
+        .current_dir(dir.path())
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{output:?}");
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(json["name"], "my-tool");
+    assert_eq!(json["priority"], "local");
+    assert_eq!(json["hash"].as_str().unwrap().len(), 64);
+    assert_eq!(json["config"]["command"], "my tool");
+    assert_eq!(json["config"]["skip"], serde_json::json!(["^noise"]));
+}
+
+#[test]
+fn show_resolved_selects_the_variant_for_the_current_directory() {
+    let dir = tempfile::TempDir::new().unwrap();
+    let show = || {
+        tokf()
+            .args(["show", "--resolved", "npm/test"])
+            .current_dir(dir.path())
+            .output()
+            .unwrap()
+    };
+
+    let output = show();
+    assert!(output.status.success(), "{output:?}");
+    let parent = String::from_utf8_lossy(&output.stdout).into_owned();
+    assert!(parent.contains("[[variant]]"), "{parent}");
+
+    std::fs::write(dir.path().join("vitest.config.ts"), "").unwrap();
+    let output = show();
+    assert!(output.status.success(), "{output:?}");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let vitest: toml::Value = toml::from_str(&stdout).unwrap();
+    assert_ne!(stdout, parent);
+    assert!(
+        vitest["command"].as_str().unwrap().contains("vitest"),
+        "{stdout}"
+    );
+    assert!(stderr.contains("variant selected here"), "{stderr}");
+}
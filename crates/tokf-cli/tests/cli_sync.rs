@@ -0,0 +1,164 @@
+//! `tokf sync status`, cursor repair and `tokf sync reset-machine`.
+
+#![allow(
+    clippy::unwrap_used,
+    clippy::expect_used,
+    clippy::significant_drop_tightening
+)]
+
+mod common;
+use common::TestHome;
+
+fn sync_status(home: &TestHome, args: &[&str]) -> String {
+    let output = home.cmd().args(["sync"]).args(args).output().unwrap();
+    assert!(output.status.success(), "tokf sync {args:?} failed");
+    String::from_utf8_lossy(&output.stdout).into_owned()
+}
+
+#[test]
+fn status_without_login_or_machine_says_so() {
+    let home = TestHome::new();
+    let stdout = sync_status(&home, &["status"]);
+    assert!(stdout.contains("not logged in"), "{stdout}");
+    assert!(stdout.contains("not registered"), "{stdout}");
+    assert!(stdout.contains("Last synced ID: 0"), "{stdout}");
+    assert!(stdout.contains("Last sync:      never"), "{stdout}");
+    assert!(stdout.contains("Pending events: 0"), "{stdout}");
+}
+
+#[test]
+fn status_shows_registered_machine_and_flag_form_matches() {
+    let home = TestHome::new();
+    std::fs::write(
+        home.path().join("machine.toml"),
+        "machine_id = \"0b5c7a10-1111-4222-8333-444455556666\"\nhostname = \"devbox\"\n",
+    )
+    .unwrap();
+    let stdout = sync_status(&home, &["status"]);
+    assert!(
+        stdout.contains("Machine:        0b5c7a10-1111-4222-8333-444455556666 (devbox)"),
+        "{stdout}"
+    );
+    assert_eq!(sync_status(&home, &["--status"]), stdout);
+}
+
+#[test]
+fn status_reports_backoff_left_by_failed_syncs() {
+    let home = TestHome::new();
+    let stdout = sync_status(&home, &["status"]); // creates the DB
+    assert!(!stdout.contains("Retry:"), "{stdout}");
+
+    let conn = rusqlite::Connection::open(home.db_path()).unwrap();
+    conn.execute_batch(
+        "INSERT INTO sync_state (key, value) VALUES
+            ('retry_attempts', '3'),
+            ('retry_next_at', '1709210096'),
+            ('sync_batch_size', '125')",
+    )
+    .unwrap();
+
+    let stdout = sync_status(&home, &["status"]);
+    assert!(
+        stdout.contains("3 failed attempt(s); background sync resumes after 2024-02-29T12:34:56Z"),
+        "{stdout}"
+    );
+    assert!(stdout.contains("Batch size:     125"), "{stdout}");
+}
+
+/// A home with a registered machine and `count` unsynced events.
+fn registered_home(count: usize) -> TestHome {
+    let home = TestHome::new();
+    std::fs::write(
+        home.path().join("machine.toml"),
+        "machine_id = \"0b5c7a10-1111-4222-8333-444455556666\"\nhostname = \"devbox\"\n",
+    )
+    .unwrap();
+    let conn = tokf::tracking::open_db(&home.db_path()).unwrap();
+    for _ in 0..count {
+        let event = tokf::tracking::build_event("git status", None, None, 400, 80, 0, 1, 0, false);
+        tokf::tracking::record_event(&conn, &event).unwrap();
+    }
+    home
+}
+
+fn sync_against(home: &TestHome, server: &mockito::Server, args: &[&str]) -> String {
+    let output = home
+        .cmd()
+        .args(["sync"])
+        .args(args)
+        .env("TOKF_TOKEN", "tokf_pat_test")
+        .env("TOKF_SERVER_URL", server.url())
+        .output()
+        .unwrap();
+    let stderr = String::from_utf8_lossy(&output.stderr).into_owned();
+    assert!(
+        output.status.success(),
+        "tokf sync {args:?} failed: {stderr}"
+    );
+    stderr
+}
+
+fn json_mock(
+    server: &mut mockito::Server,
+    path: &str,
+    request: serde_json::Value,
+    response: &str,
+) -> mockito::Mock {
+    server
+        .mock("POST", path)
+        .match_body(mockito::Matcher::PartialJson(request))
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(response)
+        .expect(1)
+        .create()
+}
+
+#[test]
+fn sync_repairs_a_server_cursor_from_a_previous_install() {
+    let home = registered_home(3);
+    let mut server = mockito::Server::new();
+    // The server remembers 50 events from before the reimage and skips all three.
+    let skipped = json_mock(
+        &mut server,
+        "/api/sync",
+        serde_json::json!({"last_event_id": 0}),
+        r#"{"accepted":0,"cursor":50}"#,
+    );
+    let reconcile = json_mock(
+        &mut server,
+        "/api/sync/reconcile",
+        serde_json::json!({"last_synced_id": 0, "max_event_id": 3, "reset": false}),
+        r#"{"cursor":0,"previous_cursor":50}"#,
+    );
+    let resent = json_mock(
+        &mut server,
+        "/api/sync",
+        serde_json::json!({"last_event_id": 0}),
+        r#"{"accepted":3,"cursor":3}"#,
+    );
+
+    let stderr = sync_against(&home, &server, &[]);
+    skipped.assert();
+    reconcile.assert();
+    resent.assert();
+    assert!(stderr.contains("server was at event 50"), "{stderr}");
+    assert!(stderr.contains("Synced 3 event(s). Cursor: 3."), "{stderr}");
+}
+
+#[test]
+fn reset_machine_rewinds_the_server_cursor() {
+    let home = registered_home(2);
+    let mut server = mockito::Server::new();
+    let reconcile = json_mock(
+        &mut server,
+        "/api/sync/reconcile",
+        serde_json::json!({"last_synced_id": 0, "max_event_id": 2, "reset": true}),
+        r#"{"cursor":0,"previous_cursor":120}"#,
+    );
+
+    let stderr = sync_against(&home, &server, &["reset-machine"]);
+    reconcile.assert();
+    assert!(stderr.contains("reset from event 120 to 0"), "{stderr}");
+    assert!(stderr.contains("2 pending event(s)"), "{stderr}");
+}
@@ -0,0 +1,59 @@
+//! Real tokenizer counts in the tracking DB (`tokenizer` feature, `[tokenizer]`).
+
+#![cfg(feature = "tokenizer")]
+#![allow(clippy::unwrap_used, clippy::expect_used)]
+
+mod common;
+use common::TestHome;
+
+type TokenRow = (i64, Option<String>, Option<i64>, Option<i64>);
+
+fn last_counts(home: &TestHome) -> TokenRow {
+    let conn = rusqlite::Connection::open(home.db_path()).unwrap();
+    conn.query_row(
+        "SELECT input_tokens_est, tokenizer, input_tokens_tok, output_tokens_tok \
+         FROM events ORDER BY rowid DESC LIMIT 1",
+        [],
+        |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)),
+    )
+    .unwrap()
+}
+
+fn run_seq(home: &TestHome, project: &std::path::Path) {
+    let output = home
+        .cmd()
+        .args(["run", "seq", "200"])
+        .current_dir(project)
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{output:?}");
+}
+
+#[test]
+fn configured_model_records_both_counts() {
+    let home = TestHome::new();
+    let project = tempfile::TempDir::new().unwrap();
+    let tokf_dir = project.path().join(".tokf");
+    std::fs::create_dir_all(tokf_dir.join("filters")).unwrap();
+    std::fs::write(
+        tokf_dir.join("filters/seq.toml"),
+        "command = \"seq\"\n\n[on_success]\noutput = \"done\"\n",
+    )
+    .unwrap();
+
+    run_seq(&home, project.path());
+    let (estimate, tokenizer, ..) = last_counts(&home);
+    assert!(estimate > 0);
+    assert_eq!(tokenizer, None, "no [tokenizer] model, no real counts");
+
+    std::fs::write(
+        tokf_dir.join("config.toml"),
+        "[tokenizer]\nmodel = \"gpt-4o\"\n",
+    )
+    .unwrap();
+    run_seq(&home, project.path());
+    let (_, tokenizer, input, output) = last_counts(&home);
+    assert_eq!(tokenizer.as_deref(), Some("o200k"));
+    assert!(input.unwrap() > output.unwrap(), "{input:?} vs {output:?}");
+    assert_eq!(output, Some(1), "\"done\" is one token");
+}
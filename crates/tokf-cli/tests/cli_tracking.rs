@@ -209,3 +209,211 @@ fn gain_by_filter_json_output_is_array() {
     let parsed: serde_json::Value = serde_json::from_str(&stdout).expect("valid JSON");
     assert!(parsed.is_array(), "expected array, got: {parsed}");
 }
+
+#[test]
+fn gain_since_excludes_older_runs() {
+    let dir = temp_db_dir();
+    let db = dir.path().join("tracking.db");
+    tokf_with_db(&db)
+        .args(["run", "echo", "hello"])
+        .output()
+        .expect("run tokf run echo hello");
+
+    let commands = |args: &[&str]| {
+        let out = tokf_with_db(&db)
+            .args(["gain", "--json"])
+            .args(args)
+            .output()
+            .expect("gain json");
+        assert!(out.status.success(), "{out:?}");
+        let v: serde_json::Value = serde_json::from_slice(&out.stdout).unwrap();
+        v["total_commands"].as_i64().unwrap()
+    };
+    assert_eq!(commands(&["--period", "day"]), 1);
+    assert_eq!(commands(&["--since", "2000-01-01"]), 1);
+    assert_eq!(commands(&["--until", "2000-01-01"]), 0);
+    assert_eq!(commands(&["--since", "9999-01-01"]), 0);
+}
+
+#[test]
+fn gain_rejects_malformed_dates_and_mixed_ranges() {
+    let dir = temp_db_dir();
+    let db = dir.path().join("tracking.db");
+    for args in [
+        &["gain", "--since", "last week"][..],
+        &["gain", "--period", "week", "--since", "2024-06-01"][..],
+    ] {
+        let out = tokf_with_db(&db).args(args).output().expect("run");
+        assert_eq!(out.status.code(), Some(2), "{args:?}");
+    }
+}
+
+#[test]
+fn gain_export_writes_csv_and_markdown() {
+    let dir = temp_db_dir();
+    let db = dir.path().join("tracking.db");
+    tokf_with_db(&db)
+        .args(["run", "echo", "hello"])
+        .output()
+        .expect("run tokf run echo hello");
+
+    let out = tokf_with_db(&db)
+        .args(["gain", "export", "--format", "md", "--by-filter"])
+        .output()
+        .expect("export md");
+    assert!(out.status.success(), "{out:?}");
+    let stdout = String::from_utf8_lossy(&out.stdout);
+    let lines: Vec<&str> = stdout.lines().collect();
+    assert!(lines[0].starts_with("| filter | runs |"), "{stdout}");
+    assert!(lines[2].starts_with("| passthrough | 1 |"), "{stdout}");
+
+    let csv = dir.path().join("week.csv");
+    let out = tokf_with_db(&db)
+        .args([
+            "gain", "export", "--format", "csv", "--daily", "--period", "week",
+        ])
+        .arg("--out")
+        .arg(&csv)
+        .output()
+        .expect("export csv");
+    assert!(out.status.success(), "{out:?}");
+    assert!(out.stdout.is_empty());
+    let written = std::fs::read_to_string(&csv).unwrap();
+    assert!(written.starts_with("date,runs,"), "{written}");
+    assert_eq!(written.lines().count(), 2, "{written}");
+}
+
+#[test]
+fn gain_by_project_groups_runs_by_project_root() {
+    let dir = temp_db_dir();
+    let db = dir.path().join("tracking.db");
+    let project = dir.path().join("proj");
+    std::fs::create_dir_all(project.join(".git")).unwrap();
+    std::fs::create_dir_all(project.join("src")).unwrap();
+    for cwd in [project.clone(), project.join("src")] {
+        let out = tokf_with_db(&db)
+            .current_dir(&cwd)
+            .args(["run", "echo", "hello"])
+            .output()
+            .expect("run tokf run echo hello");
+        assert!(out.status.success(), "{out:?}");
+    }
+
+    let out = tokf_with_db(&db)
+        .args(["gain", "--by-project", "--json"])
+        .output()
+        .expect("gain by-project json");
+    assert!(out.status.success(), "{out:?}");
+    let rows: serde_json::Value = serde_json::from_slice(&out.stdout).unwrap();
+    let rows = rows.as_array().expect("array");
+    assert_eq!(rows.len(), 1, "{rows:?}");
+    let recorded = Path::new(rows[0]["project"].as_str().unwrap());
+    assert_eq!(recorded.file_name().unwrap(), "proj");
+    assert_eq!(rows[0]["commands"], 2);
+}
+
+#[test]
+fn gain_by_command_counts_unfiltered_runs() {
+    let dir = temp_db_dir();
+    let db = dir.path().join("tracking.db");
+    for word in ["there", "again"] {
+        tokf_with_db(&db)
+            .args(["run", "echo", "hello", word])
+            .output()
+            .expect("run tokf run echo");
+    }
+
+    let out = tokf_with_db(&db)
+        .args(["gain", "--by-command"])
+        .output()
+        .expect("gain by-command");
+    assert!(out.status.success(), "{out:?}");
+    let stdout = String::from_utf8_lossy(&out.stdout);
+    assert!(stdout.contains("tokf gain by command"), "{stdout}");
+    let echo_rows: Vec<&str> = stdout.lines().filter(|l| l.contains("echo")).collect();
+    assert_eq!(echo_rows.len(), 1, "{stdout}");
+    assert!(echo_rows[0].contains("echo hello"), "{stdout}");
+    assert!(echo_rows[0].contains("unfiltered: 2"), "{stdout}");
+}
+
+#[test]
+fn gain_cost_prices_savings_from_config() {
+    let dir = temp_db_dir();
+    let db = dir.path().join("tracking.db");
+    let gain_cost = |args: &[&str]| {
+        tokf_with_db(&db)
+            .current_dir(dir.path())
+            .args(["gain", "--cost"])
+            .args(args)
+            .output()
+            .expect("gain cost")
+    };
+
+    let out = gain_cost(&[]);
+    assert_eq!(out.status.code(), Some(1), "{out:?}");
+    assert!(String::from_utf8_lossy(&out.stderr).contains("[pricing]"));
+
+    let home = dir.path().join("tokf-home");
+    std::fs::create_dir_all(&home).unwrap();
+    std::fs::write(
+        home.join("config.toml"),
+        "[pricing]\nclaude-sonnet = 0.003\nclaude-haiku = 0.0008\n",
+    )
+    .unwrap();
+
+    let out = gain_cost(&["--model", "claude-sonnet", "--json"]);
+    assert!(out.status.success(), "{out:?}");
+    let rows: serde_json::Value = serde_json::from_slice(&out.stdout).unwrap();
+    assert_eq!(rows.as_array().unwrap().len(), 1, "{rows}");
+    assert_eq!(rows[0]["model"], "claude-sonnet");
+    assert_eq!(rows[0]["usd_per_1k_input_tokens"], 0.003);
+
+    let out = gain_cost(&[]);
+    let stdout = String::from_utf8_lossy(&out.stdout);
+    assert!(stdout.contains("claude-haiku"), "{stdout}");
+    assert!(stdout.contains("$0.00"), "{stdout}");
+
+    let out = gain_cost(&["--model", "gpt-4o"]);
+    assert_eq!(out.status.code(), Some(1), "{out:?}");
+}
+
+#[test]
+fn tracking_false_records_nothing_but_still_filters() {
+    let db_dir = temp_db_dir();
+    let db = db_dir.path().join("tracking.db");
+    let work_dir = TempDir::new().unwrap();
+    let tokf_dir = work_dir.path().join(".tokf");
+    std::fs::create_dir_all(tokf_dir.join("filters")).unwrap();
+    std::fs::write(tokf_dir.join("config.toml"), "tracking = false\n").unwrap();
+    std::fs::write(
+        tokf_dir.join("filters/echo.toml"),
+        "command = \"echo\"\n[on_success]\noutput = \"filtered\"",
+    )
+    .unwrap();
+
+    let out = tokf_with_db(&db)
+        .current_dir(work_dir.path())
+        .args(["run", "echo", "secret"])
+        .output()
+        .expect("run");
+    assert!(out.status.success());
+    let stdout = String::from_utf8_lossy(&out.stdout);
+    assert!(stdout.trim_end().ends_with("filtered"), "{stdout}");
+
+    let out = tokf_with_db(&db)
+        .args(["gain", "--json"])
+        .output()
+        .expect("gain");
+    let json: serde_json::Value = serde_json::from_slice(&out.stdout).unwrap();
+    assert_eq!(json["total_commands"], 0, "{json}");
+    let out = tokf_with_db(&db)
+        .current_dir(work_dir.path())
+        .args(["history", "list"])
+        .output()
+        .expect("history list");
+    assert!(
+        !String::from_utf8_lossy(&out.stdout).contains("secret"),
+        "{}",
+        String::from_utf8_lossy(&out.stdout)
+    );
+}
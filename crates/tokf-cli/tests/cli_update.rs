@@ -0,0 +1,34 @@
+//! `tokf update` without a registry round-trip.
+
+#![allow(clippy::unwrap_used, clippy::expect_used)]
+
+mod common;
+use common::TestHome;
+
+#[test]
+fn nothing_installed_means_nothing_to_update() {
+    let home = TestHome::new();
+    let output = home
+        .cmd()
+        .current_dir(home.path())
+        .args(["update", "--check"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("no filters installed from the registry"),
+        "{stderr}"
+    );
+}
+
+#[test]
+fn check_and_yes_conflict() {
+    let home = TestHome::new();
+    let output = home
+        .cmd()
+        .args(["update", "--check", "--yes"])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+}
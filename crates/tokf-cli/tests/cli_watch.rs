@@ -0,0 +1,63 @@
+//! `tokf watch`: re-run the filtered command when a watched file changes.
+
+#![cfg(unix)]
+#![allow(clippy::unwrap_used, clippy::expect_used)]
+
+mod common;
+
+use std::process::Stdio;
+use std::time::Duration;
+
+use common::tokf;
+use tempfile::TempDir;
+
+fn settle() {
+    std::thread::sleep(Duration::from_millis(800));
+}
+
+#[test]
+fn watch_reruns_on_matching_changes_and_stops_on_sigterm() {
+    let dir = TempDir::new().unwrap();
+    let filters_dir = dir.path().join(".tokf/filters");
+    std::fs::create_dir_all(&filters_dir).unwrap();
+    std::fs::write(
+        filters_dir.join("echo.toml"),
+        "command = \"echo\"\nwatch = [\"*.txt\"]\n[on_success]\noutput = \"filtered\"",
+    )
+    .unwrap();
+
+    let child = tokf()
+        .args([
+            "watch",
+            "--poll-ms",
+            "50",
+            "--debounce-ms",
+            "50",
+            "echo",
+            "hi",
+        ])
+        .current_dir(dir.path())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    settle();
+    std::fs::write(dir.path().join("notes.md"), "not watched").unwrap();
+    settle();
+    std::fs::write(dir.path().join("notes.txt"), "watched").unwrap();
+    settle();
+    let status = std::process::Command::new("kill")
+        .args(["-TERM", &child.id().to_string()])
+        .status()
+        .unwrap();
+    assert!(status.success());
+
+    let output = child.wait_with_output().unwrap();
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert_eq!(stdout.matches("filtered").count(), 2, "{stdout}\n{stderr}");
+    assert!(stderr.contains("notes.txt changed"), "{stderr}");
+    assert!(!stderr.contains("notes.md"), "{stderr}");
+    assert!(stderr.contains("watching *.txt"), "{stderr}");
+    assert!(output.status.success(), "{output:?}");
+}
@@ -44,6 +44,7 @@ const RUNTIME_ENV: &[&str] = &[
     "TOKF_SHOW_INDICATOR",
     "TOKF_SERVER_URL",
     "TOKF_HTTP_TIMEOUT",
+    "TOKF_TOKEN",
     "TOKF_ORIGINAL_PATH",
     "TOKF_CODEX_REWRITE_MODE",
     "TOKF_TELEMETRY_ENABLED",
@@ -62,6 +63,14 @@ const RUNTIME_ENV: &[&str] = &[
     "OTEL_EXPORTER_OTLP_PROTOCOL",
     "OTEL_EXPORTER_OTLP_HEADERS",
     "OTEL_RESOURCE_ATTRIBUTES",
+    // Runtime-owned proxy settings: a developer's corporate proxy must not
+    // sit between a test and its local mock server.
+    "HTTPS_PROXY",
+    "https_proxy",
+    "HTTP_PROXY",
+    "http_proxy",
+    "NO_PROXY",
+    "no_proxy",
 ];
 
 /// Point `cmd` at `home` for all tokf state, clearing every other runtime
@@ -220,3 +220,85 @@ file = "{}"
         "from file: hello"
     );
 }
+
+#[test]
+fn lua_filter_reads_sections_global() {
+    let (_ftmp, filter) = write_filter(
+        r#"
+command = "test"
+
+[[section]]
+collect_as = "failures"
+match = "^FAIL "
+
+[lua_script]
+lang = "luau"
+source = '''
+local names = {}
+for _, line in ipairs(sections.failures.lines) do
+  table.insert(names, (line:gsub("^FAIL ", "")))
+end
+return sections.failures.count .. " failed: " .. table.concat(names, ", ")
+'''
+"#,
+    );
+    let (_xtmp, fixture) = write_fixture("PASS a\nFAIL b\nPASS c\nFAIL d");
+
+    let output = tokf()
+        .args([
+            "apply",
+            filter.to_str().unwrap(),
+            fixture.to_str().unwrap(),
+            "--exit-code",
+            "1",
+        ])
+        .output()
+        .unwrap();
+
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout).trim(),
+        "2 failed: b, d"
+    );
+}
+
+#[test]
+fn lua_filter_table_return_adds_template_vars() {
+    let (_ftmp, filter) = write_filter(
+        r#"
+command = "test"
+
+[lua_script]
+lang = "luau"
+source = '''
+local n = 0
+for _ in output:gmatch("warning") do n = n + 1 end
+return { warnings = n }
+'''
+
+[on_success]
+output = "build ok ({warnings} warnings)"
+"#,
+    );
+    let (_xtmp, fixture) = write_fixture("warning: a\ncompiled\nwarning: b");
+
+    let output = tokf()
+        .args([
+            "apply",
+            filter.to_str().unwrap(),
+            fixture.to_str().unwrap(),
+            "--exit-code",
+            "0",
+        ])
+        .output()
+        .unwrap();
+
+    assert!(
+        output.status.success(),
+        "stderr: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout).trim(),
+        "build ok (2 warnings)"
+    );
+}
@@ -466,7 +466,7 @@ fn get_does_not_retry_on_404() {
 
 #[test]
 fn post_multipart_returns_raw_response_on_any_status() {
-    use reqwest::blocking::multipart::{Form, Part};
+    use reqwest::multipart::{Form, Part};
     let mut server = mockito::Server::new();
     // post_multipart returns the raw response — even 400 is not an error.
     let mock = server
@@ -490,7 +490,7 @@ fn post_multipart_returns_raw_response_on_any_status() {
 
 #[test]
 fn post_multipart_does_not_retry() {
-    use reqwest::blocking::multipart::{Form, Part};
+    use reqwest::multipart::{Form, Part};
     let mut server = mockito::Server::new();
     // POST is non-idempotent, so no retry even on connection error simulation.
     // We test with a normal error status to verify exactly 1 request.
@@ -511,7 +511,7 @@ fn post_multipart_does_not_retry() {
 
 #[test]
 fn put_multipart_returns_raw_response() {
-    use reqwest::blocking::multipart::{Form, Part};
+    use reqwest::multipart::{Form, Part};
     let mut server = mockito::Server::new();
     let mock = server
         .mock("PUT", "/api/filters/abc/tests")
@@ -0,0 +1,103 @@
+#![allow(
+    clippy::unwrap_used,
+    clippy::expect_used,
+    clippy::significant_drop_tightening
+)]
+
+use tokf::remote::filter_client;
+use tokf::remote::http::Client;
+use tokf::runtime::Runtime;
+
+const HASH: &str = "abc123def456abc123def456abc123def456abc123def456abc123def456abc1";
+
+fn download_path() -> String {
+    format!("/api/filters/{HASH}/download")
+}
+
+fn body(toml: &str) -> String {
+    serde_json::json!({ "filter_toml": toml, "test_files": [] }).to_string()
+}
+
+#[test]
+fn unchanged_download_is_served_from_the_cache() {
+    let mut server = mockito::Server::new();
+    let rt = Runtime::isolated();
+    let c = Client::unauthenticated(&rt, &server.url()).unwrap();
+
+    let first = server
+        .mock("GET", download_path().as_str())
+        .match_header("if-none-match", mockito::Matcher::Missing)
+        .with_status(200)
+        .with_header("etag", "\"v1\"")
+        .with_body(body("command = \"git push\""))
+        .expect(1)
+        .create();
+    let downloaded = filter_client::download_filter(&rt, &c, HASH).unwrap();
+    assert_eq!(downloaded.filter_toml, "command = \"git push\"");
+    first.assert();
+
+    let revalidated = server
+        .mock("GET", download_path().as_str())
+        .match_header("if-none-match", "\"v1\"")
+        .with_status(304)
+        .with_header("etag", "\"v1\"")
+        .expect(1)
+        .create();
+    let cached = filter_client::download_filter(&rt, &c, HASH).unwrap();
+    assert_eq!(cached.filter_toml, "command = \"git push\"");
+    revalidated.assert();
+}
+
+#[test]
+fn changed_download_replaces_the_cached_copy() {
+    let mut server = mockito::Server::new();
+    let rt = Runtime::isolated();
+    let c = Client::unauthenticated(&rt, &server.url()).unwrap();
+
+    let _first = server
+        .mock("GET", download_path().as_str())
+        .match_header("if-none-match", mockito::Matcher::Missing)
+        .with_status(200)
+        .with_header("etag", "\"v1\"")
+        .with_body(body("command = \"old\""))
+        .create();
+    filter_client::download_filter(&rt, &c, HASH).unwrap();
+
+    let _changed = server
+        .mock("GET", download_path().as_str())
+        .match_header("if-none-match", "\"v1\"")
+        .with_status(200)
+        .with_header("etag", "\"v2\"")
+        .with_body(body("command = \"new\""))
+        .create();
+    let downloaded = filter_client::download_filter(&rt, &c, HASH).unwrap();
+    assert_eq!(downloaded.filter_toml, "command = \"new\"");
+
+    let revalidated = server
+        .mock("GET", download_path().as_str())
+        .match_header("if-none-match", "\"v2\"")
+        .with_status(304)
+        .expect(1)
+        .create();
+    let cached = filter_client::download_filter(&rt, &c, HASH).unwrap();
+    assert_eq!(cached.filter_toml, "command = \"new\"");
+    revalidated.assert();
+}
+
+#[test]
+fn responses_without_an_etag_are_not_cached() {
+    let mut server = mockito::Server::new();
+    let rt = Runtime::isolated();
+    let c = Client::unauthenticated(&rt, &server.url()).unwrap();
+
+    let mock = server
+        .mock("GET", download_path().as_str())
+        .match_header("if-none-match", mockito::Matcher::Missing)
+        .with_status(200)
+        .with_body(body("command = \"git push\""))
+        .expect(2)
+        .create();
+    filter_client::download_filter(&rt, &c, HASH).unwrap();
+    filter_client::download_filter(&rt, &c, HASH).unwrap();
+    mock.assert();
+}
@@ -0,0 +1,97 @@
+#![allow(
+    clippy::unwrap_used,
+    clippy::expect_used,
+    clippy::panic,
+    clippy::significant_drop_tightening
+)]
+
+use tokf::remote::RemoteError;
+use tokf::remote::http::Client;
+use tokf::runtime::{ProxyEnv, Runtime};
+
+/// A runtime whose `HTTP_PROXY` points at `proxy`.
+fn runtime_via(proxy: &str) -> Runtime {
+    Runtime::builder()
+        .proxy_env(ProxyEnv {
+            http_proxy: Some(proxy.to_string()),
+            ..ProxyEnv::default()
+        })
+        .build()
+}
+
+fn remote_error(err: &anyhow::Error) -> &RemoteError {
+    err.downcast_ref::<RemoteError>()
+        .unwrap_or_else(|| panic!("expected a RemoteError, got: {err:?}"))
+}
+
+#[test]
+fn requests_go_through_the_configured_proxy() {
+    let mut proxy = mockito::Server::new();
+    let mock = proxy
+        .mock("GET", mockito::Matcher::Any)
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(r#"{"ok": true}"#)
+        .create();
+
+    let c = Client::unauthenticated(&runtime_via(&proxy.url()), "http://tokf.invalid").unwrap();
+    let body: serde_json::Value = c.get("/api/ping").unwrap();
+    assert_eq!(body["ok"], true);
+    mock.assert();
+}
+
+#[test]
+fn proxy_407_is_reported_as_a_proxy_failure() {
+    let mut proxy = mockito::Server::new();
+    let mock = proxy
+        .mock("GET", mockito::Matcher::Any)
+        .with_status(407)
+        .expect(1)
+        .create();
+
+    let c = Client::unauthenticated(&runtime_via(&proxy.url()), "http://tokf.invalid").unwrap();
+    let err = c.get::<serde_json::Value>("/api/ping").unwrap_err();
+    assert!(
+        matches!(remote_error(&err), RemoteError::ProxyFailed { .. }),
+        "{err:?}"
+    );
+    assert!(
+        err.to_string().contains("proxy requires authentication"),
+        "{err}"
+    );
+    // Not transient, so not retried.
+    mock.assert();
+}
+
+#[test]
+fn unreachable_proxy_is_reported_as_a_proxy_failure() {
+    let closed = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = closed.local_addr().unwrap();
+    drop(closed);
+
+    let rt = runtime_via(&format!("http://{addr}"));
+    let c = Client::unauthenticated(&rt, "http://tokf.invalid").unwrap();
+    let err = c.get::<serde_json::Value>("/api/ping").unwrap_err();
+    assert!(
+        matches!(remote_error(&err), RemoteError::ProxyFailed { .. }),
+        "{err:?}"
+    );
+    assert!(err.to_string().contains("through the proxy"), "{err}");
+}
+
+#[test]
+fn no_proxy_hosts_connect_directly() {
+    let mut server = mockito::Server::new();
+    let mock = server.mock("GET", "/api/ping").with_status(200).create();
+
+    let rt = Runtime::builder()
+        .proxy_env(ProxyEnv {
+            http_proxy: Some("http://127.0.0.1:1".to_string()),
+            no_proxy: Some("127.0.0.1".to_string()),
+            ..ProxyEnv::default()
+        })
+        .build();
+    let c = Client::unauthenticated(&rt, &server.url()).unwrap();
+    c.get_raw("/api/ping").unwrap();
+    mock.assert();
+}
@@ -0,0 +1,130 @@
+#![allow(
+    clippy::unwrap_used,
+    clippy::expect_used,
+    clippy::significant_drop_tightening
+)]
+
+use mockito::Matcher;
+use tokf::remote::filter_client::{self, SearchQuery, SearchSort};
+use tokf::remote::http::Client;
+use tokf::runtime::Runtime;
+
+fn summary(command: &str) -> serde_json::Value {
+    serde_json::json!({
+        "content_hash": "abc",
+        "command_pattern": command,
+        "author": "alice",
+        "savings_pct": 40.0,
+        "total_commands": 3,
+        "downloads": 12,
+        "stars": 2,
+    })
+}
+
+#[test]
+fn search_page_sends_filters_and_returns_the_cursor() {
+    let mut server = mockito::Server::new();
+    let rt = Runtime::isolated();
+    let c = Client::unauthenticated(&rt, &server.url()).unwrap();
+
+    let mock = server
+        .mock("GET", "/api/filters")
+        .match_query(Matcher::AllOf(vec![
+            Matcher::UrlEncoded("q".into(), String::new()),
+            Matcher::UrlEncoded("limit".into(), "5".into()),
+            Matcher::UrlEncoded("sort".into(), "downloads".into()),
+            Matcher::UrlEncoded("author".into(), "alice".into()),
+            Matcher::UrlEncoded("stdlib".into(), "true".into()),
+            Matcher::UrlEncoded("command".into(), "git".into()),
+            Matcher::UrlEncoded("cursor".into(), "c1".into()),
+            Matcher::UrlEncoded("org".into(), "acme".into()),
+        ]))
+        .with_status(200)
+        .with_header("x-next-cursor", "c2")
+        .with_body(serde_json::json!([summary("git push")]).to_string())
+        .expect(1)
+        .create();
+
+    let page = filter_client::search_filters_page(
+        &c,
+        &SearchQuery {
+            limit: 5,
+            sort: SearchSort::Downloads,
+            author: Some("alice"),
+            stdlib: Some(true),
+            command: Some("git"),
+            cursor: Some("c1"),
+            org: Some("acme"),
+            ..SearchQuery::default()
+        },
+    )
+    .unwrap();
+    mock.assert();
+    assert_eq!(page.next_cursor.as_deref(), Some("c2"));
+    assert_eq!(page.filters[0].command_pattern, "git push");
+    assert_eq!(page.filters[0].downloads, 12);
+    assert_eq!(page.filters[0].stars, 2);
+}
+
+#[test]
+fn plain_search_sends_only_the_query_and_limit() {
+    let mut server = mockito::Server::new();
+    let rt = Runtime::isolated();
+    let c = Client::unauthenticated(&rt, &server.url()).unwrap();
+
+    // An older server: no cursor header, no popularity fields.
+    let mock = server
+        .mock("GET", "/api/filters")
+        .match_query(Matcher::Regex("^q=git&limit=20$".into()))
+        .with_status(200)
+        .with_body(
+            serde_json::json!([{
+                "content_hash": "abc",
+                "command_pattern": "git push",
+                "author": "alice",
+                "savings_pct": 40.0,
+                "total_commands": 3,
+            }])
+            .to_string(),
+        )
+        .expect(1)
+        .create();
+
+    let results = filter_client::search_filters(&c, "git", 20).unwrap();
+    mock.assert();
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0].downloads, 0);
+}
+
+#[test]
+fn yanked_filters_are_requested_explicitly_and_explain_themselves() {
+    let mut server = mockito::Server::new();
+    let rt = Runtime::isolated();
+    let c = Client::unauthenticated(&rt, &server.url()).unwrap();
+
+    let mut yanked = summary("git push");
+    yanked["yanked_at"] = "2026-10-18 09:12:44+00".into();
+    yanked["yank_reason"] = "leaks env".into();
+    let mock = server
+        .mock("GET", "/api/filters")
+        .match_query(Matcher::UrlEncoded("include_yanked".into(), "true".into()))
+        .with_status(200)
+        .with_body(serde_json::json!([yanked]).to_string())
+        .expect(1)
+        .create();
+
+    let page = filter_client::search_filters_page(
+        &c,
+        &SearchQuery {
+            query: "git",
+            include_yanked: true,
+            ..SearchQuery::default()
+        },
+    )
+    .unwrap();
+    mock.assert();
+    assert_eq!(
+        page.filters[0].retirement().notice().as_deref(),
+        Some("yanked (leaks env)")
+    );
+}
@@ -0,0 +1,110 @@
+//! Configuration for `[[chunk]]`: splitting output into repeating blocks
+//! and extracting structured data from each one. Re-exported from `types`.
+
+use serde::{Deserialize, Serialize};
+
+/// Configuration for splitting output into repeating structural blocks.
+///
+/// Chunks split output at delimiter lines, extract structured data from
+/// each block, and collect the results as a structured collection for
+/// template rendering.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChunkConfig {
+    /// Regex that marks the start of each chunk.
+    pub split_on: String,
+
+    /// Whether the splitting line is included in the chunk (default: true).
+    #[serde(default = "super::types::default_true")]
+    pub include_split_line: bool,
+
+    /// Variable name for the structured collection in templates.
+    pub collect_as: String,
+
+    /// Extract a named field from the split (header) line.
+    pub extract: Option<ChunkExtract>,
+
+    /// Per-chunk body line extractions (first match per rule wins).
+    #[serde(default)]
+    pub body_extract: Vec<ChunkBodyExtract>,
+
+    /// Per-chunk aggregate rules (run within each chunk's lines).
+    #[serde(default)]
+    pub aggregate: Vec<ChunkAggregateRule>,
+
+    /// Field name(s) to group chunks by (merging numeric fields).
+    ///
+    /// A list nests groups (e.g. `["crate", "module"]`) when `children_as`
+    /// is set; without `children_as` the fields form one composite key.
+    pub group_by: Option<GroupBy>,
+
+    /// When set alongside `group_by`, preserve each group's original items
+    /// as a nested collection under this name instead of discarding them.
+    /// With multiple `group_by` fields, every level exposes its children
+    /// under this same name.
+    pub children_as: Option<String>,
+}
+
+/// Chunk grouping keys — either a single field or an ordered list of fields.
+///
+/// ```toml
+/// group_by = "crate"                 # Single level
+/// group_by = ["crate", "module"]     # Nested: crate → module → items
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum GroupBy {
+    Single(String),
+    Multiple(Vec<String>),
+}
+
+impl GroupBy {
+    /// All grouping fields, outermost first.
+    pub fn keys(&self) -> &[String] {
+        match self {
+            Self::Single(s) => std::slice::from_ref(s),
+            Self::Multiple(v) => v,
+        }
+    }
+}
+
+impl From<&str> for GroupBy {
+    fn from(s: &str) -> Self {
+        Self::Single(s.to_string())
+    }
+}
+
+/// Per-chunk aggregation rule. Unlike branch-level `AggregateRule`, this does
+/// not need a `from` field because it always operates on the chunk's own lines.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChunkAggregateRule {
+    /// Regex pattern to extract numeric values.
+    pub pattern: String,
+
+    /// Name for the summed value.
+    pub sum: Option<String>,
+
+    /// Name for the count of matching entries.
+    pub count_as: Option<String>,
+}
+
+/// Extract a named field from a line within a chunk (header or body).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChunkFieldExtract {
+    /// Regex pattern with a capture group.
+    pub pattern: String,
+
+    /// Variable name for the captured value.
+    #[serde(rename = "as")]
+    pub as_name: String,
+
+    /// When true, if this field is not extracted from a chunk, it inherits
+    /// the value from the most recent chunk that did extract it.
+    #[serde(default)]
+    pub carry_forward: bool,
+}
+
+/// Backward-compatible alias for header extraction.
+pub type ChunkExtract = ChunkFieldExtract;
+
+/// Backward-compatible alias for body-line extraction.
+pub type ChunkBodyExtract = ChunkFieldExtract;
@@ -0,0 +1,73 @@
+//! Configuration for the `[parse]` stage: declarative structured parsing of
+//! text, JSON and test-report output. Re-exported from `types`.
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use super::types::{ExtractRule, JsonExtractRule};
+
+/// Structured parsing configuration for status-like outputs.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ParseConfig {
+    /// Input format. `text` (default) uses `branch`/`group`; `json`/`jsonl`
+    /// run `extract` rules and `junit`/`tap` produce test-result collections.
+    /// Non-text formats render through the output branches.
+    #[serde(default)]
+    pub format: ParseFormat,
+
+    /// Rule for extracting the branch name from the first line.
+    pub branch: Option<LineExtract>,
+
+    /// Rule for grouping file entries by status code.
+    pub group: Option<GroupConfig>,
+
+    /// `JSONPath` extraction rules for the `json` and `jsonl` formats. Same
+    /// semantics as `[[json.extract]]`.
+    #[serde(default)]
+    pub extract: Vec<JsonExtractRule>,
+}
+
+/// Input format for the `[parse]` stage.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ParseFormat {
+    /// Line-oriented text (branch line + grouped entries).
+    #[default]
+    Text,
+    /// A single JSON document.
+    Json,
+    /// Newline-delimited JSON (one object per line, e.g.
+    /// `cargo build --message-format=json`). Lines that aren't JSON are
+    /// ignored; the parsed objects are queried as one array.
+    Jsonl,
+    /// `JUnit` XML test report (pytest `--junitxml`, `PHPUnit` `--log-junit`,
+    /// `go-junit-report`). Exposes `tests`/`passed`/`failed`/`skipped`.
+    Junit,
+    /// Test Anything Protocol output. Exposes the same collections as `junit`.
+    Tap,
+}
+
+/// Extracts a value from a specific line number.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LineExtract {
+    /// 1-based line number to extract from.
+    pub line: usize,
+
+    /// Regex pattern with capture groups.
+    pub pattern: String,
+
+    /// Output template using `{1}`, `{2}`, etc. for captures.
+    pub output: String,
+}
+
+/// Groups lines by a key pattern and maps keys to human labels.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GroupConfig {
+    /// Rule for extracting the group key from each line.
+    pub key: ExtractRule,
+
+    /// Map from raw key to human-readable label.
+    #[serde(default)]
+    pub labels: BTreeMap<String, String>,
+}
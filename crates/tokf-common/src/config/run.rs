@@ -0,0 +1,42 @@
+//! Configuration for how the wrapped command is run: its stdin and the
+//! `retry` policy. Re-exported from `types`.
+
+use serde::{Deserialize, Serialize};
+
+/// What the wrapped command gets as stdin.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StdinMode {
+    /// tokf's own stdin, so editors and prompts reach the user.
+    #[default]
+    Inherit,
+    /// `/dev/null`: reads hit end-of-file at once.
+    Null,
+    /// No stdin at all; reads fail. Same as `Null` on Windows.
+    Closed,
+}
+
+/// Retry policy for a flaky command.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RetryConfig {
+    /// Total number of runs, including the first.
+    #[serde(default = "default_retry_attempts")]
+    pub attempts: u32,
+    /// Exit codes worth retrying; empty means any non-zero code.
+    #[serde(default)]
+    pub on_exit: Vec<i32>,
+    /// Pause before the first retry, doubled before each further one.
+    #[serde(default)]
+    pub backoff_ms: u64,
+}
+
+const fn default_retry_attempts() -> u32 {
+    3
+}
+
+impl RetryConfig {
+    /// Whether a run that exited with `exit_code` should be retried.
+    pub fn retries(&self, exit_code: i32) -> bool {
+        exit_code != 0 && (self.on_exit.is_empty() || self.on_exit.contains(&exit_code))
+    }
+}
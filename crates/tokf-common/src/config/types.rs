@@ -4,12 +4,21 @@ use std::collections::BTreeMap;
 
 use serde::{Deserialize, Serialize};
 
+pub use super::parse::{GroupConfig, LineExtract, ParseConfig, ParseFormat};
+
+pub use super::chunk::{
+    ChunkAggregateRule, ChunkBodyExtract, ChunkConfig, ChunkExtract, ChunkFieldExtract, GroupBy,
+};
+
+pub use super::run::{RetryConfig, StdinMode};
+
 /// A command pattern — either a single string or a list of alternatives.
 ///
 /// ```toml
 /// command = "git push"                    # Single
 /// command = ["pnpm test", "npm test"]     # Multiple: any variant
 /// command = "npm run *"                   # Wildcard: * matches one word
+/// command = "npm run *:test"              # Glob: * and ? inside a word
 /// ```
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(untagged)]
@@ -51,6 +60,13 @@ pub struct FilterConfig {
     /// The command this filter applies to (e.g. "git push").
     pub command: CommandPattern,
 
+    /// Opt-in regex matched against the command line (words joined by single
+    /// spaces, program by basename), anchored at its start. A match selects
+    /// this filter in addition to `command`, which still names the filter.
+    /// Example: `pattern_regex = '^npm run (test|lint)(:\S+)?'`.
+    #[serde(default)]
+    pub pattern_regex: Option<String>,
+
     /// Optional override command to run instead of the matched command prefix.
     ///
     /// Use `{args}` to interpolate the user-supplied arguments that appear
@@ -73,6 +89,12 @@ pub struct FilterConfig {
     /// from `command_args[..words_consumed]` (which *does* preserve the flags).
     pub run: Option<String>,
 
+    /// Output channel fed to the line pipeline (replace, skip/keep, dedup,
+    /// sections, chunks, JSON/parse) and to `{output}`. `match_output`
+    /// always sees the combined output.
+    #[serde(default)]
+    pub source: Stream,
+
     /// Patterns for lines to skip (applied before section parsing).
     #[serde(default, alias = "strip_lines_matching")]
     pub skip: Vec<String>,
@@ -81,6 +103,14 @@ pub struct FilterConfig {
     #[serde(default, alias = "keep_lines_matching")]
     pub keep: Vec<String>,
 
+    /// Lines to retain before each `keep` match (like `grep -B`).
+    #[serde(default)]
+    pub keep_context_before: usize,
+
+    /// Lines to retain after each `keep` match (like `grep -A`).
+    #[serde(default)]
+    pub keep_context_after: usize,
+
     /// Pipeline steps to run before filtering.
     #[serde(default)]
     pub step: Vec<Step>,
@@ -102,6 +132,11 @@ pub struct FilterConfig {
     /// Branch taken when the command exits non-zero.
     pub on_failure: Option<OutputBranch>,
 
+    /// Branches for specific exit codes, checked before `on_success` /
+    /// `on_failure`. The first entry whose `codes` contains the exit code wins.
+    #[serde(default)]
+    pub on_exit: Vec<ExitBranch>,
+
     /// Structured parsing rules (branch line, file grouping).
     pub parse: Option<ParseConfig>,
 
@@ -120,6 +155,10 @@ pub struct FilterConfig {
     #[serde(default)]
     pub replace: Vec<ReplaceRule>,
 
+    /// Block-folding rules (stack traces), applied after replace.
+    #[serde(default)]
+    pub collapse: Vec<CollapseRule>,
+
     /// Collapse consecutive identical lines (or within a sliding window).
     #[serde(default)]
     pub dedup: bool,
@@ -127,6 +166,33 @@ pub struct FilterConfig {
     /// Window size for dedup (default: consecutive only).
     pub dedup_window: Option<usize>,
 
+    /// Append ` (xN)` to lines that dedup collapsed, where N is how many
+    /// times the line occurred.
+    #[serde(default)]
+    pub dedup_annotate: bool,
+
+    /// Replay `\r` overwrites and cursor-control sequences (progress bars)
+    /// so only the final visible text of each line remains, before replace
+    /// and skip/keep.
+    #[serde(default)]
+    pub collapse_cr: bool,
+
+    /// Make absolute paths under the project root relative and shorten deep
+    /// build-artifact paths (`target/…`, `node_modules/…`), before replace.
+    #[serde(default)]
+    pub shorten_paths: bool,
+
+    /// Mask common secrets (cloud keys, tokens, `Authorization:` headers,
+    /// `password=` assignments) in the output and in recorded history.
+    /// On by default.
+    #[serde(default = "default_true")]
+    pub redact_secrets: bool,
+
+    /// Extra regexes to mask with `[REDACTED]`, on top of the built-in
+    /// secret patterns. A leading named group `keep` is preserved.
+    #[serde(default)]
+    pub redact: Vec<String>,
+
     /// Strip ANSI escape sequences before skip/keep pattern matching.
     #[serde(default)]
     pub strip_ansi: bool,
@@ -147,6 +213,10 @@ pub struct FilterConfig {
     #[serde(default)]
     pub lua_script: Option<ScriptConfig>,
 
+    /// Optional WebAssembly plugin escape hatch: path to a `.wasm` module.
+    #[serde(default)]
+    pub wasm_module: Option<String>,
+
     /// Chunk processing: split output into repeating structural blocks.
     #[serde(default)]
     pub chunk: Vec<ChunkConfig>,
@@ -171,12 +241,61 @@ pub struct FilterConfig {
     #[serde(default)]
     pub show_history_hint: bool,
 
+    /// When true, `tokf run` compares the filtered output against the same
+    /// filter applied to the previous run of this command (from history) and
+    /// shows only new or changed lines plus an `unchanged: N lines` summary.
+    #[serde(default)]
+    pub diff_previous: bool,
+
+    /// Directory `tokf run` saves the unfiltered output of each run to, one
+    /// file per run, relative to the current directory. `--tee-raw <path>`
+    /// takes precedence.
+    pub tee_raw_dir: Option<String>,
+
     /// When true, prepend a directory of shim scripts to `PATH` before spawning
     /// the command. Each shim redirects through `tokf -c`, so commands invoked
     /// by sub-processes (e.g. git hooks) are automatically filtered.
     #[serde(default)]
     pub inject_path: bool,
 
+    /// When true, run the command attached to a pseudo-terminal instead of
+    /// pipes, for tools that change their output or refuse to run without a
+    /// TTY. A terminal has one output stream, so everything is captured as
+    /// stdout and stderr is empty. Falls back to pipes where unsupported.
+    #[serde(default)]
+    pub pty: bool,
+
+    /// Kill the command, and everything it spawned, after this many seconds.
+    /// The output captured so far is still filtered, under a
+    /// `(timed out after Ns)` banner, and the exit code is 124.
+    pub timeout_secs: Option<u64>,
+
+    /// Environment variables set for the command, e.g.
+    /// `{ CARGO_TERM_COLOR = "never", GIT_PAGER = "cat" }`, to stop color and
+    /// pager noise at the source. The shim variables of `inject_path` win.
+    #[serde(default)]
+    pub env: BTreeMap<String, String>,
+
+    /// Directory to run the command in, relative to the current directory.
+    pub cwd: Option<String>,
+
+    /// Stdin for the command. `null` or `closed` keep a command that
+    /// unexpectedly prompts from hanging; `tokf run --stdin` overrides it.
+    #[serde(default)]
+    pub stdin: StdinMode,
+
+    /// Re-run a known-flaky command (network fetches, registry pushes) when it
+    /// fails, before the failure branch renders. The filter sees the last
+    /// attempt's output and `{attempts}`.
+    pub retry: Option<RetryConfig>,
+
+    /// Whether `tokf run` exits 0 and prints `Error: Exit code N` when the
+    /// command fails. `false` propagates the command's own exit code, for
+    /// commands such as deploy scripts whose callers must see the failure.
+    /// Unset, the project's `[run] mask_exit_code` decides (default `true`).
+    /// `--no-mask-exit-code` always wins.
+    pub mask_exit_code: Option<bool>,
+
     /// Argument prefixes that trigger passthrough mode (skip filter entirely).
     ///
     /// When any element in the user's remaining args starts with any prefix in
@@ -185,10 +304,34 @@ pub struct FilterConfig {
     #[serde(default)]
     pub passthrough_args: Vec<String>,
 
+    /// Command patterns carved out of `command`: a command that matches one
+    /// runs unfiltered, even if another filter would also match it.
+    ///
+    /// Words are matched like `command` patterns, except that flag words
+    /// (`--dry-run`) may appear anywhere after the program, so
+    /// `"git push --dry-run"` also excludes `git push origin main --dry-run`.
+    #[serde(default)]
+    pub exclude: Vec<String>,
+
+    /// Files `tokf watch` re-runs the command on, as globs relative to the
+    /// current directory: `"*.rs"` matches by file name, `"src/**/*.ts"` by
+    /// path. Empty means any file.
+    #[serde(default)]
+    pub watch: Vec<String>,
+
     /// Human-readable description of what this filter does.
     /// Used in `tokf ls`, search results, and publishing metadata.
     pub description: Option<String>,
 
+    /// Free-form keywords for registry search, e.g. `["rust", "build"]`.
+    /// Shown by `tokf ls --verbose` and matched exactly by `tokf search`.
+    #[serde(default)]
+    pub tags: Vec<String>,
+
+    /// Version of this filter, e.g. `"1.2.0"`, shown by `tokf ls --verbose`
+    /// and published with the filter. Informational only.
+    pub version: Option<String>,
+
     /// Maximum character width for output lines. Lines longer than this are
     /// truncated with a trailing `…` (within the budget). Applied as a final
     /// post-processing step after all other pipeline stages.
@@ -212,6 +355,11 @@ pub struct FilterConfig {
     /// other line-limiting stages (head, tail, skip, etc.). Lines beyond
     /// this limit are silently dropped from the end.
     pub max_lines: Option<usize>,
+
+    /// Estimated token ceiling for the final output, applied after
+    /// `max_lines`. Oversized output keeps whole lines from the head and tail
+    /// and replaces the middle with an elision marker.
+    pub max_tokens: Option<usize>,
 }
 
 impl FilterConfig {
@@ -257,6 +405,9 @@ pub struct ExtractRule {
 ///
 /// At least one of `contains` (literal substring) or `pattern` (regex) must be
 /// set. When both are present, `contains` is tried first.
+///
+/// When the rule matches via `pattern`, its capture groups are exposed to the
+/// `output` template as `{1}`, `{2}`, … and named groups as `{name}`.
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(deny_unknown_fields)]
 pub struct MatchOutputRule {
@@ -266,6 +417,8 @@ pub struct MatchOutputRule {
 
     /// Regex pattern to match against the combined output (RTK-compatible).
     /// Used when `contains` is not set, or as a secondary matcher.
+    /// `matches` is accepted as an alias.
+    #[serde(alias = "matches")]
     pub pattern: Option<String>,
 
     /// Output to emit if the match succeeds.
@@ -314,6 +467,26 @@ pub struct Section {
 
     /// Variable name for the collected lines/blocks.
     pub collect_as: Option<String>,
+
+    /// Output channel this section reads. Defaults to the filter's `source`.
+    #[serde(default)]
+    pub source: Option<Stream>,
+
+    /// Child sections, only active while this section is open. They collect
+    /// afresh each time this section is entered (`[[section.children]]`).
+    #[serde(default)]
+    pub children: Vec<Self>,
+}
+
+/// A command output channel.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Stream {
+    /// stdout and stderr interleaved in arrival order.
+    #[default]
+    Combined,
+    Stdout,
+    Stderr,
 }
 
 /// Output branch for success/failure exit codes.
@@ -343,6 +516,17 @@ pub struct OutputBranch {
     pub extract: Option<ExtractRule>,
 }
 
+/// Output branch selected by specific exit codes (`[[on_exit]]`).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExitBranch {
+    /// Exit codes this branch handles.
+    pub codes: Vec<i32>,
+
+    /// The branch itself; same fields as `[on_success]` / `[on_failure]`.
+    #[serde(flatten)]
+    pub branch: OutputBranch,
+}
+
 /// Aggregates values from a collected section using regex extraction.
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct AggregateRule {
@@ -359,116 +543,10 @@ pub struct AggregateRule {
     pub count_as: Option<String>,
 }
 
-/// Configuration for splitting output into repeating structural blocks.
-///
-/// Chunks split output at delimiter lines, extract structured data from
-/// each block, and collect the results as a structured collection for
-/// template rendering.
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
-pub struct ChunkConfig {
-    /// Regex that marks the start of each chunk.
-    pub split_on: String,
-
-    /// Whether the splitting line is included in the chunk (default: true).
-    #[serde(default = "default_true")]
-    pub include_split_line: bool,
-
-    /// Variable name for the structured collection in templates.
-    pub collect_as: String,
-
-    /// Extract a named field from the split (header) line.
-    pub extract: Option<ChunkExtract>,
-
-    /// Per-chunk body line extractions (first match per rule wins).
-    #[serde(default)]
-    pub body_extract: Vec<ChunkBodyExtract>,
-
-    /// Per-chunk aggregate rules (run within each chunk's lines).
-    #[serde(default)]
-    pub aggregate: Vec<ChunkAggregateRule>,
-
-    /// Field name to group chunks by (merging numeric fields).
-    pub group_by: Option<String>,
-
-    /// When set alongside `group_by`, preserve each group's original items
-    /// as a nested collection under this name instead of discarding them.
-    pub children_as: Option<String>,
-}
-
-const fn default_true() -> bool {
+pub(super) const fn default_true() -> bool {
     true
 }
 
-/// Per-chunk aggregation rule. Unlike branch-level `AggregateRule`, this does
-/// not need a `from` field because it always operates on the chunk's own lines.
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
-pub struct ChunkAggregateRule {
-    /// Regex pattern to extract numeric values.
-    pub pattern: String,
-
-    /// Name for the summed value.
-    pub sum: Option<String>,
-
-    /// Name for the count of matching entries.
-    pub count_as: Option<String>,
-}
-
-/// Extract a named field from a line within a chunk (header or body).
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
-pub struct ChunkFieldExtract {
-    /// Regex pattern with a capture group.
-    pub pattern: String,
-
-    /// Variable name for the captured value.
-    #[serde(rename = "as")]
-    pub as_name: String,
-
-    /// When true, if this field is not extracted from a chunk, it inherits
-    /// the value from the most recent chunk that did extract it.
-    #[serde(default)]
-    pub carry_forward: bool,
-}
-
-/// Backward-compatible alias for header extraction.
-pub type ChunkExtract = ChunkFieldExtract;
-
-/// Backward-compatible alias for body-line extraction.
-pub type ChunkBodyExtract = ChunkFieldExtract;
-
-/// Structured parsing configuration for status-like outputs.
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
-pub struct ParseConfig {
-    /// Rule for extracting the branch name from the first line.
-    pub branch: Option<LineExtract>,
-
-    /// Rule for grouping file entries by status code.
-    pub group: Option<GroupConfig>,
-}
-
-/// Extracts a value from a specific line number.
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
-pub struct LineExtract {
-    /// 1-based line number to extract from.
-    pub line: usize,
-
-    /// Regex pattern with capture groups.
-    pub pattern: String,
-
-    /// Output template using `{1}`, `{2}`, etc. for captures.
-    pub output: String,
-}
-
-/// Groups lines by a key pattern and maps keys to human labels.
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
-pub struct GroupConfig {
-    /// Rule for extracting the group key from each line.
-    pub key: ExtractRule,
-
-    /// Map from raw key to human-readable label.
-    #[serde(default)]
-    pub labels: BTreeMap<String, String>,
-}
-
 /// Output formatting configuration for the final rendered result.
 #[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
 pub struct OutputConfig {
@@ -511,6 +589,22 @@ pub struct ReplaceRule {
     pub replace_all: bool,
 }
 
+/// A `[[collapse]]` rule: fold each run of consecutive lines matching
+/// `pattern` into one summary line.
+///
+/// Built for stack traces, where every frame matches something like
+/// `^\s+at ` and skipping them line by line loses how deep the trace was.
+/// The summary template supports `{count}` (lines folded), `{first}` and
+/// `{last}` (the first and last folded lines, trimmed).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CollapseRule {
+    pub pattern: String,
+    /// Summary template. Default: `… {count} frames omitted (deepest: {first})`.
+    pub output: Option<String>,
+    /// Runs shorter than this are left untouched. Default: 2.
+    pub min_lines: Option<usize>,
+}
+
 /// Supported scripting languages for the `[lua_script]` escape hatch.
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
@@ -540,8 +634,15 @@ pub struct VariantDetect {
     pub output_pattern: Option<String>,
     /// Regex pattern to match against remaining command-line arguments
     /// (pre-execution detection, Phase A.5). The pattern is tested against
-    /// the remaining args joined with spaces.
+    /// the remaining args joined with spaces. `when_args` is accepted as an alias.
+    #[serde(alias = "when_args")]
     pub args_pattern: Option<String>,
+    /// Environment variables that must all equal the given values, e.g.
+    /// `{ CI = "true" }` (pre-execution detection). On its own it selects the
+    /// variant in Phase A; alongside other criteria it is an extra condition
+    /// they all require. `when_env` is accepted as an alias.
+    #[serde(default, alias = "when_env")]
+    pub env: BTreeMap<String, String>,
 }
 
 /// A variant entry that delegates to a specialized child filter.
@@ -590,106 +691,3 @@ pub struct JsonFieldExtract {
     #[serde(rename = "as")]
     pub as_name: String,
 }
-
-#[allow(clippy::unwrap_used)]
-#[cfg(test)]
-mod tests {
-    use super::*;
-
-    fn parse(toml: &str) -> FilterConfig {
-        toml::from_str(toml).unwrap()
-    }
-
-    fn s(v: &str) -> String {
-        v.to_string()
-    }
-
-    #[test]
-    fn passthrough_empty_list_never_triggers() {
-        let cfg = parse(r#"command = "gh pr checks *""#);
-        assert!(!cfg.should_passthrough(&[s("--watch")]));
-    }
-
-    #[test]
-    fn passthrough_exact_match() {
-        let cfg = parse(
-            r#"
-command = "gh pr checks *"
-passthrough_args = ["--watch", "--web", "-w"]
-"#,
-        );
-        assert!(cfg.should_passthrough(&[s("142"), s("--watch")]));
-    }
-
-    #[test]
-    fn passthrough_prefix_match() {
-        let cfg = parse(
-            r#"
-command = "docker ps"
-passthrough_args = ["--format"]
-"#,
-        );
-        assert!(cfg.should_passthrough(&[s("--format=table")]));
-    }
-
-    #[test]
-    fn passthrough_short_flag_does_not_match_long() {
-        let cfg = parse(
-            r#"
-command = "gh pr checks *"
-passthrough_args = ["--watch"]
-"#,
-        );
-        assert!(!cfg.should_passthrough(&[s("-w")]));
-    }
-
-    #[test]
-    fn passthrough_no_match_returns_false() {
-        let cfg = parse(
-            r#"
-command = "gh pr checks *"
-passthrough_args = ["--watch", "--web"]
-"#,
-        );
-        assert!(!cfg.should_passthrough(&[s("142"), s("--json")]));
-    }
-
-    #[test]
-    fn passthrough_empty_args_never_triggers() {
-        let cfg = parse(
-            r#"
-command = "gh pr checks *"
-passthrough_args = ["--watch"]
-"#,
-        );
-        assert!(!cfg.should_passthrough(&[]));
-    }
-
-    #[test]
-    fn passthrough_args_deserializes_from_toml() {
-        let cfg = parse(
-            r#"
-command = "gh pr checks *"
-passthrough_args = ["--watch", "--web", "-w"]
-"#,
-        );
-        assert_eq!(cfg.passthrough_args, vec!["--watch", "--web", "-w"]);
-    }
-
-    #[test]
-    fn passthrough_args_defaults_to_empty() {
-        let cfg = parse(r#"command = "git push""#);
-        assert!(cfg.passthrough_args.is_empty());
-    }
-
-    #[test]
-    fn passthrough_empty_string_prefix_ignored() {
-        let cfg = parse(
-            r#"
-command = "test"
-passthrough_args = [""]
-"#,
-        );
-        assert!(!cfg.should_passthrough(&[s("--anything")]));
-    }
-}
@@ -1,7 +1,7 @@
 #![allow(clippy::unwrap_used)]
 
 use super::tree::TreeStyle;
-use super::types::{FilterConfig, MatchOutputRule};
+use super::types::{FilterConfig, MatchOutputRule, ParseFormat};
 
 #[test]
 fn match_output_validate_accepts_contains() {
@@ -128,3 +128,164 @@ this_field_does_not_exist = true
         "deny_unknown_fields should reject unknown keys"
     );
 }
+
+#[test]
+fn parse_config_defaults_to_text_format() {
+    let toml = r#"
+command = "git status"
+[parse]
+branch = { line = 1, pattern = '## (\S+)', output = "{1}" }
+"#;
+    let cfg: FilterConfig = toml::from_str(toml).unwrap();
+    let parse = cfg.parse.unwrap();
+    assert_eq!(parse.format, ParseFormat::Text);
+    assert!(parse.extract.is_empty());
+}
+
+#[test]
+fn parse_config_jsonl_with_extract_rules() {
+    let toml = r#"
+command = "cargo build --message-format=json"
+[parse]
+format = "jsonl"
+[[parse.extract]]
+path = "$[*].message.rendered"
+as = "messages"
+"#;
+    let cfg: FilterConfig = toml::from_str(toml).unwrap();
+    let parse = cfg.parse.unwrap();
+    assert_eq!(parse.format, ParseFormat::Jsonl);
+    assert_eq!(parse.extract.len(), 1);
+    assert_eq!(parse.extract[0].as_name, "messages");
+}
+
+// --- passthrough_args ---
+
+fn parse(toml: &str) -> FilterConfig {
+    toml::from_str(toml).unwrap()
+}
+
+fn s(v: &str) -> String {
+    v.to_string()
+}
+
+#[test]
+fn passthrough_empty_list_never_triggers() {
+    let cfg = parse(r#"command = "gh pr checks *""#);
+    assert!(!cfg.should_passthrough(&[s("--watch")]));
+}
+
+#[test]
+fn passthrough_exact_match() {
+    let cfg = parse(
+        r#"
+command = "gh pr checks *"
+passthrough_args = ["--watch", "--web", "-w"]
+"#,
+    );
+    assert!(cfg.should_passthrough(&[s("142"), s("--watch")]));
+}
+
+#[test]
+fn passthrough_prefix_match() {
+    let cfg = parse(
+        r#"
+command = "docker ps"
+passthrough_args = ["--format"]
+"#,
+    );
+    assert!(cfg.should_passthrough(&[s("--format=table")]));
+}
+
+#[test]
+fn passthrough_short_flag_does_not_match_long() {
+    let cfg = parse(
+        r#"
+command = "gh pr checks *"
+passthrough_args = ["--watch"]
+"#,
+    );
+    assert!(!cfg.should_passthrough(&[s("-w")]));
+}
+
+#[test]
+fn passthrough_no_match_returns_false() {
+    let cfg = parse(
+        r#"
+command = "gh pr checks *"
+passthrough_args = ["--watch", "--web"]
+"#,
+    );
+    assert!(!cfg.should_passthrough(&[s("142"), s("--json")]));
+}
+
+#[test]
+fn passthrough_empty_args_never_triggers() {
+    let cfg = parse(
+        r#"
+command = "gh pr checks *"
+passthrough_args = ["--watch"]
+"#,
+    );
+    assert!(!cfg.should_passthrough(&[]));
+}
+
+#[test]
+fn passthrough_args_deserializes_from_toml() {
+    let cfg = parse(
+        r#"
+command = "gh pr checks *"
+passthrough_args = ["--watch", "--web", "-w"]
+"#,
+    );
+    assert_eq!(cfg.passthrough_args, vec!["--watch", "--web", "-w"]);
+}
+
+#[test]
+fn passthrough_args_defaults_to_empty() {
+    let cfg = parse(r#"command = "git push""#);
+    assert!(cfg.passthrough_args.is_empty());
+}
+
+#[test]
+fn passthrough_empty_string_prefix_ignored() {
+    let cfg = parse(
+        r#"
+command = "test"
+passthrough_args = [""]
+"#,
+    );
+    assert!(!cfg.should_passthrough(&[s("--anything")]));
+}
+
+// --- retry ---
+
+#[test]
+fn retry_deserializes_with_defaults() {
+    let cfg = parse(
+        r#"
+command = "flaky"
+retry = { on_exit = [1] }
+"#,
+    );
+    let retry = cfg.retry.unwrap();
+    assert_eq!(retry.attempts, 3);
+    assert_eq!(retry.backoff_ms, 0);
+    assert!(retry.retries(1));
+    assert!(!retry.retries(2));
+    assert!(!retry.retries(0));
+}
+
+#[test]
+fn retry_without_exit_codes_retries_any_failure() {
+    let cfg = parse(
+        r#"
+command = "flaky"
+retry = { attempts = 2 }
+"#,
+    );
+    let retry = cfg.retry.unwrap();
+    assert!(retry.retries(1));
+    assert!(retry.retries(137));
+    assert!(!retry.retries(0));
+}
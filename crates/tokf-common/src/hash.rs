@@ -134,8 +134,8 @@ M = "modified"
     #[test]
     fn explicit_defaults_same_as_implicit() {
         // A minimal config relies on serde defaults for all optional fields.
-        // A config that explicitly sets every default value to its zero/empty
-        // equivalent must produce the same hash — proving that #[serde(default)]
+        // A config that explicitly sets every default value (mostly its
+        // zero/empty equivalent) must produce the same hash — proving that #[serde(default)]
         // fields are handled consistently.
         let implicit = parse(r#"command = "git push""#);
         let explicit = parse(
@@ -147,13 +147,25 @@ step = []
 match_output = []
 section = []
 replace = []
+collapse = []
 variant = []
+exclude = []
+watch = []
+tags = []
 dedup = false
+dedup_annotate = false
+collapse_cr = false
+shorten_paths = false
+redact_secrets = true
+redact = []
 strip_ansi = false
 trim_lines = false
 strip_empty_lines = false
 collapse_empty_lines = false
 inject_path = false
+pty = false
+stdin = "inherit"
+env = {}
 "#,
         );
         assert_eq!(
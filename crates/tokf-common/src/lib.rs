@@ -5,6 +5,8 @@ pub mod hash;
 pub mod multipart;
 pub mod richness;
 pub mod safety;
+#[cfg(feature = "signing")]
+pub mod signing;
 pub mod test_case;
 pub mod tokens;
 pub mod tracking;
@@ -142,6 +142,18 @@ impl SafetyCheck for HiddenUnicodeCheck {
                 });
             }
         }
+        for pattern in &config.exclude {
+            for c in find_hidden_unicode(pattern) {
+                warnings.push(SafetyWarning {
+                    kind: WarningKind::HiddenUnicode,
+                    message: format!(
+                        "Exclude pattern contains hidden Unicode character U+{:04X}",
+                        c as u32
+                    ),
+                    detail: Some(format!("U+{:04X}", c as u32)),
+                });
+            }
+        }
         for prefix in &config.passthrough_args {
             for c in find_hidden_unicode(prefix) {
                 warnings.push(SafetyWarning {
@@ -206,6 +218,24 @@ const SHELL_INJECTION_PATTERNS: &[&str] = &[
     "<",   // input redirection
 ];
 
+/// Environment variables that change which code a command runs (loader
+/// preloads, interpreter startup files, program lookup). A filter's `env`
+/// setting one of them can execute arbitrary code.
+const HIJACKING_ENV_VARS: &[&str] = &[
+    "PATH",
+    "LD_PRELOAD",
+    "LD_LIBRARY_PATH",
+    "DYLD_INSERT_LIBRARIES",
+    "DYLD_LIBRARY_PATH",
+    "BASH_ENV",
+    "ENV",
+    "PROMPT_COMMAND",
+    "NODE_OPTIONS",
+    "PYTHONSTARTUP",
+    "PERL5OPT",
+    "RUBYOPT",
+];
+
 /// Known-safe rewrite templates. The entire replacement must match one of these
 /// patterns (after stripping surrounding whitespace). Only exact matches are
 /// considered safe — the allowlist never suppresses individual metacharacter
@@ -222,7 +252,8 @@ fn check_shell_string(s: &str) -> Vec<&'static str> {
 }
 
 /// Detects shell metacharacters in rewrite replacement strings and
-/// shell-executed config fields (`run`, `step[].run`).
+/// shell-executed config fields (`run`, `step[].run`, `env` values), and
+/// `env` entries that hijack execution.
 pub(super) struct ShellInjectionCheck;
 
 impl SafetyCheck for ShellInjectionCheck {
@@ -250,6 +281,22 @@ impl SafetyCheck for ShellInjectionCheck {
                 });
             }
         }
+        for (key, value) in &config.env {
+            if HIJACKING_ENV_VARS.contains(&key.as_str()) {
+                warnings.push(SafetyWarning {
+                    kind: WarningKind::ShellInjection,
+                    message: format!("`env` sets `{key}`, which can change what the command runs"),
+                    detail: Some(key.clone()),
+                });
+            }
+            for w in check_shell_string(value) {
+                warnings.push(SafetyWarning {
+                    kind: WarningKind::ShellInjection,
+                    message: format!("`env.{key}` contains shell metacharacter `{w}`"),
+                    detail: Some(w.to_string()),
+                });
+            }
+        }
         warnings
     }
 
@@ -292,6 +339,15 @@ pub(super) fn extract_template_texts(config: &FilterConfig) -> Vec<(&'static str
         t.push(("on_failure.output", o.as_str()));
     }
 
+    for b in &config.on_exit {
+        if let Some(ref o) = b.branch.output {
+            t.push(("on_exit.output", o.as_str()));
+        }
+        if let Some(ref e) = b.branch.extract {
+            t.push(("on_exit.extract.output", e.output.as_str()));
+        }
+    }
+
     // Branch-level extract outputs
     if let Some(ref b) = config.on_success
         && let Some(ref e) = b.extract
@@ -319,6 +375,13 @@ pub(super) fn extract_template_texts(config: &FilterConfig) -> Vec<(&'static str
         t.push(("replace.output", r.output.as_str()));
     }
 
+    // Collapse summaries
+    for c in &config.collapse {
+        if let Some(ref o) = c.output {
+            t.push(("collapse.output", o.as_str()));
+        }
+    }
+
     // Output formatting
     if let Some(ref o) = config.output {
         if let Some(ref f) = o.format {
@@ -0,0 +1,199 @@
+//! Ed25519 signatures over published filter TOML.
+//!
+//! An author signs the exact filter bytes they upload; the registry stores
+//! the signature with the public key it was made with, and `tokf install`
+//! checks it against the bytes it downloads. The signed message is the TOML
+//! behind a fixed context prefix, so a filter signature can never be replayed
+//! as a signature over anything else.
+//!
+//! Keys, signatures and the stored private key (PKCS#8) are lowercase hex.
+
+use sha2::{Digest, Sha256};
+
+use ring::rand::SystemRandom;
+use ring::signature::{ED25519, Ed25519KeyPair, KeyPair as _, UnparsedPublicKey};
+
+/// Prefixed to the filter bytes before signing.
+const CONTEXT: &[u8] = b"tokf-filter-signature-v1\0";
+
+/// Length of an Ed25519 public key in bytes.
+pub const PUBLIC_KEY_LEN: usize = 32;
+
+/// Why a signature did not check out.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SignatureError {
+    /// The key is not valid hex of the right shape.
+    MalformedKey,
+    /// The signature is not hex.
+    MalformedSignature,
+    /// The signature was not made over these bytes with this key.
+    Mismatch,
+}
+
+impl std::fmt::Display for SignatureError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::MalformedKey => "malformed key",
+            Self::MalformedSignature => "malformed signature",
+            Self::Mismatch => "signature does not match the filter content",
+        })
+    }
+}
+
+impl std::error::Error for SignatureError {}
+
+/// An author's signing key.
+pub struct SigningKey(Ed25519KeyPair);
+
+impl SigningKey {
+    /// Generate a fresh key, returning it with its hex PKCS#8 encoding for
+    /// storage.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the system random source fails.
+    pub fn generate() -> Result<(Self, String), ring::error::Unspecified> {
+        let pkcs8 = Ed25519KeyPair::generate_pkcs8(&SystemRandom::new())?;
+        let key =
+            Ed25519KeyPair::from_pkcs8(pkcs8.as_ref()).map_err(|_| ring::error::Unspecified)?;
+        Ok((Self(key), hex::encode(pkcs8.as_ref())))
+    }
+
+    /// Load a key from its hex PKCS#8 encoding.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SignatureError::MalformedKey`] if the input is not a hex
+    /// Ed25519 PKCS#8 document.
+    pub fn from_pkcs8_hex(pkcs8_hex: &str) -> Result<Self, SignatureError> {
+        let pkcs8 = hex::decode(pkcs8_hex.trim()).map_err(|_| SignatureError::MalformedKey)?;
+        Ed25519KeyPair::from_pkcs8(&pkcs8)
+            .map(Self)
+            .map_err(|_| SignatureError::MalformedKey)
+    }
+
+    /// The public half, as hex.
+    pub fn public_key_hex(&self) -> String {
+        hex::encode(self.0.public_key().as_ref())
+    }
+
+    /// Sign `filter_toml`, returning the signature as hex.
+    pub fn sign(&self, filter_toml: &[u8]) -> String {
+        hex::encode(self.0.sign(&message(filter_toml)).as_ref())
+    }
+}
+
+/// Check that `signature_hex` was made over `filter_toml` with the key
+/// `public_key_hex`.
+///
+/// # Errors
+///
+/// Returns a [`SignatureError`] if the key or signature is malformed or the
+/// signature does not match.
+pub fn verify(
+    public_key_hex: &str,
+    signature_hex: &str,
+    filter_toml: &[u8],
+) -> Result<(), SignatureError> {
+    let key = parse_public_key(public_key_hex)?;
+    let signature = hex::decode(signature_hex).map_err(|_| SignatureError::MalformedSignature)?;
+    UnparsedPublicKey::new(&ED25519, key)
+        .verify(&message(filter_toml), &signature)
+        .map_err(|_| SignatureError::Mismatch)
+}
+
+/// Decode a hex public key, checking its length.
+///
+/// # Errors
+///
+/// Returns [`SignatureError::MalformedKey`] unless it is 32 bytes of hex.
+pub fn parse_public_key(public_key_hex: &str) -> Result<Vec<u8>, SignatureError> {
+    let key = hex::decode(public_key_hex).map_err(|_| SignatureError::MalformedKey)?;
+    if key.len() != PUBLIC_KEY_LEN {
+        return Err(SignatureError::MalformedKey);
+    }
+    Ok(key)
+}
+
+/// A short, stable name for a public key: the first 16 hex digits of its
+/// SHA-256. For display only.
+pub fn fingerprint(public_key_hex: &str) -> String {
+    let digest = Sha256::digest(public_key_hex.to_ascii_lowercase().as_bytes());
+    hex::encode(&digest[..8])
+}
+
+fn message(filter_toml: &[u8]) -> Vec<u8> {
+    [CONTEXT, filter_toml].concat()
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    const FILTER: &[u8] = b"command = \"git push\"\n";
+
+    #[test]
+    fn signature_roundtrips_through_pkcs8() {
+        let (key, pkcs8) = SigningKey::generate().unwrap();
+        let reloaded = SigningKey::from_pkcs8_hex(&pkcs8).unwrap();
+        assert_eq!(key.public_key_hex(), reloaded.public_key_hex());
+        assert_eq!(key.public_key_hex().len(), PUBLIC_KEY_LEN * 2);
+
+        let signature = reloaded.sign(FILTER);
+        assert_eq!(verify(&key.public_key_hex(), &signature, FILTER), Ok(()));
+    }
+
+    #[test]
+    fn tampered_content_or_wrong_key_is_a_mismatch() {
+        let (key, _) = SigningKey::generate().unwrap();
+        let (other, _) = SigningKey::generate().unwrap();
+        let signature = key.sign(FILTER);
+        assert_eq!(
+            verify(&key.public_key_hex(), &signature, b"command = \"rm -rf\"\n"),
+            Err(SignatureError::Mismatch)
+        );
+        assert_eq!(
+            verify(&other.public_key_hex(), &signature, FILTER),
+            Err(SignatureError::Mismatch)
+        );
+    }
+
+    #[test]
+    fn signature_covers_the_context_prefix() {
+        // A raw Ed25519 signature over the bare bytes must not verify.
+        let (key, _) = SigningKey::generate().unwrap();
+        let bare = hex::encode(key.0.sign(FILTER).as_ref());
+        assert_eq!(
+            verify(&key.public_key_hex(), &bare, FILTER),
+            Err(SignatureError::Mismatch)
+        );
+    }
+
+    #[test]
+    fn malformed_inputs_are_reported_as_such() {
+        let (key, _) = SigningKey::generate().unwrap();
+        let signature = key.sign(FILTER);
+        assert_eq!(
+            verify("not hex", &signature, FILTER),
+            Err(SignatureError::MalformedKey)
+        );
+        assert_eq!(
+            verify("abcd", &signature, FILTER),
+            Err(SignatureError::MalformedKey)
+        );
+        assert_eq!(
+            verify(&key.public_key_hex(), "zz", FILTER),
+            Err(SignatureError::MalformedSignature)
+        );
+        assert!(SigningKey::from_pkcs8_hex("abcd").is_err());
+    }
+
+    #[test]
+    fn fingerprint_is_short_and_case_insensitive() {
+        let (key, _) = SigningKey::generate().unwrap();
+        let hex = key.public_key_hex();
+        assert_eq!(fingerprint(&hex).len(), 16);
+        assert_eq!(fingerprint(&hex), fingerprint(&hex.to_uppercase()));
+    }
+}
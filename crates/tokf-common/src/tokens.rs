@@ -13,6 +13,10 @@
 //! optional, off-by-default `tokenizer` feature (see
 //! `crates/tokf-cli/tests/calibration.rs`). cl100k is not Claude's tokenizer,
 //! so even that is an approximation — a calibration target, not truth.
+//!
+//! A build with that feature can also record real counts next to the
+//! estimates, for the model named in `[tokenizer]` (see
+//! [`tokenizer_for_model`]). The estimates stay the numbers tokf reports.
 
 /// Bytes per estimated token.
 ///
@@ -69,8 +73,8 @@ pub fn estimate_tokens(s: &str) -> usize {
 ///
 /// Truncates, matching the integer-division semantics this estimator has
 /// always had. `DIVISOR` is an `f64` so it can later be a non-integer; the
-/// single cast `#[allow]` lives here and nowhere else, so call sites stay
-/// free of casting lints.
+/// cast `#[allow]`s live here and in [`bytes_for_tokens`] and nowhere else,
+/// so call sites stay free of casting lints.
 #[allow(
     clippy::cast_precision_loss,
     clippy::cast_sign_loss,
@@ -80,11 +84,25 @@ pub fn estimate_tokens_from_bytes(bytes: usize) -> usize {
     (bytes as f64 / DIVISOR) as usize
 }
 
+/// Byte budget that estimates to at most `tokens` tokens.
+///
+/// The inverse of [`estimate_tokens_from_bytes`], for callers that cap output
+/// to a token budget (`max_tokens`). Truncates, so the result never
+/// overshoots the budget.
+#[allow(
+    clippy::cast_precision_loss,
+    clippy::cast_sign_loss,
+    clippy::cast_possible_truncation
+)]
+pub fn bytes_for_tokens(tokens: usize) -> usize {
+    (tokens as f64 * DIVISOR) as usize
+}
+
 /// A real cl100k tokenizer, for verifying and calibrating the estimator.
 ///
 /// Available only under the optional, off-by-default `tokenizer` feature.
-/// Nothing in the shipping runtime path may use this — it exists so we can
-/// measure how wrong [`ArithmeticTokenCounter`] is, and re-check that later.
+/// The default build never uses this — it exists so we can measure how wrong
+/// [`ArithmeticTokenCounter`] is, and re-check that later.
 #[cfg(feature = "tokenizer")]
 #[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
 pub struct Cl100kTokenCounter;
@@ -97,6 +115,38 @@ impl TokenCounter for Cl100kTokenCounter {
     }
 }
 
+/// A real o200k tokenizer, the encoding of the GPT-4o generation onwards.
+///
+/// Available only under the optional, off-by-default `tokenizer` feature.
+#[cfg(feature = "tokenizer")]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct O200kTokenCounter;
+
+#[cfg(feature = "tokenizer")]
+impl TokenCounter for O200kTokenCounter {
+    fn count(&self, text: &str) -> usize {
+        bpe_openai::o200k_base().count(text)
+    }
+}
+
+/// The encoding name and tokenizer to count `model`'s tokens with.
+///
+/// `model` is a model name (`gpt-4o`, `o3`, `gpt-4`) or an encoding name
+/// (`o200k`, `cl100k`). Models with no public tokenizer, Claude among them,
+/// get cl100k as the closest stand-in.
+#[cfg(feature = "tokenizer")]
+pub fn tokenizer_for_model(model: &str) -> (&'static str, &'static dyn TokenCounter) {
+    const O200K_PREFIXES: &[&str] = &[
+        "o200k", "gpt-4o", "gpt-4.1", "gpt-4.5", "gpt-5", "o1", "o3", "o4",
+    ];
+    let model = model.trim().to_ascii_lowercase();
+    if O200K_PREFIXES.iter().any(|p| model.starts_with(p)) {
+        ("o200k", &O200kTokenCounter)
+    } else {
+        ("cl100k", &Cl100kTokenCounter)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -149,6 +199,15 @@ mod tests {
         assert!(n > 0);
     }
 
+    #[test]
+    fn bytes_for_tokens_never_overshoots() {
+        for tokens in [0, 1, 7, 100, 4096] {
+            let bytes = bytes_for_tokens(tokens);
+            assert!(estimate_tokens_from_bytes(bytes) <= tokens);
+            assert!(estimate_tokens_from_bytes(bytes) + 1 >= tokens);
+        }
+    }
+
     #[test]
     fn counter_is_object_safe() {
         let c: &dyn TokenCounter = &ArithmeticTokenCounter;
@@ -165,6 +224,22 @@ mod tests {
         assert_eq!(c.count("héllo wörld"), 6);
     }
 
+    #[cfg(feature = "tokenizer")]
+    #[test]
+    fn models_pick_their_encoding() {
+        for model in ["gpt-4o-mini", "GPT-5", "o3", "o200k"] {
+            assert_eq!(tokenizer_for_model(model).0, "o200k", "{model}");
+        }
+        for model in ["gpt-4", "gpt-3.5-turbo", "claude-sonnet-4", "cl100k"] {
+            assert_eq!(tokenizer_for_model(model).0, "cl100k", "{model}");
+        }
+        let (_, counter) = tokenizer_for_model("gpt-4o");
+        assert_eq!(
+            counter.count("hello world"),
+            O200kTokenCounter.count("hello world")
+        );
+    }
+
     #[cfg(feature = "tokenizer")]
     #[test]
     fn cl100k_is_object_safe_too() {
@@ -19,6 +19,19 @@ pub struct TrackingEvent {
     /// event was recorded. Empty string means "unknown" (legacy events
     /// recorded before this column existed, or test fixtures).
     pub project: String,
+    /// Wall-clock run time of the command. `None` when not measured.
+    pub duration_ms: Option<i64>,
+    /// CPU time the command and its children spent in user and kernel mode.
+    pub user_cpu_ms: Option<i64>,
+    pub sys_cpu_ms: Option<i64>,
+    /// Peak resident set size of the command, in KiB.
+    pub max_rss_kb: Option<i64>,
+    /// Encoding of the real tokenizer counts below (`cl100k`, `o200k`).
+    /// `None` when no tokenizer counted this event.
+    pub tokenizer: Option<String>,
+    /// Input and output tokens as counted by `tokenizer`.
+    pub input_tokens_tok: Option<i64>,
+    pub output_tokens_tok: Option<i64>,
 }
 
 #[derive(serde::Serialize)]
@@ -61,3 +74,34 @@ pub struct FilterGain {
     pub avg_filter_time_ms: f64,
     pub raw_tokens: i64,
 }
+
+#[derive(Clone, serde::Serialize)]
+pub struct ProjectGain {
+    /// Project root the runs happened in; `(unknown)` for runs recorded
+    /// before the project was tracked.
+    pub project: String,
+    pub commands: i64,
+    pub input_tokens: i64,
+    pub output_tokens: i64,
+    pub tokens_saved: i64,
+    pub savings_pct: f64,
+    pub pipe_override_count: i64,
+    pub total_filter_time_ms: i64,
+    pub raw_tokens: i64,
+}
+
+#[derive(Clone, serde::Serialize)]
+pub struct CommandGain {
+    /// The first two words of the command, e.g. `git status`.
+    pub command: String,
+    pub commands: i64,
+    /// Runs no filter matched.
+    pub passthrough_runs: i64,
+    pub input_tokens: i64,
+    pub output_tokens: i64,
+    pub tokens_saved: i64,
+    pub savings_pct: f64,
+    pub pipe_override_count: i64,
+    pub total_filter_time_ms: i64,
+    pub raw_tokens: i64,
+}
@@ -120,6 +120,7 @@ mod tests {
             SectionData {
                 lines: items.into_iter().map(String::from).collect(),
                 blocks: Vec::new(),
+                scopes: Vec::new(),
             },
         );
         map
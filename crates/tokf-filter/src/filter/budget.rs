@@ -0,0 +1,117 @@
+use tokf_common::tokens::{bytes_for_tokens, estimate_tokens};
+
+use super::cleanup::truncate_line;
+
+/// Bytes held back from the budget for the elision marker line.
+const MARKER_RESERVE: usize = 32;
+
+/// Cap `output` to an estimated `max_tokens` tokens.
+///
+/// Output already within budget is returned unchanged. Otherwise the budget
+/// is split evenly between the head and the tail: whole lines are kept from
+/// each end and the middle is replaced by a `... (N lines omitted) ...`
+/// marker. When not even the first line fits, it is cut short with `…`.
+/// The result is a hard ceiling, not a target: it may come in well under
+/// budget when lines are long.
+pub fn cap_tokens(output: String, max_tokens: usize) -> String {
+    if estimate_tokens(&output) <= max_tokens {
+        return output;
+    }
+    let half = bytes_for_tokens(max_tokens).saturating_sub(MARKER_RESERVE) / 2;
+    let trailing = output.ends_with('\n');
+    let lines: Vec<&str> = output.lines().collect();
+
+    let mut kept: Vec<String> = Vec::new();
+    let mut shown = fit_lines(lines.iter(), half);
+    kept.extend(lines[..shown].iter().map(|l| (*l).to_string()));
+    if shown == 0
+        && let Some(first) = lines.first()
+    {
+        kept.push(truncate_line(first, half).into_owned());
+        shown = 1;
+    }
+    let tail = fit_lines(lines[shown..].iter().rev(), half);
+    let omitted = lines.len() - shown - tail;
+    if omitted > 0 {
+        kept.push(format!("... ({omitted} lines omitted) ..."));
+    }
+    kept.extend(lines[lines.len() - tail..].iter().map(|l| (*l).to_string()));
+
+    let mut out = kept.join("\n");
+    if trailing {
+        out.push('\n');
+    }
+    out
+}
+
+/// Number of leading lines from `lines` whose combined size (with newlines)
+/// fits in `budget` bytes.
+fn fit_lines<'a>(lines: impl Iterator<Item = &'a &'a str>, budget: usize) -> usize {
+    let mut used = 0;
+    lines
+        .take_while(|line| {
+            used += line.len() + 1;
+            used <= budget
+        })
+        .count()
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+
+    fn numbered(n: usize) -> String {
+        (1..=n)
+            .map(|i| format!("line {i:03} with some padding text"))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    #[test]
+    fn within_budget_unchanged() {
+        let text = "short\noutput".to_string();
+        assert_eq!(cap_tokens(text.clone(), 100), text);
+    }
+
+    #[test]
+    fn keeps_head_and_tail_with_marker() {
+        let out = cap_tokens(numbered(200), 100);
+        let lines: Vec<&str> = out.lines().collect();
+        assert!(lines[0].starts_with("line 001"));
+        assert!(lines.last().unwrap().starts_with("line 200"));
+        let marker = lines.iter().find(|l| l.starts_with("... (")).unwrap();
+        assert!(marker.ends_with("lines omitted) ..."));
+        assert!(estimate_tokens(&out) <= 100, "over budget: {out}");
+    }
+
+    #[test]
+    fn omitted_count_matches_dropped_lines() {
+        let out = cap_tokens(numbered(50), 40);
+        let kept = out.lines().count() - 1;
+        let marker = out.lines().find(|l| l.starts_with("... (")).unwrap();
+        let n: usize = marker
+            .trim_start_matches("... (")
+            .split(' ')
+            .next()
+            .unwrap()
+            .parse()
+            .unwrap();
+        assert_eq!(kept + n, 50);
+    }
+
+    #[test]
+    fn single_huge_line_is_cut() {
+        let out = cap_tokens("x".repeat(10_000), 50);
+        assert!(out.starts_with("xxx"));
+        assert!(out.contains('\u{2026}'));
+        assert!(!out.contains("omitted"));
+        assert!(estimate_tokens(&out) <= 50);
+    }
+
+    #[test]
+    fn preserves_trailing_newline() {
+        let text = format!("{}\n", numbered(100));
+        assert!(cap_tokens(text, 60).ends_with('\n'));
+    }
+}
@@ -14,7 +14,12 @@ pub enum ChunkData {
     Tree {
         groups: Vec<ChunkItem>,
         children_key: String,
+        /// Leaf items merged into each group, aligned with `groups`.
         children: Vec<Vec<ChunkItem>>,
+        /// Remaining group fields for multi-level trees. When non-empty, a
+        /// group's children are regrouped via [`ChunkData::grouped`] when
+        /// expanded, so each level exposes its own children.
+        subgroups: Vec<String>,
     },
 }
 
@@ -29,6 +34,23 @@ impl ChunkData {
     pub const fn is_empty(&self) -> bool {
         self.len() == 0
     }
+
+    /// Build a tree by grouping `items` on `keys[0]`; remaining keys become
+    /// nested levels that are expanded on demand.
+    ///
+    /// Returns `Flat(items)` unchanged when `keys` is empty.
+    pub fn grouped(items: &[ChunkItem], keys: &[String], children_key: &str) -> Self {
+        let Some((first, rest)) = keys.split_first() else {
+            return Self::Flat(items.to_vec());
+        };
+        let (groups, children) = group_by_field_with_children(items, first);
+        Self::Tree {
+            groups,
+            children_key: children_key.to_string(),
+            children,
+            subgroups: rest.to_vec(),
+        }
+    }
 }
 
 /// Pre-compiled regexes for a single `ChunkConfig`, avoiding per-chunk recompilation.
@@ -64,6 +86,57 @@ impl<'a> CompiledChunkConfig<'a> {
     }
 }
 
+/// Incremental splitter for one `[[chunk]]` config, used by the streaming
+/// engine: lines go in one at a time, and each block's item comes out once the
+/// next `split_on` boundary (or the end of input) closes it.
+///
+/// Items are per-block only — carry-forward, key normalisation and grouping
+/// need every block and are left to the buffered pipeline.
+pub(super) struct ChunkStream<'a> {
+    split_re: Regex,
+    compiled: CompiledChunkConfig<'a>,
+    current: Option<Vec<String>>,
+}
+
+impl<'a> ChunkStream<'a> {
+    /// `None` when `split_on` is not a valid regex.
+    pub(super) fn new(config: &'a ChunkConfig) -> Option<Self> {
+        Some(Self {
+            split_re: Regex::new(&config.split_on).ok()?,
+            compiled: CompiledChunkConfig::new(config),
+            current: None,
+        })
+    }
+
+    pub(super) fn collect_as(&self) -> &str {
+        &self.compiled.config.collect_as
+    }
+
+    /// Feed one line; returns the item of the block this line closed, if any.
+    pub(super) fn push(&mut self, line: &str) -> Option<ChunkItem> {
+        if self.split_re.is_match(line) {
+            let closed = self.finish();
+            let mut block = Vec::new();
+            if self.compiled.config.include_split_line {
+                block.push(line.to_string());
+            }
+            self.current = Some(block);
+            return closed;
+        }
+        if let Some(ref mut block) = self.current {
+            block.push(line.to_string());
+        }
+        None
+    }
+
+    /// Close the open block at end of input.
+    pub(super) fn finish(&mut self) -> Option<ChunkItem> {
+        let block = self.current.take()?;
+        let lines: Vec<&str> = block.iter().map(String::as_str).collect();
+        Some(process_single_chunk(&lines, &self.compiled))
+    }
+}
+
 /// Process all chunk configurations against the raw output lines.
 ///
 /// For each `ChunkConfig`, splits the output at `split_on` boundaries, extracts
@@ -92,15 +165,10 @@ pub fn process_chunks(configs: &[ChunkConfig], lines: &[&str]) -> HashMap<String
         normalize_keys(config, &mut items);
 
         let data = match (&config.group_by, &config.children_as) {
-            (Some(group_field), Some(children_key)) => {
-                let (groups, children) = group_by_field_with_children(&items, group_field);
-                ChunkData::Tree {
-                    groups,
-                    children_key: children_key.clone(),
-                    children,
-                }
+            (Some(group_by), Some(children_key)) => {
+                ChunkData::grouped(&items, group_by.keys(), children_key)
             }
-            (Some(group_field), None) => ChunkData::Flat(group_by_field(&items, group_field)),
+            (Some(group_by), None) => ChunkData::Flat(group_by_fields(&items, group_by.keys())),
             _ => ChunkData::Flat(items),
         };
 
@@ -258,14 +326,18 @@ fn apply_carry_forward(config: &ChunkConfig, items: &mut [ChunkItem]) {
     }
 }
 
-/// Group chunk items by a field, merging numeric fields by summing.
+/// Group chunk items by one or more fields, merging numeric fields by summing.
 ///
-/// Non-numeric fields keep the value from the first item in each group.
-fn group_by_field(items: &[ChunkItem], field: &str) -> Vec<ChunkItem> {
-    let mut groups: Vec<(String, ChunkItem)> = Vec::new();
+/// Multiple fields form a single composite key. Non-numeric fields keep the
+/// value from the first item in each group.
+fn group_by_fields(items: &[ChunkItem], fields: &[String]) -> Vec<ChunkItem> {
+    let mut groups: Vec<(Vec<String>, ChunkItem)> = Vec::new();
 
     for item in items {
-        let key = item.get(field).cloned().unwrap_or_default();
+        let key: Vec<String> = fields
+            .iter()
+            .map(|f| item.get(f).cloned().unwrap_or_default())
+            .collect();
         if let Some((_, existing)) = groups.iter_mut().find(|(k, _)| k == &key) {
             merge_into(existing, item);
         } else {
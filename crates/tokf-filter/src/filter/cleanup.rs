@@ -51,7 +51,7 @@ pub fn apply_line_cleanup(config: &FilterConfig, lines: &[&str]) -> Vec<String>
 ///
 /// Returns `Cow::Borrowed` when no truncation is needed (avoids allocation).
 /// Uses a single pass over the string's characters.
-fn truncate_line(line: &str, max_chars: usize) -> Cow<'_, str> {
+pub(super) fn truncate_line(line: &str, max_chars: usize) -> Cow<'_, str> {
     // Single-pass: walk char_indices looking for the truncation point.
     // The ellipsis counts within the budget: max_chars=10 → 9 content + "…".
     let keep = max_chars.saturating_sub(1); // chars to keep before "…"
@@ -0,0 +1,126 @@
+use regex::Regex;
+
+use tokf_common::config::types::CollapseRule;
+
+const DEFAULT_OUTPUT: &str = "… {count} frames omitted (deepest: {first})";
+const DEFAULT_MIN_LINES: usize = 2;
+
+/// Apply `[[collapse]]` rules to `lines`, in order.
+///
+/// Each rule folds every run of consecutive lines matching its pattern into
+/// a single summary line; runs shorter than `min_lines` are kept as-is. Later
+/// rules see the output of earlier ones. Invalid patterns are silently skipped.
+pub fn apply_collapse(rules: &[CollapseRule], lines: Vec<String>) -> Vec<String> {
+    rules
+        .iter()
+        .fold(lines, |lines, rule| match Regex::new(&rule.pattern) {
+            Ok(re) => collapse_runs(rule, &re, lines),
+            Err(_) => lines,
+        })
+}
+
+fn collapse_runs(rule: &CollapseRule, re: &Regex, lines: Vec<String>) -> Vec<String> {
+    let min_lines = rule.min_lines.unwrap_or(DEFAULT_MIN_LINES).max(1);
+    let mut result = Vec::with_capacity(lines.len());
+    let mut run: Vec<String> = Vec::new();
+    for line in lines {
+        if re.is_match(&line) {
+            run.push(line);
+            continue;
+        }
+        flush_run(rule, min_lines, &mut run, &mut result);
+        result.push(line);
+    }
+    flush_run(rule, min_lines, &mut run, &mut result);
+    result
+}
+
+/// Move `run` into `out`, folded into a summary when it is long enough.
+fn flush_run(rule: &CollapseRule, min_lines: usize, run: &mut Vec<String>, out: &mut Vec<String>) {
+    if run.len() < min_lines {
+        out.append(run);
+        return;
+    }
+    let (Some(first), Some(last)) = (run.first(), run.last()) else {
+        return;
+    };
+    #[allow(clippy::literal_string_with_formatting_args)]
+    let summary = rule
+        .output
+        .as_deref()
+        .unwrap_or(DEFAULT_OUTPUT)
+        .replace("{count}", &run.len().to_string())
+        .replace("{first}", first.trim())
+        .replace("{last}", last.trim());
+    out.push(summary);
+    run.clear();
+}
+
+#[cfg(test)]
+#[allow(
+    clippy::unwrap_used,
+    clippy::expect_used,
+    clippy::literal_string_with_formatting_args
+)]
+mod tests {
+    use super::*;
+
+    fn rule(pattern: &str) -> CollapseRule {
+        CollapseRule {
+            pattern: pattern.to_string(),
+            output: None,
+            min_lines: None,
+        }
+    }
+
+    fn lines(text: &str) -> Vec<String> {
+        text.lines().map(String::from).collect()
+    }
+
+    #[test]
+    fn folds_java_stack_frames() {
+        let input = lines(
+            "java.lang.NullPointerException: boom\n\
+             \tat com.acme.Foo.bar(Foo.java:10)\n\
+             \tat com.acme.Foo.main(Foo.java:3)\n\
+             \tat java.base/jdk.internal.Main.run(Main.java:1)\n\
+             done",
+        );
+        assert_eq!(
+            apply_collapse(&[rule(r"^\s+at ")], input),
+            vec![
+                "java.lang.NullPointerException: boom",
+                "… 3 frames omitted (deepest: at com.acme.Foo.bar(Foo.java:10))",
+                "done",
+            ]
+        );
+    }
+
+    #[test]
+    fn each_run_is_folded_separately() {
+        let input = lines("  at a\n  at b\nCaused by: x\n  at c\n  at d\n  at e");
+        let mut r = rule(r"^\s+at ");
+        r.output = Some("[{count}: {first} .. {last}]".to_string());
+        assert_eq!(
+            apply_collapse(&[r], input),
+            vec!["[2: at a .. at b]", "Caused by: x", "[3: at c .. at e]"]
+        );
+    }
+
+    #[test]
+    fn short_runs_are_kept() {
+        let input = lines("err\n  at only\nnext");
+        assert_eq!(apply_collapse(&[rule(r"^\s+at ")], input.clone()), input);
+
+        let mut r = rule(r"^\s+at ");
+        r.min_lines = Some(3);
+        let input = lines("  at a\n  at b");
+        assert_eq!(apply_collapse(&[r], input.clone()), input);
+    }
+
+    #[test]
+    fn invalid_pattern_is_skipped() {
+        let input = lines("  at a\n  at b");
+        assert_eq!(apply_collapse(&[rule("(")], input.clone()), input);
+    }
+}
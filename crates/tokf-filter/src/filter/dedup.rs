@@ -7,36 +7,94 @@ use std::collections::VecDeque;
 ///
 /// Returns a filtered vec of references into the input slice.
 pub fn apply_dedup<'a>(lines: &[&'a str], window: Option<usize>) -> Vec<&'a str> {
+    apply_dedup_counted(lines, window)
+        .into_iter()
+        .map(|(line, _)| line)
+        .collect()
+}
+
+/// Like [`apply_dedup`], but pairs each surviving line with the number of
+/// times it occurred (itself plus the duplicates collapsed into it).
+pub fn apply_dedup_counted<'a>(lines: &[&'a str], window: Option<usize>) -> Vec<(&'a str, usize)> {
     window.map_or_else(|| dedup_consecutive(lines), |n| dedup_windowed(lines, n))
 }
 
-fn dedup_consecutive<'a>(lines: &[&'a str]) -> Vec<&'a str> {
-    let mut result: Vec<&'a str> = Vec::with_capacity(lines.len());
+fn dedup_consecutive<'a>(lines: &[&'a str]) -> Vec<(&'a str, usize)> {
+    let mut result: Vec<(&'a str, usize)> = Vec::with_capacity(lines.len());
     for &line in lines {
-        if result.last().copied() != Some(line) {
-            result.push(line);
+        match result.last_mut() {
+            Some((last, count)) if *last == line => *count += 1,
+            _ => result.push((line, 1)),
         }
     }
     result
 }
 
-fn dedup_windowed<'a>(lines: &[&'a str], window: usize) -> Vec<&'a str> {
-    let mut result: Vec<&'a str> = Vec::with_capacity(lines.len());
-    // Ring buffer of the last `window` output lines for fast lookup.
-    let mut recent: VecDeque<&'a str> = VecDeque::with_capacity(window);
+fn dedup_windowed<'a>(lines: &[&'a str], window: usize) -> Vec<(&'a str, usize)> {
+    let mut result: Vec<(&'a str, usize)> = Vec::with_capacity(lines.len());
+    // Ring buffer of the last `window` output lines (as indices into
+    // `result`) for fast lookup.
+    let mut recent: VecDeque<usize> = VecDeque::with_capacity(window);
     for &line in lines {
-        if recent.contains(&line) {
+        if let Some(&idx) = recent.iter().find(|&&i| result[i].0 == line) {
+            result[idx].1 += 1;
             continue;
         }
-        result.push(line);
         if recent.len() == window {
             recent.pop_front();
         }
-        recent.push_back(line);
+        recent.push_back(result.len());
+        result.push((line, 1));
     }
     result
 }
 
+/// Join `lines`, appending ` (xN)` to each line whose matching entry in
+/// `counts` is greater than one.
+pub fn join_annotated(lines: &[&str], counts: &[usize]) -> String {
+    let annotated: Vec<String> = lines
+        .iter()
+        .zip(counts)
+        .map(|(&line, &count)| {
+            if count > 1 {
+                format!("{line} (x{count})")
+            } else {
+                line.to_string()
+            }
+        })
+        .collect();
+    annotated.join("\n")
+}
+
+/// Line-at-a-time dedup with the same semantics as [`apply_dedup`].
+pub(super) struct Deduper {
+    window: usize,
+    recent: VecDeque<String>,
+}
+
+impl Deduper {
+    /// `window = None` compares against the previous output line only.
+    pub(super) fn new(window: Option<usize>) -> Self {
+        let window = window.unwrap_or(1);
+        Self {
+            window,
+            recent: VecDeque::with_capacity(window),
+        }
+    }
+
+    /// Whether `line` survives dedup; survivors are remembered.
+    pub(super) fn admit(&mut self, line: &str) -> bool {
+        if self.recent.iter().any(|l| l == line) {
+            return false;
+        }
+        if self.recent.len() == self.window {
+            self.recent.pop_front();
+        }
+        self.recent.push_back(line.to_string());
+        true
+    }
+}
+
 #[cfg(test)]
 #[allow(clippy::unwrap_used, clippy::expect_used)]
 mod tests {
@@ -85,4 +143,30 @@ mod tests {
         let lines = vec!["only"];
         assert_eq!(apply_dedup(&lines, None), vec!["only"]);
     }
+
+    #[test]
+    fn counted_consecutive_tracks_run_lengths() {
+        let lines = vec!["a", "a", "b", "b", "b", "a"];
+        assert_eq!(
+            apply_dedup_counted(&lines, None),
+            vec![("a", 2), ("b", 3), ("a", 1)]
+        );
+    }
+
+    #[test]
+    fn counted_window_credits_surviving_copy() {
+        let lines = vec!["warn", "x", "warn", "y", "warn"];
+        assert_eq!(
+            apply_dedup_counted(&lines, Some(3)),
+            vec![("warn", 3), ("x", 1), ("y", 1)]
+        );
+    }
+
+    #[test]
+    fn join_annotated_marks_only_repeats() {
+        assert_eq!(
+            join_annotated(&["warn", "done"], &[400, 1]),
+            "warn (x400)\ndone"
+        );
+    }
 }
@@ -0,0 +1,79 @@
+//! Line diff for `diff_previous`: keep only what is new since the last run.
+
+use std::collections::HashMap;
+
+/// Drop lines of `current` that also appear in `previous`.
+///
+/// Lines are matched as a multiset, so a line printed three times now and
+/// once before keeps two copies. Surviving lines stay in their original
+/// order. When anything was dropped, an `unchanged: N lines` summary line is
+/// appended; when nothing was, `current` is returned as-is.
+pub fn diff_against_previous(current: &str, previous: &str) -> String {
+    let mut seen: HashMap<&str, usize> = HashMap::new();
+    for line in previous.lines() {
+        *seen.entry(line).or_default() += 1;
+    }
+
+    let mut changed: Vec<&str> = Vec::new();
+    let mut unchanged = 0usize;
+    for line in current.lines() {
+        match seen.get_mut(line) {
+            Some(count) if *count > 0 => {
+                *count -= 1;
+                unchanged += 1;
+            }
+            _ => changed.push(line),
+        }
+    }
+
+    if unchanged == 0 {
+        return current.to_string();
+    }
+    let noun = if unchanged == 1 { "line" } else { "lines" };
+    let summary = format!("unchanged: {unchanged} {noun}");
+    changed.push(&summary);
+    changed.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_new_lines_are_kept() {
+        let previous = "warning: unused a\nwarning: unused b";
+        let current = "warning: unused a\nwarning: unused b\nwarning: unused c";
+        assert_eq!(
+            diff_against_previous(current, previous),
+            "warning: unused c\nunchanged: 2 lines"
+        );
+    }
+
+    #[test]
+    fn changed_lines_are_kept_in_order() {
+        let previous = "a\nb\nsummary: 2 warnings";
+        let current = "x\na\nb\nsummary: 3 warnings";
+        assert_eq!(
+            diff_against_previous(current, previous),
+            "x\nsummary: 3 warnings\nunchanged: 2 lines"
+        );
+    }
+
+    #[test]
+    fn identical_output_is_summary_only() {
+        assert_eq!(diff_against_previous("a", "a"), "unchanged: 1 line");
+    }
+
+    #[test]
+    fn repeated_lines_match_as_multiset() {
+        assert_eq!(
+            diff_against_previous("dup\ndup\ndup", "dup"),
+            "dup\ndup\nunchanged: 1 line"
+        );
+    }
+
+    #[test]
+    fn nothing_in_common_returns_current() {
+        assert_eq!(diff_against_previous("new\n", "old"), "new\n");
+    }
+}
@@ -3,11 +3,55 @@ use std::collections::HashMap;
 use serde_json::Value;
 use serde_json_path::JsonPath;
 
-use tokf_common::config::types::{JsonConfig, JsonExtractRule};
+use tokf_common::config::types::{FilterConfig, JsonConfig, JsonExtractRule, ParseFormat};
 
 use super::chunk::{ChunkData, ChunkItem};
 use super::template::ChunkMap;
 
+/// Result of a JSON extraction: `(json_parsed, vars, chunks)`.
+pub type JsonExtraction = (bool, HashMap<String, String>, ChunkMap);
+
+/// Run whichever structured extraction the filter configures, if any.
+///
+/// `[json]` takes precedence; otherwise `[parse]` with a non-text `format`
+/// supplies the data (`json`/`jsonl` via its extract rules, `junit`/`tap` via
+/// the test-report parsers). Returns `None` when the filter has no such stage.
+pub fn run_configured(config: &FilterConfig, text: &str) -> Option<JsonExtraction> {
+    if let Some(ref json_config) = config.json {
+        return Some(extract_json(text, json_config));
+    }
+    let parse = config.parse.as_ref()?;
+    let root = match parse.format {
+        ParseFormat::Json => serde_json::from_str::<Value>(text).ok(),
+        ParseFormat::Jsonl => parse_json_lines(text),
+        ParseFormat::Junit | ParseFormat::Tap => return super::report::run(parse.format, text),
+        ParseFormat::Text => return None,
+    };
+    Some(root.map_or_else(
+        || (false, HashMap::new(), ChunkMap::new()),
+        |root| extract_from_root(&root, &parse.extract),
+    ))
+}
+
+/// Parse newline-delimited JSON into a single array value.
+///
+/// Blank lines and lines that aren't valid JSON (e.g. compiler progress
+/// interleaved with `--message-format=json` output) are skipped. Returns
+/// `None` when no line parses.
+pub fn parse_json_lines(text: &str) -> Option<Value> {
+    let values: Vec<Value> = text
+        .lines()
+        .map(str::trim)
+        .filter(|l| !l.is_empty())
+        .filter_map(|l| serde_json::from_str::<Value>(l).ok())
+        .collect();
+    if values.is_empty() {
+        None
+    } else {
+        Some(Value::Array(values))
+    }
+}
+
 /// Extract variables and chunks from JSON output using `JSONPath` queries.
 ///
 /// For each extraction rule:
@@ -20,23 +64,24 @@ use super::template::ChunkMap;
 /// Returns `(json_parsed, vars, chunks)` where `json_parsed` indicates whether the
 /// input was valid JSON (regardless of whether any rules matched). This lets the
 /// caller distinguish "not JSON" from "valid JSON with no matches".
-pub fn extract_json(
-    stdout: &str,
-    config: &JsonConfig,
-) -> (bool, HashMap<String, String>, ChunkMap) {
-    let mut vars = HashMap::new();
-    let mut chunks = ChunkMap::new();
-
+pub fn extract_json(stdout: &str, config: &JsonConfig) -> JsonExtraction {
     let Ok(root) = serde_json::from_str::<Value>(stdout) else {
-        return (false, vars, chunks);
+        return (false, HashMap::new(), ChunkMap::new());
     };
+    extract_from_root(&root, &config.extract)
+}
+
+/// Apply extraction rules to an already-parsed JSON document.
+fn extract_from_root(root: &Value, rules: &[JsonExtractRule]) -> JsonExtraction {
+    let mut vars = HashMap::new();
+    let mut chunks = ChunkMap::new();
 
-    for rule in &config.extract {
+    for rule in rules {
         let Ok(path) = JsonPath::parse(&rule.path) else {
             continue;
         };
 
-        let node_list = path.query(&root);
+        let node_list = path.query(root);
         let nodes: Vec<&Value> = node_list.all();
 
         if nodes.is_empty() {
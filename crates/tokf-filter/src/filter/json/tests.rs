@@ -501,3 +501,17 @@ fn missing_field_subpath_defaults_to_empty() {
         panic!("expected Flat chunk data");
     }
 }
+
+// --- parse_json_lines ---
+
+#[test]
+fn parse_json_lines_skips_non_json() {
+    let root = parse_json_lines("{\"a\":1}\nnoise\n\n{\"a\":2}\n").unwrap();
+    assert_eq!(root, serde_json::json!([{"a": 1}, {"a": 2}]));
+}
+
+#[test]
+fn parse_json_lines_none_when_nothing_parses() {
+    assert!(parse_json_lines("just text\nmore text").is_none());
+    assert!(parse_json_lines("").is_none());
+}
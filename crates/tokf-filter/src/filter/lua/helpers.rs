@@ -0,0 +1,207 @@
+//! The `tokf` helper table injected into every sandboxed script.
+//!
+//! - `tokf.split(s, sep)` — split on a plain-text separator (whitespace runs
+//!   when `sep` is omitted); returns an array of strings
+//! - `tokf.trim(s)` — strip leading and trailing whitespace
+//! - `tokf.json_decode(s)` — decode JSON into tables; returns `nil, err` on
+//!   invalid input. JSON `null` becomes `nil`.
+//! - `tokf.match_all(s, regex)` — every match of a Rust regex; each item is
+//!   the matched text, or a table of captures (by index and by name) when the
+//!   regex has capture groups
+
+use mlua::{IntoLua, Lua, Table, Value};
+use regex::Regex;
+
+/// Install the `tokf` global table.
+pub(super) fn install(lua: &Lua) -> mlua::Result<()> {
+    let tokf = lua.create_table()?;
+    tokf.set(
+        "split",
+        lua.create_function(|lua, (s, sep): (String, Option<String>)| {
+            split(lua, &s, sep.as_deref())
+        })?,
+    )?;
+    tokf.set(
+        "trim",
+        lua.create_function(|_, s: String| Ok(s.trim().to_string()))?,
+    )?;
+    tokf.set(
+        "json_decode",
+        lua.create_function(|lua, s: String| {
+            match serde_json::from_str::<serde_json::Value>(&s) {
+                Ok(value) => Ok((json_to_lua(lua, &value)?, Value::Nil)),
+                Err(e) => Ok((Value::Nil, e.to_string().into_lua(lua)?)),
+            }
+        })?,
+    )?;
+    tokf.set(
+        "match_all",
+        lua.create_function(|lua, (s, pattern): (String, String)| match_all(lua, &s, &pattern))?,
+    )?;
+    lua.globals().set("tokf", tokf)
+}
+
+fn split(lua: &Lua, s: &str, sep: Option<&str>) -> mlua::Result<Table> {
+    match sep {
+        None => lua.create_sequence_from(s.split_whitespace()),
+        Some("") => Err(mlua::Error::runtime(
+            "tokf.split: separator must not be empty",
+        )),
+        Some(sep) => lua.create_sequence_from(s.split(sep)),
+    }
+}
+
+fn match_all(lua: &Lua, s: &str, pattern: &str) -> mlua::Result<Table> {
+    let re = Regex::new(pattern)
+        .map_err(|e| mlua::Error::runtime(format!("tokf.match_all: invalid regex: {e}")))?;
+    let matches = lua.create_table()?;
+    if re.captures_len() == 1 {
+        for (i, m) in re.find_iter(s).enumerate() {
+            matches.set(i + 1, m.as_str())?;
+        }
+        return Ok(matches);
+    }
+    let names: Vec<Option<&str>> = re.capture_names().collect();
+    for (i, caps) in re.captures_iter(s).enumerate() {
+        let item = lua.create_table()?;
+        for (idx, name) in names.iter().enumerate().skip(1) {
+            let text = caps.get(idx).map_or("", |m| m.as_str());
+            item.set(idx, text)?;
+            if let Some(name) = name {
+                item.set(*name, text)?;
+            }
+        }
+        matches.set(i + 1, item)?;
+    }
+    Ok(matches)
+}
+
+fn json_to_lua(lua: &Lua, value: &serde_json::Value) -> mlua::Result<Value> {
+    use serde_json::Value as J;
+    match value {
+        J::Null => Ok(Value::Nil),
+        J::Bool(b) => Ok(Value::Boolean(*b)),
+        J::Number(n) => n.as_i64().map_or_else(
+            || n.as_f64().unwrap_or(f64::NAN).into_lua(lua),
+            |i| i.into_lua(lua),
+        ),
+        J::String(s) => s.as_str().into_lua(lua),
+        J::Array(items) => {
+            let table = lua.create_table()?;
+            for (i, item) in items.iter().enumerate() {
+                table.set(i + 1, json_to_lua(lua, item)?)?;
+            }
+            Ok(Value::Table(table))
+        }
+        J::Object(map) => {
+            let table = lua.create_table()?;
+            for (key, item) in map {
+                table.set(key.as_str(), json_to_lua(lua, item)?)?;
+            }
+            Ok(Value::Table(table))
+        }
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::super::{SandboxLimits, run_lua_script_sandboxed};
+
+    fn eval(source: &str, output: &str) -> Option<String> {
+        run_lua_script_sandboxed(source, output, 0, &[], &SandboxLimits::default()).unwrap()
+    }
+
+    #[test]
+    fn split_on_separator_and_whitespace() {
+        assert_eq!(
+            eval(
+                r#"return table.concat(tokf.split(output, ","), "|")"#,
+                "a,b,,c"
+            ),
+            Some("a|b||c".to_string())
+        );
+        assert_eq!(
+            eval(
+                r#"return table.concat(tokf.split(output), "|")"#,
+                "  a \t b\n c "
+            ),
+            Some("a|b|c".to_string())
+        );
+    }
+
+    #[test]
+    fn split_rejects_empty_separator() {
+        let result = run_lua_script_sandboxed(
+            r#"return tokf.split("abc", "")"#,
+            "",
+            0,
+            &[],
+            &SandboxLimits::default(),
+        );
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn trim_strips_whitespace() {
+        assert_eq!(
+            eval("return '[' .. tokf.trim(output) .. ']'", "  hi \n"),
+            Some("[hi]".to_string())
+        );
+    }
+
+    #[test]
+    fn json_decode_builds_tables() {
+        let json = r#"{"name":"core","tests":[{"ok":true},{"ok":false}],"ms":12.5,"n":3}"#;
+        let script = r#"
+            local doc = tokf.json_decode(output)
+            return doc.name .. " " .. #doc.tests .. " " .. tostring(doc.tests[2].ok)
+                .. " " .. doc.ms .. " " .. doc.n
+        "#;
+        assert_eq!(eval(script, json), Some("core 2 false 12.5 3".to_string()));
+    }
+
+    #[test]
+    fn json_decode_returns_nil_and_error_on_invalid_input() {
+        let script = r#"
+            local doc, err = tokf.json_decode(output)
+            if doc == nil and err ~= nil then return "invalid" end
+        "#;
+        assert_eq!(eval(script, "{not json"), Some("invalid".to_string()));
+    }
+
+    #[test]
+    fn match_all_without_groups_returns_matches() {
+        assert_eq!(
+            eval(
+                r#"return table.concat(tokf.match_all(output, "\\d+"), ",")"#,
+                "a1 b22 c333"
+            ),
+            Some("1,22,333".to_string())
+        );
+    }
+
+    #[test]
+    fn match_all_with_groups_returns_captures() {
+        let script = r#"
+            local parts = {}
+            for _, m in ipairs(tokf.match_all(output, "(?P<name>\\w+)=(\\d+)")) do
+                table.insert(parts, m.name .. ":" .. m[2])
+            end
+            return table.concat(parts, ",")
+        "#;
+        assert_eq!(eval(script, "a=1 b=2"), Some("a:1,b:2".to_string()));
+    }
+
+    #[test]
+    fn match_all_invalid_regex_is_error() {
+        let result = run_lua_script_sandboxed(
+            r#"return tokf.match_all("x", "(")"#,
+            "",
+            0,
+            &[],
+            &SandboxLimits::default(),
+        );
+        assert!(result.is_err());
+    }
+}
@@ -0,0 +1,530 @@
+use std::collections::HashMap;
+
+use anyhow::Context as _;
+use mlua::Lua;
+
+use tokf_common::config::types::ScriptConfig;
+
+use super::chunk::{ChunkData, ChunkItem};
+use super::section::SectionMap;
+use super::template::ChunkMap;
+
+mod helpers;
+
+/// Default instruction limit for sandboxed execution (1 million instructions).
+const DEFAULT_INSTRUCTION_LIMIT: u32 = 1_000_000;
+
+/// Default memory limit for sandboxed execution (16 MB).
+const DEFAULT_MEMORY_LIMIT: usize = 16 * 1024 * 1024;
+
+/// Load the Lua script source from a [`ScriptConfig`].
+///
+/// Resolves `file` (read from disk) or `source` (inline). Setting both
+/// is rejected — exactly one must be provided.
+///
+/// This is `pub(crate)` because callers should use
+/// [`run_lua_script_sandboxed`] for execution; this function is only
+/// exposed for the `apply` pipeline to load + run as two steps.
+///
+/// # Errors
+///
+/// Returns an error if both `file` and `source` are set, if neither is
+/// set, or if the referenced file cannot be read.
+pub(crate) fn load_source(script_config: &ScriptConfig) -> anyhow::Result<String> {
+    match (&script_config.file, &script_config.source) {
+        (Some(_), Some(_)) => Err(anyhow::anyhow!(
+            "lua_script must set 'file' or 'source', not both"
+        )),
+        (Some(file), None) => std::fs::read_to_string(file)
+            .with_context(|| format!("lua_script: cannot read file '{file}'")),
+        (None, Some(source)) => Ok(source.clone()),
+        (None, None) => Err(anyhow::anyhow!("lua_script must set 'file' or 'source'")),
+    }
+}
+
+/// Sandboxed Lua execution limits.
+pub struct SandboxLimits {
+    /// Maximum number of Luau instructions before termination.
+    pub instruction_limit: u32,
+    /// Maximum memory in bytes the Luau VM may allocate.
+    pub memory_limit: usize,
+}
+
+impl Default for SandboxLimits {
+    fn default() -> Self {
+        Self {
+            instruction_limit: DEFAULT_INSTRUCTION_LIMIT,
+            memory_limit: DEFAULT_MEMORY_LIMIT,
+        }
+    }
+}
+
+/// What a script asked the pipeline to do.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LuaOutcome {
+    /// The script returned a string: it replaces the output.
+    Replace(String),
+    /// The script returned `nil`: the TOML pipeline continues unchanged.
+    Passthrough,
+    /// The script returned a table: its entries become template variables
+    /// and the TOML pipeline continues.
+    Vars(HashMap<String, String>),
+}
+
+/// Inputs exposed to a script as globals.
+pub struct LuaContext<'a> {
+    /// Pre-filtered output (`output`).
+    pub output: &'a str,
+    pub exit_code: i32,
+    pub args: &'a [String],
+    /// Collected `[[section]]` data (`sections`).
+    pub sections: &'a SectionMap,
+    /// Collected `[[chunk]]` and `[json]` collections (`chunks`).
+    pub chunks: &'a ChunkMap,
+}
+
+/// Run a Luau filter script with resource limits (instruction count + memory).
+///
+/// Convenience wrapper around [`run_lua_script_with_context`] for scripts
+/// that only need `output`, `exit_code` and `args`. Returning a table is
+/// treated like returning `nil`.
+///
+/// # Errors
+///
+/// See [`run_lua_script_with_context`].
+pub fn run_lua_script_sandboxed(
+    source: &str,
+    output: &str,
+    exit_code: i32,
+    args: &[String],
+    limits: &SandboxLimits,
+) -> anyhow::Result<Option<String>> {
+    let ctx = LuaContext {
+        output,
+        exit_code,
+        args,
+        sections: &SectionMap::new(),
+        chunks: &ChunkMap::new(),
+    };
+    match run_lua_script_with_context(source, &ctx, limits)? {
+        LuaOutcome::Replace(text) => Ok(Some(text)),
+        LuaOutcome::Passthrough | LuaOutcome::Vars(_) => Ok(None),
+    }
+}
+
+/// Run a Luau filter script with resource limits and the full [`LuaContext`].
+///
+/// Prevents infinite loops and memory exhaustion via instruction-count
+/// and memory-limit constraints. Only inline source code is accepted.
+///
+/// ## Sandbox guarantees
+///
+/// mlua's Luau VM disables `os`, `io`, `package`, and other dangerous
+/// standard libraries by default — scripts cannot access the filesystem
+/// or execute commands. This is verified by tests (`os_blocked_by_sandbox`,
+/// `io_blocked_by_sandbox`). If upgrading mlua, re-run these tests to
+/// confirm the sandbox is intact. Scripts get the `tokf` helper table in
+/// their place (see `helpers`).
+///
+/// # Errors
+///
+/// Returns an error if:
+/// - The script exceeds the instruction limit (likely infinite loop)
+/// - The script exceeds the memory limit
+/// - The script returns something other than a string, table or nil
+/// - Any other Lua runtime error
+pub fn run_lua_script_with_context(
+    source: &str,
+    ctx: &LuaContext<'_>,
+    limits: &SandboxLimits,
+) -> anyhow::Result<LuaOutcome> {
+    // mlua's Luau VM sandboxes by default: os, io, package, etc. are nil.
+    let lua = Lua::new();
+
+    // Set memory limit (returns previous limit, which we discard)
+    let _ = lua.set_memory_limit(limits.memory_limit);
+
+    // Per-invocation counter — each call gets its own Arc so there's no
+    // cross-call interference. The interrupt handler runs synchronously on
+    // the VM's single thread, so Relaxed ordering is safe.
+    let counter = std::sync::Arc::new(std::sync::atomic::AtomicU32::new(0));
+    let instruction_limit = limits.instruction_limit;
+    lua.set_interrupt(move |_lua| {
+        // mlua's Luau interrupt fires roughly every ~1000 VM instructions.
+        // saturating_mul caps at u32::MAX which is always >= any reasonable
+        // instruction_limit, so overflow doesn't bypass the check.
+        let calls = counter.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+        if calls.saturating_mul(1000) >= instruction_limit {
+            return Ok(mlua::VmState::Yield);
+        }
+        Ok(mlua::VmState::Continue)
+    });
+
+    set_globals(&lua, ctx)?;
+
+    let value: mlua::Value = lua
+        .load(source)
+        .eval()
+        .context("sandboxed lua_script execution")?;
+
+    match value {
+        mlua::Value::String(s) => Ok(LuaOutcome::Replace(s.to_str()?.to_string())),
+        mlua::Value::Nil => Ok(LuaOutcome::Passthrough),
+        mlua::Value::Table(t) => table_to_vars(&t).map(LuaOutcome::Vars),
+        other => Err(anyhow::anyhow!(
+            "lua_script must return a string, table or nil, got {}",
+            other.type_name()
+        )),
+    }
+}
+
+/// Compile a Luau script without running it.
+///
+/// # Errors
+///
+/// Returns the compiler's message (which includes the line number) when the
+/// script has a syntax error.
+pub fn check_syntax(source: &str) -> anyhow::Result<()> {
+    Lua::new()
+        .load(source)
+        .into_function()
+        .map(|_| ())
+        .map_err(|e| anyhow::anyhow!("{e}"))
+}
+
+fn set_globals(lua: &Lua, ctx: &LuaContext<'_>) -> anyhow::Result<()> {
+    let globals = lua.globals();
+    globals
+        .set("output", ctx.output)
+        .context("set output global")?;
+    globals
+        .set("exit_code", ctx.exit_code)
+        .context("set exit_code global")?;
+    globals
+        .set(
+            "args",
+            lua.create_sequence_from(ctx.args.iter().map(String::as_str))?,
+        )
+        .context("set args global")?;
+    globals
+        .set("sections", sections_table(lua, ctx.sections)?)
+        .context("set sections global")?;
+    globals
+        .set("chunks", chunks_table(lua, ctx.chunks)?)
+        .context("set chunks global")?;
+    helpers::install(lua).context("set tokf helper global")?;
+    Ok(())
+}
+
+/// `sections.<name>` = `{ lines = {...}, blocks = {...}, count = n, scopes = {...} }`,
+/// where each scope is `{ header = "...", sections = <same shape> }`.
+fn sections_table(lua: &Lua, sections: &SectionMap) -> mlua::Result<mlua::Table> {
+    let table = lua.create_table()?;
+    for (name, data) in sections {
+        let entry = lua.create_table()?;
+        entry.set(
+            "lines",
+            lua.create_sequence_from(data.lines.iter().map(String::as_str))?,
+        )?;
+        entry.set(
+            "blocks",
+            lua.create_sequence_from(data.blocks.iter().map(String::as_str))?,
+        )?;
+        entry.set("count", data.count())?;
+        let scopes = lua.create_table()?;
+        for (i, scope) in data.scopes.iter().enumerate() {
+            let s = lua.create_table()?;
+            s.set("header", scope.header.as_str())?;
+            s.set("sections", sections_table(lua, &scope.sections)?)?;
+            scopes.set(i + 1, s)?;
+        }
+        entry.set("scopes", scopes)?;
+        table.set(name.as_str(), entry)?;
+    }
+    Ok(table)
+}
+
+/// `chunks.<name>` = array of items (field → string). Tree collections list
+/// their groups, each carrying its children under the `children_as` key.
+fn chunks_table(lua: &Lua, chunks: &ChunkMap) -> mlua::Result<mlua::Table> {
+    let table = lua.create_table()?;
+    for (name, data) in chunks {
+        let items = lua.create_table()?;
+        match data {
+            ChunkData::Flat(list) => {
+                for (i, item) in list.iter().enumerate() {
+                    items.set(i + 1, item_table(lua, item)?)?;
+                }
+            }
+            ChunkData::Tree {
+                groups,
+                children_key,
+                children,
+                ..
+            } => {
+                for (i, (group, kids)) in groups.iter().zip(children).enumerate() {
+                    let g = item_table(lua, group)?;
+                    let k = lua.create_table()?;
+                    for (j, kid) in kids.iter().enumerate() {
+                        k.set(j + 1, item_table(lua, kid)?)?;
+                    }
+                    g.set(children_key.as_str(), k)?;
+                    items.set(i + 1, g)?;
+                }
+            }
+        }
+        table.set(name.as_str(), items)?;
+    }
+    Ok(table)
+}
+
+fn item_table(lua: &Lua, item: &ChunkItem) -> mlua::Result<mlua::Table> {
+    lua.create_table_from(item.iter().map(|(k, v)| (k.as_str(), v.as_str())))
+}
+
+/// Convert a returned table into template variables. String keys only;
+/// string, number and boolean values are stringified.
+fn table_to_vars(table: &mlua::Table) -> anyhow::Result<HashMap<String, String>> {
+    let mut vars = HashMap::new();
+    for pair in table.pairs::<mlua::Value, mlua::Value>() {
+        let (key, value) = pair?;
+        let mlua::Value::String(key) = key else {
+            return Err(anyhow::anyhow!("lua_script vars must have string keys"));
+        };
+        let value = match value {
+            mlua::Value::String(s) => s.to_str()?.to_string(),
+            mlua::Value::Integer(n) => n.to_string(),
+            mlua::Value::Number(n) => n.to_string(),
+            mlua::Value::Boolean(b) => b.to_string(),
+            other => {
+                return Err(anyhow::anyhow!(
+                    "lua_script var values must be strings, numbers or booleans, got {}",
+                    other.type_name()
+                ));
+            }
+        };
+        vars.insert(key.to_str()?.to_string(), value);
+    }
+    Ok(vars)
+}
+
+/// Load and run the configured script, for the `apply` pipeline.
+///
+/// Load and runtime errors are printed to stderr and treated as passthrough.
+pub(super) fn run_configured(
+    script_cfg: &ScriptConfig,
+    ctx: &LuaContext<'_>,
+    limits: &SandboxLimits,
+) -> LuaOutcome {
+    let outcome = load_source(script_cfg)
+        .and_then(|source| run_lua_script_with_context(&source, ctx, limits));
+    outcome.unwrap_or_else(|e| {
+        eprintln!("[tokf] lua script error: {e:#}");
+        LuaOutcome::Passthrough
+    })
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+    use tokf_common::config::types::{ScriptConfig, ScriptLang};
+
+    /// Tiny limits for tests — just enough for the VM to init and run a
+    /// simple return statement. The Luau VM needs ~512 KB for its own
+    /// baseline allocations.
+    fn test_limits() -> SandboxLimits {
+        SandboxLimits {
+            instruction_limit: 10_000,
+            memory_limit: 512 * 1024, // 512 KB
+        }
+    }
+
+    #[test]
+    fn check_syntax_reports_compile_errors() {
+        assert!(check_syntax("return output").is_ok());
+        let err = check_syntax("if x then\nreturn 1").unwrap_err();
+        assert!(err.to_string().contains(":2:"), "got: {err}");
+    }
+
+    #[test]
+    fn returns_string_replaces_output() {
+        let result =
+            run_lua_script_sandboxed(r#"return "replaced""#, "original", 0, &[], &test_limits())
+                .unwrap();
+        assert_eq!(result, Some("replaced".to_string()));
+    }
+
+    #[test]
+    fn returns_nil_passthrough() {
+        let result =
+            run_lua_script_sandboxed("return nil", "original", 0, &[], &test_limits()).unwrap();
+        assert_eq!(result, None);
+    }
+
+    #[test]
+    fn output_global_available() {
+        let result =
+            run_lua_script_sandboxed("return output", "hello world", 0, &[], &test_limits())
+                .unwrap();
+        assert_eq!(result, Some("hello world".to_string()));
+    }
+
+    #[test]
+    fn exit_code_global_available() {
+        let result =
+            run_lua_script_sandboxed("return tostring(exit_code)", "", 0, &[], &test_limits())
+                .unwrap();
+        assert_eq!(result, Some("0".to_string()));
+    }
+
+    #[test]
+    fn args_global_available() {
+        let args = vec!["hello".to_string()];
+        let result =
+            run_lua_script_sandboxed("return args[1]", "", 0, &args, &test_limits()).unwrap();
+        assert_eq!(result, Some("hello".to_string()));
+    }
+
+    fn run_with(source: &str, sections: &SectionMap, chunks: &ChunkMap) -> LuaOutcome {
+        let ctx = LuaContext {
+            output: "",
+            exit_code: 0,
+            args: &[],
+            sections,
+            chunks,
+        };
+        run_lua_script_with_context(source, &ctx, &test_limits()).unwrap()
+    }
+
+    #[test]
+    fn chunks_global_exposes_items() {
+        let item = |name: &str| ChunkItem::from([("name".to_string(), name.to_string())]);
+        let mut chunks = ChunkMap::new();
+        chunks.insert(
+            "crates".to_string(),
+            ChunkData::Flat(vec![item("core"), item("cli")]),
+        );
+        let outcome = run_with(
+            "return #chunks.crates .. ':' .. chunks.crates[2].name",
+            &SectionMap::new(),
+            &chunks,
+        );
+        assert_eq!(outcome, LuaOutcome::Replace("2:cli".to_string()));
+    }
+
+    #[test]
+    fn returns_table_as_vars() {
+        let outcome = run_with(
+            r#"return { total = 3, ratio = 0.5, ok = true, label = "x" }"#,
+            &SectionMap::new(),
+            &ChunkMap::new(),
+        );
+        let expected = [
+            ("total", "3"),
+            ("ratio", "0.5"),
+            ("ok", "true"),
+            ("label", "x"),
+        ]
+        .map(|(k, v)| (k.to_string(), v.to_string()));
+        assert_eq!(outcome, LuaOutcome::Vars(HashMap::from(expected)));
+    }
+
+    #[test]
+    fn table_with_nested_value_is_error() {
+        let ctx = LuaContext {
+            output: "",
+            exit_code: 0,
+            args: &[],
+            sections: &SectionMap::new(),
+            chunks: &ChunkMap::new(),
+        };
+        let result = run_lua_script_with_context("return { a = {} }", &ctx, &test_limits());
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn load_source_file_not_found_returns_err() {
+        let config = ScriptConfig {
+            lang: ScriptLang::Luau,
+            file: Some("/nonexistent/path/script.luau".to_string()),
+            source: None,
+        };
+        let result = load_source(&config);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn load_source_rejects_both_file_and_source() {
+        let config = ScriptConfig {
+            lang: ScriptLang::Luau,
+            file: Some("script.luau".to_string()),
+            source: Some("return nil".to_string()),
+        };
+        let result = load_source(&config);
+        assert!(result.is_err());
+        let msg = result.unwrap_err().to_string();
+        assert!(
+            msg.contains("not both"),
+            "expected mutual exclusion error, got: {msg}"
+        );
+    }
+
+    #[test]
+    fn load_source_rejects_neither_file_nor_source() {
+        let config = ScriptConfig {
+            lang: ScriptLang::Luau,
+            file: None,
+            source: None,
+        };
+        let result = load_source(&config);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn invalid_syntax_returns_err() {
+        let result = run_lua_script_sandboxed("not lua !!!", "", 0, &[], &test_limits());
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn os_blocked_by_sandbox() {
+        let result =
+            run_lua_script_sandboxed(r#"return os.execute("id")"#, "", 0, &[], &test_limits());
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn io_blocked_by_sandbox() {
+        let result = run_lua_script_sandboxed("return io.read()", "", 0, &[], &test_limits());
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn infinite_loop_returns_error() {
+        let limits = SandboxLimits {
+            instruction_limit: 1_000,
+            memory_limit: 128 * 1024,
+        };
+        let result = run_lua_script_sandboxed("while true do end", "", 0, &[], &limits);
+        assert!(result.is_err(), "infinite loop should be terminated");
+    }
+
+    #[test]
+    #[ignore = "memory bomb test is slow — run with --ignored"]
+    fn memory_bomb_returns_error() {
+        let limits = SandboxLimits {
+            instruction_limit: 500_000,
+            memory_limit: 256 * 1024,
+        };
+        let script = r#"
+            local s = "xxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxx"  -- 128 bytes
+            for i = 1, 30 do
+                s = s .. s
+            end
+            return s
+        "#;
+        let result = run_lua_script_sandboxed(script, "", 0, &[], &limits);
+        assert!(result.is_err(), "memory bomb should be terminated");
+    }
+}
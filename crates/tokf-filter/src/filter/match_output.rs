@@ -6,6 +6,18 @@ use tokf_common::config::types::MatchOutputRule;
 use super::section::SectionMap;
 use super::template;
 
+/// A `match_output` rule that fired, plus the context needed to render it.
+#[derive(Debug)]
+pub struct RuleMatch<'a> {
+    pub rule: &'a MatchOutputRule,
+    /// Matched substring (for `contains`) or the line where the regex match
+    /// starts (for `pattern`). Used to resolve `{line_containing}`.
+    pub needle: String,
+    /// Regex capture groups keyed by index (`"1"`, `"2"`, …) and by name for
+    /// named groups. Empty when the rule matched via `contains`.
+    pub captures: HashMap<String, String>,
+}
+
 /// Find the first `match_output` rule that matches the combined output.
 ///
 /// Matching order per rule:
@@ -13,12 +25,11 @@ use super::template;
 /// 2. If `contains` is set and found as substring → match
 /// 3. If `pattern` is set and matches as regex → match
 ///
-/// Returns the matching rule and the matched substring (for `{line_containing}`
-/// template resolution), or `None`.
+/// Returns the matching rule with its needle and capture groups, or `None`.
 pub fn find_matching_rule<'a>(
     rules: &'a [MatchOutputRule],
     combined: &str,
-) -> Option<(&'a MatchOutputRule, String)> {
+) -> Option<RuleMatch<'a>> {
     for rule in rules {
         // Check `unless` guard first — if this regex matches, skip the rule.
         if let Some(ref unless_pat) = rule.unless
@@ -32,17 +43,21 @@ pub fn find_matching_rule<'a>(
         if let Some(ref needle) = rule.contains
             && combined.contains(needle.as_str())
         {
-            return Some((rule, needle.clone()));
+            return Some(RuleMatch {
+                rule,
+                needle: needle.clone(),
+                captures: HashMap::new(),
+            });
         }
 
         // Fall back to regex match (`pattern`).
         if let Some(ref pat) = rule.pattern
             && let Ok(re) = Regex::new(pat)
-            && let Some(m) = re.find(combined)
+            && let Some(caps) = re.captures(combined)
         {
             // Find the line containing the start of the match.
             // This works for both single-line and multi-line patterns.
-            let match_start = m.start();
+            let match_start = caps.get(0).map_or(0, |m| m.start());
             let needle = combined
                 .lines()
                 .find(|l| {
@@ -52,25 +67,46 @@ pub fn find_matching_rule<'a>(
                 })
                 .unwrap_or("")
                 .to_string();
-            return Some((rule, needle));
+            return Some(RuleMatch {
+                rule,
+                needle,
+                captures: capture_vars(&re, &caps),
+            });
         }
     }
     None
 }
 
-/// Render a `match_output` rule's output template, resolving `{line_containing}`
-/// to the first line that contains the matched substring, and `{output}` to the
-/// full combined output.
-pub fn render_output(output_tmpl: &str, needle: &str, combined: &str) -> String {
+/// Collect numbered and named capture groups into template variables.
+///
+/// Groups that did not participate in the match resolve to empty strings so
+/// templates never render a literal `{2}`.
+fn capture_vars(re: &Regex, caps: &regex::Captures<'_>) -> HashMap<String, String> {
     let mut vars = HashMap::new();
-    if !needle.is_empty()
-        && let Some(line) = combined.lines().find(|l| l.contains(needle))
+    for i in 1..caps.len() {
+        let value = caps.get(i).map_or("", |m| m.as_str());
+        vars.insert(i.to_string(), value.to_string());
+    }
+    for name in re.capture_names().flatten() {
+        let value = caps.name(name).map_or("", |m| m.as_str());
+        vars.insert(name.to_string(), value.to_string());
+    }
+    vars
+}
+
+/// Render a `match_output` rule's output template, resolving `{line_containing}`
+/// to the first line that contains the matched substring, `{output}` to the
+/// full combined output, and regex captures to `{1}`, `{2}`, … / `{name}`.
+pub fn render_output(hit: &RuleMatch<'_>, combined: &str) -> String {
+    let mut vars = hit.captures.clone();
+    if !hit.needle.is_empty()
+        && let Some(line) = combined.lines().find(|l| l.contains(hit.needle.as_str()))
     {
         vars.insert("line_containing".to_string(), line.to_string());
     }
     vars.insert("output".to_string(), combined.to_string());
     template::render_template(
-        output_tmpl,
+        &hit.rule.output,
         &vars,
         &SectionMap::new(),
         &template::ChunkMap::new(),
@@ -112,8 +148,8 @@ mod tests {
             rule_contains("up-to-date", "ok (up-to-date)"),
             rule_contains("rejected", "rejected!"),
         ];
-        let (matched, _) = find_matching_rule(&rules, "Everything up-to-date").unwrap();
-        assert_eq!(matched.output, "ok (up-to-date)");
+        let matched = find_matching_rule(&rules, "Everything up-to-date").unwrap();
+        assert_eq!(matched.rule.output, "ok (up-to-date)");
     }
 
     #[test]
@@ -142,9 +178,8 @@ mod tests {
             r"0 Warning\(s\)\n\s+0 Error\(s\)",
             "ok (build succeeded)",
         )];
-        let (matched, _) =
-            find_matching_rule(&rules, "  0 Warning(s)\n  0 Error(s)\nDone").unwrap();
-        assert_eq!(matched.output, "ok (build succeeded)");
+        let matched = find_matching_rule(&rules, "  0 Warning(s)\n  0 Error(s)\nDone").unwrap();
+        assert_eq!(matched.rule.output, "ok (build succeeded)");
     }
 
     #[test]
@@ -164,8 +199,8 @@ mod tests {
             },
             rule_contains("fallback", "found"),
         ];
-        let (matched, _) = find_matching_rule(&rules, "try fallback").unwrap();
-        assert_eq!(matched.output, "found");
+        let matched = find_matching_rule(&rules, "try fallback").unwrap();
+        assert_eq!(matched.rule.output, "found");
     }
 
     #[test]
@@ -203,8 +238,8 @@ mod tests {
             output: "ok (synced)".to_string(),
             unless: Some(r"error|failed".to_string()),
         }];
-        let (matched, _) = find_matching_rule(&rules, "total size is 42\nall good").unwrap();
-        assert_eq!(matched.output, "ok (synced)");
+        let matched = find_matching_rule(&rules, "total size is 42\nall good").unwrap();
+        assert_eq!(matched.rule.output, "ok (synced)");
     }
 
     #[test]
@@ -222,6 +257,17 @@ mod tests {
 
     // --- render_output ---
 
+    /// Render `tmpl` as if a `contains` rule had matched `needle`.
+    fn render_output(tmpl: &str, needle: &str, combined: &str) -> String {
+        let rule = rule_contains(needle, tmpl);
+        let hit = RuleMatch {
+            rule: &rule,
+            needle: needle.to_string(),
+            captures: HashMap::new(),
+        };
+        super::render_output(&hit, combined)
+    }
+
     #[test]
     fn resolves_line_containing() {
         let output = render_output(
@@ -297,16 +343,68 @@ unless = "error|failed"
             "{line_containing}",
         )];
         let input = "Build started\n  0 Warning(s)\n  0 Error(s)\nDone";
-        let (_, needle) = find_matching_rule(&rules, input).unwrap();
+        let hit = find_matching_rule(&rules, input).unwrap();
         // The match starts on the "0 Warning(s)" line
-        assert_eq!(needle, "  0 Warning(s)");
+        assert_eq!(hit.needle, "  0 Warning(s)");
     }
 
     #[test]
     fn pattern_single_line_resolves_line_containing() {
         let rules = vec![rule_pattern(r"fatal:", "{line_containing}")];
         let input = "some preamble\nfatal: bad revision\nmore stuff";
-        let (_, needle) = find_matching_rule(&rules, input).unwrap();
-        assert_eq!(needle, "fatal: bad revision");
+        let hit = find_matching_rule(&rules, input).unwrap();
+        assert_eq!(hit.needle, "fatal: bad revision");
+    }
+
+    // --- capture groups ---
+
+    #[test]
+    fn pattern_numbered_captures_in_output() {
+        let rules = vec![rule_pattern(
+            r"Compiling (\S+) v(\d+\.\d+\.\d+)",
+            "built {1} {2}",
+        )];
+        let input = "   Compiling tokf v0.2.52\n    Finished dev";
+        let hit = find_matching_rule(&rules, input).unwrap();
+        assert_eq!(super::render_output(&hit, input), "built tokf 0.2.52");
+    }
+
+    #[test]
+    fn pattern_named_captures_in_output() {
+        let rules = vec![rule_pattern(
+            r"error\[(?P<code>E\d+)\]",
+            "compile error {code}",
+        )];
+        let input = "warning: unused\nerror[E0308]: mismatched types";
+        let hit = find_matching_rule(&rules, input).unwrap();
+        assert_eq!(hit.captures.get("code").unwrap(), "E0308");
+        assert_eq!(hit.captures.get("1").unwrap(), "E0308");
+        assert_eq!(super::render_output(&hit, input), "compile error E0308");
+    }
+
+    #[test]
+    fn pattern_unmatched_optional_group_is_empty() {
+        let rules = vec![rule_pattern(r"done(?: in (\d+)s)?", "[{1}]")];
+        let hit = find_matching_rule(&rules, "done").unwrap();
+        assert_eq!(super::render_output(&hit, "done"), "[]");
+    }
+
+    #[test]
+    fn contains_match_has_no_captures() {
+        let rules = vec![rule_contains("ok", "{1}")];
+        let hit = find_matching_rule(&rules, "ok").unwrap();
+        assert!(hit.captures.is_empty());
+    }
+
+    #[test]
+    fn toml_matches_alias_for_pattern() {
+        let rule: MatchOutputRule = toml::from_str(
+            r#"
+matches = "version (\\d+)"
+output = "v{1}"
+"#,
+        )
+        .unwrap();
+        assert_eq!(rule.pattern.unwrap(), r"version (\d+)");
     }
 }
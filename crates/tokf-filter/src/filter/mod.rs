@@ -1,7 +1,10 @@
 mod aggregate;
+mod budget;
 pub mod chunk;
 mod cleanup;
+mod collapse;
 mod dedup;
+pub mod diff;
 mod extract;
 mod group;
 pub mod json;
@@ -9,20 +12,32 @@ pub mod json;
 pub mod lua;
 mod match_output;
 mod parse;
+mod paths;
+pub mod redact;
 mod replace;
+mod report;
 pub mod section;
 mod skip;
+pub mod streaming;
 mod template;
+mod terminal;
+pub mod trace;
 mod tree;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+
+use std::borrow::Cow;
 
 use regex::Regex;
 
-use tokf_common::config::types::{FilterConfig, OutputBranch};
+use tokf_common::config::types::{FilterConfig, OutputBranch, Stream};
 
 use crate::CommandResult;
 
 use self::section::SectionMap;
 
+pub use self::template::{KNOWN_PIPES, TemplateInspection, inspect_template};
+
 /// Compile a list of regex pattern strings, silently dropping invalid ones.
 pub(crate) fn compile_patterns(patterns: &[String]) -> Vec<Regex> {
     patterns.iter().filter_map(|p| Regex::new(p).ok()).collect()
@@ -42,6 +57,14 @@ pub struct FilterOptions {
     /// active — tree-rendered lines are synthesized from path components,
     /// so per-line ANSI color spans don't survive structural rearrangement.
     pub preserve_color: bool,
+    /// Project root that `shorten_paths` makes absolute paths relative to.
+    /// `None` leaves absolute paths as they are (artifact paths are still
+    /// shortened).
+    pub project_root: Option<std::path::PathBuf>,
+    /// Extra template variables from the caller, such as the runner's
+    /// `{duration}` and `{max_rss}`. Variables the filter extracts itself
+    /// (`[json]`, Lua) take precedence.
+    pub vars: std::collections::HashMap<String, String>,
 }
 
 /// The result of applying a filter to command output.
@@ -60,7 +83,12 @@ struct BranchContext<'a> {
     has_sections: bool,
     has_json: bool,
     json_parsed: bool,
+    /// Caller-supplied vars, overridden by JSON-extracted vars and any
+    /// returned by the Lua script.
     json_vars: &'a std::collections::HashMap<String, String>,
+    /// Raw channel text exposed as `{stdout}` / `{stderr}`.
+    stdout: &'a str,
+    stderr: &'a str,
     top_level_tail: Option<usize>,
     top_level_head: Option<usize>,
 }
@@ -70,17 +98,22 @@ struct BranchContext<'a> {
 /// Processing order:
 ///
 /// ```text
-/// 1.   match_output  — substring check, first match wins
-/// 1.5. [[replace]]   — per-line regex transformations
+/// 1.   match_output  — substring/regex check, first match wins
+/// 1.5. shorten_paths + [[replace]] — per-line regex transformations
+/// 1.55 [[collapse]]  — fold runs of matching lines (stack frames)
 /// 1.6. strip_ansi / trim_lines — per-line cleanup
 /// 2.   skip/keep     — top-level pre-filtering
 /// 2.5. dedup         — collapse duplicate lines
-/// 2b.  lua_script    — escape hatch (if configured)
-/// 3.   parse         — alternative structured path
-/// 4.   sections      — state-machine line collection
-/// 5.   select branch — exit code 0 → on_success, else on_failure
+/// 2b.  [json]        — JSON extraction (replaces parse/sections/chunks)
+/// 2c.  sections      — state-machine line collection, plus chunks
+/// 2d.  lua_script    — escape hatch (if configured), sees sections/chunks
+/// 2e.  wasm_module   — WASM plugin escape hatch (`wasm` feature)
+/// 3.   parse         — alternative structured path (JSON formats join 2b)
+/// 5.   select branch — on_exit codes, then 0 → on_success, else on_failure
 /// 6.   apply branch  — render output or fallback
 /// 6.5. strip_empty_lines / collapse_empty_lines — post-process output
+/// 6.6. max_lines / max_tokens — absolute caps on the final output
+/// 6.7. redact        — mask secrets (see [`redact`])
 /// ```
 /// Dual-track line storage for color passthrough mode.
 ///
@@ -95,17 +128,39 @@ struct RawLines {
     display: Option<Vec<String>>,
 }
 
-/// Apply stage 1.5 + 1.6 pre-filter transforms (`replace`, `strip_ansi`, `trim_lines`).
+/// Apply stage 1.5 + 1.6 pre-filter transforms (`collapse_cr`,
+/// `shorten_paths`, `replace`, `collapse`, `strip_ansi`, `trim_lines`).
 ///
 /// When `preserve_color` is true, always strips ANSI for clean lines and keeps
 /// the original colored lines in `display` for final output restoration.
 fn build_raw_lines(combined: &str, config: &FilterConfig, opts: &FilterOptions) -> RawLines {
-    let initial: Vec<&str> = combined.lines().collect();
+    let collapsed = if config.collapse_cr {
+        terminal::collapse_cr(combined)
+    } else {
+        Cow::Borrowed(combined)
+    };
+    let shortener = config
+        .shorten_paths
+        .then(|| paths::PathShortener::new(opts.project_root.as_deref()));
+    let short_lines: Vec<Cow<'_, str>> = collapsed
+        .lines()
+        .map(|line| {
+            shortener
+                .as_ref()
+                .map_or(Cow::Borrowed(line), |s| s.apply(line))
+        })
+        .collect();
+    let initial: Vec<&str> = short_lines.iter().map(AsRef::as_ref).collect();
     let after_replace = if config.replace.is_empty() {
         initial.iter().map(ToString::to_string).collect()
     } else {
         replace::apply_replace(&config.replace, &initial)
     };
+    let after_replace = if config.collapse.is_empty() {
+        after_replace
+    } else {
+        collapse::apply_collapse(&config.collapse, after_replace)
+    };
 
     if opts.preserve_color {
         let display = after_replace.clone();
@@ -144,7 +199,11 @@ fn build_raw_lines(combined: &str, config: &FilterConfig, opts: &FilterOptions)
 /// through skip/keep/dedup which only filter without reordering. We scan
 /// `clean` in order, matching by pointer identity, and collect the
 /// corresponding `display` line for each match.
-fn restore_display_lines(clean: &[String], display: &[String], survivors: &[&str]) -> String {
+fn restore_display_lines<'a>(
+    clean: &[String],
+    display: &'a [String],
+    survivors: &[&str],
+) -> Vec<&'a str> {
     let mut result = Vec::with_capacity(survivors.len());
     let mut si = 0;
     for (i, c) in clean.iter().enumerate() {
@@ -159,34 +218,18 @@ fn restore_display_lines(clean: &[String], display: &[String], survivors: &[&str
             si += 1;
         }
     }
-    result.join("\n")
+    result
 }
 
-/// Load and run a Lua script with the given sandbox limits.
-///
-/// Returns `Some(output)` when the script replaces output, `None` for
-/// passthrough or on error (errors are printed to stderr).
-#[cfg(feature = "lua")]
-fn run_lua(
-    script_cfg: &tokf_common::config::types::ScriptConfig,
-    text: &str,
-    exit_code: i32,
-    args: &[String],
-    limits: &lua::SandboxLimits,
-) -> Option<String> {
-    match lua::load_source(script_cfg) {
-        Ok(source) => match lua::run_lua_script_sandboxed(&source, text, exit_code, args, limits) {
-            Ok(result) => result,
-            Err(e) => {
-                eprintln!("[tokf] lua script error: {e:#}");
-                None
-            }
-        },
-        Err(e) => {
-            eprintln!("[tokf] lua script error: {e:#}");
-            None
-        }
-    }
+#[cfg(feature = "wasm")]
+fn run_wasm(path: &str, lines: &[&str], exit_code: i32) -> Option<String> {
+    wasm::run_configured(path, &lines.join("\n"), exit_code)
+}
+
+#[cfg(not(feature = "wasm"))]
+fn run_wasm(path: &str, _lines: &[&str], _exit_code: i32) -> Option<String> {
+    eprintln!("[tokf] wasm_module '{path}' ignored: tokf was built without the `wasm` feature");
+    None
 }
 
 pub fn apply(
@@ -239,28 +282,39 @@ fn apply_internal(
     } else {
         result.combined.clone()
     };
-    if let Some((rule, needle)) =
-        match_output::find_matching_rule(&config.match_output, &match_text)
-    {
-        let output = match_output::render_output(&rule.output, &needle, &match_text);
+    if let Some(hit) = match_output::find_matching_rule(&config.match_output, &match_text) {
+        let output = match_output::render_output(&hit, &match_text);
         return FilterResult {
             output: finalize_output(config, output),
         };
     }
 
+    // Channel the rest of the pipeline reads (`source`, default combined).
+    let input = result.stream(config.source);
+
     // 1.5 + 1.6. Replace + per-line cleanup (strip_ansi, trim_lines)
-    let raw = build_raw_lines(&result.combined, config, opts);
+    let raw = build_raw_lines(input, config, opts);
     let clean_lines: Vec<&str> = raw.clean.iter().map(String::as_str).collect();
 
     // 2. Top-level skip/keep pre-filtering
     let lines = skip::apply_skip(&config.skip, &clean_lines);
-    let lines = skip::apply_keep(&config.keep, &lines);
+    let lines = skip::apply_keep_with_context(
+        &config.keep,
+        &lines,
+        config.keep_context_before,
+        config.keep_context_after,
+    );
 
-    // 2.5. Dedup
-    let lines = if config.dedup {
-        dedup::apply_dedup(&lines, config.dedup_window)
+    // 2.5. Dedup. With `dedup_annotate`, the occurrence counts are kept so
+    // the pre-filtered text below can mark collapsed lines with ` (xN)`.
+    let (lines, dedup_counts) = if config.dedup && config.dedup_annotate {
+        let counted = dedup::apply_dedup_counted(&lines, config.dedup_window);
+        let counts: Vec<usize> = counted.iter().map(|&(_, n)| n).collect();
+        (counted.into_iter().map(|(l, _)| l).collect(), Some(counts))
+    } else if config.dedup {
+        (dedup::apply_dedup(&lines, config.dedup_window), None)
     } else {
-        lines
+        (lines, None)
     };
 
     // 2.6. Tree transform — restructures path-list output into a directory
@@ -285,35 +339,78 @@ fn apply_internal(
             .and_then(|tree_cfg| tree::apply_tree(tree_cfg, &lines))
     };
 
-    // 2b. Lua script escape hatch (sandboxed)
+    // 2b. JSON extraction — when configured, replaces parse/sections/chunks.
+    // `has_json` = config declares [json] or a JSON [parse] format;
+    // `json_parsed` = input was valid JSON. When parsing fails, the pipeline
+    // falls through to fallback (raw output) instead of rendering templates
+    // with empty placeholders.
+    let json_extraction = json::run_configured(config, input);
+    let has_json = json_extraction.is_some();
+    let (json_parsed, extracted_vars, json_chunks) = json_extraction.unwrap_or_default();
+    let mut json_vars = opts.vars.clone();
+    json_vars.extend(extracted_vars);
+
+    // 2c. Collect sections and chunks (skipped when json ran — JSON replaces
+    //    line-based structural processing — and when [parse] takes over at
+    //    stage 3). Collected before Lua so scripts can read them.
+    //    DESIGN NOTE: section enter/exit regexes match against the original,
+    //    unmodified lines. If the command emits ANSI codes in marker lines,
+    //    set `strip_ansi = true` AND write patterns that match the raw text,
+    //    or configure the command to disable color (e.g. `--no-color`).
+    //    Sections may each read their own channel (`source`).
+    let structural = !has_json && config.parse.is_none();
+    let has_sections = structural && !config.section.is_empty();
+    let has_chunks = structural && !config.chunk.is_empty();
+
+    let sections = if has_sections {
+        section::collect_sections_by_source(&config.section, config.source, |s| result.stream(s))
+    } else {
+        SectionMap::new()
+    };
+
+    let mut chunks = if has_chunks {
+        let raw_lines: Vec<&str> = input.lines().collect();
+        chunk::process_chunks(&config.chunk, &raw_lines)
+    } else {
+        template::ChunkMap::new()
+    };
+
+    // Merge JSON-extracted chunks into the chunk map.
+    if has_json {
+        chunks.extend(json_chunks);
+    }
+
+    // 2d. Lua script escape hatch (sandboxed). A returned string replaces
+    // the output; a returned table adds template variables.
     #[cfg(feature = "lua")]
     if let Some(ref script_cfg) = config.lua_script {
         let clean_text = lines.join("\n");
-        if let Some(output) = run_lua(script_cfg, &clean_text, result.exit_code, args, lua_limits) {
-            return FilterResult {
-                output: finalize_output(config, output),
-            };
+        let lua_ctx = lua::LuaContext {
+            output: &clean_text,
+            exit_code: result.exit_code,
+            args,
+            sections: &sections,
+            chunks: &chunks,
+        };
+        match lua::run_configured(script_cfg, &lua_ctx, lua_limits) {
+            lua::LuaOutcome::Replace(output) => {
+                return FilterResult {
+                    output: finalize_output(config, output),
+                };
+            }
+            lua::LuaOutcome::Vars(vars) => json_vars.extend(vars),
+            lua::LuaOutcome::Passthrough => {}
         }
     }
 
-    // 2c. JSON extraction — when configured, replaces parse/sections/chunks.
-    // `has_json` = config declares [json]; `json_parsed` = input was valid JSON.
-    // When parsing fails, the pipeline falls through to fallback (raw output)
-    // instead of rendering templates with empty placeholders.
-    let has_json = config.json.is_some();
-    let (json_vars, json_chunks, json_parsed) = config.json.as_ref().map_or_else(
-        || {
-            (
-                std::collections::HashMap::new(),
-                template::ChunkMap::new(),
-                false,
-            )
-        },
-        |json_config| {
-            let (parsed, vars, chunks) = json::extract_json(&result.combined, json_config);
-            (vars, chunks, parsed)
-        },
-    );
+    // 2e. WASM plugin escape hatch (sandboxed). A result replaces the output.
+    if let Some(ref path) = config.wasm_module
+        && let Some(output) = run_wasm(path, &lines, result.exit_code)
+    {
+        return FilterResult {
+            output: finalize_output(config, output),
+        };
+    }
 
     // 3. If parse exists → parse+output pipeline (skipped when json ran)
     if !has_json && let Some(ref parse_config) = config.parse {
@@ -325,48 +422,22 @@ fn apply_internal(
         };
     }
 
-    // 4. Collect sections and chunks (skipped when json ran — JSON replaces
-    //    line-based structural processing).
-    //    DESIGN NOTE: section enter/exit regexes match against the original,
-    //    unmodified lines. If the command emits ANSI codes in marker lines,
-    //    set `strip_ansi = true` AND write patterns that match the raw text,
-    //    or configure the command to disable color (e.g. `--no-color`).
-    let has_sections = !has_json && !config.section.is_empty();
-    let needs_raw_lines = !has_json && (has_sections || !config.chunk.is_empty());
-    let raw_lines: Vec<&str> = if needs_raw_lines {
-        result.combined.lines().collect()
-    } else {
-        Vec::new()
-    };
-
-    let sections = if has_sections {
-        section::collect_sections(&config.section, &raw_lines)
-    } else {
-        SectionMap::new()
-    };
-
     // Restore display lines for color mode, join tree-rendered lines, or
     // join clean lines. The tree path takes priority over color restoration
     // because the rearranged structure can't carry per-line color spans.
     let pre_filtered = if let Some(ref t) = tree_lines {
         t.join("\n")
-    } else if let Some(ref display) = raw.display {
-        restore_display_lines(&raw.clean, display, &lines)
     } else {
-        lines.join("\n")
+        let shown = raw.display.as_ref().map_or_else(
+            || lines.clone(),
+            |display| restore_display_lines(&raw.clean, display, &lines),
+        );
+        dedup_counts.as_ref().map_or_else(
+            || shown.join("\n"),
+            |counts| dedup::join_annotated(&shown, counts),
+        )
     };
 
-    let mut chunks = if !has_json && !config.chunk.is_empty() {
-        chunk::process_chunks(&config.chunk, &raw_lines)
-    } else {
-        template::ChunkMap::new()
-    };
-
-    // Merge JSON-extracted chunks into the chunk map.
-    if has_json {
-        chunks.extend(json_chunks);
-    }
-
     // 5. Select branch by exit code
     let branch = select_branch(config, result.exit_code);
     let ctx = BranchContext {
@@ -376,24 +447,37 @@ fn apply_internal(
         has_json,
         json_parsed,
         json_vars: &json_vars,
+        stdout: result.stream(Stream::Stdout),
+        stderr: result.stream(Stream::Stderr),
         top_level_tail: config.tail,
         top_level_head: config.head,
     };
-    let output = branch.map_or_else(
-        || apply_fallback(config, &pre_filtered),
-        |b| {
-            apply_branch(b, &pre_filtered, &ctx)
-                .unwrap_or_else(|| apply_fallback(config, &pre_filtered))
-        },
-    );
+    let output = render_branch(config, branch, &pre_filtered, &ctx);
 
     FilterResult {
         output: finalize_output(config, output),
     }
 }
 
+/// Render the selected branch, or the fallback when there is none or the
+/// branch declines (e.g. sections collected nothing).
+fn render_branch(
+    config: &FilterConfig,
+    branch: Option<&OutputBranch>,
+    pre_filtered: &str,
+    ctx: &BranchContext<'_>,
+) -> String {
+    branch.map_or_else(
+        || apply_fallback(config, pre_filtered),
+        |b| {
+            apply_branch(b, pre_filtered, ctx)
+                .unwrap_or_else(|| apply_fallback(config, pre_filtered))
+        },
+    )
+}
+
 /// Final output processing: post-process (strip/collapse/truncate), apply
-/// `max_lines` cap, then apply `on_empty`.
+/// the `max_lines` and `max_tokens` caps, then apply `on_empty`.
 fn finalize_output(config: &FilterConfig, output: String) -> String {
     let mut output = cleanup::post_process_output(config, output);
 
@@ -409,17 +493,31 @@ fn finalize_output(config: &FilterConfig, output: String) -> String {
         }
     }
 
+    // max_tokens: estimated-token ceiling, keeps head + tail.
+    if let Some(max) = config.max_tokens {
+        output = budget::cap_tokens(output, max);
+    }
+
     if let Some(ref msg) = config.on_empty
         && output.trim().is_empty()
     {
         return msg.clone();
     }
-    output
+
+    // Secrets are masked last so nothing after this point can reintroduce them.
+    match redact::redact(config, &output) {
+        Cow::Borrowed(_) => output,
+        Cow::Owned(redacted) => redacted,
+    }
 }
 
 /// Select the output branch based on exit code.
-/// Exit code 0 → `on_success`, anything else → `on_failure`.
-const fn select_branch(config: &FilterConfig, exit_code: i32) -> Option<&OutputBranch> {
+/// The first `[[on_exit]]` entry listing the code wins; otherwise exit code
+/// 0 → `on_success`, anything else → `on_failure`.
+fn select_branch(config: &FilterConfig, exit_code: i32) -> Option<&OutputBranch> {
+    if let Some(exit) = config.on_exit.iter().find(|e| e.codes.contains(&exit_code)) {
+        return Some(&exit.branch);
+    }
     if exit_code == 0 {
         config.on_success.as_ref()
     } else {
@@ -473,6 +571,8 @@ fn apply_branch(branch: &OutputBranch, combined: &str, ctx: &BranchContext<'_>)
         }
         let mut vars = vars;
         vars.insert("output".to_string(), combined.to_string());
+        vars.insert("stdout".to_string(), ctx.stdout.to_string());
+        vars.insert("stderr".to_string(), ctx.stderr.to_string());
         // Merge JSON-extracted vars into the template context.
         vars.extend(ctx.json_vars.iter().map(|(k, v)| (k.clone(), v.clone())));
         return Some(template::render_template(
@@ -542,12 +642,21 @@ mod tests_color;
 mod tests_json;
 #[cfg(test)]
 #[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests_nested_section;
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
 mod tests_pipeline;
 #[cfg(test)]
 #[allow(clippy::unwrap_used, clippy::expect_used)]
 mod tests_rtk_compat;
 #[cfg(test)]
 #[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests_stream;
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests_streaming;
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
 mod tests_tree;
 #[cfg(test)]
 #[allow(clippy::unwrap_used, clippy::expect_used)]
@@ -91,7 +91,7 @@ mod tests {
     use std::collections::BTreeMap;
 
     use super::*;
-    use tokf_common::config::types::{ExtractRule, GroupConfig, LineExtract};
+    use tokf_common::config::types::{ExtractRule, GroupConfig, LineExtract, ParseFormat};
 
     fn git_status_parse_config() -> ParseConfig {
         let mut labels = BTreeMap::new();
@@ -102,6 +102,7 @@ mod tests {
         labels.insert("D ".to_string(), "deleted".to_string());
 
         ParseConfig {
+            format: ParseFormat::Text,
             branch: Some(LineExtract {
                 line: 1,
                 pattern: r"## (\S+?)(?:\.\.\.(\S+))?(?:\s+\[(.+)\])?$".to_string(),
@@ -114,6 +115,7 @@ mod tests {
                 },
                 labels,
             }),
+            extract: vec![],
         }
     }
 
@@ -155,8 +157,10 @@ mod tests {
     #[test]
     fn run_parse_no_branch_config() {
         let config = ParseConfig {
+            format: ParseFormat::Text,
             branch: None,
             group: git_status_parse_config().group,
+            extract: vec![],
         };
         let lines = vec!["M  src/main.rs", "?? new.txt"];
         let result = run_parse(&config, &lines);
@@ -177,12 +181,14 @@ mod tests {
     #[test]
     fn run_parse_branch_line_out_of_bounds() {
         let config = ParseConfig {
+            format: ParseFormat::Text,
             branch: Some(LineExtract {
                 line: 99,
                 pattern: r"## (\S+)".to_string(),
                 output: "{1}".to_string(),
             }),
             group: None,
+            extract: vec![],
         };
         let lines = vec!["only one line"];
         let result = run_parse(&config, &lines);
@@ -193,12 +199,14 @@ mod tests {
     #[test]
     fn run_parse_invalid_branch_regex() {
         let config = ParseConfig {
+            format: ParseFormat::Text,
             branch: Some(LineExtract {
                 line: 1,
                 pattern: "[invalid".to_string(),
                 output: "{1}".to_string(),
             }),
             group: None,
+            extract: vec![],
         };
         let lines = vec!["## main...origin/main"];
         let result = run_parse(&config, &lines);
@@ -0,0 +1,161 @@
+//! `shorten_paths`: a built-in replace step for build-tool path noise.
+//!
+//! - Absolute paths under the project root become relative
+//!   (`/home/ci/builds/app/src/lib.rs` → `src/lib.rs`, the root itself → `.`)
+//! - Deep paths inside build-output directories keep their first two and
+//!   last components (`target/debug/build/foo-1a2b/out/gen.rs` →
+//!   `target/debug/…/gen.rs`)
+
+use std::borrow::Cow;
+use std::path::Path;
+use std::sync::OnceLock;
+
+use regex::{Captures, Regex};
+
+/// Directory names whose contents are build or dependency artifacts.
+const ARTIFACT_DIRS: &[&str] = &[
+    "target",
+    "node_modules",
+    "build",
+    "dist",
+    ".gradle",
+    "site-packages",
+    "__pycache__",
+];
+
+/// Artifact paths with at least this many components below the artifact
+/// directory are shortened.
+const MIN_ARTIFACT_DEPTH: usize = 4;
+
+fn path_regex() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| {
+        // A run of path characters containing at least one `/`. Colons are
+        // excluded so `file.rs:10:5` locations keep their line numbers apart.
+        // SAFETY: pattern is a compile-time constant and always valid.
+        #[allow(clippy::expect_used)]
+        Regex::new(r#"[^\s'"`()\[\]{}<>:,;=]*/[^\s'"`()\[\]{}<>:,;=]+"#).expect("valid path regex")
+    })
+}
+
+/// Shortens paths in output lines. Built once per filter run.
+pub(super) struct PathShortener {
+    /// Project root without a trailing slash; `None` skips relativization.
+    root: Option<String>,
+}
+
+impl PathShortener {
+    pub(super) fn new(project_root: Option<&Path>) -> Self {
+        let root = project_root
+            .map(|p| p.to_string_lossy().trim_end_matches('/').to_string())
+            .filter(|r| !r.is_empty());
+        Self { root }
+    }
+
+    /// Shorten every path in `line`. Lines without a `/` are returned borrowed.
+    pub(super) fn apply<'a>(&self, line: &'a str) -> Cow<'a, str> {
+        if !line.contains('/') {
+            return Cow::Borrowed(line);
+        }
+        path_regex().replace_all(line, |caps: &Captures| {
+            let path = &caps[0];
+            let relative = self.relativize(path);
+            shorten_artifact_path(relative).into_owned()
+        })
+    }
+
+    fn relativize<'p>(&self, path: &'p str) -> &'p str {
+        let Some(ref root) = self.root else {
+            return path;
+        };
+        match path.strip_prefix(root.as_str()) {
+            Some("" | "/") => ".",
+            Some(rest) => rest.strip_prefix('/').unwrap_or(path),
+            None => path,
+        }
+    }
+}
+
+/// `target/debug/build/foo/out/gen.rs` → `target/debug/…/gen.rs`.
+fn shorten_artifact_path(path: &str) -> Cow<'_, str> {
+    let parts: Vec<&str> = path.split('/').collect();
+    let Some(dir) = parts.iter().position(|p| ARTIFACT_DIRS.contains(p)) else {
+        return Cow::Borrowed(path);
+    };
+    let below = &parts[dir + 1..];
+    if below.len() < MIN_ARTIFACT_DEPTH {
+        return Cow::Borrowed(path);
+    }
+    let mut kept: Vec<&str> = parts[..=dir + 1].to_vec();
+    kept.push("…");
+    kept.extend(below.last());
+    Cow::Owned(kept.join("/"))
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+
+    fn shortener(root: &str) -> PathShortener {
+        PathShortener::new(Some(Path::new(root)))
+    }
+
+    #[test]
+    fn relativizes_paths_under_root() {
+        let s = shortener("/home/ci/builds/app");
+        assert_eq!(
+            s.apply("error: /home/ci/builds/app/src/lib.rs:10:5: oops"),
+            "error: src/lib.rs:10:5: oops"
+        );
+        assert_eq!(s.apply("cd /home/ci/builds/app"), "cd .");
+    }
+
+    #[test]
+    fn leaves_sibling_directories_alone() {
+        let s = shortener("/home/ci/builds/app");
+        let line = "see /home/ci/builds/app2/README and /etc/hosts";
+        assert_eq!(s.apply(line), line);
+    }
+
+    #[test]
+    fn trailing_slash_on_root_is_ignored() {
+        let s = shortener("/work/proj/");
+        assert_eq!(s.apply("(/work/proj/a/b.py)"), "(a/b.py)");
+    }
+
+    #[test]
+    fn shortens_deep_artifact_paths() {
+        let s = PathShortener::new(None);
+        assert_eq!(
+            s.apply("Compiling target/debug/build/foo-1a2b/out/gen.rs"),
+            "Compiling target/debug/…/gen.rs"
+        );
+        assert_eq!(
+            s.apply("at node_modules/react-dom/cjs/dev/react-dom.js:1"),
+            "at node_modules/react-dom/…/react-dom.js:1"
+        );
+    }
+
+    #[test]
+    fn keeps_short_artifact_paths() {
+        let s = PathShortener::new(None);
+        let line = "wrote target/debug/deps/libfoo.rlib and src/build/mod.rs";
+        assert_eq!(s.apply(line), line);
+    }
+
+    #[test]
+    fn relativizes_before_shortening() {
+        let s = shortener("/w/app");
+        assert_eq!(
+            s.apply("/w/app/target/release/build/x/out/y/z.o"),
+            "target/release/…/z.o"
+        );
+    }
+
+    #[test]
+    fn lines_without_paths_are_borrowed() {
+        let s = shortener("/w/app");
+        assert!(matches!(s.apply("no paths here"), Cow::Borrowed(_)));
+    }
+}
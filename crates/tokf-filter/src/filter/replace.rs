@@ -20,22 +20,34 @@ struct CompiledRule<'a> {
 /// line are replaced in-place (like `Regex::replace_all`), preserving unmatched
 /// portions of the line.
 pub fn apply_replace(rules: &[ReplaceRule], lines: &[&str]) -> Vec<String> {
-    // Compile all regexes up front. Rules with invalid patterns are silently dropped.
-    let compiled: Vec<CompiledRule<'_>> = rules
-        .iter()
-        .filter_map(|r| {
-            Regex::new(&r.pattern).ok().map(|re| CompiledRule {
-                re,
-                output: r.output.as_str(),
-                replace_all: r.replace_all,
+    let replacer = Replacer::new(rules);
+    lines.iter().map(|line| replacer.apply(line)).collect()
+}
+
+/// `[[replace]]` rules compiled once, for applying line by line.
+pub(super) struct Replacer<'a> {
+    compiled: Vec<CompiledRule<'a>>,
+}
+
+impl<'a> Replacer<'a> {
+    /// Compile all regexes up front. Rules with invalid patterns are silently dropped.
+    pub(super) fn new(rules: &'a [ReplaceRule]) -> Self {
+        let compiled = rules
+            .iter()
+            .filter_map(|r| {
+                Regex::new(&r.pattern).ok().map(|re| CompiledRule {
+                    re,
+                    output: r.output.as_str(),
+                    replace_all: r.replace_all,
+                })
             })
-        })
-        .collect();
+            .collect();
+        Self { compiled }
+    }
 
-    lines
-        .iter()
-        .map(|line| apply_rules_to_line(&compiled, line))
-        .collect()
+    pub(super) fn apply(&self, line: &str) -> String {
+        apply_rules_to_line(&self.compiled, line)
+    }
 }
 
 fn apply_rules_to_line(compiled: &[CompiledRule<'_>], line: &str) -> String {
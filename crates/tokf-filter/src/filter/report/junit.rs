@@ -0,0 +1,127 @@
+use quick_xml::Reader;
+use quick_xml::XmlVersion;
+use quick_xml::escape::resolve_predefined_entity;
+use quick_xml::events::{BytesRef, BytesStart, Event};
+
+use super::{Status, TestCase, first_line};
+
+/// Parse a `JUnit` XML report into test cases.
+///
+/// Leading non-XML text (e.g. a runner banner) is skipped. Returns `None` if
+/// the document is malformed or has no `<testsuite>`/`<testsuites>` element.
+pub(super) fn parse(text: &str) -> Option<Vec<TestCase>> {
+    let start = text.find('<')?;
+    let mut reader = Reader::from_str(&text[start..]);
+    let mut state = State::default();
+    loop {
+        match reader.read_event().ok()? {
+            Event::Start(e) => state.open(&e, false),
+            Event::Empty(e) => state.open(&e, true),
+            Event::End(e) => state.close(e.local_name().as_ref()),
+            Event::Text(t) => state.push_body(&t.xml10_content()),
+            Event::CData(c) => state.push_body(&c.into_inner()),
+            Event::GeneralRef(r) => state.push_body(&resolve_ref(&r)),
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+    state.saw_suite.then_some(state.cases)
+}
+
+#[derive(Default)]
+struct State {
+    cases: Vec<TestCase>,
+    /// Names of the enclosing `<testsuite>` elements.
+    suites: Vec<String>,
+    /// The `<testcase>` currently open, if any.
+    current: Option<TestCase>,
+    /// Collecting the text body of a message-less `<failure>`/`<error>`/`<skipped>`.
+    body: Option<String>,
+    saw_suite: bool,
+}
+
+impl State {
+    fn open(&mut self, e: &BytesStart<'_>, empty: bool) {
+        match e.local_name().as_ref() {
+            "testsuites" => self.saw_suite = true,
+            "testsuite" => {
+                self.saw_suite = true;
+                if !empty {
+                    self.suites.push(attr(e, "name"));
+                }
+            }
+            "testcase" => {
+                let suite = Some(attr(e, "classname"))
+                    .filter(|c| !c.is_empty())
+                    .or_else(|| self.suites.last().cloned())
+                    .unwrap_or_default();
+                let mut case = TestCase::new(attr(e, "name"), suite);
+                case.time = attr(e, "time");
+                if empty {
+                    self.cases.push(case);
+                } else {
+                    self.current = Some(case);
+                }
+            }
+            tag @ ("failure" | "error" | "skipped") => {
+                let Some(case) = self.current.as_mut() else {
+                    return;
+                };
+                case.status = match tag {
+                    "failure" => Status::Failed,
+                    "error" => Status::Error,
+                    _ => Status::Skipped,
+                };
+                case.message = first_line(&attr(e, "message"));
+                if case.message.is_empty() && !empty {
+                    self.body = Some(String::new());
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn close(&mut self, name: &str) {
+        match name {
+            "testsuite" => {
+                self.suites.pop();
+            }
+            "testcase" => {
+                if let Some(case) = self.current.take() {
+                    self.cases.push(case);
+                }
+            }
+            "failure" | "error" | "skipped" => {
+                if let (Some(body), Some(case)) = (self.body.take(), self.current.as_mut()) {
+                    case.message = first_line(&body);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn push_body(&mut self, text: &str) {
+        if let Some(body) = self.body.as_mut() {
+            body.push_str(text);
+        }
+    }
+}
+
+/// Unescaped value of attribute `key`, or empty when absent or invalid.
+fn attr(e: &BytesStart<'_>, key: &str) -> String {
+    e.attributes()
+        .flatten()
+        .find(|a| a.key.local_name().as_ref() == key)
+        .and_then(|a| a.normalized_value(XmlVersion::Implicit1_0).ok())
+        .map(std::borrow::Cow::into_owned)
+        .unwrap_or_default()
+}
+
+/// Resolve a character or predefined entity reference (`&#60;`, `&lt;`).
+fn resolve_ref(r: &BytesRef<'_>) -> String {
+    if let Ok(Some(c)) = r.resolve_char_ref() {
+        return c.to_string();
+    }
+    let name = r.xml10_content();
+    resolve_predefined_entity(&name).map_or_else(|| format!("&{name};"), str::to_string)
+}
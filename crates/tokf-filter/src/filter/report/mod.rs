@@ -0,0 +1,129 @@
+//! Test-report input formats for `[parse]`: `JUnit` XML and TAP.
+//!
+//! Both parsers produce a flat list of [`TestCase`] records, which are exposed
+//! to templates as structured collections grouped by outcome.
+
+mod junit;
+mod tap;
+
+use std::collections::HashMap;
+
+use tokf_common::config::types::ParseFormat;
+
+use super::chunk::{ChunkData, ChunkItem};
+use super::json::JsonExtraction;
+use super::template::ChunkMap;
+
+/// Outcome of a single test case.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum Status {
+    Passed,
+    Failed,
+    /// `JUnit` `<error>` — an unexpected exception rather than an assertion.
+    Error,
+    Skipped,
+}
+
+impl Status {
+    const fn as_str(self) -> &'static str {
+        match self {
+            Self::Passed => "passed",
+            Self::Failed => "failed",
+            Self::Error => "error",
+            Self::Skipped => "skipped",
+        }
+    }
+
+    /// Outcome collection this status is listed under.
+    const fn bucket(self) -> &'static str {
+        match self {
+            Self::Failed | Self::Error => "failed",
+            other => other.as_str(),
+        }
+    }
+}
+
+/// One test case from a report.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) struct TestCase {
+    pub name: String,
+    /// `JUnit` `classname` (or enclosing suite name); empty for TAP.
+    pub suite: String,
+    pub status: Status,
+    /// First line of the failure/skip message, or empty.
+    pub message: String,
+    /// Duration in seconds as reported, or empty.
+    pub time: String,
+}
+
+impl TestCase {
+    const fn new(name: String, suite: String) -> Self {
+        Self {
+            name,
+            suite,
+            status: Status::Passed,
+            message: String::new(),
+            time: String::new(),
+        }
+    }
+
+    fn to_item(&self) -> ChunkItem {
+        ChunkItem::from([
+            ("name".to_string(), self.name.clone()),
+            ("suite".to_string(), self.suite.clone()),
+            ("status".to_string(), self.status.as_str().to_string()),
+            ("message".to_string(), self.message.clone()),
+            ("time".to_string(), self.time.clone()),
+        ])
+    }
+}
+
+/// Parse `text` as a test report in `format`.
+///
+/// Returns `None` for non-report formats. An input that doesn't parse as the
+/// requested format yields `(false, ..)` so the pipeline falls back to raw
+/// output, matching `[json]` behaviour.
+pub fn run(format: ParseFormat, text: &str) -> Option<JsonExtraction> {
+    let cases = match format {
+        ParseFormat::Junit => junit::parse(text),
+        ParseFormat::Tap => tap::parse(text),
+        ParseFormat::Text | ParseFormat::Json | ParseFormat::Jsonl => return None,
+    };
+    Some(cases.map_or_else(
+        || (false, HashMap::new(), ChunkMap::new()),
+        |cases| {
+            let (vars, chunks) = to_collections(&cases);
+            (true, vars, chunks)
+        },
+    ))
+}
+
+/// Expose test cases as `tests`, `passed`, `failed` and `skipped` collections,
+/// each with a matching `{name}_count` variable. `failed` includes errors.
+fn to_collections(cases: &[TestCase]) -> (HashMap<String, String>, ChunkMap) {
+    let mut vars = HashMap::new();
+    let mut chunks = ChunkMap::new();
+    for key in ["tests", "passed", "failed", "skipped"] {
+        let items: Vec<ChunkItem> = cases
+            .iter()
+            .filter(|c| key == "tests" || c.status.bucket() == key)
+            .map(TestCase::to_item)
+            .collect();
+        vars.insert(format!("{key}_count"), items.len().to_string());
+        chunks.insert(key.to_string(), ChunkData::Flat(items));
+    }
+    (vars, chunks)
+}
+
+/// First non-empty line of `text`, trimmed.
+fn first_line(text: &str) -> String {
+    text.lines()
+        .map(str::trim)
+        .find(|l| !l.is_empty())
+        .unwrap_or_default()
+        .to_string()
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used, clippy::panic)]
+mod tests;
@@ -0,0 +1,84 @@
+use std::sync::OnceLock;
+
+use regex::Regex;
+
+use super::{Status, TestCase, first_line};
+
+fn test_point_regex() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| {
+        // `ok 1 - description # SKIP reason` / `not ok 2 description # TODO`
+        // SAFETY: pattern is a compile-time constant and always valid.
+        #[allow(clippy::expect_used)]
+        Regex::new(r"^(not )?ok\b(?:\s+\d+)?\s*(?:-\s*)?([^#]*?)\s*(?:#\s*(\S+)\s*(.*))?$")
+            .expect("valid TAP regex")
+    })
+}
+
+/// Parse TAP (Test Anything Protocol) output into test cases.
+///
+/// Only top-level test points are read; indented subtests are ignored. A
+/// `# SKIP` directive marks a test skipped, and so does `# TODO` on a failing
+/// test (TODO failures don't count as failures in TAP). The `message:` key of
+/// a YAML diagnostic block following a failure becomes its message.
+///
+/// Returns `None` when the input has neither a plan (`1..N`) nor a test point.
+pub(super) fn parse(text: &str) -> Option<Vec<TestCase>> {
+    let mut cases: Vec<TestCase> = Vec::new();
+    let mut saw_plan = false;
+    let mut in_yaml = false;
+
+    for line in text.lines() {
+        let trimmed = line.trim();
+        if in_yaml {
+            if trimmed == "..." {
+                in_yaml = false;
+            } else if let Some(msg) = trimmed.strip_prefix("message:")
+                && let Some(case) = cases.last_mut()
+                && case.message.is_empty()
+            {
+                case.message = first_line(msg.trim().trim_matches(['"', '\'']));
+            }
+            continue;
+        }
+        if line.starts_with(char::is_whitespace) {
+            in_yaml = trimmed == "---";
+            continue;
+        }
+        if is_plan(trimmed) {
+            saw_plan = true;
+        } else if let Some(case) = parse_test_point(trimmed) {
+            cases.push(case);
+        }
+    }
+
+    (saw_plan || !cases.is_empty()).then_some(cases)
+}
+
+/// `1..N`, optionally followed by `# reason`.
+fn is_plan(line: &str) -> bool {
+    line.strip_prefix("1..")
+        .is_some_and(|rest| rest.starts_with(|c: char| c.is_ascii_digit()))
+}
+
+fn parse_test_point(line: &str) -> Option<TestCase> {
+    let caps = test_point_regex().captures(line)?;
+    let ok = caps.get(1).is_none();
+    let mut case = TestCase::new(caps[2].to_string(), String::new());
+    let directive = caps.get(3).map_or("", |m| m.as_str()).to_ascii_lowercase();
+    let reason = caps.get(4).map_or("", |m| m.as_str());
+
+    case.status = if directive.starts_with("skip") {
+        Status::Skipped
+    } else if directive == "todo" {
+        if ok { Status::Passed } else { Status::Skipped }
+    } else if ok {
+        Status::Passed
+    } else {
+        Status::Failed
+    };
+    if directive.starts_with("skip") || directive == "todo" {
+        case.message = reason.trim().to_string();
+    }
+    Some(case)
+}
@@ -0,0 +1,145 @@
+use super::*;
+
+const JUNIT: &str = r#"<?xml version="1.0" encoding="utf-8"?>
+<testsuites>
+  <testsuite name="pytest" tests="4">
+    <testcase classname="tests.test_math" name="test_add" time="0.001"/>
+    <testcase classname="tests.test_math" name="test_div" time="0.002">
+      <failure message="AssertionError: assert 1 == 2&#10;extra detail">trace</failure>
+    </testcase>
+    <testcase classname="tests.test_io" name="test_read">
+      <error>OSError: no such file &amp; no dir
+more</error>
+    </testcase>
+    <testcase name="test_slow">
+      <skipped message="too slow"/>
+    </testcase>
+  </testsuite>
+</testsuites>
+"#;
+
+fn items(chunks: &ChunkMap, key: &str) -> Vec<ChunkItem> {
+    match chunks.get(key) {
+        Some(ChunkData::Flat(items)) => items.clone(),
+        other => panic!("expected flat chunk {key}, got {other:?}"),
+    }
+}
+
+#[test]
+fn junit_counts_by_status() {
+    let (parsed, vars, _) = run(ParseFormat::Junit, JUNIT).unwrap();
+    assert!(parsed);
+    assert_eq!(vars["tests_count"], "4");
+    assert_eq!(vars["passed_count"], "1");
+    assert_eq!(vars["failed_count"], "2");
+    assert_eq!(vars["skipped_count"], "1");
+}
+
+#[test]
+fn junit_case_fields() {
+    let (_, _, chunks) = run(ParseFormat::Junit, JUNIT).unwrap();
+    let failed = items(&chunks, "failed");
+    assert_eq!(failed[0]["name"], "test_div");
+    assert_eq!(failed[0]["suite"], "tests.test_math");
+    assert_eq!(failed[0]["status"], "failed");
+    assert_eq!(failed[0]["message"], "AssertionError: assert 1 == 2");
+    assert_eq!(failed[0]["time"], "0.002");
+    // Message-less <error> falls back to the first body line, unescaped.
+    assert_eq!(failed[1]["status"], "error");
+    assert_eq!(failed[1]["message"], "OSError: no such file & no dir");
+}
+
+#[test]
+fn junit_suite_name_used_without_classname() {
+    let (_, _, chunks) = run(ParseFormat::Junit, JUNIT).unwrap();
+    let skipped = items(&chunks, "skipped");
+    assert_eq!(skipped[0]["suite"], "pytest");
+    assert_eq!(skipped[0]["message"], "too slow");
+}
+
+#[test]
+fn junit_skips_leading_banner() {
+    let input = format!("running tests...\n{JUNIT}");
+    let (parsed, vars, _) = run(ParseFormat::Junit, &input).unwrap();
+    assert!(parsed);
+    assert_eq!(vars["tests_count"], "4");
+}
+
+#[test]
+fn junit_invalid_input_not_parsed() {
+    let (parsed, vars, chunks) = run(ParseFormat::Junit, "all tests passed").unwrap();
+    assert!(!parsed);
+    assert!(vars.is_empty());
+    assert!(chunks.is_empty());
+
+    let (parsed, _, _) = run(ParseFormat::Junit, "<html><body/></html>").unwrap();
+    assert!(!parsed, "XML without a testsuite is not a report");
+}
+
+#[test]
+fn junit_empty_suite_parses() {
+    let (parsed, vars, _) = run(ParseFormat::Junit, r#"<testsuite name="x"/>"#).unwrap();
+    assert!(parsed);
+    assert_eq!(vars["tests_count"], "0");
+}
+
+const TAP: &str = "\
+TAP version 13
+1..5
+ok 1 - adds numbers
+not ok 2 - divides numbers
+  ---
+  message: 'expected 2, got 1'
+  severity: fail
+  ...
+ok 3 - reads file # SKIP no fixture
+not ok 4 - flaky network # TODO fix later
+ok 5 uploads
+    # Subtest: nested
+    ok 1 - ignored subtest
+";
+
+#[test]
+fn tap_statuses_and_directives() {
+    let (parsed, vars, chunks) = run(ParseFormat::Tap, TAP).unwrap();
+    assert!(parsed);
+    assert_eq!(vars["tests_count"], "5");
+    assert_eq!(vars["passed_count"], "2");
+    assert_eq!(vars["failed_count"], "1");
+    assert_eq!(vars["skipped_count"], "2");
+
+    let tests = items(&chunks, "tests");
+    assert_eq!(tests[0]["name"], "adds numbers");
+    assert_eq!(tests[4]["name"], "uploads");
+
+    let skipped = items(&chunks, "skipped");
+    assert_eq!(skipped[0]["message"], "no fixture");
+    assert_eq!(skipped[1]["message"], "fix later");
+}
+
+#[test]
+fn tap_yaml_message_attached_to_failure() {
+    let (_, _, chunks) = run(ParseFormat::Tap, TAP).unwrap();
+    let failed = items(&chunks, "failed");
+    assert_eq!(failed[0]["name"], "divides numbers");
+    assert_eq!(failed[0]["message"], "expected 2, got 1");
+}
+
+#[test]
+fn tap_plan_only_parses_as_empty_run() {
+    let (parsed, vars, _) = run(ParseFormat::Tap, "1..0 # no tests\n").unwrap();
+    assert!(parsed);
+    assert_eq!(vars["tests_count"], "0");
+}
+
+#[test]
+fn tap_without_test_points_not_parsed() {
+    let (parsed, _, _) = run(ParseFormat::Tap, "hello\nworld\n").unwrap();
+    assert!(!parsed);
+}
+
+#[test]
+fn non_report_formats_return_none() {
+    assert!(run(ParseFormat::Text, TAP).is_none());
+    assert!(run(ParseFormat::Json, TAP).is_none());
+}
@@ -2,7 +2,7 @@ use std::collections::HashMap;
 
 use regex::Regex;
 
-use tokf_common::config::types::Section;
+use tokf_common::config::types::{Section, Stream};
 
 /// Collected data for a single named section.
 pub type SectionMap = HashMap<String, SectionData>;
@@ -12,12 +12,26 @@ pub type SectionMap = HashMap<String, SectionData>;
 pub struct SectionData {
     pub lines: Vec<String>,
     pub blocks: Vec<String>,
+    /// One scope per time a section with `children` was entered, holding what
+    /// the children collected during that span. Empty without `children`.
+    pub scopes: Vec<SectionScope>,
+}
+
+/// What a section's `children` collected during one enter/exit span.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SectionScope {
+    /// The line that opened the span (empty for a stateless parent).
+    pub header: String,
+    pub sections: SectionMap,
 }
 
 impl SectionData {
-    /// Block count if `split_on` was used, otherwise line count.
+    /// Scope count for sections with children, otherwise block count if
+    /// `split_on` was used, otherwise line count.
     pub const fn count(&self) -> usize {
-        if self.blocks.is_empty() {
+        if !self.scopes.is_empty() {
+            self.scopes.len()
+        } else if self.blocks.is_empty() {
             self.lines.len()
         } else {
             self.blocks.len()
@@ -35,7 +49,7 @@ impl SectionData {
 }
 
 /// Internal per-section tracking during the collection pass.
-struct SectionRunner {
+struct SectionRunner<'s> {
     collect_as: String,
     enter_re: Option<Regex>,
     exit_re: Option<Regex>,
@@ -44,6 +58,10 @@ struct SectionRunner {
     is_stateful: bool,
     active: bool,
     collected: Vec<String>,
+    children: &'s [Section],
+    /// Header and lines of the span currently open, when there are children.
+    span: Option<(String, Vec<String>)>,
+    scopes: Vec<SectionScope>,
 }
 
 /// Compile an optional regex pattern, returning `None` if absent or invalid.
@@ -51,8 +69,8 @@ fn compile_optional(pattern: Option<&String>) -> Option<Regex> {
     pattern.and_then(|p| Regex::new(p).ok())
 }
 
-impl SectionRunner {
-    fn new(section: &Section) -> Option<Self> {
+impl<'s> SectionRunner<'s> {
+    fn new(section: &'s Section) -> Option<Self> {
         let collect_as = section.collect_as.as_ref()?;
 
         let enter_re = compile_optional(section.enter.as_ref());
@@ -80,9 +98,31 @@ impl SectionRunner {
             is_stateful,
             active: !is_stateful, // stateless sections are always active
             collected: Vec::new(),
+            children: &section.children,
+            // A stateless parent has a single span covering the whole input.
+            span: (!is_stateful && !section.children.is_empty())
+                .then(|| (String::new(), Vec::new())),
+            scopes: Vec::new(),
         })
     }
 
+    fn open_span(&mut self, header: &str) {
+        if !self.children.is_empty() {
+            self.span = Some((header.to_string(), Vec::new()));
+        }
+    }
+
+    /// Run the children over the span's lines and record the result.
+    fn close_span(&mut self) {
+        if let Some((header, lines)) = self.span.take() {
+            let lines: Vec<&str> = lines.iter().map(String::as_str).collect();
+            self.scopes.push(SectionScope {
+                header,
+                sections: collect(self.children.iter(), &lines),
+            });
+        }
+    }
+
     fn process_line(&mut self, line: &str) {
         if self.is_stateful {
             // Check enter/exit transitions
@@ -91,6 +131,7 @@ impl SectionRunner {
                     && re.is_match(line)
                 {
                     self.active = true;
+                    self.open_span(line);
                 }
                 return; // enter line not collected (or not active)
             }
@@ -100,10 +141,15 @@ impl SectionRunner {
                 && re.is_match(line)
             {
                 self.active = false;
+                self.close_span();
                 return; // exit line not collected
             }
         }
 
+        if let Some((_, ref mut lines)) = self.span {
+            lines.push(line.to_string());
+        }
+
         // Collect (filtered by match if present)
         self.collect_if_matches(line);
     }
@@ -118,10 +164,12 @@ impl SectionRunner {
         }
     }
 
-    fn finish(self) -> (String, SectionData) {
+    fn finish(mut self) -> (String, SectionData) {
+        self.close_span();
         let mut data = SectionData {
             lines: self.collected,
             blocks: Vec::new(),
+            scopes: self.scopes,
         };
 
         if let Some(ref re) = self.split_re {
@@ -160,7 +208,35 @@ fn split_into_blocks(lines: &[String], separator: &Regex) -> Vec<String> {
 ///
 /// If multiple sections share the same `collect_as` name, the last one wins (`HashMap` insert order).
 pub fn collect_sections(sections: &[Section], lines: &[&str]) -> SectionMap {
-    let mut runners: Vec<SectionRunner> = sections.iter().filter_map(SectionRunner::new).collect();
+    collect(sections.iter(), lines)
+}
+
+/// Run section definitions over the channels they read.
+///
+/// Each section reads the channel named by its `source`, or `default` when
+/// unset. Sections are grouped by channel so every channel is split once.
+pub fn collect_sections_by_source<'a>(
+    sections: &[Section],
+    default: Stream,
+    text_for: impl Fn(Stream) -> &'a str,
+) -> SectionMap {
+    let mut map = SectionMap::new();
+    for stream in [Stream::Combined, Stream::Stdout, Stream::Stderr] {
+        let mut group = sections
+            .iter()
+            .filter(|s| s.source.unwrap_or(default) == stream)
+            .peekable();
+        if group.peek().is_none() {
+            continue;
+        }
+        let lines: Vec<&str> = text_for(stream).lines().collect();
+        map.extend(collect(group, &lines));
+    }
+    map
+}
+
+fn collect<'s>(sections: impl Iterator<Item = &'s Section>, lines: &[&str]) -> SectionMap {
+    let mut runners: Vec<SectionRunner> = sections.filter_map(SectionRunner::new).collect();
 
     for line in lines {
         for runner in &mut runners {
@@ -168,7 +244,30 @@ pub fn collect_sections(sections: &[Section], lines: &[&str]) -> SectionMap {
         }
     }
 
-    runners.into_iter().map(SectionRunner::finish).collect()
+    let mut map: SectionMap = runners.into_iter().map(SectionRunner::finish).collect();
+    let nested = flatten_scopes(&map);
+    for (name, data) in nested {
+        map.entry(name).or_insert(data);
+    }
+    map
+}
+
+/// Merge every scope's child collections into one entry per child name, so
+/// `{child}` and aggregates see all of a child's lines across every span.
+fn flatten_scopes(map: &SectionMap) -> SectionMap {
+    // Walk parents in name order so merged lines don't depend on hash order.
+    let mut parents: Vec<(&String, &SectionData)> = map.iter().collect();
+    parents.sort_by_key(|(name, _)| *name);
+    let mut flat = SectionMap::new();
+    for scope in parents.iter().flat_map(|(_, data)| &data.scopes) {
+        for (name, child) in &scope.sections {
+            let entry = flat.entry(name.clone()).or_default();
+            entry.lines.extend(child.lines.iter().cloned());
+            entry.blocks.extend(child.blocks.iter().cloned());
+            entry.scopes.extend(child.scopes.iter().cloned());
+        }
+    }
+    flat
 }
 
 #[cfg(test)]
@@ -191,6 +290,8 @@ mod tests {
             match_pattern: match_pat.map(String::from),
             split_on: split_on.map(String::from),
             collect_as: Some(collect_as.to_string()),
+            source: None,
+            children: vec![],
         }
     }
 
@@ -360,6 +461,8 @@ mod tests {
             match_pattern: None,
             split_on: None,
             collect_as: Some("data".to_string()),
+            source: None,
+            children: vec![],
         }];
         let lines: Vec<&str> = vec!["a", "b"];
         let map = collect_sections(&sections, &lines);
@@ -376,6 +479,8 @@ mod tests {
             match_pattern: None,
             split_on: None,
             collect_as: None,
+            source: None,
+            children: vec![],
         }];
         let lines: Vec<&str> = vec!["BEGIN", "a", "END"];
         let map = collect_sections(&sections, &lines);
@@ -417,6 +522,7 @@ mod tests {
         let data = SectionData {
             lines: vec!["a".to_string(), "b".to_string()],
             blocks: Vec::new(),
+            scopes: Vec::new(),
         };
         assert_eq!(data.count(), 2);
         assert_eq!(data.items(), &["a".to_string(), "b".to_string()]);
@@ -427,6 +533,7 @@ mod tests {
         let data = SectionData {
             lines: vec!["a".to_string(), "b".to_string()],
             blocks: vec!["block1".to_string()],
+            scopes: Vec::new(),
         };
         assert_eq!(data.count(), 1);
         assert_eq!(data.items(), &["block1".to_string()]);
@@ -441,6 +548,8 @@ mod tests {
             match_pattern: None,
             split_on: None,
             collect_as: Some("data".to_string()),
+            source: None,
+            children: vec![],
         }];
         let lines: Vec<&str> = vec!["BEGIN", "a"];
         let map = collect_sections(&sections, &lines);
@@ -456,6 +565,8 @@ mod tests {
             match_pattern: Some("[invalid".to_string()),
             split_on: None,
             collect_as: Some("data".to_string()),
+            source: None,
+            children: vec![],
         }];
         let lines: Vec<&str> = vec!["a", "b"];
         let map = collect_sections(&sections, &lines);
@@ -471,6 +582,8 @@ mod tests {
             match_pattern: None,
             split_on: Some("[invalid".to_string()),
             collect_as: Some("data".to_string()),
+            source: None,
+            children: vec![],
         }];
         let lines: Vec<&str> = vec!["BEGIN", "a", "END"];
         let map = collect_sections(&sections, &lines);
@@ -35,6 +35,38 @@ pub fn apply_keep<'a>(patterns: &[String], lines: &[&'a str]) -> Vec<&'a str> {
     filter_lines(patterns, lines, true)
 }
 
+/// Like [`apply_keep`], but also retain up to `before` lines preceding and
+/// `after` lines following each match (like `grep -B`/`-A`).
+///
+/// Overlapping context windows are merged; every line appears at most once
+/// and in its original order.
+pub fn apply_keep_with_context<'a>(
+    patterns: &[String],
+    lines: &[&'a str],
+    before: usize,
+    after: usize,
+) -> Vec<&'a str> {
+    if before == 0 && after == 0 {
+        return apply_keep(patterns, lines);
+    }
+    let compiled = compile_patterns(patterns);
+    if compiled.is_empty() {
+        return lines.to_vec();
+    }
+    let mut keep = vec![false; lines.len()];
+    for (i, line) in lines.iter().enumerate() {
+        if compiled.iter().any(|re| re.is_match(line)) {
+            let end = (i + after).min(lines.len() - 1);
+            keep[i.saturating_sub(before)..=end].fill(true);
+        }
+    }
+    lines
+        .iter()
+        .zip(keep)
+        .filter_map(|(line, kept)| kept.then_some(*line))
+        .collect()
+}
+
 #[cfg(test)]
 #[allow(clippy::unwrap_used, clippy::expect_used)]
 mod tests {
@@ -129,4 +161,61 @@ mod tests {
         let result = apply_skip(&patterns, &lines);
         assert_eq!(result, vec!["d4"]);
     }
+
+    #[test]
+    fn keep_context_after_retains_following_lines() {
+        let patterns = vec!["^error".to_string()];
+        let lines = vec![
+            "ok",
+            "error[E0308]: mismatched",
+            "  --> src/a.rs",
+            "help: try",
+            "ok",
+        ];
+        let result = apply_keep_with_context(&patterns, &lines, 0, 2);
+        assert_eq!(
+            result,
+            vec!["error[E0308]: mismatched", "  --> src/a.rs", "help: try"]
+        );
+    }
+
+    #[test]
+    fn keep_context_before_retains_preceding_lines() {
+        let patterns = vec!["FAIL".to_string()];
+        let lines = vec!["a", "b", "c", "FAIL x"];
+        let result = apply_keep_with_context(&patterns, &lines, 2, 0);
+        assert_eq!(result, vec!["b", "c", "FAIL x"]);
+    }
+
+    #[test]
+    fn keep_context_overlapping_windows_merge() {
+        let patterns = vec!["^m".to_string()];
+        let lines = vec!["x", "m1", "y", "m2", "z", "w"];
+        let result = apply_keep_with_context(&patterns, &lines, 1, 1);
+        assert_eq!(result, vec!["x", "m1", "y", "m2", "z"]);
+    }
+
+    #[test]
+    fn keep_context_clamps_at_edges() {
+        let patterns = vec!["^m".to_string()];
+        let lines = vec!["m1", "a", "m2"];
+        let result = apply_keep_with_context(&patterns, &lines, 5, 5);
+        assert_eq!(result, lines);
+    }
+
+    #[test]
+    fn keep_context_zero_matches_plain_keep() {
+        let patterns = vec!["^a".to_string()];
+        let lines = vec!["a", "b", "a2"];
+        assert_eq!(
+            apply_keep_with_context(&patterns, &lines, 0, 0),
+            apply_keep(&patterns, &lines)
+        );
+    }
+
+    #[test]
+    fn keep_context_empty_patterns_passthrough() {
+        let lines = vec!["a", "b"];
+        assert_eq!(apply_keep_with_context(&[], &lines, 1, 1), lines);
+    }
 }
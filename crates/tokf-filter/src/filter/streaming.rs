@@ -0,0 +1,253 @@
+//! Line-at-a-time filter engine.
+//!
+//! [`StreamingFilter`] accepts output as it is produced and reports progress
+//! through [`StreamEvent`]s, so callers can show results during long runs
+//! instead of waiting for the process to exit. [`StreamingFilter::finish`]
+//! returns the same output [`apply`](super::apply) would have produced for the
+//! whole input.
+//!
+//! Filters that only use line-level stages (replace, `strip_ansi`/`trim_lines`,
+//! skip/keep, dedup) plus branch rendering keep just the surviving lines in
+//! memory. Stages that need the whole output at once — `collapse_cr`,
+//! `[[collapse]]`, keep context lines, dedup counts, `match_output`, sections,
+//! chunks, `[json]`, `[parse]`, `[tree]`, Lua and WASM, color passthrough and
+//! `{stdout}`/`{stderr}` templates — make the engine buffer the raw input and
+//! run the regular pipeline at the end.
+//!
+//! Lines fed with [`StreamingFilter::push_line_from`] carry the channel they
+//! arrived on, so a filter reading one channel (`source`) sees only its lines,
+//! as in [`apply`](super::apply). Lines fed with [`StreamingFilter::push_line`]
+//! are treated as combined output whose channels are unknown.
+
+use std::borrow::Cow;
+
+use regex::Regex;
+
+use tokf_common::config::types::{FilterConfig, Stream};
+
+use crate::CommandResult;
+
+use super::chunk::{ChunkItem, ChunkStream};
+use super::dedup::Deduper;
+use super::paths::PathShortener;
+use super::replace::Replacer;
+use super::section::SectionMap;
+use super::{
+    BranchContext, FilterOptions, FilterResult, cleanup, compile_patterns, finalize_output, redact,
+    render_branch, select_branch, template,
+};
+
+/// Progress reported by [`StreamingFilter::push_line`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StreamEvent {
+    /// A line that survived the line-level stages (replace, cleanup,
+    /// skip/keep, dedup), with secrets masked. Later stages may still drop or
+    /// rewrite it.
+    Line(String),
+    /// A `[[chunk]]` block closed because the next `split_on` line (or the
+    /// end of input) arrived. The item holds that block's own extracted
+    /// fields; carry-forward and grouping only apply to the final output.
+    Chunk { collect_as: String, item: ChunkItem },
+}
+
+/// Incremental counterpart to [`apply`](super::apply).
+pub struct StreamingFilter<'c> {
+    config: &'c FilterConfig,
+    args: Vec<String>,
+    opts: FilterOptions,
+    shortener: Option<PathShortener>,
+    replacer: Replacer<'c>,
+    skip: Vec<Regex>,
+    keep: Vec<Regex>,
+    deduper: Option<Deduper>,
+    chunks: Vec<ChunkStream<'c>>,
+    /// Raw input, kept only when a whole-output stage is configured.
+    raw: Option<Vec<String>>,
+    /// Raw stdout and stderr lines, kept only when buffering for a stage that
+    /// reads a single channel.
+    channels: Option<(Vec<String>, Vec<String>)>,
+    /// Lines that survived the line-level stages.
+    kept: Vec<String>,
+}
+
+impl<'c> StreamingFilter<'c> {
+    pub fn new(config: &'c FilterConfig, args: &[String], opts: &FilterOptions) -> Self {
+        let raw = needs_buffer(config, opts).then(Vec::new);
+        let channels = (raw.is_some() && reads_a_channel(config)).then(Default::default);
+        Self {
+            config,
+            args: args.to_vec(),
+            opts: opts.clone(),
+            shortener: config
+                .shorten_paths
+                .then(|| PathShortener::new(opts.project_root.as_deref())),
+            replacer: Replacer::new(&config.replace),
+            skip: compile_patterns(&config.skip),
+            keep: compile_patterns(&config.keep),
+            deduper: config.dedup.then(|| Deduper::new(config.dedup_window)),
+            chunks: config.chunk.iter().filter_map(ChunkStream::new).collect(),
+            raw,
+            channels,
+            kept: Vec::new(),
+        }
+    }
+
+    /// Whether this filter buffers the full raw input (see module docs).
+    pub const fn is_buffering(&self) -> bool {
+        self.raw.is_some()
+    }
+
+    /// Feed one line of combined output (without its trailing newline).
+    pub fn push_line(&mut self, line: &str) -> Vec<StreamEvent> {
+        self.push_line_from(Stream::Combined, line)
+    }
+
+    /// Feed one line that arrived on `stream` (`Stdout` or `Stderr`).
+    pub fn push_line_from(&mut self, stream: Stream, line: &str) -> Vec<StreamEvent> {
+        if let Some(ref mut raw) = self.raw {
+            raw.push(line.to_string());
+        }
+        if let Some((ref mut stdout, ref mut stderr)) = self.channels {
+            match stream {
+                Stream::Stdout => stdout.push(line.to_string()),
+                Stream::Stderr => stderr.push(line.to_string()),
+                Stream::Combined => {}
+            }
+        }
+        let source = self.config.source;
+        if source != Stream::Combined && stream != Stream::Combined && stream != source {
+            return Vec::new();
+        }
+        let mut events = Vec::new();
+        for chunk in &mut self.chunks {
+            if let Some(item) = chunk.push(line) {
+                events.push(StreamEvent::Chunk {
+                    collect_as: chunk.collect_as().to_string(),
+                    item,
+                });
+            }
+        }
+        if let Some(clean) = self.line_stage(line) {
+            let shown = redact::redact(self.config, &clean).into_owned();
+            events.push(StreamEvent::Line(shown));
+            self.kept.push(clean);
+        }
+        events
+    }
+
+    /// End of input: close any open chunks and render the final output.
+    pub fn finish(mut self, exit_code: i32) -> (Vec<StreamEvent>, FilterResult) {
+        let events = self
+            .chunks
+            .iter_mut()
+            .filter_map(|chunk| {
+                chunk.finish().map(|item| StreamEvent::Chunk {
+                    collect_as: chunk.collect_as().to_string(),
+                    item,
+                })
+            })
+            .collect();
+        (events, self.render(exit_code))
+    }
+
+    /// Path shortening, replace, cleanup, skip/keep and dedup for one line.
+    fn line_stage(&mut self, line: &str) -> Option<String> {
+        let line = self
+            .shortener
+            .as_ref()
+            .map_or(Cow::Borrowed(line), |s| s.apply(line));
+        let mut clean = self.replacer.apply(&line);
+        if self.config.strip_ansi {
+            clean = cleanup::strip_ansi_from(&clean);
+        }
+        if self.config.trim_lines {
+            clean = clean.trim().to_string();
+        }
+        if self.skip.iter().any(|re| re.is_match(&clean)) {
+            return None;
+        }
+        if !self.keep.is_empty() && !self.keep.iter().any(|re| re.is_match(&clean)) {
+            return None;
+        }
+        if let Some(ref mut deduper) = self.deduper
+            && !deduper.admit(&clean)
+        {
+            return None;
+        }
+        Some(clean)
+    }
+
+    fn render(self, exit_code: i32) -> FilterResult {
+        if let Some(raw) = self.raw {
+            let (stdout, stderr) = self
+                .channels
+                .map(|(stdout, stderr)| (stdout.join("\n"), stderr.join("\n")))
+                .unwrap_or_default();
+            let result = CommandResult {
+                stdout,
+                stderr,
+                exit_code,
+                combined: raw.join("\n"),
+            };
+            return super::apply(self.config, &result, &self.args, &self.opts);
+        }
+
+        let pre_filtered = self.kept.join("\n");
+        let ctx = BranchContext {
+            sections: &SectionMap::new(),
+            chunks: &template::ChunkMap::new(),
+            has_sections: false,
+            has_json: false,
+            json_parsed: false,
+            json_vars: &self.opts.vars,
+            stdout: "",
+            stderr: "",
+            top_level_tail: self.config.tail,
+            top_level_head: self.config.head,
+        };
+        let branch = select_branch(self.config, exit_code);
+        let output = render_branch(self.config, branch, &pre_filtered, &ctx);
+        FilterResult {
+            output: finalize_output(self.config, output),
+        }
+    }
+}
+
+/// Whether a branch template reads `{stdout}` or `{stderr}`.
+fn uses_channel_vars(config: &FilterConfig) -> bool {
+    [config.on_success.as_ref(), config.on_failure.as_ref()]
+        .into_iter()
+        .flatten()
+        .chain(config.on_exit.iter().map(|e| &e.branch))
+        .filter_map(|b| b.output.as_deref())
+        .any(|o| o.contains("{stdout") || o.contains("{stderr"))
+}
+
+/// Whether any stage of `config` reads stdout or stderr on its own.
+fn reads_a_channel(config: &FilterConfig) -> bool {
+    config.source != Stream::Combined
+        || config
+            .section
+            .iter()
+            .any(|s| s.source.is_some_and(|s| s != Stream::Combined))
+        || uses_channel_vars(config)
+}
+
+/// Whether `config` uses a stage that needs the whole raw output.
+fn needs_buffer(config: &FilterConfig, opts: &FilterOptions) -> bool {
+    opts.preserve_color
+        || config.collapse_cr
+        || !config.collapse.is_empty()
+        || config.keep_context_before > 0
+        || config.keep_context_after > 0
+        || (config.dedup && config.dedup_annotate)
+        || uses_channel_vars(config)
+        || !config.match_output.is_empty()
+        || !config.section.is_empty()
+        || !config.chunk.is_empty()
+        || config.json.is_some()
+        || config.parse.is_some()
+        || config.tree.is_some()
+        || config.lua_script.is_some()
+        || config.wasm_module.is_some()
+}
@@ -0,0 +1,146 @@
+use std::cmp::Ordering;
+use std::collections::HashSet;
+
+use super::super::chunk::{ChunkData, ChunkItem};
+use super::{Value, format_chunk_item, parse_string_arg};
+
+/// `| sort` — order items ascending (numbers numerically, then text).
+///
+/// Structured items are ordered by their `format_chunk_item` representation.
+pub(super) fn apply_sort(value: Value) -> Value {
+    reorder(value, None, sorted_indices)
+}
+
+/// `| sort_by: "field"` — order structured items by one field's value.
+///
+/// Flat collections ignore the field and sort by item text.
+pub(super) fn apply_sort_by(arg: &str, value: Value) -> Value {
+    let field = parse_string_arg(arg);
+    reorder(value, Some(&field), sorted_indices)
+}
+
+/// `| reverse` — reverse item order.
+pub(super) fn apply_reverse(value: Value) -> Value {
+    reorder(value, None, |keys| (0..keys.len()).rev().collect())
+}
+
+/// `| unique` — drop repeated items, keeping the first occurrence.
+pub(super) fn apply_unique(value: Value) -> Value {
+    reorder(value, None, |keys| {
+        let mut seen = HashSet::new();
+        (0..keys.len())
+            .filter(|&i| seen.insert(keys[i].as_str()))
+            .collect()
+    })
+}
+
+/// `| first: N` — keep the first N items.
+pub(super) fn apply_first(arg: &str, value: Value) -> Value {
+    let Ok(n) = arg.trim().parse::<usize>() else {
+        return value;
+    };
+    reorder(value, None, |keys| (0..keys.len().min(n)).collect())
+}
+
+/// `| last: N` — keep the last N items.
+pub(super) fn apply_last(arg: &str, value: Value) -> Value {
+    let Ok(n) = arg.trim().parse::<usize>() else {
+        return value;
+    };
+    reorder(value, None, |keys| {
+        (keys.len().saturating_sub(n)..keys.len()).collect()
+    })
+}
+
+/// Name under which `| group_by` exposes each group's original items.
+const GROUP_ITEMS_KEY: &str = "items";
+
+/// `| group_by: "field"` — group structured items by a field value.
+///
+/// Numeric fields are summed into each group (same merge as chunk
+/// `group_by`). Inside a following `each`, the group's original items are
+/// available as `{items}`. Tree collections regroup their top-level groups;
+/// flat string collections pass through unchanged.
+pub(super) fn apply_group_by(arg: &str, value: Value) -> Value {
+    let field = parse_string_arg(arg);
+    let items = match value {
+        Value::StructuredCollection(items) => items,
+        Value::TreeCollection { groups, .. } => groups,
+        other => return other,
+    };
+    Value::from_chunk(ChunkData::grouped(&items, &[field], GROUP_ITEMS_KEY))
+}
+
+/// Sort key ordering: numeric values first (ascending), then text (lexicographic).
+///
+/// This is a total order, so it is safe to use with `sort_by`.
+fn compare_keys(a: &str, b: &str) -> Ordering {
+    match (a.trim().parse::<f64>(), b.trim().parse::<f64>()) {
+        (Ok(x), Ok(y)) => x.total_cmp(&y),
+        (Ok(_), Err(_)) => Ordering::Less,
+        (Err(_), Ok(_)) => Ordering::Greater,
+        (Err(_), Err(_)) => a.cmp(b),
+    }
+}
+
+/// Stable ascending sort, returned as a permutation of indices.
+fn sorted_indices(keys: &[String]) -> Vec<usize> {
+    let mut idx: Vec<usize> = (0..keys.len()).collect();
+    idx.sort_by(|&a, &b| compare_keys(&keys[a], &keys[b]));
+    idx
+}
+
+/// Key used to compare a structured item: a single field, or the whole item.
+fn item_key(item: &ChunkItem, field: Option<&str>) -> String {
+    field.map_or_else(
+        || format_chunk_item(item),
+        |f| item.get(f).cloned().unwrap_or_default(),
+    )
+}
+
+/// Pick `indices` from `items`, in that order.
+fn take_indices<T: Clone>(items: &[T], indices: &[usize]) -> Vec<T> {
+    indices.iter().map(|&i| items[i].clone()).collect()
+}
+
+/// Apply a selection/permutation to any collection value.
+///
+/// `pick` receives one comparison key per item and returns the indices to
+/// keep, in output order. Tree groups stay aligned with their children.
+/// Plain strings pass through unchanged.
+fn reorder(value: Value, field: Option<&str>, pick: impl FnOnce(&[String]) -> Vec<usize>) -> Value {
+    match value {
+        Value::Collection(items) => {
+            let idx = pick(&items);
+            Value::Collection(take_indices(&items, &idx))
+        }
+        Value::StructuredCollection(items) => {
+            let keys: Vec<String> = items.iter().map(|i| item_key(i, field)).collect();
+            let idx = pick(&keys);
+            Value::StructuredCollection(take_indices(&items, &idx))
+        }
+        Value::TreeCollection {
+            groups,
+            children_key,
+            children,
+            subgroups,
+        } => {
+            let keys: Vec<String> = groups.iter().map(|i| item_key(i, field)).collect();
+            let idx = pick(&keys);
+            Value::TreeCollection {
+                groups: take_indices(&groups, &idx),
+                children_key,
+                children: take_indices(&children, &idx),
+                subgroups,
+            }
+        }
+        Value::SectionTree { headers, scopes } => {
+            let idx = pick(&headers);
+            Value::SectionTree {
+                headers: take_indices(&headers, &idx),
+                scopes: take_indices(&scopes, &idx),
+            }
+        }
+        s @ Value::Str(_) => s,
+    }
+}
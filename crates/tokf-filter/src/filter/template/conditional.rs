@@ -0,0 +1,170 @@
+use std::borrow::Cow;
+
+use super::{TemplateContext, Value, apply_pipe, find_expressions, resolve_variable, split_pipes};
+
+/// Classification of a top-level `{...}` expression for conditional handling.
+enum Tag<'a> {
+    If { negate: bool, expr: &'a str },
+    Else,
+    End,
+    Other,
+}
+
+fn classify(inner: &str) -> Tag<'_> {
+    let trimmed = inner.trim();
+    if let Some(cond) = trimmed.strip_prefix("if ") {
+        let cond = cond.trim();
+        return cond.strip_prefix('!').map_or(
+            Tag::If {
+                negate: false,
+                expr: cond,
+            },
+            |rest| Tag::If {
+                negate: true,
+                expr: rest.trim(),
+            },
+        );
+    }
+    match trimmed {
+        "else" => Tag::Else,
+        "end" => Tag::End,
+        _ => Tag::Other,
+    }
+}
+
+/// One open `{if}` block while scanning.
+struct Frame {
+    /// Whether the enclosing scope is emitting output.
+    parent_active: bool,
+    /// Whether the `{if}` condition evaluated truthy.
+    cond: bool,
+    /// Whether we are past `{else}`.
+    in_else: bool,
+}
+
+impl Frame {
+    const fn active(&self) -> bool {
+        self.parent_active && (self.cond != self.in_else)
+    }
+}
+
+/// Resolve `{if cond}…{else}…{end}` blocks, returning the template with only
+/// the taken branches left in place. All other expressions are kept verbatim
+/// for the regular expression pass.
+///
+/// `{else}` and `{end}` are only treated as tags inside an open `{if}` block,
+/// so templates that never use `{if}` are returned unchanged. An unterminated
+/// `{if}` extends to the end of the template.
+///
+/// A tag that is alone on its line (surrounded only by whitespace) removes the
+/// whole line, so block tags can be written on their own lines without leaving
+/// blank lines behind.
+pub(super) fn expand<'t>(
+    template: &'t str,
+    ctx: &TemplateContext<'_>,
+    depth: usize,
+) -> Cow<'t, str> {
+    let spans = find_expressions(template);
+    let has_if = spans
+        .iter()
+        .any(|&(s, e)| matches!(classify(&template[s + 1..e - 1]), Tag::If { .. }));
+    if !has_if {
+        return Cow::Borrowed(template);
+    }
+
+    let mut out = String::with_capacity(template.len());
+    let mut stack: Vec<Frame> = Vec::new();
+    let mut pos = 0;
+
+    for (start, end) in spans {
+        let tag = classify(&template[start + 1..end - 1]);
+        let is_block_tag = match tag {
+            Tag::If { .. } => true,
+            Tag::Else | Tag::End => !stack.is_empty(),
+            Tag::Other => false,
+        };
+        let active = stack.last().is_none_or(Frame::active);
+
+        if !is_block_tag {
+            if active {
+                out.push_str(&template[pos..end]);
+            }
+            pos = end;
+            continue;
+        }
+
+        let (cut_start, cut_end) = standalone_bounds(template, start, end);
+        if active {
+            out.push_str(&template[pos..cut_start]);
+        }
+        pos = cut_end;
+
+        match tag {
+            Tag::If { negate, expr } => {
+                // Skip evaluation inside inactive branches — the result is unused.
+                let cond = active && is_truthy(expr, ctx, depth) != negate;
+                stack.push(Frame {
+                    parent_active: active,
+                    cond,
+                    in_else: false,
+                });
+            }
+            Tag::Else => {
+                if let Some(frame) = stack.last_mut() {
+                    frame.in_else = true;
+                }
+            }
+            Tag::End => {
+                stack.pop();
+            }
+            Tag::Other => {}
+        }
+    }
+
+    if stack.last().is_none_or(Frame::active) {
+        out.push_str(&template[pos..]);
+    }
+    Cow::Owned(out)
+}
+
+/// Widen a tag span to its whole line (including the trailing newline) when
+/// the tag is the only non-whitespace content on that line.
+fn standalone_bounds(template: &str, start: usize, end: usize) -> (usize, usize) {
+    let line_start = template[..start].rfind('\n').map_or(0, |i| i + 1);
+    let line_end = template[end..]
+        .find('\n')
+        .map_or(template.len(), |i| end + i);
+    let before = &template[line_start..start];
+    let after = &template[end..line_end];
+    if !before.trim().is_empty() || !after.trim().is_empty() {
+        return (start, end);
+    }
+    let cut_end = if line_end < template.len() {
+        line_end + 1
+    } else {
+        line_end
+    };
+    (line_start, cut_end)
+}
+
+/// Evaluate a condition expression (variable plus optional pipe chain).
+///
+/// Falsy values: empty string, `"0"`, `"false"`, and empty collections.
+/// Everything else is truthy.
+fn is_truthy(expr: &str, ctx: &TemplateContext<'_>, depth: usize) -> bool {
+    let parts = split_pipes(expr);
+    let mut value = resolve_variable(parts[0].trim(), ctx);
+    for pipe_str in &parts[1..] {
+        value = apply_pipe(pipe_str.trim(), value, ctx, depth);
+    }
+    match value {
+        Value::Str(s) => {
+            let s = s.trim();
+            !(s.is_empty() || s == "0" || s == "false")
+        }
+        Value::Collection(items) => !items.is_empty(),
+        Value::StructuredCollection(items) => !items.is_empty(),
+        Value::TreeCollection { groups, .. } => !groups.is_empty(),
+        Value::SectionTree { headers, .. } => !headers.is_empty(),
+    }
+}
@@ -0,0 +1,79 @@
+use super::{MAX_DEPTH, find_expressions, parse_string_arg, split_pipes};
+
+/// Pipe names the template engine understands.
+pub const KNOWN_PIPES: &[&str] = &[
+    "join",
+    "each",
+    "truncate",
+    "truncate_width",
+    "truncate_middle",
+    "lines",
+    "default",
+    "sort",
+    "sort_by",
+    "reverse",
+    "unique",
+    "first",
+    "last",
+    "group_by",
+    "sum",
+    "avg",
+    "min",
+    "max",
+    "keep",
+    "where",
+];
+
+/// What a template refers to, found without rendering it.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct TemplateInspection {
+    /// Variables used outside `each:` sub-templates, by base name (`failures`
+    /// for `{failures.count}`). Item fields inside `each:` are only known at
+    /// render time, so they are not listed.
+    pub variables: Vec<String>,
+    /// Pipes not in [`KNOWN_PIPES`], including those inside `each:`.
+    /// The renderer passes values through unknown pipes unchanged.
+    pub unknown_pipes: Vec<String>,
+}
+
+/// List the variables and unknown pipes of `template`.
+pub fn inspect_template(template: &str) -> TemplateInspection {
+    let mut out = TemplateInspection::default();
+    walk(template, true, 0, &mut out);
+    out
+}
+
+fn walk(template: &str, top_level: bool, depth: usize, out: &mut TemplateInspection) {
+    if depth >= MAX_DEPTH {
+        return;
+    }
+    for (start, end) in find_expressions(template) {
+        let inner = template[start + 1..end - 1].trim();
+        if matches!(inner, "else" | "end") {
+            continue;
+        }
+        let expr = inner
+            .strip_prefix("if ")
+            .map_or(inner, |cond| cond.trim().trim_start_matches('!'));
+        let parts = split_pipes(expr);
+        let var = parts[0].trim();
+        let base = var.split_once('.').map_or(var, |(base, _)| base).trim();
+        if top_level && !base.is_empty() && !out.variables.iter().any(|v| v == base) {
+            out.variables.push(base.to_string());
+        }
+        for pipe in &parts[1..] {
+            let pipe = pipe.trim();
+            let (name, arg) = pipe
+                .split_once(':')
+                .map_or((pipe, None), |(name, arg)| (name.trim(), Some(arg.trim())));
+            if !KNOWN_PIPES.contains(&name) && !out.unknown_pipes.iter().any(|p| p == name) {
+                out.unknown_pipes.push(name.to_string());
+            }
+            if name == "each"
+                && let Some(arg) = arg
+            {
+                walk(&parse_string_arg(arg), false, depth + 1, out);
+            }
+        }
+    }
+}
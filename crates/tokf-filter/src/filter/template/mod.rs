@@ -1,15 +1,26 @@
+mod collection;
+mod conditional;
+mod inspect;
+mod numeric;
+mod text;
+
 use std::collections::HashMap;
 
 use regex::Regex;
 
 use super::chunk::{ChunkData, ChunkItem};
-use super::section::SectionMap;
+use super::section::{SectionData, SectionMap};
+
+pub use inspect::{KNOWN_PIPES, TemplateInspection, inspect_template};
 
 /// Chunks map: `collect_as` name → chunk data (flat or tree).
 pub type ChunkMap = HashMap<String, ChunkData>;
 
 /// Maximum recursion depth to prevent infinite loops.
-const MAX_DEPTH: usize = 3;
+///
+/// Each nested `each:` adds one level, so this also bounds how many tree
+/// levels (multi-field `group_by`) a single template can walk.
+const MAX_DEPTH: usize = 5;
 
 /// Bundles the three lookup sources for template variable resolution.
 struct TemplateContext<'a> {
@@ -20,6 +31,9 @@ struct TemplateContext<'a> {
 
 /// Render a template string, resolving `{var}`, `{var.count}`, and pipe chains.
 ///
+/// `{if var}…{else}…{end}` blocks are resolved first; see
+/// [`conditional::expand`] for truthiness rules.
+///
 /// Variables are looked up first in `vars` (string values), then in `sections`
 /// (collection values), then in `chunks` (structured collection values).
 /// Pipe operations transform the resolved value.
@@ -42,6 +56,9 @@ fn render_template_inner(template: &str, ctx: &TemplateContext<'_>, depth: usize
         return template.to_string();
     }
 
+    let template = conditional::expand(template, ctx, depth);
+    let template = template.as_ref();
+
     let expressions = find_expressions(template);
     if expressions.is_empty() {
         return template.to_string();
@@ -121,7 +138,45 @@ enum Value {
         groups: Vec<ChunkItem>,
         children_key: String,
         children: Vec<Vec<ChunkItem>>,
+        subgroups: Vec<String>,
     },
+    /// A section with `children`: one header per span, each with the child
+    /// sections collected inside it.
+    SectionTree {
+        headers: Vec<String>,
+        scopes: Vec<SectionMap>,
+    },
+}
+
+impl Value {
+    fn from_section(data: &SectionData) -> Self {
+        if data.scopes.is_empty() {
+            return Self::Collection(data.items().to_vec());
+        }
+        let (headers, scopes) = data
+            .scopes
+            .iter()
+            .map(|scope| (scope.header.clone(), scope.sections.clone()))
+            .unzip();
+        Self::SectionTree { headers, scopes }
+    }
+
+    fn from_chunk(data: ChunkData) -> Self {
+        match data {
+            ChunkData::Flat(items) => Self::StructuredCollection(items),
+            ChunkData::Tree {
+                groups,
+                children_key,
+                children,
+                subgroups,
+            } => Self::TreeCollection {
+                groups,
+                children_key,
+                children,
+                subgroups,
+            },
+        }
+    }
 }
 
 /// Evaluate a single expression: resolve variable, apply pipe chain.
@@ -152,6 +207,7 @@ fn evaluate_expression(expr: &str, ctx: &TemplateContext<'_>, depth: usize) -> S
             .map(format_chunk_item)
             .collect::<Vec<_>>()
             .join(", "),
+        Value::SectionTree { headers, .. } => headers.join(", "),
     }
 }
 
@@ -208,22 +264,11 @@ fn resolve_variable(name: &str, ctx: &TemplateContext<'_>) -> Value {
     }
 
     if let Some(section_data) = ctx.sections.get(name) {
-        return Value::Collection(section_data.items().to_vec());
+        return Value::from_section(section_data);
     }
 
     if let Some(chunk_data) = ctx.chunks.get(name) {
-        return match chunk_data {
-            ChunkData::Flat(items) => Value::StructuredCollection(items.clone()),
-            ChunkData::Tree {
-                groups,
-                children_key,
-                children,
-            } => Value::TreeCollection {
-                groups: groups.clone(),
-                children_key: children_key.clone(),
-                children: children.clone(),
-            },
-        };
+        return Value::from_chunk(chunk_data.clone());
     }
 
     Value::Str(String::new())
@@ -236,9 +281,31 @@ fn apply_pipe(pipe: &str, value: Value, ctx: &TemplateContext<'_>, depth: usize)
     } else if let Some(arg) = pipe.strip_prefix("each:") {
         apply_each(arg.trim(), value, ctx, depth)
     } else if let Some(arg) = pipe.strip_prefix("truncate:") {
-        apply_truncate(arg.trim(), value)
+        text::apply_truncate(arg.trim(), value)
+    } else if let Some(arg) = pipe.strip_prefix("truncate_width:") {
+        text::apply_truncate_width(arg.trim(), value)
+    } else if let Some(arg) = pipe.strip_prefix("truncate_middle:") {
+        text::apply_truncate_middle(arg.trim(), value)
     } else if pipe == "lines" {
         apply_lines(value)
+    } else if let Some(arg) = pipe.strip_prefix("default:") {
+        apply_default(arg.trim(), value)
+    } else if pipe == "sort" {
+        collection::apply_sort(value)
+    } else if let Some(arg) = pipe.strip_prefix("sort_by:") {
+        collection::apply_sort_by(arg.trim(), value)
+    } else if pipe == "reverse" {
+        collection::apply_reverse(value)
+    } else if pipe == "unique" {
+        collection::apply_unique(value)
+    } else if let Some(arg) = pipe.strip_prefix("first:") {
+        collection::apply_first(arg.trim(), value)
+    } else if let Some(arg) = pipe.strip_prefix("last:") {
+        collection::apply_last(arg.trim(), value)
+    } else if let Some(arg) = pipe.strip_prefix("group_by:") {
+        collection::apply_group_by(arg.trim(), value)
+    } else if let Some((op, arg)) = numeric::parse_pipe(pipe) {
+        numeric::apply_reduce(op, arg, value)
     } else if let Some(arg) = pipe
         .strip_prefix("keep:")
         .or_else(|| pipe.strip_prefix("where:"))
@@ -263,6 +330,7 @@ fn apply_join(arg: &str, value: Value) -> Value {
             let strs: Vec<String> = groups.iter().map(format_chunk_item).collect();
             Value::Str(strs.join(&sep))
         }
+        Value::SectionTree { headers, .. } => Value::Str(headers.join(&sep)),
         Value::Str(s) => Value::Str(s), // already a string
     }
 }
@@ -322,6 +390,7 @@ fn apply_each(arg: &str, value: Value, ctx: &TemplateContext<'_>, depth: usize)
             groups,
             children_key,
             children,
+            subgroups,
         } => {
             let mapped = groups
                 .iter()
@@ -329,7 +398,10 @@ fn apply_each(arg: &str, value: Value, ctx: &TemplateContext<'_>, depth: usize)
                 .enumerate()
                 .map(|(i, (item, child_items))| {
                     let mut local_chunks = ctx.chunks.clone();
-                    local_chunks.insert(children_key.clone(), ChunkData::Flat(child_items.clone()));
+                    local_chunks.insert(
+                        children_key.clone(),
+                        ChunkData::grouped(child_items, &subgroups, &children_key),
+                    );
                     let child_ctx = TemplateContext {
                         vars: ctx.vars,
                         sections: ctx.sections,
@@ -340,6 +412,9 @@ fn apply_each(arg: &str, value: Value, ctx: &TemplateContext<'_>, depth: usize)
                 .collect();
             Value::Collection(mapped)
         }
+        Value::SectionTree { headers, scopes } => {
+            each_section_scope(&tmpl, &headers, &scopes, ctx, depth)
+        }
         Value::Str(s) => {
             if s.is_empty() {
                 return Value::Collection(Vec::new());
@@ -349,6 +424,33 @@ fn apply_each(arg: &str, value: Value, ctx: &TemplateContext<'_>, depth: usize)
     }
 }
 
+/// `each` over a section with children: `{value}` is the span header and
+/// child section names resolve to that span's collections.
+fn each_section_scope(
+    tmpl: &str,
+    headers: &[String],
+    scopes: &[SectionMap],
+    ctx: &TemplateContext<'_>,
+    depth: usize,
+) -> Value {
+    let mapped = headers
+        .iter()
+        .zip(scopes)
+        .enumerate()
+        .map(|(i, (header, scope))| {
+            let mut local_sections = ctx.sections.clone();
+            local_sections.extend(scope.iter().map(|(k, v)| (k.clone(), v.clone())));
+            let child_ctx = TemplateContext {
+                vars: ctx.vars,
+                sections: &local_sections,
+                chunks: ctx.chunks,
+            };
+            render_each_item(tmpl, &child_ctx, depth, i, header.clone(), &HashMap::new())
+        })
+        .collect();
+    Value::Collection(mapped)
+}
+
 /// Format a chunk item as a human-readable string (for `{value}` in `each`).
 fn format_chunk_item(item: &ChunkItem) -> String {
     let mut parts: Vec<String> = item.iter().map(|(k, v)| format!("{k}={v}")).collect();
@@ -356,44 +458,6 @@ fn format_chunk_item(item: &ChunkItem) -> String {
     parts.join(", ")
 }
 
-/// `| truncate: N` — truncate a string to N characters.
-fn apply_truncate(arg: &str, value: Value) -> Value {
-    let n: usize = match arg.trim().parse() {
-        Ok(n) => n,
-        Err(_) => return value,
-    };
-
-    match value {
-        Value::Str(s) => {
-            let char_count = s.chars().count();
-            if char_count <= n {
-                Value::Str(s)
-            } else {
-                let truncated: String = s.chars().take(n).collect();
-                Value::Str(format!("{truncated}..."))
-            }
-        }
-        Value::Collection(items) => {
-            // Truncate each item
-            let truncated: Vec<String> = items
-                .into_iter()
-                .map(|s| {
-                    let char_count = s.chars().count();
-                    if char_count <= n {
-                        s
-                    } else {
-                        let t: String = s.chars().take(n).collect();
-                        format!("{t}...")
-                    }
-                })
-                .collect();
-            Value::Collection(truncated)
-        }
-        sc @ Value::StructuredCollection(_) => sc, // passthrough
-        tc @ Value::TreeCollection { .. } => tc,   // passthrough
-    }
-}
-
 /// `| lines` — split a string value into a collection on newline boundaries.
 ///
 /// Collections pass through unchanged.
@@ -403,6 +467,26 @@ fn apply_lines(value: Value) -> Value {
         c @ Value::Collection(_) => c,
         sc @ Value::StructuredCollection(_) => sc,
         tc @ Value::TreeCollection { .. } => tc,
+        st @ Value::SectionTree { .. } => st,
+    }
+}
+
+/// `| default: "text"` — substitute `text` when the value is empty.
+///
+/// Empty strings (after trimming) and empty collections are replaced; any
+/// other value passes through unchanged.
+fn apply_default(arg: &str, value: Value) -> Value {
+    let is_empty = match &value {
+        Value::Str(s) => s.trim().is_empty(),
+        Value::Collection(items) => items.is_empty(),
+        Value::StructuredCollection(items) => items.is_empty(),
+        Value::TreeCollection { groups, .. } => groups.is_empty(),
+        Value::SectionTree { headers, .. } => headers.is_empty(),
+    };
+    if is_empty {
+        Value::Str(parse_string_arg(arg))
+    } else {
+        value
     }
 }
 
@@ -429,6 +513,7 @@ fn apply_keep_pipe(arg: &str, value: Value) -> Value {
             groups,
             children_key,
             children,
+            subgroups,
         } => {
             let (filtered_groups, filtered_children): (Vec<_>, Vec<_>) = groups
                 .into_iter()
@@ -439,8 +524,17 @@ fn apply_keep_pipe(arg: &str, value: Value) -> Value {
                 groups: filtered_groups,
                 children_key,
                 children: filtered_children,
+                subgroups,
             }
         }
+        Value::SectionTree { headers, scopes } => {
+            let (headers, scopes) = headers
+                .into_iter()
+                .zip(scopes)
+                .filter(|(header, _)| re.is_match(header))
+                .unzip();
+            Value::SectionTree { headers, scopes }
+        }
         s @ Value::Str(_) => s,
     }
 }
@@ -485,3 +579,18 @@ fn unescape(s: &str) -> String {
 #[cfg(test)]
 #[allow(clippy::unwrap_used, clippy::expect_used)]
 mod tests;
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests_collection;
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests_conditional;
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests_inspect;
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests_numeric;
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests_text;
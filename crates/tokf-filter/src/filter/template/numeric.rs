@@ -0,0 +1,80 @@
+use super::{Value, format_chunk_item, parse_string_arg};
+
+/// Numeric reductions supported by the `sum`/`avg`/`min`/`max` pipes.
+#[derive(Clone, Copy)]
+pub(super) enum Reduce {
+    Sum,
+    Avg,
+    Min,
+    Max,
+}
+
+/// Recognise `sum`/`avg`/`min`/`max`, with or without a `: "field"` argument.
+pub(super) fn parse_pipe(pipe: &str) -> Option<(Reduce, Option<&str>)> {
+    let (name, arg) = match pipe.split_once(':') {
+        Some((name, arg)) => (name.trim(), Some(arg.trim())),
+        None => (pipe, None),
+    };
+    let op = match name {
+        "sum" => Reduce::Sum,
+        "avg" => Reduce::Avg,
+        "min" => Reduce::Min,
+        "max" => Reduce::Max,
+        _ => return None,
+    };
+    Some((op, arg))
+}
+
+/// `| sum: "field"`, `| avg: "field"`, `| min: "field"`, `| max: "field"`.
+///
+/// Structured collections read the named field from each item; flat
+/// collections (and bare `| sum` without an argument) parse the items
+/// themselves. Values that don't parse as numbers are skipped. `sum` of
+/// nothing is `0`; `avg`/`min`/`max` of nothing render as an empty string.
+pub(super) fn apply_reduce(op: Reduce, arg: Option<&str>, value: Value) -> Value {
+    let field = arg.map(parse_string_arg).filter(|f| !f.is_empty());
+    let raw: Vec<String> = match value {
+        Value::Str(s) => vec![s],
+        Value::Collection(items) => items,
+        Value::StructuredCollection(items) => items
+            .iter()
+            .map(|item| field_value(item, field.as_deref()))
+            .collect(),
+        Value::TreeCollection { groups, .. } => groups
+            .iter()
+            .map(|item| field_value(item, field.as_deref()))
+            .collect(),
+        Value::SectionTree { headers, .. } => headers,
+    };
+    let nums: Vec<f64> = raw
+        .iter()
+        .filter_map(|s| s.trim().parse::<f64>().ok())
+        .filter(|n| n.is_finite())
+        .collect();
+
+    let result = match op {
+        Reduce::Sum => Some(nums.iter().fold(0.0, |acc, n| acc + n)),
+        #[allow(clippy::cast_precision_loss)]
+        Reduce::Avg => (!nums.is_empty()).then(|| nums.iter().sum::<f64>() / nums.len() as f64),
+        Reduce::Min => nums.iter().copied().reduce(f64::min),
+        Reduce::Max => nums.iter().copied().reduce(f64::max),
+    };
+    Value::Str(result.map(format_number).unwrap_or_default())
+}
+
+fn field_value(item: &super::super::chunk::ChunkItem, field: Option<&str>) -> String {
+    field.map_or_else(
+        || format_chunk_item(item),
+        |f| item.get(f).cloned().unwrap_or_default(),
+    )
+}
+
+/// Render whole numbers without a decimal point and everything else with at
+/// most two decimals (trailing zeros trimmed).
+fn format_number(n: f64) -> String {
+    if n.fract() == 0.0 && n.abs() < 1e15 {
+        return format!("{n:.0}");
+    }
+    let s = format!("{n:.2}");
+    s.trim_end_matches('0').trim_end_matches('.').to_string()
+}
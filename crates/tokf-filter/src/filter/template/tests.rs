@@ -16,6 +16,7 @@ fn sections_with(name: &str, items: Vec<&str>) -> SectionMap {
         SectionData {
             lines: items.into_iter().map(String::from).collect(),
             blocks: Vec::new(),
+            scopes: Vec::new(),
         },
     );
     map
@@ -28,6 +29,7 @@ fn sections_with_blocks(name: &str, blocks: Vec<&str>) -> SectionMap {
         SectionData {
             lines: Vec::new(),
             blocks: blocks.into_iter().map(String::from).collect(),
+            scopes: Vec::new(),
         },
     );
     map
@@ -226,6 +228,7 @@ fn cargo_test_failure_template() {
                 "thread panicked at tests/a.rs".to_string(),
                 "thread panicked at tests/b.rs".to_string(),
             ],
+            scopes: Vec::new(),
         },
     );
     sections.insert(
@@ -233,6 +236,7 @@ fn cargo_test_failure_template() {
         SectionData {
             lines: vec!["test result: FAILED. 1 passed; 2 failed".to_string()],
             blocks: Vec::new(),
+            scopes: Vec::new(),
         },
     );
 
@@ -419,6 +423,7 @@ fn tree_chunks_with(
             groups: groups_items,
             children_key: children_key.to_string(),
             children: children_items,
+            subgroups: vec![],
         },
     );
     map
@@ -0,0 +1,267 @@
+use super::super::chunk::ChunkData;
+use crate::filter::section::SectionData;
+
+use super::*;
+
+fn sections_with(name: &str, items: Vec<&str>) -> SectionMap {
+    let mut map = SectionMap::new();
+    map.insert(
+        name.to_string(),
+        SectionData {
+            lines: items.into_iter().map(String::from).collect(),
+            blocks: Vec::new(),
+            scopes: Vec::new(),
+        },
+    );
+    map
+}
+
+fn item(pairs: &[(&str, &str)]) -> ChunkItem {
+    pairs
+        .iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect()
+}
+
+fn chunks_with(name: &str, items: Vec<ChunkItem>) -> ChunkMap {
+    let mut map = ChunkMap::new();
+    map.insert(name.to_string(), ChunkData::Flat(items));
+    map
+}
+
+fn render_section(tmpl: &str, items: Vec<&str>) -> String {
+    render_template(
+        tmpl,
+        &HashMap::new(),
+        &sections_with("xs", items),
+        &ChunkMap::new(),
+    )
+}
+
+fn tests_chunks() -> ChunkMap {
+    chunks_with(
+        "tests",
+        vec![
+            item(&[("name", "a"), ("ms", "120")]),
+            item(&[("name", "b"), ("ms", "9")]),
+            item(&[("name", "c"), ("ms", "4500")]),
+            item(&[("name", "d"), ("ms", "75")]),
+        ],
+    )
+}
+
+fn render_chunks(tmpl: &str, chunks: &ChunkMap) -> String {
+    render_template(tmpl, &HashMap::new(), &SectionMap::new(), chunks)
+}
+
+// --- flat collections ---
+
+#[test]
+fn sort_flat_lexicographic() {
+    let out = render_section("{xs | sort | join: \",\"}", vec!["pear", "apple", "fig"]);
+    assert_eq!(out, "apple,fig,pear");
+}
+
+#[test]
+fn sort_flat_numbers_numerically() {
+    let out = render_section("{xs | sort | join: \",\"}", vec!["10", "9", "100"]);
+    assert_eq!(out, "9,10,100");
+}
+
+#[test]
+fn sort_mixed_numbers_before_text() {
+    let out = render_section("{xs | sort | join: \",\"}", vec!["b", "2", "a", "1"]);
+    assert_eq!(out, "1,2,a,b");
+}
+
+#[test]
+fn unique_keeps_first_occurrence() {
+    let out = render_section("{xs | unique | join: \",\"}", vec!["x", "y", "x", "z", "y"]);
+    assert_eq!(out, "x,y,z");
+}
+
+#[test]
+fn first_and_last() {
+    let items = vec!["1", "2", "3", "4"];
+    assert_eq!(
+        render_section("{xs | first: 2 | join: \",\"}", items.clone()),
+        "1,2"
+    );
+    assert_eq!(
+        render_section("{xs | last: 2 | join: \",\"}", items.clone()),
+        "3,4"
+    );
+    assert_eq!(
+        render_section("{xs | last: 10 | join: \",\"}", items),
+        "1,2,3,4"
+    );
+}
+
+#[test]
+fn first_invalid_arg_passthrough() {
+    let out = render_section("{xs | first: many | join: \",\"}", vec!["a", "b"]);
+    assert_eq!(out, "a,b");
+}
+
+#[test]
+fn reverse_flat() {
+    let out = render_section("{xs | reverse | join: \",\"}", vec!["a", "b", "c"]);
+    assert_eq!(out, "c,b,a");
+}
+
+#[test]
+fn string_value_passes_through() {
+    let v: HashMap<String, String> = [("s".to_string(), "b\na".to_string())].into();
+    let out = render_template("{s | sort}", &v, &SectionMap::new(), &ChunkMap::new());
+    assert_eq!(out, "b\na");
+    let out = render_template(
+        "{s | lines | sort | join: \",\"}",
+        &v,
+        &SectionMap::new(),
+        &ChunkMap::new(),
+    );
+    assert_eq!(out, "a,b");
+}
+
+// --- structured collections ---
+
+#[test]
+fn sort_by_numeric_field() {
+    let out = render_chunks(
+        "{tests | sort_by: \"ms\" | each: \"{name}\" | join: \",\"}",
+        &tests_chunks(),
+    );
+    assert_eq!(out, "b,d,a,c");
+}
+
+#[test]
+fn slowest_tests_via_sort_reverse_first() {
+    let out = render_chunks(
+        "{tests | sort_by: \"ms\" | reverse | first: 2 | each: \"{name} {ms}ms\" | join: \"\\n\"}",
+        &tests_chunks(),
+    );
+    assert_eq!(out, "c 4500ms\na 120ms");
+}
+
+#[test]
+fn sort_by_missing_field_keeps_order() {
+    let out = render_chunks(
+        "{tests | sort_by: \"nope\" | each: \"{name}\" | join: \",\"}",
+        &tests_chunks(),
+    );
+    assert_eq!(out, "a,b,c,d");
+}
+
+#[test]
+fn unique_structured_items() {
+    let c = chunks_with(
+        "w",
+        vec![
+            item(&[("file", "a.rs")]),
+            item(&[("file", "b.rs")]),
+            item(&[("file", "a.rs")]),
+        ],
+    );
+    let out = render_chunks("{w | unique | each: \"{file}\" | join: \",\"}", &c);
+    assert_eq!(out, "a.rs,b.rs");
+}
+
+#[test]
+fn last_structured_items() {
+    let out = render_chunks(
+        "{tests | last: 1 | each: \"{name}\" | join: \",\"}",
+        &tests_chunks(),
+    );
+    assert_eq!(out, "d");
+}
+
+#[test]
+fn sort_by_tree_keeps_children_aligned() {
+    let mut c = ChunkMap::new();
+    c.insert(
+        "crates".to_string(),
+        ChunkData::Tree {
+            groups: vec![
+                item(&[("crate", "zeta"), ("n", "2")]),
+                item(&[("crate", "alpha"), ("n", "1")]),
+            ],
+            children_key: "tests".to_string(),
+            children: vec![
+                vec![item(&[("t", "z1")]), item(&[("t", "z2")])],
+                vec![item(&[("t", "a1")])],
+            ],
+            subgroups: vec![],
+        },
+    );
+    let out = render_chunks(
+        "{crates | sort_by: \"crate\" | each: \"{crate}:{tests | each: \\\"{t}\\\" | join: \\\"+\\\"}\" | join: \" \"}",
+        &c,
+    );
+    assert_eq!(out, "alpha:a1 zeta:z1+z2");
+}
+
+// --- group_by pipe ---
+
+#[test]
+fn group_by_pipe_merges_and_exposes_items() {
+    let c = chunks_with(
+        "tests",
+        vec![
+            item(&[("crate", "a"), ("name", "t1"), ("ms", "5")]),
+            item(&[("crate", "b"), ("name", "t2"), ("ms", "7")]),
+            item(&[("crate", "a"), ("name", "t3"), ("ms", "1")]),
+        ],
+    );
+    let out = render_chunks(
+        "{tests | group_by: \"crate\" | each: \"{crate} {ms}ms [{items | each: \\\"{name}\\\" | join: \\\",\\\"}]\" | join: \"; \"}",
+        &c,
+    );
+    assert_eq!(out, "a 6ms [t1,t3]; b 7ms [t2]");
+}
+
+#[test]
+fn group_by_pipe_count_of_groups_and_items() {
+    let c = chunks_with(
+        "tests",
+        vec![
+            item(&[("crate", "a")]),
+            item(&[("crate", "a")]),
+            item(&[("crate", "b")]),
+        ],
+    );
+    let out = render_chunks(
+        "{tests | group_by: \"crate\" | each: \"{crate}={items.count}\" | join: \",\"}",
+        &c,
+    );
+    assert_eq!(out, "a=2,b=1");
+}
+
+#[test]
+fn group_by_pipe_on_flat_strings_passthrough() {
+    let out = render_section("{xs | group_by: \"k\" | join: \",\"}", vec!["a", "b"]);
+    assert_eq!(out, "a,b");
+}
+
+#[test]
+fn multi_level_tree_expands_nested_children() {
+    let mut c = ChunkMap::new();
+    c.insert(
+        "crates".to_string(),
+        ChunkData::grouped(
+            &[
+                item(&[("crate", "core"), ("module", "io"), ("test", "read")]),
+                item(&[("crate", "core"), ("module", "net"), ("test", "dial")]),
+                item(&[("crate", "core"), ("module", "io"), ("test", "write")]),
+                item(&[("crate", "cli"), ("module", "args"), ("test", "parse")]),
+            ],
+            &["crate".to_string(), "module".to_string()],
+            "children",
+        ),
+    );
+    let inner = "{module}({children | each: \\\\\\\"{test}\\\\\\\" | join: \\\\\\\"+\\\\\\\"})";
+    let tmpl = format!(
+        "{{crates | each: \"{{crate}}: {{children | each: \\\"{inner}\\\" | join: \\\" \\\"}}\" | join: \"\\n\"}}"
+    );
+    let out = render_chunks(&tmpl, &c);
+    assert_eq!(out, "core: io(read+write) net(dial)\ncli: args(parse)");
+}
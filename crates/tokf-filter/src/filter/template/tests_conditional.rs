@@ -0,0 +1,189 @@
+use crate::filter::section::SectionData;
+
+use super::*;
+
+fn vars(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+    pairs
+        .iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect()
+}
+
+fn render(tmpl: &str, v: &HashMap<String, String>) -> String {
+    render_template(tmpl, v, &SectionMap::new(), &ChunkMap::new())
+}
+
+fn sections_with(name: &str, items: Vec<&str>) -> SectionMap {
+    let mut map = SectionMap::new();
+    map.insert(
+        name.to_string(),
+        SectionData {
+            lines: items.into_iter().map(String::from).collect(),
+            blocks: Vec::new(),
+            scopes: Vec::new(),
+        },
+    );
+    map
+}
+
+// --- {if} / {else} / {end} ---
+
+#[test]
+fn if_true_renders_body() {
+    let v = vars(&[("name", "tokf")]);
+    assert_eq!(render("{if name}hi {name}{end}!", &v), "hi tokf!");
+}
+
+#[test]
+fn if_false_omits_body() {
+    let v = vars(&[("name", "")]);
+    assert_eq!(render("{if name}hi {name}{end}!", &v), "!");
+}
+
+#[test]
+fn if_unknown_variable_is_false() {
+    assert_eq!(render("a{if missing}b{end}c", &HashMap::new()), "ac");
+}
+
+#[test]
+fn if_else_takes_else_branch() {
+    let v = vars(&[("errors", "0")]);
+    assert_eq!(
+        render("{if errors}{errors} errors{else}clean{end}", &v),
+        "clean"
+    );
+}
+
+#[test]
+fn if_negated() {
+    let v = vars(&[("warnings", "")]);
+    assert_eq!(render("{if !warnings}no warnings{end}", &v), "no warnings");
+}
+
+#[test]
+fn if_false_string_is_falsy() {
+    let v = vars(&[("flag", "false")]);
+    assert_eq!(render("{if flag}on{else}off{end}", &v), "off");
+}
+
+#[test]
+fn nested_if_blocks() {
+    let v = vars(&[("a", "1"), ("b", "")]);
+    assert_eq!(render("{if a}A{if b}B{else}notB{end}{end}", &v), "AnotB");
+    let v = vars(&[("a", ""), ("b", "1")]);
+    assert_eq!(
+        render("{if a}A{if b}B{else}notB{end}{else}none{end}", &v),
+        "none"
+    );
+}
+
+#[test]
+fn if_empty_section_omits_header() {
+    let sections = sections_with("failures", vec![]);
+    let result = render_template(
+        "{if failures}Failures:\n{failures | join: \"\\n\"}\n{end}done",
+        &HashMap::new(),
+        &sections,
+        &ChunkMap::new(),
+    );
+    assert_eq!(result, "done");
+}
+
+#[test]
+fn if_non_empty_section_renders_block() {
+    let sections = sections_with("failures", vec!["test_a", "test_b"]);
+    let result = render_template(
+        "{if failures}Failures: {failures | join: \", \"}{end}",
+        &HashMap::new(),
+        &sections,
+        &ChunkMap::new(),
+    );
+    assert_eq!(result, "Failures: test_a, test_b");
+}
+
+#[test]
+fn if_condition_with_pipe_chain() {
+    let v = vars(&[("output", "ok\nwarning: x\nok")]);
+    assert_eq!(
+        render(
+            "{if output | lines | keep: \"^warning\"}has warnings{end}",
+            &v
+        ),
+        "has warnings"
+    );
+    assert_eq!(
+        render("{if output | lines | keep: \"^error\"}has errors{end}", &v),
+        ""
+    );
+}
+
+#[test]
+fn standalone_tag_lines_are_removed() {
+    let v = vars(&[("summary", "3 passed"), ("failed", "")]);
+    let tmpl = "{summary}\n{if failed}\nFailed: {failed}\n{end}\nend";
+    assert_eq!(render(tmpl, &v), "3 passed\nend");
+}
+
+#[test]
+fn inline_tags_keep_surrounding_text() {
+    let v = vars(&[("x", "1")]);
+    assert_eq!(render("a {if x}b{end} c", &v), "a b c");
+}
+
+#[test]
+fn unterminated_if_extends_to_end() {
+    let v = vars(&[("x", "")]);
+    assert_eq!(render("a{if x}b", &v), "a");
+}
+
+#[test]
+fn else_and_end_outside_if_are_plain_vars() {
+    let v = vars(&[("end", "fin")]);
+    assert_eq!(render("{end}", &v), "fin");
+}
+
+#[test]
+fn if_inside_each_template() {
+    let sections = sections_with("items", vec!["a", ""]);
+    let result = render_template(
+        "{items | each: \"{if value}[{value}]{else}-{end}\" | join: \",\"}",
+        &HashMap::new(),
+        &sections,
+        &ChunkMap::new(),
+    );
+    assert_eq!(result, "[a],-");
+}
+
+// --- | default ---
+
+#[test]
+fn default_pipe_on_empty_string() {
+    let v = vars(&[("branch", "")]);
+    assert_eq!(
+        render("{branch | default: \"(detached)\"}", &v),
+        "(detached)"
+    );
+}
+
+#[test]
+fn default_pipe_on_missing_variable() {
+    assert_eq!(render("{nope | default: \"n/a\"}", &HashMap::new()), "n/a");
+}
+
+#[test]
+fn default_pipe_passes_non_empty_through() {
+    let v = vars(&[("branch", "main")]);
+    assert_eq!(render("{branch | default: \"(detached)\"}", &v), "main");
+}
+
+#[test]
+fn default_pipe_on_empty_collection() {
+    let sections = sections_with("errs", vec![]);
+    let result = render_template(
+        "{errs | join: \", \" | default: \"none\"}",
+        &HashMap::new(),
+        &sections,
+        &ChunkMap::new(),
+    );
+    assert_eq!(result, "none");
+}
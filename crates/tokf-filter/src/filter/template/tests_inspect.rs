@@ -0,0 +1,32 @@
+use super::*;
+
+#[test]
+fn lists_top_level_variables_by_base_name() {
+    let found = inspect_template("{failures.count} failed\n{failures | each: \"  {value}\"}");
+    assert_eq!(found.variables, vec!["failures"]);
+    assert!(found.unknown_pipes.is_empty());
+}
+
+#[test]
+fn conditional_tags_are_not_variables() {
+    let found = inspect_template("{if !warnings}clean{else}{warnings.count} warnings{end}");
+    assert_eq!(found.variables, vec!["warnings"]);
+}
+
+#[test]
+fn reports_unknown_pipes_including_inside_each() {
+    let found = inspect_template(
+        "{items | uniq | each: \"{name | upcase}\" | join: \", \"}\n{items | sum: \"n\"}",
+    );
+    assert_eq!(found.unknown_pipes, vec!["uniq", "upcase"]);
+    // `name` is an item field inside `each:`, not a top-level variable.
+    assert_eq!(found.variables, vec!["items"]);
+}
+
+#[test]
+fn every_known_pipe_is_accepted() {
+    for pipe in KNOWN_PIPES {
+        let found = inspect_template(&format!("{{v | {pipe}: \"x\"}}"));
+        assert!(found.unknown_pipes.is_empty(), "{pipe} reported unknown");
+    }
+}
@@ -0,0 +1,124 @@
+use super::super::chunk::ChunkData;
+use crate::filter::section::SectionData;
+
+use super::*;
+
+fn item(pairs: &[(&str, &str)]) -> ChunkItem {
+    pairs
+        .iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect()
+}
+
+fn crates() -> ChunkMap {
+    let mut map = ChunkMap::new();
+    map.insert(
+        "crates".to_string(),
+        ChunkData::Flat(vec![
+            item(&[("name", "a"), ("warnings", "3"), ("secs", "1.5")]),
+            item(&[("name", "b"), ("warnings", "12"), ("secs", "0.25")]),
+            item(&[("name", "c"), ("warnings", ""), ("secs", "2")]),
+        ]),
+    );
+    map
+}
+
+fn render(tmpl: &str) -> String {
+    render_template(tmpl, &HashMap::new(), &SectionMap::new(), &crates())
+}
+
+#[test]
+fn sum_field() {
+    assert_eq!(render("{crates | sum: \"warnings\"}"), "15");
+}
+
+#[test]
+fn sum_float_field() {
+    assert_eq!(render("{crates | sum: \"secs\"}"), "3.75");
+}
+
+#[test]
+fn avg_skips_non_numeric() {
+    assert_eq!(render("{crates | avg: \"warnings\"}"), "7.5");
+}
+
+#[test]
+fn min_and_max() {
+    assert_eq!(render("{crates | min: \"secs\"}"), "0.25");
+    assert_eq!(render("{crates | max: \"warnings\"}"), "12");
+}
+
+#[test]
+fn missing_field_sum_is_zero_and_max_empty() {
+    assert_eq!(render("{crates | sum: \"nope\"}"), "0");
+    assert_eq!(render("{crates | max: \"nope\"}"), "");
+}
+
+#[test]
+fn reduce_after_keep() {
+    assert_eq!(
+        render("{crates | keep: \"name=b\" | sum: \"warnings\"}"),
+        "12"
+    );
+}
+
+#[test]
+fn bare_sum_on_flat_collection() {
+    let mut sections = SectionMap::new();
+    sections.insert(
+        "times".to_string(),
+        SectionData {
+            lines: vec!["10".to_string(), "x".to_string(), "5".to_string()],
+            blocks: Vec::new(),
+            scopes: Vec::new(),
+        },
+    );
+    let out = render_template(
+        "{times | sum} / {times | max}",
+        &HashMap::new(),
+        &sections,
+        &ChunkMap::new(),
+    );
+    assert_eq!(out, "15 / 10");
+}
+
+#[test]
+fn avg_rounds_to_two_decimals() {
+    let mut map = ChunkMap::new();
+    map.insert(
+        "xs".to_string(),
+        ChunkData::Flat(vec![
+            item(&[("n", "1")]),
+            item(&[("n", "1")]),
+            item(&[("n", "2")]),
+        ]),
+    );
+    let out = render_template(
+        "{xs | avg: \"n\"}",
+        &HashMap::new(),
+        &SectionMap::new(),
+        &map,
+    );
+    assert_eq!(out, "1.33");
+}
+
+#[test]
+fn reduce_on_tree_groups() {
+    let mut map = ChunkMap::new();
+    map.insert(
+        "g".to_string(),
+        ChunkData::Tree {
+            groups: vec![item(&[("n", "4")]), item(&[("n", "6")])],
+            children_key: "c".to_string(),
+            children: vec![vec![], vec![]],
+            subgroups: vec![],
+        },
+    );
+    let out = render_template(
+        "{g | sum: \"n\"}",
+        &HashMap::new(),
+        &SectionMap::new(),
+        &map,
+    );
+    assert_eq!(out, "10");
+}
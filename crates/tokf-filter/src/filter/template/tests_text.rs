@@ -0,0 +1,71 @@
+use unicode_width::UnicodeWidthStr;
+
+use super::*;
+
+fn render(tmpl: &str, value: &str) -> String {
+    let vars = HashMap::from([("v".to_string(), value.to_string())]);
+    render_template(tmpl, &vars, &SectionMap::new(), &ChunkMap::new())
+}
+
+#[test]
+fn truncate_keeps_grapheme_clusters_whole() {
+    // "é" as e + combining acute, and a ZWJ family emoji.
+    assert_eq!(render("{v | truncate: 2}", "ae\u{301}xyz"), "ae\u{301}...");
+    let family = "\u{1F468}\u{200D}\u{1F469}\u{200D}\u{1F467}";
+    assert_eq!(
+        render("{v | truncate: 1}", &format!("{family}{family}")),
+        format!("{family}...")
+    );
+}
+
+#[test]
+fn truncate_width_counts_columns() {
+    assert_eq!(render("{v | truncate_width: 10}", "short"), "short");
+    assert_eq!(render("{v | truncate_width: 6}", "abcdefghij"), "abcde…");
+    // Each CJK character is two columns wide.
+    let out = render("{v | truncate_width: 6}", "日本語のテキスト");
+    assert_eq!(out, "日本…");
+    assert!(out.width() <= 6);
+}
+
+#[test]
+fn truncate_width_never_exceeds_budget_with_wide_chars() {
+    // A wide character that would straddle the limit is dropped.
+    let out = render("{v | truncate_width: 4}", "a日本語");
+    assert_eq!(out, "a日…");
+    assert_eq!(render("{v | truncate_width: 0}", "abc"), "");
+}
+
+#[test]
+fn truncate_middle_keeps_head_and_tail() {
+    assert_eq!(
+        render(
+            "{v | truncate_middle: 15}",
+            "src/very/deep/nested/module/file.rs"
+        ),
+        "src/ver…file.rs"
+    );
+    assert_eq!(
+        render("{v | truncate_middle: 20}", "src/lib.rs"),
+        "src/lib.rs"
+    );
+    let out = render("{v | truncate_middle: 7}", "日本語のテキスト");
+    assert_eq!(out, "日…スト");
+    assert!(out.width() <= 7);
+}
+
+#[test]
+fn width_pipes_map_over_collections() {
+    assert_eq!(
+        render(
+            "{v | lines | truncate_width: 4 | join: \",\"}",
+            "abcdefgh\nab"
+        ),
+        "abc…,ab"
+    );
+}
+
+#[test]
+fn invalid_width_passes_through() {
+    assert_eq!(render("{v | truncate_width: x}", "abcdef"), "abcdef");
+}
@@ -0,0 +1,95 @@
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
+
+use super::Value;
+
+/// Marker for width-aware truncation; one display column wide.
+const ELLIPSIS: &str = "…";
+
+/// `| truncate: N` — keep the first N characters and append `...`.
+///
+/// Characters are grapheme clusters, so a combining accent or a ZWJ emoji
+/// sequence is never split.
+pub(super) fn apply_truncate(arg: &str, value: Value) -> Value {
+    map_strings(arg, value, |s, n| {
+        if s.graphemes(true).nth(n).is_none() {
+            return s;
+        }
+        let kept: String = s.graphemes(true).take(n).collect();
+        format!("{kept}...")
+    })
+}
+
+/// `| truncate_width: N` — fit the value in N terminal columns, ending in `…`
+/// when cut. CJK and emoji count as two columns, so table columns stay aligned.
+pub(super) fn apply_truncate_width(arg: &str, value: Value) -> Value {
+    map_strings(arg, value, |s, n| {
+        if s.width() <= n {
+            return s;
+        }
+        if n == 0 {
+            return String::new();
+        }
+        let budget = n.saturating_sub(ELLIPSIS.width());
+        format!("{}{ELLIPSIS}", head(&s, budget))
+    })
+}
+
+/// `| truncate_middle: N` — fit the value in N terminal columns by replacing
+/// its middle with `…`, keeping both ends (useful for long file paths). The
+/// tail gets the odd column.
+pub(super) fn apply_truncate_middle(arg: &str, value: Value) -> Value {
+    map_strings(arg, value, |s, n| {
+        if s.width() <= n {
+            return s;
+        }
+        if n == 0 {
+            return String::new();
+        }
+        let budget = n.saturating_sub(ELLIPSIS.width());
+        let head = head(&s, budget / 2);
+        let tail = tail(&s, budget - head.width());
+        format!("{head}{ELLIPSIS}{tail}")
+    })
+}
+
+/// Apply `f(string, N)` to a string or each item of a collection; other
+/// values, and a non-numeric N, pass through.
+fn map_strings(arg: &str, value: Value, f: impl Fn(String, usize) -> String) -> Value {
+    let Ok(n) = arg.trim().parse::<usize>() else {
+        return value;
+    };
+    match value {
+        Value::Str(s) => Value::Str(f(s, n)),
+        Value::Collection(items) => Value::Collection(items.into_iter().map(|s| f(s, n)).collect()),
+        other => other,
+    }
+}
+
+/// The longest prefix of whole grapheme clusters at most `width` columns wide.
+fn head(s: &str, width: usize) -> &str {
+    let mut used = 0;
+    let mut end = 0;
+    for (i, g) in s.grapheme_indices(true) {
+        used += g.width();
+        if used > width {
+            break;
+        }
+        end = i + g.len();
+    }
+    &s[..end]
+}
+
+/// The longest suffix of whole grapheme clusters at most `width` columns wide.
+fn tail(s: &str, width: usize) -> &str {
+    let mut used = 0;
+    let mut start = s.len();
+    for (i, g) in s.grapheme_indices(true).rev() {
+        used += g.width();
+        if used > width {
+            break;
+        }
+        start = i;
+    }
+    &s[start..]
+}
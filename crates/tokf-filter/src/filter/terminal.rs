@@ -0,0 +1,346 @@
+//! `collapse_cr`: replay carriage returns and cursor movement the way a
+//! terminal would, keeping only the text that ends up visible.
+//!
+//! Progress bars (pip, docker, cargo) redraw in place with `\r`, erase-line
+//! and cursor-up sequences. Captured raw, every redraw survives as text. This
+//! module runs the output through a minimal screen model instead:
+//!
+//! - `\r` returns to column 0; later characters overwrite earlier ones
+//! - `\x08` (backspace) moves one column left
+//! - CSI `A`/`B`/`C`/`D`/`E`/`F`/`G` move the cursor
+//! - CSI `K` erases within the line, CSI `J` (mode 0) erases below the cursor
+//! - SGR colors and OSC sequences (hyperlinks) are kept as zero-width text
+//! - every other escape sequence is dropped
+//!
+//! Clearing the whole screen (`\x1b[2J`) is deliberately ignored: in a log
+//! it would discard everything printed so far.
+//!
+//! Cursor counts come from the command, so they are clamped: moving down
+//! stops one row past the last row written (a real terminal stops at the
+//! bottom of the screen), and columns stop at [`MAX_COL`].
+
+use std::borrow::Cow;
+use std::mem;
+
+/// Rightmost column a cursor movement can reach; writing text can still go
+/// further.
+const MAX_COL: usize = 4096;
+
+/// One visible character plus the zero-width escapes written just before it.
+#[derive(Clone)]
+struct Cell {
+    esc: String,
+    ch: char,
+}
+
+impl Cell {
+    const fn blank() -> Self {
+        Self {
+            esc: String::new(),
+            ch: ' ',
+        }
+    }
+}
+
+#[derive(Default)]
+struct Row {
+    cells: Vec<Cell>,
+    /// Zero-width escapes written after the last character (e.g. a color reset).
+    trailer: String,
+}
+
+struct Screen {
+    rows: Vec<Row>,
+    row: usize,
+    col: usize,
+    /// Zero-width escapes waiting to attach to the next written character.
+    pending: String,
+}
+
+impl Screen {
+    fn new() -> Self {
+        Self {
+            rows: vec![Row::default()],
+            row: 0,
+            col: 0,
+            pending: String::new(),
+        }
+    }
+
+    fn current(&mut self) -> &mut Row {
+        // `move_to_row` keeps `row` in bounds.
+        let idx = self.row.min(self.rows.len() - 1);
+        &mut self.rows[idx]
+    }
+
+    fn write(&mut self, ch: char) {
+        let col = self.col;
+        let cell = Cell {
+            esc: mem::take(&mut self.pending),
+            ch,
+        };
+        let row = self.current();
+        if row.cells.len() < col {
+            row.cells.resize(col, Cell::blank());
+        }
+        if col < row.cells.len() {
+            row.cells[col] = cell;
+        } else {
+            row.cells.push(cell);
+        }
+        self.col = self.col.saturating_add(1);
+    }
+
+    /// Attach pending escapes to the current row before the cursor leaves it.
+    fn flush_pending(&mut self) {
+        if !self.pending.is_empty() {
+            let pending = mem::take(&mut self.pending);
+            self.current().trailer = pending;
+        }
+    }
+
+    /// Move to `row`, at most one row past the last one written.
+    fn move_to_row(&mut self, row: usize) {
+        self.flush_pending();
+        let row = row.min(self.rows.len());
+        if self.rows.len() <= row {
+            self.rows.resize_with(row + 1, Row::default);
+        }
+        self.row = row;
+    }
+
+    fn erase_line(&mut self, mode: usize) {
+        let col = self.col;
+        let row = self.current();
+        match mode {
+            0 => row.cells.truncate(col),
+            1 => {
+                let end = (col + 1).min(row.cells.len());
+                row.cells[..end].fill(Cell::blank());
+            }
+            _ => row.cells.clear(),
+        }
+    }
+
+    fn csi(&mut self, params: &str, cmd: char) {
+        let first = params
+            .trim_start_matches('?')
+            .split(';')
+            .next()
+            .and_then(|p| p.parse::<usize>().ok());
+        let n = first.unwrap_or(1).max(1);
+        match cmd {
+            'A' => self.move_to_row(self.row.saturating_sub(n)),
+            'B' => self.move_to_row(self.row.saturating_add(n)),
+            'C' => self.col = self.col.saturating_add(n).min(MAX_COL),
+            'D' => self.col = self.col.saturating_sub(n),
+            'E' | 'F' => {
+                let row = if cmd == 'E' {
+                    self.row.saturating_add(n)
+                } else {
+                    self.row.saturating_sub(n)
+                };
+                self.move_to_row(row);
+                self.col = 0;
+            }
+            'G' => self.col = (n - 1).min(MAX_COL),
+            'K' => self.erase_line(first.unwrap_or(0)),
+            'J' if first.unwrap_or(0) == 0 => {
+                self.erase_line(0);
+                self.rows.truncate(self.row.saturating_add(1));
+            }
+            _ => {}
+        }
+    }
+
+    fn render(mut self) -> String {
+        self.flush_pending();
+        let mut out = String::new();
+        for (i, row) in self.rows.iter().enumerate() {
+            if i > 0 {
+                out.push('\n');
+            }
+            for cell in &row.cells {
+                out.push_str(&cell.esc);
+                out.push(cell.ch);
+            }
+            out.push_str(&row.trailer);
+        }
+        out
+    }
+}
+
+/// One escape sequence, classified by how the screen model treats it.
+enum Escape<'a> {
+    /// Control Sequence Introducer: `ESC [ params cmd`.
+    Csi { params: &'a str, cmd: char },
+    /// Colors and OSC sequences — kept in the output as-is.
+    ZeroWidth(&'a str),
+    /// Anything else — dropped.
+    Other,
+}
+
+/// Classify the escape sequence at the start of `s` (which begins with `ESC`).
+///
+/// Returns the sequence and its length in bytes. Unterminated sequences
+/// consume the rest of the input.
+fn parse_escape(s: &str) -> (Escape<'_>, usize) {
+    let bytes = s.as_bytes();
+    match bytes.get(1) {
+        Some(b'[') => {
+            let body = bytes[2..]
+                .iter()
+                .position(|b| (0x40..=0x7e).contains(b))
+                .map(|p| p + 2);
+            let Some(end) = body else {
+                return (Escape::Other, s.len());
+            };
+            let cmd = char::from(bytes[end]);
+            if cmd == 'm' {
+                (Escape::ZeroWidth(&s[..=end]), end + 1)
+            } else {
+                let params = &s[2..end];
+                (Escape::Csi { params, cmd }, end + 1)
+            }
+        }
+        Some(b']') => {
+            let bel = s.find('\x07').map(|i| i + 1);
+            let st = s[1..].find("\x1b\\").map(|i| i + 3);
+            match (bel, st) {
+                (Some(a), Some(b)) => (Escape::ZeroWidth(&s[..a.min(b)]), a.min(b)),
+                (Some(end), None) | (None, Some(end)) => (Escape::ZeroWidth(&s[..end]), end),
+                (None, None) => (Escape::Other, s.len()),
+            }
+        }
+        _ => {
+            let len = s[1..].chars().next().map_or(1, |c| 1 + c.len_utf8());
+            (Escape::Other, len)
+        }
+    }
+}
+
+/// Replay `\r` overwrites and cursor-control sequences in `text`.
+///
+/// Text without `\r`, backspace or escape sequences is returned borrowed.
+pub fn collapse_cr(text: &str) -> Cow<'_, str> {
+    if !text.contains(['\r', '\x08', '\x1b']) {
+        return Cow::Borrowed(text);
+    }
+    let mut screen = Screen::new();
+    let mut i = 0;
+    while let Some(ch) = text[i..].chars().next() {
+        if ch == '\x1b' {
+            let (escape, len) = parse_escape(&text[i..]);
+            match escape {
+                Escape::Csi { params, cmd } => screen.csi(params, cmd),
+                Escape::ZeroWidth(seq) => screen.pending.push_str(seq),
+                Escape::Other => {}
+            }
+            i += len;
+            continue;
+        }
+        match ch {
+            '\r' => screen.col = 0,
+            '\n' => {
+                screen.move_to_row(screen.row.saturating_add(1));
+                screen.col = 0;
+            }
+            '\x08' => screen.col = screen.col.saturating_sub(1),
+            _ => screen.write(ch),
+        }
+        i += ch.len_utf8();
+    }
+    Cow::Owned(screen.render())
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn plain_text_is_borrowed() {
+        let text = "line one\nline two\n";
+        assert!(matches!(collapse_cr(text), Cow::Borrowed(t) if t == text));
+    }
+
+    #[test]
+    fn carriage_return_keeps_final_redraw() {
+        let text = "Downloading  10%\rDownloading  55%\rDownloading 100%\ndone\n";
+        assert_eq!(collapse_cr(text), "Downloading 100%\ndone\n");
+    }
+
+    #[test]
+    fn shorter_overwrite_leaves_tail_of_previous_text() {
+        assert_eq!(collapse_cr("abcdef\rxy"), "xycdef");
+    }
+
+    #[test]
+    fn crlf_line_endings_are_plain_newlines() {
+        assert_eq!(collapse_cr("one\r\ntwo\r\n"), "one\ntwo\n");
+    }
+
+    #[test]
+    fn erase_line_after_cr_clears_previous_text() {
+        assert_eq!(collapse_cr("long progress text\r\x1b[Kshort"), "short");
+        assert_eq!(collapse_cr("long progress text\r\x1b[2Kshort"), "short");
+    }
+
+    #[test]
+    fn cursor_up_redraws_earlier_lines() {
+        // Two-line progress display redrawn once, docker-style.
+        let text =
+            "layer a: 10%\nlayer b: 20%\n\x1b[2A\x1b[2Klayer a: done\n\x1b[2Klayer b: done\n";
+        assert_eq!(collapse_cr(text), "layer a: done\nlayer b: done\n");
+    }
+
+    #[test]
+    fn backspace_moves_left() {
+        assert_eq!(collapse_cr("spin |\x08/\x08-"), "spin -");
+    }
+
+    #[test]
+    fn colors_and_hyperlinks_survive() {
+        let text = "\x1b[32mok\x1b[0m \x1b]8;;http://x\x07link\x1b]8;;\x07";
+        assert_eq!(collapse_cr(text), text);
+    }
+
+    #[test]
+    fn other_control_sequences_are_dropped() {
+        // Hide/show cursor, save/restore cursor, absolute positioning.
+        assert_eq!(
+            collapse_cr("\x1b[?25lbusy\x1b[?25h\x1b7\x1b8\x1b[3;1H"),
+            "busy"
+        );
+    }
+
+    #[test]
+    fn clear_screen_is_ignored() {
+        assert_eq!(collapse_cr("kept\n\x1b[2Jafter"), "kept\nafter");
+    }
+
+    #[test]
+    fn column_moves_pad_with_spaces() {
+        assert_eq!(collapse_cr("a\x1b[3Cb"), "a   b");
+        assert_eq!(collapse_cr("abc\x1b[1Gx"), "xbc");
+    }
+
+    #[test]
+    fn cursor_moves_stop_at_the_screen_edge() {
+        // "Move to the bottom" adds one row, not 999.
+        assert_eq!(collapse_cr("top\r\x1b[999Bbottom"), "top\nbottom");
+        assert_eq!(collapse_cr("a\nb\x1b[1A\x1b[99Ec"), "a\nb\nc");
+        assert_eq!(collapse_cr("x\r\x1b[99999999999By"), "x\ny");
+        for text in ["\x1b[99999999999Cz", "ab\x1b[99999999999Gz"] {
+            let wide = collapse_cr(text);
+            assert_eq!(wide.len(), MAX_COL + 1);
+            assert!(wide.ends_with(" z"));
+        }
+    }
+
+    #[test]
+    fn unterminated_escape_is_dropped() {
+        assert_eq!(collapse_cr("text\x1b["), "text");
+        assert_eq!(collapse_cr("text\x1b"), "text");
+    }
+}
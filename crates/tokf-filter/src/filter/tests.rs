@@ -40,6 +40,62 @@ fn select_branch_failure() {
     assert!(select_branch(&config, 127).is_some());
 }
 
+#[test]
+fn select_branch_on_exit_codes_take_precedence() {
+    let config: FilterConfig = toml::from_str(
+        r#"
+command = "test"
+
+[on_success]
+output = "success"
+
+[on_failure]
+output = "failure"
+
+[[on_exit]]
+codes = [101]
+output = "compile error"
+
+[[on_exit]]
+codes = [1, 2]
+output = "test failure"
+"#,
+    )
+    .unwrap();
+    let output = |code| {
+        select_branch(&config, code)
+            .unwrap()
+            .output
+            .clone()
+            .unwrap()
+    };
+    assert_eq!(output(0), "success");
+    assert_eq!(output(101), "compile error");
+    assert_eq!(output(1), "test failure");
+    assert_eq!(output(2), "test failure");
+    assert_eq!(output(3), "failure");
+}
+
+#[test]
+fn select_branch_on_exit_can_claim_zero() {
+    let config: FilterConfig = toml::from_str(
+        r#"
+command = "test"
+
+[on_success]
+output = "success"
+
+[[on_exit]]
+codes = [0]
+output = "exit zero"
+"#,
+    )
+    .unwrap();
+    let branch = select_branch(&config, 0).unwrap();
+    assert_eq!(branch.output.as_deref(), Some("exit zero"));
+    assert!(select_branch(&config, 1).is_none());
+}
+
 // --- apply_branch ---
 
 /// Helper: call `apply_branch` with empty sections and chunks (non-section path).
@@ -51,6 +107,8 @@ fn branch_apply(branch: &OutputBranch, combined: &str) -> String {
         has_json: false,
         json_parsed: false,
         json_vars: &std::collections::HashMap::new(),
+        stdout: "",
+        stderr: "",
         top_level_tail: None,
         top_level_head: None,
     };
@@ -261,6 +319,7 @@ fn branch_with_sections_expected_but_empty_returns_none() {
         section::SectionData {
             lines: vec![],
             blocks: vec![],
+            scopes: Vec::new(),
         },
     );
     let branch = OutputBranch {
@@ -284,6 +343,8 @@ fn branch_with_sections_expected_but_empty_returns_none() {
         has_json: false,
         json_parsed: false,
         json_vars: &std::collections::HashMap::new(),
+        stdout: "",
+        stderr: "",
         top_level_tail: None,
         top_level_head: None,
     };
@@ -302,6 +363,7 @@ fn branch_with_sections_populated_renders_template() {
                 "test result: ok. 8 passed; 0 failed".to_string(),
             ],
             blocks: vec![],
+            scopes: Vec::new(),
         },
     );
     let branch = OutputBranch {
@@ -325,6 +387,8 @@ fn branch_with_sections_populated_renders_template() {
         has_json: false,
         json_parsed: false,
         json_vars: &std::collections::HashMap::new(),
+        stdout: "",
+        stderr: "",
         top_level_tail: None,
         top_level_head: None,
     };
@@ -351,6 +415,8 @@ fn branch_without_sections_ignores_has_sections_flag() {
         has_json: false,
         json_parsed: false,
         json_vars: &std::collections::HashMap::new(),
+        stdout: "",
+        stderr: "",
         top_level_tail: None,
         top_level_head: None,
     };
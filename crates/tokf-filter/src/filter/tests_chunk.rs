@@ -1,5 +1,7 @@
 use super::chunk::{ChunkData, ChunkItem, normalize_keys, process_chunks};
-use tokf_common::config::types::{ChunkAggregateRule, ChunkBodyExtract, ChunkConfig, ChunkExtract};
+use tokf_common::config::types::{
+    ChunkAggregateRule, ChunkBodyExtract, ChunkConfig, ChunkExtract, GroupBy,
+};
 
 fn basic_config() -> ChunkConfig {
     ChunkConfig {
@@ -60,7 +62,7 @@ fn group_by_merges_same_crate() {
         "test result: ok. 50 passed; 0 failed; 2 ignored",
     ];
     let mut config = basic_config();
-    config.group_by = Some("crate".to_string());
+    config.group_by = Some("crate".into());
     config.aggregate.push(ChunkAggregateRule {
         pattern: r"(\d+) ignored".to_string(),
         sum: Some("ignored".to_string()),
@@ -300,7 +302,7 @@ fn group_by_no_matching_field() {
         "test result: ok. 5 passed; 0 failed",
     ];
     let mut config = basic_config();
-    config.group_by = Some("nonexistent".to_string());
+    config.group_by = Some("nonexistent".into());
     let result = process_chunks(&[config], &lines);
     let suites = flat_items(&result["suites"]);
     // Both items have nonexistent="" (from normalize_keys), so they merge
@@ -478,7 +480,7 @@ fn merge_into_empty_existing_with_numeric_incoming() {
         "test result: ok. 10 passed; 0 failed",
     ];
     let mut config = basic_config();
-    config.group_by = Some("crate".to_string());
+    config.group_by = Some("crate".into());
     config.extract = Some(ChunkExtract {
         pattern: r"deps/([\w_-]+)-".to_string(),
         as_name: "crate".to_string(),
@@ -505,7 +507,7 @@ fn group_by_with_children_preserves_items() {
         "test result: ok. 5 passed; 0 failed",
     ];
     let mut config = basic_config();
-    config.group_by = Some("crate".to_string());
+    config.group_by = Some("crate".into());
     config.children_as = Some("children".to_string());
     let result = process_chunks(&[config], &lines);
     match &result["suites"] {
@@ -513,6 +515,7 @@ fn group_by_with_children_preserves_items() {
             groups,
             children_key,
             children,
+            subgroups,
         } => {
             assert_eq!(groups.len(), 1);
             assert_eq!(groups[0]["crate"], "tokf");
@@ -522,6 +525,7 @@ fn group_by_with_children_preserves_items() {
             assert_eq!(children[0].len(), 2); // two original items
             assert_eq!(children[0][0]["passed"], "10");
             assert_eq!(children[0][1]["passed"], "5");
+            assert!(subgroups.is_empty());
         }
         ChunkData::Flat(_) => panic!("expected Tree"),
     }
@@ -534,7 +538,7 @@ fn group_by_with_children_single_group() {
         "test result: ok. 10 passed; 0 failed",
     ];
     let mut config = basic_config();
-    config.group_by = Some("crate".to_string());
+    config.group_by = Some("crate".into());
     config.children_as = Some("kids".to_string());
     let result = process_chunks(&[config], &lines);
     match &result["suites"] {
@@ -557,10 +561,111 @@ fn group_by_without_children_as_remains_flat() {
         "test result: ok. 5 passed; 0 failed",
     ];
     let mut config = basic_config();
-    config.group_by = Some("crate".to_string());
+    config.group_by = Some("crate".into());
     // children_as is None
     let result = process_chunks(&[config], &lines);
     let suites = flat_items(&result["suites"]);
     assert_eq!(suites.len(), 1);
     assert_eq!(suites[0]["passed"], "15");
 }
+
+// --- multi-level group_by ---
+
+fn module_lines() -> Vec<&'static str> {
+    vec![
+        "     Running deps/core-abc123 io",
+        "test result: ok. 3 passed; 0 failed",
+        "     Running deps/core-abc123 net",
+        "test result: ok. 2 passed; 0 failed",
+        "     Running deps/core-abc123 io",
+        "test result: ok. 4 passed; 0 failed",
+        "     Running deps/cli-def456 args",
+        "test result: ok. 1 passed; 0 failed",
+    ]
+}
+
+fn module_config() -> ChunkConfig {
+    let mut config = basic_config();
+    config.body_extract = vec![ChunkBodyExtract {
+        pattern: r"Running \S+ (\w+)$".to_string(),
+        as_name: "module".to_string(),
+        carry_forward: false,
+    }];
+    config.group_by = Some(GroupBy::Multiple(vec![
+        "crate".to_string(),
+        "module".to_string(),
+    ]));
+    config
+}
+
+#[test]
+fn multi_level_group_by_builds_nested_tree() {
+    let lines = module_lines();
+    let mut config = module_config();
+    config.children_as = Some("children".to_string());
+    let result = process_chunks(&[config], &lines);
+    match &result["suites"] {
+        ChunkData::Tree {
+            groups,
+            children,
+            subgroups,
+            children_key,
+        } => {
+            assert_eq!(groups.len(), 2);
+            assert_eq!(groups[0]["crate"], "core");
+            assert_eq!(groups[0]["passed"], "9");
+            assert_eq!(subgroups, &vec!["module".to_string()]);
+            // Expanding the first crate's children yields the module level.
+            match ChunkData::grouped(&children[0], subgroups, children_key) {
+                ChunkData::Tree {
+                    groups: modules,
+                    children: tests,
+                    subgroups: rest,
+                    ..
+                } => {
+                    assert_eq!(modules.len(), 2);
+                    assert_eq!(modules[0]["module"], "io");
+                    assert_eq!(modules[0]["passed"], "7");
+                    assert_eq!(tests[0].len(), 2);
+                    assert!(rest.is_empty());
+                }
+                ChunkData::Flat(_) => panic!("expected nested Tree"),
+            }
+        }
+        ChunkData::Flat(_) => panic!("expected Tree"),
+    }
+}
+
+#[test]
+fn multi_level_group_by_without_children_as_uses_composite_key() {
+    let lines = module_lines();
+    let result = process_chunks(&[module_config()], &lines);
+    let suites = flat_items(&result["suites"]);
+    assert_eq!(suites.len(), 3); // core/io, core/net, cli/args
+    assert_eq!(suites[0]["passed"], "7");
+}
+
+#[test]
+fn group_by_accepts_string_or_list_in_toml() {
+    let single: ChunkConfig = toml::from_str(
+        r#"
+split_on = "^x"
+collect_as = "c"
+group_by = "crate"
+"#,
+    )
+    .unwrap();
+    assert_eq!(single.group_by.unwrap().keys(), ["crate".to_string()]);
+    let multi: ChunkConfig = toml::from_str(
+        r#"
+split_on = "^x"
+collect_as = "c"
+group_by = ["crate", "module"]
+"#,
+    )
+    .unwrap();
+    assert_eq!(
+        multi.group_by.unwrap().keys(),
+        ["crate".to_string(), "module".to_string()]
+    );
+}
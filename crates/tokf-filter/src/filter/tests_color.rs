@@ -13,6 +13,7 @@ fn make_result(combined: &str, exit_code: i32) -> CommandResult {
 fn color_opts() -> FilterOptions {
     FilterOptions {
         preserve_color: true,
+        ..FilterOptions::default()
     }
 }
 
@@ -55,6 +56,21 @@ dedup = true
     assert_eq!(filtered.output, "\x1b[31mdup\x1b[0m\n\x1b[34munique\x1b[0m");
 }
 
+#[test]
+fn apply_color_flag_with_dedup_annotate() {
+    let config: FilterConfig = toml::from_str(
+        r#"
+command = "test"
+dedup = true
+dedup_annotate = true
+"#,
+    )
+    .unwrap();
+    let result = make_result("\x1b[31mdup\x1b[0m\n\x1b[32mdup\x1b[0m\nok", 0);
+    let filtered = apply(&config, &result, &[], &color_opts());
+    assert_eq!(filtered.output, "\x1b[31mdup\x1b[0m (x2)\nok");
+}
+
 #[test]
 fn apply_color_flag_false_unchanged() {
     // color=false produces the same output as current behavior (regression test)
@@ -250,7 +266,7 @@ fn restore_display_basic_mapping() {
     // survivors: indices 0, 2, 4
     let refs: Vec<&str> = clean.iter().map(String::as_str).collect();
     let survivors = vec![refs[0], refs[2], refs[4]];
-    let result = restore_display_lines(&clean, &display, &survivors);
+    let result = restore_display_lines(&clean, &display, &survivors).join("\n");
     assert_eq!(
         result,
         "\x1b[31ma\x1b[0m\n\x1b[33mc\x1b[0m\n\x1b[35me\x1b[0m"
@@ -261,7 +277,7 @@ fn restore_display_basic_mapping() {
 fn restore_display_empty_survivors() {
     let clean: Vec<String> = vec!["a", "b"].into_iter().map(String::from).collect();
     let display: Vec<String> = vec!["A", "B"].into_iter().map(String::from).collect();
-    let result = restore_display_lines(&clean, &display, &[]);
+    let result = restore_display_lines(&clean, &display, &[]).join("\n");
     assert_eq!(result, "");
 }
 
@@ -270,7 +286,7 @@ fn restore_display_all_survive() {
     let clean: Vec<String> = vec!["x", "y", "z"].into_iter().map(String::from).collect();
     let display: Vec<String> = vec!["X", "Y", "Z"].into_iter().map(String::from).collect();
     let refs: Vec<&str> = clean.iter().map(String::as_str).collect();
-    let result = restore_display_lines(&clean, &display, &refs);
+    let result = restore_display_lines(&clean, &display, &refs).join("\n");
     assert_eq!(result, "X\nY\nZ");
 }
 
@@ -286,7 +302,7 @@ fn restore_display_first_and_last_removed() {
         .collect();
     let refs: Vec<&str> = clean.iter().map(String::as_str).collect();
     let survivors = vec![refs[1], refs[2]];
-    let result = restore_display_lines(&clean, &display, &survivors);
+    let result = restore_display_lines(&clean, &display, &survivors).join("\n");
     assert_eq!(result, "B\nC");
 }
 
@@ -295,6 +311,6 @@ fn restore_display_single_line() {
     let clean: Vec<String> = vec![String::from("only")];
     let display: Vec<String> = vec![String::from("ONLY")];
     let refs: Vec<&str> = clean.iter().map(String::as_str).collect();
-    let result = restore_display_lines(&clean, &display, &refs);
+    let result = restore_display_lines(&clean, &display, &refs).join("\n");
     assert_eq!(result, "ONLY");
 }
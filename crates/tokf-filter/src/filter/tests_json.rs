@@ -1,48 +1,75 @@
 use tokf_common::config::types::{
     ChunkConfig, CommandPattern, FilterConfig, JsonConfig, JsonExtractRule, JsonFieldExtract,
-    OutputBranch, Section,
+    OutputBranch, ParseFormat, Section, StdinMode, Stream,
 };
 
 use crate::CommandResult;
 
 use super::{FilterOptions, apply};
 
+#[allow(clippy::too_many_lines)] // one line per FilterConfig field
 fn default_config() -> FilterConfig {
     FilterConfig {
         command: CommandPattern::Single("test".to_string()),
+        pattern_regex: None,
         run: None,
+        source: Stream::Combined,
         skip: vec![],
         keep: vec![],
+        keep_context_before: 0,
+        keep_context_after: 0,
         step: vec![],
         extract: None,
         match_output: vec![],
         section: vec![],
         on_success: None,
         on_failure: None,
+        on_exit: vec![],
         parse: None,
         tree: None,
         output: None,
         fallback: None,
         replace: vec![],
+        collapse: vec![],
         dedup: false,
         dedup_window: None,
+        dedup_annotate: false,
+        collapse_cr: false,
+        shorten_paths: false,
+        redact_secrets: true,
+        redact: vec![],
         strip_ansi: false,
         trim_lines: false,
         strip_empty_lines: false,
         collapse_empty_lines: false,
         lua_script: None,
+        wasm_module: None,
         chunk: vec![],
         json: None,
         variant: vec![],
         show_history_hint: false,
+        diff_previous: false,
+        tee_raw_dir: None,
         inject_path: false,
+        pty: false,
+        timeout_secs: None,
+        env: std::collections::BTreeMap::new(),
+        cwd: None,
+        stdin: StdinMode::Inherit,
+        retry: None,
+        mask_exit_code: None,
         passthrough_args: vec![],
+        exclude: vec![],
+        watch: vec![],
         description: None,
+        tags: vec![],
+        version: None,
         truncate_lines_at: None,
         on_empty: None,
         head: None,
         tail: None,
         max_lines: None,
+        max_tokens: None,
     }
 }
 
@@ -148,8 +175,10 @@ fn json_skips_parse_when_configured() {
     });
     // Parse would normally intercept, but JSON should cause it to be skipped.
     config.parse = Some(tokf_common::config::types::ParseConfig {
+        format: ParseFormat::Text,
         branch: None,
         group: None,
+        extract: vec![],
     });
     config.on_success = Some(OutputBranch {
         output: Some("{msg}".to_string()),
@@ -265,6 +294,8 @@ fn json_skips_sections_when_configured() {
         match_pattern: None,
         split_on: None,
         collect_as: Some("errors".to_string()),
+        source: None,
+        children: vec![],
     }];
     config.on_success = Some(OutputBranch {
         output: Some("{msg}".to_string()),
@@ -336,3 +367,145 @@ fn json_skips_chunks_when_configured() {
 
     assert_eq!(result.output, "Status: ok");
 }
+
+// --- [parse] format = "json" / "jsonl" ---
+
+fn parse_format_config(format: ParseFormat, rules: Vec<JsonExtractRule>) -> FilterConfig {
+    let mut config = default_config();
+    config.parse = Some(tokf_common::config::types::ParseConfig {
+        format,
+        branch: None,
+        group: None,
+        extract: rules,
+    });
+    config
+}
+
+fn success_branch(output: &str) -> OutputBranch {
+    OutputBranch {
+        output: Some(output.to_string()),
+        aggregate: None,
+        tail: None,
+        head: None,
+        skip: vec![],
+        extract: None,
+        aggregates: vec![],
+    }
+}
+
+fn run(config: &FilterConfig, combined: &str) -> String {
+    apply(
+        config,
+        &CommandResult {
+            stdout: String::new(),
+            stderr: String::new(),
+            exit_code: 0,
+            combined: combined.to_string(),
+        },
+        &[],
+        &FilterOptions::default(),
+    )
+    .output
+}
+
+#[test]
+fn parse_json_format_extracts_paths() {
+    let mut config = parse_format_config(
+        ParseFormat::Json,
+        vec![JsonExtractRule {
+            path: "$.summary.failed".to_string(),
+            as_name: "failed".to_string(),
+            fields: vec![],
+        }],
+    );
+    config.on_success = Some(success_branch("failed: {failed}"));
+
+    let out = run(&config, r#"{"summary": {"failed": 3}}"#);
+    assert_eq!(out, "failed: 3");
+}
+
+#[test]
+fn parse_json_format_invalid_input_falls_through() {
+    let mut config = parse_format_config(
+        ParseFormat::Json,
+        vec![JsonExtractRule {
+            path: "$.x".to_string(),
+            as_name: "x".to_string(),
+            fields: vec![],
+        }],
+    );
+    config.on_success = Some(success_branch("x={x}"));
+
+    assert_eq!(run(&config, "plain text"), "plain text");
+}
+
+#[test]
+fn parse_jsonl_format_queries_lines_as_array() {
+    let mut config = parse_format_config(
+        ParseFormat::Jsonl,
+        vec![JsonExtractRule {
+            path: "$[?@.reason == 'compiler-message'].message".to_string(),
+            as_name: "msgs".to_string(),
+            fields: vec![JsonFieldExtract {
+                field: "level".to_string(),
+                as_name: "level".to_string(),
+            }],
+        }],
+    );
+    config.on_success = Some(success_branch(
+        "{msgs_count}: {msgs | each: \"{level}\" | join: \",\"}",
+    ));
+
+    let input = "\
+{\"reason\":\"compiler-artifact\"}
+   Compiling foo v0.1.0
+{\"reason\":\"compiler-message\",\"message\":{\"level\":\"warning\"}}
+
+{\"reason\":\"compiler-message\",\"message\":{\"level\":\"error\"}}
+";
+    assert_eq!(run(&config, input), "2: warning,error");
+}
+
+#[test]
+fn parse_jsonl_format_without_json_lines_falls_through() {
+    let mut config = parse_format_config(ParseFormat::Jsonl, vec![]);
+    config.on_success = Some(success_branch("never"));
+
+    assert_eq!(run(&config, "no json here"), "no json here");
+}
+
+#[test]
+fn json_section_wins_over_parse_json_format() {
+    let mut config = parse_format_config(
+        ParseFormat::Json,
+        vec![JsonExtractRule {
+            path: "$.a".to_string(),
+            as_name: "v".to_string(),
+            fields: vec![],
+        }],
+    );
+    config.json = Some(JsonConfig {
+        extract: vec![JsonExtractRule {
+            path: "$.b".to_string(),
+            as_name: "v".to_string(),
+            fields: vec![],
+        }],
+    });
+    config.on_success = Some(success_branch("{v}"));
+
+    assert_eq!(run(&config, r#"{"a": "parse", "b": "json"}"#), "json");
+}
+
+#[test]
+fn parse_junit_format_renders_failures() {
+    let mut config = parse_format_config(ParseFormat::Junit, vec![]);
+    config.on_success = Some(success_branch(
+        "{passed_count}/{tests_count} passed\n{failed | each: \"FAIL {suite}::{name}: {message}\" | join: \"\\n\"}",
+    ));
+
+    let input = r#"<testsuite name="s">
+<testcase classname="m" name="ok_case"/>
+<testcase classname="m" name="bad_case"><failure message="boom"/></testcase>
+</testsuite>"#;
+    assert_eq!(run(&config, input), "1/2 passed\nFAIL m::bad_case: boom");
+}
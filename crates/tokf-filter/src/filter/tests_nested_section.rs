@@ -0,0 +1,156 @@
+use super::*;
+use crate::CommandResult;
+
+fn make_result(combined: &str, exit_code: i32) -> CommandResult {
+    CommandResult {
+        stdout: String::new(),
+        stderr: String::new(),
+        exit_code,
+        combined: combined.to_string(),
+    }
+}
+
+fn config(toml: &str) -> FilterConfig {
+    toml::from_str(toml).unwrap()
+}
+
+const MAVEN: &str = "\
+[INFO] Scanning for projects...
+[INFO] Building core 1.0
+[INFO] --- compiler:3.11:compile (default-compile) @ core ---
+[INFO] Compiling 12 source files
+[INFO] --- surefire:3.2:test (default-test) @ core ---
+[INFO] Tests run: 4, Failures: 0
+[INFO] ------------------------------------------------------------------------
+[INFO] Building web 1.0
+[INFO] --- compiler:3.11:compile (default-compile) @ web ---
+[INFO] Nothing to compile
+[INFO] ------------------------------------------------------------------------
+[INFO] BUILD SUCCESS";
+
+const MAVEN_FILTER: &str = r#"
+command = "mvn"
+
+[[section]]
+name = "module"
+enter = '^\[INFO\] Building '
+exit = '^\[INFO\] -{20,}'
+collect_as = "modules"
+
+[[section.children]]
+name = "plugin"
+match = '^\[INFO\] --- '
+collect_as = "plugins"
+"#;
+
+#[test]
+fn children_collect_per_parent_span() {
+    let cfg = config(MAVEN_FILTER);
+    let lines: Vec<&str> = MAVEN.lines().collect();
+    let map = section::collect_sections(&cfg.section, &lines);
+
+    let modules = &map["modules"];
+    assert_eq!(modules.count(), 2);
+    assert_eq!(modules.scopes[0].header, "[INFO] Building core 1.0");
+    assert_eq!(modules.scopes[0].sections["plugins"].lines.len(), 2);
+    assert_eq!(modules.scopes[1].header, "[INFO] Building web 1.0");
+    assert_eq!(modules.scopes[1].sections["plugins"].lines.len(), 1);
+}
+
+#[test]
+fn children_are_flattened_into_top_level_map() {
+    let cfg = config(MAVEN_FILTER);
+    let lines: Vec<&str> = MAVEN.lines().collect();
+    let map = section::collect_sections(&cfg.section, &lines);
+    assert_eq!(map["plugins"].lines.len(), 3);
+}
+
+#[test]
+fn children_ignore_lines_outside_parent() {
+    let cfg = config(MAVEN_FILTER);
+    let input = "[INFO] --- stray:1.0:goal @ none ---\n[INFO] Building core 1.0\n[INFO] done";
+    let lines: Vec<&str> = input.lines().collect();
+    let map = section::collect_sections(&cfg.section, &lines);
+    assert_eq!(map["modules"].scopes.len(), 1);
+    assert!(
+        map["modules"].scopes[0].sections["plugins"]
+            .lines
+            .is_empty()
+    );
+}
+
+#[test]
+fn each_over_parent_scopes_children() {
+    let cfg = config(&format!(
+        "{MAVEN_FILTER}\n[on_success]\n\
+         output = '''{{modules | each: \"{{value}}: {{plugins.count}} plugins\" | join: \"\\n\"}}\n\
+         total: {{plugins.count}}'''\n"
+    ));
+    let out = apply(&cfg, &make_result(MAVEN, 0), &[], &FilterOptions::default()).output;
+    assert_eq!(
+        out,
+        "[INFO] Building core 1.0: 2 plugins\n\
+         [INFO] Building web 1.0: 1 plugins\n\
+         total: 3"
+    );
+}
+
+#[test]
+fn grandchildren_nest_under_children() {
+    let cfg = config(
+        r#"
+command = "t"
+
+[[section]]
+enter = "^module "
+exit = "^end module"
+collect_as = "modules"
+
+[[section.children]]
+enter = "^plugin "
+exit = "^end plugin"
+collect_as = "plugins"
+
+[[section.children.children]]
+match = "^error"
+collect_as = "errors"
+"#,
+    );
+    let input = "module a\nplugin x\nerror one\nend plugin\nplugin y\nend plugin\nend module";
+    let lines: Vec<&str> = input.lines().collect();
+    let map = section::collect_sections(&cfg.section, &lines);
+
+    let plugins = &map["modules"].scopes[0].sections["plugins"];
+    assert_eq!(plugins.scopes.len(), 2);
+    assert_eq!(
+        plugins.scopes[0].sections["errors"].lines,
+        vec!["error one"]
+    );
+    assert!(plugins.scopes[1].sections["errors"].lines.is_empty());
+    assert_eq!(map["errors"].lines, vec!["error one"]);
+}
+
+#[test]
+fn top_level_section_wins_over_child_name() {
+    let cfg = config(
+        r#"
+command = "t"
+
+[[section]]
+enter = "^BEGIN"
+exit = "^END"
+collect_as = "outer"
+
+[[section.children]]
+collect_as = "lines"
+match = "^x"
+
+[[section]]
+match = "^y"
+collect_as = "lines"
+"#,
+    );
+    let lines: Vec<&str> = vec!["BEGIN", "x1", "y1", "END"];
+    let map = section::collect_sections(&cfg.section, &lines);
+    assert_eq!(map["lines"].lines, vec!["y1"]);
+}
@@ -0,0 +1,136 @@
+use super::*;
+use crate::CommandResult;
+
+fn split_result(stdout: &str, stderr: &str, exit_code: i32) -> CommandResult {
+    CommandResult {
+        stdout: stdout.to_string(),
+        stderr: stderr.to_string(),
+        exit_code,
+        combined: format!("{stdout}\n{stderr}"),
+    }
+}
+
+fn run(toml: &str, result: &CommandResult) -> String {
+    let config: FilterConfig = toml::from_str(toml).unwrap();
+    apply(&config, result, &[], &FilterOptions::default()).output
+}
+
+#[test]
+fn source_stdout_limits_skip_keep_to_stdout() {
+    let result = split_result("keep me\ndrop me", "stderr line", 0);
+    let out = run(
+        r#"
+command = "test"
+source = "stdout"
+skip = ["^drop"]
+"#,
+        &result,
+    );
+    assert_eq!(out, "keep me");
+}
+
+#[test]
+fn stderr_template_var_preserved_verbatim() {
+    let result = split_result("a\nnoise\nb", "warning: deprecated", 1);
+    let out = run(
+        r#"
+command = "test"
+source = "stdout"
+skip = ["^noise$"]
+
+[on_failure]
+output = "{stderr}\n{output}"
+"#,
+        &result,
+    );
+    assert_eq!(out, "warning: deprecated\na\nb");
+}
+
+#[test]
+fn stdout_template_var_available() {
+    let result = split_result("out", "err", 0);
+    let out = run(
+        r#"
+command = "test"
+
+[on_success]
+output = "[{stdout}] [{stderr}]"
+"#,
+        &result,
+    );
+    assert_eq!(out, "[out] [err]");
+}
+
+#[test]
+fn section_reads_its_own_source() {
+    let result = split_result("error: stdout lookalike", "error: real\nerror: also", 1);
+    let out = run(
+        r#"
+command = "test"
+
+[[section]]
+name = "errors"
+source = "stderr"
+match = "^error:"
+collect_as = "errors"
+
+[on_failure]
+output = "{errors.count} errors\n{errors | join: \"\\n\"}"
+"#,
+        &result,
+    );
+    assert_eq!(out, "2 errors\nerror: real\nerror: also");
+}
+
+#[test]
+fn section_inherits_top_level_source() {
+    let result = split_result("error: from stdout", "error: from stderr", 1);
+    let out = run(
+        r#"
+command = "test"
+source = "stdout"
+
+[[section]]
+match = "^error:"
+collect_as = "errors"
+
+[on_failure]
+output = "{errors | join: \",\"}"
+"#,
+        &result,
+    );
+    assert_eq!(out, "error: from stdout");
+}
+
+#[test]
+fn uncaptured_channels_fall_back_to_combined() {
+    // Test-case fixtures carry only combined output.
+    let result = CommandResult {
+        stdout: String::new(),
+        stderr: String::new(),
+        exit_code: 0,
+        combined: "line".to_string(),
+    };
+    let out = run(
+        r#"
+command = "test"
+source = "stderr"
+"#,
+        &result,
+    );
+    assert_eq!(out, "line");
+}
+
+#[test]
+fn match_output_still_sees_combined() {
+    let result = split_result("ok", "FATAL", 1);
+    let out = run(
+        r#"
+command = "test"
+source = "stdout"
+match_output = [{ contains = "FATAL", output = "fatal error" }]
+"#,
+        &result,
+    );
+    assert_eq!(out, "fatal error");
+}
@@ -0,0 +1,392 @@
+use super::chunk::ChunkItem;
+use super::streaming::{StreamEvent, StreamingFilter};
+use super::*;
+use crate::CommandResult;
+
+fn config(toml: &str) -> FilterConfig {
+    toml::from_str(toml).unwrap()
+}
+
+/// Feed `input` line by line and return the events and final output.
+fn stream(config: &FilterConfig, input: &str, exit_code: i32) -> (Vec<StreamEvent>, String) {
+    let mut filter = StreamingFilter::new(config, &[], &FilterOptions::default());
+    let mut events = Vec::new();
+    for line in input.lines() {
+        events.extend(filter.push_line(line));
+    }
+    let (tail, result) = filter.finish(exit_code);
+    events.extend(tail);
+    (events, result.output)
+}
+
+/// `(collect_as, item)` for each chunk event.
+fn chunk_events(events: &[StreamEvent]) -> Vec<(&str, &ChunkItem)> {
+    events
+        .iter()
+        .filter_map(|e| match e {
+            StreamEvent::Chunk { collect_as, item } => Some((collect_as.as_str(), item)),
+            StreamEvent::Line(_) => None,
+        })
+        .collect()
+}
+
+fn buffered(config: &FilterConfig, input: &str, exit_code: i32) -> String {
+    let result = CommandResult {
+        stdout: String::new(),
+        stderr: String::new(),
+        exit_code,
+        combined: input.to_string(),
+    };
+    apply(config, &result, &[], &FilterOptions::default()).output
+}
+
+const INPUT: &str = "\
+\x1b[32m  Compiling foo\x1b[0m
+  Compiling foo
+warning: unused variable
+  Compiling bar
+error: mismatched types
+error: mismatched types
+Finished in 3.2s";
+
+#[test]
+fn line_stages_match_buffered_output() {
+    let configs = [
+        r#"command = "t""#,
+        r#"
+command = "t"
+strip_ansi = true
+trim_lines = true
+skip = ["^Compiling"]
+dedup = true
+"#,
+        r#"
+command = "t"
+keep = ["^error", "^warning"]
+dedup = true
+dedup_window = 3
+"#,
+        r#"
+command = "t"
+strip_ansi = true
+[[replace]]
+pattern = '^\s+Compiling (\S+)'
+output = "build {1}"
+[on_failure]
+tail = 2
+[on_success]
+output = "ok: {output | lines | len} lines"
+"#,
+        r#"
+command = "t"
+max_lines = 3
+on_empty = "nothing"
+keep = ["^nomatch"]
+"#,
+    ];
+    for toml in configs {
+        let cfg = config(toml);
+        for exit_code in [0, 1] {
+            let (_, out) = stream(&cfg, INPUT, exit_code);
+            assert_eq!(out, buffered(&cfg, INPUT, exit_code), "config: {toml}");
+        }
+    }
+}
+
+#[test]
+fn line_events_report_survivors() {
+    let cfg = config(
+        r#"
+command = "t"
+keep = ["^error"]
+dedup = true
+"#,
+    );
+    let mut filter = StreamingFilter::new(&cfg, &[], &FilterOptions::default());
+    assert!(!filter.is_buffering());
+    assert!(filter.push_line("noise").is_empty());
+    assert_eq!(
+        filter.push_line("error: one"),
+        vec![StreamEvent::Line("error: one".to_string())]
+    );
+    assert!(filter.push_line("error: one").is_empty());
+}
+
+#[test]
+fn chunk_events_emitted_when_block_closes() {
+    let cfg = config(
+        r#"
+command = "t"
+
+[[chunk]]
+split_on = "^Running "
+collect_as = "suites"
+extract = { pattern = 'Running (\S+)', as = "suite" }
+body_extract = [{ pattern = '(\d+) passed', as = "passed" }]
+
+[on_success]
+output = "{suites | each: \"{suite}={passed}\" | join: \",\"}"
+"#,
+    );
+    let mut filter = StreamingFilter::new(&cfg, &[], &FilterOptions::default());
+    assert!(filter.is_buffering());
+    assert!(chunk_events(&filter.push_line("Running a")).is_empty());
+    assert!(chunk_events(&filter.push_line("3 passed")).is_empty());
+
+    let events = filter.push_line("Running b");
+    let chunks = chunk_events(&events);
+    assert_eq!(chunks.len(), 1);
+    assert_eq!(chunks[0].0, "suites");
+    assert_eq!(chunks[0].1["suite"], "a");
+    assert_eq!(chunks[0].1["passed"], "3");
+
+    filter.push_line("5 passed");
+    let (tail, result) = filter.finish(0);
+    let closed = chunk_events(&tail);
+    assert_eq!(closed.len(), 1);
+    assert_eq!(closed[0].1["suite"], "b");
+    assert_eq!(result.output, "a=3,b=5");
+}
+
+#[test]
+fn whole_output_stages_match_buffered_output() {
+    let cfg = config(
+        r#"
+command = "t"
+match_output = [{ contains = "mismatched", output = "type error" }]
+"#,
+    );
+    let (_, out) = stream(&cfg, INPUT, 1);
+    assert_eq!(out, "type error");
+    assert_eq!(out, buffered(&cfg, INPUT, 1));
+}
+
+#[test]
+fn collapse_cr_buffers_for_cross_line_redraws() {
+    let cfg = config(
+        r#"
+command = "t"
+collapse_cr = true
+"#,
+    );
+    let input = "a: 10%\nb: 10%\n\x1b[2A\x1b[2Ka: done\n\x1b[2Kb: done";
+    assert!(StreamingFilter::new(&cfg, &[], &FilterOptions::default()).is_buffering());
+    let (_, out) = stream(&cfg, input, 0);
+    assert_eq!(out, "a: done\nb: done");
+    assert_eq!(out, buffered(&cfg, input, 0));
+}
+
+#[test]
+fn keep_context_buffers_and_matches_buffered_output() {
+    let cfg = config(
+        r#"
+command = "t"
+keep = ["^error"]
+keep_context_before = 1
+keep_context_after = 1
+"#,
+    );
+    assert!(StreamingFilter::new(&cfg, &[], &FilterOptions::default()).is_buffering());
+    let (_, out) = stream(&cfg, INPUT, 1);
+    assert_eq!(out, buffered(&cfg, INPUT, 1));
+}
+
+#[test]
+fn dedup_annotate_buffers_for_final_counts() {
+    let cfg = config(
+        r#"
+command = "t"
+dedup = true
+dedup_annotate = true
+"#,
+    );
+    let input = "warn: slow\nwarn: slow\nwarn: slow\ndone";
+    assert!(StreamingFilter::new(&cfg, &[], &FilterOptions::default()).is_buffering());
+    let (_, out) = stream(&cfg, input, 0);
+    assert_eq!(out, "warn: slow (x3)\ndone");
+    assert_eq!(out, buffered(&cfg, input, 0));
+}
+
+#[test]
+fn collapse_rules_buffer_and_match_buffered_output() {
+    let cfg = config(
+        r#"
+command = "t"
+
+[[collapse]]
+pattern = '^\s+at '
+"#,
+    );
+    let input = "Error: boom\n  at a\n  at b\ndone";
+    assert!(StreamingFilter::new(&cfg, &[], &FilterOptions::default()).is_buffering());
+    let (_, out) = stream(&cfg, input, 1);
+    assert_eq!(out, "Error: boom\n… 2 frames omitted (deepest: at a)\ndone");
+    assert_eq!(out, buffered(&cfg, input, 1));
+}
+
+#[test]
+fn shorten_paths_streams_line_by_line() {
+    let cfg = config(
+        r#"
+command = "t"
+shorten_paths = true
+"#,
+    );
+    let opts = FilterOptions {
+        project_root: Some("/w/app".into()),
+        ..FilterOptions::default()
+    };
+    let input = "/w/app/src/a.rs:1\nnode_modules/pkg/lib/deep/x/y.js";
+    let mut filter = StreamingFilter::new(&cfg, &[], &opts);
+    assert!(!filter.is_buffering());
+    for line in input.lines() {
+        filter.push_line(line);
+    }
+    let (_, result) = filter.finish(0);
+    assert_eq!(result.output, "src/a.rs:1\nnode_modules/pkg/…/y.js");
+    let whole = CommandResult {
+        stdout: String::new(),
+        stderr: String::new(),
+        exit_code: 0,
+        combined: input.to_string(),
+    };
+    assert_eq!(result.output, apply(&cfg, &whole, &[], &opts).output);
+}
+
+#[test]
+fn line_events_have_secrets_redacted() {
+    let cfg = config(r#"command = "t""#);
+    let mut filter = StreamingFilter::new(&cfg, &[], &FilterOptions::default());
+    let events = filter.push_line("export API_KEY=abc123");
+    assert_eq!(
+        events,
+        vec![StreamEvent::Line("export API_KEY=[REDACTED]".to_string())]
+    );
+    let (_, result) = filter.finish(0);
+    assert_eq!(result.output, "export API_KEY=[REDACTED]");
+}
+
+#[test]
+fn caller_vars_render_in_both_paths() {
+    let cfg = config(
+        "command = \"t\"\nskip = [\"^noise\"]\n\
+         [on_success]\noutput = \"{output} ({duration})\"",
+    );
+    let opts = FilterOptions {
+        vars: [("duration".to_string(), "1.5s".to_string())].into(),
+        ..FilterOptions::default()
+    };
+    let result = CommandResult {
+        stdout: String::new(),
+        stderr: String::new(),
+        exit_code: 0,
+        combined: "noise\nok".to_string(),
+    };
+    assert_eq!(apply(&cfg, &result, &[], &opts).output, "ok (1.5s)");
+
+    let mut filter = StreamingFilter::new(&cfg, &[], &opts);
+    filter.push_line("noise");
+    filter.push_line("ok");
+    assert_eq!(filter.finish(0).1.output, "ok (1.5s)");
+}
+
+#[test]
+fn extracted_vars_override_caller_vars() {
+    let cfg = config(
+        "command = \"t\"\n[json]\n[[json.extract]]\npath = \"$.duration\"\nas = \"duration\"\n\
+         [on_success]\noutput = \"took {duration}\"",
+    );
+    let opts = FilterOptions {
+        vars: [("duration".to_string(), "1.5s".to_string())].into(),
+        ..FilterOptions::default()
+    };
+    let result = CommandResult {
+        stdout: String::new(),
+        stderr: String::new(),
+        exit_code: 0,
+        combined: r#"{"duration": "9m"}"#.to_string(),
+    };
+    assert_eq!(apply(&cfg, &result, &[], &opts).output, "took 9m");
+}
+
+/// stdout and stderr lines, interleaved as they arrived.
+const TAGGED: &[(Stream, &str)] = &[
+    (Stream::Stdout, "running 2 tests"),
+    (Stream::Stderr, "warning: unused import"),
+    (Stream::Stdout, "test a ... ok"),
+    (Stream::Stderr, "error: linker failed"),
+    (Stream::Stdout, "test b ... ok"),
+];
+
+fn tagged_result(exit_code: i32) -> CommandResult {
+    let on = |stream| {
+        TAGGED
+            .iter()
+            .filter(|(s, _)| *s == stream)
+            .map(|(_, l)| *l)
+            .collect::<Vec<_>>()
+            .join("\n")
+    };
+    CommandResult {
+        stdout: on(Stream::Stdout),
+        stderr: on(Stream::Stderr),
+        exit_code,
+        combined: TAGGED
+            .iter()
+            .map(|(_, l)| *l)
+            .collect::<Vec<_>>()
+            .join("\n"),
+    }
+}
+
+#[test]
+fn source_reads_one_channel_in_both_paths() {
+    let configs = [
+        "command = \"t\"\nsource = \"stderr\"",
+        "command = \"t\"\nsource = \"stderr\"\nkeep = [\"^error\", \"^test\"]",
+        "command = \"t\"\nsource = \"stdout\"\nskip = [\"^running\"]",
+        // Buffered: sections read their channel from the whole output.
+        "command = \"t\"\nsource = \"stderr\"\n\
+         [[section]]\nname = \"errors\"\nmatch = \"^error\"\ncollect_as = \"errors\"\n\
+         [on_failure]\noutput = \"{errors | lines | len} errors\\n{output}\"",
+        "command = \"t\"\n\
+         [[section]]\nname = \"out\"\nmatch = \".\"\ncollect_as = \"out\"\nsource = \"stdout\"\n\
+         [on_failure]\noutput = \"{out}\"",
+        "command = \"t\"\n[on_failure]\noutput = \"{stderr}\"",
+    ];
+    for toml in configs {
+        let cfg = config(toml);
+        for exit_code in [0, 1] {
+            let mut filter = StreamingFilter::new(&cfg, &[], &FilterOptions::default());
+            for (stream, line) in TAGGED {
+                filter.push_line_from(*stream, line);
+            }
+            let streamed = filter.finish(exit_code).1.output;
+            let batch = apply(
+                &cfg,
+                &tagged_result(exit_code),
+                &[],
+                &FilterOptions::default(),
+            );
+            assert_eq!(streamed, batch.output, "config: {toml}, exit {exit_code}");
+        }
+    }
+}
+
+#[test]
+fn off_channel_lines_are_not_reported() {
+    let cfg = config("command = \"t\"\nsource = \"stderr\"");
+    let mut filter = StreamingFilter::new(&cfg, &[], &FilterOptions::default());
+    assert!(!filter.is_buffering());
+    assert!(
+        filter
+            .push_line_from(Stream::Stdout, "test a ... ok")
+            .is_empty()
+    );
+    assert_eq!(
+        filter.push_line_from(Stream::Stderr, "error: linker failed"),
+        vec![StreamEvent::Line("error: linker failed".to_string())]
+    );
+    // Untagged lines have no known channel, so every filter reads them.
+    assert_eq!(filter.push_line("note").len(), 1);
+}
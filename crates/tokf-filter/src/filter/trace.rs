@@ -0,0 +1,373 @@
+//! Explain mode: [`apply_traced`] runs a filter and reports what each stage
+//! did, for debugging why a line disappeared.
+//!
+//! The output comes from the regular [`apply`](super::apply) run. The report
+//! replays the line stages on clean text (ignoring color passthrough) to
+//! attribute dropped lines; the Lua and WASM escape hatches are not traced.
+
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use tokf_common::config::types::FilterConfig;
+
+use crate::CommandResult;
+
+use super::{
+    FilterOptions, FilterResult, cleanup, collapse, dedup, match_output, paths, replace, section,
+    select_branch, skip, terminal,
+};
+
+/// What a filter run did, stage by stage.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FilterReport {
+    /// Configured line stages, in pipeline order.
+    pub stages: Vec<StageReport>,
+    /// Lines removed before branch rendering, with the stage that removed them.
+    pub dropped: Vec<DroppedLine>,
+    /// How the output was produced: `match_output`, `parse`, `on_exit`,
+    /// `on_success`, `on_failure` or `fallback`, with a reason when useful.
+    pub branch: String,
+    /// Items each `[[section]]` collected, by name.
+    pub sections: Vec<(String, usize)>,
+    /// Wall time of the filter run that produced the output.
+    pub total: Duration,
+}
+
+/// Line counts and timing for one stage.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StageReport {
+    pub name: &'static str,
+    pub lines_in: usize,
+    pub lines_out: usize,
+    pub elapsed: Duration,
+}
+
+/// A line removed by a stage.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DroppedLine {
+    pub stage: &'static str,
+    pub line: String,
+}
+
+/// Apply a filter like [`apply`](super::apply) and explain the result.
+pub fn apply_traced(
+    config: &FilterConfig,
+    result: &CommandResult,
+    args: &[String],
+    opts: &FilterOptions,
+) -> (FilterResult, FilterReport) {
+    let start = Instant::now();
+    let filtered = super::apply(config, result, args, opts);
+    let mut report = FilterReport {
+        total: start.elapsed(),
+        ..FilterReport::default()
+    };
+
+    let match_text = if config.strip_ansi {
+        cleanup::strip_ansi_from(&result.combined)
+    } else {
+        result.combined.clone()
+    };
+    if match_output::find_matching_rule(&config.match_output, &match_text).is_some() {
+        "match_output".clone_into(&mut report.branch);
+        return (filtered, report);
+    }
+
+    trace_lines(config, result.stream(config.source), opts, &mut report);
+    report.sections = collect_section_counts(config, result);
+    report.branch = describe_branch(config, result.exit_code, &report.sections);
+    (filtered, report)
+}
+
+fn trace_lines(
+    config: &FilterConfig,
+    input: &str,
+    opts: &FilterOptions,
+    report: &mut FilterReport,
+) {
+    let mut lines: Vec<String> = input.lines().map(String::from).collect();
+    if config.collapse_cr {
+        lines = stage(report, "collapse_cr", &lines, |_| {
+            terminal::collapse_cr(input)
+                .lines()
+                .map(String::from)
+                .collect()
+        });
+    }
+    if config.shorten_paths {
+        let shortener = paths::PathShortener::new(opts.project_root.as_deref());
+        lines = stage(report, "shorten_paths", &lines, |l| {
+            l.iter().map(|s| shortener.apply(s).into_owned()).collect()
+        });
+    }
+    if !config.replace.is_empty() {
+        lines = stage(report, "replace", &lines, |l| {
+            replace::apply_replace(&config.replace, &refs(l))
+        });
+    }
+    if !config.collapse.is_empty() {
+        lines = stage(report, "collapse", &lines, |l| {
+            collapse::apply_collapse(&config.collapse, l.to_vec())
+        });
+    }
+    if config.strip_ansi || config.trim_lines {
+        lines = stage(report, "strip_ansi/trim_lines", &lines, |l| {
+            cleanup::apply_line_cleanup(config, &refs(l))
+        });
+    }
+    if !config.skip.is_empty() {
+        lines = stage(report, "skip", &lines, |l| {
+            owned(&skip::apply_skip(&config.skip, &refs(l)))
+        });
+    }
+    if !config.keep.is_empty() {
+        lines = stage(report, "keep", &lines, |l| {
+            owned(&skip::apply_keep_with_context(
+                &config.keep,
+                &refs(l),
+                config.keep_context_before,
+                config.keep_context_after,
+            ))
+        });
+    }
+    if config.dedup {
+        stage(report, "dedup", &lines, |l| {
+            owned(&dedup::apply_dedup(&refs(l), config.dedup_window))
+        });
+    }
+}
+
+/// Run one stage, recording its counts, timing and the lines it removed.
+fn stage(
+    report: &mut FilterReport,
+    name: &'static str,
+    lines: &[String],
+    f: impl FnOnce(&[String]) -> Vec<String>,
+) -> Vec<String> {
+    let start = Instant::now();
+    let out = f(lines);
+    report.stages.push(StageReport {
+        name,
+        lines_in: lines.len(),
+        lines_out: out.len(),
+        elapsed: start.elapsed(),
+    });
+    report.dropped.extend(
+        removed_lines(lines, &out)
+            .into_iter()
+            .map(|line| DroppedLine { stage: name, line }),
+    );
+    out
+}
+
+/// Lines of `before` missing from `after`. Stages keep the survivors in
+/// order; lines a stage synthesized (collapse summaries) are skipped over.
+///
+/// Linear in the line count: `remaining` counts the lines of `before` not
+/// yet visited, so telling a synthesized line apart is a lookup, not a scan.
+fn removed_lines(before: &[String], after: &[String]) -> Vec<String> {
+    let mut remaining: HashMap<&str, usize> = HashMap::new();
+    for line in before {
+        *remaining.entry(line.as_str()).or_default() += 1;
+    }
+    let mut removed = Vec::new();
+    let mut ai = 0;
+    for line in before {
+        while ai < after.len()
+            && after[ai] != *line
+            && remaining.get(after[ai].as_str()).is_none_or(|&n| n == 0)
+        {
+            ai += 1;
+        }
+        if ai < after.len() && after[ai] == *line {
+            ai += 1;
+        } else {
+            removed.push(line.clone());
+        }
+        if let Some(n) = remaining.get_mut(line.as_str()) {
+            *n -= 1;
+        }
+    }
+    removed
+}
+
+fn collect_section_counts(config: &FilterConfig, result: &CommandResult) -> Vec<(String, usize)> {
+    let structural = config.json.is_none() && config.parse.is_none();
+    if !structural || config.section.is_empty() {
+        return Vec::new();
+    }
+    let sections =
+        section::collect_sections_by_source(&config.section, config.source, |s| result.stream(s));
+    let mut counts: Vec<(String, usize)> = sections
+        .iter()
+        .map(|(name, data)| (name.clone(), data.count()))
+        .collect();
+    counts.sort();
+    counts
+}
+
+fn describe_branch(config: &FilterConfig, exit_code: i32, sections: &[(String, usize)]) -> String {
+    if config.parse.is_some() && config.json.is_none() {
+        return "parse".to_string();
+    }
+    let name = if config.on_exit.iter().any(|e| e.codes.contains(&exit_code)) {
+        format!("on_exit (code {exit_code})")
+    } else if exit_code == 0 {
+        "on_success".to_string()
+    } else {
+        "on_failure".to_string()
+    };
+    let Some(branch) = select_branch(config, exit_code) else {
+        return format!("fallback (no {name} branch)");
+    };
+    let sections_empty = !sections.is_empty() && sections.iter().all(|(_, n)| *n == 0);
+    if branch.output.is_some() && sections_empty {
+        return format!("{name} → fallback (sections collected nothing)");
+    }
+    name
+}
+
+fn refs(lines: &[String]) -> Vec<&str> {
+    lines.iter().map(String::as_str).collect()
+}
+
+fn owned(lines: &[&str]) -> Vec<String> {
+    lines.iter().map(ToString::to_string).collect()
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+
+    fn run(toml: &str, output: &str, exit_code: i32) -> (FilterResult, FilterReport) {
+        let config: FilterConfig = toml::from_str(toml).unwrap();
+        let result = CommandResult {
+            stdout: String::new(),
+            stderr: String::new(),
+            exit_code,
+            combined: output.to_string(),
+        };
+        apply_traced(&config, &result, &[], &FilterOptions::default())
+    }
+
+    #[test]
+    fn attributes_dropped_lines_to_stages() {
+        let (filtered, report) = run(
+            r#"
+command = "t"
+skip = ["^Compiling"]
+keep = ["error|ok"]
+dedup = true
+"#,
+            "Compiling a\nerror: x\nerror: x\nnote: y\nok",
+            0,
+        );
+        assert_eq!(filtered.output, "error: x\nok");
+        let names: Vec<_> = report.stages.iter().map(|s| s.name).collect();
+        assert_eq!(names, vec!["skip", "keep", "dedup"]);
+        assert_eq!(
+            report.dropped,
+            vec![
+                DroppedLine {
+                    stage: "skip",
+                    line: "Compiling a".to_string()
+                },
+                DroppedLine {
+                    stage: "keep",
+                    line: "note: y".to_string()
+                },
+                DroppedLine {
+                    stage: "dedup",
+                    line: "error: x".to_string()
+                },
+            ]
+        );
+        assert_eq!(report.branch, "fallback (no on_success branch)");
+    }
+
+    #[test]
+    fn collapse_summary_lines_are_not_mistaken_for_drops() {
+        let (_, report) = run(
+            r#"
+command = "t"
+[[collapse]]
+pattern = '^\s+at '
+"#,
+            "Error\n  at a\n  at b\ndone",
+            1,
+        );
+        let dropped: Vec<_> = report.dropped.iter().map(|d| d.line.as_str()).collect();
+        assert_eq!(dropped, vec!["  at a", "  at b"]);
+    }
+
+    #[test]
+    fn removed_lines_keeps_up_with_long_logs() {
+        let owned = |v: &[&str]| v.iter().map(ToString::to_string).collect::<Vec<_>>();
+        let before = owned(&["a", "b", "a", "c", "b"]);
+        let after = owned(&["a", "summary", "c", "b"]);
+        assert_eq!(removed_lines(&before, &after), ["b", "a"]);
+
+        // Quadratic before: 200k lines, every other one dropped.
+        let before: Vec<String> = (0..200_000).map(|i| format!("line {i}")).collect();
+        let mut after: Vec<String> = before.iter().step_by(2).cloned().collect();
+        after.push("+ 100000 more".to_string());
+        let removed = removed_lines(&before, &after);
+        assert_eq!(removed.len(), 100_000);
+        assert_eq!(removed[0], "line 1");
+    }
+
+    #[test]
+    fn reports_sections_and_branch() {
+        let (_, report) = run(
+            r#"
+command = "t"
+[[section]]
+name = "failures"
+match = "^FAIL"
+collect_as = "failures"
+
+[on_failure]
+output = "{failures.count} failed"
+"#,
+            "PASS a\nFAIL b",
+            1,
+        );
+        assert_eq!(report.sections, vec![("failures".to_string(), 1)]);
+        assert_eq!(report.branch, "on_failure");
+
+        let (_, report) = run(
+            r#"
+command = "t"
+[[section]]
+match = "^FAIL"
+collect_as = "failures"
+
+[on_failure]
+output = "{failures.count} failed"
+"#,
+            "PASS a",
+            1,
+        );
+        assert_eq!(
+            report.branch,
+            "on_failure → fallback (sections collected nothing)"
+        );
+    }
+
+    #[test]
+    fn match_output_short_circuit_is_reported() {
+        let (filtered, report) = run(
+            r#"
+command = "t"
+skip = ["x"]
+match_output = [{ contains = "rejected", output = "push rejected" }]
+"#,
+            "x\nrejected",
+            1,
+        );
+        assert_eq!(filtered.output, "push rejected");
+        assert_eq!(report.branch, "match_output");
+        assert!(report.stages.is_empty());
+    }
+}
@@ -0,0 +1,270 @@
+//! `wasm_module`: a WebAssembly plugin escape hatch for filters that need a
+//! real parser (protobuf, binary logs) rather than Lua string handling.
+//!
+//! ## Plugin ABI
+//!
+//! The module must import nothing and export:
+//!
+//! - `memory` — its linear memory
+//! - `alloc(len: i32) -> i32` — return a buffer of `len` bytes for the input
+//! - `filter(ptr: i32, len: i32, exit_code: i32) -> i64` — filter the UTF-8
+//!   output written at `ptr`. Return `(out_ptr << 32) | out_len` to replace
+//!   the output, or any negative value to fall through to the TOML pipeline.
+//!
+//! Execution is bounded by fuel (roughly one unit per instruction), a
+//! memory cap and a cap on the size of the result; a module with imports fails to instantiate, so plugins have no
+//! filesystem, network or clock access.
+
+use anyhow::Context as _;
+use wasmtime::{Config, Engine, Instance, Module, Store, StoreLimits, StoreLimitsBuilder};
+
+/// Default fuel for one plugin call (100 million units).
+const DEFAULT_FUEL: u64 = 100_000_000;
+
+/// Default linear-memory cap (16 MB).
+const DEFAULT_MEMORY_LIMIT: usize = 16 * 1024 * 1024;
+
+/// Default cap on the result a plugin may return (8 MB).
+const DEFAULT_OUTPUT_LIMIT: usize = 8 * 1024 * 1024;
+
+/// Sandboxed WASM execution limits.
+pub struct SandboxLimits {
+    /// Fuel available to one call before it traps.
+    pub fuel: u64,
+    /// Maximum linear memory in bytes the module may use.
+    pub memory_limit: usize,
+    /// Maximum size in bytes of the result the module may return.
+    pub output_limit: usize,
+}
+
+impl Default for SandboxLimits {
+    fn default() -> Self {
+        Self {
+            fuel: DEFAULT_FUEL,
+            memory_limit: DEFAULT_MEMORY_LIMIT,
+            output_limit: DEFAULT_OUTPUT_LIMIT,
+        }
+    }
+}
+
+/// Run a WASM plugin (binary or text format) against `output`.
+///
+/// Returns `Some(text)` when the plugin replaces the output, `None` when it
+/// asks to fall through.
+///
+/// # Errors
+///
+/// Returns an error if the module fails to compile or instantiate (including
+/// when it declares imports), lacks a required export, runs out of fuel or
+/// memory, traps, or returns an out-of-bounds, oversized or non-UTF-8 result.
+pub fn run_wasm_module(
+    module_bytes: &[u8],
+    output: &str,
+    exit_code: i32,
+    limits: &SandboxLimits,
+) -> anyhow::Result<Option<String>> {
+    let mut config = Config::new();
+    config.consume_fuel(true);
+    let engine = Engine::new(&config)?;
+    let module = Module::new(&engine, module_bytes)
+        .map_err(anyhow::Error::from)
+        .context("compile wasm_module")?;
+
+    let store_limits = StoreLimitsBuilder::new()
+        .memory_size(limits.memory_limit)
+        .instances(1)
+        .build();
+    let mut store: Store<StoreLimits> = Store::new(&engine, store_limits);
+    store.limiter(|limits| limits);
+    store.set_fuel(limits.fuel)?;
+
+    let instance = Instance::new(&mut store, &module, &[])
+        .map_err(anyhow::Error::from)
+        .context("instantiate wasm_module")?;
+    let memory = instance
+        .get_memory(&mut store, "memory")
+        .context("wasm_module must export 'memory'")?;
+    let alloc = instance.get_typed_func::<i32, i32>(&mut store, "alloc")?;
+    let filter = instance.get_typed_func::<(i32, i32, i32), i64>(&mut store, "filter")?;
+
+    let len = i32::try_from(output.len()).context("output too large for wasm_module")?;
+    let ptr = alloc
+        .call(&mut store, len)
+        .map_err(anyhow::Error::from)
+        .context("wasm_module alloc")?;
+    memory
+        .write(&mut store, offset(ptr)?, output.as_bytes())
+        .context("wasm_module alloc returned an out-of-bounds buffer")?;
+
+    let packed = filter
+        .call(&mut store, (ptr, len, exit_code))
+        .map_err(anyhow::Error::from)
+        .context("wasm_module filter")?;
+    if packed < 0 {
+        return Ok(None);
+    }
+    let packed = packed.cast_unsigned();
+    let out_ptr = usize::try_from(packed >> 32)?;
+    let out_len = usize::try_from(packed & 0xffff_ffff)?;
+    // Check before allocating: the plugin picks `out_len`.
+    if out_ptr
+        .checked_add(out_len)
+        .is_none_or(|end| end > memory.data_size(&store))
+    {
+        anyhow::bail!("wasm_module returned an out-of-bounds result");
+    }
+    if out_len > limits.output_limit {
+        anyhow::bail!(
+            "wasm_module returned {out_len} bytes, over the {} byte limit",
+            limits.output_limit
+        );
+    }
+    let mut buf = vec![0; out_len];
+    memory
+        .read(&store, out_ptr, &mut buf)
+        .context("wasm_module returned an out-of-bounds result")?;
+    let text = String::from_utf8(buf).context("wasm_module returned invalid UTF-8")?;
+    Ok(Some(text))
+}
+
+fn offset(ptr: i32) -> anyhow::Result<usize> {
+    Ok(usize::try_from(ptr.cast_unsigned())?)
+}
+
+/// Load and run the configured plugin, for the `apply` pipeline.
+///
+/// Load and runtime errors are printed to stderr and treated as passthrough.
+pub(super) fn run_configured(path: &str, output: &str, exit_code: i32) -> Option<String> {
+    let result = std::fs::read(path)
+        .with_context(|| format!("wasm_module: cannot read file '{path}'"))
+        .and_then(|bytes| run_wasm_module(&bytes, output, exit_code, &SandboxLimits::default()));
+    result.unwrap_or_else(|e| {
+        eprintln!("[tokf] wasm module error: {e:#}");
+        None
+    })
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+
+    /// `alloc` hands out a fixed buffer at 1024.
+    const PRELUDE: &str = r#"
+        (memory (export "memory") 1)
+        (func (export "alloc") (param i32) (result i32) i32.const 1024)
+    "#;
+
+    fn run(body: &str, output: &str, exit_code: i32) -> anyhow::Result<Option<String>> {
+        let wat = format!("(module {PRELUDE} {body})");
+        run_wasm_module(wat.as_bytes(), output, exit_code, &SandboxLimits::default())
+    }
+
+    #[test]
+    fn echo_plugin_returns_its_input() {
+        let body = r#"
+            (func (export "filter") (param i32 i32 i32) (result i64)
+              (i64.or
+                (i64.shl (i64.extend_i32_u (local.get 0)) (i64.const 32))
+                (i64.extend_i32_u (local.get 1))))
+        "#;
+        assert_eq!(run(body, "héllo", 0).unwrap(), Some("héllo".to_string()));
+    }
+
+    #[test]
+    fn negative_result_falls_through() {
+        // Replace with "FAILED" on non-zero exit, otherwise fall through.
+        let body = r#"
+            (data (i32.const 16) "FAILED")
+            (func (export "filter") (param i32 i32 i32) (result i64)
+              (if (result i64) (local.get 2)
+                (then (i64.const 68719476742))
+                (else (i64.const -1))))
+        "#;
+        assert_eq!(run(body, "x", 0).unwrap(), None);
+        assert_eq!(run(body, "x", 1).unwrap(), Some("FAILED".to_string()));
+    }
+
+    #[test]
+    fn infinite_loop_runs_out_of_fuel() {
+        let body = r#"
+            (func (export "filter") (param i32 i32 i32) (result i64)
+              (loop br 0)
+              i64.const -1)
+        "#;
+        let limits = SandboxLimits {
+            fuel: 10_000,
+            ..SandboxLimits::default()
+        };
+        let wat = format!("(module {PRELUDE} {body})");
+        assert!(run_wasm_module(wat.as_bytes(), "", 0, &limits).is_err());
+    }
+
+    #[test]
+    fn memory_beyond_limit_is_rejected() {
+        // 512 pages = 32 MB, over the 16 MB default.
+        let wat = r#"
+            (module
+              (memory (export "memory") 512)
+              (func (export "alloc") (param i32) (result i32) i32.const 0)
+              (func (export "filter") (param i32 i32 i32) (result i64) i64.const -1))
+        "#;
+        assert!(run_wasm_module(wat.as_bytes(), "", 0, &SandboxLimits::default()).is_err());
+    }
+
+    #[test]
+    fn imports_are_rejected() {
+        let wat = format!(
+            r#"(module
+                 (import "env" "read_file" (func (param i32)))
+                 {PRELUDE}
+                 (func (export "filter") (param i32 i32 i32) (result i64) i64.const -1))"#
+        );
+        let err = run_wasm_module(wat.as_bytes(), "", 0, &SandboxLimits::default()).unwrap_err();
+        assert!(format!("{err:#}").contains("instantiate"), "{err:#}");
+    }
+
+    #[test]
+    fn out_of_bounds_result_is_error() {
+        let body = r#"
+            (func (export "filter") (param i32 i32 i32) (result i64)
+              i64.const 0x7fff000000000010)
+        "#;
+        assert!(run(body, "", 0).is_err());
+    }
+
+    #[test]
+    fn huge_result_length_is_rejected_before_reading() {
+        // Claims 4 GiB - 1 at offset 0 of a one-page memory.
+        let body = r#"
+            (func (export "filter") (param i32 i32 i32) (result i64)
+              i64.const 0xffffffff)
+        "#;
+        let err = run(body, "", 0).unwrap_err();
+        assert!(format!("{err:#}").contains("out-of-bounds"), "{err:#}");
+    }
+
+    #[test]
+    fn result_over_the_output_limit_is_rejected() {
+        let body = r#"
+            (func (export "filter") (param i32 i32 i32) (result i64)
+              i64.const 100)
+        "#;
+        let wat = format!("(module {PRELUDE} {body})");
+        let limits = SandboxLimits {
+            output_limit: 99,
+            ..SandboxLimits::default()
+        };
+        let err = run_wasm_module(wat.as_bytes(), "", 0, &limits).unwrap_err();
+        assert!(
+            format!("{err:#}").contains("over the 99 byte limit"),
+            "{err:#}"
+        );
+        assert!(run(body, "", 0).unwrap().is_some());
+    }
+
+    #[test]
+    fn missing_file_falls_through() {
+        assert_eq!(run_configured("/nonexistent/plugin.wasm", "x", 0), None);
+    }
+}
@@ -3,6 +3,8 @@ pub mod examples;
 pub mod filter;
 pub mod verify;
 
+use tokf_common::config::types::Stream;
+
 /// The result of executing a command, used as input to the filter pipeline.
 ///
 /// This struct contains only the data needed for filtering — it does not
@@ -14,3 +16,19 @@ pub struct CommandResult {
     pub exit_code: i32,
     pub combined: String,
 }
+
+impl CommandResult {
+    /// Text of one output channel.
+    ///
+    /// Results without per-channel capture (both `stdout` and `stderr` empty,
+    /// as with test-case fixtures) serve `combined` for every channel, so
+    /// filters that select a channel still see their input.
+    pub fn stream(&self, stream: Stream) -> &str {
+        let captured = !self.stdout.is_empty() || !self.stderr.is_empty();
+        match stream {
+            Stream::Stdout if captured => &self.stdout,
+            Stream::Stderr if captured => &self.stderr,
+            _ => &self.combined,
+        }
+    }
+}
@@ -1,3 +1,5 @@
+use std::collections::BTreeMap;
+
 use serde::{Deserialize, Serialize};
 
 use crate::engine::ExternalEngineConfig;
@@ -38,6 +40,28 @@ pub struct RewriteConfig {
     /// are local, so the whole command is wrapped with `tokf run` and its
     /// output filtered. See issue #403.
     pub local_wrapper: Option<LocalWrapperConfig>,
+
+    /// Per-tool hook settings, keyed by hook format (`claude-code`, `gemini`,
+    /// `cursor`, `codex`, `windsurf`) or adapter name: `[hook.claude-code]`.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub hook: BTreeMap<String, HookToolConfig>,
+}
+
+/// Which commands one tool's hook may rewrite.
+///
+/// Both lists hold globs (`*`, `?`) matched against each simple command of a
+/// compound command, with any env prefix stripped. A command is left
+/// untouched — not wrapped, its exit code not masked — when any part matches
+/// `never`, or when `only` is set and some part matches none of it.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct HookToolConfig {
+    /// When non-empty, rewrite only commands matching one of these globs.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub only: Vec<String>,
+
+    /// Never rewrite commands matching one of these globs.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub never: Vec<String>,
 }
 
 /// "Transparent-arg" commands: their last argument is opaque shell code.
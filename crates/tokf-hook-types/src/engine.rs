@@ -21,7 +21,8 @@ pub struct ExternalEngineConfig {
     pub on_error: ErrorFallback,
 
     /// Override the default format strings used for `{format}` substitution.
-    /// Keys are the default names (`claude-code`, `gemini`, `cursor`);
+    /// Keys are the default names (`claude-code`, `gemini`, `cursor`, `codex`,
+    /// `windsurf`, `adapter`);
     /// values are the replacements the engine expects.
     ///
     /// Example: `{ "claude-code" = "claude", "gemini" = "google" }`
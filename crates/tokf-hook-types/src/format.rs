@@ -10,6 +10,12 @@ pub enum HookFormat {
     Cursor,
     /// `OpenAI` Codex CLI: `hookSpecificOutput.permissionDecision = "deny"`.
     Codex,
+    /// Windsurf Cascade: `decision`, as for Gemini CLI. Windsurf itself only
+    /// reads the exit code and stderr of a `pre_run_command` hook.
+    Windsurf,
+    /// A tool described by a TOML hook adapter: engines answer in Claude
+    /// Code's shape, `hookSpecificOutput.permissionDecision`.
+    Adapter,
 }
 
 impl HookFormat {
@@ -22,6 +28,8 @@ impl HookFormat {
             Self::Gemini => "gemini",
             Self::Cursor => "cursor",
             Self::Codex => "codex",
+            Self::Windsurf => "windsurf",
+            Self::Adapter => "adapter",
         }
     }
 }
@@ -0,0 +1,10 @@
+-- Author-supplied metadata from the filter TOML (`description`, `tags`,
+-- `version`), copied out at publish time so search can match on tags without
+-- reading every filter from R2.
+--
+-- Existing rows keep NULL / empty values; they are filled in the next time
+-- the filter is published. Tags are stored lowercased.
+ALTER TABLE filters ADD COLUMN description TEXT;
+ALTER TABLE filters ADD COLUMN tags TEXT[] NOT NULL DEFAULT ARRAY[]::TEXT[];
+ALTER TABLE filters ADD COLUMN version TEXT;
+CREATE INVERTED INDEX filters_tags_idx ON filters(tags);
@@ -0,0 +1,20 @@
+-- Ed25519 filter signing.
+--
+-- `signing_keys` holds the public keys authors register with `tokf auth keys
+-- generate`; a key belongs to exactly one user. Publishing with a signature
+-- requires the key to be registered to the publisher.
+--
+-- `filters.signature` / `filters.signing_key` record the signature over the
+-- uploaded TOML and the public key it verifies with, both hex. NULL for
+-- unsigned filters, including every filter published before this migration.
+-- The key is copied rather than referenced so that revoking a key does not
+-- erase the record of what an existing filter was signed with.
+CREATE TABLE signing_keys (
+    public_key TEXT PRIMARY KEY,
+    user_id BIGINT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+CREATE INDEX signing_keys_user_id_idx ON signing_keys(user_id);
+
+ALTER TABLE filters ADD COLUMN signature TEXT;
+ALTER TABLE filters ADD COLUMN signing_key TEXT;
@@ -0,0 +1,14 @@
+-- Personal access tokens for CI.
+--
+-- A personal access token is an `auth_tokens` row with a `name`; rows from
+-- the device flow leave it NULL and keep full access. `scopes` limits what a
+-- personal access token may do (`read`, `publish`, `sync`, `stdlib`); it is
+-- NULL for device-flow tokens.
+--
+-- `users.stdlib_publisher` is granted by operators (there is no endpoint for
+-- it) and is required to mint or use a token with the `stdlib` scope, which
+-- the service-token routes accept in place of a service token.
+ALTER TABLE auth_tokens ADD COLUMN name TEXT;
+ALTER TABLE auth_tokens ADD COLUMN scopes TEXT[];
+
+ALTER TABLE users ADD COLUMN stdlib_publisher BOOLEAN NOT NULL DEFAULT FALSE;
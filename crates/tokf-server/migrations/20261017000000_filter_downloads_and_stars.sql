@@ -0,0 +1,14 @@
+-- Popularity signals for sorting search results.
+--
+-- `download_count` is bumped on every successful `GET .../download`,
+-- including `304 Not Modified` revalidations. `filter_stars` holds one row
+-- per user who starred a filter.
+ALTER TABLE filters ADD COLUMN download_count BIGINT NOT NULL DEFAULT 0;
+
+CREATE TABLE filter_stars (
+    user_id BIGINT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    filter_hash TEXT NOT NULL REFERENCES filters(content_hash) ON DELETE CASCADE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (user_id, filter_hash)
+);
+CREATE INDEX idx_filter_stars_filter ON filter_stars(filter_hash);
@@ -0,0 +1,9 @@
+-- Yanking: an author (or a stdlib publisher) withdraws a filter. Yanked
+-- filters stay downloadable by hash, so existing installs keep working, but
+-- drop out of search and the catalog.
+--
+-- Authors can now also deprecate their own filters through the API, reusing
+-- `deprecated_at` and `successor_hash`; for such manual deprecations
+-- `deprecated_at` holds an RFC 3339 timestamp rather than a tokf version.
+ALTER TABLE filters ADD COLUMN yanked_at TIMESTAMPTZ;
+ALTER TABLE filters ADD COLUMN yank_reason TEXT;
@@ -0,0 +1,11 @@
+-- Organization namespaces: a filter may be published under a GitHub org the
+-- author belongs to (`org/<name>`). `org` holds the org's GitHub login as
+-- cached in `users.orgs` at sign-in.
+--
+-- A `private` filter is visible only to its author and to members of its
+-- org: it is left out of search, the catalog and public statistics for
+-- everyone else, and fetching it by hash answers 404.
+ALTER TABLE filters ADD COLUMN org TEXT;
+ALTER TABLE filters ADD COLUMN visibility TEXT NOT NULL DEFAULT 'public'
+    CHECK (visibility IN ('public', 'private'));
+CREATE INDEX idx_filters_org ON filters(org) WHERE org IS NOT NULL;
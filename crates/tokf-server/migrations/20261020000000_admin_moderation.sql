@@ -0,0 +1,31 @@
+-- Moderation: admins can delist filters, ban users and review filters that
+-- run Luau. Every admin action is recorded in `admin_audit_log`.
+--
+-- Roles are granted by hand:
+--   UPDATE users SET role = 'admin' WHERE username = '...';
+ALTER TABLE users ADD COLUMN role TEXT NOT NULL DEFAULT 'user'
+    CHECK (role IN ('user', 'admin'));
+ALTER TABLE users ADD COLUMN banned_at TIMESTAMPTZ;
+ALTER TABLE users ADD COLUMN ban_reason TEXT;
+
+-- A delisted filter is hidden from everyone, its author included: search,
+-- the catalog, details and downloads all answer as if it did not exist.
+ALTER TABLE filters ADD COLUMN delisted_at TIMESTAMPTZ;
+ALTER TABLE filters ADD COLUMN delist_reason TEXT;
+
+-- NULL for filters without a `[lua_script]`; published Luau filters start
+-- out `pending`. A `rejected` filter is hidden like a delisted one.
+ALTER TABLE filters ADD COLUMN review_status TEXT
+    CHECK (review_status IN ('pending', 'approved', 'rejected'));
+CREATE INDEX idx_filters_review_status ON filters(review_status)
+    WHERE review_status IS NOT NULL;
+
+CREATE TABLE admin_audit_log (
+    id BIGSERIAL PRIMARY KEY,
+    admin_id BIGINT REFERENCES users(id) ON DELETE SET NULL,
+    action TEXT NOT NULL,
+    -- A filter's content hash or a user's login, depending on `action`.
+    target TEXT NOT NULL,
+    reason TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
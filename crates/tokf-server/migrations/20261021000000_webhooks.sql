@@ -0,0 +1,21 @@
+-- Webhooks: a user registers a URL to be told about registry activity on
+-- their own filters, or, with `org` set, on filters published under a
+-- GitHub org they belong to. Payloads are signed with HMAC-SHA256 using
+-- `secret`, which is shown to the user once, when the webhook is created.
+--
+-- `events` lists the events delivered (`filter.published`,
+-- `filter.tests_updated`, `filter.yanked`). `last_status` holds the HTTP
+-- status of the latest delivery, or NULL if it failed before a response.
+CREATE TABLE webhooks (
+    id BIGSERIAL PRIMARY KEY,
+    user_id BIGINT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    org TEXT,
+    url TEXT NOT NULL,
+    secret TEXT NOT NULL,
+    events TEXT[] NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    last_delivery_at TIMESTAMPTZ,
+    last_status INT
+);
+CREATE INDEX idx_webhooks_user_id ON webhooks(user_id);
+CREATE INDEX idx_webhooks_org ON webhooks(org) WHERE org IS NOT NULL;
@@ -0,0 +1,74 @@
+use axum::{extract::FromRequestParts, http::request::Parts};
+
+use super::token::AuthUser;
+use crate::error::AppError;
+
+/// A user's role on the registry, stored in `users.role`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Role {
+    #[default]
+    User,
+    /// May use the `/api/admin` moderation routes.
+    Admin,
+}
+
+impl Role {
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::User => "user",
+            Self::Admin => "admin",
+        }
+    }
+
+    /// Parse a stored role. Anything unrecognised is an ordinary user, so an
+    /// unexpected value never grants admin rights.
+    pub fn parse(s: &str) -> Self {
+        if s == Self::Admin.as_str() {
+            Self::Admin
+        } else {
+            Self::User
+        }
+    }
+}
+
+/// An authenticated user with the `admin` role.
+///
+/// Rejects like [`AuthUser`] for missing, invalid or banned tokens, and with
+/// `403 Forbidden` for everyone who is not an admin. Personal access tokens
+/// never reach the admin routes: [`super::scope`] closes them.
+#[derive(Debug, Clone)]
+pub struct AdminUser(pub AuthUser);
+
+impl FromRequestParts<crate::state::AppState> for AdminUser {
+    type Rejection = AppError;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &crate::state::AppState,
+    ) -> Result<Self, Self::Rejection> {
+        let user = AuthUser::from_request_parts(parts, state).await?;
+        if user.role != Role::Admin {
+            return Err(AppError::Forbidden(
+                "this endpoint is restricted to registry admins".to_string(),
+            ));
+        }
+        Ok(Self(user))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn role_names_roundtrip() {
+        assert_eq!(Role::parse(Role::Admin.as_str()), Role::Admin);
+        assert_eq!(Role::parse(Role::User.as_str()), Role::User);
+    }
+
+    #[test]
+    fn unknown_roles_are_ordinary_users() {
+        assert_eq!(Role::parse("Admin"), Role::User);
+        assert_eq!(Role::parse(""), Role::User);
+    }
+}
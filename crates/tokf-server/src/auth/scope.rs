@@ -0,0 +1,128 @@
+use axum::http::Method;
+
+/// What a personal access token may be used for.
+///
+/// Every scope can make `GET` requests; the others add the writes named
+/// below. Anything not covered — account deletion, signing keys, minting or
+/// revoking tokens, webhooks, the admin routes — needs a device-flow login.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Scope {
+    /// Read-only access.
+    Read,
+    /// Publish filters, replace their test suites, and yank or deprecate them.
+    Publish,
+    /// Register machines and sync usage.
+    Sync,
+    /// Use the service-token routes (standard library publishing, backfills,
+    /// catalog refresh). Requires `users.stdlib_publisher`.
+    Stdlib,
+}
+
+impl Scope {
+    pub const ALL: [Self; 4] = [Self::Read, Self::Publish, Self::Sync, Self::Stdlib];
+
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Read => "read",
+            Self::Publish => "publish",
+            Self::Sync => "sync",
+            Self::Stdlib => "stdlib",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|scope| scope.as_str() == s)
+    }
+}
+
+/// The scope a token needs for `method` on `path`, or `None` if the route is
+/// closed to personal access tokens.
+pub fn required_scope(method: &Method, path: &str) -> Option<Scope> {
+    if *method == Method::GET || *method == Method::HEAD {
+        let session_only = ["/api/tokens", "/api/webhooks", "/api/admin"]
+            .iter()
+            .any(|prefix| path.starts_with(prefix));
+        return (!session_only).then_some(Scope::Read);
+    }
+    let segments: Vec<&str> = path.trim_end_matches('/').split('/').collect();
+    match (method.as_str(), segments.as_slice()) {
+        ("POST", ["", "api", "filters"])
+        | ("PUT", ["", "api", "filters", _, "tests"])
+        | ("PUT" | "DELETE", ["", "api", "filters", _, "yank" | "deprecation"]) => {
+            Some(Scope::Publish)
+        }
+        ("POST", ["", "api", "sync" | "machines"] | ["", "api", "sync", "reconcile"]) => {
+            Some(Scope::Sync)
+        }
+        _ => None,
+    }
+}
+
+/// Whether a token holding `scopes` may call `method` on `path`.
+pub fn permits(scopes: &[String], method: &Method, path: &str) -> bool {
+    required_scope(method, path)
+        .is_some_and(|needed| needed == Scope::Read || scopes.iter().any(|s| s == needed.as_str()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scopes(names: &[&str]) -> Vec<String> {
+        names.iter().map(ToString::to_string).collect()
+    }
+
+    #[test]
+    fn scope_names_roundtrip() {
+        for scope in Scope::ALL {
+            assert_eq!(Scope::parse(scope.as_str()), Some(scope));
+        }
+        assert_eq!(Scope::parse("admin"), None);
+    }
+
+    #[test]
+    fn every_scope_can_read() {
+        let read = scopes(&["read"]);
+        assert!(permits(&read, &Method::GET, "/api/filters"));
+        assert!(permits(&scopes(&["sync"]), &Method::GET, "/api/gain"));
+        assert!(!permits(&read, &Method::POST, "/api/filters"));
+        assert!(!permits(&read, &Method::POST, "/api/sync"));
+    }
+
+    #[test]
+    fn writes_need_their_scope() {
+        let publish = scopes(&["publish"]);
+        assert!(permits(&publish, &Method::POST, "/api/filters"));
+        assert!(permits(&publish, &Method::PUT, "/api/filters/abc/tests"));
+        assert!(permits(&publish, &Method::PUT, "/api/filters/abc/yank"));
+        assert!(permits(
+            &publish,
+            &Method::DELETE,
+            "/api/filters/abc/deprecation"
+        ));
+        assert!(!permits(&publish, &Method::PUT, "/api/filters/abc/star"));
+        assert!(!permits(&publish, &Method::POST, "/api/filters/abc/star"));
+        assert!(!permits(&publish, &Method::POST, "/api/sync"));
+
+        let sync = scopes(&["sync"]);
+        assert!(permits(&sync, &Method::POST, "/api/sync"));
+        assert!(permits(&sync, &Method::POST, "/api/machines"));
+        assert!(permits(&sync, &Method::POST, "/api/sync/reconcile"));
+        assert!(!permits(&sync, &Method::POST, "/api/filters"));
+    }
+
+    #[test]
+    fn session_only_routes_are_closed() {
+        let all = scopes(&["read", "publish", "sync", "stdlib"]);
+        assert!(!permits(&all, &Method::DELETE, "/api/account"));
+        assert!(!permits(&all, &Method::POST, "/api/keys"));
+        assert!(!permits(&all, &Method::DELETE, "/api/keys/abc"));
+        assert!(!permits(&all, &Method::POST, "/api/tokens"));
+        assert!(!permits(&all, &Method::GET, "/api/tokens"));
+        assert!(!permits(&all, &Method::POST, "/api/tos/accept"));
+        assert!(!permits(&all, &Method::GET, "/api/admin/audit"));
+        assert!(!permits(&all, &Method::GET, "/api/webhooks"));
+        assert!(!permits(&all, &Method::POST, "/api/webhooks"));
+        assert!(!permits(&all, &Method::PUT, "/api/admin/users/eve/ban"));
+    }
+}
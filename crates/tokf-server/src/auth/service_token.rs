@@ -1,13 +1,16 @@
 use axum::extract::FromRequestParts;
 use axum::http::request::Parts;
 
+use super::scope::Scope;
 use super::token::hash_token;
 use crate::error::AppError;
 
 /// Authenticated service token extracted from `Authorization: Bearer <token>`.
 ///
 /// Unlike [`super::token::AuthUser`], this is not tied to a user account.
-/// It validates against the `service_tokens` table for CI automation.
+/// It validates against the `service_tokens` table for CI automation, and
+/// also accepts an unexpired personal access token with the `stdlib` scope
+/// whose owner is a `stdlib_publisher` and not banned.
 #[derive(Debug, Clone)]
 pub struct ServiceAuth;
 
@@ -37,7 +40,7 @@ impl FromRequestParts<crate::state::AppState> for ServiceAuth {
                 .map_err(|e| AppError::Internal(e.to_string()))?;
 
         if !exists {
-            return Err(AppError::Unauthorized);
+            return stdlib_token(&state.db, &token_hash).await;
         }
 
         // Fire-and-forget: update last_used_at for auditing
@@ -58,6 +61,32 @@ impl FromRequestParts<crate::state::AppState> for ServiceAuth {
     }
 }
 
+/// Accept a personal access token in place of a service token when it has the
+/// `stdlib` scope and its owner may publish the standard library.
+async fn stdlib_token(db: &sqlx::PgPool, token_hash: &str) -> Result<ServiceAuth, AppError> {
+    let allowed: bool = sqlx::query_scalar(
+        "SELECT EXISTS(
+             SELECT 1 FROM auth_tokens t
+             JOIN users u ON u.id = t.user_id
+             WHERE t.token_hash = $1
+               AND $2 = ANY(t.scopes)
+               AND u.stdlib_publisher
+               AND u.banned_at IS NULL
+               AND (t.expires_at IS NULL OR t.expires_at > NOW()))",
+    )
+    .bind(token_hash)
+    .bind(Scope::Stdlib.as_str())
+    .fetch_one(db)
+    .await
+    .map_err(|e| AppError::Internal(e.to_string()))?;
+
+    if allowed {
+        Ok(ServiceAuth)
+    } else {
+        Err(AppError::Unauthorized)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -2,6 +2,8 @@ use axum::{extract::FromRequestParts, http::request::Parts};
 use sha2::{Digest, Sha256};
 use sqlx::PgPool;
 
+use super::admin::Role;
+use super::scope;
 use crate::error::AppError;
 
 /// Generates a cryptographically random bearer token (64 hex chars = 32 bytes).
@@ -24,6 +26,7 @@ pub fn hash_token(token: &str) -> String {
 pub struct AuthUser {
     pub user_id: i64,
     pub username: String,
+    pub role: Role,
 }
 
 impl FromRequestParts<crate::state::AppState> for AuthUser {
@@ -44,13 +47,42 @@ impl FromRequestParts<crate::state::AppState> for AuthUser {
             .ok_or(AppError::Unauthorized)?;
         let token_hash = hash_token(token);
 
-        lookup_user_by_token_hash(&state.db, &token_hash).await
+        let (user, scopes) = lookup_user_by_token_hash(&state.db, &token_hash).await?;
+        if let Some(scopes) = scopes
+            && !scope::permits(&scopes, &parts.method, parts.uri.path())
+        {
+            return Err(AppError::Forbidden(format!(
+                "this personal access token cannot {} {}",
+                parts.method,
+                parts.uri.path()
+            )));
+        }
+        Ok(user)
     }
 }
 
-async fn lookup_user_by_token_hash(db: &PgPool, token_hash: &str) -> Result<AuthUser, AppError> {
-    let row = sqlx::query_as::<_, (i64, String, Option<chrono::DateTime<chrono::Utc>>)>(
-        "SELECT u.id, u.username, t.expires_at
+#[derive(sqlx::FromRow)]
+struct TokenRow {
+    user_id: i64,
+    username: String,
+    role: String,
+    expires_at: Option<chrono::DateTime<chrono::Utc>>,
+    scopes: Option<Vec<String>>,
+    banned: bool,
+    ban_reason: Option<String>,
+}
+
+/// The token's user, plus its scopes when it is a personal access token.
+///
+/// Tokens of banned users are refused with `403 Forbidden`, so the ban is
+/// explained rather than looking like an expired login.
+async fn lookup_user_by_token_hash(
+    db: &PgPool,
+    token_hash: &str,
+) -> Result<(AuthUser, Option<Vec<String>>), AppError> {
+    let row = sqlx::query_as::<_, TokenRow>(
+        "SELECT u.id AS user_id, u.username, u.role, t.expires_at, t.scopes,
+                u.banned_at IS NOT NULL AS banned, u.ban_reason
          FROM auth_tokens t
          JOIN users u ON u.id = t.user_id
          WHERE t.token_hash = $1",
@@ -61,12 +93,26 @@ async fn lookup_user_by_token_hash(db: &PgPool, token_hash: &str) -> Result<Auth
     .map_err(|e| AppError::Internal(e.to_string()))?
     .ok_or(AppError::Unauthorized)?;
 
-    let (user_id, username, expires_at) = row;
+    let TokenRow {
+        user_id,
+        username,
+        role,
+        expires_at,
+        scopes,
+        banned,
+        ban_reason,
+    } = row;
 
     if let Some(exp) = expires_at.filter(|&exp| exp < chrono::Utc::now()) {
         tracing::debug!(expires_at = %exp, "token expired");
         return Err(AppError::Unauthorized);
     }
+    if banned {
+        return Err(AppError::Forbidden(ban_reason.map_or_else(
+            || "this account has been suspended".to_string(),
+            |reason| format!("this account has been suspended: {reason}"),
+        )));
+    }
 
     // Fire-and-forget: update last_used_at
     let db = db.clone();
@@ -82,7 +128,12 @@ async fn lookup_user_by_token_hash(db: &PgPool, token_hash: &str) -> Result<Auth
         }
     });
 
-    Ok(AuthUser { user_id, username })
+    let user = AuthUser {
+        user_id,
+        username,
+        role: Role::parse(&role),
+    };
+    Ok((user, scopes))
 }
 
 #[cfg(test)]
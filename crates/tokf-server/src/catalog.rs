@@ -171,7 +171,8 @@ pub async fn build_catalog_index(pool: &PgPool) -> Result<CatalogIndex, crate::e
 ///
 /// # Errors
 ///
-/// Returns `NotFound` if the filter doesn't exist, or an internal error on DB failure.
+/// Returns `NotFound` if the filter doesn't exist, is private or has been
+/// taken down by an admin, or an internal error on DB failure.
 pub async fn build_filter_metadata(
     pool: &PgPool,
     hash: &str,
@@ -193,7 +194,8 @@ pub async fn build_filter_metadata(
          FROM filters f
          JOIN users u ON u.id = f.author_id
          LEFT JOIN filter_stats fs ON fs.filter_hash = f.content_hash
-         WHERE f.content_hash = $1",
+         WHERE f.content_hash = $1 AND f.visibility = 'public'
+           AND f.delisted_at IS NULL AND f.review_status IS DISTINCT FROM 'rejected'",
     )
     .bind(hash)
     .fetch_optional(pool)
@@ -316,6 +318,8 @@ async fn fetch_all_versioned_entries(
          FROM filters f
          JOIN users u ON u.id = f.author_id
          LEFT JOIN filter_stats fs ON fs.filter_hash = f.content_hash
+         WHERE f.yanked_at IS NULL AND f.visibility = 'public'
+           AND f.delisted_at IS NULL AND f.review_status IS DISTINCT FROM 'rejected'
          ORDER BY COALESCE(fs.savings_pct, 0.0)
                   * (1.0 + LN(CAST(COALESCE(fs.total_commands, 0) + 1 AS FLOAT8))) DESC,
                   f.created_at DESC",
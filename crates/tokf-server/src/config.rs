@@ -112,6 +112,11 @@ pub struct Config {
     /// The URL where users can read the Terms of Service.
     /// Defaults to `{public_url}/terms` when `TERMS_URL` is not set.
     pub terms_url: String,
+    /// When `true`, webhooks may target loopback, private and link-local
+    /// addresses, and plain `http` to `localhost`. Only for local
+    /// development: on a shared registry it lets users probe the internal
+    /// network. Set via `WEBHOOKS_ALLOW_PRIVATE`; defaults to `false`.
+    pub webhooks_allow_private: bool,
     /// Rate-limit configuration for all endpoints.
     /// Override via the `RATE_LIMITS` environment variable (JSON).
     pub rate_limits: RateLimitConfig,
@@ -150,6 +155,7 @@ impl std::fmt::Debug for Config {
             .field("trust_proxy", &self.trust_proxy)
             .field("public_url", &self.public_url)
             .field("terms_url", &self.terms_url)
+            .field("webhooks_allow_private", &self.webhooks_allow_private)
             .field("rate_limits", &self.rate_limits)
             .finish()
     }
@@ -225,6 +231,8 @@ impl Config {
             public_url: public_url.clone(),
             terms_url: Self::env_non_empty("TERMS_URL")
                 .unwrap_or_else(|| format!("{public_url}/terms")),
+            webhooks_allow_private: std::env::var("WEBHOOKS_ALLOW_PRIVATE")
+                .is_ok_and(|v| matches!(v.to_lowercase().as_str(), "true" | "1" | "yes")),
             rate_limits: Self::env_non_empty("RATE_LIMITS")
                 .map(|s| {
                     serde_json::from_str(&s).unwrap_or_else(|e| {
@@ -371,6 +379,7 @@ mod tests {
             github_client_secret: Some("gh-secret-value".to_string()),
             public_url: "http://localhost:8080".to_string(),
             terms_url: "http://localhost:8080/terms".to_string(),
+            webhooks_allow_private: false,
             rate_limits: RateLimitConfig::default(),
         };
         let debug_str = format!("{cfg:?}");
@@ -594,6 +603,7 @@ mod tests {
             github_client_secret: None,
             public_url: "http://localhost:8080".to_string(),
             terms_url: "http://localhost:8080/terms".to_string(),
+            webhooks_allow_private: false,
             rate_limits: RateLimitConfig::default(),
         }
     }
@@ -3,9 +3,11 @@ pub mod catalog;
 pub mod config;
 pub mod db;
 pub mod error;
+pub mod openapi;
 pub mod rate_limit;
 pub mod routes;
 pub mod state;
 pub mod storage;
 pub mod tos;
 pub mod verify;
+pub mod webhooks;
@@ -94,6 +94,7 @@ async fn cmd_serve() -> Result<()> {
         trust_proxy: cfg.trust_proxy,
         public_url: cfg.public_url.clone(),
         terms_url: cfg.terms_url.clone(),
+        webhooks_allow_private: cfg.webhooks_allow_private,
         publish_rate_limiter: Arc::new(rate_limit::PublishRateLimiter::new(
             cfg.rate_limits.publish.max,
             cfg.rate_limits.publish.safe_window_secs(),
@@ -258,6 +259,7 @@ mod tests {
             github_client_secret: Some("gh-secret".to_string()),
             public_url: "http://localhost:8080".to_string(),
             terms_url: "http://localhost:8080/terms".to_string(),
+            webhooks_allow_private: false,
             rate_limits: config::RateLimitConfig::default(),
         }
     }
@@ -278,6 +280,7 @@ mod tests {
             github_client_secret: Some("gh-secret".to_string()),
             public_url: "http://localhost:8080".to_string(),
             terms_url: "http://localhost:8080/terms".to_string(),
+            webhooks_allow_private: false,
             rate_limits: config::RateLimitConfig::default(),
         }
     }
@@ -0,0 +1,128 @@
+//! `OpenAPI` description of the HTTP API.
+//!
+//! `openapi/openapi.json` is maintained by hand next to the route table. The
+//! tests below compare the two, so adding, removing or re-methoding a route
+//! without documenting it fails CI; the e2e contract tests check that the
+//! CLI's client types accept the documented response examples.
+
+use std::sync::OnceLock;
+
+use serde_json::Value;
+
+/// The `OpenAPI` 3.1 document, embedded at compile time.
+pub const OPENAPI_JSON: &str = include_str!("openapi/openapi.json");
+
+fn spec() -> &'static Value {
+    static SPEC: OnceLock<Value> = OnceLock::new();
+    SPEC.get_or_init(|| serde_json::from_str(OPENAPI_JSON).unwrap_or_default())
+}
+
+/// The document as served: the server's version, and `public_url` as the
+/// only server so "try it out" calls go to this deployment.
+pub fn document(public_url: &str) -> Value {
+    let mut doc = spec().clone();
+    doc["info"]["version"] = Value::from(env!("CARGO_PKG_VERSION"));
+    doc["servers"] = serde_json::json!([{ "url": public_url }]);
+    doc
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use std::collections::{BTreeMap, BTreeSet};
+
+    use super::*;
+
+    const METHODS: [&str; 4] = ["get", "post", "put", "delete"];
+
+    /// `(path, methods)` for every `.route(...)` in the router source.
+    fn routed() -> BTreeMap<String, BTreeSet<String>> {
+        let source = include_str!("routes/mod.rs");
+        let mut routes = BTreeMap::new();
+        for chunk in source.split(".route(").skip(1) {
+            let path = chunk.split('"').nth(1).expect("route path literal");
+            let handlers = chunk.split(".layer(axum::middleware").next().unwrap();
+            let methods = METHODS
+                .iter()
+                .filter(|m| {
+                    handlers.match_indices(&format!("{m}(")).any(|(at, _)| {
+                        at == 0 || !handlers.as_bytes()[at - 1].is_ascii_alphanumeric()
+                    })
+                })
+                .map(ToString::to_string)
+                .collect();
+            routes.insert(path.to_string(), methods);
+        }
+        routes
+    }
+
+    fn documented() -> BTreeMap<String, BTreeSet<String>> {
+        spec()["paths"]
+            .as_object()
+            .unwrap()
+            .iter()
+            .map(|(path, item)| {
+                let methods = item.as_object().unwrap().keys().cloned().collect();
+                (path.clone(), methods)
+            })
+            .collect()
+    }
+
+    fn refs<'a>(value: &'a Value, out: &mut Vec<&'a str>) {
+        match value {
+            Value::Object(map) => {
+                if let Some(Value::String(target)) = map.get("$ref") {
+                    out.push(target);
+                }
+                map.values().for_each(|v| refs(v, out));
+            }
+            Value::Array(items) => items.iter().for_each(|v| refs(v, out)),
+            _ => {}
+        }
+    }
+
+    #[test]
+    fn spec_is_valid_json() {
+        assert!(serde_json::from_str::<Value>(OPENAPI_JSON).is_ok());
+        assert_eq!(spec()["openapi"], "3.1.0");
+    }
+
+    #[test]
+    fn every_route_is_documented_with_its_methods() {
+        let routed = routed();
+        assert!(
+            routed.contains_key("/api/filters"),
+            "router not parsed: {routed:?}"
+        );
+        assert_eq!(routed, documented());
+    }
+
+    #[test]
+    fn operation_ids_are_unique() {
+        let mut seen = BTreeSet::new();
+        for item in spec()["paths"].as_object().unwrap().values() {
+            for op in item.as_object().unwrap().values() {
+                let id = op["operationId"].as_str().expect("operationId");
+                assert!(seen.insert(id), "duplicate operationId {id}");
+            }
+        }
+    }
+
+    #[test]
+    fn every_ref_resolves() {
+        let mut found = Vec::new();
+        refs(spec(), &mut found);
+        assert!(!found.is_empty());
+        for target in found {
+            let pointer = target.strip_prefix('#').expect("local $ref");
+            assert!(spec().pointer(pointer).is_some(), "dangling $ref {target}");
+        }
+    }
+
+    #[test]
+    fn served_document_names_this_server() {
+        let doc = document("https://registry.example.com");
+        assert_eq!(doc["servers"][0]["url"], "https://registry.example.com");
+        assert_eq!(doc["info"]["version"], env!("CARGO_PKG_VERSION"));
+    }
+}
@@ -9,8 +9,9 @@ use crate::state::AppState;
 ///
 /// Anonymizes the user row (clears personal data, sets `visible = false`,
 /// marks `deleted_at`) so filter `author_id` references remain valid.
-/// Cascades deletion of auth tokens, machines (and their usage events /
-/// sync cursors), device flows, and `ToS` acceptance records.
+/// Cascades deletion of auth tokens, signing keys, webhooks, machines (and
+/// their usage events / sync cursors), device flows, and `ToS` acceptance
+/// records.
 ///
 /// Returns `204 No Content` on success.
 pub async fn delete_account(
@@ -46,6 +47,16 @@ pub async fn delete_account(
         .execute(&state.db)
         .await?;
 
+    sqlx::query("DELETE FROM signing_keys WHERE user_id = $1")
+        .bind(user.user_id)
+        .execute(&state.db)
+        .await?;
+
+    sqlx::query("DELETE FROM webhooks WHERE user_id = $1")
+        .bind(user.user_id)
+        .execute(&state.db)
+        .await?;
+
     sqlx::query("DELETE FROM auth_tokens WHERE user_id = $1")
         .bind(user.user_id)
         .execute(&state.db)
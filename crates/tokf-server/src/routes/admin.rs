@@ -0,0 +1,548 @@
+use axum::{
+    Json,
+    extract::{Path, Query, State},
+};
+use serde::{Deserialize, Serialize};
+
+use crate::auth::admin::{AdminUser, Role};
+use crate::error::AppError;
+use crate::state::AppState;
+use crate::storage;
+
+/// Longest accepted moderation reason or review note, in bytes.
+const MAX_REASON_LEN: usize = 500;
+const DEFAULT_REVIEW_LIMIT: i64 = 20;
+const MAX_REVIEW_LIMIT: i64 = 50;
+const DEFAULT_AUDIT_LIMIT: i64 = 50;
+const MAX_AUDIT_LIMIT: i64 = 200;
+
+// ── Request / Response types ─────────────────────────────────────────────────
+
+#[derive(Debug, Default, Deserialize)]
+pub struct DelistRequest {
+    /// Recorded in the audit log; never shown to users.
+    #[serde(default)]
+    pub reason: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct BanRequest {
+    /// Shown to the banned user whenever their token is refused.
+    #[serde(default)]
+    pub reason: Option<String>,
+    /// Also delist every filter the user has published.
+    #[serde(default)]
+    pub delist_filters: bool,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Decision {
+    Approve,
+    Reject,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ReviewRequest {
+    pub decision: Decision,
+    #[serde(default)]
+    pub note: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ReviewQuery {
+    /// `pending` (default), `approved` or `rejected`.
+    #[serde(default)]
+    pub status: Option<String>,
+    #[serde(default)]
+    pub limit: Option<i64>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AuditQuery {
+    #[serde(default)]
+    pub limit: Option<i64>,
+    /// Only entries older than this id, for paging backwards.
+    #[serde(default)]
+    pub before: Option<i64>,
+}
+
+/// A filter's moderation state after a change.
+#[derive(Debug, Serialize, sqlx::FromRow)]
+pub struct FilterModeration {
+    pub content_hash: String,
+    pub author: String,
+    pub delisted_at: Option<String>,
+    pub delist_reason: Option<String>,
+    pub review_status: Option<String>,
+}
+
+/// A user's moderation state after a change.
+#[derive(Debug, Serialize, sqlx::FromRow)]
+pub struct UserModeration {
+    pub username: String,
+    pub role: String,
+    pub banned_at: Option<String>,
+    pub ban_reason: Option<String>,
+    /// How many of the user's filters are currently delisted.
+    pub delisted_filters: i64,
+}
+
+/// A Luau filter in the review queue, with its source for the reviewer.
+#[derive(Debug, Serialize)]
+pub struct ReviewEntry {
+    pub content_hash: String,
+    pub command_pattern: String,
+    pub author: String,
+    pub created_at: String,
+    pub review_status: String,
+    pub safety_passed: bool,
+    pub filter_toml: String,
+}
+
+#[derive(Debug, Serialize, sqlx::FromRow)]
+pub struct AuditEntry {
+    pub id: i64,
+    /// `None` once the acting admin's account has been deleted.
+    pub admin: Option<String>,
+    pub action: String,
+    pub target: String,
+    pub reason: Option<String>,
+    pub created_at: String,
+}
+
+// ── Internal helpers ──────────────────────────────────────────────────────────
+
+/// What an audit log entry records.
+#[derive(Debug, Clone, Copy)]
+enum AuditAction {
+    DelistFilter,
+    RelistFilter,
+    ApproveFilter,
+    RejectFilter,
+    BanUser,
+    UnbanUser,
+    DelistUserFilters,
+}
+
+impl AuditAction {
+    const fn as_str(self) -> &'static str {
+        match self {
+            Self::DelistFilter => "delist_filter",
+            Self::RelistFilter => "relist_filter",
+            Self::ApproveFilter => "approve_filter",
+            Self::RejectFilter => "reject_filter",
+            Self::BanUser => "ban_user",
+            Self::UnbanUser => "unban_user",
+            Self::DelistUserFilters => "delist_user_filters",
+        }
+    }
+}
+
+/// Append an entry to the audit log, in the transaction making the change.
+async fn record(
+    tx: &mut sqlx::PgConnection,
+    admin_id: i64,
+    action: AuditAction,
+    target: &str,
+    reason: Option<&str>,
+) -> Result<(), AppError> {
+    sqlx::query(
+        "INSERT INTO admin_audit_log (admin_id, action, target, reason) VALUES ($1, $2, $3, $4)",
+    )
+    .bind(admin_id)
+    .bind(action.as_str())
+    .bind(target)
+    .bind(reason)
+    .execute(tx)
+    .await?;
+    Ok(())
+}
+
+/// Trim a free-text reason, treating a blank one as absent.
+fn clean_reason(reason: Option<&str>) -> Result<Option<String>, AppError> {
+    let reason = reason.map(str::trim).filter(|r| !r.is_empty());
+    if reason.is_some_and(|r| r.len() > MAX_REASON_LEN) {
+        return Err(AppError::BadRequest(format!(
+            "reason must not exceed {MAX_REASON_LEN} bytes"
+        )));
+    }
+    Ok(reason.map(str::to_string))
+}
+
+/// The id and role of the live account named `username`, compared
+/// case-insensitively as GitHub does.
+async fn find_user(db: &sqlx::PgPool, username: &str) -> Result<(i64, Role), AppError> {
+    let rows: Vec<(i64, String)> = sqlx::query_as(
+        "SELECT id, role FROM users
+         WHERE LOWER(username) = LOWER($1) AND deleted_at IS NULL",
+    )
+    .bind(username)
+    .fetch_all(db)
+    .await?;
+    match rows.as_slice() {
+        [] => Err(AppError::NotFound(format!("user not found: {username}"))),
+        [(id, role)] => Ok((*id, Role::parse(role))),
+        _ => Err(AppError::Conflict(format!(
+            "several accounts are named '{username}'"
+        ))),
+    }
+}
+
+async fn filter_moderation(db: &sqlx::PgPool, hash: &str) -> Result<FilterModeration, AppError> {
+    Ok(sqlx::query_as::<_, FilterModeration>(
+        "SELECT f.content_hash, u.username AS author, f.delisted_at::TEXT AS delisted_at,
+                f.delist_reason, f.review_status
+         FROM filters f JOIN users u ON u.id = f.author_id
+         WHERE f.content_hash = $1",
+    )
+    .bind(hash)
+    .fetch_one(db)
+    .await?)
+}
+
+async fn user_moderation(db: &sqlx::PgPool, user_id: i64) -> Result<UserModeration, AppError> {
+    Ok(sqlx::query_as::<_, UserModeration>(
+        "SELECT u.username, u.role, u.banned_at::TEXT AS banned_at, u.ban_reason,
+                (SELECT COUNT(*)::BIGINT FROM filters f
+                 WHERE f.author_id = u.id AND f.delisted_at IS NOT NULL) AS delisted_filters
+         FROM users u WHERE u.id = $1",
+    )
+    .bind(user_id)
+    .fetch_one(db)
+    .await?)
+}
+
+/// Rebuild the catalog in the background after filters were hidden or
+/// restored.
+fn refresh_catalog(state: &AppState, hashes: Vec<String>) {
+    crate::catalog::spawn_batch_catalog_update(
+        state.db.clone(),
+        state.storage.clone(),
+        hashes,
+        false,
+    );
+}
+
+/// Delist a filter (with `reason`) or relist it, and record the action.
+async fn set_delisted(
+    state: &AppState,
+    admin_id: i64,
+    hash: &str,
+    action: AuditAction,
+    reason: Option<&str>,
+) -> Result<Json<FilterModeration>, AppError> {
+    let mut tx = state.db.begin().await?;
+    let updated = sqlx::query(
+        "UPDATE filters SET
+             delisted_at = CASE WHEN $2::BOOL THEN COALESCE(delisted_at, NOW()) END,
+             delist_reason = $3
+         WHERE content_hash = $1",
+    )
+    .bind(hash)
+    .bind(matches!(action, AuditAction::DelistFilter))
+    .bind(reason)
+    .execute(&mut *tx)
+    .await?;
+    if updated.rows_affected() == 0 {
+        return Err(AppError::NotFound(format!("filter not found: {hash}")));
+    }
+    record(&mut tx, admin_id, action, hash, reason).await?;
+    tx.commit().await?;
+    refresh_catalog(state, vec![hash.to_string()]);
+    Ok(Json(filter_moderation(&state.db, hash).await?))
+}
+
+// ── PUT /api/admin/filters/:hash/delist ──────────────────────────────────────
+
+/// Delist a filter: hide it from everyone, its author included, as if it had
+/// never been published. Delisting again only replaces the reason.
+///
+/// # Errors
+///
+/// - `400 Bad Request` if the reason exceeds 500 bytes.
+/// - `401 Unauthorized` if the bearer token is missing or invalid.
+/// - `403 Forbidden` if the caller is not an admin.
+/// - `404 Not Found` if no filter with the given hash exists.
+/// - `500 Internal Server Error` on database failures.
+pub async fn delist_filter(
+    AdminUser(admin): AdminUser,
+    State(state): State<AppState>,
+    Path(hash): Path<String>,
+    Json(req): Json<DelistRequest>,
+) -> Result<Json<FilterModeration>, AppError> {
+    let reason = clean_reason(req.reason.as_deref())?;
+    tracing::info!(hash = %hash, admin = %admin.username, "filter delisted");
+    let action = AuditAction::DelistFilter;
+    set_delisted(&state, admin.user_id, &hash, action, reason.as_deref()).await
+}
+
+// ── DELETE /api/admin/filters/:hash/delist ───────────────────────────────────
+
+/// Undo a delisting.
+///
+/// # Errors
+///
+/// - `401 Unauthorized` if the bearer token is missing or invalid.
+/// - `403 Forbidden` if the caller is not an admin.
+/// - `404 Not Found` if no filter with the given hash exists.
+/// - `500 Internal Server Error` on database failures.
+pub async fn relist_filter(
+    AdminUser(admin): AdminUser,
+    State(state): State<AppState>,
+    Path(hash): Path<String>,
+) -> Result<Json<FilterModeration>, AppError> {
+    tracing::info!(hash = %hash, admin = %admin.username, "filter relisted");
+    let action = AuditAction::RelistFilter;
+    set_delisted(&state, admin.user_id, &hash, action, None).await
+}
+
+// ── PUT /api/admin/users/:username/ban ───────────────────────────────────────
+
+/// Ban a user: every request they make with any of their tokens is refused
+/// with `403 Forbidden` and the reason. With `delist_filters`, their
+/// published filters are delisted too; unbanning does not restore those.
+///
+/// # Errors
+///
+/// - `400 Bad Request` if the reason exceeds 500 bytes or the user is an admin.
+/// - `401 Unauthorized` if the bearer token is missing or invalid.
+/// - `403 Forbidden` if the caller is not an admin.
+/// - `404 Not Found` if no such user exists.
+/// - `409 Conflict` if several accounts share the name.
+/// - `500 Internal Server Error` on database failures.
+pub async fn ban_user(
+    AdminUser(admin): AdminUser,
+    State(state): State<AppState>,
+    Path(username): Path<String>,
+    Json(req): Json<BanRequest>,
+) -> Result<Json<UserModeration>, AppError> {
+    let reason = clean_reason(req.reason.as_deref())?;
+    let (user_id, role) = find_user(&state.db, &username).await?;
+    if role == Role::Admin {
+        return Err(AppError::BadRequest(
+            "admins cannot be banned; revoke the role first".to_string(),
+        ));
+    }
+    tracing::info!(user = %username, admin = %admin.username, "user banned");
+    let mut tx = state.db.begin().await?;
+    sqlx::query(
+        "UPDATE users SET banned_at = COALESCE(banned_at, NOW()), ban_reason = $2 WHERE id = $1",
+    )
+    .bind(user_id)
+    .bind(&reason)
+    .execute(&mut *tx)
+    .await?;
+    record(
+        &mut tx,
+        admin.user_id,
+        AuditAction::BanUser,
+        &username,
+        reason.as_deref(),
+    )
+    .await?;
+    let mut delisted = Vec::new();
+    if req.delist_filters {
+        delisted = sqlx::query_scalar(
+            "UPDATE filters SET delisted_at = NOW(), delist_reason = $2
+             WHERE author_id = $1 AND delisted_at IS NULL
+             RETURNING content_hash",
+        )
+        .bind(user_id)
+        .bind(&reason)
+        .fetch_all(&mut *tx)
+        .await?;
+        let action = AuditAction::DelistUserFilters;
+        record(&mut tx, admin.user_id, action, &username, reason.as_deref()).await?;
+    }
+    tx.commit().await?;
+    if !delisted.is_empty() {
+        refresh_catalog(&state, delisted);
+    }
+    Ok(Json(user_moderation(&state.db, user_id).await?))
+}
+
+// ── DELETE /api/admin/users/:username/ban ────────────────────────────────────
+
+/// Lift a ban. Filters delisted along with it stay delisted.
+///
+/// # Errors
+///
+/// - `401 Unauthorized` if the bearer token is missing or invalid.
+/// - `403 Forbidden` if the caller is not an admin.
+/// - `404 Not Found` if no such user exists.
+/// - `409 Conflict` if several accounts share the name.
+/// - `500 Internal Server Error` on database failures.
+pub async fn unban_user(
+    AdminUser(admin): AdminUser,
+    State(state): State<AppState>,
+    Path(username): Path<String>,
+) -> Result<Json<UserModeration>, AppError> {
+    let (user_id, _) = find_user(&state.db, &username).await?;
+    tracing::info!(user = %username, admin = %admin.username, "user unbanned");
+    let mut tx = state.db.begin().await?;
+    sqlx::query("UPDATE users SET banned_at = NULL, ban_reason = NULL WHERE id = $1")
+        .bind(user_id)
+        .execute(&mut *tx)
+        .await?;
+    record(
+        &mut tx,
+        admin.user_id,
+        AuditAction::UnbanUser,
+        &username,
+        None,
+    )
+    .await?;
+    tx.commit().await?;
+    Ok(Json(user_moderation(&state.db, user_id).await?))
+}
+
+// ── GET /api/admin/reviews ───────────────────────────────────────────────────
+
+type ReviewRow = (String, String, String, String, String, bool, String);
+
+/// List published Luau filters by review status, oldest first, with each
+/// filter's TOML so the script can be read in place.
+///
+/// # Errors
+///
+/// - `400 Bad Request` for an unknown `status`.
+/// - `401 Unauthorized` if the bearer token is missing or invalid.
+/// - `403 Forbidden` if the caller is not an admin.
+/// - `500 Internal Server Error` on storage or database failures.
+pub async fn list_reviews(
+    AdminUser(_): AdminUser,
+    State(state): State<AppState>,
+    Query(query): Query<ReviewQuery>,
+) -> Result<Json<Vec<ReviewEntry>>, AppError> {
+    let status = query.status.as_deref().unwrap_or("pending");
+    if !matches!(status, "pending" | "approved" | "rejected") {
+        return Err(AppError::BadRequest(format!(
+            "status must be 'pending', 'approved' or 'rejected', got '{status}'"
+        )));
+    }
+    let limit = query
+        .limit
+        .unwrap_or(DEFAULT_REVIEW_LIMIT)
+        .clamp(1, MAX_REVIEW_LIMIT);
+    let rows: Vec<ReviewRow> = sqlx::query_as(
+        "SELECT f.content_hash, f.command_pattern, u.username, f.created_at::TEXT,
+                f.review_status, f.safety_passed, f.r2_key
+         FROM filters f JOIN users u ON u.id = f.author_id
+         WHERE f.review_status = $1
+         ORDER BY f.created_at, f.content_hash
+         LIMIT $2",
+    )
+    .bind(status)
+    .bind(limit)
+    .fetch_all(&state.db)
+    .await?;
+
+    let mut entries = Vec::with_capacity(rows.len());
+    for (content_hash, command_pattern, author, created_at, review_status, safety, r2_key) in rows {
+        let filter_toml = storage::get_utf8(&*state.storage, &r2_key)
+            .await
+            .map_err(|e| AppError::Internal(format!("filter {content_hash}: {e}")))?;
+        entries.push(ReviewEntry {
+            content_hash,
+            command_pattern,
+            author,
+            created_at,
+            review_status,
+            safety_passed: safety,
+            filter_toml,
+        });
+    }
+    Ok(Json(entries))
+}
+
+// ── PUT /api/admin/reviews/:hash ─────────────────────────────────────────────
+
+/// Approve or reject a Luau filter. A rejected filter is hidden like a
+/// delisted one; approving it later restores it.
+///
+/// # Errors
+///
+/// - `400 Bad Request` if the filter has no Luau script or the note exceeds
+///   500 bytes.
+/// - `401 Unauthorized` if the bearer token is missing or invalid.
+/// - `403 Forbidden` if the caller is not an admin.
+/// - `404 Not Found` if no filter with the given hash exists.
+/// - `500 Internal Server Error` on database failures.
+pub async fn review_filter(
+    AdminUser(admin): AdminUser,
+    State(state): State<AppState>,
+    Path(hash): Path<String>,
+    Json(req): Json<ReviewRequest>,
+) -> Result<Json<FilterModeration>, AppError> {
+    let note = clean_reason(req.note.as_deref())?;
+    let current: Option<Option<String>> =
+        sqlx::query_scalar("SELECT review_status FROM filters WHERE content_hash = $1")
+            .bind(&hash)
+            .fetch_optional(&state.db)
+            .await?;
+    match current {
+        None => return Err(AppError::NotFound(format!("filter not found: {hash}"))),
+        Some(None) => {
+            return Err(AppError::BadRequest(
+                "only filters with a Luau script are reviewed".to_string(),
+            ));
+        }
+        Some(Some(_)) => {}
+    }
+    let (status, action) = match req.decision {
+        Decision::Approve => ("approved", AuditAction::ApproveFilter),
+        Decision::Reject => ("rejected", AuditAction::RejectFilter),
+    };
+    tracing::info!(hash = %hash, admin = %admin.username, status, "filter reviewed");
+    let mut tx = state.db.begin().await?;
+    sqlx::query("UPDATE filters SET review_status = $2 WHERE content_hash = $1")
+        .bind(&hash)
+        .bind(status)
+        .execute(&mut *tx)
+        .await?;
+    record(&mut tx, admin.user_id, action, &hash, note.as_deref()).await?;
+    tx.commit().await?;
+    refresh_catalog(&state, vec![hash.clone()]);
+    Ok(Json(filter_moderation(&state.db, &hash).await?))
+}
+
+// ── GET /api/admin/audit ─────────────────────────────────────────────────────
+
+/// The admin audit trail, newest first. Page backwards by passing the
+/// smallest `id` seen as `before`.
+///
+/// # Errors
+///
+/// - `401 Unauthorized` if the bearer token is missing or invalid.
+/// - `403 Forbidden` if the caller is not an admin.
+/// - `500 Internal Server Error` on database failures.
+pub async fn get_audit_log(
+    AdminUser(_): AdminUser,
+    State(state): State<AppState>,
+    Query(query): Query<AuditQuery>,
+) -> Result<Json<Vec<AuditEntry>>, AppError> {
+    let limit = query
+        .limit
+        .unwrap_or(DEFAULT_AUDIT_LIMIT)
+        .clamp(1, MAX_AUDIT_LIMIT);
+    let entries = sqlx::query_as::<_, AuditEntry>(
+        "SELECT a.id, u.username AS admin, a.action, a.target, a.reason,
+                a.created_at::TEXT AS created_at
+         FROM admin_audit_log a
+         LEFT JOIN users u ON u.id = a.admin_id
+         WHERE $1::INT8 IS NULL OR a.id < $1
+         ORDER BY a.id DESC
+         LIMIT $2",
+    )
+    .bind(query.before)
+    .bind(limit)
+    .fetch_all(&state.db)
+    .await?;
+    Ok(Json(entries))
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+#[path = "admin_tests.rs"]
+mod tests;
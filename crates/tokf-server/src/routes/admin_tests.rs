@@ -0,0 +1,212 @@
+use std::sync::Arc;
+
+use axum::http::StatusCode;
+use sqlx::PgPool;
+
+use crate::storage::mock::InMemoryStorageClient;
+
+use super::super::filters::test_helpers::{
+    get_request, insert_test_user, make_state_with_storage, publish_filter_helper, send_json,
+};
+use super::super::test_helpers::assert_status;
+
+const LUAU_FILTER: &[u8] =
+    b"command = \"luau-tool\"\n\n[lua_script]\nlang = \"luau\"\nsource = 'return output'\n";
+
+struct Registry {
+    pool: PgPool,
+    storage: Arc<InMemoryStorageClient>,
+}
+
+impl Registry {
+    fn new(pool: PgPool) -> Self {
+        Self {
+            pool,
+            storage: Arc::new(InMemoryStorageClient::new()),
+        }
+    }
+
+    fn app(&self) -> axum::Router {
+        crate::routes::create_router(make_state_with_storage(
+            self.pool.clone(),
+            Arc::clone(&self.storage),
+        ))
+    }
+
+    async fn admin(&self, username: &str) -> String {
+        let (id, token) = insert_test_user(&self.pool, username).await;
+        sqlx::query("UPDATE users SET role = 'admin' WHERE id = $1")
+            .bind(id)
+            .execute(&self.pool)
+            .await
+            .unwrap();
+        token
+    }
+
+    async fn json(&self, token: &str, uri: &str) -> serde_json::Value {
+        let resp = get_request(self.app(), token, uri).await;
+        let body = assert_status(resp, StatusCode::OK).await;
+        serde_json::from_slice(&body).unwrap()
+    }
+
+    async fn send(
+        &self,
+        method: &str,
+        token: &str,
+        uri: &str,
+        body: serde_json::Value,
+    ) -> axum::response::Response {
+        send_json(self.app(), method, token, uri, &body).await
+    }
+}
+
+#[crdb_test_macro::crdb_test(migrations = "./migrations")]
+async fn admin_routes_are_closed_to_other_users(pool: PgPool) {
+    let reg = Registry::new(pool);
+    let (_, user) = insert_test_user(&reg.pool, "admin_not_me").await;
+    let (_, author) = insert_test_user(&reg.pool, "admin_author").await;
+    let hash = publish_filter_helper(reg.app(), &author, b"command = \"pytest\"", &[]).await;
+
+    let resp = get_request(reg.app(), &user, "/api/admin/audit").await;
+    assert_status(resp, StatusCode::FORBIDDEN).await;
+    let uri = format!("/api/admin/filters/{hash}/delist");
+    let resp = reg.send("PUT", &author, &uri, serde_json::json!({})).await;
+    assert_status(resp, StatusCode::FORBIDDEN).await;
+}
+
+#[crdb_test_macro::crdb_test(migrations = "./migrations")]
+async fn delisted_filters_are_hidden_from_everyone(pool: PgPool) {
+    let reg = Registry::new(pool);
+    let admin = reg.admin("delist_admin").await;
+    let (_, author) = insert_test_user(&reg.pool, "delist_author").await;
+    let hash = publish_filter_helper(reg.app(), &author, b"command = \"cargo doc\"", &[]).await;
+    let uri = format!("/api/admin/filters/{hash}/delist");
+
+    let resp = reg
+        .send(
+            "PUT",
+            &admin,
+            &uri,
+            serde_json::json!({"reason": "malware"}),
+        )
+        .await;
+    assert_status(resp, StatusCode::OK).await;
+    for token in [&author, &admin] {
+        let resp = get_request(reg.app(), token, &format!("/api/filters/{hash}")).await;
+        assert_status(resp, StatusCode::NOT_FOUND).await;
+    }
+    let found = reg.json(&author, "/api/filters?q=cargo+doc").await;
+    assert!(found.as_array().unwrap().is_empty(), "{found}");
+
+    let resp = reg
+        .send("DELETE", &admin, &uri, serde_json::json!({}))
+        .await;
+    assert_status(resp, StatusCode::OK).await;
+    let resp = get_request(reg.app(), &author, &format!("/api/filters/{hash}")).await;
+    assert_status(resp, StatusCode::OK).await;
+
+    let audit = reg.json(&admin, "/api/admin/audit").await;
+    let actions: Vec<&str> = audit
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|e| e["action"].as_str().unwrap())
+        .collect();
+    assert_eq!(actions, ["relist_filter", "delist_filter"]);
+    assert_eq!(audit[1]["admin"], "delist_admin");
+    assert_eq!(audit[1]["target"], hash);
+    assert_eq!(audit[1]["reason"], "malware");
+}
+
+#[crdb_test_macro::crdb_test(migrations = "./migrations")]
+async fn banned_users_are_refused_with_the_reason(pool: PgPool) {
+    let reg = Registry::new(pool);
+    let admin = reg.admin("ban_admin").await;
+    let (_, spammer) = insert_test_user(&reg.pool, "Ban_Spammer").await;
+    let hash = publish_filter_helper(reg.app(), &spammer, b"command = \"spam\"", &[]).await;
+
+    let body = serde_json::json!({"reason": "spam", "delist_filters": true});
+    let resp = reg
+        .send("PUT", &admin, "/api/admin/users/ban_spammer/ban", body)
+        .await;
+    let body = assert_status(resp, StatusCode::OK).await;
+    let status: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(status["delisted_filters"], 1);
+
+    let resp = get_request(reg.app(), &spammer, "/api/filters?q=spam").await;
+    assert_status(resp, StatusCode::FORBIDDEN).await;
+    let resp = get_request(reg.app(), &admin, &format!("/api/filters/{hash}")).await;
+    assert_status(resp, StatusCode::NOT_FOUND).await;
+
+    let resp = reg
+        .send(
+            "PUT",
+            &admin,
+            "/api/admin/users/ban_admin/ban",
+            serde_json::json!({}),
+        )
+        .await;
+    assert_status(resp, StatusCode::BAD_REQUEST).await;
+
+    let resp = reg
+        .send(
+            "DELETE",
+            &admin,
+            "/api/admin/users/Ban_Spammer/ban",
+            serde_json::json!({}),
+        )
+        .await;
+    assert_status(resp, StatusCode::OK).await;
+    let resp = get_request(reg.app(), &spammer, "/api/filters?q=spam").await;
+    assert_status(resp, StatusCode::OK).await;
+}
+
+#[crdb_test_macro::crdb_test(migrations = "./migrations")]
+async fn luau_filters_wait_in_the_review_queue(pool: PgPool) {
+    let reg = Registry::new(pool);
+    let admin = reg.admin("review_admin").await;
+    let (_, author) = insert_test_user(&reg.pool, "review_author").await;
+    let luau = publish_filter_helper(reg.app(), &author, LUAU_FILTER, &[]).await;
+    let plain = publish_filter_helper(reg.app(), &author, b"command = \"plain\"", &[]).await;
+
+    let queue = reg.json(&admin, "/api/admin/reviews").await;
+    let queue = queue.as_array().unwrap();
+    assert_eq!(queue.len(), 1, "{queue:?}");
+    assert_eq!(queue[0]["content_hash"], luau);
+    assert!(
+        queue[0]["filter_toml"]
+            .as_str()
+            .unwrap()
+            .contains("lua_script")
+    );
+
+    let review = format!("/api/admin/reviews/{luau}");
+    let reject = serde_json::json!({"decision": "reject", "note": "shells out"});
+    assert_status(
+        reg.send("PUT", &admin, &review, reject).await,
+        StatusCode::OK,
+    )
+    .await;
+    let resp = get_request(reg.app(), &author, &format!("/api/filters/{luau}")).await;
+    assert_status(resp, StatusCode::NOT_FOUND).await;
+    assert!(reg.json(&admin, "/api/admin/reviews").await == serde_json::json!([]));
+
+    let approve = serde_json::json!({"decision": "approve"});
+    assert_status(
+        reg.send("PUT", &admin, &review, approve.clone()).await,
+        StatusCode::OK,
+    )
+    .await;
+    let resp = get_request(reg.app(), &author, &format!("/api/filters/{luau}")).await;
+    assert_status(resp, StatusCode::OK).await;
+
+    let resp = reg
+        .send(
+            "PUT",
+            &admin,
+            &format!("/api/admin/reviews/{plain}"),
+            approve,
+        )
+        .await;
+    assert_status(resp, StatusCode::BAD_REQUEST).await;
+}
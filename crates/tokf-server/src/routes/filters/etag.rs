@@ -0,0 +1,83 @@
+use axum::http::{HeaderMap, header::IF_NONE_MATCH};
+use sha2::{Digest, Sha256};
+
+/// Strong `ETag` for a filter download.
+///
+/// Computed from the database alone so a revalidation never touches storage.
+/// The filter TOML is immutable under its hash, so what can change is the
+/// test suite — each `PUT /api/filters/{hash}/tests` inserts fresh
+/// `filter_tests` rows with a new `updated_at` — and the server-recomputed
+/// hashes in the payload, which the server version stands in for.
+pub fn download_etag(
+    hash: &str,
+    signature: Option<&str>,
+    tests: &[(String, chrono::DateTime<chrono::Utc>)],
+) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(env!("CARGO_PKG_VERSION").as_bytes());
+    hasher.update(b"\0");
+    hasher.update(hash.as_bytes());
+    hasher.update(b"\0");
+    hasher.update(signature.unwrap_or_default().as_bytes());
+    for (key, updated_at) in tests {
+        hasher.update(b"\0");
+        hasher.update(key.as_bytes());
+        hasher.update(b"@");
+        hasher.update(updated_at.timestamp_micros().to_be_bytes());
+    }
+    format!("\"{}\"", hex::encode(hasher.finalize()))
+}
+
+/// Whether the request's `If-None-Match` matches `etag`.
+///
+/// Accepts `*`, comma-separated lists and weak (`W/`) validators, per the
+/// weak comparison RFC 9110 prescribes for `If-None-Match`.
+pub fn if_none_match(headers: &HeaderMap, etag: &str) -> bool {
+    headers
+        .get_all(IF_NONE_MATCH)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .map(str::trim)
+        .any(|candidate| candidate == "*" || candidate.trim_start_matches("W/") == etag)
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    fn at(secs: i64) -> chrono::DateTime<chrono::Utc> {
+        chrono::DateTime::from_timestamp(secs, 0).unwrap()
+    }
+
+    #[test]
+    fn etag_changes_when_the_test_suite_is_replaced() {
+        let tests = vec![("filters/abc/tests/a.toml".to_string(), at(1))];
+        let before = download_etag("abc", None, &tests);
+        assert_eq!(before, download_etag("abc", None, &tests));
+        assert!(before.starts_with('"') && before.ends_with('"'));
+
+        let replaced = vec![("filters/abc/tests/a.toml".to_string(), at(2))];
+        assert_ne!(before, download_etag("abc", None, &replaced));
+        assert_ne!(before, download_etag("abc", None, &[]));
+        assert_ne!(before, download_etag("abd", None, &tests));
+        assert_ne!(before, download_etag("abc", Some("sig"), &tests));
+    }
+
+    #[test]
+    fn if_none_match_uses_weak_comparison() {
+        let etag = "\"abc\"";
+        let mut headers = HeaderMap::new();
+        assert!(!if_none_match(&headers, etag));
+
+        headers.insert(IF_NONE_MATCH, "\"other\", W/\"abc\"".parse().unwrap());
+        assert!(if_none_match(&headers, etag));
+
+        headers.insert(IF_NONE_MATCH, "\"other\"".parse().unwrap());
+        assert!(!if_none_match(&headers, etag));
+
+        headers.insert(IF_NONE_MATCH, "*".parse().unwrap());
+        assert!(if_none_match(&headers, etag));
+    }
+}
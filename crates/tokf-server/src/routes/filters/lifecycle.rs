@@ -0,0 +1,241 @@
+use axum::{
+    Json,
+    extract::{Path, State},
+};
+use serde::{Deserialize, Serialize};
+
+use crate::auth::token::AuthUser;
+use crate::error::AppError;
+use crate::state::AppState;
+
+/// Longest accepted yank reason, in bytes.
+const MAX_REASON_LEN: usize = 500;
+
+// ── Request / Response types ─────────────────────────────────────────────────
+
+#[derive(Debug, Default, Deserialize)]
+pub struct YankRequest {
+    /// Shown to users who have the filter installed.
+    #[serde(default)]
+    pub reason: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct DeprecateRequest {
+    /// Content hash of the filter to use instead.
+    #[serde(default)]
+    pub successor_hash: Option<String>,
+}
+
+/// A filter's yank and deprecation state after a change.
+#[derive(Debug, Serialize, sqlx::FromRow)]
+pub struct FilterStatus {
+    pub content_hash: String,
+    pub deprecated_at: Option<String>,
+    pub successor_hash: Option<String>,
+    pub yanked_at: Option<String>,
+    pub yank_reason: Option<String>,
+}
+
+// ── Internal helpers ──────────────────────────────────────────────────────────
+
+/// Verify the filter exists and the caller is its author or a moderator: a
+/// stdlib publisher or an admin.
+async fn verify_maintainer(state: &AppState, hash: &str, user_id: i64) -> Result<(), AppError> {
+    let row = sqlx::query_as::<_, (i64, bool)>(
+        "SELECT f.author_id, u.stdlib_publisher OR u.role = 'admin'
+         FROM filters f, users u
+         WHERE f.content_hash = $1 AND u.id = $2",
+    )
+    .bind(hash)
+    .bind(user_id)
+    .fetch_optional(&state.db)
+    .await?;
+
+    let (author_id, moderator) =
+        row.ok_or_else(|| AppError::NotFound(format!("filter not found: {hash}")))?;
+    if author_id != user_id && !moderator {
+        // Private filters the caller cannot see are "not found".
+        super::orgs::ensure_visible(&state.db, hash, user_id).await?;
+        return Err(AppError::Forbidden(
+            "you are not the author of this filter".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// Apply `update` (binding `$1` to the hash) and return the new state.
+async fn update_status(
+    state: &AppState,
+    query: sqlx::query::Query<'_, sqlx::Postgres, sqlx::postgres::PgArguments>,
+    hash: &str,
+) -> Result<Json<FilterStatus>, AppError> {
+    query.execute(&state.db).await?;
+    let status = sqlx::query_as::<_, FilterStatus>(
+        "SELECT content_hash, deprecated_at, successor_hash,
+                yanked_at::TEXT AS yanked_at, yank_reason
+         FROM filters WHERE content_hash = $1",
+    )
+    .bind(hash)
+    .fetch_one(&state.db)
+    .await?;
+    Ok(Json(status))
+}
+
+// ── PUT /api/filters/:hash/yank ──────────────────────────────────────────────
+
+/// Yank a filter: hide it from search and the catalog and warn users who
+/// have it installed. It stays downloadable by hash. Yanking again only
+/// replaces the reason.
+///
+/// # Errors
+///
+/// - `400 Bad Request` if the reason exceeds 500 bytes.
+/// - `401 Unauthorized` if the bearer token is missing or invalid.
+/// - `403 Forbidden` if the caller is neither the author nor a moderator.
+/// - `404 Not Found` if no filter with the given hash exists.
+/// - `500 Internal Server Error` on database failures.
+pub async fn yank_filter(
+    auth: AuthUser,
+    State(state): State<AppState>,
+    Path(hash): Path<String>,
+    Json(req): Json<YankRequest>,
+) -> Result<Json<FilterStatus>, AppError> {
+    if req
+        .reason
+        .as_ref()
+        .is_some_and(|r| r.len() > MAX_REASON_LEN)
+    {
+        return Err(AppError::BadRequest(format!(
+            "yank reason must not exceed {MAX_REASON_LEN} bytes"
+        )));
+    }
+    verify_maintainer(&state, &hash, auth.user_id).await?;
+    tracing::info!(hash = %hash, user = %auth.username, "filter yanked");
+    let query = sqlx::query(
+        "UPDATE filters SET yanked_at = COALESCE(yanked_at, NOW()), yank_reason = $2
+         WHERE content_hash = $1",
+    )
+    .bind(&hash)
+    .bind(
+        req.reason
+            .as_deref()
+            .map(str::trim)
+            .filter(|r| !r.is_empty()),
+    );
+    let status = update_status(&state, query, &hash).await?;
+    let event = crate::webhooks::WebhookEvent::Yanked;
+    crate::webhooks::spawn_dispatch(&state, event, &hash, status.yank_reason.clone());
+    Ok(status)
+}
+
+// ── DELETE /api/filters/:hash/yank ───────────────────────────────────────────
+
+/// Undo a yank.
+///
+/// # Errors
+///
+/// - `401 Unauthorized` if the bearer token is missing or invalid.
+/// - `403 Forbidden` if the caller is neither the author nor a moderator.
+/// - `404 Not Found` if no filter with the given hash exists.
+/// - `500 Internal Server Error` on database failures.
+pub async fn unyank_filter(
+    auth: AuthUser,
+    State(state): State<AppState>,
+    Path(hash): Path<String>,
+) -> Result<Json<FilterStatus>, AppError> {
+    verify_maintainer(&state, &hash, auth.user_id).await?;
+    tracing::info!(hash = %hash, user = %auth.username, "filter unyanked");
+    let query = sqlx::query(
+        "UPDATE filters SET yanked_at = NULL, yank_reason = NULL WHERE content_hash = $1",
+    )
+    .bind(&hash);
+    update_status(&state, query, &hash).await
+}
+
+// ── PUT /api/filters/:hash/deprecation ───────────────────────────────────────
+
+/// Deprecate a filter, optionally naming its replacement. `tokf update`
+/// stops offering deprecated filters and `tokf install` warns about them.
+/// Deprecating again only replaces the successor.
+///
+/// # Errors
+///
+/// - `400 Bad Request` if the successor is the filter itself, does not
+///   exist, or is yanked.
+/// - `401 Unauthorized` if the bearer token is missing or invalid.
+/// - `403 Forbidden` if the caller is neither the author nor a moderator.
+/// - `404 Not Found` if no filter with the given hash exists.
+/// - `500 Internal Server Error` on database failures.
+pub async fn deprecate_filter(
+    auth: AuthUser,
+    State(state): State<AppState>,
+    Path(hash): Path<String>,
+    Json(req): Json<DeprecateRequest>,
+) -> Result<Json<FilterStatus>, AppError> {
+    verify_maintainer(&state, &hash, auth.user_id).await?;
+    if let Some(successor) = &req.successor_hash {
+        if *successor == hash {
+            return Err(AppError::BadRequest(
+                "a filter cannot succeed itself".to_string(),
+            ));
+        }
+        let sql = format!(
+            "SELECT f.yanked_at IS NOT NULL FROM filters f WHERE f.content_hash = $1 AND {}",
+            super::orgs::visible_to("$2")
+        );
+        // SQL-safe: the only interpolation is the `visible_to` fragment.
+        let yanked: Option<bool> = sqlx::query_scalar(sqlx::AssertSqlSafe(sql))
+            .bind(successor)
+            .bind(auth.user_id)
+            .fetch_optional(&state.db)
+            .await?;
+        match yanked {
+            None => {
+                return Err(AppError::BadRequest(format!(
+                    "successor filter not found: {successor}"
+                )));
+            }
+            Some(true) => {
+                return Err(AppError::BadRequest(format!(
+                    "successor filter is yanked: {successor}"
+                )));
+            }
+            Some(false) => {}
+        }
+    }
+    tracing::info!(hash = %hash, user = %auth.username, "filter deprecated");
+    let now = chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Secs, true);
+    let query = sqlx::query(
+        "UPDATE filters SET deprecated_at = COALESCE(deprecated_at, $2), successor_hash = $3
+         WHERE content_hash = $1",
+    )
+    .bind(&hash)
+    .bind(now)
+    .bind(&req.successor_hash);
+    update_status(&state, query, &hash).await
+}
+
+// ── DELETE /api/filters/:hash/deprecation ────────────────────────────────────
+
+/// Undo a deprecation.
+///
+/// # Errors
+///
+/// - `401 Unauthorized` if the bearer token is missing or invalid.
+/// - `403 Forbidden` if the caller is neither the author nor a moderator.
+/// - `404 Not Found` if no filter with the given hash exists.
+/// - `500 Internal Server Error` on database failures.
+pub async fn undeprecate_filter(
+    auth: AuthUser,
+    State(state): State<AppState>,
+    Path(hash): Path<String>,
+) -> Result<Json<FilterStatus>, AppError> {
+    verify_maintainer(&state, &hash, auth.user_id).await?;
+    tracing::info!(hash = %hash, user = %auth.username, "filter undeprecated");
+    let query = sqlx::query(
+        "UPDATE filters SET deprecated_at = NULL, successor_hash = NULL WHERE content_hash = $1",
+    )
+    .bind(&hash);
+    update_status(&state, query, &hash).await
+}
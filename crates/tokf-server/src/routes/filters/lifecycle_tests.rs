@@ -0,0 +1,176 @@
+use std::sync::Arc;
+
+use axum::http::StatusCode;
+use http_body_util::BodyExt;
+use sqlx::PgPool;
+
+use crate::storage::mock::InMemoryStorageClient;
+
+use super::test_helpers::{
+    assert_status, get_request, insert_test_user, make_state_with_storage, publish_filter_helper,
+    send_json,
+};
+
+struct Registry {
+    pool: PgPool,
+    storage: Arc<InMemoryStorageClient>,
+}
+
+impl Registry {
+    fn new(pool: PgPool) -> Self {
+        Self {
+            pool,
+            storage: Arc::new(InMemoryStorageClient::new()),
+        }
+    }
+
+    fn app(&self) -> axum::Router {
+        crate::routes::create_router(make_state_with_storage(
+            self.pool.clone(),
+            Arc::clone(&self.storage),
+        ))
+    }
+
+    async fn publish(&self, token: &str, toml: &[u8]) -> String {
+        publish_filter_helper(self.app(), token, toml, &[]).await
+    }
+
+    async fn send(
+        &self,
+        method: &str,
+        token: &str,
+        uri: &str,
+        body: &serde_json::Value,
+    ) -> (StatusCode, serde_json::Value) {
+        let resp = send_json(self.app(), method, token, uri, body).await;
+        let status = resp.status();
+        let bytes = resp.into_body().collect().await.unwrap().to_bytes();
+        (status, serde_json::from_slice(&bytes).unwrap_or_default())
+    }
+
+    async fn get(&self, token: &str, uri: &str) -> (StatusCode, serde_json::Value) {
+        let resp = get_request(self.app(), token, uri).await;
+        let status = resp.status();
+        let bytes = resp.into_body().collect().await.unwrap().to_bytes();
+        (status, serde_json::from_slice(&bytes).unwrap_or_default())
+    }
+}
+
+#[crdb_test_macro::crdb_test(migrations = "./migrations")]
+async fn yanked_filters_leave_search_but_stay_downloadable(pool: PgPool) {
+    let registry = Registry::new(pool.clone());
+    let (_, token) = insert_test_user(&pool, "yank_author").await;
+    let hash = registry.publish(&token, b"command = \"git push\"\n").await;
+    let yank = format!("/api/filters/{hash}/yank");
+
+    let (status, body) = registry
+        .send(
+            "PUT",
+            &token,
+            &yank,
+            &serde_json::json!({ "reason": "leaks env" }),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(body["yanked_at"].is_string());
+    assert_eq!(body["yank_reason"], "leaks env");
+
+    let (_, results) = registry.get(&token, "/api/filters?q=git").await;
+    assert_eq!(results, serde_json::json!([]));
+    let (_, results) = registry
+        .get(&token, "/api/filters?q=git&include_yanked=true")
+        .await;
+    assert_eq!(results[0]["yank_reason"], "leaks env");
+
+    let (_, details) = registry.get(&token, &format!("/api/filters/{hash}")).await;
+    assert!(details["yanked_at"].is_string());
+    let resp = get_request(
+        registry.app(),
+        &token,
+        &format!("/api/filters/{hash}/download"),
+    )
+    .await;
+    assert_status(&resp, StatusCode::OK);
+
+    let (status, body) = registry
+        .send("DELETE", &token, &yank, &serde_json::json!({}))
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(body["yanked_at"].is_null());
+    let (_, results) = registry.get(&token, "/api/filters?q=git").await;
+    assert_eq!(results.as_array().unwrap().len(), 1);
+}
+
+#[crdb_test_macro::crdb_test(migrations = "./migrations")]
+async fn only_the_author_or_a_stdlib_publisher_may_yank(pool: PgPool) {
+    let registry = Registry::new(pool.clone());
+    let (_, author) = insert_test_user(&pool, "yank_owner").await;
+    let (_, stranger) = insert_test_user(&pool, "yank_stranger").await;
+    let (moderator_id, moderator) = insert_test_user(&pool, "yank_moderator").await;
+    sqlx::query("UPDATE users SET stdlib_publisher = TRUE WHERE id = $1")
+        .bind(moderator_id)
+        .execute(&pool)
+        .await
+        .unwrap();
+    let hash = registry.publish(&author, b"command = \"make\"\n").await;
+    let yank = format!("/api/filters/{hash}/yank");
+    let empty = serde_json::json!({});
+
+    let (status, _) = registry.send("PUT", &stranger, &yank, &empty).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    let (status, _) = registry.send("PUT", &moderator, &yank, &empty).await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, _) = registry
+        .send("PUT", &author, "/api/filters/deadbeef/yank", &empty)
+        .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[crdb_test_macro::crdb_test(migrations = "./migrations")]
+async fn deprecation_names_a_live_successor(pool: PgPool) {
+    let registry = Registry::new(pool.clone());
+    let (_, token) = insert_test_user(&pool, "deprecate_author").await;
+    let old = registry
+        .publish(&token, b"command = \"cargo test\"\n")
+        .await;
+    let new = registry
+        .publish(
+            &token,
+            b"command = \"cargo test\"\nskip = [\"^ *Running\"]\n",
+        )
+        .await;
+    let deprecation = format!("/api/filters/{old}/deprecation");
+
+    for bad in [old.as_str(), "deadbeef"] {
+        let (status, _) = registry
+            .send(
+                "PUT",
+                &token,
+                &deprecation,
+                &serde_json::json!({ "successor_hash": bad }),
+            )
+            .await;
+        assert_eq!(status, StatusCode::BAD_REQUEST, "successor {bad}");
+    }
+
+    let (status, body) = registry
+        .send(
+            "PUT",
+            &token,
+            &deprecation,
+            &serde_json::json!({ "successor_hash": new }),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(body["deprecated_at"].is_string());
+    assert_eq!(body["successor_hash"], new.as_str());
+    let (_, details) = registry.get(&token, &format!("/api/filters/{old}")).await;
+    assert_eq!(details["successor_hash"], new.as_str());
+
+    let (status, body) = registry
+        .send("DELETE", &token, &deprecation, &serde_json::json!({}))
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(body["deprecated_at"].is_null());
+    assert!(body["successor_hash"].is_null());
+}
@@ -2,19 +2,35 @@ mod backfill;
 #[cfg(test)]
 #[allow(clippy::unwrap_used, clippy::expect_used)]
 mod backfill_tests;
+mod etag;
+mod lifecycle;
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod lifecycle_tests;
+pub(super) mod orgs;
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod orgs_tests;
 mod publish;
 mod regenerate;
 mod search;
+mod search_query;
 #[cfg(test)]
 #[allow(clippy::unwrap_used, clippy::expect_used)]
 mod search_tests;
+mod stars;
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod stars_tests;
 #[cfg(test)]
 pub mod test_helpers;
 mod update_tests;
 
 pub use backfill::{backfill_v1_hashes, backfill_versions};
+pub use lifecycle::{deprecate_filter, undeprecate_filter, unyank_filter, yank_filter};
 pub use publish::publish_filter;
 pub use publish::stdlib::publish_stdlib;
 pub use regenerate::regenerate_examples;
 pub use search::{download_filter, get_filter, search_filters};
+pub use stars::{star_filter, unstar_filter};
 pub use update_tests::update_tests;
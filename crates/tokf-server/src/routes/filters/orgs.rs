@@ -0,0 +1,223 @@
+//! Organization namespaces and filter visibility.
+//!
+//! A private filter is visible only to its author and, when it is published
+//! under a GitHub org, to the org's members. Everyone else gets the same
+//! answers as for a filter that does not exist.
+//!
+//! A filter published under a GitHub org carries the org's login. Org
+//! membership is not asked of GitHub per request: it is fetched when the
+//! user signs in and cached in `users.orgs`, so joining or leaving an org
+//! takes effect at the next `tokf auth login`.
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::error::AppError;
+
+/// Longest GitHub login, in bytes.
+const MAX_ORG_LEN: usize = 39;
+
+/// Who can see a published filter.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Visibility {
+    /// Everyone.
+    #[default]
+    Public,
+    /// The author, and members of the filter's org if it has one.
+    Private,
+}
+
+impl Visibility {
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Public => "public",
+            Self::Private => "private",
+        }
+    }
+
+    /// Parse the `visibility` publish field.
+    ///
+    /// # Errors
+    ///
+    /// Returns `400 Bad Request` for anything but `public` or `private`.
+    pub fn parse(s: &str) -> Result<Self, AppError> {
+        match s.trim() {
+            "public" => Ok(Self::Public),
+            "private" => Ok(Self::Private),
+            other => Err(AppError::BadRequest(format!(
+                "visibility must be 'public' or 'private', got '{other}'"
+            ))),
+        }
+    }
+}
+
+/// SQL condition over `filters f` that holds when the user whose id is
+/// bound at `viewer` (e.g. `"$2"`) may see the filter. Filters delisted or
+/// rejected by an admin are hidden from everyone.
+pub fn visible_to(viewer: &str) -> String {
+    format!(
+        "(f.delisted_at IS NULL AND f.review_status IS DISTINCT FROM 'rejected'
+          AND (f.visibility = 'public' OR f.author_id = {viewer}
+               OR (f.org IS NOT NULL AND EXISTS (
+                   SELECT 1 FROM users m
+                   WHERE m.id = {viewer} AND m.orgs @> jsonb_build_array(f.org)))))"
+    )
+}
+
+/// The key a filter is stored under.
+///
+/// A public filter is keyed by its content hash, so identical public uploads
+/// are one filter. A private filter's key also names who can see it: its org,
+/// or its author when it has none. Identical private content from another
+/// account is then a separate filter, and publishing it neither collides
+/// with nor reveals the first.
+pub fn storage_key(
+    content_hash: &str,
+    visibility: Visibility,
+    org: Option<&str>,
+    author_id: i64,
+) -> String {
+    if visibility == Visibility::Public {
+        return content_hash.to_string();
+    }
+    let owner = org.map_or_else(
+        || format!("user:{author_id}"),
+        |org| format!("org:{}", org.to_lowercase()),
+    );
+    let mut hasher = Sha256::new();
+    hasher.update(format!("tokf-private\0{owner}\0{content_hash}"));
+    hex::encode(hasher.finalize())
+}
+
+/// SQL condition over `filters f` that holds for filters in the namespace a
+/// publish lands in: public filters for a public publish, or private ones of
+/// the same org (or, without one, the same author) for a private one. The
+/// arguments name the bound visibility, org and author id (e.g. `"$3"`).
+pub fn same_namespace(visibility: &str, org: &str, author: &str) -> String {
+    format!(
+        "(f.visibility = {visibility}
+          AND ({visibility} = 'public'
+               OR ({org}::TEXT IS NOT NULL AND lower(f.org) = lower({org}))
+               OR ({org}::TEXT IS NULL AND f.org IS NULL AND f.author_id = {author})))"
+    )
+}
+
+/// Fail with `404 Not Found` unless the filter exists and `user_id` may see
+/// it. Hidden filters are indistinguishable from missing ones.
+///
+/// # Errors
+///
+/// Returns `404 Not Found` as above, or propagates database failures.
+pub async fn ensure_visible(db: &sqlx::PgPool, hash: &str, user_id: i64) -> Result<(), AppError> {
+    let sql = format!(
+        "SELECT f.content_hash FROM filters f WHERE f.content_hash = $1 AND {}",
+        visible_to("$2")
+    );
+    // SQL-safe: the only interpolation is the `visible_to` fragment.
+    let found: Option<String> = sqlx::query_scalar(sqlx::AssertSqlSafe(sql))
+        .bind(hash)
+        .bind(user_id)
+        .fetch_optional(db)
+        .await?;
+    found
+        .map(|_| ())
+        .ok_or_else(|| AppError::NotFound(format!("filter not found: {hash}")))
+}
+
+/// Resolve the org a filter is published under to its GitHub login, checking
+/// that `user_id` is a member. Matching is case-insensitive, as on GitHub.
+///
+/// # Errors
+///
+/// - `400 Bad Request` if `requested` is not a valid GitHub login.
+/// - `403 Forbidden` if the user is not a member as of their last sign-in.
+pub async fn resolve_org(
+    db: &sqlx::PgPool,
+    user_id: i64,
+    requested: &str,
+) -> Result<String, AppError> {
+    let requested = requested.trim();
+    if !is_valid_login(requested) {
+        return Err(AppError::BadRequest(format!(
+            "invalid org name: '{requested}'"
+        )));
+    }
+    let orgs: serde_json::Value = sqlx::query_scalar("SELECT orgs FROM users WHERE id = $1")
+        .bind(user_id)
+        .fetch_one(db)
+        .await?;
+    find_org(&orgs, requested).ok_or_else(|| {
+        AppError::Forbidden(format!(
+            "you are not a member of the GitHub org '{requested}' \
+             (joined recently? run `tokf auth login` again)"
+        ))
+    })
+}
+
+/// The login in the cached `orgs` array matching `requested`.
+fn find_org(orgs: &serde_json::Value, requested: &str) -> Option<String> {
+    orgs.as_array()?
+        .iter()
+        .filter_map(serde_json::Value::as_str)
+        .find(|o| o.eq_ignore_ascii_case(requested))
+        .map(str::to_string)
+}
+
+/// GitHub logins are 1–39 ASCII alphanumerics or single inner hyphens.
+fn is_valid_login(s: &str) -> bool {
+    !s.is_empty()
+        && s.len() <= MAX_ORG_LEN
+        && !s.starts_with('-')
+        && !s.ends_with('-')
+        && !s.contains("--")
+        && s.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn org_membership_is_case_insensitive() {
+        let orgs = serde_json::json!(["Acme-Corp", "other"]);
+        assert_eq!(find_org(&orgs, "acme-corp").as_deref(), Some("Acme-Corp"));
+        assert_eq!(find_org(&orgs, "acme"), None);
+        assert_eq!(find_org(&serde_json::json!({}), "acme"), None);
+    }
+
+    #[test]
+    fn org_names_follow_github_login_rules() {
+        assert!(is_valid_login("acme"));
+        assert!(is_valid_login("acme-corp-2"));
+        for bad in ["", "-acme", "acme-", "ac--me", "acme/corp", &"a".repeat(40)] {
+            assert!(!is_valid_login(bad), "{bad:?} should be rejected");
+        }
+    }
+
+    #[test]
+    fn private_filters_are_keyed_by_owner() {
+        let hash = "ab".repeat(32);
+        assert_eq!(
+            storage_key(&hash, Visibility::Public, Some("acme"), 1),
+            hash
+        );
+        let mine = storage_key(&hash, Visibility::Private, None, 1);
+        assert_eq!(mine.len(), 64);
+        assert_ne!(mine, hash);
+        assert_ne!(mine, storage_key(&hash, Visibility::Private, None, 2));
+        let org = storage_key(&hash, Visibility::Private, Some("Acme"), 1);
+        assert_eq!(
+            org,
+            storage_key(&hash, Visibility::Private, Some("acme"), 2)
+        );
+        assert_ne!(org, mine);
+    }
+
+    #[test]
+    fn visibility_parses_publish_field() {
+        assert_eq!(Visibility::parse("private").ok(), Some(Visibility::Private));
+        assert_eq!(Visibility::parse(" public ").ok(), Some(Visibility::Public));
+        assert!(Visibility::parse("secret").is_err());
+    }
+}
@@ -0,0 +1,168 @@
+use axum::http::StatusCode;
+use http_body_util::BodyExt;
+use sqlx::PgPool;
+
+use super::test_helpers::{
+    DEFAULT_PASSING_TEST, MIT_ACCEPT, assert_status, get_request, insert_test_user, make_state,
+    post_filter,
+};
+
+const FILTER: &[u8] = b"command = \"internal-deploy\"";
+
+fn app(pool: &PgPool) -> axum::Router {
+    crate::routes::create_router(make_state(pool.clone()))
+}
+
+async fn join_orgs(pool: &PgPool, user_id: i64, orgs: &[&str]) {
+    sqlx::query("UPDATE users SET orgs = $1 WHERE id = $2")
+        .bind(serde_json::json!(orgs))
+        .bind(user_id)
+        .execute(pool)
+        .await
+        .unwrap();
+}
+
+async fn publish(pool: &PgPool, token: &str, extra: &[(&str, &[u8])]) -> axum::response::Response {
+    let mut fields: Vec<(&str, &[u8])> = vec![("filter", FILTER), MIT_ACCEPT, DEFAULT_PASSING_TEST];
+    fields.extend_from_slice(extra);
+    post_filter(app(pool), token, &fields).await
+}
+
+async fn publish_json(pool: &PgPool, token: &str, extra: &[(&str, &[u8])]) -> serde_json::Value {
+    let resp = publish(pool, token, extra).await;
+    assert_status(&resp, StatusCode::CREATED);
+    let bytes = resp.into_body().collect().await.unwrap().to_bytes();
+    serde_json::from_slice(&bytes).unwrap()
+}
+
+async fn get_json(pool: &PgPool, token: &str, uri: &str) -> (StatusCode, serde_json::Value) {
+    let resp = get_request(app(pool), token, uri).await;
+    let status = resp.status();
+    let bytes = resp.into_body().collect().await.unwrap().to_bytes();
+    (status, serde_json::from_slice(&bytes).unwrap_or_default())
+}
+
+#[crdb_test_macro::crdb_test(migrations = "./migrations")]
+async fn private_org_filters_are_visible_only_to_members(pool: PgPool) {
+    let (author_id, author) = insert_test_user(&pool, "alice").await;
+    let (member_id, member) = insert_test_user(&pool, "bob").await;
+    let (_, outsider) = insert_test_user(&pool, "eve").await;
+    join_orgs(&pool, author_id, &["Acme"]).await;
+    join_orgs(&pool, member_id, &["Acme"]).await;
+
+    let resp = publish(
+        &pool,
+        &author,
+        &[("org", b"acme"), ("visibility", b"private")],
+    )
+    .await;
+    assert_status(&resp, StatusCode::CREATED);
+    let body = resp.into_body().collect().await.unwrap().to_bytes();
+    let hash = serde_json::from_slice::<serde_json::Value>(&body).unwrap()["content_hash"]
+        .as_str()
+        .unwrap()
+        .to_string();
+
+    for token in [&author, &member] {
+        let (status, details) = get_json(&pool, token, &format!("/api/filters/{hash}")).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(details["org"], "Acme", "org keeps GitHub's spelling");
+        assert_eq!(details["visibility"], "private");
+        let (_, found) = get_json(&pool, token, "/api/filters?org=ACME").await;
+        assert_eq!(found.as_array().unwrap().len(), 1);
+        let resp = get_request(app(&pool), token, &format!("/api/filters/{hash}/download")).await;
+        assert_status(&resp, StatusCode::OK);
+    }
+
+    let (status, _) = get_json(&pool, &outsider, &format!("/api/filters/{hash}")).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    let (_, found) = get_json(&pool, &outsider, "/api/filters?q=internal").await;
+    assert!(found.as_array().unwrap().is_empty());
+    let resp = get_request(
+        app(&pool),
+        &outsider,
+        &format!("/api/filters/{hash}/download"),
+    )
+    .await;
+    assert_status(&resp, StatusCode::NOT_FOUND);
+
+    // Publishing the same content succeeds as a fresh upload, so it can't be
+    // used to detect the private filter.
+    let published = publish_json(&pool, &outsider, &[]).await;
+    assert_eq!(published["author"], "eve");
+    assert_ne!(published["content_hash"], hash.as_str());
+}
+
+#[crdb_test_macro::crdb_test(migrations = "./migrations")]
+async fn publishing_under_an_org_requires_membership(pool: PgPool) {
+    let (user_id, token) = insert_test_user(&pool, "alice").await;
+    join_orgs(&pool, user_id, &["acme"]).await;
+
+    let resp = publish(&pool, &token, &[("org", b"globex")]).await;
+    assert_status(&resp, StatusCode::FORBIDDEN);
+    let resp = publish(&pool, &token, &[("org", b"acme/infra")]).await;
+    assert_status(&resp, StatusCode::BAD_REQUEST);
+
+    // A public org filter is visible to everyone, namespaced by the org.
+    let resp = publish(&pool, &token, &[("org", b"acme")]).await;
+    assert_status(&resp, StatusCode::CREATED);
+    let (_, other) = insert_test_user(&pool, "eve").await;
+    let (_, found) = get_json(&pool, &other, "/api/filters?org=acme").await;
+    assert_eq!(found[0]["org"], "acme");
+    assert_eq!(found[0]["visibility"], "public");
+}
+
+#[crdb_test_macro::crdb_test(migrations = "./migrations")]
+async fn private_filters_without_an_org_are_visible_only_to_their_author(pool: PgPool) {
+    let (author_id, author) = insert_test_user(&pool, "alice").await;
+    let (other_id, other) = insert_test_user(&pool, "bob").await;
+    // Sharing an org doesn't matter when the filter isn't published under it.
+    join_orgs(&pool, author_id, &["acme"]).await;
+    join_orgs(&pool, other_id, &["acme"]).await;
+
+    let resp = publish(&pool, &author, &[("visibility", b"private")]).await;
+    assert_status(&resp, StatusCode::CREATED);
+    let body = resp.into_body().collect().await.unwrap().to_bytes();
+    let hash = serde_json::from_slice::<serde_json::Value>(&body).unwrap()["content_hash"]
+        .as_str()
+        .unwrap()
+        .to_string();
+
+    let (status, details) = get_json(&pool, &author, &format!("/api/filters/{hash}")).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(details["visibility"], "private");
+    assert!(details.get("org").is_none());
+    let (_, found) = get_json(&pool, &author, "/api/filters?q=internal").await;
+    assert_eq!(found.as_array().unwrap().len(), 1);
+
+    let (status, _) = get_json(&pool, &other, &format!("/api/filters/{hash}")).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    let (_, found) = get_json(&pool, &other, "/api/filters?q=internal").await;
+    assert!(found.as_array().unwrap().is_empty());
+    let resp = get_request(app(&pool), &other, &format!("/api/filters/{hash}/download")).await;
+    assert_status(&resp, StatusCode::NOT_FOUND);
+}
+
+#[crdb_test_macro::crdb_test(migrations = "./migrations")]
+async fn identical_uploads_do_not_reveal_a_private_filter(pool: PgPool) {
+    let (_, author) = insert_test_user(&pool, "alice").await;
+    let (_, other) = insert_test_user(&pool, "bob").await;
+
+    let private = publish_json(&pool, &author, &[("visibility", b"private")]).await;
+    let hash = private["content_hash"].as_str().unwrap();
+
+    // Another user publishing the same content, privately or publicly, gets
+    // the response of a fresh upload: their own filter under its own hash.
+    let theirs = publish_json(&pool, &other, &[("visibility", b"private")]).await;
+    assert_eq!(theirs["author"], "bob");
+    assert_ne!(theirs["content_hash"], hash);
+    let public = publish_json(&pool, &other, &[]).await;
+    assert_eq!(public["author"], "bob");
+    assert_ne!(public["content_hash"], hash);
+
+    let (status, _) = get_json(&pool, &other, &format!("/api/filters/{hash}")).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    let (status, details) = get_json(&pool, &author, &format!("/api/filters/{hash}")).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(details["author"], "alice");
+}
@@ -5,6 +5,7 @@ use tokf_common::config::types::FilterConfig;
 use tokf_common::hash::canonical_hash;
 use tokf_common::test_case::TestCase;
 
+use super::orgs::{self, Visibility};
 use crate::auth::token::AuthUser;
 use crate::error::AppError;
 use crate::state::AppState;
@@ -26,19 +27,26 @@ pub struct PublishFilterResponse {
 
 // ── Internal helpers ──────────────────────────────────────────────────────────
 
+#[derive(Default)]
 struct MultipartFields {
     filter_bytes: Vec<u8>,
     test_files: Vec<(String, Vec<u8>)>,
     mit_license_accepted: bool,
+    /// Hex Ed25519 signature over `filter_bytes`, if the author signed it.
+    signature: Option<String>,
+    /// Hex public key the signature was made with.
+    public_key: Option<String>,
+    /// GitHub org to publish under.
+    org: Option<String>,
+    visibility: Visibility,
 }
 
 async fn parse_multipart(
     multipart: &mut axum::extract::Multipart,
 ) -> Result<MultipartFields, AppError> {
+    let mut fields = MultipartFields::default();
     let mut filter_bytes: Option<Vec<u8>> = None;
-    let mut test_files: Vec<(String, Vec<u8>)> = Vec::new();
     let mut total_size: usize = 0;
-    let mut mit_license_accepted = false;
 
     while let Some(field) = multipart
         .next_field()
@@ -67,22 +75,77 @@ async fn parse_multipart(
                 ));
             }
             filter_bytes = Some(bytes.to_vec());
-        } else if name == "mit_license_accepted" {
-            mit_license_accepted = bytes.as_ref() == b"true";
-        } else if let Some(filename) = name.strip_prefix("test:")
-            && !filename.is_empty()
-        {
-            test_files.push((filename.to_string(), bytes.to_vec()));
+        } else {
+            store_field(&mut fields, &name, &bytes)?;
         }
     }
 
-    let filter_bytes = filter_bytes
+    fields.filter_bytes = filter_bytes
         .ok_or_else(|| AppError::BadRequest("missing required 'filter' field".to_string()))?;
-    Ok(MultipartFields {
-        filter_bytes,
-        test_files,
-        mit_license_accepted,
-    })
+    Ok(fields)
+}
+
+/// Record a multipart field other than `filter`; unknown fields are ignored.
+fn store_field(fields: &mut MultipartFields, name: &str, bytes: &[u8]) -> Result<(), AppError> {
+    match name {
+        "mit_license_accepted" => fields.mit_license_accepted = bytes == b"true",
+        "signature" => fields.signature = Some(hex_field(name, bytes)?),
+        "public_key" => fields.public_key = Some(hex_field(name, bytes)?),
+        "org" => fields.org = Some(text_field(name, bytes)?).filter(|o| !o.is_empty()),
+        "visibility" => fields.visibility = Visibility::parse(&text_field(name, bytes)?)?,
+        _ => {
+            if let Some(filename) = name.strip_prefix("test:")
+                && !filename.is_empty()
+            {
+                fields
+                    .test_files
+                    .push((filename.to_string(), bytes.to_vec()));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// A text multipart field, trimmed.
+fn text_field(name: &str, bytes: &[u8]) -> Result<String, AppError> {
+    std::str::from_utf8(bytes)
+        .map(|v| v.trim().to_string())
+        .map_err(|_| AppError::BadRequest(format!("'{name}' is not valid UTF-8")))
+}
+
+/// A hex multipart field, trimmed and lowercased.
+fn hex_field(name: &str, bytes: &[u8]) -> Result<String, AppError> {
+    text_field(name, bytes).map(|v| v.to_ascii_lowercase())
+}
+
+/// Check the optional filter signature: it must come with a public key
+/// registered to `user_id` and verify over the uploaded filter bytes.
+///
+/// Returns `(public_key, signature)` for a valid signature, `None` for an
+/// unsigned upload.
+async fn check_signature(
+    db: &sqlx::PgPool,
+    user_id: i64,
+    fields: &MultipartFields,
+) -> Result<Option<(String, String)>, AppError> {
+    let (public_key, signature) = match (&fields.public_key, &fields.signature) {
+        (None, None) => return Ok(None),
+        (Some(k), Some(s)) => (k.clone(), s.clone()),
+        _ => {
+            return Err(AppError::BadRequest(
+                "'signature' and 'public_key' must be sent together".to_string(),
+            ));
+        }
+    };
+    if !crate::routes::keys::key_belongs_to(db, &public_key, user_id).await? {
+        return Err(AppError::BadRequest(
+            "signing key is not registered to your account — run `tokf auth keys generate`"
+                .to_string(),
+        ));
+    }
+    tokf_common::signing::verify(&public_key, &signature, &fields.filter_bytes)
+        .map_err(|e| AppError::BadRequest(format!("invalid filter signature: {e}")))?;
+    Ok(Some((public_key, signature)))
 }
 
 /// Grouped fields for inserting a filter record.
@@ -94,6 +157,45 @@ struct FilterInsert<'a> {
     author_id: i64,
     r2_key: &'a str,
     safety_passed: bool,
+    description: Option<&'a str>,
+    tags: &'a [String],
+    version: Option<&'a str>,
+    signature: Option<&'a str>,
+    signing_key: Option<&'a str>,
+    org: Option<&'a str>,
+    visibility: Visibility,
+    /// Whether the filter runs Luau and so waits for an admin's review.
+    needs_review: bool,
+}
+
+/// Insert the filter row; returns `false` when a row with the same
+/// `content_hash` already exists.
+async fn insert_filter_row(db: &sqlx::PgPool, insert: &FilterInsert<'_>) -> Result<bool, AppError> {
+    let result = sqlx::query(
+        "INSERT INTO filters (content_hash, v1_hash, command_pattern, canonical_command, author_id, r2_key, safety_passed,
+                              description, tags, version, signature, signing_key, org, visibility,
+                              review_status)
+         VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15)
+         ON CONFLICT (content_hash) DO NOTHING",
+    )
+    .bind(insert.content_hash)
+    .bind(insert.v1_hash)
+    .bind(insert.command_pattern)
+    .bind(insert.canonical_command)
+    .bind(insert.author_id)
+    .bind(insert.r2_key)
+    .bind(insert.safety_passed)
+    .bind(insert.description)
+    .bind(insert.tags)
+    .bind(insert.version)
+    .bind(insert.signature)
+    .bind(insert.signing_key)
+    .bind(insert.org)
+    .bind(insert.visibility.as_str())
+    .bind(insert.needs_review.then_some("pending"))
+    .execute(db)
+    .await?;
+    Ok(result.rows_affected() > 0)
 }
 
 /// Resolved view of a publish attempt: the author and `content_hash` of
@@ -109,7 +211,8 @@ struct UpsertResult {
 ///
 /// Two duplicate paths: byte-identical (caught by `ON CONFLICT (content_hash)`)
 /// and v1-equivalent (caught by the pre-check below — same canonical TOML
-/// shape, different `content_hash`). Both return the existing row's author
+/// shape, different `content_hash`, same namespace so another account's
+/// private filters are never matched). Both return the existing row's author
 /// and canonical hash. Legacy rows with NULL `v1_hash` are excluded from the
 /// v1-equivalence check via SQL three-valued logic until they are backfilled.
 ///
@@ -121,16 +224,23 @@ async fn upsert_filter_record(
     insert: &FilterInsert<'_>,
     author_username: &str,
 ) -> Result<UpsertResult, AppError> {
-    if let Some((existing_hash, existing_author)) = sqlx::query_as::<_, (String, String)>(
+    let sql = format!(
         "SELECT f.content_hash, u.username FROM filters f
          JOIN users u ON u.id = f.author_id
-         WHERE f.v1_hash = $1 AND f.content_hash <> $2
+         WHERE f.v1_hash = $1 AND f.content_hash <> $2 AND {}
          LIMIT 1",
-    )
-    .bind(insert.v1_hash)
-    .bind(insert.content_hash)
-    .fetch_optional(&state.db)
-    .await?
+        orgs::same_namespace("$3", "$4", "$5")
+    );
+    // SQL-safe: the only interpolation is the `same_namespace` fragment.
+    if let Some((existing_hash, existing_author)) =
+        sqlx::query_as::<_, (String, String)>(sqlx::AssertSqlSafe(sql))
+            .bind(insert.v1_hash)
+            .bind(insert.content_hash)
+            .bind(insert.visibility.as_str())
+            .bind(insert.org)
+            .bind(insert.author_id)
+            .fetch_optional(&state.db)
+            .await?
     {
         tracing::info!(
             new_hash = %insert.content_hash,
@@ -145,22 +255,9 @@ async fn upsert_filter_record(
         });
     }
 
-    let result = sqlx::query(
-        "INSERT INTO filters (content_hash, v1_hash, command_pattern, canonical_command, author_id, r2_key, safety_passed)
-         VALUES ($1, $2, $3, $4, $5, $6, $7)
-         ON CONFLICT (content_hash) DO NOTHING",
-    )
-    .bind(insert.content_hash)
-    .bind(insert.v1_hash)
-    .bind(insert.command_pattern)
-    .bind(insert.canonical_command)
-    .bind(insert.author_id)
-    .bind(insert.r2_key)
-    .bind(insert.safety_passed)
-    .execute(&state.db)
-    .await?;
+    let inserted = insert_filter_row(&state.db, insert).await?;
 
-    if result.rows_affected() == 0 {
+    if !inserted {
         // Byte-identical duplicate — fetch the original author's username.
         let existing_author: String = sqlx::query_scalar(
             "SELECT u.username FROM filters f
@@ -247,6 +344,8 @@ pub(super) struct PreparedFilter {
     pub(super) command_pattern: String,
     pub(super) canonical_command: String,
     pub(super) config: FilterConfig,
+    /// `config.tags`, trimmed, lowercased and deduplicated for search.
+    pub(super) tags: Vec<String>,
     pub(super) filter_bytes: Vec<u8>,
     pub(super) test_files: Vec<(String, Vec<u8>)>,
     pub(super) test_cases: Vec<TestCase>,
@@ -308,6 +407,7 @@ pub(super) fn validate_and_prepare(
         v1_hash,
         command_pattern,
         canonical_command,
+        tags: normalize_tags(&config.tags),
         config,
         filter_bytes: filter_toml.to_vec(),
         test_files,
@@ -315,6 +415,19 @@ pub(super) fn validate_and_prepare(
     })
 }
 
+/// Trim, lowercase and deduplicate tags, dropping empty ones. Search compares
+/// tags exactly, so they are stored in one canonical form.
+fn normalize_tags(tags: &[String]) -> Vec<String> {
+    let mut out: Vec<String> = Vec::with_capacity(tags.len());
+    for tag in tags {
+        let tag = tag.trim().to_lowercase();
+        if !tag.is_empty() && !out.contains(&tag) {
+            out.push(tag);
+        }
+    }
+    out
+}
+
 /// Validate the multipart fields, check server-side constraints, and compute
 /// the content hash. Delegates to `validate_and_prepare` after MIT license check.
 fn prepare_filter(fields: MultipartFields) -> Result<PreparedFilter, AppError> {
@@ -401,6 +514,9 @@ pub async fn generate_examples_and_safety(
 /// - `filter` — filter TOML bytes (required, ≤ 64 KB)
 /// - `mit_license_accepted` — must be `"true"` to acknowledge MIT license (required)
 /// - `test:<filename>` — individual test TOML files (required, ≥ 1, total upload ≤ 1 MB)
+/// - `org` — GitHub org to publish under; the caller must be a member (optional)
+/// - `visibility` — `public` (default) or `private`, which hides the filter
+///   from everyone but the author and, with `org`, the org's members
 ///
 /// The server computes the content hash from the uploaded bytes; clients never
 /// supply a hash. This prevents hash forgery.
@@ -411,8 +527,12 @@ pub async fn generate_examples_and_safety(
 /// # Errors
 ///
 /// - `400 Bad Request` if the multipart is malformed, the TOML is invalid,
-///   size limits are exceeded, or MIT license was not accepted.
+///   size limits are exceeded, MIT license was not accepted, or a signature
+///   was sent that does not verify against a key registered to the caller.
 /// - `401 Unauthorized` if the bearer token is missing or invalid.
+/// - `403 Forbidden` if the caller is not a member of the requested org.
+/// - `409 Conflict` if an identical filter was delisted or rejected
+///   and the caller cannot see it.
 /// - `429 Too Many Requests` if the user exceeds publish rate limits.
 /// - `500 Internal Server Error` on storage or database failures.
 #[allow(clippy::too_many_lines)]
@@ -434,7 +554,19 @@ pub async fn publish_filter(
     }
 
     let fields = parse_multipart(&mut multipart).await?;
-    let prepared = prepare_filter(fields)?;
+    let signed = check_signature(&state.db, auth.user_id, &fields).await?;
+    let visibility = fields.visibility;
+    let org = match fields.org.as_deref() {
+        Some(org) => Some(orgs::resolve_org(&state.db, auth.user_id, org).await?),
+        None => None,
+    };
+    let mut prepared = prepare_filter(fields)?;
+    prepared.content_hash = orgs::storage_key(
+        &prepared.content_hash,
+        visibility,
+        org.as_deref(),
+        auth.user_id,
+    );
 
     // Run server-side test verification before persisting anything.
     run_verification(&prepared.config, &prepared.test_cases)
@@ -469,8 +601,24 @@ pub async fn publish_filter(
         author_id: auth.user_id,
         r2_key: &r2_key,
         safety_passed,
+        description: prepared.config.description.as_deref(),
+        tags: &prepared.tags,
+        version: prepared.config.version.as_deref(),
+        signature: signed.as_ref().map(|(_, sig)| sig.as_str()),
+        signing_key: signed.as_ref().map(|(key, _)| key.as_str()),
+        org: org.as_deref(),
+        visibility,
+        needs_review: prepared.config.lua_script.is_some(),
     };
     let upserted = upsert_filter_record(&state, &insert, &auth.username).await?;
+    if !upserted.is_new {
+        // Identical content an admin has delisted or rejected stays refused.
+        orgs::ensure_visible(&state.db, &upserted.content_hash, auth.user_id)
+            .await
+            .map_err(|_| {
+                AppError::Conflict("an identical filter has already been published".to_string())
+            })?;
+    }
 
     // Below, examples + test rows are keyed by `prepared.content_hash` (the
     // submitted hash) while the response uses `upserted.content_hash` (the
@@ -489,12 +637,17 @@ pub async fn publish_filter(
     if upserted.is_new {
         insert_filter_tests(&state.db, &prepared.content_hash, &test_r2_keys).await?;
 
-        // Fire-and-forget: materialize per-filter metadata + catalog index to R2
-        crate::catalog::spawn_catalog_update(
-            state.db.clone(),
-            state.storage.clone(),
-            prepared.content_hash.clone(),
-        );
+        // Fire-and-forget: materialize per-filter metadata + catalog index to
+        // R2. Private filters stay out of the public catalog.
+        if visibility == Visibility::Public {
+            crate::catalog::spawn_catalog_update(
+                state.db.clone(),
+                state.storage.clone(),
+                prepared.content_hash.clone(),
+            );
+        }
+        let published = crate::webhooks::WebhookEvent::Published;
+        crate::webhooks::spawn_dispatch(&state, published, &prepared.content_hash, None);
     }
 
     let registry_url = format!("{}/filters/{}", state.public_url, upserted.content_hash);
@@ -522,3 +675,7 @@ pub mod stdlib;
 #[cfg(test)]
 #[allow(clippy::unwrap_used, clippy::expect_used)]
 mod tests;
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod signing_tests;
@@ -0,0 +1,137 @@
+use axum::http::StatusCode;
+use http_body_util::BodyExt;
+use tokf_common::signing::SigningKey;
+
+use super::super::test_helpers::{
+    DEFAULT_PASSING_TEST, MIT_ACCEPT, get_request, insert_test_user, make_state, post_filter,
+    post_json,
+};
+
+const FILTER_TOML: &[u8] = b"command = \"signed-tool\"\n";
+
+/// Register a fresh signing key for the user behind `token`.
+async fn register_key(app: &axum::Router, token: &str) -> SigningKey {
+    let (key, _) = SigningKey::generate().unwrap();
+    let body = serde_json::json!({ "public_key": key.public_key_hex() });
+    let resp = post_json(app.clone(), token, "/api/keys", &body).await;
+    assert_eq!(resp.status(), StatusCode::CREATED);
+    key
+}
+
+#[crdb_test_macro::crdb_test(migrations = "./migrations")]
+async fn signed_publish_is_served_with_its_signature(pool: PgPool) {
+    let (_, token) = insert_test_user(&pool, "alice_signs").await;
+    let app = crate::routes::create_router(make_state(pool));
+    let key = register_key(&app, &token).await;
+    let signature = key.sign(FILTER_TOML);
+    let public_key = key.public_key_hex();
+
+    let resp = post_filter(
+        app.clone(),
+        &token,
+        &[
+            ("filter", FILTER_TOML),
+            MIT_ACCEPT,
+            DEFAULT_PASSING_TEST,
+            ("signature", signature.as_bytes()),
+            ("public_key", public_key.as_bytes()),
+        ],
+    )
+    .await;
+    assert_eq!(resp.status(), StatusCode::CREATED);
+    let body = resp.into_body().collect().await.unwrap().to_bytes();
+    let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    let hash = json["content_hash"].as_str().unwrap();
+
+    let resp = get_request(app, &token, &format!("/api/filters/{hash}/download")).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    let body = resp.into_body().collect().await.unwrap().to_bytes();
+    let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(json["signature"], signature);
+    assert_eq!(json["signing_key"], public_key);
+}
+
+#[crdb_test_macro::crdb_test(migrations = "./migrations")]
+async fn unsigned_download_omits_signature_fields(pool: PgPool) {
+    let (_, token) = insert_test_user(&pool, "alice_unsigned").await;
+    let app = crate::routes::create_router(make_state(pool));
+    let resp = post_filter(
+        app.clone(),
+        &token,
+        &[("filter", FILTER_TOML), MIT_ACCEPT, DEFAULT_PASSING_TEST],
+    )
+    .await;
+    let body = resp.into_body().collect().await.unwrap().to_bytes();
+    let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    let hash = json["content_hash"].as_str().unwrap();
+
+    let resp = get_request(app, &token, &format!("/api/filters/{hash}/download")).await;
+    let body = resp.into_body().collect().await.unwrap().to_bytes();
+    let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert!(json.get("signature").is_none(), "{json}");
+    assert!(json.get("signing_key").is_none(), "{json}");
+}
+
+#[crdb_test_macro::crdb_test(migrations = "./migrations")]
+async fn signature_over_other_content_is_rejected(pool: PgPool) {
+    let (_, token) = insert_test_user(&pool, "alice_tampers").await;
+    let app = crate::routes::create_router(make_state(pool));
+    let key = register_key(&app, &token).await;
+    let signature = key.sign(b"command = \"other-tool\"\n");
+    let public_key = key.public_key_hex();
+
+    let resp = post_filter(
+        app,
+        &token,
+        &[
+            ("filter", FILTER_TOML),
+            MIT_ACCEPT,
+            DEFAULT_PASSING_TEST,
+            ("signature", signature.as_bytes()),
+            ("public_key", public_key.as_bytes()),
+        ],
+    )
+    .await;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+}
+
+#[crdb_test_macro::crdb_test(migrations = "./migrations")]
+async fn unregistered_key_is_rejected(pool: PgPool) {
+    let (_, token) = insert_test_user(&pool, "alice_unregistered").await;
+    let app = crate::routes::create_router(make_state(pool));
+    let (key, _) = SigningKey::generate().unwrap();
+    let signature = key.sign(FILTER_TOML);
+    let public_key = key.public_key_hex();
+
+    let resp = post_filter(
+        app,
+        &token,
+        &[
+            ("filter", FILTER_TOML),
+            MIT_ACCEPT,
+            DEFAULT_PASSING_TEST,
+            ("signature", signature.as_bytes()),
+            ("public_key", public_key.as_bytes()),
+        ],
+    )
+    .await;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+}
+
+#[crdb_test_macro::crdb_test(migrations = "./migrations")]
+async fn signature_without_key_is_rejected(pool: PgPool) {
+    let (_, token) = insert_test_user(&pool, "alice_half").await;
+    let app = crate::routes::create_router(make_state(pool));
+    let resp = post_filter(
+        app,
+        &token,
+        &[
+            ("filter", FILTER_TOML),
+            MIT_ACCEPT,
+            DEFAULT_PASSING_TEST,
+            ("signature", b"abcd"),
+        ],
+    )
+    .await;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+}
@@ -369,8 +369,9 @@ async fn persist_filter(
         upload_tests(state, &prepared.content_hash, prepared.test_files.clone()).await?;
 
     sqlx::query(
-        "INSERT INTO filters (content_hash, v1_hash, command_pattern, canonical_command, author_id, r2_key, is_stdlib)
-         VALUES ($1, $2, $3, $4, $5, $6, TRUE)
+        "INSERT INTO filters (content_hash, v1_hash, command_pattern, canonical_command, author_id, r2_key, is_stdlib,
+                              description, tags, version)
+         VALUES ($1, $2, $3, $4, $5, $6, TRUE, $7, $8, $9)
          ON CONFLICT (content_hash) DO UPDATE SET is_stdlib = TRUE, v1_hash = COALESCE(filters.v1_hash, EXCLUDED.v1_hash)",
     )
     .bind(&prepared.content_hash)
@@ -379,6 +380,9 @@ async fn persist_filter(
     .bind(&prepared.canonical_command)
     .bind(author_id)
     .bind(&r2_key)
+    .bind(&prepared.config.description)
+    .bind(&prepared.tags)
+    .bind(&prepared.config.version)
     .execute(&state.db)
     .await?;
 
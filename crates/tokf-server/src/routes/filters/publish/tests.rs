@@ -281,6 +281,40 @@ async fn publish_filter_stores_canonical_command(pool: PgPool) {
     );
 }
 
+#[crdb_test_macro::crdb_test(migrations = "./migrations")]
+async fn publish_filter_stores_metadata(pool: PgPool) {
+    let (_, token) = insert_test_user(&pool, "alice_metadata").await;
+    let app = crate::routes::create_router(make_state(pool.clone()));
+
+    let toml = b"command = \"git push\"\ndescription = \"Compact push\"\n\
+                 tags = [\"Git\", \" vcs \", \"git\"]\nversion = \"1.2.0\"\n";
+    let resp = post_filter(
+        app,
+        &token,
+        &[("filter", toml), MIT_ACCEPT, DEFAULT_PASSING_TEST],
+    )
+    .await;
+    assert_eq!(resp.status(), StatusCode::CREATED);
+
+    let body = resp.into_body().collect().await.unwrap().to_bytes();
+    let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    let hash = json["content_hash"].as_str().unwrap();
+
+    let (description, tags, version): (Option<String>, Vec<String>, Option<String>) =
+        sqlx::query_as("SELECT description, tags, version FROM filters WHERE content_hash = $1")
+            .bind(hash)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+    assert_eq!(description.as_deref(), Some("Compact push"));
+    assert_eq!(
+        tags,
+        vec!["git", "vcs"],
+        "tags are normalized and deduplicated"
+    );
+    assert_eq!(version.as_deref(), Some("1.2.0"));
+}
+
 #[crdb_test_macro::crdb_test(migrations = "./migrations")]
 async fn publish_filter_rate_limits_user(pool: PgPool) {
     let (_, token) = insert_test_user(&pool, "alice_rl").await;
@@ -295,6 +329,7 @@ async fn publish_filter_rate_limits_user(pool: PgPool) {
         trust_proxy: false,
         public_url: "https://registry.tokf.net".to_string(),
         terms_url: "https://registry.tokf.net/terms".to_string(),
+        webhooks_allow_private: false,
         publish_rate_limiter: Arc::new(PublishRateLimiter::new(1, 3600)),
         search_rate_limiter: Arc::new(PublishRateLimiter::new(1000, 3600)),
         sync_rate_limiter: Arc::new(SyncRateLimiter::new(100, 3600)),
@@ -1,11 +1,14 @@
 use axum::{
     Json,
     extract::{Path, Query, State},
-    http::HeaderMap,
+    http::{HeaderMap, HeaderValue, StatusCode, header::ETAG},
+    response::{IntoResponse, Response},
 };
 use serde::{Deserialize, Serialize};
 use sqlx::Row as _;
 
+use super::orgs::visible_to;
+use super::search_query::{self, NEXT_CURSOR_HEADER, SortOrder};
 use crate::auth::token::AuthUser;
 use crate::error::AppError;
 use crate::state::AppState;
@@ -18,6 +21,22 @@ pub struct SearchParams {
     pub q: String,
     #[serde(default = "default_limit")]
     pub limit: i64,
+    #[serde(default)]
+    pub sort: SortOrder,
+    /// `x-next-cursor` from the previous page.
+    pub cursor: Option<String>,
+    /// Only filters by this author (case-insensitive).
+    pub author: Option<String>,
+    /// Only standard-library filters (`true`) or only community ones (`false`).
+    pub stdlib: Option<bool>,
+    /// Only filters for this command: `command=git` matches `git` and every
+    /// `git <subcommand>` pattern.
+    pub command: Option<String>,
+    /// Include yanked filters, which are hidden by default.
+    #[serde(default)]
+    pub include_yanked: bool,
+    /// Only filters published under this GitHub org (case-insensitive).
+    pub org: Option<String>,
 }
 
 const fn default_limit() -> i64 {
@@ -36,11 +55,32 @@ pub struct FilterSummary {
     /// ISO 8601 timestamp when the filter was first published. P3.2.
     pub created_at: String,
     pub test_count: i64,
+    pub downloads: i64,
+    pub stars: i64,
     pub is_stdlib: bool,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub introduced_at: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub deprecated_at: Option<String>,
+    /// Suggested replacement for a deprecated filter.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub successor_hash: Option<String>,
+    /// When the filter was yanked; yanked filters stay downloadable by hash.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub yanked_at: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub yank_reason: Option<String>,
+    /// GitHub org the filter is published under.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub org: Option<String>,
+    /// `public`, or `private` for a filter only its author and org can see.
+    pub visibility: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
 }
 
 #[derive(Debug, Serialize)]
@@ -52,12 +92,34 @@ pub struct FilterDetails {
     pub total_commands: i64,
     pub created_at: String,
     pub test_count: i64,
+    /// Downloads, including `304 Not Modified` revalidations.
+    pub downloads: i64,
+    pub stars: i64,
     pub registry_url: String,
     pub is_stdlib: bool,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub introduced_at: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub deprecated_at: Option<String>,
+    /// Suggested replacement for a deprecated filter.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub successor_hash: Option<String>,
+    /// When the filter was yanked; yanked filters stay downloadable by hash.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub yanked_at: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub yank_reason: Option<String>,
+    /// GitHub org the filter is published under.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub org: Option<String>,
+    /// `public`, or `private` for a filter only its author and org can see.
+    pub visibility: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
 }
 
 #[derive(Debug, Serialize)]
@@ -88,6 +150,13 @@ pub struct DownloadPayload {
     /// download; the client falls back to `content_hash`.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub v1_hash: Option<String>,
+    /// Author's Ed25519 signature over `filter_toml` (hex); omitted for
+    /// unsigned filters.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub signature: Option<String>,
+    /// Public key `signature` was made with (hex).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub signing_key: Option<String>,
 }
 
 // ── Helpers ───────────────────────────────────────────────────────────────────
@@ -104,9 +173,13 @@ fn filename_from_r2_key(r2_key: &str) -> String {
 
 /// SQL fragment: correlated subquery that counts tests for a given filter hash.
 /// Used by both `search_filters` and `get_filter` to avoid duplication.
-const TEST_COUNT_SUBQUERY: &str =
+pub(super) const TEST_COUNT_SUBQUERY: &str =
     "(SELECT COUNT(*)::BIGINT FROM filter_tests WHERE filter_hash = f.content_hash) AS test_count";
 
+/// SQL fragment: correlated subquery that counts a filter's stars.
+pub(super) const STAR_COUNT_SUBQUERY: &str =
+    "(SELECT COUNT(*)::BIGINT FROM filter_stars s WHERE s.filter_hash = f.content_hash) AS stars";
+
 /// Escape `\`, `%`, and `_` for use in a SQL ILIKE pattern.
 ///
 /// Without escaping, user-supplied `%` or `_` characters would act as ILIKE
@@ -114,7 +187,7 @@ const TEST_COUNT_SUBQUERY: &str =
 /// respectively. Backslashes must be escaped first because the query uses
 /// `ESCAPE '\\'` — an unescaped `\` would modify the interpretation of the
 /// next character and produce unexpected matches.
-fn escape_ilike(s: &str) -> String {
+pub(super) fn escape_ilike(s: &str) -> String {
     s.replace('\\', "\\\\")
         .replace('%', "\\%")
         .replace('_', "\\_")
@@ -124,18 +197,21 @@ fn escape_ilike(s: &str) -> String {
 
 /// Search the community filter registry.
 ///
-/// Returns filters sorted by a relevance score combining savings percentage
-/// and usage volume. Requires a valid bearer token.
+/// Matches `q` against command patterns (substring) and tags (exact,
+/// case-insensitive), narrowed by the optional `author`, `stdlib`, `command`
+/// and `org` filters. Private filters are only found by their author and
+/// members of their org. Results are ordered by `sort` — by default a relevance
+/// score combining savings percentage and usage volume. A full page carries
+/// an `x-next-cursor` header; passing it back as `cursor` returns the next
+/// page. Requires a valid bearer token.
 ///
 /// # Errors
 ///
-/// - `400 Bad Request` if the query string exceeds 200 characters.
+/// - `400 Bad Request` if the query string exceeds 200 characters, a filter
+///   value is too long, or the cursor is invalid.
 /// - `401 Unauthorized` if the bearer token is missing or invalid.
 /// - `429 Too Many Requests` if the caller exceeds the search rate limit.
 /// - `500 Internal Server Error` on database failures.
-// Exceeds the 60-line guideline due to the test_count correlated subquery and
-// associated query-building logic.
-#[allow(clippy::too_many_lines)]
 pub async fn search_filters(
     auth: AuthUser,
     crate::routes::ip::PeerIp(peer_ip): crate::routes::ip::PeerIp,
@@ -149,6 +225,15 @@ pub async fn search_filters(
             "search query must not exceed 200 characters".to_string(),
         ));
     }
+    let too_long = |v: &Option<String>| v.as_ref().is_some_and(|v| v.len() > 200);
+    if too_long(&params.author) || too_long(&params.command) || too_long(&params.org) {
+        return Err(AppError::BadRequest(
+            "search filters must not exceed 200 characters".to_string(),
+        ));
+    }
+    if params.cursor.as_ref().is_some_and(|c| c.len() > 512) {
+        return Err(AppError::BadRequest("invalid cursor".to_string()));
+    }
 
     // Per-IP rate limit (60/min).
     let ip = crate::routes::ip::extract_ip(&headers, state.trust_proxy, peer_ip.as_deref());
@@ -165,63 +250,17 @@ pub async fn search_filters(
 
     let rl = crate::routes::ip::most_restrictive(ip_rl, user_rl);
 
-    let limit = clamp_limit(params.limit);
-    // P1.1: Escape ILIKE wildcards in user-supplied query to prevent wildcard injection.
-    let pattern = if params.q.is_empty() {
-        "%".to_string()
-    } else {
-        format!("%{}%", escape_ilike(&params.q))
-    };
-
-    let sql = format!(
-        "SELECT f.content_hash, f.command_pattern,
-                CASE WHEN u.visible THEN u.username ELSE 'tokf' END AS author,
-                COALESCE(fs.savings_pct, 0.0) AS savings_pct,
-                COALESCE(fs.total_commands, 0) AS total_commands,
-                f.created_at::TEXT AS created_at,
-                {TEST_COUNT_SUBQUERY},
-                f.is_stdlib,
-                f.introduced_at,
-                f.deprecated_at
-         FROM filters f
-         JOIN users u ON u.id = f.author_id
-         LEFT JOIN filter_stats fs ON fs.filter_hash = f.content_hash
-         WHERE f.command_pattern ILIKE $1 ESCAPE '\\'
-         ORDER BY COALESCE(fs.savings_pct, 0.0)
-                  * (1.0 + LN(CAST(COALESCE(fs.total_commands, 0) + 1 AS FLOAT8))) DESC,
-                  f.created_at DESC
-         LIMIT $2"
-    );
-    // SQL-safe: the only interpolation is the `TEST_COUNT_SUBQUERY` constant;
-    // all user input is bound via `.bind()`.
-    let rows = sqlx::query(sqlx::AssertSqlSafe(sql))
-        .bind(&pattern)
-        .bind(limit)
-        .fetch_all(&state.db)
-        .await?;
-
-    // Propagate DB mapping errors for all columns — COALESCE ensures they are
-    // non-null so unwrap_or would only hide real schema/type mismatches.
-    let summaries: Vec<FilterSummary> = rows
-        .iter()
-        .map(|row| -> Result<FilterSummary, sqlx::Error> {
-            Ok(FilterSummary {
-                content_hash: row.try_get("content_hash")?,
-                command_pattern: row.try_get("command_pattern")?,
-                author: row.try_get("author")?,
-                savings_pct: row.try_get("savings_pct")?,
-                total_commands: row.try_get("total_commands")?,
-                created_at: row.try_get("created_at")?,
-                test_count: row.try_get("test_count")?,
-                is_stdlib: row.try_get("is_stdlib")?,
-                introduced_at: row.try_get("introduced_at")?,
-                deprecated_at: row.try_get("deprecated_at")?,
-            })
-        })
-        .collect::<Result<Vec<_>, _>>()
-        .map_err(|e| AppError::Internal(format!("db mapping error: {e}")))?;
-
-    Ok((crate::routes::ip::rate_limit_headers(&rl), Json(summaries)))
+    let page =
+        search_query::fetch_page(&state.db, &params, clamp_limit(params.limit), auth.user_id)
+            .await?;
+    let mut response_headers = crate::routes::ip::rate_limit_headers(&rl);
+    if let Some(value) = page
+        .next_cursor
+        .and_then(|c| HeaderValue::from_str(&c).ok())
+    {
+        response_headers.insert(NEXT_CURSOR_HEADER, value);
+    }
+    Ok((response_headers, Json(page.filters)))
 }
 
 // ── GET /api/filters/:hash ────────────────────────────────────────────────────
@@ -231,8 +270,11 @@ pub async fn search_filters(
 /// # Errors
 ///
 /// - `401 Unauthorized` if the bearer token is missing or invalid.
-/// - `404 Not Found` if no filter with the given hash exists.
+/// - `404 Not Found` if no filter with the given hash exists or it is
+///   private and the caller may not see it.
 /// - `500 Internal Server Error` on database failures.
+// Over the 60-line guideline due to per-IP + per-user rate-limit checks.
+#[allow(clippy::too_many_lines)]
 pub async fn get_filter(
     auth: AuthUser,
     crate::routes::ip::PeerIp(peer_ip): crate::routes::ip::PeerIp,
@@ -257,18 +299,30 @@ pub async fn get_filter(
                 COALESCE(fs.total_commands, 0) AS total_commands,
                 f.created_at::TEXT AS created_at,
                 {TEST_COUNT_SUBQUERY},
+                f.download_count AS downloads,
+                {STAR_COUNT_SUBQUERY},
                 f.is_stdlib,
                 f.introduced_at,
-                f.deprecated_at
+                f.deprecated_at,
+                f.successor_hash,
+                f.yanked_at::TEXT AS yanked_at,
+                f.yank_reason,
+                f.org,
+                f.visibility,
+                f.description,
+                f.tags,
+                f.version
          FROM filters f
          JOIN users u ON u.id = f.author_id
          LEFT JOIN filter_stats fs ON fs.filter_hash = f.content_hash
-         WHERE f.content_hash = $1"
+         WHERE f.content_hash = $1 AND {}",
+        visible_to("$2")
     );
-    // SQL-safe: the only interpolation is the `TEST_COUNT_SUBQUERY` constant;
-    // the user-supplied hash is bound via `.bind()`.
+    // SQL-safe: the only interpolations are the `TEST_COUNT_SUBQUERY`,
+    // `STAR_COUNT_SUBQUERY` and `visible_to` constants; the user-supplied hash is bound via `.bind()`.
     let row = sqlx::query(sqlx::AssertSqlSafe(sql))
         .bind(&hash)
+        .bind(auth.user_id)
         .fetch_optional(&state.db)
         .await?
         .ok_or_else(|| AppError::NotFound(format!("filter not found: {hash}")))?;
@@ -286,10 +340,20 @@ pub async fn get_filter(
             total_commands: row.try_get("total_commands")?,
             created_at: row.try_get("created_at")?,
             test_count: row.try_get("test_count")?,
+            downloads: row.try_get("downloads")?,
+            stars: row.try_get("stars")?,
             registry_url,
             is_stdlib: row.try_get("is_stdlib")?,
             introduced_at: row.try_get("introduced_at")?,
             deprecated_at: row.try_get("deprecated_at")?,
+            successor_hash: row.try_get("successor_hash")?,
+            yanked_at: row.try_get("yanked_at")?,
+            yank_reason: row.try_get("yank_reason")?,
+            org: row.try_get("org")?,
+            visibility: row.try_get("visibility")?,
+            description: row.try_get("description")?,
+            tags: row.try_get("tags")?,
+            version: row.try_get("version")?,
         })
     })()
     .map_err(|e| AppError::Internal(format!("db mapping error: {e}")))?;
@@ -301,12 +365,16 @@ pub async fn get_filter(
 
 /// Download a filter's TOML and test files by content hash.
 ///
+/// The response carries an `ETag`; a request whose `If-None-Match` matches it
+/// gets `304 Not Modified` without the filter being read from storage.
+///
 /// # Errors
 ///
 /// - `401 Unauthorized` if the bearer token is missing or invalid.
-/// - `404 Not Found` if no filter with the given hash exists.
+/// - `404 Not Found` if no filter with the given hash exists or it is
+///   private and the caller may not see it.
 /// - `500 Internal Server Error` on storage or database failures.
-// 8 lines over the 60-line guideline due to per-IP + per-user rate-limit checks.
+// Over the 60-line guideline due to per-IP + per-user rate-limit checks.
 #[allow(clippy::too_many_lines)]
 pub async fn download_filter(
     auth: AuthUser,
@@ -314,7 +382,7 @@ pub async fn download_filter(
     headers: HeaderMap,
     State(state): State<AppState>,
     Path(hash): Path<String>,
-) -> Result<(HeaderMap, Json<DownloadPayload>), AppError> {
+) -> Result<Response, AppError> {
     let ip = crate::routes::ip::extract_ip(&headers, state.trust_proxy, peer_ip.as_deref());
     let ip_rl = state.ip_download_rate_limiter.check_and_increment(ip);
     if !ip_rl.allowed {
@@ -325,13 +393,42 @@ pub async fn download_filter(
         return Err(AppError::rate_limited(&user_rl));
     }
     let rl = crate::routes::ip::most_restrictive(ip_rl, user_rl);
-    let r2_key: Option<String> =
-        sqlx::query_scalar("SELECT r2_key FROM filters WHERE content_hash = $1")
+    let sql = format!(
+        "SELECT f.r2_key, f.signature, f.signing_key FROM filters f
+         WHERE f.content_hash = $1 AND {}",
+        visible_to("$2")
+    );
+    // SQL-safe: the only interpolation is the `visible_to` fragment.
+    let row: Option<(String, Option<String>, Option<String>)> =
+        sqlx::query_as(sqlx::AssertSqlSafe(sql))
             .bind(&hash)
+            .bind(auth.user_id)
             .fetch_optional(&state.db)
             .await?;
 
-    let r2_key = r2_key.ok_or_else(|| AppError::NotFound(format!("filter not found: {hash}")))?;
+    let (r2_key, signature, signing_key) =
+        row.ok_or_else(|| AppError::NotFound(format!("filter not found: {hash}")))?;
+    // Counted before the `If-None-Match` check: a revalidated download is
+    // still an install.
+    sqlx::query("UPDATE filters SET download_count = download_count + 1 WHERE content_hash = $1")
+        .bind(&hash)
+        .execute(&state.db)
+        .await?;
+
+    let tests: Vec<(String, chrono::DateTime<chrono::Utc>)> = sqlx::query_as(
+        "SELECT r2_key, updated_at FROM filter_tests WHERE filter_hash = $1 ORDER BY r2_key",
+    )
+    .bind(&hash)
+    .fetch_all(&state.db)
+    .await?;
+    let etag = super::etag::download_etag(&hash, signature.as_deref(), &tests);
+    let mut response_headers = crate::routes::ip::rate_limit_headers(&rl);
+    if let Ok(value) = HeaderValue::from_str(&etag) {
+        response_headers.insert(ETAG, value);
+    }
+    if super::etag::if_none_match(&headers, &etag) {
+        return Ok((StatusCode::NOT_MODIFIED, response_headers).into_response());
+    }
 
     // P2.1: Log R2 key internally but return a generic message to the client.
     let filter_bytes = state
@@ -365,14 +462,8 @@ pub async fn download_filter(
     // back to `content_hash`. See #350.
     let v1_hash = compute_v1_best_effort(&filter_toml, &hash);
 
-    let test_keys: Vec<String> =
-        sqlx::query_scalar("SELECT r2_key FROM filter_tests WHERE filter_hash = $1")
-            .bind(&hash)
-            .fetch_all(&state.db)
-            .await?;
-
-    let mut test_files = Vec::with_capacity(test_keys.len());
-    for key in &test_keys {
+    let mut test_files = Vec::with_capacity(tests.len());
+    for (key, _) in &tests {
         let bytes = state
             .storage
             .get(key)
@@ -397,14 +488,17 @@ pub async fn download_filter(
     }
 
     Ok((
-        crate::routes::ip::rate_limit_headers(&rl),
+        response_headers,
         Json(DownloadPayload {
             filter_toml,
             test_files,
             content_hash,
             v1_hash,
+            signature,
+            signing_key,
         }),
-    ))
+    )
+        .into_response())
 }
 
 /// Parse `toml_str` into a [`FilterConfig`] and return its current
@@ -0,0 +1,301 @@
+//! Sorting, filtering and cursor pagination for `GET /api/filters`.
+//!
+//! Pages are walked with keyset pagination: every result row carries a sort
+//! key, and the cursor handed out with a full page records the key,
+//! `created_at` and hash of its last row. The next page continues strictly
+//! after that position, so rows published in the meantime never shift or
+//! repeat results the way an offset would.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{PgPool, Row as _};
+
+use super::orgs::visible_to;
+use super::search::{
+    FilterSummary, STAR_COUNT_SUBQUERY, SearchParams, TEST_COUNT_SUBQUERY, escape_ilike,
+};
+use crate::error::AppError;
+
+/// Response header carrying the cursor for the next page; absent on the
+/// last page.
+pub const NEXT_CURSOR_HEADER: &str = "x-next-cursor";
+
+/// How search results are ordered. Ties are broken by newest first.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SortOrder {
+    /// Savings percentage weighted by usage volume.
+    #[default]
+    Relevance,
+    /// Most downloaded first.
+    Downloads,
+    /// Most recently published first.
+    Recent,
+    /// Most starred first.
+    Stars,
+}
+
+impl SortOrder {
+    /// SQL expression for the sort key over the columns of the inner query
+    /// aliased `r`.
+    const fn key_sql(self) -> &'static str {
+        match self {
+            Self::Relevance => "r.savings_pct * (1.0 + LN(CAST(r.total_commands + 1 AS FLOAT8)))",
+            Self::Downloads => "r.downloads::FLOAT8",
+            Self::Recent => "0.0::FLOAT8",
+            Self::Stars => "r.stars::FLOAT8",
+        }
+    }
+}
+
+/// The position after the last row of a page.
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct Cursor {
+    sort: SortOrder,
+    key: f64,
+    /// `created_at` in microseconds since the epoch.
+    created_at: i64,
+    hash: String,
+}
+
+impl Cursor {
+    /// Opaque text form, safe to pass back in a query string.
+    pub fn encode(&self) -> String {
+        hex::encode(serde_json::to_vec(self).unwrap_or_default())
+    }
+
+    /// Parse a cursor handed out for a search sorted by `sort`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `400 Bad Request` if the cursor is malformed or belongs to a
+    /// different sort order.
+    pub fn decode(s: &str, sort: SortOrder) -> Result<Self, AppError> {
+        let cursor: Self = hex::decode(s)
+            .ok()
+            .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+            .ok_or_else(|| AppError::BadRequest("invalid cursor".to_string()))?;
+        if cursor.sort != sort {
+            return Err(AppError::BadRequest(
+                "cursor belongs to a different sort order".to_string(),
+            ));
+        }
+        Ok(cursor)
+    }
+
+    fn created_at(&self) -> Result<DateTime<Utc>, AppError> {
+        DateTime::from_timestamp_micros(self.created_at)
+            .ok_or_else(|| AppError::BadRequest("invalid cursor".to_string()))
+    }
+}
+
+/// One page of search results.
+pub struct Page {
+    pub filters: Vec<FilterSummary>,
+    pub next_cursor: Option<String>,
+}
+
+/// Run the search described by `params`, returning up to `limit` rows.
+/// Only filters `viewer` may see are returned.
+///
+/// # Errors
+///
+/// Returns `400 Bad Request` for an invalid cursor and propagates database
+/// failures.
+pub async fn fetch_page(
+    db: &PgPool,
+    params: &SearchParams,
+    limit: i64,
+    viewer: i64,
+) -> Result<Page, AppError> {
+    let cursor = params
+        .cursor
+        .as_deref()
+        .map(|c| Cursor::decode(c, params.sort))
+        .transpose()?;
+    // P1.1: Escape ILIKE wildcards in user-supplied query to prevent wildcard injection.
+    let pattern = if params.q.is_empty() {
+        "%".to_string()
+    } else {
+        format!("%{}%", escape_ilike(&params.q))
+    };
+    // Tags are stored lowercased at publish time, so an exact match on the
+    // lowercased query finds filters whose pattern does not mention it.
+    let tag = params.q.trim().to_lowercase();
+    // `command=git` matches `git` itself and every `git <subcommand>`.
+    let command_prefix = params
+        .command
+        .as_deref()
+        .map(|c| format!("{} %", escape_ilike(c)));
+
+    let sql = page_sql(params.sort);
+    // SQL-safe: the only interpolations are constants; all user input is
+    // bound via `.bind()`.
+    let rows = sqlx::query(sqlx::AssertSqlSafe(sql))
+        .bind(&pattern)
+        .bind(limit)
+        .bind(&tag)
+        .bind(params.author.as_deref())
+        .bind(params.stdlib)
+        .bind(params.command.as_deref())
+        .bind(command_prefix)
+        .bind(cursor.as_ref().map(|c| c.key))
+        .bind(cursor.as_ref().map(Cursor::created_at).transpose()?)
+        .bind(cursor.as_ref().map(|c| c.hash.as_str()))
+        .bind(params.include_yanked)
+        .bind(viewer)
+        .bind(params.org.as_deref())
+        .fetch_all(db)
+        .await?;
+
+    let filters = rows
+        .iter()
+        .map(summary_from_row)
+        .collect::<Result<Vec<_>, _>>()?;
+    let next_cursor = match rows.last() {
+        Some(last) if i64::try_from(rows.len()) == Ok(limit) => {
+            Some(cursor_after(last, params.sort)?.encode())
+        }
+        _ => None,
+    };
+    Ok(Page {
+        filters,
+        next_cursor,
+    })
+}
+
+fn cursor_after(row: &sqlx::postgres::PgRow, sort: SortOrder) -> Result<Cursor, AppError> {
+    let created_at: DateTime<Utc> = row.try_get("created_ts").map_err(|e| mapping_error(&e))?;
+    Ok(Cursor {
+        sort,
+        key: row.try_get("sort_key").map_err(|e| mapping_error(&e))?,
+        created_at: created_at.timestamp_micros(),
+        hash: row.try_get("content_hash").map_err(|e| mapping_error(&e))?,
+    })
+}
+
+fn page_sql(sort: SortOrder) -> String {
+    let key = sort.key_sql();
+    let visible = visible_to("$12");
+    format!(
+        "SELECT r.*, {key} AS sort_key FROM (
+             SELECT f.content_hash, f.command_pattern,
+                    CASE WHEN u.visible THEN u.username ELSE 'tokf' END AS author,
+                    COALESCE(fs.savings_pct, 0.0) AS savings_pct,
+                    COALESCE(fs.total_commands, 0) AS total_commands,
+                    f.created_at::TEXT AS created_at,
+                    f.created_at AS created_ts,
+                    {TEST_COUNT_SUBQUERY},
+                    f.download_count AS downloads,
+                    {STAR_COUNT_SUBQUERY},
+                    f.is_stdlib,
+                    f.introduced_at,
+                    f.deprecated_at,
+                    f.successor_hash,
+                    f.yanked_at::TEXT AS yanked_at,
+                    f.yank_reason,
+                    f.org,
+                    f.visibility,
+                    f.description,
+                    f.tags,
+                    f.version
+             FROM filters f
+             JOIN users u ON u.id = f.author_id
+             LEFT JOIN filter_stats fs ON fs.filter_hash = f.content_hash
+             WHERE (f.command_pattern ILIKE $1 ESCAPE '\\' OR $3 = ANY(f.tags))
+               AND ($4::TEXT IS NULL
+                    OR LOWER(CASE WHEN u.visible THEN u.username ELSE 'tokf' END) = LOWER($4))
+               AND ($5::BOOL IS NULL OR f.is_stdlib = $5)
+               AND ($6::TEXT IS NULL OR f.command_pattern = $6
+                    OR f.command_pattern LIKE $7 ESCAPE '\\')
+               AND ($11::BOOL OR f.yanked_at IS NULL)
+               AND ($13::TEXT IS NULL OR LOWER(f.org) = LOWER($13))
+               AND {visible}
+         ) AS r
+         WHERE $8::FLOAT8 IS NULL
+            OR ({key}, r.created_ts, r.content_hash) < ($8, $9::TIMESTAMPTZ, $10::TEXT)
+         ORDER BY sort_key DESC, r.created_ts DESC, r.content_hash DESC
+         LIMIT $2"
+    )
+}
+
+fn mapping_error(e: &sqlx::Error) -> AppError {
+    AppError::Internal(format!("db mapping error: {e}"))
+}
+
+// Propagate DB mapping errors for all columns — COALESCE ensures they are
+// non-null so unwrap_or would only hide real schema/type mismatches.
+fn summary_from_row(row: &sqlx::postgres::PgRow) -> Result<FilterSummary, AppError> {
+    (|| -> Result<FilterSummary, sqlx::Error> {
+        Ok(FilterSummary {
+            content_hash: row.try_get("content_hash")?,
+            command_pattern: row.try_get("command_pattern")?,
+            author: row.try_get("author")?,
+            savings_pct: row.try_get("savings_pct")?,
+            total_commands: row.try_get("total_commands")?,
+            created_at: row.try_get("created_at")?,
+            test_count: row.try_get("test_count")?,
+            downloads: row.try_get("downloads")?,
+            stars: row.try_get("stars")?,
+            is_stdlib: row.try_get("is_stdlib")?,
+            introduced_at: row.try_get("introduced_at")?,
+            deprecated_at: row.try_get("deprecated_at")?,
+            successor_hash: row.try_get("successor_hash")?,
+            yanked_at: row.try_get("yanked_at")?,
+            yank_reason: row.try_get("yank_reason")?,
+            org: row.try_get("org")?,
+            visibility: row.try_get("visibility")?,
+            description: row.try_get("description")?,
+            tags: row.try_get("tags")?,
+            version: row.try_get("version")?,
+        })
+    })()
+    .map_err(|e| mapping_error(&e))
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    fn cursor(sort: SortOrder) -> Cursor {
+        Cursor {
+            sort,
+            key: 12.345_678_901_234_567,
+            created_at: 1_760_000_000_123_456,
+            hash: "abc".to_string(),
+        }
+    }
+
+    #[test]
+    fn cursor_roundtrips_exactly() {
+        let c = cursor(SortOrder::Relevance);
+        let decoded = Cursor::decode(&c.encode(), SortOrder::Relevance).unwrap();
+        assert_eq!(decoded, c);
+        assert_eq!(
+            decoded.created_at().unwrap().timestamp_micros(),
+            c.created_at
+        );
+    }
+
+    #[test]
+    fn cursor_is_tied_to_its_sort_order() {
+        let encoded = cursor(SortOrder::Downloads).encode();
+        assert!(Cursor::decode(&encoded, SortOrder::Stars).is_err());
+        assert!(Cursor::decode("not-hex", SortOrder::Downloads).is_err());
+        assert!(Cursor::decode("7b7d", SortOrder::Downloads).is_err());
+    }
+
+    #[test]
+    fn sort_orders_parse_from_query_strings() {
+        for (name, sort) in [
+            ("relevance", SortOrder::Relevance),
+            ("downloads", SortOrder::Downloads),
+            ("recent", SortOrder::Recent),
+            ("stars", SortOrder::Stars),
+        ] {
+            let parsed: SortOrder = serde_json::from_value(serde_json::json!(name)).unwrap();
+            assert_eq!(parsed, sort);
+        }
+    }
+}
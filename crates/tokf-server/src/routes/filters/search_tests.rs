@@ -268,6 +268,45 @@ async fn download_returns_test_files(pool: PgPool) {
     assert!(filenames.contains("edge.toml"), "expected edge.toml");
 }
 
+#[crdb_test_macro::crdb_test(migrations = "./migrations")]
+async fn download_honours_if_none_match(pool: PgPool) {
+    use tower::ServiceExt as _;
+
+    let (_, token) = insert_test_user(&pool, "dl_etag").await;
+    let storage = Arc::new(InMemoryStorageClient::new());
+    let app =
+        crate::routes::create_router(make_state_with_storage(pool.clone(), Arc::clone(&storage)));
+    let hash = publish_filter_helper(app, &token, b"command = \"git push\"\n", &[]).await;
+    let uri = format!("/api/filters/{hash}/download");
+
+    let app =
+        crate::routes::create_router(make_state_with_storage(pool.clone(), Arc::clone(&storage)));
+    let resp = get_request(app, &token, &uri).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    let etag = resp.headers()["etag"].to_str().unwrap().to_string();
+
+    let conditional = |etag: &str| {
+        axum::http::Request::builder()
+            .uri(&uri)
+            .header("authorization", format!("Bearer {token}"))
+            .header("if-none-match", etag)
+            .body(axum::body::Body::empty())
+            .unwrap()
+    };
+    let app =
+        crate::routes::create_router(make_state_with_storage(pool.clone(), Arc::clone(&storage)));
+    let resp = app.oneshot(conditional(&etag)).await.unwrap();
+    assert_eq!(resp.status(), StatusCode::NOT_MODIFIED);
+    assert_eq!(resp.headers()["etag"].to_str().unwrap(), etag);
+    let body = resp.into_body().collect().await.unwrap().to_bytes();
+    assert!(body.is_empty(), "304 must not carry a body");
+
+    let app =
+        crate::routes::create_router(make_state_with_storage(pool.clone(), Arc::clone(&storage)));
+    let resp = app.oneshot(conditional("\"stale\"")).await.unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+}
+
 #[crdb_test_macro::crdb_test(migrations = "./migrations")]
 async fn download_returns_404_for_unknown_hash(pool: PgPool) {
     assert_unknown_hash_returns_404(pool, "dl_404", "/download").await;
@@ -379,3 +418,173 @@ async fn search_wildcard_injection_does_not_return_all(pool: PgPool) {
         "escaped underscore should match nothing (no underscore in 'cargo build')"
     );
 }
+
+#[crdb_test_macro::crdb_test(migrations = "./migrations")]
+async fn search_matches_tags(pool: PgPool) {
+    let results = publish_and_search(
+        pool,
+        "search_tags",
+        &[
+            b"command = \"cargo build\"\ntags = [\"Rust\", \"build\"]\nversion = \"0.3.0\"\n",
+            b"command = \"npm run build\"\ntags = [\"node\"]\n",
+        ],
+        "/api/filters?q=rust",
+    )
+    .await;
+    assert_eq!(
+        results.len(),
+        1,
+        "tag query should match only the tagged filter"
+    );
+    assert_eq!(results[0]["command_pattern"], "cargo build");
+    assert_eq!(results[0]["tags"], serde_json::json!(["rust", "build"]));
+    assert_eq!(results[0]["version"], "0.3.0");
+}
+
+async fn search_page(
+    pool: &PgPool,
+    token: &str,
+    uri: &str,
+) -> (Vec<serde_json::Value>, Option<String>) {
+    let app = crate::routes::create_router(make_state(pool.clone()));
+    let resp = get_request(app, token, uri).await;
+    assert_eq!(resp.status(), StatusCode::OK, "GET {uri}");
+    let cursor = resp
+        .headers()
+        .get("x-next-cursor")
+        .map(|v| v.to_str().unwrap().to_string());
+    let body = resp.into_body().collect().await.unwrap().to_bytes();
+    (serde_json::from_slice(&body).unwrap(), cursor)
+}
+
+async fn send(pool: &PgPool, method: &str, token: &str, uri: &str) -> StatusCode {
+    use tower::ServiceExt as _;
+    let app = crate::routes::create_router(make_state(pool.clone()));
+    app.oneshot(
+        axum::http::Request::builder()
+            .method(method)
+            .uri(uri)
+            .header("authorization", format!("Bearer {token}"))
+            .body(axum::body::Body::empty())
+            .unwrap(),
+    )
+    .await
+    .unwrap()
+    .status()
+}
+
+#[crdb_test_macro::crdb_test(migrations = "./migrations")]
+async fn search_pages_through_results_with_a_cursor(pool: PgPool) {
+    let tomls: &[&[u8]] = &[
+        b"command = \"git push\"\n",
+        b"command = \"git pull\"\n",
+        b"command = \"git fetch\"\n",
+    ];
+    publish_and_search(pool.clone(), "search_pages", tomls, "/api/filters").await;
+    let (_, token) = insert_test_user(&pool, "search_pages_reader").await;
+
+    let (first, cursor) = search_page(&pool, &token, "/api/filters?sort=recent&limit=2").await;
+    assert_eq!(first.len(), 2);
+    let cursor = cursor.expect("a full page should carry a cursor");
+    let (second, cursor) = search_page(
+        &pool,
+        &token,
+        &format!("/api/filters?sort=recent&limit=2&cursor={cursor}"),
+    )
+    .await;
+    assert_eq!(second.len(), 1);
+    assert!(cursor.is_none(), "the last page should not carry a cursor");
+
+    let mut seen: Vec<_> = first
+        .iter()
+        .chain(&second)
+        .map(|f| f["command_pattern"].as_str().unwrap().to_string())
+        .collect();
+    seen.sort();
+    assert_eq!(seen, ["git fetch", "git pull", "git push"]);
+
+    // A cursor only continues the sort order it was issued for.
+    let (_, cursor) = search_page(&pool, &token, "/api/filters?sort=recent&limit=1").await;
+    let app = crate::routes::create_router(make_state(pool.clone()));
+    let uri = format!("/api/filters?sort=stars&cursor={}", cursor.unwrap());
+    let resp = get_request(app, &token, &uri).await;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+}
+
+#[crdb_test_macro::crdb_test(migrations = "./migrations")]
+async fn search_sorts_by_stars_and_downloads(pool: PgPool) {
+    let (_, token) = insert_test_user(&pool, "search_sorts").await;
+    let storage = Arc::new(InMemoryStorageClient::new());
+    let mut hashes = Vec::new();
+    for toml in [b"command = \"make\"\n".as_slice(), b"command = \"ninja\"\n"] {
+        let app = crate::routes::create_router(make_state_with_storage(
+            pool.clone(),
+            Arc::clone(&storage),
+        ));
+        hashes.push(publish_filter_helper(app, &token, toml, &[]).await);
+    }
+    let (make, ninja) = (&hashes[0], &hashes[1]);
+
+    let star = format!("/api/filters/{ninja}/star");
+    assert_eq!(
+        send(&pool, "PUT", &token, &star).await,
+        StatusCode::NO_CONTENT
+    );
+    assert_eq!(
+        send(&pool, "PUT", &token, &star).await,
+        StatusCode::NO_CONTENT
+    );
+    let missing = "/api/filters/deadbeef/star";
+    assert_eq!(
+        send(&pool, "PUT", &token, missing).await,
+        StatusCode::NOT_FOUND
+    );
+
+    let app =
+        crate::routes::create_router(make_state_with_storage(pool.clone(), Arc::clone(&storage)));
+    let resp = get_request(app, &token, &format!("/api/filters/{make}/download")).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+
+    let (by_stars, _) = search_page(&pool, &token, "/api/filters?sort=stars").await;
+    assert_eq!(by_stars[0]["command_pattern"], "ninja");
+    assert_eq!(by_stars[0]["stars"], 1);
+    let (by_downloads, _) = search_page(&pool, &token, "/api/filters?sort=downloads").await;
+    assert_eq!(by_downloads[0]["command_pattern"], "make");
+    assert_eq!(by_downloads[0]["downloads"], 1);
+
+    assert_eq!(
+        send(&pool, "DELETE", &token, &star).await,
+        StatusCode::NO_CONTENT
+    );
+    let (by_stars, _) = search_page(&pool, &token, "/api/filters?sort=stars").await;
+    assert!(by_stars.iter().all(|f| f["stars"] == 0));
+}
+
+#[crdb_test_macro::crdb_test(migrations = "./migrations")]
+async fn search_filters_by_command_author_and_stdlib(pool: PgPool) {
+    let tomls: &[&[u8]] = &[
+        b"command = \"git\"\n",
+        b"command = \"git push\"\n",
+        b"command = \"gitk\"\n",
+    ];
+    publish_and_search(pool.clone(), "search_narrow", tomls, "/api/filters").await;
+    let (_, token) = insert_test_user(&pool, "search_narrow_reader").await;
+
+    let (results, _) = search_page(&pool, &token, "/api/filters?command=git").await;
+    let mut commands: Vec<_> = results
+        .iter()
+        .map(|f| f["command_pattern"].as_str().unwrap())
+        .collect();
+    commands.sort_unstable();
+    assert_eq!(commands, ["git", "git push"]);
+
+    let (results, _) = search_page(&pool, &token, "/api/filters?author=SEARCH_NARROW").await;
+    assert_eq!(results.len(), 3, "author match is case-insensitive");
+    let (results, _) = search_page(&pool, &token, "/api/filters?author=someone-else").await;
+    assert!(results.is_empty());
+
+    let (results, _) = search_page(&pool, &token, "/api/filters?stdlib=true").await;
+    assert!(results.is_empty());
+    let (results, _) = search_page(&pool, &token, "/api/filters?stdlib=false&q=gitk").await;
+    assert_eq!(results.len(), 1);
+}
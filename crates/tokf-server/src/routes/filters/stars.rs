@@ -0,0 +1,57 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+};
+
+use crate::auth::token::AuthUser;
+use crate::error::AppError;
+use crate::state::AppState;
+
+// ── PUT|POST /api/filters/:hash/star ─────────────────────────────────────────
+
+/// Star a filter for the authenticated user. Starring twice is a no-op.
+///
+/// # Errors
+///
+/// - `401 Unauthorized` if the bearer token is missing or invalid.
+/// - `404 Not Found` if no filter with the given hash exists or the caller
+///   may not see it.
+/// - `500 Internal Server Error` on database failures.
+pub async fn star_filter(
+    auth: AuthUser,
+    State(state): State<AppState>,
+    Path(hash): Path<String>,
+) -> Result<StatusCode, AppError> {
+    super::orgs::ensure_visible(&state.db, &hash, auth.user_id).await?;
+    sqlx::query(
+        "INSERT INTO filter_stars (user_id, filter_hash) VALUES ($1, $2)
+         ON CONFLICT (user_id, filter_hash) DO NOTHING",
+    )
+    .bind(auth.user_id)
+    .bind(&hash)
+    .execute(&state.db)
+    .await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+// ── DELETE /api/filters/:hash/star ───────────────────────────────────────────
+
+/// Remove the authenticated user's star from a filter. Unstarring a filter
+/// that is not starred is a no-op.
+///
+/// # Errors
+///
+/// - `401 Unauthorized` if the bearer token is missing or invalid.
+/// - `500 Internal Server Error` on database failures.
+pub async fn unstar_filter(
+    auth: AuthUser,
+    State(state): State<AppState>,
+    Path(hash): Path<String>,
+) -> Result<StatusCode, AppError> {
+    sqlx::query("DELETE FROM filter_stars WHERE user_id = $1 AND filter_hash = $2")
+        .bind(auth.user_id)
+        .bind(&hash)
+        .execute(&state.db)
+        .await?;
+    Ok(StatusCode::NO_CONTENT)
+}
@@ -0,0 +1,51 @@
+use std::sync::Arc;
+
+use axum::http::StatusCode;
+use http_body_util::BodyExt;
+
+use crate::storage::mock::InMemoryStorageClient;
+
+use super::test_helpers::{
+    get_request, insert_test_user, make_state_with_storage, publish_filter_helper, send_json,
+};
+
+async fn details(app: axum::Router, token: &str, hash: &str) -> serde_json::Value {
+    let resp = get_request(app, token, &format!("/api/filters/{hash}")).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    let body = resp.into_body().collect().await.unwrap().to_bytes();
+    serde_json::from_slice(&body).unwrap()
+}
+
+#[crdb_test_macro::crdb_test(migrations = "./migrations")]
+async fn filter_details_count_stars_and_downloads(pool: sqlx::PgPool) {
+    let storage = Arc::new(InMemoryStorageClient::new());
+    let app = || {
+        crate::routes::create_router(make_state_with_storage(pool.clone(), Arc::clone(&storage)))
+    };
+    let (_, alice) = insert_test_user(&pool, "stars_alice").await;
+    let (_, bob) = insert_test_user(&pool, "stars_bob").await;
+    let hash = publish_filter_helper(app(), &alice, b"command = \"pytest\"", &[]).await;
+
+    let fresh = details(app(), &alice, &hash).await;
+    assert_eq!(fresh["stars"], 0);
+    assert_eq!(fresh["downloads"], 0);
+
+    let star = format!("/api/filters/{hash}/star");
+    let empty = serde_json::json!({});
+    for (method, token) in [("POST", &alice), ("PUT", &bob), ("POST", &bob)] {
+        let resp = send_json(app(), method, token, &star, &empty).await;
+        assert_eq!(resp.status(), StatusCode::NO_CONTENT, "{method} star");
+    }
+    for token in [&alice, &bob] {
+        let resp = get_request(app(), token, &format!("/api/filters/{hash}/download")).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+    }
+
+    let counted = details(app(), &alice, &hash).await;
+    assert_eq!(counted["stars"], 2, "starring twice counts once");
+    assert_eq!(counted["downloads"], 2);
+
+    let resp = send_json(app(), "DELETE", &bob, &star, &empty).await;
+    assert_eq!(resp.status(), StatusCode::NO_CONTENT);
+    assert_eq!(details(app(), &alice, &hash).await["stars"], 1);
+}
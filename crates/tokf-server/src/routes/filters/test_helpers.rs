@@ -37,6 +37,7 @@ fn base_test_state(pool: PgPool) -> AppState {
         trust_proxy: false,
         public_url: "https://registry.tokf.net".to_string(),
         terms_url: "https://registry.tokf.net/terms".to_string(),
+        webhooks_allow_private: true,
         publish_rate_limiter: Arc::new(PublishRateLimiter::new(100, 3600)),
         search_rate_limiter: Arc::new(PublishRateLimiter::new(1000, 3600)),
         sync_rate_limiter: Arc::new(SyncRateLimiter::new(100, 3600)),
@@ -163,10 +164,21 @@ pub async fn post_json(
     token: &str,
     uri: &str,
     body: &serde_json::Value,
+) -> axum::response::Response {
+    send_json(app, "POST", token, uri, body).await
+}
+
+/// Send a JSON body with `method` to a URI with a bearer token.
+pub async fn send_json(
+    app: axum::Router,
+    method: &str,
+    token: &str,
+    uri: &str,
+    body: &serde_json::Value,
 ) -> axum::response::Response {
     app.oneshot(
         Request::builder()
-            .method("POST")
+            .method(method)
             .uri(uri)
             .header("authorization", format!("Bearer {token}"))
             .header("content-type", "application/json")
@@ -266,6 +266,8 @@ pub async fn update_tests(
 
     // Fire-and-forget: update R2 metadata (test_count changed) + catalog index.
     crate::catalog::spawn_catalog_update(state.db.clone(), state.storage.clone(), hash.clone());
+    let event = crate::webhooks::WebhookEvent::TestsUpdated;
+    crate::webhooks::spawn_dispatch(&state, event, &hash, None);
 
     let registry_url = format!("{}/filters/{}", state.public_url, hash);
     Ok((
@@ -185,7 +185,9 @@ async fn fetch_global_by_filter(pool: &PgPool) -> Result<Vec<FilterRow>, AppErro
                 COALESCE(SUM(output_tokens)::INT8, 0),
                 COALESCE(SUM(command_count)::INT8, 0),
                 COALESCE(SUM(raw_tokens)::INT8, 0)
-         FROM usage_events
+         FROM usage_events ue
+         WHERE NOT EXISTS (SELECT 1 FROM filters f
+                           WHERE f.content_hash = ue.filter_hash AND f.visibility <> 'public')
          GROUP BY filter_name, filter_hash
          ORDER BY COALESCE(SUM(input_tokens)::INT8, 0) DESC
          LIMIT 100",
@@ -299,7 +301,7 @@ pub async fn get_filter_gain(
                 fs.savings_pct, fs.total_raw_tokens, fs.last_updated
          FROM filter_stats fs
          LEFT JOIN filters f ON fs.filter_hash = f.content_hash
-         WHERE fs.filter_hash = $1",
+         WHERE fs.filter_hash = $1 AND COALESCE(f.visibility, 'public') = 'public'",
     )
     .bind(&hash)
     .fetch_optional(&state.db)
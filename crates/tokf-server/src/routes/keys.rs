@@ -0,0 +1,291 @@
+use axum::{
+    Json,
+    extract::{Path, State},
+    http::StatusCode,
+};
+use serde::{Deserialize, Serialize};
+use tokf_common::signing;
+
+use crate::auth::token::AuthUser;
+use crate::error::AppError;
+use crate::state::AppState;
+
+// ── Request / Response types ─────────────────────────────────────────────────
+
+#[derive(Debug, Deserialize)]
+pub struct RegisterKeyRequest {
+    /// Ed25519 public key, hex.
+    pub public_key: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SigningKeyResponse {
+    pub public_key: String,
+    pub fingerprint: String,
+    pub created_at: String,
+}
+
+// ── Internal helpers ──────────────────────────────────────────────────────────
+
+type KeyRow = (String, chrono::DateTime<chrono::Utc>);
+
+fn row_to_response((public_key, created_at): KeyRow) -> SigningKeyResponse {
+    SigningKeyResponse {
+        fingerprint: signing::fingerprint(&public_key),
+        public_key,
+        created_at: created_at.to_rfc3339(),
+    }
+}
+
+/// Maximum number of signing keys a single user may register.
+const MAX_KEYS_PER_USER: i64 = 20;
+
+/// Whether `public_key` is registered to `user_id`.
+pub async fn key_belongs_to(
+    db: &sqlx::PgPool,
+    public_key: &str,
+    user_id: i64,
+) -> Result<bool, AppError> {
+    let owner: Option<i64> =
+        sqlx::query_scalar("SELECT user_id FROM signing_keys WHERE public_key = $1")
+            .bind(public_key)
+            .fetch_optional(db)
+            .await?;
+    Ok(owner == Some(user_id))
+}
+
+// ── POST /api/keys ────────────────────────────────────────────────────────────
+
+/// Register a filter-signing public key for the authenticated user.
+///
+/// Keys are stored lowercased. Registering a key the user already has is a
+/// no-op that returns `200 OK`; a new key returns `201 Created`.
+///
+/// # Errors
+///
+/// - `400 Bad Request` if the key is not 32 bytes of hex.
+/// - `401 Unauthorized` if the bearer token is missing or invalid.
+/// - `409 Conflict` if the key is registered to another user.
+/// - `429 Too Many Requests` if the user has reached the key limit.
+/// - `500 Internal Server Error` on database failures.
+pub async fn register_key(
+    auth: AuthUser,
+    State(state): State<AppState>,
+    Json(body): Json<RegisterKeyRequest>,
+) -> Result<(StatusCode, Json<SigningKeyResponse>), AppError> {
+    let public_key = body.public_key.to_ascii_lowercase();
+    signing::parse_public_key(&public_key)
+        .map_err(|e| AppError::BadRequest(format!("public_key: {e}")))?;
+
+    let existing = sqlx::query_as::<_, (i64, chrono::DateTime<chrono::Utc>)>(
+        "SELECT user_id, created_at FROM signing_keys WHERE public_key = $1",
+    )
+    .bind(&public_key)
+    .fetch_optional(&state.db)
+    .await?;
+    match existing {
+        Some((owner, _)) if owner != auth.user_id => {
+            return Err(AppError::Conflict(
+                "signing key is already registered by another user".to_string(),
+            ));
+        }
+        Some((_, created_at)) => {
+            return Ok((
+                StatusCode::OK,
+                Json(row_to_response((public_key, created_at))),
+            ));
+        }
+        None => {}
+    }
+
+    let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM signing_keys WHERE user_id = $1")
+        .bind(auth.user_id)
+        .fetch_one(&state.db)
+        .await?;
+    if count >= MAX_KEYS_PER_USER {
+        // Constant is 20 — always fits in u32.
+        #[allow(clippy::cast_possible_truncation)]
+        return Err(AppError::RateLimited {
+            retry_after_secs: 0,
+            limit: MAX_KEYS_PER_USER as u32,
+            remaining: 0,
+        });
+    }
+
+    let row = sqlx::query_as::<_, KeyRow>(
+        "INSERT INTO signing_keys (public_key, user_id) VALUES ($1, $2)
+         RETURNING public_key, created_at",
+    )
+    .bind(&public_key)
+    .bind(auth.user_id)
+    .fetch_one(&state.db)
+    .await?;
+    Ok((StatusCode::CREATED, Json(row_to_response(row))))
+}
+
+// ── GET /api/keys ─────────────────────────────────────────────────────────────
+
+/// List the authenticated user's signing keys, newest first.
+///
+/// # Errors
+///
+/// - `401 Unauthorized` if the bearer token is missing or invalid.
+/// - `500 Internal Server Error` on database failures.
+pub async fn list_keys(
+    auth: AuthUser,
+    State(state): State<AppState>,
+) -> Result<Json<Vec<SigningKeyResponse>>, AppError> {
+    let rows = sqlx::query_as::<_, KeyRow>(
+        "SELECT public_key, created_at FROM signing_keys
+         WHERE user_id = $1
+         ORDER BY created_at DESC",
+    )
+    .bind(auth.user_id)
+    .fetch_all(&state.db)
+    .await?;
+    Ok(Json(rows.into_iter().map(row_to_response).collect()))
+}
+
+// ── DELETE /api/keys/{public_key} ─────────────────────────────────────────────
+
+/// Revoke one of the authenticated user's signing keys.
+///
+/// Filters already signed with it keep their signature; new publishes can
+/// no longer use it.
+///
+/// # Errors
+///
+/// - `401 Unauthorized` if the bearer token is missing or invalid.
+/// - `404 Not Found` if the user has no such key.
+/// - `500 Internal Server Error` on database failures.
+pub async fn revoke_key(
+    auth: AuthUser,
+    State(state): State<AppState>,
+    Path(public_key): Path<String>,
+) -> Result<StatusCode, AppError> {
+    let deleted = sqlx::query("DELETE FROM signing_keys WHERE public_key = $1 AND user_id = $2")
+        .bind(public_key.to_ascii_lowercase())
+        .bind(auth.user_id)
+        .execute(&state.db)
+        .await?;
+    if deleted.rows_affected() == 0 {
+        return Err(AppError::NotFound("signing key not found".to_string()));
+    }
+    Ok(StatusCode::NO_CONTENT)
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use axum::{
+        body::Body,
+        http::{Request, StatusCode},
+    };
+    use tower::ServiceExt;
+
+    use crate::routes::test_helpers::{assert_status, create_user_and_token, make_state};
+
+    fn request(
+        method: &str,
+        uri: &str,
+        token: &str,
+        body: Option<serde_json::Value>,
+    ) -> Request<Body> {
+        let builder = Request::builder()
+            .method(method)
+            .uri(uri)
+            .header("authorization", format!("Bearer {token}"));
+        match body {
+            Some(json) => builder
+                .header("content-type", "application/json")
+                .body(Body::from(serde_json::to_vec(&json).unwrap()))
+                .unwrap(),
+            None => builder.body(Body::empty()).unwrap(),
+        }
+    }
+
+    fn public_key() -> String {
+        tokf_common::signing::SigningKey::generate()
+            .unwrap()
+            .0
+            .public_key_hex()
+    }
+
+    #[crdb_test_macro::crdb_test(migrations = "./migrations")]
+    async fn register_list_and_revoke(pool: PgPool) {
+        let (_, token) = create_user_and_token(&pool).await;
+        let app = crate::routes::create_router(make_state(pool));
+        let key = public_key();
+        let body = Some(serde_json::json!({ "public_key": key }));
+
+        let resp = app
+            .clone()
+            .oneshot(request("POST", "/api/keys", &token, body.clone()))
+            .await
+            .unwrap();
+        assert_status(resp, StatusCode::CREATED).await;
+        let resp = app
+            .clone()
+            .oneshot(request("POST", "/api/keys", &token, body))
+            .await
+            .unwrap();
+        assert_status(resp, StatusCode::OK).await;
+
+        let resp = app
+            .clone()
+            .oneshot(request("GET", "/api/keys", &token, None))
+            .await
+            .unwrap();
+        let listed = assert_status(resp, StatusCode::OK).await;
+        let listed: serde_json::Value = serde_json::from_slice(&listed).unwrap();
+        assert_eq!(listed.as_array().unwrap().len(), 1);
+        assert_eq!(listed[0]["public_key"], key);
+
+        let uri = format!("/api/keys/{key}");
+        let resp = app
+            .clone()
+            .oneshot(request("DELETE", &uri, &token, None))
+            .await
+            .unwrap();
+        assert_status(resp, StatusCode::NO_CONTENT).await;
+        let resp = app
+            .oneshot(request("DELETE", &uri, &token, None))
+            .await
+            .unwrap();
+        assert_status(resp, StatusCode::NOT_FOUND).await;
+    }
+
+    #[crdb_test_macro::crdb_test(migrations = "./migrations")]
+    async fn another_users_key_conflicts(pool: PgPool) {
+        let (_, alice) = create_user_and_token(&pool).await;
+        let (_, bob) = create_user_and_token(&pool).await;
+        let app = crate::routes::create_router(make_state(pool));
+        let body = Some(serde_json::json!({ "public_key": public_key() }));
+
+        let resp = app
+            .clone()
+            .oneshot(request("POST", "/api/keys", &alice, body.clone()))
+            .await
+            .unwrap();
+        assert_status(resp, StatusCode::CREATED).await;
+        let resp = app
+            .oneshot(request("POST", "/api/keys", &bob, body))
+            .await
+            .unwrap();
+        assert_status(resp, StatusCode::CONFLICT).await;
+    }
+
+    #[crdb_test_macro::crdb_test(migrations = "./migrations")]
+    async fn malformed_key_is_rejected(pool: PgPool) {
+        let (_, token) = create_user_and_token(&pool).await;
+        let app = crate::routes::create_router(make_state(pool));
+        let body = Some(serde_json::json!({ "public_key": "abcd" }));
+        let resp = app
+            .oneshot(request("POST", "/api/keys", &token, body))
+            .await
+            .unwrap();
+        assert_status(resp, StatusCode::BAD_REQUEST).await;
+    }
+}
@@ -1,15 +1,20 @@
 mod account;
+mod admin;
 pub mod auth;
 mod catalog;
+mod docs;
 mod filters;
 mod gain;
 mod health;
 pub mod ip;
+mod keys;
 mod machines;
 mod middleware;
 mod ready;
 mod sync;
+mod tokens;
 mod tos;
+mod webhooks;
 
 #[cfg(any(test, feature = "test-helpers"))]
 pub mod test_helpers;
@@ -23,10 +28,14 @@ use axum::{
 use crate::state::AppState;
 use middleware::general_rate_limit;
 
+// A flat route table reads best as one list, even past the 60-line guideline.
+#[allow(clippy::too_many_lines)]
 pub fn create_router(state: AppState) -> Router {
     Router::new()
         .route("/health", get(health::health))
         .route("/ready", get(ready::ready))
+        .route("/api/openapi.json", get(docs::get_openapi))
+        .route("/api/docs", get(docs::get_docs))
         .route("/api/auth/device", post(auth::initiate_device_flow))
         .route("/api/auth/token", post(auth::poll_token))
         .route(
@@ -43,6 +52,20 @@ pub fn create_router(state: AppState) -> Router {
             get(filters::download_filter),
         )
         .route("/api/filters/{hash}/tests", put(filters::update_tests))
+        .route(
+            "/api/filters/{hash}/yank",
+            put(filters::yank_filter).delete(filters::unyank_filter),
+        )
+        .route(
+            "/api/filters/{hash}/deprecation",
+            put(filters::deprecate_filter).delete(filters::undeprecate_filter),
+        )
+        .route(
+            "/api/filters/{hash}/star",
+            put(filters::star_filter)
+                .post(filters::star_filter)
+                .delete(filters::unstar_filter),
+        )
         .route(
             "/api/filters/regenerate-examples",
             post(filters::regenerate_examples),
@@ -59,7 +82,20 @@ pub fn create_router(state: AppState) -> Router {
             "/api/filters/backfill-v1-hashes",
             post(filters::backfill_v1_hashes),
         )
+        .route("/api/keys", post(keys::register_key).get(keys::list_keys))
+        .route("/api/keys/{public_key}", delete(keys::revoke_key))
+        .route(
+            "/api/tokens",
+            post(tokens::create_token).get(tokens::list_tokens),
+        )
+        .route("/api/tokens/{id}", delete(tokens::revoke_token))
+        .route(
+            "/api/webhooks",
+            post(webhooks::create_webhook).get(webhooks::list_webhooks),
+        )
+        .route("/api/webhooks/{id}", delete(webhooks::delete_webhook))
         .route("/api/sync", post(sync::sync_usage))
+        .route("/api/sync/reconcile", post(sync::reconcile_cursor))
         .route("/api/catalog/refresh", post(catalog::refresh_catalog))
         .route("/api/catalog/grouped", get(catalog::get_grouped_catalog))
         .route("/api/gain", get(gain::get_gain))
@@ -69,6 +105,17 @@ pub fn create_router(state: AppState) -> Router {
         .route("/api/tos", get(tos::get_tos_info))
         .route("/api/tos/accept", post(tos::accept_tos))
         .route("/api/account", delete(account::delete_account))
+        .route(
+            "/api/admin/filters/{hash}/delist",
+            put(admin::delist_filter).delete(admin::relist_filter),
+        )
+        .route(
+            "/api/admin/users/{username}/ban",
+            put(admin::ban_user).delete(admin::unban_user),
+        )
+        .route("/api/admin/reviews", get(admin::list_reviews))
+        .route("/api/admin/reviews/{hash}", put(admin::review_filter))
+        .route("/api/admin/audit", get(admin::get_audit_log))
         .layer(axum::middleware::from_fn_with_state(
             state.clone(),
             general_rate_limit,
@@ -276,6 +276,106 @@ pub async fn sync_usage(
     ))
 }
 
+// ── POST /api/sync/reconcile ─────────────────────────────────────────────────
+
+#[derive(Debug, Deserialize)]
+pub struct ReconcileRequest {
+    pub machine_id: String,
+    /// The client's local `last_synced_id`.
+    pub last_synced_id: i64,
+    /// The highest event id the client has ever recorded (at least
+    /// `last_synced_id`, even when older events have been archived).
+    pub max_event_id: i64,
+    /// Take the client's cursor unconditionally — `tokf sync reset-machine`,
+    /// for a machine whose tracking database started over.
+    #[serde(default)]
+    pub reset: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ReconcileResponse {
+    /// Where the client should resume; the server's cursor now matches it.
+    pub cursor: i64,
+    /// The server's cursor before reconciliation.
+    pub previous_cursor: i64,
+}
+
+/// Where a machine whose server cursor is `server` should resume.
+///
+/// A server cursor beyond any id the client has recorded means the client's
+/// database started over (a reimaged laptop), so its ids are new events and
+/// the client's cursor wins. Otherwise the server's cursor is authoritative:
+/// it advances in the same transaction as the inserts, so events up to it
+/// are stored and events after it are not.
+pub const fn resume_point(server: i64, req: &ReconcileRequest) -> i64 {
+    if req.reset || server > req.max_event_id {
+        req.last_synced_id
+    } else {
+        server
+    }
+}
+
+/// Negotiate a machine's sync cursor with the client.
+///
+/// # Errors
+///
+/// - `400 Bad Request` for an invalid machine id or negative event ids.
+/// - `401 Unauthorized` if the machine belongs to another user.
+/// - `404 Not Found` if the machine does not exist.
+/// - `429 Too Many Requests` when the machine's sync rate limit is exhausted.
+pub async fn reconcile_cursor(
+    auth: AuthUser,
+    State(state): State<AppState>,
+    Json(req): Json<ReconcileRequest>,
+) -> Result<(axum::http::HeaderMap, Json<ReconcileResponse>), AppError> {
+    if req.last_synced_id < 0 || req.max_event_id < req.last_synced_id {
+        return Err(AppError::BadRequest(
+            "expected 0 <= last_synced_id <= max_event_id".to_string(),
+        ));
+    }
+    let machine_id = Uuid::parse_str(&req.machine_id)
+        .map_err(|_| AppError::BadRequest("invalid machine_id UUID".to_string()))?;
+    verify_machine_owner(&state.db, machine_id, auth.user_id).await?;
+
+    let rl = state
+        .sync_rate_limiter
+        .check_and_increment(machine_id.as_u128());
+    if !rl.allowed {
+        return Err(AppError::rate_limited(&rl));
+    }
+
+    let mut tx = state.db.begin().await?;
+    let previous_cursor: i64 = sqlx::query_scalar(
+        "SELECT last_event_id FROM sync_cursors WHERE machine_id = $1 FOR UPDATE",
+    )
+    .bind(machine_id)
+    .fetch_optional(&mut *tx)
+    .await?
+    .unwrap_or(0);
+    let cursor = resume_point(previous_cursor, &req);
+    if cursor != previous_cursor {
+        sqlx::query(
+            "INSERT INTO sync_cursors (machine_id, last_event_id, synced_at)
+             VALUES ($1, $2, NOW())
+             ON CONFLICT (machine_id) DO UPDATE SET last_event_id = EXCLUDED.last_event_id",
+        )
+        .bind(machine_id)
+        .bind(cursor)
+        .execute(&mut *tx)
+        .await?;
+        tracing::info!(%machine_id, previous_cursor, cursor, reset = req.reset, "sync cursor reconciled");
+    }
+    tx.commit().await?;
+
+    Ok((
+        crate::routes::ip::rate_limit_headers(&rl),
+        Json(ReconcileResponse {
+            cursor,
+            previous_cursor,
+        }),
+    ))
+}
+
 // Tests live in sibling files to keep this file within the 500-line soft limit.
 #[cfg(test)]
 #[allow(clippy::unwrap_used, clippy::expect_used)]
@@ -286,3 +386,8 @@ mod tests;
 #[allow(clippy::unwrap_used, clippy::expect_used)]
 #[path = "sync_validation_tests.rs"]
 mod validation_tests;
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+#[path = "sync_reconcile_tests.rs"]
+mod reconcile_tests;
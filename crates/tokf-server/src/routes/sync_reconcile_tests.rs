@@ -0,0 +1,144 @@
+use axum::{
+    Router,
+    body::Body,
+    http::{Request, StatusCode},
+    routing::post,
+};
+use sqlx::PgPool;
+use tower::ServiceExt;
+
+use crate::routes::test_helpers::*;
+
+use super::{ReconcileRequest, reconcile_cursor, resume_point, sync_usage};
+
+fn app(pool: PgPool) -> Router {
+    Router::new()
+        .route("/api/sync", post(sync_usage))
+        .route("/api/sync/reconcile", post(reconcile_cursor))
+        .with_state(make_state(pool))
+}
+
+fn request(last_synced_id: i64, max_event_id: i64, reset: bool) -> ReconcileRequest {
+    ReconcileRequest {
+        machine_id: String::new(),
+        last_synced_id,
+        max_event_id,
+        reset,
+    }
+}
+
+#[test]
+fn a_restarted_client_history_wins() {
+    // Reimaged: the server saw up to 500, the client has only recorded 20.
+    assert_eq!(resume_point(500, &request(0, 20, false)), 0);
+}
+
+#[test]
+fn otherwise_the_server_cursor_wins() {
+    // Local cursor lost or restored from a backup: skip what's stored.
+    assert_eq!(resume_point(40, &request(10, 60, false)), 40);
+    // Server cursor behind the client's: resend what the server lacks.
+    assert_eq!(resume_point(10, &request(40, 60, false)), 10);
+    assert_eq!(resume_point(60, &request(60, 60, false)), 60);
+}
+
+#[test]
+fn reset_always_takes_the_client_cursor() {
+    // Reimaged and already past the old cursor — indistinguishable from a
+    // restored backup without the user saying so.
+    assert_eq!(resume_point(40, &request(0, 60, true)), 0);
+}
+
+async fn post_json(
+    app: Router,
+    token: &str,
+    uri: &str,
+    body: &serde_json::Value,
+) -> (StatusCode, serde_json::Value) {
+    let resp = app
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri(uri)
+                .header("Authorization", format!("Bearer {token}"))
+                .header("Content-Type", "application/json")
+                .body(Body::from(serde_json::to_string(body).unwrap()))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    let status = resp.status();
+    let bytes = axum::body::to_bytes(resp.into_body(), 4096).await.unwrap();
+    (status, serde_json::from_slice(&bytes).unwrap_or_default())
+}
+
+fn events(ids: std::ops::RangeInclusive<i64>) -> Vec<serde_json::Value> {
+    ids.map(|id| {
+        serde_json::json!({
+            "id": id,
+            "filter_name": "git/push",
+            "filter_hash": null,
+            "input_tokens": 100,
+            "output_tokens": 20,
+            "command_count": 1,
+            "recorded_at": "2026-01-01T00:00:00Z"
+        })
+    })
+    .collect()
+}
+
+#[crdb_test_macro::crdb_test(migrations = "./migrations")]
+async fn reconcile_rewinds_the_cursor_for_a_restarted_machine(pool: PgPool) {
+    let (user_id, token) = create_user_and_token(&pool).await;
+    let machine = create_machine(&pool, user_id).await;
+    let machine_id = machine.to_string();
+
+    let sync = serde_json::json!({
+        "machine_id": machine_id, "last_event_id": 0, "events": events(1..=5)
+    });
+    let (status, _) = post_json(app(pool.clone()), &token, "/api/sync", &sync).await;
+    assert_eq!(status, StatusCode::OK);
+
+    // The reimaged machine has recorded 3 events, ids 1..=3.
+    let reconcile = serde_json::json!({
+        "machine_id": machine_id, "last_synced_id": 0, "max_event_id": 3
+    });
+    let (status, body) =
+        post_json(app(pool.clone()), &token, "/api/sync/reconcile", &reconcile).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["previous_cursor"], 5);
+    assert_eq!(body["cursor"], 0);
+
+    let sync = serde_json::json!({
+        "machine_id": machine_id, "last_event_id": 0, "events": events(1..=3)
+    });
+    let (_, body) = post_json(app(pool.clone()), &token, "/api/sync", &sync).await;
+    assert_eq!(body["accepted"], 3, "events must no longer be dropped");
+
+    let stored: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM usage_events WHERE machine_id = $1")
+        .bind(machine)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert_eq!(stored, 8);
+}
+
+#[crdb_test_macro::crdb_test(migrations = "./migrations")]
+async fn reconcile_rejects_inconsistent_ids_and_foreign_machines(pool: PgPool) {
+    let (user_id, token) = create_user_and_token(&pool).await;
+    let (other_id, _) = create_user_and_token(&pool).await;
+    let mine = create_machine(&pool, user_id).await.to_string();
+    let theirs = create_machine(&pool, other_id).await.to_string();
+
+    let body = serde_json::json!({
+        "machine_id": mine, "last_synced_id": 10, "max_event_id": 5
+    });
+    let (status, _) = post_json(app(pool.clone()), &token, "/api/sync/reconcile", &body).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let body = serde_json::json!({
+        "machine_id": theirs, "last_synced_id": 0, "max_event_id": 0, "reset": true
+    });
+    let (status, _) = post_json(app(pool), &token, "/api/sync/reconcile", &body).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+}
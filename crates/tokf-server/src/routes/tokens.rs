@@ -0,0 +1,391 @@
+use axum::{
+    Json,
+    extract::{Path, State},
+    http::StatusCode,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::auth::scope::Scope;
+use crate::auth::token::{AuthUser, generate_token, hash_token};
+use crate::error::AppError;
+use crate::state::AppState;
+
+// ── Request / Response types ─────────────────────────────────────────────────
+
+#[derive(Debug, Deserialize)]
+pub struct CreateTokenRequest {
+    /// What the token is for, e.g. `github-actions`.
+    pub name: String,
+    pub scopes: Vec<String>,
+    /// Lifetime in days (default 90, at most 365).
+    #[serde(default)]
+    pub expires_in_days: Option<i64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TokenResponse {
+    pub id: i64,
+    pub name: String,
+    pub scopes: Vec<String>,
+    pub created_at: String,
+    pub expires_at: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_used_at: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CreatedTokenResponse {
+    /// The bearer token. Only ever returned here; the server keeps its hash.
+    pub token: String,
+    #[serde(flatten)]
+    pub info: TokenResponse,
+}
+
+// ── Internal helpers ──────────────────────────────────────────────────────────
+
+/// Prefix on personal access tokens, so they are recognisable in logs and
+/// secret scanners.
+pub const TOKEN_PREFIX: &str = "tokf_pat_";
+
+const DEFAULT_LIFETIME_DAYS: i64 = 90;
+const MAX_LIFETIME_DAYS: i64 = 365;
+const MAX_NAME_LEN: usize = 64;
+
+/// Maximum number of unexpired personal access tokens per user.
+const MAX_TOKENS_PER_USER: i64 = 20;
+
+type Timestamp = chrono::DateTime<chrono::Utc>;
+type TokenRow = (
+    i64,
+    String,
+    Vec<String>,
+    Timestamp,
+    Timestamp,
+    Option<Timestamp>,
+);
+
+fn row_to_response(
+    (id, name, scopes, created_at, expires_at, last_used_at): TokenRow,
+) -> TokenResponse {
+    TokenResponse {
+        id,
+        name,
+        scopes,
+        created_at: created_at.to_rfc3339(),
+        expires_at: expires_at.to_rfc3339(),
+        last_used_at: last_used_at.map(|t| t.to_rfc3339()),
+    }
+}
+
+/// Check the request, returning the de-duplicated scopes and the lifetime.
+fn validate(body: &CreateTokenRequest) -> Result<(Vec<String>, i64), AppError> {
+    let name = body.name.trim();
+    if name.is_empty() || name.len() > MAX_NAME_LEN {
+        return Err(AppError::BadRequest(format!(
+            "name must be 1-{MAX_NAME_LEN} characters"
+        )));
+    }
+    if body.scopes.is_empty() {
+        return Err(AppError::BadRequest(
+            "at least one scope is required".to_string(),
+        ));
+    }
+    let mut scopes = Vec::new();
+    for raw in &body.scopes {
+        let scope = Scope::parse(raw).ok_or_else(|| {
+            let known: Vec<&str> = Scope::ALL.iter().map(|s| s.as_str()).collect();
+            AppError::BadRequest(format!(
+                "unknown scope '{raw}' (expected one of: {})",
+                known.join(", ")
+            ))
+        })?;
+        if !scopes.iter().any(|s| s == scope.as_str()) {
+            scopes.push(scope.as_str().to_string());
+        }
+    }
+    let days = body.expires_in_days.unwrap_or(DEFAULT_LIFETIME_DAYS);
+    if !(1..=MAX_LIFETIME_DAYS).contains(&days) {
+        return Err(AppError::BadRequest(format!(
+            "expires_in_days must be between 1 and {MAX_LIFETIME_DAYS}"
+        )));
+    }
+    Ok((scopes, days))
+}
+
+// ── POST /api/tokens ──────────────────────────────────────────────────────────
+
+/// Mint a scoped, expiring personal access token for the authenticated user.
+///
+/// The token is returned once and stored only as a hash. Personal access
+/// tokens cannot mint further tokens.
+///
+/// # Errors
+///
+/// - `400 Bad Request` for an empty or overlong name, an unknown or missing
+///   scope, or a lifetime outside 1-365 days.
+/// - `401 Unauthorized` if the bearer token is missing or invalid.
+/// - `403 Forbidden` if the caller is a personal access token, or asks for
+///   the `stdlib` scope without being a standard library publisher.
+/// - `429 Too Many Requests` if the user has reached the token limit.
+/// - `500 Internal Server Error` on database failures.
+pub async fn create_token(
+    auth: AuthUser,
+    State(state): State<AppState>,
+    Json(body): Json<CreateTokenRequest>,
+) -> Result<(StatusCode, Json<CreatedTokenResponse>), AppError> {
+    let (scopes, days) = validate(&body)?;
+
+    if scopes.iter().any(|s| s == Scope::Stdlib.as_str()) {
+        let publisher: bool =
+            sqlx::query_scalar("SELECT stdlib_publisher FROM users WHERE id = $1")
+                .bind(auth.user_id)
+                .fetch_one(&state.db)
+                .await?;
+        if !publisher {
+            return Err(AppError::Forbidden(
+                "the stdlib scope is reserved for standard library publishers".to_string(),
+            ));
+        }
+    }
+
+    let count: i64 = sqlx::query_scalar(
+        "SELECT COUNT(*) FROM auth_tokens
+         WHERE user_id = $1 AND name IS NOT NULL AND expires_at > NOW()",
+    )
+    .bind(auth.user_id)
+    .fetch_one(&state.db)
+    .await?;
+    if count >= MAX_TOKENS_PER_USER {
+        // Constant is 20 — always fits in u32.
+        #[allow(clippy::cast_possible_truncation)]
+        return Err(AppError::RateLimited {
+            retry_after_secs: 0,
+            limit: MAX_TOKENS_PER_USER as u32,
+            remaining: 0,
+        });
+    }
+
+    let token = format!("{TOKEN_PREFIX}{}", generate_token());
+    let expires_at = chrono::Utc::now() + chrono::Duration::days(days);
+    let row = sqlx::query_as::<_, TokenRow>(
+        "INSERT INTO auth_tokens (user_id, token_hash, expires_at, name, scopes)
+         VALUES ($1, $2, $3, $4, $5)
+         RETURNING id, name, scopes, created_at, expires_at, last_used_at",
+    )
+    .bind(auth.user_id)
+    .bind(hash_token(&token))
+    .bind(expires_at)
+    .bind(body.name.trim())
+    .bind(&scopes)
+    .fetch_one(&state.db)
+    .await?;
+
+    Ok((
+        StatusCode::CREATED,
+        Json(CreatedTokenResponse {
+            token,
+            info: row_to_response(row),
+        }),
+    ))
+}
+
+// ── GET /api/tokens ───────────────────────────────────────────────────────────
+
+/// List the authenticated user's unexpired personal access tokens, newest
+/// first. Token values are never returned.
+///
+/// # Errors
+///
+/// - `401 Unauthorized` if the bearer token is missing or invalid.
+/// - `403 Forbidden` if the caller is a personal access token.
+/// - `500 Internal Server Error` on database failures.
+pub async fn list_tokens(
+    auth: AuthUser,
+    State(state): State<AppState>,
+) -> Result<Json<Vec<TokenResponse>>, AppError> {
+    let rows = sqlx::query_as::<_, TokenRow>(
+        "SELECT id, name, scopes, created_at, expires_at, last_used_at
+         FROM auth_tokens
+         WHERE user_id = $1 AND name IS NOT NULL AND expires_at > NOW()
+         ORDER BY created_at DESC",
+    )
+    .bind(auth.user_id)
+    .fetch_all(&state.db)
+    .await?;
+    Ok(Json(rows.into_iter().map(row_to_response).collect()))
+}
+
+// ── DELETE /api/tokens/{id} ───────────────────────────────────────────────────
+
+/// Revoke one of the authenticated user's personal access tokens.
+///
+/// # Errors
+///
+/// - `401 Unauthorized` if the bearer token is missing or invalid.
+/// - `403 Forbidden` if the caller is a personal access token.
+/// - `404 Not Found` if the user has no such token.
+/// - `500 Internal Server Error` on database failures.
+pub async fn revoke_token(
+    auth: AuthUser,
+    State(state): State<AppState>,
+    Path(id): Path<i64>,
+) -> Result<StatusCode, AppError> {
+    let deleted =
+        sqlx::query("DELETE FROM auth_tokens WHERE id = $1 AND user_id = $2 AND name IS NOT NULL")
+            .bind(id)
+            .bind(auth.user_id)
+            .execute(&state.db)
+            .await?;
+    if deleted.rows_affected() == 0 {
+        return Err(AppError::NotFound("token not found".to_string()));
+    }
+    Ok(StatusCode::NO_CONTENT)
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use axum::{
+        body::Body,
+        http::{Request, StatusCode},
+    };
+    use tower::ServiceExt;
+
+    use crate::routes::test_helpers::{assert_status, create_user_and_token, make_state};
+
+    fn request(
+        method: &str,
+        uri: &str,
+        token: &str,
+        body: Option<serde_json::Value>,
+    ) -> Request<Body> {
+        let builder = Request::builder()
+            .method(method)
+            .uri(uri)
+            .header("authorization", format!("Bearer {token}"));
+        match body {
+            Some(json) => builder
+                .header("content-type", "application/json")
+                .body(Body::from(serde_json::to_vec(&json).unwrap()))
+                .unwrap(),
+            None => builder.body(Body::empty()).unwrap(),
+        }
+    }
+
+    async fn create(
+        app: &axum::Router,
+        token: &str,
+        body: serde_json::Value,
+    ) -> axum::response::Response {
+        app.clone()
+            .oneshot(request("POST", "/api/tokens", token, Some(body)))
+            .await
+            .unwrap()
+    }
+
+    #[crdb_test_macro::crdb_test(migrations = "./migrations")]
+    async fn create_list_and_revoke(pool: PgPool) {
+        let (_, session) = create_user_and_token(&pool).await;
+        let app = crate::routes::create_router(make_state(pool));
+
+        let body = serde_json::json!({ "name": "ci", "scopes": ["publish", "publish"] });
+        let created = assert_status(create(&app, &session, body).await, StatusCode::CREATED).await;
+        let created: serde_json::Value = serde_json::from_slice(&created).unwrap();
+        assert!(
+            created["token"]
+                .as_str()
+                .unwrap()
+                .starts_with(super::TOKEN_PREFIX)
+        );
+        assert_eq!(created["scopes"], serde_json::json!(["publish"]));
+
+        let resp = app
+            .clone()
+            .oneshot(request("GET", "/api/tokens", &session, None))
+            .await
+            .unwrap();
+        let listed = assert_status(resp, StatusCode::OK).await;
+        let listed: serde_json::Value = serde_json::from_slice(&listed).unwrap();
+        assert_eq!(listed.as_array().unwrap().len(), 1);
+        assert!(listed[0].get("token").is_none());
+
+        let uri = format!("/api/tokens/{}", created["id"]);
+        let resp = app
+            .clone()
+            .oneshot(request("DELETE", &uri, &session, None))
+            .await
+            .unwrap();
+        assert_status(resp, StatusCode::NO_CONTENT).await;
+        let pat = created["token"].as_str().unwrap();
+        let resp = app
+            .oneshot(request("GET", "/api/keys", pat, None))
+            .await
+            .unwrap();
+        assert_status(resp, StatusCode::UNAUTHORIZED).await;
+    }
+
+    #[crdb_test_macro::crdb_test(migrations = "./migrations")]
+    async fn scopes_are_enforced(pool: PgPool) {
+        let (_, session) = create_user_and_token(&pool).await;
+        let app = crate::routes::create_router(make_state(pool));
+
+        let body = serde_json::json!({ "name": "ci", "scopes": ["sync"] });
+        let created = assert_status(create(&app, &session, body).await, StatusCode::CREATED).await;
+        let created: serde_json::Value = serde_json::from_slice(&created).unwrap();
+        let pat = created["token"].as_str().unwrap();
+
+        let resp = app
+            .clone()
+            .oneshot(request("GET", "/api/keys", pat, None))
+            .await
+            .unwrap();
+        assert_status(resp, StatusCode::OK).await;
+
+        let body = serde_json::json!({ "name": "nested", "scopes": ["sync"] });
+        assert_status(create(&app, pat, body).await, StatusCode::FORBIDDEN).await;
+
+        let resp = app
+            .oneshot(request("DELETE", "/api/account", pat, None))
+            .await
+            .unwrap();
+        assert_status(resp, StatusCode::FORBIDDEN).await;
+    }
+
+    #[crdb_test_macro::crdb_test(migrations = "./migrations")]
+    async fn stdlib_scope_needs_a_publisher(pool: PgPool) {
+        let (user_id, session) = create_user_and_token(&pool).await;
+        let app = crate::routes::create_router(make_state(pool.clone()));
+        let body = serde_json::json!({ "name": "stdlib", "scopes": ["stdlib"] });
+
+        assert_status(
+            create(&app, &session, body.clone()).await,
+            StatusCode::FORBIDDEN,
+        )
+        .await;
+
+        sqlx::query("UPDATE users SET stdlib_publisher = TRUE WHERE id = $1")
+            .bind(user_id)
+            .execute(&pool)
+            .await
+            .unwrap();
+        assert_status(create(&app, &session, body).await, StatusCode::CREATED).await;
+    }
+
+    #[crdb_test_macro::crdb_test(migrations = "./migrations")]
+    async fn invalid_requests_are_rejected(pool: PgPool) {
+        let (_, session) = create_user_and_token(&pool).await;
+        let app = crate::routes::create_router(make_state(pool));
+
+        for body in [
+            serde_json::json!({ "name": "", "scopes": ["read"] }),
+            serde_json::json!({ "name": "ci", "scopes": [] }),
+            serde_json::json!({ "name": "ci", "scopes": ["admin"] }),
+            serde_json::json!({ "name": "ci", "scopes": ["read"], "expires_in_days": 0 }),
+            serde_json::json!({ "name": "ci", "scopes": ["read"], "expires_in_days": 366 }),
+        ] {
+            assert_status(create(&app, &session, body).await, StatusCode::BAD_REQUEST).await;
+        }
+    }
+}
@@ -0,0 +1,261 @@
+use axum::{
+    Json,
+    extract::{Path, State},
+    http::StatusCode,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::auth::token::{AuthUser, generate_token};
+use crate::error::AppError;
+use crate::state::AppState;
+use crate::webhooks::{self, WebhookEvent};
+
+// ── Request / Response types ─────────────────────────────────────────────────
+
+#[derive(Debug, Deserialize)]
+pub struct CreateWebhookRequest {
+    pub url: String,
+    /// Events to deliver; all of them when empty.
+    #[serde(default)]
+    pub events: Vec<String>,
+    /// GitHub org whose filters to watch instead of the caller's own.
+    #[serde(default)]
+    pub org: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct WebhookResponse {
+    pub id: i64,
+    pub url: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub org: Option<String>,
+    pub events: Vec<String>,
+    pub created_at: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_delivery_at: Option<String>,
+    /// HTTP status of the latest delivery; absent if it got no response.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_status: Option<i32>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CreatedWebhookResponse {
+    /// The signing secret. Only ever returned here.
+    pub secret: String,
+    #[serde(flatten)]
+    pub info: WebhookResponse,
+}
+
+// ── Internal helpers ──────────────────────────────────────────────────────────
+
+/// Prefix on webhook secrets, so they are recognisable in secret scanners.
+pub const SECRET_PREFIX: &str = "tokf_whsec_";
+
+const MAX_URL_LEN: usize = 2048;
+
+/// Maximum number of webhooks per user.
+const MAX_WEBHOOKS_PER_USER: i64 = 10;
+
+type Timestamp = chrono::DateTime<chrono::Utc>;
+type WebhookRow = (
+    i64,
+    String,
+    Option<String>,
+    Vec<String>,
+    Timestamp,
+    Option<Timestamp>,
+    Option<i32>,
+);
+
+fn row_to_response(
+    (id, url, org, events, created_at, last_delivery_at, last_status): WebhookRow,
+) -> WebhookResponse {
+    WebhookResponse {
+        id,
+        url,
+        org,
+        events,
+        created_at: created_at.to_rfc3339(),
+        last_delivery_at: last_delivery_at.map(|t| t.to_rfc3339()),
+        last_status,
+    }
+}
+
+/// Check the delivery URL: `https` to a host that resolves only to public
+/// addresses. With `allow_private` (local development), plain `http` and
+/// private addresses are accepted too.
+async fn validate_url(raw: &str, allow_private: bool) -> Result<String, AppError> {
+    let raw = raw.trim();
+    if raw.len() > MAX_URL_LEN {
+        return Err(AppError::BadRequest(format!(
+            "url must not exceed {MAX_URL_LEN} bytes"
+        )));
+    }
+    let url = reqwest::Url::parse(raw)
+        .map_err(|e| AppError::BadRequest(format!("invalid url '{raw}': {e}")))?;
+    match url.scheme() {
+        "https" => {}
+        "http" if allow_private => {}
+        _ => {
+            return Err(AppError::BadRequest(
+                "webhook url must use https".to_string(),
+            ));
+        }
+    }
+    webhooks::resolve_target(&url, allow_private)
+        .await
+        .map_err(|e| AppError::BadRequest(format!("webhook {e}")))?;
+    Ok(url.into())
+}
+
+/// The de-duplicated event names, defaulting to every event.
+fn validate_events(events: &[String]) -> Result<Vec<String>, AppError> {
+    if events.is_empty() {
+        return Ok(WebhookEvent::ALL
+            .iter()
+            .map(|e| e.as_str().to_string())
+            .collect());
+    }
+    let mut out: Vec<String> = Vec::new();
+    for raw in events {
+        let event = WebhookEvent::parse(raw).ok_or_else(|| {
+            let known: Vec<&str> = WebhookEvent::ALL.iter().map(|e| e.as_str()).collect();
+            AppError::BadRequest(format!(
+                "unknown event '{raw}' (expected one of: {})",
+                known.join(", ")
+            ))
+        })?;
+        if !out.iter().any(|e| e == event.as_str()) {
+            out.push(event.as_str().to_string());
+        }
+    }
+    Ok(out)
+}
+
+// ── POST /api/webhooks ────────────────────────────────────────────────────────
+
+/// Register a webhook for the caller's own filters or, with `org`, for the
+/// filters published under a GitHub org the caller belongs to.
+///
+/// The signing secret is returned once.
+///
+/// # Errors
+///
+/// - `400 Bad Request` for an invalid or non-`https` URL, one that resolves
+///   to a loopback, private or link-local address, an unknown event, or an
+///   invalid org name.
+/// - `401 Unauthorized` if the bearer token is missing or invalid.
+/// - `403 Forbidden` if the caller is a personal access token or not a
+///   member of `org`.
+/// - `429 Too Many Requests` if the user has reached the webhook limit.
+/// - `500 Internal Server Error` on database failures.
+pub async fn create_webhook(
+    auth: AuthUser,
+    State(state): State<AppState>,
+    Json(body): Json<CreateWebhookRequest>,
+) -> Result<(StatusCode, Json<CreatedWebhookResponse>), AppError> {
+    let url = validate_url(&body.url, state.webhooks_allow_private).await?;
+    let events = validate_events(&body.events)?;
+    let org = match body.org.as_deref().map(str::trim) {
+        Some(org) if !org.is_empty() => {
+            Some(super::filters::orgs::resolve_org(&state.db, auth.user_id, org).await?)
+        }
+        _ => None,
+    };
+
+    let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM webhooks WHERE user_id = $1")
+        .bind(auth.user_id)
+        .fetch_one(&state.db)
+        .await?;
+    if count >= MAX_WEBHOOKS_PER_USER {
+        // Constant is 10 — always fits in u32.
+        #[allow(clippy::cast_possible_truncation)]
+        return Err(AppError::RateLimited {
+            retry_after_secs: 0,
+            limit: MAX_WEBHOOKS_PER_USER as u32,
+            remaining: 0,
+        });
+    }
+
+    let secret = format!("{SECRET_PREFIX}{}", generate_token());
+    let row = sqlx::query_as::<_, WebhookRow>(
+        "INSERT INTO webhooks (user_id, org, url, secret, events)
+         VALUES ($1, $2, $3, $4, $5)
+         RETURNING id, url, org, events, created_at, last_delivery_at, last_status",
+    )
+    .bind(auth.user_id)
+    .bind(&org)
+    .bind(&url)
+    .bind(&secret)
+    .bind(&events)
+    .fetch_one(&state.db)
+    .await?;
+
+    Ok((
+        StatusCode::CREATED,
+        Json(CreatedWebhookResponse {
+            secret,
+            info: row_to_response(row),
+        }),
+    ))
+}
+
+// ── GET /api/webhooks ─────────────────────────────────────────────────────────
+
+/// List the webhooks the authenticated user registered, newest first.
+/// Secrets are never returned.
+///
+/// # Errors
+///
+/// - `401 Unauthorized` if the bearer token is missing or invalid.
+/// - `403 Forbidden` if the caller is a personal access token.
+/// - `500 Internal Server Error` on database failures.
+pub async fn list_webhooks(
+    auth: AuthUser,
+    State(state): State<AppState>,
+) -> Result<Json<Vec<WebhookResponse>>, AppError> {
+    let rows = sqlx::query_as::<_, WebhookRow>(
+        "SELECT id, url, org, events, created_at, last_delivery_at, last_status
+         FROM webhooks WHERE user_id = $1
+         ORDER BY created_at DESC, id DESC",
+    )
+    .bind(auth.user_id)
+    .fetch_all(&state.db)
+    .await?;
+    Ok(Json(rows.into_iter().map(row_to_response).collect()))
+}
+
+// ── DELETE /api/webhooks/:id ──────────────────────────────────────────────────
+
+/// Remove one of the authenticated user's webhooks.
+///
+/// # Errors
+///
+/// - `401 Unauthorized` if the bearer token is missing or invalid.
+/// - `403 Forbidden` if the caller is a personal access token.
+/// - `404 Not Found` if the user has no webhook with that id.
+/// - `500 Internal Server Error` on database failures.
+pub async fn delete_webhook(
+    auth: AuthUser,
+    State(state): State<AppState>,
+    Path(id): Path<i64>,
+) -> Result<StatusCode, AppError> {
+    let result = sqlx::query("DELETE FROM webhooks WHERE id = $1 AND user_id = $2")
+        .bind(id)
+        .bind(auth.user_id)
+        .execute(&state.db)
+        .await?;
+    if result.rows_affected() == 0 {
+        return Err(AppError::NotFound(format!("webhook not found: {id}")));
+    }
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[cfg(test)]
+#[allow(
+    clippy::unwrap_used,
+    clippy::expect_used,
+    clippy::significant_drop_tightening
+)]
+#[path = "webhooks_tests.rs"]
+mod tests;
@@ -0,0 +1,162 @@
+use std::time::Duration;
+
+use axum::http::StatusCode;
+use sqlx::PgPool;
+
+use super::super::filters::test_helpers::{
+    DEFAULT_PASSING_TEST, MIT_ACCEPT, get_request, insert_test_user, make_state, post_filter,
+    send_json,
+};
+use super::super::test_helpers::assert_status;
+use super::*;
+use crate::webhooks::{EVENT_HEADER, SIGNATURE_HEADER};
+
+fn app(pool: &PgPool) -> axum::Router {
+    crate::routes::create_router(make_state(pool.clone()))
+}
+
+async fn register(pool: &PgPool, token: &str, body: serde_json::Value) -> serde_json::Value {
+    let resp = send_json(app(pool), "POST", token, "/api/webhooks", &body).await;
+    let body = assert_status(resp, StatusCode::CREATED).await;
+    serde_json::from_slice(&body).unwrap()
+}
+
+/// Wait for the background delivery to reach `mock`.
+async fn delivered(mock: &mockito::Mock) -> bool {
+    for _ in 0..50 {
+        if mock.matched_async().await {
+            return true;
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+    false
+}
+
+#[tokio::test]
+async fn urls_must_be_https_to_public_addresses() {
+    assert!(validate_url("https://203.0.113.7/hook", true).await.is_ok());
+    assert!(validate_url("https://8.8.8.8/hook", false).await.is_ok());
+    for bad in [
+        "http://8.8.8.8/hook",
+        "ftp://example.com",
+        "not a url",
+        "https://127.0.0.1:9000/hook",
+        "https://localhost/hook",
+        "https://10.0.0.5/hook",
+        "https://169.254.169.254/latest/meta-data/",
+        "https://[::1]/hook",
+        "https://[fd00::1]/hook",
+        "https://[::ffff:192.168.1.1]/hook",
+    ] {
+        assert!(
+            validate_url(bad, false).await.is_err(),
+            "{bad} should be rejected"
+        );
+    }
+    assert!(
+        validate_url(" http://localhost:9000/hook ", true)
+            .await
+            .is_ok()
+    );
+    let long = format!("https://8.8.8.8/{}", "a".repeat(MAX_URL_LEN));
+    assert!(validate_url(&long, false).await.is_err());
+}
+
+#[test]
+fn events_default_to_all_and_are_deduplicated() {
+    assert_eq!(validate_events(&[]).unwrap().len(), WebhookEvent::ALL.len());
+    let events = ["filter.yanked".to_string(), "filter.yanked".to_string()];
+    assert_eq!(validate_events(&events).unwrap(), ["filter.yanked"]);
+    assert!(validate_events(&["filter.deleted".to_string()]).is_err());
+}
+
+#[crdb_test_macro::crdb_test(migrations = "./migrations")]
+async fn webhooks_are_registered_listed_and_removed(pool: PgPool) {
+    let (_, token) = insert_test_user(&pool, "hook_owner").await;
+    let created = register(
+        &pool,
+        &token,
+        serde_json::json!({"url": "https://203.0.113.7/hook", "events": ["filter.yanked"]}),
+    )
+    .await;
+    assert!(
+        created["secret"]
+            .as_str()
+            .unwrap()
+            .starts_with(SECRET_PREFIX)
+    );
+    assert_eq!(created["events"], serde_json::json!(["filter.yanked"]));
+
+    let resp = get_request(app(&pool), &token, "/api/webhooks").await;
+    let listed: serde_json::Value =
+        serde_json::from_slice(&assert_status(resp, StatusCode::OK).await).unwrap();
+    assert_eq!(listed[0]["id"], created["id"]);
+    assert!(listed[0].get("secret").is_none());
+
+    let uri = format!("/api/webhooks/{}", created["id"]);
+    let empty = serde_json::json!({});
+    let resp = send_json(app(&pool), "DELETE", &token, &uri, &empty).await;
+    assert_status(resp, StatusCode::NO_CONTENT).await;
+    let resp = send_json(app(&pool), "DELETE", &token, &uri, &empty).await;
+    assert_status(resp, StatusCode::NOT_FOUND).await;
+
+    let org_hook = serde_json::json!({"url": "https://203.0.113.7/hook", "org": "acme"});
+    let resp = send_json(app(&pool), "POST", &token, "/api/webhooks", &org_hook).await;
+    assert_status(resp, StatusCode::FORBIDDEN).await;
+}
+
+#[crdb_test_macro::crdb_test(migrations = "./migrations")]
+async fn publishing_notifies_the_author_and_org_webhooks(pool: PgPool) {
+    let (author_id, author) = insert_test_user(&pool, "hook_author").await;
+    let (member_id, member) = insert_test_user(&pool, "hook_member").await;
+    sqlx::query("UPDATE users SET orgs = '[\"Acme\"]'::jsonb WHERE id = ANY($1)")
+        .bind(vec![author_id, member_id])
+        .execute(&pool)
+        .await
+        .unwrap();
+
+    let mut server = mockito::Server::new_async().await;
+    register(
+        &pool,
+        &author,
+        serde_json::json!({"url": format!("{}/own", server.url())}),
+    )
+    .await;
+    register(
+        &pool,
+        &member,
+        serde_json::json!({"url": format!("{}/org", server.url()), "org": "acme"}),
+    )
+    .await;
+
+    let own_mock = server
+        .mock("POST", "/own")
+        .match_header(EVENT_HEADER, "filter.published")
+        .match_header(SIGNATURE_HEADER, mockito::Matcher::Regex("^sha256=".into()))
+        .match_body(mockito::Matcher::PartialJson(serde_json::json!({
+            "event": "filter.published",
+            "filter": {"author": "hook_author", "org": "Acme"},
+        })))
+        .create_async()
+        .await;
+    let org_mock = server
+        .mock("POST", "/org")
+        .match_header(EVENT_HEADER, "filter.published")
+        .create_async()
+        .await;
+
+    let fields: [(&str, &[u8]); 4] = [
+        ("filter", b"command = \"hooked\""),
+        MIT_ACCEPT,
+        DEFAULT_PASSING_TEST,
+        ("org", b"acme"),
+    ];
+    let resp = post_filter(app(&pool), &author, &fields).await;
+    assert_status(resp, StatusCode::CREATED).await;
+
+    assert!(
+        delivered(&own_mock).await,
+        "author's webhook was not called"
+    );
+    assert!(delivered(&org_mock).await, "org webhook was not called");
+}
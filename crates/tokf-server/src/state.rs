@@ -14,6 +14,8 @@ pub struct AppState {
     pub trust_proxy: bool,
     pub public_url: String,
     pub terms_url: String,
+    /// Let webhooks target private networks; see `Config::webhooks_allow_private`.
+    pub webhooks_allow_private: bool,
     pub publish_rate_limiter: Arc<PublishRateLimiter>,
     /// Rate limiter for search/download endpoints (higher limit than publish).
     pub search_rate_limiter: Arc<PublishRateLimiter>,
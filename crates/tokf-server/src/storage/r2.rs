@@ -172,6 +172,7 @@ mod tests {
             github_client_secret: None,
             public_url: "http://localhost:8080".to_string(),
             terms_url: "http://localhost:8080/terms".to_string(),
+            webhooks_allow_private: false,
             rate_limits: crate::config::RateLimitConfig::default(),
         }
     }
@@ -5,7 +5,8 @@ use tokf_filter::verify::{VerifyResult, verify_filter_sandboxed};
 
 /// Validate that a filter config is safe for server-side execution.
 ///
-/// Rejects filters with `lua_script.file` — only inline `source` is supported.
+/// Rejects filters with `lua_script.file` — only inline `source` is supported —
+/// and filters with a `wasm_module`, which always references a local file.
 fn validate_filter_for_server(config: &FilterConfig) -> Result<(), String> {
     if let Some(ref script) = config.lua_script
         && script.file.is_some()
@@ -15,6 +16,9 @@ fn validate_filter_for_server(config: &FilterConfig) -> Result<(), String> {
                 .to_string(),
         );
     }
+    if config.wasm_module.is_some() {
+        return Err("wasm_module is not supported for published filters".to_string());
+    }
     for (i, rule) in config.match_output.iter().enumerate() {
         rule.validate()
             .map_err(|e| format!("match_output[{i}]: {e}"))?;
@@ -44,6 +48,7 @@ fn validate_cases_for_server(cases: &[TestCase]) -> Result<(), String> {
 ///
 /// Returns `Err` if:
 /// - The filter uses `lua_script.file` (only inline `source` supported)
+/// - The filter uses `wasm_module`
 /// - Any test case uses `fixture` (only `inline` supported)
 pub fn verify_filter_server(
     config: &FilterConfig,
@@ -177,6 +182,22 @@ file = "/some/path/script.luau"
         );
     }
 
+    #[test]
+    fn server_verify_rejects_wasm_module() {
+        let config = make_config(
+            r#"
+command = "test"
+wasm_module = "plugin.wasm"
+"#,
+        );
+        let cases = vec![make_case("basic", "hello", vec![expect_equals("hello")])];
+        let err = verify_filter_server(&config, &cases).unwrap_err();
+        assert!(
+            err.contains("wasm_module"),
+            "expected wasm_module rejection, got: {err}"
+        );
+    }
+
     #[test]
     fn server_verify_allows_inline_lua_source() {
         // Deterministic-still-passes companion to
@@ -0,0 +1,447 @@
+//! Webhook delivery.
+//!
+//! Registry events are sent as JSON to every webhook subscribed to them:
+//! the author's own webhooks, and those registered for the filter's org by
+//! its members. Each body is signed with HMAC-SHA256 over the raw bytes
+//! using the webhook's secret, in the same `sha256=<hex>` form GitHub uses.
+//!
+//! Delivery is best-effort: one attempt per event with a 10s timeout, made
+//! in the background after the triggering request has been answered. The
+//! outcome is recorded on the webhook row for its owner to inspect.
+//!
+//! Because owners see that outcome, a webhook must not be able to reach the
+//! registry's own network. Its host is resolved when it is registered and
+//! again for every delivery; any loopback, private, link-local or otherwise
+//! non-public address refuses it, the connection is pinned to the addresses
+//! checked, and redirects are not followed.
+
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::time::Duration;
+
+use hmac::{Hmac, KeyInit, Mac};
+use serde::Serialize;
+use sha2::Sha256;
+
+use crate::state::AppState;
+
+pub const EVENT_HEADER: &str = "x-tokf-event";
+pub const SIGNATURE_HEADER: &str = "x-tokf-signature-256";
+pub const DELIVERY_HEADER: &str = "x-tokf-delivery";
+
+const DELIVERY_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Registry activity a webhook can subscribe to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WebhookEvent {
+    /// A new filter was published.
+    Published,
+    /// A filter's test suite was replaced.
+    TestsUpdated,
+    /// A filter was yanked.
+    Yanked,
+}
+
+impl WebhookEvent {
+    pub const ALL: [Self; 3] = [Self::Published, Self::TestsUpdated, Self::Yanked];
+
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Published => "filter.published",
+            Self::TestsUpdated => "filter.tests_updated",
+            Self::Yanked => "filter.yanked",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|event| event.as_str() == s)
+    }
+}
+
+/// The filter an event is about.
+#[derive(Debug, Serialize, sqlx::FromRow)]
+pub struct FilterRef {
+    pub content_hash: String,
+    pub command_pattern: String,
+    pub author: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub org: Option<String>,
+    pub visibility: String,
+    #[sqlx(skip)]
+    pub registry_url: String,
+}
+
+/// The JSON body of a delivery.
+#[derive(Debug, Serialize)]
+pub struct Payload {
+    pub event: &'static str,
+    pub sent_at: String,
+    pub filter: FilterRef,
+    /// The yank reason, for `filter.yanked`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
+
+/// The signature header value for `body`: `sha256=` and the hex
+/// HMAC-SHA256 of the body keyed with `secret`.
+///
+/// # Panics
+///
+/// Never: HMAC accepts keys of any length.
+pub fn sign(secret: &str, body: &[u8]) -> String {
+    #[allow(clippy::expect_used)]
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC takes any key length");
+    mac.update(body);
+    format!("sha256={}", hex::encode(mac.finalize().into_bytes()))
+}
+
+/// Whether `ip` is a public unicast address a webhook may be delivered to.
+pub fn is_public(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(v4) => is_public_v4(v4),
+        IpAddr::V6(v6) => v6
+            .to_ipv4_mapped()
+            .map_or_else(|| is_public_v6(v6), is_public_v4),
+    }
+}
+
+const fn is_public_v4(ip: Ipv4Addr) -> bool {
+    let [a, b, ..] = ip.octets();
+    !(ip.is_unspecified()
+        || ip.is_loopback()
+        || ip.is_private()
+        || ip.is_link_local()
+        || ip.is_broadcast()
+        || ip.is_multicast()
+        || ip.is_documentation()
+        || a == 0
+        // Carrier-grade NAT, 100.64.0.0/10.
+        || (a == 100 && (b & 0xc0) == 64)
+        // Reserved, 240.0.0.0/4.
+        || a >= 240)
+}
+
+const fn is_public_v6(ip: Ipv6Addr) -> bool {
+    let first = ip.segments()[0];
+    !(ip.is_unspecified()
+        || ip.is_loopback()
+        || ip.is_multicast()
+        // Unique local, fc00::/7.
+        || (first & 0xfe00) == 0xfc00
+        // Link-local, fe80::/10.
+        || (first & 0xffc0) == 0xfe80
+        // Documentation, 2001:db8::/32.
+        || (first == 0x2001 && ip.segments()[1] == 0x0db8))
+}
+
+/// Why a webhook URL cannot be delivered to.
+#[derive(Debug, PartialEq, Eq)]
+pub enum TargetError {
+    /// The URL has no host.
+    NoHost,
+    /// The host does not resolve.
+    Unresolvable(String),
+    /// The host resolves to a non-public address.
+    NotPublic(IpAddr),
+}
+
+impl std::fmt::Display for TargetError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NoHost => write!(f, "url has no host"),
+            Self::Unresolvable(host) => write!(f, "host '{host}' does not resolve"),
+            Self::NotPublic(ip) => write!(f, "url resolves to non-public address {ip}"),
+        }
+    }
+}
+
+/// Resolve the host of `url` to the addresses a delivery may connect to.
+///
+/// Unless `allow_private`, every address must be public: a name with even
+/// one private answer is refused.
+///
+/// # Errors
+///
+/// Returns a [`TargetError`] when the URL has no host, the host does not
+/// resolve, or it resolves to a non-public address.
+pub async fn resolve_target(
+    url: &reqwest::Url,
+    allow_private: bool,
+) -> Result<Vec<SocketAddr>, TargetError> {
+    let host = url.host_str().ok_or(TargetError::NoHost)?;
+    let port = url.port_or_known_default().unwrap_or(443);
+    let addrs: Vec<SocketAddr> = match ip_literal(host) {
+        Some(ip) => vec![SocketAddr::new(ip, port)],
+        None => tokio::net::lookup_host((host, port))
+            .await
+            .map_err(|_| TargetError::Unresolvable(host.to_string()))?
+            .collect(),
+    };
+    if addrs.is_empty() {
+        return Err(TargetError::Unresolvable(host.to_string()));
+    }
+    if !allow_private && let Some(bad) = addrs.iter().find(|a| !is_public(a.ip())) {
+        return Err(TargetError::NotPublic(bad.ip()));
+    }
+    Ok(addrs)
+}
+
+/// The address a URL host names directly, if it is an IP literal.
+fn ip_literal(host: &str) -> Option<IpAddr> {
+    host.trim_start_matches('[')
+        .trim_end_matches(']')
+        .parse()
+        .ok()
+}
+
+/// A client that only connects to `addrs` for `url`'s host and never
+/// follows redirects.
+fn client_for(url: &reqwest::Url, addrs: &[SocketAddr]) -> reqwest::Result<reqwest::Client> {
+    let mut builder = reqwest::Client::builder()
+        .user_agent("tokf-webhooks")
+        .timeout(DELIVERY_TIMEOUT)
+        .redirect(reqwest::redirect::Policy::none());
+    if let Some(domain) = url.host_str().filter(|h| ip_literal(h).is_none()) {
+        builder = builder.resolve_to_addrs(domain, addrs);
+    }
+    builder.build()
+}
+
+/// Why a delivery got no response.
+#[derive(Debug)]
+pub enum DeliveryError {
+    /// The URL no longer passes [`resolve_target`].
+    Target(TargetError),
+    /// The request failed or timed out.
+    Http(reqwest::Error),
+}
+
+impl std::fmt::Display for DeliveryError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Target(e) => write!(f, "{e}"),
+            Self::Http(e) => write!(f, "{e}"),
+        }
+    }
+}
+
+/// POST a signed payload to `url`, returning the response status. Any
+/// response counts as delivered, redirects included; only a refused target
+/// or a transport failure is an error.
+///
+/// # Errors
+///
+/// Returns [`DeliveryError::Target`] if the URL now resolves to a non-public
+/// address (and `allow_private` is off), or [`DeliveryError::Http`] if no
+/// response arrived within the timeout.
+pub async fn deliver(
+    url: &str,
+    secret: &str,
+    event: WebhookEvent,
+    body: Vec<u8>,
+    allow_private: bool,
+) -> Result<u16, DeliveryError> {
+    let parsed =
+        reqwest::Url::parse(url).map_err(|_| DeliveryError::Target(TargetError::NoHost))?;
+    let addrs = resolve_target(&parsed, allow_private)
+        .await
+        .map_err(DeliveryError::Target)?;
+    let client = client_for(&parsed, &addrs).map_err(DeliveryError::Http)?;
+    let signature = sign(secret, &body);
+    let response = client
+        .post(parsed)
+        .header(reqwest::header::CONTENT_TYPE, "application/json")
+        .header(EVENT_HEADER, event.as_str())
+        .header(SIGNATURE_HEADER, signature)
+        .header(DELIVERY_HEADER, uuid::Uuid::new_v4().to_string())
+        .body(body)
+        .send()
+        .await
+        .map_err(DeliveryError::Http)?;
+    Ok(response.status().as_u16())
+}
+
+/// Deliver `event` for the filter `hash` in the background.
+pub fn spawn_dispatch(state: &AppState, event: WebhookEvent, hash: &str, reason: Option<String>) {
+    let state = state.clone();
+    let hash = hash.to_string();
+    tokio::spawn(async move {
+        if let Err(e) = dispatch(&state, event, &hash, reason).await {
+            tracing::warn!(hash = %hash, event = event.as_str(), "webhook dispatch failed: {e}");
+        }
+    });
+}
+
+/// Deliver to every webhook subscribed to `event` for the filter: its
+/// author's own, and those its org's members registered for the org.
+/// Webhooks of banned users and of users who have left the org are skipped.
+async fn dispatch(
+    state: &AppState,
+    event: WebhookEvent,
+    hash: &str,
+    reason: Option<String>,
+) -> Result<(), sqlx::Error> {
+    let db = &state.db;
+    let Some(mut filter) = sqlx::query_as::<_, FilterRef>(
+        "SELECT f.content_hash, f.command_pattern, u.username AS author, f.org, f.visibility
+         FROM filters f JOIN users u ON u.id = f.author_id
+         WHERE f.content_hash = $1",
+    )
+    .bind(hash)
+    .fetch_optional(db)
+    .await?
+    else {
+        return Ok(());
+    };
+    filter.registry_url = format!("{}/filters/{hash}", state.public_url);
+
+    let hooks: Vec<(i64, String, String)> = sqlx::query_as(
+        "SELECT w.id, w.url, w.secret
+         FROM webhooks w
+         JOIN users u ON u.id = w.user_id
+         JOIN filters f ON f.content_hash = $1
+         WHERE $2 = ANY(w.events) AND u.banned_at IS NULL
+           AND ((w.org IS NULL AND w.user_id = f.author_id)
+                OR (w.org = f.org AND u.orgs @> jsonb_build_array(f.org)))",
+    )
+    .bind(hash)
+    .bind(event.as_str())
+    .fetch_all(db)
+    .await?;
+    if hooks.is_empty() {
+        return Ok(());
+    }
+
+    let payload = Payload {
+        event: event.as_str(),
+        sent_at: chrono::Utc::now().to_rfc3339(),
+        filter,
+        reason,
+    };
+    let body = serde_json::to_vec(&payload).map_err(|e| sqlx::Error::Encode(Box::new(e)))?;
+    let allow_private = state.webhooks_allow_private;
+    for (id, url, secret) in hooks {
+        let delivered = deliver(&url, &secret, event, body.clone(), allow_private).await;
+        let status = delivered
+            .inspect_err(|e| tracing::warn!(webhook = id, "webhook delivery failed: {e}"))
+            .ok()
+            .map(i32::from);
+        sqlx::query("UPDATE webhooks SET last_delivery_at = NOW(), last_status = $2 WHERE id = $1")
+            .bind(id)
+            .bind(status)
+            .execute(db)
+            .await?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::significant_drop_tightening)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn event_names_roundtrip() {
+        for event in WebhookEvent::ALL {
+            assert_eq!(WebhookEvent::parse(event.as_str()), Some(event));
+        }
+        assert_eq!(WebhookEvent::parse("filter.deleted"), None);
+    }
+
+    #[test]
+    fn signature_is_hmac_sha256() {
+        // RFC 4231, test case 2.
+        assert_eq!(
+            sign("Jefe", b"what do ya want for nothing?"),
+            "sha256=5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+    }
+
+    #[test]
+    fn only_public_addresses_are_deliverable() {
+        for public in ["8.8.8.8", "2606:4700::1111", "::ffff:1.1.1.1"] {
+            assert!(is_public(public.parse().unwrap()), "{public}");
+        }
+        for private in [
+            "127.0.0.1",
+            "10.1.2.3",
+            "172.16.0.1",
+            "192.168.0.1",
+            "169.254.169.254",
+            "100.64.0.1",
+            "0.0.0.0",
+            "::1",
+            "fe80::1",
+            "fd12::1",
+            "::ffff:127.0.0.1",
+        ] {
+            assert!(!is_public(private.parse().unwrap()), "{private}");
+        }
+    }
+
+    #[tokio::test]
+    async fn private_targets_are_refused_at_delivery() {
+        let err = deliver(
+            "http://127.0.0.1:9/hook",
+            "s3cret",
+            WebhookEvent::Yanked,
+            Vec::new(),
+            false,
+        )
+        .await
+        .unwrap_err();
+        assert!(matches!(
+            err,
+            DeliveryError::Target(TargetError::NotPublic(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn redirects_are_not_followed() {
+        let mut server = mockito::Server::new_async().await;
+        let redirect = server
+            .mock("POST", "/hook")
+            .with_status(307)
+            .with_header("location", "/internal")
+            .create_async()
+            .await;
+        let internal = server
+            .mock("POST", "/internal")
+            .expect(0)
+            .create_async()
+            .await;
+
+        let url = format!("{}/hook", server.url());
+        let status = deliver(&url, "s3cret", WebhookEvent::Yanked, Vec::new(), true)
+            .await
+            .unwrap();
+        assert_eq!(status, 307);
+        redirect.assert_async().await;
+        internal.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn deliveries_are_signed_and_name_their_event() {
+        let body = br#"{"event":"filter.yanked"}"#.to_vec();
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("POST", "/hook")
+            .match_header(EVENT_HEADER, "filter.yanked")
+            .match_header(SIGNATURE_HEADER, sign("s3cret", &body).as_str())
+            .match_header(DELIVERY_HEADER, mockito::Matcher::Any)
+            .match_header("content-type", "application/json")
+            .match_body(mockito::Matcher::Exact(
+                String::from_utf8(body.clone()).unwrap(),
+            ))
+            .with_status(202)
+            .create_async()
+            .await;
+
+        let url = format!("{}/hook", server.url());
+        let status = deliver(&url, "s3cret", WebhookEvent::Yanked, body, true)
+            .await
+            .unwrap();
+        assert_eq!(status, 202);
+        mock.assert_async().await;
+    }
+}